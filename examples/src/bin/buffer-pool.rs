@@ -34,7 +34,6 @@ use vulkano::{
         Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo,
     },
     image::{view::ImageView, ImageAccess, ImageUsage, SwapchainImage},
-    impl_vertex,
     instance::{Instance, InstanceCreateInfo},
     pipeline::{
         graphics::{
@@ -58,11 +57,12 @@ use winit::{
 };
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+#[derive(
+    Clone, Copy, Debug, Default, Zeroable, Pod, vulkano::pipeline::graphics::vertex_input::Vertex,
+)]
 struct Vertex {
     position: [f32; 2],
 }
-impl_vertex!(Vertex, position);
 
 fn main() {
     let required_extensions = vulkano_win::required_extensions();
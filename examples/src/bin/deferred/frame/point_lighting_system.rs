@@ -19,7 +19,6 @@ use vulkano::{
     descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
     device::Queue,
     image::ImageViewAbstract,
-    impl_vertex,
     pipeline::{
         graphics::{
             color_blend::{AttachmentBlend, BlendFactor, BlendOp, ColorBlendState},
@@ -187,11 +186,12 @@ impl PointLightingSystem {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+#[derive(
+    Clone, Copy, Debug, Default, Zeroable, Pod, vulkano::pipeline::graphics::vertex_input::Vertex,
+)]
 struct Vertex {
     position: [f32; 2],
 }
-impl_vertex!(Vertex, position);
 
 mod vs {
     vulkano_shaders::shader! {
@@ -16,7 +16,6 @@ use vulkano::{
         SecondaryAutoCommandBuffer,
     },
     device::Queue,
-    impl_vertex,
     pipeline::{
         graphics::{
             depth_stencil::DepthStencilState,
@@ -114,11 +113,12 @@ impl TriangleDrawSystem {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+#[derive(
+    Clone, Copy, Debug, Default, Zeroable, Pod, vulkano::pipeline::graphics::vertex_input::Vertex,
+)]
 struct Vertex {
     position: [f32; 2],
 }
-impl_vertex!(Vertex, position);
 
 mod vs {
     vulkano_shaders::shader! {
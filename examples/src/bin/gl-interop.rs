@@ -25,7 +25,6 @@ mod linux {
         },
         format::Format,
         image::{view::ImageView, ImageCreateFlags, ImageUsage, StorageImage, SwapchainImage},
-        impl_vertex,
         instance::{
             debug::{DebugUtilsMessenger, DebugUtilsMessengerCreateInfo},
             Instance, InstanceCreateInfo, InstanceExtensions,
@@ -34,7 +33,7 @@ mod linux {
             graphics::{
                 color_blend::ColorBlendState,
                 input_assembly::{InputAssemblyState, PrimitiveTopology},
-                vertex_input::BuffersDefinition,
+                vertex_input::{BuffersDefinition, Vertex},
                 viewport::{Scissor, Viewport, ViewportState},
             },
             GraphicsPipeline, Pipeline, PipelineBindPoint,
@@ -356,11 +355,10 @@ mod linux {
     }
 
     #[repr(C)]
-    #[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+    #[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
     struct Vertex {
         position: [f32; 2],
     }
-    impl_vertex!(Vertex, position);
 
     fn vk_setup(
         display: glium::HeadlessRenderer,
@@ -26,13 +26,12 @@ use vulkano::{
         view::ImageView, ImageAccess, ImageDimensions, ImageLayout, ImageUsage, StorageImage,
         SwapchainImage,
     },
-    impl_vertex,
     instance::{Instance, InstanceCreateInfo},
     pipeline::{
         graphics::{
             color_blend::ColorBlendState,
             input_assembly::{InputAssemblyState, PrimitiveTopology},
-            vertex_input::BuffersDefinition,
+            vertex_input::{BuffersDefinition, Vertex},
             viewport::{Viewport, ViewportState},
         },
         GraphicsPipeline, Pipeline, PipelineBindPoint,
@@ -137,11 +136,10 @@ fn main() {
     };
 
     #[repr(C)]
-    #[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+    #[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
     struct Vertex {
         position: [f32; 2],
     }
-    impl_vertex!(Vertex, position);
 
     let vertices = [
         Vertex {
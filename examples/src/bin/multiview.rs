@@ -30,12 +30,11 @@ use vulkano::{
         view::ImageView, ImageAccess, ImageCreateFlags, ImageDimensions, ImageLayout,
         ImageSubresourceLayers, ImageUsage, SampleCount, StorageImage,
     },
-    impl_vertex,
     instance::{Instance, InstanceCreateInfo, InstanceExtensions},
     pipeline::{
         graphics::{
             input_assembly::InputAssemblyState,
-            vertex_input::BuffersDefinition,
+            vertex_input::{BuffersDefinition, Vertex},
             viewport::{Viewport, ViewportState},
         },
         GraphicsPipeline,
@@ -140,11 +139,10 @@ fn main() {
     let image_view = ImageView::new_default(image.clone()).unwrap();
 
     #[repr(C)]
-    #[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+    #[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
     struct Vertex {
         position: [f32; 2],
     }
-    impl_vertex!(Vertex, position);
 
     let vertices = [
         Vertex {
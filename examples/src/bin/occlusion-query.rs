@@ -24,13 +24,12 @@ use vulkano::{
     },
     format::Format,
     image::{view::ImageView, AttachmentImage, ImageAccess, ImageUsage, SwapchainImage},
-    impl_vertex,
     instance::{Instance, InstanceCreateInfo},
     pipeline::{
         graphics::{
             depth_stencil::DepthStencilState,
             input_assembly::InputAssemblyState,
-            vertex_input::BuffersDefinition,
+            vertex_input::{BuffersDefinition, Vertex},
             viewport::{Viewport, ViewportState},
         },
         GraphicsPipeline,
@@ -132,12 +131,11 @@ fn main() {
     };
 
     #[repr(C)]
-    #[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+    #[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
     struct Vertex {
         position: [f32; 3],
         color: [f32; 3],
     }
-    impl_vertex!(Vertex, position, color);
 
     let vertices = [
         // The first triangle (red) is the same one as in the triangle example.
@@ -207,7 +205,7 @@ fn main() {
     // Create a buffer on the CPU to hold the results of the three queries.
     // Query results are always represented as either `u32` or `u64`.
     // For occlusion queries, you always need one element per query. You can ask for the number of
-    // elements needed at runtime by calling `QueryType::result_len`.
+    // elements needed at runtime by calling `QueryPool::result_len`.
     // If you retrieve query results with `with_availability` enabled, then this array needs to
     // be 6 elements long instead of 3.
     let mut query_results = [0u32; 3];
@@ -356,53 +354,50 @@ fn main() {
             )
             .unwrap();
 
-            // Beginning or resetting a query is unsafe for now.
-            unsafe {
-                builder
-                    // A query must be reset before each use, including the first use.
-                    // This must be done outside a render pass.
-                    .reset_query_pool(query_pool.clone(), 0..3)
-                    .unwrap()
-                    .set_viewport(0, [viewport.clone()])
-                    .bind_pipeline_graphics(pipeline.clone())
-                    .begin_render_pass(
-                        RenderPassBeginInfo {
-                            clear_values: vec![Some([0.0, 0.0, 1.0, 1.0].into()), Some(1.0.into())],
-                            ..RenderPassBeginInfo::framebuffer(framebuffers[image_num].clone())
-                        },
-                        SubpassContents::Inline,
-                    )
-                    .unwrap()
-                    // Begin query 0, then draw the red triangle.
-                    // Enabling the `precise` bit would give exact numeric results. This needs
-                    // the `occlusion_query_precise` feature to be enabled on the device.
-                    .begin_query(query_pool.clone(), 0, QueryControlFlags { precise: false })
-                    .unwrap()
-                    .bind_vertex_buffers(0, triangle1.clone())
-                    .draw(triangle1.len() as u32, 1, 0, 0)
-                    .unwrap()
-                    // End query 0.
-                    .end_query(query_pool.clone(), 0)
-                    .unwrap()
-                    // Begin query 1 for the cyan triangle.
-                    .begin_query(query_pool.clone(), 1, QueryControlFlags { precise: false })
-                    .unwrap()
-                    .bind_vertex_buffers(0, triangle2.clone())
-                    .draw(triangle2.len() as u32, 1, 0, 0)
-                    .unwrap()
-                    .end_query(query_pool.clone(), 1)
-                    .unwrap()
-                    // Finally, query 2 for the green triangle.
-                    .begin_query(query_pool.clone(), 2, QueryControlFlags { precise: false })
-                    .unwrap()
-                    .bind_vertex_buffers(0, triangle3.clone())
-                    .draw(triangle3.len() as u32, 1, 0, 0)
-                    .unwrap()
-                    .end_query(query_pool.clone(), 2)
-                    .unwrap()
-                    .end_render_pass()
-                    .unwrap();
-            }
+            builder
+                // A query must be reset before each use, including the first use.
+                // This must be done outside a render pass.
+                .reset_query_pool(query_pool.clone(), 0..3)
+                .unwrap()
+                .set_viewport(0, [viewport.clone()])
+                .bind_pipeline_graphics(pipeline.clone())
+                .begin_render_pass(
+                    RenderPassBeginInfo {
+                        clear_values: vec![Some([0.0, 0.0, 1.0, 1.0].into()), Some(1.0.into())],
+                        ..RenderPassBeginInfo::framebuffer(framebuffers[image_num].clone())
+                    },
+                    SubpassContents::Inline,
+                )
+                .unwrap()
+                // Begin query 0, then draw the red triangle.
+                // Enabling the `precise` bit would give exact numeric results. This needs
+                // the `occlusion_query_precise` feature to be enabled on the device.
+                .begin_query(query_pool.clone(), 0, QueryControlFlags { precise: false })
+                .unwrap()
+                .bind_vertex_buffers(0, triangle1.clone())
+                .draw(triangle1.len() as u32, 1, 0, 0)
+                .unwrap()
+                // End query 0.
+                .end_query(query_pool.clone(), 0)
+                .unwrap()
+                // Begin query 1 for the cyan triangle.
+                .begin_query(query_pool.clone(), 1, QueryControlFlags { precise: false })
+                .unwrap()
+                .bind_vertex_buffers(0, triangle2.clone())
+                .draw(triangle2.len() as u32, 1, 0, 0)
+                .unwrap()
+                .end_query(query_pool.clone(), 1)
+                .unwrap()
+                // Finally, query 2 for the green triangle.
+                .begin_query(query_pool.clone(), 2, QueryControlFlags { precise: false })
+                .unwrap()
+                .bind_vertex_buffers(0, triangle3.clone())
+                .draw(triangle3.len() as u32, 1, 0, 0)
+                .unwrap()
+                .end_query(query_pool.clone(), 2)
+                .unwrap()
+                .end_render_pass()
+                .unwrap();
 
             let command_buffer = builder.build().unwrap();
 
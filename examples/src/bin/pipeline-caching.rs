@@ -17,15 +17,11 @@
 // You can retrieve the data in the cache as a `Vec<u8>` and
 // save that to a binary file. Later you can load that file and build a
 // PipelineCache with the given data. Be aware that the Vulkan
-// implementation does not check if the data is valid and vulkano
-// currently does not either. Invalid data can lead to driver crashes
-// or worse. Using the same cache data with a different GPU probably
-// won't work, a simple driver update can lead to invalid data as well.
-// To check if your data is valid you can find inspiration here:
-//      https://zeux.io/2019/07/17/serializing-pipeline-cache/
-//
-// In the future, vulkano might implement those safety checks, but for
-// now, you would have to do that yourself or trust the data and the user.
+// implementation does not check if the data is valid. Invalid data can
+// lead to driver crashes or worse. Using the same cache data with a
+// different GPU probably won't work, a simple driver update can lead to
+// invalid data as well. `PipelineCache::save` and `PipelineCache::load`
+// take care of these checks, and reject data that can't be used.
 
 use std::{
     fs::{remove_file, rename, File},
@@ -138,7 +134,8 @@ fn main() {
     // We are now going to retrieve the cache data into a Vec<u8> and save that to a file on
     // our disk.
 
-    if let Ok(data) = pipeline_cache.get_data() {
+    // `save` appends a checksum to the data, so that corrupted files are detected when loading.
+    if let Ok(data) = pipeline_cache.save() {
         if let Ok(mut file) = File::create("pipeline_cache.bin.tmp") {
             if let Ok(_) = file.write_all(&data) {
                 let _ = rename("pipeline_cache.bin.tmp", "pipeline_cache.bin");
@@ -153,8 +150,8 @@ fn main() {
     // exist in the cache can be build far quicker.
     //
     // To load the cache from the file, we just need to load the data into a Vec<u8> and build
-    // the PipelineCache from that. Note that this function is currently unsafe as there are
-    // no checks, as it was mentioned at the start of this example.
+    // the PipelineCache from that. `load` checks the checksum and the header of the data, and
+    // returns an error if the data is corrupted or was created by another device or driver.
     let data = {
         if let Ok(mut file) = File::open("pipeline_cache.bin") {
             let mut data = Vec::new();
@@ -168,12 +165,9 @@ fn main() {
         }
     };
 
-    let second_cache = if let Some(data) = data {
-        // This is unsafe because there is no way to be sure that the file contains valid data.
-        unsafe { PipelineCache::with_data(device.clone(), &data).unwrap() }
-    } else {
-        PipelineCache::empty(device.clone()).unwrap()
-    };
+    let second_cache = data
+        .and_then(|data| PipelineCache::load(device.clone(), &data).ok())
+        .unwrap_or_else(|| PipelineCache::empty(device.clone()).unwrap());
 
     // As the PipelineCache of the Vulkan implementation saves an opaque blob of data,
     // there is no real way to know if the data is correct. There might be differences
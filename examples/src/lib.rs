@@ -1,5 +1,4 @@
 use bytemuck::{Pod, Zeroable};
-use vulkano::impl_vertex;
 
 // Copyright (c) 2016 The vulkano developers
 // Licensed under the Apache License, Version 2.0
@@ -11,13 +10,13 @@ use vulkano::impl_vertex;
 // according to those terms.
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+#[derive(
+    Clone, Copy, Debug, Default, Zeroable, Pod, vulkano::pipeline::graphics::vertex_input::Vertex,
+)]
 pub struct Vertex {
     position: [f32; 3],
 }
 
-impl_vertex!(Vertex, position);
-
 pub const VERTICES: [Vertex; 531] = [
     Vertex {
         position: [0.0, 0.0, 0.0],
@@ -1616,13 +1615,13 @@ pub const VERTICES: [Vertex; 531] = [
 ];
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, Default, Zeroable, Pod)]
+#[derive(
+    Clone, Copy, Debug, Default, Zeroable, Pod, vulkano::pipeline::graphics::vertex_input::Vertex,
+)]
 pub struct Normal {
     normal: [f32; 3],
 }
 
-impl_vertex!(Normal, normal);
-
 pub const NORMALS: [Normal; 531] = [
     Normal {
         normal: [0.0, 0.0, 0.0],
@@ -0,0 +1,106 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataStruct, DeriveInput, Error, Fields, Ident, LitStr, Result};
+
+pub fn derive_vertex(ast: DeriveInput) -> Result<TokenStream> {
+    let struct_name = &ast.ident;
+
+    let fields = match &ast.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(Error::new_spanned(
+                &ast,
+                "Vertex can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let crate_ident = quote! { ::vulkano };
+    let vertex_input = quote! { #crate_ident::pipeline::graphics::vertex_input };
+
+    let mut per_instance = false;
+
+    for attr in &ast.attrs {
+        if attr.path().is_ident("per_instance") {
+            attr.meta.require_path_only()?;
+            per_instance = true;
+        }
+    }
+
+    let mut member_arms = TokenStream::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_name_lit = LitStr::new(&field_name.to_string(), field_name.span());
+        let field_ty = &field.ty;
+        let mut format = quote! { None };
+
+        for attr in &field.attrs {
+            if attr.path().is_ident("format") {
+                let format_ident: Ident = attr.parse_args()?;
+                format = quote! { Some(#crate_ident::format::Format::#format_ident) };
+            }
+        }
+
+        member_arms.extend(quote! {
+            #field_name_lit => {
+                let (ty, array_size) = <#field_ty as #vertex_input::VertexMember>::format();
+                // SAFETY: only the address of the field is taken, the memory is not read.
+                let member_ptr = unsafe { ::std::ptr::addr_of!((*dummy_ptr).#field_name) };
+
+                Some(#vertex_input::VertexMemberInfo {
+                    offset: member_ptr as usize - dummy_ptr as usize,
+                    ty,
+                    array_size,
+                    format: #format,
+                })
+            }
+        });
+    }
+
+    let input_rate = if per_instance {
+        quote! {
+            #[inline]
+            fn input_rate() -> #vertex_input::VertexInputRate {
+                #vertex_input::VertexInputRate::Instance { divisor: 1 }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let (impl_generics, type_generics, where_clause) = ast.generics.split_for_impl();
+
+    Ok(quote! {
+        #[allow(unsafe_code)]
+        unsafe impl #impl_generics #vertex_input::Vertex
+            for #struct_name #type_generics #where_clause
+        {
+            #[inline]
+            #[allow(unused_variables)]
+            fn member(name: &str) -> Option<#vertex_input::VertexMemberInfo> {
+                let dummy = ::std::mem::MaybeUninit::<Self>::uninit();
+                let dummy_ptr = dummy.as_ptr();
+
+                match name {
+                    #member_arms
+                    _ => None,
+                }
+            }
+
+            #input_rate
+        }
+    })
+}
@@ -0,0 +1,55 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Procedural macros used by vulkano.
+//!
+//! These are re-exported by vulkano when its `macros` feature is enabled, which it is by default.
+//! You should use them through vulkano rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod derive_vertex;
+
+/// Derives the `Vertex` trait.
+///
+/// The struct must have named fields, and every field must implement `VertexMember`. The offset
+/// of each field is taken from the actual layout of the struct, so padding between fields is
+/// handled correctly.
+///
+/// # Attributes
+///
+/// - `#[format(FORMAT)]` on a field sets the format that is used to read the field in the vertex
+///   shader, where `FORMAT` is the name of a variant of `vulkano::format::Format`. This allows,
+///   for example, reading a `[u8; 4]` field as a normalized `vec4` with `R8G8B8A8_UNORM`. If no
+///   format is given, the format is derived from the type of the shader input.
+/// - `#[per_instance]` on the struct makes `Vertex::input_rate` return an instance input rate,
+///   for structs that are meant to be used as per-instance data.
+///
+/// # Example
+///
+/// ```ignore
+/// use vulkano::pipeline::graphics::vertex_input::Vertex;
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy, Debug, Default, Zeroable, Pod, Vertex)]
+/// struct MyVertex {
+///     position: [f32; 3],
+///     #[format(R8G8B8A8_UNORM)]
+///     color: [u8; 4],
+/// }
+/// ```
+#[proc_macro_derive(Vertex, attributes(format, per_instance))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    derive_vertex::derive_vertex(ast)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
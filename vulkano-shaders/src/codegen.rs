@@ -12,10 +12,11 @@ use crate::read_file_to_string;
 use crate::structs;
 use crate::RegisteredType;
 use crate::TypesMeta;
+use heck::{ToShoutySnakeCase, ToSnakeCase};
 use proc_macro2::TokenStream;
 pub use shaderc::{CompilationArtifact, IncludeType, ResolvedInclude, ShaderKind};
 use shaderc::{CompileOptions, Compiler, EnvVersion, SpirvVersion, TargetEnv};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::iter::Iterator;
 use std::path::Path;
 use std::{
@@ -143,6 +144,71 @@ fn include_callback(
     })
 }
 
+/// Severity of a message reported by the GLSL compiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A single message reported by the GLSL compiler, split into its location and text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file the message refers to, as named by the compiler. For code embedded with `src`
+    /// this is the placeholder name of the root source, for included files it is the resolved
+    /// path of the include.
+    pub file: Option<String>,
+    /// The 1-based line within `file`, if the compiler reported one.
+    pub line: Option<u32>,
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+}
+
+/// Splits the error or warning output of shaderc into individual diagnostics.
+///
+/// Messages have the form `<file>:<line>: <severity>: <message>`; lines that don't match this
+/// form, such as the trailing `N errors generated.` summary, are skipped.
+pub fn parse_diagnostics(messages: &str) -> Vec<Diagnostic> {
+    messages
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_end();
+            let (location, severity, message) = [
+                ("error: ", DiagnosticSeverity::Error),
+                ("warning: ", DiagnosticSeverity::Warning),
+            ]
+            .iter()
+            .find_map(|&(marker, severity)| {
+                if let Some(message) = line.strip_prefix(marker) {
+                    Some(("", severity, message))
+                } else {
+                    line.split_once(&format!(": {}", marker))
+                        .map(|(location, message)| (location, severity, message))
+                }
+            })?;
+
+            let (file, line) = match location.rsplit_once(':') {
+                Some((file, line)) if line.parse::<u32>().is_ok() => {
+                    (Some(file.to_owned()), line.parse().ok())
+                }
+                _ if location.is_empty() => (None, None),
+                _ => (Some(location.to_owned()), None),
+            };
+
+            Some(Diagnostic {
+                file,
+                line,
+                severity,
+                message: message.trim().to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// The name under which an embedded (`src`) shader is passed to the compiler, and therefore the
+/// file name that diagnostics for it refer to.
+pub const EMBEDDED_SOURCE_NAME: &str = "shader.glsl";
+
 pub fn compile(
     path: Option<String>,
     base_path: &impl AsRef<Path>,
@@ -170,7 +236,7 @@ pub fn compile(
         path
     } else {
         // An arbitrary placeholder file name for embedded shaders
-        "shader.glsl"
+        EMBEDDED_SOURCE_NAME
     };
 
     // Specify file resolution callback for the `#include` directive
@@ -198,7 +264,11 @@ pub fn compile(
 
     let content = compiler
         .compile_into_spirv(&code, ty, root_source_path, "main", Some(&compile_options))
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| match e {
+            // Keep only the compiler output, so that it can be split into diagnostics.
+            shaderc::Error::CompilationError(_, messages) => messages,
+            e => e.to_string(),
+        })?;
 
     let includes = includes_tracker.borrow().clone();
 
@@ -238,13 +308,17 @@ where
             }
         }
     };
-    let spirv_capabilities = reflect::spirv_capabilities(&spirv).map(|capability| {
-        let name = format_ident!("{}", format!("{:?}", capability));
-        quote! { &::vulkano::shader::spirv::Capability::#name }
-    });
-    let spirv_extensions = reflect::spirv_extensions(&spirv);
-    let entry_points = reflect::entry_points(&spirv)
-        .map(|(name, model, info)| entry_point::write_entry_point(&name, model, &info));
+    let spirv_capabilities: Vec<_> = reflect::spirv_capabilities(&spirv)
+        .map(|capability| {
+            let name = format_ident!("{}", format!("{:?}", capability));
+            quote! { &::vulkano::shader::spirv::Capability::#name }
+        })
+        .collect();
+    let spirv_extensions: Vec<_> = reflect::spirv_extensions(&spirv).collect();
+    let entry_points: Vec<_> = reflect::entry_points(&spirv).collect();
+    let all_entry_points = entry_points
+        .iter()
+        .map(|(name, model, info)| entry_point::write_entry_point(name, *model, info));
 
     let specialization_constants = structs::write_specialization_constants(
         prefix,
@@ -254,13 +328,68 @@ where
         types_registry,
     );
 
-    let load_name = if prefix.is_empty() {
-        format_ident!("load")
+    let (load_name, words_name) = if prefix.is_empty() {
+        (format_ident!("load"), format_ident!("WORDS"))
+    } else {
+        (
+            format_ident!("load_{}", prefix),
+            format_ident!("WORDS_{}", prefix.to_shouty_snake_case()),
+        )
+    };
+
+    // When the module contains several entry points, for example when it was compiled from HLSL
+    // or by rust-gpu, also generate a load function for each entry point individually.
+    let entry_point_loads: Vec<TokenStream> = if entry_points.len() > 1 {
+        let mut load_names = HashSet::new();
+
+        entry_points
+            .iter()
+            .map(|(name, model, info)| {
+                let entry_point_load_name =
+                    format_ident!("{}_{}", load_name, name.to_snake_case());
+
+                if !load_names.insert(entry_point_load_name.clone()) {
+                    panic!(
+                        "Multiple entry points map to the same load function `{}`",
+                        entry_point_load_name,
+                    );
+                }
+
+                let doc = format!(
+                    " Loads the shader in Vulkan as a `ShaderModule`, containing only the `{}` \
+                    entry point.",
+                    name,
+                );
+                let entry_point = entry_point::write_entry_point(name, *model, info);
+
+                quote! {
+                    #[doc = #doc]
+                    #[inline]
+                    #[allow(unsafe_code)]
+                    pub fn #entry_point_load_name(device: ::std::sync::Arc<::vulkano::device::Device>)
+                        -> Result<::std::sync::Arc<::vulkano::shader::ShaderModule>, ::vulkano::shader::ShaderCreationError>
+                    {
+                        unsafe {
+                            Ok(::vulkano::shader::ShaderModule::from_words_with_data(
+                                device,
+                                #words_name,
+                                #spirv_version,
+                                [#(#spirv_capabilities),*],
+                                [#(#spirv_extensions),*],
+                                [#entry_point]
+                            )?)
+                        }
+                    }
+                }
+            })
+            .collect()
     } else {
-        format_ident!("load_{}", prefix)
+        Vec::new()
     };
 
     let shader_code = quote! {
+        static #words_name: &[u32] = &[ #( #words ),* ];
+
         /// Loads the shader in Vulkan as a `ShaderModule`.
         #[inline]
         #[allow(unsafe_code)]
@@ -269,20 +398,20 @@ where
         {
             let _bytes = ( #( #include_bytes),* );
 
-            static WORDS: &[u32] = &[ #( #words ),* ];
-
             unsafe {
                 Ok(::vulkano::shader::ShaderModule::from_words_with_data(
                     device,
-                    WORDS,
+                    #words_name,
                     #spirv_version,
                     [#(#spirv_capabilities),*],
                     [#(#spirv_extensions),*],
-                    [#(#entry_points),*],
+                    [#(#all_entry_points)*],
                 )?)
             }
         }
 
+        #(#entry_point_loads)*
+
         #specialization_constants
     };
 
@@ -445,6 +574,40 @@ mod tests {
         structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new());
     }
 
+    #[test]
+    fn test_parse_diagnostics() {
+        let diagnostics = parse_diagnostics(
+            "shader.glsl:4: error: 'foo' : undeclared identifier\n\
+            /abs/include/common.glsl:12: warning: '#extension' : extension not supported\n\
+            error: #version: missing\n\
+            2 errors generated.\n",
+        );
+
+        assert_eq!(
+            diagnostics,
+            [
+                Diagnostic {
+                    file: Some("shader.glsl".to_owned()),
+                    line: Some(4),
+                    severity: DiagnosticSeverity::Error,
+                    message: "'foo' : undeclared identifier".to_owned(),
+                },
+                Diagnostic {
+                    file: Some("/abs/include/common.glsl".to_owned()),
+                    line: Some(12),
+                    severity: DiagnosticSeverity::Warning,
+                    message: "'#extension' : extension not supported".to_owned(),
+                },
+                Diagnostic {
+                    file: None,
+                    line: None,
+                    severity: DiagnosticSeverity::Error,
+                    message: "#version: missing".to_owned(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_include_resolution() {
         let root_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -35,6 +35,8 @@ pub(super) fn write_entry_point(
         write_specialization_constant_requirements(&info.specialization_constant_requirements);
     let input_interface = write_interface(&info.input_interface);
     let output_interface = write_interface(&info.output_interface);
+    let clip_distance_array_size = info.clip_distance_array_size;
+    let cull_distance_array_size = info.cull_distance_array_size;
 
     quote! {
         (
@@ -47,6 +49,8 @@ pub(super) fn write_entry_point(
                 specialization_constant_requirements: #specialization_constant_requirements.into_iter().collect(),
                 input_interface: #input_interface,
                 output_interface: #output_interface,
+                clip_distance_array_size: #clip_distance_array_size,
+                cull_distance_array_size: #cull_distance_array_size,
             },
         ),
     }
@@ -96,6 +100,7 @@ fn write_descriptor_requirements(
             image_multisampled,
             image_scalar_type,
             image_view_type,
+            name,
             sampler_compare,
             sampler_no_unnormalized_coordinates,
             sampler_no_ycbcr_conversion,
@@ -131,6 +136,10 @@ fn write_descriptor_requirements(
             }
             None => quote! { None },
         };
+        let name = match name {
+            Some(name) => quote! { Some(#name.to_owned()) },
+            None => quote! { None },
+        };
         let sampler_compare = sampler_compare.iter();
         let sampler_no_unnormalized_coordinates = sampler_no_unnormalized_coordinates.iter();
         let sampler_no_ycbcr_conversion = sampler_no_ycbcr_conversion.iter();
@@ -206,6 +215,7 @@ fn write_descriptor_requirements(
                     image_multisampled: #image_multisampled,
                     image_scalar_type: #image_scalar_type,
                     image_view_type: #image_view_type,
+                    name: #name,
                     sampler_compare: [#(#sampler_compare),*].into_iter().collect(),
                     sampler_no_unnormalized_coordinates: [#(#sampler_no_unnormalized_coordinates),*].into_iter().collect(),
                     sampler_no_ycbcr_conversion: [#(#sampler_no_ycbcr_conversion),*].into_iter().collect(),
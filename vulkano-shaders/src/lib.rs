@@ -42,6 +42,13 @@
 //! enabled.
 //! * If the `shaders` option is used, then instead of one `load` constructor, there is one for each
 //! shader. They are named based on the provided names, `load_first`, `load_second` etc.
+//! * If the shader data contains more than one entry point, as is common for SPIR-V produced from
+//! HLSL or by rust-gpu, then there is additionally one constructor for each entry point, named
+//! after the entry point in snake case, e.g. `load_vs_main` and `load_ps_main`. The
+//! `ShaderModule` that these return only contains that one entry point. The regular `load`
+//! constructor returns a module containing all entry points, each with its own reflected
+//! interface, which can be retrieved with
+//! [`ShaderModule::entry_point`](https://docs.rs/vulkano/*/vulkano/shader/struct.ShaderModule.html#method.entry_point).
 //! * A Rust struct translated from each struct contained in the shader data.
 //! By default each structure has a `Clone` and a `Copy` implementations. This
 //! behavior could be customized through the `types_meta` macro option(see below
@@ -200,10 +207,43 @@
 //! interface and bytecode. See [`src/descriptor_sets.rs`][descriptor_sets]
 //! for the exact logic.
 //!
+//! ## `debug_printf: true`
+//!
+//! Defines the `VULKANO_DEBUG_PRINTF` macro in the pre-processor, so that `debugPrintfEXT` calls
+//! can be kept out of builds that don't need them:
+//!
+//! ```glsl
+//! #ifdef VULKANO_DEBUG_PRINTF
+//! #extension GL_EXT_debug_printf : enable
+//! #endif
+//!
+//! void main() {
+//! #ifdef VULKANO_DEBUG_PRINTF
+//!     debugPrintfEXT("value = %f", value);
+//! #endif
+//! }
+//! ```
+//!
+//! A shader that uses `debugPrintfEXT` requires the `SPV_KHR_non_semantic_info` SPIR-V extension,
+//! and therefore the `khr_shader_non_semantic_info` device extension or Vulkan 1.3. The output is
+//! produced by the validation layer, when `ValidationFeatureEnable::DebugPrintf` is enabled on
+//! the instance, and can be received with `DebugUtilsMessengerCreateInfo::debug_printf`.
+//!
 //! ## `dump: true`
 //!
 //! The crate fails to compile but prints the generated rust code to stdout.
 //!
+//! # Diagnostics
+//!
+//! GLSL compilation errors are reported as Rust compile errors, one per message from the
+//! compiler, pointing at the `src` or `path` literal of the shader that failed. The message gives
+//! the line within the embedded source, or the file and line for shaders loaded with `path`.
+//! Includes are resolved with their line information preserved, so errors in an included file
+//! are reported with that file's path and line rather than a position in the including source.
+//!
+//! GLSL compiler warnings are emitted as Rust compiler warnings, located the same way, and don't
+//! prevent the shader from being built.
+//!
 //! [reflect]: https://github.com/vulkano-rs/vulkano/blob/master/vulkano-shaders/src/lib.rs#L67
 //! [cargo-expand]: https://github.com/dtolnay/cargo-expand
 //! [ShaderModule::new]: https://docs.rs/vulkano/*/vulkano/pipeline/shader/struct.ShaderModule.html#method.new
@@ -224,6 +264,7 @@ extern crate syn;
 extern crate proc_macro;
 
 use crate::codegen::ShaderKind;
+use proc_macro2::Span;
 use shaderc::{EnvVersion, SpirvVersion};
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -243,8 +284,8 @@ mod entry_point;
 mod structs;
 
 enum SourceKind {
-    Src(String),
-    Path(String),
+    Src(LitStr),
+    Path(LitStr),
     Bytes(String),
 }
 
@@ -367,6 +408,7 @@ struct MacroInput {
 
 impl Parse for MacroInput {
     fn parse(input: ParseStream) -> Result<Self> {
+        let mut debug_printf = None;
         let mut dump = None;
         let mut exact_entrypoint_interface = None;
         let mut include_directories = Vec::new();
@@ -428,7 +470,7 @@ impl Parse for MacroInput {
                     }
 
                     let path: LitStr = input.parse()?;
-                    output.1 = Some(SourceKind::Path(path.value()));
+                    output.1 = Some(SourceKind::Path(path));
                 }
 
                 "src" => {
@@ -437,7 +479,7 @@ impl Parse for MacroInput {
                     }
 
                     let src: LitStr = input.parse()?;
-                    output.1 = Some(SourceKind::Src(src.value()));
+                    output.1 = Some(SourceKind::Src(src));
                 }
 
                 other => unreachable!("Unexpected entry key {:?}", other),
@@ -559,6 +601,13 @@ impl Parse for MacroInput {
                         }
                     }
                 }
+                "debug_printf" => {
+                    if debug_printf.is_some() {
+                        panic!("Only one `debug_printf` can be defined")
+                    }
+                    let lit: LitBool = input.parse()?;
+                    debug_printf = Some(lit.value);
+                }
                 "dump" => {
                     if dump.is_some() {
                         panic!("Only one `dump` can be defined")
@@ -767,6 +816,10 @@ impl Parse for MacroInput {
             _ => (),
         }
 
+        if debug_printf.unwrap_or(false) {
+            macro_defines.push(("VULKANO_DEBUG_PRINTF".to_string(), "1".to_string()));
+        }
+
         Ok(Self {
             dump: dump.unwrap_or(false),
             include_directories,
@@ -791,6 +844,51 @@ pub(self) fn read_file_to_string(full_path: &Path) -> IoResult<String> {
     Ok(buf)
 }
 
+/// Formats a compiler diagnostic for display in a Rust compiler message.
+///
+/// Locations in the root source are given relative to the `src` literal or `path` file, while
+/// locations in included files keep the path of that file.
+fn describe_diagnostic(
+    diagnostic: &codegen::Diagnostic,
+    prefix: &str,
+    is_single: bool,
+    root_path: Option<&str>,
+) -> String {
+    let root_name = root_path.unwrap_or(codegen::EMBEDDED_SOURCE_NAME);
+    let location = match (&diagnostic.file, diagnostic.line) {
+        (Some(file), Some(line)) if file == root_name => match root_path {
+            Some(path) => format!("{}:{}: ", path, line),
+            None => format!("line {} of the shader source: ", line),
+        },
+        (Some(file), Some(line)) => format!("{}:{}: ", file, line),
+        (Some(file), None) if file != root_name => format!("{}: ", file),
+        _ => String::new(),
+    };
+
+    if is_single {
+        format!("{}{}", location, diagnostic.message)
+    } else {
+        format!("Shader {:?}: {}{}", prefix, location, diagnostic.message)
+    }
+}
+
+/// Emits `message` as a compiler warning pointing at `span`.
+///
+/// There is no stable way for a procedural macro to emit a warning, so this uses a deprecated
+/// item whose deprecation note carries the message.
+fn warning_tokens(span: Span, message: &str) -> proc_macro2::TokenStream {
+    let note = format!("shader compilation warning: {}", message);
+
+    quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #note)]
+            #[allow(dead_code)]
+            struct ShaderWarning;
+            let _ = ShaderWarning;
+        };
+    }
+}
+
 #[proc_macro]
 pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as MacroInput);
@@ -802,6 +900,7 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let mut shaders_code = Vec::with_capacity(input.shaders.len());
     let mut types_code = Vec::with_capacity(input.shaders.len());
     let mut types_registry = HashMap::new();
+    let mut warnings = Vec::new();
 
     for (prefix, (shader_kind, shader_source)) in input.shaders {
         let (code, types) = if let SourceKind::Bytes(path) = shader_source {
@@ -830,15 +929,16 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             .unwrap()
             .into()
         } else {
-            let (path, full_path, source_code) = match shader_source {
-                SourceKind::Src(source) => (None, None, source),
-                SourceKind::Path(path) => {
+            let (path, full_path, source_code, source_lit) = match shader_source {
+                SourceKind::Src(source) => (None, None, source.value(), source),
+                SourceKind::Path(path_lit) => {
+                    let path = path_lit.value();
                     let full_path = root_path.join(&path);
                     let source_code = read_file_to_string(&full_path)
                         .expect(&format!("Error reading source from {:?}", path));
 
                     if full_path.is_file() {
-                        (Some(path.clone()), Some(full_path), source_code)
+                        (Some(path), Some(full_path), source_code, path_lit)
                     } else {
                         panic!("File {:?} was not found; note that the path must be relative to your Cargo.toml", path);
                     }
@@ -858,7 +958,7 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                 .collect::<Vec<_>>();
 
             let (content, includes) = match codegen::compile(
-                path,
+                path.clone(),
                 &root_path,
                 &source_code,
                 shader_kind,
@@ -869,14 +969,51 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             ) {
                 Ok(ok) => ok,
                 Err(e) => {
-                    if is_single {
-                        panic!("{}", e.replace("(s): ", "(s):\n"))
-                    } else {
-                        panic!("Shader {:?} {}", prefix, e.replace("(s): ", "(s):\n"))
+                    let mut diagnostics = codegen::parse_diagnostics(&e);
+
+                    if diagnostics.is_empty() {
+                        diagnostics.push(codegen::Diagnostic {
+                            file: None,
+                            line: None,
+                            severity: codegen::DiagnosticSeverity::Error,
+                            message: e,
+                        });
                     }
+
+                    let error = diagnostics
+                        .iter()
+                        .filter(|diagnostic| {
+                            diagnostic.severity == codegen::DiagnosticSeverity::Error
+                        })
+                        .map(|diagnostic| {
+                            let message = describe_diagnostic(
+                                diagnostic,
+                                &prefix,
+                                is_single,
+                                path.as_deref(),
+                            );
+                            syn::Error::new(source_lit.span(), message)
+                        })
+                        .reduce(|mut acc, error| {
+                            acc.combine(error);
+                            acc
+                        })
+                        .unwrap();
+
+                    return error.to_compile_error().into();
                 }
             };
 
+            warnings.extend(
+                codegen::parse_diagnostics(&content.get_warning_messages())
+                    .iter()
+                    .map(|diagnostic| {
+                        let message =
+                            describe_diagnostic(diagnostic, &prefix, is_single, path.as_deref());
+                        warning_tokens(source_lit.span(), &message)
+                    }),
+            );
+
             let input_paths = includes.iter().map(|s| s.as_ref()).chain(
                 full_path
                     .as_ref()
@@ -903,6 +1040,10 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let uses = &input.types_meta.uses;
 
     let result = quote! {
+        #(
+            #warnings
+        )*
+
         #(
             #shaders_code
         )*
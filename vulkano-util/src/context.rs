@@ -8,12 +8,15 @@
 // according to those terms.
 
 use std::sync::Arc;
-use vulkano::device::physical::{PhysicalDevice, PhysicalDeviceType};
+use vulkano::descriptor_set::pool::StdDescriptorPool;
+use vulkano::device::physical::{PhysicalDevice, PhysicalDeviceType, QueueFlags};
 use vulkano::device::{
     Device, DeviceCreateInfo, DeviceExtensions, Features, Queue, QueueCreateInfo,
 };
 use vulkano::instance::debug::{DebugUtilsMessenger, DebugUtilsMessengerCreateInfo};
 use vulkano::instance::{Instance, InstanceCreateInfo};
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::render_pass::RenderPassCache;
 use vulkano::Version;
 
 /// A configuration struct to pass various creation options to create [`VulkanoContext`].
@@ -26,6 +29,10 @@ pub struct VulkanoConfig {
     pub device_filter_fn: Arc<dyn Fn(&PhysicalDevice) -> bool>,
     /// Pass priority order function for your physical device selection. See default for example.
     pub device_priority_fn: Arc<dyn Fn(&PhysicalDevice) -> u32>,
+    /// The queue capabilities that the selected physical device must provide. They may be spread
+    /// over several queue families. Physical devices that lack any of them are skipped,
+    /// regardless of `device_filter_fn`. Defaults to graphics and compute.
+    pub required_queue_flags: QueueFlags,
     pub device_extensions: DeviceExtensions,
     pub device_features: Features,
     /// Print your selected device name at start.
@@ -55,6 +62,11 @@ impl Default for VulkanoConfig {
                 PhysicalDeviceType::Cpu => 4,
                 PhysicalDeviceType::Other => 5,
             }),
+            required_queue_flags: QueueFlags {
+                graphics: true,
+                compute: true,
+                ..QueueFlags::none()
+            },
             print_device_name: false,
             device_extensions,
             device_features: Features::none(),
@@ -83,6 +95,9 @@ pub struct VulkanoContext {
     device: Arc<Device>,
     graphics_queue: Arc<Queue>,
     compute_queue: Arc<Queue>,
+    pipeline_cache: Arc<PipelineCache>,
+    render_pass_cache: Arc<RenderPassCache>,
+    descriptor_pool: Arc<StdDescriptorPool>,
 }
 
 impl Default for VulkanoContext {
@@ -112,7 +127,10 @@ impl VulkanoContext {
         };
         // Get prioritized device
         let physical_device = PhysicalDevice::enumerate(&instance)
-            .filter(|p| (config.device_filter_fn)(p))
+            .filter(|p| {
+                supported_queue_flags(p).is_superset_of(&config.required_queue_flags)
+                    && (config.device_filter_fn)(p)
+            })
             .min_by_key(|p| (config.device_priority_fn)(p))
             .expect("Failed to create physical device");
         // Print used device
@@ -131,12 +149,21 @@ impl VulkanoContext {
             config.device_features,
         );
 
+        // Caches shared by all the windows rendering with this context
+        let pipeline_cache =
+            PipelineCache::empty(device.clone()).expect("Failed to create pipeline cache");
+        let render_pass_cache = Device::render_pass_cache(&device);
+        let descriptor_pool = Device::standard_descriptor_pool(&device);
+
         Self {
             instance,
             _debug_utils_messenger,
             device,
             graphics_queue,
             compute_queue,
+            pipeline_cache,
+            render_pass_cache,
+            descriptor_pool,
         }
     }
 
@@ -226,6 +253,32 @@ impl VulkanoContext {
     pub fn compute_queue(&self) -> Arc<Queue> {
         self.compute_queue.clone()
     }
+
+    /// Access the pipeline cache shared by all windows. Pass it to pipeline creation so that
+    /// pipelines created for one window are reused by the others.
+    pub fn pipeline_cache(&self) -> Arc<PipelineCache> {
+        self.pipeline_cache.clone()
+    }
+
+    /// Access the render pass and framebuffer cache shared by all windows.
+    pub fn render_pass_cache(&self) -> Arc<RenderPassCache> {
+        self.render_pass_cache.clone()
+    }
+
+    /// Access the descriptor pool shared by all windows. This is the standard descriptor pool of
+    /// the device, which is kept alive for as long as the context exists.
+    pub fn descriptor_pool(&self) -> Arc<StdDescriptorPool> {
+        self.descriptor_pool.clone()
+    }
+}
+
+/// Returns the union of the capabilities of all the queue families of a physical device.
+fn supported_queue_flags(physical: &PhysicalDevice) -> QueueFlags {
+    physical
+        .queue_families()
+        .fold(QueueFlags::none(), |flags, family| {
+            flags | family.queue_flags()
+        })
 }
 
 /// Create instance, but remind user to install vulkan SDK on mac os if loading error is received on that platform.
@@ -0,0 +1,286 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::context::VulkanoContext;
+use crate::renderer::DEFAULT_IMAGE_FORMAT;
+use vulkano::{
+    buffer::{BufferUsage, CpuAccessibleBuffer},
+    command_buffer::{
+        AutoCommandBufferBuilder, CommandBufferExecFuture, CommandBufferUsage,
+        CopyImageToBufferInfo, PrimaryAutoCommandBuffer,
+    },
+    device::Queue,
+    format::Format,
+    image::{view::ImageView, AttachmentImage, ImageUsage},
+    sync,
+    sync::{FenceSignalFuture, FlushError, GpuFuture},
+};
+
+/// Attachment image view of a headless render target.
+pub type HeadlessImageView = Arc<ImageView<AttachmentImage>>;
+
+/// The future of a submitted frame, signaled once its image has been copied to the host
+type FrameFuture =
+    Arc<FenceSignalFuture<CommandBufferExecFuture<Box<dyn GpuFuture>, PrimaryAutoCommandBuffer>>>;
+
+/// Describes the render targets of a [`VulkanoHeadlessRenderer`].
+#[derive(Debug, Clone)]
+pub struct HeadlessDescriptor {
+    /// The width of the render targets in pixels.
+    pub width: u32,
+    /// The height of the render targets in pixels.
+    pub height: u32,
+    /// The format of the render targets. It must be a color format.
+    pub format: Format,
+    /// Usages of the render targets in addition to `color_attachment` and `transfer_src`, which
+    /// are always enabled.
+    pub usage: ImageUsage,
+    /// The number of render targets, which is the maximum number of frames that the GPU can work
+    /// on at the same time.
+    pub frames_in_flight: usize,
+}
+
+impl Default for HeadlessDescriptor {
+    fn default() -> Self {
+        HeadlessDescriptor {
+            width: 1280,
+            height: 720,
+            format: DEFAULT_IMAGE_FORMAT,
+            usage: ImageUsage::none(),
+            frames_in_flight: 2,
+        }
+    }
+}
+
+/// A renderer that draws to a chain of offscreen images instead of a swapchain, for image-diff
+/// tests and server-side rendering.
+///
+/// It is used like [`VulkanoWindowRenderer`](crate::renderer::VulkanoWindowRenderer): begin
+/// rendering with [`VulkanoHeadlessRenderer::acquire`], render to
+/// [`VulkanoHeadlessRenderer::image_view`] and finish with [`VulkanoHeadlessRenderer::present`].
+/// Instead of showing the image, `present` copies it to host memory and returns a
+/// [`FrameDownload`] that can be waited on to read the pixels.
+pub struct VulkanoHeadlessRenderer {
+    queue: Arc<Queue>,
+    targets: Vec<HeadlessImageView>,
+    format: Format,
+    extent: [u32; 2],
+    previous_frame_end: Option<Box<dyn GpuFuture>>,
+    /// Frames that were submitted and may still be executing, oldest first
+    frames_in_flight: VecDeque<FrameFuture>,
+    frame_index: usize,
+}
+
+impl VulkanoHeadlessRenderer {
+    /// Creates a new [`VulkanoHeadlessRenderer`] rendering on the graphics queue of the context,
+    /// with render targets described by [`HeadlessDescriptor`].
+    pub fn new(
+        vulkano_context: &VulkanoContext,
+        descriptor: &HeadlessDescriptor,
+    ) -> VulkanoHeadlessRenderer {
+        let queue = vulkano_context.graphics_queue();
+        let extent = [descriptor.width, descriptor.height];
+        let usage = ImageUsage {
+            transfer_src: true,
+            ..descriptor.usage
+        };
+
+        let targets = (0..descriptor.frames_in_flight.max(1))
+            .map(|_| {
+                let image = AttachmentImage::with_usage(
+                    vulkano_context.device(),
+                    extent,
+                    descriptor.format,
+                    usage,
+                )
+                .unwrap();
+                ImageView::new_default(image).unwrap()
+            })
+            .collect();
+
+        let previous_frame_end = Some(sync::now(vulkano_context.device()).boxed());
+
+        VulkanoHeadlessRenderer {
+            queue,
+            targets,
+            format: descriptor.format,
+            extent,
+            previous_frame_end,
+            frames_in_flight: VecDeque::new(),
+            frame_index: 0,
+        }
+    }
+
+    /// Queue that the frames are rendered and downloaded on
+    pub fn queue(&self) -> Arc<Queue> {
+        self.queue.clone()
+    }
+
+    /// Format of the render targets
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Size of the render targets
+    pub fn image_size(&self) -> [u32; 2] {
+        self.extent
+    }
+
+    /// Returns the index of the current frame, between 0 and
+    /// [`frames_in_flight`](Self::frames_in_flight). Use it to pick per-frame resources, such as
+    /// uniform buffers, which are guaranteed not to be used by the GPU anymore after `acquire`.
+    pub fn frame_index(&self) -> usize {
+        self.frame_index
+    }
+
+    /// Returns the maximum number of frames that the GPU can work on at the same time
+    pub fn frames_in_flight(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Return the render target of the current frame
+    pub fn image_view(&self) -> HeadlessImageView {
+        self.targets[self.frame_index].clone()
+    }
+
+    /// Blocks until the GPU has finished all the submitted frames
+    pub fn wait_for_frames(&mut self) {
+        for frame in self.frames_in_flight.drain(..) {
+            if let Err(err) = frame.wait(None) {
+                println!("Failed to wait for frame: {:?}", err);
+            }
+        }
+    }
+
+    /// Begin your rendering by calling `acquire`.
+    /// Returns a [`GpuFuture`](vulkano::sync::future::GpuFuture) representing the time after which
+    /// the previous frame ended. Execute your command buffers after calling this function, render
+    /// to [`image_view`](Self::image_view) and finish rendering by calling
+    /// [`VulkanoHeadlessRenderer::present`].
+    ///
+    /// If [`frames_in_flight`](Self::frames_in_flight) frames are still being rendered, this
+    /// blocks until the oldest one has finished, so that its render target can be reused.
+    pub fn acquire(&mut self) -> Box<dyn GpuFuture> {
+        while self.frames_in_flight.len() >= self.targets.len() {
+            let frame = self.frames_in_flight.pop_front().unwrap();
+            if let Err(err) = frame.wait(None) {
+                println!("Failed to wait for frame: {:?}", err);
+            }
+        }
+
+        self.previous_frame_end.take().unwrap()
+    }
+
+    /// Finishes rendering by copying the current render target to host memory. Pass your last
+    /// future as an input to this function.
+    ///
+    /// Returns a [`FrameDownload`] that can be waited on to read the rendered pixels.
+    pub fn present(
+        &mut self,
+        after_future: Box<dyn GpuFuture>,
+    ) -> Result<FrameDownload, FlushError> {
+        let device = self.queue.device().clone();
+        let target = self.image_view();
+        let [width, height] = self.extent;
+        let len = width as u64
+            * height as u64
+            * self
+                .format
+                .block_size()
+                .expect("render target format has no block size");
+
+        let buffer = unsafe {
+            CpuAccessibleBuffer::<[u8]>::uninitialized_array(
+                device.clone(),
+                len,
+                BufferUsage::transfer_dst(),
+                true,
+            )
+            .unwrap()
+        };
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            self.queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(
+                target.image().clone(),
+                buffer.clone(),
+            ))
+            .unwrap();
+        let command_buffer = builder.build().unwrap();
+
+        let future = after_future
+            .then_execute(self.queue.clone(), command_buffer)
+            .unwrap()
+            .then_signal_fence_and_flush();
+
+        self.frame_index = (self.frame_index + 1) % self.targets.len();
+
+        match future {
+            Ok(future) => {
+                let future = Arc::new(future);
+                self.frames_in_flight.push_back(future.clone());
+                self.previous_frame_end = Some(future.clone().boxed());
+
+                Ok(FrameDownload {
+                    future,
+                    buffer,
+                    format: self.format,
+                    extent: self.extent,
+                })
+            }
+            Err(err) => {
+                self.previous_frame_end = Some(sync::now(device).boxed());
+                Err(err)
+            }
+        }
+    }
+}
+
+/// The pixels of a frame that was submitted with [`VulkanoHeadlessRenderer::present`], which are
+/// available once the GPU has finished rendering it.
+pub struct FrameDownload {
+    future: FrameFuture,
+    buffer: Arc<CpuAccessibleBuffer<[u8]>>,
+    format: Format,
+    extent: [u32; 2],
+}
+
+impl FrameDownload {
+    /// Format of the pixels
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Size of the frame in pixels
+    pub fn image_size(&self) -> [u32; 2] {
+        self.extent
+    }
+
+    /// The host-visible buffer that the frame is copied to. It must not be read before the
+    /// download has finished.
+    pub fn buffer(&self) -> &Arc<CpuAccessibleBuffer<[u8]>> {
+        &self.buffer
+    }
+
+    /// Blocks until the frame has been copied to host memory, and returns its pixels, tightly
+    /// packed row by row.
+    pub fn wait(&self, timeout: Option<Duration>) -> Result<Vec<u8>, FlushError> {
+        self.future.wait(timeout)?;
+        Ok(self.buffer.read().unwrap().to_vec())
+    }
+}
@@ -8,5 +8,7 @@
 // according to those terms.
 
 pub mod context;
+pub mod headless;
+pub mod post_process;
 pub mod renderer;
 pub mod window;
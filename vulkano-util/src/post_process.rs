@@ -0,0 +1,406 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::{error, fmt, sync::Arc};
+
+use vulkano::{
+    command_buffer::{
+        AutoCommandBufferBuilder, BuildError, CommandBufferBeginError, CommandBufferExecError,
+        CommandBufferUsage, DispatchError, DrawError, RenderPassBeginInfo, RenderPassError,
+        SubpassContents,
+    },
+    descriptor_set::{DescriptorSetCreationError, PersistentDescriptorSet, WriteDescriptorSet},
+    device::Queue,
+    format::Format,
+    image::ImageViewAbstract,
+    pipeline::{
+        compute::ComputePipelineCreationError,
+        graphics::{
+            viewport::{Viewport, ViewportState},
+            GraphicsPipelineCreationError,
+        },
+        ComputePipeline, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+    },
+    render_pass::{
+        Framebuffer, FramebufferCreateInfo, FramebufferCreationError, RenderPass,
+        RenderPassCreationError, Subpass,
+    },
+    sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerCreationError},
+    shader::{ShaderCreationError, ShaderModule},
+    sync::GpuFuture,
+};
+
+/// A full-screen effect, such as tonemapping or FXAA, that reads an input image and writes every
+/// pixel of an output image, configured from a single shader.
+///
+/// The shader's entry point must be named `main`, and its descriptor set 0 must be laid out as
+/// follows:
+///
+/// - binding 0: a `sampler2D` with the input image, sampled with linear filtering and clamped
+///   to the edge.
+/// - binding 1, compute shaders only: an `image2D` with the output image, to which one
+///   invocation writes one pixel.
+///
+/// A fragment shader receives the texture coordinates of the pixel at location 0, as a `vec2`
+/// between 0 and 1, and writes its color to location 0. The shader may also declare push
+/// constants, which are passed to [`PostProcess::apply`].
+pub struct PostProcess {
+    queue: Arc<Queue>,
+    pipeline: PostProcessPipeline,
+    sampler: Arc<Sampler>,
+}
+
+enum PostProcessPipeline {
+    Compute {
+        pipeline: Arc<ComputePipeline>,
+        local_size: [u32; 2],
+    },
+    Fragment {
+        pipeline: Arc<GraphicsPipeline>,
+        render_pass: Arc<RenderPass>,
+    },
+}
+
+impl PostProcess {
+    /// Creates a post-process pass that runs the compute shader `shader`, whose local size in the
+    /// x and y dimensions is `local_size`. Enough workgroups are dispatched to cover the output
+    /// image, so the shader must ignore the invocations that fall outside of it.
+    ///
+    /// The output image must have the `storage` usage.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if either element of `local_size` is 0.
+    pub fn compute(
+        queue: Arc<Queue>,
+        shader: Arc<ShaderModule>,
+        local_size: [u32; 2],
+    ) -> Result<PostProcess, PostProcessError> {
+        assert!(local_size[0] != 0 && local_size[1] != 0);
+
+        let pipeline = ComputePipeline::new(
+            queue.device().clone(),
+            shader
+                .entry_point("main")
+                .ok_or(PostProcessError::MissingEntryPoint)?,
+            &(),
+            None,
+            |_| {},
+        )?;
+
+        Ok(PostProcess {
+            sampler: create_sampler(&queue)?,
+            queue,
+            pipeline: PostProcessPipeline::Compute {
+                pipeline,
+                local_size,
+            },
+        })
+    }
+
+    /// Creates a post-process pass that runs the fragment shader `shader` over a triangle that
+    /// covers the output image, which must have the format `output_format` and the
+    /// `color_attachment` usage.
+    pub fn fragment(
+        queue: Arc<Queue>,
+        shader: Arc<ShaderModule>,
+        output_format: Format,
+    ) -> Result<PostProcess, PostProcessError> {
+        let device = queue.device().clone();
+
+        let render_pass = vulkano::single_pass_renderpass!(
+            device.clone(),
+            attachments: {
+                output: {
+                    load: DontCare,
+                    store: Store,
+                    format: output_format,
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [output],
+                depth_stencil: {}
+            }
+        )?;
+
+        // The SPIR-V below is valid, and only uses core features.
+        let vs = unsafe { ShaderModule::from_words(device.clone(), &FULLSCREEN_TRIANGLE_VS)? };
+        let pipeline = GraphicsPipeline::start()
+            .vertex_shader(vs.entry_point("main").unwrap(), ())
+            .fragment_shader(
+                shader
+                    .entry_point("main")
+                    .ok_or(PostProcessError::MissingEntryPoint)?,
+                (),
+            )
+            .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+            .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+            .build(device)?;
+
+        Ok(PostProcess {
+            sampler: create_sampler(&queue)?,
+            queue,
+            pipeline: PostProcessPipeline::Fragment {
+                pipeline,
+                render_pass,
+            },
+        })
+    }
+
+    /// Queue that the pass is executed on
+    pub fn queue(&self) -> Arc<Queue> {
+        self.queue.clone()
+    }
+
+    /// Layout of the pipeline, to which the push constants must conform
+    pub fn pipeline_layout(&self) -> &Arc<PipelineLayout> {
+        match &self.pipeline {
+            PostProcessPipeline::Compute { pipeline, .. } => pipeline.layout(),
+            PostProcessPipeline::Fragment { pipeline, .. } => pipeline.layout(),
+        }
+    }
+
+    /// Runs the pass from `input` to `output` after `before_future`, and returns the future of the
+    /// execution. Pass `()` as `push_constants` if the shader has none.
+    ///
+    /// `input` must have the `sampled` usage, and must not be the same image as `output`.
+    pub fn apply<Pc>(
+        &self,
+        before_future: Box<dyn GpuFuture>,
+        input: Arc<dyn ImageViewAbstract>,
+        output: Arc<dyn ImageViewAbstract>,
+        push_constants: Pc,
+    ) -> Result<Box<dyn GpuFuture>, PostProcessError> {
+        let [width, height] = output.dimensions().width_height();
+        let layout = self.pipeline_layout().clone();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.queue.device().clone(),
+            self.queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+
+        match &self.pipeline {
+            PostProcessPipeline::Compute {
+                pipeline,
+                local_size,
+            } => {
+                let set = PersistentDescriptorSet::new(
+                    layout.set_layouts()[0].clone(),
+                    [
+                        WriteDescriptorSet::image_view_sampler(0, input, self.sampler.clone()),
+                        WriteDescriptorSet::image_view(1, output),
+                    ],
+                )?;
+
+                builder
+                    .bind_pipeline_compute(pipeline.clone())
+                    .bind_descriptor_sets(PipelineBindPoint::Compute, layout.clone(), 0, set)
+                    .push_constants(layout, 0, push_constants)
+                    .dispatch([
+                        (width + local_size[0] - 1) / local_size[0],
+                        (height + local_size[1] - 1) / local_size[1],
+                        1,
+                    ])?;
+            }
+            PostProcessPipeline::Fragment {
+                pipeline,
+                render_pass,
+            } => {
+                let set = PersistentDescriptorSet::new(
+                    layout.set_layouts()[0].clone(),
+                    [WriteDescriptorSet::image_view_sampler(
+                        0,
+                        input,
+                        self.sampler.clone(),
+                    )],
+                )?;
+                let framebuffer = Framebuffer::new(
+                    render_pass.clone(),
+                    FramebufferCreateInfo {
+                        attachments: vec![output],
+                        ..Default::default()
+                    },
+                )?;
+
+                builder
+                    .begin_render_pass(
+                        RenderPassBeginInfo {
+                            clear_values: vec![None],
+                            ..RenderPassBeginInfo::framebuffer(framebuffer)
+                        },
+                        SubpassContents::Inline,
+                    )?
+                    .set_viewport(
+                        0,
+                        [Viewport {
+                            origin: [0.0, 0.0],
+                            dimensions: [width as f32, height as f32],
+                            depth_range: 0.0..1.0,
+                        }],
+                    )
+                    .bind_pipeline_graphics(pipeline.clone())
+                    .bind_descriptor_sets(PipelineBindPoint::Graphics, layout.clone(), 0, set)
+                    .push_constants(layout, 0, push_constants)
+                    .draw(3, 1, 0, 0)?
+                    .end_render_pass()?;
+            }
+        }
+
+        let command_buffer = builder.build()?;
+
+        Ok(before_future
+            .then_execute(self.queue.clone(), command_buffer)?
+            .boxed())
+    }
+}
+
+fn create_sampler(queue: &Arc<Queue>) -> Result<Arc<Sampler>, SamplerCreationError> {
+    Sampler::new(
+        queue.device().clone(),
+        SamplerCreateInfo {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            address_mode: [SamplerAddressMode::ClampToEdge; 3],
+            ..Default::default()
+        },
+    )
+}
+
+/// Error that can happen when creating or applying a [`PostProcess`].
+#[derive(Debug)]
+pub enum PostProcessError {
+    /// The shader has no `main` entry point.
+    MissingEntryPoint,
+    BeginError(CommandBufferBeginError),
+    BuildError(BuildError),
+    ComputePipelineCreationError(ComputePipelineCreationError),
+    DescriptorSetCreationError(DescriptorSetCreationError),
+    DispatchError(DispatchError),
+    DrawError(DrawError),
+    ExecError(CommandBufferExecError),
+    FramebufferCreationError(FramebufferCreationError),
+    GraphicsPipelineCreationError(GraphicsPipelineCreationError),
+    RenderPassCreationError(RenderPassCreationError),
+    RenderPassError(RenderPassError),
+    SamplerCreationError(SamplerCreationError),
+    ShaderCreationError(ShaderCreationError),
+}
+
+impl error::Error for PostProcessError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::MissingEntryPoint => None,
+            Self::BeginError(err) => Some(err),
+            Self::BuildError(err) => Some(err),
+            Self::ComputePipelineCreationError(err) => Some(err),
+            Self::DescriptorSetCreationError(err) => Some(err),
+            Self::DispatchError(err) => Some(err),
+            Self::DrawError(err) => Some(err),
+            Self::ExecError(err) => Some(err),
+            Self::FramebufferCreationError(err) => Some(err),
+            Self::GraphicsPipelineCreationError(err) => Some(err),
+            Self::RenderPassCreationError(err) => Some(err),
+            Self::RenderPassError(err) => Some(err),
+            Self::SamplerCreationError(err) => Some(err),
+            Self::ShaderCreationError(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for PostProcessError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::MissingEntryPoint => write!(fmt, "the shader has no `main` entry point"),
+            Self::BeginError(_) => write!(fmt, "beginning the command buffer failed"),
+            Self::BuildError(_) => write!(fmt, "building the command buffer failed"),
+            Self::ComputePipelineCreationError(_) => {
+                write!(fmt, "creating the compute pipeline failed")
+            }
+            Self::DescriptorSetCreationError(_) => {
+                write!(fmt, "creating the descriptor set failed")
+            }
+            Self::DispatchError(_) => write!(fmt, "recording the dispatch failed"),
+            Self::DrawError(_) => write!(fmt, "recording the draw failed"),
+            Self::ExecError(_) => write!(fmt, "executing the command buffer failed"),
+            Self::FramebufferCreationError(_) => write!(fmt, "creating the framebuffer failed"),
+            Self::GraphicsPipelineCreationError(_) => {
+                write!(fmt, "creating the graphics pipeline failed")
+            }
+            Self::RenderPassCreationError(_) => write!(fmt, "creating the render pass failed"),
+            Self::RenderPassError(_) => write!(fmt, "recording the render pass failed"),
+            Self::SamplerCreationError(_) => write!(fmt, "creating the sampler failed"),
+            Self::ShaderCreationError(_) => write!(fmt, "creating the vertex shader failed"),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($err:ident => $variant:ident,)*) => {
+        $(
+            impl From<$err> for PostProcessError {
+                fn from(err: $err) -> Self {
+                    Self::$variant(err)
+                }
+            }
+        )*
+    };
+}
+
+impl_from! {
+    CommandBufferBeginError => BeginError,
+    BuildError => BuildError,
+    ComputePipelineCreationError => ComputePipelineCreationError,
+    DescriptorSetCreationError => DescriptorSetCreationError,
+    DispatchError => DispatchError,
+    DrawError => DrawError,
+    CommandBufferExecError => ExecError,
+    FramebufferCreationError => FramebufferCreationError,
+    GraphicsPipelineCreationError => GraphicsPipelineCreationError,
+    RenderPassCreationError => RenderPassCreationError,
+    RenderPassError => RenderPassError,
+    SamplerCreationError => SamplerCreationError,
+    ShaderCreationError => ShaderCreationError,
+}
+
+// SPIR-V of the following vertex shader, which draws a single triangle that covers the whole
+// viewport. It is embedded so that this crate doesn't need a shader compiler.
+//
+// #version 450
+//
+// layout(location = 0) out vec2 v_tex_coords;
+//
+// void main() {
+//     v_tex_coords = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+//     gl_Position = vec4(v_tex_coords * 2.0 - 1.0, 0.0, 1.0);
+// }
+const FULLSCREEN_TRIANGLE_VS: [u32; 162] = [
+    0x07230203, 0x00010000, 0x00000000, 0x0000001f, 0x00000000, 0x00020011, 0x00000001, 0x0003000e,
+    0x00000000, 0x00000001, 0x0008000f, 0x00000000, 0x00000001, 0x6e69616d, 0x00000000, 0x00000002,
+    0x00000003, 0x00000004, 0x00040047, 0x00000002, 0x0000000b, 0x0000002a, 0x00040047, 0x00000003,
+    0x0000001e, 0x00000000, 0x00040047, 0x00000004, 0x0000000b, 0x00000000, 0x00020013, 0x00000005,
+    0x00030021, 0x00000006, 0x00000005, 0x00040015, 0x00000007, 0x00000020, 0x00000001, 0x00030016,
+    0x00000008, 0x00000020, 0x00040017, 0x00000009, 0x00000008, 0x00000002, 0x00040017, 0x0000000a,
+    0x00000008, 0x00000004, 0x00040020, 0x0000000b, 0x00000001, 0x00000007, 0x00040020, 0x0000000c,
+    0x00000003, 0x00000009, 0x00040020, 0x0000000d, 0x00000003, 0x0000000a, 0x0004003b, 0x0000000b,
+    0x00000002, 0x00000001, 0x0004003b, 0x0000000c, 0x00000003, 0x00000003, 0x0004003b, 0x0000000d,
+    0x00000004, 0x00000003, 0x0004002b, 0x00000007, 0x0000000e, 0x00000001, 0x0004002b, 0x00000007,
+    0x0000000f, 0x00000002, 0x0004002b, 0x00000008, 0x00000010, 0x00000000, 0x0004002b, 0x00000008,
+    0x00000011, 0x3f800000, 0x0004002b, 0x00000008, 0x00000012, 0x40000000, 0x0005002c, 0x00000009,
+    0x00000013, 0x00000011, 0x00000011, 0x00050036, 0x00000005, 0x00000001, 0x00000000, 0x00000006,
+    0x000200f8, 0x00000014, 0x0004003d, 0x00000007, 0x00000015, 0x00000002, 0x000500c4, 0x00000007,
+    0x00000016, 0x00000015, 0x0000000e, 0x000500c7, 0x00000007, 0x00000017, 0x00000016, 0x0000000f,
+    0x000500c7, 0x00000007, 0x00000018, 0x00000015, 0x0000000f, 0x0004006f, 0x00000008, 0x00000019,
+    0x00000017, 0x0004006f, 0x00000008, 0x0000001a, 0x00000018, 0x00050050, 0x00000009, 0x0000001b,
+    0x00000019, 0x0000001a, 0x0003003e, 0x00000003, 0x0000001b, 0x0005008e, 0x00000009, 0x0000001c,
+    0x0000001b, 0x00000012, 0x00050083, 0x00000009, 0x0000001d, 0x0000001c, 0x00000013, 0x00060050,
+    0x0000000a, 0x0000001e, 0x0000001d, 0x00000010, 0x00000011, 0x0003003e, 0x00000004, 0x0000001e,
+    0x000100fd, 0x00010038,
+];
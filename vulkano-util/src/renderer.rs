@@ -7,7 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
 use crate::context::VulkanoContext;
@@ -19,9 +19,12 @@ use vulkano::{
     format::Format,
     image::{view::ImageView, ImageAccess, ImageViewAbstract},
     swapchain,
-    swapchain::{AcquireError, Surface, Swapchain, SwapchainCreateInfo, SwapchainCreationError},
+    swapchain::{
+        AcquireError, PresentFuture, Surface, Swapchain, SwapchainCreateInfo,
+        SwapchainCreationError,
+    },
     sync,
-    sync::{FlushError, GpuFuture},
+    sync::{FenceSignalFuture, FlushError, GpuFuture},
 };
 use vulkano_win::create_surface_from_winit;
 use winit::window::Window;
@@ -34,6 +37,9 @@ pub type DeviceImageView = Arc<ImageView<StorageImage>>;
 /// Most common image format
 pub const DEFAULT_IMAGE_FORMAT: Format = Format::R8G8B8A8_UNORM;
 
+/// The future of a submitted frame, signaled once the GPU has finished rendering it
+type FrameFuture = Arc<FenceSignalFuture<PresentFuture<Box<dyn GpuFuture>, Window>>>;
+
 /// A window renderer struct holding the winit window surface and functionality for organizing your render
 /// between frames.
 ///
@@ -52,6 +58,10 @@ pub struct VulkanoWindowRenderer {
     additional_image_views: HashMap<usize, DeviceImageView>,
     recreate_swapchain: bool,
     previous_frame_end: Option<Box<dyn GpuFuture>>,
+    /// Frames that were submitted and may still be executing, oldest first
+    frames_in_flight: VecDeque<FrameFuture>,
+    max_frames_in_flight: usize,
+    frame_index: usize,
     image_index: usize,
     present_mode: vulkano::swapchain::PresentMode,
 }
@@ -87,6 +97,9 @@ impl VulkanoWindowRenderer {
             additional_image_views: HashMap::default(),
             recreate_swapchain: false,
             previous_frame_end,
+            frames_in_flight: VecDeque::new(),
+            max_frames_in_flight: descriptor.frames_in_flight.max(1),
+            frame_index: 0,
             image_index: 0,
             present_mode: descriptor.present_mode,
         }
@@ -156,6 +169,27 @@ impl VulkanoWindowRenderer {
         self.image_index
     }
 
+    /// Returns the index of the current frame, between 0 and
+    /// [`frames_in_flight`](Self::frames_in_flight). Use it to pick per-frame resources, such as
+    /// uniform buffers, which are guaranteed not to be used by the GPU anymore after `acquire`.
+    pub fn frame_index(&self) -> usize {
+        self.frame_index
+    }
+
+    /// Returns the maximum number of frames of this window that the GPU can work on at the same time
+    pub fn frames_in_flight(&self) -> usize {
+        self.max_frames_in_flight
+    }
+
+    /// Blocks until the GPU has finished all the submitted frames of this window
+    pub fn wait_for_frames(&mut self) {
+        for frame in self.frames_in_flight.drain(..) {
+            if let Err(err) = frame.wait(None) {
+                println!("Failed to wait for frame: {:?}", err);
+            }
+        }
+    }
+
     /// Graphics queue of this window. You also can access this through [`VulkanoContext`]
     pub fn graphics_queue(&self) -> Arc<Queue> {
         self.graphics_queue.clone()
@@ -239,7 +273,18 @@ impl VulkanoWindowRenderer {
     /// Returns a [`GpuFuture`](vulkano::sync::future::GpuFuture) representing the time after which the swapchain image has been acquired
     /// and previous frame ended.
     /// Execute your command buffers after calling this function and finish rendering by calling [`VulkanoWindowRenderer::present`].
+    ///
+    /// If [`frames_in_flight`](Self::frames_in_flight) frames of this window are still being
+    /// rendered, this blocks until the oldest one has finished.
     pub fn acquire(&mut self) -> std::result::Result<Box<dyn GpuFuture>, AcquireError> {
+        // Limit the number of frames the GPU works on, so that per-frame resources can be reused
+        while self.frames_in_flight.len() >= self.max_frames_in_flight {
+            let frame = self.frames_in_flight.pop_front().unwrap();
+            if let Err(err) = frame.wait(None) {
+                println!("Failed to wait for frame: {:?}", err);
+            }
+        }
+
         // Recreate swap chain if needed (when resizing of window occurs or swapchain is outdated)
         // Also resize render views if needed
         if self.recreate_swapchain {
@@ -291,6 +336,9 @@ impl VulkanoWindowRenderer {
                     future.cleanup_finished();
                 }
 
+                let future = Arc::new(future);
+                self.frames_in_flight.push_back(future.clone());
+                self.frame_index = (self.frame_index + 1) % self.max_frames_in_flight;
                 self.previous_frame_end = Some(future.boxed());
             }
             Err(FlushError::OutOfDate) => {
@@ -222,9 +222,12 @@ impl VulkanoWindows {
         self.primary
     }
 
-    /// Remove renderer by window id
+    /// Remove renderer by window id. Waits for the GPU to finish rendering the window's frames
+    /// before its swapchain is destroyed.
     pub fn remove_renderer(&mut self, id: winit::window::WindowId) {
-        self.windows.remove(&id);
+        if let Some(mut renderer) = self.windows.remove(&id) {
+            renderer.wait_for_frames();
+        }
         if let Some(primary) = self.primary {
             if primary == id {
                 self.primary = None;
@@ -241,6 +244,14 @@ impl VulkanoWindows {
     pub fn iter_mut(&mut self) -> IterMut<WindowId, VulkanoWindowRenderer> {
         self.windows.iter_mut()
     }
+
+    /// Blocks until the GPU has finished the submitted frames of all windows, for example before
+    /// destroying resources shared between windows.
+    pub fn wait_for_frames(&mut self) {
+        for renderer in self.windows.values_mut() {
+            renderer.wait_for_frames();
+        }
+    }
 }
 
 fn get_fitting_videomode(
@@ -343,6 +354,10 @@ pub struct WindowDescriptor {
     pub mode: WindowMode,
     /// Sets whether the background of the window should be transparent.
     pub transparent: bool,
+    /// The maximum number of frames of the window that the GPU can work on at the same time.
+    ///
+    /// Each window is throttled independently, so a slow window doesn't hold back the others.
+    pub frames_in_flight: usize,
 }
 
 impl Default for WindowDescriptor {
@@ -361,6 +376,7 @@ impl Default for WindowDescriptor {
             cursor_visible: true,
             mode: WindowMode::Windowed,
             transparent: false,
+            frames_in_flight: 2,
         }
     }
 }
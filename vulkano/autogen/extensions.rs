@@ -466,33 +466,39 @@ fn extensions_members(ty: &str, extensions: &IndexMap<&str, &Extension>) -> Vec<
                 });
             }
 
-            if let Some(req) = ext.requires.as_ref() {
-                requires.extend(req.split(',').map(|mut vk_name| {
+            if let Some(depends) = ext.depends.as_ref() {
+                requires.extend(parse_depends(depends).into_iter().map(|clause| {
                     let mut dependencies = OneOfDependencies::default();
 
-                    loop {
-                        if let Some(version) = vk_name.strip_prefix("VK_VERSION_") {
-                            let (major, minor) = version.split_once('_').unwrap();
-                            dependencies.api_version = Some((major.to_owned(), minor.to_owned()));
-                            break;
-                        } else {
-                            let ident = format_ident!(
-                                "{}",
-                                vk_name.strip_prefix("VK_").unwrap().to_snake_case()
-                            );
-                            let extension = extensions[vk_name];
-
-                            match extension.ext_type.as_ref().map(|s| s.as_str()) {
-                                Some("device") => &mut dependencies.device_extensions,
-                                Some("instance") => &mut dependencies.instance_extensions,
-                                _ => unreachable!(),
-                            }
-                            .insert(0, ident);
-
-                            if let Some(promotedto) = extension.promotedto.as_ref() {
-                                vk_name = promotedto.as_str();
-                            } else {
+                    for mut vk_name in clause {
+                        loop {
+                            if let Some(version) = vk_name.strip_prefix("VK_VERSION_") {
+                                let (major, minor) = version.split_once('_').unwrap();
+                                dependencies.api_version =
+                                    Some((major.to_owned(), minor.to_owned()));
                                 break;
+                            } else {
+                                let ident = format_ident!(
+                                    "{}",
+                                    vk_name.strip_prefix("VK_").unwrap().to_snake_case()
+                                );
+                                let extension = extensions[vk_name];
+
+                                let list = match extension.ext_type.as_ref().map(|s| s.as_str()) {
+                                    Some("device") => &mut dependencies.device_extensions,
+                                    Some("instance") => &mut dependencies.instance_extensions,
+                                    _ => unreachable!(),
+                                };
+
+                                if !list.contains(&ident) {
+                                    list.insert(0, ident);
+                                }
+
+                                if let Some(promotedto) = extension.promotedto.as_ref() {
+                                    vk_name = promotedto.as_str();
+                                } else {
+                                    break;
+                                }
                             }
                         }
                     }
@@ -694,3 +700,58 @@ fn make_doc(ext: &mut ExtensionsMember) {
         .unwrap();
     }
 }
+
+/// Parses a `depends` expression from vk.xml into conjunctive normal form: a list of clauses that
+/// must all be satisfied, where each clause is satisfied if any one of its elements is.
+fn parse_depends(depends: &str) -> Vec<Vec<&str>> {
+    fn parse_or<'a>(input: &mut &'a str) -> Vec<Vec<&'a str>> {
+        let mut result = parse_and(input);
+
+        while let Some(rest) = input.strip_prefix(',') {
+            *input = rest;
+            let rhs = parse_and(input);
+            result = result
+                .iter()
+                .flat_map(|lhs_clause| {
+                    rhs.iter().map(move |rhs_clause| {
+                        let mut clause = lhs_clause.clone();
+                        clause.extend(rhs_clause.iter().filter(|n| !lhs_clause.contains(n)));
+                        clause
+                    })
+                })
+                .collect();
+        }
+
+        result
+    }
+
+    fn parse_and<'a>(input: &mut &'a str) -> Vec<Vec<&'a str>> {
+        let mut result = parse_atom(input);
+
+        while let Some(rest) = input.strip_prefix('+') {
+            *input = rest;
+            result.extend(parse_atom(input));
+        }
+
+        result
+    }
+
+    fn parse_atom<'a>(input: &mut &'a str) -> Vec<Vec<&'a str>> {
+        if let Some(rest) = input.strip_prefix('(') {
+            *input = rest;
+            let result = parse_or(input);
+            *input = input.strip_prefix(')').unwrap();
+            result
+        } else {
+            let end = input.find(|c| matches!(c, '+' | ',' | ')')).unwrap_or(input.len());
+            let (name, rest) = input.split_at(end);
+            *input = rest;
+            vec![vec![name]]
+        }
+    }
+
+    let mut input = depends;
+    let result = parse_or(&mut input);
+    assert!(input.is_empty());
+    result
+}
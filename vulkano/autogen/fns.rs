@@ -100,19 +100,14 @@ fn fns_output(extension_members: &[FnsMember], fns_level: &str, doc: &str) -> To
 fn extension_fns_members(ty: &str, extensions: &IndexMap<&str, &Extension>) -> Vec<FnsMember> {
     extensions
         .values()
-        .filter(|ext| ext.ext_type.as_ref().unwrap() == ty)
-        // Filter only extensions that have functions
         .filter(|ext| {
-            ext.children.iter().any(|ch| {
-                if let ExtensionChild::Require { items, .. } = ch {
-                    items
-                        .iter()
-                        .any(|i| matches!(i, InterfaceItem::Command { .. }))
-                } else {
-                    false
-                }
-            })
+            ext.ext_type.as_ref().unwrap() == ty
+                // Device extensions can have physical device-level functions, which must be
+                // loaded from the instance.
+                || ty == "instance" && has_commands(ext, is_physical_device_command)
         })
+        // Filter only extensions that have functions
+        .filter(|ext| has_commands(ext, |_| true))
         .map(|ext| {
             let base = ext.name.strip_prefix("VK_").unwrap().to_snake_case();
             let name = format_ident!("{}", base);
@@ -121,3 +116,20 @@ fn extension_fns_members(ty: &str, extensions: &IndexMap<&str, &Extension>) -> V
         })
         .collect()
 }
+
+fn has_commands(ext: &Extension, filter: impl Fn(&str) -> bool) -> bool {
+    ext.children.iter().any(|ch| {
+        if let ExtensionChild::Require { items, .. } = ch {
+            items.iter().any(|i| match i {
+                InterfaceItem::Command { name, .. } => filter(name),
+                _ => false,
+            })
+        } else {
+            false
+        }
+    })
+}
+
+fn is_physical_device_command(name: &str) -> bool {
+    name.starts_with("vkGetPhysicalDevice") || name.starts_with("vkEnumeratePhysicalDevice")
+}
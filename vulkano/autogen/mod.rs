@@ -177,7 +177,10 @@ impl<'r> VkRegistryData<'r> {
             .filter_map(|child| {
                 if let RegistryChild::Extensions(ext) = child {
                     return Some(ext.children.iter().filter_map(|ext| {
-                        if ext.supported.as_ref().map(|s| s.as_str()) == Some("vulkan")
+                        if ext
+                            .supported
+                            .as_ref()
+                            .map_or(false, |s| s.split(',').any(|api| api == "vulkan"))
                             && ext.obsoletedby.is_none()
                         {
                             return Some(ext);
@@ -211,7 +214,9 @@ impl<'r> VkRegistryData<'r> {
             .iter()
             .filter_map(|child| {
                 if let RegistryChild::Feature(feat) = child {
-                    return Some((feat.name.as_str(), feat));
+                    if feat.api.split(',').any(|api| api == "vulkan") {
+                        return Some((feat.name.as_str(), feat));
+                    }
                 }
 
                 None
@@ -488,8 +488,14 @@ fn vulkano_type(ty: &str, len: Option<&str>) -> TokenStream {
             "VkDeviceSize" => quote! { DeviceSize },
             "VkDriverId" => quote! { DriverId },
             "VkExtent2D" => quote! { [u32; 2] },
+            "VkMemoryDecompressionMethodFlagsNV" => quote! { MemoryDecompressionMethods },
+            "VkOpticalFlowGridSizeFlagsNV" => quote! { OpticalFlowGridSizes },
             "VkPhysicalDeviceType" => quote! { PhysicalDeviceType },
+            "VkPipelineRobustnessBufferBehaviorEXT" => quote! { PipelineRobustnessBufferBehavior },
+            "VkPipelineRobustnessImageBehaviorEXT" => quote! { PipelineRobustnessImageBehavior },
             "VkPointClippingBehavior" => quote! { PointClippingBehavior },
+            "VkQueueFlags" => quote! { QueueFlags },
+            "VkRayTracingInvocationReorderModeNV" => quote! { RayTracingInvocationReorderMode },
             "VkResolveModeFlags" => quote! { ResolveModes },
             "VkSampleCountFlags" => quote! { SampleCounts },
             "VkSampleCountFlagBits" => quote! { SampleCount },
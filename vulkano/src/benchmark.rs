@@ -0,0 +1,529 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Micro-benchmarks of basic device operations.
+//!
+//! [`run`] measures how fast vulkano records command buffers, how long a submission takes to
+//! round-trip through a queue, and the bandwidth of buffer-to-buffer copies. It doesn't need a
+//! surface, so it can run in tests and on headless machines. Comparing the results between
+//! versions of vulkano helps catch performance regressions, and comparing them between devices
+//! gives an idea of their relative overhead.
+//!
+//! ```no_run
+//! use vulkano::benchmark::{self, BenchmarkInfo};
+//!
+//! # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+//! let results = benchmark::run(queue, BenchmarkInfo::default()).unwrap();
+//!
+//! println!("device: {}", results.device_name);
+//! println!(
+//!     "recording: {:.0} commands/s",
+//!     results.recording.commands_per_second()
+//! );
+//! println!("submit latency: {:?}", results.submit_latency.median);
+//! println!(
+//!     "copy bandwidth: {:.2} GiB/s",
+//!     results.copy.bytes_per_second() / (1u64 << 30) as f64
+//! );
+//! ```
+
+use crate::{
+    buffer::{BufferUsage, DeviceLocalBuffer},
+    command_buffer::{
+        AutoCommandBufferBuilder, BuildError, CommandBufferBeginError, CommandBufferExecError,
+        CommandBufferUsage, CopyBufferInfo, CopyError, FillBufferInfo, PrimaryCommandBuffer,
+        QueryError,
+    },
+    device::Queue,
+    memory::DeviceMemoryAllocationError,
+    query::{
+        GetResultsError, QueryPool, QueryPoolCreateInfo, QueryPoolCreationError, QueryResultFlags,
+        QueryType,
+    },
+    sync::{FlushError, GpuFuture, PipelineStage},
+    DeviceSize,
+};
+use std::{
+    error, fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Parameters of [`run`].
+#[derive(Clone, Debug)]
+pub struct BenchmarkInfo {
+    /// The number of times that each benchmark is repeated.
+    ///
+    /// The default value is `16`.
+    pub iterations: u32,
+
+    /// The number of commands that are recorded into each command buffer by the recording
+    /// benchmark.
+    ///
+    /// The default value is `1024`.
+    pub commands_per_command_buffer: u32,
+
+    /// The number of bytes that are copied by the copy benchmark. Must be a multiple of 4.
+    ///
+    /// The default value is 64 MiB.
+    pub copy_size: DeviceSize,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for BenchmarkInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            iterations: 16,
+            commands_per_command_buffer: 1024,
+            copy_size: 64 << 20,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The results of [`run`].
+#[derive(Clone, Debug)]
+pub struct BenchmarkResults {
+    /// The name of the device that the benchmarks ran on.
+    pub device_name: String,
+
+    /// The time it took to record and build a command buffer.
+    pub recording: RecordingResults,
+
+    /// The time from submitting an empty command buffer until the host was notified of its
+    /// completion.
+    pub submit_latency: Timings,
+
+    /// The time it took to copy between two buffers.
+    pub copy: CopyResults,
+}
+
+/// The results of the recording benchmark.
+#[derive(Clone, Debug)]
+pub struct RecordingResults {
+    /// The number of commands that were recorded into each command buffer.
+    pub commands_per_command_buffer: u32,
+
+    /// The time it took to record and build each command buffer.
+    pub timings: Timings,
+}
+
+impl RecordingResults {
+    /// Returns the number of commands recorded per second, based on the median time.
+    #[inline]
+    pub fn commands_per_second(&self) -> f64 {
+        self.commands_per_command_buffer as f64 / self.timings.median.as_secs_f64()
+    }
+}
+
+/// The results of the copy benchmark.
+#[derive(Clone, Debug)]
+pub struct CopyResults {
+    /// The number of bytes that were copied in each iteration.
+    pub size: DeviceSize,
+
+    /// The time it took to perform each copy.
+    pub timings: Timings,
+
+    /// Whether the copies were timed on the device with timestamp queries. If `false`, the queue
+    /// doesn't support timestamps, and the timings were measured on the host instead, which
+    /// includes the submission overhead.
+    pub device_timed: bool,
+}
+
+impl CopyResults {
+    /// Returns the number of bytes copied per second, based on the median time.
+    #[inline]
+    pub fn bytes_per_second(&self) -> f64 {
+        self.size as f64 / self.timings.median.as_secs_f64()
+    }
+}
+
+/// Summary of the durations measured by the iterations of a benchmark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timings {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl Timings {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        debug_assert!(!samples.is_empty());
+        samples.sort();
+
+        Timings {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            mean: samples.iter().sum::<Duration>() / samples.len() as u32,
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Runs all the benchmarks on `queue`, and returns their results.
+///
+/// This blocks until the benchmarks have finished, and uses the queue exclusively in the meantime.
+/// Other work on the same device influences the results.
+///
+/// # Panics
+///
+/// - Panics if `info.iterations` or `info.commands_per_command_buffer` is `0`.
+/// - Panics if `info.copy_size` is `0` or not a multiple of 4.
+pub fn run(queue: Arc<Queue>, info: BenchmarkInfo) -> Result<BenchmarkResults, BenchmarkError> {
+    let BenchmarkInfo {
+        iterations,
+        commands_per_command_buffer,
+        copy_size,
+        _ne: _,
+    } = info;
+
+    assert!(iterations != 0);
+    assert!(commands_per_command_buffer != 0);
+    assert!(copy_size != 0 && copy_size % 4 == 0);
+
+    Ok(BenchmarkResults {
+        device_name: queue
+            .device()
+            .physical_device()
+            .properties()
+            .device_name
+            .clone(),
+        recording: RecordingResults {
+            commands_per_command_buffer,
+            timings: recording(&queue, iterations, commands_per_command_buffer)?,
+        },
+        submit_latency: submit_latency(&queue, iterations)?,
+        copy: copy(&queue, iterations, copy_size)?,
+    })
+}
+
+fn recording(
+    queue: &Arc<Queue>,
+    iterations: u32,
+    commands_per_command_buffer: u32,
+) -> Result<Timings, BenchmarkError> {
+    let device = queue.device();
+    let buffer = DeviceLocalBuffer::<[u32]>::array(
+        device.clone(),
+        64,
+        BufferUsage {
+            transfer_dst: true,
+            ..BufferUsage::none()
+        },
+        [queue.family()],
+    )?;
+
+    let samples = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            let mut builder = AutoCommandBufferBuilder::primary(
+                device.clone(),
+                queue.family(),
+                CommandBufferUsage::OneTimeSubmit,
+            )?;
+
+            for data in 0..commands_per_command_buffer {
+                builder.fill_buffer(FillBufferInfo {
+                    data,
+                    ..FillBufferInfo::dst_buffer(buffer.clone())
+                })?;
+            }
+
+            builder.build()?;
+
+            Ok(start.elapsed())
+        })
+        .collect::<Result<_, BenchmarkError>>()?;
+
+    Ok(Timings::from_samples(samples))
+}
+
+fn submit_latency(queue: &Arc<Queue>, iterations: u32) -> Result<Timings, BenchmarkError> {
+    let device = queue.device();
+
+    let samples = (0..iterations)
+        .map(|_| {
+            let command_buffer = AutoCommandBufferBuilder::primary(
+                device.clone(),
+                queue.family(),
+                CommandBufferUsage::OneTimeSubmit,
+            )?
+            .build()?;
+
+            let start = Instant::now();
+            command_buffer
+                .execute(queue.clone())?
+                .then_signal_fence_and_flush()?
+                .wait(None)?;
+
+            Ok(start.elapsed())
+        })
+        .collect::<Result<_, BenchmarkError>>()?;
+
+    Ok(Timings::from_samples(samples))
+}
+
+fn copy(
+    queue: &Arc<Queue>,
+    iterations: u32,
+    copy_size: DeviceSize,
+) -> Result<CopyResults, BenchmarkError> {
+    let device = queue.device();
+    let timestamp_valid_bits = queue.family().timestamp_valid_bits();
+
+    let new_buffer = |usage| {
+        DeviceLocalBuffer::<[u8]>::array(device.clone(), copy_size, usage, [queue.family()])
+    };
+    let src_buffer = new_buffer(BufferUsage {
+        transfer_src: true,
+        ..BufferUsage::none()
+    })?;
+    let dst_buffer = new_buffer(BufferUsage {
+        transfer_dst: true,
+        ..BufferUsage::none()
+    })?;
+
+    let query_pool = match timestamp_valid_bits {
+        Some(_) => Some(QueryPool::new(
+            device.clone(),
+            QueryPoolCreateInfo {
+                query_count: 2,
+                ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+            },
+        )?),
+        None => None,
+    };
+
+    let samples = (0..iterations)
+        .map(|_| {
+            let mut builder = AutoCommandBufferBuilder::primary(
+                device.clone(),
+                queue.family(),
+                CommandBufferUsage::OneTimeSubmit,
+            )?;
+
+            if let Some(query_pool) = &query_pool {
+                builder
+                    .reset_query_pool(query_pool.clone(), 0..2)?
+                    .write_timestamp(query_pool.clone(), 0, PipelineStage::TopOfPipe)?;
+            }
+
+            builder.copy_buffer(CopyBufferInfo::buffers(
+                src_buffer.clone(),
+                dst_buffer.clone(),
+            ))?;
+
+            if let Some(query_pool) = &query_pool {
+                builder.write_timestamp(query_pool.clone(), 1, PipelineStage::BottomOfPipe)?;
+            }
+
+            let command_buffer = builder.build()?;
+
+            let start = Instant::now();
+            command_buffer
+                .execute(queue.clone())?
+                .then_signal_fence_and_flush()?
+                .wait(None)?;
+            let elapsed = start.elapsed();
+
+            match (&query_pool, timestamp_valid_bits) {
+                (Some(query_pool), Some(valid_bits)) => {
+                    let mut timestamps = [0u64; 2];
+                    query_pool.queries_range(0..2).unwrap().get_results(
+                        &mut timestamps,
+                        QueryResultFlags {
+                            wait: true,
+                            ..Default::default()
+                        },
+                    )?;
+
+                    let mask = u64::MAX >> (64 - valid_bits);
+                    let ticks = timestamps[1].wrapping_sub(timestamps[0]) & mask;
+                    let period = device.physical_device().properties().timestamp_period as f64;
+
+                    Ok(Duration::from_nanos((ticks as f64 * period) as u64))
+                }
+                _ => Ok(elapsed),
+            }
+        })
+        .collect::<Result<_, BenchmarkError>>()?;
+
+    Ok(CopyResults {
+        size: copy_size,
+        timings: Timings::from_samples(samples),
+        device_timed: query_pool.is_some(),
+    })
+}
+
+/// Error that can happen when running the benchmarks.
+#[derive(Clone, Debug)]
+pub enum BenchmarkError {
+    /// Allocating the memory of a buffer failed.
+    AllocationError(DeviceMemoryAllocationError),
+
+    /// Beginning a command buffer failed.
+    CommandBufferBeginError(CommandBufferBeginError),
+
+    /// Building a command buffer failed.
+    BuildError(BuildError),
+
+    /// Recording a transfer command failed.
+    CopyError(CopyError),
+
+    /// Recording a query command failed.
+    QueryError(QueryError),
+
+    /// Creating the timestamp query pool failed.
+    QueryPoolCreationError(QueryPoolCreationError),
+
+    /// Retrieving the timestamps failed.
+    GetResultsError(GetResultsError),
+
+    /// Submitting a command buffer failed.
+    CommandBufferExecError(CommandBufferExecError),
+
+    /// Flushing a submission or waiting for it failed.
+    FlushError(FlushError),
+}
+
+impl error::Error for BenchmarkError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::AllocationError(ref err) => Some(err),
+            Self::CommandBufferBeginError(ref err) => Some(err),
+            Self::BuildError(ref err) => Some(err),
+            Self::CopyError(ref err) => Some(err),
+            Self::QueryError(ref err) => Some(err),
+            Self::QueryPoolCreationError(ref err) => Some(err),
+            Self::GetResultsError(ref err) => Some(err),
+            Self::CommandBufferExecError(ref err) => Some(err),
+            Self::FlushError(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for BenchmarkError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::AllocationError(_) => write!(fmt, "allocating the memory of a buffer failed"),
+            Self::CommandBufferBeginError(_) => write!(fmt, "beginning a command buffer failed"),
+            Self::BuildError(_) => write!(fmt, "building a command buffer failed"),
+            Self::CopyError(_) => write!(fmt, "recording a transfer command failed"),
+            Self::QueryError(_) => write!(fmt, "recording a query command failed"),
+            Self::QueryPoolCreationError(_) => {
+                write!(fmt, "creating the timestamp query pool failed")
+            }
+            Self::GetResultsError(_) => write!(fmt, "retrieving the timestamps failed"),
+            Self::CommandBufferExecError(_) => write!(fmt, "submitting a command buffer failed"),
+            Self::FlushError(_) => write!(fmt, "flushing a submission failed"),
+        }
+    }
+}
+
+impl From<DeviceMemoryAllocationError> for BenchmarkError {
+    #[inline]
+    fn from(err: DeviceMemoryAllocationError) -> Self {
+        Self::AllocationError(err)
+    }
+}
+
+impl From<CommandBufferBeginError> for BenchmarkError {
+    #[inline]
+    fn from(err: CommandBufferBeginError) -> Self {
+        Self::CommandBufferBeginError(err)
+    }
+}
+
+impl From<BuildError> for BenchmarkError {
+    #[inline]
+    fn from(err: BuildError) -> Self {
+        Self::BuildError(err)
+    }
+}
+
+impl From<CopyError> for BenchmarkError {
+    #[inline]
+    fn from(err: CopyError) -> Self {
+        Self::CopyError(err)
+    }
+}
+
+impl From<QueryError> for BenchmarkError {
+    #[inline]
+    fn from(err: QueryError) -> Self {
+        Self::QueryError(err)
+    }
+}
+
+impl From<QueryPoolCreationError> for BenchmarkError {
+    #[inline]
+    fn from(err: QueryPoolCreationError) -> Self {
+        Self::QueryPoolCreationError(err)
+    }
+}
+
+impl From<GetResultsError> for BenchmarkError {
+    #[inline]
+    fn from(err: GetResultsError) -> Self {
+        Self::GetResultsError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for BenchmarkError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> Self {
+        Self::CommandBufferExecError(err)
+    }
+}
+
+impl From<FlushError> for BenchmarkError {
+    #[inline]
+    fn from(err: FlushError) -> Self {
+        Self::FlushError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, BenchmarkInfo};
+
+    #[test]
+    fn small_run() {
+        let (_, queue) = gfx_dev_and_queue!();
+
+        let results = run(
+            queue,
+            BenchmarkInfo {
+                iterations: 3,
+                commands_per_command_buffer: 8,
+                copy_size: 1024,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        for timings in [
+            results.recording.timings,
+            results.submit_latency,
+            results.copy.timings,
+        ] {
+            assert!(timings.min <= timings.median && timings.median <= timings.max);
+            assert!(timings.min <= timings.mean && timings.mean <= timings.max);
+        }
+
+        assert_eq!(results.recording.commands_per_command_buffer, 8);
+        assert_eq!(results.copy.size, 1024);
+    }
+}
@@ -12,9 +12,22 @@
 //! The `CpuAccessibleBuffer` is a basic general-purpose buffer. It can be used in any situation
 //! but may not perform as well as other buffer types.
 //!
-//! Each access from the CPU or from the GPU locks the whole buffer for either reading or writing.
-//! You can read the buffer multiple times simultaneously. Trying to read and write simultaneously,
-//! or write and write simultaneously will block.
+//! The constructors take a [`HostMemoryPreference`], which selects between host-cached memory,
+//! best suited for reading back data written by the GPU, and write-combined memory, best suited
+//! for uploading data. On devices with resizable BAR, device-local memory can also be selected,
+//! so that the GPU reads the data directly from its own memory. For compatibility, a `bool` can
+//! also be given, where `true` means host-cached.
+//!
+//! Each access from the CPU or from the GPU locks the accessed range of the buffer for either
+//! reading or writing. You can read a range multiple times simultaneously. Trying to read and
+//! write overlapping ranges simultaneously, or write and write them simultaneously will block.
+//!
+//! `read()` and `write()` lock the whole buffer. For buffers containing arrays, `read_range()`
+//! and `write_range()` only lock the given elements, which allows the CPU to access one region
+//! of a big buffer while other regions are in use by the GPU.
+//!
+//! The `try_*` variants don't block either, but when the buffer is in use by the GPU they report
+//! which command buffers are still using it, so that work can be scheduled around them.
 
 use super::{
     sys::UnsafeBuffer, BufferAccess, BufferAccessObject, BufferContents, BufferInner, BufferUsage,
@@ -24,13 +37,14 @@ use crate::{
     device::{physical::QueueFamily, Device, DeviceOwned},
     memory::{
         pool::{
-            AllocFromRequirementsFilter, AllocLayout, MappingRequirement, MemoryPoolAlloc,
+            AllocLayout, HostMemoryPreference, MappingRequirement, MemoryPoolAlloc,
             PotentialDedicatedAllocation, StdMemoryPoolAlloc,
         },
-        DedicatedAllocation, DeviceMemoryAllocationError, MemoryPool,
+        DedicatedAllocation, DeviceMemoryAllocationError, MemoryMapError, MemoryPool,
     },
-    sync::Sharing,
-    DeviceSize,
+    sync::{FenceSignalFuture, FlushError, GpuFuture, Sharing},
+    video::VideoProfile,
+    DeviceSize, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{
@@ -41,14 +55,17 @@ use std::{
     ops::{Deref, DerefMut, Range},
     ptr,
     sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
 };
 
 /// Buffer whose content is accessible by the CPU.
 ///
-/// Setting the `host_cached` field on the various initializers to `true` will make it so
-/// the `CpuAccessibleBuffer` prefers to allocate from host_cached memory. Host cached
-/// memory caches GPU data on the CPU side. This can be more performant in cases where
-/// the cpu needs to read data coming off the GPU.
+/// Setting the `memory_preference` parameter of the various initializers to
+/// `HostMemoryPreference::HostCached` (or `true`) will make it so the `CpuAccessibleBuffer`
+/// prefers to allocate from host_cached memory. Host cached memory caches GPU data on the CPU
+/// side. This can be more performant in cases where the cpu needs to read data coming off the
+/// GPU. `HostMemoryPreference::WriteCombined` (or `false`) prefers uncached memory instead, which
+/// is usually faster for data that the CPU only writes.
 #[derive(Debug)]
 pub struct CpuAccessibleBuffer<T, A = PotentialDedicatedAllocation<StdMemoryPoolAlloc>>
 where
@@ -91,7 +108,7 @@ where
     pub fn from_data(
         device: Arc<Device>,
         usage: BufferUsage,
-        host_cached: bool,
+        memory_preference: impl Into<HostMemoryPreference>,
         data: T,
     ) -> Result<Arc<CpuAccessibleBuffer<T>>, DeviceMemoryAllocationError> {
         unsafe {
@@ -99,7 +116,7 @@ where
                 device,
                 size_of::<T>() as DeviceSize,
                 usage,
-                host_cached,
+                memory_preference,
                 [],
             )?;
 
@@ -125,9 +142,15 @@ where
     pub unsafe fn uninitialized(
         device: Arc<Device>,
         usage: BufferUsage,
-        host_cached: bool,
+        memory_preference: impl Into<HostMemoryPreference>,
     ) -> Result<Arc<CpuAccessibleBuffer<T>>, DeviceMemoryAllocationError> {
-        CpuAccessibleBuffer::raw(device, size_of::<T>() as DeviceSize, usage, host_cached, [])
+        CpuAccessibleBuffer::raw(
+            device,
+            size_of::<T>() as DeviceSize,
+            usage,
+            memory_preference,
+            [],
+        )
     }
 }
 
@@ -145,7 +168,7 @@ where
     pub fn from_iter<I>(
         device: Arc<Device>,
         usage: BufferUsage,
-        host_cached: bool,
+        memory_preference: impl Into<HostMemoryPreference>,
         data: I,
     ) -> Result<Arc<CpuAccessibleBuffer<[T]>>, DeviceMemoryAllocationError>
     where
@@ -159,7 +182,7 @@ where
                 device,
                 data.len() as DeviceSize,
                 usage,
-                host_cached,
+                memory_preference,
             )?;
 
             // Note that we are in panic-unsafety land here. However a panic should never ever
@@ -189,18 +212,53 @@ where
         device: Arc<Device>,
         len: DeviceSize,
         usage: BufferUsage,
-        host_cached: bool,
+        memory_preference: impl Into<HostMemoryPreference>,
     ) -> Result<Arc<CpuAccessibleBuffer<[T]>>, DeviceMemoryAllocationError> {
         CpuAccessibleBuffer::raw(
             device,
             len * size_of::<T>() as DeviceSize,
             usage,
-            host_cached,
+            memory_preference,
             [],
         )
     }
 }
 
+impl CpuAccessibleBuffer<[u8]> {
+    /// Builds a new buffer that holds the bitstream data of a video stream, to be decoded with a
+    /// video session for `profile`. The buffer has the `video_decode_src` and `transfer_dst`
+    /// usages.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `len` is zero.
+    /// - Panics if the [`khr_video_decode_queue`](crate::device::DeviceExtensions::khr_video_decode_queue)
+    ///   extension is not enabled on `device`.
+    pub fn video_bitstream(
+        device: Arc<Device>,
+        len: DeviceSize,
+        profile: &VideoProfile,
+        memory_preference: impl Into<HostMemoryPreference>,
+    ) -> Result<Arc<CpuAccessibleBuffer<[u8]>>, DeviceMemoryAllocationError> {
+        assert!(device.enabled_extensions().khr_video_decode_queue);
+
+        unsafe {
+            CpuAccessibleBuffer::raw_with_video_profiles(
+                device,
+                len,
+                BufferUsage {
+                    video_decode_src: true,
+                    transfer_dst: true,
+                    ..BufferUsage::none()
+                },
+                memory_preference,
+                [],
+                [*profile].into_iter().collect(),
+            )
+        }
+    }
+}
+
 impl<T> CpuAccessibleBuffer<T>
 where
     T: BufferContents + ?Sized,
@@ -218,12 +276,34 @@ where
         device: Arc<Device>,
         size: DeviceSize,
         usage: BufferUsage,
-        host_cached: bool,
+        memory_preference: impl Into<HostMemoryPreference>,
         queue_families: I,
     ) -> Result<Arc<CpuAccessibleBuffer<T>>, DeviceMemoryAllocationError>
     where
         I: IntoIterator<Item = QueueFamily<'a>>,
     {
+        CpuAccessibleBuffer::raw_with_video_profiles(
+            device,
+            size,
+            usage,
+            memory_preference,
+            queue_families,
+            SmallVec::new(),
+        )
+    }
+
+    unsafe fn raw_with_video_profiles<'a, I>(
+        device: Arc<Device>,
+        size: DeviceSize,
+        usage: BufferUsage,
+        memory_preference: impl Into<HostMemoryPreference>,
+        queue_families: I,
+        video_profiles: SmallVec<[VideoProfile; 1]>,
+    ) -> Result<Arc<CpuAccessibleBuffer<T>>, DeviceMemoryAllocationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        let memory_preference = memory_preference.into();
         let queue_families = queue_families
             .into_iter()
             .map(|f| f.id())
@@ -240,6 +320,7 @@ where
                     },
                     size,
                     usage,
+                    video_profiles,
                     ..Default::default()
                 },
             ) {
@@ -257,21 +338,7 @@ where
             AllocLayout::Linear,
             MappingRequirement::Map,
             Some(DedicatedAllocation::Buffer(&buffer)),
-            |m| {
-                if m.is_host_cached() {
-                    if host_cached {
-                        AllocFromRequirementsFilter::Preferred
-                    } else {
-                        AllocFromRequirementsFilter::Allowed
-                    }
-                } else {
-                    if host_cached {
-                        AllocFromRequirementsFilter::Allowed
-                    } else {
-                        AllocFromRequirementsFilter::Preferred
-                    }
-                }
-            },
+            |m| memory_preference.memory_type_filter(m),
         )?;
         debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
         debug_assert!(memory.mapped_memory().is_some());
@@ -322,17 +389,119 @@ where
     /// accesses (ie. reads).
     #[inline]
     pub fn read(&self) -> Result<ReadLock<T, A>, ReadLockError> {
+        self.try_read().map_err(ReadLockError::from)
+    }
+
+    /// Same as `read()`, but if the buffer is in use by the GPU, the returned error contains the
+    /// command buffers that are still using it.
+    ///
+    /// This never blocks, so it can be used to find out which submission must finish before the
+    /// buffer can be accessed, and to do other work in the meantime.
+    #[inline]
+    pub fn try_read(&self) -> Result<ReadLock<T, A>, TryLockError> {
+        let offset = self.memory.offset();
+        let data_range = offset..offset + self.size();
+        let (buffer_range, memory_range) = self.lock_ranges_for(&data_range);
+
+        self.lock_read(buffer_range, memory_range, data_range)
+    }
+
+    /// Waits for `future` to be signalled, then locks the buffer in order to read its content
+    /// from the CPU.
+    ///
+    /// This is meant to be used with the fence of the submission that last used the buffer. If
+    /// the buffer is still in use by another submission after the fence has been signalled, the
+    /// error returned by `try_read()` is returned.
+    ///
+    /// If `timeout` is `Some` and the fence isn't signalled within that time,
+    /// `FlushError::Timeout` is returned.
+    #[inline]
+    pub fn read_when_idle<F>(
+        &self,
+        future: &FenceSignalFuture<F>,
+        timeout: Option<Duration>,
+    ) -> Result<ReadLock<T, A>, ReadWhenIdleError>
+    where
+        F: GpuFuture,
+    {
+        future.wait(timeout)?;
+        Ok(self.try_read()?)
+    }
+
+    /// Locks the buffer in order to write its content from the CPU.
+    ///
+    /// If the buffer is currently in use by the GPU, this function will return an error. Similarly
+    /// if you called `read()` on the buffer and haven't dropped the lock, this function will
+    /// return an error as well.
+    ///
+    /// After this function successfully locks the buffer, any attempt to submit a command buffer
+    /// that uses it and any attempt to call `read()` will return an error.
+    #[inline]
+    pub fn write(&self) -> Result<WriteLock<T, A>, WriteLockError> {
+        self.try_write().map_err(WriteLockError::from)
+    }
+
+    /// Same as `write()`, but if the buffer is in use by the GPU, the returned error contains the
+    /// command buffers that are still using it.
+    ///
+    /// This never blocks, so it can be used to find out which submission must finish before the
+    /// buffer can be accessed, and to do other work in the meantime.
+    #[inline]
+    pub fn try_write(&self) -> Result<WriteLock<T, A>, TryLockError> {
+        let offset = self.memory.offset();
+        let data_range = offset..offset + self.size();
+        let (buffer_range, memory_range) = self.lock_ranges_for(&data_range);
+
+        self.lock_write(buffer_range, memory_range, data_range)
+    }
+
+    // Returns the range of the buffer to lock and the range of memory to invalidate and flush,
+    // in order to access the bytes of memory in `data_range`.
+    fn lock_ranges_for(
+        &self,
+        data_range: &Range<DeviceSize>,
+    ) -> (Range<DeviceSize>, Range<DeviceSize>) {
+        // Invalidating and flushing must happen on whole atoms, so the lock has to cover them
+        // too, or we could discard or overwrite data that someone else is accessing. The memory
+        // pool pads allocations in non-coherent memory to whole atoms, so the aligned range
+        // doesn't reach into other allocations.
+        let offset = self.memory.offset();
+        let memory_range = self
+            .memory
+            .mapped_memory()
+            .unwrap()
+            .align_range(data_range.clone());
+        debug_assert!(memory_range.start >= offset);
+
+        // The padding at the end of the allocation isn't part of the buffer.
+        let buffer_offset = self.inner().offset;
+        let buffer_range = buffer_offset + memory_range.start - offset
+            ..buffer_offset + (memory_range.end - offset).min(self.size());
+
+        (buffer_range, memory_range)
+    }
+
+    // Locks `buffer_range` for reading, invalidates `memory_range` and returns the data in
+    // `data_range`.
+    fn lock_read(
+        &self,
+        buffer_range: Range<DeviceSize>,
+        memory_range: Range<DeviceSize>,
+        data_range: Range<DeviceSize>,
+    ) -> Result<ReadLock<T, A>, TryLockError> {
         let mut state = self.inner.state();
-        let buffer_range = self.inner().offset..self.inner().offset + self.size();
 
-        unsafe {
-            state.check_cpu_read(buffer_range.clone())?;
-            state.cpu_read_lock(buffer_range.clone());
+        if let Err(err) = state.check_cpu_read(buffer_range.clone()) {
+            return Err(match err {
+                ReadLockError::CpuWriteLocked => TryLockError::CpuLocked,
+                ReadLockError::GpuWriteLocked => TryLockError::GpuLocked {
+                    command_buffers: state.gpu_users(buffer_range),
+                },
+                ReadLockError::MemoryMapError(err) => TryLockError::MemoryMapError(err),
+            });
         }
 
         let mapped_memory = self.memory.mapped_memory().unwrap();
-        let offset = self.memory.offset();
-        let memory_range = offset..offset + self.inner.size();
 
         let bytes = unsafe {
             // If there are other read locks being held at this point, they also called
@@ -340,58 +509,236 @@ where
             // lock, so there will no new data and this call will do nothing.
             // TODO: probably still more efficient to call it only if we're the first to acquire a
             // read lock, but the number of CPU locks isn't currently tracked anywhere.
-            mapped_memory
-                .invalidate_range(memory_range.clone())
-                .unwrap();
-            mapped_memory.read(memory_range.clone()).unwrap()
+            mapped_memory.invalidate_range(memory_range.clone())?;
+            mapped_memory.read(memory_range.clone())?
         };
 
+        unsafe {
+            state.cpu_read_lock(buffer_range.clone());
+        }
+
+        let data_start = (data_range.start - memory_range.start) as usize;
+        let data_end = (data_range.end - memory_range.start) as usize;
+
         Ok(ReadLock {
             inner: self,
             buffer_range,
             memory_range,
-            data: T::from_bytes(bytes).unwrap(),
+            data: T::from_bytes(&bytes[data_start..data_end]).unwrap(),
         })
     }
 
-    /// Locks the buffer in order to write its content from the CPU.
-    ///
-    /// If the buffer is currently in use by the GPU, this function will return an error. Similarly
-    /// if you called `read()` on the buffer and haven't dropped the lock, this function will
-    /// return an error as well.
-    ///
-    /// After this function successfully locks the buffer, any attempt to submit a command buffer
-    /// that uses it and any attempt to call `read()` will return an error.
-    #[inline]
-    pub fn write(&self) -> Result<WriteLock<T, A>, WriteLockError> {
+    // Locks `buffer_range` for writing, invalidates `memory_range` and returns the data in
+    // `data_range`. `memory_range` is flushed when the lock is dropped.
+    fn lock_write(
+        &self,
+        buffer_range: Range<DeviceSize>,
+        memory_range: Range<DeviceSize>,
+        data_range: Range<DeviceSize>,
+    ) -> Result<WriteLock<T, A>, TryLockError> {
         let mut state = self.inner.state();
-        let buffer_range = self.inner().offset..self.inner().offset + self.size();
 
-        unsafe {
-            state.check_cpu_write(buffer_range.clone())?;
-            state.cpu_write_lock(buffer_range.clone());
+        if let Err(err) = state.check_cpu_write(buffer_range.clone()) {
+            return Err(match err {
+                WriteLockError::CpuLocked => TryLockError::CpuLocked,
+                WriteLockError::GpuLocked => TryLockError::GpuLocked {
+                    command_buffers: state.gpu_users(buffer_range),
+                },
+                WriteLockError::MemoryMapError(err) => TryLockError::MemoryMapError(err),
+            });
         }
 
         let mapped_memory = self.memory.mapped_memory().unwrap();
-        let offset = self.memory.offset();
-        let memory_range = offset..offset + self.size();
 
         let bytes = unsafe {
-            mapped_memory
-                .invalidate_range(memory_range.clone())
-                .unwrap();
-            mapped_memory.write(memory_range.clone()).unwrap()
+            mapped_memory.invalidate_range(memory_range.clone())?;
+            mapped_memory.write(memory_range.clone())?
         };
 
+        unsafe {
+            state.cpu_write_lock(buffer_range.clone());
+        }
+
+        let data_start = (data_range.start - memory_range.start) as usize;
+        let data_end = (data_range.end - memory_range.start) as usize;
+
         Ok(WriteLock {
             inner: self,
             buffer_range,
             memory_range,
-            data: T::from_bytes_mut(bytes).unwrap(),
+            data: T::from_bytes_mut(&mut bytes[data_start..data_end]).unwrap(),
         })
     }
 }
 
+impl<T, A> CpuAccessibleBuffer<[T], A>
+where
+    [T]: BufferContents,
+    A: MemoryPoolAlloc,
+{
+    /// Locks a range of elements of the buffer in order to read them from the CPU.
+    ///
+    /// This behaves like `read()`, except that only the elements in `range` are locked. Other
+    /// parts of the buffer can meanwhile be written by the CPU or used by the GPU.
+    ///
+    /// If the memory of the buffer is not host-coherent, the locked range is widened to a
+    /// multiple of the [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size)
+    /// device property, so neighbouring elements may be locked as well.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `range` is empty or out of bounds of the buffer.
+    #[inline]
+    pub fn read_range(&self, range: Range<DeviceSize>) -> Result<ReadLock<[T], A>, ReadLockError> {
+        self.try_read_range(range).map_err(ReadLockError::from)
+    }
+
+    /// Same as `read_range()`, but if the range is in use by the GPU, the returned error contains
+    /// the command buffers that are still using it.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `range` is empty or out of bounds of the buffer.
+    #[inline]
+    pub fn try_read_range(
+        &self,
+        range: Range<DeviceSize>,
+    ) -> Result<ReadLock<[T], A>, TryLockError> {
+        let (buffer_range, memory_range, data_range) = self.lock_ranges(range);
+        self.lock_read(buffer_range, memory_range, data_range)
+    }
+
+    /// Locks a range of elements of the buffer in order to write them from the CPU.
+    ///
+    /// This behaves like `write()`, except that only the elements in `range` are locked. Other
+    /// parts of the buffer can meanwhile be accessed by the CPU or used by the GPU.
+    ///
+    /// If the memory of the buffer is not host-coherent, the locked range is widened to a
+    /// multiple of the [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size)
+    /// device property, so neighbouring elements may be locked as well.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `range` is empty or out of bounds of the buffer.
+    #[inline]
+    pub fn write_range(
+        &self,
+        range: Range<DeviceSize>,
+    ) -> Result<WriteLock<[T], A>, WriteLockError> {
+        self.try_write_range(range).map_err(WriteLockError::from)
+    }
+
+    /// Same as `write_range()`, but if the range is in use by the GPU, the returned error
+    /// contains the command buffers that are still using it.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `range` is empty or out of bounds of the buffer.
+    #[inline]
+    pub fn try_write_range(
+        &self,
+        range: Range<DeviceSize>,
+    ) -> Result<WriteLock<[T], A>, TryLockError> {
+        let (buffer_range, memory_range, data_range) = self.lock_ranges(range);
+        self.lock_write(buffer_range, memory_range, data_range)
+    }
+
+    /// Makes the writes of the device to a range of elements visible to the host.
+    ///
+    /// The locks returned by `read_range()` and `write_range()` already do this when they are
+    /// created, so this is only needed to do the work ahead of time, for example on another
+    /// thread once the fence of a readback has been signalled. It does nothing if the memory of
+    /// the buffer is host-coherent. Otherwise, the range is widened to a multiple of the
+    /// [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size) device
+    /// property.
+    ///
+    /// Returns an error if the range is locked for writing by the CPU or the GPU.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `range` is empty or out of bounds of the buffer.
+    pub fn invalidate_range(&self, range: Range<DeviceSize>) -> Result<(), TryLockError> {
+        let (buffer_range, memory_range, _) = self.lock_ranges(range);
+        let state = self.inner.state();
+
+        if let Err(err) = state.check_cpu_read(buffer_range.clone()) {
+            return Err(match err {
+                ReadLockError::CpuWriteLocked => TryLockError::CpuLocked,
+                ReadLockError::GpuWriteLocked => TryLockError::GpuLocked {
+                    command_buffers: state.gpu_users(buffer_range),
+                },
+                ReadLockError::MemoryMapError(err) => TryLockError::MemoryMapError(err),
+            });
+        }
+
+        // The state stays locked, so no writes can start until the invalidation is done.
+        unsafe {
+            self.memory
+                .mapped_memory()
+                .unwrap()
+                .invalidate_range(memory_range)?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes the writes of the host to a range of elements visible to the device.
+    ///
+    /// The locks returned by `write()` and `write_range()` already do this when they are dropped,
+    /// so this is only needed if the memory was written by other means. It does nothing if the
+    /// memory of the buffer is host-coherent. Otherwise, the range is widened to a multiple of the
+    /// [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size) device
+    /// property.
+    ///
+    /// Returns an error if the range is locked by the CPU or in use by the GPU.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `range` is empty or out of bounds of the buffer.
+    pub fn flush_range(&self, range: Range<DeviceSize>) -> Result<(), TryLockError> {
+        let (buffer_range, memory_range, _) = self.lock_ranges(range);
+        let mut state = self.inner.state();
+
+        if let Err(err) = state.check_cpu_write(buffer_range.clone()) {
+            return Err(match err {
+                WriteLockError::CpuLocked => TryLockError::CpuLocked,
+                WriteLockError::GpuLocked => TryLockError::GpuLocked {
+                    command_buffers: state.gpu_users(buffer_range),
+                },
+                WriteLockError::MemoryMapError(err) => TryLockError::MemoryMapError(err),
+            });
+        }
+
+        // The state stays locked, so the GPU can't start using the memory until the flush is
+        // done.
+        unsafe {
+            self.memory
+                .mapped_memory()
+                .unwrap()
+                .flush_range(memory_range)?;
+        }
+
+        Ok(())
+    }
+
+    // Converts a range of elements into the range of the buffer to lock, the range of memory to
+    // invalidate and flush, and the range of memory that holds the requested elements.
+    fn lock_ranges(
+        &self,
+        range: Range<DeviceSize>,
+    ) -> (Range<DeviceSize>, Range<DeviceSize>, Range<DeviceSize>) {
+        let element_size = size_of::<T>() as DeviceSize;
+        assert!(!range.is_empty());
+        assert!(range.end * element_size <= self.size());
+
+        let offset = self.memory.offset();
+        let data_range = offset + range.start * element_size..offset + range.end * element_size;
+        let (buffer_range, memory_range) = self.lock_ranges_for(&data_range);
+
+        (buffer_range, memory_range, data_range)
+    }
+}
+
 unsafe impl<T, A> BufferAccess for CpuAccessibleBuffer<T, A>
 where
     T: BufferContents + ?Sized,
@@ -440,6 +787,18 @@ where
     }
 }
 
+unsafe impl<T, A> VulkanObject for CpuAccessibleBuffer<T, A>
+where
+    T: BufferContents + ?Sized,
+{
+    type Object = ash::vk::Buffer;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Buffer {
+        self.inner.internal_object()
+    }
+}
+
 impl<T, A> PartialEq for CpuAccessibleBuffer<T, A>
 where
     T: BufferContents + ?Sized,
@@ -581,9 +940,19 @@ pub enum ReadLockError {
     CpuWriteLocked,
     /// The buffer is already locked for write mode by the GPU.
     GpuWriteLocked,
+    /// Invalidating or accessing the mapped memory of the buffer failed.
+    MemoryMapError(MemoryMapError),
 }
 
-impl error::Error for ReadLockError {}
+impl error::Error for ReadLockError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::MemoryMapError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 impl fmt::Display for ReadLockError {
     #[inline]
@@ -598,11 +967,23 @@ impl fmt::Display for ReadLockError {
                 ReadLockError::GpuWriteLocked => {
                     "the buffer is already locked for write mode by the GPU"
                 }
+                ReadLockError::MemoryMapError(_) => "accessing the mapped memory failed",
             }
         )
     }
 }
 
+impl From<TryLockError> for ReadLockError {
+    #[inline]
+    fn from(err: TryLockError) -> Self {
+        match err {
+            TryLockError::CpuLocked => Self::CpuWriteLocked,
+            TryLockError::GpuLocked { .. } => Self::GpuWriteLocked,
+            TryLockError::MemoryMapError(err) => Self::MemoryMapError(err),
+        }
+    }
+}
+
 /// Error when attempting to CPU-write a buffer.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum WriteLockError {
@@ -610,9 +991,19 @@ pub enum WriteLockError {
     CpuLocked,
     /// The buffer is already locked by the GPU.
     GpuLocked,
+    /// Invalidating or accessing the mapped memory of the buffer failed.
+    MemoryMapError(MemoryMapError),
 }
 
-impl error::Error for WriteLockError {}
+impl error::Error for WriteLockError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::MemoryMapError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 impl fmt::Display for WriteLockError {
     #[inline]
@@ -623,14 +1014,129 @@ impl fmt::Display for WriteLockError {
             match *self {
                 WriteLockError::CpuLocked => "the buffer is already locked by the CPU",
                 WriteLockError::GpuLocked => "the buffer is already locked by the GPU",
+                WriteLockError::MemoryMapError(_) => "accessing the mapped memory failed",
             }
         )
     }
 }
 
+impl From<TryLockError> for WriteLockError {
+    #[inline]
+    fn from(err: TryLockError) -> Self {
+        match err {
+            TryLockError::CpuLocked => Self::CpuLocked,
+            TryLockError::GpuLocked { .. } => Self::GpuLocked,
+            TryLockError::MemoryMapError(err) => Self::MemoryMapError(err),
+        }
+    }
+}
+
+/// Error when attempting to CPU-read or CPU-write a buffer with `try_read()` or `try_write()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TryLockError {
+    /// The buffer is already locked by the CPU.
+    CpuLocked,
+
+    /// The buffer is already locked by the GPU.
+    GpuLocked {
+        /// The command buffers that have been submitted and are still using the buffer. These
+        /// can be compared with the `internal_object()` of the inner `UnsafeCommandBuffer` of a
+        /// primary command buffer.
+        command_buffers: Vec<ash::vk::CommandBuffer>,
+    },
+
+    /// Invalidating or accessing the mapped memory of the buffer failed.
+    MemoryMapError(MemoryMapError),
+}
+
+impl error::Error for TryLockError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::MemoryMapError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TryLockError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::CpuLocked => write!(fmt, "the buffer is already locked by the CPU"),
+            Self::GpuLocked { command_buffers } => write!(
+                fmt,
+                "the buffer is already locked by the GPU, by {} command buffer(s)",
+                command_buffers.len(),
+            ),
+            Self::MemoryMapError(_) => write!(fmt, "accessing the mapped memory failed"),
+        }
+    }
+}
+
+impl From<MemoryMapError> for TryLockError {
+    #[inline]
+    fn from(err: MemoryMapError) -> Self {
+        Self::MemoryMapError(err)
+    }
+}
+
+/// Error when attempting to CPU-read a buffer with `read_when_idle()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReadWhenIdleError {
+    /// Waiting for the fence failed.
+    FlushError(FlushError),
+
+    /// The buffer was still locked after the fence was signalled.
+    TryLockError(TryLockError),
+}
+
+impl error::Error for ReadWhenIdleError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::FlushError(err) => Some(err),
+            Self::TryLockError(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for ReadWhenIdleError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FlushError(err) => err.fmt(f),
+            Self::TryLockError(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<FlushError> for ReadWhenIdleError {
+    #[inline]
+    fn from(err: FlushError) -> Self {
+        Self::FlushError(err)
+    }
+}
+
+impl From<TryLockError> for ReadWhenIdleError {
+    #[inline]
+    fn from(err: TryLockError) -> Self {
+        Self::TryLockError(err)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::buffer::{BufferUsage, CpuAccessibleBuffer};
+    use super::{ReadLockError, TryLockError, WriteLockError};
+    use crate::{
+        buffer::{BufferUsage, CpuAccessibleBuffer},
+        command_buffer::{
+            AutoCommandBufferBuilder, CommandBufferUsage, FillBufferInfo, PrimaryCommandBuffer,
+        },
+        memory::pool::{HostMemoryPreference, MemoryPoolAlloc},
+        sync::GpuFuture,
+        DeviceSize, VulkanObject,
+    };
 
     #[test]
     fn create_empty_buffer() {
@@ -645,4 +1151,163 @@ mod tests {
                 .unwrap();
         });
     }
+
+    #[test]
+    fn host_cached_preference() {
+        let (device, _queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::all(),
+            HostMemoryPreference::HostCached,
+            0..256u32,
+        )
+        .unwrap();
+
+        let memory_type_bits = buffer.inner.memory_requirements().memory_type_bits;
+        let has_host_cached = device.physical_device().memory_types().any(|t| {
+            memory_type_bits & (1 << t.id()) != 0 && t.is_host_visible() && t.is_host_cached()
+        });
+        assert_eq!(
+            buffer.memory.memory().memory_type().is_host_cached(),
+            has_host_cached
+        );
+
+        let content = buffer.read_range(128..256).unwrap();
+        assert!(content.iter().copied().eq(128..256u32));
+    }
+
+    #[test]
+    fn unaligned_ranges() {
+        let (device, _queue) = gfx_dev_and_queue!();
+        let atom_size = device.physical_device().properties().non_coherent_atom_size;
+        let len = atom_size * 4 + 3;
+
+        // Host-cached memory is often not host-coherent, so the element ranges below aren't
+        // aligned to the atoms that get invalidated and flushed.
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device,
+            BufferUsage::all(),
+            HostMemoryPreference::HostCached,
+            vec![0u8; len as usize],
+        )
+        .unwrap();
+
+        let mut content = buffer.write_range(1..atom_size * 2 + 1).unwrap();
+        assert_eq!(content.len() as DeviceSize, atom_size * 2);
+        content.fill(7);
+        drop(content);
+
+        let content = buffer.read_range(atom_size * 2..len).unwrap();
+        assert_eq!(content.len() as DeviceSize, len - atom_size * 2);
+        assert_eq!(content[0], 7);
+        assert!(content[1..].iter().all(|&x| x == 0));
+        drop(content);
+
+        let content = buffer.read().unwrap();
+        assert_eq!(content[0], 0);
+        assert!(content[1..atom_size as usize * 2 + 1]
+            .iter()
+            .all(|&x| x == 7));
+    }
+
+    #[test]
+    fn flush_and_invalidate_ranges() {
+        let (device, _queue) = gfx_dev_and_queue!();
+
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device,
+            BufferUsage::all(),
+            HostMemoryPreference::HostCached,
+            [0u32; 256],
+        )
+        .unwrap();
+
+        buffer.flush_range(3..130).unwrap();
+        buffer.invalidate_range(3..130).unwrap();
+
+        let lock = buffer.write_range(0..64).unwrap();
+        assert_eq!(
+            buffer.invalidate_range(10..20).unwrap_err(),
+            TryLockError::CpuLocked
+        );
+        assert_eq!(
+            buffer.flush_range(10..20).unwrap_err(),
+            TryLockError::CpuLocked
+        );
+        drop(lock);
+
+        let lock = buffer.read_range(0..64).unwrap();
+        buffer.invalidate_range(10..20).unwrap();
+        assert_eq!(
+            buffer.flush_range(10..20).unwrap_err(),
+            TryLockError::CpuLocked
+        );
+        drop(lock);
+    }
+
+    #[test]
+    fn write_disjoint_ranges() {
+        let (device, _queue) = gfx_dev_and_queue!();
+
+        let buffer =
+            CpuAccessibleBuffer::from_iter(device, BufferUsage::all(), false, [0u32; 1024])
+                .unwrap();
+
+        let mut first = buffer.write_range(0..256).unwrap();
+        let mut second = buffer.write_range(512..1024).unwrap();
+        assert_eq!(first.len(), 256);
+        assert_eq!(second.len(), 512);
+
+        assert_eq!(
+            buffer.write_range(128..640).unwrap_err(),
+            WriteLockError::CpuLocked
+        );
+        assert_eq!(buffer.read().unwrap_err(), ReadLockError::CpuWriteLocked);
+
+        first[0] = 1;
+        second[511] = 2;
+        drop(first);
+        drop(second);
+
+        let content = buffer.read().unwrap();
+        assert_eq!(content[0], 1);
+        assert_eq!(content[1023], 2);
+    }
+
+    #[test]
+    fn try_write_reports_submission() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer =
+            CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(), false, [0u32; 16])
+                .unwrap();
+
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        cbb.fill_buffer(FillBufferInfo {
+            data: 42,
+            ..FillBufferInfo::dst_buffer(buffer.clone())
+        })
+        .unwrap();
+        let cb = cbb.build().unwrap();
+        let command_buffer = cb.inner().internal_object();
+
+        let future = cb.execute(queue).unwrap();
+
+        assert_eq!(
+            buffer.try_write().unwrap_err(),
+            TryLockError::GpuLocked {
+                command_buffers: vec![command_buffer],
+            }
+        );
+
+        let future = future.then_signal_fence_and_flush().unwrap();
+        let content = buffer.read_when_idle(&future, None).unwrap();
+        assert_eq!(*content, [42u32; 16]);
+    }
 }
@@ -16,8 +16,8 @@ use crate::{
     device::{Device, DeviceOwned},
     memory::{
         pool::{
-            AllocFromRequirementsFilter, AllocLayout, MappingRequirement, MemoryPoolAlloc,
-            PotentialDedicatedAllocation, StdMemoryPool,
+            AllocFromRequirementsFilter, AllocLayout, HostMemoryPreference, MappingRequirement,
+            MemoryPoolAlloc, PotentialDedicatedAllocation, StdMemoryPool,
         },
         DedicatedAllocation, DeviceMemoryAllocationError, MemoryPool,
     },
@@ -52,6 +52,14 @@ use std::{
 /// Contrary to a `Vec`, elements automatically free themselves when they are dropped (ie. usually
 /// when you call `cleanup_finished()` on a future, or when you drop that future).
 ///
+/// # Capacity
+///
+/// The pool never shrinks by itself, so it keeps the memory needed by the highest number of
+/// elements that were in use at the same time. Applications whose usage varies a lot can call
+/// [`shrink_to_fit`](CpuBufferPool::shrink_to_fit) from time to time, and limit the growth with
+/// [`set_max_capacity`](CpuBufferPool::set_max_capacity). The [`statistics`](CpuBufferPool::statistics)
+/// show how much of the capacity is actually used.
+///
 /// # Arc-like
 ///
 /// The `CpuBufferPool` struct internally contains an `Arc`. You can clone the `CpuBufferPool` for
@@ -108,10 +116,45 @@ where
     // Buffer usage.
     usage: BufferUsage,
 
+    // The kind of memory that buffers are preferably allocated from, if any.
+    memory_preference: Option<HostMemoryPreference>,
+
+    // The limits and statistics of the pool, shared with all its clones.
+    shared: Arc<SharedState>,
+
     // Necessary to make it compile.
     marker: PhantomData<Box<T>>,
 }
 
+// State that is shared between all the clones of a pool.
+#[derive(Debug, Default)]
+struct SharedState {
+    // The capacity that the pool never grows beyond, or 0 if there is no limit.
+    max_capacity: AtomicU64,
+
+    // Number of buffers that were allocated.
+    buffer_allocations: AtomicU64,
+}
+
+/// Statistics about the usage of a [`CpuBufferPool`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpuBufferPoolStatistics {
+    /// The capacity of the current buffer, in number of elements.
+    pub capacity: DeviceSize,
+
+    /// The number of elements of the current buffer that are in use, including the padding needed
+    /// for alignment.
+    pub len: DeviceSize,
+
+    /// The highest number of elements of the current buffer that were in use at the same time,
+    /// since the buffer was allocated or since the last call to
+    /// [`shrink_to_fit`](CpuBufferPool::shrink_to_fit).
+    pub peak_len: DeviceSize,
+
+    /// The number of buffers that the pool and its clones have allocated since it was created.
+    pub buffer_allocations: u64,
+}
+
 // One buffer of the pool.
 #[derive(Debug)]
 struct ActualBuffer<A>
@@ -130,6 +173,10 @@ where
     // The index of the chunk that should be available next for the ring buffer.
     next_index: AtomicU64,
 
+    // Highest number of elements that were in use at the same time. Only modified while
+    // `chunks_in_use` is locked.
+    peak_len: AtomicU64,
+
     // Number of elements in the buffer.
     capacity: DeviceSize,
 }
@@ -203,10 +250,34 @@ where
             pool: pool,
             current_buffer: Mutex::new(None),
             usage: usage.clone(),
+            memory_preference: None,
+            shared: Arc::new(SharedState::default()),
             marker: PhantomData,
         }
     }
 
+    /// Builds a `CpuBufferPool` whose buffers are preferably allocated from the given kind of
+    /// memory.
+    ///
+    /// For example, a pool of dynamic uniform or vertex data can use
+    /// `HostMemoryPreference::DeviceLocal`, so that the data is written directly to the memory of
+    /// the device when it has resizable BAR.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `T` has zero size.
+    #[inline]
+    pub fn with_memory_preference(
+        device: Arc<Device>,
+        usage: BufferUsage,
+        memory_preference: impl Into<HostMemoryPreference>,
+    ) -> CpuBufferPool<T> {
+        CpuBufferPool {
+            memory_preference: Some(memory_preference.into()),
+            ..CpuBufferPool::new(device, usage)
+        }
+    }
+
     /// Builds a `CpuBufferPool` meant for simple uploads.
     ///
     /// Shortcut for a pool that can only be used as transfer source and with exclusive queue
@@ -286,6 +357,103 @@ where
         }
     }
 
+    /// Returns the capacity that the pool never grows beyond, in number of elements, if any.
+    #[inline]
+    pub fn max_capacity(&self) -> Option<DeviceSize> {
+        match self.shared.max_capacity.load(Ordering::SeqCst) {
+            0 => None,
+            max_capacity => Some(max_capacity),
+        }
+    }
+
+    /// Sets the capacity that the pool never grows beyond, in number of elements.
+    ///
+    /// Once the pool has reached this capacity, a new buffer of the same capacity is allocated
+    /// when it is full, instead of a larger one. The buffers that are full are freed once their
+    /// subbuffers are dropped. Allocating more elements at once than `max_capacity` returns
+    /// [`DeviceMemoryAllocationError::PoolCapacityExceeded`].
+    ///
+    /// This doesn't change the current buffer if it is already larger. Call
+    /// [`shrink_to_fit`](Self::shrink_to_fit) to replace it.
+    ///
+    /// The limit applies to all the clones of the pool.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `max_capacity` is `Some(0)`.
+    #[inline]
+    pub fn set_max_capacity(&self, max_capacity: Option<DeviceSize>) {
+        assert!(max_capacity != Some(0));
+        self.shared
+            .max_capacity
+            .store(max_capacity.unwrap_or(0), Ordering::SeqCst);
+    }
+
+    /// Returns statistics about the usage of the pool.
+    pub fn statistics(&self) -> CpuBufferPoolStatistics {
+        let (capacity, len, peak_len) = match *self.current_buffer.lock().unwrap() {
+            None => (0, 0, 0),
+            Some(ref buf) => {
+                let chunks_in_use = buf.chunks_in_use.lock().unwrap();
+                (
+                    buf.capacity,
+                    chunks_in_use.iter().map(|c| c.len).sum(),
+                    buf.peak_len.load(Ordering::SeqCst),
+                )
+            }
+        };
+
+        CpuBufferPoolStatistics {
+            capacity,
+            len,
+            peak_len,
+            buffer_allocations: self.shared.buffer_allocations.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Replaces the current buffer with a smaller one, if it is larger than the highest number of
+    /// elements that were in use at the same time since it was allocated, or since the last call
+    /// to this method. If no elements were in use, the buffer is freed and the capacity becomes
+    /// 0.
+    ///
+    /// The subbuffers that are still in use keep the memory of the previous buffer alive until
+    /// they are dropped.
+    ///
+    /// Since this can involve a memory allocation, an `OomError` can happen.
+    pub fn shrink_to_fit(&self) -> Result<(), DeviceMemoryAllocationError> {
+        let mut cur_buf = self.current_buffer.lock().unwrap();
+
+        let (capacity, len, peak_len) = match *cur_buf {
+            None => return Ok(()),
+            Some(ref buf) => {
+                let chunks_in_use = buf.chunks_in_use.lock().unwrap();
+                (
+                    buf.capacity,
+                    chunks_in_use.iter().map(|c| c.len).sum::<DeviceSize>(),
+                    buf.peak_len.load(Ordering::SeqCst),
+                )
+            }
+        };
+
+        let target_capacity = match self.max_capacity() {
+            Some(max_capacity) => peak_len.min(max_capacity),
+            None => peak_len,
+        };
+
+        if target_capacity == 0 {
+            *cur_buf = None;
+        } else if target_capacity < capacity {
+            self.reset_buf(&mut cur_buf, target_capacity)?;
+        } else {
+            // Start measuring again from the elements that are currently in use.
+            let buf = cur_buf.as_ref().unwrap();
+            let _chunks_in_use = buf.chunks_in_use.lock().unwrap();
+            buf.peak_len.store(len, Ordering::SeqCst);
+        }
+
+        Ok(())
+    }
+
     /// Makes sure that the capacity is at least `capacity`. Allocates memory if it is not the
     /// case.
     ///
@@ -295,6 +463,15 @@ where
             return Ok(());
         }
 
+        if let Some(max_capacity) = self.max_capacity() {
+            if capacity > max_capacity {
+                return Err(DeviceMemoryAllocationError::PoolCapacityExceeded {
+                    required_capacity: capacity,
+                    max_capacity,
+                });
+            }
+        }
+
         let mut cur_buf = self.current_buffer.lock().unwrap();
 
         // Check current capacity.
@@ -359,11 +536,22 @@ where
             Err(d) => d,
         };
 
-        let next_capacity = match *mutex {
+        let mut next_capacity = match *mutex {
             Some(ref b) if (data.len() as DeviceSize) < b.capacity => 2 * b.capacity,
             _ => 2 * data.len().max(1) as DeviceSize,
         };
 
+        if let Some(max_capacity) = self.max_capacity() {
+            if data.len() as DeviceSize > max_capacity {
+                return Err(DeviceMemoryAllocationError::PoolCapacityExceeded {
+                    required_capacity: data.len() as DeviceSize,
+                    max_capacity,
+                });
+            }
+
+            next_capacity = next_capacity.min(max_capacity);
+        }
+
         self.reset_buf(&mut mutex, next_capacity)?;
 
         match self.try_next_impl(&mut mutex, data) {
@@ -424,7 +612,10 @@ where
                 AllocLayout::Linear,
                 MappingRequirement::Map,
                 Some(DedicatedAllocation::Buffer(&buffer)),
-                |_| AllocFromRequirementsFilter::Allowed,
+                |memory_type| match self.memory_preference {
+                    Some(memory_preference) => memory_preference.memory_type_filter(memory_type),
+                    None => AllocFromRequirementsFilter::Allowed,
+                },
             )?;
             debug_assert!((mem.offset() % mem_reqs.alignment) == 0);
             debug_assert!(mem.mapped_memory().is_some());
@@ -435,8 +626,12 @@ where
                 memory: mem,
                 chunks_in_use: Mutex::new(vec![]),
                 next_index: AtomicU64::new(0),
+                peak_len: AtomicU64::new(0),
                 capacity: capacity,
             }));
+            self.shared
+                .buffer_allocations
+                .fetch_add(1, Ordering::SeqCst);
 
             Ok(())
         }
@@ -587,6 +782,8 @@ where
             len: occupied_len,
             num_cpu_accesses: 1,
         });
+        let len = chunks_in_use.iter().map(|c| c.len).sum();
+        current_buffer.peak_len.fetch_max(len, Ordering::SeqCst);
 
         Ok(CpuBufferPoolChunk {
             // TODO: remove .clone() once non-lexical borrows land
@@ -613,6 +810,8 @@ where
             pool: self.pool.clone(),
             current_buffer: Mutex::new(buf.clone()),
             usage: self.usage.clone(),
+            memory_preference: self.memory_preference,
+            shared: self.shared.clone(),
             marker: PhantomData,
         }
     }
@@ -863,7 +1062,10 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::buffer::CpuBufferPool;
+    use crate::{
+        buffer::{BufferUsage, CpuBufferPool},
+        memory::{pool::HostMemoryPreference, DeviceMemoryAllocationError},
+    };
     use std::mem;
 
     #[test]
@@ -883,6 +1085,19 @@ mod tests {
         assert_eq!(pool.capacity(), 83);
     }
 
+    #[test]
+    fn device_local_preference() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let pool = CpuBufferPool::<u8>::with_memory_preference(
+            device,
+            BufferUsage::uniform_buffer(),
+            HostMemoryPreference::DeviceLocal,
+        );
+        pool.reserve(64).unwrap();
+        assert_eq!(pool.capacity(), 64);
+    }
+
     #[test]
     fn capacity_increase() {
         let (device, _) = gfx_dev_and_queue!();
@@ -939,6 +1154,83 @@ mod tests {
         assert_eq!(pool.capacity(), 5);
     }
 
+    #[test]
+    fn shrink_to_fit() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let pool = CpuBufferPool::<u8>::upload(device);
+        pool.reserve(64).unwrap();
+
+        let a = pool.chunk(vec![0; 8]).unwrap();
+        let b = pool.chunk(vec![0; 8]).unwrap();
+        drop(b);
+
+        let statistics = pool.statistics();
+        assert_eq!(statistics.capacity, 64);
+        assert_eq!(statistics.len, 8);
+        assert_eq!(statistics.peak_len, 16);
+        assert_eq!(statistics.buffer_allocations, 1);
+
+        pool.shrink_to_fit().unwrap();
+        assert_eq!(pool.capacity(), 16);
+        assert_eq!(pool.statistics().buffer_allocations, 2);
+
+        // The new buffer wasn't used, so shrinking again frees it.
+        drop(a);
+        pool.shrink_to_fit().unwrap();
+        assert_eq!(pool.capacity(), 0);
+    }
+
+    #[test]
+    fn max_capacity() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let pool = CpuBufferPool::<u8>::upload(device);
+        pool.set_max_capacity(Some(8));
+
+        let _a = pool.chunk(vec![0; 6]).unwrap();
+        assert_eq!(pool.capacity(), 8);
+
+        // The pool doesn't grow, but allocates a new buffer of the same capacity.
+        let _b = pool.chunk(vec![0; 6]).unwrap();
+        assert_eq!(pool.capacity(), 8);
+        assert_eq!(pool.statistics().buffer_allocations, 2);
+
+        match pool.chunk(vec![0; 9]) {
+            Err(DeviceMemoryAllocationError::PoolCapacityExceeded {
+                required_capacity: 9,
+                max_capacity: 8,
+            }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn clones_share_limits_and_statistics() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let pool = CpuBufferPool::<u8>::upload(device);
+        let clone = pool.clone();
+        clone.set_max_capacity(Some(8));
+        assert_eq!(pool.max_capacity(), Some(8));
+
+        let _a = pool.chunk(vec![0; 6]).unwrap();
+        let _b = clone.chunk(vec![0; 6]).unwrap();
+        assert_eq!(pool.statistics().buffer_allocations, 2);
+        assert_eq!(clone.statistics().buffer_allocations, 2);
+
+        match clone.chunk(vec![0; 9]) {
+            Err(DeviceMemoryAllocationError::PoolCapacityExceeded {
+                required_capacity: 9,
+                max_capacity: 8,
+            }) => (),
+            _ => panic!(),
+        }
+
+        pool.set_max_capacity(None);
+        assert_eq!(clone.max_capacity(), None);
+    }
+
     #[test]
     fn chunk_0_elems_doesnt_pollute() {
         let (device, _) = gfx_dev_and_queue!();
@@ -20,20 +20,25 @@ use super::{
     BufferUsage, TypedBufferAccess,
 };
 use crate::{
-    device::{physical::QueueFamily, Device, DeviceOwned},
+    device::{
+        physical::{MemoryType, QueueFamily},
+        AndroidHardwareBufferProperties, Device, DeviceOwned,
+    },
     memory::{
         pool::{
-            alloc_dedicated_with_exportable_fd, AllocFromRequirementsFilter, AllocLayout,
+            alloc_dedicated_with_export_handle_types, AllocFromRequirementsFilter, AllocLayout,
             MappingRequirement, MemoryPoolAlloc, PotentialDedicatedAllocation, StdMemoryPoolAlloc,
         },
-        DedicatedAllocation, DeviceMemoryAllocationError, DeviceMemoryExportError,
-        ExternalMemoryHandleType, MemoryPool, MemoryRequirements,
+        DedicatedAllocation, DeviceMemory, DeviceMemoryAllocationError, DeviceMemoryExportError,
+        ExternalMemoryHandleType, ExternalMemoryHandleTypes, MemoryAllocateInfo, MemoryImportInfo,
+        MemoryPool, MemoryRequirements,
     },
     sync::Sharing,
-    DeviceSize,
+    DeviceSize, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{
+    ffi::c_void,
     fs::File,
     hash::{Hash, Hasher},
     marker::PhantomData,
@@ -208,12 +213,56 @@ where
     /// # Panics
     ///
     /// - Panics if `size` is zero.
+    #[inline]
     pub unsafe fn raw<'a, I>(
         device: Arc<Device>,
         size: DeviceSize,
         usage: BufferUsage,
         queue_families: I,
     ) -> Result<Arc<DeviceLocalBuffer<T>>, DeviceMemoryAllocationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        DeviceLocalBuffer::raw_impl(device, size, usage, None, queue_families)
+    }
+
+    /// Same as `raw`, but the memory of the buffer is allocated with the given priority, between
+    /// 0.0 and 1.0. When the device runs out of memory, the implementation moves buffers and
+    /// images with a lower priority to system memory first.
+    ///
+    /// The priority is ignored if the
+    /// [`memory_priority`](crate::device::Features::memory_priority) feature is not enabled on
+    /// the device.
+    ///
+    /// # Safety
+    ///
+    /// - You must ensure that the size that you pass is correct for `T`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `size` is zero.
+    /// - Panics if `priority` is not between 0.0 and 1.0 inclusive.
+    #[inline]
+    pub unsafe fn raw_with_priority<'a, I>(
+        device: Arc<Device>,
+        size: DeviceSize,
+        usage: BufferUsage,
+        priority: f32,
+        queue_families: I,
+    ) -> Result<Arc<DeviceLocalBuffer<T>>, DeviceMemoryAllocationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        DeviceLocalBuffer::raw_impl(device, size, usage, Some(priority), queue_families)
+    }
+
+    unsafe fn raw_impl<'a, I>(
+        device: Arc<Device>,
+        size: DeviceSize,
+        usage: BufferUsage,
+        priority: Option<f32>,
+        queue_families: I,
+    ) -> Result<Arc<DeviceLocalBuffer<T>>, DeviceMemoryAllocationError>
     where
         I: IntoIterator<Item = QueueFamily<'a>>,
     {
@@ -222,22 +271,42 @@ where
             .map(|f| f.id())
             .collect::<SmallVec<[u32; 4]>>();
 
-        let (buffer, mem_reqs) = Self::build_buffer(&device, size, usage, &queue_families)?;
-
-        let memory = MemoryPool::alloc_from_requirements(
-            &Device::standard_pool(&device),
-            &mem_reqs,
-            AllocLayout::Linear,
-            MappingRequirement::DoNotMap,
-            Some(DedicatedAllocation::Buffer(&buffer)),
-            |t| {
-                if t.is_device_local() {
-                    AllocFromRequirementsFilter::Preferred
-                } else {
-                    AllocFromRequirementsFilter::Allowed
-                }
-            },
+        let (buffer, mem_reqs) = Self::build_buffer(
+            &device,
+            size,
+            usage,
+            ExternalMemoryHandleTypes::none(),
+            &queue_families,
         )?;
+
+        let pool = Device::standard_pool(&device);
+        let dedicated_allocation = Some(DedicatedAllocation::Buffer(&buffer));
+        let filter = |t: MemoryType| {
+            if t.is_device_local() {
+                AllocFromRequirementsFilter::Preferred
+            } else {
+                AllocFromRequirementsFilter::Allowed
+            }
+        };
+        let memory = match priority {
+            Some(priority) => MemoryPool::alloc_from_requirements_with_priority(
+                &pool,
+                &mem_reqs,
+                AllocLayout::Linear,
+                MappingRequirement::DoNotMap,
+                dedicated_allocation,
+                priority,
+                filter,
+            )?,
+            None => MemoryPool::alloc_from_requirements(
+                &pool,
+                &mem_reqs,
+                AllocLayout::Linear,
+                MappingRequirement::DoNotMap,
+                dedicated_allocation,
+                filter,
+            )?,
+        };
         debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
         buffer.bind_memory(memory.memory(), memory.offset())?;
 
@@ -266,19 +335,46 @@ where
         assert!(device.enabled_extensions().khr_external_memory_fd);
         assert!(device.enabled_extensions().khr_external_memory);
 
+        DeviceLocalBuffer::raw_with_external_memory(
+            device,
+            size,
+            usage,
+            ExternalMemoryHandleTypes::posix(),
+            queue_families,
+        )
+    }
+
+    /// Same as `raw`, but the buffer is backed by a dedicated allocation that can be exported
+    /// with `export_handle_types`, to share the buffer's contents with other APIs.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `size` is zero.
+    pub unsafe fn raw_with_external_memory<'a, I>(
+        device: Arc<Device>,
+        size: DeviceSize,
+        usage: BufferUsage,
+        export_handle_types: ExternalMemoryHandleTypes,
+        queue_families: I,
+    ) -> Result<Arc<DeviceLocalBuffer<T>>, DeviceMemoryAllocationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
         let queue_families = queue_families
             .into_iter()
             .map(|f| f.id())
             .collect::<SmallVec<[u32; 4]>>();
 
-        let (buffer, mem_reqs) = Self::build_buffer(&device, size, usage, &queue_families)?;
+        let (buffer, mem_reqs) =
+            Self::build_buffer(&device, size, usage, export_handle_types, &queue_families)?;
 
-        let memory = alloc_dedicated_with_exportable_fd(
+        let memory = alloc_dedicated_with_export_handle_types(
             device.clone(),
             &mem_reqs,
             AllocLayout::Linear,
             MappingRequirement::DoNotMap,
             DedicatedAllocation::Buffer(&buffer),
+            export_handle_types,
             |t| {
                 if t.is_device_local() {
                     AllocFromRequirementsFilter::Preferred
@@ -299,10 +395,68 @@ where
         }))
     }
 
+    /// Same as `raw`, but the memory of the buffer is imported from an Android hardware buffer
+    /// with the `AHARDWAREBUFFER_FORMAT_BLOB` format and the
+    /// `AHARDWAREBUFFER_USAGE_GPU_DATA_BUFFER` usage. `properties` must have been returned by
+    /// [`Device::android_hardware_buffer_properties`] for `buffer`.
+    ///
+    /// # Safety
+    ///
+    /// - You must ensure that the size that you pass is correct for `T`, and is not greater than
+    ///   the size of the hardware buffer.
+    /// - See the documentation of [`MemoryImportInfo::AndroidHardwareBuffer`].
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `size` is zero.
+    pub unsafe fn raw_from_android_hardware_buffer<'a, I>(
+        device: Arc<Device>,
+        size: DeviceSize,
+        usage: BufferUsage,
+        queue_families: I,
+        buffer: *mut c_void,
+        properties: &AndroidHardwareBufferProperties,
+    ) -> Result<Arc<DeviceLocalBuffer<T>>, DeviceMemoryAllocationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        let queue_families = queue_families
+            .into_iter()
+            .map(|f| f.id())
+            .collect::<SmallVec<[u32; 4]>>();
+
+        let (inner, _) = Self::build_buffer(
+            &device,
+            size,
+            usage,
+            ExternalMemoryHandleType::AndroidHardwareBuffer.into(),
+            &queue_families,
+        )?;
+
+        let memory = DeviceMemory::import(
+            device,
+            MemoryAllocateInfo {
+                allocation_size: properties.allocation_size,
+                memory_type_index: properties.memory_type_bits.trailing_zeros(),
+                ..Default::default()
+            },
+            MemoryImportInfo::AndroidHardwareBuffer { buffer },
+        )?;
+        inner.bind_memory(&memory, 0)?;
+
+        Ok(Arc::new(DeviceLocalBuffer {
+            inner,
+            memory: PotentialDedicatedAllocation::Dedicated(memory),
+            queue_families,
+            marker: PhantomData,
+        }))
+    }
+
     unsafe fn build_buffer(
         device: &Arc<Device>,
         size: DeviceSize,
         usage: BufferUsage,
+        external_memory_handle_types: ExternalMemoryHandleTypes,
         queue_families: &SmallVec<[u32; 4]>,
     ) -> Result<(Arc<UnsafeBuffer>, MemoryRequirements), DeviceMemoryAllocationError> {
         let buffer = {
@@ -316,11 +470,18 @@ where
                     },
                     size,
                     usage,
+                    external_memory_handle_types,
                     ..Default::default()
                 },
             ) {
                 Ok(b) => b,
                 Err(BufferCreationError::AllocError(err)) => return Err(err),
+                Err(BufferCreationError::ExtensionNotEnabled { extension, reason }) => {
+                    return Err(DeviceMemoryAllocationError::ExtensionNotEnabled {
+                        extension,
+                        reason,
+                    })
+                }
                 Err(_) => unreachable!(), // We don't use sparse binding, therefore the other
                                           // errors can't happen
             }
@@ -333,9 +494,47 @@ where
     /// requires `khr_external_memory_fd` and `khr_external_memory` extensions to be loaded.
     /// Only works on Linux/BSD.
     pub fn export_posix_fd(&self) -> Result<File, DeviceMemoryExportError> {
-        self.memory
-            .memory()
-            .export_fd(ExternalMemoryHandleType::OpaqueFd)
+        self.export_fd(ExternalMemoryHandleType::OpaqueFd)
+    }
+
+    /// Exports the memory backing the buffer into a Unix file descriptor. The caller owns the
+    /// returned `File`.
+    ///
+    /// The buffer must have been created with `raw_with_external_memory`, and `handle_type` must
+    /// have been included in `export_handle_types`. The buffer's contents start at offset 0 of
+    /// the exported memory.
+    pub fn export_fd(
+        &self,
+        handle_type: ExternalMemoryHandleType,
+    ) -> Result<File, DeviceMemoryExportError> {
+        self.memory.memory().export_fd(handle_type)
+    }
+
+    /// Exports the memory backing the buffer into a Win32 handle.
+    ///
+    /// The buffer must have been created with `raw_with_external_memory`, and `handle_type` must
+    /// have been included in `export_handle_types`. The buffer's contents start at offset 0 of
+    /// the exported memory.
+    pub fn export_win32_handle(
+        &self,
+        handle_type: ExternalMemoryHandleType,
+    ) -> Result<*mut c_void, DeviceMemoryExportError> {
+        self.memory.memory().export_win32_handle(handle_type)
+    }
+
+    /// Exports the memory backing the buffer into an Android hardware buffer. The caller owns a
+    /// reference to the returned `AHardwareBuffer`.
+    ///
+    /// The buffer must have been created with `raw_with_external_memory`, and
+    /// `android_hardware_buffer` must have been included in `export_handle_types`.
+    pub fn export_android_hardware_buffer(&self) -> Result<*mut c_void, DeviceMemoryExportError> {
+        self.memory.memory().export_android_hardware_buffer()
+    }
+
+    /// Returns the size of the memory allocation backing the buffer, which can be greater than
+    /// the size of the buffer.
+    pub fn mem_size(&self) -> DeviceSize {
+        self.memory.memory().allocation_size()
     }
 }
 
@@ -369,6 +568,18 @@ where
     }
 }
 
+unsafe impl<T, A> VulkanObject for DeviceLocalBuffer<T, A>
+where
+    T: BufferContents + ?Sized,
+{
+    type Object = ash::vk::Buffer;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Buffer {
+        self.inner.internal_object()
+    }
+}
+
 unsafe impl<T, A> BufferAccess for DeviceLocalBuffer<T, A>
 where
     T: BufferContents + ?Sized,
@@ -436,3 +647,70 @@ where
         self.size().hash(state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DeviceLocalBuffer;
+    use crate::buffer::BufferUsage;
+    use crate::device::physical::PhysicalDevice;
+    use crate::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo};
+    use crate::instance::{Instance, InstanceCreateInfo, InstanceExtensions};
+    use crate::memory::{
+        DeviceMemoryExportError, ExternalMemoryHandleType, ExternalMemoryHandleTypes,
+    };
+
+    #[test]
+    fn external_memory_export() {
+        let instance = match Instance::new(InstanceCreateInfo {
+            enabled_extensions: InstanceExtensions {
+                khr_get_physical_device_properties2: true,
+                khr_external_memory_capabilities: true,
+                ..InstanceExtensions::none()
+            },
+            ..Default::default()
+        }) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+
+        let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+        let queue_family = physical_device.queue_families().next().unwrap();
+
+        let (device, _) = match Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                enabled_extensions: DeviceExtensions {
+                    khr_dedicated_allocation: true,
+                    khr_external_memory: true,
+                    khr_external_memory_fd: true,
+                    ..DeviceExtensions::none()
+                },
+                queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
+                ..Default::default()
+            },
+        ) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+
+        let buffer = unsafe {
+            DeviceLocalBuffer::<[u32]>::raw_with_external_memory(
+                device.clone(),
+                1024,
+                BufferUsage::storage_buffer(),
+                ExternalMemoryHandleTypes::posix(),
+                [queue_family],
+            )
+            .unwrap()
+        };
+        let _fd = buffer
+            .export_fd(ExternalMemoryHandleType::OpaqueFd)
+            .unwrap();
+
+        // `dma_buf` was not in `export_handle_types`.
+        assert!(matches!(
+            buffer.export_fd(ExternalMemoryHandleType::DmaBuf),
+            Err(DeviceMemoryExportError::HandleTypeNotSupported { .. })
+        ));
+    }
+}
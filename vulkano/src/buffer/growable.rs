@@ -0,0 +1,457 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Device-local buffer that can grow.
+//!
+//! A [`GrowableBuffer`] wraps a [`DeviceLocalBuffer`] of an array. When more elements are needed,
+//! [`grow`](GrowableBuffer::grow) allocates a larger buffer and submits a copy of the old contents
+//! to it. The old buffer stays the current one until the copy has finished, so it can be used in
+//! the meantime. Calling [`update`](GrowableBuffer::update) regularly, for example once per frame,
+//! replaces the current buffer with the larger one once the copy is done.
+//!
+//! ```
+//! use vulkano::buffer::{growable::GrowableBuffer, BufferUsage};
+//! # let device: std::sync::Arc<vulkano::device::Device> = return;
+//! # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+//!
+//! let particles = GrowableBuffer::<[f32; 4]>::new(
+//!     device.clone(),
+//!     1024,
+//!     BufferUsage::storage_buffer(),
+//!     [queue.family()],
+//! )
+//! .unwrap();
+//!
+//! // More particles were spawned than there is room for.
+//! particles.grow(queue.clone(), 4096).unwrap();
+//!
+//! // Later, when recording the next frame.
+//! if particles.update().unwrap() {
+//!     // The buffer was replaced, descriptor sets that use it must be recreated.
+//! }
+//! let buffer = particles.buffer();
+//! ```
+
+use super::{BufferContents, BufferUsage, DeviceLocalBuffer, TypedBufferAccess};
+use crate::{
+    command_buffer::{
+        AutoCommandBufferBuilder, BuildError, CommandBufferBeginError, CommandBufferExecError,
+        CommandBufferUsage, CopyBufferInfo, CopyError, PrimaryCommandBuffer,
+    },
+    device::{physical::QueueFamily, Device, DeviceOwned, Queue},
+    memory::DeviceMemoryAllocationError,
+    sync::{self, FenceSignalFuture, FlushError, GpuFuture},
+    DeviceSize, OomError,
+};
+use parking_lot::Mutex;
+use std::{error, fmt, sync::Arc};
+
+/// Device-local buffer of an array that can grow, while keeping its contents.
+///
+/// The buffers are always created with the `transfer_src` and `transfer_dst` usages, in addition
+/// to the usage that is provided, so that the contents can be copied.
+///
+/// # Accessing the buffer while it grows
+///
+/// Until the copy to the larger buffer has finished, [`buffer`](Self::buffer) keeps returning the
+/// old buffer. Commands can keep reading from it, but anything that is written to it after
+/// [`grow`](Self::grow) is called is not copied to the larger buffer. Work that writes to the
+/// buffer should therefore be submitted before growing it, and passed to
+/// [`grow_after`](Self::grow_after).
+pub struct GrowableBuffer<T>
+where
+    [T]: BufferContents,
+{
+    device: Arc<Device>,
+    usage: BufferUsage,
+    state: Mutex<GrowableBufferState<T>>,
+}
+
+struct GrowableBufferState<T>
+where
+    [T]: BufferContents,
+{
+    // The buffer that is returned by `buffer()`.
+    current: Arc<DeviceLocalBuffer<[T]>>,
+    // The larger buffer that the contents are being copied to, if any.
+    pending: Option<PendingGrowth<T>>,
+}
+
+struct PendingGrowth<T>
+where
+    [T]: BufferContents,
+{
+    buffer: Arc<DeviceLocalBuffer<[T]>>,
+    future: Arc<FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>>,
+}
+
+impl<T> GrowableBuffer<T>
+where
+    [T]: BufferContents,
+{
+    /// Creates a new buffer with room for `len` elements.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `T` has zero size.
+    /// - Panics if `len` is zero.
+    pub fn new<'a, I>(
+        device: Arc<Device>,
+        len: DeviceSize,
+        usage: BufferUsage,
+        queue_families: I,
+    ) -> Result<GrowableBuffer<T>, DeviceMemoryAllocationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        let usage = BufferUsage {
+            transfer_src: true,
+            transfer_dst: true,
+            ..usage
+        };
+        let current = DeviceLocalBuffer::array(device.clone(), len, usage, queue_families)?;
+
+        Ok(GrowableBuffer {
+            device,
+            usage,
+            state: Mutex::new(GrowableBufferState {
+                current,
+                pending: None,
+            }),
+        })
+    }
+
+    /// Returns the current buffer.
+    ///
+    /// This is the old buffer until a growth has finished and [`update`](Self::update) or
+    /// [`wait`](Self::wait) has been called.
+    #[inline]
+    pub fn buffer(&self) -> Arc<DeviceLocalBuffer<[T]>> {
+        self.state.lock().current.clone()
+    }
+
+    /// Returns the number of elements that the buffer will have once all growths have finished.
+    #[inline]
+    pub fn target_len(&self) -> DeviceSize {
+        let state = self.state.lock();
+
+        match &state.pending {
+            Some(pending) => pending.buffer.len(),
+            None => state.current.len(),
+        }
+    }
+
+    /// Returns whether a growth has been submitted, but the buffer hasn't been replaced yet.
+    #[inline]
+    pub fn is_growing(&self) -> bool {
+        self.state.lock().pending.is_some()
+    }
+
+    /// Grows the buffer so that it has room for at least `len` elements, by copying its contents
+    /// to a larger buffer on `queue`.
+    ///
+    /// Does nothing if the buffer already has, or is already growing to, at least `len` elements.
+    /// If a growth is already in progress, the copy starts from the buffer of that growth once it
+    /// has finished.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `queue` doesn't belong to the same device as the buffer.
+    #[inline]
+    pub fn grow(&self, queue: Arc<Queue>, len: DeviceSize) -> Result<(), GrowableBufferError> {
+        let now = sync::now(self.device.clone());
+        self.grow_after(now, queue, len)
+    }
+
+    /// Same as [`grow`](Self::grow), but the copy is submitted after `future`.
+    ///
+    /// This is needed if the buffer is still being written by previously submitted work.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `queue` doesn't belong to the same device as the buffer.
+    pub fn grow_after<F>(
+        &self,
+        future: F,
+        queue: Arc<Queue>,
+        len: DeviceSize,
+    ) -> Result<(), GrowableBufferError>
+    where
+        F: GpuFuture + Send + Sync + 'static,
+    {
+        assert_eq!(queue.device(), &self.device);
+
+        let mut state = self.state.lock();
+
+        // If a growth is in progress, its buffer holds the most recent contents once it is done.
+        let src_buffer = match &state.pending {
+            Some(pending) => pending.buffer.clone(),
+            None => state.current.clone(),
+        };
+
+        if len <= src_buffer.len() {
+            return Ok(());
+        }
+
+        let future = match &state.pending {
+            Some(pending) => future.join(pending.future.clone()).boxed_send_sync(),
+            None => future.boxed_send_sync(),
+        };
+
+        let dst_buffer = DeviceLocalBuffer::array(
+            self.device.clone(),
+            len,
+            self.usage,
+            src_buffer.queue_families(),
+        )?;
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        builder.copy_buffer(CopyBufferInfo::buffers(src_buffer, dst_buffer.clone()))?;
+
+        let future = builder
+            .build()?
+            .execute_after(future, queue)?
+            .boxed_send_sync()
+            .then_signal_fence_and_flush()?;
+
+        state.pending = Some(PendingGrowth {
+            buffer: dst_buffer,
+            future: Arc::new(future),
+        });
+
+        Ok(())
+    }
+
+    /// Replaces the current buffer with the larger one if the growth has finished.
+    ///
+    /// Returns whether the buffer was replaced, in which case descriptor sets and other objects
+    /// that refer to the old buffer must be recreated. This never blocks.
+    pub fn update(&self) -> Result<bool, OomError> {
+        let mut state = self.state.lock();
+
+        match &state.pending {
+            Some(pending) if pending.future.is_signaled()? => (),
+            _ => return Ok(false),
+        }
+
+        let pending = state.pending.take().unwrap();
+        state.current = pending.buffer;
+
+        Ok(true)
+    }
+
+    /// Blocks the current thread until the growth has finished, and replaces the current buffer
+    /// with the larger one.
+    ///
+    /// Returns whether the buffer was replaced.
+    pub fn wait(&self) -> Result<bool, FlushError> {
+        let mut state = self.state.lock();
+
+        let pending = match state.pending.take() {
+            Some(pending) => pending,
+            None => return Ok(false),
+        };
+
+        if let Err(err) = pending.future.wait(None) {
+            state.pending = Some(pending);
+            return Err(err);
+        }
+
+        state.current = pending.buffer;
+
+        Ok(true)
+    }
+}
+
+unsafe impl<T> DeviceOwned for GrowableBuffer<T>
+where
+    [T]: BufferContents,
+{
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl<T> fmt::Debug for GrowableBuffer<T>
+where
+    [T]: BufferContents,
+{
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let state = self.state.lock();
+
+        fmt.debug_struct("GrowableBuffer")
+            .field("usage", &self.usage)
+            .field("len", &state.current.len())
+            .field(
+                "pending_len",
+                &state.pending.as_ref().map(|pending| pending.buffer.len()),
+            )
+            .finish()
+    }
+}
+
+/// Error that can happen when growing a [`GrowableBuffer`].
+#[derive(Clone, Debug)]
+pub enum GrowableBufferError {
+    AllocError(DeviceMemoryAllocationError),
+    BeginError(CommandBufferBeginError),
+    BuildError(BuildError),
+    CopyError(CopyError),
+    ExecError(CommandBufferExecError),
+    FlushError(FlushError),
+}
+
+impl error::Error for GrowableBufferError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::AllocError(err) => Some(err),
+            Self::BeginError(err) => Some(err),
+            Self::BuildError(err) => Some(err),
+            Self::CopyError(err) => Some(err),
+            Self::ExecError(err) => Some(err),
+            Self::FlushError(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for GrowableBufferError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::AllocError(_) => write!(fmt, "allocating the larger buffer failed"),
+            Self::BeginError(_) => write!(fmt, "beginning the command buffer failed"),
+            Self::BuildError(_) => write!(fmt, "building the command buffer failed"),
+            Self::CopyError(_) => write!(fmt, "recording the copy failed"),
+            Self::ExecError(_) => write!(fmt, "executing the command buffer failed"),
+            Self::FlushError(_) => write!(fmt, "submitting the command buffer failed"),
+        }
+    }
+}
+
+impl From<DeviceMemoryAllocationError> for GrowableBufferError {
+    #[inline]
+    fn from(err: DeviceMemoryAllocationError) -> Self {
+        Self::AllocError(err)
+    }
+}
+
+impl From<CommandBufferBeginError> for GrowableBufferError {
+    #[inline]
+    fn from(err: CommandBufferBeginError) -> Self {
+        Self::BeginError(err)
+    }
+}
+
+impl From<BuildError> for GrowableBufferError {
+    #[inline]
+    fn from(err: BuildError) -> Self {
+        Self::BuildError(err)
+    }
+}
+
+impl From<CopyError> for GrowableBufferError {
+    #[inline]
+    fn from(err: CopyError) -> Self {
+        Self::CopyError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for GrowableBufferError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> Self {
+        Self::ExecError(err)
+    }
+}
+
+impl From<FlushError> for GrowableBufferError {
+    #[inline]
+    fn from(err: FlushError) -> Self {
+        Self::FlushError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GrowableBuffer;
+    use crate::{
+        buffer::{BufferUsage, CpuAccessibleBuffer, TypedBufferAccess},
+        command_buffer::{
+            readback, AutoCommandBufferBuilder, CommandBufferUsage, CopyBufferInfo,
+            PrimaryCommandBuffer,
+        },
+        sync::GpuFuture,
+    };
+
+    #[test]
+    fn grow_keeps_contents() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let growable = GrowableBuffer::<u32>::new(
+            device.clone(),
+            4,
+            BufferUsage::storage_buffer(),
+            [queue.family()],
+        )
+        .unwrap();
+
+        let source = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_src(),
+            false,
+            0..4u32,
+        )
+        .unwrap();
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .copy_buffer(CopyBufferInfo::buffers(source, growable.buffer()))
+            .unwrap();
+        let future = builder.build().unwrap().execute(queue.clone()).unwrap();
+
+        growable.grow_after(future, queue.clone(), 8).unwrap();
+        assert!(growable.is_growing());
+        assert_eq!(growable.buffer().len(), 4);
+        assert_eq!(growable.target_len(), 8);
+
+        assert!(growable.wait().unwrap());
+        assert!(!growable.is_growing());
+        assert_eq!(growable.buffer().len(), 8);
+
+        let host_buffer = readback::read_buffer(queue, growable.buffer())
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert_eq!(host_buffer.read().unwrap()[..4], [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn grow_smaller_does_nothing() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let growable =
+            GrowableBuffer::<u32>::new(device, 16, BufferUsage::storage_buffer(), [queue.family()])
+                .unwrap();
+
+        growable.grow(queue, 8).unwrap();
+        assert!(!growable.is_growing());
+        assert!(!growable.update().unwrap());
+        assert_eq!(growable.buffer().len(), 16);
+    }
+}
@@ -37,7 +37,7 @@ use crate::{
         DedicatedAllocation, DeviceMemoryAllocationError, MemoryPool,
     },
     sync::{NowFuture, Sharing},
-    DeviceSize, OomError,
+    DeviceSize, OomError, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{
@@ -450,6 +450,18 @@ where
     }
 }
 
+unsafe impl<T, A> VulkanObject for ImmutableBuffer<T, A>
+where
+    T: BufferContents + ?Sized,
+{
+    type Object = ash::vk::Buffer;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Buffer {
+        self.inner.internal_object()
+    }
+}
+
 impl<T, A> PartialEq for ImmutableBuffer<T, A>
 where
     T: BufferContents + ?Sized,
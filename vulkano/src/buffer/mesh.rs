@@ -0,0 +1,549 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Uploading meshes to the device.
+//!
+//! Asset loaders usually produce a mesh as a slice of vertices and a slice of indices. The
+//! [`upload_mesh`] function copies both to device-local [`ImmutableBuffer`]s with a single command
+//! buffer, and can optionally split the mesh into [`Meshlet`]s for use with mesh shaders.
+//!
+//! ```
+//! use vulkano::buffer::mesh::{upload_mesh, MeshUploadInfo};
+//! use vulkano::sync::GpuFuture;
+//! # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+//!
+//! let vertices = [[0.0f32, 0.0], [1.0, 0.0], [0.0, 1.0]];
+//! let indices = [0u16, 1, 2];
+//!
+//! let (mesh, future) =
+//!     upload_mesh(queue.clone(), &vertices, &indices, MeshUploadInfo::default()).unwrap();
+//!
+//! // The buffers of `mesh` can be used once `future` has been submitted.
+//! future.then_signal_fence_and_flush().unwrap().wait(None).unwrap();
+//! ```
+
+use super::{
+    immutable::ImmutableBufferCreationError, BufferContents, BufferUsage, CpuAccessibleBuffer,
+    ImmutableBuffer,
+};
+use crate::{
+    command_buffer::{
+        AutoCommandBufferBuilder, CommandBufferExecFuture, CommandBufferUsage, CopyBufferInfo,
+        PrimaryAutoCommandBuffer, PrimaryCommandBuffer,
+    },
+    device::Queue,
+    pipeline::graphics::input_assembly::{Index, IndexBuffer},
+    sync::NowFuture,
+    DeviceSize,
+};
+use bytemuck::{Pod, Zeroable};
+use std::{collections::HashMap, error, fmt, sync::Arc};
+
+/// The future that represents the upload of a mesh.
+pub type MeshUploadFuture = CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>;
+
+/// Parameters to upload a mesh.
+#[derive(Clone, Debug)]
+pub struct MeshUploadInfo {
+    /// The usage of the vertex buffer. `transfer_dst` is always added.
+    ///
+    /// The default value is [`BufferUsage::vertex_buffer()`].
+    pub vertex_usage: BufferUsage,
+
+    /// The usage of the index buffer. `transfer_dst` is always added.
+    ///
+    /// The default value is [`BufferUsage::index_buffer()`].
+    pub index_usage: BufferUsage,
+
+    /// If `Some`, the mesh is also split into meshlets with the given limits.
+    ///
+    /// The default value is `None`.
+    pub meshlets: Option<MeshletLimits>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for MeshUploadInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            vertex_usage: BufferUsage::vertex_buffer(),
+            index_usage: BufferUsage::index_buffer(),
+            meshlets: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The maximum size of the meshlets that a mesh is split into.
+///
+/// These should match the output limits that the mesh shader declares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MeshletLimits {
+    /// The maximum number of unique vertices in a meshlet. Must be between 3 and 256.
+    pub max_vertices: u32,
+
+    /// The maximum number of triangles in a meshlet. Must be at least 1.
+    pub max_primitives: u32,
+}
+
+impl Default for MeshletLimits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_vertices: 64,
+            max_primitives: 126,
+        }
+    }
+}
+
+/// A range of a mesh that is processed by one mesh shader workgroup.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Zeroable, Pod)]
+pub struct Meshlet {
+    /// The offset of the first element of this meshlet in [`Meshlets::vertex_indices`].
+    pub vertex_offset: u32,
+
+    /// The number of unique vertices of this meshlet.
+    pub vertex_count: u32,
+
+    /// The offset of the first element of this meshlet in [`Meshlets::primitive_indices`].
+    pub primitive_offset: u32,
+
+    /// The number of triangles of this meshlet. The meshlet uses `primitive_count * 3` elements of
+    /// [`Meshlets::primitive_indices`].
+    pub primitive_count: u32,
+}
+
+/// The meshlets of an uploaded mesh.
+#[derive(Clone, Debug)]
+pub struct Meshlets {
+    /// The meshlets that the mesh was split into. Usable as a storage buffer.
+    pub meshlets: Arc<ImmutableBuffer<[Meshlet]>>,
+
+    /// For each meshlet, the indices into the vertex buffer of its unique vertices. Usable as a
+    /// storage buffer.
+    pub vertex_indices: Arc<ImmutableBuffer<[u32]>>,
+
+    /// For each triangle of each meshlet, three indices into the meshlet's range of
+    /// `vertex_indices`. Usable as a storage buffer.
+    pub primitive_indices: Arc<ImmutableBuffer<[u8]>>,
+}
+
+/// A mesh whose vertices and indices are stored in device buffers.
+#[derive(Debug)]
+pub struct Mesh<V, I>
+where
+    [V]: BufferContents,
+    [I]: BufferContents,
+{
+    vertex_buffer: Arc<ImmutableBuffer<[V]>>,
+    index_buffer: Arc<ImmutableBuffer<[I]>>,
+    meshlets: Option<Meshlets>,
+}
+
+impl<V, I> Mesh<V, I>
+where
+    [V]: BufferContents,
+    [I]: BufferContents,
+    I: Index,
+{
+    /// Returns the vertex buffer of the mesh.
+    #[inline]
+    pub fn vertex_buffer(&self) -> &Arc<ImmutableBuffer<[V]>> {
+        &self.vertex_buffer
+    }
+
+    /// Returns the index buffer of the mesh.
+    #[inline]
+    pub fn index_buffer(&self) -> &Arc<ImmutableBuffer<[I]>> {
+        &self.index_buffer
+    }
+
+    /// Returns the index buffer of the mesh, ready to be bound to a command buffer.
+    #[inline]
+    pub fn typed_index_buffer(&self) -> IndexBuffer {
+        IndexBuffer::new(self.index_buffer.clone())
+    }
+
+    /// Returns the meshlets of the mesh, if they were requested when uploading it.
+    #[inline]
+    pub fn meshlets(&self) -> Option<&Meshlets> {
+        self.meshlets.as_ref()
+    }
+}
+
+/// Uploads a mesh to the device.
+///
+/// This creates device-local buffers, copies `vertices` and `indices` into them using `queue`,
+/// and, if requested in `upload_info`, splits the mesh into meshlets and uploads them as well.
+/// All the copies are recorded in a single command buffer.
+///
+/// This function returns two objects: the mesh, and a future representing the upload operation.
+/// In order to be allowed to use the buffers of the mesh, you must either submit your operation
+/// after this future, or execute this future and wait for it to be finished before submitting
+/// your own operation.
+pub fn upload_mesh<V, I>(
+    queue: Arc<Queue>,
+    vertices: &[V],
+    indices: &[I],
+    upload_info: MeshUploadInfo,
+) -> Result<(Mesh<V, I>, MeshUploadFuture), MeshUploadError>
+where
+    V: Pod + Send + Sync,
+    I: Index + Into<u32> + Pod + Send + Sync,
+{
+    let MeshUploadInfo {
+        vertex_usage,
+        index_usage,
+        meshlets: meshlet_limits,
+        _ne: _,
+    } = upload_info;
+
+    if vertices.is_empty() || indices.is_empty() {
+        return Err(MeshUploadError::EmptyMesh);
+    }
+
+    let vertex_count = vertices.len() as u32;
+
+    if let Some(index) = indices
+        .iter()
+        .map(|&index| index.into())
+        .find(|&index| index >= vertex_count)
+    {
+        return Err(MeshUploadError::IndexOutOfRange {
+            index,
+            vertex_count,
+        });
+    }
+
+    let meshlets = match meshlet_limits {
+        Some(limits) => Some(build_meshlets(indices, limits)?),
+        None => None,
+    };
+
+    let mut builder = AutoCommandBufferBuilder::primary(
+        queue.device().clone(),
+        queue.family(),
+        CommandBufferUsage::OneTimeSubmit,
+    )
+    .map_err(ImmutableBufferCreationError::from)?;
+
+    let vertex_buffer = stage(&mut builder, &queue, vertices, vertex_usage)?;
+    let index_buffer = stage(&mut builder, &queue, indices, index_usage)?;
+    let meshlets = match meshlets {
+        Some(MeshletData {
+            meshlets,
+            vertex_indices,
+            primitive_indices,
+        }) => Some(Meshlets {
+            meshlets: stage(
+                &mut builder,
+                &queue,
+                &meshlets,
+                BufferUsage::storage_buffer(),
+            )?,
+            vertex_indices: stage(
+                &mut builder,
+                &queue,
+                &vertex_indices,
+                BufferUsage::storage_buffer(),
+            )?,
+            primitive_indices: stage(
+                &mut builder,
+                &queue,
+                &primitive_indices,
+                BufferUsage::storage_buffer(),
+            )?,
+        }),
+        None => None,
+    };
+
+    let command_buffer = builder.build().unwrap(); // TODO: return OomError
+
+    let future = match command_buffer.execute(queue) {
+        Ok(f) => f,
+        Err(_) => unreachable!(),
+    };
+
+    Ok((
+        Mesh {
+            vertex_buffer,
+            index_buffer,
+            meshlets,
+        },
+        future,
+    ))
+}
+
+// Creates a device-local buffer and records a copy of `data` into it.
+fn stage<T>(
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    queue: &Queue,
+    data: &[T],
+    usage: BufferUsage,
+) -> Result<Arc<ImmutableBuffer<[T]>>, ImmutableBufferCreationError>
+where
+    T: Pod + Send + Sync,
+{
+    let source = CpuAccessibleBuffer::from_iter(
+        queue.device().clone(),
+        BufferUsage::transfer_src(),
+        false,
+        data.iter().copied(),
+    )?;
+
+    let (buffer, init) = unsafe {
+        ImmutableBuffer::uninitialized_array(
+            queue.device().clone(),
+            data.len() as DeviceSize,
+            BufferUsage {
+                transfer_dst: true,
+                ..usage
+            },
+        )?
+    };
+
+    builder
+        .copy_buffer(CopyBufferInfo::buffers(source, init))
+        .unwrap(); // TODO: return error?
+
+    Ok(buffer)
+}
+
+// Splits a triangle list into meshlets, greedily adding triangles in order until either limit
+// would be exceeded.
+fn build_meshlets<I>(indices: &[I], limits: MeshletLimits) -> Result<MeshletData, MeshUploadError>
+where
+    I: Copy + Into<u32>,
+{
+    let MeshletLimits {
+        max_vertices,
+        max_primitives,
+    } = limits;
+
+    if !(3..=256).contains(&max_vertices) || max_primitives == 0 {
+        return Err(MeshUploadError::MeshletLimitsInvalid { limits });
+    }
+
+    if indices.len() % 3 != 0 {
+        return Err(MeshUploadError::IndexCountNotMultipleOfThree {
+            index_count: indices.len() as u32,
+        });
+    }
+
+    let mut meshlets = Vec::new();
+    let mut vertex_indices = Vec::new();
+    let mut primitive_indices = Vec::new();
+    let mut current = Meshlet::default();
+    let mut local_indices: HashMap<u32, u8> = HashMap::default();
+
+    for triangle in indices.chunks_exact(3) {
+        let triangle: [u32; 3] = [triangle[0].into(), triangle[1].into(), triangle[2].into()];
+        let new_vertices = triangle
+            .iter()
+            .enumerate()
+            .filter(|&(i, index)| {
+                !local_indices.contains_key(index) && !triangle[..i].contains(index)
+            })
+            .count() as u32;
+
+        if current.vertex_count + new_vertices > max_vertices
+            || current.primitive_count == max_primitives
+        {
+            meshlets.push(current);
+            current = Meshlet {
+                vertex_offset: vertex_indices.len() as u32,
+                vertex_count: 0,
+                primitive_offset: primitive_indices.len() as u32,
+                primitive_count: 0,
+            };
+            local_indices.clear();
+        }
+
+        for index in triangle {
+            let local_index = *local_indices.entry(index).or_insert_with(|| {
+                vertex_indices.push(index);
+                current.vertex_count += 1;
+                (current.vertex_count - 1) as u8
+            });
+            primitive_indices.push(local_index);
+        }
+
+        current.primitive_count += 1;
+    }
+
+    meshlets.push(current);
+
+    Ok(MeshletData {
+        meshlets,
+        vertex_indices,
+        primitive_indices,
+    })
+}
+
+// The contents of the buffers of `Meshlets`.
+struct MeshletData {
+    meshlets: Vec<Meshlet>,
+    vertex_indices: Vec<u32>,
+    primitive_indices: Vec<u8>,
+}
+
+/// Error that can happen when uploading a mesh.
+#[derive(Clone, Debug)]
+pub enum MeshUploadError {
+    /// Creating one of the buffers failed.
+    BufferCreationError(ImmutableBufferCreationError),
+
+    /// The vertex or index slice was empty.
+    EmptyMesh,
+
+    /// Meshlets were requested, but the number of indices is not a multiple of 3.
+    IndexCountNotMultipleOfThree { index_count: u32 },
+
+    /// An index refers to a vertex that does not exist.
+    IndexOutOfRange { index: u32, vertex_count: u32 },
+
+    /// The meshlet limits are outside the allowed range.
+    MeshletLimitsInvalid { limits: MeshletLimits },
+}
+
+impl error::Error for MeshUploadError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::BufferCreationError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MeshUploadError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferCreationError(_) => write!(f, "creating one of the buffers failed"),
+            Self::EmptyMesh => write!(f, "the vertex or index slice was empty"),
+            Self::IndexCountNotMultipleOfThree { index_count } => write!(
+                f,
+                "meshlets were requested, but the number of indices ({}) is not a multiple of 3",
+                index_count,
+            ),
+            Self::IndexOutOfRange {
+                index,
+                vertex_count,
+            } => write!(
+                f,
+                "the index {} refers to a vertex that does not exist, as there are only {} vertices",
+                index, vertex_count,
+            ),
+            Self::MeshletLimitsInvalid { limits } => write!(
+                f,
+                "the meshlet limits ({} vertices, {} primitives) are outside the allowed range",
+                limits.max_vertices, limits.max_primitives,
+            ),
+        }
+    }
+}
+
+impl From<ImmutableBufferCreationError> for MeshUploadError {
+    #[inline]
+    fn from(err: ImmutableBufferCreationError) -> Self {
+        Self::BufferCreationError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_meshlets, upload_mesh, MeshUploadError, MeshUploadInfo, MeshletData, MeshletLimits,
+    };
+    use crate::{buffer::TypedBufferAccess, sync::GpuFuture};
+
+    #[test]
+    fn meshlets_respect_limits() {
+        // A strip of 6 quads, each made of 2 triangles.
+        let indices: Vec<u16> = (0..6u16)
+            .flat_map(|i| {
+                let v = i * 2;
+                [v, v + 1, v + 2, v + 2, v + 1, v + 3]
+            })
+            .collect();
+        let limits = MeshletLimits {
+            max_vertices: 6,
+            max_primitives: 4,
+        };
+
+        let MeshletData {
+            meshlets,
+            vertex_indices,
+            primitive_indices,
+        } = build_meshlets(&indices, limits).unwrap();
+
+        assert_eq!(meshlets.iter().map(|m| m.primitive_count).sum::<u32>(), 12);
+        assert_eq!(primitive_indices.len(), 36);
+
+        for meshlet in &meshlets {
+            assert!(meshlet.vertex_count <= limits.max_vertices);
+            assert!(meshlet.primitive_count <= limits.max_primitives);
+
+            // Every triangle must map back to the original indices.
+            let start = meshlet.primitive_offset as usize;
+            let end = start + meshlet.primitive_count as usize * 3;
+
+            for (i, &local) in primitive_indices[start..end].iter().enumerate() {
+                assert!((local as u32) < meshlet.vertex_count);
+                assert_eq!(
+                    vertex_indices[meshlet.vertex_offset as usize + local as usize],
+                    indices[start + i] as u32,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn index_out_of_range() {
+        let (_, queue) = gfx_dev_and_queue!();
+
+        match upload_mesh(queue, &[[0.0f32; 2]; 3], &[0u32, 1, 3], Default::default()) {
+            Err(MeshUploadError::IndexOutOfRange {
+                index: 3,
+                vertex_count: 3,
+            }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn upload_with_meshlets() {
+        let (_, queue) = gfx_dev_and_queue!();
+
+        let (mesh, future) = upload_mesh(
+            queue,
+            &[[0.0f32; 2]; 4],
+            &[0u16, 1, 2, 2, 1, 3],
+            MeshUploadInfo {
+                meshlets: Some(MeshletLimits::default()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        future
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        assert_eq!(mesh.vertex_buffer().len(), 4);
+        assert_eq!(mesh.index_buffer().len(), 6);
+        assert_eq!(mesh.meshlets().unwrap().meshlets.len(), 1);
+        assert_eq!(mesh.meshlets().unwrap().primitive_indices.len(), 6);
+    }
+}
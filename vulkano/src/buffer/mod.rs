@@ -85,14 +85,17 @@
 
 pub use self::{
     cpu_access::CpuAccessibleBuffer,
-    cpu_pool::CpuBufferPool,
+    cpu_pool::{CpuBufferPool, CpuBufferPoolStatistics},
     device_local::DeviceLocalBuffer,
+    growable::GrowableBuffer,
     immutable::ImmutableBuffer,
     slice::BufferSlice,
+    sparse::SparseBuffer,
     sys::{BufferCreationError, SparseLevel},
     traits::{
         BufferAccess, BufferAccessObject, BufferDeviceAddressError, BufferInner, TypedBufferAccess,
     },
+    transient::TransientBuffer,
     usage::BufferUsage,
 };
 use crate::{
@@ -108,8 +111,12 @@ use std::mem::size_of;
 pub mod cpu_access;
 pub mod cpu_pool;
 pub mod device_local;
+pub mod growable;
 pub mod immutable;
+pub mod mesh;
+pub mod sparse;
 pub mod sys;
+pub mod transient;
 pub mod view;
 
 mod slice;
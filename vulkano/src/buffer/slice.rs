@@ -10,6 +10,7 @@
 use super::{BufferAccess, BufferAccessObject, BufferContents, BufferInner, TypedBufferAccess};
 use crate::{
     device::{Device, DeviceOwned},
+    memory::transient::MemoryAlias,
     DeviceSize,
 };
 use std::{
@@ -225,6 +226,11 @@ where
     fn size(&self) -> DeviceSize {
         self.size
     }
+
+    #[inline]
+    fn memory_alias(&self) -> Option<MemoryAlias> {
+        self.resource.memory_alias()
+    }
 }
 
 impl<T, B> BufferAccessObject for Arc<BufferSlice<T, B>>
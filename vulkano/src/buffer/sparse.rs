@@ -0,0 +1,391 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Buffer whose memory is bound sparsely, on demand.
+//!
+//! A sparse buffer is created without any memory. Memory is bound to ranges of the buffer, and
+//! unbound again, by submitting sparse binding operations to a queue that supports them. This
+//! makes it possible to reserve a very large range of addresses, and to only back the parts that
+//! are actually used with memory.
+
+use super::{
+    sys::{UnsafeBuffer, UnsafeBufferCreateInfo},
+    BufferAccess, BufferAccessObject, BufferContents, BufferCreationError, BufferInner,
+    BufferUsage, SparseLevel, TypedBufferAccess,
+};
+use crate::{
+    device::{physical::QueueFamily, Device, DeviceOwned},
+    memory::MemoryRequirements,
+    sync::Sharing,
+    DeviceSize, VulkanObject,
+};
+use smallvec::SmallVec;
+use std::{
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::size_of,
+    sync::Arc,
+};
+
+/// Buffer whose memory is bound sparsely, on demand.
+///
+/// Memory is bound with [`Queue::bind_sparse`](crate::device::Queue::bind_sparse) or
+/// [`GpuFuture::then_bind_sparse`](crate::sync::GpuFuture::then_bind_sparse). The memory must
+/// satisfy the requirements returned by [`memory_requirements`](SparseBuffer::memory_requirements):
+/// its `alignment` is the size of a sparse block, and binds must start and end on a block
+/// boundary. The buffer keeps the memory that is bound to it alive.
+///
+/// Unless the buffer was created with `sparse_residency`, all of it must be bound to memory
+/// before it is used by the GPU.
+///
+/// # Example
+///
+/// ```
+/// use vulkano::buffer::{BufferUsage, SparseBuffer, SparseLevel};
+/// use vulkano::memory::{DeviceMemory, MemoryAllocateInfo};
+/// use vulkano::sync::{BindSparseInfo, GpuFuture, SparseBufferBindInfo, SparseBufferMemoryBind};
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+///
+/// // Reserve 4 GiB of addresses.
+/// let buffer = SparseBuffer::<[u8]>::array(
+///     device.clone(),
+///     4 << 30,
+///     BufferUsage::storage_buffer(),
+///     SparseLevel {
+///         sparse_residency: true,
+///         ..SparseLevel::none()
+///     },
+///     [queue.family()],
+/// )
+/// .unwrap();
+///
+/// // Back the first block with memory.
+/// let requirements = buffer.memory_requirements();
+/// let memory = DeviceMemory::allocate(
+///     device.clone(),
+///     MemoryAllocateInfo {
+///         allocation_size: requirements.alignment,
+///         memory_type_index: requirements.memory_type_bits.trailing_zeros(),
+///         ..Default::default()
+///     },
+/// )
+/// .unwrap();
+///
+/// queue
+///     .bind_sparse(BindSparseInfo {
+///         buffer_binds: vec![SparseBufferBindInfo {
+///             buffer: buffer.clone(),
+///             binds: vec![SparseBufferMemoryBind {
+///                 offset: 0,
+///                 size: requirements.alignment,
+///                 memory: Some((memory.into(), 0)),
+///                 ..Default::default()
+///             }],
+///         }],
+///         ..Default::default()
+///     })
+///     .unwrap()
+///     .then_signal_fence_and_flush()
+///     .unwrap()
+///     .wait(None)
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SparseBuffer<T>
+where
+    T: BufferContents + ?Sized,
+{
+    // Inner content.
+    inner: Arc<UnsafeBuffer>,
+
+    // Queue families allowed to access this buffer.
+    queue_families: SmallVec<[u32; 4]>,
+
+    // Necessary to make it compile.
+    marker: PhantomData<Box<T>>,
+}
+
+impl<T> SparseBuffer<T>
+where
+    T: BufferContents,
+{
+    /// Builds a new buffer. Only allowed for sized data.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `T` has zero size.
+    #[inline]
+    pub fn new<'a, I>(
+        device: Arc<Device>,
+        usage: BufferUsage,
+        sparse_level: SparseLevel,
+        queue_families: I,
+    ) -> Result<Arc<SparseBuffer<T>>, BufferCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        unsafe {
+            SparseBuffer::raw(
+                device,
+                size_of::<T>() as DeviceSize,
+                usage,
+                sparse_level,
+                queue_families,
+            )
+        }
+    }
+}
+
+impl<T> SparseBuffer<[T]>
+where
+    [T]: BufferContents,
+{
+    /// Builds a new buffer. Can be used for arrays.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `T` has zero size.
+    /// - Panics if `len` is zero.
+    #[inline]
+    pub fn array<'a, I>(
+        device: Arc<Device>,
+        len: DeviceSize,
+        usage: BufferUsage,
+        sparse_level: SparseLevel,
+        queue_families: I,
+    ) -> Result<Arc<SparseBuffer<[T]>>, BufferCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        unsafe {
+            SparseBuffer::raw(
+                device,
+                len * size_of::<T>() as DeviceSize,
+                usage,
+                sparse_level,
+                queue_families,
+            )
+        }
+    }
+}
+
+impl<T> SparseBuffer<T>
+where
+    T: BufferContents + ?Sized,
+{
+    /// Builds a new buffer without checking the size.
+    ///
+    /// # Safety
+    ///
+    /// - You must ensure that the size that you pass is correct for `T`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `size` is zero.
+    pub unsafe fn raw<'a, I>(
+        device: Arc<Device>,
+        size: DeviceSize,
+        usage: BufferUsage,
+        sparse_level: SparseLevel,
+        queue_families: I,
+    ) -> Result<Arc<SparseBuffer<T>>, BufferCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        let queue_families = queue_families
+            .into_iter()
+            .map(|f| f.id())
+            .collect::<SmallVec<[u32; 4]>>();
+
+        let inner = UnsafeBuffer::new(
+            device,
+            UnsafeBufferCreateInfo {
+                sharing: if queue_families.len() >= 2 {
+                    Sharing::Concurrent(queue_families.clone())
+                } else {
+                    Sharing::Exclusive
+                },
+                size,
+                sparse: Some(sparse_level),
+                usage,
+                ..Default::default()
+            },
+        )?;
+
+        Ok(Arc::new(SparseBuffer {
+            inner,
+            queue_families,
+            marker: PhantomData,
+        }))
+    }
+
+    /// Returns the requirements for the memory that is bound to the buffer.
+    ///
+    /// `alignment` is the size of a sparse block, and `size` is the total amount of memory needed
+    /// to back the whole buffer.
+    #[inline]
+    pub fn memory_requirements(&self) -> MemoryRequirements {
+        self.inner.memory_requirements()
+    }
+
+    /// Returns the sparse binding parameters that the buffer was created with.
+    #[inline]
+    pub fn sparse_level(&self) -> SparseLevel {
+        self.inner.sparse().unwrap()
+    }
+
+    /// Returns true if the given range of the buffer is entirely bound to memory, as far as the
+    /// sparse binding operations submitted so far are concerned.
+    #[inline]
+    pub fn is_bound(&self, offset: DeviceSize, size: DeviceSize) -> bool {
+        size == 0
+            || self
+                .inner
+                .sparse_memory()
+                .contains_all(&(offset..offset + size))
+    }
+
+    /// Returns the queue families this buffer can be used on.
+    // TODO: use a custom iterator
+    #[inline]
+    pub fn queue_families(&self) -> Vec<QueueFamily> {
+        self.queue_families
+            .iter()
+            .map(|&num| {
+                self.device()
+                    .physical_device()
+                    .queue_family_by_id(num)
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+unsafe impl<T> DeviceOwned for SparseBuffer<T>
+where
+    T: BufferContents + ?Sized,
+{
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.inner.device()
+    }
+}
+
+unsafe impl<T> VulkanObject for SparseBuffer<T>
+where
+    T: BufferContents + ?Sized,
+{
+    type Object = ash::vk::Buffer;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Buffer {
+        self.inner.internal_object()
+    }
+}
+
+unsafe impl<T> BufferAccess for SparseBuffer<T>
+where
+    T: BufferContents + ?Sized,
+{
+    #[inline]
+    fn inner(&self) -> BufferInner {
+        BufferInner {
+            buffer: &self.inner,
+            offset: 0,
+        }
+    }
+
+    #[inline]
+    fn size(&self) -> DeviceSize {
+        self.inner.size()
+    }
+}
+
+impl<T> BufferAccessObject for Arc<SparseBuffer<T>>
+where
+    T: BufferContents + ?Sized,
+{
+    #[inline]
+    fn as_buffer_access_object(&self) -> Arc<dyn BufferAccess> {
+        self.clone()
+    }
+}
+
+unsafe impl<T> TypedBufferAccess for SparseBuffer<T>
+where
+    T: BufferContents + ?Sized,
+{
+    type Content = T;
+}
+
+impl<T> PartialEq for SparseBuffer<T>
+where
+    T: BufferContents + ?Sized,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner() == other.inner() && self.size() == other.size()
+    }
+}
+
+impl<T> Eq for SparseBuffer<T> where T: BufferContents + ?Sized {}
+
+impl<T> Hash for SparseBuffer<T>
+where
+    T: BufferContents + ?Sized,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner().hash(state);
+        self.size().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseBuffer;
+    use crate::buffer::{BufferCreationError, BufferUsage, SparseLevel};
+
+    #[test]
+    fn feature_not_enabled() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        match SparseBuffer::<[u8]>::array(
+            device,
+            1 << 20,
+            BufferUsage::storage_buffer(),
+            SparseLevel::none(),
+            [queue.family()],
+        ) {
+            Err(BufferCreationError::FeatureNotEnabled {
+                feature: "sparse_binding",
+                ..
+            }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn not_bound_after_creation() {
+        let (device, queue) = gfx_dev_and_queue!(sparse_binding);
+
+        let buffer = SparseBuffer::<[u8]>::array(
+            device,
+            1 << 20,
+            BufferUsage::storage_buffer(),
+            SparseLevel::none(),
+            [queue.family()],
+        )
+        .unwrap();
+
+        assert!(!buffer.is_bound(0, 1 << 20));
+        assert!(buffer.is_bound(0, 0));
+    }
+}
@@ -32,14 +32,18 @@ use crate::range_map::RangeMap;
 use crate::{
     check_errors,
     device::{Device, DeviceOwned},
-    memory::{DeviceMemory, DeviceMemoryAllocationError, MemoryRequirements},
+    memory::{
+        DeviceMemory, DeviceMemoryAllocationError, ExternalMemoryHandleTypes, MemoryRequirements,
+    },
     sync::{AccessError, CurrentAccess, Sharing},
+    video::{VideoProfile, VideoProfilesVk},
     DeviceSize, Error, OomError, Version, VulkanObject,
 };
 use ash::vk::Handle;
 use parking_lot::{Mutex, MutexGuard};
 use smallvec::SmallVec;
 use std::{
+    borrow::Cow,
     error, fmt,
     hash::{Hash, Hasher},
     mem::MaybeUninit,
@@ -55,9 +59,13 @@ pub struct UnsafeBuffer {
     device: Arc<Device>,
 
     size: DeviceSize,
+    sparse: Option<SparseLevel>,
     usage: BufferUsage,
+    external_memory_handle_types: ExternalMemoryHandleTypes,
 
     state: Mutex<BufferState>,
+    // The memory currently bound to each range of a sparse buffer.
+    sparse_memory: Mutex<RangeMap<DeviceSize, Arc<DeviceMemory>>>,
 }
 
 impl UnsafeBuffer {
@@ -78,6 +86,9 @@ impl UnsafeBuffer {
             size,
             sparse,
             usage,
+            external_memory_handle_types,
+            video_profiles,
+            debug_name,
             _ne: _,
         } = create_info;
 
@@ -139,6 +150,31 @@ impl UnsafeBuffer {
             }
         };
 
+        if !external_memory_handle_types.is_empty() {
+            if !(device.api_version() >= Version::V1_1
+                || device.enabled_extensions().khr_external_memory)
+            {
+                return Err(BufferCreationError::ExtensionNotEnabled {
+                    extension: "khr_external_memory",
+                    reason: "one or more fields of external_memory_handle_types were set",
+                });
+            }
+        }
+
+        if usage.video_decode_src || usage.video_decode_dst {
+            if !device.enabled_extensions().khr_video_decode_queue {
+                return Err(BufferCreationError::ExtensionNotEnabled {
+                    extension: "khr_video_decode_queue",
+                    reason: "usage included `video_decode_src` or `video_decode_dst`",
+                });
+            }
+
+            // VUID-VkBufferCreateInfo-usage-04813
+            if video_profiles.is_empty() {
+                return Err(BufferCreationError::VideoProfilesEmpty);
+            }
+        }
+
         if let Some(max_buffer_size) = device.physical_device().properties().max_buffer_size {
             // VUID-VkBufferCreateInfo-size-06409
             if size > max_buffer_size {
@@ -150,13 +186,37 @@ impl UnsafeBuffer {
         }
 
         // Everything now ok. Creating the buffer.
-        let create_info = ash::vk::BufferCreateInfo::builder()
+        let mut create_info = ash::vk::BufferCreateInfo::builder()
             .flags(flags)
             .size(size)
             .usage(usage.into())
             .sharing_mode(sharing_mode)
             .queue_family_indices(queue_family_indices);
 
+        let mut external_memory_buffer_create_info = if !external_memory_handle_types.is_empty() {
+            Some(ash::vk::ExternalMemoryBufferCreateInfo {
+                handle_types: external_memory_handle_types.into(),
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+
+        if let Some(info) = external_memory_buffer_create_info.as_mut() {
+            create_info = create_info.push_next(info);
+        }
+
+        let video_profiles_vk = VideoProfilesVk::new(&video_profiles);
+        let mut video_profile_list_info = if !video_profiles.is_empty() {
+            Some(video_profiles_vk.profile_list_info())
+        } else {
+            None
+        };
+
+        if let Some(info) = video_profile_list_info.as_mut() {
+            create_info = create_info.push_next(info);
+        }
+
         let handle = unsafe {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -174,14 +234,64 @@ impl UnsafeBuffer {
             device,
 
             size,
+            sparse,
             usage,
+            external_memory_handle_types,
 
             state: Mutex::new(BufferState::new(size)),
+            sparse_memory: Mutex::new(RangeMap::new()),
         };
 
+        buffer
+            .device
+            .set_debug_name(&buffer, debug_name.as_deref())?;
+
         Ok(Arc::new(buffer))
     }
 
+    /// Creates a new `UnsafeBuffer` from a raw handle that was created outside of vulkano.
+    ///
+    /// `create_info` must describe how the buffer was created: vulkano relies on `size`, `usage`,
+    /// `external_memory_handle_types` and `sparse`, and ignores the other members. If `sparse`
+    /// is `Some` without `sparse_residency`, the whole buffer must be bound to memory with sparse
+    /// binding operations before it is used by the GPU. The buffer is destroyed when the returned
+    /// `UnsafeBuffer` is dropped.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid buffer handle, created from `device`.
+    /// - `create_info` must match the parameters that the buffer was created with.
+    /// - The caller must not destroy the buffer, and must not use it after the returned
+    ///   `UnsafeBuffer` is dropped.
+    /// - If memory is already bound to the buffer, it must be kept alive for as long as the
+    ///   buffer is used.
+    pub unsafe fn from_handle(
+        device: Arc<Device>,
+        handle: ash::vk::Buffer,
+        create_info: UnsafeBufferCreateInfo,
+    ) -> Arc<UnsafeBuffer> {
+        let UnsafeBufferCreateInfo {
+            size,
+            sparse,
+            usage,
+            external_memory_handle_types,
+            ..
+        } = create_info;
+
+        Arc::new(UnsafeBuffer {
+            handle,
+            device,
+
+            size,
+            sparse,
+            usage,
+            external_memory_handle_types,
+
+            state: Mutex::new(BufferState::new(size)),
+            sparse_memory: Mutex::new(RangeMap::new()),
+        })
+    }
+
     /// Returns the memory requirements for this buffer.
     pub fn memory_requirements(&self) -> MemoryRequirements {
         #[inline]
@@ -325,18 +435,50 @@ impl UnsafeBuffer {
         self.state.lock()
     }
 
+    pub(crate) fn sparse_memory(&self) -> MutexGuard<RangeMap<DeviceSize, Arc<DeviceMemory>>> {
+        self.sparse_memory.lock()
+    }
+
+    /// Checks that a sparse buffer that is not partially resident is entirely bound to memory, as
+    /// is required before it is used by the GPU. Always succeeds for other buffers.
+    pub(crate) fn check_sparse_bound(&self) -> Result<(), AccessError> {
+        match self.sparse {
+            Some(sparse_level) if !sparse_level.sparse_residency => {
+                if self.sparse_memory.lock().contains_all(&(0..self.size)) {
+                    Ok(())
+                } else {
+                    Err(AccessError::SparseNotBound)
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Returns the size of the buffer in bytes.
     #[inline]
     pub fn size(&self) -> DeviceSize {
         self.size
     }
 
+    /// Returns the sparse binding parameters that the buffer was created with, or `None` if the
+    /// buffer is not sparse.
+    #[inline]
+    pub fn sparse(&self) -> Option<SparseLevel> {
+        self.sparse
+    }
+
     /// Returns the usage the buffer was created with.
     #[inline]
     pub fn usage(&self) -> &BufferUsage {
         &self.usage
     }
 
+    /// Returns the external memory handle types that are supported with this buffer.
+    #[inline]
+    pub fn external_memory_handle_types(&self) -> ExternalMemoryHandleTypes {
+        self.external_memory_handle_types
+    }
+
     /// Returns a key unique to each `UnsafeBuffer`. Can be used for the `conflicts_key` method.
     #[inline]
     pub fn key(&self) -> u64 {
@@ -402,6 +544,10 @@ pub struct UnsafeBufferCreateInfo {
 
     /// Create a buffer with sparsely bound memory.
     ///
+    /// If `Some`, the [`sparse_binding`](crate::device::Features::sparse_binding) feature must be
+    /// enabled on the device. Memory is then not bound with `bind_memory`, but with a sparse
+    /// binding operation on a queue, see [`Queue::bind_sparse`](crate::device::Queue::bind_sparse).
+    ///
     /// The default value is `None`.
     pub sparse: Option<SparseLevel>,
 
@@ -410,6 +556,34 @@ pub struct UnsafeBufferCreateInfo {
     /// The default value is [`BufferUsage::none()`], which must be overridden.
     pub usage: BufferUsage,
 
+    /// The external memory handle types that are going to be used with the buffer.
+    ///
+    /// If any of the fields in this value are set, the device must either support API version 1.1
+    /// or the [`khr_external_memory`](crate::device::DeviceExtensions::khr_external_memory)
+    /// extension must be enabled.
+    ///
+    /// The default value is [`ExternalMemoryHandleTypes::none()`].
+    pub external_memory_handle_types: ExternalMemoryHandleTypes,
+
+    /// The video profiles that the buffer is going to be used with.
+    ///
+    /// If `usage` contains `video_decode_src` or `video_decode_dst`, this must not be empty, and
+    /// the buffer can then only be used by video sessions that were created with one of these
+    /// profiles.
+    ///
+    /// The default value is empty.
+    pub video_profiles: SmallVec<[VideoProfile; 1]>,
+
+    /// A name to give to the buffer, to identify it in debugging tools.
+    ///
+    /// If `Some`, and the [`ext_debug_utils`](crate::instance::InstanceExtensions::ext_debug_utils)
+    /// extension is enabled on the instance, the name is set with
+    /// [`Device::set_debug_utils_object_name`](crate::device::Device::set_debug_utils_object_name)
+    /// when the buffer is created. Otherwise it is ignored.
+    ///
+    /// The default value is `None`.
+    pub debug_name: Option<Cow<'static, str>>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -421,6 +595,9 @@ impl Default for UnsafeBufferCreateInfo {
             size: 0,
             sparse: None,
             usage: BufferUsage::none(),
+            external_memory_handle_types: ExternalMemoryHandleTypes::none(),
+            video_profiles: SmallVec::new(),
+            debug_name: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -447,6 +624,9 @@ pub enum BufferCreationError {
     /// The sharing mode was set to `Concurrent`, but one of the specified queue family ids was not
     /// valid.
     SharingInvalidQueueFamilyId { id: u32 },
+
+    /// The usage contained a video usage, but no video profiles were provided.
+    VideoProfilesEmpty,
 }
 
 impl error::Error for BufferCreationError {
@@ -479,6 +659,10 @@ impl fmt::Display for BufferCreationError {
             Self::SharingInvalidQueueFamilyId { id } => {
                 write!(fmt, "the sharing mode was set to `Concurrent`, but one of the specified queue family ids was not valid")
             }
+            Self::VideoProfilesEmpty => write!(
+                fmt,
+                "the usage contained a video usage, but no video profiles were provided",
+            ),
         }
     }
 }
@@ -505,7 +689,7 @@ impl From<Error> for BufferCreationError {
     }
 }
 
-/// The level of sparse binding that a buffer should be created with.
+/// The level of sparse binding that a buffer or image should be created with.
 #[derive(Clone, Copy, Debug)]
 pub struct SparseLevel {
     pub sparse_residency: bool,
@@ -565,6 +749,7 @@ impl BufferState {
                         cpu_reads: 0,
                         gpu_reads: 0,
                     },
+                    gpu_users: SmallVec::new(),
                 },
             )]
             .into_iter()
@@ -669,7 +854,11 @@ impl BufferState {
         Ok(())
     }
 
-    pub(crate) unsafe fn gpu_read_lock(&mut self, range: Range<DeviceSize>) {
+    pub(crate) unsafe fn gpu_read_lock(
+        &mut self,
+        range: Range<DeviceSize>,
+        command_buffer: ash::vk::CommandBuffer,
+    ) {
         self.ranges.split_at(&range.start);
         self.ranges.split_at(&range.end);
 
@@ -679,10 +868,16 @@ impl BufferState {
                 | CurrentAccess::Shared { gpu_reads, .. } => *gpu_reads += 1,
                 _ => unreachable!("Buffer is being written by the CPU"),
             }
+
+            state.gpu_users.push(command_buffer);
         }
     }
 
-    pub(crate) unsafe fn gpu_read_unlock(&mut self, range: Range<DeviceSize>) {
+    pub(crate) unsafe fn gpu_read_unlock(
+        &mut self,
+        range: Range<DeviceSize>,
+        command_buffer: ash::vk::CommandBuffer,
+    ) {
         self.ranges.split_at(&range.start);
         self.ranges.split_at(&range.end);
 
@@ -692,6 +887,8 @@ impl BufferState {
                 CurrentAccess::Shared { gpu_reads, .. } => *gpu_reads -= 1,
                 _ => unreachable!("Buffer was not locked for GPU read"),
             }
+
+            state.remove_gpu_user(command_buffer);
         }
     }
 
@@ -709,7 +906,11 @@ impl BufferState {
         Ok(())
     }
 
-    pub(crate) unsafe fn gpu_write_lock(&mut self, range: Range<DeviceSize>) {
+    pub(crate) unsafe fn gpu_write_lock(
+        &mut self,
+        range: Range<DeviceSize>,
+        command_buffer: ash::vk::CommandBuffer,
+    ) {
         self.ranges.split_at(&range.start);
         self.ranges.split_at(&range.end);
 
@@ -727,10 +928,16 @@ impl BufferState {
                 }
                 _ => unreachable!("Buffer is being accessed by the CPU"),
             }
+
+            state.gpu_users.push(command_buffer);
         }
     }
 
-    pub(crate) unsafe fn gpu_write_unlock(&mut self, range: Range<DeviceSize>) {
+    pub(crate) unsafe fn gpu_write_unlock(
+        &mut self,
+        range: Range<DeviceSize>,
+        command_buffer: ash::vk::CommandBuffer,
+    ) {
         self.ranges.split_at(&range.start);
         self.ranges.split_at(&range.end);
 
@@ -748,14 +955,46 @@ impl BufferState {
                 CurrentAccess::GpuExclusive { gpu_writes, .. } => *gpu_writes -= 1,
                 _ => unreachable!("Buffer was not locked for GPU write"),
             }
+
+            state.remove_gpu_user(command_buffer);
+        }
+    }
+
+    /// Returns the command buffers that currently hold a GPU lock on some part of `range`,
+    /// without duplicates.
+    pub(crate) fn gpu_users(&self, range: Range<DeviceSize>) -> Vec<ash::vk::CommandBuffer> {
+        let mut users = Vec::new();
+
+        for (_range, state) in self.ranges.range(&range) {
+            for &command_buffer in &state.gpu_users {
+                if !users.contains(&command_buffer) {
+                    users.push(command_buffer);
+                }
+            }
         }
+
+        users
     }
 }
 
 /// The current state of a specific range of bytes in a buffer.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 struct BufferRangeState {
     current_access: CurrentAccess,
+
+    // The command buffers holding a GPU lock on this range, once for each lock.
+    gpu_users: SmallVec<[ash::vk::CommandBuffer; 1]>,
+}
+
+impl BufferRangeState {
+    fn remove_gpu_user(&mut self, command_buffer: ash::vk::CommandBuffer) {
+        let index = self
+            .gpu_users
+            .iter()
+            .position(|&user| user == command_buffer)
+            .expect("Buffer was not locked by this command buffer");
+        self.gpu_users.swap_remove(index);
+    }
 }
 
 #[cfg(test)]
@@ -765,8 +1004,77 @@ mod tests {
     use super::SparseLevel;
     use super::UnsafeBuffer;
     use super::UnsafeBufferCreateInfo;
+    use crate::device::physical::PhysicalDevice;
     use crate::device::Device;
+    use crate::device::DeviceCreateInfo;
     use crate::device::DeviceOwned;
+    use crate::device::QueueCreateInfo;
+    use crate::instance::Instance;
+    use crate::instance::InstanceCreateInfo;
+    use crate::instance::InstanceExtensions;
+    use crate::VulkanObject;
+
+    #[test]
+    fn create_with_debug_name() {
+        // The name is set if `ext_debug_utils` is enabled, and ignored otherwise. Creation must
+        // succeed in both cases.
+        for ext_debug_utils in [false, true] {
+            let supported = match InstanceExtensions::supported_by_core() {
+                Ok(supported) => supported,
+                Err(_) => return,
+            };
+
+            if ext_debug_utils && !supported.ext_debug_utils {
+                continue;
+            }
+
+            let instance = match Instance::new(InstanceCreateInfo {
+                enabled_extensions: InstanceExtensions {
+                    ext_debug_utils,
+                    ..InstanceExtensions::none()
+                },
+                ..Default::default()
+            }) {
+                Ok(instance) => instance,
+                Err(_) => return,
+            };
+
+            let (physical_device, queue_family) = match PhysicalDevice::enumerate(&instance)
+                .find_map(|p| Some((p, p.queue_families().find(|q| q.supports_graphics())?)))
+            {
+                Some(x) => x,
+                None => return,
+            };
+
+            let (device, _) = match Device::new(
+                physical_device,
+                DeviceCreateInfo {
+                    queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
+                    ..Default::default()
+                },
+            ) {
+                Ok(x) => x,
+                Err(_) => return,
+            };
+
+            assert_eq!(
+                device.instance().enabled_extensions().ext_debug_utils,
+                ext_debug_utils,
+            );
+
+            let buffer = UnsafeBuffer::new(
+                device.clone(),
+                UnsafeBufferCreateInfo {
+                    size: 128,
+                    usage: BufferUsage::all(),
+                    debug_name: Some("vertices".into()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(buffer.size(), 128);
+        }
+    }
 
     #[test]
     fn create() {
@@ -787,6 +1095,45 @@ mod tests {
         assert_eq!(&**buf.device() as *const Device, &*device as *const Device);
     }
 
+    #[test]
+    fn from_handle() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let handle = unsafe {
+            let create_info = ash::vk::BufferCreateInfo {
+                size: 128,
+                usage: BufferUsage::all().into(),
+                ..Default::default()
+            };
+            let fns = device.fns();
+            let mut output = std::mem::MaybeUninit::uninit();
+            crate::check_errors((fns.v1_0.create_buffer)(
+                device.internal_object(),
+                &create_info,
+                std::ptr::null(),
+                output.as_mut_ptr(),
+            ))
+            .unwrap();
+            output.assume_init()
+        };
+
+        let buf = unsafe {
+            UnsafeBuffer::from_handle(
+                device,
+                handle,
+                UnsafeBufferCreateInfo {
+                    size: 128,
+                    usage: BufferUsage::all(),
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert_eq!(buf.internal_object(), handle);
+        assert_eq!(buf.size(), 128);
+        assert!(buf.memory_requirements().size >= 128);
+    }
+
     #[test]
     fn missing_feature_sparse_binding() {
         let (device, _) = gfx_dev_and_queue!();
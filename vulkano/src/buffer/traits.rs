@@ -8,7 +8,9 @@
 // according to those terms.
 
 use super::{sys::UnsafeBuffer, BufferContents, BufferSlice, BufferUsage};
-use crate::{device::DeviceOwned, DeviceSize, SafeDeref, VulkanObject};
+use crate::{
+    device::DeviceOwned, memory::transient::MemoryAlias, DeviceSize, SafeDeref, VulkanObject,
+};
 use std::{
     error, fmt,
     hash::{Hash, Hasher},
@@ -107,6 +109,16 @@ pub unsafe trait BufferAccess: DeviceOwned + Send + Sync {
             Ok(NonZeroU64::new_unchecked(ptr + inner.offset))
         }
     }
+
+    /// Returns the part of a block of transient memory that the buffer is bound to, if its
+    /// memory may be shared with other resources.
+    ///
+    /// Command buffers insert a barrier before a buffer whose memory is shared is used after
+    /// another resource that uses the same memory.
+    #[inline]
+    fn memory_alias(&self) -> Option<MemoryAlias> {
+        None
+    }
 }
 
 pub trait BufferAccessObject {
@@ -143,6 +155,11 @@ where
     fn size(&self) -> DeviceSize {
         (**self).size()
     }
+
+    #[inline]
+    fn memory_alias(&self) -> Option<MemoryAlias> {
+        (**self).memory_alias()
+    }
 }
 
 /// Extension trait for `BufferAccess`. Indicates the type of the content of the buffer.
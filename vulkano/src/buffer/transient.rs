@@ -0,0 +1,129 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Buffer whose memory may be shared with other transient resources.
+//!
+//! See the [`transient`](crate::memory::transient) module for more information.
+
+use super::{sys::UnsafeBuffer, BufferAccess, BufferAccessObject, BufferInner, TypedBufferAccess};
+use crate::{
+    device::{Device, DeviceOwned},
+    memory::transient::{MemoryAlias, TransientMemory},
+    DeviceSize, VulkanObject,
+};
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+    sync::Arc,
+};
+
+/// Buffer whose memory may be shared with other transient resources.
+///
+/// Created with a [`TransientAllocator`](crate::memory::transient::TransientAllocator). The
+/// contents of the buffer are not preserved when a resource that shares its memory is used.
+#[derive(Debug)]
+pub struct TransientBuffer {
+    inner: Arc<UnsafeBuffer>,
+    memory: Arc<TransientMemory>,
+    range: Range<DeviceSize>,
+}
+
+impl TransientBuffer {
+    // Wraps a buffer that is bound to `range` of `memory`.
+    pub(crate) fn new(
+        inner: Arc<UnsafeBuffer>,
+        memory: Arc<TransientMemory>,
+        range: Range<DeviceSize>,
+    ) -> Arc<TransientBuffer> {
+        Arc::new(TransientBuffer {
+            inner,
+            memory,
+            range,
+        })
+    }
+
+    /// Returns the memory that the buffer is bound to.
+    #[inline]
+    pub fn memory(&self) -> &Arc<TransientMemory> {
+        &self.memory
+    }
+
+    /// Returns the offset in the memory that the buffer is bound to.
+    #[inline]
+    pub fn memory_offset(&self) -> DeviceSize {
+        self.range.start
+    }
+}
+
+unsafe impl DeviceOwned for TransientBuffer {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.inner.device()
+    }
+}
+
+unsafe impl VulkanObject for TransientBuffer {
+    type Object = ash::vk::Buffer;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Buffer {
+        self.inner.internal_object()
+    }
+}
+
+unsafe impl BufferAccess for TransientBuffer {
+    #[inline]
+    fn inner(&self) -> BufferInner {
+        BufferInner {
+            buffer: &self.inner,
+            offset: 0,
+        }
+    }
+
+    #[inline]
+    fn size(&self) -> DeviceSize {
+        self.inner.size()
+    }
+
+    #[inline]
+    fn memory_alias(&self) -> Option<MemoryAlias> {
+        Some(MemoryAlias {
+            memory: self.memory.clone(),
+            range: self.range.clone(),
+        })
+    }
+}
+
+impl BufferAccessObject for Arc<TransientBuffer> {
+    #[inline]
+    fn as_buffer_access_object(&self) -> Arc<dyn BufferAccess> {
+        self.clone()
+    }
+}
+
+unsafe impl TypedBufferAccess for TransientBuffer {
+    type Content = [u8];
+}
+
+impl PartialEq for TransientBuffer {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner() == other.inner() && self.size() == other.size()
+    }
+}
+
+impl Eq for TransientBuffer {}
+
+impl Hash for TransientBuffer {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner().hash(state);
+        self.size().hash(state);
+    }
+}
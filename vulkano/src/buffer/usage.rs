@@ -27,6 +27,8 @@ pub struct BufferUsage {
     pub vertex_buffer: bool,
     pub indirect_buffer: bool,
     pub device_address: bool,
+    pub video_decode_src: bool,
+    pub video_decode_dst: bool,
     pub _ne: crate::NonExhaustive,
 }
 
@@ -44,6 +46,8 @@ impl Default for BufferUsage {
             vertex_buffer: false,
             indirect_buffer: false,
             device_address: false,
+            video_decode_src: false,
+            video_decode_dst: false,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -64,6 +68,8 @@ impl BufferUsage {
             vertex_buffer: false,
             indirect_buffer: false,
             device_address: false,
+            video_decode_src: false,
+            video_decode_dst: false,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -82,6 +88,8 @@ impl BufferUsage {
             vertex_buffer: true,
             indirect_buffer: true,
             device_address: true,
+            video_decode_src: true,
+            video_decode_dst: true,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -235,6 +243,12 @@ impl From<BufferUsage> for ash::vk::BufferUsageFlags {
         if val.device_address {
             result |= ash::vk::BufferUsageFlags::SHADER_DEVICE_ADDRESS;
         }
+        if val.video_decode_src {
+            result |= ash::vk::BufferUsageFlags::VIDEO_DECODE_SRC_KHR;
+        }
+        if val.video_decode_dst {
+            result |= ash::vk::BufferUsageFlags::VIDEO_DECODE_DST_KHR;
+        }
         result
     }
 }
@@ -255,6 +269,8 @@ impl BitOr for BufferUsage {
             vertex_buffer: self.vertex_buffer || rhs.vertex_buffer,
             indirect_buffer: self.indirect_buffer || rhs.indirect_buffer,
             device_address: self.device_address || rhs.device_address,
+            video_decode_src: self.video_decode_src || rhs.video_decode_src,
+            video_decode_dst: self.video_decode_dst || rhs.video_decode_dst,
             _ne: crate::NonExhaustive(()),
         }
     }
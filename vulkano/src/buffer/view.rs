@@ -41,14 +41,38 @@
 //!     },
 //! ).unwrap();
 //! ```
+//!
+//! For the common case of a read-only lookup table, such as skinning matrices or a color ramp,
+//! [`BufferView::uniform_texel_buffer_from_iter`] uploads the data and creates the view in one
+//! step, with the format chosen from the element type. The view can then be written to a
+//! descriptor set with [`WriteDescriptorSet::buffer_view`].
+//!
+//! ```
+//! # use std::sync::Arc;
+//! use vulkano::buffer::view::BufferView;
+//! use vulkano::descriptor_set::WriteDescriptorSet;
+//!
+//! # let queue: Arc<vulkano::device::Queue> = return;
+//! let lut: Vec<[f32; 4]> = (0..256).map(|i| [i as f32 / 255.0; 4]).collect();
+//! let (view, _future) = BufferView::uniform_texel_buffer_from_iter(lut, queue.clone()).unwrap();
+//! let write = WriteDescriptorSet::buffer_view(0, view);
+//! ```
+//!
+//! [`WriteDescriptorSet::buffer_view`]: crate::descriptor_set::WriteDescriptorSet::buffer_view
 
-use super::{BufferAccess, BufferAccessObject, BufferInner};
+use super::{
+    immutable::ImmutableBufferCreationError, BufferAccess, BufferAccessObject, BufferInner,
+    BufferUsage, ImmutableBuffer,
+};
 use crate::{
     check_errors,
-    device::{Device, DeviceOwned},
+    command_buffer::{CommandBufferExecFuture, PrimaryAutoCommandBuffer},
+    device::{Device, DeviceOwned, Queue},
     format::{Format, FormatFeatures},
+    sync::NowFuture,
     DeviceSize, Error, OomError, Version, VulkanObject,
 };
+use bytemuck::Pod;
 use std::{
     error, fmt,
     hash::{Hash, Hasher},
@@ -295,6 +319,107 @@ where
     }
 }
 
+impl<T> BufferView<ImmutableBuffer<[T]>>
+where
+    T: TexelBufferElement,
+{
+    /// Uploads `data` to a new buffer with the `uniform_texel_buffer` usage, and creates a view
+    /// of it with the format [`T::FORMAT`](TexelBufferElement::FORMAT).
+    ///
+    /// The number of elements is checked against the
+    /// [`max_texel_buffer_elements`](crate::device::Properties::max_texel_buffer_elements) limit
+    /// before anything is uploaded.
+    ///
+    /// This function returns two objects: the newly-created view, and a future representing the
+    /// upload operation. In order to be allowed to use the view, you must either submit your
+    /// operation after this future, or execute this future and wait for it to be finished before
+    /// submitting your own operation.
+    pub fn uniform_texel_buffer_from_iter<D>(
+        data: D,
+        queue: Arc<Queue>,
+    ) -> Result<
+        (
+            Arc<Self>,
+            CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>,
+        ),
+        TexelBufferCreationError,
+    >
+    where
+        D: IntoIterator<Item = T>,
+        D::IntoIter: ExactSizeIterator,
+    {
+        let data = data.into_iter();
+        let max_elements = queue
+            .device()
+            .physical_device()
+            .properties()
+            .max_texel_buffer_elements;
+
+        if data.len() == 0 {
+            return Err(TexelBufferCreationError::Empty);
+        }
+
+        // VUID-VkBufferViewCreateInfo-range-00930
+        if data.len() as u64 > max_elements as u64 {
+            return Err(TexelBufferCreationError::MaxTexelBufferElementsExceeded {
+                element_count: data.len() as u64,
+                max: max_elements,
+            });
+        }
+
+        let (buffer, future) = ImmutableBuffer::from_iter(
+            data,
+            BufferUsage {
+                uniform_texel_buffer: true,
+                ..BufferUsage::none()
+            },
+            queue,
+        )?;
+        let view = BufferView::new(
+            buffer,
+            BufferViewCreateInfo {
+                format: Some(T::FORMAT),
+                ..Default::default()
+            },
+        )?;
+
+        Ok((view, future))
+    }
+}
+
+/// Types that can be stored in a texel buffer, with a format that matches their layout.
+///
+/// # Safety
+///
+/// `FORMAT` must be an uncompressed format with one texel per block, whose block size is equal to
+/// the size of `Self`.
+pub unsafe trait TexelBufferElement: Pod + Send + Sync {
+    /// The format that a view of a buffer of `Self` elements is created with.
+    const FORMAT: Format;
+}
+
+macro_rules! impl_texel_buffer_element {
+    ($($ty:ty => $format:ident,)+) => {
+        $(
+            unsafe impl TexelBufferElement for $ty {
+                const FORMAT: Format = Format::$format;
+            }
+        )+
+    };
+}
+
+impl_texel_buffer_element!(
+    f32 => R32_SFLOAT,
+    [f32; 2] => R32G32_SFLOAT,
+    [f32; 4] => R32G32B32A32_SFLOAT,
+    u32 => R32_UINT,
+    [u32; 2] => R32G32_UINT,
+    [u32; 4] => R32G32B32A32_UINT,
+    i32 => R32_SINT,
+    [i32; 2] => R32G32_SINT,
+    [i32; 4] => R32G32B32A32_SINT,
+);
+
 /// Parameters to create a new `BufferView`.
 #[derive(Clone, Debug)]
 pub struct BufferViewCreateInfo {
@@ -387,6 +512,63 @@ impl fmt::Display for BufferViewCreationError {
     }
 }
 
+/// Error that can happen when creating a texel buffer from data.
+#[derive(Clone, Debug)]
+pub enum TexelBufferCreationError {
+    /// Creating or uploading the buffer failed.
+    BufferCreationError(ImmutableBufferCreationError),
+
+    /// Creating the buffer view failed.
+    BufferViewCreationError(BufferViewCreationError),
+
+    /// The data was empty.
+    Empty,
+
+    /// The number of elements exceeds the `max_texel_buffer_elements` limit.
+    MaxTexelBufferElementsExceeded { element_count: u64, max: u32 },
+}
+
+impl error::Error for TexelBufferCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::BufferCreationError(err) => Some(err),
+            Self::BufferViewCreationError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TexelBufferCreationError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferCreationError(_) => write!(f, "creating the buffer failed"),
+            Self::BufferViewCreationError(_) => write!(f, "creating the buffer view failed"),
+            Self::Empty => write!(f, "the data was empty"),
+            Self::MaxTexelBufferElementsExceeded { element_count, max } => write!(
+                f,
+                "the number of elements ({}) exceeds the `max_texel_buffer_elements` limit ({})",
+                element_count, max,
+            ),
+        }
+    }
+}
+
+impl From<ImmutableBufferCreationError> for TexelBufferCreationError {
+    #[inline]
+    fn from(err: ImmutableBufferCreationError) -> Self {
+        Self::BufferCreationError(err)
+    }
+}
+
+impl From<BufferViewCreationError> for TexelBufferCreationError {
+    #[inline]
+    fn from(err: BufferViewCreationError) -> Self {
+        Self::BufferViewCreationError(err)
+    }
+}
+
 impl From<OomError> for BufferViewCreationError {
     #[inline]
     fn from(err: OomError) -> BufferViewCreationError {
@@ -463,7 +645,9 @@ impl Hash for dyn BufferViewAbstract {
 #[cfg(test)]
 mod tests {
     use crate::buffer::immutable::ImmutableBuffer;
-    use crate::buffer::view::{BufferView, BufferViewCreateInfo, BufferViewCreationError};
+    use crate::buffer::view::{
+        BufferView, BufferViewCreateInfo, BufferViewCreationError, TexelBufferCreationError,
+    };
     use crate::buffer::BufferUsage;
     use crate::format::Format;
 
@@ -535,6 +719,31 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn uniform_texel_buffer_from_iter() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let (view, _) = BufferView::uniform_texel_buffer_from_iter(
+            (0..64).map(|i| [i as f32; 4]),
+            queue.clone(),
+        )
+        .unwrap();
+        assert_eq!(view.format, Some(Format::R32G32B32A32_SFLOAT));
+        assert_eq!(view.range, 0..64 * 16);
+
+        let max = device
+            .physical_device()
+            .properties()
+            .max_texel_buffer_elements;
+        match BufferView::uniform_texel_buffer_from_iter(
+            (0..max as usize + 1).map(|_| 0.0f32),
+            queue,
+        ) {
+            Err(TexelBufferCreationError::MaxTexelBufferElementsExceeded { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn wrong_usage() {
         // `VK_FORMAT_R8G8B8A8_UNORM` guaranteed to be a supported format
@@ -11,20 +11,25 @@ use super::{
     commands::pipeline::{
         CheckDescriptorSetsValidityError, CheckDispatchError, CheckDynamicStateValidityError,
         CheckIndexBufferError, CheckIndirectBufferError, CheckPipelineError,
-        CheckPushConstantsValidityError, CheckVertexBufferError,
+        CheckPushConstantsValidityError, CheckVertexBufferError, DescriptorValidationMode,
+        DescriptorValidationState,
     },
     pool::{
-        standard::{StandardCommandPoolAlloc, StandardCommandPoolBuilder},
-        CommandPool, CommandPoolAlloc, CommandPoolBuilderAlloc,
+        standard::{
+            StandardCommandBufferAllocator, StandardCommandPoolAlloc, StandardCommandPoolBuilder,
+        },
+        CommandBufferAllocator, CommandPoolAlloc, CommandPoolBuilderAlloc,
     },
     synced::{
-        CommandBufferState, SyncCommandBuffer, SyncCommandBufferBuilder,
+        BarrierReason, CommandBufferState, SyncCommandBuffer, SyncCommandBufferBuilder,
         SyncCommandBufferBuilderError,
     },
     sys::{CommandBufferBeginInfo, UnsafeCommandBuffer},
+    timing::{CommandTiming, CommandTimingError},
     CommandBufferExecError, CommandBufferInheritanceInfo, CommandBufferInheritanceRenderPassInfo,
     CommandBufferInheritanceRenderPassType, CommandBufferLevel, CommandBufferUsage,
-    PrimaryCommandBuffer, RenderingAttachmentInfo, SecondaryCommandBuffer, SubpassContents,
+    PrimaryCommandBuffer, QueryCommand, QueryError, RenderingAttachmentInfo,
+    SecondaryCommandBuffer, SubpassContents, UsageInference,
 };
 use crate::{
     buffer::{sys::UnsafeBuffer, BufferAccess},
@@ -32,10 +37,13 @@ use crate::{
     device::{physical::QueueFamily, Device, DeviceOwned, Queue},
     image::{sys::UnsafeImage, ImageAccess, ImageLayout, ImageSubresourceRange},
     pipeline::{graphics::render_pass::PipelineRenderPassType, GraphicsPipeline},
-    query::{QueryControlFlags, QueryType},
+    query::{
+        QueryControlFlags, QueryPipelineStatisticFlags, QueryPool, QueryPoolCreateInfo, QueryType,
+    },
     render_pass::{Framebuffer, Subpass},
     sync::{AccessCheckError, AccessFlags, GpuFuture, PipelineMemoryAccess, PipelineStages},
-    DeviceSize, OomError,
+    video::{VideoReferenceSlot, VideoSession, VideoSessionParameters},
+    DeviceSize, OomError, VulkanObject,
 };
 use std::{
     collections::HashMap,
@@ -72,6 +80,18 @@ pub struct AutoCommandBufferBuilder<L, P = StandardCommandPoolBuilder> {
     // If any queries are active, this hashmap contains their state.
     pub(super) query_state: HashMap<ash::vk::QueryType, QueryState>,
 
+    // For each query that was reset or used so far, whether it was reset and not used since.
+    pub(super) query_reset_state: HashMap<(ash::vk::QueryPool, u32), bool>,
+
+    // The commands that reset or use queries, in recording order. Checked on submission.
+    pub(super) query_commands: Vec<QueryCommand>,
+
+    // How descriptor sets are validated by draw and dispatch commands.
+    pub(super) descriptor_validation: DescriptorValidationState,
+
+    // If we're inside a video coding scope, contains its state.
+    pub(super) video_coding_state: Option<VideoCodingState>,
+
     _data: PhantomData<L>,
 }
 
@@ -115,11 +135,19 @@ pub(super) struct BeginRenderingState {
     pub(super) stencil_attachment: Option<RenderingAttachmentInfo>,
 }
 
+// The state of an active video coding scope.
+pub(super) struct VideoCodingState {
+    pub(super) session: Arc<VideoSession>,
+    pub(super) parameters: Option<Arc<VideoSessionParameters>>,
+    pub(super) reference_slots: Vec<VideoReferenceSlot>,
+}
+
 // The state of an active query.
 pub(super) struct QueryState {
     pub(super) query_pool: ash::vk::QueryPool,
     pub(super) query: u32,
     pub(super) ty: QueryType,
+    pub(super) pipeline_statistics: QueryPipelineStatisticFlags,
     pub(super) flags: QueryControlFlags,
     pub(super) in_subpass: bool,
 }
@@ -135,14 +163,41 @@ impl AutoCommandBufferBuilder<PrimaryAutoCommandBuffer, StandardCommandPoolBuild
         AutoCommandBufferBuilder<PrimaryAutoCommandBuffer, StandardCommandPoolBuilder>,
         CommandBufferBeginError,
     > {
+        AutoCommandBufferBuilder::primary_with_allocator(
+            &StandardCommandBufferAllocator::new(device),
+            queue_family,
+            usage,
+        )
+    }
+}
+
+impl<P> AutoCommandBufferBuilder<PrimaryAutoCommandBuffer<P::Alloc>, P>
+where
+    P: CommandPoolBuilderAlloc,
+{
+    /// Starts recording a primary command buffer, allocated from `allocator`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `queue_family` doesn't belong to the same physical device as `allocator`.
+    #[inline]
+    pub fn primary_with_allocator<A>(
+        allocator: &A,
+        queue_family: QueueFamily,
+        usage: CommandBufferUsage,
+    ) -> Result<Self, CommandBufferBeginError>
+    where
+        A: CommandBufferAllocator<Builder = P>,
+    {
         unsafe {
             AutoCommandBufferBuilder::begin(
-                device,
+                allocator,
                 queue_family,
                 CommandBufferLevel::Primary,
                 CommandBufferBeginInfo {
                     usage,
                     inheritance_info: None,
+                    debug_name: None,
                     _ne: crate::NonExhaustive(()),
                 },
             )
@@ -162,14 +217,43 @@ impl AutoCommandBufferBuilder<SecondaryAutoCommandBuffer, StandardCommandPoolBui
         AutoCommandBufferBuilder<SecondaryAutoCommandBuffer, StandardCommandPoolBuilder>,
         CommandBufferBeginError,
     > {
+        AutoCommandBufferBuilder::secondary_with_allocator(
+            &StandardCommandBufferAllocator::new(device),
+            queue_family,
+            usage,
+            inheritance_info,
+        )
+    }
+}
+
+impl<P> AutoCommandBufferBuilder<SecondaryAutoCommandBuffer<P::Alloc>, P>
+where
+    P: CommandPoolBuilderAlloc,
+{
+    /// Starts recording a secondary command buffer, allocated from `allocator`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `queue_family` doesn't belong to the same physical device as `allocator`.
+    #[inline]
+    pub fn secondary_with_allocator<A>(
+        allocator: &A,
+        queue_family: QueueFamily,
+        usage: CommandBufferUsage,
+        inheritance_info: CommandBufferInheritanceInfo,
+    ) -> Result<Self, CommandBufferBeginError>
+    where
+        A: CommandBufferAllocator<Builder = P>,
+    {
         unsafe {
             Ok(AutoCommandBufferBuilder::begin(
-                device,
+                allocator,
                 queue_family,
                 CommandBufferLevel::Secondary,
                 CommandBufferBeginInfo {
                     usage,
                     inheritance_info: Some(inheritance_info),
+                    debug_name: None,
                     _ne: crate::NonExhaustive(()),
                 },
             )?)
@@ -177,22 +261,28 @@ impl AutoCommandBufferBuilder<SecondaryAutoCommandBuffer, StandardCommandPoolBui
     }
 }
 
-impl<L> AutoCommandBufferBuilder<L, StandardCommandPoolBuilder> {
+impl<L, P> AutoCommandBufferBuilder<L, P>
+where
+    P: CommandPoolBuilderAlloc,
+{
     // Actual constructor. Private.
     //
     // `begin_info.inheritance_info` must match `level`.
-    unsafe fn begin(
-        device: Arc<Device>,
+    unsafe fn begin<A>(
+        allocator: &A,
         queue_family: QueueFamily,
         level: CommandBufferLevel,
         begin_info: CommandBufferBeginInfo,
-    ) -> Result<AutoCommandBufferBuilder<L, StandardCommandPoolBuilder>, CommandBufferBeginError>
+    ) -> Result<AutoCommandBufferBuilder<L, P>, CommandBufferBeginError>
+    where
+        A: CommandBufferAllocator<Builder = P>,
     {
-        Self::validate_begin(&device, &queue_family, level, &begin_info)?;
+        Self::validate_begin(allocator.device(), &queue_family, level, &begin_info)?;
 
         let &CommandBufferBeginInfo {
             usage,
             ref inheritance_info,
+            debug_name: _,
             _ne: _,
         } = &begin_info;
 
@@ -233,8 +323,8 @@ impl<L> AutoCommandBufferBuilder<L, StandardCommandPoolBuilder> {
             }
         }
 
-        let pool_builder_alloc = Device::standard_command_pool(&device, queue_family)
-            .allocate(level, 1)?
+        let pool_builder_alloc = allocator
+            .allocate(queue_family, level, 1)?
             .next()
             .expect("Requested one command buffer from the command pool, but got zero.");
         let inner = SyncCommandBufferBuilder::new(pool_builder_alloc.inner(), begin_info)?;
@@ -245,6 +335,10 @@ impl<L> AutoCommandBufferBuilder<L, StandardCommandPoolBuilder> {
             queue_family_id: queue_family.id(),
             render_pass_state,
             query_state: HashMap::default(),
+            query_reset_state: HashMap::default(),
+            query_commands: Vec::new(),
+            descriptor_validation: DescriptorValidationState::default(),
+            video_coding_state: None,
             inheritance_info,
             usage,
             _data: PhantomData,
@@ -263,6 +357,7 @@ impl<L> AutoCommandBufferBuilder<L, StandardCommandPoolBuilder> {
         let &CommandBufferBeginInfo {
             usage,
             ref inheritance_info,
+            debug_name: _,
             _ne: _,
         } = &begin_info;
 
@@ -547,6 +642,10 @@ where
             return Err(AutoCommandBufferBuilderContextError::QueryIsActive.into());
         }
 
+        if self.video_coding_state.is_some() {
+            return Err(AutoCommandBufferBuilderContextError::VideoCodingScopeActive.into());
+        }
+
         let submit_state = match self.usage {
             CommandBufferUsage::MultipleSubmit => SubmitState::ExclusiveUse {
                 in_use: AtomicBool::new(false),
@@ -560,9 +659,60 @@ where
         Ok(PrimaryAutoCommandBuffer {
             inner: self.inner.build()?,
             pool_alloc: self.pool_builder_alloc.into_alloc(),
+            query_commands: self.query_commands,
             submit_state,
         })
     }
+
+    /// Enables command timing for the commands that are recorded from now on.
+    ///
+    /// The commands are split into ranges of `interval` commands, and a timestamp is written
+    /// between each range. At most `max_ranges` ranges are timed; any commands after that are
+    /// added to the last range. Once the command buffer has been built, the timestamps can be read
+    /// back with `command_timing`. See `CommandTiming` for more information.
+    ///
+    /// This creates a timestamp query pool, and records a command to reset it, so it must be
+    /// called outside of a render pass.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `interval` or `max_ranges` is `0`.
+    pub fn enable_command_timing(
+        &mut self,
+        interval: u32,
+        max_ranges: u32,
+    ) -> Result<&mut Self, CommandTimingError> {
+        assert!(interval != 0);
+        assert!(max_ranges != 0);
+
+        if self.inner.command_timing.is_some() {
+            return Err(CommandTimingError::AlreadyEnabled);
+        }
+
+        let timestamp_valid_bits = self
+            .queue_family()
+            .timestamp_valid_bits()
+            .ok_or(QueryError::NoTimestampValidBits)?;
+        let query_pool = QueryPool::new(
+            self.device().clone(),
+            QueryPoolCreateInfo {
+                query_count: max_ranges + 1,
+                ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+            },
+        )?;
+
+        self.reset_query_pool(query_pool.clone(), 0..max_ranges + 1)?;
+
+        let first_command = self.inner.commands.len();
+        self.inner.command_timing = Some(CommandTiming::new(
+            query_pool,
+            interval,
+            timestamp_valid_bits,
+            first_command,
+        ));
+
+        Ok(self)
+    }
 }
 
 impl<P> AutoCommandBufferBuilder<SecondaryAutoCommandBuffer<P::Alloc>, P>
@@ -590,12 +740,23 @@ where
             inner: self.inner.build()?,
             pool_alloc: self.pool_builder_alloc.into_alloc(),
             inheritance_info: self.inheritance_info.unwrap(),
+            query_commands: self.query_commands,
             submit_state,
         })
     }
 }
 
 impl<L, P> AutoCommandBufferBuilder<L, P> {
+    /// Enables usage inference for the commands that are recorded from now on.
+    ///
+    /// Once the command buffer is built, the usages that were collected can be retrieved with
+    /// `inferred_usage`. See `UsageInference` for more information.
+    #[inline]
+    pub fn enable_usage_inference(&mut self) -> &mut Self {
+        self.inner.enable_usage_inference();
+        self
+    }
+
     #[inline]
     pub(super) fn ensure_outside_render_pass(
         &self,
@@ -697,6 +858,49 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
+    /// Checks that a draw command using shader objects can be recorded, and returns the view mask
+    /// and the number of color attachments of the current render pass instance.
+    pub(super) fn ensure_inside_rendering_inline(
+        &self,
+    ) -> Result<(u32, u32), AutoCommandBufferBuilderContextError> {
+        let render_pass_state = self
+            .render_pass_state
+            .as_ref()
+            .ok_or(AutoCommandBufferBuilderContextError::ForbiddenOutsideRenderPass)?;
+
+        // Subpass must be for inline commands
+        if render_pass_state.contents != SubpassContents::Inline {
+            return Err(AutoCommandBufferBuilderContextError::WrongSubpassType);
+        }
+
+        // Shader objects have no render pass, so they can only be used with dynamic rendering.
+        match &render_pass_state.render_pass {
+            RenderPassStateType::BeginRenderPass(_) => {
+                Err(AutoCommandBufferBuilderContextError::ShaderObjectsRequireBeginRendering)
+            }
+            RenderPassStateType::BeginRendering(state) => {
+                Ok((state.view_mask, state.color_attachments.len() as u32))
+            }
+            RenderPassStateType::Inherited => {
+                match self
+                    .inheritance_info
+                    .as_ref()
+                    .unwrap()
+                    .render_pass
+                    .as_ref()
+                    .unwrap()
+                {
+                    CommandBufferInheritanceRenderPassType::BeginRenderPass(_) => Err(
+                        AutoCommandBufferBuilderContextError::ShaderObjectsRequireBeginRendering,
+                    ),
+                    CommandBufferInheritanceRenderPassType::BeginRendering(info) => {
+                        Ok((info.view_mask, info.color_attachment_formats.len() as u32))
+                    }
+                }
+            }
+        }
+    }
+
     #[inline]
     pub(super) fn queue_family(&self) -> QueueFamily {
         self.device()
@@ -710,6 +914,36 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     pub fn state(&self) -> CommandBufferState {
         self.inner.state()
     }
+
+    /// Returns how the resources in bound descriptor sets are validated by draw and dispatch
+    /// commands.
+    #[inline]
+    pub fn descriptor_validation_mode(&self) -> DescriptorValidationMode {
+        self.descriptor_validation.mode
+    }
+
+    /// Sets how the resources in bound descriptor sets are validated by the draw and dispatch
+    /// commands that are recorded after this call.
+    ///
+    /// The default is [`DescriptorValidationMode::Full`].
+    #[inline]
+    pub fn set_descriptor_validation_mode(&mut self, mode: DescriptorValidationMode) -> &mut Self {
+        self.descriptor_validation.mode = mode;
+        self
+    }
+
+    /// Gives the command buffer that is being recorded a name, to identify it in debugging tools.
+    /// If `debug_name` is `None`, the name that was given before is removed.
+    ///
+    /// If the [`ext_debug_utils`](crate::instance::InstanceExtensions::ext_debug_utils) extension
+    /// is enabled on the instance, the name is set with
+    /// [`Device::set_debug_utils_object_name`](crate::device::Device::set_debug_utils_object_name).
+    /// Otherwise it is ignored.
+    #[inline]
+    pub fn set_debug_name(&mut self, debug_name: Option<&str>) -> Result<&mut Self, OomError> {
+        self.device().reset_debug_name(&self.inner, debug_name)?;
+        Ok(self)
+    }
 }
 
 unsafe impl<L, P> DeviceOwned for AutoCommandBufferBuilder<L, P> {
@@ -722,11 +956,100 @@ unsafe impl<L, P> DeviceOwned for AutoCommandBufferBuilder<L, P> {
 pub struct PrimaryAutoCommandBuffer<P = StandardCommandPoolAlloc> {
     inner: SyncCommandBuffer,
     pool_alloc: P, // Safety: must be dropped after `inner`
+    query_commands: Vec<QueryCommand>,
 
     // Tracks usage of the command buffer on the GPU.
     submit_state: SubmitState,
 }
 
+impl<P> PrimaryAutoCommandBuffer<P> {
+    /// Returns the reasons why pipeline barriers were inserted into this command buffer.
+    ///
+    /// See `SyncCommandBuffer::barrier_reasons` for more information.
+    #[inline]
+    pub fn barrier_reasons(&self) -> &[BarrierReason] {
+        self.inner.barrier_reasons()
+    }
+
+    /// Returns the usages that the resources of this command buffer require, or `None` if usage
+    /// inference wasn't enabled on the builder.
+    ///
+    /// See `UsageInference` for more information.
+    #[inline]
+    pub fn inferred_usage(&self) -> Option<&UsageInference> {
+        self.inner.inferred_usage()
+    }
+
+    /// Returns the timestamps that are written between ranges of commands, or `None` if command
+    /// timing wasn't enabled on the builder.
+    ///
+    /// See `CommandTiming` for more information.
+    #[inline]
+    pub fn command_timing(&self) -> Option<&CommandTiming> {
+        self.inner.command_timing()
+    }
+
+    // Checks that every query is reset before it is used, given the current state of the query
+    // pools, and calls `lock` while the states are locked. If `lock` succeeds, the states are
+    // updated with the effect of the command buffer.
+    fn lock_queries(
+        &self,
+        lock: impl FnOnce() -> Result<(), CommandBufferExecError>,
+    ) -> Result<(), CommandBufferExecError> {
+        if self.query_commands.is_empty() {
+            return lock();
+        }
+
+        // Sorting the pools ensures that they are always locked in the same order.
+        let mut query_pools: Vec<&Arc<QueryPool>> = self
+            .query_commands
+            .iter()
+            .map(QueryCommand::query_pool)
+            .collect();
+        query_pools.sort_unstable_by_key(|query_pool| query_pool.internal_object());
+        query_pools.dedup_by_key(|query_pool| query_pool.internal_object());
+
+        let mut reset_states: Vec<_> = query_pools
+            .iter()
+            .map(|query_pool| query_pool.reset_state())
+            .collect();
+        let mut new_reset_states: Vec<Vec<bool>> =
+            reset_states.iter().map(|state| state.to_vec()).collect();
+
+        for command in &self.query_commands {
+            let pool_index = query_pools
+                .binary_search_by_key(&command.query_pool().internal_object(), |query_pool| {
+                    query_pool.internal_object()
+                })
+                .unwrap();
+            let state = &mut new_reset_states[pool_index]
+                [command.queries().start as usize..command.queries().end as usize];
+
+            match command {
+                QueryCommand::Reset { .. } => state.fill(true),
+                QueryCommand::Use { query_pool, .. } => {
+                    if let Some(index) = state.iter().position(|&reset| !reset) {
+                        return Err(CommandBufferExecError::QueryNotReset {
+                            query_pool: query_pool.clone(),
+                            query: command.queries().start + index as u32,
+                        });
+                    }
+
+                    state.fill(false);
+                }
+            }
+        }
+
+        lock()?;
+
+        for (state, new_state) in reset_states.iter_mut().zip(new_reset_states) {
+            **state = new_state;
+        }
+
+        Ok(())
+    }
+}
+
 unsafe impl<P> DeviceOwned for PrimaryAutoCommandBuffer<P> {
     #[inline]
     fn device(&self) -> &Arc<Device> {
@@ -734,6 +1057,15 @@ unsafe impl<P> DeviceOwned for PrimaryAutoCommandBuffer<P> {
     }
 }
 
+unsafe impl<P> VulkanObject for PrimaryAutoCommandBuffer<P> {
+    type Object = ash::vk::CommandBuffer;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::CommandBuffer {
+        PrimaryCommandBuffer::inner(self).internal_object()
+    }
+}
+
 unsafe impl<P> PrimaryCommandBuffer for PrimaryAutoCommandBuffer<P>
 where
     P: CommandPoolAlloc,
@@ -767,12 +1099,12 @@ where
             SubmitState::Concurrent => (),
         };
 
-        let err = match self.inner.lock_submit(future, queue) {
+        let err = match self.lock_queries(|| self.inner.lock_submit(future, queue)) {
             Ok(()) => return Ok(()),
             Err(err) => err,
         };
 
-        // If `self.inner.lock_submit()` failed, we revert action.
+        // If locking failed, we revert action.
         match self.submit_state {
             SubmitState::OneTime {
                 ref already_submitted,
@@ -837,11 +1169,31 @@ pub struct SecondaryAutoCommandBuffer<P = StandardCommandPoolAlloc> {
     inner: SyncCommandBuffer,
     pool_alloc: P, // Safety: must be dropped after `inner`
     inheritance_info: CommandBufferInheritanceInfo,
+    query_commands: Vec<QueryCommand>,
 
     // Tracks usage of the command buffer on the GPU.
     submit_state: SubmitState,
 }
 
+impl<P> SecondaryAutoCommandBuffer<P> {
+    /// Returns the reasons why pipeline barriers were inserted into this command buffer.
+    ///
+    /// See `SyncCommandBuffer::barrier_reasons` for more information.
+    #[inline]
+    pub fn barrier_reasons(&self) -> &[BarrierReason] {
+        self.inner.barrier_reasons()
+    }
+
+    /// Returns the usages that the resources of this command buffer require, or `None` if usage
+    /// inference wasn't enabled on the builder.
+    ///
+    /// See `UsageInference` for more information.
+    #[inline]
+    pub fn inferred_usage(&self) -> Option<&UsageInference> {
+        self.inner.inferred_usage()
+    }
+}
+
 unsafe impl<P> DeviceOwned for SecondaryAutoCommandBuffer<P> {
     #[inline]
     fn device(&self) -> &Arc<Device> {
@@ -849,6 +1201,15 @@ unsafe impl<P> DeviceOwned for SecondaryAutoCommandBuffer<P> {
     }
 }
 
+unsafe impl<P> VulkanObject for SecondaryAutoCommandBuffer<P> {
+    type Object = ash::vk::CommandBuffer;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::CommandBuffer {
+        SecondaryCommandBuffer::inner(self).internal_object()
+    }
+}
+
 unsafe impl<P> SecondaryCommandBuffer for SecondaryAutoCommandBuffer<P>
 where
     P: CommandPoolAlloc,
@@ -902,6 +1263,11 @@ where
         &self.inheritance_info
     }
 
+    #[inline]
+    fn query_commands(&self) -> &[QueryCommand] {
+        &self.query_commands
+    }
+
     #[inline]
     fn num_buffers(&self) -> usize {
         self.inner.num_buffers()
@@ -1100,6 +1466,11 @@ pub enum AutoCommandBufferBuilderContextError {
     QueryNotActive,
     /// A query is active that is not included in the `inheritance` of the secondary command buffer.
     QueryNotInherited,
+    /// Tried to draw with shader objects inside a render pass instance that was not begun with
+    /// `begin_rendering`.
+    ShaderObjectsRequireBeginRendering,
+    /// Tried to build a command buffer whose video coding scope was not ended.
+    VideoCodingScopeActive,
     /// Tried to use a graphics pipeline or secondary command buffer whose subpass index
     /// didn't match the current subpass index.
     WrongSubpassIndex,
@@ -1147,6 +1518,13 @@ impl fmt::Display for AutoCommandBufferBuilderContextError {
                 AutoCommandBufferBuilderContextError::QueryNotInherited => {
                     "a query is active that is not included in the inheritance of the secondary command buffer"
                 }
+                AutoCommandBufferBuilderContextError::ShaderObjectsRequireBeginRendering => {
+                    "tried to draw with shader objects inside a render pass instance that was not \
+                 begun with `begin_rendering`"
+                }
+                AutoCommandBufferBuilderContextError::VideoCodingScopeActive => {
+                    "tried to build a command buffer whose video coding scope was not ended"
+                }
                 AutoCommandBufferBuilderContextError::WrongSubpassIndex => {
                     "tried to use a graphics pipeline whose subpass index didn't match the current \
                  subpass index"
@@ -1166,8 +1544,12 @@ mod tests {
     use super::*;
     use crate::{
         buffer::{BufferUsage, CpuAccessibleBuffer},
-        command_buffer::{BufferCopy, CopyBufferInfoTyped, CopyError, ExecuteCommandsError},
+        command_buffer::{
+            BufferCopy, CopyBufferInfoTyped, CopyError, ExecuteCommandsError, QueryError,
+        },
         device::{physical::PhysicalDevice, DeviceCreateInfo, QueueCreateInfo},
+        query::QueryPoolCreateInfo,
+        sync::now,
     };
 
     #[test]
@@ -1398,4 +1780,225 @@ mod tests {
             })
         ));
     }
+
+    #[test]
+    fn usage_inference() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let source = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::all(),
+            true,
+            [0_u32, 1, 2, 3].iter().copied(),
+        )
+        .unwrap();
+        let destination = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::all(),
+            true,
+            [0_u32; 4].iter().copied(),
+        )
+        .unwrap();
+        let unused =
+            CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), true, 0_u32)
+                .unwrap();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        builder
+            .enable_usage_inference()
+            .copy_buffer(CopyBufferInfoTyped::buffers(
+                source.clone(),
+                destination.clone(),
+            ))
+            .unwrap();
+
+        let cb = builder.build().unwrap();
+        let usage = cb.inferred_usage().unwrap();
+
+        assert_eq!(
+            usage.buffer_usage(source.as_ref()),
+            Some(BufferUsage::transfer_src())
+        );
+        assert_eq!(
+            usage.buffer_usage(destination.as_ref()),
+            Some(BufferUsage::transfer_dst())
+        );
+        assert_eq!(usage.buffer_usage(unused.as_ref()), None);
+        assert_eq!(usage.buffers().len(), 2);
+
+        let builder = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        assert!(builder.build().unwrap().inferred_usage().is_none());
+    }
+
+    #[test]
+    fn command_timing() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        if queue.family().timestamp_valid_bits().is_none() {
+            return;
+        }
+
+        let source = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::all(),
+            true,
+            [0_u32, 1, 2, 3].iter().copied(),
+        )
+        .unwrap();
+        let destination = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::all(),
+            true,
+            [0_u32; 4].iter().copied(),
+        )
+        .unwrap();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder.enable_command_timing(2, 16).unwrap();
+        assert!(matches!(
+            builder.enable_command_timing(2, 16),
+            Err(CommandTimingError::AlreadyEnabled)
+        ));
+
+        for _ in 0..5 {
+            builder
+                .copy_buffer(CopyBufferInfoTyped::buffers(
+                    source.clone(),
+                    destination.clone(),
+                ))
+                .unwrap();
+        }
+
+        let cb = builder.build().unwrap();
+        let command_timing = cb.command_timing().unwrap();
+        assert_eq!(command_timing.range_count(), 3);
+
+        cb.execute(queue)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let report = command_timing.report().unwrap().unwrap();
+        assert_eq!(
+            report
+                .ranges
+                .iter()
+                .map(|range| range.command_names.len())
+                .collect::<Vec<_>>(),
+            [2, 2, 1]
+        );
+    }
+
+    #[test]
+    fn descriptor_validation_mode() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        assert_eq!(
+            builder.descriptor_validation_mode(),
+            DescriptorValidationMode::Full
+        );
+
+        builder.set_descriptor_validation_mode(DescriptorValidationMode::Deferred {
+            min_descriptor_count: 1024,
+        });
+        assert_eq!(
+            builder.descriptor_validation_mode(),
+            DescriptorValidationMode::Deferred {
+                min_descriptor_count: 1024
+            }
+        );
+    }
+
+    #[test]
+    fn query_reset() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let query_pool = QueryPool::new(
+            device.clone(),
+            QueryPoolCreateInfo {
+                query_count: 2,
+                ..QueryPoolCreateInfo::query_type(QueryType::Occlusion)
+            },
+        )
+        .unwrap();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::MultipleSubmit,
+        )
+        .unwrap();
+        builder
+            .reset_query_pool(query_pool.clone(), 0..2)
+            .unwrap()
+            .begin_query(query_pool.clone(), 0, QueryControlFlags::default())
+            .unwrap()
+            .end_query(query_pool.clone(), 0)
+            .unwrap();
+
+        // Using a query twice without resetting it in between is an error.
+        assert!(matches!(
+            builder.begin_query(query_pool.clone(), 0, QueryControlFlags::default()),
+            Err(QueryError::QueryNotReset { query: 0 })
+        ));
+
+        let reset_cb = builder.build().unwrap();
+
+        // Query 1 is not reset in this command buffer, so it is checked on submission.
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::MultipleSubmit,
+        )
+        .unwrap();
+        builder
+            .begin_query(query_pool.clone(), 1, QueryControlFlags::default())
+            .unwrap()
+            .end_query(query_pool.clone(), 1)
+            .unwrap();
+        let use_cb = builder.build().unwrap();
+
+        let future = now(device);
+
+        assert!(matches!(
+            use_cb.lock_submit(&future, &queue),
+            Err(CommandBufferExecError::QueryNotReset { query: 1, .. })
+        ));
+
+        reset_cb.lock_submit(&future, &queue).unwrap();
+        unsafe { reset_cb.unlock() };
+
+        // Query 1 was reset by the first command buffer, but only once.
+        use_cb.lock_submit(&future, &queue).unwrap();
+        unsafe { use_cb.unlock() };
+
+        assert!(matches!(
+            use_cb.lock_submit(&future, &queue),
+            Err(CommandBufferExecError::QueryNotReset { query: 1, .. })
+        ));
+    }
 }
@@ -8,7 +8,7 @@
 // according to those terms.
 
 use crate::{
-    buffer::{BufferAccess, BufferContents, TypedBufferAccess},
+    buffer::{BufferAccess, BufferContents},
     command_buffer::{
         synced::{Command, SetOrPush, SyncCommandBufferBuilder},
         sys::UnsafeCommandBufferBuilder,
@@ -22,12 +22,12 @@ use crate::{
     device::DeviceOwned,
     pipeline::{
         graphics::{
-            input_assembly::{Index, IndexType},
+            input_assembly::{IndexBuffer, IndexType},
             vertex_input::VertexBuffersCollection,
         },
         ComputePipeline, GraphicsPipeline, PipelineBindPoint, PipelineLayout,
     },
-    shader::ShaderStages,
+    shader::{ShaderObject, ShaderStage, ShaderStages},
     DeviceSize, VulkanObject,
 };
 use parking_lot::Mutex;
@@ -86,11 +86,19 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             );
 
             let pipeline_set = &pipeline_layout.set_layouts()[first_set as usize + num];
-            assert!(
-                pipeline_set.is_compatible_with(set.as_ref().0.layout()),
-                "the element of descriptor_sets being bound to slot {} is not compatible with the corresponding slot in pipeline_layout",
-                first_set as usize + num,
-            );
+            if !pipeline_set.is_compatible_with(set.as_ref().0.layout()) {
+                let differences = pipeline_set
+                    .compatibility_differences(set.as_ref().0.layout())
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+
+                panic!(
+                    "the element of descriptor_sets being bound to slot {} is not compatible with the corresponding slot in pipeline_layout: {}",
+                    first_set as usize + num,
+                    differences.join("; "),
+                );
+            }
 
             // TODO: see https://github.com/vulkano-rs/vulkano/issues/1643
             // For each dynamic uniform or storage buffer binding in pDescriptorSets, the sum of the
@@ -115,6 +123,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
     /// Binds an index buffer for future indexed draw calls.
     ///
+    /// `index_buffer` can be any typed buffer whose content is a slice of an
+    /// [`Index`](crate::pipeline::graphics::input_assembly::Index) type, or an
+    /// [`IndexBuffer`] which carries the index type along with the buffer.
+    ///
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
@@ -124,18 +136,21 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// - If the index buffer contains `u8` indices, panics if the
     ///   [`index_type_uint8`](crate::device::Features::index_type_uint8) feature is not
     ///   enabled on the device.
-    pub fn bind_index_buffer<Ib, I>(&mut self, index_buffer: Arc<Ib>) -> &mut Self
+    pub fn bind_index_buffer<Ib>(&mut self, index_buffer: Ib) -> &mut Self
     where
-        Ib: TypedBufferAccess<Content = [I]> + 'static,
-        I: Index + 'static,
+        Ib: Into<IndexBuffer>,
     {
+        let index_buffer = index_buffer.into();
+        let index_type = index_buffer.index_type();
+        let buffer = index_buffer.buffer();
+
         assert!(
             self.queue_family().supports_graphics(),
             "the queue family of the command buffer must support graphics operations"
         );
 
         assert_eq!(
-            index_buffer.device().internal_object(),
+            buffer.device().internal_object(),
             self.device().internal_object()
         );
 
@@ -144,7 +159,7 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         // buffer, must be a multiple of the type indicated by indexType
 
         assert!(
-            index_buffer.inner().buffer.usage().index_buffer,
+            buffer.inner().buffer.usage().index_buffer,
             "index_buffer must have the index_buffer usage enabled"
         );
 
@@ -153,11 +168,11 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         // VkDeviceMemory object
 
         if !self.device().enabled_features().index_type_uint8 {
-            assert!(I::ty() != IndexType::U8, "if the index buffer contains u8 indices, the index_type_uint8 feature must be enabled on the device");
+            assert!(index_type != IndexType::U8, "if the index buffer contains u8 indices, the index_type_uint8 feature must be enabled on the device");
         }
 
         unsafe {
-            self.inner.bind_index_buffer(index_buffer, I::ty());
+            self.inner.bind_index_buffer(buffer.clone(), index_type);
         }
 
         self
@@ -255,6 +270,79 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         self
     }
 
+    /// Binds shader objects to shader stages for future draw or dispatch calls.
+    ///
+    /// Each element of `shaders` binds a shader object to a stage, or unbinds the stage if the
+    /// shader object is `None`. Stages that are not mentioned keep their current binding.
+    ///
+    /// Binding a shader object to a graphics stage unbinds the current graphics pipeline, and
+    /// binding one to the compute stage unbinds the current compute pipeline. Binding a pipeline
+    /// in turn unbinds all shader objects of the same pipeline type.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the [`shader_object`](crate::device::Features::shader_object) feature is not
+    ///   enabled on the device.
+    /// - Panics if a graphics stage is bound and the queue family of the command buffer does not
+    ///   support graphics operations, or if the compute stage is bound and the queue family
+    ///   does not support compute operations.
+    /// - Panics if the same stage appears more than once in `shaders`.
+    /// - Panics if a shader object was not created for the stage it is bound to.
+    /// - Panics if `self` and any shader object do not belong to the same device.
+    pub fn bind_shaders<I>(&mut self, shaders: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (ShaderStage, Option<Arc<ShaderObject>>)>,
+    {
+        let shaders: SmallVec<[_; 5]> = shaders.into_iter().collect();
+
+        // VUID?
+        assert!(
+            self.device().enabled_features().shader_object,
+            "the shader_object feature must be enabled on the device"
+        );
+
+        for (index, (stage, shader)) in shaders.iter().enumerate() {
+            if *stage == ShaderStage::Compute {
+                assert!(
+                    self.queue_family().supports_compute(),
+                    "the queue family of the command buffer must support compute operations"
+                );
+            } else {
+                assert!(
+                    self.queue_family().supports_graphics(),
+                    "the queue family of the command buffer must support graphics operations"
+                );
+            }
+
+            // VUID?
+            assert!(
+                shaders[..index].iter().all(|(other, _)| other != stage),
+                "the stage {:?} was provided more than once",
+                stage
+            );
+
+            if let Some(shader) = shader {
+                assert_eq!(
+                    shader.device().internal_object(),
+                    self.device().internal_object()
+                );
+
+                // VUID?
+                assert_eq!(
+                    shader.stage(),
+                    *stage,
+                    "the shader object was not created for the stage it is bound to"
+                );
+            }
+        }
+
+        unsafe {
+            self.inner.bind_shaders(shaders);
+        }
+
+        self
+    }
+
     /// Binds vertex buffers for future draw calls.
     ///
     /// # Panics
@@ -434,10 +522,11 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             "the descriptor set slot being bound must be less than the number of sets in pipeline_layout"
         );
 
-        let descriptor_writes: SmallVec<[_; 8]> = descriptor_writes.into_iter().collect();
+        let mut descriptor_writes: SmallVec<[_; 8]> = descriptor_writes.into_iter().collect();
         let descriptor_set_layout = &pipeline_layout.set_layouts()[set_num as usize];
 
-        for write in &descriptor_writes {
+        for write in &mut descriptor_writes {
+            write.resolve_name(descriptor_set_layout).unwrap();
             check_descriptor_write(write, descriptor_set_layout, 0).unwrap();
         }
 
@@ -504,6 +593,7 @@ impl SyncCommandBufferBuilder {
             }
         }
 
+        self.current_state.shaders.remove(&ShaderStage::Compute);
         self.current_state.pipeline_compute = Some(pipeline.clone());
         self.commands.push(Box::new(Cmd { pipeline }));
     }
@@ -533,10 +623,60 @@ impl SyncCommandBufferBuilder {
                 .filter(|(_, d)| !d) // not dynamic
                 .map(|(s, _)| s),
         );
+        self.current_state
+            .shaders
+            .retain(|&stage, _| stage == ShaderStage::Compute);
         self.current_state.pipeline_graphics = Some(pipeline.clone());
         self.commands.push(Box::new(Cmd { pipeline }));
     }
 
+    /// Calls `vkCmdBindShadersEXT` on the builder.
+    #[inline]
+    pub unsafe fn bind_shaders(
+        &mut self,
+        shaders: impl IntoIterator<Item = (ShaderStage, Option<Arc<ShaderObject>>)>,
+    ) {
+        struct Cmd {
+            shaders: SmallVec<[(ShaderStage, Option<Arc<ShaderObject>>); 5]>,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "bind_shaders"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.bind_shaders(
+                    self.shaders
+                        .iter()
+                        .map(|(stage, shader)| (*stage, shader.as_deref())),
+                );
+            }
+        }
+
+        let shaders: SmallVec<[_; 5]> = shaders.into_iter().collect();
+
+        // Binding shader objects disturbs the pipeline bound to the same bind point.
+        for (stage, shader) in &shaders {
+            if *stage == ShaderStage::Compute {
+                self.current_state.pipeline_compute = None;
+            } else {
+                self.current_state.pipeline_graphics = None;
+            }
+
+            match shader {
+                Some(shader) => {
+                    self.current_state.shaders.insert(*stage, shader.clone());
+                }
+                None => {
+                    self.current_state.shaders.remove(stage);
+                }
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { shaders }));
+    }
+
     /// Starts the process of binding vertex buffers. Returns an intermediate struct which can be
     /// used to add the buffers.
     #[inline]
@@ -668,6 +808,10 @@ impl SyncCommandBufferBuilder {
             set_resources.update(write);
         }
 
+        if let Some(usage_inference) = &mut self.usage_inference {
+            usage_inference.add_descriptor_set(layout, set_resources);
+        }
+
         self.commands.push(Box::new(Cmd {
             pipeline_bind_point,
             pipeline_layout,
@@ -746,6 +890,13 @@ impl<'b> SyncCommandBufferBuilderBindDescriptorSets<'b> {
                 .insert(first_set + set_num as u32, SetOrPush::Set(set.clone()));
         }
 
+        if let Some(usage_inference) = &mut self.builder.usage_inference {
+            for set in &self.descriptor_sets {
+                let set = set.as_ref().0;
+                usage_inference.add_descriptor_set(set.layout(), set.resources());
+            }
+        }
+
         self.builder.commands.push(Box::new(Cmd {
             descriptor_sets: self.descriptor_sets,
             pipeline_bind_point,
@@ -879,6 +1030,39 @@ impl UnsafeCommandBufferBuilder {
         );
     }
 
+    /// Calls `vkCmdBindShadersEXT` on the builder.
+    ///
+    /// If the list is empty then the command is automatically ignored.
+    #[inline]
+    pub unsafe fn bind_shaders<'a>(
+        &mut self,
+        shaders: impl IntoIterator<Item = (ShaderStage, Option<&'a ShaderObject>)>,
+    ) {
+        debug_assert!(self.device.enabled_extensions().ext_shader_object);
+
+        let (stages_vk, shaders_vk): (SmallVec<[_; 5]>, SmallVec<[_; 5]>) = shaders
+            .into_iter()
+            .map(|(stage, shader)| {
+                (
+                    ash::vk::ShaderStageFlags::from(stage),
+                    shader.map_or_else(ash::vk::ShaderEXT::null, |shader| shader.internal_object()),
+                )
+            })
+            .unzip();
+
+        if stages_vk.is_empty() {
+            return;
+        }
+
+        let fns = self.device.fns();
+        (fns.ext_shader_object.cmd_bind_shaders_ext)(
+            self.handle,
+            stages_vk.len() as u32,
+            stages_vk.as_ptr(),
+            shaders_vk.as_ptr(),
+        );
+    }
+
     /// Calls `vkCmdBindVertexBuffers` on the builder.
     ///
     /// Does nothing if the list of buffers is empty, as it would be a no-op and isn't a valid
@@ -8,15 +8,23 @@
 // according to those terms.
 
 use crate::{
+    buffer::BufferAccess,
     command_buffer::{
-        synced::{Command, SyncCommandBufferBuilder},
+        synced::{Command, Resource, SyncCommandBufferBuilder, SyncCommandBufferBuilderError},
         sys::UnsafeCommandBufferBuilder,
         AutoCommandBufferBuilder,
     },
     device::DeviceOwned,
     instance::debug::DebugUtilsLabel,
+    sync::{AccessFlags, PipelineMemoryAccess, PipelineStage, PipelineStages},
+    DeviceSize, VulkanObject,
+};
+use std::{
+    error,
+    ffi::{c_void, CString},
+    fmt,
+    sync::Arc,
 };
-use std::{error, ffi::CString, fmt};
 
 /// # Commands for debugging.
 ///
@@ -60,6 +68,11 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             return Err(DebugUtilsError::NotSupportedByQueueFamily);
         }
 
+        // VUID-VkDebugUtilsLabelEXT-pLabelName-parameter
+        if label_info.label_name.contains('\0') {
+            return Err(DebugUtilsError::LabelNameContainsNul);
+        }
+
         Ok(())
     }
 
@@ -142,6 +155,132 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             return Err(DebugUtilsError::NotSupportedByQueueFamily);
         }
 
+        // VUID-VkDebugUtilsLabelEXT-pLabelName-parameter
+        if label_info.label_name.contains('\0') {
+            return Err(DebugUtilsError::LabelNameContainsNul);
+        }
+
+        Ok(())
+    }
+
+    /// Writes `marker` to `dst_buffer` at `dst_offset` once all previous commands have completed
+    /// `stage`.
+    ///
+    /// Markers are a lightweight way to track the progress of the GPU, for example to find out
+    /// which command was executing when a device was lost. Unlike diagnostic checkpoints, they
+    /// can be left enabled in production builds: reading back the buffer after a device loss
+    /// gives the last marker that was written. Marker writes are ordered with respect to each
+    /// other, but are otherwise not synchronized, so the buffer should be host-visible and
+    /// host-coherent if it is read after a device loss.
+    ///
+    /// The marker is written as a transfer operation, and the buffer is tracked like for other
+    /// transfer commands.
+    ///
+    /// The [`amd_buffer_marker`](crate::device::DeviceExtensions::amd_buffer_marker) extension
+    /// must be enabled on the device.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `dst_buffer` was not created from the same device as `self`.
+    #[inline]
+    pub fn write_marker<B>(
+        &mut self,
+        dst_buffer: Arc<B>,
+        dst_offset: DeviceSize,
+        marker: u32,
+        stage: PipelineStage,
+    ) -> Result<&mut Self, WriteMarkerError>
+    where
+        B: BufferAccess + 'static,
+    {
+        self.validate_write_marker(&dst_buffer, dst_offset, stage)?;
+
+        unsafe {
+            self.inner
+                .write_marker(dst_buffer, dst_offset, marker, stage)?;
+        }
+
+        Ok(self)
+    }
+
+    fn validate_write_marker(
+        &self,
+        dst_buffer: &dyn BufferAccess,
+        dst_offset: DeviceSize,
+        stage: PipelineStage,
+    ) -> Result<(), WriteMarkerError> {
+        let device = self.device();
+
+        if !device.enabled_extensions().amd_buffer_marker {
+            return Err(WriteMarkerError::ExtensionNotEnabled {
+                extension: "amd_buffer_marker",
+                reason: "tried to record a buffer marker",
+            });
+        }
+
+        // VUID-vkCmdWriteBufferMarkerAMD-commandBuffer-cmdpool
+        if !(self.queue_family().explicitly_supports_transfers()
+            || self.queue_family().supports_graphics()
+            || self.queue_family().supports_compute())
+        {
+            return Err(WriteMarkerError::NotSupportedByQueueFamily);
+        }
+
+        // VUID-vkCmdWriteBufferMarkerAMD-commonparent
+        assert_eq!(device, dst_buffer.device());
+
+        // VUID-vkCmdWriteBufferMarkerAMD-pipelineStage-parameter
+        // The stage must be one of the original pipeline stages, which fit in 32 bits.
+        if ash::vk::PipelineStageFlags2::from(stage).as_raw() > u32::MAX as u64
+            || !self.queue_family().supports_stage(stage)
+        {
+            return Err(WriteMarkerError::StageNotSupported);
+        }
+
+        match stage {
+            PipelineStage::GeometryShader => {
+                // VUID-vkCmdWriteBufferMarkerAMD-pipelineStage-04074
+                if !device.enabled_features().geometry_shader {
+                    return Err(WriteMarkerError::FeatureNotEnabled {
+                        feature: "geometry_shader",
+                        reason: "stage was GeometryShader",
+                    });
+                }
+            }
+            PipelineStage::TessellationControlShader
+            | PipelineStage::TessellationEvaluationShader => {
+                // VUID-vkCmdWriteBufferMarkerAMD-pipelineStage-04075
+                if !device.enabled_features().tessellation_shader {
+                    return Err(WriteMarkerError::FeatureNotEnabled {
+                        feature: "tessellation_shader",
+                        reason:
+                            "stage was TessellationControlShader or TessellationEvaluationShader",
+                    });
+                }
+            }
+            _ => (),
+        }
+
+        // VUID-vkCmdWriteBufferMarkerAMD-dstBuffer-01799
+        if !dst_buffer.usage().transfer_dst {
+            return Err(WriteMarkerError::MissingUsage);
+        }
+
+        // VUID-vkCmdWriteBufferMarkerAMD-dstOffset-01798
+        if dst_offset + 4 > dst_buffer.size() {
+            return Err(WriteMarkerError::OutOfBufferBounds {
+                offset: dst_offset,
+                buffer_size: dst_buffer.size(),
+            });
+        }
+
+        // VUID-vkCmdWriteBufferMarkerAMD-dstOffset-01801
+        if (dst_buffer.inner().offset + dst_offset) % 4 != 0 {
+            return Err(WriteMarkerError::OffsetNotAligned {
+                offset: dst_buffer.inner().offset + dst_offset,
+            });
+        }
+
         Ok(())
     }
 }
@@ -218,6 +357,74 @@ impl SyncCommandBufferBuilder {
 
         self.commands.push(Box::new(Cmd { label_info }));
     }
+
+    /// Calls `vkCmdWriteBufferMarkerAMD` on the builder.
+    #[inline]
+    pub unsafe fn write_marker(
+        &mut self,
+        dst_buffer: Arc<dyn BufferAccess>,
+        dst_offset: DeviceSize,
+        marker: u32,
+        stage: PipelineStage,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        struct Cmd {
+            dst_buffer: Arc<dyn BufferAccess>,
+            dst_offset: DeviceSize,
+            marker: u32,
+            stage: PipelineStage,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "write_marker"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.write_marker(
+                    self.dst_buffer.as_ref(),
+                    self.dst_offset,
+                    self.marker,
+                    self.stage,
+                );
+            }
+        }
+
+        let resources = [(
+            "dst_buffer".into(),
+            Resource::Buffer {
+                buffer: dst_buffer.clone(),
+                range: dst_offset..dst_offset + 4,
+                memory: PipelineMemoryAccess {
+                    stages: PipelineStages {
+                        transfer: true,
+                        ..PipelineStages::none()
+                    },
+                    access: AccessFlags {
+                        transfer_write: true,
+                        ..AccessFlags::none()
+                    },
+                    exclusive: true,
+                },
+            },
+        )];
+
+        for resource in &resources {
+            self.check_resource_conflicts(resource)?;
+        }
+
+        self.commands.push(Box::new(Cmd {
+            dst_buffer,
+            dst_offset,
+            marker,
+            stage,
+        }));
+
+        for resource in resources {
+            self.add_resource(resource);
+        }
+
+        Ok(())
+    }
 }
 
 impl UnsafeCommandBufferBuilder {
@@ -279,6 +486,41 @@ impl UnsafeCommandBufferBuilder {
         let fns = self.device.instance().fns();
         (fns.ext_debug_utils.cmd_insert_debug_utils_label_ext)(self.handle, &label_info);
     }
+
+    /// Calls `vkCmdSetCheckpointNV` on the builder.
+    ///
+    /// # Safety
+    /// The [`nv_device_diagnostic_checkpoints`](crate::device::DeviceExtensions::nv_device_diagnostic_checkpoints)
+    /// extension must be enabled on the device.
+    #[inline]
+    pub unsafe fn set_checkpoint(&mut self, marker: usize) {
+        let fns = self.device.fns();
+        (fns.nv_device_diagnostic_checkpoints.cmd_set_checkpoint_nv)(
+            self.handle,
+            marker as *const c_void,
+        );
+    }
+
+    /// Calls `vkCmdWriteBufferMarkerAMD` on the builder.
+    #[inline]
+    pub unsafe fn write_marker(
+        &mut self,
+        dst_buffer: &dyn BufferAccess,
+        dst_offset: DeviceSize,
+        marker: u32,
+        stage: PipelineStage,
+    ) {
+        let dst_buffer_inner = dst_buffer.inner();
+
+        let fns = self.device.fns();
+        (fns.amd_buffer_marker.cmd_write_buffer_marker_amd)(
+            self.handle,
+            stage.into(),
+            dst_buffer_inner.buffer.internal_object(),
+            dst_buffer_inner.offset + dst_offset,
+            marker,
+        );
+    }
 }
 
 /// Error that can happen when recording a debug utils command.
@@ -289,6 +531,9 @@ pub enum DebugUtilsError {
         reason: &'static str,
     },
 
+    /// The name of the label contains a nul character.
+    LabelNameContainsNul,
+
     /// The queue family doesn't allow this operation.
     NotSupportedByQueueFamily,
 }
@@ -302,9 +547,164 @@ impl fmt::Display for DebugUtilsError {
             Self::ExtensionNotEnabled { extension, reason } => {
                 write!(f, "the extension {} must be enabled: {}", extension, reason)
             }
+            Self::LabelNameContainsNul => {
+                write!(f, "the name of the label contains a nul character")
+            }
             Self::NotSupportedByQueueFamily => {
                 write!(f, "the queue family doesn't allow this operation")
             }
         }
     }
 }
+
+/// Error that can happen when recording a buffer marker.
+#[derive(Clone, Debug)]
+pub enum WriteMarkerError {
+    SyncCommandBufferBuilderError(SyncCommandBufferBuilderError),
+
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// The destination buffer is missing the `transfer_dst` usage.
+    MissingUsage,
+
+    /// The queue family doesn't allow this operation.
+    NotSupportedByQueueFamily,
+
+    /// The offset of the marker is not a multiple of 4.
+    OffsetNotAligned {
+        offset: DeviceSize,
+    },
+
+    /// The marker doesn't fit in the destination buffer.
+    OutOfBufferBounds {
+        offset: DeviceSize,
+        buffer_size: DeviceSize,
+    },
+
+    /// The provided stage is not supported by the queue family, or is not one of the stages that
+    /// can be used without `synchronization2`.
+    StageNotSupported,
+}
+
+impl error::Error for WriteMarkerError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::SyncCommandBufferBuilderError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WriteMarkerError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::SyncCommandBufferBuilderError(_) => write!(f, "a SyncCommandBufferBuilderError"),
+            Self::ExtensionNotEnabled { extension, reason } => {
+                write!(f, "the extension {} must be enabled: {}", extension, reason)
+            }
+            Self::FeatureNotEnabled { feature, reason } => {
+                write!(f, "the feature {} must be enabled: {}", feature, reason)
+            }
+            Self::MissingUsage => write!(
+                f,
+                "the destination buffer is missing the `transfer_dst` usage",
+            ),
+            Self::NotSupportedByQueueFamily => {
+                write!(f, "the queue family doesn't allow this operation")
+            }
+            Self::OffsetNotAligned { offset } => write!(
+                f,
+                "the offset of the marker ({}) is not a multiple of 4",
+                offset,
+            ),
+            Self::OutOfBufferBounds {
+                offset,
+                buffer_size,
+            } => write!(
+                f,
+                "the marker at offset {} doesn't fit in the destination buffer of size {}",
+                offset, buffer_size,
+            ),
+            Self::StageNotSupported => write!(
+                f,
+                "the provided stage is not supported for writing a buffer marker",
+            ),
+        }
+    }
+}
+
+impl From<SyncCommandBufferBuilderError> for WriteMarkerError {
+    #[inline]
+    fn from(err: SyncCommandBufferBuilderError) -> Self {
+        Self::SyncCommandBufferBuilderError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DebugUtilsError, WriteMarkerError};
+    use crate::{
+        buffer::{BufferUsage, CpuAccessibleBuffer},
+        command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage},
+        instance::debug::DebugUtilsLabel,
+        sync::PipelineStage,
+    };
+
+    #[test]
+    fn extension_not_enabled() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        if device.instance().enabled_extensions().ext_debug_utils {
+            return;
+        }
+
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        match cbb.begin_debug_utils_label(DebugUtilsLabel {
+            label_name: "shadow pass".to_owned(),
+            color: [1.0, 0.0, 0.0, 1.0],
+            ..Default::default()
+        }) {
+            Err(DebugUtilsError::ExtensionNotEnabled { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn write_marker_extension_not_enabled() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        if device.enabled_extensions().amd_buffer_marker {
+            return;
+        }
+
+        let buffer =
+            CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), true, 0u32).unwrap();
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            cbb.write_marker(buffer, 0, 1, PipelineStage::BottomOfPipe),
+            Err(WriteMarkerError::ExtensionNotEnabled { .. })
+        ));
+    }
+}
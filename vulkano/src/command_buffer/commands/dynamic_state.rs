@@ -14,12 +14,15 @@ use crate::{
         AutoCommandBufferBuilder,
     },
     device::DeviceOwned,
+    format::Format,
+    image::SampleCount,
     pipeline::{
         graphics::{
-            color_blend::LogicOp,
+            color_blend::{AttachmentBlend, BlendFactor, ColorComponents, LogicOp},
             depth_stencil::{CompareOp, StencilFaces, StencilOp, StencilOps},
             input_assembly::PrimitiveTopology,
-            rasterization::{CullMode, DepthBias, FrontFace, LineStipple},
+            rasterization::{CullMode, DepthBias, FrontFace, LineStipple, PolygonMode},
+            vertex_input::{VertexInputRate, VertexInputState},
             viewport::{Scissor, Viewport},
         },
         DynamicState,
@@ -41,6 +44,114 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         })
     }
 
+    /// Sets whether dynamic alpha-to-coverage is enabled for future draw calls.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_alpha_to_coverage_enable`](crate::device::Features::extended_dynamic_state3_alpha_to_coverage_enable)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    #[inline]
+    pub fn set_alpha_to_coverage_enable(&mut self, enable: bool) -> &mut Self {
+        self.validate_set_alpha_to_coverage_enable(enable).unwrap();
+
+        unsafe {
+            self.inner.set_alpha_to_coverage_enable(enable);
+        }
+
+        self
+    }
+
+    fn validate_set_alpha_to_coverage_enable(
+        &self,
+        _enable: bool,
+    ) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::AlphaToCoverageEnable) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetAlphaToCoverageEnableEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_alpha_to_coverage_enable
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_alpha_to_coverage_enable",
+                reason: "called set_alpha_to_coverage_enable",
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Sets whether dynamic alpha-to-one is enabled for future draw calls.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_alpha_to_one_enable`](crate::device::Features::extended_dynamic_state3_alpha_to_one_enable)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    /// - Panics if `enable` is `true` and the
+    ///   [`alpha_to_one`](crate::device::Features::alpha_to_one) feature is not enabled on the
+    ///   device.
+    #[inline]
+    pub fn set_alpha_to_one_enable(&mut self, enable: bool) -> &mut Self {
+        self.validate_set_alpha_to_one_enable(enable).unwrap();
+
+        unsafe {
+            self.inner.set_alpha_to_one_enable(enable);
+        }
+
+        self
+    }
+
+    fn validate_set_alpha_to_one_enable(&self, enable: bool) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::AlphaToOneEnable) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetAlphaToOneEnableEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_alpha_to_one_enable
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_alpha_to_one_enable",
+                reason: "called set_alpha_to_one_enable",
+            });
+        }
+
+        // VUID?
+        if enable && !self.device().enabled_features().alpha_to_one {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "alpha_to_one",
+                reason: "enable was true",
+            });
+        }
+
+        Ok(())
+    }
+
     /// Sets the dynamic blend constants for future draw calls.
     ///
     /// # Panics
@@ -73,6 +184,152 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
+    /// Sets whether dynamic blending is enabled for each color attachment for future draw calls.
+    ///
+    /// One value is provided for each attachment, starting at `first_attachment`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_color_blend_enable`](crate::device::Features::extended_dynamic_state3_color_blend_enable)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    pub fn set_color_blend_enable<I>(&mut self, first_attachment: u32, enables: I) -> &mut Self
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        let enables: SmallVec<[bool; 4]> = enables.into_iter().collect();
+        self.validate_set_color_blend_enable(first_attachment, &enables)
+            .unwrap();
+
+        unsafe {
+            self.inner.set_color_blend_enable(first_attachment, enables);
+        }
+
+        self
+    }
+
+    fn validate_set_color_blend_enable(
+        &self,
+        _first_attachment: u32,
+        _enables: &[bool],
+    ) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::ColorBlendEnable) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetColorBlendEnableEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_color_blend_enable
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_color_blend_enable",
+                reason: "called set_color_blend_enable",
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Sets the dynamic blend equations of color attachments for future draw calls.
+    ///
+    /// One equation is provided for each attachment, starting at `first_attachment`. The
+    /// equations are only used for attachments that have blending enabled.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_color_blend_equation`](crate::device::Features::extended_dynamic_state3_color_blend_equation)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    /// - Panics if any of the equations uses a `Src1` blend factor and the
+    ///   [`dual_src_blend`](crate::device::Features::dual_src_blend) feature is not enabled on the
+    ///   device.
+    pub fn set_color_blend_equation<I>(&mut self, first_attachment: u32, equations: I) -> &mut Self
+    where
+        I: IntoIterator<Item = AttachmentBlend>,
+    {
+        let equations: SmallVec<[AttachmentBlend; 4]> = equations.into_iter().collect();
+        self.validate_set_color_blend_equation(first_attachment, &equations)
+            .unwrap();
+
+        unsafe {
+            self.inner
+                .set_color_blend_equation(first_attachment, equations);
+        }
+
+        self
+    }
+
+    fn validate_set_color_blend_equation(
+        &self,
+        _first_attachment: u32,
+        equations: &[AttachmentBlend],
+    ) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::ColorBlendEquation) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetColorBlendEquationEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_color_blend_equation
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_color_blend_equation",
+                reason: "called set_color_blend_equation",
+            });
+        }
+
+        // VUID?
+        if !self.device().enabled_features().dual_src_blend
+            && equations.iter().any(|equation| {
+                [
+                    equation.color_source,
+                    equation.color_destination,
+                    equation.alpha_source,
+                    equation.alpha_destination,
+                ]
+                .into_iter()
+                .any(|blend_factor| {
+                    matches!(
+                        blend_factor,
+                        BlendFactor::Src1Color
+                            | BlendFactor::OneMinusSrc1Color
+                            | BlendFactor::Src1Alpha
+                            | BlendFactor::OneMinusSrc1Alpha
+                    )
+                })
+            })
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "dual_src_blend",
+                reason: "one of the BlendFactor members of equations was set to Src1",
+            });
+        }
+
+        Ok(())
+    }
+
     /// Sets whether dynamic color writes should be enabled for each attachment in the
     /// framebuffer.
     ///
@@ -142,14 +399,72 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
+    /// Sets the dynamic color write masks of color attachments for future draw calls.
+    ///
+    /// One mask is provided for each attachment, starting at `first_attachment`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_color_write_mask`](crate::device::Features::extended_dynamic_state3_color_write_mask)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    pub fn set_color_write_mask<I>(&mut self, first_attachment: u32, masks: I) -> &mut Self
+    where
+        I: IntoIterator<Item = ColorComponents>,
+    {
+        let masks: SmallVec<[ColorComponents; 4]> = masks.into_iter().collect();
+        self.validate_set_color_write_mask(first_attachment, &masks)
+            .unwrap();
+
+        unsafe {
+            self.inner.set_color_write_mask(first_attachment, masks);
+        }
+
+        self
+    }
+
+    fn validate_set_color_write_mask(
+        &self,
+        _first_attachment: u32,
+        _masks: &[ColorComponents],
+    ) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::ColorWriteMask) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetColorWriteMaskEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_color_write_mask
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_color_write_mask",
+                reason: "called set_color_write_mask",
+            });
+        }
+
+        Ok(())
+    }
+
     /// Sets the dynamic cull mode for future draw calls.
     ///
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_cull_mode(&mut self, cull_mode: CullMode) -> &mut Self {
@@ -174,7 +489,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetCullMode-None-03384
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -242,9 +558,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state2`](crate::device::Features::extended_dynamic_state2) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state2`](crate::device::Features::extended_dynamic_state2) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_depth_bias_enable(&mut self, enable: bool) -> &mut Self {
@@ -269,7 +586,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetDepthBiasEnable-None-04872
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state2)
+            || self.device().enabled_features().extended_dynamic_state2
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state2",
@@ -335,9 +653,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_depth_bounds_test_enable(&mut self, enable: bool) -> &mut Self {
@@ -365,7 +684,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetDepthBoundsTestEnable-None-03349
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -376,14 +696,73 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
+    /// Sets whether dynamic depth clamping is enabled for future draw calls.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_depth_clamp_enable`](crate::device::Features::extended_dynamic_state3_depth_clamp_enable)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    /// - Panics if `enable` is `true` and the
+    ///   [`depth_clamp`](crate::device::Features::depth_clamp) feature is not enabled on the
+    ///   device.
+    #[inline]
+    pub fn set_depth_clamp_enable(&mut self, enable: bool) -> &mut Self {
+        self.validate_set_depth_clamp_enable(enable).unwrap();
+
+        unsafe {
+            self.inner.set_depth_clamp_enable(enable);
+        }
+
+        self
+    }
+
+    fn validate_set_depth_clamp_enable(&self, enable: bool) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::DepthClampEnable) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetDepthClampEnableEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_depth_clamp_enable
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_depth_clamp_enable",
+                reason: "called set_depth_clamp_enable",
+            });
+        }
+
+        // VUID?
+        if enable && !self.device().enabled_features().depth_clamp {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "depth_clamp",
+                reason: "enable was true",
+            });
+        }
+
+        Ok(())
+    }
+
     /// Sets the dynamic depth compare op for future draw calls.
     ///
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_depth_compare_op(&mut self, compare_op: CompareOp) -> &mut Self {
@@ -411,7 +790,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetDepthCompareOp-None-03353
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -427,9 +807,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_depth_test_enable(&mut self, enable: bool) -> &mut Self {
@@ -454,7 +835,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetDepthTestEnable-None-03352
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -470,9 +852,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_depth_write_enable(&mut self, enable: bool) -> &mut Self {
@@ -497,7 +880,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetDepthWriteEnable-None-03354
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -585,9 +969,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_front_face(&mut self, face: FrontFace) -> &mut Self {
@@ -612,7 +997,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetFrontFace-None-03383
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -716,9 +1102,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the
+    /// - Panics if neither the
     ///   [`extended_dynamic_state2_logic_op`](crate::device::Features::extended_dynamic_state2_logic_op)
-    ///   feature is not enabled on the device.
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_logic_op(&mut self, logic_op: LogicOp) -> &mut Self {
@@ -742,10 +1129,11 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         }
 
         // VUID-vkCmdSetLogicOpEXT-None-04867
-        if !self
+        if !(self
             .device()
             .enabled_features()
             .extended_dynamic_state2_logic_op
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state2_logic_op",
@@ -756,60 +1144,119 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
-    /// Sets the dynamic number of patch control points for future draw calls.
+    /// Sets whether dynamic logic operations are enabled for future draw calls.
     ///
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the
-    ///   [`extended_dynamic_state2_patch_control_points`](crate::device::Features::extended_dynamic_state2_patch_control_points)
-    ///   feature is not enabled on the device.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_logic_op_enable`](crate::device::Features::extended_dynamic_state3_logic_op_enable)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
-    /// - Panics if `num` is 0.
-    /// - Panics if `num` is greater than the
-    ///   [`max_tessellation_patch_size`](crate::device::Properties::max_tessellation_patch_size)
-    ///   property of the device.
+    /// - If the [`logic_op`](crate::device::Features::logic_op) feature is not enabled, panics if
+    ///   `enable` is `true`.
     #[inline]
-    pub fn set_patch_control_points(&mut self, num: u32) -> &mut Self {
-        self.validate_set_patch_control_points(num).unwrap();
+    pub fn set_logic_op_enable(&mut self, enable: bool) -> &mut Self {
+        self.validate_set_logic_op_enable(enable).unwrap();
 
         unsafe {
-            self.inner.set_patch_control_points(num);
+            self.inner.set_logic_op_enable(enable);
         }
 
         self
     }
 
-    fn validate_set_patch_control_points(&self, num: u32) -> Result<(), SetDynamicStateError> {
-        if self.has_fixed_state(DynamicState::PatchControlPoints) {
+    fn validate_set_logic_op_enable(&self, enable: bool) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::LogicOpEnable) {
             return Err(SetDynamicStateError::PipelineHasFixedState);
         }
 
-        // VUID-vkCmdSetPatchControlPointsEXT-commandBuffer-cmdpool
+        // VUID-vkCmdSetLogicOpEnableEXT-commandBuffer-cmdpool
         if !self.queue_family().supports_graphics() {
             return Err(SetDynamicStateError::NotSupportedByQueueFamily);
         }
 
-        // VUID-vkCmdSetPatchControlPointsEXT-None-04873
-        if !self
+        // VUID?
+        if !(self
             .device()
             .enabled_features()
-            .extended_dynamic_state2_patch_control_points
+            .extended_dynamic_state3_logic_op_enable
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
-                feature: "extended_dynamic_state2_patch_control_points",
-                reason: "called set_patch_control_points",
+                feature: "extended_dynamic_state3_logic_op_enable",
+                reason: "called set_logic_op_enable",
             });
         }
 
-        // VUID-vkCmdSetPatchControlPointsEXT-patchControlPoints-04874
-        assert!(num > 0, "num must be greater than 0");
+        // VUID?
+        if enable && !self.device().enabled_features().logic_op {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "logic_op",
+                reason: "enable was true",
+            });
+        }
 
-        // VUID-vkCmdSetPatchControlPointsEXT-patchControlPoints-04874
-        if num
-            > self
-                .device()
-                .physical_device()
+        Ok(())
+    }
+
+    /// Sets the dynamic number of patch control points for future draw calls.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state2_patch_control_points`](crate::device::Features::extended_dynamic_state2_patch_control_points)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    /// - Panics if `num` is 0.
+    /// - Panics if `num` is greater than the
+    ///   [`max_tessellation_patch_size`](crate::device::Properties::max_tessellation_patch_size)
+    ///   property of the device.
+    #[inline]
+    pub fn set_patch_control_points(&mut self, num: u32) -> &mut Self {
+        self.validate_set_patch_control_points(num).unwrap();
+
+        unsafe {
+            self.inner.set_patch_control_points(num);
+        }
+
+        self
+    }
+
+    fn validate_set_patch_control_points(&self, num: u32) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::PatchControlPoints) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetPatchControlPointsEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID-vkCmdSetPatchControlPointsEXT-None-04873
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state2_patch_control_points
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state2_patch_control_points",
+                reason: "called set_patch_control_points",
+            });
+        }
+
+        // VUID-vkCmdSetPatchControlPointsEXT-patchControlPoints-04874
+        assert!(num > 0, "num must be greater than 0");
+
+        // VUID-vkCmdSetPatchControlPointsEXT-patchControlPoints-04874
+        if num
+            > self
+                .device()
+                .physical_device()
                 .properties()
                 .max_tessellation_patch_size
         {
@@ -826,14 +1273,78 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
+    /// Sets the dynamic polygon mode for future draw calls.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_polygon_mode`](crate::device::Features::extended_dynamic_state3_polygon_mode)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    /// - Panics if `polygon_mode` is not `Fill` and the
+    ///   [`fill_mode_non_solid`](crate::device::Features::fill_mode_non_solid) feature is not
+    ///   enabled on the device.
+    #[inline]
+    pub fn set_polygon_mode(&mut self, polygon_mode: PolygonMode) -> &mut Self {
+        self.validate_set_polygon_mode(polygon_mode).unwrap();
+
+        unsafe {
+            self.inner.set_polygon_mode(polygon_mode);
+        }
+
+        self
+    }
+
+    fn validate_set_polygon_mode(
+        &self,
+        polygon_mode: PolygonMode,
+    ) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::PolygonMode) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetPolygonModeEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_polygon_mode
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_polygon_mode",
+                reason: "called set_polygon_mode",
+            });
+        }
+
+        // VUID?
+        if polygon_mode != PolygonMode::Fill
+            && !self.device().enabled_features().fill_mode_non_solid
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "fill_mode_non_solid",
+                reason: "polygon_mode was not Fill",
+            });
+        }
+
+        Ok(())
+    }
+
     /// Sets whether dynamic primitive restart is enabled for future draw calls.
     ///
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state2`](crate::device::Features::extended_dynamic_state2) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state2`](crate::device::Features::extended_dynamic_state2) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_primitive_restart_enable(&mut self, enable: bool) -> &mut Self {
@@ -861,7 +1372,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetPrimitiveRestartEnable-None-04866
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state2)
+            || self.device().enabled_features().extended_dynamic_state2
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state2",
@@ -877,9 +1389,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     /// - If the [`geometry_shader`](crate::device::Features::geometry_shader) feature is not
     ///   enabled, panics if `topology` is a `WithAdjacency` topology.
@@ -911,7 +1424,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetPrimitiveTopology-None-03347
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -948,14 +1462,65 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
+    /// Sets the dynamic number of rasterization samples for future draw calls.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_rasterization_samples`](crate::device::Features::extended_dynamic_state3_rasterization_samples)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    #[inline]
+    pub fn set_rasterization_samples(&mut self, samples: SampleCount) -> &mut Self {
+        self.validate_set_rasterization_samples(samples).unwrap();
+
+        unsafe {
+            self.inner.set_rasterization_samples(samples);
+        }
+
+        self
+    }
+
+    fn validate_set_rasterization_samples(
+        &self,
+        _samples: SampleCount,
+    ) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::RasterizationSamples) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetRasterizationSamplesEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_rasterization_samples
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_rasterization_samples",
+                reason: "called set_rasterization_samples",
+            });
+        }
+
+        Ok(())
+    }
+
     /// Sets whether dynamic rasterizer discard is enabled for future draw calls.
     ///
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state2`](crate::device::Features::extended_dynamic_state2) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state2`](crate::device::Features::extended_dynamic_state2) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_rasterizer_discard_enable(&mut self, enable: bool) -> &mut Self {
@@ -983,7 +1548,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetRasterizerDiscardEnable-None-04871
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state2)
+            || self.device().enabled_features().extended_dynamic_state2
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state2",
@@ -994,6 +1560,60 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
+    /// Sets the dynamic sample mask for future draw calls.
+    ///
+    /// `samples` is the number of samples that `mask` covers. Only the first word of `mask` is
+    /// used unless `samples` is [`SampleCount::Sample64`].
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`extended_dynamic_state3_sample_mask`](crate::device::Features::extended_dynamic_state3_sample_mask)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    #[inline]
+    pub fn set_sample_mask(&mut self, samples: SampleCount, mask: [u32; 2]) -> &mut Self {
+        self.validate_set_sample_mask(samples, mask).unwrap();
+
+        unsafe {
+            self.inner.set_sample_mask(samples, mask);
+        }
+
+        self
+    }
+
+    fn validate_set_sample_mask(
+        &self,
+        _samples: SampleCount,
+        _mask: [u32; 2],
+    ) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::SampleMask) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetSampleMaskEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self
+            .device()
+            .enabled_features()
+            .extended_dynamic_state3_sample_mask
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "extended_dynamic_state3_sample_mask",
+                reason: "called set_sample_mask",
+            });
+        }
+
+        Ok(())
+    }
+
     /// Sets the dynamic scissors for future draw calls.
     ///
     /// # Panics
@@ -1068,9 +1688,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     /// - Panics if the highest scissor slot being set is greater than the
     ///   [`max_viewports`](crate::device::Properties::max_viewports) device property.
@@ -1106,7 +1727,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetScissorWithCount-None-03396
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -1176,9 +1798,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_stencil_op(
@@ -1219,7 +1842,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetStencilOp-None-03351
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -1269,9 +1893,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     #[inline]
     pub fn set_stencil_test_enable(&mut self, enable: bool) -> &mut Self {
@@ -1296,7 +1921,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetStencilTestEnable-None-03350
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -1341,6 +1967,178 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(())
     }
 
+    /// Sets the dynamic vertex input state for future draw calls.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the queue family of the command buffer does not support graphics operations.
+    /// - Panics if neither the
+    ///   [`vertex_input_dynamic_state`](crate::device::Features::vertex_input_dynamic_state)
+    ///   nor the [`shader_object`](crate::device::Features::shader_object) feature is enabled
+    ///   on the device.
+    /// - Panics if the currently bound graphics pipeline already contains this state internally.
+    /// - Panics if `vertex_input_state` exceeds any of the vertex input limits of the device, or
+    ///   if an attribute refers to a binding that is not present.
+    /// - Panics if an attribute's format does not support being used as a vertex buffer format.
+    pub fn set_vertex_input(&mut self, vertex_input_state: VertexInputState) -> &mut Self {
+        self.validate_set_vertex_input(&vertex_input_state).unwrap();
+
+        unsafe {
+            self.inner.set_vertex_input(vertex_input_state);
+        }
+
+        self
+    }
+
+    fn validate_set_vertex_input(
+        &self,
+        vertex_input_state: &VertexInputState,
+    ) -> Result<(), SetDynamicStateError> {
+        if self.has_fixed_state(DynamicState::VertexInput) {
+            return Err(SetDynamicStateError::PipelineHasFixedState);
+        }
+
+        // VUID-vkCmdSetVertexInputEXT-commandBuffer-cmdpool
+        if !self.queue_family().supports_graphics() {
+            return Err(SetDynamicStateError::NotSupportedByQueueFamily);
+        }
+
+        // VUID?
+        if !(self.device().enabled_features().vertex_input_dynamic_state
+            || self.device().enabled_features().shader_object)
+        {
+            return Err(SetDynamicStateError::FeatureNotEnabled {
+                feature: "vertex_input_dynamic_state",
+                reason: "called set_vertex_input",
+            });
+        }
+
+        let VertexInputState {
+            bindings,
+            attributes,
+        } = vertex_input_state;
+        let device = self.device();
+        let properties = device.physical_device().properties();
+
+        // VUID?
+        if bindings.len() > properties.max_vertex_input_bindings as usize {
+            return Err(SetDynamicStateError::MaxVertexInputBindingsExceeded {
+                provided: bindings.len() as u32,
+                max: properties.max_vertex_input_bindings,
+            });
+        }
+
+        for (&binding, binding_desc) in bindings {
+            // VUID?
+            if binding >= properties.max_vertex_input_bindings {
+                return Err(SetDynamicStateError::MaxVertexInputBindingsExceeded {
+                    provided: binding + 1,
+                    max: properties.max_vertex_input_bindings,
+                });
+            }
+
+            // VUID?
+            if binding_desc.stride > properties.max_vertex_input_binding_stride {
+                return Err(SetDynamicStateError::MaxVertexInputBindingStrideExceeded {
+                    binding,
+                    provided: binding_desc.stride,
+                    max: properties.max_vertex_input_binding_stride,
+                });
+            }
+
+            match binding_desc.input_rate {
+                VertexInputRate::Instance { divisor } if divisor != 1 => {
+                    // VUID?
+                    if !device
+                        .enabled_features()
+                        .vertex_attribute_instance_rate_divisor
+                    {
+                        return Err(SetDynamicStateError::FeatureNotEnabled {
+                            feature: "vertex_attribute_instance_rate_divisor",
+                            reason: "VertexInputRate::Instance::divisor was not 1",
+                        });
+                    }
+
+                    // VUID?
+                    if divisor == 0
+                        && !device
+                            .enabled_features()
+                            .vertex_attribute_instance_rate_zero_divisor
+                    {
+                        return Err(SetDynamicStateError::FeatureNotEnabled {
+                            feature: "vertex_attribute_instance_rate_zero_divisor",
+                            reason: "VertexInputRate::Instance::divisor was 0",
+                        });
+                    }
+
+                    // VUID?
+                    if divisor > properties.max_vertex_attrib_divisor.unwrap() {
+                        return Err(SetDynamicStateError::MaxVertexAttribDivisorExceeded {
+                            binding,
+                            provided: divisor,
+                            max: properties.max_vertex_attrib_divisor.unwrap(),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // VUID?
+        if attributes.len() > properties.max_vertex_input_attributes as usize {
+            return Err(SetDynamicStateError::MaxVertexInputAttributesExceeded {
+                provided: attributes.len() as u32,
+                max: properties.max_vertex_input_attributes,
+            });
+        }
+
+        for (&location, attribute_desc) in attributes {
+            // VUID?
+            if location >= properties.max_vertex_input_attributes {
+                return Err(SetDynamicStateError::MaxVertexInputAttributesExceeded {
+                    provided: location + 1,
+                    max: properties.max_vertex_input_attributes,
+                });
+            }
+
+            // VUID?
+            if !bindings.contains_key(&attribute_desc.binding) {
+                return Err(SetDynamicStateError::VertexInputAttributeInvalidBinding {
+                    location,
+                    binding: attribute_desc.binding,
+                });
+            }
+
+            // VUID?
+            if attribute_desc.offset > properties.max_vertex_input_attribute_offset {
+                return Err(
+                    SetDynamicStateError::MaxVertexInputAttributeOffsetExceeded {
+                        location,
+                        provided: attribute_desc.offset,
+                        max: properties.max_vertex_input_attribute_offset,
+                    },
+                );
+            }
+
+            // VUID?
+            if !device
+                .physical_device()
+                .format_properties(attribute_desc.format)
+                .buffer_features
+                .vertex_buffer
+            {
+                return Err(
+                    SetDynamicStateError::VertexInputAttributeUnsupportedFormat {
+                        location,
+                        format: attribute_desc.format,
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     /// Sets the dynamic viewports for future draw calls.
     ///
     /// # Panics
@@ -1416,9 +2214,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// # Panics
     ///
     /// - Panics if the queue family of the command buffer does not support graphics operations.
-    /// - Panics if the device API version is less than 1.3 and the
-    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) feature is
-    ///   not enabled on the device.
+    /// - Panics if the device API version is less than 1.3 and neither the
+    ///   [`extended_dynamic_state`](crate::device::Features::extended_dynamic_state) nor the
+    ///   [`shader_object`](crate::device::Features::shader_object) feature is enabled on the
+    ///   device.
     /// - Panics if the currently bound graphics pipeline already contains this state internally.
     /// - Panics if the highest viewport slot being set is greater than the
     ///   [`max_viewports`](crate::device::Properties::max_viewports) device property.
@@ -1454,7 +2253,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         // VUID-vkCmdSetViewportWithCount-None-03393
         if !(self.device().api_version() >= Version::V1_3
-            || self.device().enabled_features().extended_dynamic_state)
+            || self.device().enabled_features().extended_dynamic_state
+            || self.device().enabled_features().shader_object)
         {
             return Err(SetDynamicStateError::FeatureNotEnabled {
                 feature: "extended_dynamic_state",
@@ -1483,6 +2283,48 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 }
 
 impl SyncCommandBufferBuilder {
+    /// Calls `vkCmdSetAlphaToCoverageEnableEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_alpha_to_coverage_enable(&mut self, enable: bool) {
+        struct Cmd {
+            enable: bool,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_alpha_to_coverage_enable"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_alpha_to_coverage_enable(self.enable);
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { enable }));
+        self.current_state.alpha_to_coverage_enable = Some(enable);
+    }
+
+    /// Calls `vkCmdSetAlphaToOneEnableEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_alpha_to_one_enable(&mut self, enable: bool) {
+        struct Cmd {
+            enable: bool,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_alpha_to_one_enable"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_alpha_to_one_enable(self.enable);
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { enable }));
+        self.current_state.alpha_to_one_enable = Some(enable);
+    }
+
     /// Calls `vkCmdSetBlendConstants` on the builder.
     #[inline]
     pub unsafe fn set_blend_constants(&mut self, constants: [f32; 4]) {
@@ -1504,6 +2346,83 @@ impl SyncCommandBufferBuilder {
         self.current_state.blend_constants = Some(constants);
     }
 
+    /// Calls `vkCmdSetColorBlendEnableEXT` on the builder.
+    ///
+    /// If the list is empty then the command is automatically ignored.
+    #[inline]
+    pub unsafe fn set_color_blend_enable<I>(&mut self, first_attachment: u32, enables: I)
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        struct Cmd {
+            first_attachment: u32,
+            enables: Mutex<SmallVec<[bool; 4]>>,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_color_blend_enable"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_color_blend_enable(self.first_attachment, self.enables.lock().drain(..));
+            }
+        }
+
+        let enables: SmallVec<[bool; 4]> = enables.into_iter().collect();
+
+        for (num, enable) in enables.iter().enumerate() {
+            let num = num as u32 + first_attachment;
+            self.current_state.color_blend_enable.insert(num, *enable);
+        }
+
+        self.commands.push(Box::new(Cmd {
+            first_attachment,
+            enables: Mutex::new(enables),
+        }));
+    }
+
+    /// Calls `vkCmdSetColorBlendEquationEXT` on the builder.
+    ///
+    /// If the list is empty then the command is automatically ignored.
+    #[inline]
+    pub unsafe fn set_color_blend_equation<I>(&mut self, first_attachment: u32, equations: I)
+    where
+        I: IntoIterator<Item = AttachmentBlend>,
+    {
+        struct Cmd {
+            first_attachment: u32,
+            equations: Mutex<SmallVec<[AttachmentBlend; 4]>>,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_color_blend_equation"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_color_blend_equation(
+                    self.first_attachment,
+                    self.equations.lock().drain(..),
+                );
+            }
+        }
+
+        let equations: SmallVec<[AttachmentBlend; 4]> = equations.into_iter().collect();
+
+        for (num, equation) in equations.iter().enumerate() {
+            let num = num as u32 + first_attachment;
+            self.current_state
+                .color_blend_equation
+                .insert(num, *equation);
+        }
+
+        self.commands.push(Box::new(Cmd {
+            first_attachment,
+            equations: Mutex::new(equations),
+        }));
+    }
+
     /// Calls `vkCmdSetColorWriteEnableEXT` on the builder.
     ///
     /// If the list is empty then the command is automatically ignored.
@@ -1529,10 +2448,46 @@ impl SyncCommandBufferBuilder {
             }
         }
 
-        let enables: SmallVec<[bool; 4]> = enables.into_iter().collect();
-        self.current_state.color_write_enable = Some(enables.clone());
+        let enables: SmallVec<[bool; 4]> = enables.into_iter().collect();
+        self.current_state.color_write_enable = Some(enables.clone());
+        self.commands.push(Box::new(Cmd {
+            enables: Mutex::new(Some(enables)),
+        }));
+    }
+
+    /// Calls `vkCmdSetColorWriteMaskEXT` on the builder.
+    ///
+    /// If the list is empty then the command is automatically ignored.
+    #[inline]
+    pub unsafe fn set_color_write_mask<I>(&mut self, first_attachment: u32, masks: I)
+    where
+        I: IntoIterator<Item = ColorComponents>,
+    {
+        struct Cmd {
+            first_attachment: u32,
+            masks: Mutex<SmallVec<[ColorComponents; 4]>>,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_color_write_mask"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_color_write_mask(self.first_attachment, self.masks.lock().drain(..));
+            }
+        }
+
+        let masks: SmallVec<[ColorComponents; 4]> = masks.into_iter().collect();
+
+        for (num, mask) in masks.iter().enumerate() {
+            let num = num as u32 + first_attachment;
+            self.current_state.color_write_mask.insert(num, *mask);
+        }
+
         self.commands.push(Box::new(Cmd {
-            enables: Mutex::new(Some(enables)),
+            first_attachment,
+            masks: Mutex::new(masks),
         }));
     }
 
@@ -1653,6 +2608,27 @@ impl SyncCommandBufferBuilder {
         self.current_state.depth_bounds_test_enable = Some(enable);
     }
 
+    /// Calls `vkCmdSetDepthClampEnableEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_depth_clamp_enable(&mut self, enable: bool) {
+        struct Cmd {
+            enable: bool,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_depth_clamp_enable"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_depth_clamp_enable(self.enable);
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { enable }));
+        self.current_state.depth_clamp_enable = Some(enable);
+    }
+
     /// Calls `vkCmdSetDepthCompareOpEXT` on the builder.
     #[inline]
     pub unsafe fn set_depth_compare_op(&mut self, compare_op: CompareOp) {
@@ -1839,6 +2815,27 @@ impl SyncCommandBufferBuilder {
         self.current_state.logic_op = Some(logic_op);
     }
 
+    /// Calls `vkCmdSetLogicOpEnableEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_logic_op_enable(&mut self, enable: bool) {
+        struct Cmd {
+            enable: bool,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_logic_op_enable"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_logic_op_enable(self.enable);
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { enable }));
+        self.current_state.logic_op_enable = Some(enable);
+    }
+
     /// Calls `vkCmdSetPatchControlPointsEXT` on the builder.
     #[inline]
     pub unsafe fn set_patch_control_points(&mut self, num: u32) {
@@ -1860,6 +2857,27 @@ impl SyncCommandBufferBuilder {
         self.current_state.patch_control_points = Some(num);
     }
 
+    /// Calls `vkCmdSetPolygonModeEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_polygon_mode(&mut self, polygon_mode: PolygonMode) {
+        struct Cmd {
+            polygon_mode: PolygonMode,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_polygon_mode"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_polygon_mode(self.polygon_mode);
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { polygon_mode }));
+        self.current_state.polygon_mode = Some(polygon_mode);
+    }
+
     /// Calls `vkCmdSetPrimitiveRestartEnableEXT` on the builder.
     #[inline]
     pub unsafe fn set_primitive_restart_enable(&mut self, enable: bool) {
@@ -1902,6 +2920,27 @@ impl SyncCommandBufferBuilder {
         self.current_state.primitive_topology = Some(topology);
     }
 
+    /// Calls `vkCmdSetRasterizationSamplesEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_rasterization_samples(&mut self, samples: SampleCount) {
+        struct Cmd {
+            samples: SampleCount,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_rasterization_samples"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_rasterization_samples(self.samples);
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { samples }));
+        self.current_state.rasterization_samples = Some(samples);
+    }
+
     /// Calls `vkCmdSetRasterizerDiscardEnableEXT` on the builder.
     #[inline]
     pub unsafe fn set_rasterizer_discard_enable(&mut self, enable: bool) {
@@ -2103,6 +3142,28 @@ impl SyncCommandBufferBuilder {
         }
     }
 
+    /// Calls `vkCmdSetSampleMaskEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_sample_mask(&mut self, samples: SampleCount, mask: [u32; 2]) {
+        struct Cmd {
+            samples: SampleCount,
+            mask: [u32; 2],
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_sample_mask"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_sample_mask(self.samples, self.mask);
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { samples, mask }));
+        self.current_state.sample_mask = Some((samples, mask));
+    }
+
     /// Calls `vkCmdSetScissor` on the builder.
     ///
     /// If the list is empty then the command is automatically ignored.
@@ -2168,6 +3229,27 @@ impl SyncCommandBufferBuilder {
         }));
     }
 
+    /// Calls `vkCmdSetVertexInputEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_vertex_input(&mut self, vertex_input_state: VertexInputState) {
+        struct Cmd {
+            vertex_input_state: VertexInputState,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "set_vertex_input"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.set_vertex_input(&self.vertex_input_state);
+            }
+        }
+
+        self.current_state.vertex_input = Some(vertex_input_state.clone());
+        self.commands.push(Box::new(Cmd { vertex_input_state }));
+    }
+
     /// Calls `vkCmdSetViewport` on the builder.
     ///
     /// If the list is empty then the command is automatically ignored.
@@ -2235,6 +3317,30 @@ impl SyncCommandBufferBuilder {
 }
 
 impl UnsafeCommandBufferBuilder {
+    /// Calls `vkCmdSetAlphaToCoverageEnableEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_alpha_to_coverage_enable(&mut self, enable: bool) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3
+            .cmd_set_alpha_to_coverage_enable_ext)(self.handle, enable as ash::vk::Bool32);
+    }
+
+    /// Calls `vkCmdSetAlphaToOneEnableEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_alpha_to_one_enable(&mut self, enable: bool) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3
+            .cmd_set_alpha_to_one_enable_ext)(self.handle, enable as ash::vk::Bool32);
+    }
+
     /// Calls `vkCmdSetBlendConstants` on the builder.
     #[inline]
     pub unsafe fn set_blend_constants(&mut self, constants: [f32; 4]) {
@@ -2242,6 +3348,70 @@ impl UnsafeCommandBufferBuilder {
         (fns.v1_0.cmd_set_blend_constants)(self.handle, &constants);
     }
 
+    /// Calls `vkCmdSetColorBlendEnableEXT` on the builder.
+    ///
+    /// If the list is empty then the command is automatically ignored.
+    #[inline]
+    pub unsafe fn set_color_blend_enable(
+        &mut self,
+        first_attachment: u32,
+        enables: impl IntoIterator<Item = bool>,
+    ) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+
+        let enables = enables
+            .into_iter()
+            .map(|v| v as ash::vk::Bool32)
+            .collect::<SmallVec<[_; 4]>>();
+        if enables.is_empty() {
+            return;
+        }
+
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3
+            .cmd_set_color_blend_enable_ext)(
+            self.handle,
+            first_attachment,
+            enables.len() as u32,
+            enables.as_ptr(),
+        );
+    }
+
+    /// Calls `vkCmdSetColorBlendEquationEXT` on the builder.
+    ///
+    /// If the list is empty then the command is automatically ignored.
+    #[inline]
+    pub unsafe fn set_color_blend_equation(
+        &mut self,
+        first_attachment: u32,
+        equations: impl IntoIterator<Item = AttachmentBlend>,
+    ) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+
+        let equations = equations
+            .into_iter()
+            .map(|v| v.into())
+            .collect::<SmallVec<[ash::vk::ColorBlendEquationEXT; 4]>>();
+        if equations.is_empty() {
+            return;
+        }
+
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3
+            .cmd_set_color_blend_equation_ext)(
+            self.handle,
+            first_attachment,
+            equations.len() as u32,
+            equations.as_ptr(),
+        );
+    }
+
     /// Calls `vkCmdSetColorWriteEnableEXT` on the builder.
     ///
     /// If the list is empty then the command is automatically ignored.
@@ -2265,6 +3435,37 @@ impl UnsafeCommandBufferBuilder {
         );
     }
 
+    /// Calls `vkCmdSetColorWriteMaskEXT` on the builder.
+    ///
+    /// If the list is empty then the command is automatically ignored.
+    #[inline]
+    pub unsafe fn set_color_write_mask(
+        &mut self,
+        first_attachment: u32,
+        masks: impl IntoIterator<Item = ColorComponents>,
+    ) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+
+        let masks = masks
+            .into_iter()
+            .map(|v| v.into())
+            .collect::<SmallVec<[ash::vk::ColorComponentFlags; 4]>>();
+        if masks.is_empty() {
+            return;
+        }
+
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3.cmd_set_color_write_mask_ext)(
+            self.handle,
+            first_attachment,
+            masks.len() as u32,
+            masks.as_ptr(),
+        );
+    }
+
     /// Calls `vkCmdSetCullModeEXT` on the builder.
     #[inline]
     pub unsafe fn set_cull_mode(&mut self, cull_mode: CullMode) {
@@ -2273,7 +3474,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_cull_mode)(self.handle, cull_mode.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state.cmd_set_cull_mode_ext)(self.handle, cull_mode.into());
         }
     }
@@ -2293,7 +3497,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_depth_bias_enable)(self.handle, enable.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state2);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state2
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state2
                 .cmd_set_depth_bias_enable_ext)(self.handle, enable.into());
         }
@@ -2314,12 +3521,27 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_depth_bounds_test_enable)(self.handle, enable.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state
                 .cmd_set_depth_bounds_test_enable_ext)(self.handle, enable.into());
         }
     }
 
+    /// Calls `vkCmdSetDepthClampEnableEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_depth_clamp_enable(&mut self, enable: bool) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3
+            .cmd_set_depth_clamp_enable_ext)(self.handle, enable as ash::vk::Bool32);
+    }
+
     /// Calls `vkCmdSetDepthCompareOpEXT` on the builder.
     #[inline]
     pub unsafe fn set_depth_compare_op(&mut self, compare_op: CompareOp) {
@@ -2328,7 +3550,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_depth_compare_op)(self.handle, compare_op.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state.cmd_set_depth_compare_op_ext)(
                 self.handle,
                 compare_op.into(),
@@ -2344,7 +3569,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_depth_test_enable)(self.handle, enable.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state.cmd_set_depth_test_enable_ext)(
                 self.handle,
                 enable.into(),
@@ -2360,7 +3588,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_depth_write_enable)(self.handle, enable.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state
                 .cmd_set_depth_write_enable_ext)(self.handle, enable.into());
         }
@@ -2412,7 +3643,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_front_face)(self.handle, face.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state.cmd_set_front_face_ext)(self.handle, face.into());
         }
     }
@@ -2435,26 +3669,59 @@ impl UnsafeCommandBufferBuilder {
     /// Calls `vkCmdSetLogicOpEXT` on the builder.
     #[inline]
     pub unsafe fn set_logic_op(&mut self, logic_op: LogicOp) {
-        debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state2);
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state2
+                || self.device.enabled_extensions().ext_shader_object
+        );
         debug_assert!(
             self.device
                 .enabled_features()
                 .extended_dynamic_state2_logic_op
+                || self.device.enabled_features().shader_object
         );
         let fns = self.device.fns();
 
         (fns.ext_extended_dynamic_state2.cmd_set_logic_op_ext)(self.handle, logic_op.into());
     }
 
+    /// Calls `vkCmdSetLogicOpEnableEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_logic_op_enable(&mut self, enable: bool) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3.cmd_set_logic_op_enable_ext)(self.handle, enable.into());
+    }
+
     /// Calls `vkCmdSetPatchControlPointsEXT` on the builder.
     #[inline]
     pub unsafe fn set_patch_control_points(&mut self, num: u32) {
-        debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state2);
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state2
+                || self.device.enabled_extensions().ext_shader_object
+        );
         let fns = self.device.fns();
         (fns.ext_extended_dynamic_state2
             .cmd_set_patch_control_points_ext)(self.handle, num);
     }
 
+    /// Calls `vkCmdSetPolygonModeEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_polygon_mode(&mut self, polygon_mode: PolygonMode) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3.cmd_set_polygon_mode_ext)(
+            self.handle,
+            polygon_mode.into(),
+        );
+    }
+
     /// Calls `vkCmdSetPrimitiveRestartEnableEXT` on the builder.
     #[inline]
     pub unsafe fn set_primitive_restart_enable(&mut self, enable: bool) {
@@ -2463,7 +3730,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_primitive_restart_enable)(self.handle, enable.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state2);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state2
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state2
                 .cmd_set_primitive_restart_enable_ext)(self.handle, enable.into());
         }
@@ -2477,12 +3747,28 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_primitive_topology)(self.handle, topology.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state
                 .cmd_set_primitive_topology_ext)(self.handle, topology.into());
         }
     }
 
+    /// Calls `vkCmdSetRasterizationSamplesEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_rasterization_samples(&mut self, samples: SampleCount) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3
+            .cmd_set_rasterization_samples_ext)(self.handle, samples.into());
+    }
+
     /// Calls `vkCmdSetRasterizerDiscardEnableEXT` on the builder.
     #[inline]
     pub unsafe fn set_rasterizer_discard_enable(&mut self, enable: bool) {
@@ -2491,7 +3777,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_rasterizer_discard_enable)(self.handle, enable.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state2);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state2
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state2
                 .cmd_set_rasterizer_discard_enable_ext)(self.handle, enable.into());
         }
@@ -2526,7 +3815,10 @@ impl UnsafeCommandBufferBuilder {
                 compare_op.into(),
             );
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state.cmd_set_stencil_op_ext)(
                 self.handle,
                 face_mask.into(),
@@ -2553,7 +3845,10 @@ impl UnsafeCommandBufferBuilder {
         if self.device.api_version() >= Version::V1_3 {
             (fns.v1_3.cmd_set_stencil_test_enable)(self.handle, enable.into());
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state
                 .cmd_set_stencil_test_enable_ext)(self.handle, enable.into());
         }
@@ -2566,6 +3861,22 @@ impl UnsafeCommandBufferBuilder {
         (fns.v1_0.cmd_set_stencil_write_mask)(self.handle, face_mask.into(), write_mask);
     }
 
+    /// Calls `vkCmdSetSampleMaskEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_sample_mask(&mut self, samples: SampleCount, mask: [u32; 2]) {
+        debug_assert!(
+            self.device.enabled_extensions().ext_extended_dynamic_state3
+                || self.device.enabled_extensions().ext_shader_object
+        );
+
+        let fns = self.device.fns();
+        (fns.ext_extended_dynamic_state3.cmd_set_sample_mask_ext)(
+            self.handle,
+            samples.into(),
+            mask.as_ptr(),
+        );
+    }
+
     /// Calls `vkCmdSetScissor` on the builder.
     ///
     /// If the list is empty then the command is automatically ignored.
@@ -2614,7 +3925,10 @@ impl UnsafeCommandBufferBuilder {
                 scissors.as_ptr(),
             );
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state
                 .cmd_set_scissor_with_count_ext)(
                 self.handle,
@@ -2624,6 +3938,60 @@ impl UnsafeCommandBufferBuilder {
         }
     }
 
+    /// Calls `vkCmdSetVertexInputEXT` on the builder.
+    #[inline]
+    pub unsafe fn set_vertex_input(&mut self, vertex_input_state: &VertexInputState) {
+        debug_assert!(
+            self.device
+                .enabled_extensions()
+                .ext_vertex_input_dynamic_state
+                || self.device.enabled_extensions().ext_shader_object
+        );
+
+        let bindings_vk: SmallVec<[_; 8]> = vertex_input_state
+            .bindings
+            .iter()
+            .map(|(&binding, binding_desc)| {
+                let (input_rate, divisor) = match binding_desc.input_rate {
+                    VertexInputRate::Vertex => (ash::vk::VertexInputRate::VERTEX, 1),
+                    VertexInputRate::Instance { divisor } => {
+                        (ash::vk::VertexInputRate::INSTANCE, divisor)
+                    }
+                };
+
+                ash::vk::VertexInputBindingDescription2EXT {
+                    binding,
+                    stride: binding_desc.stride,
+                    input_rate,
+                    divisor,
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let attributes_vk: SmallVec<[_; 8]> = vertex_input_state
+            .attributes
+            .iter()
+            .map(
+                |(&location, attribute_desc)| ash::vk::VertexInputAttributeDescription2EXT {
+                    location,
+                    binding: attribute_desc.binding,
+                    format: attribute_desc.format.into(),
+                    offset: attribute_desc.offset,
+                    ..Default::default()
+                },
+            )
+            .collect();
+
+        let fns = self.device.fns();
+        (fns.ext_vertex_input_dynamic_state.cmd_set_vertex_input_ext)(
+            self.handle,
+            bindings_vk.len() as u32,
+            bindings_vk.as_ptr(),
+            attributes_vk.len() as u32,
+            attributes_vk.as_ptr(),
+        );
+    }
+
     /// Calls `vkCmdSetViewport` on the builder.
     ///
     /// If the list is empty then the command is automatically ignored.
@@ -2675,7 +4043,10 @@ impl UnsafeCommandBufferBuilder {
                 viewports.as_ptr(),
             );
         } else {
-            debug_assert!(self.device.enabled_extensions().ext_extended_dynamic_state);
+            debug_assert!(
+                self.device.enabled_extensions().ext_extended_dynamic_state
+                    || self.device.enabled_extensions().ext_shader_object
+            );
             (fns.ext_extended_dynamic_state
                 .cmd_set_viewport_with_count_ext)(
                 self.handle,
@@ -2708,6 +4079,38 @@ enum SetDynamicStateError {
     /// limit has been exceeded.
     MaxTessellationPatchSizeExceeded { provided: u32, max: u32 },
 
+    /// The [`max_vertex_attrib_divisor`](crate::device::Properties::max_vertex_attrib_divisor)
+    /// limit has been exceeded.
+    MaxVertexAttribDivisorExceeded {
+        binding: u32,
+        provided: u32,
+        max: u32,
+    },
+
+    /// The [`max_vertex_input_attribute_offset`](crate::device::Properties::max_vertex_input_attribute_offset)
+    /// limit has been exceeded.
+    MaxVertexInputAttributeOffsetExceeded {
+        location: u32,
+        provided: u32,
+        max: u32,
+    },
+
+    /// The [`max_vertex_input_attributes`](crate::device::Properties::max_vertex_input_attributes)
+    /// limit has been exceeded.
+    MaxVertexInputAttributesExceeded { provided: u32, max: u32 },
+
+    /// The [`max_vertex_input_binding_stride`](crate::device::Properties::max_vertex_input_binding_stride)
+    /// limit has been exceeded.
+    MaxVertexInputBindingStrideExceeded {
+        binding: u32,
+        provided: u32,
+        max: u32,
+    },
+
+    /// The [`max_vertex_input_bindings`](crate::device::Properties::max_vertex_input_bindings)
+    /// limit has been exceeded.
+    MaxVertexInputBindingsExceeded { provided: u32, max: u32 },
+
     /// The [`max_viewports`](crate::device::Properties::max_viewports)
     /// limit has been exceeded.
     MaxViewportsExceeded { provided: u32, max: u32 },
@@ -2725,6 +4128,12 @@ enum SetDynamicStateError {
     /// The currently bound pipeline contains this state as internally fixed state, which cannot be
     /// overridden with dynamic state.
     PipelineHasFixedState,
+
+    /// A vertex attribute refers to a binding that is not present in the vertex input state.
+    VertexInputAttributeInvalidBinding { location: u32, binding: u32 },
+
+    /// The format of a vertex attribute does not support being used as a vertex buffer format.
+    VertexInputAttributeUnsupportedFormat { location: u32, format: Format },
 }
 
 impl error::Error for SetDynamicStateError {}
@@ -2752,6 +4161,29 @@ impl fmt::Display for SetDynamicStateError {
                 f,
                 "the `max_tessellation_patch_size` limit has been exceeded",
             ),
+            Self::MaxVertexAttribDivisorExceeded { binding, .. } => write!(
+                f,
+                "the `max_vertex_attrib_divisor` limit has been exceeded for binding {}",
+                binding,
+            ),
+            Self::MaxVertexInputAttributeOffsetExceeded { location, .. } => write!(
+                f,
+                "the `max_vertex_input_attribute_offset` limit has been exceeded for location {}",
+                location,
+            ),
+            Self::MaxVertexInputAttributesExceeded { .. } => write!(
+                f,
+                "the `max_vertex_input_attributes` limit has been exceeded",
+            ),
+            Self::MaxVertexInputBindingStrideExceeded { binding, .. } => write!(
+                f,
+                "the `max_vertex_input_binding_stride` limit has been exceeded for binding {}",
+                binding,
+            ),
+            Self::MaxVertexInputBindingsExceeded { .. } => write!(
+                f,
+                "the `max_vertex_input_bindings` limit has been exceeded",
+            ),
             Self::MaxViewportsExceeded { .. } => write!(
                 f,
                 "the `max_viewports` limit has been exceeded",
@@ -2772,6 +4204,16 @@ impl fmt::Display for SetDynamicStateError {
                 f,
                 "the currently bound pipeline contains this state as internally fixed state, which cannot be overridden with dynamic state",
             ),
+            Self::VertexInputAttributeInvalidBinding { location, binding } => write!(
+                f,
+                "the vertex attribute at location {} refers to binding {}, which is not present",
+                location, binding,
+            ),
+            Self::VertexInputAttributeUnsupportedFormat { location, format } => write!(
+                f,
+                "the format {:?} of the vertex attribute at location {} does not support being used as a vertex buffer format",
+                format, location,
+            ),
         }
     }
 }
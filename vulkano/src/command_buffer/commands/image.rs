@@ -11,9 +11,9 @@ use crate::{
     command_buffer::{
         synced::{Command, Resource, SyncCommandBufferBuilder, SyncCommandBufferBuilderError},
         sys::UnsafeCommandBufferBuilder,
-        AutoCommandBufferBuilder, CopyError, CopyErrorResource,
+        AutoCommandBufferBuilder, CopyError, CopyErrorResource, CopyImageInfo, ImageCopy,
     },
-    device::DeviceOwned,
+    device::{Device, DeviceOwned},
     format::{ClearColorValue, ClearDepthStencilValue, NumericType},
     image::{
         ImageAccess, ImageAspects, ImageDimensions, ImageLayout, ImageSubresourceLayers,
@@ -61,6 +61,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// If `layer_count` is greater than 1, the blit will happen between each individual layer as
     /// if they were separate images.
     ///
+    /// If `blit_image_info.fallback` is `true`, some blits that break these restrictions are
+    /// replaced with a supported alternative instead of returning an error. See
+    /// [`BlitImageInfo::fallback`].
+    ///
     /// # Panic
     ///
     /// - Panics if the source or the destination was not created with `device`.
@@ -69,6 +73,23 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         &mut self,
         mut blit_image_info: BlitImageInfo,
     ) -> Result<&mut Self, CopyError> {
+        if blit_image_info.fallback {
+            // The formats don't support blitting, but the blit may be a plain copy.
+            if !(blit_image_info.src_image.format_features().blit_src
+                && blit_image_info.dst_image.format_features().blit_dst)
+            {
+                if let Some(copy_image_info) = blit_image_info.to_copy_image_info() {
+                    return self.copy_image(copy_image_info);
+                }
+            }
+
+            blit_image_info.filter = supported_blit_filter(
+                self.device(),
+                blit_image_info.src_image.as_ref(),
+                blit_image_info.filter,
+            );
+        }
+
         self.validate_blit_image(&mut blit_image_info)?;
 
         unsafe {
@@ -98,6 +119,7 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             dst_image_layout,
             ref regions,
             filter,
+            fallback: _,
             _ne: _,
         } = blit_image_info;
 
@@ -1154,6 +1176,7 @@ impl SyncCommandBufferBuilder {
             dst_image_layout,
             ref regions,
             filter,
+            fallback: _,
             _ne: _,
         } = &blit_image_info;
 
@@ -1483,6 +1506,7 @@ impl UnsafeCommandBufferBuilder {
             dst_image_layout,
             ref regions,
             filter,
+            fallback: _,
             _ne,
         } = blit_image_info;
 
@@ -1892,6 +1916,19 @@ pub struct BlitImageInfo {
     /// The default value is [`Filter::Nearest`].
     pub filter: Filter,
 
+    /// Whether to fall back to a supported alternative, instead of returning an error, if the
+    /// blit is not supported by the formats of the images.
+    ///
+    /// If `true`:
+    /// - If the formats don't support blitting, but no region is scaled or flipped, the regions
+    ///   are copied with `copy_image` instead. The formats must then be compatible for copying.
+    /// - If `filter` is not supported by the format of `src_image`, the best filter that is
+    ///   supported is used instead: `Cubic` falls back to `Linear`, and `Linear` to `Nearest`.
+    ///   Depth and stencil formats always use `Nearest`.
+    ///
+    /// The default value is `false`.
+    pub fallback: bool,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1924,9 +1961,72 @@ impl BlitImageInfo {
             dst_image_layout: ImageLayout::TransferDstOptimal,
             regions: smallvec![region],
             filter: Filter::Nearest,
+            fallback: false,
             _ne: crate::NonExhaustive(()),
         }
     }
+
+    // Returns the copy that is equivalent to the blit, if no region is scaled or flipped.
+    fn to_copy_image_info(&self) -> Option<CopyImageInfo> {
+        let regions = self
+            .regions
+            .iter()
+            .map(|region| {
+                let [src_min, src_max] = region.src_offsets;
+                let [dst_min, dst_max] = region.dst_offsets;
+                let mut extent = [0; 3];
+
+                for i in 0..3 {
+                    if src_max[i] < src_min[i]
+                        || dst_max[i] < dst_min[i]
+                        || src_max[i] - src_min[i] != dst_max[i] - dst_min[i]
+                    {
+                        return None;
+                    }
+
+                    extent[i] = src_max[i] - src_min[i];
+                }
+
+                Some(ImageCopy {
+                    src_subresource: region.src_subresource.clone(),
+                    src_offset: src_min,
+                    dst_subresource: region.dst_subresource.clone(),
+                    dst_offset: dst_min,
+                    extent,
+                    ..Default::default()
+                })
+            })
+            .collect::<Option<_>>()?;
+
+        Some(CopyImageInfo {
+            src_image_layout: self.src_image_layout,
+            dst_image_layout: self.dst_image_layout,
+            regions,
+            ..CopyImageInfo::images(self.src_image.clone(), self.dst_image.clone())
+        })
+    }
+}
+
+// Returns the best filter, no better than `filter`, that can be used to blit from `src_image`.
+fn supported_blit_filter(device: &Device, src_image: &dyn ImageAccess, filter: Filter) -> Filter {
+    let format_features = src_image.format_features();
+
+    // VUID-VkBlitImageInfo2-srcImage-00232
+    if !src_image.format().aspects().color {
+        return Filter::Nearest;
+    }
+
+    let cubic_supported = device.enabled_extensions().ext_filter_cubic
+        && format_features.sampled_image_filter_cubic
+        && matches!(src_image.dimensions(), ImageDimensions::Dim2d { .. });
+
+    match filter {
+        Filter::Cubic if cubic_supported => Filter::Cubic,
+        Filter::Cubic | Filter::Linear if format_features.sampled_image_filter_linear => {
+            Filter::Linear
+        }
+        _ => Filter::Nearest,
+    }
 }
 
 /// A region of data to blit between images.
@@ -11,12 +11,14 @@ pub(super) mod bind_push;
 pub(super) mod debug;
 pub(super) mod dynamic_state;
 pub(super) mod image;
+pub(super) mod optical_flow;
 pub(super) mod pipeline;
 pub(super) mod query;
 pub(super) mod render_pass;
 pub(super) mod secondary;
 pub(super) mod sync;
 pub(super) mod transfer;
+pub(super) mod video;
 
 use super::synced::SyncCommandBufferBuilderError;
 use crate::{
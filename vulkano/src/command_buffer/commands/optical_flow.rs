@@ -0,0 +1,317 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::{
+    command_buffer::{
+        synced::{Command, Resource, SyncCommandBufferBuilder, SyncCommandBufferBuilderError},
+        sys::UnsafeCommandBufferBuilder,
+        AutoCommandBufferBuilder,
+    },
+    device::DeviceOwned,
+    image::{view::ImageViewAbstract, ImageLayout},
+    optical_flow::{OpticalFlowExecuteInfo, OpticalFlowSession, OpticalFlowSessionBindingPoint},
+    sync::{AccessFlags, PipelineMemoryAccess, PipelineStages},
+    VulkanObject,
+};
+use smallvec::SmallVec;
+use std::{error, fmt, sync::Arc};
+
+/// # Commands for optical flow.
+///
+/// These commands require the
+/// [`nv_optical_flow`](crate::device::DeviceExtensions::nv_optical_flow) extension to be enabled
+/// on the device.
+impl<L, P> AutoCommandBufferBuilder<L, P> {
+    /// Estimates the motion between the input and reference images that are bound to `session`,
+    /// and writes it to the output images that are bound to `session`.
+    ///
+    /// The images that are bound to the session when this command is recorded are the ones that
+    /// are used when the command buffer is executed.
+    pub fn optical_flow_execute(
+        &mut self,
+        session: Arc<OpticalFlowSession>,
+        execute_info: OpticalFlowExecuteInfo,
+    ) -> Result<&mut Self, OpticalFlowExecuteError> {
+        let bound_images = session.bound_images();
+        self.validate_optical_flow_execute(&session, &execute_info, &bound_images)?;
+
+        unsafe {
+            self.inner
+                .optical_flow_execute(session, execute_info, bound_images)?;
+        }
+
+        Ok(self)
+    }
+
+    fn validate_optical_flow_execute(
+        &self,
+        session: &OpticalFlowSession,
+        execute_info: &OpticalFlowExecuteInfo,
+        bound_images: &[(OpticalFlowSessionBindingPoint, Arc<dyn ImageViewAbstract>)],
+    ) -> Result<(), OpticalFlowExecuteError> {
+        let device = self.device();
+
+        assert_eq!(device, session.device());
+
+        if self.render_pass_state.is_some() {
+            return Err(OpticalFlowExecuteError::ForbiddenInsideRenderPass);
+        }
+
+        if !self.queue_family().supports_optical_flow() {
+            return Err(OpticalFlowExecuteError::NotSupportedByQueueFamily);
+        }
+
+        let mut required_binding_points: SmallVec<[_; 8]> = SmallVec::new();
+        required_binding_points.extend([
+            OpticalFlowSessionBindingPoint::Input,
+            OpticalFlowSessionBindingPoint::Reference,
+            OpticalFlowSessionBindingPoint::FlowVector,
+        ]);
+
+        if session.hint_grid_size().is_some() {
+            required_binding_points.push(OpticalFlowSessionBindingPoint::Hint);
+        }
+
+        if session.cost_format().is_some() {
+            required_binding_points.push(OpticalFlowSessionBindingPoint::Cost);
+        }
+
+        if session.enable_global_flow() {
+            required_binding_points.push(OpticalFlowSessionBindingPoint::GlobalFlow);
+        }
+
+        if session.both_directions() {
+            required_binding_points.push(OpticalFlowSessionBindingPoint::BackwardFlowVector);
+
+            if session.cost_format().is_some() {
+                required_binding_points.push(OpticalFlowSessionBindingPoint::BackwardCost);
+            }
+        }
+
+        for binding_point in required_binding_points {
+            if !bound_images
+                .iter()
+                .any(|&(bound, _)| bound == binding_point)
+            {
+                return Err(OpticalFlowExecuteError::ImageNotBound { binding_point });
+            }
+        }
+
+        if !execute_info.regions.is_empty() {
+            if !session.allow_regions() {
+                return Err(OpticalFlowExecuteError::RegionsNotAllowed);
+            }
+
+            let max = device
+                .physical_device()
+                .properties()
+                .max_num_regions_of_interest
+                .unwrap_or(0);
+
+            if execute_info.regions.len() as u32 > max {
+                return Err(OpticalFlowExecuteError::MaxRegionsExceeded {
+                    provided: execute_info.regions.len() as u32,
+                    max,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SyncCommandBufferBuilder {
+    /// Calls `vkCmdOpticalFlowExecuteNV` on the builder.
+    #[inline]
+    pub unsafe fn optical_flow_execute(
+        &mut self,
+        session: Arc<OpticalFlowSession>,
+        execute_info: OpticalFlowExecuteInfo,
+        bound_images: SmallVec<[(OpticalFlowSessionBindingPoint, Arc<dyn ImageViewAbstract>); 4]>,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        struct Cmd {
+            session: Arc<OpticalFlowSession>,
+            execute_info: OpticalFlowExecuteInfo,
+            _bound_images:
+                SmallVec<[(OpticalFlowSessionBindingPoint, Arc<dyn ImageViewAbstract>); 4]>,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "optical_flow_execute"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.optical_flow_execute(&self.session, &self.execute_info);
+            }
+        }
+
+        let resources: SmallVec<[_; 8]> = bound_images
+            .iter()
+            .map(|(binding_point, image_view)| {
+                let write = binding_point.is_output();
+
+                (
+                    format!("{:?}", binding_point).into(),
+                    Resource::Image {
+                        image: image_view.image(),
+                        subresource_range: image_view.subresource_range().clone(),
+                        memory: PipelineMemoryAccess {
+                            // TODO: use the optical flow stage and access types once
+                            // synchronization2 is supported.
+                            stages: PipelineStages {
+                                all_commands: true,
+                                ..PipelineStages::none()
+                            },
+                            access: AccessFlags {
+                                memory_read: !write,
+                                memory_write: write,
+                                ..AccessFlags::none()
+                            },
+                            exclusive: write,
+                        },
+                        start_layout: ImageLayout::General,
+                        end_layout: ImageLayout::General,
+                    },
+                )
+            })
+            .collect();
+
+        for resource in &resources {
+            self.check_resource_conflicts(resource)?;
+        }
+
+        self.commands.push(Box::new(Cmd {
+            session,
+            execute_info,
+            _bound_images: bound_images,
+        }));
+
+        for resource in resources {
+            self.add_resource(resource);
+        }
+
+        Ok(())
+    }
+}
+
+impl UnsafeCommandBufferBuilder {
+    /// Calls `vkCmdOpticalFlowExecuteNV` on the builder.
+    #[inline]
+    pub unsafe fn optical_flow_execute(
+        &mut self,
+        session: &OpticalFlowSession,
+        execute_info: &OpticalFlowExecuteInfo,
+    ) {
+        let &OpticalFlowExecuteInfo {
+            disable_temporal_hints,
+            ref regions,
+            _ne: _,
+        } = execute_info;
+
+        let mut flags = ash::vk::OpticalFlowExecuteFlagsNV::empty();
+
+        if disable_temporal_hints {
+            flags |= ash::vk::OpticalFlowExecuteFlagsNV::DISABLE_TEMPORAL_HINTS;
+        }
+
+        let regions: SmallVec<[_; 2]> =
+            regions.iter().cloned().map(ash::vk::Rect2D::from).collect();
+
+        let execute_info = ash::vk::OpticalFlowExecuteInfoNV {
+            flags,
+            region_count: regions.len() as u32,
+            p_regions: regions.as_ptr(),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        (fns.nv_optical_flow.cmd_optical_flow_execute_nv)(
+            self.handle,
+            session.internal_object(),
+            &execute_info,
+        );
+    }
+}
+
+/// Error that can happen when recording an optical flow command.
+#[derive(Clone, Debug)]
+pub enum OpticalFlowExecuteError {
+    SyncCommandBufferBuilderError(SyncCommandBufferBuilderError),
+
+    /// Operation forbidden inside of a render pass.
+    ForbiddenInsideRenderPass,
+
+    /// No image view is bound to a binding point that the session writes to or reads from.
+    ImageNotBound {
+        binding_point: OpticalFlowSessionBindingPoint,
+    },
+
+    /// More regions of interest were provided than the
+    /// [`max_num_regions_of_interest`](crate::device::Properties::max_num_regions_of_interest)
+    /// limit allows.
+    MaxRegionsExceeded {
+        provided: u32,
+        max: u32,
+    },
+
+    /// The queue family doesn't allow this operation.
+    NotSupportedByQueueFamily,
+
+    /// Regions of interest were provided, but the session was not created with `allow_regions`
+    /// enabled.
+    RegionsNotAllowed,
+}
+
+impl error::Error for OpticalFlowExecuteError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::SyncCommandBufferBuilderError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OpticalFlowExecuteError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::SyncCommandBufferBuilderError(_) => write!(f, "a SyncCommandBufferBuilderError"),
+            Self::ForbiddenInsideRenderPass => {
+                write!(f, "operation forbidden inside of a render pass")
+            }
+            Self::ImageNotBound { binding_point } => write!(
+                f,
+                "no image view is bound to the {:?} binding point of the session",
+                binding_point,
+            ),
+            Self::MaxRegionsExceeded { provided, max } => write!(
+                f,
+                "{} regions of interest were provided, but the maximum is {}",
+                provided, max,
+            ),
+            Self::NotSupportedByQueueFamily => {
+                write!(f, "the queue family doesn't allow this operation")
+            }
+            Self::RegionsNotAllowed => write!(
+                f,
+                "regions of interest were provided, but the session was not created with \
+                `allow_regions` enabled",
+            ),
+        }
+    }
+}
+
+impl From<SyncCommandBufferBuilderError> for OpticalFlowExecuteError {
+    #[inline]
+    fn from(err: SyncCommandBufferBuilderError) -> Self {
+        Self::SyncCommandBufferBuilderError(err)
+    }
+}
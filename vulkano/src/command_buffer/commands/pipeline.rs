@@ -8,10 +8,10 @@
 // according to those terms.
 
 use crate::{
-    buffer::{view::BufferViewAbstract, BufferAccess, TypedBufferAccess},
+    buffer::{view::BufferViewAbstract, BufferAccess, BufferAccessObject, TypedBufferAccess},
     command_buffer::{
         synced::{
-            Command, CommandBufferState, Resource, SyncCommandBufferBuilder,
+            Command, CommandBufferState, Resource, SetOrPush, SyncCommandBufferBuilder,
             SyncCommandBufferBuilderError,
         },
         sys::UnsafeCommandBufferBuilder,
@@ -30,8 +30,9 @@ use crate::{
         graphics::{
             input_assembly::PrimitiveTopology,
             render_pass::PipelineRenderPassType,
-            vertex_input::{VertexInputRate, VertexInputState},
+            vertex_input::{VertexInputBindingDescription, VertexInputRate, VertexInputState},
         },
+        layout::PipelineLayoutDifference,
         ComputePipeline, DynamicState, GraphicsPipeline, PartialStateMode, Pipeline,
         PipelineBindPoint, PipelineLayout,
     },
@@ -40,7 +41,8 @@ use crate::{
     sync::{AccessFlags, PipelineMemoryAccess, PipelineStages},
     DeviceSize, VulkanObject,
 };
-use std::{borrow::Cow, error, fmt, mem::size_of, ops::Range, sync::Arc};
+use parking_lot::Mutex;
+use std::{borrow::Cow, collections::HashSet, error, fmt, mem::size_of, ops::Range, sync::Arc};
 
 /// # Commands to execute a bound pipeline.
 ///
@@ -51,16 +53,30 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// A compute pipeline must have been bound using
     /// [`bind_pipeline_compute`](Self::bind_pipeline_compute). Any resources used by the compute
     /// pipeline, such as descriptor sets, must have been set beforehand.
+    ///
+    /// Instead of a compute pipeline, a compute shader object can be bound using
+    /// [`bind_shaders`](Self::bind_shaders).
     #[inline]
     pub fn dispatch(&mut self, group_counts: [u32; 3]) -> Result<&mut Self, DispatchError> {
         if !self.queue_family().supports_compute() {
             return Err(AutoCommandBufferBuilderContextError::NotSupportedByQueueFamily.into());
         }
 
-        let pipeline = check_pipeline_compute(self.state())?;
-        self.ensure_outside_render_pass()?;
-        check_descriptor_sets_validity(self.state(), pipeline, pipeline.descriptor_requirements())?;
-        check_push_constants_validity(self.state(), pipeline.layout())?;
+        match check_pipeline_compute(self.state())? {
+            Some(pipeline) => {
+                self.ensure_outside_render_pass()?;
+                check_descriptor_sets_validity(
+                    self.state(),
+                    &self.descriptor_validation,
+                    pipeline.bind_point(),
+                    pipeline.layout(),
+                    pipeline.num_used_descriptor_sets(),
+                    pipeline.descriptor_requirements(),
+                )?;
+                check_push_constants_validity(self.state(), pipeline.layout())?;
+            }
+            None => self.validate_dispatch_shader_object::<DispatchError>()?,
+        }
         check_dispatch(self.device(), group_counts)?;
 
         unsafe {
@@ -76,6 +92,9 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// A compute pipeline must have been bound using
     /// [`bind_pipeline_compute`](Self::bind_pipeline_compute). Any resources used by the compute
     /// pipeline, such as descriptor sets, must have been set beforehand.
+    ///
+    /// Instead of a compute pipeline, a compute shader object can be bound using
+    /// [`bind_shaders`](Self::bind_shaders).
     #[inline]
     pub fn dispatch_indirect<Inb>(
         &mut self,
@@ -88,10 +107,21 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             return Err(AutoCommandBufferBuilderContextError::NotSupportedByQueueFamily.into());
         }
 
-        let pipeline = check_pipeline_compute(self.state())?;
-        self.ensure_outside_render_pass()?;
-        check_descriptor_sets_validity(self.state(), pipeline, pipeline.descriptor_requirements())?;
-        check_push_constants_validity(self.state(), pipeline.layout())?;
+        match check_pipeline_compute(self.state())? {
+            Some(pipeline) => {
+                self.ensure_outside_render_pass()?;
+                check_descriptor_sets_validity(
+                    self.state(),
+                    &self.descriptor_validation,
+                    pipeline.bind_point(),
+                    pipeline.layout(),
+                    pipeline.num_used_descriptor_sets(),
+                    pipeline.descriptor_requirements(),
+                )?;
+                check_push_constants_validity(self.state(), pipeline.layout())?;
+            }
+            None => self.validate_dispatch_shader_object::<DispatchIndirectError>()?,
+        }
         check_indirect_buffer(self.device(), indirect_buffer.as_ref())?;
 
         unsafe {
@@ -112,6 +142,11 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// pipeline, such as descriptor sets, vertex buffers and dynamic state, must have been set
     /// beforehand. If the bound graphics pipeline uses vertex buffers, then the provided vertex and
     /// instance ranges must be in range of the bound vertex buffers.
+    ///
+    /// Instead of a graphics pipeline, shader objects can be bound using
+    /// [`bind_shaders`](Self::bind_shaders). All the state that a graphics pipeline would
+    /// otherwise contain must then be set dynamically, and the render pass instance must have been
+    /// begun with [`begin_rendering`](Self::begin_rendering).
     #[inline]
     pub fn draw(
         &mut self,
@@ -120,17 +155,33 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         first_vertex: u32,
         first_instance: u32,
     ) -> Result<&mut Self, DrawError> {
-        let pipeline = check_pipeline_graphics(self.state())?;
-        self.ensure_inside_render_pass_inline(pipeline)?;
-        check_dynamic_state_validity(self.state(), pipeline)?;
-        check_descriptor_sets_validity(self.state(), pipeline, pipeline.descriptor_requirements())?;
-        check_push_constants_validity(self.state(), pipeline.layout())?;
-        check_vertex_buffers(
-            self.state(),
-            pipeline,
-            Some((first_vertex, vertex_count)),
-            Some((first_instance, instance_count)),
-        )?;
+        match check_pipeline_graphics(self.state())? {
+            Some(pipeline) => {
+                self.ensure_inside_render_pass_inline(pipeline)?;
+                check_dynamic_state_validity(self.state(), pipeline)?;
+                check_descriptor_sets_validity(
+                    self.state(),
+                    &self.descriptor_validation,
+                    pipeline.bind_point(),
+                    pipeline.layout(),
+                    pipeline.num_used_descriptor_sets(),
+                    pipeline.descriptor_requirements(),
+                )?;
+                check_push_constants_validity(self.state(), pipeline.layout())?;
+                check_vertex_buffers(
+                    self.state(),
+                    self.device(),
+                    pipeline.vertex_input_state(),
+                    pipeline_view_mask(pipeline),
+                    Some((first_vertex, vertex_count)),
+                    Some((first_instance, instance_count)),
+                )?;
+            }
+            None => self.validate_draw_shader_objects::<DrawError>(
+                Some((first_vertex, vertex_count)),
+                Some((first_instance, instance_count)),
+            )?,
+        }
 
         unsafe {
             self.inner
@@ -140,6 +191,55 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         Ok(self)
     }
 
+    /// Binds a per-vertex buffer and a per-instance buffer, and draws all the vertices of
+    /// `vertex_buffer` for `instance_count` instances.
+    ///
+    /// `vertex_buffer` is bound to vertex binding 0, and `instance_buffer` to vertex binding 1.
+    /// The bound graphics pipeline must use [`VertexInputRate::Vertex`] for binding 0 and
+    /// [`VertexInputRate::Instance`] for binding 1. Before anything is bound, `instance_count` is
+    /// checked against the number of elements in `instance_buffer`, so that drawing too many
+    /// instances is reported as an error rather than reading past the end of the buffer.
+    ///
+    /// Other than that, this behaves the same as [`draw`](Self::draw) with `first_vertex` and
+    /// `first_instance` set to 0.
+    ///
+    /// # Panics
+    ///
+    /// - Panics under the same conditions as
+    ///   [`bind_vertex_buffers`](Self::bind_vertex_buffers).
+    pub fn draw_instanced<Vb, Ib>(
+        &mut self,
+        vertex_buffer: Vb,
+        instance_buffer: Ib,
+        instance_count: u32,
+    ) -> Result<&mut Self, DrawError>
+    where
+        Vb: BufferAccessObject,
+        Ib: BufferAccessObject,
+    {
+        let vertex_buffer = vertex_buffer.as_buffer_access_object();
+        let instance_buffer = instance_buffer.as_buffer_access_object();
+
+        let vertex_input_state = match check_pipeline_graphics(self.state())? {
+            Some(pipeline) => pipeline.vertex_input_state(),
+            None => self
+                .state()
+                .vertex_input()
+                .ok_or(CheckDynamicStateValidityError::NotSet {
+                    dynamic_state: DynamicState::VertexInput,
+                })?,
+        };
+        let vertex_count = check_instanced_buffers(
+            vertex_input_state,
+            vertex_buffer.size(),
+            instance_buffer.size(),
+            instance_count,
+        )?;
+
+        self.bind_vertex_buffers(0, (vertex_buffer, instance_buffer));
+        self.draw(vertex_count, instance_count, 0, 0)
+    }
+
     /// Perform multiple draw operations using a graphics pipeline.
     ///
     /// One draw is performed for each [`DrawIndirectCommand`] struct in `indirect_buffer`.
@@ -155,6 +255,16 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// beforehand. If the bound graphics pipeline uses vertex buffers, then the vertex and instance
     /// ranges of each `DrawIndirectCommand` in the indirect buffer must be in range of the bound
     /// vertex buffers.
+    ///
+    /// If the [`draw_indirect_first_instance`](crate::device::Features::draw_indirect_first_instance)
+    /// feature is not enabled, the `first_instance` of each `DrawIndirectCommand` must be 0. This
+    /// is not checked by vulkano, because the contents of the indirect buffer may be written by
+    /// the device.
+    ///
+    /// Instead of a graphics pipeline, shader objects can be bound using
+    /// [`bind_shaders`](Self::bind_shaders). All the state that a graphics pipeline would
+    /// otherwise contain must then be set dynamically, and the render pass instance must have been
+    /// begun with [`begin_rendering`](Self::begin_rendering).
     #[inline]
     pub fn draw_indirect<Inb>(
         &mut self,
@@ -163,37 +273,45 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     where
         Inb: TypedBufferAccess<Content = [DrawIndirectCommand]> + Send + Sync + 'static,
     {
-        let pipeline = check_pipeline_graphics(self.state())?;
-        self.ensure_inside_render_pass_inline(pipeline)?;
-        check_dynamic_state_validity(self.state(), pipeline)?;
-        check_descriptor_sets_validity(self.state(), pipeline, pipeline.descriptor_requirements())?;
-        check_push_constants_validity(self.state(), pipeline.layout())?;
-        check_vertex_buffers(self.state(), pipeline, None, None)?;
+        match check_pipeline_graphics(self.state())? {
+            Some(pipeline) => {
+                self.ensure_inside_render_pass_inline(pipeline)?;
+                check_dynamic_state_validity(self.state(), pipeline)?;
+                check_descriptor_sets_validity(
+                    self.state(),
+                    &self.descriptor_validation,
+                    pipeline.bind_point(),
+                    pipeline.layout(),
+                    pipeline.num_used_descriptor_sets(),
+                    pipeline.descriptor_requirements(),
+                )?;
+                check_push_constants_validity(self.state(), pipeline.layout())?;
+                check_vertex_buffers(
+                    self.state(),
+                    self.device(),
+                    pipeline.vertex_input_state(),
+                    pipeline_view_mask(pipeline),
+                    None,
+                    None,
+                )?;
+            }
+            None => self.validate_draw_shader_objects::<DrawIndirectError>(None, None)?,
+        }
         check_indirect_buffer(self.device(), indirect_buffer.as_ref())?;
 
         let draw_count = indirect_buffer.len() as u32;
-        let limit = self
-            .device()
-            .physical_device()
-            .properties()
-            .max_draw_indirect_count;
-
-        if draw_count > limit {
-            return Err(
-                CheckIndirectBufferError::MaxDrawIndirectCountLimitExceeded {
-                    limit,
-                    requested: draw_count,
-                }
-                .into(),
-            );
-        }
+        let stride = size_of::<DrawIndirectCommand>() as u32;
+        check_indirect_draw(
+            self.device(),
+            indirect_buffer.as_ref(),
+            draw_count,
+            stride,
+            size_of::<DrawIndirectCommand>() as u32,
+        )?;
 
         unsafe {
-            self.inner.draw_indirect(
-                indirect_buffer,
-                draw_count,
-                size_of::<DrawIndirectCommand>() as u32,
-            )?;
+            self.inner
+                .draw_indirect(indirect_buffer, draw_count, stride)?;
         }
 
         Ok(self)
@@ -217,6 +335,11 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// beforehand. If the bound graphics pipeline uses vertex buffers, then the provided instance
     /// range must be in range of the bound vertex buffers. The vertex indices in the index buffer
     /// must be in range of the bound vertex buffers.
+    ///
+    /// Instead of a graphics pipeline, shader objects can be bound using
+    /// [`bind_shaders`](Self::bind_shaders). All the state that a graphics pipeline would
+    /// otherwise contain must then be set dynamically, and the render pass instance must have been
+    /// begun with [`begin_rendering`](Self::begin_rendering).
     #[inline]
     pub fn draw_indexed(
         &mut self,
@@ -227,17 +350,33 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         first_instance: u32,
     ) -> Result<&mut Self, DrawIndexedError> {
         // TODO: how to handle an index out of range of the vertex buffers?
-        let pipeline = check_pipeline_graphics(self.state())?;
-        self.ensure_inside_render_pass_inline(pipeline)?;
-        check_dynamic_state_validity(self.state(), pipeline)?;
-        check_descriptor_sets_validity(self.state(), pipeline, pipeline.descriptor_requirements())?;
-        check_push_constants_validity(self.state(), pipeline.layout())?;
-        check_vertex_buffers(
-            self.state(),
-            pipeline,
-            None,
-            Some((first_instance, instance_count)),
-        )?;
+        match check_pipeline_graphics(self.state())? {
+            Some(pipeline) => {
+                self.ensure_inside_render_pass_inline(pipeline)?;
+                check_dynamic_state_validity(self.state(), pipeline)?;
+                check_descriptor_sets_validity(
+                    self.state(),
+                    &self.descriptor_validation,
+                    pipeline.bind_point(),
+                    pipeline.layout(),
+                    pipeline.num_used_descriptor_sets(),
+                    pipeline.descriptor_requirements(),
+                )?;
+                check_push_constants_validity(self.state(), pipeline.layout())?;
+                check_vertex_buffers(
+                    self.state(),
+                    self.device(),
+                    pipeline.vertex_input_state(),
+                    pipeline_view_mask(pipeline),
+                    None,
+                    Some((first_instance, instance_count)),
+                )?;
+            }
+            None => self.validate_draw_shader_objects::<DrawIndexedError>(
+                None,
+                Some((first_instance, instance_count)),
+            )?,
+        }
         check_index_buffer(self.state(), Some((first_index, index_count)))?;
 
         unsafe {
@@ -273,6 +412,16 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     /// beforehand. If the bound graphics pipeline uses vertex buffers, then the instance ranges of
     /// each `DrawIndexedIndirectCommand` in the indirect buffer must be in range of the bound
     /// vertex buffers.
+    ///
+    /// If the [`draw_indirect_first_instance`](crate::device::Features::draw_indirect_first_instance)
+    /// feature is not enabled, the `first_instance` of each `DrawIndexedIndirectCommand` must be 0.
+    /// This is not checked by vulkano, because the contents of the indirect buffer may be written
+    /// by the device.
+    ///
+    /// Instead of a graphics pipeline, shader objects can be bound using
+    /// [`bind_shaders`](Self::bind_shaders). All the state that a graphics pipeline would
+    /// otherwise contain must then be set dynamically, and the render pass instance must have been
+    /// begun with [`begin_rendering`](Self::begin_rendering).
     #[inline]
     pub fn draw_indexed_indirect<Inb>(
         &mut self,
@@ -281,64 +430,172 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     where
         Inb: TypedBufferAccess<Content = [DrawIndexedIndirectCommand]> + 'static,
     {
-        let pipeline = check_pipeline_graphics(self.state())?;
-        self.ensure_inside_render_pass_inline(pipeline)?;
-        check_dynamic_state_validity(self.state(), pipeline)?;
-        check_descriptor_sets_validity(self.state(), pipeline, pipeline.descriptor_requirements())?;
-        check_push_constants_validity(self.state(), pipeline.layout())?;
-        check_vertex_buffers(self.state(), pipeline, None, None)?;
+        match check_pipeline_graphics(self.state())? {
+            Some(pipeline) => {
+                self.ensure_inside_render_pass_inline(pipeline)?;
+                check_dynamic_state_validity(self.state(), pipeline)?;
+                check_descriptor_sets_validity(
+                    self.state(),
+                    &self.descriptor_validation,
+                    pipeline.bind_point(),
+                    pipeline.layout(),
+                    pipeline.num_used_descriptor_sets(),
+                    pipeline.descriptor_requirements(),
+                )?;
+                check_push_constants_validity(self.state(), pipeline.layout())?;
+                check_vertex_buffers(
+                    self.state(),
+                    self.device(),
+                    pipeline.vertex_input_state(),
+                    pipeline_view_mask(pipeline),
+                    None,
+                    None,
+                )?;
+            }
+            None => self.validate_draw_shader_objects::<DrawIndexedIndirectError>(None, None)?,
+        }
         check_index_buffer(self.state(), None)?;
         check_indirect_buffer(self.device(), indirect_buffer.as_ref())?;
 
         let draw_count = indirect_buffer.len() as u32;
-        let limit = self
-            .device()
-            .physical_device()
-            .properties()
-            .max_draw_indirect_count;
+        let stride = size_of::<DrawIndexedIndirectCommand>() as u32;
+        check_indirect_draw(
+            self.device(),
+            indirect_buffer.as_ref(),
+            draw_count,
+            stride,
+            size_of::<DrawIndexedIndirectCommand>() as u32,
+        )?;
 
-        if draw_count > limit {
-            return Err(
-                CheckIndirectBufferError::MaxDrawIndirectCountLimitExceeded {
-                    limit,
-                    requested: draw_count,
-                }
-                .into(),
-            );
+        unsafe {
+            self.inner
+                .draw_indexed_indirect(indirect_buffer, draw_count, stride)?;
         }
 
-        unsafe {
-            self.inner.draw_indexed_indirect(
-                indirect_buffer,
-                draw_count,
-                size_of::<DrawIndexedIndirectCommand>() as u32,
+        Ok(self)
+    }
+    // Helper function for draw commands that use shader objects instead of a graphics pipeline.
+    fn validate_draw_shader_objects<E>(
+        &self,
+        vertices: Option<(u32, u32)>,
+        instances: Option<(u32, u32)>,
+    ) -> Result<(), E>
+    where
+        E: From<AutoCommandBufferBuilderContextError>
+            + From<CheckDynamicStateValidityError>
+            + From<CheckDescriptorSetsValidityError>
+            + From<CheckPushConstantsValidityError>
+            + From<CheckVertexBufferError>,
+    {
+        let (view_mask, color_attachment_count) = self.ensure_inside_rendering_inline()?;
+        let current_state = self.state();
+        check_shader_objects_dynamic_state_validity(
+            current_state,
+            self.device(),
+            color_attachment_count,
+        )?;
+
+        for shader in GRAPHICS_SHADER_STAGES
+            .into_iter()
+            .filter_map(|stage| current_state.shader(stage))
+        {
+            check_descriptor_sets_validity(
+                current_state,
+                &self.descriptor_validation,
+                PipelineBindPoint::Graphics,
+                shader.layout(),
+                shader.num_used_descriptor_sets(),
+                shader.descriptor_requirements(),
             )?;
+            check_push_constants_validity(current_state, shader.layout())?;
         }
 
-        Ok(self)
+        if let Some(vertex_input) = current_state.vertex_input() {
+            check_vertex_buffers(
+                current_state,
+                self.device(),
+                vertex_input,
+                view_mask,
+                vertices,
+                instances,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Helper function for dispatch commands that use a shader object instead of a compute
+    // pipeline.
+    fn validate_dispatch_shader_object<E>(&self) -> Result<(), E>
+    where
+        E: From<AutoCommandBufferBuilderContextError>
+            + From<CheckDescriptorSetsValidityError>
+            + From<CheckPushConstantsValidityError>,
+    {
+        self.ensure_outside_render_pass()?;
+
+        // Checked by `check_pipeline_compute`.
+        let shader = self.state().shader(ShaderStage::Compute).unwrap();
+        check_descriptor_sets_validity(
+            self.state(),
+            &self.descriptor_validation,
+            PipelineBindPoint::Compute,
+            shader.layout(),
+            shader.num_used_descriptor_sets(),
+            shader.descriptor_requirements(),
+        )?;
+        check_push_constants_validity(self.state(), shader.layout())?;
+
+        Ok(())
     }
 }
 
+// The shader stages that shader objects can be bound to for draw commands.
+const GRAPHICS_SHADER_STAGES: [ShaderStage; 5] = [
+    ShaderStage::Vertex,
+    ShaderStage::TessellationControl,
+    ShaderStage::TessellationEvaluation,
+    ShaderStage::Geometry,
+    ShaderStage::Fragment,
+];
+
+/// Returns the view mask of the render pass that a graphics pipeline was created for.
+fn pipeline_view_mask(pipeline: &GraphicsPipeline) -> u32 {
+    match pipeline.render_pass() {
+        PipelineRenderPassType::BeginRenderPass(subpass) => subpass.render_pass().views_used(),
+        PipelineRenderPassType::BeginRendering(rendering_info) => rendering_info.view_mask,
+    }
+}
+
+// Returns the bound compute pipeline, or `None` if a compute shader object is bound instead.
 fn check_pipeline_compute(
     current_state: CommandBufferState,
-) -> Result<&ComputePipeline, CheckPipelineError> {
-    let pipeline = match current_state.pipeline_compute() {
-        Some(x) => x,
-        None => return Err(CheckPipelineError::PipelineNotBound),
-    };
+) -> Result<Option<&ComputePipeline>, CheckPipelineError> {
+    if let Some(pipeline) = current_state.pipeline_compute() {
+        return Ok(Some(pipeline));
+    }
 
-    Ok(pipeline)
+    if current_state.shader(ShaderStage::Compute).is_some() {
+        return Ok(None);
+    }
+
+    Err(CheckPipelineError::PipelineNotBound)
 }
 
+// Returns the bound graphics pipeline, or `None` if graphics shader objects are bound instead.
 fn check_pipeline_graphics(
     current_state: CommandBufferState,
-) -> Result<&GraphicsPipeline, CheckPipelineError> {
-    let pipeline = match current_state.pipeline_graphics() {
-        Some(x) => x,
-        None => return Err(CheckPipelineError::PipelineNotBound),
-    };
+) -> Result<Option<&GraphicsPipeline>, CheckPipelineError> {
+    if let Some(pipeline) = current_state.pipeline_graphics() {
+        return Ok(Some(pipeline));
+    }
+
+    // VUID?
+    if current_state.shader(ShaderStage::Vertex).is_some() {
+        return Ok(None);
+    }
 
-    Ok(pipeline)
+    Err(CheckPipelineError::PipelineNotBound)
 }
 
 /// Error that can happen when checking whether the pipeline is valid.
@@ -362,34 +619,133 @@ impl fmt::Display for CheckPipelineError {
     }
 }
 
+/// How the resources in bound descriptor sets are validated by draw and dispatch commands.
+///
+/// Validating the individual descriptors that are accessed by a pipeline has a cost proportional
+/// to the number of descriptors, which is paid on every command. For renderers that bind large
+/// descriptor arrays, such as bindless renderers, this can make recording very slow. The other
+/// modes trade some of this validation for speed. In all modes, the compatibility of the bound
+/// descriptor sets with the pipeline layout is still checked on every command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescriptorValidationMode {
+    /// Every descriptor that is accessed by the pipeline is validated on every command.
+    ///
+    /// This is the default.
+    Full,
+
+    /// The descriptors of a binding are validated by the first command that uses a bound
+    /// descriptor set with a given pipeline or shader, and are skipped by later commands that use
+    /// the same descriptor set and pipeline.
+    ///
+    /// Descriptor sets can't be modified after they have been created, so this skips no checks
+    /// of the descriptors themselves. However, checks that involve other descriptor sets, such as
+    /// the compatibility between a separate sampler and the image views it's used with, are not
+    /// repeated when only the other descriptor set is rebound. Push descriptors are always
+    /// validated.
+    OncePerSet,
+
+    /// The descriptors of bindings whose descriptor count, as required by the pipeline, is at
+    /// least `min_descriptor_count` are not validated at all.
+    ///
+    /// This is intended to be used together with the GPU-assisted validation of the validation
+    /// layers, which validates the descriptors that are actually accessed by the shaders.
+    Deferred { min_descriptor_count: u32 },
+}
+
+impl Default for DescriptorValidationMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
+// The state used by `check_descriptor_sets_validity` to skip checks according to a
+// `DescriptorValidationMode`.
+#[derive(Debug, Default)]
+pub(in crate::command_buffer) struct DescriptorValidationState {
+    pub(in crate::command_buffer) mode: DescriptorValidationMode,
+
+    // For `OncePerSet`, the bindings that were validated so far, identified by the bind point,
+    // the set number, the binding number, the address of the descriptor set object and the
+    // address of the descriptor requirements.
+    validated: Mutex<HashSet<(PipelineBindPoint, u32, u32, usize, usize)>>,
+}
+
 /// Checks whether descriptor sets are compatible with the pipeline.
-fn check_descriptor_sets_validity<'a, P: Pipeline>(
+fn check_descriptor_sets_validity<'a>(
     current_state: CommandBufferState,
-    pipeline: &P,
+    validation: &DescriptorValidationState,
+    pipeline_bind_point: PipelineBindPoint,
+    pipeline_layout: &PipelineLayout,
+    num_used_descriptor_sets: u32,
     descriptor_requirements: impl IntoIterator<Item = ((u32, u32), &'a DescriptorRequirements)>,
 ) -> Result<(), CheckDescriptorSetsValidityError> {
-    if pipeline.num_used_descriptor_sets() == 0 {
+    if num_used_descriptor_sets == 0 {
         return Ok(());
     }
 
     // VUID-vkCmdDispatch-None-02697
     let bindings_pipeline_layout =
-        match current_state.descriptor_sets_pipeline_layout(pipeline.bind_point()) {
+        match current_state.descriptor_sets_pipeline_layout(pipeline_bind_point) {
             Some(x) => x,
-            None => return Err(CheckDescriptorSetsValidityError::IncompatiblePipelineLayout),
+            None => {
+                return Err(
+                    CheckDescriptorSetsValidityError::IncompatiblePipelineLayout {
+                        differences: Vec::new(),
+                    },
+                )
+            }
         };
 
     // VUID-vkCmdDispatch-None-02697
-    if !pipeline.layout().is_compatible_with(
-        bindings_pipeline_layout,
-        pipeline.num_used_descriptor_sets(),
-    ) {
-        return Err(CheckDescriptorSetsValidityError::IncompatiblePipelineLayout);
+    if !pipeline_layout.is_compatible_with(bindings_pipeline_layout, num_used_descriptor_sets) {
+        return Err(
+            CheckDescriptorSetsValidityError::IncompatiblePipelineLayout {
+                differences: pipeline_layout
+                    .compatibility_differences(bindings_pipeline_layout, num_used_descriptor_sets),
+            },
+        );
     }
 
     for ((set_num, binding_num), reqs) in descriptor_requirements {
+        let set_or_push = match current_state.descriptor_set(pipeline_bind_point, set_num) {
+            Some(x) => x,
+            None => return Err(CheckDescriptorSetsValidityError::MissingDescriptorSet { set_num }),
+        };
+
+        let validated_key = match validation.mode {
+            DescriptorValidationMode::Full => None,
+            DescriptorValidationMode::OncePerSet => match set_or_push {
+                SetOrPush::Set(set) => {
+                    let key = (
+                        pipeline_bind_point,
+                        set_num,
+                        binding_num,
+                        Arc::as_ptr(set.as_ref().0) as *const () as usize,
+                        reqs as *const DescriptorRequirements as usize,
+                    );
+
+                    if validation.validated.lock().contains(&key) {
+                        continue;
+                    }
+
+                    Some(key)
+                }
+                SetOrPush::Push(_) => None,
+            },
+            DescriptorValidationMode::Deferred {
+                min_descriptor_count,
+            } => {
+                if reqs.descriptor_count >= min_descriptor_count {
+                    continue;
+                }
+
+                None
+            }
+        };
+
         let layout_binding =
-            &pipeline.layout().set_layouts()[set_num as usize].bindings()[&binding_num];
+            &pipeline_layout.set_layouts()[set_num as usize].bindings()[&binding_num];
 
         let check_buffer = |index: u32, buffer: &Arc<dyn BufferAccess>| Ok(());
 
@@ -575,7 +931,7 @@ fn check_descriptor_sets_validity<'a, P: Pipeline>(
                 // It will be caught later by check_resources.
                 let iter = with_images.iter().filter_map(|id| {
                     current_state
-                        .descriptor_set(pipeline.bind_point(), id.set)
+                        .descriptor_set(pipeline_bind_point, id.set)
                         .and_then(|set| set.resources().binding(id.binding))
                         .and_then(|res| match res {
                             DescriptorBindingResources::ImageView(elements) => elements
@@ -608,12 +964,7 @@ fn check_descriptor_sets_validity<'a, P: Pipeline>(
             Ok(())
         };
 
-        let set_resources = match current_state.descriptor_set(pipeline.bind_point(), set_num) {
-            Some(x) => x.resources(),
-            None => return Err(CheckDescriptorSetsValidityError::MissingDescriptorSet { set_num }),
-        };
-
-        let binding_resources = set_resources.binding(binding_num).unwrap();
+        let binding_resources = set_or_push.resources().binding(binding_num).unwrap();
 
         match binding_resources {
             DescriptorBindingResources::None(elements) => {
@@ -641,6 +992,10 @@ fn check_descriptor_sets_validity<'a, P: Pipeline>(
                 check_resources(set_num, binding_num, reqs, elements, check_sampler)?;
             }
         }
+
+        if let Some(key) = validated_key {
+            validation.validated.lock().insert(key);
+        }
     }
 
     Ok(())
@@ -649,7 +1004,13 @@ fn check_descriptor_sets_validity<'a, P: Pipeline>(
 /// Error that can happen when checking descriptor sets validity.
 #[derive(Clone, Debug)]
 pub enum CheckDescriptorSetsValidityError {
-    IncompatiblePipelineLayout,
+    /// The layout used to bind the descriptor sets is not compatible with the pipeline's layout.
+    ///
+    /// `differences` lists how the layout used for binding differs from the pipeline's layout. It
+    /// is empty if no descriptor sets were bound at all.
+    IncompatiblePipelineLayout {
+        differences: Vec<PipelineLayoutDifference>,
+    },
     InvalidDescriptorResource {
         set_num: u32,
         binding_num: u32,
@@ -675,8 +1036,14 @@ impl fmt::Display for CheckDescriptorSetsValidityError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
-            Self::IncompatiblePipelineLayout => {
-                write!(fmt, "the bound pipeline is not compatible with the layout used to bind the descriptor sets")
+            Self::IncompatiblePipelineLayout { differences } => {
+                write!(fmt, "the bound pipeline is not compatible with the layout used to bind the descriptor sets")?;
+
+                for (index, difference) in differences.iter().enumerate() {
+                    write!(fmt, "{} {}", if index == 0 { ":" } else { ";" }, difference)?;
+                }
+
+                Ok(())
             }
             Self::InvalidDescriptorResource {
                 set_num,
@@ -924,11 +1291,41 @@ fn check_dynamic_state_validity(
         .map(|(s, _)| s)
     {
         match dynamic_state {
+            DynamicState::AlphaToCoverageEnable => {
+                if current_state.alpha_to_coverage_enable().is_none() {
+                    return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                }
+            }
+            DynamicState::AlphaToOneEnable => {
+                if current_state.alpha_to_one_enable().is_none() {
+                    return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                }
+            }
             DynamicState::BlendConstants => {
                 if current_state.blend_constants().is_none() {
                     return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
                 }
             }
+            DynamicState::ColorBlendEnable => {
+                let attachment_count = pipeline.color_blend_state().unwrap().attachments.len();
+
+                for num in 0..attachment_count as u32 {
+                    if current_state.color_blend_enable(num).is_none() {
+                        return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                    }
+                }
+            }
+            DynamicState::ColorBlendEquation => {
+                let attachments = &pipeline.color_blend_state().unwrap().attachments;
+
+                for (num, state) in attachments.iter().enumerate() {
+                    if state.blend.is_some()
+                        && current_state.color_blend_equation(num as u32).is_none()
+                    {
+                        return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                    }
+                }
+            }
             DynamicState::ColorWriteEnable => {
                 let enables = if let Some(enables) = current_state.color_write_enable() {
                     enables
@@ -944,6 +1341,15 @@ fn check_dynamic_state_validity(
                     });
                 }
             }
+            DynamicState::ColorWriteMask => {
+                let attachment_count = pipeline.color_blend_state().unwrap().attachments.len();
+
+                for num in 0..attachment_count as u32 {
+                    if current_state.color_write_mask(num).is_none() {
+                        return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                    }
+                }
+            }
             DynamicState::CullMode => {
                 if current_state.cull_mode().is_none() {
                     return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
@@ -969,6 +1375,11 @@ fn check_dynamic_state_validity(
                     return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
                 }
             }
+            DynamicState::DepthClampEnable => {
+                if current_state.depth_clamp_enable().is_none() {
+                    return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                }
+            }
             DynamicState::DepthCompareOp => {
                 if current_state.depth_compare_op().is_none() {
                     return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
@@ -1021,11 +1432,21 @@ fn check_dynamic_state_validity(
                     return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
                 }
             }
+            DynamicState::LogicOpEnable => {
+                if current_state.logic_op_enable().is_none() {
+                    return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                }
+            }
             DynamicState::PatchControlPoints => {
                 if current_state.patch_control_points().is_none() {
                     return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
                 }
             }
+            DynamicState::PolygonMode => {
+                if current_state.polygon_mode().is_none() {
+                    return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                }
+            }
             DynamicState::PrimitiveRestartEnable => {
                 let primitive_restart_enable =
                     if let Some(enable) = current_state.primitive_restart_enable() {
@@ -1113,6 +1534,11 @@ fn check_dynamic_state_validity(
 
                 // TODO: check that the topology matches the geometry shader
             }
+            DynamicState::RasterizationSamples => {
+                if current_state.rasterization_samples().is_none() {
+                    return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                }
+            }
             DynamicState::RasterizerDiscardEnable => {
                 if current_state.rasterizer_discard_enable().is_none() {
                     return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
@@ -1122,6 +1548,11 @@ fn check_dynamic_state_validity(
                 "RayTracingPipelineStackSize dynamic state should not occur on a graphics pipeline"
             ),
             DynamicState::SampleLocations => todo!(),
+            DynamicState::SampleMask => {
+                if current_state.sample_mask().is_none() {
+                    return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                }
+            }
             DynamicState::Scissor => {
                 for num in 0..pipeline.viewport_state().unwrap().count().unwrap() {
                     if current_state.scissor(num).is_none() {
@@ -1185,7 +1616,11 @@ fn check_dynamic_state_validity(
                     return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
                 }
             }
-            DynamicState::VertexInput => todo!(),
+            DynamicState::VertexInput => {
+                if current_state.vertex_input().is_none() {
+                    return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+                }
+            }
             DynamicState::VertexInputBindingStride => todo!(),
             DynamicState::Viewport => {
                 for num in 0..pipeline.viewport_state().unwrap().count().unwrap() {
@@ -1242,6 +1677,219 @@ fn check_dynamic_state_validity(
 }
 
 /// Error that can happen when validating dynamic states.
+/// Checks whether all the state that is needed to draw with shader objects has been set.
+///
+/// Shader objects contain none of the state of a graphics pipeline, so all of it must be set
+/// dynamically. States that are only used when some other state or a device feature is enabled
+/// are only required in that case.
+fn check_shader_objects_dynamic_state_validity(
+    current_state: CommandBufferState,
+    device: &Device,
+    color_attachment_count: u32,
+) -> Result<(), CheckDynamicStateValidityError> {
+    let features = device.enabled_features();
+
+    let mut required = vec![
+        (
+            DynamicState::VertexInput,
+            current_state.vertex_input().is_some(),
+        ),
+        (
+            DynamicState::PrimitiveTopology,
+            current_state.primitive_topology().is_some(),
+        ),
+        (
+            DynamicState::PrimitiveRestartEnable,
+            current_state.primitive_restart_enable().is_some(),
+        ),
+        (
+            DynamicState::ViewportWithCount,
+            current_state.viewport_with_count().is_some(),
+        ),
+        (
+            DynamicState::ScissorWithCount,
+            current_state.scissor_with_count().is_some(),
+        ),
+        (
+            DynamicState::RasterizerDiscardEnable,
+            current_state.rasterizer_discard_enable().is_some(),
+        ),
+    ];
+
+    if current_state
+        .shader(ShaderStage::TessellationControl)
+        .is_some()
+    {
+        required.push((
+            DynamicState::PatchControlPoints,
+            current_state.patch_control_points().is_some(),
+        ));
+    }
+
+    if current_state.rasterizer_discard_enable() == Some(false) {
+        required.extend([
+            (
+                DynamicState::PolygonMode,
+                current_state.polygon_mode().is_some(),
+            ),
+            (
+                DynamicState::RasterizationSamples,
+                current_state.rasterization_samples().is_some(),
+            ),
+            (
+                DynamicState::SampleMask,
+                current_state.sample_mask().is_some(),
+            ),
+            (
+                DynamicState::AlphaToCoverageEnable,
+                current_state.alpha_to_coverage_enable().is_some(),
+            ),
+            (DynamicState::CullMode, current_state.cull_mode().is_some()),
+            (
+                DynamicState::FrontFace,
+                current_state.front_face().is_some(),
+            ),
+            (
+                DynamicState::DepthTestEnable,
+                current_state.depth_test_enable().is_some(),
+            ),
+            (
+                DynamicState::DepthWriteEnable,
+                current_state.depth_write_enable().is_some(),
+            ),
+            (
+                DynamicState::DepthBiasEnable,
+                current_state.depth_bias_enable().is_some(),
+            ),
+            (
+                DynamicState::StencilTestEnable,
+                current_state.stencil_test_enable().is_some(),
+            ),
+        ]);
+
+        if features.alpha_to_one {
+            required.push((
+                DynamicState::AlphaToOneEnable,
+                current_state.alpha_to_one_enable().is_some(),
+            ));
+        }
+
+        if features.depth_clamp {
+            required.push((
+                DynamicState::DepthClampEnable,
+                current_state.depth_clamp_enable().is_some(),
+            ));
+        }
+
+        if features.depth_bounds {
+            required.push((
+                DynamicState::DepthBoundsTestEnable,
+                current_state.depth_bounds_test_enable().is_some(),
+            ));
+        }
+
+        if features.logic_op {
+            required.push((
+                DynamicState::LogicOpEnable,
+                current_state.logic_op_enable().is_some(),
+            ));
+        }
+
+        if current_state.depth_test_enable() == Some(true) {
+            required.push((
+                DynamicState::DepthCompareOp,
+                current_state.depth_compare_op().is_some(),
+            ));
+        }
+
+        if current_state.depth_bias_enable() == Some(true) {
+            required.push((
+                DynamicState::DepthBias,
+                current_state.depth_bias().is_some(),
+            ));
+        }
+
+        if current_state.depth_bounds_test_enable() == Some(true) {
+            required.push((
+                DynamicState::DepthBounds,
+                current_state.depth_bounds().is_some(),
+            ));
+        }
+
+        if current_state.stencil_test_enable() == Some(true) {
+            let stencil_op = current_state.stencil_op();
+            let compare_mask = current_state.stencil_compare_mask();
+            let write_mask = current_state.stencil_write_mask();
+            let reference = current_state.stencil_reference();
+
+            required.extend([
+                (
+                    DynamicState::StencilOp,
+                    stencil_op.front.is_some() && stencil_op.back.is_some(),
+                ),
+                (
+                    DynamicState::StencilCompareMask,
+                    compare_mask.front.is_some() && compare_mask.back.is_some(),
+                ),
+                (
+                    DynamicState::StencilWriteMask,
+                    write_mask.front.is_some() && write_mask.back.is_some(),
+                ),
+                (
+                    DynamicState::StencilReference,
+                    reference.front.is_some() && reference.back.is_some(),
+                ),
+            ]);
+        }
+
+        if current_state.logic_op_enable() == Some(true) {
+            required.push((DynamicState::LogicOp, current_state.logic_op().is_some()));
+        }
+
+        if current_state.shader(ShaderStage::Fragment).is_some() {
+            for num in 0..color_attachment_count {
+                required.extend([
+                    (
+                        DynamicState::ColorBlendEnable,
+                        current_state.color_blend_enable(num).is_some(),
+                    ),
+                    (
+                        DynamicState::ColorWriteMask,
+                        current_state.color_write_mask(num).is_some(),
+                    ),
+                ]);
+
+                if current_state.color_blend_enable(num) == Some(true) {
+                    required.push((
+                        DynamicState::ColorBlendEquation,
+                        current_state.color_blend_equation(num).is_some(),
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(&(dynamic_state, _)) = required.iter().find(|(_, is_set)| !is_set) {
+        return Err(CheckDynamicStateValidityError::NotSet { dynamic_state });
+    }
+
+    // Both were checked above.
+    let viewport_count = current_state.viewport_with_count().unwrap().len() as u32;
+    let scissor_count = current_state.scissor_with_count().unwrap().len() as u32;
+
+    // VUID?
+    if viewport_count != scissor_count {
+        return Err(
+            CheckDynamicStateValidityError::ViewportScissorCountMismatch {
+                viewport_count,
+                scissor_count,
+            },
+        );
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum CheckDynamicStateValidityError {
     /// A device feature that was required for a particular dynamic state value was not enabled.
@@ -1406,11 +2054,100 @@ fn check_indirect_buffer(
     Ok(())
 }
 
+/// Checks whether an indirect draw of `draw_count` commands of `command_size` bytes each, spaced
+/// `stride` bytes apart, is valid for the given indirect buffer.
+fn check_indirect_draw(
+    device: &Device,
+    buffer: &dyn BufferAccess,
+    draw_count: u32,
+    stride: u32,
+    command_size: u32,
+) -> Result<(), CheckIndirectBufferError> {
+    // VUID-vkCmdDrawIndirect-offset-02710
+    // VUID-vkCmdDrawIndexedIndirect-offset-02710
+    let offset = buffer.inner().offset;
+    if offset % 4 != 0 {
+        return Err(CheckIndirectBufferError::OffsetNotAligned { offset });
+    }
+
+    // VUID-vkCmdDrawIndirect-drawCount-02718
+    // VUID-vkCmdDrawIndexedIndirect-drawCount-02718
+    if draw_count > 1 && !device.enabled_features().multi_draw_indirect {
+        return Err(CheckIndirectBufferError::FeatureNotEnabled {
+            feature: "multi_draw_indirect",
+            reason: "the indirect buffer contained more than one draw command",
+        });
+    }
+
+    // VUID-vkCmdDrawIndirect-drawCount-02719
+    // VUID-vkCmdDrawIndexedIndirect-drawCount-02719
+    let limit = device
+        .physical_device()
+        .properties()
+        .max_draw_indirect_count;
+    if draw_count > limit {
+        return Err(
+            CheckIndirectBufferError::MaxDrawIndirectCountLimitExceeded {
+                limit,
+                requested: draw_count,
+            },
+        );
+    }
+
+    if draw_count == 0 {
+        return Ok(());
+    }
+
+    // VUID-vkCmdDrawIndirect-drawCount-00476
+    // VUID-vkCmdDrawIndexedIndirect-drawCount-00528
+    if draw_count > 1 && (stride % 4 != 0 || stride < command_size) {
+        return Err(CheckIndirectBufferError::InvalidStride {
+            stride,
+            command_size,
+        });
+    }
+
+    // VUID-vkCmdDrawIndirect-drawCount-00487
+    // VUID-vkCmdDrawIndirect-drawCount-00488
+    let required_size =
+        stride as DeviceSize * (draw_count - 1) as DeviceSize + command_size as DeviceSize;
+    if required_size > buffer.size() {
+        return Err(CheckIndirectBufferError::OutOfBounds {
+            required_size,
+            actual_size: buffer.size(),
+        });
+    }
+
+    // VUID-VkDrawIndirectCommand-firstInstance-00501
+    // VUID-VkDrawIndexedIndirectCommand-firstInstance-00554
+    // The `first_instance` values are only known once the buffer has been written, which may
+    // happen on the device, so the `draw_indirect_first_instance` feature can't be checked here.
+    // This requirement is documented on `draw_indirect` and `draw_indexed_indirect` instead.
+
+    Ok(())
+}
+
 /// Error that can happen when checking whether binding an indirect buffer is valid.
 #[derive(Debug, Copy, Clone)]
 pub enum CheckIndirectBufferError {
     /// The "indirect buffer" usage must be enabled on the indirect buffer.
     BufferMissingUsage,
+
+    /// A device feature that was required for the indirect draw was not enabled.
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// The stride between the draw commands is not a multiple of 4, or is smaller than the size
+    /// of a single draw command.
+    InvalidStride {
+        /// The stride between the draw commands.
+        stride: u32,
+        /// The size of a single draw command.
+        command_size: u32,
+    },
+
     /// The maximum number of indirect draws has been exceeded.
     MaxDrawIndirectCountLimitExceeded {
         /// The limit that must be fulfilled.
@@ -1418,6 +2155,20 @@ pub enum CheckIndirectBufferError {
         /// What was requested.
         requested: u32,
     },
+
+    /// The offset of the indirect buffer is not a multiple of 4.
+    OffsetNotAligned {
+        /// The offset of the indirect buffer.
+        offset: DeviceSize,
+    },
+
+    /// The draw commands would read past the end of the indirect buffer.
+    OutOfBounds {
+        /// The number of bytes that the draw commands read from the buffer.
+        required_size: DeviceSize,
+        /// The size of the buffer.
+        actual_size: DeviceSize,
+    },
 }
 
 impl error::Error for CheckIndirectBufferError {}
@@ -1425,31 +2176,57 @@ impl error::Error for CheckIndirectBufferError {}
 impl fmt::Display for CheckIndirectBufferError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(
-            fmt,
-            "{}",
-            match *self {
-                CheckIndirectBufferError::BufferMissingUsage => {
-                    "the indirect buffer usage must be enabled on the indirect buffer"
-                }
-                CheckIndirectBufferError::MaxDrawIndirectCountLimitExceeded {
-                    limit,
-                    requested,
-                } => {
-                    "the maximum number of indirect draws has been exceeded"
-                }
+        match *self {
+            CheckIndirectBufferError::BufferMissingUsage => write!(
+                fmt,
+                "the indirect buffer usage must be enabled on the indirect buffer",
+            ),
+            CheckIndirectBufferError::FeatureNotEnabled { feature, reason } => {
+                write!(fmt, "the feature {} must be enabled: {}", feature, reason)
             }
-        )
+            CheckIndirectBufferError::InvalidStride {
+                stride,
+                command_size,
+            } => write!(
+                fmt,
+                "the stride {} between draw commands is not a multiple of 4, or is smaller than \
+                the size of a draw command ({})",
+                stride, command_size,
+            ),
+            CheckIndirectBufferError::MaxDrawIndirectCountLimitExceeded { limit, requested } => {
+                write!(
+                    fmt,
+                    "the maximum number of indirect draws has been exceeded: {} were requested, \
+                    but the limit is {}",
+                    requested, limit,
+                )
+            }
+            CheckIndirectBufferError::OffsetNotAligned { offset } => write!(
+                fmt,
+                "the offset {} of the indirect buffer is not a multiple of 4",
+                offset,
+            ),
+            CheckIndirectBufferError::OutOfBounds {
+                required_size,
+                actual_size,
+            } => write!(
+                fmt,
+                "the draw commands read {} bytes from the indirect buffer, but it is only {} bytes \
+                in size",
+                required_size, actual_size,
+            ),
+        }
     }
 }
 
 fn check_vertex_buffers(
     current_state: CommandBufferState,
-    pipeline: &GraphicsPipeline,
+    device: &Device,
+    vertex_input: &VertexInputState,
+    view_mask: u32,
     vertices: Option<(u32, u32)>,
     instances: Option<(u32, u32)>,
 ) -> Result<(), CheckVertexBufferError> {
-    let vertex_input = pipeline.vertex_input_state();
     let mut max_vertex_count: Option<u32> = None;
     let mut max_instance_count: Option<u32> = None;
 
@@ -1516,14 +2293,8 @@ fn check_vertex_buffers(
             }
         }
 
-        let view_mask = match pipeline.render_pass() {
-            PipelineRenderPassType::BeginRenderPass(subpass) => subpass.render_pass().views_used(),
-            PipelineRenderPassType::BeginRendering(rendering_info) => rendering_info.view_mask,
-        };
-
         if view_mask != 0 {
-            let max_instance_index = pipeline
-                .device()
+            let max_instance_index = device
                 .physical_device()
                 .properties()
                 .max_multiview_instance_index
@@ -1575,6 +2346,21 @@ pub enum CheckVertexBufferError {
         /// The allowed amount of instances.
         max_instance_count: u32,
     },
+
+    /// The instance buffer passed to `draw_instanced` does not contain enough elements for the
+    /// requested number of instances.
+    InstanceBufferTooSmall {
+        /// The requested amount of instances.
+        instance_count: u32,
+        /// The number of elements in the instance buffer.
+        instance_buffer_len: u32,
+        /// The amount of instances that the instance buffer allows.
+        max_instance_count: u32,
+    },
+
+    /// The bound pipeline does not have a vertex binding with the input rate that
+    /// `draw_instanced` requires for this binding number.
+    WrongInputRate { binding_num: u32 },
 }
 
 impl error::Error for CheckVertexBufferError {}
@@ -1582,27 +2368,106 @@ impl error::Error for CheckVertexBufferError {}
 impl fmt::Display for CheckVertexBufferError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(
-            fmt,
-            "{}",
-            match *self {
-                CheckVertexBufferError::BufferNotBound { .. } => {
-                    "no buffer was bound to a binding slot needed by the pipeline"
-                }
-                CheckVertexBufferError::BufferMissingUsage { .. } => {
-                    "the vertex buffer usage is missing on a vertex buffer"
-                }
-                CheckVertexBufferError::TooManyVertices { .. } => {
-                    "the draw command requested too many vertices"
-                }
-                CheckVertexBufferError::TooManyInstances { .. } => {
-                    "the draw command requested too many instances"
-                }
+        match *self {
+            CheckVertexBufferError::BufferNotBound { binding_num } => write!(
+                fmt,
+                "no buffer was bound to binding slot {}, which is needed by the pipeline",
+                binding_num,
+            ),
+            CheckVertexBufferError::BufferMissingUsage { .. } => {
+                write!(fmt, "the vertex buffer usage is missing on a vertex buffer")
             }
-        )
+            CheckVertexBufferError::TooManyVertices {
+                vertex_count,
+                max_vertex_count,
+            } => write!(
+                fmt,
+                "the draw command requested {} vertices, but the bound vertex buffers only allow {}",
+                vertex_count, max_vertex_count,
+            ),
+            CheckVertexBufferError::TooManyInstances {
+                instance_count,
+                max_instance_count,
+            } => write!(
+                fmt,
+                "the draw command requested {} instances, but the bound vertex buffers only allow \
+                {}",
+                instance_count, max_instance_count,
+            ),
+            CheckVertexBufferError::InstanceBufferTooSmall {
+                instance_count,
+                instance_buffer_len,
+                max_instance_count,
+            } => write!(
+                fmt,
+                "{} instances were requested, but the instance buffer contains {} elements, which \
+                only allow {} instances",
+                instance_count, instance_buffer_len, max_instance_count,
+            ),
+            CheckVertexBufferError::WrongInputRate { binding_num } => write!(
+                fmt,
+                "the bound pipeline does not have a vertex binding {} with the input rate \
+                required by draw_instanced",
+                binding_num,
+            ),
+        }
     }
 }
 
+/// Checks whether a vertex buffer and an instance buffer of the given sizes, bound to bindings 0
+/// and 1, can be used to draw `instance_count` instances. Returns the number of vertices in the
+/// vertex buffer.
+fn check_instanced_buffers(
+    vertex_input: &VertexInputState,
+    vertex_buffer_size: DeviceSize,
+    instance_buffer_size: DeviceSize,
+    instance_count: u32,
+) -> Result<u32, CheckVertexBufferError> {
+    let vertex_binding = match vertex_input.bindings.get(&0) {
+        Some(
+            binding_desc @ VertexInputBindingDescription {
+                input_rate: VertexInputRate::Vertex,
+                ..
+            },
+        ) => binding_desc,
+        _ => return Err(CheckVertexBufferError::WrongInputRate { binding_num: 0 }),
+    };
+
+    let (instance_binding, divisor) = match vertex_input.bindings.get(&1) {
+        Some(
+            binding_desc @ VertexInputBindingDescription {
+                input_rate: VertexInputRate::Instance { divisor },
+                ..
+            },
+        ) => (binding_desc, *divisor),
+        _ => return Err(CheckVertexBufferError::WrongInputRate { binding_num: 1 }),
+    };
+
+    let vertex_count = (vertex_buffer_size / vertex_binding.stride as DeviceSize)
+        .try_into()
+        .unwrap_or(u32::MAX);
+    let instance_buffer_len: u32 = (instance_buffer_size / instance_binding.stride as DeviceSize)
+        .try_into()
+        .unwrap_or(u32::MAX);
+
+    // Same as in `check_vertex_buffers`: a divisor of 0 lets a single element be used for any
+    // number of instances, a larger divisor lets each element be used for several instances.
+    let max_instance_count = match divisor {
+        0 if instance_buffer_len != 0 => u32::MAX,
+        _ => instance_buffer_len.saturating_mul(divisor),
+    };
+
+    if instance_count > max_instance_count {
+        return Err(CheckVertexBufferError::InstanceBufferTooSmall {
+            instance_count,
+            instance_buffer_len,
+            max_instance_count,
+        });
+    }
+
+    Ok(vertex_count)
+}
+
 /// Checks whether the dispatch dimensions are supported by the device.
 fn check_dispatch(device: &Device, dimensions: [u32; 3]) -> Result<(), CheckDispatchError> {
     let max = device
@@ -1680,14 +2545,8 @@ impl SyncCommandBufferBuilder {
             }
         }
 
-        let pipeline = self.current_state.pipeline_compute.as_ref().unwrap();
-
         let mut resources = Vec::new();
-        self.add_descriptor_set_resources(
-            &mut resources,
-            PipelineBindPoint::Compute,
-            pipeline.descriptor_requirements(),
-        );
+        self.add_compute_resources(&mut resources);
 
         for resource in &resources {
             self.check_resource_conflicts(resource)?;
@@ -1722,14 +2581,8 @@ impl SyncCommandBufferBuilder {
             }
         }
 
-        let pipeline = self.current_state.pipeline_compute.as_ref().unwrap();
-
         let mut resources = Vec::new();
-        self.add_descriptor_set_resources(
-            &mut resources,
-            PipelineBindPoint::Compute,
-            pipeline.descriptor_requirements(),
-        );
+        self.add_compute_resources(&mut resources);
         self.add_indirect_buffer_resources(&mut resources, &indirect_buffer);
 
         for resource in &resources {
@@ -1776,15 +2629,8 @@ impl SyncCommandBufferBuilder {
             }
         }
 
-        let pipeline = self.current_state.pipeline_graphics.as_ref().unwrap();
-
         let mut resources = Vec::new();
-        self.add_descriptor_set_resources(
-            &mut resources,
-            PipelineBindPoint::Graphics,
-            pipeline.descriptor_requirements(),
-        );
-        self.add_vertex_buffer_resources(&mut resources, pipeline.vertex_input_state());
+        self.add_graphics_resources(&mut resources);
 
         for resource in &resources {
             self.check_resource_conflicts(resource)?;
@@ -1838,15 +2684,8 @@ impl SyncCommandBufferBuilder {
             }
         }
 
-        let pipeline = self.current_state.pipeline_graphics.as_ref().unwrap();
-
         let mut resources = Vec::new();
-        self.add_descriptor_set_resources(
-            &mut resources,
-            PipelineBindPoint::Graphics,
-            pipeline.descriptor_requirements(),
-        );
-        self.add_vertex_buffer_resources(&mut resources, pipeline.vertex_input_state());
+        self.add_graphics_resources(&mut resources);
         self.add_index_buffer_resources(&mut resources);
 
         for resource in &resources {
@@ -1892,15 +2731,8 @@ impl SyncCommandBufferBuilder {
             }
         }
 
-        let pipeline = self.current_state.pipeline_graphics.as_ref().unwrap();
-
         let mut resources = Vec::new();
-        self.add_descriptor_set_resources(
-            &mut resources,
-            PipelineBindPoint::Graphics,
-            pipeline.descriptor_requirements(),
-        );
-        self.add_vertex_buffer_resources(&mut resources, pipeline.vertex_input_state());
+        self.add_graphics_resources(&mut resources);
         self.add_indirect_buffer_resources(&mut resources, &indirect_buffer);
 
         for resource in &resources {
@@ -1948,15 +2780,8 @@ impl SyncCommandBufferBuilder {
             }
         }
 
-        let pipeline = self.current_state.pipeline_graphics.as_ref().unwrap();
-
         let mut resources = Vec::new();
-        self.add_descriptor_set_resources(
-            &mut resources,
-            PipelineBindPoint::Graphics,
-            pipeline.descriptor_requirements(),
-        );
-        self.add_vertex_buffer_resources(&mut resources, pipeline.vertex_input_state());
+        self.add_graphics_resources(&mut resources);
         self.add_index_buffer_resources(&mut resources);
         self.add_indirect_buffer_resources(&mut resources, &indirect_buffer);
 
@@ -1977,6 +2802,53 @@ impl SyncCommandBufferBuilder {
         Ok(())
     }
 
+    fn add_compute_resources(&self, resources: &mut Vec<(Cow<'static, str>, Resource)>) {
+        if let Some(pipeline) = self.current_state.pipeline_compute.as_ref() {
+            self.add_descriptor_set_resources(
+                resources,
+                PipelineBindPoint::Compute,
+                pipeline.descriptor_requirements(),
+            );
+        } else {
+            let descriptor_requirements = self
+                .current_state
+                .shader_descriptor_requirements(PipelineBindPoint::Compute);
+            self.add_descriptor_set_resources(
+                resources,
+                PipelineBindPoint::Compute,
+                descriptor_requirements
+                    .iter()
+                    .map(|(loc, reqs)| (*loc, reqs)),
+            );
+        }
+    }
+
+    fn add_graphics_resources(&self, resources: &mut Vec<(Cow<'static, str>, Resource)>) {
+        if let Some(pipeline) = self.current_state.pipeline_graphics.as_ref() {
+            self.add_descriptor_set_resources(
+                resources,
+                PipelineBindPoint::Graphics,
+                pipeline.descriptor_requirements(),
+            );
+            self.add_vertex_buffer_resources(resources, pipeline.vertex_input_state());
+        } else {
+            let descriptor_requirements = self
+                .current_state
+                .shader_descriptor_requirements(PipelineBindPoint::Graphics);
+            self.add_descriptor_set_resources(
+                resources,
+                PipelineBindPoint::Graphics,
+                descriptor_requirements
+                    .iter()
+                    .map(|(loc, reqs)| (*loc, reqs)),
+            );
+
+            if let Some(vertex_input) = self.current_state.vertex_input.as_ref() {
+                self.add_vertex_buffer_resources(resources, vertex_input);
+            }
+        }
+    }
+
     fn add_descriptor_set_resources<'a>(
         &self,
         resources: &mut Vec<(Cow<'static, str>, Resource)>,
@@ -2356,6 +3228,7 @@ impl UnsafeCommandBufferBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::buffer::{BufferUsage, CpuAccessibleBuffer};
 
     #[test]
     fn max_checked() {
@@ -2381,6 +3254,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn instance_buffer_too_small() {
+        let vertex_input = VertexInputState::new()
+            .binding(
+                0,
+                VertexInputBindingDescription {
+                    stride: 16,
+                    input_rate: VertexInputRate::Vertex,
+                },
+            )
+            .binding(
+                1,
+                VertexInputBindingDescription {
+                    stride: 8,
+                    input_rate: VertexInputRate::Instance { divisor: 1 },
+                },
+            );
+
+        assert_eq!(
+            check_instanced_buffers(&vertex_input, 16 * 3, 8 * 4, 4).unwrap(),
+            3
+        );
+
+        match check_instanced_buffers(&vertex_input, 16 * 3, 8 * 4, 5) {
+            Err(CheckVertexBufferError::InstanceBufferTooSmall {
+                instance_count: 5,
+                instance_buffer_len: 4,
+                max_instance_count: 4,
+            }) => (),
+            _ => panic!(),
+        }
+
+        match check_instanced_buffers(&VertexInputState::new(), 16 * 3, 8 * 4, 1) {
+            Err(CheckVertexBufferError::WrongInputRate { binding_num: 0 }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn indirect_draw_checked() {
+        // Drawing more than one command requires `multi_draw_indirect`.
+        let (device, _) = gfx_dev_and_queue!(multi_draw_indirect);
+
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::indirect_buffer(),
+            false,
+            [DrawIndirectCommand::default(); 2],
+        )
+        .unwrap();
+        let command_size = size_of::<DrawIndirectCommand>() as u32;
+
+        match check_indirect_draw(&device, buffer.as_ref(), 3, command_size, command_size) {
+            Err(CheckIndirectBufferError::OutOfBounds {
+                required_size,
+                actual_size,
+            }) => {
+                assert_eq!(required_size, 3 * command_size as DeviceSize);
+                assert_eq!(actual_size, 2 * command_size as DeviceSize);
+            }
+            _ => panic!(),
+        }
+
+        check_indirect_draw(&device, buffer.as_ref(), 2, command_size, command_size).unwrap();
+    }
+
+    #[test]
+    fn indirect_draw_first_instance_unchecked() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        // `first_instance` is documented as the caller's responsibility, since the device may
+        // write the buffer, so a non-zero value passes validation even without the feature.
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::indirect_buffer(),
+            false,
+            [DrawIndirectCommand {
+                vertex_count: 3,
+                instance_count: 1,
+                first_vertex: 0,
+                first_instance: 1,
+            }],
+        )
+        .unwrap();
+        let command_size = size_of::<DrawIndirectCommand>() as u32;
+
+        assert!(!device.enabled_features().draw_indirect_first_instance);
+        check_indirect_draw(&device, buffer.as_ref(), 1, command_size, command_size).unwrap();
+    }
+
     #[test]
     fn zero_dimension_checked() {
         let (device, _) = gfx_dev_and_queue!();
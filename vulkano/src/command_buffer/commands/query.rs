@@ -17,8 +17,8 @@ use crate::{
     },
     device::{physical::QueueFamily, DeviceOwned},
     query::{
-        QueriesRange, Query, QueryControlFlags, QueryPool, QueryResultElement, QueryResultFlags,
-        QueryType,
+        performance::PerformanceCounterScope, QueriesRange, Query, QueryControlFlags, QueryPool,
+        QueryResultElement, QueryResultFlags, QueryType,
     },
     sync::{AccessFlags, PipelineMemoryAccess, PipelineStage, PipelineStages},
     DeviceSize, VulkanObject,
@@ -31,9 +31,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     ///
     /// The query will be active until [`end_query`](Self::end_query) is called for the same query.
     ///
-    /// # Safety
-    /// The query must be unavailable, ensured by calling [`reset_query_pool`](Self::reset_query_pool).
-    pub unsafe fn begin_query(
+    /// The query must have been reset with [`reset_query_pool`](Self::reset_query_pool) since it
+    /// was last used. If it was not reset in this command buffer, this is checked when the
+    /// command buffer is submitted.
+    pub fn begin_query(
         &mut self,
         query_pool: Arc<QueryPool>,
         query: u32,
@@ -42,15 +43,25 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         self.validate_begin_query(&query_pool, query, flags)?;
 
         let ty = query_pool.query_type();
+        let pipeline_statistics = query_pool.pipeline_statistics();
         let raw_query_pool = query_pool.internal_object();
+        let queries = query..query + self.query_view_count();
+
+        unsafe {
+            self.inner.begin_query(query_pool.clone(), query, flags);
+        }
 
-        self.inner.begin_query(query_pool, query, flags);
+        self.add_query_command(QueryCommand::Use {
+            query_pool,
+            queries,
+        });
         self.query_state.insert(
             ty.into(),
             QueryState {
                 query_pool: raw_query_pool,
                 query,
                 ty,
+                pipeline_statistics,
                 flags,
                 in_subpass: self.render_pass_state.is_some(),
             },
@@ -94,7 +105,9 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
                     });
                 }
             }
-            QueryType::PipelineStatistics(statistic_flags) => {
+            QueryType::PipelineStatistics => {
+                let statistic_flags = query_pool.pipeline_statistics();
+
                 // VUID-vkCmdBeginQuery-commandBuffer-cmdpool
                 // VUID-vkCmdBeginQuery-queryType-00804
                 // VUID-vkCmdBeginQuery-queryType-00805
@@ -109,6 +122,36 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
                     return Err(QueryError::InvalidFlags);
                 }
             }
+            QueryType::PerformanceQuery => {
+                // VUID?
+                if self.queue_family().id() != query_pool.performance_queue_family_index() {
+                    return Err(QueryError::NotSupportedByQueueFamily);
+                }
+
+                // VUID-vkCmdBeginQuery-queryPool-03223
+                if !device.is_profiling_lock_held() {
+                    return Err(QueryError::ProfilingLockNotHeld);
+                }
+
+                // VUID-vkCmdBeginQuery-queryPool-03225
+                if self.render_pass_state.is_some()
+                    && query_pool
+                        .performance_counters()
+                        .iter()
+                        .any(|counter| counter.scope == PerformanceCounterScope::RenderPass)
+                {
+                    return Err(QueryError::ForbiddenInsideRenderPass);
+                }
+
+                // TODO:
+                // VUID-vkCmdBeginQuery-queryPool-03224
+                // VUID-vkCmdBeginQuery-queryPool-03226
+                // VUID-vkCmdBeginQuery-None-02863
+
+                if flags.precise {
+                    return Err(QueryError::InvalidFlags);
+                }
+            }
             // VUID-vkCmdBeginQuery-queryType-02804
             QueryType::Timestamp => return Err(QueryError::NotPermitted),
         }
@@ -121,27 +164,7 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             return Err(QueryError::QueryIsActive);
         }
 
-        if let Some(state) = &self.render_pass_state {
-            let view_mask = match &state.render_pass {
-                RenderPassStateType::BeginRenderPass(state) => {
-                    state.subpass.subpass_desc().view_mask
-                }
-                RenderPassStateType::BeginRendering(state) => state.view_mask,
-                RenderPassStateType::Inherited => match self
-                    .inheritance_info
-                    .as_ref()
-                    .unwrap()
-                    .render_pass
-                    .as_ref()
-                    .unwrap()
-                {
-                    CommandBufferInheritanceRenderPassType::BeginRenderPass(info) => {
-                        info.subpass.subpass_desc().view_mask
-                    }
-                    CommandBufferInheritanceRenderPassType::BeginRendering(info) => info.view_mask,
-                },
-            };
-
+        if let Some(view_mask) = self.render_pass_view_mask() {
             // VUID-vkCmdBeginQuery-query-00808
             if query + view_mask.count_ones() > query_pool.query_count() {
                 return Err(QueryError::OutOfRangeMultiview);
@@ -149,8 +172,8 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         }
 
         // VUID-vkCmdBeginQuery-None-00807
-        // Not checked, therefore unsafe.
-        // TODO: add check.
+        // Queries that were not reset in this command buffer are checked on submission.
+        self.check_queries_reset(query_pool, query..query + self.query_view_count())?;
 
         Ok(())
     }
@@ -194,30 +217,18 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             return Err(QueryError::QueryNotActive.into());
         }
 
+        // A query that was begun outside a render pass instance must also be ended outside of it.
+        // The converse is checked when ending the subpass.
+        if self.render_pass_state.is_some()
+            && !self.query_state[&query_pool.query_type().into()].in_subpass
+        {
+            return Err(QueryError::ForbiddenInsideRenderPass);
+        }
+
         // VUID-vkCmdEndQuery-query-00810
         query_pool.query(query).ok_or(QueryError::OutOfRange)?;
 
-        if let Some(state) = &self.render_pass_state {
-            let view_mask = match &state.render_pass {
-                RenderPassStateType::BeginRenderPass(state) => {
-                    state.subpass.subpass_desc().view_mask
-                }
-                RenderPassStateType::BeginRendering(state) => state.view_mask,
-                RenderPassStateType::Inherited => match self
-                    .inheritance_info
-                    .as_ref()
-                    .unwrap()
-                    .render_pass
-                    .as_ref()
-                    .unwrap()
-                {
-                    CommandBufferInheritanceRenderPassType::BeginRenderPass(info) => {
-                        info.subpass.subpass_desc().view_mask
-                    }
-                    CommandBufferInheritanceRenderPassType::BeginRendering(info) => info.view_mask,
-                },
-            };
-
+        if let Some(view_mask) = self.render_pass_view_mask() {
             // VUID-vkCmdEndQuery-query-00812
             if query + view_mask.count_ones() > query_pool.query_count() {
                 return Err(QueryError::OutOfRangeMultiview);
@@ -229,9 +240,10 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
     /// Writes a timestamp to a timestamp query.
     ///
-    /// # Safety
-    /// The query must be unavailable, ensured by calling [`reset_query_pool`](Self::reset_query_pool).
-    pub unsafe fn write_timestamp(
+    /// The query must have been reset with [`reset_query_pool`](Self::reset_query_pool) since it
+    /// was last used. If it was not reset in this command buffer, this is checked when the
+    /// command buffer is submitted.
+    pub fn write_timestamp(
         &mut self,
         query_pool: Arc<QueryPool>,
         query: u32,
@@ -239,7 +251,16 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     ) -> Result<&mut Self, QueryError> {
         self.validate_write_timestamp(self.queue_family(), &query_pool, query, stage)?;
 
-        self.inner.write_timestamp(query_pool, query, stage);
+        let queries = query..query + self.query_view_count();
+
+        unsafe {
+            self.inner.write_timestamp(query_pool.clone(), query, stage);
+        }
+
+        self.add_query_command(QueryCommand::Use {
+            query_pool,
+            queries,
+        });
 
         Ok(self)
     }
@@ -306,44 +327,23 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         // VUID-vkCmdWriteTimestamp-query-04904
         query_pool.query(query).ok_or(QueryError::OutOfRange)?;
 
-        if let Some(state) = &self.render_pass_state {
-            let view_mask = match &state.render_pass {
-                RenderPassStateType::BeginRenderPass(state) => {
-                    state.subpass.subpass_desc().view_mask
-                }
-                RenderPassStateType::BeginRendering(state) => state.view_mask,
-                RenderPassStateType::Inherited => match self
-                    .inheritance_info
-                    .as_ref()
-                    .unwrap()
-                    .render_pass
-                    .as_ref()
-                    .unwrap()
-                {
-                    CommandBufferInheritanceRenderPassType::BeginRenderPass(info) => {
-                        info.subpass.subpass_desc().view_mask
-                    }
-                    CommandBufferInheritanceRenderPassType::BeginRendering(info) => info.view_mask,
-                },
-            };
-
+        if let Some(view_mask) = self.render_pass_view_mask() {
             // VUID-vkCmdWriteTimestamp-query-00831
             if query + view_mask.count_ones() > query_pool.query_count() {
                 return Err(QueryError::OutOfRangeMultiview);
             }
         }
 
-        // VUID-vkCmdWriteTimestamp-queryPool-00828
         // VUID-vkCmdWriteTimestamp-None-00830
-        // Not checked, therefore unsafe.
-        // TODO: add check.
+        // Queries that were not reset in this command buffer are checked on submission.
+        self.check_queries_reset(query_pool, query..query + self.query_view_count())?;
 
         Ok(())
     }
 
     /// Copies the results of a range of queries to a buffer on the GPU.
     ///
-    /// [`query_pool.ty().result_len()`](crate::query::QueryType::result_len) elements
+    /// [`query_pool.result_len()`](crate::query::QueryPool::result_len) elements
     /// will be written for each query in the range, plus 1 extra element per query if
     /// [`QueryResultFlags::with_availability`] is enabled.
     /// The provided buffer must be large enough to hold the data.
@@ -368,8 +368,7 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         )?;
 
         unsafe {
-            let per_query_len =
-                query_pool.query_type().result_len() + flags.with_availability as DeviceSize;
+            let per_query_len = query_pool.result_len() + flags.with_availability as DeviceSize;
             let stride = per_query_len * std::mem::size_of::<T>() as DeviceSize;
             self.inner
                 .copy_query_pool_results(query_pool, queries, destination, stride, flags)?;
@@ -418,9 +417,13 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
             .queries_range(queries.clone())
             .ok_or(QueryError::OutOfRange)?;
 
+        // Performance query results can't be copied as plain integers.
+        if matches!(query_pool.query_type(), QueryType::PerformanceQuery) {
+            return Err(QueryError::NotPermitted);
+        }
+
         let count = queries.end - queries.start;
-        let per_query_len =
-            query_pool.query_type().result_len() + flags.with_availability as DeviceSize;
+        let per_query_len = query_pool.result_len() + flags.with_availability as DeviceSize;
         let required_len = per_query_len * count as DeviceSize;
 
         // VUID-vkCmdCopyQueryPoolResults-dstBuffer-00824
@@ -448,18 +451,22 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     ///
     /// The affected queries will be marked as "unavailable" after this command runs, and will no
     /// longer return any results. They will be ready to have new results recorded for them.
-    ///
-    /// # Safety
-    /// The queries in the specified range must not be active in another command buffer.
-    // TODO: Do other command buffers actually matter here? Not sure on the Vulkan spec.
-    pub unsafe fn reset_query_pool(
+    pub fn reset_query_pool(
         &mut self,
         query_pool: Arc<QueryPool>,
         queries: Range<u32>,
     ) -> Result<&mut Self, QueryError> {
         self.validate_reset_query_pool(&query_pool, queries.clone())?;
 
-        self.inner.reset_query_pool(query_pool, queries);
+        unsafe {
+            self.inner
+                .reset_query_pool(query_pool.clone(), queries.clone());
+        }
+
+        self.add_query_command(QueryCommand::Reset {
+            query_pool,
+            queries,
+        });
 
         Ok(self)
     }
@@ -499,6 +506,114 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
         Ok(())
     }
+
+    // Returns the view mask of the current subpass, or `None` if outside a render pass.
+    fn render_pass_view_mask(&self) -> Option<u32> {
+        self.render_pass_state
+            .as_ref()
+            .map(|state| match &state.render_pass {
+                RenderPassStateType::BeginRenderPass(state) => {
+                    state.subpass.subpass_desc().view_mask
+                }
+                RenderPassStateType::BeginRendering(state) => state.view_mask,
+                RenderPassStateType::Inherited => match self
+                    .inheritance_info
+                    .as_ref()
+                    .unwrap()
+                    .render_pass
+                    .as_ref()
+                    .unwrap()
+                {
+                    CommandBufferInheritanceRenderPassType::BeginRenderPass(info) => {
+                        info.subpass.subpass_desc().view_mask
+                    }
+                    CommandBufferInheritanceRenderPassType::BeginRendering(info) => info.view_mask,
+                },
+            })
+    }
+
+    // Returns the number of consecutive queries that a query command uses. With multiview, one
+    // query is used for each view.
+    fn query_view_count(&self) -> u32 {
+        self.render_pass_view_mask()
+            .map_or(1, |view_mask| view_mask.count_ones().max(1))
+    }
+
+    // Checks that none of the queries were used in this command buffer after being reset.
+    fn check_queries_reset(
+        &self,
+        query_pool: &QueryPool,
+        queries: Range<u32>,
+    ) -> Result<(), QueryError> {
+        let handle = query_pool.internal_object();
+
+        for query in queries {
+            if self.query_reset_state.get(&(handle, query)) == Some(&false) {
+                return Err(QueryError::QueryNotReset { query });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Records a command that changes the reset state of queries.
+    pub(in crate::command_buffer) fn add_query_command(&mut self, command: QueryCommand) {
+        let (query_pool, queries, reset) = match &command {
+            QueryCommand::Reset {
+                query_pool,
+                queries,
+            } => (query_pool, queries, true),
+            QueryCommand::Use {
+                query_pool,
+                queries,
+            } => (query_pool, queries, false),
+        };
+        let handle = query_pool.internal_object();
+
+        for query in queries.clone() {
+            self.query_reset_state.insert((handle, query), reset);
+        }
+
+        self.query_commands.push(command);
+    }
+}
+
+/// A recorded command that changes whether queries are reset.
+///
+/// A query must be reset before each use. Whether this is the case is only known when a command
+/// buffer is submitted, so command buffers keep a list of these commands to check it.
+#[derive(Clone, Debug)]
+pub enum QueryCommand {
+    /// The queries are reset by `reset_query_pool`.
+    Reset {
+        query_pool: Arc<QueryPool>,
+        queries: Range<u32>,
+    },
+
+    /// The queries are used by `begin_query` or `write_timestamp`, and must have been reset
+    /// beforehand.
+    Use {
+        query_pool: Arc<QueryPool>,
+        queries: Range<u32>,
+    },
+}
+
+impl QueryCommand {
+    /// Returns the query pool that the command applies to.
+    #[inline]
+    pub fn query_pool(&self) -> &Arc<QueryPool> {
+        match self {
+            Self::Reset { query_pool, .. } | Self::Use { query_pool, .. } => query_pool,
+        }
+    }
+
+    /// Returns the range of queries that the command applies to.
+    #[inline]
+    pub fn queries(&self) -> Range<u32> {
+        match self {
+            Self::Reset { queries, .. } | Self::Use { queries, .. } => queries.clone(),
+        }
+    }
 }
 
 impl SyncCommandBufferBuilder {
@@ -807,6 +922,9 @@ pub enum QueryError {
     /// This operation is not permitted on this query type.
     NotPermitted,
 
+    /// The device's profiling lock is not held, but is required for performance queries.
+    ProfilingLockNotHeld,
+
     /// The queue family doesn't allow this operation.
     NotSupportedByQueueFamily,
 
@@ -823,6 +941,11 @@ pub enum QueryError {
     /// This query was not active.
     QueryNotActive,
 
+    /// The query was used earlier in the command buffer, and has not been reset since.
+    QueryNotReset {
+        query: u32,
+    },
+
     /// The provided stage is not supported by the queue family.
     StageNotSupported,
 }
@@ -865,6 +988,10 @@ impl fmt::Display for QueryError {
             Self::NotSupportedByQueueFamily => {
                 write!(f, "the queue family doesn't allow this operation")
             }
+            Self::ProfilingLockNotHeld => write!(
+                f,
+                "the device's profiling lock is not held, but is required for performance queries",
+            ),
             Self::OutOfRange => write!(f, "the provided query index is not valid for this pool"),
             Self::OutOfRangeMultiview => write!(
                 f,
@@ -875,6 +1002,11 @@ impl fmt::Display for QueryError {
                 "a query is active that conflicts with the current operation"
             ),
             Self::QueryNotActive => write!(f, "this query was not active"),
+            Self::QueryNotReset { query } => write!(
+                f,
+                "query {} was used earlier in the command buffer, and has not been reset since",
+                query,
+            ),
             Self::StageNotSupported => {
                 write!(f, "the provided stage is not supported by the queue family")
             }
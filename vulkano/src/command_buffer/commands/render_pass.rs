@@ -103,7 +103,7 @@ where
             ref framebuffer,
             render_area_offset,
             render_area_extent,
-            ref clear_values,
+            ref mut clear_values,
             _ne: _,
         } = render_pass_begin_info;
 
@@ -262,17 +262,17 @@ where
         }
 
         // VUID-VkRenderPassBeginInfo-clearValueCount-00902
+        // Missing trailing values are filled in with `None`, which is checked against the load
+        // operations below.
         if clear_values.len() < render_pass.attachments().len() {
-            return Err(RenderPassError::ClearValueMissing {
-                attachment_index: clear_values.len() as u32,
-            });
+            clear_values.resize(render_pass.attachments().len(), None);
         }
 
         // VUID-VkRenderPassBeginInfo-clearValueCount-04962
         for (attachment_index, (attachment_desc, &clear_value)) in render_pass
             .attachments()
             .iter()
-            .zip(clear_values)
+            .zip(clear_values.iter())
             .enumerate()
         {
             let attachment_index = attachment_index as u32;
@@ -1577,6 +1577,12 @@ impl SyncCommandBufferBuilder {
             self.check_resource_conflicts(resource)?;
         }
 
+        if let Some(usage_inference) = &mut self.usage_inference {
+            usage_inference.add_render_pass(render_pass, framebuffer);
+        }
+
+        let multiview = render_pass.views_used() != 0;
+
         self.commands.push(Box::new(Cmd {
             render_pass_begin_info,
             subpass_contents,
@@ -1588,6 +1594,12 @@ impl SyncCommandBufferBuilder {
 
         self.latest_render_pass_enter = Some(self.commands.len() - 1);
 
+        if let Some(command_timing) = &mut self.command_timing {
+            if multiview {
+                command_timing.pause(self.commands.len());
+            }
+        }
+
         Ok(())
     }
 
@@ -1629,6 +1641,10 @@ impl SyncCommandBufferBuilder {
         self.commands.push(Box::new(Cmd));
         debug_assert!(self.latest_render_pass_enter.is_some());
         self.latest_render_pass_enter = None;
+
+        if let Some(command_timing) = &mut self.command_timing {
+            command_timing.resume(self.commands.len());
+        }
     }
 
     /// Calls `vkCmdBeginRendering` on the builder.
@@ -1878,6 +1894,11 @@ impl SyncCommandBufferBuilder {
             self.check_resource_conflicts(resource)?;
         }
 
+        if let Some(usage_inference) = &mut self.usage_inference {
+            usage_inference.add_rendering(&rendering_info);
+        }
+
+        let multiview = rendering_info.view_mask != 0;
         self.commands.push(Box::new(Cmd { rendering_info }));
 
         for resource in resources {
@@ -1886,6 +1907,12 @@ impl SyncCommandBufferBuilder {
 
         self.latest_render_pass_enter = Some(self.commands.len() - 1);
 
+        if let Some(command_timing) = &mut self.command_timing {
+            if multiview {
+                command_timing.pause(self.commands.len());
+            }
+        }
+
         Ok(())
     }
 
@@ -1907,6 +1934,10 @@ impl SyncCommandBufferBuilder {
         self.commands.push(Box::new(Cmd));
         debug_assert!(self.latest_render_pass_enter.is_some());
         self.latest_render_pass_enter = None;
+
+        if let Some(command_timing) = &mut self.command_timing {
+            command_timing.resume(self.commands.len());
+        }
     }
 
     /// Calls `vkCmdClearAttachments` on the builder.
@@ -2259,12 +2290,15 @@ pub struct RenderPassBeginInfo {
 
     /// Provides, for each attachment in `render_pass` that has a load operation of
     /// [`LoadOp::Clear`], the clear values that should be used for the attachments in the
-    /// framebuffer. There must be exactly [`framebuffer.attachments().len()`] elements provided,
+    /// framebuffer. There must be at most [`framebuffer.attachments().len()`] elements provided,
     /// and each one must match the attachment format.
     ///
     /// To skip over an attachment whose load operation is something else, provide `None`.
+    /// Elements missing from the end are treated as `None`. Values can also be set by the name of
+    /// the attachment with [`set_clear_value`](Self::set_clear_value).
     ///
-    /// The default value is empty, which must be overridden if the framebuffer has attachments.
+    /// The default value is empty, which must be overridden if the render pass has attachments
+    /// with a load operation of [`LoadOp::Clear`].
     pub clear_values: Vec<Option<ClearValue>>,
 
     pub _ne: crate::NonExhaustive,
@@ -2284,6 +2318,28 @@ impl RenderPassBeginInfo {
             _ne: crate::NonExhaustive(()),
         }
     }
+
+    /// Sets the clear value of the attachment of `render_pass` whose `name` is `name`, growing
+    /// `clear_values` with `None` as needed.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `render_pass` has no attachment named `name`.
+    #[inline]
+    pub fn set_clear_value(&mut self, name: &str, clear_value: impl Into<ClearValue>) -> &mut Self {
+        let index = self
+            .render_pass
+            .attachment_index(name)
+            .unwrap_or_else(|| panic!("the render pass has no attachment named `{}`", name))
+            as usize;
+
+        if self.clear_values.len() <= index {
+            self.clear_values.resize(index + 1, None);
+        }
+
+        self.clear_values[index] = Some(clear_value.into());
+        self
+    }
 }
 
 /// Parameters to begin rendering.
@@ -14,7 +14,8 @@ use crate::{
         synced::{Command, Resource, SyncCommandBufferBuilder, SyncCommandBufferBuilderError},
         sys::UnsafeCommandBufferBuilder,
         AutoCommandBufferBuilder, CommandBufferExecError, CommandBufferInheritanceRenderPassType,
-        CommandBufferUsage, PrimaryAutoCommandBuffer, SecondaryCommandBuffer, SubpassContents,
+        CommandBufferUsage, PrimaryAutoCommandBuffer, QueryCommand, SecondaryCommandBuffer,
+        SubpassContents,
     },
     device::DeviceOwned,
     format::Format,
@@ -47,6 +48,8 @@ where
     {
         self.validate_execute_commands(&command_buffer, 0)?;
 
+        let query_commands = command_buffer.query_commands().to_vec();
+
         unsafe {
             let secondary_usage = command_buffer.inner().usage();
             let mut builder = self.inner.execute_commands();
@@ -60,6 +63,10 @@ where
             self.usage = std::cmp::min(self.usage, secondary_usage);
         }
 
+        for command in query_commands {
+            self.add_query_command(command);
+        }
+
         Ok(self)
     }
 
@@ -80,6 +87,11 @@ where
             self.validate_execute_commands(command_buffer, command_buffer_index as u32)?;
         }
 
+        let query_commands: Vec<_> = command_buffers
+            .iter()
+            .flat_map(|command_buffer| command_buffer.query_commands().iter().cloned())
+            .collect();
+
         unsafe {
             let mut secondary_usage = CommandBufferUsage::SimultaneousUse; // Most permissive usage
 
@@ -97,6 +109,10 @@ where
             self.usage = std::cmp::min(self.usage, secondary_usage);
         }
 
+        for command in query_commands {
+            self.add_query_command(command);
+        }
+
         Ok(self)
     }
 
@@ -343,7 +359,8 @@ where
                         });
                     }
                 }
-                QueryType::PipelineStatistics(state_flags) => {
+                QueryType::PipelineStatistics => {
+                    let state_flags = state.pipeline_statistics;
                     let inherited_flags = command_buffer.inheritance_info().query_statistics_flags;
                     let inherited_flags_vk =
                         ash::vk::QueryPipelineStatisticFlags::from(inherited_flags);
@@ -360,7 +377,20 @@ where
                         );
                     }
                 }
-                QueryType::Timestamp => (),
+                QueryType::Timestamp | QueryType::PerformanceQuery => (),
+            }
+
+            // VUID-vkCmdExecuteCommands-pCommandBuffers-00105
+            // Timestamps are never active, so a `Use` of a pool of an active query type comes
+            // from `begin_query`.
+            if command_buffer.query_commands().iter().any(|command| {
+                matches!(command, QueryCommand::Use { .. })
+                    && command.query_pool().query_type() == state.ty
+            }) {
+                return Err(ExecuteCommandsError::QueryTypeActive {
+                    command_buffer_index,
+                    query_type: state.ty,
+                });
             }
         }
 
@@ -368,7 +398,6 @@ where
         // VUID-vkCmdExecuteCommands-pCommandBuffers-00091
         // VUID-vkCmdExecuteCommands-pCommandBuffers-00092
         // VUID-vkCmdExecuteCommands-pCommandBuffers-00093
-        // VUID-vkCmdExecuteCommands-pCommandBuffers-00105
 
         // VUID-vkCmdExecuteCommands-bufferlevel
         // Ensured by the type of the impl block.
@@ -590,6 +619,12 @@ pub enum ExecuteCommandsError {
         inherited_flags: QueryPipelineStatisticFlags,
     },
 
+    /// A command buffer begins a query of a type that is currently active.
+    QueryTypeActive {
+        command_buffer_index: u32,
+        query_type: QueryType,
+    },
+
     /// The inherited color attachment count of a command buffer does not match the current
     /// attachment count.
     RenderPassColorAttachmentCountMismatch {
@@ -742,6 +777,14 @@ impl fmt::Display for ExecuteCommandsError {
                 "the inherited pipeline statistics query flags ({:?}) of command buffer {} are not a superset of the currently active flags ({:?})",
                 inherited_flags, command_buffer_index, required_flags,
             ),
+            Self::QueryTypeActive {
+                command_buffer_index,
+                query_type,
+            } => write!(
+                f,
+                "command buffer {} begins a query of type {:?}, which is currently active",
+                command_buffer_index, query_type,
+            ),
             Self::RenderPassColorAttachmentCountMismatch {
                 command_buffer_index,
                 required_count,
@@ -890,3 +933,76 @@ impl From<SyncCommandBufferBuilderError> for ExecuteCommandsError {
         Self::SyncCommandBufferBuilderError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ExecuteCommandsError;
+    use crate::{
+        command_buffer::{
+            AutoCommandBufferBuilder, CommandBufferInheritanceInfo, CommandBufferUsage,
+        },
+        query::{
+            QueryControlFlags, QueryPipelineStatisticFlags, QueryPool, QueryPoolCreateInfo,
+            QueryType,
+        },
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn query_type_active() {
+        let (device, queue) = gfx_dev_and_queue!(inherited_queries, pipeline_statistics_query);
+
+        let pipeline_statistics = QueryPipelineStatisticFlags {
+            input_assembly_vertices: true,
+            ..QueryPipelineStatisticFlags::none()
+        };
+        let query_pool = QueryPool::new(
+            device.clone(),
+            QueryPoolCreateInfo {
+                query_count: 2,
+                pipeline_statistics,
+                ..QueryPoolCreateInfo::query_type(QueryType::PipelineStatistics)
+            },
+        )
+        .unwrap();
+
+        // The secondary command buffer inherits the active query, but also begins a query of the
+        // same type itself.
+        let mut builder = AutoCommandBufferBuilder::secondary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+            CommandBufferInheritanceInfo {
+                query_statistics_flags: pipeline_statistics,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        builder
+            .begin_query(query_pool.clone(), 1, QueryControlFlags::default())
+            .unwrap()
+            .end_query(query_pool.clone(), 1)
+            .unwrap();
+        let secondary = Arc::new(builder.build().unwrap());
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .reset_query_pool(query_pool.clone(), 0..2)
+            .unwrap()
+            .begin_query(query_pool, 0, QueryControlFlags::default())
+            .unwrap();
+
+        match builder.execute_commands(secondary) {
+            Err(ExecuteCommandsError::QueryTypeActive {
+                command_buffer_index: 0,
+                query_type: QueryType::PipelineStatistics,
+            }) => (),
+            _ => panic!(),
+        }
+    }
+}
@@ -8,18 +8,19 @@
 // according to those terms.
 
 use crate::{
+    buffer::BufferAccess,
     command_buffer::{
-        synced::{Command, SyncCommandBufferBuilder},
+        synced::{Command, Resource, SyncCommandBufferBuilder, SyncCommandBufferBuilderError},
         sys::UnsafeCommandBufferBuilder,
     },
-    image::ImageLayout,
+    image::{ImageAccess, ImageLayout},
     sync::{
-        BufferMemoryBarrier, DependencyInfo, Event, ImageMemoryBarrier, MemoryBarrier,
-        PipelineStages,
+        AccessFlags, BufferMemoryBarrier, DependencyInfo, Event, ImageMemoryBarrier, MemoryBarrier,
+        PipelineMemoryAccess, PipelineStages, QueueFamilyTransfer,
     },
     Version, VulkanObject,
 };
-use smallvec::SmallVec;
+use smallvec::{smallvec, SmallVec};
 use std::sync::Arc;
 
 impl SyncCommandBufferBuilder {
@@ -64,6 +65,280 @@ impl SyncCommandBufferBuilder {
 
         self.commands.push(Box::new(Cmd { event, stages }));
     }
+
+    /// Records a barrier that releases the ownership of `buffer` to another queue family, after
+    /// it has been written by transfer commands.
+    pub(in crate::command_buffer) unsafe fn release_buffer_ownership(
+        &mut self,
+        buffer: Arc<dyn BufferAccess>,
+        queue_family_transfer: QueueFamilyTransfer,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        let memory = PipelineMemoryAccess {
+            stages: PipelineStages {
+                transfer: true,
+                ..PipelineStages::none()
+            },
+            access: AccessFlags {
+                transfer_write: true,
+                ..AccessFlags::none()
+            },
+            exclusive: true,
+        };
+
+        // The second half of the barrier is recorded by the queue family that acquires the
+        // buffer, so the destination scope is empty.
+        let barrier = BufferMemoryBarrier {
+            source_stages: memory.stages,
+            source_access: memory.access,
+            queue_family_transfer: Some(queue_family_transfer),
+            ..buffer_ownership_barrier(buffer.as_ref())
+        };
+
+        self.buffer_ownership_transfer("release_buffer_ownership", buffer, barrier, memory)
+    }
+
+    /// Records a barrier that acquires the ownership of `buffer` from another queue family. The
+    /// barrier must match one that was recorded with `release_buffer_ownership`.
+    pub(in crate::command_buffer) unsafe fn acquire_buffer_ownership(
+        &mut self,
+        buffer: Arc<dyn BufferAccess>,
+        queue_family_transfer: QueueFamilyTransfer,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        let memory = PipelineMemoryAccess {
+            stages: PipelineStages {
+                all_commands: true,
+                ..PipelineStages::none()
+            },
+            access: AccessFlags {
+                memory_read: true,
+                memory_write: true,
+                ..AccessFlags::none()
+            },
+            exclusive: true,
+        };
+
+        // The first half of the barrier was recorded by the queue family that released the
+        // buffer, so the source scope is empty.
+        let barrier = BufferMemoryBarrier {
+            destination_stages: memory.stages,
+            destination_access: memory.access,
+            queue_family_transfer: Some(queue_family_transfer),
+            ..buffer_ownership_barrier(buffer.as_ref())
+        };
+
+        self.buffer_ownership_transfer("acquire_buffer_ownership", buffer, barrier, memory)
+    }
+
+    unsafe fn buffer_ownership_transfer(
+        &mut self,
+        name: &'static str,
+        buffer: Arc<dyn BufferAccess>,
+        barrier: BufferMemoryBarrier,
+        memory: PipelineMemoryAccess,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        struct Cmd {
+            name: &'static str,
+            dependency_info: DependencyInfo,
+            // Keeps the memory of the buffer alive.
+            _buffer: Arc<dyn BufferAccess>,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                self.name
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.pipeline_barrier(&self.dependency_info);
+            }
+        }
+
+        let resource = (
+            "buffer".into(),
+            Resource::Buffer {
+                buffer: buffer.clone(),
+                range: 0..buffer.size(),
+                memory,
+            },
+        );
+
+        self.check_resource_conflicts(&resource)?;
+
+        self.commands.push(Box::new(Cmd {
+            name,
+            dependency_info: DependencyInfo {
+                buffer_memory_barriers: smallvec![barrier],
+                ..Default::default()
+            },
+            _buffer: buffer,
+        }));
+
+        self.add_resource(resource);
+
+        Ok(())
+    }
+
+    /// Records a barrier that releases the ownership of `image` to another queue family, after
+    /// it has been written by transfer commands in the `TransferDstOptimal` layout. The image is
+    /// transitioned to `new_layout` as part of the transfer.
+    pub(in crate::command_buffer) unsafe fn release_image_ownership(
+        &mut self,
+        image: Arc<dyn ImageAccess>,
+        queue_family_transfer: QueueFamilyTransfer,
+        new_layout: ImageLayout,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        let memory = PipelineMemoryAccess {
+            stages: PipelineStages {
+                transfer: true,
+                ..PipelineStages::none()
+            },
+            access: AccessFlags {
+                transfer_write: true,
+                ..AccessFlags::none()
+            },
+            exclusive: true,
+        };
+
+        let barrier = ImageMemoryBarrier {
+            source_stages: memory.stages,
+            source_access: memory.access,
+            old_layout: ImageLayout::TransferDstOptimal,
+            new_layout,
+            queue_family_transfer: Some(queue_family_transfer),
+            ..image_ownership_barrier(image.as_ref())
+        };
+
+        self.image_ownership_transfer(
+            "release_image_ownership",
+            image,
+            barrier,
+            memory,
+            ImageLayout::TransferDstOptimal,
+            new_layout,
+        )
+    }
+
+    /// Records a barrier that acquires the ownership of `image` from another queue family. The
+    /// barrier must match one that was recorded with `release_image_ownership`.
+    pub(in crate::command_buffer) unsafe fn acquire_image_ownership(
+        &mut self,
+        image: Arc<dyn ImageAccess>,
+        queue_family_transfer: QueueFamilyTransfer,
+        new_layout: ImageLayout,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        let memory = PipelineMemoryAccess {
+            stages: PipelineStages {
+                all_commands: true,
+                ..PipelineStages::none()
+            },
+            access: AccessFlags {
+                memory_read: true,
+                memory_write: true,
+                ..AccessFlags::none()
+            },
+            exclusive: true,
+        };
+
+        // The layout transition happens between the release and the acquire, and must be
+        // specified identically in both barriers.
+        let barrier = ImageMemoryBarrier {
+            destination_stages: memory.stages,
+            destination_access: memory.access,
+            old_layout: ImageLayout::TransferDstOptimal,
+            new_layout,
+            queue_family_transfer: Some(queue_family_transfer),
+            ..image_ownership_barrier(image.as_ref())
+        };
+
+        // The image is in `new_layout` once the barrier has executed, whatever the layout that
+        // the command buffer expects it to be in at the start.
+        self.image_ownership_transfer(
+            "acquire_image_ownership",
+            image,
+            barrier,
+            memory,
+            ImageLayout::Undefined,
+            new_layout,
+        )
+    }
+
+    unsafe fn image_ownership_transfer(
+        &mut self,
+        name: &'static str,
+        image: Arc<dyn ImageAccess>,
+        barrier: ImageMemoryBarrier,
+        memory: PipelineMemoryAccess,
+        start_layout: ImageLayout,
+        end_layout: ImageLayout,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        struct Cmd {
+            name: &'static str,
+            dependency_info: DependencyInfo,
+            // Keeps the memory of the image alive.
+            _image: Arc<dyn ImageAccess>,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                self.name
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.pipeline_barrier(&self.dependency_info);
+            }
+        }
+
+        let resource = (
+            "image".into(),
+            Resource::Image {
+                image: image.clone(),
+                subresource_range: image.subresource_range(),
+                memory,
+                start_layout,
+                end_layout,
+            },
+        );
+
+        self.check_resource_conflicts(&resource)?;
+
+        self.commands.push(Box::new(Cmd {
+            name,
+            dependency_info: DependencyInfo {
+                image_memory_barriers: smallvec![barrier],
+                ..Default::default()
+            },
+            _image: image,
+        }));
+
+        self.add_resource(resource);
+
+        Ok(())
+    }
+}
+
+// Returns a barrier covering the whole of `buffer`, without any scopes.
+fn buffer_ownership_barrier(buffer: &dyn BufferAccess) -> BufferMemoryBarrier {
+    let inner = buffer.inner();
+
+    BufferMemoryBarrier {
+        range: inner.offset..inner.offset + buffer.size(),
+        ..BufferMemoryBarrier::buffer(inner.buffer.clone())
+    }
+}
+
+// Returns a barrier covering the whole of `image`, without any scopes or layouts.
+fn image_ownership_barrier(image: &dyn ImageAccess) -> ImageMemoryBarrier {
+    let inner = image.inner();
+    let mut subresource_range = image.subresource_range();
+    subresource_range.array_layers.start += inner.first_layer;
+    subresource_range.array_layers.end += inner.first_layer;
+    subresource_range.mip_levels.start += inner.first_mipmap_level;
+    subresource_range.mip_levels.end += inner.first_mipmap_level;
+
+    ImageMemoryBarrier {
+        subresource_range,
+        ..ImageMemoryBarrier::image(inner.image.clone())
+    }
 }
 
 impl UnsafeCommandBufferBuilder {
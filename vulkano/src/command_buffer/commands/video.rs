@@ -0,0 +1,943 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::{
+    buffer::BufferAccess,
+    command_buffer::{
+        auto::VideoCodingState,
+        pool::CommandPoolBuilderAlloc,
+        synced::{Command, Resource, SyncCommandBufferBuilder, SyncCommandBufferBuilderError},
+        sys::UnsafeCommandBufferBuilder,
+        AutoCommandBufferBuilder, PrimaryAutoCommandBuffer,
+    },
+    device::DeviceOwned,
+    image::ImageLayout,
+    sync::{AccessFlags, PipelineMemoryAccess, PipelineStages},
+    video::{
+        VideoBeginCodingInfo, VideoDecodeInfo, VideoDecodePictureInfo, VideoDecodeReferenceInfo,
+        VideoPictureResource, VideoReferenceSlot,
+    },
+    DeviceSize, VulkanObject,
+};
+use smallvec::SmallVec;
+use std::{error, fmt, ptr};
+
+/// # Commands for video decoding.
+///
+/// These commands require the
+/// [`khr_video_queue`](crate::device::DeviceExtensions::khr_video_queue) and
+/// [`khr_video_decode_queue`](crate::device::DeviceExtensions::khr_video_decode_queue)
+/// extensions to be enabled on the device, and can only be recorded into primary command buffers
+/// for a queue family that supports video decode operations.
+///
+/// See the [`video`](crate::video) module for an overview.
+impl<P> AutoCommandBufferBuilder<PrimaryAutoCommandBuffer<P::Alloc>, P>
+where
+    P: CommandPoolBuilderAlloc,
+{
+    /// Begins a video coding scope, in which the video session of `begin_info` is used by the
+    /// video decode commands.
+    pub fn begin_video_coding(
+        &mut self,
+        begin_info: VideoBeginCodingInfo,
+    ) -> Result<&mut Self, VideoCodingError> {
+        self.validate_begin_video_coding(&begin_info)?;
+
+        unsafe {
+            self.inner.begin_video_coding(begin_info.clone())?;
+        }
+
+        let VideoBeginCodingInfo {
+            session,
+            parameters,
+            reference_slots,
+            _ne: _,
+        } = begin_info;
+
+        self.video_coding_state = Some(VideoCodingState {
+            session,
+            parameters,
+            reference_slots,
+        });
+
+        Ok(self)
+    }
+
+    fn validate_begin_video_coding(
+        &self,
+        begin_info: &VideoBeginCodingInfo,
+    ) -> Result<(), VideoCodingError> {
+        let device = self.device();
+
+        let VideoBeginCodingInfo {
+            session,
+            parameters,
+            reference_slots,
+            _ne: _,
+        } = begin_info;
+
+        assert_eq!(device, session.device());
+
+        // VUID-vkCmdBeginVideoCodingKHR-renderpass
+        if self.render_pass_state.is_some() {
+            return Err(VideoCodingError::ForbiddenInsideRenderPass);
+        }
+
+        // VUID-vkCmdBeginVideoCodingKHR-commandBuffer-cmdpool
+        if !self.queue_family().supports_video_decode() {
+            return Err(VideoCodingError::NotSupportedByQueueFamily);
+        }
+
+        // VUID-vkCmdBeginVideoCodingKHR-videocoding
+        if self.video_coding_state.is_some() {
+            return Err(VideoCodingError::VideoCodingScopeActive);
+        }
+
+        // VUID-vkCmdBeginVideoCodingKHR-commandBuffer-07231
+        if session.queue_family_index() != self.queue_family().id() {
+            return Err(VideoCodingError::SessionQueueFamilyMismatch);
+        }
+
+        // VUID-VkVideoBeginCodingInfoKHR-videoSessionParameters-04857
+        if let Some(parameters) = parameters {
+            if parameters.session() != session {
+                return Err(VideoCodingError::ParametersSessionMismatch);
+            }
+        }
+
+        for reference_slot in reference_slots {
+            // VUID-VkVideoBeginCodingInfoKHR-slotIndex-04856
+            if let Some(slot_index) = reference_slot.slot_index {
+                if slot_index >= session.max_dpb_slots() {
+                    return Err(VideoCodingError::SlotIndexOutOfRange {
+                        slot_index,
+                        max_dpb_slots: session.max_dpb_slots(),
+                    });
+                }
+            }
+
+            assert_eq!(device, reference_slot.picture_resource.image_view.device());
+        }
+
+        Ok(())
+    }
+
+    /// Resets the video session that is used by the current video coding scope, and deactivates
+    /// all its DPB slots. This must be done once for a new session, before the first picture is
+    /// decoded.
+    pub fn reset_video_coding(&mut self) -> Result<&mut Self, VideoCodingError> {
+        // VUID-vkCmdControlVideoCodingKHR-videocoding
+        if self.video_coding_state.is_none() {
+            return Err(VideoCodingError::VideoCodingScopeNotActive);
+        }
+
+        unsafe {
+            self.inner.reset_video_coding();
+        }
+
+        Ok(self)
+    }
+
+    /// Decodes a picture, with the video session of the current video coding scope.
+    pub fn decode_video(
+        &mut self,
+        decode_info: VideoDecodeInfo,
+    ) -> Result<&mut Self, VideoCodingError> {
+        self.validate_decode_video(&decode_info)?;
+
+        unsafe {
+            self.inner.decode_video(decode_info)?;
+        }
+
+        Ok(self)
+    }
+
+    fn validate_decode_video(&self, decode_info: &VideoDecodeInfo) -> Result<(), VideoCodingError> {
+        let device = self.device();
+
+        // VUID-vkCmdDecodeVideoKHR-videocoding
+        let video_coding_state = self
+            .video_coding_state
+            .as_ref()
+            .ok_or(VideoCodingError::VideoCodingScopeNotActive)?;
+        let session = &video_coding_state.session;
+        let capabilities = session.capabilities();
+
+        let VideoDecodeInfo {
+            src_buffer,
+            src_buffer_range,
+            dst_picture_resource,
+            setup_reference_slot,
+            reference_slots,
+            picture_info,
+            _ne: _,
+        } = decode_info;
+
+        assert_eq!(device, src_buffer.device());
+        assert_eq!(device, dst_picture_resource.image_view.device());
+
+        // VUID-vkCmdDecodeVideoKHR-None-07011
+        if video_coding_state.parameters.is_none() {
+            return Err(VideoCodingError::ParametersNotBound);
+        }
+
+        if !picture_info.is_for_codec(&session.profile().codec) {
+            return Err(VideoCodingError::CodecMismatch);
+        }
+
+        // VUID-VkVideoDecodeInfoKHR-srcBuffer-04165
+        if !src_buffer.usage().video_decode_src {
+            return Err(VideoCodingError::SrcBufferMissingUsage);
+        }
+
+        // VUID-VkVideoDecodeInfoKHR-srcBufferOffset-04167
+        if src_buffer_range.start >= src_buffer_range.end
+            || src_buffer_range.end > src_buffer.size()
+        {
+            return Err(VideoCodingError::SrcBufferRangeOutOfBounds {
+                range: src_buffer_range.clone(),
+                buffer_size: src_buffer.size(),
+            });
+        }
+
+        // VUID-VkVideoDecodeInfoKHR-srcBufferOffset-07131
+        // VUID-VkVideoDecodeInfoKHR-srcBufferRange-07133
+        if src_buffer_range.start % capabilities.min_bitstream_buffer_offset_alignment != 0
+            || (src_buffer_range.end - src_buffer_range.start)
+                % capabilities.min_bitstream_buffer_size_alignment
+                != 0
+        {
+            return Err(VideoCodingError::SrcBufferRangeNotAligned {
+                range: src_buffer_range.clone(),
+                offset_alignment: capabilities.min_bitstream_buffer_offset_alignment,
+                size_alignment: capabilities.min_bitstream_buffer_size_alignment,
+            });
+        }
+
+        // VUID-vkCmdDecodeVideoKHR-activeReferencePictureCount-07150
+        if reference_slots.len() as u32 > session.max_active_reference_pictures() {
+            return Err(VideoCodingError::MaxActiveReferencePicturesExceeded {
+                provided: reference_slots.len() as u32,
+                max: session.max_active_reference_pictures(),
+            });
+        }
+
+        let check_slot = |reference_slot: &VideoReferenceSlot| -> Result<(), VideoCodingError> {
+            assert_eq!(device, reference_slot.picture_resource.image_view.device());
+
+            let slot_index = reference_slot
+                .slot_index
+                .ok_or(VideoCodingError::SlotIndexMissing)?;
+
+            // VUID-VkVideoDecodeInfoKHR-slotIndex-07256
+            if slot_index >= session.max_dpb_slots() {
+                return Err(VideoCodingError::SlotIndexOutOfRange {
+                    slot_index,
+                    max_dpb_slots: session.max_dpb_slots(),
+                });
+            }
+
+            match &reference_slot.reference_info {
+                Some(reference_info) if reference_info.is_for_codec(&session.profile().codec) => {}
+                Some(_) => return Err(VideoCodingError::CodecMismatch),
+                None => return Err(VideoCodingError::ReferenceInfoMissing { slot_index }),
+            }
+
+            // VUID-vkCmdDecodeVideoKHR-pPictureResource-07265
+            if !video_coding_state.reference_slots.iter().any(|bound| {
+                bound
+                    .slot_index
+                    .map_or(true, |bound_index| bound_index == slot_index)
+                    && bound
+                        .picture_resource
+                        .is_same_picture(&reference_slot.picture_resource)
+            }) {
+                return Err(VideoCodingError::ReferencePictureNotBound { slot_index });
+            }
+
+            Ok(())
+        };
+
+        if let Some(setup_reference_slot) = setup_reference_slot {
+            check_slot(setup_reference_slot)?;
+
+            // VUID-vkCmdDecodeVideoKHR-None-07266
+            if dst_picture_resource.is_same_picture(&setup_reference_slot.picture_resource) {
+                if !capabilities.dpb_and_output_coincide {
+                    return Err(VideoCodingError::DpbAndOutputCoincideNotSupported);
+                }
+            } else if !capabilities.dpb_and_output_distinct {
+                return Err(VideoCodingError::DpbAndOutputDistinctNotSupported);
+            }
+        }
+
+        for reference_slot in reference_slots {
+            check_slot(reference_slot)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ends the current video coding scope.
+    pub fn end_video_coding(&mut self) -> Result<&mut Self, VideoCodingError> {
+        // VUID-vkCmdEndVideoCodingKHR-videocoding
+        if self.video_coding_state.is_none() {
+            return Err(VideoCodingError::VideoCodingScopeNotActive);
+        }
+
+        unsafe {
+            self.inner.end_video_coding();
+        }
+
+        self.video_coding_state = None;
+
+        Ok(self)
+    }
+}
+
+impl SyncCommandBufferBuilder {
+    /// Calls `vkCmdBeginVideoCodingKHR` on the builder.
+    #[inline]
+    pub unsafe fn begin_video_coding(
+        &mut self,
+        begin_info: VideoBeginCodingInfo,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        struct Cmd {
+            begin_info: VideoBeginCodingInfo,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "begin_video_coding"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.begin_video_coding(&self.begin_info);
+            }
+        }
+
+        self.commands.push(Box::new(Cmd { begin_info }));
+
+        Ok(())
+    }
+
+    /// Calls `vkCmdControlVideoCodingKHR` on the builder, with the reset flag.
+    #[inline]
+    pub unsafe fn reset_video_coding(&mut self) {
+        struct Cmd;
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "reset_video_coding"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.reset_video_coding();
+            }
+        }
+
+        self.commands.push(Box::new(Cmd));
+    }
+
+    /// Calls `vkCmdDecodeVideoKHR` on the builder.
+    #[inline]
+    pub unsafe fn decode_video(
+        &mut self,
+        decode_info: VideoDecodeInfo,
+    ) -> Result<(), SyncCommandBufferBuilderError> {
+        struct Cmd {
+            decode_info: VideoDecodeInfo,
+        }
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "decode_video"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.decode_video(&self.decode_info);
+            }
+        }
+
+        // TODO: use the video decode stage and access types once synchronization2 is supported.
+        let memory_access = |write: bool| PipelineMemoryAccess {
+            stages: PipelineStages {
+                all_commands: true,
+                ..PipelineStages::none()
+            },
+            access: AccessFlags {
+                memory_read: !write,
+                memory_write: write,
+                ..AccessFlags::none()
+            },
+            exclusive: write,
+        };
+
+        let picture_resource =
+            |picture_resource: &VideoPictureResource, write: bool, layout: ImageLayout| {
+                let mut subresource_range = picture_resource.image_view.subresource_range().clone();
+                let array_layer = picture_resource.image_array_layer();
+                subresource_range.array_layers = array_layer..array_layer + 1;
+
+                Resource::Image {
+                    image: picture_resource.image_view.image(),
+                    subresource_range,
+                    memory: memory_access(write),
+                    start_layout: layout,
+                    end_layout: layout,
+                }
+            };
+
+        let VideoDecodeInfo {
+            src_buffer,
+            src_buffer_range,
+            dst_picture_resource,
+            setup_reference_slot,
+            reference_slots,
+            ..
+        } = &decode_info;
+
+        let mut resources: SmallVec<[_; 8]> = SmallVec::new();
+        resources.push((
+            "src_buffer".into(),
+            Resource::Buffer {
+                buffer: src_buffer.clone(),
+                range: src_buffer_range.clone(),
+                memory: memory_access(false),
+            },
+        ));
+
+        let dst_is_setup = setup_reference_slot.as_ref().map_or(false, |setup| {
+            dst_picture_resource.is_same_picture(&setup.picture_resource)
+        });
+        resources.push((
+            "dst_picture_resource".into(),
+            picture_resource(
+                dst_picture_resource,
+                true,
+                if dst_is_setup {
+                    ImageLayout::VideoDecodeDpb
+                } else {
+                    ImageLayout::VideoDecodeDst
+                },
+            ),
+        ));
+
+        if let Some(setup_reference_slot) = setup_reference_slot {
+            if !dst_is_setup {
+                resources.push((
+                    "setup_reference_slot".into(),
+                    picture_resource(
+                        &setup_reference_slot.picture_resource,
+                        true,
+                        ImageLayout::VideoDecodeDpb,
+                    ),
+                ));
+            }
+        }
+
+        for (index, reference_slot) in reference_slots.iter().enumerate() {
+            resources.push((
+                format!("reference_slots[{}]", index).into(),
+                picture_resource(
+                    &reference_slot.picture_resource,
+                    false,
+                    ImageLayout::VideoDecodeDpb,
+                ),
+            ));
+        }
+
+        for resource in &resources {
+            self.check_resource_conflicts(resource)?;
+        }
+
+        self.commands.push(Box::new(Cmd { decode_info }));
+
+        for resource in resources {
+            self.add_resource(resource);
+        }
+
+        Ok(())
+    }
+
+    /// Calls `vkCmdEndVideoCodingKHR` on the builder.
+    #[inline]
+    pub unsafe fn end_video_coding(&mut self) {
+        struct Cmd;
+
+        impl Command for Cmd {
+            fn name(&self) -> &'static str {
+                "end_video_coding"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                out.end_video_coding();
+            }
+        }
+
+        self.commands.push(Box::new(Cmd));
+    }
+}
+
+// The codec-specific information about a reference picture, in the form that Vulkan expects.
+enum DpbSlotInfoVk {
+    H264(ash::vk::VideoDecodeH264DpbSlotInfoKHR),
+    H265(ash::vk::VideoDecodeH265DpbSlotInfoKHR),
+}
+
+impl DpbSlotInfoVk {
+    fn new(reference_info: &VideoDecodeReferenceInfo) -> Self {
+        match reference_info {
+            VideoDecodeReferenceInfo::H264(std_reference_info) => {
+                Self::H264(ash::vk::VideoDecodeH264DpbSlotInfoKHR {
+                    p_std_reference_info: std_reference_info,
+                    ..Default::default()
+                })
+            }
+            VideoDecodeReferenceInfo::H265(std_reference_info) => {
+                Self::H265(ash::vk::VideoDecodeH265DpbSlotInfoKHR {
+                    p_std_reference_info: std_reference_info,
+                    ..Default::default()
+                })
+            }
+        }
+    }
+
+    fn as_ptr(&self) -> *const std::ffi::c_void {
+        match self {
+            Self::H264(info) => info as *const _ as *const _,
+            Self::H265(info) => info as *const _ as *const _,
+        }
+    }
+}
+
+impl UnsafeCommandBufferBuilder {
+    /// Calls `vkCmdBeginVideoCodingKHR` on the builder.
+    #[inline]
+    pub unsafe fn begin_video_coding(&mut self, begin_info: &VideoBeginCodingInfo) {
+        let VideoBeginCodingInfo {
+            session,
+            parameters,
+            reference_slots,
+            _ne: _,
+        } = begin_info;
+
+        let picture_resources: SmallVec<[_; 8]> = reference_slots
+            .iter()
+            .map(|reference_slot| reference_slot.picture_resource.to_vulkan())
+            .collect();
+        let reference_slots_vk: SmallVec<[_; 8]> = reference_slots
+            .iter()
+            .zip(&picture_resources)
+            .map(
+                |(reference_slot, picture_resource)| ash::vk::VideoReferenceSlotInfoKHR {
+                    slot_index: reference_slot.slot_index.map_or(-1, |index| index as i32),
+                    p_picture_resource: picture_resource,
+                    ..Default::default()
+                },
+            )
+            .collect();
+
+        let begin_info = ash::vk::VideoBeginCodingInfoKHR {
+            video_session: session.internal_object(),
+            video_session_parameters: parameters
+                .as_ref()
+                .map_or(ash::vk::VideoSessionParametersKHR::null(), |parameters| {
+                    parameters.internal_object()
+                }),
+            reference_slot_count: reference_slots_vk.len() as u32,
+            p_reference_slots: reference_slots_vk.as_ptr(),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        (fns.khr_video_queue.cmd_begin_video_coding_khr)(self.handle, &begin_info);
+    }
+
+    /// Calls `vkCmdControlVideoCodingKHR` on the builder, with the reset flag.
+    #[inline]
+    pub unsafe fn reset_video_coding(&mut self) {
+        let control_info = ash::vk::VideoCodingControlInfoKHR {
+            flags: ash::vk::VideoCodingControlFlagsKHR::RESET,
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        (fns.khr_video_queue.cmd_control_video_coding_khr)(self.handle, &control_info);
+    }
+
+    /// Calls `vkCmdDecodeVideoKHR` on the builder.
+    #[inline]
+    pub unsafe fn decode_video(&mut self, decode_info: &VideoDecodeInfo) {
+        let VideoDecodeInfo {
+            src_buffer,
+            src_buffer_range,
+            dst_picture_resource,
+            setup_reference_slot,
+            reference_slots,
+            picture_info,
+            _ne: _,
+        } = decode_info;
+
+        let h264_picture_info;
+        let h265_picture_info;
+        let p_next: *const _ = match picture_info {
+            VideoDecodePictureInfo::H264 {
+                std_picture_info,
+                slice_offsets,
+            } => {
+                h264_picture_info = ash::vk::VideoDecodeH264PictureInfoKHR {
+                    p_std_picture_info: std_picture_info,
+                    slice_count: slice_offsets.len() as u32,
+                    p_slice_offsets: slice_offsets.as_ptr(),
+                    ..Default::default()
+                };
+                &h264_picture_info as *const _ as *const _
+            }
+            VideoDecodePictureInfo::H265 {
+                std_picture_info,
+                slice_segment_offsets,
+            } => {
+                h265_picture_info = ash::vk::VideoDecodeH265PictureInfoKHR {
+                    p_std_picture_info: std_picture_info,
+                    slice_segment_count: slice_segment_offsets.len() as u32,
+                    p_slice_segment_offsets: slice_segment_offsets.as_ptr(),
+                    ..Default::default()
+                };
+                &h265_picture_info as *const _ as *const _
+            }
+        };
+
+        // The setup reference slot, if any, is stored after the reference slots.
+        let all_slots = || reference_slots.iter().chain(setup_reference_slot);
+        let dpb_slot_infos: SmallVec<[_; 8]> = all_slots()
+            .map(|reference_slot| {
+                reference_slot
+                    .reference_info
+                    .as_ref()
+                    .map(DpbSlotInfoVk::new)
+            })
+            .collect();
+        let picture_resources: SmallVec<[_; 8]> = all_slots()
+            .map(|reference_slot| reference_slot.picture_resource.to_vulkan())
+            .collect();
+        let reference_slots_vk: SmallVec<[_; 8]> = all_slots()
+            .zip(&picture_resources)
+            .zip(&dpb_slot_infos)
+            .map(|((reference_slot, picture_resource), dpb_slot_info)| {
+                ash::vk::VideoReferenceSlotInfoKHR {
+                    p_next: dpb_slot_info
+                        .as_ref()
+                        .map_or(ptr::null(), DpbSlotInfoVk::as_ptr),
+                    slot_index: reference_slot.slot_index.map_or(-1, |index| index as i32),
+                    p_picture_resource: picture_resource,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        let src_buffer_inner = src_buffer.inner();
+
+        let decode_info = ash::vk::VideoDecodeInfoKHR {
+            p_next,
+            src_buffer: src_buffer_inner.buffer.internal_object(),
+            src_buffer_offset: src_buffer_inner.offset + src_buffer_range.start,
+            src_buffer_range: src_buffer_range.end - src_buffer_range.start,
+            dst_picture_resource: dst_picture_resource.to_vulkan(),
+            p_setup_reference_slot: if setup_reference_slot.is_some() {
+                reference_slots_vk.last().unwrap() as *const _
+            } else {
+                ptr::null()
+            },
+            reference_slot_count: reference_slots.len() as u32,
+            p_reference_slots: reference_slots_vk.as_ptr(),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        (fns.khr_video_decode_queue.cmd_decode_video_khr)(self.handle, &decode_info);
+    }
+
+    /// Calls `vkCmdEndVideoCodingKHR` on the builder.
+    #[inline]
+    pub unsafe fn end_video_coding(&mut self) {
+        let end_info = ash::vk::VideoEndCodingInfoKHR::default();
+
+        let fns = self.device.fns();
+        (fns.khr_video_queue.cmd_end_video_coding_khr)(self.handle, &end_info);
+    }
+}
+
+/// Error that can happen when recording a video coding command.
+#[derive(Clone, Debug)]
+pub enum VideoCodingError {
+    SyncCommandBufferBuilderError(SyncCommandBufferBuilderError),
+
+    /// The picture information or a reference information is not for the codec of the video
+    /// session.
+    CodecMismatch,
+
+    /// The decoded picture is written to the setup reference slot, but the
+    /// [`dpb_and_output_coincide`](crate::video::VideoCapabilities::dpb_and_output_coincide)
+    /// capability is not supported.
+    DpbAndOutputCoincideNotSupported,
+
+    /// The decoded picture is written to another picture resource than the setup reference slot,
+    /// but the
+    /// [`dpb_and_output_distinct`](crate::video::VideoCapabilities::dpb_and_output_distinct)
+    /// capability is not supported.
+    DpbAndOutputDistinctNotSupported,
+
+    /// Operation forbidden inside of a render pass.
+    ForbiddenInsideRenderPass,
+
+    /// More reference pictures were provided than the session allows.
+    MaxActiveReferencePicturesExceeded {
+        provided: u32,
+        max: u32,
+    },
+
+    /// The queue family doesn't allow this operation.
+    NotSupportedByQueueFamily,
+
+    /// The video session parameters do not belong to the video session.
+    ParametersSessionMismatch,
+
+    /// No video session parameters were provided when beginning the video coding scope.
+    ParametersNotBound,
+
+    /// A reference picture was not among the reference slots that were provided when beginning
+    /// the video coding scope, or was associated with another DPB slot.
+    ReferencePictureNotBound {
+        slot_index: u32,
+    },
+
+    /// A reference slot of a decode command has no reference information.
+    ReferenceInfoMissing {
+        slot_index: u32,
+    },
+
+    /// The video session was created for another queue family than the one of the command
+    /// buffer.
+    SessionQueueFamilyMismatch,
+
+    /// A reference slot of a decode command has no slot index.
+    SlotIndexMissing,
+
+    /// A slot index is not less than the number of DPB slots of the video session.
+    SlotIndexOutOfRange {
+        slot_index: u32,
+        max_dpb_slots: u32,
+    },
+
+    /// The source buffer was not created with the `video_decode_src` usage.
+    SrcBufferMissingUsage,
+
+    /// The source buffer range is not aligned to the bitstream buffer alignments of the video
+    /// session.
+    SrcBufferRangeNotAligned {
+        range: std::ops::Range<DeviceSize>,
+        offset_alignment: DeviceSize,
+        size_alignment: DeviceSize,
+    },
+
+    /// The source buffer range is empty or exceeds the size of the buffer.
+    SrcBufferRangeOutOfBounds {
+        range: std::ops::Range<DeviceSize>,
+        buffer_size: DeviceSize,
+    },
+
+    /// Tried to begin a video coding scope while one is already active.
+    VideoCodingScopeActive,
+
+    /// The operation requires an active video coding scope.
+    VideoCodingScopeNotActive,
+}
+
+impl error::Error for VideoCodingError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::SyncCommandBufferBuilderError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for VideoCodingError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::SyncCommandBufferBuilderError(_) => write!(f, "a SyncCommandBufferBuilderError"),
+            Self::CodecMismatch => write!(
+                f,
+                "the picture information or a reference information is not for the codec of the \
+                video session",
+            ),
+            Self::DpbAndOutputCoincideNotSupported => write!(
+                f,
+                "the decoded picture is written to the setup reference slot, but the \
+                `dpb_and_output_coincide` capability is not supported",
+            ),
+            Self::DpbAndOutputDistinctNotSupported => write!(
+                f,
+                "the decoded picture is written to another picture resource than the setup \
+                reference slot, but the `dpb_and_output_distinct` capability is not supported",
+            ),
+            Self::ForbiddenInsideRenderPass => {
+                write!(f, "operation forbidden inside of a render pass")
+            }
+            Self::MaxActiveReferencePicturesExceeded { provided, max } => write!(
+                f,
+                "{} reference pictures were provided, but the session allows at most {}",
+                provided, max,
+            ),
+            Self::NotSupportedByQueueFamily => {
+                write!(f, "the queue family doesn't allow this operation")
+            }
+            Self::ParametersSessionMismatch => write!(
+                f,
+                "the video session parameters do not belong to the video session",
+            ),
+            Self::ParametersNotBound => write!(
+                f,
+                "no video session parameters were provided when beginning the video coding scope",
+            ),
+            Self::ReferencePictureNotBound { slot_index } => write!(
+                f,
+                "the reference picture of DPB slot {} was not among the reference slots that were \
+                provided when beginning the video coding scope",
+                slot_index,
+            ),
+            Self::ReferenceInfoMissing { slot_index } => write!(
+                f,
+                "the reference slot for DPB slot {} has no reference information",
+                slot_index,
+            ),
+            Self::SessionQueueFamilyMismatch => write!(
+                f,
+                "the video session was created for another queue family than the one of the \
+                command buffer",
+            ),
+            Self::SlotIndexMissing => {
+                write!(f, "a reference slot of a decode command has no slot index")
+            }
+            Self::SlotIndexOutOfRange {
+                slot_index,
+                max_dpb_slots,
+            } => write!(
+                f,
+                "the slot index {} is not less than the number of DPB slots of the video session \
+                ({})",
+                slot_index, max_dpb_slots,
+            ),
+            Self::SrcBufferMissingUsage => write!(
+                f,
+                "the source buffer was not created with the `video_decode_src` usage",
+            ),
+            Self::SrcBufferRangeNotAligned {
+                range,
+                offset_alignment,
+                size_alignment,
+            } => write!(
+                f,
+                "the source buffer range {:?} is not aligned to the offset alignment {} and the \
+                size alignment {} of the video session",
+                range, offset_alignment, size_alignment,
+            ),
+            Self::SrcBufferRangeOutOfBounds { range, buffer_size } => write!(
+                f,
+                "the source buffer range {:?} is empty or exceeds the size of the buffer ({})",
+                range, buffer_size,
+            ),
+            Self::VideoCodingScopeActive => write!(
+                f,
+                "tried to begin a video coding scope while one is already active",
+            ),
+            Self::VideoCodingScopeNotActive => {
+                write!(f, "the operation requires an active video coding scope")
+            }
+        }
+    }
+}
+
+impl From<SyncCommandBufferBuilderError> for VideoCodingError {
+    #[inline]
+    fn from(err: SyncCommandBufferBuilderError) -> Self {
+        Self::SyncCommandBufferBuilderError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VideoCodingError;
+    use crate::{
+        buffer::{BufferUsage, CpuAccessibleBuffer},
+        command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage},
+        format::Format,
+        image::{ImageUsage, StorageImage},
+        video::{VideoDecodeInfo, VideoDecodePictureInfo, VideoPictureResource},
+    };
+    use std::mem;
+
+    #[test]
+    fn video_coding_scope_not_active() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            builder.reset_video_coding(),
+            Err(VideoCodingError::VideoCodingScopeNotActive)
+        ));
+        assert!(matches!(
+            builder.end_video_coding(),
+            Err(VideoCodingError::VideoCodingScopeNotActive)
+        ));
+
+        let src_buffer = CpuAccessibleBuffer::from_iter(
+            device,
+            BufferUsage::all(),
+            false,
+            [0_u8; 256].iter().copied(),
+        )
+        .unwrap();
+        let dst_image_view = StorageImage::general_purpose_image_view(
+            queue,
+            [64, 64],
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+        )
+        .unwrap();
+
+        // The codec-specific structures are plain C data, which are valid when zeroed.
+        let decode_info = VideoDecodeInfo::new(
+            src_buffer,
+            0..256,
+            VideoPictureResource::image_view(dst_image_view),
+            VideoDecodePictureInfo::H264 {
+                std_picture_info: unsafe { mem::zeroed() },
+                slice_offsets: vec![0],
+            },
+        );
+
+        assert!(matches!(
+            builder.decode_video(decode_info),
+            Err(VideoCodingError::VideoCodingScopeNotActive)
+        ));
+    }
+}
@@ -52,9 +52,8 @@
 //! use vulkano::command_buffer::SubpassContents;
 //!
 //! # #[repr(C)]
-//! # #[derive(Clone, Copy, Debug, Default, bytemuck::Zeroable, bytemuck::Pod)]
+//! # #[derive(Clone, Copy, Debug, Default, bytemuck::Zeroable, bytemuck::Pod, vulkano::pipeline::graphics::vertex_input::Vertex)]
 //! # struct Vertex { position: [f32; 3] };
-//! # vulkano::impl_vertex!(Vertex, position);
 //! # use vulkano::buffer::TypedBufferAccess;
 //! # let device: std::sync::Arc<vulkano::device::Device> = return;
 //! # let queue: std::sync::Arc<vulkano::device::Queue> = return;
@@ -90,17 +89,18 @@
 //! information.
 
 pub use self::commands::{
-    debug::DebugUtilsError,
+    debug::{DebugUtilsError, WriteMarkerError},
     image::{
         BlitImageInfo, ClearColorImageInfo, ClearDepthStencilImageInfo, ImageBlit, ImageResolve,
         ResolveImageInfo,
     },
+    optical_flow::OpticalFlowExecuteError,
     pipeline::{
         CheckDescriptorSetsValidityError, CheckDispatchError, CheckDynamicStateValidityError,
         CheckIndexBufferError, CheckIndirectBufferError, CheckPipelineError,
-        CheckPushConstantsValidityError, CheckVertexBufferError,
+        CheckPushConstantsValidityError, CheckVertexBufferError, DescriptorValidationMode,
     },
-    query::QueryError,
+    query::{QueryCommand, QueryError},
     render_pass::{
         ClearAttachment, ClearRect, RenderPassBeginInfo, RenderPassError, RenderingAttachmentInfo,
         RenderingAttachmentResolveInfo, RenderingInfo,
@@ -110,6 +110,7 @@ pub use self::commands::{
         BufferCopy, BufferImageCopy, CopyBufferInfo, CopyBufferInfoTyped, CopyBufferToImageInfo,
         CopyImageInfo, CopyImageToBufferInfo, FillBufferInfo, ImageCopy,
     },
+    video::VideoCodingError,
     CopyError, CopyErrorResource,
 };
 pub use self::{
@@ -119,10 +120,12 @@ pub use self::{
         DrawIndexedIndirectError, DrawIndirectError, PrimaryAutoCommandBuffer,
         SecondaryAutoCommandBuffer,
     },
+    timing::{CommandTiming, CommandTimingError, CommandTimingReport, TimedCommandRange},
     traits::{
         CommandBufferExecError, CommandBufferExecFuture, PrimaryCommandBuffer,
         SecondaryCommandBuffer,
     },
+    usage::UsageInference,
 };
 use crate::{
     format::Format,
@@ -136,10 +139,16 @@ use std::sync::Arc;
 mod auto;
 mod commands;
 pub mod pool;
+pub mod readback;
+pub mod staging_belt;
 pub mod submit;
 pub mod synced;
 pub mod sys;
+mod timing;
 mod traits;
+pub mod upload;
+pub mod upload_manager;
+mod usage;
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, Zeroable, Pod, PartialEq, Eq)]
@@ -15,9 +15,17 @@
 //! In vulkano, creating a command buffer requires passing an implementation of the `CommandPool`
 //! trait. By default vulkano will use the `StandardCommandPool` struct, but you can implement
 //! this trait yourself by wrapping around the `UnsafeCommandPool` type.
+//!
+//! Command buffers that are created through `AutoCommandBufferBuilder` are allocated from an
+//! implementation of the `CommandBufferAllocator` trait, which is not tied to a single queue
+//! family. The `StandardCommandBufferAllocator` implements this trait by forwarding to the
+//! standard command pools of the device. For command buffers that are re-recorded every frame,
+//! the `RingCommandBufferAllocator` keeps a ring of pools that are reset all at once, which avoids
+//! resetting and reallocating command buffers one by one.
 
 pub use self::{
-    standard::StandardCommandPool,
+    ring::RingCommandBufferAllocator,
+    standard::{StandardCommandBufferAllocator, StandardCommandPool},
     sys::{
         CommandPoolTrimError, UnsafeCommandPool, UnsafeCommandPoolAlloc,
         UnsafeCommandPoolCreateInfo, UnsafeCommandPoolCreationError,
@@ -29,6 +37,7 @@ use crate::{
     OomError,
 };
 
+pub mod ring;
 pub mod standard;
 mod sys;
 
@@ -74,6 +83,41 @@ pub unsafe trait CommandPool: DeviceOwned {
     fn queue_family(&self) -> QueueFamily;
 }
 
+/// Types that allocate command buffers for the queue families of a device.
+///
+/// Unlike `CommandPool`, an allocator is not tied to a single queue family. This is what
+/// `AutoCommandBufferBuilder` uses to obtain its command buffers.
+///
+/// # Safety
+///
+/// The same requirements as for `CommandPool` apply to the command buffers returned by
+/// `allocate`. In addition, the command buffers must belong to the queue family that was passed
+/// to `allocate`.
+///
+pub unsafe trait CommandBufferAllocator: DeviceOwned {
+    /// See `allocate()`.
+    type Iter: Iterator<Item = Self::Builder>;
+    /// Represents a command buffer that has been allocated and that is currently being built.
+    type Builder: CommandPoolBuilderAlloc<Alloc = Self::Alloc>;
+    /// Represents a command buffer that has been allocated and that is pending execution or is
+    /// being executed.
+    type Alloc: CommandPoolAlloc;
+
+    /// Allocates command buffers for the given queue family.
+    ///
+    /// Returns an iterator that contains a bunch of allocated command buffers.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `queue_family` doesn't belong to the same physical device as the allocator.
+    fn allocate(
+        &self,
+        queue_family: QueueFamily,
+        level: CommandBufferLevel,
+        command_buffer_count: u32,
+    ) -> Result<Self::Iter, OomError>;
+}
+
 /// A command buffer allocated from a pool and that can be recorded.
 ///
 /// # Safety
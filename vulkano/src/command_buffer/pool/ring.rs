@@ -0,0 +1,506 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! A command buffer allocator that amortizes allocations across frames.
+//!
+//! Applications typically record a new set of command buffers every frame, and keep a fixed
+//! number of frames in flight. The `RingCommandBufferAllocator` owns one set of command pools per
+//! frame in flight. Command buffers are allocated from the pools of the current frame, and when
+//! the application moves on to the next frame, the pools of that frame are reset in a single
+//! call. The command buffers that they contain are then handed out again, without going through
+//! the driver.
+//!
+//! ```
+//! use vulkano::command_buffer::pool::RingCommandBufferAllocator;
+//! use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage};
+//!
+//! # let device: std::sync::Arc<vulkano::device::Device> = return;
+//! # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+//! let allocator = RingCommandBufferAllocator::new(device.clone(), 2);
+//!
+//! loop {
+//!     // Wait for the command buffers of the frame we're about to reuse, then drop them.
+//!     // ...
+//!
+//!     allocator.next_frame().unwrap();
+//!
+//!     let mut builder = AutoCommandBufferBuilder::primary_with_allocator(
+//!         &allocator,
+//!         queue.family(),
+//!         CommandBufferUsage::OneTimeSubmit,
+//!     )
+//!     .unwrap();
+//!     // ...
+//! #   break;
+//! }
+//! ```
+
+use super::{
+    sys::{CommandBufferAllocateInfo, UnsafeCommandPoolCreateInfo, UnsafeCommandPoolCreationError},
+    CommandBufferAllocator, CommandPoolAlloc, CommandPoolBuilderAlloc, UnsafeCommandPool,
+    UnsafeCommandPoolAlloc,
+};
+use crate::{
+    command_buffer::CommandBufferLevel,
+    device::{physical::QueueFamily, Device, DeviceOwned},
+    OomError, VulkanObject,
+};
+use crossbeam_queue::SegQueue;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    error, fmt,
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    vec::IntoIter as VecIntoIter,
+};
+
+/// Command buffer allocator that keeps one set of command pools per frame in flight.
+///
+/// Command buffers are always allocated from the pools of the current frame. Calling
+/// [`next_frame`](Self::next_frame) moves on to the next frame in the ring and resets its pools,
+/// which returns all of the command buffers that were allocated from them to the initial state at
+/// once. This requires all of those command buffers to have been dropped, which in turn means
+/// that the GPU has finished executing them.
+///
+/// Like `StandardCommandPool`, one Vulkan pool is used per thread and per queue family in order
+/// to avoid locking. Command buffers can't be moved between threads during the building process,
+/// but finished command buffers can.
+#[derive(Debug)]
+pub struct RingCommandBufferAllocator {
+    device: Arc<Device>,
+    frames: Vec<Arc<RingFrame>>,
+    current_frame: Mutex<usize>,
+}
+
+#[derive(Debug)]
+struct RingFrame {
+    // The pools of this frame, for each thread and queue family.
+    pools: Mutex<HashMap<(thread::ThreadId, u32), Arc<RingPool>>>,
+    // Number of command buffers allocated from this frame that are still alive.
+    alive_command_buffers: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct RingPool {
+    pool: Mutex<UnsafeCommandPool>,
+    queue_family: u32,
+    // Command buffers that are in the initial state and are available for reuse.
+    available_primary_command_buffers: SegQueue<UnsafeCommandPoolAlloc>,
+    available_secondary_command_buffers: SegQueue<UnsafeCommandPoolAlloc>,
+    // Command buffers that have been dropped since the last reset of the pool.
+    used_primary_command_buffers: SegQueue<UnsafeCommandPoolAlloc>,
+    used_secondary_command_buffers: SegQueue<UnsafeCommandPoolAlloc>,
+}
+
+unsafe impl Send for RingCommandBufferAllocator {}
+unsafe impl Sync for RingCommandBufferAllocator {}
+
+impl RingCommandBufferAllocator {
+    /// Creates a new allocator with `frames_in_flight` sets of pools.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `frames_in_flight` is 0.
+    pub fn new(device: Arc<Device>, frames_in_flight: usize) -> RingCommandBufferAllocator {
+        assert!(frames_in_flight != 0);
+
+        RingCommandBufferAllocator {
+            device,
+            frames: (0..frames_in_flight)
+                .map(|_| {
+                    Arc::new(RingFrame {
+                        pools: Mutex::new(HashMap::default()),
+                        alive_command_buffers: AtomicUsize::new(0),
+                    })
+                })
+                .collect(),
+            current_frame: Mutex::new(0),
+        }
+    }
+
+    /// Returns the number of frames in the ring.
+    #[inline]
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the index of the frame that command buffers are currently allocated from.
+    #[inline]
+    pub fn current_frame(&self) -> usize {
+        *self.current_frame.lock().unwrap()
+    }
+
+    /// Moves on to the next frame in the ring, and resets its pools so that their command buffers
+    /// can be reused. Returns the index of the new current frame.
+    ///
+    /// Returns an error, and stays on the current frame, if some command buffers that were
+    /// allocated during the previous use of the next frame are still alive. This usually means
+    /// that the future of that frame hasn't been cleaned up yet.
+    pub fn next_frame(&self) -> Result<usize, NextFrameError> {
+        let mut current_frame = self.current_frame.lock().unwrap();
+        let next_frame = (*current_frame + 1) % self.frames.len();
+        let frame = &self.frames[next_frame];
+
+        // Holding the lock prevents new allocations from this frame until it's been reset.
+        let pools = frame.pools.lock().unwrap();
+        let alive_command_buffers = frame.alive_command_buffers.load(Ordering::Acquire);
+
+        if alive_command_buffers != 0 {
+            return Err(NextFrameError::FrameInUse {
+                frame: next_frame,
+                alive_command_buffers,
+            });
+        }
+
+        for pool in pools.values() {
+            // Safety: all command buffers of the pool have been dropped, so none of them can be
+            // pending execution or be recorded.
+            unsafe { pool.pool.lock().unwrap().reset(false)? };
+
+            for (used, available) in [
+                (
+                    &pool.used_primary_command_buffers,
+                    &pool.available_primary_command_buffers,
+                ),
+                (
+                    &pool.used_secondary_command_buffers,
+                    &pool.available_secondary_command_buffers,
+                ),
+            ] {
+                while let Some(cmd) = used.pop() {
+                    available.push(cmd);
+                }
+            }
+        }
+
+        *current_frame = next_frame;
+        Ok(next_frame)
+    }
+}
+
+unsafe impl CommandBufferAllocator for RingCommandBufferAllocator {
+    type Iter = VecIntoIter<RingCommandBufferBuilder>;
+    type Builder = RingCommandBufferBuilder;
+    type Alloc = RingCommandBufferAlloc;
+
+    fn allocate(
+        &self,
+        queue_family: QueueFamily,
+        level: CommandBufferLevel,
+        mut command_buffer_count: u32,
+    ) -> Result<Self::Iter, OomError> {
+        assert_eq!(
+            self.device.physical_device().internal_object(),
+            queue_family.physical_device().internal_object()
+        );
+
+        let frame = self.frames[self.current_frame()].clone();
+        let mut pools = frame.pools.lock().unwrap();
+
+        let pool = match pools.entry((thread::current().id(), queue_family.id())) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                let new_pool = UnsafeCommandPool::new(
+                    self.device.clone(),
+                    UnsafeCommandPoolCreateInfo {
+                        queue_family_index: queue_family.id(),
+                        transient: true,
+                        ..Default::default()
+                    },
+                )
+                .map_err(|err| match err {
+                    UnsafeCommandPoolCreationError::OomError(err) => err,
+                    _ => panic!("Unexpected error: {}", err),
+                })?;
+
+                entry
+                    .insert(Arc::new(RingPool {
+                        pool: Mutex::new(new_pool),
+                        queue_family: queue_family.id(),
+                        available_primary_command_buffers: SegQueue::new(),
+                        available_secondary_command_buffers: SegQueue::new(),
+                        used_primary_command_buffers: SegQueue::new(),
+                        used_secondary_command_buffers: SegQueue::new(),
+                    }))
+                    .clone()
+            }
+        };
+
+        let mut output = Vec::with_capacity(command_buffer_count as usize);
+        let new_builder = |cmd| RingCommandBufferBuilder {
+            inner: RingCommandBufferAlloc {
+                cmd: ManuallyDrop::new(cmd),
+                pool: pool.clone(),
+                frame: frame.clone(),
+                level,
+                device: self.device.clone(),
+            },
+            dummy_avoid_send_sync: PhantomData,
+        };
+
+        // First, pick from the command buffers that were reset with the pool.
+        let existing = match level {
+            CommandBufferLevel::Primary => &pool.available_primary_command_buffers,
+            CommandBufferLevel::Secondary => &pool.available_secondary_command_buffers,
+        };
+
+        while output.len() < command_buffer_count as usize {
+            match existing.pop() {
+                Some(cmd) => output.push(new_builder(cmd)),
+                None => break,
+            }
+        }
+
+        // Then allocate the rest.
+        if output.len() < command_buffer_count as usize {
+            command_buffer_count -= output.len() as u32;
+
+            for cmd in
+                pool.pool
+                    .lock()
+                    .unwrap()
+                    .allocate_command_buffers(CommandBufferAllocateInfo {
+                        level,
+                        command_buffer_count,
+                        ..Default::default()
+                    })?
+            {
+                output.push(new_builder(cmd));
+            }
+        }
+
+        // This is done while the frame is locked, so that `next_frame` can't reset the pool
+        // before the command buffers are accounted for.
+        frame
+            .alive_command_buffers
+            .fetch_add(output.len(), Ordering::AcqRel);
+        drop(pools);
+
+        Ok(output.into_iter())
+    }
+}
+
+unsafe impl DeviceOwned for RingCommandBufferAllocator {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+/// Command buffer allocated from a `RingCommandBufferAllocator` and that is currently being
+/// built.
+pub struct RingCommandBufferBuilder {
+    // The only difference between a `RingCommandBufferBuilder` and a `RingCommandBufferAlloc`
+    // is that the former must not implement `Send` and `Sync`. Therefore we just share the structs.
+    inner: RingCommandBufferAlloc,
+    // Unimplemented `Send` and `Sync` from the builder.
+    dummy_avoid_send_sync: PhantomData<*const u8>,
+}
+
+unsafe impl CommandPoolBuilderAlloc for RingCommandBufferBuilder {
+    type Alloc = RingCommandBufferAlloc;
+
+    #[inline]
+    fn inner(&self) -> &UnsafeCommandPoolAlloc {
+        self.inner.inner()
+    }
+
+    #[inline]
+    fn into_alloc(self) -> Self::Alloc {
+        self.inner
+    }
+
+    #[inline]
+    fn queue_family(&self) -> QueueFamily {
+        self.inner.queue_family()
+    }
+}
+
+unsafe impl DeviceOwned for RingCommandBufferBuilder {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.inner.device()
+    }
+}
+
+/// Command buffer allocated from a `RingCommandBufferAllocator`.
+pub struct RingCommandBufferAlloc {
+    // The actual command buffer. Extracted in the `Drop` implementation.
+    cmd: ManuallyDrop<UnsafeCommandPoolAlloc>,
+    // The pool that the command buffer is returned to on drop.
+    pool: Arc<RingPool>,
+    // The frame that the command buffer was allocated for.
+    frame: Arc<RingFrame>,
+    // Command buffer level.
+    level: CommandBufferLevel,
+    // The device we belong to. Necessary because of the `DeviceOwned` trait implementation.
+    device: Arc<Device>,
+}
+
+unsafe impl Send for RingCommandBufferAlloc {}
+unsafe impl Sync for RingCommandBufferAlloc {}
+
+unsafe impl CommandPoolAlloc for RingCommandBufferAlloc {
+    #[inline]
+    fn inner(&self) -> &UnsafeCommandPoolAlloc {
+        &self.cmd
+    }
+
+    #[inline]
+    fn queue_family(&self) -> QueueFamily {
+        self.device
+            .physical_device()
+            .queue_family_by_id(self.pool.queue_family)
+            .unwrap()
+    }
+}
+
+unsafe impl DeviceOwned for RingCommandBufferAlloc {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl Drop for RingCommandBufferAlloc {
+    fn drop(&mut self) {
+        // Safe because `self.cmd` is wrapped in a `ManuallyDrop`.
+        let cmd: UnsafeCommandPoolAlloc = unsafe { ptr::read(&*self.cmd) };
+
+        // The command buffer can only be reused once the pool has been reset.
+        match self.level {
+            CommandBufferLevel::Primary => self.pool.used_primary_command_buffers.push(cmd),
+            CommandBufferLevel::Secondary => self.pool.used_secondary_command_buffers.push(cmd),
+        }
+
+        self.frame
+            .alive_command_buffers
+            .fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Error that can happen when moving a `RingCommandBufferAllocator` to the next frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NextFrameError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// Some command buffers that were allocated during the previous use of the frame are still
+    /// alive.
+    FrameInUse {
+        frame: usize,
+        alive_command_buffers: usize,
+    },
+}
+
+impl error::Error for NextFrameError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::OomError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for NextFrameError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::OomError(_) => write!(f, "not enough memory available"),
+            Self::FrameInUse {
+                frame,
+                alive_command_buffers,
+            } => write!(
+                f,
+                "{} command buffers allocated for frame {} are still alive",
+                alive_command_buffers, frame,
+            ),
+        }
+    }
+}
+
+impl From<OomError> for NextFrameError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NextFrameError, RingCommandBufferAllocator};
+    use crate::{
+        command_buffer::{
+            pool::{CommandBufferAllocator, CommandPoolBuilderAlloc},
+            AutoCommandBufferBuilder, CommandBufferLevel, CommandBufferUsage,
+        },
+        VulkanObject,
+    };
+
+    #[test]
+    fn reuse_after_next_frame() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let allocator = RingCommandBufferAllocator::new(device, 2);
+
+        let cb = allocator
+            .allocate(queue.family(), CommandBufferLevel::Primary, 1)
+            .unwrap()
+            .next()
+            .unwrap();
+        let raw = cb.inner().internal_object();
+
+        // Frame 1 has never been used, so it can be reset while frame 0 is still in use.
+        assert_eq!(allocator.next_frame(), Ok(1));
+
+        // Frame 0 still has a command buffer alive.
+        assert_eq!(
+            allocator.next_frame(),
+            Err(NextFrameError::FrameInUse {
+                frame: 0,
+                alive_command_buffers: 1,
+            })
+        );
+        assert_eq!(allocator.current_frame(), 1);
+
+        drop(cb);
+        assert_eq!(allocator.next_frame(), Ok(0));
+
+        let cb2 = allocator
+            .allocate(queue.family(), CommandBufferLevel::Primary, 1)
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(raw, cb2.inner().internal_object());
+    }
+
+    #[test]
+    fn build_with_allocator() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let allocator = RingCommandBufferAllocator::new(device, 1);
+
+        let builder = AutoCommandBufferBuilder::primary_with_allocator(
+            &allocator,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        let cb = builder.build().unwrap();
+        assert!(allocator.next_frame().is_err());
+
+        drop(cb);
+        assert_eq!(allocator.next_frame(), Ok(0));
+    }
+}
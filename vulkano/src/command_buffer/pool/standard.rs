@@ -1,7 +1,7 @@
 use super::{
     sys::{CommandBufferAllocateInfo, UnsafeCommandPoolCreateInfo, UnsafeCommandPoolCreationError},
-    CommandPool, CommandPoolAlloc, CommandPoolBuilderAlloc, UnsafeCommandPool,
-    UnsafeCommandPoolAlloc,
+    CommandBufferAllocator, CommandPool, CommandPoolAlloc, CommandPoolBuilderAlloc,
+    UnsafeCommandPool, UnsafeCommandPoolAlloc,
 };
 use crate::{
     command_buffer::CommandBufferLevel,
@@ -189,6 +189,47 @@ unsafe impl CommandPool for Arc<StandardCommandPool> {
     }
 }
 
+/// Standard implementation of a command buffer allocator.
+///
+/// Allocates command buffers from the standard command pool of the requested queue family. See
+/// `Device::standard_command_pool`.
+#[derive(Clone, Debug)]
+pub struct StandardCommandBufferAllocator {
+    device: Arc<Device>,
+}
+
+impl StandardCommandBufferAllocator {
+    /// Creates a new allocator for `device`.
+    #[inline]
+    pub fn new(device: Arc<Device>) -> StandardCommandBufferAllocator {
+        StandardCommandBufferAllocator { device }
+    }
+}
+
+unsafe impl CommandBufferAllocator for StandardCommandBufferAllocator {
+    type Iter = VecIntoIter<StandardCommandPoolBuilder>;
+    type Builder = StandardCommandPoolBuilder;
+    type Alloc = StandardCommandPoolAlloc;
+
+    #[inline]
+    fn allocate(
+        &self,
+        queue_family: QueueFamily,
+        level: CommandBufferLevel,
+        command_buffer_count: u32,
+    ) -> Result<Self::Iter, OomError> {
+        Device::standard_command_pool(&self.device, queue_family)
+            .allocate(level, command_buffer_count)
+    }
+}
+
+unsafe impl DeviceOwned for StandardCommandBufferAllocator {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
 unsafe impl DeviceOwned for StandardCommandPool {
     #[inline]
     fn device(&self) -> &Arc<Device> {
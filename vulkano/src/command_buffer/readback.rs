@@ -0,0 +1,459 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Reading back the contents of buffers and images.
+//!
+//! Reading data that the GPU has written, for example a screenshot or the result of a compute
+//! shader, requires copying it to a host-visible buffer, submitting the copy and waiting for it.
+//! [`read_buffer`] and [`read_image`] do all of this, and return a [`ReadbackFuture`] that gives
+//! access to the data once the copy has finished. The data can be retrieved by blocking with
+//! [`wait`](ReadbackFuture::wait), or by awaiting the future.
+//!
+//! The `_after` variants of the functions submit the copy after another future, which is needed
+//! if the resource is still being written by previously submitted work.
+//!
+//! ```
+//! use vulkano::command_buffer::readback;
+//! # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+//! # let image: std::sync::Arc<vulkano::image::StorageImage> = return;
+//!
+//! let readback = readback::read_image(queue.clone(), image.clone()).unwrap();
+//!
+//! // Do other work here...
+//!
+//! let buffer = readback.wait().unwrap();
+//! let pixels: Vec<u8> = buffer.read().unwrap().to_vec();
+//! ```
+
+use super::{
+    AutoCommandBufferBuilder, BufferImageCopy, BuildError, CommandBufferBeginError,
+    CommandBufferExecError, CommandBufferExecFuture, CommandBufferUsage, CopyBufferInfo, CopyError,
+    CopyImageToBufferInfo, PrimaryAutoCommandBuffer, PrimaryCommandBuffer,
+};
+use crate::{
+    buffer::{BufferContents, BufferUsage, CpuAccessibleBuffer, TypedBufferAccess},
+    device::{Device, DeviceOwned, Queue},
+    image::ImageAccess,
+    memory::{pool::HostMemoryPreference, DeviceMemoryAllocationError},
+    sync::{self, FenceSignalFuture, FlushError, GpuFuture, NowFuture},
+    OomError,
+};
+use parking_lot::Mutex;
+use std::{
+    error, fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+/// Copies the contents of `buffer` to a new host-visible buffer on `queue`.
+///
+/// # Panics
+///
+/// - Panics if `buffer` doesn't belong to the same device as `queue`.
+#[inline]
+pub fn read_buffer<B, T>(
+    queue: Arc<Queue>,
+    buffer: Arc<B>,
+) -> Result<ReadbackFuture<T, NowFuture>, ReadbackError>
+where
+    B: TypedBufferAccess<Content = T> + 'static,
+    T: BufferContents + ?Sized,
+{
+    let device = queue.device().clone();
+    read_buffer_after(sync::now(device), queue, buffer)
+}
+
+/// Copies the contents of `buffer` to a new host-visible buffer on `queue`, after `future`.
+///
+/// # Panics
+///
+/// - Panics if `buffer` doesn't belong to the same device as `queue`.
+pub fn read_buffer_after<F, B, T>(
+    future: F,
+    queue: Arc<Queue>,
+    buffer: Arc<B>,
+) -> Result<ReadbackFuture<T, F>, ReadbackError>
+where
+    F: GpuFuture,
+    B: TypedBufferAccess<Content = T> + 'static,
+    T: BufferContents + ?Sized,
+{
+    let device = queue.device().clone();
+    assert_eq!(&device, buffer.device());
+
+    // The size is the size of `buffer`, which is correct for `T`.
+    let host_buffer = unsafe {
+        CpuAccessibleBuffer::<T>::raw(
+            device.clone(),
+            buffer.size(),
+            BufferUsage::transfer_dst(),
+            HostMemoryPreference::HostCached,
+            [queue.family()],
+        )?
+    };
+
+    let mut builder = begin(device, &queue)?;
+    builder.copy_buffer(CopyBufferInfo::buffers(buffer, host_buffer.clone()))?;
+
+    submit(future, queue, builder, host_buffer)
+}
+
+/// Copies the first mip level of `image` to a new host-visible buffer on `queue`.
+///
+/// The texels are tightly packed in the buffer, with the array layers one after the other. If
+/// the image is multi-planar, only the first plane is copied.
+///
+/// # Panics
+///
+/// - Panics if `image` doesn't belong to the same device as `queue`.
+#[inline]
+pub fn read_image(
+    queue: Arc<Queue>,
+    image: Arc<dyn ImageAccess>,
+) -> Result<ReadbackFuture<[u8], NowFuture>, ReadbackError> {
+    let device = queue.device().clone();
+    read_image_after(sync::now(device), queue, image)
+}
+
+/// Copies the first mip level of `image` to a new host-visible buffer on `queue`, after
+/// `future`.
+///
+/// See [`read_image`] for the layout of the data.
+///
+/// # Panics
+///
+/// - Panics if `image` doesn't belong to the same device as `queue`.
+pub fn read_image_after<F>(
+    future: F,
+    queue: Arc<Queue>,
+    image: Arc<dyn ImageAccess>,
+) -> Result<ReadbackFuture<[u8], F>, ReadbackError>
+where
+    F: GpuFuture,
+{
+    let device = queue.device().clone();
+    assert_eq!(&device, image.device());
+
+    let region = BufferImageCopy {
+        image_subresource: image.subresource_layers(),
+        image_extent: image.dimensions().width_height_depth(),
+        ..Default::default()
+    };
+    let size = region.buffer_copy_size(image.format());
+
+    let host_buffer = unsafe {
+        CpuAccessibleBuffer::<[u8]>::raw(
+            device.clone(),
+            size,
+            BufferUsage::transfer_dst(),
+            HostMemoryPreference::HostCached,
+            [queue.family()],
+        )?
+    };
+
+    let mut builder = begin(device, &queue)?;
+    builder.copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(
+        image,
+        host_buffer.clone(),
+    ))?;
+
+    submit(future, queue, builder, host_buffer)
+}
+
+#[inline]
+fn begin(
+    device: Arc<Device>,
+    queue: &Queue,
+) -> Result<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>, ReadbackError> {
+    Ok(AutoCommandBufferBuilder::primary(
+        device,
+        queue.family(),
+        CommandBufferUsage::OneTimeSubmit,
+    )?)
+}
+
+fn submit<F, T>(
+    future: F,
+    queue: Arc<Queue>,
+    builder: AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    host_buffer: Arc<CpuAccessibleBuffer<T>>,
+) -> Result<ReadbackFuture<T, F>, ReadbackError>
+where
+    F: GpuFuture,
+    T: BufferContents + ?Sized,
+{
+    let future = builder
+        .build()?
+        .execute_after(future, queue)?
+        .then_signal_fence_and_flush()?;
+
+    Ok(ReadbackFuture {
+        future: Arc::new(future),
+        host_buffer,
+        waiter: None,
+    })
+}
+
+/// The copy of a resource to a host-visible buffer, submitted by [`read_buffer`] or
+/// [`read_image`].
+///
+/// Whether the copy has finished can be polled with [`is_ready`](Self::is_ready). The buffer is
+/// retrieved by blocking with [`wait`](Self::wait), or by awaiting the future. Awaiting the future
+/// starts a thread that waits for the copy to finish.
+pub struct ReadbackFuture<T, F>
+where
+    T: BufferContents + ?Sized,
+    F: GpuFuture,
+{
+    future: Arc<FenceSignalFuture<CommandBufferExecFuture<F, PrimaryAutoCommandBuffer>>>,
+    host_buffer: Arc<CpuAccessibleBuffer<T>>,
+    // Shared with the thread that waits for the copy, once the future has been polled.
+    waiter: Option<Arc<Mutex<WaiterState>>>,
+}
+
+impl<T, F> ReadbackFuture<T, F>
+where
+    T: BufferContents + ?Sized,
+    F: GpuFuture,
+{
+    /// Returns whether the copy has finished.
+    ///
+    /// This never blocks.
+    #[inline]
+    pub fn is_ready(&self) -> Result<bool, OomError> {
+        self.future.is_signaled()
+    }
+
+    /// Blocks the current thread until the copy has finished, and returns the buffer that the
+    /// data was copied to.
+    #[inline]
+    pub fn wait(self) -> Result<Arc<CpuAccessibleBuffer<T>>, FlushError> {
+        self.future.wait(None)?;
+
+        Ok(self.host_buffer)
+    }
+}
+
+impl<T, F> Future for ReadbackFuture<T, F>
+where
+    T: BufferContents + ?Sized,
+    F: GpuFuture + Send + Sync + 'static,
+{
+    type Output = Result<Arc<CpuAccessibleBuffer<T>>, FlushError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Some(waiter) = &this.waiter {
+            let mut state = waiter.lock();
+
+            if let Some(result) = state.result.take() {
+                return Poll::Ready(result.map(|()| this.host_buffer.clone()));
+            }
+
+            state.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let waiter = Arc::new(Mutex::new(WaiterState {
+            result: None,
+            waker: Some(cx.waker().clone()),
+        }));
+        this.waiter = Some(waiter.clone());
+
+        // Fences can't wake up a task, so a thread waits for the fence instead.
+        let future = this.future.clone();
+        thread::Builder::new()
+            .name("vulkano-readback".to_owned())
+            .spawn(move || {
+                let result = future.wait(None);
+
+                let waker = {
+                    let mut state = waiter.lock();
+                    state.result = Some(result);
+                    state.waker.take()
+                };
+
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            })
+            .expect("failed to spawn a readback thread");
+
+        Poll::Pending
+    }
+}
+
+impl<T, F> fmt::Debug for ReadbackFuture<T, F>
+where
+    T: BufferContents + ?Sized,
+    F: GpuFuture,
+{
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("ReadbackFuture")
+            .field("host_buffer", &self.host_buffer)
+            .finish()
+    }
+}
+
+struct WaiterState {
+    result: Option<Result<(), FlushError>>,
+    waker: Option<Waker>,
+}
+
+/// Error that can happen when reading back a resource.
+#[derive(Clone, Debug)]
+pub enum ReadbackError {
+    AllocError(DeviceMemoryAllocationError),
+    BeginError(CommandBufferBeginError),
+    BuildError(BuildError),
+    CopyError(CopyError),
+    ExecError(CommandBufferExecError),
+    FlushError(FlushError),
+}
+
+impl error::Error for ReadbackError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::AllocError(err) => Some(err),
+            Self::BeginError(err) => Some(err),
+            Self::BuildError(err) => Some(err),
+            Self::CopyError(err) => Some(err),
+            Self::ExecError(err) => Some(err),
+            Self::FlushError(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for ReadbackError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::AllocError(_) => write!(fmt, "allocating the host-visible buffer failed"),
+            Self::BeginError(_) => write!(fmt, "beginning the command buffer failed"),
+            Self::BuildError(_) => write!(fmt, "building the command buffer failed"),
+            Self::CopyError(_) => write!(fmt, "recording the copy failed"),
+            Self::ExecError(_) => write!(fmt, "executing the command buffer failed"),
+            Self::FlushError(_) => write!(fmt, "submitting the command buffer failed"),
+        }
+    }
+}
+
+impl From<DeviceMemoryAllocationError> for ReadbackError {
+    #[inline]
+    fn from(err: DeviceMemoryAllocationError) -> Self {
+        Self::AllocError(err)
+    }
+}
+
+impl From<CommandBufferBeginError> for ReadbackError {
+    #[inline]
+    fn from(err: CommandBufferBeginError) -> Self {
+        Self::BeginError(err)
+    }
+}
+
+impl From<BuildError> for ReadbackError {
+    #[inline]
+    fn from(err: BuildError) -> Self {
+        Self::BuildError(err)
+    }
+}
+
+impl From<CopyError> for ReadbackError {
+    #[inline]
+    fn from(err: CopyError) -> Self {
+        Self::CopyError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for ReadbackError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> Self {
+        Self::ExecError(err)
+    }
+}
+
+impl From<FlushError> for ReadbackError {
+    #[inline]
+    fn from(err: FlushError) -> Self {
+        Self::FlushError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_buffer, read_image_after};
+    use crate::{
+        buffer::{BufferUsage, CpuAccessibleBuffer},
+        command_buffer::{
+            AutoCommandBufferBuilder, ClearColorImageInfo, CommandBufferUsage, PrimaryCommandBuffer,
+        },
+        format::{ClearColorValue, Format},
+        image::{ImageDimensions, StorageImage},
+        sync::GpuFuture,
+    };
+
+    #[test]
+    fn read_back_buffer() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer =
+            CpuAccessibleBuffer::from_iter(device, BufferUsage::transfer_src(), false, 0..16u32)
+                .unwrap();
+
+        let host_buffer = read_buffer(queue, buffer).unwrap().wait().unwrap();
+        assert_eq!(
+            *host_buffer.read().unwrap(),
+            (0..16).collect::<Vec<u32>>()[..]
+        );
+    }
+
+    #[test]
+    fn read_back_image_after_clear() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let image = StorageImage::new(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: 4,
+                height: 4,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UINT,
+            [queue.family()],
+        )
+        .unwrap();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .clear_color_image(ClearColorImageInfo {
+                clear_value: ClearColorValue::Uint([1, 2, 3, 4]),
+                ..ClearColorImageInfo::image(image.clone())
+            })
+            .unwrap();
+        let future = builder.build().unwrap().execute(queue.clone()).unwrap();
+
+        let host_buffer = read_image_after(future, queue, image)
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert_eq!(*host_buffer.read().unwrap(), [1u8, 2, 3, 4].repeat(16)[..]);
+    }
+}
@@ -0,0 +1,636 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Uploading data to device-local buffers through reusable staging memory.
+//!
+//! Writing data to a device-local buffer requires copying it from a host-visible staging buffer.
+//! Creating a staging buffer for every upload is wasteful, and keeping track of when a staging
+//! buffer can be reused is tedious. A [`StagingBelt`] owns a set of mapped staging buffers, called
+//! chunks, and sub-allocates the data of many uploads from them:
+//!
+//! - [`write`](StagingBelt::write) copies data into a chunk and records a copy from it into a
+//!   command buffer.
+//! - [`finish`](StagingBelt::finish) is called after the command buffer is submitted, with the
+//!   fence of the submission. The chunks that were written since the previous call can't be
+//!   reused until the fence is signaled.
+//! - [`recall`](StagingBelt::recall) makes the chunks whose fence has been signaled available for
+//!   new writes. It is usually called once per frame.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use vulkano::buffer::{BufferUsage, DeviceLocalBuffer};
+//! use vulkano::command_buffer::{
+//!     staging_belt::StagingBelt, AutoCommandBufferBuilder, CommandBufferUsage,
+//!     PrimaryCommandBuffer,
+//! };
+//! use vulkano::sync::GpuFuture;
+//! # let device: Arc<vulkano::device::Device> = return;
+//! # let queue: Arc<vulkano::device::Queue> = return;
+//!
+//! let buffer = DeviceLocalBuffer::<[f32]>::array(
+//!     device.clone(),
+//!     4,
+//!     BufferUsage::transfer_dst() | BufferUsage::uniform_buffer(),
+//!     [queue.family()],
+//! )
+//! .unwrap();
+//!
+//! let mut belt = StagingBelt::new(device.clone(), 64 * 1024);
+//!
+//! // Once per frame.
+//! belt.recall().unwrap();
+//!
+//! let mut builder = AutoCommandBufferBuilder::primary(
+//!     device.clone(),
+//!     queue.family(),
+//!     CommandBufferUsage::OneTimeSubmit,
+//! )
+//! .unwrap();
+//! belt.write(buffer.clone(), &[1.0f32, 2.0, 3.0, 4.0], &mut builder)
+//!     .unwrap();
+//! let command_buffer = builder.build().unwrap();
+//!
+//! let future = Arc::new(
+//!     command_buffer
+//!         .execute(queue.clone())
+//!         .unwrap()
+//!         .then_signal_fence_and_flush()
+//!         .unwrap(),
+//! );
+//! belt.finish(future.clone());
+//! ```
+
+use super::{AutoCommandBufferBuilder, BufferCopy, CopyBufferInfo, CopyError};
+use crate::{
+    buffer::{
+        sys::{UnsafeBuffer, UnsafeBufferCreateInfo},
+        BufferAccess, BufferAccessObject, BufferContents, BufferCreationError, BufferInner,
+        BufferUsage,
+    },
+    device::{Device, DeviceOwned},
+    memory::{
+        pool::{
+            AllocLayout, HostMemoryPreference, MappingRequirement, MemoryPoolAlloc,
+            PotentialDedicatedAllocation, StdMemoryPoolAlloc,
+        },
+        DedicatedAllocation, DeviceMemoryAllocationError, MemoryMapError, MemoryPool,
+    },
+    sync::FenceStatus,
+    DeviceSize, OomError,
+};
+use smallvec::SmallVec;
+use std::{error, fmt, sync::Arc};
+
+/// Set of mapped staging buffers that uploads to other buffers are sub-allocated from.
+///
+/// See the [module-level documentation](self) for more information.
+pub struct StagingBelt {
+    device: Arc<Device>,
+    chunk_size: DeviceSize,
+    // Alignment of the data in the chunks. Chunks start and end on atom boundaries of their
+    // memory, so aligning the data to the atom size lets each write be flushed separately.
+    alignment: DeviceSize,
+
+    // Chunks that were written to since the last call to `finish`.
+    active: Vec<Chunk>,
+    // Chunks that are in use by a submission, together with its fence.
+    closed: Vec<(Arc<dyn FenceStatus>, Vec<Chunk>)>,
+    // Chunks that can be written to.
+    free: Vec<Chunk>,
+}
+
+impl StagingBelt {
+    /// Creates a new `StagingBelt` that allocates chunks of `chunk_size` bytes.
+    ///
+    /// Writes that are larger than `chunk_size` get a chunk of their own.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `chunk_size` is zero.
+    #[inline]
+    pub fn new(device: Arc<Device>, chunk_size: DeviceSize) -> StagingBelt {
+        assert!(chunk_size != 0);
+
+        let alignment = device
+            .physical_device()
+            .properties()
+            .non_coherent_atom_size
+            .max(4);
+
+        StagingBelt {
+            device,
+            chunk_size,
+            alignment,
+            active: Vec::new(),
+            closed: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Returns the size of the chunks that are allocated.
+    #[inline]
+    pub fn chunk_size(&self) -> DeviceSize {
+        self.chunk_size
+    }
+
+    /// Returns the total size of the chunks that are owned by the belt, including the ones that
+    /// are in use.
+    #[inline]
+    pub fn allocated_size(&self) -> DeviceSize {
+        self.active
+            .iter()
+            .chain(self.closed.iter().flat_map(|(_, chunks)| chunks))
+            .chain(&self.free)
+            .map(|chunk| chunk.buffer.size())
+            .sum()
+    }
+
+    /// Copies `data` to the start of `dst_buffer`.
+    ///
+    /// The data is written into a chunk immediately, and a copy from the chunk to `dst_buffer` is
+    /// recorded into `builder`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `dst_buffer` doesn't belong to the same device as the belt.
+    #[inline]
+    pub fn write<T, L, P>(
+        &mut self,
+        dst_buffer: Arc<dyn BufferAccess>,
+        data: &T,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+    ) -> Result<(), StagingBeltError>
+    where
+        T: BufferContents + ?Sized,
+    {
+        self.write_regions(dst_buffer, [(0, data.as_bytes())], builder)
+    }
+
+    /// Copies several pieces of data to `dst_buffer`, each at the given offset in bytes.
+    ///
+    /// The pieces are placed next to each other in the chunks, and the copies are recorded into
+    /// `builder` as a single copy command per chunk that is used.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `dst_buffer` doesn't belong to the same device as the belt.
+    pub fn write_regions<'d, L, P>(
+        &mut self,
+        dst_buffer: Arc<dyn BufferAccess>,
+        regions: impl IntoIterator<Item = (DeviceSize, &'d [u8])>,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+    ) -> Result<(), StagingBeltError> {
+        assert_eq!(self.device, *dst_buffer.device());
+
+        // The copies that are recorded from the current chunk, which is the last active one.
+        let mut copies: SmallVec<[BufferCopy; 1]> = SmallVec::new();
+
+        for (dst_offset, data) in regions {
+            if data.is_empty() {
+                continue;
+            }
+
+            let size = data.len() as DeviceSize;
+
+            let fits = self
+                .active
+                .last()
+                .map_or(false, |chunk| chunk.space(self.alignment) >= size);
+
+            if !fits {
+                if !copies.is_empty() {
+                    let chunk = self.active.last().unwrap();
+                    builder.copy_buffer(CopyBufferInfo {
+                        regions: std::mem::take(&mut copies),
+                        ..CopyBufferInfo::buffers(chunk.buffer.clone(), dst_buffer.clone())
+                    })?;
+                }
+
+                let chunk = self.take_chunk(size)?;
+                self.active.push(chunk);
+            }
+
+            let chunk = self.active.last_mut().unwrap();
+            let src_offset = chunk.allocate(size, self.alignment);
+            chunk.buffer.write(src_offset, data)?;
+
+            copies.push(BufferCopy {
+                src_offset,
+                dst_offset,
+                size,
+                ..Default::default()
+            });
+        }
+
+        if !copies.is_empty() {
+            let chunk = self.active.last().unwrap();
+            builder.copy_buffer(CopyBufferInfo {
+                regions: copies,
+                ..CopyBufferInfo::buffers(chunk.buffer.clone(), dst_buffer)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies `data` into a chunk at an offset that is a multiple of `alignment`, and returns the
+    /// chunk together with the offset.
+    ///
+    /// This is used to record copies other than buffer-to-buffer copies, for example to an
+    /// image. The copy from the chunk must be recorded before the next call to
+    /// [`finish`](Self::finish), into a command buffer that the fence given to it covers.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `alignment` is zero.
+    pub fn stage(
+        &mut self,
+        data: &[u8],
+        alignment: DeviceSize,
+    ) -> Result<(Arc<dyn BufferAccess>, DeviceSize), StagingBeltError> {
+        assert!(alignment != 0);
+
+        let alignment = lcm(self.alignment, alignment);
+        let size = data.len() as DeviceSize;
+
+        let fits = self
+            .active
+            .last()
+            .map_or(false, |chunk| chunk.space(alignment) >= size);
+
+        if !fits {
+            let chunk = self.take_chunk(size)?;
+            self.active.push(chunk);
+        }
+
+        let chunk = self.active.last_mut().unwrap();
+        let offset = chunk.allocate(size, alignment);
+        chunk.buffer.write(offset, data)?;
+
+        Ok((chunk.buffer.clone(), offset))
+    }
+
+    /// Marks the chunks that were written to since the last call as in use until `fence` is
+    /// signaled.
+    ///
+    /// This must be called after the command buffers that `write` recorded into have been
+    /// submitted, with the fence that is signaled when the last of them has finished executing.
+    #[inline]
+    pub fn finish(&mut self, fence: Arc<dyn FenceStatus>) {
+        if !self.active.is_empty() {
+            self.closed.push((fence, std::mem::take(&mut self.active)));
+        }
+    }
+
+    /// Makes the chunks whose fence has been signaled available for new writes.
+    ///
+    /// This never blocks.
+    pub fn recall(&mut self) -> Result<(), OomError> {
+        let mut index = 0;
+
+        while index < self.closed.len() {
+            if self.closed[index].0.is_signaled()? {
+                let (_, chunks) = self.closed.swap_remove(index);
+                self.free.extend(chunks.into_iter().map(|mut chunk| {
+                    chunk.used = 0;
+                    chunk
+                }));
+            } else {
+                index += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drops the chunks that are not in use, to free their memory.
+    #[inline]
+    pub fn trim(&mut self) {
+        self.free.clear();
+    }
+
+    // Returns a free chunk with room for `size` bytes, or allocates a new one.
+    fn take_chunk(&mut self, size: DeviceSize) -> Result<Chunk, StagingBeltError> {
+        if let Some(index) = self
+            .free
+            .iter()
+            .position(|chunk| chunk.buffer.size() >= size)
+        {
+            return Ok(self.free.swap_remove(index));
+        }
+
+        let buffer = StagingBuffer::new(self.device.clone(), size.max(self.chunk_size))?;
+
+        Ok(Chunk { buffer, used: 0 })
+    }
+}
+
+impl fmt::Debug for StagingBelt {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("StagingBelt")
+            .field("chunk_size", &self.chunk_size)
+            .field("active", &self.active.len())
+            .field("closed", &self.closed.len())
+            .field("free", &self.free.len())
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct Chunk {
+    buffer: Arc<StagingBuffer>,
+    // Number of bytes at the start of the buffer that are already written to.
+    used: DeviceSize,
+}
+
+impl Chunk {
+    // Returns the number of bytes that can still be allocated.
+    fn space(&self, alignment: DeviceSize) -> DeviceSize {
+        let start = align_up(self.used, alignment);
+        self.buffer.size().saturating_sub(start)
+    }
+
+    // Allocates `size` bytes and returns their offset. The space must have been checked before.
+    fn allocate(&mut self, size: DeviceSize, alignment: DeviceSize) -> DeviceSize {
+        let offset = align_up(self.used, alignment);
+        debug_assert!(offset + size <= self.buffer.size());
+        self.used = offset + size;
+        offset
+    }
+}
+
+#[inline]
+fn align_up(value: DeviceSize, alignment: DeviceSize) -> DeviceSize {
+    (value + alignment - 1) / alignment * alignment
+}
+
+#[inline]
+fn lcm(a: DeviceSize, b: DeviceSize) -> DeviceSize {
+    let (mut x, mut y) = (a, b);
+
+    while y != 0 {
+        let r = x % y;
+        x = y;
+        y = r;
+    }
+
+    a / x * b
+}
+
+// Mapped buffer that the data of writes is copied to.
+//
+// The GPU accesses are synchronized with the fences given to `StagingBelt::finish`, so this type
+// doesn't use the CPU locks of `CpuAccessibleBuffer`.
+#[derive(Debug)]
+struct StagingBuffer {
+    inner: Arc<UnsafeBuffer>,
+    memory: PotentialDedicatedAllocation<StdMemoryPoolAlloc>,
+}
+
+impl StagingBuffer {
+    fn new(device: Arc<Device>, size: DeviceSize) -> Result<Arc<StagingBuffer>, StagingBeltError> {
+        let inner = match UnsafeBuffer::new(
+            device.clone(),
+            UnsafeBufferCreateInfo {
+                size,
+                usage: BufferUsage::transfer_src(),
+                ..Default::default()
+            },
+        ) {
+            Ok(b) => b,
+            Err(BufferCreationError::AllocError(err)) => return Err(err.into()),
+            Err(_) => unreachable!(), // We don't use sparse binding, therefore the other
+                                      // errors can't happen
+        };
+        let mut mem_reqs = inner.memory_requirements();
+
+        // Each write is flushed separately, and flushing happens on whole atoms of the memory
+        // allocation. The chunk must own all the atoms it overlaps, so that flushing it doesn't
+        // affect the memory next to it.
+        let atom_size = device.physical_device().properties().non_coherent_atom_size;
+        mem_reqs.alignment = mem_reqs.alignment.max(atom_size);
+        mem_reqs.size = align_up(mem_reqs.size, atom_size);
+
+        let memory = MemoryPool::alloc_from_requirements(
+            &Device::standard_pool(&device),
+            &mem_reqs,
+            AllocLayout::Linear,
+            MappingRequirement::Map,
+            Some(DedicatedAllocation::Buffer(&inner)),
+            |m| HostMemoryPreference::WriteCombined.memory_type_filter(m),
+        )?;
+        debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
+        debug_assert!(memory.mapped_memory().is_some());
+        unsafe {
+            inner
+                .bind_memory(memory.memory(), memory.offset())
+                .map_err(DeviceMemoryAllocationError::from)?;
+        }
+
+        Ok(Arc::new(StagingBuffer { inner, memory }))
+    }
+
+    // Copies `data` to `offset` in the buffer and flushes it.
+    fn write(&self, offset: DeviceSize, data: &[u8]) -> Result<(), MemoryMapError> {
+        let mapped_memory = self.memory.mapped_memory().unwrap();
+        let memory_offset = self.memory.offset();
+        let data_range = memory_offset + offset..memory_offset + offset + data.len() as DeviceSize;
+
+        // Flushing must happen on whole atoms. Writes are aligned to the atom size, and the chunk
+        // owns all the atoms it overlaps, so this only covers bytes that aren't used by other
+        // writes.
+        let memory_range = mapped_memory.align_range(data_range.clone());
+
+        unsafe {
+            // The chunk is only written to while the GPU isn't using it.
+            let bytes = mapped_memory.write(memory_range.clone())?;
+            let start = (data_range.start - memory_range.start) as usize;
+            bytes[start..start + data.len()].copy_from_slice(data);
+            mapped_memory.flush_range(memory_range)?;
+        }
+
+        Ok(())
+    }
+}
+
+unsafe impl DeviceOwned for StagingBuffer {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.inner.device()
+    }
+}
+
+unsafe impl BufferAccess for StagingBuffer {
+    #[inline]
+    fn inner(&self) -> BufferInner {
+        BufferInner {
+            buffer: &self.inner,
+            offset: 0,
+        }
+    }
+
+    #[inline]
+    fn size(&self) -> DeviceSize {
+        self.inner.size()
+    }
+}
+
+impl BufferAccessObject for Arc<StagingBuffer> {
+    #[inline]
+    fn as_buffer_access_object(&self) -> Arc<dyn BufferAccess> {
+        self.clone()
+    }
+}
+
+/// Error that can happen when writing to a [`StagingBelt`].
+#[derive(Clone, Debug)]
+pub enum StagingBeltError {
+    /// Allocating a new chunk failed.
+    AllocError(DeviceMemoryAllocationError),
+
+    /// Writing to a chunk failed.
+    MemoryMapError(MemoryMapError),
+
+    /// Recording the copy into the command buffer failed.
+    CopyError(CopyError),
+}
+
+impl error::Error for StagingBeltError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::AllocError(err) => Some(err),
+            Self::MemoryMapError(err) => Some(err),
+            Self::CopyError(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for StagingBeltError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::AllocError(_) => write!(fmt, "allocating a new chunk failed"),
+            Self::MemoryMapError(_) => write!(fmt, "writing to a chunk failed"),
+            Self::CopyError(_) => write!(fmt, "recording the copy failed"),
+        }
+    }
+}
+
+impl From<DeviceMemoryAllocationError> for StagingBeltError {
+    #[inline]
+    fn from(err: DeviceMemoryAllocationError) -> Self {
+        Self::AllocError(err)
+    }
+}
+
+impl From<MemoryMapError> for StagingBeltError {
+    #[inline]
+    fn from(err: MemoryMapError) -> Self {
+        Self::MemoryMapError(err)
+    }
+}
+
+impl From<CopyError> for StagingBeltError {
+    #[inline]
+    fn from(err: CopyError) -> Self {
+        Self::CopyError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StagingBelt, StagingBuffer};
+    use crate::{
+        buffer::{BufferUsage, DeviceLocalBuffer},
+        command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage},
+        memory::pool::MemoryPoolAlloc,
+        sync::{Fence, FenceCreateInfo},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn recycle_chunks() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = DeviceLocalBuffer::<[u32]>::array(
+            device.clone(),
+            1024,
+            BufferUsage::transfer_dst(),
+            [queue.family()],
+        )
+        .unwrap();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        let mut belt = StagingBelt::new(device.clone(), 1024);
+        belt.write(buffer.clone(), &[1u32; 64], &mut builder)
+            .unwrap();
+        belt.write_regions(
+            buffer.clone(),
+            [(0, &[1u8; 512][..]), (1024, &[2u8; 512][..])],
+            &mut builder,
+        )
+        .unwrap();
+
+        // The second write doesn't fit in the first chunk.
+        assert_eq!(belt.active.len(), 2);
+        assert_eq!(belt.allocated_size(), 2048);
+
+        let unsignaled = Arc::new(Fence::new(device.clone(), Default::default()).unwrap());
+        belt.finish(unsignaled);
+        belt.recall().unwrap();
+        assert!(belt.free.is_empty());
+
+        // Writes after `finish` don't reuse chunks that are in use.
+        belt.write(buffer.clone(), &[3u32; 16], &mut builder)
+            .unwrap();
+        assert_eq!(belt.allocated_size(), 3072);
+
+        let signaled = Arc::new(
+            Fence::new(
+                device.clone(),
+                FenceCreateInfo {
+                    signaled: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+        belt.finish(signaled);
+        belt.recall().unwrap();
+        assert_eq!(belt.free.len(), 1);
+
+        // The recalled chunk is reused.
+        belt.write(buffer, &[4u32; 16], &mut builder).unwrap();
+        assert!(belt.free.is_empty());
+        assert_eq!(belt.allocated_size(), 3072);
+    }
+
+    #[test]
+    fn chunks_own_whole_atoms() {
+        let (device, _queue) = gfx_dev_and_queue!();
+        let atom_size = device.physical_device().properties().non_coherent_atom_size;
+
+        // Two small chunks that could otherwise share an atom of the same memory block.
+        let first = StagingBuffer::new(device.clone(), 3).unwrap();
+        let second = StagingBuffer::new(device, 3).unwrap();
+        assert_eq!(first.memory.offset() % atom_size, 0);
+        assert_eq!(second.memory.offset() % atom_size, 0);
+
+        first.write(0, &[1, 2, 3]).unwrap();
+        second.write(1, &[4, 5]).unwrap();
+    }
+}
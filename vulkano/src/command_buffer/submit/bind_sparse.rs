@@ -11,6 +11,7 @@ use crate::buffer::sys::UnsafeBuffer;
 use crate::check_errors;
 use crate::device::Queue;
 use crate::image::sys::UnsafeImage;
+use crate::image::ImageAspect;
 use crate::memory::DeviceMemory;
 use crate::sync::Fence;
 use crate::sync::Semaphore;
@@ -430,13 +431,22 @@ impl<'a> SubmitBindSparseImageOpaqueBindBuilder<'a> {
         });
     }
 
-    pub unsafe fn add_unbind(&mut self, offset: DeviceSize, size: DeviceSize) {
+    pub unsafe fn add_unbind(
+        &mut self,
+        offset: DeviceSize,
+        size: DeviceSize,
+        unbind_metadata: bool,
+    ) {
         self.binds.push(ash::vk::SparseMemoryBind {
             resource_offset: offset,
             size,
             memory: ash::vk::DeviceMemory::null(),
             memory_offset: 0,
-            flags: ash::vk::SparseMemoryBindFlags::empty(), // TODO: is that relevant?
+            flags: if unbind_metadata {
+                ash::vk::SparseMemoryBindFlags::METADATA
+            } else {
+                ash::vk::SparseMemoryBindFlags::empty()
+            },
         });
     }
 }
@@ -460,7 +470,68 @@ impl<'a> SubmitBindSparseImageBindBuilder<'a> {
         }
     }
 
-    // TODO: finish
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn add_bind(
+        &mut self,
+        aspect: ImageAspect,
+        mip_level: u32,
+        array_layer: u32,
+        offset: [u32; 3],
+        extent: [u32; 3],
+        memory: &DeviceMemory,
+        memory_offset: DeviceSize,
+    ) {
+        self.binds.push(ash::vk::SparseImageMemoryBind {
+            subresource: ash::vk::ImageSubresource {
+                aspect_mask: aspect.into(),
+                mip_level,
+                array_layer,
+            },
+            offset: ash::vk::Offset3D {
+                x: offset[0] as i32,
+                y: offset[1] as i32,
+                z: offset[2] as i32,
+            },
+            extent: ash::vk::Extent3D {
+                width: extent[0],
+                height: extent[1],
+                depth: extent[2],
+            },
+            memory: memory.internal_object(),
+            memory_offset,
+            flags: ash::vk::SparseMemoryBindFlags::empty(), // Only relevant for opaque binds.
+        });
+    }
+
+    pub unsafe fn add_unbind(
+        &mut self,
+        aspect: ImageAspect,
+        mip_level: u32,
+        array_layer: u32,
+        offset: [u32; 3],
+        extent: [u32; 3],
+    ) {
+        self.binds.push(ash::vk::SparseImageMemoryBind {
+            subresource: ash::vk::ImageSubresource {
+                aspect_mask: aspect.into(),
+                mip_level,
+                array_layer,
+            },
+            offset: ash::vk::Offset3D {
+                x: offset[0] as i32,
+                y: offset[1] as i32,
+                z: offset[2] as i32,
+            },
+            extent: ash::vk::Extent3D {
+                width: extent[0],
+                height: extent[1],
+                depth: extent[2],
+            },
+            memory: ash::vk::DeviceMemory::null(),
+            memory_offset: 0,
+            flags: ash::vk::SparseMemoryBindFlags::empty(),
+        });
+    }
 }
 
 /// Error that can happen when submitting the present prototype.
@@ -7,24 +7,32 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use parking_lot::Mutex;
 use smallvec::SmallVec;
 use std::error;
 use std::fmt;
 use std::marker::PhantomData;
 use std::ptr;
+use std::sync::Arc;
 
 use crate::device::DeviceOwned;
 use crate::device::Queue;
 use crate::swapchain::PresentRegion;
 use crate::swapchain::Swapchain;
+use crate::swapchain::SwapchainPresentResult;
+use crate::sync::Fence;
 use crate::sync::Semaphore;
 
 use crate::check_errors;
 use crate::Error;
 use crate::OomError;
+use crate::Success;
 use crate::SynchronizedVulkanObject;
 use crate::VulkanObject;
 
+// The fences of the presentations of a swapchain that may not have finished yet.
+type PresentFences = Mutex<Vec<Arc<Fence>>>;
+
 /// Prototype for a submission that presents a swapchain on the screen.
 // TODO: example here
 pub struct SubmitPresentBuilder<'a> {
@@ -33,6 +41,12 @@ pub struct SubmitPresentBuilder<'a> {
     image_indices: SmallVec<[u32; 4]>,
     present_regions: SmallVec<[ash::vk::PresentRegionKHR; 4]>,
     rect_layers: SmallVec<[ash::vk::RectLayerKHR; 4]>,
+    present_ids: SmallVec<[u64; 4]>,
+    // For each swapchain, if the `swapchain_maintenance1` feature is enabled, the list of
+    // present fences of the swapchain.
+    present_fences: SmallVec<[Option<&'a PresentFences>; 4]>,
+    // For each swapchain, where the result of the presentation is stored.
+    present_results: SmallVec<[&'a Mutex<Option<SwapchainPresentResult>>; 4]>,
     marker: PhantomData<&'a ()>,
 }
 
@@ -46,6 +60,9 @@ impl<'a> SubmitPresentBuilder<'a> {
             image_indices: SmallVec::new(),
             present_regions: SmallVec::new(),
             rect_layers: SmallVec::new(),
+            present_ids: SmallVec::new(),
+            present_fences: SmallVec::new(),
+            present_results: SmallVec::new(),
             marker: PhantomData,
         }
     }
@@ -87,12 +104,16 @@ impl<'a> SubmitPresentBuilder<'a> {
     ///
     /// - The swapchains and semaphores must all belong to the same device.
     ///
+    /// - If `present_id` is `Some`, the `present_id` feature must be enabled on the device, and
+    ///   the ID must be greater than all the present IDs previously used with the swapchain.
+    ///
     #[inline]
     pub unsafe fn add_swapchain<W>(
         &mut self,
         swapchain: &'a Swapchain<W>,
         image_num: u32,
         present_region: Option<&'a PresentRegion>,
+        present_id: Option<u64>,
     ) {
         debug_assert!(image_num < swapchain.image_count());
 
@@ -121,17 +142,63 @@ impl<'a> SubmitPresentBuilder<'a> {
             self.present_regions.push(vk_present_region);
         }
 
+        if let Some(present_id) = present_id {
+            debug_assert!(swapchain.device().enabled_features().present_id);
+
+            // Swapchains that were added earlier without a present ID get the ID 0.
+            self.present_ids.resize(self.swapchains.len(), 0);
+            self.present_ids.push(present_id);
+        } else if !self.present_ids.is_empty() {
+            self.present_ids.push(0);
+        }
+
+        // So that the swapchain knows when the presentation no longer uses it.
+        self.present_fences.push(
+            if swapchain.device().enabled_features().swapchain_maintenance1 {
+                Some(swapchain.present_fences())
+            } else {
+                None
+            },
+        );
+
+        self.present_results
+            .push(swapchain.last_present_result_mut());
         self.swapchains.push(swapchain.internal_object());
         self.image_indices.push(image_num);
     }
 
     /// Submits the command. Calls `vkQueuePresentKHR`.
     ///
+    /// If presenting to one of the swapchains fails, the error of the first such swapchain is
+    /// returned. The result for each swapchain is also available afterwards through
+    /// [`Swapchain::last_present_result`].
+    ///
     /// # Panic
     ///
     /// Panics if no swapchain image has been added to the builder.
     ///
-    pub fn submit(mut self, queue: &Queue) -> Result<(), SubmitPresentError> {
+    pub fn submit(self, queue: &Queue) -> Result<(), SubmitPresentError> {
+        for result in self.submit_with_results(queue)? {
+            if let SwapchainPresentResult::Failed(err) = result {
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Same as `submit`, but returns the result of the presentation for each swapchain, in the
+    /// order in which they were added. An error is only returned if presenting failed as a whole,
+    /// for example because the device was lost.
+    ///
+    /// # Panic
+    ///
+    /// Panics if no swapchain image has been added to the builder.
+    ///
+    pub fn submit_with_results(
+        mut self,
+        queue: &Queue,
+    ) -> Result<SmallVec<[SwapchainPresentResult; 4]>, SubmitPresentError> {
         unsafe {
             debug_assert_eq!(self.swapchains.len(), self.image_indices.len());
             assert!(
@@ -139,7 +206,7 @@ impl<'a> SubmitPresentBuilder<'a> {
                 "Tried to submit a present command without any swapchain"
             );
 
-            let present_regions = {
+            let mut present_regions = {
                 if !self.present_regions.is_empty() {
                     debug_assert!(queue.device().enabled_extensions().khr_incremental_present);
                     debug_assert_eq!(self.swapchains.len(), self.present_regions.len());
@@ -158,16 +225,53 @@ impl<'a> SubmitPresentBuilder<'a> {
                 }
             };
 
+            let mut present_ids = if !self.present_ids.is_empty() {
+                debug_assert_eq!(self.swapchains.len(), self.present_ids.len());
+                Some(ash::vk::PresentIdKHR {
+                    swapchain_count: self.present_ids.len() as u32,
+                    p_present_ids: self.present_ids.as_ptr(),
+                    ..Default::default()
+                })
+            } else {
+                None
+            };
+
+            let present_fences = self
+                .present_fences
+                .iter()
+                .map(|list| {
+                    list.map(|list| {
+                        Fence::from_pool(queue.device().clone())
+                            .map(|fence| (Arc::new(fence), list))
+                    })
+                    .transpose()
+                })
+                .collect::<Result<SmallVec<[_; 4]>, _>>()
+                .map_err(SubmitPresentError::OomError)?;
+            let present_fences_vk: SmallVec<[_; 4]> = present_fences
+                .iter()
+                .map(|fence| {
+                    fence
+                        .as_ref()
+                        .map_or(ash::vk::Fence::null(), |(fence, _)| fence.internal_object())
+                })
+                .collect();
+            let mut present_fence_info = if present_fences.iter().any(Option::is_some) {
+                Some(ash::vk::SwapchainPresentFenceInfoEXT {
+                    swapchain_count: present_fences_vk.len() as u32,
+                    p_fences: present_fences_vk.as_ptr(),
+                    ..Default::default()
+                })
+            } else {
+                None
+            };
+
             let mut results = vec![ash::vk::Result::SUCCESS; self.swapchains.len()];
 
             let fns = queue.device().fns();
             let queue = queue.internal_object_guard();
 
-            let infos = ash::vk::PresentInfoKHR {
-                p_next: present_regions
-                    .as_ref()
-                    .map(|pr| pr as *const ash::vk::PresentRegionsKHR as *const _)
-                    .unwrap_or(ptr::null()),
+            let mut infos = ash::vk::PresentInfoKHR {
                 wait_semaphore_count: self.wait_semaphores.len() as u32,
                 p_wait_semaphores: self.wait_semaphores.as_ptr(),
                 swapchain_count: self.swapchains.len() as u32,
@@ -177,13 +281,71 @@ impl<'a> SubmitPresentBuilder<'a> {
                 ..Default::default()
             };
 
-            check_errors((fns.khr_swapchain.queue_present_khr)(*queue, &infos))?;
+            if let Some(present_regions) = present_regions.as_mut() {
+                present_regions.p_next = infos.p_next;
+                infos.p_next = present_regions as *const _ as *const _;
+            }
+
+            if let Some(present_ids) = present_ids.as_mut() {
+                present_ids.p_next = infos.p_next;
+                infos.p_next = present_ids as *const _ as *const _;
+            }
+
+            if let Some(present_fence_info) = present_fence_info.as_mut() {
+                present_fence_info.p_next = infos.p_next;
+                infos.p_next = present_fence_info as *const _ as *const _;
+            }
+
+            let result = check_errors((fns.khr_swapchain.queue_present_khr)(*queue, &infos));
 
-            for result in results {
-                check_errors(result)?;
+            // The presentation is still queued if it fails because of the surface, so the fences
+            // will be signaled.
+            if matches!(
+                result,
+                Ok(_)
+                    | Err(Error::OutOfDate)
+                    | Err(Error::SurfaceLost)
+                    | Err(Error::FullScreenExclusiveLost)
+            ) {
+                for (fence, list) in present_fences.into_iter().flatten() {
+                    let mut list = list.lock();
+                    list.retain(|fence| !fence.is_signaled().unwrap_or(false));
+                    list.push(fence);
+                }
+            }
+
+            // The errors that are not specific to a swapchain apply to the whole presentation.
+            if let Err(
+                err @ (Error::OutOfHostMemory | Error::OutOfDeviceMemory | Error::DeviceLost),
+            ) = result
+            {
+                return Err(err.into());
+            }
+
+            let results: SmallVec<[_; 4]> = results
+                .into_iter()
+                .map(|result| match check_errors(result) {
+                    Ok(Success::Suboptimal) => SwapchainPresentResult::Suboptimal,
+                    Ok(_) => SwapchainPresentResult::Success,
+                    Err(err) => SwapchainPresentResult::Failed(err.into()),
+                })
+                .collect();
+
+            for (&present_result, result) in self.present_results.iter().zip(&results) {
+                *present_result.lock() = Some(*result);
+            }
+
+            // Should not happen, but don't lose an error that wasn't reported for any swapchain.
+            if let Err(err) = result {
+                if !results
+                    .iter()
+                    .any(|result| matches!(result, SwapchainPresentResult::Failed(_)))
+                {
+                    return Err(err.into());
+                }
             }
 
-            Ok(())
+            Ok(results)
         }
     }
 }
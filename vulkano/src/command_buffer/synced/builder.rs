@@ -7,7 +7,9 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
-use super::{Command, Resource, SyncCommandBuffer};
+use super::{
+    BarrierReason, BarrierResource, BarrierResourceUse, Command, Resource, SyncCommandBuffer,
+};
 pub use crate::command_buffer::commands::{
     bind_push::{
         SyncCommandBufferBuilderBindDescriptorSets, SyncCommandBufferBuilderBindVertexBuffer,
@@ -21,32 +23,36 @@ use crate::{
         pool::UnsafeCommandPoolAlloc,
         synced::{BufferFinalState, BufferUse, ImageFinalState, ImageUse},
         sys::{CommandBufferBeginInfo, UnsafeCommandBufferBuilder},
-        CommandBufferExecError, CommandBufferLevel,
+        timing::CommandTiming,
+        CommandBufferExecError, CommandBufferLevel, UsageInference,
     },
     descriptor_set::{DescriptorSetResources, DescriptorSetWithOffsets},
     device::{Device, DeviceOwned},
-    image::{sys::UnsafeImage, ImageAccess, ImageLayout, ImageSubresourceRange},
+    image::{sys::UnsafeImage, ImageAccess, ImageLayout, ImageSubresourceRange, SampleCount},
+    memory::transient::{MemoryAlias, TransientMemory},
     pipeline::{
         graphics::{
-            color_blend::LogicOp,
+            color_blend::{AttachmentBlend, ColorComponents, LogicOp},
             depth_stencil::{CompareOp, StencilOps},
             input_assembly::{IndexType, PrimitiveTopology},
-            rasterization::{CullMode, DepthBias, FrontFace, LineStipple},
+            rasterization::{CullMode, DepthBias, FrontFace, LineStipple, PolygonMode},
+            vertex_input::VertexInputState,
             viewport::{Scissor, Viewport},
         },
         ComputePipeline, DynamicState, GraphicsPipeline, PipelineBindPoint, PipelineLayout,
     },
     range_set::RangeSet,
+    shader::{DescriptorRequirements, ShaderObject, ShaderStage},
     sync::{
-        AccessFlags, BufferMemoryBarrier, DependencyInfo, ImageMemoryBarrier, PipelineMemoryAccess,
-        PipelineStages,
+        AccessFlags, BufferMemoryBarrier, DependencyInfo, ImageMemoryBarrier, MemoryBarrier,
+        PipelineMemoryAccess, PipelineStages,
     },
     DeviceSize, OomError, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{
     borrow::Cow,
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     error, fmt,
     ops::{Range, RangeInclusive},
     sync::Arc,
@@ -83,6 +89,13 @@ pub struct SyncCommandBufferBuilder {
     // TODO: present only in cfg(debug_assertions)?
     barriers: Vec<usize>,
 
+    // The resource accesses that caused each of the inserted pipeline barriers.
+    barrier_reasons: Vec<BarrierReason>,
+
+    // The resource accesses that caused `pending_barrier`. Their `barrier_index` is filled in
+    // when the barrier is flushed.
+    pending_barrier_reasons: Vec<BarrierReason>,
+
     // Only the commands before `first_unflushed` have already been sent to the inner
     // `UnsafeCommandBufferBuilder`.
     first_unflushed: usize,
@@ -95,6 +108,18 @@ pub struct SyncCommandBufferBuilder {
     buffers2: HashMap<Arc<UnsafeBuffer>, RangeMap<DeviceSize, BufferState>>,
     images2: HashMap<Arc<UnsafeImage>, RangeMap<DeviceSize, ImageState>>,
 
+    // Stores which resource last used each range of the memory of transient resources.
+    transient_memory: HashMap<
+        ash::vk::DeviceMemory,
+        (
+            Arc<TransientMemory>,
+            RangeMap<DeviceSize, Option<MemoryAliasUse>>,
+        ),
+    >,
+
+    // The transient images that are used by the command buffer.
+    transient_images: HashSet<ash::vk::Image>,
+
     // Resources and their accesses. Used for executing secondary command buffers in a primary.
     buffers: Vec<(
         Arc<dyn BufferAccess>,
@@ -109,6 +134,12 @@ pub struct SyncCommandBufferBuilder {
         ImageLayout,
     )>,
 
+    // Usages required by the resources, if usage inference is enabled.
+    pub(in crate::command_buffer) usage_inference: Option<UsageInference>,
+
+    // Timestamps that are written between ranges of commands, if command timing is enabled.
+    pub(in crate::command_buffer) command_timing: Option<CommandTiming>,
+
     // Current binding/setting state.
     pub(in crate::command_buffer) current_state: CurrentState,
 }
@@ -164,16 +195,31 @@ impl SyncCommandBufferBuilder {
             commands: Vec::new(),
             pending_barrier: DependencyInfo::default(),
             barriers: Vec::new(),
+            barrier_reasons: Vec::new(),
+            pending_barrier_reasons: Vec::new(),
             first_unflushed: 0,
             latest_render_pass_enter,
             buffers2: HashMap::default(),
             images2: HashMap::default(),
+            transient_memory: HashMap::default(),
+            transient_images: HashSet::default(),
             buffers: Vec::new(),
             images: Vec::new(),
+            usage_inference: None,
+            command_timing: None,
             current_state: Default::default(),
         }
     }
 
+    /// Enables usage inference for the commands that are recorded from now on.
+    ///
+    /// See `UsageInference` for more information. Does nothing if usage inference is already
+    /// enabled.
+    #[inline]
+    pub fn enable_usage_inference(&mut self) {
+        self.usage_inference.get_or_insert_with(UsageInference::new);
+    }
+
     /// Returns the binding/setting state.
     #[inline]
     pub fn state(&self) -> CommandBufferState {
@@ -401,6 +447,10 @@ impl SyncCommandBufferBuilder {
     ) {
         self.buffers.push((buffer.clone(), range.clone(), memory));
 
+        if let Some(usage_inference) = &mut self.usage_inference {
+            usage_inference.add_buffer_access(buffer.as_ref(), &memory);
+        }
+
         // Barriers work differently in render passes, so if we're in one, we can only insert a
         // barrier before the start of the render pass.
         let last_allowed_barrier_index = self
@@ -411,6 +461,20 @@ impl SyncCommandBufferBuilder {
         range.start += inner.offset;
         range.end += inner.offset;
 
+        if let Some(alias) = buffer.memory_alias() {
+            let memory_range = alias.range.start + range.start..alias.range.start + range.end;
+            self.add_memory_alias_use(
+                &resource_name,
+                MemoryAlias {
+                    range: memory_range,
+                    ..alias
+                },
+                MemoryAliasOwner::Buffer(inner.buffer.internal_object()),
+                memory,
+                last_allowed_barrier_index,
+            );
+        }
+
         let range_map = self
             .buffers2
             .entry(inner.buffer.clone())
@@ -463,18 +527,46 @@ impl SyncCommandBufferBuilder {
                             self.inner.pipeline_barrier(&self.pending_barrier);
                             self.pending_barrier.clear();
                             self.barriers.push(self.first_unflushed); // Track inserted barriers
+                            let barrier_index = self.first_unflushed;
+                            self.barrier_reasons.extend(
+                                self.pending_barrier_reasons.drain(..).map(|reason| {
+                                    BarrierReason {
+                                        barrier_index,
+                                        ..reason
+                                    }
+                                }),
+                            );
 
-                            for command in
-                                &mut self.commands[self.first_unflushed..last_allowed_barrier_index]
-                            {
-                                command.send(&mut self.inner);
-                            }
+                            send_commands(
+                                &mut self.inner,
+                                self.command_timing.as_mut(),
+                                &self.commands,
+                                self.first_unflushed..last_allowed_barrier_index,
+                            );
 
                             self.first_unflushed = last_allowed_barrier_index;
                         }
                     }
 
                     // Modify the pipeline barrier to handle the collision.
+                    let previous_use = state.resource_uses.last().unwrap();
+                    self.pending_barrier_reasons.push(BarrierReason {
+                        barrier_index: 0,
+                        resource: BarrierResource::Buffer {
+                            buffer: inner.buffer.clone(),
+                            range: range.clone(),
+                        },
+                        previous_use: Some(BarrierResourceUse {
+                            command_name: self.commands[previous_use.command_index].name(),
+                            command_offset: previous_use.command_index,
+                            command_param: previous_use.name.clone(),
+                        }),
+                        next_use: Some(BarrierResourceUse {
+                            command_name: self.commands[self.commands.len() - 1].name(),
+                            command_offset: self.commands.len() - 1,
+                            command_param: resource_name.clone(),
+                        }),
+                    });
                     self.pending_barrier
                         .buffer_memory_barriers
                         .push(BufferMemoryBarrier {
@@ -520,6 +612,10 @@ impl SyncCommandBufferBuilder {
             end_layout,
         ));
 
+        if let Some(usage_inference) = &mut self.usage_inference {
+            usage_inference.add_image_access(image.as_ref(), &memory);
+        }
+
         // Barriers work differently in render passes, so if we're in one, we can only insert a
         // barrier before the start of the render pass.
         let last_allowed_barrier_index = self
@@ -532,14 +628,36 @@ impl SyncCommandBufferBuilder {
         subresource_range.mip_levels.start += inner.first_mipmap_level;
         subresource_range.mip_levels.end += inner.first_mipmap_level;
 
+        // The accesses of an earlier resource that used the memory of a transient image. These
+        // must finish before the image is transitioned out of the undefined layout.
+        let previous_alias_memory = match image.memory_alias() {
+            Some(alias) => {
+                self.transient_images.insert(inner.image.internal_object());
+                self.add_memory_alias_use(
+                    &resource_name,
+                    alias,
+                    MemoryAliasOwner::Image(inner.image.internal_object()),
+                    memory,
+                    last_allowed_barrier_index,
+                )
+            }
+            None => None,
+        };
+        let is_transient = self
+            .transient_images
+            .contains(&inner.image.internal_object());
+
         let range_map = self.images2.entry(inner.image.clone()).or_insert_with(|| {
             [(
                 0..inner.image.range_size(),
                 match self.level {
                     CommandBufferLevel::Primary => {
                         // In a primary command buffer, the initial layout is determined
-                        // by the image.
-                        let initial_layout = if !image.is_layout_initialized() {
+                        // by the image. The contents of transient images are discarded, since
+                        // their memory may have been used by other resources in the meantime.
+                        let initial_layout = if is_transient {
+                            ImageLayout::Undefined
+                        } else if !image.is_layout_initialized() {
                             unsafe {
                                 image.layout_initialized();
                             }
@@ -631,13 +749,44 @@ impl SyncCommandBufferBuilder {
                                         //   to be merged with an existing barrier. While it may still be
                                         //   suboptimal in some cases, in the general situation it will be ok.
                                         //
+                                        // The exception is a transient image whose memory was used by another
+                                        // resource before, where the transition must wait for that resource.
+                                        self.pending_barrier_reasons.push(BarrierReason {
+                                            barrier_index: 0,
+                                            resource: BarrierResource::Image {
+                                                image: inner.image.clone(),
+                                                subresource_range: inner
+                                                    .image
+                                                    .range_to_subresources(range.clone()),
+                                                old_layout: state.initial_layout,
+                                                new_layout: start_layout,
+                                            },
+                                            previous_use: None,
+                                            next_use: Some(BarrierResourceUse {
+                                                command_name: self.commands
+                                                    [self.commands.len() - 1]
+                                                    .name(),
+                                                command_offset: self.commands.len() - 1,
+                                                command_param: resource_name.clone(),
+                                            }),
+                                        });
+                                        let (source_stages, source_access) =
+                                            match previous_alias_memory {
+                                                Some(previous) => {
+                                                    (previous.stages, previous.access)
+                                                }
+                                                None => (
+                                                    PipelineStages {
+                                                        bottom_of_pipe: true,
+                                                        ..PipelineStages::none()
+                                                    },
+                                                    AccessFlags::none(),
+                                                ),
+                                            };
                                         self.pending_barrier.image_memory_barriers.push(
                                             ImageMemoryBarrier {
-                                                source_stages: PipelineStages {
-                                                    bottom_of_pipe: true,
-                                                    ..PipelineStages::none()
-                                                },
-                                                source_access: AccessFlags::none(),
+                                                source_stages,
+                                                source_access,
                                                 destination_stages: memory.stages,
                                                 destination_access: memory.access,
                                                 old_layout: state.initial_layout,
@@ -689,17 +838,47 @@ impl SyncCommandBufferBuilder {
                                 self.inner.pipeline_barrier(&self.pending_barrier);
                                 self.pending_barrier.clear();
                                 self.barriers.push(self.first_unflushed); // Track inserted barriers
-
-                                for command in &mut self.commands
-                                    [self.first_unflushed..last_allowed_barrier_index]
-                                {
-                                    command.send(&mut self.inner);
-                                }
+                                let barrier_index = self.first_unflushed;
+                                self.barrier_reasons.extend(
+                                    self.pending_barrier_reasons.drain(..).map(|reason| {
+                                        BarrierReason {
+                                            barrier_index,
+                                            ..reason
+                                        }
+                                    }),
+                                );
+
+                                send_commands(
+                                    &mut self.inner,
+                                    self.command_timing.as_mut(),
+                                    &self.commands,
+                                    self.first_unflushed..last_allowed_barrier_index,
+                                );
                                 self.first_unflushed = last_allowed_barrier_index;
                             }
                         }
 
                         // Modify the pipeline barrier to handle the collision.
+                        let previous_use = state.resource_uses.last().unwrap();
+                        self.pending_barrier_reasons.push(BarrierReason {
+                            barrier_index: 0,
+                            resource: BarrierResource::Image {
+                                image: inner.image.clone(),
+                                subresource_range: inner.image.range_to_subresources(range.clone()),
+                                old_layout: state.current_layout,
+                                new_layout: start_layout,
+                            },
+                            previous_use: Some(BarrierResourceUse {
+                                command_name: self.commands[previous_use.command_index].name(),
+                                command_offset: previous_use.command_index,
+                                command_param: previous_use.name.clone(),
+                            }),
+                            next_use: Some(BarrierResourceUse {
+                                command_name: self.commands[self.commands.len() - 1].name(),
+                                command_offset: self.commands.len() - 1,
+                                command_param: resource_name.clone(),
+                            }),
+                        });
                         self.pending_barrier
                             .image_memory_barriers
                             .push(ImageMemoryBarrier {
@@ -737,6 +916,137 @@ impl SyncCommandBufferBuilder {
         }
     }
 
+    // Records that `owner` uses the memory of a transient resource. If the memory was last used
+    // by a different resource, a memory barrier is added so that the accesses of that resource are
+    // finished before the new ones start.
+    //
+    // Returns the accesses of the resources that previously used the memory, if there were any.
+    fn add_memory_alias_use(
+        &mut self,
+        resource_name: &Cow<'static, str>,
+        alias: MemoryAlias,
+        owner: MemoryAliasOwner,
+        memory: PipelineMemoryAccess,
+        last_allowed_barrier_index: usize,
+    ) -> Option<PipelineMemoryAccess> {
+        let command_index = self.commands.len() - 1;
+        let MemoryAlias {
+            memory: transient_memory,
+            range,
+        } = alias;
+
+        let (_, range_map) = self
+            .transient_memory
+            .entry(transient_memory.memory().internal_object())
+            .or_insert_with(|| {
+                let range_map = [(0..transient_memory.size(), None)].into_iter().collect();
+                (transient_memory.clone(), range_map)
+            });
+        range_map.split_at(&range.start);
+        range_map.split_at(&range.end);
+
+        let previous_uses: Vec<_> = range_map
+            .range(&range)
+            .filter_map(|(range, alias_use)| match alias_use {
+                Some(alias_use) if alias_use.owner != owner => {
+                    Some((range.clone(), alias_use.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        for (_range, alias_use) in range_map.range_mut(&range) {
+            match alias_use {
+                Some(alias_use) if alias_use.owner == owner => {
+                    alias_use.command_index = command_index;
+                    alias_use.name = resource_name.clone();
+                    alias_use.memory.stages |= memory.stages;
+                    alias_use.memory.access |= memory.access;
+                }
+                _ => {
+                    *alias_use = Some(MemoryAliasUse {
+                        owner,
+                        command_index,
+                        name: resource_name.clone(),
+                        memory,
+                    });
+                }
+            }
+        }
+
+        if previous_uses.is_empty() {
+            return None;
+        }
+
+        // The barrier is submitted before the unflushed commands, so if one of them used the
+        // memory, the pending barrier must be flushed first.
+        if previous_uses
+            .iter()
+            .any(|(_, alias_use)| alias_use.command_index >= self.first_unflushed)
+        {
+            unsafe {
+                // Flush the pending barrier.
+                self.inner.pipeline_barrier(&self.pending_barrier);
+                self.pending_barrier.clear();
+                self.barriers.push(self.first_unflushed); // Track inserted barriers
+                let barrier_index = self.first_unflushed;
+                self.barrier_reasons
+                    .extend(
+                        self.pending_barrier_reasons
+                            .drain(..)
+                            .map(|reason| BarrierReason {
+                                barrier_index,
+                                ..reason
+                            }),
+                    );
+
+                send_commands(
+                    &mut self.inner,
+                    self.command_timing.as_mut(),
+                    &self.commands,
+                    self.first_unflushed..last_allowed_barrier_index,
+                );
+                self.first_unflushed = last_allowed_barrier_index;
+            }
+        }
+
+        let mut previous_memory = PipelineMemoryAccess::default();
+
+        for (range, previous_use) in previous_uses {
+            previous_memory.stages |= previous_use.memory.stages;
+            previous_memory.access |= previous_use.memory.access;
+
+            self.pending_barrier_reasons.push(BarrierReason {
+                barrier_index: 0,
+                resource: BarrierResource::Memory {
+                    memory: transient_memory.clone(),
+                    range,
+                },
+                previous_use: Some(BarrierResourceUse {
+                    command_name: self.commands[previous_use.command_index].name(),
+                    command_offset: previous_use.command_index,
+                    command_param: previous_use.name,
+                }),
+                next_use: Some(BarrierResourceUse {
+                    command_name: self.commands[command_index].name(),
+                    command_offset: command_index,
+                    command_param: resource_name.clone(),
+                }),
+            });
+        }
+
+        // Memory barriers can't be restricted to a range of memory, so a global barrier is used.
+        self.pending_barrier.memory_barriers.push(MemoryBarrier {
+            source_stages: previous_memory.stages,
+            source_access: previous_memory.access,
+            destination_stages: memory.stages,
+            destination_access: memory.access,
+            ..Default::default()
+        });
+
+        Some(previous_memory)
+    }
+
     /// Builds the command buffer and turns it into a `SyncCommandBuffer`.
     #[inline]
     pub fn build(mut self) -> Result<SyncCommandBuffer, OomError> {
@@ -748,9 +1058,26 @@ impl SyncCommandBufferBuilder {
             self.pending_barrier.clear();
             let start = self.first_unflushed;
             self.barriers.push(start); // Track inserted barriers
+            let barrier_index = start;
+            self.barrier_reasons
+                .extend(
+                    self.pending_barrier_reasons
+                        .drain(..)
+                        .map(|reason| BarrierReason {
+                            barrier_index,
+                            ..reason
+                        }),
+                );
+
+            send_commands(
+                &mut self.inner,
+                self.command_timing.as_mut(),
+                &self.commands,
+                start..self.commands.len(),
+            );
 
-            for command in &mut self.commands[start..] {
-                command.send(&mut self.inner);
+            if let Some(command_timing) = &mut self.command_timing {
+                command_timing.finish(&mut self.inner);
             }
         }
 
@@ -758,10 +1085,32 @@ impl SyncCommandBufferBuilder {
         if self.level == CommandBufferLevel::Primary {
             unsafe {
                 for (image, range_map) in self.images2.iter_mut() {
+                    // Transitioning a transient image at the end would write to memory that may
+                    // be in use by other resources by then, so it's left in its current layout.
+                    if self.transient_images.contains(&image.internal_object()) {
+                        continue;
+                    }
+
                     for (range, state) in range_map
                         .iter_mut()
                         .filter(|(_range, state)| state.final_layout != state.current_layout)
                     {
+                        let previous_use = state.resource_uses.last();
+                        self.barrier_reasons.push(BarrierReason {
+                            barrier_index: self.commands.len(),
+                            resource: BarrierResource::Image {
+                                image: image.clone(),
+                                subresource_range: image.range_to_subresources(range.clone()),
+                                old_layout: state.current_layout,
+                                new_layout: state.final_layout,
+                            },
+                            previous_use: previous_use.map(|previous_use| BarrierResourceUse {
+                                command_name: self.commands[previous_use.command_index].name(),
+                                command_offset: previous_use.command_index,
+                                command_param: previous_use.name.clone(),
+                            }),
+                            next_use: None,
+                        });
                         self.pending_barrier
                             .image_memory_barriers
                             .push(ImageMemoryBarrier {
@@ -810,19 +1159,22 @@ impl SyncCommandBufferBuilder {
             })
             .collect();
 
+        let transient_images = self.transient_images;
         let images2: HashMap<_, _> = self
             .images2
             .into_iter()
             .map(|(resource, range_map)| {
+                let is_transient = transient_images.contains(&resource.internal_object());
                 let range_map = range_map
                     .into_iter()
                     .filter(|(_range, state)| {
                         !state.resource_uses.is_empty()
                             || (self.level == CommandBufferLevel::Primary
+                                && !is_transient
                                 && state.current_layout != state.final_layout)
                     })
                     .map(|(range, mut state)| {
-                        if self.level == CommandBufferLevel::Primary {
+                        if self.level == CommandBufferLevel::Primary && !is_transient {
                             state.current_layout = state.final_layout;
                         }
 
@@ -851,10 +1203,42 @@ impl SyncCommandBufferBuilder {
             images2,
             commands: self.commands,
             barriers: self.barriers,
+            barrier_reasons: self.barrier_reasons,
+            usage_inference: self.usage_inference,
+            command_timing: self.command_timing,
         })
     }
 }
 
+// Sends the commands in `range` to `inner`. If the `nv_device_diagnostic_checkpoints` extension
+// is enabled, each command is preceded by a checkpoint that identifies it. See
+// `Queue::diagnostic_checkpoints`. If command timing is enabled, timestamps are written between
+// the ranges of commands.
+unsafe fn send_commands(
+    inner: &mut UnsafeCommandBufferBuilder,
+    mut command_timing: Option<&mut CommandTiming>,
+    commands: &[Box<dyn Command>],
+    range: Range<usize>,
+) {
+    let checkpoints = inner
+        .device()
+        .enabled_extensions()
+        .nv_device_diagnostic_checkpoints;
+
+    for (index, command) in commands[range.clone()].iter().enumerate() {
+        if let Some(command_timing) = command_timing.as_deref_mut() {
+            command_timing.before_command(inner, range.start + index, command.name());
+        }
+
+        if checkpoints {
+            let marker = inner.device().checkpoint_marker(command.name());
+            inner.set_checkpoint(marker);
+        }
+
+        command.send(inner);
+    }
+}
+
 unsafe impl DeviceOwned for SyncCommandBufferBuilder {
     #[inline]
     fn device(&self) -> &Arc<Device> {
@@ -862,6 +1246,15 @@ unsafe impl DeviceOwned for SyncCommandBufferBuilder {
     }
 }
 
+unsafe impl VulkanObject for SyncCommandBufferBuilder {
+    type Object = ash::vk::CommandBuffer;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::CommandBuffer {
+        self.inner.internal_object()
+    }
+}
+
 impl fmt::Debug for SyncCommandBufferBuilder {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -941,6 +1334,25 @@ struct ImageState {
     final_layout: ImageLayout,
 }
 
+// The resource that last used a range of the memory of transient resources.
+#[derive(Clone, PartialEq, Eq)]
+struct MemoryAliasUse {
+    owner: MemoryAliasOwner,
+
+    // The last command that used the range through `owner`.
+    command_index: usize,
+    name: Cow<'static, str>,
+
+    // Memory accesses of `owner` since it started using the range.
+    memory: PipelineMemoryAccess,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MemoryAliasOwner {
+    Buffer(ash::vk::Buffer),
+    Image(ash::vk::Image),
+}
+
 /// Holds the current binding and setting state.
 #[derive(Default)]
 pub(in crate::command_buffer) struct CurrentState {
@@ -948,18 +1360,25 @@ pub(in crate::command_buffer) struct CurrentState {
     pub(in crate::command_buffer) index_buffer: Option<(Arc<dyn BufferAccess>, IndexType)>,
     pub(in crate::command_buffer) pipeline_compute: Option<Arc<ComputePipeline>>,
     pub(in crate::command_buffer) pipeline_graphics: Option<Arc<GraphicsPipeline>>,
+    pub(in crate::command_buffer) shaders: HashMap<ShaderStage, Arc<ShaderObject>>,
     pub(in crate::command_buffer) vertex_buffers: HashMap<u32, Arc<dyn BufferAccess>>,
 
     pub(in crate::command_buffer) push_constants: RangeSet<u32>,
     pub(in crate::command_buffer) push_constants_pipeline_layout: Option<Arc<PipelineLayout>>,
 
+    pub(in crate::command_buffer) alpha_to_coverage_enable: Option<bool>,
+    pub(in crate::command_buffer) alpha_to_one_enable: Option<bool>,
     pub(in crate::command_buffer) blend_constants: Option<[f32; 4]>,
+    pub(in crate::command_buffer) color_blend_enable: HashMap<u32, bool>,
+    pub(in crate::command_buffer) color_blend_equation: HashMap<u32, AttachmentBlend>,
     pub(in crate::command_buffer) color_write_enable: Option<SmallVec<[bool; 4]>>,
+    pub(in crate::command_buffer) color_write_mask: HashMap<u32, ColorComponents>,
     pub(in crate::command_buffer) cull_mode: Option<CullMode>,
     pub(in crate::command_buffer) depth_bias: Option<DepthBias>,
     pub(in crate::command_buffer) depth_bias_enable: Option<bool>,
     pub(in crate::command_buffer) depth_bounds: Option<RangeInclusive<f32>>,
     pub(in crate::command_buffer) depth_bounds_test_enable: Option<bool>,
+    pub(in crate::command_buffer) depth_clamp_enable: Option<bool>,
     pub(in crate::command_buffer) depth_compare_op: Option<CompareOp>,
     pub(in crate::command_buffer) depth_test_enable: Option<bool>,
     pub(in crate::command_buffer) depth_write_enable: Option<bool>,
@@ -968,10 +1387,14 @@ pub(in crate::command_buffer) struct CurrentState {
     pub(in crate::command_buffer) line_stipple: Option<LineStipple>,
     pub(in crate::command_buffer) line_width: Option<f32>,
     pub(in crate::command_buffer) logic_op: Option<LogicOp>,
+    pub(in crate::command_buffer) logic_op_enable: Option<bool>,
     pub(in crate::command_buffer) patch_control_points: Option<u32>,
+    pub(in crate::command_buffer) polygon_mode: Option<PolygonMode>,
     pub(in crate::command_buffer) primitive_restart_enable: Option<bool>,
     pub(in crate::command_buffer) primitive_topology: Option<PrimitiveTopology>,
+    pub(in crate::command_buffer) rasterization_samples: Option<SampleCount>,
     pub(in crate::command_buffer) rasterizer_discard_enable: Option<bool>,
+    pub(in crate::command_buffer) sample_mask: Option<(SampleCount, [u32; 2])>,
     pub(in crate::command_buffer) scissor: HashMap<u32, Scissor>,
     pub(in crate::command_buffer) scissor_with_count: Option<SmallVec<[Scissor; 2]>>,
     pub(in crate::command_buffer) stencil_compare_mask: StencilStateDynamic,
@@ -979,6 +1402,7 @@ pub(in crate::command_buffer) struct CurrentState {
     pub(in crate::command_buffer) stencil_reference: StencilStateDynamic,
     pub(in crate::command_buffer) stencil_test_enable: Option<bool>,
     pub(in crate::command_buffer) stencil_write_mask: StencilStateDynamic,
+    pub(in crate::command_buffer) vertex_input: Option<VertexInputState>,
     pub(in crate::command_buffer) viewport: HashMap<u32, Viewport>,
     pub(in crate::command_buffer) viewport_with_count: Option<SmallVec<[Viewport; 2]>>,
 }
@@ -990,13 +1414,19 @@ impl CurrentState {
     ) {
         for state in states {
             match state {
+                DynamicState::AlphaToCoverageEnable => self.alpha_to_coverage_enable = None,
+                DynamicState::AlphaToOneEnable => self.alpha_to_one_enable = None,
                 DynamicState::BlendConstants => self.blend_constants = None,
+                DynamicState::ColorBlendEnable => self.color_blend_enable.clear(),
+                DynamicState::ColorBlendEquation => self.color_blend_equation.clear(),
                 DynamicState::ColorWriteEnable => self.color_write_enable = None,
+                DynamicState::ColorWriteMask => self.color_write_mask.clear(),
                 DynamicState::CullMode => self.cull_mode = None,
                 DynamicState::DepthBias => self.depth_bias = None,
                 DynamicState::DepthBiasEnable => self.depth_bias_enable = None,
                 DynamicState::DepthBounds => self.depth_bounds = None,
                 DynamicState::DepthBoundsTestEnable => self.depth_bounds_test_enable = None,
+                DynamicState::DepthClampEnable => self.depth_clamp_enable = None,
                 DynamicState::DepthCompareOp => self.depth_compare_op = None,
                 DynamicState::DepthTestEnable => self.depth_test_enable = None,
                 DynamicState::DepthWriteEnable => self.depth_write_enable = None,
@@ -1007,12 +1437,16 @@ impl CurrentState {
                 DynamicState::LineStipple => self.line_stipple = None,
                 DynamicState::LineWidth => self.line_width = None,
                 DynamicState::LogicOp => self.logic_op = None,
+                DynamicState::LogicOpEnable => self.logic_op_enable = None,
                 DynamicState::PatchControlPoints => self.patch_control_points = None,
+                DynamicState::PolygonMode => self.polygon_mode = None,
                 DynamicState::PrimitiveRestartEnable => self.primitive_restart_enable = None,
                 DynamicState::PrimitiveTopology => self.primitive_topology = None,
+                DynamicState::RasterizationSamples => self.rasterization_samples = None,
                 DynamicState::RasterizerDiscardEnable => self.rasterizer_discard_enable = None,
                 DynamicState::RayTracingPipelineStackSize => (), // TODO:
                 DynamicState::SampleLocations => (),             // TODO:
+                DynamicState::SampleMask => self.sample_mask = None,
                 DynamicState::Scissor => self.scissor.clear(),
                 DynamicState::ScissorWithCount => self.scissor_with_count = None,
                 DynamicState::StencilCompareMask => self.stencil_compare_mask = Default::default(),
@@ -1020,7 +1454,7 @@ impl CurrentState {
                 DynamicState::StencilReference => self.stencil_reference = Default::default(),
                 DynamicState::StencilTestEnable => self.stencil_test_enable = None,
                 DynamicState::StencilWriteMask => self.stencil_write_mask = Default::default(),
-                DynamicState::VertexInput => (), // TODO:
+                DynamicState::VertexInput => self.vertex_input = None,
                 DynamicState::VertexInputBindingStride => (), // TODO:
                 DynamicState::Viewport => self.viewport.clear(),
                 DynamicState::ViewportCoarseSampleOrder => (), // TODO:
@@ -1031,6 +1465,39 @@ impl CurrentState {
         }
     }
 
+    /// Returns the combined descriptor requirements of the shader objects that are bound to
+    /// `pipeline_bind_point`.
+    pub(in crate::command_buffer) fn shader_descriptor_requirements(
+        &self,
+        pipeline_bind_point: PipelineBindPoint,
+    ) -> HashMap<(u32, u32), DescriptorRequirements> {
+        let mut descriptor_requirements: HashMap<(u32, u32), DescriptorRequirements> =
+            HashMap::default();
+
+        for (_, shader) in self.shaders.iter().filter(|(&stage, _)| {
+            (stage == ShaderStage::Compute) == (pipeline_bind_point == PipelineBindPoint::Compute)
+        }) {
+            for (loc, reqs) in shader.descriptor_requirements() {
+                match descriptor_requirements.entry(loc) {
+                    Entry::Occupied(entry) => {
+                        let previous = entry.into_mut();
+
+                        // Each shader was checked against its own layout, so a conflict here can
+                        // only mean that the shaders disagree; keep the first requirements.
+                        if let Ok(intersection) = previous.intersection(reqs) {
+                            *previous = intersection;
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(reqs.clone());
+                    }
+                }
+            }
+        }
+
+        descriptor_requirements
+    }
+
     pub(in crate::command_buffer) fn invalidate_descriptor_sets(
         &mut self,
         pipeline_bind_point: PipelineBindPoint,
@@ -1167,6 +1634,13 @@ impl<'a> CommandBufferState<'a> {
         self.current_state.pipeline_graphics.as_ref()
     }
 
+    /// Returns the shader object currently bound to a given shader stage, or `None` if nothing
+    /// has been bound yet.
+    #[inline]
+    pub fn shader(&self, stage: ShaderStage) -> Option<&'a Arc<ShaderObject>> {
+        self.current_state.shaders.get(&stage)
+    }
+
     /// Returns the vertex buffer currently bound to a given binding slot number, or `None` if
     /// nothing has been bound yet.
     #[inline]
@@ -1188,12 +1662,38 @@ impl<'a> CommandBufferState<'a> {
         self.current_state.push_constants_pipeline_layout.as_ref()
     }
 
+    /// Returns whether alpha-to-coverage is enabled, or `None` if nothing has been set yet.
+    #[inline]
+    pub fn alpha_to_coverage_enable(&self) -> Option<bool> {
+        self.current_state.alpha_to_coverage_enable
+    }
+
+    /// Returns whether alpha-to-one is enabled, or `None` if nothing has been set yet.
+    #[inline]
+    pub fn alpha_to_one_enable(&self) -> Option<bool> {
+        self.current_state.alpha_to_one_enable
+    }
+
     /// Returns the current blend constants, or `None` if nothing has been set yet.
     #[inline]
     pub fn blend_constants(&self) -> Option<[f32; 4]> {
         self.current_state.blend_constants
     }
 
+    /// Returns whether blending is enabled for a color attachment, or `None` if nothing has been
+    /// set yet.
+    #[inline]
+    pub fn color_blend_enable(&self, num: u32) -> Option<bool> {
+        self.current_state.color_blend_enable.get(&num).copied()
+    }
+
+    /// Returns the current blend equation of a color attachment, or `None` if nothing has been
+    /// set yet.
+    #[inline]
+    pub fn color_blend_equation(&self, num: u32) -> Option<&'a AttachmentBlend> {
+        self.current_state.color_blend_equation.get(&num)
+    }
+
     /// Returns the current color write enable settings, or `None` if nothing has been set yet.
     #[inline]
     pub fn color_write_enable(&self) -> Option<&'a [bool]> {
@@ -1203,6 +1703,13 @@ impl<'a> CommandBufferState<'a> {
             .map(|x| x.as_slice())
     }
 
+    /// Returns the current color write mask of a color attachment, or `None` if nothing has been
+    /// set yet.
+    #[inline]
+    pub fn color_write_mask(&self, num: u32) -> Option<ColorComponents> {
+        self.current_state.color_write_mask.get(&num).copied()
+    }
+
     /// Returns the current cull mode, or `None` if nothing has been set yet.
     #[inline]
     pub fn cull_mode(&self) -> Option<CullMode> {
@@ -1230,7 +1737,13 @@ impl<'a> CommandBufferState<'a> {
     /// Returns whether depth bound testing is enabled, or `None` if nothing has been set yet.
     #[inline]
     pub fn depth_bounds_test_enable(&self) -> Option<bool> {
-        self.current_state.depth_bias_enable
+        self.current_state.depth_bounds_test_enable
+    }
+
+    /// Returns whether depth clamping is enabled, or `None` if nothing has been set yet.
+    #[inline]
+    pub fn depth_clamp_enable(&self) -> Option<bool> {
+        self.current_state.depth_clamp_enable
     }
 
     /// Returns the current depth compare op, or `None` if nothing has been set yet.
@@ -1281,12 +1794,24 @@ impl<'a> CommandBufferState<'a> {
         self.current_state.logic_op
     }
 
+    /// Returns whether logic operations are enabled, or `None` if nothing has been set yet.
+    #[inline]
+    pub fn logic_op_enable(&self) -> Option<bool> {
+        self.current_state.logic_op_enable
+    }
+
     /// Returns the current number of patch control points, or `None` if nothing has been set yet.
     #[inline]
     pub fn patch_control_points(&self) -> Option<u32> {
         self.current_state.patch_control_points
     }
 
+    /// Returns the current polygon mode, or `None` if nothing has been set yet.
+    #[inline]
+    pub fn polygon_mode(&self) -> Option<PolygonMode> {
+        self.current_state.polygon_mode
+    }
+
     /// Returns whether primitive restart is enabled, or `None` if nothing has been set yet.
     #[inline]
     pub fn primitive_restart_enable(&self) -> Option<bool> {
@@ -1299,12 +1824,24 @@ impl<'a> CommandBufferState<'a> {
         self.current_state.primitive_topology
     }
 
+    /// Returns the current number of rasterization samples, or `None` if nothing has been set yet.
+    #[inline]
+    pub fn rasterization_samples(&self) -> Option<SampleCount> {
+        self.current_state.rasterization_samples
+    }
+
     /// Returns whether rasterizer discard is enabled, or `None` if nothing has been set yet.
     #[inline]
     pub fn rasterizer_discard_enable(&self) -> Option<bool> {
         self.current_state.rasterizer_discard_enable
     }
 
+    /// Returns the current sample count and sample mask, or `None` if nothing has been set yet.
+    #[inline]
+    pub fn sample_mask(&self) -> Option<(SampleCount, [u32; 2])> {
+        self.current_state.sample_mask
+    }
+
     /// Returns the current scissor for a given viewport slot, or `None` if nothing has been set yet.
     #[inline]
     pub fn scissor(&self, num: u32) -> Option<&'a Scissor> {
@@ -1350,6 +1887,12 @@ impl<'a> CommandBufferState<'a> {
         self.current_state.stencil_write_mask
     }
 
+    /// Returns the current vertex input state, or `None` if nothing has been set yet.
+    #[inline]
+    pub fn vertex_input(&self) -> Option<&'a VertexInputState> {
+        self.current_state.vertex_input.as_ref()
+    }
+
     /// Returns the current viewport for a given viewport slot, or `None` if nothing has been set yet.
     #[inline]
     pub fn viewport(&self, num: u32) -> Option<&'a Viewport> {
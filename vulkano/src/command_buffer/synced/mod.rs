@@ -72,17 +72,19 @@ pub use self::builder::{
 };
 use super::{
     sys::{UnsafeCommandBuffer, UnsafeCommandBufferBuilder},
-    CommandBufferExecError,
+    timing::CommandTiming,
+    CommandBufferExecError, UsageInference,
 };
 use crate::range_map::RangeMap;
 use crate::{
     buffer::{sys::UnsafeBuffer, BufferAccess},
     device::{Device, DeviceOwned, Queue},
     image::{sys::UnsafeImage, ImageAccess, ImageLayout, ImageSubresourceRange},
+    memory::transient::TransientMemory,
     sync::{
         AccessCheckError, AccessError, AccessFlags, GpuFuture, PipelineMemoryAccess, PipelineStages,
     },
-    DeviceSize,
+    DeviceSize, VulkanObject,
 };
 use std::{borrow::Cow, collections::HashMap, ops::Range, sync::Arc};
 
@@ -102,6 +104,16 @@ pub struct SyncCommandBuffer {
     // TODO: present only in cfg(debug_assertions)?
     barriers: Vec<usize>,
 
+    // The resource accesses that caused each of the inserted pipeline barriers.
+    barrier_reasons: Vec<BarrierReason>,
+
+    // Usages required by the resources, if usage inference was enabled on the builder.
+    usage_inference: Option<UsageInference>,
+
+    // Timestamps that are written between ranges of commands, if command timing was enabled on
+    // the builder.
+    command_timing: Option<CommandTiming>,
+
     // State of all the resources used by this command buffer.
     buffers2: HashMap<Arc<UnsafeBuffer>, RangeMap<DeviceSize, BufferFinalState>>,
     images2: HashMap<Arc<UnsafeImage>, RangeMap<DeviceSize, ImageFinalState>>,
@@ -138,6 +150,23 @@ impl SyncCommandBuffer {
             .buffers2
             .iter()
             .map(|(buffer, range_map)| {
+                // Unbound regions of sparse buffers are only allowed with sparse residency.
+                if let Err(err) = buffer.check_sparse_bound() {
+                    // Ranges that were only transitioned have no uses, but every resource is used
+                    // by at least one command.
+                    let resource_use = range_map
+                        .iter()
+                        .find_map(|(_, state)| state.resource_uses.first())
+                        .unwrap();
+
+                    return Err(CommandBufferExecError::AccessError {
+                        error: err,
+                        command_name: self.commands[resource_use.command_index].name().into(),
+                        command_param: resource_use.name.clone(),
+                        command_offset: resource_use.command_index,
+                    });
+                }
+
                 let mut buffer_state = buffer.state();
 
                 for (range, state) in range_map.iter() {
@@ -187,6 +216,23 @@ impl SyncCommandBuffer {
             .images2
             .iter()
             .map(|(image, range_map)| {
+                // Unbound regions of sparse images are only allowed with sparse residency.
+                if let Err(err) = image.check_sparse_bound() {
+                    // Ranges that were only transitioned have no uses, but every resource is used
+                    // by at least one command.
+                    let resource_use = range_map
+                        .iter()
+                        .find_map(|(_, state)| state.resource_uses.first())
+                        .unwrap();
+
+                    return Err(CommandBufferExecError::AccessError {
+                        error: err,
+                        command_name: self.commands[resource_use.command_index].name().into(),
+                        command_param: resource_use.name.clone(),
+                        command_offset: resource_use.command_index,
+                    });
+                }
+
                 let mut image_state = image.state();
 
                 for (range, state) in range_map.iter() {
@@ -245,9 +291,9 @@ impl SyncCommandBuffer {
             for (buffer, mut buffer_state) in buffer_state_mutexes {
                 for (range, state) in self.buffers2[buffer].iter() {
                     if state.exclusive {
-                        buffer_state.gpu_write_lock(range.clone());
+                        buffer_state.gpu_write_lock(range.clone(), self.inner.internal_object());
                     } else {
-                        buffer_state.gpu_read_lock(range.clone());
+                        buffer_state.gpu_read_lock(range.clone(), self.inner.internal_object());
                     }
                 }
             }
@@ -282,9 +328,9 @@ impl SyncCommandBuffer {
 
             for (range, state) in range_map.iter() {
                 if state.exclusive {
-                    buffer_state.gpu_write_unlock(range.clone());
+                    buffer_state.gpu_write_unlock(range.clone(), self.inner.internal_object());
                 } else {
-                    buffer_state.gpu_read_unlock(range.clone());
+                    buffer_state.gpu_read_unlock(range.clone(), self.inner.internal_object());
                 }
             }
         }
@@ -380,6 +426,31 @@ impl SyncCommandBuffer {
             .map(Some)
     }
 
+    /// Returns the reasons why the builder inserted pipeline barriers into this command buffer.
+    ///
+    /// Each element describes one resource that was synchronized by a barrier, along with the
+    /// pair of commands whose accesses to that resource conflicted. A single barrier can have
+    /// multiple reasons. This can be used to understand where synchronization comes from, and to
+    /// reorder commands to reduce the number of barriers.
+    #[inline]
+    pub fn barrier_reasons(&self) -> &[BarrierReason] {
+        &self.barrier_reasons
+    }
+
+    /// Returns the usages that the resources of this command buffer require, or `None` if usage
+    /// inference wasn't enabled on the builder.
+    #[inline]
+    pub fn inferred_usage(&self) -> Option<&UsageInference> {
+        self.usage_inference.as_ref()
+    }
+
+    /// Returns the timestamps that are written between ranges of commands, or `None` if command
+    /// timing wasn't enabled on the builder.
+    #[inline]
+    pub fn command_timing(&self) -> Option<&CommandTiming> {
+        self.command_timing.as_ref()
+    }
+
     #[inline]
     pub fn num_buffers(&self) -> usize {
         self.buffers.len()
@@ -486,6 +557,63 @@ struct ImageUse {
     name: Cow<'static, str>,
 }
 
+/// Describes why `SyncCommandBufferBuilder` inserted a pipeline barrier.
+#[derive(Clone, Debug)]
+pub struct BarrierReason {
+    /// The index of the command that the barrier was inserted before. If this is equal to the
+    /// number of commands, then the barrier was inserted at the end of the command buffer.
+    pub barrier_index: usize,
+
+    /// The resource that is synchronized by the barrier.
+    pub resource: BarrierResource,
+
+    /// The earlier use of the resource that the barrier waits for.
+    ///
+    /// This is `None` if the barrier transitions an image out of the layout that it has at the
+    /// start of the command buffer.
+    pub previous_use: Option<BarrierResourceUse>,
+
+    /// The later use of the resource that must wait for the barrier.
+    ///
+    /// This is `None` if the barrier transitions an image into the layout that it must have at
+    /// the end of the command buffer.
+    pub next_use: Option<BarrierResourceUse>,
+}
+
+/// A resource that is synchronized by a pipeline barrier.
+#[derive(Clone, Debug)]
+pub enum BarrierResource {
+    Buffer {
+        buffer: Arc<UnsafeBuffer>,
+        range: Range<DeviceSize>,
+    },
+    Image {
+        image: Arc<UnsafeImage>,
+        subresource_range: ImageSubresourceRange,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+    },
+    /// A range of memory that is shared by transient resources, which is used by a different
+    /// resource than before.
+    Memory {
+        memory: Arc<TransientMemory>,
+        range: Range<DeviceSize>,
+    },
+}
+
+/// A use of a resource by a command, which caused a pipeline barrier to be inserted.
+#[derive(Clone, Debug)]
+pub struct BarrierResourceUse {
+    /// The name of the command.
+    pub command_name: &'static str,
+
+    /// The index of the command within the command buffer.
+    pub command_offset: usize,
+
+    /// The name of the command parameter that the resource was provided for.
+    pub command_param: Cow<'static, str>,
+}
+
 /// Type of resource whose state is to be tracked.
 #[derive(Clone)]
 pub(super) enum Resource {
@@ -523,7 +651,7 @@ impl std::fmt::Debug for dyn Command {
 mod tests {
     use super::*;
     use crate::{
-        buffer::{BufferUsage, CpuAccessibleBuffer, ImmutableBuffer},
+        buffer::{sys::UnsafeBufferCreateInfo, BufferUsage, CpuAccessibleBuffer, ImmutableBuffer},
         command_buffer::{
             pool::{CommandPool, CommandPoolBuilderAlloc},
             sys::CommandBufferBeginInfo,
@@ -536,6 +664,7 @@ mod tests {
             },
             PersistentDescriptorSet, WriteDescriptorSet,
         },
+        memory::transient::TransientAllocator,
         pipeline::{layout::PipelineLayoutCreateInfo, PipelineBindPoint, PipelineLayout},
         sampler::{Sampler, SamplerCreateInfo},
         shader::ShaderStages,
@@ -632,6 +761,17 @@ mod tests {
                 // Ensure that the builder added a barrier between the two writes
                 assert_eq!(&names, &["execute_commands", "execute_commands"]);
                 assert_eq!(&primary.barriers, &[0, 1]);
+
+                // Ensure that the barrier is attributed to the two writes
+                let reasons = primary.barrier_reasons();
+                assert_eq!(reasons.len(), 1);
+                assert_eq!(reasons[0].barrier_index, 1);
+                assert!(matches!(
+                    reasons[0].resource,
+                    BarrierResource::Buffer { ref range, .. } if *range == (0..4)
+                ));
+                assert_eq!(reasons[0].previous_use.as_ref().unwrap().command_offset, 0);
+                assert_eq!(reasons[0].next_use.as_ref().unwrap().command_offset, 1);
             }
 
             {
@@ -654,6 +794,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn transient_memory_barrier() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        // Two buffers that share the same memory.
+        let mut allocator = TransientAllocator::new(device.clone());
+        let ids = (0..2)
+            .map(|pass| {
+                allocator
+                    .add_buffer(
+                        UnsafeBufferCreateInfo {
+                            size: 4096,
+                            usage: BufferUsage::transfer_dst(),
+                            ..Default::default()
+                        },
+                        pass..pass + 1,
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let resources = allocator.allocate().unwrap();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        for &id in &ids {
+            builder
+                .fill_buffer(FillBufferInfo {
+                    data: 42u32,
+                    ..FillBufferInfo::dst_buffer(resources.buffer(id))
+                })
+                .unwrap();
+        }
+
+        let primary = builder.build().unwrap();
+
+        // Ensure that the builder added a barrier between the uses of the two buffers.
+        let reasons = primary.barrier_reasons();
+        assert_eq!(reasons.len(), 1);
+        assert_eq!(reasons[0].barrier_index, 1);
+        assert!(matches!(
+            reasons[0].resource,
+            BarrierResource::Memory { ref range, .. } if *range == (0..4096)
+        ));
+        assert_eq!(reasons[0].previous_use.as_ref().unwrap().command_offset, 0);
+        assert_eq!(reasons[0].next_use.as_ref().unwrap().command_offset, 1);
+    }
+
     #[test]
     fn vertex_buffer_binding() {
         unsafe {
@@ -25,7 +25,7 @@ use crate::{
     OomError, VulkanObject,
 };
 use smallvec::SmallVec;
-use std::{ptr, sync::Arc};
+use std::{borrow::Cow, ptr, sync::Arc};
 
 /// Command buffer being built.
 ///
@@ -58,6 +58,7 @@ impl UnsafeCommandBufferBuilder {
         let CommandBufferBeginInfo {
             usage,
             inheritance_info,
+            debug_name,
             _ne: _,
         } = begin_info;
 
@@ -184,11 +185,17 @@ impl UnsafeCommandBufferBuilder {
             ))?;
         }
 
-        Ok(UnsafeCommandBufferBuilder {
+        let builder = UnsafeCommandBufferBuilder {
             handle: pool_alloc.internal_object(),
             device,
             usage,
-        })
+        };
+
+        builder
+            .device
+            .reset_debug_name(&builder, debug_name.as_deref())?;
+
+        Ok(builder)
     }
 
     /// Turns the builder into an actual command buffer.
@@ -238,6 +245,17 @@ pub struct CommandBufferBeginInfo {
     /// The default value is `None`.
     pub inheritance_info: Option<CommandBufferInheritanceInfo>,
 
+    /// A name to give to the command buffer, to identify it in debugging tools.
+    ///
+    /// If the [`ext_debug_utils`](crate::instance::InstanceExtensions::ext_debug_utils) extension
+    /// is enabled on the instance, the name is set with
+    /// [`Device::set_debug_utils_object_name`](crate::device::Device::set_debug_utils_object_name)
+    /// when recording begins. Command buffers are reused by their pool, so if this is `None`, the
+    /// name of a previous recording is removed. If the extension is not enabled, this is ignored.
+    ///
+    /// The default value is `None`.
+    pub debug_name: Option<Cow<'static, str>>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -247,6 +265,7 @@ impl Default for CommandBufferBeginInfo {
         Self {
             usage: CommandBufferUsage::MultipleSubmit,
             inheritance_info: None,
+            debug_name: None,
             _ne: crate::NonExhaustive(()),
         }
     }
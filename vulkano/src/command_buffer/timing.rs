@@ -0,0 +1,346 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::{
+    command_buffer::{sys::UnsafeCommandBufferBuilder, QueryError},
+    device::DeviceOwned,
+    query::{GetResultsError, QueryPool, QueryPoolCreationError, QueryResultFlags},
+    sync::PipelineStage,
+};
+use std::{error, fmt, ops::Range, sync::Arc};
+
+/// Measures how long ranges of commands of a command buffer take to execute on the GPU.
+///
+/// Command timing is opt-in. It is enabled by calling `enable_command_timing` on a primary command
+/// buffer builder, after which the commands that are recorded are split into ranges of `interval`
+/// commands, and a timestamp is written between each range. Once the command buffer has been
+/// executed, `report` reads back the timestamps and returns the duration of each range, so that
+/// the slowest parts of a frame can be found without placing timestamps by hand.
+///
+/// If the [`nv_device_diagnostic_checkpoints`](crate::device::DeviceExtensions::nv_device_diagnostic_checkpoints)
+/// extension is enabled, every command is also preceded by a checkpoint, so if the device is lost
+/// while executing an instrumented command buffer, `Queue::diagnostic_checkpoints` reports which
+/// command was executing.
+///
+/// No timestamps are written inside render pass instances that use multiview, because a timestamp
+/// written there occupies one query for each view. The commands of such a render pass instance
+/// are added to the range that was open when it began. When all the queries have been used, the
+/// remaining commands are added to the last range.
+#[derive(Debug)]
+pub struct CommandTiming {
+    query_pool: Arc<QueryPool>,
+    interval: u32,
+    timestamp_period: f32,
+    timestamp_mask: u64,
+
+    // The index of the first command that is timed. Commands before it are not part of any range.
+    first_command: usize,
+    // Ranges of command indices before which no timestamps may be written.
+    paused: Vec<Range<usize>>,
+    // The start of the pause that is currently active, if any.
+    paused_since: Option<usize>,
+
+    // The ranges that timestamps have been written for so far. Range `i` starts at query `i` and
+    // ends at query `i + 1`.
+    ranges: Vec<TimedCommandRange>,
+    // The number of commands that were added to the last range.
+    commands_in_range: u32,
+}
+
+impl CommandTiming {
+    pub(crate) fn new(
+        query_pool: Arc<QueryPool>,
+        interval: u32,
+        timestamp_valid_bits: u32,
+        first_command: usize,
+    ) -> Self {
+        let timestamp_period = query_pool
+            .device()
+            .physical_device()
+            .properties()
+            .timestamp_period;
+
+        CommandTiming {
+            query_pool,
+            interval,
+            timestamp_period,
+            timestamp_mask: u64::MAX >> (u64::BITS - timestamp_valid_bits),
+
+            first_command,
+            paused: Vec::new(),
+            paused_since: None,
+
+            ranges: Vec::new(),
+            commands_in_range: 0,
+        }
+    }
+
+    /// Returns the query pool that holds the timestamps.
+    #[inline]
+    pub fn query_pool(&self) -> &Arc<QueryPool> {
+        &self.query_pool
+    }
+
+    /// Returns the maximum number of commands in each range.
+    #[inline]
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// Returns the number of ranges that timestamps were written for.
+    #[inline]
+    pub fn range_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    // Stops writing timestamps before the commands starting at `command_index`.
+    pub(crate) fn pause(&mut self, command_index: usize) {
+        debug_assert!(self.paused_since.is_none());
+        self.paused_since = Some(command_index);
+    }
+
+    // Resumes writing timestamps before the commands starting at `command_index`.
+    pub(crate) fn resume(&mut self, command_index: usize) {
+        if let Some(start) = self.paused_since.take() {
+            self.paused.push(start..command_index);
+        }
+    }
+
+    fn is_paused(&self, command_index: usize) -> bool {
+        self.paused_since
+            .map_or(false, |start| command_index >= start)
+            || self
+                .paused
+                .iter()
+                .any(|range| range.contains(&command_index))
+    }
+
+    // Called before the command at `command_index` is sent to `inner`. Writes a timestamp if a new
+    // range must be started.
+    pub(crate) unsafe fn before_command(
+        &mut self,
+        inner: &mut UnsafeCommandBufferBuilder,
+        command_index: usize,
+        command_name: &'static str,
+    ) {
+        if command_index < self.first_command {
+            return;
+        }
+
+        // The last query is kept for the end of the last range.
+        let start_range = (self.ranges.is_empty() || self.commands_in_range >= self.interval)
+            && (self.ranges.len() as u32) < self.query_pool.query_count() - 1
+            && !self.is_paused(command_index);
+
+        if start_range {
+            let query = self.ranges.len() as u32;
+            inner.write_timestamp(
+                self.query_pool.query(query).unwrap(),
+                PipelineStage::BottomOfPipe,
+            );
+            self.ranges.push(TimedCommandRange {
+                first_command: command_index,
+                command_names: Vec::new(),
+                duration_ns: 0,
+            });
+            self.commands_in_range = 0;
+        }
+
+        if let Some(range) = self.ranges.last_mut() {
+            range.command_names.push(command_name);
+            self.commands_in_range += 1;
+        }
+    }
+
+    // Called after all commands have been sent to `inner`. Writes the timestamp that ends the last
+    // range.
+    pub(crate) unsafe fn finish(&mut self, inner: &mut UnsafeCommandBufferBuilder) {
+        if !self.ranges.is_empty() {
+            let query = self.ranges.len() as u32;
+            inner.write_timestamp(
+                self.query_pool.query(query).unwrap(),
+                PipelineStage::BottomOfPipe,
+            );
+        }
+    }
+
+    /// Reads back the timestamps of the last execution of the command buffer, and returns the
+    /// duration of each range of commands.
+    ///
+    /// Returns `None` if the results are not available yet, which is the case until the command
+    /// buffer has finished executing.
+    pub fn report(&self) -> Result<Option<CommandTimingReport>, GetResultsError> {
+        if self.ranges.is_empty() {
+            return Ok(Some(CommandTimingReport { ranges: Vec::new() }));
+        }
+
+        let mut timestamps = vec![0u64; self.ranges.len() + 1];
+
+        if !self
+            .query_pool
+            .queries_range(0..timestamps.len() as u32)
+            .unwrap()
+            .get_results(&mut timestamps, QueryResultFlags::default())?
+        {
+            return Ok(None);
+        }
+
+        let ranges = self
+            .ranges
+            .iter()
+            .zip(timestamps.windows(2))
+            .map(|(range, timestamps)| {
+                let ticks = timestamps[1].wrapping_sub(timestamps[0]) & self.timestamp_mask;
+
+                TimedCommandRange {
+                    duration_ns: (ticks as f64 * self.timestamp_period as f64) as u64,
+                    ..range.clone()
+                }
+            })
+            .collect();
+
+        Ok(Some(CommandTimingReport { ranges }))
+    }
+}
+
+/// A range of commands that was timed by `CommandTiming`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimedCommandRange {
+    /// The index of the first command of the range, counted from the start of the command buffer.
+    pub first_command: usize,
+
+    /// The names of the commands in the range, in recording order.
+    pub command_names: Vec<&'static str>,
+
+    /// The time that the GPU took to execute the range, in nanoseconds.
+    pub duration_ns: u64,
+}
+
+/// The durations of the ranges of commands of a command buffer, as returned by
+/// `CommandTiming::report`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandTimingReport {
+    /// The timed ranges, in recording order.
+    pub ranges: Vec<TimedCommandRange>,
+}
+
+impl CommandTimingReport {
+    /// Returns the total time of all ranges, in nanoseconds.
+    #[inline]
+    pub fn total_ns(&self) -> u64 {
+        self.ranges.iter().map(|range| range.duration_ns).sum()
+    }
+
+    /// Returns up to `count` ranges, from the slowest to the fastest.
+    pub fn slowest(&self, count: usize) -> Vec<&TimedCommandRange> {
+        let mut ranges: Vec<_> = self.ranges.iter().collect();
+        ranges.sort_by(|a, b| b.duration_ns.cmp(&a.duration_ns));
+        ranges.truncate(count);
+        ranges
+    }
+}
+
+impl fmt::Display for CommandTimingReport {
+    /// Writes one line for each range, from the slowest to the fastest.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total_ns = self.total_ns().max(1);
+
+        for range in self.slowest(self.ranges.len()) {
+            writeln!(
+                f,
+                "{:>10.3} ms {:>5.1}% commands {}..{}: {}",
+                range.duration_ns as f64 / 1_000_000.0,
+                range.duration_ns as f64 * 100.0 / total_ns as f64,
+                range.first_command,
+                range.first_command + range.command_names.len(),
+                range.command_names.join(", "),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Error that can happen when enabling command timing.
+#[derive(Clone, Debug)]
+pub enum CommandTimingError {
+    /// Command timing is already enabled on the builder.
+    AlreadyEnabled,
+
+    /// Creating the timestamp query pool failed.
+    QueryPoolCreationError(QueryPoolCreationError),
+
+    /// Recording the reset of the timestamp queries failed.
+    QueryError(QueryError),
+}
+
+impl error::Error for CommandTimingError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::QueryPoolCreationError(err) => Some(err),
+            Self::QueryError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CommandTimingError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AlreadyEnabled => write!(f, "command timing is already enabled"),
+            Self::QueryPoolCreationError(_) => {
+                write!(f, "creating the timestamp query pool failed")
+            }
+            Self::QueryError(_) => write!(f, "recording a query command failed"),
+        }
+    }
+}
+
+impl From<QueryPoolCreationError> for CommandTimingError {
+    #[inline]
+    fn from(err: QueryPoolCreationError) -> Self {
+        Self::QueryPoolCreationError(err)
+    }
+}
+
+impl From<QueryError> for CommandTimingError {
+    #[inline]
+    fn from(err: QueryError) -> Self {
+        Self::QueryError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandTimingReport, TimedCommandRange};
+
+    #[test]
+    fn slowest() {
+        let range = |first_command, duration_ns| TimedCommandRange {
+            first_command,
+            command_names: vec!["draw"],
+            duration_ns,
+        };
+        let report = CommandTimingReport {
+            ranges: vec![range(0, 10), range(1, 30), range(2, 20)],
+        };
+
+        assert_eq!(report.total_ns(), 60);
+        assert_eq!(
+            report
+                .slowest(2)
+                .into_iter()
+                .map(|range| range.first_command)
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+    }
+}
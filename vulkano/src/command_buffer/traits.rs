@@ -10,12 +10,13 @@
 use super::{
     submit::{SubmitAnyBuilder, SubmitCommandBufferBuilder},
     sys::UnsafeCommandBuffer,
-    CommandBufferInheritanceInfo,
+    CommandBufferInheritanceInfo, QueryCommand,
 };
 use crate::{
     buffer::{sys::UnsafeBuffer, BufferAccess},
     device::{Device, DeviceOwned, Queue},
     image::{sys::UnsafeImage, ImageAccess, ImageLayout, ImageSubresourceRange},
+    query::QueryPool,
     sync::{
         now, AccessCheckError, AccessError, AccessFlags, FlushError, GpuFuture, NowFuture,
         PipelineMemoryAccess, PipelineStages,
@@ -223,6 +224,16 @@ pub unsafe trait SecondaryCommandBuffer: DeviceOwned + Send + Sync {
     /// buffer inherits from its parent primary command buffer.
     fn inheritance_info(&self) -> &CommandBufferInheritanceInfo;
 
+    /// Returns the commands of this command buffer that reset or use queries, in the order that
+    /// they were recorded.
+    ///
+    /// These are checked when a primary command buffer that executes this command buffer is
+    /// submitted.
+    #[inline]
+    fn query_commands(&self) -> &[QueryCommand] {
+        &[]
+    }
+
     /// Returns the number of buffers accessed by this command buffer.
     fn num_buffers(&self) -> usize;
 
@@ -281,6 +292,11 @@ where
         (**self).inheritance_info()
     }
 
+    #[inline]
+    fn query_commands(&self) -> &[QueryCommand] {
+        (**self).query_commands()
+    }
+
     #[inline]
     fn num_buffers(&self) -> usize {
         (**self).num_buffers()
@@ -526,6 +542,12 @@ pub enum CommandBufferExecError {
     /// The command buffer or one of the secondary command buffers it executes is already in use by
     /// the GPU and was not created with the "concurrent" flag.
     ExclusiveAlreadyInUse,
+
+    /// The command buffer uses a query that was not reset since it was last used.
+    QueryNotReset {
+        query_pool: Arc<QueryPool>,
+        query: u32,
+    },
     // TODO: missing entries (eg. wrong queue family, secondary command buffer)
 }
 
@@ -557,6 +579,9 @@ impl fmt::Display for CommandBufferExecError {
                     "the command buffer or one of the secondary command buffers it executes is \
                  already in use was not created with the \"concurrent\" flag"
                 }
+                CommandBufferExecError::QueryNotReset { .. } => {
+                    "the command buffer uses a query that was not reset since it was last used"
+                }
             }
         )
     }
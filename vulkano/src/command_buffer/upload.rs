@@ -0,0 +1,337 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Spreading uploads over several frames.
+//!
+//! Streaming a lot of data, for example textures, in a single command buffer can take long
+//! enough to starve the rendering work submitted to the same queue. An [`UploadScheduler`] queues
+//! copies from staging buffers, and records at most a given number of bytes of them into each
+//! frame's command buffer. Copies that don't fit are carried over to the following frames.
+//!
+//! Buffer-to-buffer copies are split at any byte. Buffer-to-image copies are only split between
+//! regions, so that a region is never copied partially.
+
+use super::{
+    AutoCommandBufferBuilder, BufferCopy, CopyBufferInfo, CopyBufferToImageInfo, CopyError,
+};
+use crate::DeviceSize;
+use smallvec::SmallVec;
+use std::collections::VecDeque;
+
+/// Queues copies from staging buffers and records them into command buffers while respecting a
+/// per-frame byte budget.
+///
+/// Uploads with a higher [`UploadPriority`] are always recorded before the ones with a lower
+/// priority, and uploads with the same priority are recorded in the order they were queued.
+#[derive(Debug)]
+pub struct UploadScheduler {
+    bytes_per_frame: DeviceSize,
+    // One queue per priority class, the highest priority first.
+    queues: [VecDeque<Upload>; 3],
+}
+
+impl UploadScheduler {
+    /// Creates a new `UploadScheduler` that records at most `bytes_per_frame` bytes per call to
+    /// [`record`](Self::record).
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `bytes_per_frame` is zero.
+    #[inline]
+    pub fn new(bytes_per_frame: DeviceSize) -> Self {
+        assert!(bytes_per_frame != 0);
+
+        UploadScheduler {
+            bytes_per_frame,
+            queues: Default::default(),
+        }
+    }
+
+    /// Returns the maximum number of bytes that are recorded per frame.
+    #[inline]
+    pub fn bytes_per_frame(&self) -> DeviceSize {
+        self.bytes_per_frame
+    }
+
+    /// Sets the maximum number of bytes that are recorded per frame.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `bytes_per_frame` is zero.
+    #[inline]
+    pub fn set_bytes_per_frame(&mut self, bytes_per_frame: DeviceSize) {
+        assert!(bytes_per_frame != 0);
+        self.bytes_per_frame = bytes_per_frame;
+    }
+
+    /// Queues a copy between two buffers.
+    #[inline]
+    pub fn upload_buffer(
+        &mut self,
+        priority: UploadPriority,
+        copy_buffer_info: impl Into<CopyBufferInfo>,
+    ) {
+        self.queues[priority as usize].push_back(Upload::Buffer(copy_buffer_info.into()));
+    }
+
+    /// Queues a copy from a buffer to an image.
+    #[inline]
+    pub fn upload_image(
+        &mut self,
+        priority: UploadPriority,
+        copy_buffer_to_image_info: CopyBufferToImageInfo,
+    ) {
+        self.queues[priority as usize].push_back(Upload::Image(copy_buffer_to_image_info));
+    }
+
+    /// Returns the number of bytes that are still waiting to be recorded.
+    #[inline]
+    pub fn pending_bytes(&self) -> DeviceSize {
+        self.queues
+            .iter()
+            .flatten()
+            .map(|upload| upload.size())
+            .sum()
+    }
+
+    /// Returns true if there is nothing left to record.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(|queue| queue.is_empty())
+    }
+
+    /// Records the queued copies into `builder`, up to the per-frame byte budget, and returns the
+    /// number of bytes that were recorded.
+    ///
+    /// If the first region of a buffer-to-image copy that is recorded in this call is larger
+    /// than the budget, it is recorded anyway, so that it doesn't block the queue forever.
+    ///
+    /// If recording a copy returns an error, the rest of that copy is removed from the queue.
+    pub fn record<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+    ) -> Result<DeviceSize, CopyError> {
+        let mut recorded = 0;
+
+        'outer: for queue in &mut self.queues {
+            while let Some(upload) = queue.front_mut() {
+                let budget = self.bytes_per_frame.saturating_sub(recorded);
+
+                if budget == 0 {
+                    break 'outer;
+                }
+
+                // Stop at the first upload that doesn't fit, so that lower priority or later
+                // uploads don't overtake it.
+                let (chunk, size) = match upload.split(budget, recorded == 0) {
+                    Some(x) => x,
+                    None => break 'outer,
+                };
+                let finished = upload.is_empty();
+
+                if finished {
+                    queue.pop_front();
+                }
+
+                let result = match chunk {
+                    Upload::Buffer(info) => builder.copy_buffer(info).map(|_| ()),
+                    Upload::Image(info) => builder.copy_buffer_to_image(info).map(|_| ()),
+                };
+
+                if let Err(err) = result {
+                    if !finished {
+                        queue.pop_front();
+                    }
+
+                    return Err(err);
+                }
+
+                recorded += size;
+            }
+        }
+
+        Ok(recorded)
+    }
+}
+
+/// The priority class of an upload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum UploadPriority {
+    /// Data that is needed as soon as possible, for example for the next frame.
+    High = 0,
+
+    /// Data that isn't needed immediately.
+    Normal = 1,
+
+    /// Data that can arrive late, for example higher-resolution mip levels of a texture that is
+    /// already being displayed.
+    Low = 2,
+}
+
+impl Default for UploadPriority {
+    #[inline]
+    fn default() -> Self {
+        UploadPriority::Normal
+    }
+}
+
+#[derive(Debug)]
+enum Upload {
+    Buffer(CopyBufferInfo),
+    Image(CopyBufferToImageInfo),
+}
+
+impl Upload {
+    fn size(&self) -> DeviceSize {
+        match self {
+            Upload::Buffer(info) => info.regions.iter().map(|region| region.size).sum(),
+            Upload::Image(info) => {
+                let format = info.dst_image.format();
+                info.regions
+                    .iter()
+                    .map(|region| region.buffer_copy_size(format))
+                    .sum()
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Upload::Buffer(info) => info.regions.is_empty(),
+            Upload::Image(info) => info.regions.is_empty(),
+        }
+    }
+
+    // Removes at most `budget` bytes from the front of the upload, and returns them as a separate
+    // upload along with their size. If `force` is true, a first image region larger than
+    // `budget` is returned as well.
+    fn split(&mut self, budget: DeviceSize, force: bool) -> Option<(Upload, DeviceSize)> {
+        let mut size = 0;
+
+        match self {
+            Upload::Buffer(info) => {
+                let mut regions = SmallVec::new();
+
+                while let Some(region) = info.regions.first_mut() {
+                    let left = budget - size;
+
+                    if left == 0 {
+                        break;
+                    }
+
+                    if region.size <= left {
+                        size += region.size;
+                        regions.push(info.regions.remove(0));
+                    } else {
+                        regions.push(BufferCopy {
+                            src_offset: region.src_offset,
+                            dst_offset: region.dst_offset,
+                            size: left,
+                            ..Default::default()
+                        });
+                        region.src_offset += left;
+                        region.dst_offset += left;
+                        region.size -= left;
+                        size += left;
+                    }
+                }
+
+                (!regions.is_empty()).then(|| {
+                    let chunk = CopyBufferInfo {
+                        regions,
+                        ..CopyBufferInfo::buffers(info.src_buffer.clone(), info.dst_buffer.clone())
+                    };
+
+                    (Upload::Buffer(chunk), size)
+                })
+            }
+            Upload::Image(info) => {
+                let format = info.dst_image.format();
+                let mut regions = SmallVec::new();
+
+                while let Some(region) = info.regions.first() {
+                    let region_size = region.buffer_copy_size(format);
+
+                    if size + region_size > budget && !(force && regions.is_empty()) {
+                        break;
+                    }
+
+                    size += region_size;
+                    regions.push(info.regions.remove(0));
+                }
+
+                (!regions.is_empty()).then(|| {
+                    let chunk = CopyBufferToImageInfo {
+                        regions,
+                        ..info.clone()
+                    };
+
+                    (Upload::Image(chunk), size)
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UploadPriority, UploadScheduler};
+    use crate::{
+        buffer::{BufferUsage, CpuAccessibleBuffer},
+        command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, CopyBufferInfo},
+    };
+
+    #[test]
+    fn budget_and_priorities() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = || {
+            CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(), false, [0u8; 1024])
+                .unwrap()
+        };
+        let (low_src, low_dst) = (buffer(), buffer());
+        let (high_src, high_dst) = (buffer(), buffer());
+
+        let mut scheduler = UploadScheduler::new(768);
+        scheduler.upload_buffer(
+            UploadPriority::Low,
+            CopyBufferInfo::buffers(low_src, low_dst),
+        );
+        scheduler.upload_buffer(
+            UploadPriority::High,
+            CopyBufferInfo::buffers(high_src, high_dst),
+        );
+        assert_eq!(scheduler.pending_bytes(), 2048);
+
+        let mut frames = 0;
+
+        while !scheduler.is_empty() {
+            let mut builder = AutoCommandBufferBuilder::primary(
+                device.clone(),
+                queue.family(),
+                CommandBufferUsage::OneTimeSubmit,
+            )
+            .unwrap();
+
+            let recorded = scheduler.record(&mut builder).unwrap();
+            assert!(recorded <= 768);
+            frames += 1;
+
+            if frames == 1 {
+                // Only the high priority upload is started in the first frame.
+                assert_eq!(scheduler.pending_bytes(), 2048 - 768);
+                assert_eq!(scheduler.queues[UploadPriority::Low as usize].len(), 1);
+                assert_eq!(scheduler.queues[UploadPriority::High as usize].len(), 1);
+            }
+        }
+
+        assert_eq!(frames, 3);
+    }
+}
@@ -0,0 +1,677 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Uploading data on a dedicated transfer queue.
+//!
+//! Many devices have a queue family that only supports transfer operations, and that can copy
+//! data while the graphics queue is busy rendering. An [`UploadManager`] owns a queue of such a
+//! family, and records uploads to buffers and images into command buffers that it submits to
+//! that queue:
+//!
+//! - [`upload_buffer`](UploadManager::upload_buffer) and
+//!   [`upload_image`](UploadManager::upload_image) copy the data into a
+//!   [`StagingBelt`](super::staging_belt::StagingBelt) and record the copies.
+//! - [`submit`](UploadManager::submit) submits the copies recorded since the previous call, and
+//!   returns an [`UploadBatch`].
+//! - [`UploadBatch::acquire`] records the second half of the queue family ownership transfers
+//!   into a command buffer of the queue family that uses the resources, and
+//!   [`UploadBatch::into_future`] returns a future that this command buffer must be executed
+//!   after.
+//!
+//! The resources that are uploaded to must be created with exclusive sharing, for example by
+//! passing only the queue family that uses them when creating them. Their ownership is
+//! transferred from the transfer queue family to that queue family after the copies. If both
+//! queues belong to the same family, no ownership transfer is needed and
+//! [`acquire`](UploadBatch::acquire) does nothing.
+//!
+//! Only whole buffers and whole mip levels of images are uploaded, so the previous contents of
+//! the resources are discarded.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use vulkano::buffer::{BufferUsage, DeviceLocalBuffer};
+//! use vulkano::command_buffer::{
+//!     upload_manager::UploadManager, AutoCommandBufferBuilder, CommandBufferUsage,
+//! };
+//! use vulkano::sync::GpuFuture;
+//! # let device: Arc<vulkano::device::Device> = return;
+//! # let transfer_queue: Arc<vulkano::device::Queue> = return;
+//! # let graphics_queue: Arc<vulkano::device::Queue> = return;
+//!
+//! let buffer = DeviceLocalBuffer::<[f32]>::array(
+//!     device.clone(),
+//!     4,
+//!     BufferUsage::transfer_dst() | BufferUsage::vertex_buffer(),
+//!     [graphics_queue.family()],
+//! )
+//! .unwrap();
+//!
+//! let mut uploads = UploadManager::new(transfer_queue, graphics_queue.family(), 64 * 1024);
+//! uploads
+//!     .upload_buffer(buffer.clone(), &[1.0f32, 2.0, 3.0, 4.0])
+//!     .unwrap();
+//! let mut batch = uploads.submit().unwrap().unwrap();
+//!
+//! let mut builder = AutoCommandBufferBuilder::primary(
+//!     device.clone(),
+//!     graphics_queue.family(),
+//!     CommandBufferUsage::OneTimeSubmit,
+//! )
+//! .unwrap();
+//! batch.acquire(&mut builder).unwrap();
+//! // Use the buffer...
+//! let command_buffer = builder.build().unwrap();
+//!
+//! batch
+//!     .into_future()
+//!     .then_execute(graphics_queue.clone(), command_buffer)
+//!     .unwrap()
+//!     .then_signal_fence_and_flush()
+//!     .unwrap();
+//! ```
+
+use super::{
+    staging_belt::{StagingBelt, StagingBeltError},
+    AutoCommandBufferBuilder, BufferImageCopy, BuildError, CommandBufferBeginError,
+    CommandBufferExecError, CommandBufferExecFuture, CommandBufferUsage, CopyBufferToImageInfo,
+    CopyError, PrimaryAutoCommandBuffer, PrimaryCommandBuffer,
+};
+use crate::{
+    buffer::{BufferAccess, BufferContents},
+    command_buffer::synced::SyncCommandBufferBuilderError,
+    device::{physical::QueueFamily, Queue},
+    image::{ImageAccess, ImageLayout, ImageSubresourceLayers},
+    sync::{
+        FenceStatus, FlushError, GpuFuture, NowFuture, QueueFamilyTransfer, SemaphoreSignalFuture,
+    },
+    DeviceSize, OomError,
+};
+use smallvec::{smallvec, SmallVec};
+use std::{error, fmt, sync::Arc};
+
+/// Records uploads to buffers and images, and submits them to a transfer queue.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+pub struct UploadManager {
+    queue: Arc<Queue>,
+    // The queue family that the resources are transferred to after the uploads.
+    destination_family: u32,
+    belt: StagingBelt,
+
+    // Command buffer that the uploads since the last submission are recorded into, if any.
+    builder: Option<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
+    // Resources whose ownership is released by the current command buffer.
+    releases: Vec<OwnershipTransfer>,
+}
+
+impl UploadManager {
+    /// Creates a new `UploadManager` that submits uploads to `queue`, and transfers the
+    /// resources to `destination_family` afterwards.
+    ///
+    /// The data of the uploads is staged in chunks of `chunk_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `destination_family` doesn't belong to the same physical device as `queue`.
+    /// - Panics if `chunk_size` is zero.
+    #[inline]
+    pub fn new(
+        queue: Arc<Queue>,
+        destination_family: QueueFamily,
+        chunk_size: DeviceSize,
+    ) -> UploadManager {
+        assert_eq!(
+            queue.device().physical_device().index(),
+            destination_family.physical_device().index(),
+        );
+
+        let belt = StagingBelt::new(queue.device().clone(), chunk_size);
+
+        UploadManager {
+            destination_family: destination_family.id(),
+            queue,
+            belt,
+            builder: None,
+            releases: Vec::new(),
+        }
+    }
+
+    /// Returns the queue that the uploads are submitted to.
+    #[inline]
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Returns true if no uploads were recorded since the last call to [`submit`](Self::submit).
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.builder.is_none()
+    }
+
+    /// Records an upload of `data` to the start of `dst_buffer`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `dst_buffer` doesn't belong to the same device as the queue.
+    pub fn upload_buffer<T>(
+        &mut self,
+        dst_buffer: Arc<dyn BufferAccess>,
+        data: &T,
+    ) -> Result<(), UploadError>
+    where
+        T: BufferContents + ?Sized,
+    {
+        let queue_family_transfer = self.queue_family_transfer();
+        let builder = begin(&mut self.builder, &self.queue)?;
+        self.belt.write(dst_buffer.clone(), data, builder)?;
+
+        if let Some(queue_family_transfer) = queue_family_transfer {
+            unsafe {
+                builder
+                    .inner
+                    .release_buffer_ownership(dst_buffer.clone(), queue_family_transfer)?;
+            }
+
+            self.releases.push(OwnershipTransfer::Buffer(dst_buffer));
+        }
+
+        Ok(())
+    }
+
+    /// Records an upload of the mip levels of `dst_image`.
+    ///
+    /// `mip_levels` contains the data of each mip level, starting from the first one, in the
+    /// layout that copying from a buffer expects. The data of each level must cover all of its
+    /// array layers, and the aspects selected by
+    /// [`subresource_layers`](ImageAccess::subresource_layers). If fewer levels are given than
+    /// the image has, the remaining levels are left undefined.
+    ///
+    /// Once the upload has finished, the image is in the layout of its
+    /// [`final_layout_requirement`](ImageAccess::final_layout_requirement).
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `dst_image` doesn't belong to the same device as the queue.
+    pub fn upload_image<'d>(
+        &mut self,
+        dst_image: Arc<dyn ImageAccess>,
+        mip_levels: impl IntoIterator<Item = &'d [u8]>,
+    ) -> Result<(), UploadError> {
+        let format = dst_image.format();
+        let dimensions = dst_image.dimensions();
+        let image_mip_levels = dst_image.mip_levels();
+
+        // Regions must start at a multiple of the texel block size.
+        let alignment = format.block_size().unwrap_or(1).max(1);
+
+        // Check all the levels before recording anything, so that a failed upload leaves the
+        // pending command buffer untouched.
+        let levels = (0..)
+            .zip(mip_levels)
+            .map(|(mip_level, data)| {
+                if mip_level >= image_mip_levels {
+                    return Err(UploadError::MipLevelOutOfRange {
+                        mip_level,
+                        image_mip_levels,
+                    });
+                }
+
+                let region = BufferImageCopy {
+                    image_subresource: ImageSubresourceLayers {
+                        mip_level,
+                        ..dst_image.subresource_layers()
+                    },
+                    image_extent: dimensions
+                        .mip_level_dimensions(mip_level)
+                        .unwrap()
+                        .width_height_depth(),
+                    ..Default::default()
+                };
+
+                let required_size = region.buffer_copy_size(format);
+                let provided_size = data.len() as DeviceSize;
+
+                if provided_size != required_size {
+                    return Err(UploadError::DataSizeMismatch {
+                        mip_level,
+                        required_size,
+                        provided_size,
+                    });
+                }
+
+                Ok((region, data))
+            })
+            .collect::<Result<SmallVec<[_; 4]>, _>>()?;
+
+        let queue_family_transfer = self.queue_family_transfer();
+        let builder = begin(&mut self.builder, &self.queue)?;
+
+        for (region, data) in levels {
+            let (src_buffer, buffer_offset) = self.belt.stage(data, alignment)?;
+            builder.copy_buffer_to_image(CopyBufferToImageInfo {
+                regions: smallvec![BufferImageCopy {
+                    buffer_offset,
+                    ..region
+                }],
+                ..CopyBufferToImageInfo::buffer_image(src_buffer, dst_image.clone())
+            })?;
+        }
+
+        if let Some(queue_family_transfer) = queue_family_transfer {
+            let layout = dst_image.final_layout_requirement();
+
+            unsafe {
+                builder.inner.release_image_ownership(
+                    dst_image.clone(),
+                    queue_family_transfer,
+                    layout,
+                )?;
+            }
+
+            self.releases
+                .push(OwnershipTransfer::Image(dst_image, layout));
+        }
+
+        Ok(())
+    }
+
+    /// Submits the uploads that were recorded since the last call to the queue.
+    ///
+    /// Returns `None` if there was nothing to submit.
+    pub fn submit(&mut self) -> Result<Option<UploadBatch>, UploadError> {
+        let builder = match self.builder.take() {
+            Some(builder) => builder,
+            None => return Ok(None),
+        };
+        let releases = std::mem::take(&mut self.releases);
+
+        let command_buffer = Arc::new(builder.build()?);
+        let future = command_buffer
+            .clone()
+            .execute(self.queue.clone())?
+            .then_signal_semaphore_and_flush()?;
+
+        self.belt
+            .finish(Arc::new(CommandBufferReleased(command_buffer)));
+
+        Ok(Some(UploadBatch {
+            future,
+            queue_family_transfer: self.queue_family_transfer(),
+            acquires: releases,
+        }))
+    }
+
+    /// Makes the staging memory of the uploads that have finished executing available for new
+    /// uploads.
+    ///
+    /// This never blocks. It is usually called once per frame.
+    #[inline]
+    pub fn recall(&mut self) -> Result<(), OomError> {
+        self.belt.recall()
+    }
+
+    /// Frees the staging memory that is not in use.
+    #[inline]
+    pub fn trim(&mut self) {
+        self.belt.trim();
+    }
+
+    // Returns the ownership transfer of the uploaded resources, if the queue families differ.
+    #[inline]
+    fn queue_family_transfer(&self) -> Option<QueueFamilyTransfer> {
+        let source_index = self.queue.family().id();
+
+        (source_index != self.destination_family).then(|| QueueFamilyTransfer {
+            source_index,
+            destination_index: self.destination_family,
+        })
+    }
+}
+
+// Returns the builder of the current command buffer, and begins a new one if needed.
+fn begin<'a>(
+    builder: &'a mut Option<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
+    queue: &Arc<Queue>,
+) -> Result<&'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>, UploadError> {
+    if builder.is_none() {
+        *builder = Some(AutoCommandBufferBuilder::primary(
+            queue.device().clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?);
+    }
+
+    Ok(builder.as_mut().unwrap())
+}
+
+/// Uploads that were submitted together by an [`UploadManager`].
+///
+/// Dropping a batch without waiting for its future blocks until the uploads have finished.
+pub struct UploadBatch {
+    future:
+        SemaphoreSignalFuture<CommandBufferExecFuture<NowFuture, Arc<PrimaryAutoCommandBuffer>>>,
+    queue_family_transfer: Option<QueueFamilyTransfer>,
+    acquires: Vec<OwnershipTransfer>,
+}
+
+impl UploadBatch {
+    /// Records the acquisition of the ownership of the uploaded resources into `builder`.
+    ///
+    /// This must be called once for each batch, before the resources are used in any other way
+    /// in `builder`. The command buffer must then be executed after
+    /// [`into_future`](Self::into_future).
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `builder` doesn't belong to the queue family that the `UploadManager`
+    ///   transfers the resources to.
+    pub fn acquire<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+    ) -> Result<(), UploadError> {
+        let queue_family_transfer = match self.queue_family_transfer {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        assert_eq!(
+            builder.queue_family().id(),
+            queue_family_transfer.destination_index,
+        );
+
+        if builder.render_pass_state.is_some() {
+            return Err(UploadError::ForbiddenInsideRenderPass);
+        }
+
+        for acquire in self.acquires.drain(..) {
+            unsafe {
+                match acquire {
+                    OwnershipTransfer::Buffer(buffer) => builder
+                        .inner
+                        .acquire_buffer_ownership(buffer, queue_family_transfer)?,
+                    OwnershipTransfer::Image(image, layout) => builder
+                        .inner
+                        .acquire_image_ownership(image, queue_family_transfer, layout)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the future that is signaled once the uploads have finished.
+    ///
+    /// The future signals a semaphore, so it can be followed by a submission to another queue.
+    #[inline]
+    pub fn into_future(
+        self,
+    ) -> SemaphoreSignalFuture<CommandBufferExecFuture<NowFuture, Arc<PrimaryAutoCommandBuffer>>>
+    {
+        self.future
+    }
+}
+
+impl fmt::Debug for UploadBatch {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("UploadBatch")
+            .field("queue_family_transfer", &self.queue_family_transfer)
+            .field("acquires", &self.acquires)
+            .finish()
+    }
+}
+
+// A resource whose ownership is transferred to another queue family after an upload.
+#[derive(Debug)]
+enum OwnershipTransfer {
+    Buffer(Arc<dyn BufferAccess>),
+    // The image is transitioned to the layout as part of the transfer.
+    Image(Arc<dyn ImageAccess>, ImageLayout),
+}
+
+// Signaled once the command buffer is no longer referenced by its future. Futures only release
+// their command buffer once it has finished executing, or block until then when dropped.
+struct CommandBufferReleased(Arc<PrimaryAutoCommandBuffer>);
+
+impl FenceStatus for CommandBufferReleased {
+    #[inline]
+    fn is_signaled(&self) -> Result<bool, OomError> {
+        Ok(Arc::strong_count(&self.0) == 1)
+    }
+}
+
+/// Error that can happen when uploading with an [`UploadManager`].
+#[derive(Clone, Debug)]
+pub enum UploadError {
+    BeginError(CommandBufferBeginError),
+    BuildError(BuildError),
+    CopyError(CopyError),
+    ExecError(CommandBufferExecError),
+    FlushError(FlushError),
+    StagingBeltError(StagingBeltError),
+    SyncCommandBufferBuilderError(SyncCommandBufferBuilderError),
+
+    /// The size of the data of a mip level doesn't match the size of the level.
+    DataSizeMismatch {
+        mip_level: u32,
+        required_size: DeviceSize,
+        provided_size: DeviceSize,
+    },
+
+    /// Acquiring the ownership of resources is forbidden inside a render pass.
+    ForbiddenInsideRenderPass,
+
+    /// More mip levels were provided than the image has.
+    MipLevelOutOfRange {
+        mip_level: u32,
+        image_mip_levels: u32,
+    },
+}
+
+impl error::Error for UploadError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::BeginError(err) => Some(err),
+            Self::BuildError(err) => Some(err),
+            Self::CopyError(err) => Some(err),
+            Self::ExecError(err) => Some(err),
+            Self::FlushError(err) => Some(err),
+            Self::StagingBeltError(err) => Some(err),
+            Self::SyncCommandBufferBuilderError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for UploadError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::BeginError(_) => write!(fmt, "beginning the command buffer failed"),
+            Self::BuildError(_) => write!(fmt, "building the command buffer failed"),
+            Self::CopyError(_) => write!(fmt, "recording a copy failed"),
+            Self::ExecError(_) => write!(fmt, "executing the command buffer failed"),
+            Self::FlushError(_) => write!(fmt, "submitting the command buffer failed"),
+            Self::StagingBeltError(_) => write!(fmt, "staging the data failed"),
+            Self::SyncCommandBufferBuilderError(_) => {
+                write!(fmt, "a SyncCommandBufferBuilderError")
+            }
+            Self::DataSizeMismatch {
+                mip_level,
+                required_size,
+                provided_size,
+            } => write!(
+                fmt,
+                "the data of mip level {} is {} bytes, but the level requires {} bytes",
+                mip_level, provided_size, required_size,
+            ),
+            Self::ForbiddenInsideRenderPass => {
+                write!(fmt, "operation forbidden inside of a render pass")
+            }
+            Self::MipLevelOutOfRange {
+                mip_level,
+                image_mip_levels,
+            } => write!(
+                fmt,
+                "data was provided for mip level {}, but the image only has {} mip levels",
+                mip_level, image_mip_levels,
+            ),
+        }
+    }
+}
+
+impl From<CommandBufferBeginError> for UploadError {
+    #[inline]
+    fn from(err: CommandBufferBeginError) -> Self {
+        Self::BeginError(err)
+    }
+}
+
+impl From<BuildError> for UploadError {
+    #[inline]
+    fn from(err: BuildError) -> Self {
+        Self::BuildError(err)
+    }
+}
+
+impl From<CopyError> for UploadError {
+    #[inline]
+    fn from(err: CopyError) -> Self {
+        Self::CopyError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for UploadError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> Self {
+        Self::ExecError(err)
+    }
+}
+
+impl From<FlushError> for UploadError {
+    #[inline]
+    fn from(err: FlushError) -> Self {
+        Self::FlushError(err)
+    }
+}
+
+impl From<StagingBeltError> for UploadError {
+    #[inline]
+    fn from(err: StagingBeltError) -> Self {
+        Self::StagingBeltError(err)
+    }
+}
+
+impl From<SyncCommandBufferBuilderError> for UploadError {
+    #[inline]
+    fn from(err: SyncCommandBufferBuilderError) -> Self {
+        Self::SyncCommandBufferBuilderError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UploadError, UploadManager};
+    use crate::{
+        buffer::{BufferUsage, DeviceLocalBuffer},
+        command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage},
+        format::Format,
+        image::{ImageCreateFlags, ImageDimensions, ImageLayout, ImageUsage, ImmutableImage},
+        sync::GpuFuture,
+    };
+
+    #[test]
+    fn upload_buffer_and_image() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = DeviceLocalBuffer::<[u32]>::array(
+            device.clone(),
+            16,
+            BufferUsage::transfer_dst(),
+            [queue.family()],
+        )
+        .unwrap();
+
+        let (_image, init) = ImmutableImage::uninitialized(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: 4,
+                height: 4,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            2,
+            ImageUsage {
+                transfer_dst: true,
+                sampled: true,
+                ..ImageUsage::none()
+            },
+            ImageCreateFlags::none(),
+            ImageLayout::ShaderReadOnlyOptimal,
+            [queue.family()],
+        )
+        .unwrap();
+
+        let mut uploads = UploadManager::new(queue.clone(), queue.family(), 1024);
+        assert!(uploads.submit().unwrap().is_none());
+
+        match uploads.upload_image(init.clone(), [&[0u8; 60][..]]) {
+            Err(UploadError::DataSizeMismatch {
+                mip_level: 0,
+                required_size: 64,
+                provided_size: 60,
+            }) => (),
+            _ => panic!(),
+        }
+
+        match uploads.upload_image(
+            init.clone(),
+            [&[0u8; 64][..], &[0u8; 16][..], &[0u8; 4][..]],
+        ) {
+            Err(UploadError::MipLevelOutOfRange {
+                mip_level: 2,
+                image_mip_levels: 2,
+            }) => (),
+            _ => panic!(),
+        }
+
+        // Failed uploads don't record anything.
+        assert!(uploads.is_empty());
+
+        uploads.upload_buffer(buffer, &[1u32; 16]).unwrap();
+        // The second level is 2x2 texels.
+        uploads
+            .upload_image(init, [&[0u8; 64][..], &[0u8; 16][..]])
+            .unwrap();
+        assert!(!uploads.is_empty());
+
+        let mut batch = uploads.submit().unwrap().unwrap();
+        assert!(uploads.is_empty());
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        batch.acquire(&mut builder).unwrap();
+        let command_buffer = builder.build().unwrap();
+
+        batch
+            .into_future()
+            .then_execute(queue.clone(), command_buffer)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+}
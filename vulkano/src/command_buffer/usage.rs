@@ -0,0 +1,302 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::{
+    buffer::{sys::UnsafeBuffer, BufferAccess, BufferUsage},
+    command_buffer::RenderingInfo,
+    descriptor_set::{
+        layout::{DescriptorSetLayout, DescriptorType},
+        DescriptorBindingResources, DescriptorSetResources,
+    },
+    image::{sys::UnsafeImage, ImageAccess, ImageUsage},
+    render_pass::{Framebuffer, RenderPass},
+    sync::PipelineMemoryAccess,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Records the usages that buffers and images actually need, based on the commands they are
+/// used in.
+///
+/// Usage inference is opt-in. It is enabled by calling `enable_usage_inference` on a command
+/// buffer builder, after which every command recorded into the builder adds the usages it
+/// requires to the resources it uses. Once the command buffer is built, `inferred_usage` returns
+/// the collected information.
+///
+/// The intended workflow has two phases. First, create resources with a broad usage such as
+/// `BufferUsage::all()` and record the command buffers in a "dry run" with usage inference
+/// enabled. Then, replace the broad usage with the minimal flags that were reported.
+///
+/// Resources are tracked by the underlying `UnsafeBuffer` or `UnsafeImage`, so all buffer slices
+/// and image views of the same resource are combined. Host accesses are not tracked, as they don't
+/// require any usage flags. When a resource is used by several command buffers, the results of
+/// each of them can be combined with `merge`.
+#[derive(Clone, Debug, Default)]
+pub struct UsageInference {
+    buffers: HashMap<Arc<UnsafeBuffer>, BufferUsage>,
+    images: HashMap<Arc<UnsafeImage>, ImageUsage>,
+}
+
+impl UsageInference {
+    /// Creates a new `UsageInference` that hasn't recorded any usages yet.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the minimal usage that the underlying buffer of `buffer` needs, or `None` if it
+    /// wasn't used by any of the recorded commands.
+    #[inline]
+    pub fn buffer_usage(&self, buffer: &dyn BufferAccess) -> Option<BufferUsage> {
+        self.buffers.get(buffer.inner().buffer).copied()
+    }
+
+    /// Returns the minimal usage that the underlying image of `image` needs, or `None` if it
+    /// wasn't used by any of the recorded commands.
+    #[inline]
+    pub fn image_usage(&self, image: &dyn ImageAccess) -> Option<ImageUsage> {
+        self.images.get(image.inner().image).copied()
+    }
+
+    /// Returns an iterator over all recorded buffers, along with their minimal usage.
+    #[inline]
+    pub fn buffers(&self) -> impl ExactSizeIterator<Item = (&Arc<UnsafeBuffer>, &BufferUsage)> {
+        self.buffers.iter()
+    }
+
+    /// Returns an iterator over all recorded images, along with their minimal usage.
+    #[inline]
+    pub fn images(&self) -> impl ExactSizeIterator<Item = (&Arc<UnsafeImage>, &ImageUsage)> {
+        self.images.iter()
+    }
+
+    /// Adds the usages recorded in `other` to `self`.
+    pub fn merge(&mut self, other: &UsageInference) {
+        for (buffer, &usage) in &other.buffers {
+            self.add_buffer_usage(buffer, usage);
+        }
+
+        for (image, &usage) in &other.images {
+            self.add_image_usage(image, usage);
+        }
+    }
+
+    fn add_buffer_usage(&mut self, buffer: &Arc<UnsafeBuffer>, usage: BufferUsage) {
+        let entry = self
+            .buffers
+            .entry(buffer.clone())
+            .or_insert_with(BufferUsage::none);
+        *entry = *entry | usage;
+    }
+
+    fn add_image_usage(&mut self, image: &Arc<UnsafeImage>, usage: ImageUsage) {
+        let entry = self
+            .images
+            .entry(image.clone())
+            .or_insert_with(ImageUsage::none);
+        *entry = *entry | usage;
+    }
+
+    // Records a buffer access made by a command.
+    //
+    // Shader and uniform accesses are ignored here, because the access flags don't say which kind
+    // of descriptor the buffer was bound to. Those are recorded from the descriptor sets instead.
+    pub(in crate::command_buffer) fn add_buffer_access(
+        &mut self,
+        buffer: &dyn BufferAccess,
+        memory: &PipelineMemoryAccess,
+    ) {
+        let access = &memory.access;
+        let usage = BufferUsage {
+            transfer_src: access.transfer_read,
+            transfer_dst: access.transfer_write,
+            index_buffer: access.index_read,
+            vertex_buffer: access.vertex_attribute_read,
+            indirect_buffer: access.indirect_command_read,
+            ..BufferUsage::none()
+        };
+
+        self.add_buffer_usage(buffer.inner().buffer, usage);
+    }
+
+    // Records an image access made by a command.
+    //
+    // As with buffers, descriptor and attachment accesses are ignored here, and are recorded from
+    // the descriptor sets and render passes instead.
+    pub(in crate::command_buffer) fn add_image_access(
+        &mut self,
+        image: &dyn ImageAccess,
+        memory: &PipelineMemoryAccess,
+    ) {
+        let access = &memory.access;
+        let usage = ImageUsage {
+            transfer_src: access.transfer_read,
+            transfer_dst: access.transfer_write,
+            ..ImageUsage::none()
+        };
+
+        self.add_image_usage(image.inner().image, usage);
+    }
+
+    // Records the usages required by the resources that are written to a descriptor set.
+    pub(in crate::command_buffer) fn add_descriptor_set(
+        &mut self,
+        layout: &DescriptorSetLayout,
+        resources: &DescriptorSetResources,
+    ) {
+        for (&binding_num, binding) in layout.bindings() {
+            let binding_resources = match resources.binding(binding_num) {
+                Some(x) => x,
+                None => continue,
+            };
+
+            let buffer_usage = match binding.descriptor_type {
+                DescriptorType::UniformBuffer | DescriptorType::UniformBufferDynamic => {
+                    BufferUsage {
+                        uniform_buffer: true,
+                        ..BufferUsage::none()
+                    }
+                }
+                DescriptorType::StorageBuffer | DescriptorType::StorageBufferDynamic => {
+                    BufferUsage {
+                        storage_buffer: true,
+                        ..BufferUsage::none()
+                    }
+                }
+                DescriptorType::UniformTexelBuffer => BufferUsage {
+                    uniform_texel_buffer: true,
+                    ..BufferUsage::none()
+                },
+                DescriptorType::StorageTexelBuffer => BufferUsage {
+                    storage_texel_buffer: true,
+                    ..BufferUsage::none()
+                },
+                _ => BufferUsage::none(),
+            };
+
+            let image_usage = match binding.descriptor_type {
+                DescriptorType::CombinedImageSampler | DescriptorType::SampledImage => ImageUsage {
+                    sampled: true,
+                    ..ImageUsage::none()
+                },
+                DescriptorType::StorageImage => ImageUsage {
+                    storage: true,
+                    ..ImageUsage::none()
+                },
+                DescriptorType::InputAttachment => ImageUsage {
+                    input_attachment: true,
+                    ..ImageUsage::none()
+                },
+                _ => ImageUsage::none(),
+            };
+
+            match binding_resources {
+                DescriptorBindingResources::None(_) | DescriptorBindingResources::Sampler(_) => (),
+                DescriptorBindingResources::Buffer(elements) => {
+                    for buffer in elements.iter().flatten() {
+                        self.add_buffer_usage(buffer.inner().buffer, buffer_usage);
+                    }
+                }
+                DescriptorBindingResources::BufferView(elements) => {
+                    for buffer_view in elements.iter().flatten() {
+                        self.add_buffer_usage(buffer_view.buffer().inner().buffer, buffer_usage);
+                    }
+                }
+                DescriptorBindingResources::ImageView(elements) => {
+                    for image_view in elements.iter().flatten() {
+                        self.add_image_usage(image_view.image().inner().image, image_usage);
+                    }
+                }
+                DescriptorBindingResources::ImageViewSampler(elements) => {
+                    for (image_view, _) in elements.iter().flatten() {
+                        self.add_image_usage(image_view.image().inner().image, image_usage);
+                    }
+                }
+            }
+        }
+    }
+
+    // Records the usages required by the attachments of a framebuffer, based on how the subpasses
+    // of the render pass use them.
+    pub(in crate::command_buffer) fn add_render_pass(
+        &mut self,
+        render_pass: &RenderPass,
+        framebuffer: &Framebuffer,
+    ) {
+        let attachments = framebuffer.attachments();
+
+        for subpass in render_pass.subpasses() {
+            let color_attachments = subpass
+                .color_attachments
+                .iter()
+                .chain(subpass.resolve_attachments.iter())
+                .flatten();
+
+            for atch_ref in color_attachments {
+                let image = attachments[atch_ref.attachment as usize].image();
+                self.add_image_usage(
+                    image.inner().image,
+                    ImageUsage {
+                        color_attachment: true,
+                        ..ImageUsage::none()
+                    },
+                );
+            }
+
+            if let Some(atch_ref) = &subpass.depth_stencil_attachment {
+                let image = attachments[atch_ref.attachment as usize].image();
+                self.add_image_usage(
+                    image.inner().image,
+                    ImageUsage {
+                        depth_stencil_attachment: true,
+                        ..ImageUsage::none()
+                    },
+                );
+            }
+
+            for atch_ref in subpass.input_attachments.iter().flatten() {
+                let image = attachments[atch_ref.attachment as usize].image();
+                self.add_image_usage(
+                    image.inner().image,
+                    ImageUsage {
+                        input_attachment: true,
+                        ..ImageUsage::none()
+                    },
+                );
+            }
+        }
+    }
+
+    // Records the usages required by the attachments used in dynamic rendering.
+    pub(in crate::command_buffer) fn add_rendering(&mut self, rendering_info: &RenderingInfo) {
+        let color_usage = ImageUsage {
+            color_attachment: true,
+            ..ImageUsage::none()
+        };
+        let depth_stencil_usage = ImageUsage {
+            depth_stencil_attachment: true,
+            ..ImageUsage::none()
+        };
+
+        let attachments = (rendering_info.color_attachments.iter().flatten())
+            .map(|attachment_info| (attachment_info, color_usage))
+            .chain(
+                (rendering_info.depth_attachment.iter())
+                    .chain(rendering_info.stencil_attachment.iter())
+                    .map(|attachment_info| (attachment_info, depth_stencil_usage)),
+            );
+
+        for (attachment_info, usage) in attachments {
+            self.add_image_usage(attachment_info.image_view.image().inner().image, usage);
+
+            if let Some(resolve_info) = &attachment_info.resolve_info {
+                self.add_image_usage(resolve_info.image_view.image().inner().image, usage);
+            }
+        }
+    }
+}
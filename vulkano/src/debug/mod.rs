@@ -0,0 +1,16 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Integration with external debugging tools.
+//!
+//! The debug utilities of the Vulkan API itself, such as debug messengers and object names, are
+//! found in the [`instance::debug`](crate::instance::debug) module.
+
+#[cfg(feature = "renderdoc")]
+pub mod renderdoc;
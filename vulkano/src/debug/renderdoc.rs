@@ -0,0 +1,286 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Programmatic frame captures with [RenderDoc](https://renderdoc.org).
+//!
+//! RenderDoc exposes an in-application API that lets the application itself decide when a capture
+//! starts and ends, instead of relying on the capture key. This is useful for capturing work that
+//! isn't presented to a window, such as compute work in tests and offline tools.
+//!
+//! The in-application API is only available when RenderDoc is loaded into the process. This is
+//! the case when the application is launched from RenderDoc, or when the RenderDoc library is
+//! loaded by [`RenderDoc::load`] before the Vulkan instance is created.
+//!
+//! ```no_run
+//! use vulkano::debug::renderdoc::RenderDoc;
+//!
+//! # let instance: std::sync::Arc<vulkano::instance::Instance> = return;
+//! let renderdoc = RenderDoc::load().ok();
+//!
+//! if let Some(renderdoc) = &renderdoc {
+//!     renderdoc.start_frame_capture(&instance);
+//! }
+//!
+//! // Submit the work to capture, and wait for it to complete.
+//!
+//! if let Some(renderdoc) = &renderdoc {
+//!     renderdoc.end_frame_capture(&instance);
+//! }
+//! ```
+
+use crate::{instance::Instance, Version, VulkanObject};
+use ash::vk::Handle;
+use shared_library::dynamic_library::DynamicLibrary;
+use std::{
+    error,
+    ffi::{c_void, CString},
+    fmt, mem,
+    os::raw::{c_char, c_int},
+    path::Path,
+    ptr,
+};
+
+// `eRENDERDOC_API_Version_1_1_0`, the oldest version that provides all the functions we use.
+const RENDERDOC_API_VERSION: c_int = 10100;
+
+/// The RenderDoc in-application API.
+pub struct RenderDoc {
+    // Kept alive so that `api` remains valid.
+    _library: DynamicLibrary,
+    api: *const RenderDocApi,
+}
+
+// RenderDoc documents its in-application API as being callable from any thread.
+unsafe impl Send for RenderDoc {}
+unsafe impl Sync for RenderDoc {}
+
+impl RenderDoc {
+    /// Loads the RenderDoc library and retrieves its in-application API.
+    ///
+    /// If RenderDoc wasn't already loaded into the process, it is loaded now. In that case, it can
+    /// only capture the Vulkan instances that are created after this call.
+    pub fn load() -> Result<RenderDoc, RenderDocError> {
+        #[cfg(windows)]
+        fn get_path() -> &'static Path {
+            Path::new("renderdoc.dll")
+        }
+        #[cfg(all(unix, not(target_os = "android")))]
+        fn get_path() -> &'static Path {
+            Path::new("librenderdoc.so")
+        }
+        #[cfg(target_os = "android")]
+        fn get_path() -> &'static Path {
+            Path::new("libVkLayer_GLES_RenderDoc.so")
+        }
+
+        let library =
+            DynamicLibrary::open(Some(get_path())).map_err(RenderDocError::LibraryLoadFailure)?;
+
+        unsafe {
+            let get_api: unsafe extern "C" fn(c_int, *mut *mut c_void) -> c_int = {
+                let ptr: *mut c_void = library.symbol("RENDERDOC_GetAPI").map_err(|_| {
+                    RenderDocError::MissingEntryPoint("RENDERDOC_GetAPI".to_owned())
+                })?;
+                mem::transmute(ptr)
+            };
+
+            let mut api = ptr::null_mut();
+
+            if get_api(RENDERDOC_API_VERSION, &mut api) != 1 || api.is_null() {
+                return Err(RenderDocError::UnsupportedVersion);
+            }
+
+            Ok(RenderDoc {
+                _library: library,
+                api: api as *const RenderDocApi,
+            })
+        }
+    }
+
+    /// Returns the version of the in-application API that RenderDoc provides.
+    #[inline]
+    pub fn api_version(&self) -> Version {
+        let (mut major, mut minor, mut patch) = (0, 0, 0);
+        unsafe { (self.api().get_api_version)(&mut major, &mut minor, &mut patch) };
+
+        Version {
+            major: major as u32,
+            minor: minor as u32,
+            patch: patch as u32,
+        }
+    }
+
+    /// Starts capturing the commands that are submitted through `instance`.
+    ///
+    /// The capture covers all the devices created from `instance`, and lasts until
+    /// [`end_frame_capture`](Self::end_frame_capture) is called with the same instance.
+    #[inline]
+    pub fn start_frame_capture(&self, instance: &Instance) {
+        unsafe { (self.api().start_frame_capture)(device_pointer(instance), ptr::null_mut()) };
+    }
+
+    /// Returns whether a capture started by [`start_frame_capture`](Self::start_frame_capture) is
+    /// in progress.
+    #[inline]
+    pub fn is_frame_capturing(&self) -> bool {
+        unsafe { (self.api().is_frame_capturing)() != 0 }
+    }
+
+    /// Ends a capture started by [`start_frame_capture`](Self::start_frame_capture), and writes
+    /// it to disk.
+    ///
+    /// Returns `false` if there was no capture in progress for `instance`, or if the capture
+    /// failed.
+    #[inline]
+    pub fn end_frame_capture(&self, instance: &Instance) -> bool {
+        unsafe { (self.api().end_frame_capture)(device_pointer(instance), ptr::null_mut()) != 0 }
+    }
+
+    /// Captures the next frame that is presented, as if the capture key had been pressed.
+    #[inline]
+    pub fn trigger_capture(&self) {
+        unsafe { (self.api().trigger_capture)() };
+    }
+
+    /// Captures the next `num_frames` frames that are presented.
+    #[inline]
+    pub fn trigger_multi_frame_capture(&self, num_frames: u32) {
+        unsafe { (self.api().trigger_multi_frame_capture)(num_frames) };
+    }
+
+    /// Returns the number of captures that have been made so far.
+    #[inline]
+    pub fn num_captures(&self) -> u32 {
+        unsafe { (self.api().get_num_captures)() }
+    }
+
+    /// Sets the template that the paths of the capture files are built from.
+    ///
+    /// RenderDoc appends a timestamp and the frame number to the template. If it isn't an absolute
+    /// path, it is relative to the working directory of the application.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `path_template` contains a nul character.
+    #[inline]
+    pub fn set_capture_file_path_template(&self, path_template: &str) {
+        let path_template = CString::new(path_template).unwrap();
+        unsafe { (self.api().set_capture_file_path_template)(path_template.as_ptr()) };
+    }
+
+    #[inline]
+    fn api(&self) -> &RenderDocApi {
+        unsafe { &*self.api }
+    }
+}
+
+impl fmt::Debug for RenderDoc {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("RenderDoc")
+            .field("api_version", &self.api_version())
+            .finish()
+    }
+}
+
+// `RENDERDOC_DEVICEPOINTER_FROM_VKINSTANCE`: the dispatch table pointer that the handle of a
+// dispatchable Vulkan object points to.
+#[inline]
+fn device_pointer(instance: &Instance) -> *mut c_void {
+    unsafe { *(instance.internal_object().as_raw() as *const *mut c_void) }
+}
+
+// The layout of `RENDERDOC_API_1_1_0`. The functions that we don't use are not typed.
+#[repr(C)]
+struct RenderDocApi {
+    get_api_version: unsafe extern "C" fn(*mut c_int, *mut c_int, *mut c_int),
+    set_capture_option_u32: *const c_void,
+    set_capture_option_f32: *const c_void,
+    get_capture_option_u32: *const c_void,
+    get_capture_option_f32: *const c_void,
+    set_focus_toggle_keys: *const c_void,
+    set_capture_keys: *const c_void,
+    get_overlay_bits: *const c_void,
+    mask_overlay_bits: *const c_void,
+    remove_hooks: *const c_void,
+    unload_crash_handler: *const c_void,
+    set_capture_file_path_template: unsafe extern "C" fn(*const c_char),
+    get_capture_file_path_template: *const c_void,
+    get_num_captures: unsafe extern "C" fn() -> u32,
+    get_capture: *const c_void,
+    trigger_capture: unsafe extern "C" fn(),
+    is_target_control_connected: *const c_void,
+    launch_replay_ui: *const c_void,
+    set_active_window: *const c_void,
+    start_frame_capture: unsafe extern "C" fn(*mut c_void, *mut c_void),
+    is_frame_capturing: unsafe extern "C" fn() -> u32,
+    end_frame_capture: unsafe extern "C" fn(*mut c_void, *mut c_void) -> u32,
+    trigger_multi_frame_capture: unsafe extern "C" fn(u32),
+}
+
+/// Error that can happen when loading the RenderDoc in-application API.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenderDocError {
+    /// Failed to load the RenderDoc shared library.
+    LibraryLoadFailure(String),
+
+    /// The entry point of the in-application API is missing from the library.
+    MissingEntryPoint(String),
+
+    /// The loaded RenderDoc library doesn't support the version of the in-application API that
+    /// vulkano requires.
+    UnsupportedVersion,
+}
+
+impl error::Error for RenderDocError {}
+
+impl fmt::Display for RenderDocError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::LibraryLoadFailure(msg) => {
+                write!(f, "failed to load the RenderDoc shared library: {}", msg)
+            }
+            Self::MissingEntryPoint(name) => write!(
+                f,
+                "the RenderDoc shared library is missing the entry point {}",
+                name
+            ),
+            Self::UnsupportedVersion => write!(
+                f,
+                "the RenderDoc shared library doesn't support version 1.1.0 of the \
+                in-application API",
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderDoc, RenderDocApi, RenderDocError};
+    use std::{ffi::c_void, mem};
+
+    #[test]
+    fn api_layout() {
+        // `RENDERDOC_API_1_1_0` is made of 23 function pointers.
+        assert_eq!(
+            mem::size_of::<RenderDocApi>(),
+            23 * mem::size_of::<*const c_void>()
+        );
+    }
+
+    #[test]
+    fn load() {
+        match RenderDoc::load() {
+            Ok(renderdoc) => assert!(renderdoc.api_version().major >= 1),
+            Err(RenderDocError::LibraryLoadFailure(_)) => (),
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
@@ -41,7 +41,23 @@ pub struct DescriptorSetLayout {
 
 impl DescriptorSetLayout {
     /// Creates a new `DescriptorSetLayout`.
+    ///
+    /// Descriptor set layouts are deduplicated: if a layout that was created with an identical
+    /// `create_info` on the same device is still alive, it is returned instead of creating a new
+    /// one. Pipeline layouts that are created from identical descriptions therefore share their
+    /// descriptor set layouts, which makes checking their compatibility cheap.
     pub fn new(
+        device: Arc<Device>,
+        create_info: DescriptorSetLayoutCreateInfo,
+    ) -> Result<Arc<DescriptorSetLayout>, DescriptorSetLayoutCreationError> {
+        device
+            .descriptor_set_layout_cache()
+            .get_or_try_insert(create_info.clone(), || {
+                Self::new_uncached(device.clone(), create_info)
+            })
+    }
+
+    fn new_uncached(
         device: Arc<Device>,
         mut create_info: DescriptorSetLayoutCreateInfo,
     ) -> Result<Arc<DescriptorSetLayout>, DescriptorSetLayoutCreationError> {
@@ -313,6 +329,10 @@ impl DescriptorSetLayout {
             output.assume_init()
         };
 
+        device
+            .statistics_counters()
+            .record_descriptor_set_layout_created();
+
         Ok(handle)
     }
 
@@ -362,7 +382,73 @@ impl DescriptorSetLayout {
     #[inline]
     pub fn is_compatible_with(&self, other: &DescriptorSetLayout) -> bool {
         self == other
-            || (self.bindings == other.bindings && self.push_descriptor == other.push_descriptor)
+            || (self.push_descriptor == other.push_descriptor
+                && self.bindings.len() == other.bindings.len()
+                && self.bindings.iter().zip(&other.bindings).all(
+                    |((binding_num1, binding1), (binding_num2, binding2))| {
+                        binding_num1 == binding_num2 && binding1.is_identically_defined(binding2)
+                    },
+                ))
+    }
+
+    /// Returns the differences that make `self` incompatible with `other`, treating `self` as the
+    /// expected layout and `other` as the one that was found.
+    ///
+    /// The returned list is empty if and only if [`is_compatible_with`](Self::is_compatible_with)
+    /// returns `true`.
+    pub fn compatibility_differences(
+        &self,
+        other: &DescriptorSetLayout,
+    ) -> Vec<DescriptorSetLayoutDifference> {
+        let mut differences = Vec::new();
+
+        if self == other {
+            return differences;
+        }
+
+        if self.push_descriptor != other.push_descriptor {
+            differences.push(DescriptorSetLayoutDifference::PushDescriptor {
+                expected: self.push_descriptor,
+                found: other.push_descriptor,
+            });
+        }
+
+        for (&binding_num, expected) in &self.bindings {
+            match other.bindings.get(&binding_num) {
+                Some(found) if expected.is_identically_defined(found) => (),
+                Some(found) => differences.push(DescriptorSetLayoutDifference::BindingMismatch {
+                    binding_num,
+                    expected: expected.clone(),
+                    found: found.clone(),
+                }),
+                None => differences.push(DescriptorSetLayoutDifference::BindingMissing {
+                    binding_num,
+                    expected: expected.clone(),
+                }),
+            }
+        }
+
+        for (&binding_num, found) in &other.bindings {
+            if !self.bindings.contains_key(&binding_num) {
+                differences.push(DescriptorSetLayoutDifference::BindingUnexpected {
+                    binding_num,
+                    found: found.clone(),
+                });
+            }
+        }
+
+        differences
+    }
+
+    /// Returns the number of the binding with the given name, if there is one.
+    ///
+    /// See [`DescriptorSetLayoutBinding::name`].
+    #[inline]
+    pub fn binding_by_name(&self, name: &str) -> Option<u32> {
+        self.bindings
+            .iter()
+            .find(|(_, binding)| binding.name.as_deref() == Some(name))
+            .map(|(&binding_num, _)| binding_num)
     }
 }
 
@@ -526,7 +612,7 @@ impl std::fmt::Display for DescriptorSetLayoutCreationError {
 }
 
 /// Parameters to create a new `DescriptorSetLayout`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DescriptorSetLayoutCreateInfo {
     /// The bindings of the desriptor set layout. These are specified according to binding number.
     ///
@@ -592,7 +678,7 @@ impl DescriptorSetLayoutCreateInfo {
 }
 
 /// A binding in a descriptor set layout.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DescriptorSetLayoutBinding {
     /// The content and layout of each array element of a binding.
     ///
@@ -636,6 +722,18 @@ pub struct DescriptorSetLayoutBinding {
     /// The default value is empty.
     pub immutable_samplers: Vec<Arc<Sampler>>,
 
+    /// The name of the shader variable that this binding corresponds to.
+    ///
+    /// This is used to look up bindings by name, such as with [`WriteDescriptorSet::named`]. It
+    /// is filled in automatically when the binding is created from shader reflection data. The
+    /// name is not part of the Vulkan definition of the binding, and is ignored when checking
+    /// whether two layouts are compatible.
+    ///
+    /// The default value is `None`.
+    ///
+    /// [`WriteDescriptorSet::named`]: crate::descriptor_set::WriteDescriptorSet::named
+    pub name: Option<String>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -649,10 +747,30 @@ impl DescriptorSetLayoutBinding {
             variable_descriptor_count: false,
             stages: ShaderStages::none(),
             immutable_samplers: Vec::new(),
+            name: None,
             _ne: crate::NonExhaustive(()),
         }
     }
 
+    // Returns whether `self` and `other` are equal, ignoring the names.
+    fn is_identically_defined(&self, other: &Self) -> bool {
+        let Self {
+            descriptor_type,
+            descriptor_count,
+            variable_descriptor_count,
+            stages,
+            ref immutable_samplers,
+            name: _,
+            _ne: _,
+        } = *self;
+
+        descriptor_type == other.descriptor_type
+            && descriptor_count == other.descriptor_count
+            && variable_descriptor_count == other.variable_descriptor_count
+            && stages == other.stages
+            && *immutable_samplers == other.immutable_samplers
+    }
+
     /// Checks whether the descriptor of a pipeline layout `self` is compatible with the
     /// requirements of a shader `other`.
     #[inline]
@@ -667,6 +785,7 @@ impl DescriptorSetLayoutBinding {
             image_multisampled,
             image_scalar_type,
             image_view_type,
+            name: _,
             sampler_compare,
             sampler_no_unnormalized_coordinates,
             sampler_no_ycbcr_conversion,
@@ -710,6 +829,7 @@ impl From<&DescriptorRequirements> for DescriptorSetLayoutBinding {
             variable_descriptor_count: false,
             stages: reqs.stages,
             immutable_samplers: Vec::new(),
+            name: reqs.name.clone(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -758,6 +878,119 @@ impl fmt::Display for DescriptorRequirementsNotMet {
     }
 }
 
+/// A difference between two descriptor set layouts that makes them incompatible.
+///
+/// Returned by [`DescriptorSetLayout::compatibility_differences`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptorSetLayoutDifference {
+    /// One layout is a push descriptor layout, the other is not.
+    PushDescriptor { expected: bool, found: bool },
+
+    /// A binding of the expected layout is not present in the found layout.
+    BindingMissing {
+        binding_num: u32,
+        expected: DescriptorSetLayoutBinding,
+    },
+
+    /// A binding of the found layout is not present in the expected layout.
+    BindingUnexpected {
+        binding_num: u32,
+        found: DescriptorSetLayoutBinding,
+    },
+
+    /// A binding is present in both layouts, but is not identically defined.
+    BindingMismatch {
+        binding_num: u32,
+        expected: DescriptorSetLayoutBinding,
+        found: DescriptorSetLayoutBinding,
+    },
+}
+
+impl fmt::Display for DescriptorSetLayoutDifference {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fn write_binding_num(
+            fmt: &mut fmt::Formatter,
+            binding_num: u32,
+            name: Option<&str>,
+        ) -> Result<(), fmt::Error> {
+            match name {
+                Some(name) => write!(fmt, "binding {} (`{}`)", binding_num, name),
+                None => write!(fmt, "binding {}", binding_num),
+            }
+        }
+
+        fn write_binding(
+            fmt: &mut fmt::Formatter,
+            binding: &DescriptorSetLayoutBinding,
+        ) -> Result<(), fmt::Error> {
+            write!(
+                fmt,
+                "{:?} (count {}{})",
+                binding.descriptor_type,
+                binding.descriptor_count,
+                if binding.variable_descriptor_count {
+                    ", variable"
+                } else {
+                    ""
+                },
+            )
+        }
+
+        match self {
+            Self::PushDescriptor { expected, found } => write!(
+                fmt,
+                "expected a {}push descriptor layout, found a {}push descriptor layout",
+                if *expected { "" } else { "non-" },
+                if *found { "" } else { "non-" },
+            ),
+            Self::BindingMissing {
+                binding_num,
+                expected,
+            } => {
+                write_binding_num(fmt, *binding_num, expected.name.as_deref())?;
+                write!(fmt, ": expected ")?;
+                write_binding(fmt, expected)?;
+                write!(fmt, ", found nothing")
+            }
+            Self::BindingUnexpected { binding_num, found } => {
+                write_binding_num(fmt, *binding_num, found.name.as_deref())?;
+                write!(fmt, ": expected nothing, found ")?;
+                write_binding(fmt, found)
+            }
+            Self::BindingMismatch {
+                binding_num,
+                expected,
+                found,
+            } => {
+                write_binding_num(
+                    fmt,
+                    *binding_num,
+                    expected.name.as_deref().or(found.name.as_deref()),
+                )?;
+                write!(fmt, ": expected ")?;
+                write_binding(fmt, expected)?;
+                write!(fmt, ", found ")?;
+                write_binding(fmt, found)?;
+
+                if expected.stages != found.stages {
+                    write!(
+                        fmt,
+                        "; expected stages {:?}, found {:?}",
+                        expected.stages, found.stages,
+                    )?;
+                }
+
+                if expected.immutable_samplers != found.immutable_samplers {
+                    write!(fmt, "; the immutable samplers differ")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
 /// Describes what kind of resource may later be bound to a descriptor.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(i32)]
@@ -816,9 +1049,14 @@ mod tests {
     use crate::descriptor_set::layout::DescriptorSetLayout;
     use crate::descriptor_set::layout::DescriptorSetLayoutBinding;
     use crate::descriptor_set::layout::DescriptorSetLayoutCreateInfo;
+    use crate::descriptor_set::layout::DescriptorSetLayoutDifference;
     use crate::descriptor_set::layout::DescriptorType;
+    use crate::descriptor_set::{
+        DescriptorSetUpdateError, WriteDescriptorSet, WriteDescriptorSetElements,
+    };
     use crate::shader::ShaderStages;
     use std::collections::HashMap;
+    use std::sync::Arc;
 
     #[test]
     fn empty() {
@@ -853,4 +1091,134 @@ mod tests {
                 .collect::<HashMap<_, _>>(),
         );
     }
+
+    #[test]
+    fn deduplicated() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let create_layout = |descriptor_type| {
+            DescriptorSetLayout::new(
+                device.clone(),
+                DescriptorSetLayoutCreateInfo {
+                    bindings: [(
+                        0,
+                        DescriptorSetLayoutBinding::descriptor_type(descriptor_type),
+                    )]
+                    .into(),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let a = create_layout(DescriptorType::UniformBuffer);
+        let b = create_layout(DescriptorType::UniformBuffer);
+        let c = create_layout(DescriptorType::StorageBuffer);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+    }
+
+    #[test]
+    fn named_bindings() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let create_layout = |name: &str| {
+            DescriptorSetLayout::new(
+                device.clone(),
+                DescriptorSetLayoutCreateInfo {
+                    bindings: [(
+                        3,
+                        DescriptorSetLayoutBinding {
+                            stages: ShaderStages::all_graphics(),
+                            name: Some(name.to_owned()),
+                            ..DescriptorSetLayoutBinding::descriptor_type(
+                                DescriptorType::UniformBuffer,
+                            )
+                        },
+                    )]
+                    .into(),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let first = create_layout("u_globals");
+        let second = create_layout("u_other");
+
+        assert_eq!(first.binding_by_name("u_globals"), Some(3));
+        assert_eq!(first.binding_by_name("u_other"), None);
+        assert!(first.is_compatible_with(&second));
+
+        let mut write = WriteDescriptorSet::named("u_missing", WriteDescriptorSetElements::None(1));
+        match write.resolve_name(&first) {
+            Err(DescriptorSetUpdateError::BindingNameNotFound { name, available }) => {
+                assert_eq!(name, "u_missing");
+                assert_eq!(available, ["u_globals"]);
+            }
+            _ => panic!(),
+        }
+
+        let mut write = WriteDescriptorSet::named("u_globals", WriteDescriptorSetElements::None(1));
+        write.resolve_name(&first).unwrap();
+        assert_eq!(write.binding(), 3);
+    }
+
+    #[test]
+    fn compatibility_differences() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let create_layout = |bindings: Vec<(u32, DescriptorType)>| {
+            DescriptorSetLayout::new(
+                device.clone(),
+                DescriptorSetLayoutCreateInfo {
+                    bindings: bindings
+                        .into_iter()
+                        .map(|(binding_num, descriptor_type)| {
+                            (
+                                binding_num,
+                                DescriptorSetLayoutBinding {
+                                    stages: ShaderStages::all_graphics(),
+                                    name: Some(format!("u_{}", binding_num)),
+                                    ..DescriptorSetLayoutBinding::descriptor_type(descriptor_type)
+                                },
+                            )
+                        })
+                        .collect(),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let expected = create_layout(vec![
+            (0, DescriptorType::UniformBuffer),
+            (1, DescriptorType::SampledImage),
+        ]);
+        let found = create_layout(vec![
+            (1, DescriptorType::StorageImage),
+            (2, DescriptorType::Sampler),
+        ]);
+
+        assert!(expected
+            .compatibility_differences(&create_layout(vec![
+                (0, DescriptorType::UniformBuffer),
+                (1, DescriptorType::SampledImage),
+            ]))
+            .is_empty());
+
+        let differences = expected.compatibility_differences(&found);
+        assert!(matches!(
+            differences.as_slice(),
+            [
+                DescriptorSetLayoutDifference::BindingMissing { binding_num: 0, .. },
+                DescriptorSetLayoutDifference::BindingMismatch { binding_num: 1, .. },
+                DescriptorSetLayoutDifference::BindingUnexpected { binding_num: 2, .. },
+            ]
+        ));
+        assert_eq!(
+            differences[1].to_string(),
+            "binding 1 (`u_1`): expected SampledImage (count 1), found StorageImage (count 1)",
+        );
+    }
 }
@@ -179,7 +179,8 @@ impl DescriptorSetInner {
         let mut descriptor_write_info: SmallVec<[_; 8]> = SmallVec::with_capacity(lower_size_bound);
         let mut write_descriptor_set: SmallVec<[_; 8]> = SmallVec::with_capacity(lower_size_bound);
 
-        for write in descriptor_writes {
+        for mut write in descriptor_writes {
+            write.resolve_name(&layout)?;
             let layout_binding =
                 check_descriptor_write(&write, &layout, variable_descriptor_count)?;
 
@@ -496,7 +497,7 @@ where
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum DescriptorSetCreationError {
     DescriptorSetUpdateError(DescriptorSetUpdateError),
     OomError(OomError),
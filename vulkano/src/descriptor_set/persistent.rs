@@ -29,6 +29,7 @@ use crate::descriptor_set::{
     DescriptorSetResources, UnsafeDescriptorSet,
 };
 use crate::device::{Device, DeviceOwned};
+use crate::OomError;
 use crate::VulkanObject;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
@@ -108,7 +109,29 @@ impl PersistentDescriptorSet {
             descriptor_writes,
         )?;
 
-        Ok(Arc::new(PersistentDescriptorSet { alloc, inner }))
+        let set = PersistentDescriptorSet { alloc, inner };
+
+        // The pool may have reused a descriptor set that was given a name before.
+        set.device().reset_debug_name(&set, None)?;
+
+        Ok(Arc::new(set))
+    }
+}
+
+impl<P> PersistentDescriptorSet<P>
+where
+    P: DescriptorPoolAlloc,
+{
+    /// Gives the descriptor set a name, to identify it in debugging tools. If `debug_name` is
+    /// `None`, the name that was given before is removed.
+    ///
+    /// If the [`ext_debug_utils`](crate::instance::InstanceExtensions::ext_debug_utils) extension
+    /// is enabled on the instance, the name is set with
+    /// [`Device::set_debug_utils_object_name`](crate::device::Device::set_debug_utils_object_name).
+    /// Otherwise it is ignored.
+    #[inline]
+    pub fn set_debug_name(&self, debug_name: Option<&str>) -> Result<(), OomError> {
+        self.device().reset_debug_name(self, debug_name)
     }
 }
 
@@ -142,6 +165,18 @@ where
     }
 }
 
+unsafe impl<P> VulkanObject for PersistentDescriptorSet<P>
+where
+    P: DescriptorPoolAlloc,
+{
+    type Object = ash::vk::DescriptorSet;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::DescriptorSet {
+        self.inner().internal_object()
+    }
+}
+
 impl<P> PartialEq for PersistentDescriptorSet<P>
 where
     P: DescriptorPoolAlloc,
@@ -237,6 +237,10 @@ impl UnsafeDescriptorPool {
             output
         };
 
+        self.device
+            .statistics_counters()
+            .record_descriptor_sets_allocated(output.len() as u64);
+
         Ok(output
             .into_iter()
             .map(|handle| UnsafeDescriptorSet::new(handle)))
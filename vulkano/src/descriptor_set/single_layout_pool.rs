@@ -227,6 +227,15 @@ unsafe impl DeviceOwned for SingleLayoutDescSet {
     }
 }
 
+unsafe impl VulkanObject for SingleLayoutDescSet {
+    type Object = ash::vk::DescriptorSet;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::DescriptorSet {
+        self.inner().internal_object()
+    }
+}
+
 impl PartialEq for SingleLayoutDescSet {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -15,7 +15,7 @@ use crate::{
     sampler::{Sampler, SamplerImageViewIncompatibleError},
     DeviceSize, VulkanObject,
 };
-use smallvec::SmallVec;
+use smallvec::{smallvec, SmallVec};
 use std::{ptr, sync::Arc};
 
 /// Represents a single write operation to the binding of a descriptor set.
@@ -27,8 +27,12 @@ use std::{ptr, sync::Arc};
 ///   non-arrayed bindings, where `descriptor_count` in the descriptor set layout is 1.
 /// - The `_array` variant writes several elements and allows specifying the target array index.
 ///   At least one element must be provided; a panic results if the provided iterator is empty.
+///
+/// Instead of a binding number, a write can also refer to a binding by the name of its variable
+/// in the shader, using the `named` constructor.
 pub struct WriteDescriptorSet {
     binding: u32,
+    name: Option<String>,
     first_array_element: u32,
     elements: WriteDescriptorSetElements,
 }
@@ -55,6 +59,7 @@ impl WriteDescriptorSet {
         assert!(num_elements != 0);
         Self {
             binding,
+            name: None,
             first_array_element,
             elements: WriteDescriptorSetElements::None(num_elements),
         }
@@ -77,6 +82,7 @@ impl WriteDescriptorSet {
         assert!(!elements.is_empty());
         Self {
             binding,
+            name: None,
             first_array_element,
             elements: WriteDescriptorSetElements::Buffer(elements),
         }
@@ -99,6 +105,7 @@ impl WriteDescriptorSet {
         assert!(!elements.is_empty());
         Self {
             binding,
+            name: None,
             first_array_element,
             elements: WriteDescriptorSetElements::BufferView(elements),
         }
@@ -121,6 +128,7 @@ impl WriteDescriptorSet {
         assert!(!elements.is_empty());
         Self {
             binding,
+            name: None,
             first_array_element,
             elements: WriteDescriptorSetElements::ImageView(elements),
         }
@@ -147,6 +155,7 @@ impl WriteDescriptorSet {
         assert!(!elements.is_empty());
         Self {
             binding,
+            name: None,
             first_array_element,
             elements: WriteDescriptorSetElements::ImageViewSampler(elements),
         }
@@ -169,17 +178,78 @@ impl WriteDescriptorSet {
         assert!(!elements.is_empty());
         Self {
             binding,
+            name: None,
             first_array_element,
             elements: WriteDescriptorSetElements::Sampler(elements),
         }
     }
 
+    /// Write a single element to array element 0 of the binding with the given name.
+    ///
+    /// The name is looked up in the descriptor set layout when the write is performed, using
+    /// [`DescriptorSetLayoutBinding::name`]. Layouts that were created from shader reflection data
+    /// have the names of the shader variables filled in, if the shader was compiled with debug
+    /// information. If no binding with the name exists, the write fails with
+    /// [`DescriptorSetUpdateError::BindingNameNotFound`], which lists the names that are
+    /// available.
+    ///
+    /// ```ignore
+    /// let set = PersistentDescriptorSet::new(
+    ///     layout.clone(),
+    ///     [WriteDescriptorSet::named("u_albedo", (image_view, sampler))],
+    /// )?;
+    /// ```
+    #[inline]
+    pub fn named(name: impl Into<String>, element: impl Into<WriteDescriptorSetElements>) -> Self {
+        Self {
+            binding: u32::MAX,
+            name: Some(name.into()),
+            first_array_element: 0,
+            elements: element.into(),
+        }
+    }
+
     /// Returns the binding number that is updated by this descriptor write.
+    ///
+    /// For writes that were created with `named`, the binding number is not known until the write
+    /// is performed, and this returns `u32::MAX`.
     #[inline]
     pub fn binding(&self) -> u32 {
         self.binding
     }
 
+    /// Returns the name of the binding that is updated by this descriptor write, if it was
+    /// created with `named`.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // Looks up the binding number of a named write in `layout`. Does nothing for writes that were
+    // created with a binding number.
+    pub(crate) fn resolve_name(
+        &mut self,
+        layout: &DescriptorSetLayout,
+    ) -> Result<(), DescriptorSetUpdateError> {
+        let name = match &self.name {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+
+        self.binding = layout.binding_by_name(name).ok_or_else(|| {
+            DescriptorSetUpdateError::BindingNameNotFound {
+                name: name.clone(),
+                available: layout
+                    .bindings()
+                    .values()
+                    .filter_map(|binding| binding.name.clone())
+                    .collect(),
+            }
+        })?;
+
+        Ok(())
+    }
+
     /// Returns the first array element in the binding that is updated by this descriptor write.
     #[inline]
     pub fn first_array_element(&self) -> u32 {
@@ -365,6 +435,51 @@ impl WriteDescriptorSetElements {
     }
 }
 
+impl From<Arc<dyn BufferAccess>> for WriteDescriptorSetElements {
+    #[inline]
+    fn from(buffer: Arc<dyn BufferAccess>) -> Self {
+        Self::Buffer(smallvec![buffer])
+    }
+}
+
+impl From<Arc<dyn BufferViewAbstract>> for WriteDescriptorSetElements {
+    #[inline]
+    fn from(buffer_view: Arc<dyn BufferViewAbstract>) -> Self {
+        Self::BufferView(smallvec![buffer_view])
+    }
+}
+
+impl From<Arc<dyn ImageViewAbstract>> for WriteDescriptorSetElements {
+    #[inline]
+    fn from(image_view: Arc<dyn ImageViewAbstract>) -> Self {
+        Self::ImageView(smallvec![image_view])
+    }
+}
+
+impl From<(Arc<dyn ImageViewAbstract>, Arc<Sampler>)> for WriteDescriptorSetElements {
+    #[inline]
+    fn from(image_view_sampler: (Arc<dyn ImageViewAbstract>, Arc<Sampler>)) -> Self {
+        Self::ImageViewSampler(smallvec![image_view_sampler])
+    }
+}
+
+impl<I> From<(Arc<I>, Arc<Sampler>)> for WriteDescriptorSetElements
+where
+    I: ImageViewAbstract + 'static,
+{
+    #[inline]
+    fn from((image_view, sampler): (Arc<I>, Arc<Sampler>)) -> Self {
+        Self::ImageViewSampler(smallvec![(image_view as Arc<_>, sampler)])
+    }
+}
+
+impl From<Arc<Sampler>> for WriteDescriptorSetElements {
+    #[inline]
+    fn from(sampler: Arc<Sampler>) -> Self {
+        Self::Sampler(smallvec![sampler])
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum DescriptorWriteInfo {
     Image(SmallVec<[ash::vk::DescriptorImageInfo; 1]>),
@@ -569,11 +684,15 @@ pub(crate) fn check_descriptor_write<'a>(
                     }
 
                     // VUID-VkDescriptorImageInfo-imageView-00343
+                    // VUID-VkDescriptorImageInfo-descriptorType-06714
                     if matches!(
                         image_view.view_type(),
                         ImageViewType::Dim2d | ImageViewType::Dim2dArray
                     ) && image_view.image().inner().image.dimensions().image_type()
                         == ImageType::Dim3d
+                        && !(image_view.view_type() == ImageViewType::Dim2d
+                            && image_view.image().inner().image.view_2d_compatible()
+                            && layout.device().enabled_features().sampler2_d_view_of3_d)
                     {
                         return Err(DescriptorSetUpdateError::ImageView2dFrom3d {
                             binding: write.binding(),
@@ -591,6 +710,15 @@ pub(crate) fn check_descriptor_write<'a>(
                         });
                     }
 
+                    // VUID-VkWriteDescriptorSet-descriptorType-01948
+                    // VUID-VkWriteDescriptorSet-descriptorType-02738
+                    if image_view.sampler_ycbcr_conversion() != sampler.sampler_ycbcr_conversion() {
+                        return Err(DescriptorSetUpdateError::SamplerYcbcrConversionMismatch {
+                            binding: write.binding(),
+                            index: descriptor_range_start + index as u32,
+                        });
+                    }
+
                     if let Err(error) = sampler.check_can_sample(image_view.as_ref()) {
                         return Err(DescriptorSetUpdateError::ImageViewIncompatibleSampler {
                             binding: write.binding(),
@@ -617,11 +745,15 @@ pub(crate) fn check_descriptor_write<'a>(
                     }
 
                     // VUID-VkDescriptorImageInfo-imageView-00343
+                    // VUID-VkDescriptorImageInfo-descriptorType-06714
                     if matches!(
                         image_view.view_type(),
                         ImageViewType::Dim2d | ImageViewType::Dim2dArray
                     ) && image_view.image().inner().image.dimensions().image_type()
                         == ImageType::Dim3d
+                        && !(image_view.view_type() == ImageViewType::Dim2d
+                            && image_view.image().inner().image.view_2d_compatible()
+                            && layout.device().enabled_features().sampler2_d_view_of3_d)
                     {
                         return Err(DescriptorSetUpdateError::ImageView2dFrom3d {
                             binding: write.binding(),
@@ -667,11 +799,15 @@ pub(crate) fn check_descriptor_write<'a>(
                     }
 
                     // VUID-VkDescriptorImageInfo-imageView-00343
+                    // VUID-VkDescriptorImageInfo-descriptorType-06713
                     if matches!(
                         image_view.view_type(),
                         ImageViewType::Dim2d | ImageViewType::Dim2dArray
                     ) && image_view.image().inner().image.dimensions().image_type()
                         == ImageType::Dim3d
+                        && !(image_view.view_type() == ImageViewType::Dim2d
+                            && image_view.image().inner().image.view_2d_compatible()
+                            && layout.device().enabled_features().image2_d_view_of3_d)
                     {
                         return Err(DescriptorSetUpdateError::ImageView2dFrom3d {
                             binding: write.binding(),
@@ -810,11 +946,15 @@ pub(crate) fn check_descriptor_write<'a>(
                     }
 
                     // VUID-VkDescriptorImageInfo-imageView-00343
+                    // VUID-VkDescriptorImageInfo-descriptorType-06714
                     if matches!(
                         image_view.view_type(),
                         ImageViewType::Dim2d | ImageViewType::Dim2dArray
                     ) && image_view.image().inner().image.dimensions().image_type()
                         == ImageType::Dim3d
+                        && !(image_view.view_type() == ImageViewType::Dim2d
+                            && image_view.image().inner().image.view_2d_compatible()
+                            && layout.device().enabled_features().sampler2_d_view_of3_d)
                     {
                         return Err(DescriptorSetUpdateError::ImageView2dFrom3d {
                             binding: write.binding(),
@@ -896,7 +1036,7 @@ pub(crate) fn check_descriptor_write<'a>(
     Ok(layout_binding)
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum DescriptorSetUpdateError {
     /// Tried to write more elements than were available in a binding.
     ArrayIndexOutOfBounds {
@@ -908,7 +1048,21 @@ pub enum DescriptorSetUpdateError {
         written_count: u32,
     },
 
+    /// Tried to write to a binding by name, but the layout has no binding with that name.
+    BindingNameNotFound {
+        /// The name that was requested.
+        name: String,
+        /// The names of the bindings in the layout that have a name.
+        available: Vec<String>,
+    },
+
     /// Tried to write an image view with a 2D type and a 3D underlying image.
+    ///
+    /// This is only allowed for a storage or sampled image descriptor, if the view type is
+    /// `Dim2d`, the image was created with `view_2d_compatible`, and the
+    /// [`image2_d_view_of3_d`](crate::device::Features::image2_d_view_of3_d) or
+    /// [`sampler2_d_view_of3_d`](crate::device::Features::sampler2_d_view_of3_d) feature
+    /// respectively is enabled.
     ImageView2dFrom3d { binding: u32, index: u32 },
 
     /// Tried to write an image view that has both the `depth` and `stencil` aspects.
@@ -953,6 +1107,10 @@ pub enum DescriptorSetUpdateError {
 
     /// Tried to write a sampler to a binding with immutable samplers.
     SamplerIsImmutable { binding: u32 },
+
+    /// Tried to write an image view to a binding with an immutable sampler, but the sampler YCbCr
+    /// conversion of the image view was not the same as that of the immutable sampler.
+    SamplerYcbcrConversionMismatch { binding: u32, index: u32 },
 }
 
 impl std::error::Error for DescriptorSetUpdateError {
@@ -977,6 +1135,22 @@ impl std::fmt::Display for DescriptorSetUpdateError {
                 "tried to write up to element {} to binding {}, but only {} descriptors are available",
                 written_count, binding, available_count,
             ),
+            Self::BindingNameNotFound { name, available } => {
+                if available.is_empty() {
+                    write!(
+                        fmt,
+                        "tried to write to a binding named `{}`, but the layout has no named bindings",
+                        name,
+                    )
+                } else {
+                    write!(
+                        fmt,
+                        "tried to write to a binding named `{}`, but the layout has no binding with that name; available names are: {}",
+                        name,
+                        available.join(", "),
+                    )
+                }
+            }
             Self::ImageView2dFrom3d { binding, index } => write!(
                 fmt,
                 "tried to write an image view to binding {} index {} with a 2D type and a 3D underlying image",
@@ -1036,6 +1210,119 @@ impl std::fmt::Display for DescriptorSetUpdateError {
                 "tried to write a sampler to binding {}, which already contains immutable samplers in the descriptor set layout",
                 binding,
             ),
+            Self::SamplerYcbcrConversionMismatch { binding, index } => write!(
+                fmt,
+                "tried to write an image view to binding {} index {}, but its sampler YCbCr conversion was not the same as that of the immutable sampler",
+                binding, index,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DescriptorSetUpdateError, WriteDescriptorSet};
+    use crate::descriptor_set::layout::{
+        DescriptorSetLayout, DescriptorSetLayoutBinding, DescriptorSetLayoutCreateInfo,
+        DescriptorType,
+    };
+    use crate::descriptor_set::{DescriptorSetCreationError, PersistentDescriptorSet};
+    use crate::format::Format;
+    use crate::image::view::{ImageView, ImageViewCreateInfo};
+    use crate::image::{ImageCreateFlags, ImageDimensions, ImageUsage, StorageImage};
+    use crate::sampler::ycbcr::{SamplerYcbcrConversion, SamplerYcbcrConversionCreateInfo};
+    use crate::sampler::{Sampler, SamplerCreateInfo};
+    use crate::shader::ShaderStages;
+
+    #[test]
+    fn sampler_ycbcr_conversion_mismatch() {
+        let (device, queue) = gfx_dev_and_queue!(sampler_ycbcr_conversion);
+
+        let format = Format::G8_B8R8_2PLANE_420_UNORM;
+        if !device
+            .physical_device()
+            .format_properties(format)
+            .optimal_tiling_features
+            .sampled_image
+        {
+            return;
+        }
+
+        let conversion = match SamplerYcbcrConversion::new(
+            device.clone(),
+            SamplerYcbcrConversionCreateInfo {
+                format: Some(format),
+                ..Default::default()
+            },
+        ) {
+            Ok(conversion) => conversion,
+            Err(_) => return,
+        };
+
+        let sampler = Sampler::new(
+            device.clone(),
+            SamplerCreateInfo {
+                sampler_ycbcr_conversion: Some(conversion),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let layout = DescriptorSetLayout::new(
+            device.clone(),
+            DescriptorSetLayoutCreateInfo {
+                bindings: [(
+                    0,
+                    DescriptorSetLayoutBinding {
+                        stages: ShaderStages::all_graphics(),
+                        immutable_samplers: vec![sampler],
+                        ..DescriptorSetLayoutBinding::descriptor_type(
+                            DescriptorType::CombinedImageSampler,
+                        )
+                    },
+                )]
+                .into(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let image = StorageImage::with_usage(
+            device,
+            ImageDimensions::Dim2d {
+                width: 64,
+                height: 64,
+                array_layers: 1,
+            },
+            format,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+            ImageCreateFlags {
+                mutable_format: true,
+                ..ImageCreateFlags::none()
+            },
+            Some(queue.family()),
+        )
+        .unwrap();
+
+        // A view of a single plane has no conversion, unlike the immutable sampler.
+        let image_view = ImageView::new(
+            image.clone(),
+            ImageViewCreateInfo::from_image_plane(&image, 0),
+        )
+        .unwrap();
+
+        match PersistentDescriptorSet::new(layout, [WriteDescriptorSet::image_view(0, image_view)])
+        {
+            Err(DescriptorSetCreationError::DescriptorSetUpdateError(
+                DescriptorSetUpdateError::SamplerYcbcrConversionMismatch {
+                    binding: 0,
+                    index: 0,
+                },
+            )) => (),
+            _ => panic!(),
         }
     }
 }
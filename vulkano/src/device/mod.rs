@@ -96,18 +96,38 @@
 //! TODO: write
 
 use self::physical::{PhysicalDevice, QueueFamily};
-pub(crate) use self::{features::FeaturesFfi, properties::PropertiesFfi};
+pub(crate) use self::{
+    features::FeaturesFfi, object_cache::ObjectCache, properties::PropertiesFfi,
+    statistics::StatisticsCounters,
+};
 pub use self::{
     features::{FeatureRestriction, FeatureRestrictionError, Features},
     properties::Properties,
+    statistics::DeviceStatistics,
 };
 use crate::{
     check_errors,
     command_buffer::pool::StandardCommandPool,
-    descriptor_set::pool::StdDescriptorPool,
+    descriptor_set::{
+        layout::{DescriptorSetLayout, DescriptorSetLayoutCreateInfo},
+        pool::StdDescriptorPool,
+    },
+    format::{Format, FormatFeatures},
     instance::{debug::DebugUtilsLabel, Instance},
-    memory::{pool::StdMemoryPool, ExternalMemoryHandleType},
-    Error, OomError, SynchronizedVulkanObject, Version, VulkanObject,
+    memory::{pool::StdMemoryPool, tracker::AllocationTracker, ExternalMemoryHandleType},
+    render_pass::RenderPassCache,
+    sampler::{
+        ycbcr::{
+            ChromaLocation, SamplerYcbcrConversionCreateInfo, SamplerYcbcrModelConversion,
+            SamplerYcbcrRange,
+        },
+        ComponentMapping, Sampler, SamplerCacheKey,
+    },
+    sync::{
+        now, BindSparseError, BindSparseFuture, BindSparseInfo, DeferredDeleter, GpuFuture,
+        NowFuture, PipelineStages,
+    },
+    DeviceSize, Error, OomError, SynchronizedVulkanObject, Version, VulkanObject,
 };
 pub use crate::{
     device::extensions::DeviceExtensions,
@@ -119,20 +139,25 @@ use smallvec::SmallVec;
 use std::{
     collections::{hash_map::Entry, HashMap},
     error,
-    ffi::CString,
+    ffi::{c_void, CString},
     fmt,
     fs::File,
     hash::{Hash, Hasher},
     mem::{self, MaybeUninit},
     ops::Deref,
     ptr,
-    sync::{Arc, Mutex, MutexGuard, Weak},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, Weak,
+    },
 };
 
 pub(crate) mod extensions;
 pub(crate) mod features;
+mod object_cache;
 pub mod physical;
 pub(crate) mod properties;
+mod statistics;
 
 /// Represents a Vulkan context.
 #[derive(Debug)]
@@ -148,14 +173,47 @@ pub struct Device {
     fns: DeviceFunctions,
     standard_pool: Mutex<Weak<StdMemoryPool>>,
     standard_descriptor_pool: Mutex<Weak<StdDescriptorPool>>,
+    render_pass_cache: Mutex<Weak<RenderPassCache>>,
     standard_command_pools: Mutex<HashMap<u32, Weak<StandardCommandPool>>>,
     enabled_extensions: DeviceExtensions,
     enabled_features: Features,
     active_queue_families: SmallVec<[u32; 2]>,
     allocation_count: Mutex<u32>,
+    allocated_memory: [AtomicU64; ash::vk::MAX_MEMORY_HEAPS],
+    allocation_tracker: Option<Arc<AllocationTracker>>,
+    #[cfg(feature = "test-support")]
+    simulated_heap_limits: Mutex<SimulatedHeapLimits>,
     fence_pool: Mutex<Vec<ash::vk::Fence>>,
     semaphore_pool: Mutex<Vec<ash::vk::Semaphore>>,
     event_pool: Mutex<Vec<ash::vk::Event>>,
+    profiling_lock_held: AtomicBool,
+    checkpoint_names: Mutex<CheckpointNames>,
+    deferred_deleter: DeferredDeleter,
+    statistics: StatisticsCounters,
+    sampler_cache: ObjectCache<SamplerCacheKey, Sampler>,
+    descriptor_set_layout_cache: ObjectCache<DescriptorSetLayoutCreateInfo, DescriptorSetLayout>,
+}
+
+// The command names that diagnostic checkpoint markers refer to. A marker is the index of the name
+// in `names` plus one, so that it's never null.
+#[derive(Debug, Default)]
+struct CheckpointNames {
+    markers: HashMap<&'static str, usize>,
+    names: Vec<&'static str>,
+}
+
+// The artificial memory heap limits of a device, and the memory that is allocated from each heap.
+#[cfg(feature = "test-support")]
+#[derive(Debug, Default)]
+pub(crate) struct SimulatedHeapLimits {
+    pub(crate) limits: [Option<crate::DeviceSize>; ash::vk::MAX_MEMORY_HEAPS],
+    pub(crate) usage: [crate::DeviceSize; ash::vk::MAX_MEMORY_HEAPS],
+}
+
+// A queue to get from a newly-created device.
+struct QueueToGet {
+    family: u32,
+    id: u32,
 }
 
 // The `StandardCommandPool` type doesn't implement Send/Sync, so we have to manually reimplement
@@ -183,6 +241,7 @@ impl Device {
             mut enabled_extensions,
             mut enabled_features,
             queue_create_infos,
+            allocation_tracker,
             _ne: _,
         } = create_info;
 
@@ -194,11 +253,6 @@ impl Device {
             Queues
         */
 
-        struct QueueToGet {
-            family: u32,
-            id: u32,
-        }
-
         // VUID-VkDeviceCreateInfo-queueCreateInfoCount-arraylength
         assert!(!queue_create_infos.is_empty());
 
@@ -392,8 +446,101 @@ impl Device {
             output.assume_init()
         };
 
+        Ok(unsafe {
+            Self::from_handle_inner(
+                physical_device,
+                handle,
+                enabled_extensions,
+                enabled_features,
+                active_queue_families,
+                queues_to_get,
+                allocation_tracker,
+            )
+        })
+    }
+
+    /// Creates a new `Device` from a raw handle that was created outside of vulkano, for example
+    /// by an OpenXR runtime with `xrCreateVulkanDeviceKHR`.
+    ///
+    /// `create_info` must describe how the device was created. Unlike [`Device::new`], it is not
+    /// validated, and `robust_buffer_access` is not enabled automatically. Returns the device and
+    /// its queues, in the same order as with `Device::new`. The device is destroyed when the
+    /// returned `Device` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if one of the queue families in `create_info.queues` doesn't belong to the given
+    ///   physical device.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid device handle, created from `physical_device`.
+    /// - `create_info` must match the parameters that the device was created with. Extensions and
+    ///   features that were enabled but are not in `create_info` are allowed, but vulkano won't
+    ///   use them.
+    /// - The caller must not destroy the device, and must not use it after the returned `Device`
+    ///   is dropped.
+    pub unsafe fn from_handle(
+        physical_device: PhysicalDevice,
+        handle: ash::vk::Device,
+        create_info: DeviceCreateInfo,
+    ) -> (Arc<Device>, impl ExactSizeIterator<Item = Arc<Queue>>) {
+        let DeviceCreateInfo {
+            enabled_extensions,
+            enabled_features,
+            queue_create_infos,
+            allocation_tracker,
+            _ne: _,
+        } = create_info;
+
+        let mut active_queue_families: SmallVec<[_; 2]> =
+            SmallVec::with_capacity(queue_create_infos.len());
+        let mut queues_to_get: SmallVec<[_; 2]> = SmallVec::with_capacity(queue_create_infos.len());
+
+        for QueueCreateInfo {
+            family,
+            queues,
+            _ne: _,
+        } in &queue_create_infos
+        {
+            assert_eq!(
+                family.physical_device().internal_object(),
+                physical_device.internal_object()
+            );
+
+            let family = family.id();
+            active_queue_families.push(family);
+            queues_to_get.extend((0..queues.len() as u32).map(move |id| QueueToGet { family, id }));
+        }
+
+        active_queue_families.sort_unstable();
+        active_queue_families.dedup();
+
+        Self::from_handle_inner(
+            physical_device,
+            handle,
+            enabled_extensions,
+            enabled_features,
+            active_queue_families,
+            queues_to_get,
+            allocation_tracker,
+        )
+    }
+
+    unsafe fn from_handle_inner(
+        physical_device: PhysicalDevice,
+        handle: ash::vk::Device,
+        enabled_extensions: DeviceExtensions,
+        enabled_features: Features,
+        active_queue_families: SmallVec<[u32; 2]>,
+        queues_to_get: SmallVec<[QueueToGet; 2]>,
+        allocation_tracker: Option<Arc<AllocationTracker>>,
+    ) -> (Arc<Device>, impl ExactSizeIterator<Item = Arc<Queue>>) {
+        let fns_i = physical_device.instance().fns();
+        let api_version = physical_device.api_version();
+
         // loading the function pointers of the newly-created device
-        let fns = DeviceFunctions::load(|name| unsafe {
+        let fns = DeviceFunctions::load(|name| {
             mem::transmute((fns_i.v1_0.get_device_proc_addr)(handle, name.as_ptr()))
         });
 
@@ -405,14 +552,25 @@ impl Device {
             fns,
             standard_pool: Mutex::new(Weak::new()),
             standard_descriptor_pool: Mutex::new(Weak::new()),
+            render_pass_cache: Mutex::new(Weak::new()),
             standard_command_pools: Mutex::new(Default::default()),
             enabled_extensions,
             enabled_features,
             active_queue_families,
             allocation_count: Mutex::new(0),
+            allocated_memory: Default::default(),
+            allocation_tracker,
+            #[cfg(feature = "test-support")]
+            simulated_heap_limits: Mutex::new(Default::default()),
             fence_pool: Mutex::new(Vec::new()),
             semaphore_pool: Mutex::new(Vec::new()),
             event_pool: Mutex::new(Vec::new()),
+            profiling_lock_held: AtomicBool::new(false),
+            checkpoint_names: Mutex::new(Default::default()),
+            deferred_deleter: DeferredDeleter::new(),
+            statistics: Default::default(),
+            sampler_cache: ObjectCache::new(),
+            descriptor_set_layout_cache: ObjectCache::new(),
         });
 
         // Iterator to return the queues
@@ -420,7 +578,7 @@ impl Device {
             let device = device.clone();
             queues_to_get
                 .into_iter()
-                .map(move |QueueToGet { family, id }| unsafe {
+                .map(move |QueueToGet { family, id }| {
                     let fns = device.fns();
                     let mut output = MaybeUninit::uninit();
                     (fns.v1_0.get_device_queue)(handle, family, id, output.as_mut_ptr());
@@ -434,7 +592,7 @@ impl Device {
                 })
         };
 
-        Ok((device, queues_iter))
+        (device, queues_iter)
     }
 
     /// Returns the Vulkan version supported by the device.
@@ -506,6 +664,61 @@ impl Device {
         &self.enabled_features
     }
 
+    /// Returns whether the profiling lock is currently held.
+    ///
+    /// The lock is acquired by creating a
+    /// [`ProfilingLock`](crate::query::performance::ProfilingLock).
+    #[inline]
+    pub fn is_profiling_lock_held(&self) -> bool {
+        self.profiling_lock_held.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn profiling_lock_held(&self) -> &AtomicBool {
+        &self.profiling_lock_held
+    }
+
+    /// Returns the statistics of the device, counted since its creation or since the last call to
+    /// [`take_statistics`](Self::take_statistics).
+    #[inline]
+    pub fn statistics(&self) -> DeviceStatistics {
+        self.statistics.load()
+    }
+
+    /// Returns the statistics of the device like [`statistics`](Self::statistics), and resets
+    /// them to zero.
+    ///
+    /// Calling this once per frame gives the statistics of each frame.
+    #[inline]
+    pub fn take_statistics(&self) -> DeviceStatistics {
+        self.statistics.take()
+    }
+
+    #[inline]
+    pub(crate) fn statistics_counters(&self) -> &StatisticsCounters {
+        &self.statistics
+    }
+
+    #[inline]
+    pub(crate) fn sampler_cache(&self) -> &ObjectCache<SamplerCacheKey, Sampler> {
+        &self.sampler_cache
+    }
+
+    #[inline]
+    pub(crate) fn descriptor_set_layout_cache(
+        &self,
+    ) -> &ObjectCache<DescriptorSetLayoutCreateInfo, DescriptorSetLayout> {
+        &self.descriptor_set_layout_cache
+    }
+
+    /// Returns the queue of resources that are destroyed once the GPU has finished using them.
+    ///
+    /// See the [`deferred`](crate::sync::deferred) module for more information.
+    #[inline]
+    pub fn deferred_deleter(&self) -> &DeferredDeleter {
+        &self.deferred_deleter
+    }
+
     /// Returns the standard memory pool used by default if you don't provide any other pool.
     pub fn standard_pool(me: &Arc<Self>) -> Arc<StdMemoryPool> {
         let mut pool = me.standard_pool.lock().unwrap();
@@ -520,6 +733,20 @@ impl Device {
         new_pool
     }
 
+    /// Replaces the standard memory pool, for example with one that was created with a custom
+    /// block size or heap budgets.
+    ///
+    /// The device only keeps a weak reference to the pool, so you must keep `pool` alive
+    /// yourself. Once it is dropped, a new pool with default parameters is created when needed.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `pool` was not created from this device.
+    pub fn set_standard_pool(&self, pool: &Arc<StdMemoryPool>) {
+        assert_eq!(pool.device().internal_object(), self.internal_object());
+        *self.standard_pool.lock().unwrap() = Arc::downgrade(pool);
+    }
+
     /// Returns the standard descriptor pool used by default if you don't provide any other pool.
     pub fn standard_descriptor_pool(me: &Arc<Self>) -> Arc<StdDescriptorPool> {
         let mut pool = me.standard_descriptor_pool.lock().unwrap();
@@ -534,6 +761,23 @@ impl Device {
         new_pool
     }
 
+    /// Returns the standard render pass and framebuffer cache of the device.
+    ///
+    /// The cache is destroyed when the last `Arc` returned by this function is dropped, so keep
+    /// one alive for as long as the cached objects should be reused.
+    pub fn render_pass_cache(me: &Arc<Self>) -> Arc<RenderPassCache> {
+        let mut cache = me.render_pass_cache.lock().unwrap();
+
+        if let Some(c) = cache.upgrade() {
+            return c;
+        }
+
+        // The weak pointer is empty, so we create the cache.
+        let new_cache = Arc::new(RenderPassCache::new(me.clone()));
+        *cache = Arc::downgrade(&new_cache);
+        new_cache
+    }
+
     /// Returns the standard command buffer pool used by default if you don't provide any other
     /// pool.
     ///
@@ -573,6 +817,79 @@ impl Device {
         &self.allocation_count
     }
 
+    /// Returns the number of bytes of device memory that are currently allocated from a memory
+    /// heap through this device.
+    ///
+    /// This is a running total of the sizes of all [`DeviceMemory`](crate::memory::DeviceMemory)
+    /// objects that are alive, whether they were allocated directly or by a memory pool. Unlike
+    /// the `usage` returned by
+    /// [`PhysicalDevice::memory_budget`](crate::device::physical::PhysicalDevice::memory_budget),
+    /// it is always available and cheap to query, but it doesn't include memory that is used
+    /// internally by the driver or by other devices.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `heap_index` is not less than the number of memory heaps of the physical
+    ///   device.
+    #[inline]
+    pub fn allocated_memory(&self, heap_index: u32) -> crate::DeviceSize {
+        assert!(heap_index < self.physical_device().memory_heaps().len() as u32);
+        self.allocated_memory[heap_index as usize].load(Ordering::Relaxed)
+    }
+
+    /// Returns the allocation tracker that was given when creating the device, if any.
+    #[inline]
+    pub fn allocation_tracker(&self) -> Option<&Arc<AllocationTracker>> {
+        self.allocation_tracker.as_ref()
+    }
+
+    #[inline]
+    pub(crate) fn allocated_memory_counters(&self) -> &[AtomicU64; ash::vk::MAX_MEMORY_HEAPS] {
+        &self.allocated_memory
+    }
+
+    /// Sets an artificial limit on the number of bytes that can be allocated from a memory heap,
+    /// or removes it if `limit` is `None`.
+    ///
+    /// Once the memory that is allocated from the heap through this device would exceed the
+    /// limit, allocating more fails with `OomError::OutOfDeviceMemory`, just like a real device
+    /// that runs out of memory. This is meant for testing how an application and its allocators
+    /// handle running out of memory. Memory that is already allocated is not affected by a new
+    /// limit.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `heap_index` is not less than the number of memory heaps of the physical
+    ///   device.
+    #[cfg(feature = "test-support")]
+    pub fn set_simulated_heap_limit(&self, heap_index: u32, limit: Option<crate::DeviceSize>) {
+        assert!(heap_index < self.physical_device().memory_heaps().len() as u32);
+        let mut limits = self.simulated_heap_limits.lock().unwrap();
+        limits.limits[heap_index as usize] = limit;
+    }
+
+    /// Returns the limit that was set with `set_simulated_heap_limit` for a memory heap.
+    #[cfg(feature = "test-support")]
+    #[inline]
+    pub fn simulated_heap_limit(&self, heap_index: u32) -> Option<crate::DeviceSize> {
+        let limits = self.simulated_heap_limits.lock().unwrap();
+        limits.limits.get(heap_index as usize).copied().flatten()
+    }
+
+    /// Returns the number of bytes that are currently allocated from a memory heap through this
+    /// device, which is what the limit of `set_simulated_heap_limit` is compared against.
+    #[cfg(feature = "test-support")]
+    #[inline]
+    pub fn simulated_heap_usage(&self, heap_index: u32) -> crate::DeviceSize {
+        let limits = self.simulated_heap_limits.lock().unwrap();
+        limits.usage.get(heap_index as usize).copied().unwrap_or(0)
+    }
+
+    #[cfg(feature = "test-support")]
+    pub(crate) fn simulated_heap_limits(&self) -> &Mutex<SimulatedHeapLimits> {
+        &self.simulated_heap_limits
+    }
+
     pub(crate) fn fence_pool(&self) -> &Mutex<Vec<ash::vk::Fence>> {
         &self.fence_pool
     }
@@ -585,6 +902,25 @@ impl Device {
         &self.event_pool
     }
 
+    /// Returns the diagnostic checkpoint marker that identifies commands named `command_name`.
+    pub(crate) fn checkpoint_marker(&self, command_name: &'static str) -> usize {
+        let mut checkpoint_names = self.checkpoint_names.lock().unwrap();
+        let CheckpointNames { markers, names } = &mut *checkpoint_names;
+
+        *markers.entry(command_name).or_insert_with(|| {
+            names.push(command_name);
+            names.len()
+        })
+    }
+
+    /// Returns the name of the commands identified by a diagnostic checkpoint marker.
+    pub(crate) fn checkpoint_command_name(&self, marker: usize) -> Option<&'static str> {
+        let checkpoint_names = self.checkpoint_names.lock().unwrap();
+        marker
+            .checked_sub(1)
+            .and_then(|index| checkpoint_names.names.get(index).copied())
+    }
+
     /// Retrieves the properties of an external file descriptor when imported as a given external
     /// handle type.
     ///
@@ -632,6 +968,75 @@ impl Device {
         }
     }
 
+    /// Retrieves the properties of an Android hardware buffer when it is imported, including the
+    /// format that images must be created with to use it.
+    ///
+    /// An error will be returned if the
+    /// [`android_external_memory_android_hardware_buffer`](DeviceExtensions::android_external_memory_android_hardware_buffer)
+    /// extension was not enabled on the device.
+    ///
+    /// # Safety
+    ///
+    /// - `buffer` must be a valid pointer to an `AHardwareBuffer`, which was allocated with at
+    ///   least one of the `AHARDWAREBUFFER_USAGE_GPU_*` usage flags.
+    pub unsafe fn android_hardware_buffer_properties(
+        &self,
+        buffer: *const c_void,
+    ) -> Result<AndroidHardwareBufferProperties, AndroidHardwareBufferPropertiesError> {
+        if !self
+            .enabled_extensions()
+            .android_external_memory_android_hardware_buffer
+        {
+            return Err(AndroidHardwareBufferPropertiesError::NotSupported);
+        }
+
+        let mut format_properties =
+            ash::vk::AndroidHardwareBufferFormatPropertiesANDROID::default();
+        let mut properties = ash::vk::AndroidHardwareBufferPropertiesANDROID {
+            p_next: &mut format_properties as *mut _ as *mut _,
+            ..Default::default()
+        };
+
+        let fns = self.fns();
+        check_errors((fns
+            .android_external_memory_android_hardware_buffer
+            .get_android_hardware_buffer_properties_android)(
+            self.handle,
+            buffer as *const _,
+            &mut properties,
+        ))?;
+
+        Ok(AndroidHardwareBufferProperties {
+            allocation_size: properties.allocation_size,
+            memory_type_bits: properties.memory_type_bits,
+            format: format_properties.format.try_into().ok(),
+            external_format: format_properties.external_format,
+            format_features: format_properties.format_features.into(),
+            sampler_ycbcr_conversion_components: format_properties
+                .sampler_ycbcr_conversion_components
+                .try_into()
+                .unwrap_or_else(|_| ComponentMapping::identity()),
+            suggested_ycbcr_model: format_properties
+                .suggested_ycbcr_model
+                .try_into()
+                .unwrap_or(SamplerYcbcrModelConversion::RgbIdentity),
+            suggested_ycbcr_range: format_properties
+                .suggested_ycbcr_range
+                .try_into()
+                .unwrap_or(SamplerYcbcrRange::ItuFull),
+            suggested_chroma_offset: [
+                format_properties
+                    .suggested_x_chroma_offset
+                    .try_into()
+                    .unwrap_or(ChromaLocation::CositedEven),
+                format_properties
+                    .suggested_y_chroma_offset
+                    .try_into()
+                    .unwrap_or(ChromaLocation::CositedEven),
+            ],
+        })
+    }
+
     /// Assigns a human-readable name to `object` for debugging purposes.
     ///
     /// If `object_name` is `None`, a previously set object name is removed.
@@ -649,7 +1054,9 @@ impl Device {
         let info = ash::vk::DebugUtilsObjectNameInfoEXT {
             object_type: T::Object::TYPE,
             object_handle: object.internal_object().as_raw(),
-            p_object_name: object_name_vk.map_or(ptr::null(), |object_name| object_name.as_ptr()),
+            p_object_name: object_name_vk
+                .as_ref()
+                .map_or(ptr::null(), |object_name| object_name.as_ptr()),
             ..Default::default()
         };
 
@@ -663,6 +1070,40 @@ impl Device {
 
         Ok(())
     }
+
+    /// Gives `object` the name `debug_name`, if it is `Some` and the `ext_debug_utils` extension
+    /// is enabled on the instance. Otherwise does nothing.
+    ///
+    /// This is used by the constructors of objects whose create info has a `debug_name`.
+    pub(crate) fn set_debug_name<T: VulkanObject + DeviceOwned>(
+        &self,
+        object: &T,
+        debug_name: Option<&str>,
+    ) -> Result<(), OomError> {
+        match debug_name {
+            Some(debug_name) if self.instance.enabled_extensions().ext_debug_utils => {
+                self.set_debug_utils_object_name(object, Some(debug_name))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Same as `set_debug_name`, but if `debug_name` is `None`, removes the name that `object`
+    /// already has.
+    ///
+    /// This is used for objects that are reused by a pool, so that they don't keep the name that
+    /// was given to them for a previous use.
+    pub(crate) fn reset_debug_name<T: VulkanObject + DeviceOwned>(
+        &self,
+        object: &T,
+        debug_name: Option<&str>,
+    ) -> Result<(), OomError> {
+        if self.instance.enabled_extensions().ext_debug_utils {
+            self.set_debug_utils_object_name(object, debug_name)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl Drop for Device {
@@ -671,6 +1112,13 @@ impl Drop for Device {
         let fns = self.fns();
 
         unsafe {
+            if !self.deferred_deleter.is_empty() {
+                // Nothing sensible can be done if this fails, the resources have to be destroyed
+                // before the device either way.
+                let _ = self.wait();
+                self.deferred_deleter.destroy_all();
+            }
+
             for &raw_fence in self.fence_pool.lock().unwrap().iter() {
                 (fns.v1_0.destroy_fence)(self.handle, raw_fence, ptr::null());
             }
@@ -831,6 +1279,11 @@ pub struct DeviceCreateInfo<'qf> {
     /// The default value is empty, which must be overridden.
     pub queue_create_infos: Vec<QueueCreateInfo<'qf>>,
 
+    /// Records all the device memory that is allocated on the device, to debug memory usage.
+    ///
+    /// The default value is `None`.
+    pub allocation_tracker: Option<Arc<AllocationTracker>>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -841,6 +1294,7 @@ impl Default for DeviceCreateInfo<'static> {
             enabled_extensions: DeviceExtensions::none(),
             enabled_features: Features::none(),
             queue_create_infos: Vec::new(),
+            allocation_tracker: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -907,6 +1361,105 @@ pub struct MemoryFdProperties {
     pub memory_type_bits: u32,
 }
 
+/// The properties of an Android hardware buffer when it is imported.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct AndroidHardwareBufferProperties {
+    /// The size of the memory allocation that the buffer must be imported into.
+    pub allocation_size: DeviceSize,
+
+    /// A bitmask of the indices of memory types that can be used with the buffer.
+    pub memory_type_bits: u32,
+
+    /// The Vulkan format that images created from the buffer must have, or `None` if the format
+    /// of the buffer has no Vulkan equivalent. In that case, the buffer can only be sampled with
+    /// a sampler YCbCr conversion that uses `external_format`.
+    pub format: Option<Format>,
+
+    /// The implementation-defined external format of the buffer, which can be used in
+    /// [`SamplerYcbcrConversionCreateInfo::external_format`].
+    pub external_format: u64,
+
+    /// The features that are supported by images created from the buffer.
+    pub format_features: FormatFeatures,
+
+    /// The component mapping that a sampler YCbCr conversion should use.
+    pub sampler_ycbcr_conversion_components: ComponentMapping,
+
+    /// The YCbCr model that a sampler YCbCr conversion should use.
+    pub suggested_ycbcr_model: SamplerYcbcrModelConversion,
+
+    /// The YCbCr range that a sampler YCbCr conversion should use.
+    pub suggested_ycbcr_range: SamplerYcbcrRange,
+
+    /// The chroma offsets in the x and y direction that a sampler YCbCr conversion should use.
+    pub suggested_chroma_offset: [ChromaLocation; 2],
+}
+
+impl AndroidHardwareBufferProperties {
+    /// Returns the parameters of a sampler YCbCr conversion that reads the buffer with the
+    /// suggested model, range, component mapping and chroma offsets. The conversion uses `format`
+    /// if the buffer has one, and `external_format` otherwise.
+    pub fn suggested_ycbcr_conversion(&self) -> SamplerYcbcrConversionCreateInfo {
+        SamplerYcbcrConversionCreateInfo {
+            format: self.format,
+            external_format: if self.format.is_none() {
+                Some(self.external_format)
+            } else {
+                None
+            },
+            ycbcr_model: self.suggested_ycbcr_model,
+            ycbcr_range: self.suggested_ycbcr_range,
+            component_mapping: self.sampler_ycbcr_conversion_components,
+            chroma_offset: self.suggested_chroma_offset,
+            ..Default::default()
+        }
+    }
+}
+
+/// Error that can happen when calling `android_hardware_buffer_properties`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AndroidHardwareBufferPropertiesError {
+    /// No memory available on the host.
+    OutOfHostMemory,
+
+    /// The provided external handle was not valid.
+    InvalidExternalHandle,
+
+    /// The `android_external_memory_android_hardware_buffer` extension was not enabled on the
+    /// device.
+    NotSupported,
+}
+
+impl error::Error for AndroidHardwareBufferPropertiesError {}
+
+impl fmt::Display for AndroidHardwareBufferPropertiesError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::OutOfHostMemory => write!(fmt, "no memory available on the host"),
+            Self::InvalidExternalHandle => {
+                write!(fmt, "the provided external handle was not valid")
+            }
+            Self::NotSupported => write!(
+                fmt,
+                "the `android_external_memory_android_hardware_buffer` extension was not enabled on the device",
+            ),
+        }
+    }
+}
+
+impl From<Error> for AndroidHardwareBufferPropertiesError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            Error::OutOfHostMemory => Self::OutOfHostMemory,
+            Error::InvalidExternalHandle => Self::InvalidExternalHandle,
+            _ => panic!("Unexpected error value: {}", err as i32),
+        }
+    }
+}
+
 /// Error that can happen when calling `memory_fd_properties`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MemoryFdPropertiesError {
@@ -966,6 +1519,41 @@ pub struct Queue {
 }
 
 impl Queue {
+    /// Creates a new `Queue` from a raw handle that was retrieved outside of vulkano.
+    ///
+    /// This is only needed for queues of a device that is shared with another library. The queues
+    /// of a device created with [`Device::new`] or [`Device::from_handle`] are returned by these
+    /// functions.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `family_index` is not the index of a queue family of the physical device.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be the queue with index `id` of the queue family `family_index` of
+    ///   `device`.
+    /// - Access to the queue must be externally synchronized with the other library, which must
+    ///   not use the queue while vulkano does.
+    pub unsafe fn from_handle(
+        device: Arc<Device>,
+        handle: ash::vk::Queue,
+        family_index: u32,
+        id: u32,
+    ) -> Arc<Queue> {
+        assert!(device
+            .physical_device()
+            .queue_family_by_id(family_index)
+            .is_some());
+
+        Arc::new(Queue {
+            handle: Mutex::new(handle),
+            device,
+            family: family_index,
+            id,
+        })
+    }
+
     /// Returns the device this queue belongs to.
     #[inline]
     pub fn device(&self) -> &Arc<Device> {
@@ -987,6 +1575,30 @@ impl Queue {
         self.id
     }
 
+    /// Binds memory to sparse resources, or unbinds it.
+    ///
+    /// The family of the queue must support sparse binding, and the
+    /// [`sparse_binding`](crate::device::Features::sparse_binding) feature must have been enabled
+    /// on the device to create the resources. The returned future must be flushed for the
+    /// operation to be submitted. Operations that are submitted after it wait until the binding
+    /// has been performed.
+    ///
+    /// > **Note**: This is just a shortcut for `now(device).then_bind_sparse(queue, bind_info)`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if one of the resources or memory in `bind_info` does not belong to the same
+    ///   device as the queue.
+    /// - Panics if the `size` of a buffer or opaque image bind, or the `extent` of an image bind,
+    ///   is zero.
+    #[inline]
+    pub fn bind_sparse(
+        self: &Arc<Self>,
+        bind_info: BindSparseInfo,
+    ) -> Result<BindSparseFuture<NowFuture>, BindSparseError> {
+        now(self.device.clone()).then_bind_sparse(self.clone(), bind_info)
+    }
+
     /// Waits until all work on this queue has finished.
     ///
     /// Just like `Device::wait()`, you shouldn't have to call this function in a typical program.
@@ -1049,6 +1661,11 @@ impl Queue {
             });
         }
 
+        // VUID-VkDebugUtilsLabelEXT-pLabelName-parameter
+        if label_info.label_name.contains('\0') {
+            return Err(DebugUtilsError::LabelNameContainsNul);
+        }
+
         Ok(())
     }
 
@@ -1142,6 +1759,74 @@ impl Queue {
             });
         }
 
+        // VUID-VkDebugUtilsLabelEXT-pLabelName-parameter
+        if label_info.label_name.contains('\0') {
+            return Err(DebugUtilsError::LabelNameContainsNul);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the last diagnostic checkpoints that were reached by the commands executing on
+    /// this queue.
+    ///
+    /// If the [`nv_device_diagnostic_checkpoints`](DeviceExtensions::nv_device_diagnostic_checkpoints)
+    /// extension is enabled on the device, command buffers that are built with
+    /// `AutoCommandBufferBuilder` set a checkpoint before each of their commands. After the
+    /// device has been lost, this can be used to find out which command was executing at the
+    /// time. Implementations typically report one checkpoint for the `top_of_pipe` stage, which
+    /// is the last command that started executing, and one for the `bottom_of_pipe` stage, which
+    /// is the last command that finished executing.
+    ///
+    /// # Safety
+    ///
+    /// - The device must be lost, which means that a previous operation must have returned a
+    ///   `DeviceLost` error.
+    pub unsafe fn diagnostic_checkpoints(
+        &self,
+    ) -> Result<Vec<DiagnosticCheckpoint>, DiagnosticCheckpointsError> {
+        self.validate_diagnostic_checkpoints()?;
+
+        let fns = self.device.fns();
+        let handle = self.handle.lock().unwrap();
+
+        let mut count = 0;
+        (fns.nv_device_diagnostic_checkpoints
+            .get_queue_checkpoint_data_nv)(*handle, &mut count, ptr::null_mut());
+
+        let mut checkpoint_data = vec![ash::vk::CheckpointDataNV::default(); count as usize];
+        (fns.nv_device_diagnostic_checkpoints
+            .get_queue_checkpoint_data_nv)(
+            *handle, &mut count, checkpoint_data.as_mut_ptr()
+        );
+        checkpoint_data.truncate(count as usize);
+
+        Ok(checkpoint_data
+            .into_iter()
+            .map(|data| DiagnosticCheckpoint {
+                stages: data.stage.into(),
+                command_name: self
+                    .device
+                    .checkpoint_command_name(data.p_checkpoint_marker as usize),
+            })
+            .collect())
+    }
+
+    fn validate_diagnostic_checkpoints(&self) -> Result<(), DiagnosticCheckpointsError> {
+        if !self
+            .device()
+            .enabled_extensions()
+            .nv_device_diagnostic_checkpoints
+        {
+            return Err(DiagnosticCheckpointsError::ExtensionNotEnabled {
+                extension: "nv_device_diagnostic_checkpoints",
+                reason: "tried to retrieve the diagnostic checkpoints of a queue",
+            });
+        }
+
+        // VUID-vkGetQueueCheckpointDataNV-queue-02025
+        // Can't be checked, so unsafe.
+
         Ok(())
     }
 }
@@ -1155,6 +1840,18 @@ unsafe impl SynchronizedVulkanObject for Queue {
     }
 }
 
+/// Returns a copy of the handle of the queue. Access to the queue must be synchronized by the
+/// caller, for example by holding the guard returned by
+/// [`internal_object_guard`](SynchronizedVulkanObject::internal_object_guard).
+unsafe impl VulkanObject for Queue {
+    type Object = ash::vk::Queue;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Queue {
+        *self.handle.lock().unwrap()
+    }
+}
+
 unsafe impl DeviceOwned for Queue {
     fn device(&self) -> &Arc<Device> {
         &self.device
@@ -1178,6 +1875,39 @@ impl Hash for Queue {
     }
 }
 
+/// A diagnostic checkpoint that was reached on a queue. See `Queue::diagnostic_checkpoints`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticCheckpoint {
+    /// The pipeline stages that the checkpoint was reached in.
+    pub stages: PipelineStages,
+
+    /// The name of the command that follows the checkpoint, or `None` if the checkpoint wasn't
+    /// set by vulkano.
+    pub command_name: Option<&'static str>,
+}
+
+/// Error that can happen when retrieving the diagnostic checkpoints of a queue.
+#[derive(Clone, Debug)]
+pub enum DiagnosticCheckpointsError {
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+}
+
+impl error::Error for DiagnosticCheckpointsError {}
+
+impl fmt::Display for DiagnosticCheckpointsError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::ExtensionNotEnabled { extension, reason } => {
+                write!(f, "the extension {} must be enabled: {}", extension, reason)
+            }
+        }
+    }
+}
+
 /// Error that can happen when submitting a debug utils command to a queue.
 #[derive(Clone, Debug)]
 pub enum DebugUtilsError {
@@ -1185,6 +1915,9 @@ pub enum DebugUtilsError {
         extension: &'static str,
         reason: &'static str,
     },
+
+    /// The name of the label contains a nul character.
+    LabelNameContainsNul,
 }
 
 impl error::Error for DebugUtilsError {}
@@ -1196,6 +1929,9 @@ impl fmt::Display for DebugUtilsError {
             Self::ExtensionNotEnabled { extension, reason } => {
                 write!(f, "the extension {} must be enabled: {}", extension, reason)
             }
+            Self::LabelNameContainsNul => {
+                write!(f, "the name of the label contains a nul character")
+            }
         }
     }
 }
@@ -1204,7 +1940,9 @@ impl fmt::Display for DebugUtilsError {
 mod tests {
     use crate::device::physical::PhysicalDevice;
     use crate::device::{Device, DeviceCreateInfo, DeviceCreationError, QueueCreateInfo};
-    use crate::device::{FeatureRestriction, FeatureRestrictionError, Features};
+    use crate::device::{
+        DiagnosticCheckpointsError, FeatureRestriction, FeatureRestrictionError, Features,
+    };
     use std::sync::Arc;
 
     #[test]
@@ -1307,4 +2045,54 @@ mod tests {
             )
         });
     }
+
+    #[test]
+    fn checkpoint_markers() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let copy = device.checkpoint_marker("vkCmdCopyBuffer");
+        let draw = device.checkpoint_marker("vkCmdDraw");
+        assert_ne!(copy, 0);
+        assert_ne!(copy, draw);
+        assert_eq!(device.checkpoint_marker("vkCmdCopyBuffer"), copy);
+
+        assert_eq!(
+            device.checkpoint_command_name(copy),
+            Some("vkCmdCopyBuffer")
+        );
+        assert_eq!(device.checkpoint_command_name(draw), Some("vkCmdDraw"));
+        assert_eq!(device.checkpoint_command_name(0), None);
+        assert_eq!(device.checkpoint_command_name(draw + 1), None);
+    }
+
+    #[test]
+    fn diagnostic_checkpoints_extension_not_enabled() {
+        let (_, queue) = gfx_dev_and_queue!();
+
+        assert!(matches!(
+            unsafe { queue.diagnostic_checkpoints() },
+            Err(DiagnosticCheckpointsError::ExtensionNotEnabled { .. })
+        ));
+    }
+
+    #[test]
+    fn take_statistics() {
+        use crate::sampler::{Sampler, SamplerCreateInfo};
+
+        let (device, _) = gfx_dev_and_queue!();
+        device.take_statistics();
+
+        let _samplers = [
+            Sampler::new(
+                device.clone(),
+                SamplerCreateInfo::simple_repeat_linear_no_mipmap(),
+            )
+            .unwrap(),
+            Sampler::new(device.clone(), SamplerCreateInfo::default()).unwrap(),
+        ];
+
+        assert_eq!(device.statistics().samplers_created, 2);
+        assert_eq!(device.take_statistics().samplers_created, 2);
+        assert_eq!(device.statistics(), Default::default());
+    }
 }
@@ -0,0 +1,65 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    sync::{Arc, Mutex, Weak},
+};
+
+/// Deduplicates objects that are created from identical parameters.
+///
+/// Only weak references are stored, so that the objects, which hold a reference to the device,
+/// don't keep the device alive. An object is destroyed as usual once it's no longer used, and
+/// creating it again afterwards creates a new object.
+pub(crate) struct ObjectCache<K, V> {
+    objects: Mutex<HashMap<K, Weak<V>>>,
+}
+
+impl<K, V> ObjectCache<K, V>
+where
+    K: Eq + Hash,
+{
+    pub(crate) fn new() -> Self {
+        ObjectCache {
+            objects: Mutex::new(HashMap::default()),
+        }
+    }
+
+    /// Returns the object stored for `key` if it's still alive, or creates it with `create`
+    /// otherwise.
+    pub(crate) fn get_or_try_insert<E>(
+        &self,
+        key: K,
+        create: impl FnOnce() -> Result<Arc<V>, E>,
+    ) -> Result<Arc<V>, E> {
+        // The lock is held during creation, so that two threads don't create the same object.
+        let mut objects = self.objects.lock().unwrap();
+
+        if let Some(object) = objects.get(&key).and_then(Weak::upgrade) {
+            return Ok(object);
+        }
+
+        let object = create()?;
+        objects.retain(|_, object| object.strong_count() != 0);
+        objects.insert(key, Arc::downgrade(&object));
+
+        Ok(object)
+    }
+}
+
+impl<K, V> fmt::Debug for ObjectCache<K, V> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("ObjectCache")
+            .field("len", &self.objects.lock().unwrap().len())
+            .finish()
+    }
+}
@@ -11,17 +11,30 @@ use crate::{
     buffer::{BufferUsage, ExternalBufferInfo, ExternalBufferProperties},
     check_errors,
     device::{DeviceExtensions, Features, FeaturesFfi, Properties, PropertiesFfi},
-    format::{Format, FormatProperties},
-    image::{ImageCreateFlags, ImageFormatInfo, ImageFormatProperties, ImageUsage},
+    format::{DrmFormatModifierProperties, Format, FormatProperties},
+    image::{
+        ImageCreateFlags, ImageFormatInfo, ImageFormatProperties, ImageTiling, ImageUsage,
+        SparseImageFormatInfo, SparseImageFormatProperties,
+    },
     instance::{Instance, InstanceCreationError},
+    memory::ExternalMemoryHandleType,
+    query::performance::PerformanceCounter,
     swapchain::{
-        ColorSpace, FullScreenExclusive, PresentMode, SupportedSurfaceTransforms, Surface,
-        SurfaceApi, SurfaceCapabilities, SurfaceInfo,
+        ColorSpace, FullScreenExclusive, PresentMode, SupportedPresentGravity,
+        SupportedPresentScaling, SupportedSurfaceTransforms, Surface, SurfaceApi,
+        SurfaceCapabilities, SurfaceInfo,
+    },
+    sync::{
+        ExternalFenceInfo, ExternalFenceProperties, ExternalSemaphoreInfo,
+        ExternalSemaphoreProperties, PipelineStage,
+    },
+    video::{
+        is_video_profile_unsupported, VideoCapabilities, VideoDecodeCodec, VideoProfile,
+        VideoProfilesVk,
     },
-    sync::{ExternalSemaphoreInfo, ExternalSemaphoreProperties, PipelineStage},
     DeviceSize, Error, OomError, Success, Version, VulkanObject,
 };
-use std::{error, ffi::CStr, fmt, hash::Hash, mem::MaybeUninit, ptr, sync::Arc};
+use std::{error, ffi::CStr, fmt, hash::Hash, mem::MaybeUninit, ops::BitOr, ptr, sync::Arc};
 
 #[derive(Clone, Debug)]
 pub(crate) struct PhysicalDeviceInfo {
@@ -360,6 +373,27 @@ impl<'a> PhysicalDevice<'a> {
             })
     }
 
+    /// Returns the physical device of `instance` that has the raw handle `handle`. Returns `None`
+    /// if there is no such physical device.
+    ///
+    /// This is useful when another API, such as OpenXR, chooses the physical device to use.
+    #[inline]
+    pub fn from_handle(
+        instance: &'a Arc<Instance>,
+        handle: ash::vk::PhysicalDevice,
+    ) -> Option<PhysicalDevice<'a>> {
+        instance
+            .physical_device_infos
+            .iter()
+            .enumerate()
+            .find(|(_, info)| info.handle == handle)
+            .map(|(index, info)| PhysicalDevice {
+                instance,
+                index,
+                info,
+            })
+    }
+
     /// Returns the instance corresponding to this physical device.
     ///
     /// # Example
@@ -549,6 +583,152 @@ impl<'a> PhysicalDevice<'a> {
         }
     }
 
+    /// Returns the Linux DRM format modifiers that are supported for images with a given format,
+    /// and their properties.
+    ///
+    /// An empty list is returned if the
+    /// [`ext_image_drm_format_modifier`](crate::device::DeviceExtensions::ext_image_drm_format_modifier)
+    /// extension is not supported by the physical device, or if the instance API version is less
+    /// than 1.1 and the
+    /// [`khr_get_physical_device_properties2`](crate::instance::InstanceExtensions::khr_get_physical_device_properties2)
+    /// extension is not enabled on the instance.
+    pub fn drm_format_modifier_properties(
+        &self,
+        format: Format,
+    ) -> Vec<DrmFormatModifierProperties> {
+        if !self.supported_extensions().ext_image_drm_format_modifier {
+            return Vec::new();
+        }
+
+        let get_format_properties2 = |format_properties2: &mut ash::vk::FormatProperties2| unsafe {
+            let fns = self.instance.fns();
+
+            if self.api_version() >= Version::V1_1 {
+                (fns.v1_1.get_physical_device_format_properties2)(
+                    self.info.handle,
+                    format.into(),
+                    format_properties2,
+                );
+            } else if self
+                .instance
+                .enabled_extensions()
+                .khr_get_physical_device_properties2
+            {
+                (fns.khr_get_physical_device_properties2
+                    .get_physical_device_format_properties2_khr)(
+                    self.info.handle,
+                    format.into(),
+                    format_properties2,
+                );
+            } else {
+                return false;
+            }
+
+            true
+        };
+
+        let mut properties_list = ash::vk::DrmFormatModifierPropertiesListEXT::default();
+        let mut format_properties2 = ash::vk::FormatProperties2 {
+            p_next: &mut properties_list as *mut _ as *mut _,
+            ..Default::default()
+        };
+
+        if !get_format_properties2(&mut format_properties2) {
+            // Can't query this, return unsupported
+            return Vec::new();
+        }
+
+        let mut properties = vec![
+            ash::vk::DrmFormatModifierPropertiesEXT::default();
+            properties_list.drm_format_modifier_count as usize
+        ];
+        properties_list.p_drm_format_modifier_properties = properties.as_mut_ptr();
+        format_properties2.p_next = &mut properties_list as *mut _ as *mut _;
+        get_format_properties2(&mut format_properties2);
+        properties.truncate(properties_list.drm_format_modifier_count as usize);
+
+        properties
+            .into_iter()
+            .map(|properties| DrmFormatModifierProperties {
+                drm_format_modifier: properties.drm_format_modifier,
+                drm_format_modifier_plane_count: properties.drm_format_modifier_plane_count,
+                drm_format_modifier_tiling_features: properties
+                    .drm_format_modifier_tiling_features
+                    .into(),
+            })
+            .collect()
+    }
+
+    /// Retrieves the external handle properties supported for fences with a given
+    /// configuration.
+    ///
+    /// Returns `None` if the instance API version is less than 1.1 and the
+    /// [`khr_external_fence_capabilities`](crate::instance::InstanceExtensions::khr_external_fence_capabilities)
+    /// extension is not enabled on the instance.
+    pub fn external_fence_properties(
+        &self,
+        info: ExternalFenceInfo,
+    ) -> Option<ExternalFenceProperties> {
+        if !(self.instance.api_version() >= Version::V1_1
+            || self
+                .instance
+                .enabled_extensions()
+                .khr_external_fence_capabilities)
+        {
+            return None;
+        }
+
+        /* Input */
+
+        let ExternalFenceInfo {
+            handle_type,
+            _ne: _,
+        } = info;
+
+        let external_fence_info = ash::vk::PhysicalDeviceExternalFenceInfo {
+            handle_type: handle_type.into(),
+            ..Default::default()
+        };
+
+        /* Output */
+
+        let mut external_fence_properties = ash::vk::ExternalFenceProperties::default();
+
+        /* Call */
+
+        unsafe {
+            let fns = self.instance.fns();
+
+            if self.instance.api_version() >= Version::V1_1 {
+                (fns.v1_1.get_physical_device_external_fence_properties)(
+                    self.info.handle,
+                    &external_fence_info,
+                    &mut external_fence_properties,
+                )
+            } else {
+                (fns.khr_external_fence_capabilities
+                    .get_physical_device_external_fence_properties_khr)(
+                    self.info.handle,
+                    &external_fence_info,
+                    &mut external_fence_properties,
+                );
+            }
+        }
+
+        Some(ExternalFenceProperties {
+            exportable: external_fence_properties
+                .external_fence_features
+                .intersects(ash::vk::ExternalFenceFeatureFlags::EXPORTABLE),
+            importable: external_fence_properties
+                .external_fence_features
+                .intersects(ash::vk::ExternalFenceFeatureFlags::IMPORTABLE),
+            export_from_imported_handle_types: external_fence_properties
+                .export_from_imported_handle_types
+                .into(),
+            compatible_handle_types: external_fence_properties.compatible_handle_types.into(),
+        })
+    }
+
     /// Retrieves the external handle properties supported for semaphores with a given
     /// configuration.
     ///
@@ -626,6 +806,8 @@ impl<'a> PhysicalDevice<'a> {
     /// # Panics
     ///
     /// - Panics if `image_format_info.format` is `None`.
+    /// - Panics if `image_format_info.tiling` is [`ImageTiling::DrmFormatModifier`] and
+    ///   `image_format_info.drm_format_modifier` is `None`.
     pub fn image_format_properties(
         &self,
         image_format_info: ImageFormatInfo,
@@ -641,14 +823,22 @@ impl<'a> PhysicalDevice<'a> {
             mutable_format,
             cube_compatible,
             array_2d_compatible,
+            view_2d_compatible,
             block_texel_view_compatible,
+            video_profiles,
+            drm_format_modifier,
+            sparse,
             _ne: _,
         } = image_format_info;
 
         let flags = ImageCreateFlags {
+            sparse_binding: sparse.is_some(),
+            sparse_residency: sparse.map_or(false, |sparse| sparse.sparse_residency),
+            sparse_aliased: sparse.map_or(false, |sparse| sparse.sparse_aliased),
             mutable_format,
             cube_compatible,
             array_2d_compatible,
+            view_2d_compatible,
             block_texel_view_compatible,
             ..ImageCreateFlags::none()
         };
@@ -706,6 +896,41 @@ impl<'a> PhysicalDevice<'a> {
             format_info2 = format_info2.push_next(next);
         }
 
+        let video_profiles_vk = VideoProfilesVk::new(&video_profiles);
+        let mut video_profile_list_info = if !video_profiles.is_empty() {
+            if !self.supported_extensions().khr_video_queue {
+                // Can't query this, return unsupported
+                return Ok(None);
+            }
+
+            Some(video_profiles_vk.profile_list_info())
+        } else {
+            None
+        };
+
+        if let Some(next) = video_profile_list_info.as_mut() {
+            format_info2 = format_info2.push_next(next);
+        }
+
+        let mut image_drm_format_modifier_info = if tiling == ImageTiling::DrmFormatModifier {
+            if !self.supported_extensions().ext_image_drm_format_modifier {
+                // Can't query this, return unsupported
+                return Ok(None);
+            }
+
+            Some(
+                ash::vk::PhysicalDeviceImageDrmFormatModifierInfoEXT::builder()
+                    .drm_format_modifier(drm_format_modifier.unwrap())
+                    .sharing_mode(ash::vk::SharingMode::EXCLUSIVE),
+            )
+        } else {
+            None
+        };
+
+        if let Some(next) = image_drm_format_modifier_info.as_mut() {
+            format_info2 = format_info2.push_next(next);
+        }
+
         /* Output */
 
         let mut image_format_properties2 = ash::vk::ImageFormatProperties2::default();
@@ -732,10 +957,26 @@ impl<'a> PhysicalDevice<'a> {
             image_format_properties2.p_next = next as *mut _ as *mut _;
         }
 
+        let mut android_hardware_buffer_usage = if external_memory_handle_type
+            == Some(ExternalMemoryHandleType::AndroidHardwareBuffer)
+            && self
+                .supported_extensions()
+                .android_external_memory_android_hardware_buffer
+        {
+            Some(ash::vk::AndroidHardwareBufferUsageANDROID::default())
+        } else {
+            None
+        };
+
+        if let Some(next) = android_hardware_buffer_usage.as_mut() {
+            next.p_next = image_format_properties2.p_next;
+            image_format_properties2.p_next = next as *mut _ as *mut _;
+        }
+
         let result = unsafe {
             let fns = self.instance.fns();
 
-            check_errors(if self.api_version() >= Version::V1_1 {
+            let result = if self.api_version() >= Version::V1_1 {
                 (fns.v1_1.get_physical_device_image_format_properties2)(
                     self.info.handle,
                     &format_info2.build(),
@@ -767,7 +1008,13 @@ impl<'a> PhysicalDevice<'a> {
                     format_info2.flags,
                     &mut image_format_properties2.image_format_properties,
                 )
-            })
+            };
+
+            if is_video_profile_unsupported(result) {
+                return Ok(None);
+            }
+
+            check_errors(result)
         };
 
         match result {
@@ -783,6 +1030,8 @@ impl<'a> PhysicalDevice<'a> {
                     .map_or(false, |properties| {
                         properties.filter_cubic_minmax != ash::vk::FALSE
                     }),
+                android_hardware_buffer_usage: android_hardware_buffer_usage
+                    .map(|usage| usage.android_hardware_buffer_usage),
                 ..image_format_properties2.image_format_properties.into()
             })),
             Err(Error::FormatNotSupported) => Ok(None),
@@ -790,6 +1039,251 @@ impl<'a> PhysicalDevice<'a> {
         }
     }
 
+    /// Returns the properties that are supported by the physical device for sparse images with a
+    /// given configuration.
+    ///
+    /// An empty list is returned if the configuration is not supported for sparse residency
+    /// images. Otherwise, there is one element for each group of aspects of the format that share
+    /// the same properties.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `sparse_image_format_info.format` is `None`.
+    pub fn sparse_image_format_properties(
+        &self,
+        sparse_image_format_info: SparseImageFormatInfo,
+    ) -> Vec<SparseImageFormatProperties> {
+        let SparseImageFormatInfo {
+            format,
+            image_type,
+            samples,
+            usage,
+            tiling,
+            _ne: _,
+        } = sparse_image_format_info;
+
+        let format_info2 = ash::vk::PhysicalDeviceSparseImageFormatInfo2 {
+            format: format.unwrap().into(),
+            ty: image_type.into(),
+            samples: samples.into(),
+            usage: usage.into(),
+            tiling: tiling.into(),
+            ..Default::default()
+        };
+
+        unsafe {
+            let fns = self.instance.fns();
+
+            if self.api_version() >= Version::V1_1
+                || self
+                    .instance
+                    .enabled_extensions()
+                    .khr_get_physical_device_properties2
+            {
+                let get_sparse_image_format_properties2 = if self.api_version() >= Version::V1_1 {
+                    fns.v1_1.get_physical_device_sparse_image_format_properties2
+                } else {
+                    fns.khr_get_physical_device_properties2
+                        .get_physical_device_sparse_image_format_properties2_khr
+                };
+
+                let mut count = 0;
+                get_sparse_image_format_properties2(
+                    self.info.handle,
+                    &format_info2,
+                    &mut count,
+                    ptr::null_mut(),
+                );
+
+                let mut properties =
+                    vec![ash::vk::SparseImageFormatProperties2::default(); count as usize];
+                get_sparse_image_format_properties2(
+                    self.info.handle,
+                    &format_info2,
+                    &mut count,
+                    properties.as_mut_ptr(),
+                );
+                properties.truncate(count as usize);
+
+                properties
+                    .into_iter()
+                    .map(|properties2| properties2.properties.into())
+                    .collect()
+            } else {
+                let mut count = 0;
+                (fns.v1_0.get_physical_device_sparse_image_format_properties)(
+                    self.info.handle,
+                    format_info2.format,
+                    format_info2.ty,
+                    format_info2.samples,
+                    format_info2.usage,
+                    format_info2.tiling,
+                    &mut count,
+                    ptr::null_mut(),
+                );
+
+                let mut properties =
+                    vec![ash::vk::SparseImageFormatProperties::default(); count as usize];
+                (fns.v1_0.get_physical_device_sparse_image_format_properties)(
+                    self.info.handle,
+                    format_info2.format,
+                    format_info2.ty,
+                    format_info2.samples,
+                    format_info2.usage,
+                    format_info2.tiling,
+                    &mut count,
+                    properties.as_mut_ptr(),
+                );
+                properties.truncate(count as usize);
+
+                properties.into_iter().map(Into::into).collect()
+            }
+        }
+    }
+
+    /// Returns the capabilities of the physical device for decoding video with the given profile.
+    ///
+    /// `Some` is returned if the profile is supported, `None` if it is not. `None` is also returned
+    /// if the physical device doesn't support the
+    /// [`khr_video_decode_queue`](crate::device::DeviceExtensions::khr_video_decode_queue)
+    /// extension, or the extension of the codec.
+    pub fn video_capabilities(
+        &self,
+        profile: &VideoProfile,
+    ) -> Result<Option<VideoCapabilities>, OomError> {
+        if !(self.supported_extensions().khr_video_queue
+            && self.supported_extensions().khr_video_decode_queue
+            && profile.codec.is_supported_by(self.supported_extensions()))
+        {
+            // Can't query this, return unsupported
+            return Ok(None);
+        }
+
+        let profiles_vk = VideoProfilesVk::new(std::slice::from_ref(profile));
+
+        let mut capabilities = ash::vk::VideoCapabilitiesKHR::default();
+        let mut decode_capabilities = ash::vk::VideoDecodeCapabilitiesKHR::default();
+        let mut h264_capabilities = ash::vk::VideoDecodeH264CapabilitiesKHR::default();
+        let mut h265_capabilities = ash::vk::VideoDecodeH265CapabilitiesKHR::default();
+
+        capabilities.p_next = &mut decode_capabilities as *mut _ as *mut _;
+        decode_capabilities.p_next = match profile.codec {
+            VideoDecodeCodec::H264 { .. } => &mut h264_capabilities as *mut _ as *mut _,
+            VideoDecodeCodec::H265 { .. } => &mut h265_capabilities as *mut _ as *mut _,
+        };
+
+        let result = unsafe {
+            let fns = self.instance.fns();
+            (fns.khr_video_queue
+                .get_physical_device_video_capabilities_khr)(
+                self.info.handle,
+                profiles_vk.profile_info(),
+                &mut capabilities,
+            )
+        };
+
+        if is_video_profile_unsupported(result) {
+            return Ok(None);
+        }
+
+        match check_errors(result) {
+            Ok(_) => (),
+            Err(Error::FormatNotSupported) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+
+        // Don't leave dangling pointers in the returned structures.
+        capabilities.p_next = ptr::null_mut();
+        decode_capabilities.p_next = ptr::null_mut();
+
+        let max_level_idc = match profile.codec {
+            VideoDecodeCodec::H264 { .. } => h264_capabilities.max_level_idc as u32,
+            VideoDecodeCodec::H265 { .. } => h265_capabilities.max_level_idc as u32,
+        };
+
+        Ok(Some(VideoCapabilities::from_vulkan(
+            &capabilities,
+            &decode_capabilities,
+            max_level_idc,
+        )))
+    }
+
+    /// Returns the formats that can be used for images with the given video profile and usage.
+    ///
+    /// `usage` must contain `video_decode_dst`, `video_decode_dpb` or both. An empty list is
+    /// returned if the profile is not supported.
+    pub fn video_formats(
+        &self,
+        profile: &VideoProfile,
+        usage: ImageUsage,
+    ) -> Result<Vec<Format>, OomError> {
+        if !(self.supported_extensions().khr_video_queue
+            && self.supported_extensions().khr_video_decode_queue
+            && profile.codec.is_supported_by(self.supported_extensions()))
+        {
+            // Can't query this, return unsupported
+            return Ok(Vec::new());
+        }
+
+        let profiles_vk = VideoProfilesVk::new(std::slice::from_ref(profile));
+        let profile_list_info = profiles_vk.profile_list_info();
+        let format_info = ash::vk::PhysicalDeviceVideoFormatInfoKHR {
+            p_next: &profile_list_info as *const _ as *const _,
+            image_usage: usage.into(),
+            ..Default::default()
+        };
+
+        let properties = unsafe {
+            let fns = self.instance.fns();
+
+            loop {
+                let mut count = 0;
+                let result = (fns
+                    .khr_video_queue
+                    .get_physical_device_video_format_properties_khr)(
+                    self.info.handle,
+                    &format_info,
+                    &mut count,
+                    ptr::null_mut(),
+                );
+
+                if is_video_profile_unsupported(result) {
+                    return Ok(Vec::new());
+                }
+
+                match check_errors(result) {
+                    Ok(_) => (),
+                    Err(Error::FormatNotSupported) => return Ok(Vec::new()),
+                    Err(err) => return Err(err.into()),
+                }
+
+                let mut properties =
+                    vec![ash::vk::VideoFormatPropertiesKHR::default(); count as usize];
+                let result = check_errors((fns
+                    .khr_video_queue
+                    .get_physical_device_video_format_properties_khr)(
+                    self.info.handle,
+                    &format_info,
+                    &mut count,
+                    properties.as_mut_ptr(),
+                ))?;
+
+                if !matches!(result, Success::Incomplete) {
+                    properties.truncate(count as usize);
+                    break properties;
+                }
+            }
+        };
+
+        let mut formats: Vec<Format> = properties
+            .into_iter()
+            .filter_map(|properties| properties.format.try_into().ok())
+            .collect();
+        formats.dedup();
+
+        Ok(formats)
+    }
+
     /// Builds an iterator that enumerates all the memory types on this physical device.
     #[inline]
     pub fn memory_types(&self) -> impl ExactSizeIterator<Item = MemoryType<'a>> {
@@ -848,6 +1342,115 @@ impl<'a> PhysicalDevice<'a> {
         }
     }
 
+    /// Builds an iterator that enumerates the memory types that are both device-local and
+    /// host-visible.
+    ///
+    /// Memory of these types is located on the device, but can be mapped and written directly by
+    /// the CPU, without going through a staging buffer. On discrete GPUs, the CPU can usually only
+    /// access a small window of 256 MiB of the device memory, unless resizable BAR is enabled. See
+    /// [`has_resizable_bar`](Self::has_resizable_bar).
+    #[inline]
+    pub fn host_visible_device_local_memory_types(&self) -> impl Iterator<Item = MemoryType<'a>> {
+        self.memory_types()
+            .filter(|memory_type| memory_type.is_device_local() && memory_type.is_host_visible())
+    }
+
+    /// Returns the total size of the heaps that contain memory types that are both device-local
+    /// and host-visible.
+    pub fn host_visible_device_local_memory_size(&self) -> DeviceSize {
+        let mut heaps = [false; ash::vk::MAX_MEMORY_HEAPS];
+
+        for memory_type in self.host_visible_device_local_memory_types() {
+            heaps[memory_type.heap().id() as usize] = true;
+        }
+
+        self.memory_heaps()
+            .filter(|heap| heaps[heap.id() as usize])
+            .map(|heap| heap.size())
+            .sum()
+    }
+
+    /// Returns true if the CPU can access all of the memory of the largest device-local heap.
+    ///
+    /// This is the case on discrete GPUs when resizable BAR is enabled, and on most integrated
+    /// GPUs. Data that is written by the CPU and read by the GPU can then be placed directly in
+    /// device-local memory instead of being copied through a staging buffer, by allocating it with
+    /// [`HostMemoryPreference::DeviceLocal`](crate::memory::pool::HostMemoryPreference::DeviceLocal).
+    pub fn has_resizable_bar(&self) -> bool {
+        let largest_heap = match self
+            .memory_heaps()
+            .filter(|heap| heap.is_device_local())
+            .max_by_key(|heap| heap.size())
+        {
+            Some(heap) => heap,
+            None => return false,
+        };
+
+        self.host_visible_device_local_memory_types()
+            .any(|memory_type| memory_type.heap().id() == largest_heap.id())
+    }
+
+    /// Returns the current memory budget and usage of each memory heap of this physical device,
+    /// indexed by the heap index.
+    ///
+    /// Unlike the other properties of the physical device, these values change over time, as the
+    /// application and other processes on the system allocate and free memory. They should be
+    /// queried again regularly, for example once per frame, before deciding whether more memory
+    /// can be allocated.
+    ///
+    /// Returns `None` if the
+    /// [`ext_memory_budget`](crate::device::DeviceExtensions::ext_memory_budget) extension is
+    /// not supported by the physical device, or if the instance API version is less than 1.1 and
+    /// the
+    /// [`khr_get_physical_device_properties2`](crate::instance::InstanceExtensions::khr_get_physical_device_properties2)
+    /// extension is not enabled on the instance.
+    pub fn memory_budget(&self) -> Option<Vec<MemoryHeapBudget>> {
+        if !self.supported_extensions().ext_memory_budget {
+            return None;
+        }
+
+        let mut memory_budget_properties =
+            ash::vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+        let mut memory_properties2 = ash::vk::PhysicalDeviceMemoryProperties2 {
+            p_next: &mut memory_budget_properties as *mut _ as *mut _,
+            ..Default::default()
+        };
+
+        unsafe {
+            let fns = self.instance.fns();
+
+            if self.api_version() >= Version::V1_1 {
+                (fns.v1_1.get_physical_device_memory_properties2)(
+                    self.info.handle,
+                    &mut memory_properties2,
+                );
+            } else if self
+                .instance
+                .enabled_extensions()
+                .khr_get_physical_device_properties2
+            {
+                (fns.khr_get_physical_device_properties2
+                    .get_physical_device_memory_properties2_khr)(
+                    self.info.handle,
+                    &mut memory_properties2,
+                );
+            } else {
+                // Can't query this, return unsupported
+                return None;
+            }
+        }
+
+        let heap_count = memory_properties2.memory_properties.memory_heap_count as usize;
+
+        Some(
+            memory_budget_properties.heap_budget[..heap_count]
+                .iter()
+                .zip(&memory_budget_properties.heap_usage[..heap_count])
+                .map(|(&budget, &usage)| MemoryHeapBudget { budget, usage })
+                .collect(),
+        )
+    }
+
     /// Builds an iterator that enumerates all the queue families on this physical device.
     #[inline]
     pub fn queue_families(&self) -> impl ExactSizeIterator<Item = QueueFamily<'a>> {
@@ -897,6 +1500,7 @@ impl<'a> PhysicalDevice<'a> {
         let SurfaceInfo {
             full_screen_exclusive,
             win32_monitor,
+            present_mode,
             _ne: _,
         } = surface_info;
 
@@ -933,6 +1537,24 @@ impl<'a> PhysicalDevice<'a> {
             }
         };
 
+        let mut surface_present_mode_info = if let Some(present_mode) = present_mode {
+            if !(self.instance.enabled_extensions().ext_surface_maintenance1
+                && self
+                    .instance
+                    .enabled_extensions()
+                    .khr_get_surface_capabilities2)
+            {
+                return Err(SurfacePropertiesError::NotSupported);
+            }
+
+            Some(ash::vk::SurfacePresentModeEXT {
+                present_mode: present_mode.into(),
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+
         let mut surface_info2 = ash::vk::PhysicalDeviceSurfaceInfo2KHR {
             surface: surface.internal_object(),
             ..Default::default()
@@ -952,6 +1574,11 @@ impl<'a> PhysicalDevice<'a> {
             surface_info2.p_next = surface_full_screen_exclusive_win32_info as *const _ as *const _;
         }
 
+        if let Some(surface_present_mode_info) = surface_present_mode_info.as_mut() {
+            surface_present_mode_info.p_next = surface_info2.p_next as *mut _;
+            surface_info2.p_next = surface_present_mode_info as *const _ as *const _;
+        }
+
         /* Output */
 
         let mut surface_capabilities2 = ash::vk::SurfaceCapabilities2KHR::default();
@@ -972,6 +1599,35 @@ impl<'a> PhysicalDevice<'a> {
                 surface_capabilities_full_screen_exclusive as *mut _ as *mut _;
         }
 
+        let mut surface_present_scaling_capabilities = if surface_present_mode_info.is_some() {
+            Some(ash::vk::SurfacePresentScalingCapabilitiesEXT::default())
+        } else {
+            None
+        };
+
+        if let Some(surface_present_scaling_capabilities) =
+            surface_present_scaling_capabilities.as_mut()
+        {
+            surface_present_scaling_capabilities.p_next = surface_capabilities2.p_next as *mut _;
+            surface_capabilities2.p_next = surface_present_scaling_capabilities as *mut _ as *mut _;
+        }
+
+        // The compatible present modes are queried in two steps: first their number, then the
+        // modes themselves.
+        let mut compatible_present_modes_vk: Vec<ash::vk::PresentModeKHR> = Vec::new();
+        let mut surface_present_mode_compatibility = if surface_present_mode_info.is_some() {
+            Some(ash::vk::SurfacePresentModeCompatibilityEXT::default())
+        } else {
+            None
+        };
+
+        if let Some(surface_present_mode_compatibility) =
+            surface_present_mode_compatibility.as_mut()
+        {
+            surface_present_mode_compatibility.p_next = surface_capabilities2.p_next as *mut _;
+            surface_capabilities2.p_next = surface_present_mode_compatibility as *mut _ as *mut _;
+        }
+
         unsafe {
             let fns = self.instance.fns();
 
@@ -987,6 +1643,29 @@ impl<'a> PhysicalDevice<'a> {
                     &surface_info2,
                     &mut surface_capabilities2,
                 ))?;
+
+                if let Some(surface_present_mode_compatibility) =
+                    surface_present_mode_compatibility.as_mut()
+                {
+                    compatible_present_modes_vk = vec![
+                        ash::vk::PresentModeKHR::default();
+                        surface_present_mode_compatibility.present_mode_count
+                            as usize
+                    ];
+                    surface_present_mode_compatibility.p_present_modes =
+                        compatible_present_modes_vk.as_mut_ptr();
+
+                    check_errors((fns
+                        .khr_get_surface_capabilities2
+                        .get_physical_device_surface_capabilities2_khr)(
+                        self.internal_object(),
+                        &surface_info2,
+                        &mut surface_capabilities2,
+                    ))?;
+
+                    compatible_present_modes_vk
+                        .truncate(surface_present_mode_compatibility.present_mode_count as usize);
+                }
             } else {
                 check_errors((fns
                     .khr_surface
@@ -1079,6 +1758,40 @@ impl<'a> PhysicalDevice<'a> {
 
             full_screen_exclusive_supported: surface_capabilities_full_screen_exclusive
                 .map_or(false, |c| c.full_screen_exclusive_supported != 0),
+
+            supported_present_scaling: surface_present_scaling_capabilities
+                .map_or(SupportedPresentScaling::none(), |c| {
+                    c.supported_present_scaling.into()
+                }),
+            supported_present_gravity: surface_present_scaling_capabilities.map_or(
+                [SupportedPresentGravity::none(); 2],
+                |c| {
+                    [
+                        c.supported_present_gravity_x.into(),
+                        c.supported_present_gravity_y.into(),
+                    ]
+                },
+            ),
+            min_scaled_image_extent: surface_present_scaling_capabilities
+                .filter(|c| !c.supported_present_scaling.is_empty())
+                .map(|c| {
+                    [
+                        c.min_scaled_image_extent.width,
+                        c.min_scaled_image_extent.height,
+                    ]
+                }),
+            max_scaled_image_extent: surface_present_scaling_capabilities
+                .filter(|c| !c.supported_present_scaling.is_empty())
+                .map(|c| {
+                    [
+                        c.max_scaled_image_extent.width,
+                        c.max_scaled_image_extent.height,
+                    ]
+                }),
+            compatible_present_modes: compatible_present_modes_vk
+                .into_iter()
+                .filter_map(|mode_vk| mode_vk.try_into().ok())
+                .collect(),
         })
     }
 
@@ -1106,6 +1819,7 @@ impl<'a> PhysicalDevice<'a> {
             let SurfaceInfo {
                 full_screen_exclusive,
                 win32_monitor,
+                present_mode: _,
                 _ne: _,
             } = surface_info;
 
@@ -1480,6 +2194,33 @@ impl<'a> MemoryHeap<'a> {
     }
 }
 
+/// The memory budget and usage of a memory heap.
+///
+/// Returned by [`PhysicalDevice::memory_budget`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MemoryHeapBudget {
+    /// An estimate of how many bytes the current process can allocate from the heap before
+    /// allocations fail or cause a performance degradation.
+    ///
+    /// This takes into account the memory that is used by other processes, and can therefore be
+    /// less than the size of the heap. It can be less than `usage` if other processes started
+    /// using more memory.
+    pub budget: DeviceSize,
+
+    /// An estimate of how many bytes of the heap are currently used by the current process. This
+    /// includes memory that is used internally by the driver.
+    pub usage: DeviceSize,
+}
+
+impl MemoryHeapBudget {
+    /// Returns the number of bytes that can still be allocated before `usage` reaches `budget`,
+    /// or 0 if the budget is already exceeded.
+    #[inline]
+    pub fn available(&self) -> DeviceSize {
+        self.budget.saturating_sub(self.usage)
+    }
+}
+
 /// Represents a queue family in a physical device.
 ///
 /// A queue family is group of one or multiple queues. All queues of one family have the same
@@ -1533,6 +2274,12 @@ impl<'a> QueueFamily<'a> {
         [granularity.width, granularity.height, granularity.depth]
     }
 
+    /// Returns the capabilities of the queues of this family.
+    #[inline]
+    pub fn queue_flags(&self) -> QueueFlags {
+        self.properties.queue_flags.into()
+    }
+
     /// Returns `true` if queues of this family can execute graphics operations.
     #[inline]
     pub fn supports_graphics(&self) -> bool {
@@ -1569,6 +2316,45 @@ impl<'a> QueueFamily<'a> {
             .contains(ash::vk::QueueFlags::SPARSE_BINDING)
     }
 
+    /// Returns `true` if queues of this family can execute protected operations, and queues can be
+    /// created from it with the `protected` flag.
+    #[inline]
+    pub fn supports_protected(&self) -> bool {
+        self.properties
+            .queue_flags
+            .contains(ash::vk::QueueFlags::PROTECTED)
+    }
+
+    /// Returns `true` if queues of this family can execute video decode operations.
+    ///
+    /// This requires the `khr_video_decode_queue` extension to be supported.
+    #[inline]
+    pub fn supports_video_decode(&self) -> bool {
+        self.properties
+            .queue_flags
+            .contains(ash::vk::QueueFlags::VIDEO_DECODE_KHR)
+    }
+
+    /// Returns `true` if queues of this family can execute video encode operations.
+    ///
+    /// This requires the `khr_video_encode_queue` extension to be supported.
+    #[inline]
+    pub fn supports_video_encode(&self) -> bool {
+        self.properties
+            .queue_flags
+            .contains(ash::vk::QueueFlags::VIDEO_ENCODE_KHR)
+    }
+
+    /// Returns `true` if queues of this family can execute optical flow operations.
+    ///
+    /// This requires the `nv_optical_flow` extension to be supported.
+    #[inline]
+    pub fn supports_optical_flow(&self) -> bool {
+        self.properties
+            .queue_flags
+            .contains(ash::vk::QueueFlags::OPTICAL_FLOW_NV)
+    }
+
     /// Returns `true` if the queues of this family support a particular pipeline stage.
     #[inline]
     pub fn supports_stage(&self, stage: PipelineStage) -> bool {
@@ -1577,6 +2363,105 @@ impl<'a> QueueFamily<'a> {
             .contains(stage.required_queue_flags())
     }
 
+    /// Returns the hardware performance counters that can be queried on queues of this family.
+    ///
+    /// The index of a counter in the returned `Vec` is the index that is used to select it in
+    /// [`QueryPoolCreateInfo::performance_counter_indices`].
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the physical device does not support the `khr_performance_query` extension.
+    ///
+    /// [`QueryPoolCreateInfo::performance_counter_indices`]: crate::query::QueryPoolCreateInfo::performance_counter_indices
+    pub fn performance_counters(&self) -> Result<Vec<PerformanceCounter>, OomError> {
+        assert!(
+            self.physical_device
+                .supported_extensions()
+                .khr_performance_query
+        );
+
+        let fns = self.physical_device.instance.fns();
+
+        let (counters, descriptions) = unsafe {
+            loop {
+                let mut count = 0;
+                check_errors(
+                    (fns.khr_performance_query
+                        .enumerate_physical_device_queue_family_performance_query_counters_khr)(
+                        self.physical_device.internal_object(),
+                        self.id,
+                        &mut count,
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                    ),
+                )?;
+
+                let mut counters = vec![ash::vk::PerformanceCounterKHR::default(); count as usize];
+                let mut descriptions =
+                    vec![ash::vk::PerformanceCounterDescriptionKHR::default(); count as usize];
+                let result = check_errors((fns
+                    .khr_performance_query
+                    .enumerate_physical_device_queue_family_performance_query_counters_khr)(
+                    self.physical_device.internal_object(),
+                    self.id,
+                    &mut count,
+                    counters.as_mut_ptr(),
+                    descriptions.as_mut_ptr(),
+                ))?;
+
+                if !matches!(result, Success::Incomplete) {
+                    counters.truncate(count as usize);
+                    descriptions.truncate(count as usize);
+                    break (counters, descriptions);
+                }
+            }
+        };
+
+        Ok(counters
+            .iter()
+            .zip(descriptions.iter())
+            .map(|(counter, description)| PerformanceCounter::from_vulkan(counter, description))
+            .collect())
+    }
+
+    /// Returns the number of passes that are needed to query all of the given performance
+    /// counters on queues of this family.
+    ///
+    /// A command buffer that uses a performance query pool with these counters must be submitted
+    /// once for each pass.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the physical device does not support the `khr_performance_query` extension.
+    /// - Panics if `counter_indices` is empty.
+    pub fn performance_query_passes(&self, counter_indices: &[u32]) -> u32 {
+        assert!(
+            self.physical_device
+                .supported_extensions()
+                .khr_performance_query
+        );
+        assert!(!counter_indices.is_empty());
+
+        let info = ash::vk::QueryPoolPerformanceCreateInfoKHR {
+            queue_family_index: self.id,
+            counter_index_count: counter_indices.len() as u32,
+            p_counter_indices: counter_indices.as_ptr(),
+            ..Default::default()
+        };
+
+        unsafe {
+            let fns = self.physical_device.instance.fns();
+            let mut num_passes = 0;
+            (fns.khr_performance_query
+                .get_physical_device_queue_family_performance_query_passes_khr)(
+                self.physical_device.internal_object(),
+                &info,
+                &mut num_passes,
+            );
+            num_passes
+        }
+    }
+
     /// Returns whether queues of this family can draw on the given surface.
     pub fn supports_surface<W>(
         &self,
@@ -1777,6 +2662,178 @@ impl From<ash::vk::ShaderCorePropertiesFlagsAMD> for ShaderCoreProperties {
     }
 }
 
+/// Specifies which memory decompression methods are supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryDecompressionMethods {
+    pub gdeflate_1_0: bool,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl From<ash::vk::MemoryDecompressionMethodFlagsNV> for MemoryDecompressionMethods {
+    #[inline]
+    fn from(val: ash::vk::MemoryDecompressionMethodFlagsNV) -> Self {
+        Self {
+            gdeflate_1_0: val.intersects(ash::vk::MemoryDecompressionMethodFlagsNV::GDEFLATE_1_0),
+
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Specifies which grid sizes are supported for optical flow images.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpticalFlowGridSizes {
+    pub size_1x1: bool,
+    pub size_2x2: bool,
+    pub size_4x4: bool,
+    pub size_8x8: bool,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl From<ash::vk::OpticalFlowGridSizeFlagsNV> for OpticalFlowGridSizes {
+    #[inline]
+    fn from(val: ash::vk::OpticalFlowGridSizeFlagsNV) -> Self {
+        Self {
+            size_1x1: val.intersects(ash::vk::OpticalFlowGridSizeFlagsNV::TYPE_1X1),
+            size_2x2: val.intersects(ash::vk::OpticalFlowGridSizeFlagsNV::TYPE_2X2),
+            size_4x4: val.intersects(ash::vk::OpticalFlowGridSizeFlagsNV::TYPE_4X4),
+            size_8x8: val.intersects(ash::vk::OpticalFlowGridSizeFlagsNV::TYPE_8X8),
+
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Specifies a set of queue capabilities.
+///
+/// # Example
+///
+/// ```
+/// use vulkano::device::physical::{PhysicalDevice, QueueFlags};
+///
+/// # let physical_device: PhysicalDevice = return;
+/// let required = QueueFlags {
+///     graphics: true,
+///     video_decode: true,
+///     ..QueueFlags::none()
+/// };
+///
+/// // Checks whether the physical device has queues for all of the required capabilities,
+/// // possibly spread over several queue families.
+/// let supported = physical_device
+///     .queue_families()
+///     .fold(QueueFlags::none(), |flags, family| flags | family.queue_flags());
+/// let suitable = supported.is_superset_of(&required);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueFlags {
+    pub graphics: bool,
+    pub compute: bool,
+    pub transfer: bool,
+    pub sparse_binding: bool,
+    pub protected: bool,
+    pub video_decode: bool,
+    pub video_encode: bool,
+    pub optical_flow: bool,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl From<ash::vk::QueueFlags> for QueueFlags {
+    #[inline]
+    fn from(val: ash::vk::QueueFlags) -> Self {
+        Self {
+            graphics: val.intersects(ash::vk::QueueFlags::GRAPHICS),
+            compute: val.intersects(ash::vk::QueueFlags::COMPUTE),
+            transfer: val.intersects(ash::vk::QueueFlags::TRANSFER),
+            sparse_binding: val.intersects(ash::vk::QueueFlags::SPARSE_BINDING),
+            protected: val.intersects(ash::vk::QueueFlags::PROTECTED),
+            video_decode: val.intersects(ash::vk::QueueFlags::VIDEO_DECODE_KHR),
+            video_encode: val.intersects(ash::vk::QueueFlags::VIDEO_ENCODE_KHR),
+            optical_flow: val.intersects(ash::vk::QueueFlags::OPTICAL_FLOW_NV),
+
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl QueueFlags {
+    /// Builds a `QueueFlags` with all values set to false.
+    #[inline]
+    pub const fn none() -> QueueFlags {
+        QueueFlags {
+            graphics: false,
+            compute: false,
+            transfer: false,
+            sparse_binding: false,
+            protected: false,
+            video_decode: false,
+            video_encode: false,
+            optical_flow: false,
+
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    /// Returns whether all the flags that are set in `other` are also set in `self`.
+    #[inline]
+    pub const fn is_superset_of(&self, other: &QueueFlags) -> bool {
+        (self.graphics || !other.graphics)
+            && (self.compute || !other.compute)
+            && (self.transfer || !other.transfer)
+            && (self.sparse_binding || !other.sparse_binding)
+            && (self.protected || !other.protected)
+            && (self.video_decode || !other.video_decode)
+            && (self.video_encode || !other.video_encode)
+            && (self.optical_flow || !other.optical_flow)
+    }
+}
+
+impl BitOr for QueueFlags {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        QueueFlags {
+            graphics: self.graphics || rhs.graphics,
+            compute: self.compute || rhs.compute,
+            transfer: self.transfer || rhs.transfer,
+            sparse_binding: self.sparse_binding || rhs.sparse_binding,
+            protected: self.protected || rhs.protected,
+            video_decode: self.video_decode || rhs.video_decode,
+            video_encode: self.video_encode || rhs.video_encode,
+            optical_flow: self.optical_flow || rhs.optical_flow,
+
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Specifies the reordering behavior of ray tracing invocations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum RayTracingInvocationReorderMode {
+    /// The implementation does not reorder invocations.
+    None = ash::vk::RayTracingInvocationReorderModeNV::NONE.as_raw(),
+    /// The implementation may reorder invocations.
+    Reorder = ash::vk::RayTracingInvocationReorderModeNV::REORDER.as_raw(),
+}
+
+impl TryFrom<ash::vk::RayTracingInvocationReorderModeNV> for RayTracingInvocationReorderMode {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::RayTracingInvocationReorderModeNV) -> Result<Self, Self::Error> {
+        match val {
+            ash::vk::RayTracingInvocationReorderModeNV::NONE => Ok(Self::None),
+            ash::vk::RayTracingInvocationReorderModeNV::REORDER => Ok(Self::Reorder),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Error that can happen when retrieving properties of a surface.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
@@ -1,10 +1,12 @@
 use crate::device::physical::{
-    ConformanceVersion, DriverId, PhysicalDeviceType, PointClippingBehavior, ShaderCoreProperties,
-    ShaderFloatControlsIndependence, SubgroupFeatures,
+    ConformanceVersion, DriverId, MemoryDecompressionMethods, OpticalFlowGridSizes,
+    PhysicalDeviceType, PointClippingBehavior, QueueFlags, RayTracingInvocationReorderMode,
+    ShaderCoreProperties, ShaderFloatControlsIndependence, SubgroupFeatures,
 };
 use crate::device::DeviceExtensions;
 use crate::image::{SampleCount, SampleCounts};
 use crate::instance::InstanceExtensions;
+use crate::pipeline::{PipelineRobustnessBufferBehavior, PipelineRobustnessImageBehavior};
 use crate::render_pass::ResolveModes;
 use crate::shader::ShaderStages;
 use crate::DeviceSize;
@@ -136,6 +138,20 @@ impl FromVulkan<ash::vk::Extent2D> for [u32; 2] {
     }
 }
 
+impl FromVulkan<ash::vk::MemoryDecompressionMethodFlagsNV> for MemoryDecompressionMethods {
+    #[inline]
+    fn from_vulkan(val: ash::vk::MemoryDecompressionMethodFlagsNV) -> Option<Self> {
+        Some(val.into())
+    }
+}
+
+impl FromVulkan<ash::vk::OpticalFlowGridSizeFlagsNV> for OpticalFlowGridSizes {
+    #[inline]
+    fn from_vulkan(val: ash::vk::OpticalFlowGridSizeFlagsNV) -> Option<Self> {
+        Some(val.into())
+    }
+}
+
 impl FromVulkan<ash::vk::PhysicalDeviceType> for PhysicalDeviceType {
     #[inline]
     fn from_vulkan(val: ash::vk::PhysicalDeviceType) -> Option<Self> {
@@ -150,6 +166,34 @@ impl FromVulkan<ash::vk::PointClippingBehavior> for PointClippingBehavior {
     }
 }
 
+impl FromVulkan<ash::vk::PipelineRobustnessBufferBehaviorEXT> for PipelineRobustnessBufferBehavior {
+    #[inline]
+    fn from_vulkan(val: ash::vk::PipelineRobustnessBufferBehaviorEXT) -> Option<Self> {
+        val.try_into().ok()
+    }
+}
+
+impl FromVulkan<ash::vk::PipelineRobustnessImageBehaviorEXT> for PipelineRobustnessImageBehavior {
+    #[inline]
+    fn from_vulkan(val: ash::vk::PipelineRobustnessImageBehaviorEXT) -> Option<Self> {
+        val.try_into().ok()
+    }
+}
+
+impl FromVulkan<ash::vk::QueueFlags> for QueueFlags {
+    #[inline]
+    fn from_vulkan(val: ash::vk::QueueFlags) -> Option<Self> {
+        Some(val.into())
+    }
+}
+
+impl FromVulkan<ash::vk::RayTracingInvocationReorderModeNV> for RayTracingInvocationReorderMode {
+    #[inline]
+    fn from_vulkan(val: ash::vk::RayTracingInvocationReorderModeNV) -> Option<Self> {
+        val.try_into().ok()
+    }
+}
+
 impl FromVulkan<ash::vk::ResolveModeFlags> for ResolveModes {
     #[inline]
     fn from_vulkan(val: ash::vk::ResolveModeFlags) -> Option<Self> {
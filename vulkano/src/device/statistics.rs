@@ -0,0 +1,117 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counts of cache lookups and object creations on a device.
+///
+/// These are meant for checking that caching layers work as intended. Once an application has
+/// warmed up, a cache that deduplicates layouts or samplers should keep the number of created
+/// layouts and samplers at zero from one frame to the next, and most pipelines should be found
+/// in the pipeline cache.
+///
+/// Returned by [`Device::statistics`](super::Device::statistics) and
+/// [`Device::take_statistics`](super::Device::take_statistics).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeviceStatistics {
+    /// The number of pipelines that were found in a pipeline cache when they were created.
+    ///
+    /// Only pipelines that were created with cache statistics enabled are counted. See
+    /// [`GraphicsPipelineBuilder::record_cache_statistics`].
+    ///
+    /// [`GraphicsPipelineBuilder::record_cache_statistics`]: crate::pipeline::graphics::GraphicsPipelineBuilder::record_cache_statistics
+    pub pipeline_cache_hits: u64,
+
+    /// The number of pipelines that were not found in a pipeline cache when they were created.
+    ///
+    /// Only pipelines that were created with cache statistics enabled are counted.
+    pub pipeline_cache_misses: u64,
+
+    /// The number of descriptor set layouts that were created.
+    pub descriptor_set_layouts_created: u64,
+
+    /// The number of pipeline layouts that were created.
+    pub pipeline_layouts_created: u64,
+
+    /// The number of samplers that were created.
+    pub samplers_created: u64,
+
+    /// The number of descriptor sets that were allocated from descriptor pools.
+    pub descriptor_sets_allocated: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StatisticsCounters {
+    pipeline_cache_hits: AtomicU64,
+    pipeline_cache_misses: AtomicU64,
+    descriptor_set_layouts_created: AtomicU64,
+    pipeline_layouts_created: AtomicU64,
+    samplers_created: AtomicU64,
+    descriptor_sets_allocated: AtomicU64,
+}
+
+impl StatisticsCounters {
+    #[inline]
+    pub(crate) fn record_pipeline_cache_lookup(&self, hit: bool) {
+        if hit {
+            self.pipeline_cache_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.pipeline_cache_misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record_descriptor_set_layout_created(&self) {
+        self.descriptor_set_layouts_created
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_pipeline_layout_created(&self) {
+        self.pipeline_layouts_created
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_sampler_created(&self) {
+        self.samplers_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_descriptor_sets_allocated(&self, count: u64) {
+        self.descriptor_sets_allocated
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn load(&self) -> DeviceStatistics {
+        DeviceStatistics {
+            pipeline_cache_hits: self.pipeline_cache_hits.load(Ordering::Relaxed),
+            pipeline_cache_misses: self.pipeline_cache_misses.load(Ordering::Relaxed),
+            descriptor_set_layouts_created: self
+                .descriptor_set_layouts_created
+                .load(Ordering::Relaxed),
+            pipeline_layouts_created: self.pipeline_layouts_created.load(Ordering::Relaxed),
+            samplers_created: self.samplers_created.load(Ordering::Relaxed),
+            descriptor_sets_allocated: self.descriptor_sets_allocated.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn take(&self) -> DeviceStatistics {
+        DeviceStatistics {
+            pipeline_cache_hits: self.pipeline_cache_hits.swap(0, Ordering::Relaxed),
+            pipeline_cache_misses: self.pipeline_cache_misses.swap(0, Ordering::Relaxed),
+            descriptor_set_layouts_created: self
+                .descriptor_set_layouts_created
+                .swap(0, Ordering::Relaxed),
+            pipeline_layouts_created: self.pipeline_layouts_created.swap(0, Ordering::Relaxed),
+            samplers_created: self.samplers_created.swap(0, Ordering::Relaxed),
+            descriptor_sets_allocated: self.descriptor_sets_allocated.swap(0, Ordering::Relaxed),
+        }
+    }
+}
@@ -701,6 +701,25 @@ impl From<(f32, u32)> for ClearDepthStencilValue {
     }
 }
 
+/// The properties of a Linux DRM format modifier that are supported by a physical device, for a
+/// particular format.
+///
+/// Returned by
+/// [`PhysicalDevice::drm_format_modifier_properties`](crate::device::physical::PhysicalDevice::drm_format_modifier_properties).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct DrmFormatModifierProperties {
+    /// The DRM format modifier, as defined in the Linux `drm_fourcc.h` header.
+    pub drm_format_modifier: u64,
+
+    /// The number of memory planes of images with this format and modifier. This can be
+    /// different from the number of planes of the format.
+    pub drm_format_modifier_plane_count: u32,
+
+    /// Features available for images with this format and modifier.
+    pub drm_format_modifier_tiling_features: FormatFeatures,
+}
+
 /// The properties of a format that are supported by a physical device.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct FormatProperties {
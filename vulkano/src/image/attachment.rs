@@ -8,8 +8,11 @@
 // according to those terms.
 
 use super::{
-    sys::UnsafeImage, traits::ImageContent, ImageAccess, ImageCreationError,
-    ImageDescriptorLayouts, ImageInner, ImageLayout, ImageUsage, SampleCount,
+    sys::UnsafeImage,
+    traits::ImageContent,
+    view::{ImageView, ImageViewCreationError},
+    ImageAccess, ImageCreationError, ImageDescriptorLayouts, ImageInner, ImageLayout, ImageUsage,
+    SampleCount,
 };
 use crate::{
     device::{Device, DeviceOwned},
@@ -23,9 +26,10 @@ use crate::{
         DedicatedAllocation, DeviceMemoryExportError, ExternalMemoryHandleType,
         ExternalMemoryHandleTypes, MemoryPool,
     },
-    DeviceSize,
+    DeviceSize, VulkanObject,
 };
 use std::{
+    error, fmt,
     fs::File,
     hash::{Hash, Hasher},
     sync::{
@@ -623,6 +627,15 @@ unsafe impl<A> DeviceOwned for AttachmentImage<A> {
     }
 }
 
+unsafe impl<A> VulkanObject for AttachmentImage<A> {
+    type Object = ash::vk::Image;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Image {
+        self.image.internal_object()
+    }
+}
+
 unsafe impl<P, A> ImageContent<P> for AttachmentImage<A>
 where
     A: MemoryPoolAlloc,
@@ -655,10 +668,229 @@ where
     }
 }
 
+/// Owns attachment images whose size follows the size of a window, such as depth buffers,
+/// g-buffers and intermediate render targets, and recreates them all when the window is resized.
+///
+/// Each attachment is added once with its format, usage and sample count, and is identified by
+/// the returned `AttachmentId`. After the swapchain has been recreated, a single call to `resize`
+/// recreates the images that have the wrong size, after which `view` returns the new views.
+///
+/// ```
+/// use vulkano::format::Format;
+/// use vulkano::image::{attachment::AttachmentPool, ImageUsage, SampleCount};
+///
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+/// let mut pool = AttachmentPool::new(device, [1024, 768]);
+/// let depth = pool
+///     .add(Format::D16_UNORM, ImageUsage::depth_stencil_attachment(), SampleCount::Sample1)
+///     .unwrap();
+///
+/// // When the swapchain has been recreated:
+/// if pool.resize([1280, 720]).unwrap() {
+///     // Recreate the framebuffers using `pool.view(depth)`.
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AttachmentPool {
+    device: Arc<Device>,
+    extent: [u32; 2],
+    attachments: Vec<Option<PoolAttachment>>,
+}
+
+#[derive(Debug)]
+struct PoolAttachment {
+    format: Format,
+    usage: ImageUsage,
+    samples: SampleCount,
+    view: Arc<ImageView<AttachmentImage>>,
+}
+
+impl AttachmentPool {
+    /// Creates an empty pool whose attachments will have the extent `extent`.
+    #[inline]
+    pub fn new(device: Arc<Device>, extent: [u32; 2]) -> AttachmentPool {
+        AttachmentPool {
+            device,
+            extent,
+            attachments: Vec::new(),
+        }
+    }
+
+    /// Returns the extent of the attachments.
+    #[inline]
+    pub fn extent(&self) -> [u32; 2] {
+        self.extent
+    }
+
+    /// Creates an attachment image with the current extent of the pool.
+    pub fn add(
+        &mut self,
+        format: Format,
+        usage: ImageUsage,
+        samples: SampleCount,
+    ) -> Result<AttachmentId, AttachmentPoolError> {
+        let view = Self::create_view(self.device.clone(), self.extent, format, usage, samples)?;
+        let attachment = PoolAttachment {
+            format,
+            usage,
+            samples,
+            view,
+        };
+
+        let index = match self.attachments.iter().position(Option::is_none) {
+            Some(index) => {
+                self.attachments[index] = Some(attachment);
+                index
+            }
+            None => {
+                self.attachments.push(Some(attachment));
+                self.attachments.len() - 1
+            }
+        };
+
+        Ok(AttachmentId(index))
+    }
+
+    /// Removes an attachment from the pool. The image is destroyed once it's no longer used.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `id` was already removed.
+    #[inline]
+    pub fn remove(&mut self, id: AttachmentId) {
+        assert!(
+            self.attachments[id.0].take().is_some(),
+            "the attachment was already removed"
+        );
+    }
+
+    /// Returns the view of the current image of an attachment.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `id` was removed.
+    #[inline]
+    pub fn view(&self, id: AttachmentId) -> Arc<ImageView<AttachmentImage>> {
+        self.attachments[id.0]
+            .as_ref()
+            .expect("the attachment was removed")
+            .view
+            .clone()
+    }
+
+    /// Recreates all the attachment images with the extent `extent`, if it's different from the
+    /// current extent. Returns whether the images were recreated.
+    ///
+    /// If creating an image fails, the images that weren't recreated yet keep the old extent, and
+    /// calling `resize` again will try to recreate them.
+    pub fn resize(&mut self, extent: [u32; 2]) -> Result<bool, AttachmentPoolError> {
+        if extent == self.extent
+            && self
+                .attachments
+                .iter()
+                .flatten()
+                .all(|attachment| attachment.view.image().dimensions().width_height() == extent)
+        {
+            return Ok(false);
+        }
+
+        self.extent = extent;
+
+        for attachment in self.attachments.iter_mut().flatten() {
+            if attachment.view.image().dimensions().width_height() == extent {
+                continue;
+            }
+
+            attachment.view = Self::create_view(
+                self.device.clone(),
+                extent,
+                attachment.format,
+                attachment.usage,
+                attachment.samples,
+            )?;
+        }
+
+        Ok(true)
+    }
+
+    fn create_view(
+        device: Arc<Device>,
+        extent: [u32; 2],
+        format: Format,
+        usage: ImageUsage,
+        samples: SampleCount,
+    ) -> Result<Arc<ImageView<AttachmentImage>>, AttachmentPoolError> {
+        let image =
+            AttachmentImage::multisampled_with_usage(device, extent, samples, format, usage)?;
+
+        Ok(ImageView::new_default(image)?)
+    }
+}
+
+unsafe impl DeviceOwned for AttachmentPool {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+/// Identifies an attachment of an `AttachmentPool`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AttachmentId(usize);
+
+/// Error that can happen when creating the images of an `AttachmentPool`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttachmentPoolError {
+    /// Creating an attachment image failed.
+    ImageCreationError(ImageCreationError),
+
+    /// Creating the view of an attachment image failed.
+    ImageViewCreationError(ImageViewCreationError),
+}
+
+impl error::Error for AttachmentPoolError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::ImageCreationError(err) => Some(err),
+            Self::ImageViewCreationError(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for AttachmentPoolError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ImageCreationError(_) => write!(f, "creating an attachment image failed"),
+            Self::ImageViewCreationError(_) => {
+                write!(f, "creating the view of an attachment image failed")
+            }
+        }
+    }
+}
+
+impl From<ImageCreationError> for AttachmentPoolError {
+    #[inline]
+    fn from(err: ImageCreationError) -> Self {
+        Self::ImageCreationError(err)
+    }
+}
+
+impl From<ImageViewCreationError> for AttachmentPoolError {
+    #[inline]
+    fn from(err: ImageViewCreationError) -> Self {
+        Self::ImageViewCreationError(err)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::AttachmentImage;
-    use crate::format::Format;
+    use super::{AttachmentImage, AttachmentPool};
+    use crate::{
+        format::Format,
+        image::{ImageAccess, ImageUsage, SampleCount},
+    };
 
     #[test]
     fn create_regular() {
@@ -677,4 +909,27 @@ mod tests {
         let (device, _) = gfx_dev_and_queue!();
         let _img = AttachmentImage::new(device, [32, 32], Format::D16_UNORM).unwrap();
     }
+
+    #[test]
+    fn pool_resize() {
+        let (device, _) = gfx_dev_and_queue!();
+        let mut pool = AttachmentPool::new(device, [32, 32]);
+        let depth = pool
+            .add(
+                Format::D16_UNORM,
+                ImageUsage::depth_stencil_attachment(),
+                SampleCount::Sample1,
+            )
+            .unwrap();
+        let old_view = pool.view(depth);
+
+        assert!(!pool.resize([32, 32]).unwrap());
+        assert!(pool.resize([64, 48]).unwrap());
+        assert_eq!(pool.extent(), [64, 48]);
+        assert_eq!(
+            pool.view(depth).image().dimensions().width_height(),
+            [64, 48]
+        );
+        assert_eq!(old_view.image().dimensions().width_height(), [32, 32]);
+    }
 }
@@ -31,7 +31,7 @@ use crate::{
     },
     sampler::Filter,
     sync::{NowFuture, Sharing},
-    OomError,
+    OomError, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{
@@ -197,6 +197,7 @@ impl ImmutableImage {
                 mutable_format: flags.mutable_format,
                 cube_compatible: flags.cube_compatible,
                 array_2d_compatible: flags.array_2d_compatible,
+                view_2d_compatible: flags.view_2d_compatible,
                 block_texel_view_compatible: flags.block_texel_view_compatible,
                 ..Default::default()
             },
@@ -337,6 +338,15 @@ unsafe impl<A> DeviceOwned for ImmutableImage<A> {
     }
 }
 
+unsafe impl<A> VulkanObject for ImmutableImage<A> {
+    type Object = ash::vk::Image;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Image {
+        self.image.internal_object()
+    }
+}
+
 unsafe impl<A> ImageAccess for ImmutableImage<A>
 where
     A: MemoryPoolAlloc,
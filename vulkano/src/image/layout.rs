@@ -32,7 +32,7 @@ use crate::descriptor_set::layout::DescriptorType;
 /// specified as well. Some operations allow for different layouts, but require the user to specify
 /// which one. Vulkano helps with this by providing sensible defaults, automatically tracking the
 /// layout of each image when creating a command buffer, and adding layout transitions where needed.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(i32)]
 pub enum ImageLayout {
     /// The layout of the data is unknown, and the image is treated as containing no valid data.
@@ -76,6 +76,22 @@ pub enum ImageLayout {
     /// acquired from the swapchain, and must be transitioned back into this layout before
     /// presenting them.
     PresentSrc = ash::vk::ImageLayout::PRESENT_SRC_KHR.as_raw(),
+
+    /// For an image used as a fragment density map attachment in a render pass. Images that are
+    /// transitioned into this layout must have the `fragment_density_map` usage enabled.
+    FragmentDensityMapOptimal = ash::vk::ImageLayout::FRAGMENT_DENSITY_MAP_OPTIMAL_EXT.as_raw(),
+
+    /// For an image used as the output picture of a video decode operation. Images that are
+    /// transitioned into this layout must have the `video_decode_dst` usage enabled.
+    VideoDecodeDst = ash::vk::ImageLayout::VIDEO_DECODE_DST_KHR.as_raw(),
+
+    /// Reserved for future use by the Vulkan specification.
+    VideoDecodeSrc = ash::vk::ImageLayout::VIDEO_DECODE_SRC_KHR.as_raw(),
+
+    /// For an image used as a decoded picture buffer (DPB) picture of a video decode operation,
+    /// including the output picture if it is also used as a reference picture. Images that are
+    /// transitioned into this layout must have the `video_decode_dpb` usage enabled.
+    VideoDecodeDpb = ash::vk::ImageLayout::VIDEO_DECODE_DPB_KHR.as_raw(),
 }
 
 impl From<ImageLayout> for ash::vk::ImageLayout {
@@ -49,21 +49,28 @@
 pub use self::aspect::ImageAspect;
 pub use self::aspect::ImageAspects;
 pub use self::attachment::AttachmentImage;
+pub use self::attachment::AttachmentPool;
 pub use self::immutable::ImmutableImage;
 pub use self::layout::ImageDescriptorLayouts;
 pub use self::layout::ImageLayout;
+pub use self::sparse::SparseImage;
 pub use self::storage::StorageImage;
+pub use self::streaming::MipResidency;
 pub use self::swapchain::SwapchainImage;
 pub use self::sys::ImageCreationError;
 pub use self::traits::ImageAccess;
 pub use self::traits::ImageInner;
+pub use self::transient::TransientImage;
 pub use self::usage::ImageUsage;
 pub use self::view::ImageViewAbstract;
 use self::view::ImageViewType;
+use crate::buffer::SparseLevel;
 use crate::format::Format;
 use crate::memory::ExternalMemoryHandleType;
 use crate::memory::ExternalMemoryProperties;
+use crate::video::VideoProfile;
 use crate::DeviceSize;
+use smallvec::SmallVec;
 use std::cmp;
 use std::ops::BitAnd;
 use std::ops::Range;
@@ -72,14 +79,17 @@ mod aspect;
 pub mod attachment; // TODO: make private
 pub mod immutable; // TODO: make private
 mod layout;
+mod sparse;
 mod storage;
+mod streaming;
 pub mod swapchain; // TODO: make private
 pub mod sys;
 pub mod traits;
+mod transient;
 mod usage;
 pub mod view;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u32)]
 pub enum SampleCount {
     Sample1 = ash::vk::SampleCountFlags::TYPE_1.as_raw(),
@@ -333,6 +343,13 @@ pub struct ImageCreateFlags {
     pub cube_compatible: bool,
     /// For 3D images, allows creation of an image view of type `Dim2d` or `Dim2dArray`.
     pub array_2d_compatible: bool,
+    /// For 3D images, allows creation of an image view of type `Dim2d` that covers a single
+    /// slice of the image, and that can be used in a storage image or sampled image descriptor.
+    ///
+    /// Requires the
+    /// [`ext_image_2d_view_of_3d`](crate::device::DeviceExtensions::ext_image_2d_view_of_3d)
+    /// extension to be enabled.
+    pub view_2d_compatible: bool,
     /// For images with a compressed format, allows creation of an image view with an uncompressed
     /// format, where each texel in the view will correspond to a compressed texel block in the
     /// image.
@@ -356,6 +373,7 @@ impl From<ImageCreateFlags> for ash::vk::ImageCreateFlags {
             mutable_format,
             cube_compatible,
             array_2d_compatible,
+            view_2d_compatible,
             block_texel_view_compatible,
         } = flags;
 
@@ -378,6 +396,9 @@ impl From<ImageCreateFlags> for ash::vk::ImageCreateFlags {
         if array_2d_compatible {
             vk_flags |= ash::vk::ImageCreateFlags::TYPE_2D_ARRAY_COMPATIBLE
         };
+        if view_2d_compatible {
+            vk_flags |= ash::vk::ImageCreateFlags::TYPE_2D_VIEW_COMPATIBLE_EXT
+        };
         if block_texel_view_compatible {
             vk_flags |= ash::vk::ImageCreateFlags::BLOCK_TEXEL_VIEW_COMPATIBLE
         };
@@ -403,6 +424,14 @@ impl From<ImageType> for ash::vk::ImageType {
 pub enum ImageTiling {
     Optimal = ash::vk::ImageTiling::OPTIMAL.as_raw(),
     Linear = ash::vk::ImageTiling::LINEAR.as_raw(),
+
+    /// The tiling is defined by a Linux DRM format modifier, so that the image can be shared with
+    /// other APIs and devices, such as KMS or GBM, without copying.
+    ///
+    /// Requires the
+    /// [`ext_image_drm_format_modifier`](crate::device::DeviceExtensions::ext_image_drm_format_modifier)
+    /// extension.
+    DrmFormatModifier = ash::vk::ImageTiling::DRM_FORMAT_MODIFIER_EXT.as_raw(),
 }
 
 impl From<ImageTiling> for ash::vk::ImageTiling {
@@ -752,11 +781,39 @@ pub struct ImageFormatInfo {
     /// The default value is `false`.
     pub array_2d_compatible: bool,
 
+    /// The `view_2d_compatible` that the image will have.
+    ///
+    /// The default value is `false`.
+    pub view_2d_compatible: bool,
+
     /// The `block_texel_view_compatible` that the image will have.
     ///
     /// The default value is `false`.
     pub block_texel_view_compatible: bool,
 
+    /// The `video_profiles` that the image will have.
+    ///
+    /// This must not be empty if `usage` contains a video usage, and the physical device API
+    /// version must then be at least 1.1 or the
+    /// [`khr_get_physical_device_properties2`](crate::instance::InstanceExtensions::khr_get_physical_device_properties2)
+    /// extension must be enabled on the instance.
+    ///
+    /// The default value is empty.
+    pub video_profiles: SmallVec<[VideoProfile; 1]>,
+
+    /// The Linux DRM format modifier that the image will have.
+    ///
+    /// This must be `Some` if `tiling` is [`ImageTiling::DrmFormatModifier`], and `None`
+    /// otherwise. The image is assumed to use [`Sharing::Exclusive`](crate::sync::Sharing).
+    ///
+    /// The default value is `None`.
+    pub drm_format_modifier: Option<u64>,
+
+    /// The `sparse` that the image will have.
+    ///
+    /// The default value is `None`.
+    pub sparse: Option<SparseLevel>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -773,7 +830,11 @@ impl Default for ImageFormatInfo {
             mutable_format: false,
             cube_compatible: false,
             array_2d_compatible: false,
+            view_2d_compatible: false,
             block_texel_view_compatible: false,
+            video_profiles: SmallVec::new(),
+            drm_format_modifier: None,
+            sparse: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -812,6 +873,12 @@ pub struct ImageFormatProperties {
     /// [`Min`](crate::sampler::SamplerReductionMode::Min) or
     /// [`Max`](crate::sampler::SamplerReductionMode::Max) `reduction_mode`.
     pub filter_cubic_minmax: bool,
+
+    /// When querying with the
+    /// [`AndroidHardwareBuffer`](crate::memory::ExternalMemoryHandleType::AndroidHardwareBuffer)
+    /// external memory handle type, the `AHARDWAREBUFFER_USAGE_*` flags that an Android hardware
+    /// buffer should be allocated with, to be optimally used with an image of this type.
+    pub android_hardware_buffer_usage: Option<u64>,
 }
 
 impl From<ash::vk::ImageFormatProperties> for ImageFormatProperties {
@@ -829,6 +896,142 @@ impl From<ash::vk::ImageFormatProperties> for ImageFormatProperties {
             external_memory_properties: Default::default(),
             filter_cubic: false,
             filter_cubic_minmax: false,
+            android_hardware_buffer_usage: None,
+        }
+    }
+}
+
+/// The image configuration to query in
+/// [`PhysicalDevice::sparse_image_format_properties`](crate::device::physical::PhysicalDevice::sparse_image_format_properties).
+#[derive(Clone, Debug)]
+pub struct SparseImageFormatInfo {
+    /// The `format` that the image will have.
+    ///
+    /// The default value is `None`, which must be overridden.
+    pub format: Option<Format>,
+
+    /// The dimension type that the image will have.
+    ///
+    /// The default value is [`ImageType::Dim2d`].
+    pub image_type: ImageType,
+
+    /// The `samples` that the image will have.
+    ///
+    /// The default value is `SampleCount::Sample1`.
+    pub samples: SampleCount,
+
+    /// The `usage` that the image will have.
+    ///
+    /// The default value is [`ImageUsage::none()`], which must be overridden.
+    pub usage: ImageUsage,
+
+    /// The `tiling` that the image will have.
+    ///
+    /// The default value is [`ImageTiling::Optimal`].
+    pub tiling: ImageTiling,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for SparseImageFormatInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            format: None,
+            image_type: ImageType::Dim2d,
+            samples: SampleCount::Sample1,
+            usage: ImageUsage::none(),
+            tiling: ImageTiling::Optimal,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The properties that are supported by a physical device for sparse images of a certain type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SparseImageFormatProperties {
+    /// The aspects of the image that the properties apply to.
+    pub aspects: ImageAspects,
+
+    /// The size in texels of a sparse image block. Regions of the image that are bound to memory
+    /// must be aligned to this granularity.
+    pub image_granularity: [u32; 3],
+
+    /// Whether the image uses a single mip tail region for all of its array layers, instead of
+    /// one per array layer.
+    pub single_miptail: bool,
+
+    /// Whether the first mip level whose dimensions are not a multiple of `image_granularity` is
+    /// the start of the mip tail.
+    pub aligned_mip_size: bool,
+
+    /// Whether the image uses a non-standard sparse image block size, so that
+    /// `image_granularity` does not match the standard block size for the format.
+    pub nonstandard_block_size: bool,
+}
+
+impl From<ash::vk::SparseImageFormatProperties> for SparseImageFormatProperties {
+    #[inline]
+    fn from(props: ash::vk::SparseImageFormatProperties) -> Self {
+        Self {
+            aspects: props.aspect_mask.into(),
+            image_granularity: [
+                props.image_granularity.width,
+                props.image_granularity.height,
+                props.image_granularity.depth,
+            ],
+            single_miptail: props
+                .flags
+                .intersects(ash::vk::SparseImageFormatFlags::SINGLE_MIPTAIL),
+            aligned_mip_size: props
+                .flags
+                .intersects(ash::vk::SparseImageFormatFlags::ALIGNED_MIP_SIZE),
+            nonstandard_block_size: props
+                .flags
+                .intersects(ash::vk::SparseImageFormatFlags::NONSTANDARD_BLOCK_SIZE),
+        }
+    }
+}
+
+/// The requirements for the memory that is bound to some aspects of a sparse image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SparseImageMemoryRequirements {
+    /// The format properties of the aspects that these requirements apply to.
+    pub format_properties: SparseImageFormatProperties,
+
+    /// The first mip level that is part of the mip tail. The mip tail can only be bound to memory
+    /// as a whole, with an opaque bind.
+    ///
+    /// If this is equal to or greater than the number of mip levels of the image, the image has
+    /// no mip tail.
+    pub image_mip_tail_first_lod: u32,
+
+    /// The size in bytes of the mip tail. This is a multiple of the sparse block size.
+    pub image_mip_tail_size: DeviceSize,
+
+    /// The opaque offset in bytes of the mip tail of the first array layer, to be used with an
+    /// opaque bind.
+    pub image_mip_tail_offset: DeviceSize,
+
+    /// The offset stride in bytes between the mip tails of consecutive array layers. This is
+    /// `None` if `single_miptail` is set.
+    pub image_mip_tail_stride: Option<DeviceSize>,
+}
+
+impl From<ash::vk::SparseImageMemoryRequirements> for SparseImageMemoryRequirements {
+    #[inline]
+    fn from(reqs: ash::vk::SparseImageMemoryRequirements) -> Self {
+        let format_properties = SparseImageFormatProperties::from(reqs.format_properties);
+
+        Self {
+            format_properties,
+            image_mip_tail_first_lod: reqs.image_mip_tail_first_lod,
+            image_mip_tail_size: reqs.image_mip_tail_size,
+            image_mip_tail_offset: reqs.image_mip_tail_offset,
+            image_mip_tail_stride: (!format_properties.single_miptail)
+                .then(|| reqs.image_mip_tail_stride),
         }
     }
 }
@@ -0,0 +1,416 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use super::{
+    sys::{SparseImageMemory, UnsafeImage, UnsafeImageCreateInfo},
+    ImageAccess, ImageAspect, ImageCreationError, ImageDescriptorLayouts, ImageDimensions,
+    ImageInner, ImageLayout, ImageUsage, MipmapsCount, SparseImageMemoryRequirements,
+};
+use crate::{
+    buffer::SparseLevel,
+    device::{physical::QueueFamily, Device, DeviceOwned},
+    format::Format,
+    memory::MemoryRequirements,
+    sync::Sharing,
+    DeviceSize, VulkanObject,
+};
+use smallvec::SmallVec;
+use std::{
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// Image whose memory is bound sparsely, on demand.
+///
+/// Memory is bound with [`Queue::bind_sparse`](crate::device::Queue::bind_sparse) or
+/// [`GpuFuture::then_bind_sparse`](crate::sync::GpuFuture::then_bind_sparse). The image keeps the
+/// memory that is bound to it alive.
+///
+/// If the image was created with `sparse_residency`, memory can be bound to individual blocks of
+/// its subresources, whose size is given by the `image_granularity` of
+/// [`sparse_memory_requirements`](SparseImage::sparse_memory_requirements). The regions that are
+/// left unbound can still be accessed by the GPU: reads return undefined values, or zero if the
+/// [`residency_non_resident_strict`](crate::device::Properties::residency_non_resident_strict)
+/// property is set, and writes are discarded. This is the building block of virtual texturing.
+/// The mip tail, which contains the smallest mip levels, can only be bound as a whole with an
+/// opaque bind.
+///
+/// Without `sparse_residency`, the memory is bound with opaque binds only, and all of it must be
+/// bound before the image is used by the GPU.
+#[derive(Debug)]
+pub struct SparseImage {
+    // Inner implementation.
+    image: Arc<UnsafeImage>,
+
+    // Queue families allowed to access this image.
+    queue_families: SmallVec<[u32; 4]>,
+}
+
+impl SparseImage {
+    /// Creates a new sparse image with the given dimensions, format and number of mip levels.
+    pub fn new<'a, I>(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        mip_levels: impl Into<MipmapsCount>,
+        usage: ImageUsage,
+        sparse_level: SparseLevel,
+        queue_families: I,
+    ) -> Result<Arc<SparseImage>, ImageCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        let queue_families = queue_families
+            .into_iter()
+            .map(|f| f.id())
+            .collect::<SmallVec<[u32; 4]>>();
+
+        let image = UnsafeImage::new(
+            device,
+            UnsafeImageCreateInfo {
+                dimensions,
+                format: Some(format),
+                mip_levels: match mip_levels.into() {
+                    MipmapsCount::Specific(num) => num,
+                    MipmapsCount::Log2 => dimensions.max_mip_levels(),
+                    MipmapsCount::One => 1,
+                },
+                usage,
+                sharing: if queue_families.len() >= 2 {
+                    Sharing::Concurrent(queue_families.iter().cloned().collect())
+                } else {
+                    Sharing::Exclusive
+                },
+                sparse: Some(sparse_level),
+                ..Default::default()
+            },
+        )?;
+
+        Ok(Arc::new(SparseImage {
+            image,
+            queue_families,
+        }))
+    }
+
+    /// Returns the requirements for the memory that is bound to the image.
+    ///
+    /// `alignment` is the size of a sparse block in bytes, and `size` is the total amount of
+    /// memory needed to back the whole image.
+    #[inline]
+    pub fn memory_requirements(&self) -> MemoryRequirements {
+        self.image.memory_requirements()
+    }
+
+    /// Returns the requirements for binding memory to individual blocks and to the mip tail of
+    /// the image, for each group of aspects of its format.
+    ///
+    /// The list is empty if the image was not created with `sparse_residency`.
+    #[inline]
+    pub fn sparse_memory_requirements(&self) -> Vec<SparseImageMemoryRequirements> {
+        self.image.sparse_memory_requirements()
+    }
+
+    /// Returns the sparse binding parameters that the image was created with.
+    #[inline]
+    pub fn sparse_level(&self) -> SparseLevel {
+        self.image.sparse().unwrap()
+    }
+
+    /// Returns true if a region of a subresource of the image is entirely bound to memory with
+    /// image binds, as far as the sparse binding operations submitted so far are concerned.
+    ///
+    /// Returns false if the image was not created with `sparse_residency`, if `aspect` is not
+    /// part of its format, or if `mip_level` is part of the mip tail.
+    pub fn is_resident(
+        &self,
+        aspect: ImageAspect,
+        mip_level: u32,
+        array_layer: u32,
+        offset: [u32; 3],
+        extent: [u32; 3],
+    ) -> bool {
+        let requirements = self
+            .sparse_memory_requirements()
+            .into_iter()
+            .find(|requirements| {
+                requirements
+                    .format_properties
+                    .aspects
+                    .contains(&aspect.into())
+            });
+        let requirements = match requirements {
+            Some(x) => x,
+            None => return false,
+        };
+
+        if mip_level >= requirements.image_mip_tail_first_lod {
+            return false;
+        }
+
+        let sparse_memory = self.image.sparse_memory();
+
+        SparseImageMemory::region_blocks(
+            aspect,
+            mip_level,
+            array_layer,
+            offset,
+            extent,
+            requirements.format_properties.image_granularity,
+        )
+        .all(|block| sparse_memory.blocks.contains_key(&block))
+    }
+
+    /// Returns true if the given range of the opaque memory of the image is entirely bound to
+    /// memory, as far as the sparse binding operations submitted so far are concerned.
+    ///
+    /// For an image created with `sparse_residency`, this can be used to check whether the mip
+    /// tail is bound.
+    #[inline]
+    pub fn is_opaque_bound(&self, offset: DeviceSize, size: DeviceSize) -> bool {
+        size == 0
+            || self
+                .image
+                .sparse_memory()
+                .opaque
+                .contains_all(&(offset..offset + size))
+    }
+
+    /// Returns the queue families this image can be used on.
+    // TODO: use a custom iterator
+    #[inline]
+    pub fn queue_families(&self) -> Vec<QueueFamily> {
+        self.queue_families
+            .iter()
+            .map(|&num| {
+                self.device()
+                    .physical_device()
+                    .queue_family_by_id(num)
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+unsafe impl DeviceOwned for SparseImage {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.device()
+    }
+}
+
+unsafe impl VulkanObject for SparseImage {
+    type Object = ash::vk::Image;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Image {
+        self.image.internal_object()
+    }
+}
+
+unsafe impl ImageAccess for SparseImage {
+    #[inline]
+    fn inner(&self) -> ImageInner {
+        ImageInner {
+            image: &self.image,
+            first_layer: 0,
+            num_layers: self.image.dimensions().array_layers(),
+            first_mipmap_level: 0,
+            num_mipmap_levels: self.image.mip_levels(),
+        }
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_layouts(&self) -> Option<ImageDescriptorLayouts> {
+        Some(ImageDescriptorLayouts {
+            storage_image: ImageLayout::General,
+            combined_image_sampler: ImageLayout::General,
+            sampled_image: ImageLayout::General,
+            input_attachment: ImageLayout::General,
+        })
+    }
+}
+
+impl PartialEq for SparseImage {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner() == other.inner()
+    }
+}
+
+impl Eq for SparseImage {}
+
+impl Hash for SparseImage {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseImage;
+    use crate::{
+        buffer::SparseLevel,
+        command_buffer::{
+            AutoCommandBufferBuilder, ClearColorImageInfo, CommandBufferExecError,
+            CommandBufferUsage,
+        },
+        format::Format,
+        image::{
+            ImageAspect, ImageAspects, ImageCreationError, ImageDimensions, ImageSubresourceRange,
+            ImageUsage, MipmapsCount,
+        },
+        sync::{self, AccessError, GpuFuture},
+    };
+
+    #[test]
+    fn feature_not_enabled() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        match SparseImage::new(
+            device,
+            ImageDimensions::Dim2d {
+                width: 1024,
+                height: 1024,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            MipmapsCount::One,
+            ImageUsage::sampled(),
+            SparseLevel::none(),
+            [queue.family()],
+        ) {
+            Err(ImageCreationError::FeatureNotEnabled {
+                feature: "sparse_binding",
+                ..
+            }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn residency_1d() {
+        let (device, queue) = gfx_dev_and_queue!(sparse_binding);
+
+        match SparseImage::new(
+            device,
+            ImageDimensions::Dim1d {
+                width: 1024,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            MipmapsCount::One,
+            ImageUsage::sampled(),
+            SparseLevel {
+                sparse_residency: true,
+                ..SparseLevel::none()
+            },
+            [queue.family()],
+        ) {
+            Err(ImageCreationError::SparseResidency1d) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn not_resident_after_creation() {
+        let (device, queue) = gfx_dev_and_queue!(sparse_binding, sparse_residency_image2_d);
+
+        let image = match SparseImage::new(
+            device,
+            ImageDimensions::Dim2d {
+                width: 1024,
+                height: 1024,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            MipmapsCount::Log2,
+            ImageUsage::sampled(),
+            SparseLevel {
+                sparse_residency: true,
+                ..SparseLevel::none()
+            },
+            [queue.family()],
+        ) {
+            Ok(x) => x,
+            Err(ImageCreationError::SparseResidencyFormatNotSupported) => return,
+            Err(err) => panic!("{:?}", err),
+        };
+
+        assert!(!image.sparse_memory_requirements().is_empty());
+        assert!(!image.is_resident(ImageAspect::Color, 0, 0, [0; 3], [1024, 1024, 1]));
+        assert!(image.is_opaque_bound(0, 0));
+    }
+
+    #[test]
+    fn unbound_partially_used() {
+        let (device, queue) = gfx_dev_and_queue!(sparse_binding);
+
+        let image = SparseImage::new(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: 64,
+                height: 64,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            MipmapsCount::Specific(2),
+            ImageUsage {
+                transfer_dst: true,
+                ..ImageUsage::none()
+            },
+            SparseLevel::none(),
+            [queue.family()],
+        )
+        .unwrap();
+
+        // Only the second mip level is used, so the first one has no uses in the command buffer.
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .clear_color_image(ClearColorImageInfo {
+                regions: [ImageSubresourceRange {
+                    aspects: ImageAspects {
+                        color: true,
+                        ..ImageAspects::none()
+                    },
+                    mip_levels: 1..2,
+                    array_layers: 0..1,
+                }]
+                .into_iter()
+                .collect(),
+                ..ClearColorImageInfo::image(image)
+            })
+            .unwrap();
+        let command_buffer = builder.build().unwrap();
+
+        match sync::now(device).then_execute(queue, command_buffer) {
+            Err(CommandBufferExecError::AccessError {
+                error: AccessError::SparseNotBound,
+                command_name,
+                ..
+            }) => assert_eq!(command_name, "clear_color_image"),
+            _ => panic!(),
+        }
+    }
+}
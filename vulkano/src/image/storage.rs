@@ -9,27 +9,31 @@
 
 use super::{
     sys::UnsafeImage, traits::ImageContent, ImageAccess, ImageCreateFlags, ImageCreationError,
-    ImageDescriptorLayouts, ImageDimensions, ImageInner, ImageLayout, ImageUsage,
+    ImageDescriptorLayouts, ImageDimensions, ImageInner, ImageLayout, ImageTiling, ImageUsage,
 };
 use crate::device::Queue;
 use crate::image::view::ImageView;
 use crate::{
-    device::{physical::QueueFamily, Device, DeviceOwned},
+    device::{
+        physical::{MemoryType, QueueFamily},
+        AndroidHardwareBufferProperties, Device, DeviceOwned,
+    },
     format::Format,
     image::sys::UnsafeImageCreateInfo,
     memory::{
         pool::{
-            alloc_dedicated_with_exportable_fd, AllocFromRequirementsFilter, AllocLayout,
+            alloc_dedicated_with_export_handle_types, AllocFromRequirementsFilter, AllocLayout,
             MappingRequirement, MemoryPoolAlloc, PotentialDedicatedAllocation, StdMemoryPool,
         },
-        DedicatedAllocation, DeviceMemoryExportError, ExternalMemoryHandleType,
-        ExternalMemoryHandleTypes, MemoryPool,
+        DedicatedAllocation, DeviceMemory, DeviceMemoryExportError, ExternalMemoryHandleType,
+        ExternalMemoryHandleTypes, MemoryAllocateInfo, MemoryImportInfo, MemoryPool,
     },
     sync::Sharing,
-    DeviceSize,
+    DeviceSize, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{
+    ffi::c_void,
     fs::File,
     hash::{Hash, Hasher},
     sync::Arc,
@@ -86,6 +90,10 @@ impl StorageImage {
             depth_stencil_attachment: is_depth,
             input_attachment: true,
             transient_attachment: false,
+            fragment_density_map: false,
+            video_decode_dst: false,
+            video_decode_src: false,
+            video_decode_dpb: false,
         };
         let flags = ImageCreateFlags::none();
 
@@ -93,6 +101,7 @@ impl StorageImage {
     }
 
     /// Same as `new`, but allows specifying the usage.
+    #[inline]
     pub fn with_usage<'a, I>(
         device: Arc<Device>,
         dimensions: ImageDimensions,
@@ -101,6 +110,64 @@ impl StorageImage {
         flags: ImageCreateFlags,
         queue_families: I,
     ) -> Result<Arc<StorageImage>, ImageCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        StorageImage::with_usage_impl(
+            device,
+            dimensions,
+            format,
+            usage,
+            flags,
+            None,
+            queue_families,
+        )
+    }
+
+    /// Same as `with_usage`, but the memory of the image is allocated with the given priority,
+    /// between 0.0 and 1.0. When the device runs out of memory, the implementation moves images
+    /// and buffers with a lower priority to system memory first.
+    ///
+    /// The priority is ignored if the
+    /// [`memory_priority`](crate::device::Features::memory_priority) feature is not enabled on
+    /// the device.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `priority` is not between 0.0 and 1.0 inclusive.
+    #[inline]
+    pub fn with_priority<'a, I>(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        usage: ImageUsage,
+        flags: ImageCreateFlags,
+        priority: f32,
+        queue_families: I,
+    ) -> Result<Arc<StorageImage>, ImageCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        StorageImage::with_usage_impl(
+            device,
+            dimensions,
+            format,
+            usage,
+            flags,
+            Some(priority),
+            queue_families,
+        )
+    }
+
+    fn with_usage_impl<'a, I>(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        usage: ImageUsage,
+        flags: ImageCreateFlags,
+        priority: Option<f32>,
+        queue_families: I,
+    ) -> Result<Arc<StorageImage>, ImageCreationError>
     where
         I: IntoIterator<Item = QueueFamily<'a>>,
     {
@@ -123,26 +190,41 @@ impl StorageImage {
                 mutable_format: flags.mutable_format,
                 cube_compatible: flags.cube_compatible,
                 array_2d_compatible: flags.array_2d_compatible,
+                view_2d_compatible: flags.view_2d_compatible,
                 block_texel_view_compatible: flags.block_texel_view_compatible,
                 ..Default::default()
             },
         )?;
 
         let mem_reqs = image.memory_requirements();
-        let memory = MemoryPool::alloc_from_requirements(
-            &Device::standard_pool(&device),
-            &mem_reqs,
-            AllocLayout::Optimal,
-            MappingRequirement::DoNotMap,
-            Some(DedicatedAllocation::Image(&image)),
-            |t| {
-                if t.is_device_local() {
-                    AllocFromRequirementsFilter::Preferred
-                } else {
-                    AllocFromRequirementsFilter::Allowed
-                }
-            },
-        )?;
+        let pool = Device::standard_pool(&device);
+        let dedicated_allocation = Some(DedicatedAllocation::Image(&image));
+        let filter = |t: MemoryType| {
+            if t.is_device_local() {
+                AllocFromRequirementsFilter::Preferred
+            } else {
+                AllocFromRequirementsFilter::Allowed
+            }
+        };
+        let memory = match priority {
+            Some(priority) => MemoryPool::alloc_from_requirements_with_priority(
+                &pool,
+                &mem_reqs,
+                AllocLayout::Optimal,
+                MappingRequirement::DoNotMap,
+                dedicated_allocation,
+                priority,
+                filter,
+            )?,
+            None => MemoryPool::alloc_from_requirements(
+                &pool,
+                &mem_reqs,
+                AllocLayout::Optimal,
+                MappingRequirement::DoNotMap,
+                dedicated_allocation,
+                filter,
+            )?,
+        };
         debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
         unsafe {
             image.bind_memory(memory.memory(), memory.offset())?;
@@ -165,6 +247,47 @@ impl StorageImage {
         flags: ImageCreateFlags,
         queue_families: I,
     ) -> Result<Arc<StorageImage>, ImageCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        StorageImage::new_with_external_memory(
+            device,
+            dimensions,
+            format,
+            usage,
+            flags,
+            ImageTiling::Optimal,
+            ExternalMemoryHandleTypes {
+                opaque_fd: true,
+                ..ExternalMemoryHandleTypes::none()
+            },
+            queue_families,
+        )
+    }
+
+    /// Same as `with_usage`, but the image has the given `tiling`, and is backed by a dedicated
+    /// allocation that can be exported with `export_handle_types`, to share the image with other
+    /// APIs or processes.
+    ///
+    /// Images that are shared as [`ExternalMemoryHandleType::DmaBuf`] with other drivers or
+    /// devices, such as Wayland compositors, V4L2 or hardware decoders, should use
+    /// [`ImageTiling::Linear`], because only the driver that created an optimally tiled image
+    /// knows its memory layout. The layout of a linear image, which the other side needs to know,
+    /// is returned by [`UnsafeImage::color_linear_layout`].
+    ///
+    /// If `export_handle_types` contains `android_hardware_buffer`, the size of the allocation is
+    /// chosen by the implementation, and the memory is allocated from the first device-local
+    /// memory type.
+    pub fn new_with_external_memory<'a, I>(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        usage: ImageUsage,
+        flags: ImageCreateFlags,
+        tiling: ImageTiling,
+        export_handle_types: ExternalMemoryHandleTypes,
+        queue_families: I,
+    ) -> Result<Arc<StorageImage>, ImageCreationError>
     where
         I: IntoIterator<Item = QueueFamily<'a>>,
     {
@@ -173,56 +296,283 @@ impl StorageImage {
             .map(|f| f.id())
             .collect::<SmallVec<[u32; 4]>>();
 
-        let image = UnsafeImage::new(
+        let image = StorageImage::create_external(
+            &device,
+            dimensions,
+            format,
+            usage,
+            flags,
+            tiling,
+            export_handle_types,
+            &queue_families,
+        )?;
+
+        let memory = if export_handle_types.android_hardware_buffer {
+            // The memory requirements of the image can't be queried before it's bound.
+            let memory_type = device
+                .physical_device()
+                .memory_types()
+                .find(|t| t.is_device_local())
+                .unwrap();
+            let memory = DeviceMemory::allocate(
+                device.clone(),
+                MemoryAllocateInfo {
+                    allocation_size: 0,
+                    memory_type_index: memory_type.id(),
+                    export_handle_types,
+                    ..MemoryAllocateInfo::dedicated_allocation(DedicatedAllocation::Image(&image))
+                },
+            )?;
+            unsafe {
+                image.bind_memory(&memory, 0)?;
+            }
+
+            PotentialDedicatedAllocation::Dedicated(memory)
+        } else {
+            let mem_reqs = image.memory_requirements();
+            let memory = alloc_dedicated_with_export_handle_types(
+                device.clone(),
+                &mem_reqs,
+                match tiling {
+                    ImageTiling::Optimal | ImageTiling::DrmFormatModifier => AllocLayout::Optimal,
+                    ImageTiling::Linear => AllocLayout::Linear,
+                },
+                MappingRequirement::DoNotMap,
+                DedicatedAllocation::Image(&image),
+                export_handle_types,
+                |t| {
+                    if t.is_device_local() {
+                        AllocFromRequirementsFilter::Preferred
+                    } else {
+                        AllocFromRequirementsFilter::Allowed
+                    }
+                },
+            )?;
+            debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
+            unsafe {
+                image.bind_memory(memory.memory(), memory.offset())?;
+            }
+
+            memory
+        };
+
+        Ok(Arc::new(StorageImage {
+            image,
+            memory,
+            dimensions,
+            format,
+            queue_families,
+        }))
+    }
+
+    /// Creates a new image whose memory is imported from a Unix file descriptor, which was
+    /// exported with [`export_posix_fd`](Self::export_posix_fd) from an image created by
+    /// [`new_with_exportable_fd`](Self::new_with_exportable_fd), possibly in another process.
+    ///
+    /// `allocation_size` and `memory_type_index` must be the values that
+    /// [`mem_size`](Self::mem_size) and [`memory_type_index`](Self::memory_type_index) return
+    /// for the exported image.
+    ///
+    /// # Safety
+    ///
+    /// - `file` must be a valid opaque file descriptor, exported from the memory of an image that
+    ///   was created with the same `dimensions`, `format`, `usage` and `flags`, on a device with
+    ///   the same device and driver UUIDs.
+    /// - Vulkan takes ownership of `file` if the import succeeds. You must not perform any
+    ///   operations on it or its duplicates afterwards.
+    pub unsafe fn new_from_imported_fd<'a, I>(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        usage: ImageUsage,
+        flags: ImageCreateFlags,
+        queue_families: I,
+        file: File,
+        allocation_size: DeviceSize,
+        memory_type_index: u32,
+    ) -> Result<Arc<StorageImage>, ImageCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        StorageImage::new_from_imported_memory(
+            device,
+            dimensions,
+            format,
+            usage,
+            flags,
+            ImageTiling::Optimal,
+            queue_families,
+            MemoryImportInfo::Fd {
+                handle_type: ExternalMemoryHandleType::OpaqueFd,
+                file,
+            },
+            allocation_size,
+            memory_type_index,
+        )
+    }
+
+    /// Creates a new image with the given `tiling`, whose memory is imported from an external
+    /// source described by `import_info`. This is the counterpart of
+    /// [`new_with_external_memory`](Self::new_with_external_memory), but it can also import
+    /// memory that was not created by Vulkan, such as a dma-buf from a Wayland compositor, a V4L2
+    /// device or a hardware decoder.
+    ///
+    /// For memory exported by Vulkan, `allocation_size` and `memory_type_index` must be the
+    /// values that [`mem_size`](Self::mem_size) and [`memory_type_index`](Self::memory_type_index)
+    /// return for the exported image. For a foreign dma-buf, `allocation_size` is the size of the
+    /// buffer, and `memory_type_index` must be one of the memory types returned by
+    /// [`Device::memory_fd_properties`].
+    ///
+    /// # Safety
+    ///
+    /// - See the documentation of the variants of [`MemoryImportInfo`].
+    /// - The imported memory must contain an image that was created with the same `dimensions`,
+    ///   `format`, `usage`, `flags` and `tiling`. For a foreign dma-buf, this means that `tiling`
+    ///   must be [`ImageTiling::Linear`], and that the row pitch of the buffer must be the one
+    ///   returned by [`UnsafeImage::color_linear_layout`].
+    pub unsafe fn new_from_imported_memory<'a, I>(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        usage: ImageUsage,
+        flags: ImageCreateFlags,
+        tiling: ImageTiling,
+        queue_families: I,
+        import_info: MemoryImportInfo,
+        allocation_size: DeviceSize,
+        memory_type_index: u32,
+    ) -> Result<Arc<StorageImage>, ImageCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        let queue_families = queue_families
+            .into_iter()
+            .map(|f| f.id())
+            .collect::<SmallVec<[u32; 4]>>();
+
+        let handle_type = match import_info {
+            MemoryImportInfo::Fd { handle_type, .. } => handle_type,
+            MemoryImportInfo::Win32 { handle_type, .. } => handle_type,
+            MemoryImportInfo::AndroidHardwareBuffer { .. } => {
+                ExternalMemoryHandleType::AndroidHardwareBuffer
+            }
+        };
+        let image = StorageImage::create_external(
+            &device,
+            dimensions,
+            format,
+            usage,
+            flags,
+            tiling,
+            handle_type.into(),
+            &queue_families,
+        )?;
+
+        let memory = DeviceMemory::import(
+            device,
+            MemoryAllocateInfo {
+                allocation_size,
+                memory_type_index,
+                ..MemoryAllocateInfo::dedicated_allocation(DedicatedAllocation::Image(&image))
+            },
+            import_info,
+        )?;
+        image.bind_memory(&memory, 0)?;
+
+        Ok(Arc::new(StorageImage {
+            image,
+            memory: PotentialDedicatedAllocation::Dedicated(memory),
+            dimensions,
+            format,
+            queue_families,
+        }))
+    }
+
+    /// Creates a new image whose memory is imported from an Android hardware buffer, such as a
+    /// camera frame or a buffer shared by another app. `properties` must have been returned by
+    /// [`Device::android_hardware_buffer_properties`] for `buffer`, and `dimensions` must be the
+    /// dimensions of the buffer.
+    ///
+    /// To choose the `AHARDWAREBUFFER_USAGE_*` flags of a buffer that is allocated for use with
+    /// an image, query
+    /// [`PhysicalDevice::image_format_properties`](crate::device::physical::PhysicalDevice::image_format_properties)
+    /// with the `AndroidHardwareBuffer` external memory handle type, and read
+    /// [`android_hardware_buffer_usage`](crate::image::ImageFormatProperties::android_hardware_buffer_usage).
+    ///
+    /// If the buffer is in a YCbCr format, sample the image with the conversion returned by
+    /// [`AndroidHardwareBufferProperties::suggested_ycbcr_conversion`].
+    ///
+    /// # Safety
+    ///
+    /// - See the documentation of [`MemoryImportInfo::AndroidHardwareBuffer`].
+    /// - `usage` and `flags` must be supported by the usage flags that the buffer was allocated
+    ///   with.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `properties.format` is `None`. Buffers with an external format are not
+    ///   supported yet.
+    pub unsafe fn new_from_android_hardware_buffer<'a, I>(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        usage: ImageUsage,
+        flags: ImageCreateFlags,
+        queue_families: I,
+        buffer: *mut c_void,
+        properties: &AndroidHardwareBufferProperties,
+    ) -> Result<Arc<StorageImage>, ImageCreationError>
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        let format = properties
+            .format
+            .expect("Android hardware buffers with an external format are not supported yet");
+
+        StorageImage::new_from_imported_memory(
+            device,
+            dimensions,
+            format,
+            usage,
+            flags,
+            ImageTiling::Optimal,
+            queue_families,
+            MemoryImportInfo::AndroidHardwareBuffer { buffer },
+            properties.allocation_size,
+            properties.memory_type_bits.trailing_zeros(),
+        )
+    }
+
+    fn create_external(
+        device: &Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        usage: ImageUsage,
+        flags: ImageCreateFlags,
+        tiling: ImageTiling,
+        external_memory_handle_types: ExternalMemoryHandleTypes,
+        queue_families: &[u32],
+    ) -> Result<Arc<UnsafeImage>, ImageCreationError> {
+        UnsafeImage::new(
             device.clone(),
             UnsafeImageCreateInfo {
                 dimensions,
                 format: Some(format),
+                tiling,
                 usage,
                 sharing: if queue_families.len() >= 2 {
                     Sharing::Concurrent(queue_families.iter().cloned().collect())
                 } else {
                     Sharing::Exclusive
                 },
-                external_memory_handle_types: ExternalMemoryHandleTypes {
-                    opaque_fd: true,
-                    ..ExternalMemoryHandleTypes::none()
-                },
+                external_memory_handle_types,
                 mutable_format: flags.mutable_format,
                 cube_compatible: flags.cube_compatible,
                 array_2d_compatible: flags.array_2d_compatible,
+                view_2d_compatible: flags.view_2d_compatible,
                 block_texel_view_compatible: flags.block_texel_view_compatible,
                 ..Default::default()
             },
-        )?;
-
-        let mem_reqs = image.memory_requirements();
-        let memory = alloc_dedicated_with_exportable_fd(
-            device.clone(),
-            &mem_reqs,
-            AllocLayout::Optimal,
-            MappingRequirement::DoNotMap,
-            DedicatedAllocation::Image(&image),
-            |t| {
-                if t.is_device_local() {
-                    AllocFromRequirementsFilter::Preferred
-                } else {
-                    AllocFromRequirementsFilter::Allowed
-                }
-            },
-        )?;
-        debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
-        unsafe {
-            image.bind_memory(memory.memory(), memory.offset())?;
-        }
-
-        Ok(Arc::new(StorageImage {
-            image,
-            memory,
-            dimensions,
-            format,
-            queue_families,
-        }))
+        )
     }
 
     /// Allows the creation of a simple 2D general purpose image view from `StorageImage`.
@@ -261,15 +611,50 @@ impl StorageImage {
     /// Exports posix file descriptor for the allocated memory
     /// requires `khr_external_memory_fd` and `khr_external_memory` extensions to be loaded.
     pub fn export_posix_fd(&self) -> Result<File, DeviceMemoryExportError> {
-        self.memory
-            .memory()
-            .export_fd(ExternalMemoryHandleType::OpaqueFd)
+        self.export_fd(ExternalMemoryHandleType::OpaqueFd)
+    }
+
+    /// Exports the memory backing the image into a Unix file descriptor. The caller owns the
+    /// returned `File`.
+    ///
+    /// The image must have been created with `new_with_external_memory`, and `handle_type` must
+    /// have been included in `export_handle_types`.
+    pub fn export_fd(
+        &self,
+        handle_type: ExternalMemoryHandleType,
+    ) -> Result<File, DeviceMemoryExportError> {
+        self.memory.memory().export_fd(handle_type)
+    }
+
+    /// Exports the memory backing the image into a Win32 handle.
+    ///
+    /// The image must have been created with `new_with_external_memory`, and `handle_type` must
+    /// have been included in `export_handle_types`.
+    pub fn export_win32_handle(
+        &self,
+        handle_type: ExternalMemoryHandleType,
+    ) -> Result<*mut c_void, DeviceMemoryExportError> {
+        self.memory.memory().export_win32_handle(handle_type)
+    }
+
+    /// Exports the memory backing the image into an Android hardware buffer. The caller owns a
+    /// reference to the returned `AHardwareBuffer`.
+    ///
+    /// The image must have been created with `new_with_external_memory`, and
+    /// `android_hardware_buffer` must have been included in `export_handle_types`.
+    pub fn export_android_hardware_buffer(&self) -> Result<*mut c_void, DeviceMemoryExportError> {
+        self.memory.memory().export_android_hardware_buffer()
     }
 
     /// Return the size of the allocated memory (used for e.g. with cuda)
     pub fn mem_size(&self) -> DeviceSize {
         self.memory.memory().allocation_size()
     }
+
+    /// Returns the index of the memory type of the allocated memory.
+    pub fn memory_type_index(&self) -> u32 {
+        self.memory.memory().memory_type().id()
+    }
 }
 
 unsafe impl<A> DeviceOwned for StorageImage<A>
@@ -281,6 +666,18 @@ where
     }
 }
 
+unsafe impl<A> VulkanObject for StorageImage<A>
+where
+    A: MemoryPool,
+{
+    type Object = ash::vk::Image;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Image {
+        self.image.internal_object()
+    }
+}
+
 unsafe impl<A> ImageAccess for StorageImage<A>
 where
     A: MemoryPool,
@@ -352,9 +749,15 @@ where
 #[cfg(test)]
 mod tests {
     use super::StorageImage;
+    use crate::device::physical::PhysicalDevice;
+    use crate::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo};
     use crate::format::Format;
     use crate::image::view::ImageViewCreationError;
-    use crate::image::{ImageAccess, ImageCreationError, ImageDimensions, ImageUsage};
+    use crate::image::{
+        ImageAccess, ImageCreateFlags, ImageCreationError, ImageDimensions, ImageTiling, ImageUsage,
+    };
+    use crate::instance::{Instance, InstanceCreateInfo, InstanceExtensions};
+    use crate::memory::{ExternalMemoryHandleType, ExternalMemoryHandleTypes, MemoryImportInfo};
 
     #[test]
     fn create() {
@@ -412,4 +815,87 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn external_memory_roundtrip() {
+        let instance = match Instance::new(InstanceCreateInfo {
+            enabled_extensions: InstanceExtensions {
+                khr_get_physical_device_properties2: true,
+                khr_external_memory_capabilities: true,
+                ..InstanceExtensions::none()
+            },
+            ..Default::default()
+        }) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+
+        let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+        let queue_family = physical_device.queue_families().next().unwrap();
+
+        let (device, _) = match Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                enabled_extensions: DeviceExtensions {
+                    khr_dedicated_allocation: true,
+                    khr_external_memory: true,
+                    khr_external_memory_fd: true,
+                    ..DeviceExtensions::none()
+                },
+                queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
+                ..Default::default()
+            },
+        ) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+
+        let dimensions = ImageDimensions::Dim2d {
+            width: 32,
+            height: 32,
+            array_layers: 1,
+        };
+        let usage = ImageUsage {
+            transfer_src: true,
+            transfer_dst: true,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+
+        let image = StorageImage::new_with_external_memory(
+            device.clone(),
+            dimensions,
+            Format::R8G8B8A8_UNORM,
+            usage,
+            ImageCreateFlags::none(),
+            ImageTiling::Optimal,
+            ExternalMemoryHandleTypes {
+                opaque_fd: true,
+                ..ExternalMemoryHandleTypes::none()
+            },
+            [queue_family],
+        )
+        .unwrap();
+        let file = image.export_fd(ExternalMemoryHandleType::OpaqueFd).unwrap();
+
+        let imported = unsafe {
+            StorageImage::new_from_imported_memory(
+                device,
+                dimensions,
+                Format::R8G8B8A8_UNORM,
+                usage,
+                ImageCreateFlags::none(),
+                ImageTiling::Optimal,
+                [queue_family],
+                MemoryImportInfo::Fd {
+                    handle_type: ExternalMemoryHandleType::OpaqueFd,
+                    file,
+                },
+                image.mem_size(),
+                image.memory_type_index(),
+            )
+        }
+        .unwrap();
+        assert_eq!(imported.dimensions(), dimensions);
+    }
 }
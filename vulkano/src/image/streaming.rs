@@ -0,0 +1,213 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use super::{
+    view::{ImageView, ImageViewCreateInfo, ImageViewCreationError},
+    ImageAccess,
+};
+use crate::{
+    buffer::BufferAccess,
+    command_buffer::{AutoCommandBufferBuilder, BufferImageCopy, CopyBufferToImageInfo, CopyError},
+    device::DeviceOwned,
+    sampler::{Sampler, SamplerCreateInfo, SamplerCreationError},
+};
+use smallvec::smallvec;
+use std::sync::Arc;
+
+/// Tracks which mip levels of a sampled image are resident, so that the most detailed levels can
+/// be streamed in and out over time without using sparse images.
+///
+/// The resident levels are always the least detailed ones: a level is resident if it is at or
+/// after [`most_detailed_level`](Self::most_detailed_level). Sampling is clamped so that only
+/// resident levels are accessed. If the [`min_lod`](crate::device::Features::min_lod) feature is
+/// enabled on the device, the clamp is applied by [`image_view`](Self::image_view) with
+/// [`ImageViewCreateInfo::min_lod`]. Otherwise it's applied by [`sampler`](Self::sampler), by
+/// clamping the LOD range of the sampler.
+///
+/// The memory of non-resident levels is not freed, but their contents can be overwritten or left
+/// undefined. To stream in a level, upload it with [`upload_level`](Self::upload_level), and once
+/// the upload has finished executing, make it resident with
+/// [`set_most_detailed_level`](Self::set_most_detailed_level). To discard levels, make a less
+/// detailed level the most detailed one. In both cases, the image view and sampler must be
+/// created again, and the descriptor sets that use them updated.
+#[derive(Debug)]
+pub struct MipResidency<I>
+where
+    I: ImageAccess + ?Sized,
+{
+    image: Arc<I>,
+    most_detailed_level: u32,
+}
+
+impl<I> MipResidency<I>
+where
+    I: ImageAccess + ?Sized,
+{
+    /// Creates a new `MipResidency` for `image`.
+    ///
+    /// Initially, only the least detailed level is resident. It must be uploaded before the image
+    /// is sampled.
+    #[inline]
+    pub fn new(image: Arc<I>) -> Self {
+        let most_detailed_level = image.mip_levels() - 1;
+
+        MipResidency {
+            image,
+            most_detailed_level,
+        }
+    }
+
+    /// Returns the image whose residency is tracked.
+    #[inline]
+    pub fn image(&self) -> &Arc<I> {
+        &self.image
+    }
+
+    /// Returns the most detailed mip level that is resident.
+    #[inline]
+    pub fn most_detailed_level(&self) -> u32 {
+        self.most_detailed_level
+    }
+
+    /// Sets the most detailed mip level that is resident.
+    ///
+    /// All the levels from `level` to the last level must have been uploaded, and the uploads
+    /// must have finished executing before the image is sampled with the new clamp.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `level` is not less than the number of mip levels of the image.
+    #[inline]
+    pub fn set_most_detailed_level(&mut self, level: u32) {
+        assert!(level < self.image.mip_levels());
+        self.most_detailed_level = level;
+    }
+
+    /// Returns whether the clamp is applied by the image view rather than by the sampler.
+    #[inline]
+    pub fn uses_image_view_min_lod(&self) -> bool {
+        self.image.inner().image.device().enabled_features().min_lod
+    }
+
+    /// Creates an image view of the whole image, which is clamped to the resident levels if
+    /// [`uses_image_view_min_lod`](Self::uses_image_view_min_lod) returns `true`.
+    pub fn image_view(&self) -> Result<Arc<ImageView<I>>, ImageViewCreationError> {
+        let mut create_info = ImageViewCreateInfo::from_image(&*self.image);
+
+        if self.uses_image_view_min_lod() {
+            create_info.min_lod = self.most_detailed_level as f32;
+        }
+
+        ImageView::new(self.image.clone(), create_info)
+    }
+
+    /// Creates a sampler from `create_info` to sample the view returned by
+    /// [`image_view`](Self::image_view). If
+    /// [`uses_image_view_min_lod`](Self::uses_image_view_min_lod) returns `false`, the LOD range
+    /// of the sampler is clamped to the resident levels.
+    ///
+    /// Samplers are deduplicated by the device, so calling this again for the same residency
+    /// returns the same sampler.
+    pub fn sampler(
+        &self,
+        mut create_info: SamplerCreateInfo,
+    ) -> Result<Arc<Sampler>, SamplerCreationError> {
+        if !self.uses_image_view_min_lod() {
+            let min_lod = create_info.lod.start().max(self.most_detailed_level as f32);
+            let max_lod = create_info.lod.end().max(min_lod);
+            create_info.lod = min_lod..=max_lod;
+        }
+
+        Sampler::new(self.image.inner().image.device().clone(), create_info)
+    }
+}
+
+impl<I> MipResidency<I>
+where
+    I: ImageAccess + 'static,
+{
+    /// Records a copy of `src_buffer` into all array layers of mip level `level` of the image.
+    ///
+    /// The buffer must contain the tightly packed texels of the level. The level doesn't become
+    /// resident until [`set_most_detailed_level`](Self::set_most_detailed_level) is called.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `level` is not less than the number of mip levels of the image.
+    pub fn upload_level<'a, L, P>(
+        &self,
+        builder: &'a mut AutoCommandBufferBuilder<L, P>,
+        level: u32,
+        src_buffer: Arc<dyn BufferAccess>,
+    ) -> Result<&'a mut AutoCommandBufferBuilder<L, P>, CopyError> {
+        let dimensions = self.image.dimensions().mip_level_dimensions(level).unwrap();
+        let mut image_subresource = self.image.subresource_layers();
+        image_subresource.mip_level = level;
+
+        builder.copy_buffer_to_image(CopyBufferToImageInfo {
+            regions: smallvec![BufferImageCopy {
+                image_subresource,
+                image_extent: dimensions.width_height_depth(),
+                ..Default::default()
+            }],
+            ..CopyBufferToImageInfo::buffer_image(src_buffer, self.image.clone())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MipResidency;
+    use crate::{
+        format::Format,
+        image::{ImageCreateFlags, ImageDimensions, ImageLayout, ImageUsage, ImmutableImage},
+        sampler::{SamplerCreateInfo, LOD_CLAMP_NONE},
+    };
+
+    #[test]
+    fn sampler_lod_clamp() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let (image, _) = ImmutableImage::uninitialized(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: 16,
+                height: 16,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            5,
+            ImageUsage {
+                transfer_dst: true,
+                sampled: true,
+                ..ImageUsage::none()
+            },
+            ImageCreateFlags::none(),
+            ImageLayout::ShaderReadOnlyOptimal,
+            [queue.family()],
+        )
+        .unwrap();
+
+        let mut residency = MipResidency::new(image);
+        assert_eq!(residency.most_detailed_level(), 4);
+        residency.set_most_detailed_level(2);
+        assert!(!residency.uses_image_view_min_lod());
+
+        let view = residency.image_view().unwrap();
+        assert_eq!(view.min_lod(), 0.0);
+
+        let sampler = residency
+            .sampler(SamplerCreateInfo {
+                lod: 0.0..=LOD_CLAMP_NONE,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(sampler.lod(), 2.0..=LOD_CLAMP_NONE);
+    }
+}
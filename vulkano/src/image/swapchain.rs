@@ -11,7 +11,7 @@ use super::{traits::ImageContent, ImageAccess, ImageDescriptorLayouts, ImageInne
 use crate::{
     device::{Device, DeviceOwned},
     swapchain::Swapchain,
-    OomError,
+    OomError, VulkanObject,
 };
 use std::{
     hash::{Hash, Hasher},
@@ -82,6 +82,15 @@ unsafe impl<W> DeviceOwned for SwapchainImage<W> {
     }
 }
 
+unsafe impl<W> VulkanObject for SwapchainImage<W> {
+    type Object = ash::vk::Image;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Image {
+        self.my_image().image.internal_object()
+    }
+}
+
 unsafe impl<W> ImageAccess for SwapchainImage<W>
 where
     W: Send + Sync,
@@ -16,27 +16,33 @@
 use super::{
     ImageAspect, ImageAspects, ImageCreateFlags, ImageDimensions, ImageLayout,
     ImageSubresourceLayers, ImageSubresourceRange, ImageTiling, ImageUsage, SampleCount,
-    SampleCounts,
+    SampleCounts, SparseImageFormatInfo, SparseImageMemoryRequirements,
 };
 use crate::image::view::ImageViewCreationError;
 use crate::range_map::RangeMap;
 use crate::{
-    buffer::cpu_access::{ReadLockError, WriteLockError},
+    buffer::{
+        cpu_access::{ReadLockError, WriteLockError},
+        SparseLevel,
+    },
     check_errors,
     device::{Device, DeviceOwned},
-    format::{ChromaSampling, Format, FormatFeatures, NumericType},
+    format::{ChromaSampling, DrmFormatModifierProperties, Format, FormatFeatures, NumericType},
     image::{ImageFormatInfo, ImageFormatProperties, ImageType},
     memory::{
         DeviceMemory, DeviceMemoryAllocationError, ExternalMemoryHandleType,
         ExternalMemoryHandleTypes, MemoryRequirements,
     },
     sync::{AccessError, CurrentAccess, Sharing},
+    video::{VideoProfile, VideoProfilesVk},
     DeviceSize, Error, OomError, Version, VulkanObject,
 };
 use ash::vk::Handle;
 use parking_lot::{Mutex, MutexGuard};
 use smallvec::{smallvec, SmallVec};
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     error, fmt,
     hash::{Hash, Hasher},
     iter::{FusedIterator, Peekable},
@@ -71,10 +77,15 @@ pub struct UnsafeImage {
     samples: SampleCount,
     tiling: ImageTiling,
     usage: ImageUsage,
+    external_memory_handle_types: ExternalMemoryHandleTypes,
     mutable_format: bool,
     cube_compatible: bool,
     array_2d_compatible: bool,
+    view_2d_compatible: bool,
     block_texel_view_compatible: bool,
+    view_formats: SmallVec<[Format; 4]>,
+    drm_format_modifier: Option<u64>,
+    sparse: Option<SparseLevel>,
 
     aspect_list: SmallVec<[ImageAspect; 4]>,
     aspect_size: DeviceSize,
@@ -82,6 +93,9 @@ pub struct UnsafeImage {
     needs_destruction: bool, // `vkDestroyImage` is called only if true.
     range_size: DeviceSize,
     state: Mutex<ImageState>,
+
+    // The memory currently bound to a sparse image.
+    sparse_memory: Mutex<SparseImageMemory>,
 }
 
 impl UnsafeImage {
@@ -105,9 +119,23 @@ impl UnsafeImage {
         device: Arc<Device>,
         mut create_info: UnsafeImageCreateInfo,
     ) -> Result<Arc<UnsafeImage>, ImageCreationError> {
-        let format_features = Self::validate(&device, &mut create_info)?;
+        let mut format_features = Self::validate(&device, &mut create_info)?;
         let handle = unsafe { Self::create(&device, &create_info)? };
 
+        // With a list of DRM format modifiers, the implementation picks one of them, so the
+        // features are those of the modifier that was actually chosen.
+        let drm_format_modifier = if create_info.tiling == ImageTiling::DrmFormatModifier {
+            let drm_format_modifier = unsafe { Self::query_drm_format_modifier(&device, handle)? };
+            format_features = Self::drm_format_modifier_features(
+                &device,
+                create_info.format.unwrap(),
+                drm_format_modifier,
+            );
+            Some(drm_format_modifier)
+        } else {
+            None
+        };
+
         let UnsafeImageCreateInfo {
             dimensions,
             format,
@@ -121,7 +149,14 @@ impl UnsafeImage {
             mutable_format,
             cube_compatible,
             array_2d_compatible,
+            view_2d_compatible,
             block_texel_view_compatible,
+            view_formats,
+            video_profiles: _,
+            drm_format_modifiers: _,
+            drm_format_modifier_plane_layouts: _,
+            sparse,
+            debug_name,
             _ne: _,
         } = create_info;
 
@@ -143,10 +178,15 @@ impl UnsafeImage {
             samples,
             tiling,
             usage,
+            external_memory_handle_types,
             mutable_format,
             cube_compatible,
             array_2d_compatible,
+            view_2d_compatible,
             block_texel_view_compatible,
+            view_formats,
+            drm_format_modifier,
+            sparse,
 
             aspect_list,
             aspect_size,
@@ -154,11 +194,119 @@ impl UnsafeImage {
             needs_destruction: true,
             range_size,
             state: Mutex::new(ImageState::new(range_size, initial_layout)),
+            sparse_memory: Mutex::new(SparseImageMemory::default()),
         };
 
+        image.device.set_debug_name(&image, debug_name.as_deref())?;
+
         Ok(Arc::new(image))
     }
 
+    /// Creates a new `UnsafeImage` from a raw handle that was created outside of vulkano.
+    ///
+    /// `create_info` must describe how the image was created. It is not validated, and
+    /// `debug_name` is ignored. The image is destroyed when the returned `UnsafeImage` is dropped.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.format` is `None`.
+    /// - Panics if `create_info.tiling` is [`ImageTiling::DrmFormatModifier`] and querying the
+    ///   DRM format modifier of the image fails.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid image handle, created from `device`.
+    /// - `create_info` must match the parameters that the image was created with.
+    /// - The image must be in `create_info.initial_layout`.
+    /// - The caller must not destroy the image, and must not use it after the returned
+    ///   `UnsafeImage` is dropped.
+    /// - If memory is already bound to the image, it must be kept alive for as long as the image
+    ///   is used.
+    pub unsafe fn from_handle(
+        device: Arc<Device>,
+        handle: ash::vk::Image,
+        create_info: UnsafeImageCreateInfo,
+    ) -> Arc<UnsafeImage> {
+        let UnsafeImageCreateInfo {
+            dimensions,
+            format,
+            mip_levels,
+            samples,
+            tiling,
+            usage,
+            sharing: _,
+            initial_layout,
+            external_memory_handle_types,
+            mutable_format,
+            cube_compatible,
+            array_2d_compatible,
+            view_2d_compatible,
+            block_texel_view_compatible,
+            view_formats,
+            video_profiles: _,
+            drm_format_modifiers: _,
+            drm_format_modifier_plane_layouts: _,
+            sparse,
+            debug_name: _,
+            _ne: _,
+        } = create_info;
+
+        let drm_format_modifier = (tiling == ImageTiling::DrmFormatModifier).then(|| {
+            Self::query_drm_format_modifier(&device, handle)
+                .expect("failed to query the DRM format modifier of the image")
+        });
+
+        let format_features = {
+            let format_properties = device.physical_device().format_properties(format.unwrap());
+            match tiling {
+                ImageTiling::Linear => format_properties.linear_tiling_features,
+                ImageTiling::Optimal => format_properties.optimal_tiling_features,
+                ImageTiling::DrmFormatModifier => Self::drm_format_modifier_features(
+                    &device,
+                    format.unwrap(),
+                    drm_format_modifier.unwrap(),
+                ),
+            }
+        };
+
+        let aspects = format.unwrap().aspects();
+        let aspect_list: SmallVec<[ImageAspect; 4]> = aspects.iter().collect();
+        let mip_level_size = dimensions.array_layers() as DeviceSize;
+        let aspect_size = mip_level_size * mip_levels as DeviceSize;
+        let range_size = aspect_list.len() as DeviceSize * aspect_size;
+
+        Arc::new(UnsafeImage {
+            device,
+            handle,
+
+            dimensions,
+            format,
+            format_features,
+            mip_levels,
+            initial_layout,
+            samples,
+            tiling,
+            usage,
+            external_memory_handle_types,
+            mutable_format,
+            cube_compatible,
+            array_2d_compatible,
+            view_2d_compatible,
+            block_texel_view_compatible,
+            view_formats,
+            drm_format_modifier,
+            sparse,
+
+            aspect_list,
+            aspect_size,
+            mip_level_size,
+            needs_destruction: true,
+            range_size,
+            state: Mutex::new(ImageState::new(range_size, initial_layout)),
+            sparse_memory: Mutex::new(SparseImageMemory::default()),
+        })
+    }
+
     fn validate(
         device: &Device,
         create_info: &mut UnsafeImageCreateInfo,
@@ -176,7 +324,14 @@ impl UnsafeImage {
             mutable_format,
             cube_compatible,
             array_2d_compatible,
+            view_2d_compatible,
             block_texel_view_compatible,
+            ref view_formats,
+            ref video_profiles,
+            ref drm_format_modifiers,
+            ref drm_format_modifier_plane_layouts,
+            sparse,
+            debug_name: _,
             _ne: _,
         } = create_info;
 
@@ -215,12 +370,78 @@ impl UnsafeImage {
         // VUID-VkImageCreateInfo-flags-01573
         assert!(!(block_texel_view_compatible && !mutable_format));
 
+        /* Check DRM format modifiers */
+
+        let drm_format_modifier_properties: SmallVec<[DrmFormatModifierProperties; 1]> = if tiling
+            == ImageTiling::DrmFormatModifier
+        {
+            if !device.enabled_extensions().ext_image_drm_format_modifier {
+                return Err(ImageCreationError::ExtensionNotEnabled {
+                    extension: "ext_image_drm_format_modifier",
+                    reason: "tiling was `DrmFormatModifier`",
+                });
+            }
+
+            // VUID-VkImageCreateInfo-tiling-02261
+            if drm_format_modifiers.is_empty() {
+                return Err(ImageCreationError::DrmFormatModifiersEmpty);
+            }
+
+            // VUID-VkImageCreateInfo-tiling-02353
+            if mutable_format && view_formats.is_empty() {
+                return Err(ImageCreationError::DrmFormatModifierMutableFormatNoViewFormats);
+            }
+
+            if !drm_format_modifier_plane_layouts.is_empty() && drm_format_modifiers.len() != 1 {
+                return Err(ImageCreationError::DrmFormatModifierPlaneLayoutsMultipleModifiers);
+            }
+
+            let supported_properties = physical_device.drm_format_modifier_properties(format);
+            let drm_format_modifier_properties = drm_format_modifiers
+                .iter()
+                .map(|&modifier| {
+                    supported_properties
+                        .iter()
+                        .find(|properties| properties.drm_format_modifier == modifier)
+                        .copied()
+                        .ok_or(ImageCreationError::DrmFormatModifierNotSupported { modifier })
+                })
+                .collect::<Result<SmallVec<[_; 1]>, _>>()?;
+
+            // VUID-VkImageDrmFormatModifierExplicitCreateInfoEXT-drmFormatModifierPlaneCount-02265
+            if !drm_format_modifier_plane_layouts.is_empty()
+                && drm_format_modifier_plane_layouts.len() as u32
+                    != drm_format_modifier_properties[0].drm_format_modifier_plane_count
+            {
+                return Err(ImageCreationError::DrmFormatModifierPlaneCountMismatch {
+                    provided: drm_format_modifier_plane_layouts.len() as u32,
+                    required: drm_format_modifier_properties[0].drm_format_modifier_plane_count,
+                });
+            }
+
+            drm_format_modifier_properties
+        } else {
+            if !drm_format_modifiers.is_empty() || !drm_format_modifier_plane_layouts.is_empty() {
+                return Err(ImageCreationError::DrmFormatModifiersNotDrmTiling);
+            }
+
+            SmallVec::new()
+        };
+
         // Get format features
         let format_features = {
             let format_properties = physical_device.format_properties(format);
             match tiling {
                 ImageTiling::Linear => format_properties.linear_tiling_features,
                 ImageTiling::Optimal => format_properties.optimal_tiling_features,
+                // The implementation may choose any of the listed modifiers, so a feature is
+                // available if at least one of them supports it.
+                ImageTiling::DrmFormatModifier => drm_format_modifier_properties.iter().fold(
+                    FormatFeatures::default(),
+                    |features, properties| {
+                        &features | &properties.drm_format_modifier_tiling_features
+                    },
+                ),
             }
         };
 
@@ -363,6 +584,48 @@ impl UnsafeImage {
             });
         }
 
+        if usage.fragment_density_map {
+            if !device.enabled_extensions().ext_fragment_density_map {
+                return Err(ImageCreationError::ExtensionNotEnabled {
+                    extension: "ext_fragment_density_map",
+                    reason: "usage included `fragment_density_map`",
+                });
+            }
+
+            if !format_features.fragment_density_map {
+                return Err(ImageCreationError::FormatUsageNotSupported {
+                    usage: "fragment_density_map",
+                });
+            }
+        }
+
+        if usage.video_decode_dst || usage.video_decode_src || usage.video_decode_dpb {
+            if !device.enabled_extensions().khr_video_decode_queue {
+                return Err(ImageCreationError::ExtensionNotEnabled {
+                    extension: "khr_video_decode_queue",
+                    reason: "usage included `video_decode_dst`, `video_decode_src` or \
+                        `video_decode_dpb`",
+                });
+            }
+
+            // VUID-VkImageCreateInfo-usage-04815
+            if video_profiles.is_empty() {
+                return Err(ImageCreationError::VideoProfilesEmpty);
+            }
+
+            if usage.video_decode_dst && !format_features.video_decode_output {
+                return Err(ImageCreationError::FormatUsageNotSupported {
+                    usage: "video_decode_dst",
+                });
+            }
+
+            if usage.video_decode_dpb && !format_features.video_decode_dpb {
+                return Err(ImageCreationError::FormatUsageNotSupported {
+                    usage: "video_decode_dpb",
+                });
+            }
+        }
+
         // VUID-VkImageCreateInfo-usage-00964
         // VUID-VkImageCreateInfo-usage-00965
         if (usage.color_attachment
@@ -437,6 +700,20 @@ impl UnsafeImage {
             }
         }
 
+        if view_2d_compatible {
+            if !device.enabled_extensions().ext_image_2d_view_of_3d {
+                return Err(ImageCreationError::ExtensionNotEnabled {
+                    extension: "ext_image_2d_view_of_3d",
+                    reason: "view_2d_compatible was set",
+                });
+            }
+
+            // VUID-VkImageCreateInfo-flags-07755
+            if image_type != ImageType::Dim3d {
+                return Err(ImageCreationError::View2dCompatibleNot3d);
+            }
+        }
+
         if block_texel_view_compatible {
             // VUID-VkImageCreateInfo-flags-01572
             if format.compression().is_none() {
@@ -444,6 +721,136 @@ impl UnsafeImage {
             }
         }
 
+        if !view_formats.is_empty() {
+            if !(device.api_version() >= Version::V1_2
+                || device.enabled_extensions().khr_image_format_list)
+            {
+                return Err(ImageCreationError::ExtensionNotEnabled {
+                    extension: "khr_image_format_list",
+                    reason: "view_formats was not empty",
+                });
+            }
+
+            // VUID-VkImageCreateInfo-flags-04738
+            if !mutable_format && !(view_formats.len() == 1 && view_formats[0] == format) {
+                return Err(ImageCreationError::ViewFormatsNotMutable);
+            }
+
+            for &view_format in view_formats.iter() {
+                // VUID-VkImageCreateInfo-pNext-06722
+                if !(view_format.compatibility() == format.compatibility()
+                    || block_texel_view_compatible
+                        && view_format.block_size() == format.block_size())
+                {
+                    return Err(ImageCreationError::ViewFormatNotCompatible { view_format });
+                }
+            }
+        }
+
+        /* Check sparse binding */
+
+        if let Some(sparse_level) = sparse {
+            // VUID-VkImageCreateInfo-flags-00969
+            if !device.enabled_features().sparse_binding {
+                return Err(ImageCreationError::FeatureNotEnabled {
+                    feature: "sparse_binding",
+                    reason: "sparse was `Some`",
+                });
+            }
+
+            // VUID-VkImageCreateInfo-None-01925
+            if usage.transient_attachment {
+                return Err(ImageCreationError::SparseTransientAttachment);
+            }
+
+            if sparse_level.sparse_residency {
+                // VUID-VkImageCreateInfo-tiling-04121
+                if tiling != ImageTiling::Optimal {
+                    return Err(ImageCreationError::SparseResidencyNotOptimalTiling);
+                }
+
+                match image_type {
+                    // VUID-VkImageCreateInfo-imageType-00970
+                    ImageType::Dim1d => {
+                        return Err(ImageCreationError::SparseResidency1d);
+                    }
+                    // VUID-VkImageCreateInfo-imageType-00971
+                    ImageType::Dim2d => {
+                        if !device.enabled_features().sparse_residency_image2_d {
+                            return Err(ImageCreationError::FeatureNotEnabled {
+                                feature: "sparse_residency_image2_d",
+                                reason: "sparse was `Some`, `sparse_residency` was set and the image type was 2D",
+                            });
+                        }
+                    }
+                    // VUID-VkImageCreateInfo-imageType-00972
+                    ImageType::Dim3d => {
+                        if !device.enabled_features().sparse_residency_image3_d {
+                            return Err(ImageCreationError::FeatureNotEnabled {
+                                feature: "sparse_residency_image3_d",
+                                reason: "sparse was `Some`, `sparse_residency` was set and the image type was 3D",
+                            });
+                        }
+                    }
+                }
+
+                // VUID-VkImageCreateInfo-imageType-00973
+                // VUID-VkImageCreateInfo-imageType-00974
+                // VUID-VkImageCreateInfo-imageType-00975
+                // VUID-VkImageCreateInfo-imageType-00976
+                let samples_feature = match samples {
+                    SampleCount::Sample2 => Some((
+                        device.enabled_features().sparse_residency2_samples,
+                        "sparse_residency2_samples",
+                    )),
+                    SampleCount::Sample4 => Some((
+                        device.enabled_features().sparse_residency4_samples,
+                        "sparse_residency4_samples",
+                    )),
+                    SampleCount::Sample8 => Some((
+                        device.enabled_features().sparse_residency8_samples,
+                        "sparse_residency8_samples",
+                    )),
+                    SampleCount::Sample16 => Some((
+                        device.enabled_features().sparse_residency16_samples,
+                        "sparse_residency16_samples",
+                    )),
+                    _ => None,
+                };
+
+                if let Some((false, feature)) = samples_feature {
+                    return Err(ImageCreationError::FeatureNotEnabled {
+                        feature,
+                        reason: "sparse was `Some`, `sparse_residency` was set and the image was multisampled",
+                    });
+                }
+
+                // The format must support sparse residency for this image configuration, which is
+                // the case if the implementation reports sparse properties for it.
+                if physical_device
+                    .sparse_image_format_properties(SparseImageFormatInfo {
+                        format: Some(format),
+                        image_type,
+                        samples,
+                        usage,
+                        tiling,
+                        ..Default::default()
+                    })
+                    .is_empty()
+                {
+                    return Err(ImageCreationError::SparseResidencyFormatNotSupported);
+                }
+            }
+
+            // VUID-VkImageCreateInfo-flags-01924
+            if sparse_level.sparse_aliased && !device.enabled_features().sparse_residency_aliased {
+                return Err(ImageCreationError::FeatureNotEnabled {
+                    feature: "sparse_residency_aliased",
+                    reason: "sparse was `Some` and `sparse_aliased` was set",
+                });
+            }
+        }
+
         /* Check sharing mode and queue families */
 
         match sharing {
@@ -647,12 +1054,16 @@ impl UnsafeImage {
             || mip_levels_must_query()
             || array_layers_must_query()
             || samples_must_query()
-            || linear_must_query();
+            || linear_must_query()
+            || !external_memory_handle_types.is_empty()
+            || !video_profiles.is_empty()
+            || tiling == ImageTiling::DrmFormatModifier
+            || sparse.is_some();
 
         // We determined that we must query the device in order to be sure that the image
         // configuration is supported.
         if must_query_device {
-            let external_memory_handle_types: SmallVec<[Option<ExternalMemoryHandleType>; 4]> =
+            let handle_types_to_query: SmallVec<[Option<ExternalMemoryHandleType>; 4]> =
                 if !external_memory_handle_types.is_empty() {
                     // If external memory handles are used, the properties need to be queried
                     // individually for each handle type.
@@ -663,23 +1074,39 @@ impl UnsafeImage {
                 } else {
                     smallvec![None]
                 };
+            let drm_format_modifiers_to_query: SmallVec<[Option<u64>; 1]> =
+                if tiling == ImageTiling::DrmFormatModifier {
+                    drm_format_modifiers.iter().copied().map(Some).collect()
+                } else {
+                    smallvec![None]
+                };
 
-            for external_memory_handle_type in external_memory_handle_types {
-                let image_format_properties =
-                    device
-                        .physical_device()
-                        .image_format_properties(ImageFormatInfo {
-                            format: Some(format),
-                            image_type,
-                            tiling,
-                            usage,
-                            mutable_format,
-                            cube_compatible,
-                            array_2d_compatible,
-                            block_texel_view_compatible,
-                            external_memory_handle_type,
-                            ..Default::default()
-                        })?;
+            for external_memory_handle_type in handle_types_to_query {
+                // The implementation picks one of the listed DRM format modifiers, so the image
+                // configuration only needs to be supported with one of them.
+                let image_format_properties = drm_format_modifiers_to_query
+                    .iter()
+                    .find_map(|&drm_format_modifier| {
+                        physical_device
+                            .image_format_properties(ImageFormatInfo {
+                                format: Some(format),
+                                image_type,
+                                tiling,
+                                usage,
+                                mutable_format,
+                                cube_compatible,
+                                array_2d_compatible,
+                                view_2d_compatible,
+                                block_texel_view_compatible,
+                                external_memory_handle_type,
+                                video_profiles: video_profiles.clone(),
+                                drm_format_modifier,
+                                sparse,
+                                ..Default::default()
+                            })
+                            .transpose()
+                    })
+                    .transpose()?;
 
                 let ImageFormatProperties {
                     max_extent,
@@ -687,12 +1114,23 @@ impl UnsafeImage {
                     max_array_layers,
                     sample_counts,
                     max_resource_size,
+                    external_memory_properties,
                     ..
                 } = match image_format_properties {
                     Some(x) => x,
                     None => return Err(ImageCreationError::ImageFormatPropertiesNotSupported),
                 };
 
+                // VUID-VkImageCreateInfo-pNext-00990
+                if external_memory_handle_type.is_some()
+                    && !ash::vk::ExternalMemoryHandleTypeFlags::from(
+                        external_memory_properties.compatible_handle_types,
+                    )
+                    .contains(external_memory_handle_types.into())
+                {
+                    return Err(ImageCreationError::ExternalMemoryHandleTypesNotCompatible);
+                }
+
                 // VUID-VkImageCreateInfo-extent-02252
                 // VUID-VkImageCreateInfo-extent-02253
                 // VUID-VkImageCreateInfo-extent-02254
@@ -754,14 +1192,25 @@ impl UnsafeImage {
             mutable_format,
             cube_compatible,
             array_2d_compatible,
+            view_2d_compatible,
             block_texel_view_compatible,
+            ref view_formats,
+            ref video_profiles,
+            ref drm_format_modifiers,
+            ref drm_format_modifier_plane_layouts,
+            sparse,
+            debug_name: _,
             _ne: _,
         } = create_info;
 
         let flags = ImageCreateFlags {
+            sparse_binding: sparse.is_some(),
+            sparse_residency: sparse.map_or(false, |sparse| sparse.sparse_residency),
+            sparse_aliased: sparse.map_or(false, |sparse| sparse.sparse_aliased),
             mutable_format,
             cube_compatible,
             array_2d_compatible,
+            view_2d_compatible,
             block_texel_view_compatible,
             ..ImageCreateFlags::none()
         };
@@ -819,6 +1268,73 @@ impl UnsafeImage {
             create_info = create_info.push_next(next);
         }
 
+        let view_formats_vk: SmallVec<[_; 4]> =
+            view_formats.iter().copied().map(Into::into).collect();
+        let mut image_format_list_create_info = if !view_formats.is_empty() {
+            Some(ash::vk::ImageFormatListCreateInfo {
+                view_format_count: view_formats_vk.len() as u32,
+                p_view_formats: view_formats_vk.as_ptr(),
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+
+        if let Some(next) = image_format_list_create_info.as_mut() {
+            create_info = create_info.push_next(next);
+        }
+
+        let video_profiles_vk = VideoProfilesVk::new(video_profiles);
+        let mut video_profile_list_info = if !video_profiles.is_empty() {
+            Some(video_profiles_vk.profile_list_info())
+        } else {
+            None
+        };
+
+        if let Some(next) = video_profile_list_info.as_mut() {
+            create_info = create_info.push_next(next);
+        }
+
+        let plane_layouts_vk: SmallVec<[_; 4]> = drm_format_modifier_plane_layouts
+            .iter()
+            .map(|layout| ash::vk::SubresourceLayout {
+                offset: layout.offset,
+                size: layout.size,
+                row_pitch: layout.row_pitch,
+                array_pitch: layout.array_pitch,
+                depth_pitch: layout.depth_pitch,
+            })
+            .collect();
+        let mut drm_format_modifier_explicit_create_info =
+            if !drm_format_modifier_plane_layouts.is_empty() {
+                Some(ash::vk::ImageDrmFormatModifierExplicitCreateInfoEXT {
+                    drm_format_modifier: drm_format_modifiers[0],
+                    drm_format_modifier_plane_count: plane_layouts_vk.len() as u32,
+                    p_plane_layouts: plane_layouts_vk.as_ptr(),
+                    ..Default::default()
+                })
+            } else {
+                None
+            };
+        let mut drm_format_modifier_list_create_info =
+            if !drm_format_modifiers.is_empty() && drm_format_modifier_plane_layouts.is_empty() {
+                Some(ash::vk::ImageDrmFormatModifierListCreateInfoEXT {
+                    drm_format_modifier_count: drm_format_modifiers.len() as u32,
+                    p_drm_format_modifiers: drm_format_modifiers.as_ptr(),
+                    ..Default::default()
+                })
+            } else {
+                None
+            };
+
+        if let Some(next) = drm_format_modifier_explicit_create_info.as_mut() {
+            create_info = create_info.push_next(next);
+        }
+
+        if let Some(next) = drm_format_modifier_list_create_info.as_mut() {
+            create_info = create_info.push_next(next);
+        }
+
         let handle = {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -834,6 +1350,40 @@ impl UnsafeImage {
         Ok(handle)
     }
 
+    // Returns the DRM format modifier that the implementation chose for an image created with
+    // `ImageTiling::DrmFormatModifier`.
+    unsafe fn query_drm_format_modifier(
+        device: &Device,
+        handle: ash::vk::Image,
+    ) -> Result<u64, OomError> {
+        let fns = device.fns();
+        let mut properties = ash::vk::ImageDrmFormatModifierPropertiesEXT::default();
+        check_errors((fns
+            .ext_image_drm_format_modifier
+            .get_image_drm_format_modifier_properties_ext)(
+            device.internal_object(),
+            handle,
+            &mut properties,
+        ))?;
+
+        Ok(properties.drm_format_modifier)
+    }
+
+    // Returns the format features of `format` when used with the given DRM format modifier.
+    fn drm_format_modifier_features(
+        device: &Device,
+        format: Format,
+        drm_format_modifier: u64,
+    ) -> FormatFeatures {
+        device
+            .physical_device()
+            .drm_format_modifier_properties(format)
+            .into_iter()
+            .find(|properties| properties.drm_format_modifier == drm_format_modifier)
+            .map(|properties| properties.drm_format_modifier_tiling_features)
+            .unwrap_or_default()
+    }
+
     /// Creates an image from a raw handle. The image won't be destroyed.
     ///
     /// This function is for example used at the swapchain's initialization.
@@ -843,6 +1393,7 @@ impl UnsafeImage {
         usage: ImageUsage,
         format: Format,
         flags: ImageCreateFlags,
+        view_formats: SmallVec<[Format; 4]>,
         dimensions: ImageDimensions,
         samples: SampleCount,
         mip_levels: u32,
@@ -885,10 +1436,15 @@ impl UnsafeImage {
             samples,
             tiling,
             usage,
+            external_memory_handle_types: ExternalMemoryHandleTypes::none(),
             mutable_format: flags.mutable_format,
             cube_compatible: flags.cube_compatible,
             array_2d_compatible: flags.array_2d_compatible,
+            view_2d_compatible: flags.view_2d_compatible,
             block_texel_view_compatible: flags.block_texel_view_compatible,
+            view_formats,
+            drm_format_modifier: None,
+            sparse: None,
 
             aspect_list,
             aspect_size,
@@ -896,12 +1452,16 @@ impl UnsafeImage {
             needs_destruction: false, // TODO: pass as parameter
             range_size,
             state: Mutex::new(ImageState::new(range_size, initial_layout)),
+            sparse_memory: Mutex::new(SparseImageMemory::default()),
         };
 
         Arc::new(image)
     }
 
     /// Returns the memory requirements for this image.
+    ///
+    /// If the image was created with the `android_hardware_buffer` external memory handle type,
+    /// this must only be called after memory has been bound to it.
     pub fn memory_requirements(&self) -> MemoryRequirements {
         let image_memory_requirements_info2 = ash::vk::ImageMemoryRequirementsInfo2 {
             image: self.handle,
@@ -961,6 +1521,79 @@ impl UnsafeImage {
         }
     }
 
+    /// Returns the requirements for the memory that is bound to a sparse residency image.
+    ///
+    /// There is one element for each group of aspects of the format that share the same
+    /// requirements. The list is empty if the image was not created with `sparse_residency`.
+    pub fn sparse_memory_requirements(&self) -> Vec<SparseImageMemoryRequirements> {
+        let image_sparse_memory_requirements_info2 = ash::vk::ImageSparseMemoryRequirementsInfo2 {
+            image: self.handle,
+            ..Default::default()
+        };
+
+        unsafe {
+            let fns = self.device.fns();
+
+            if self.device.api_version() >= Version::V1_1
+                || self
+                    .device
+                    .enabled_extensions()
+                    .khr_get_memory_requirements2
+            {
+                let get_image_sparse_memory_requirements2 =
+                    if self.device.api_version() >= Version::V1_1 {
+                        fns.v1_1.get_image_sparse_memory_requirements2
+                    } else {
+                        fns.khr_get_memory_requirements2
+                            .get_image_sparse_memory_requirements2_khr
+                    };
+
+                let mut count = 0;
+                get_image_sparse_memory_requirements2(
+                    self.device.internal_object(),
+                    &image_sparse_memory_requirements_info2,
+                    &mut count,
+                    ptr::null_mut(),
+                );
+
+                let mut requirements =
+                    vec![ash::vk::SparseImageMemoryRequirements2::default(); count as usize];
+                get_image_sparse_memory_requirements2(
+                    self.device.internal_object(),
+                    &image_sparse_memory_requirements_info2,
+                    &mut count,
+                    requirements.as_mut_ptr(),
+                );
+                requirements.truncate(count as usize);
+
+                requirements
+                    .into_iter()
+                    .map(|requirements2| requirements2.memory_requirements.into())
+                    .collect()
+            } else {
+                let mut count = 0;
+                (fns.v1_0.get_image_sparse_memory_requirements)(
+                    self.device.internal_object(),
+                    self.handle,
+                    &mut count,
+                    ptr::null_mut(),
+                );
+
+                let mut requirements =
+                    vec![ash::vk::SparseImageMemoryRequirements::default(); count as usize];
+                (fns.v1_0.get_image_sparse_memory_requirements)(
+                    self.device.internal_object(),
+                    self.handle,
+                    &mut count,
+                    requirements.as_mut_ptr(),
+                );
+                requirements.truncate(count as usize);
+
+                requirements.into_iter().map(Into::into).collect()
+            }
+        }
+    }
+
     pub unsafe fn bind_memory(
         &self,
         memory: &DeviceMemory,
@@ -968,20 +1601,23 @@ impl UnsafeImage {
     ) -> Result<(), OomError> {
         let fns = self.device.fns();
 
-        // We check for correctness in debug mode.
-        debug_assert!({
-            let mut mem_reqs = MaybeUninit::uninit();
-            (fns.v1_0.get_image_memory_requirements)(
-                self.device.internal_object(),
-                self.handle,
-                mem_reqs.as_mut_ptr(),
-            );
+        // We check for correctness in debug mode. The memory requirements of an image that uses
+        // Android hardware buffer memory can't be queried before it is bound.
+        debug_assert!(
+            self.external_memory_handle_types.android_hardware_buffer || {
+                let mut mem_reqs = MaybeUninit::uninit();
+                (fns.v1_0.get_image_memory_requirements)(
+                    self.device.internal_object(),
+                    self.handle,
+                    mem_reqs.as_mut_ptr(),
+                );
 
-            let mem_reqs = mem_reqs.assume_init();
-            mem_reqs.size <= memory.allocation_size() - offset
-                && offset % mem_reqs.alignment == 0
-                && mem_reqs.memory_type_bits & (1 << memory.memory_type().id()) != 0
-        });
+                let mem_reqs = mem_reqs.assume_init();
+                mem_reqs.size <= memory.allocation_size() - offset
+                    && offset % mem_reqs.alignment == 0
+                    && mem_reqs.memory_type_bits & (1 << memory.memory_type().id()) != 0
+            }
+        );
 
         check_errors((fns.v1_0.bind_image_memory)(
             self.device.internal_object(),
@@ -1097,6 +1733,28 @@ impl UnsafeImage {
         self.state.lock()
     }
 
+    #[inline]
+    pub(crate) fn sparse_memory(&self) -> MutexGuard<SparseImageMemory> {
+        self.sparse_memory.lock()
+    }
+
+    /// Checks that a sparse image that is not partially resident is entirely bound to memory, as
+    /// is required before it is used by the GPU. Always succeeds for other images.
+    pub(crate) fn check_sparse_bound(&self) -> Result<(), AccessError> {
+        match self.sparse {
+            Some(sparse_level) if !sparse_level.sparse_residency => {
+                let size = self.memory_requirements().size;
+
+                if self.sparse_memory.lock().opaque.contains_all(&(0..size)) {
+                    Ok(())
+                } else {
+                    Err(AccessError::SparseNotBound)
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Returns the dimensions of the image.
     #[inline]
     pub fn dimensions(&self) -> ImageDimensions {
@@ -1145,6 +1803,12 @@ impl UnsafeImage {
         &self.usage
     }
 
+    /// Returns the external memory handle types that are supported with this image.
+    #[inline]
+    pub fn external_memory_handle_types(&self) -> ExternalMemoryHandleTypes {
+        self.external_memory_handle_types
+    }
+
     /// Returns whether `mutable_format` is enabled on the image.
     #[inline]
     pub fn mutable_format(&self) -> bool {
@@ -1163,12 +1827,39 @@ impl UnsafeImage {
         self.array_2d_compatible
     }
 
+    /// Returns whether `view_2d_compatible` is enabled on the image.
+    #[inline]
+    pub fn view_2d_compatible(&self) -> bool {
+        self.view_2d_compatible
+    }
+
     /// Returns whether `block_texel_view_compatible` is enabled on the image.
     #[inline]
     pub fn block_texel_view_compatible(&self) -> bool {
         self.block_texel_view_compatible
     }
 
+    /// Returns the formats that image views of the image are allowed to have. If empty, the image
+    /// views are not restricted beyond the usual compatibility rules.
+    #[inline]
+    pub fn view_formats(&self) -> &[Format] {
+        &self.view_formats
+    }
+
+    /// If the image was created with [`ImageTiling::DrmFormatModifier`], returns the DRM format
+    /// modifier that the implementation chose for it.
+    #[inline]
+    pub fn drm_format_modifier(&self) -> Option<u64> {
+        self.drm_format_modifier
+    }
+
+    /// Returns the sparse binding parameters that the image was created with, or `None` if the
+    /// image is not sparse.
+    #[inline]
+    pub fn sparse(&self) -> Option<SparseLevel> {
+        self.sparse
+    }
+
     /// Returns an `ImageSubresourceLayers` covering the first mip level of the image. All aspects
     /// of the image are selected, or `plane0` if the image is multi-planar.
     #[inline]
@@ -1299,6 +1990,32 @@ impl UnsafeImage {
         self.linear_layout_impl(0, aspect)
     }
 
+    /// Returns the layout of a memory plane of an image that was created with
+    /// [`ImageTiling::DrmFormatModifier`]. The number of memory planes is given by the
+    /// `drm_format_modifier_plane_count` of the image's modifier, as returned by
+    /// [`PhysicalDevice::drm_format_modifier_properties`](crate::device::physical::PhysicalDevice::drm_format_modifier_properties).
+    ///
+    /// This can be used to export the image to other APIs, such as KMS or GBM, that need the
+    /// offset and row pitch of each plane.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the image was not created with `ImageTiling::DrmFormatModifier`.
+    /// - Panics if `plane` is greater than 2.
+    #[inline]
+    pub fn drm_format_modifier_plane_layout(&self, plane: usize) -> LinearLayout {
+        assert!(self.tiling == ImageTiling::DrmFormatModifier);
+
+        let aspect = match plane {
+            0 => ImageAspect::MemoryPlane0,
+            1 => ImageAspect::MemoryPlane1,
+            2 => ImageAspect::MemoryPlane2,
+            _ => panic!("plane index {} is out of range", plane),
+        };
+
+        unsafe { self.linear_layout_impl(0, aspect) }
+    }
+
     // Implementation of the `*_layout` functions.
     unsafe fn linear_layout_impl(&self, mip_level: u32, aspect: ImageAspect) -> LinearLayout {
         let fns = self.device.fns();
@@ -1455,6 +2172,20 @@ pub struct UnsafeImageCreateInfo {
     /// The default value is `false`.
     pub array_2d_compatible: bool,
 
+    /// For 3D images, whether an image view of type
+    /// [`ImageViewType::Dim2d`](crate::image::view::ImageViewType::Dim2d) can be created from a
+    /// single slice of the image, and be used in a storage image descriptor (requires the
+    /// [`image2_d_view_of3_d`](crate::device::Features::image2_d_view_of3_d) feature) or a
+    /// sampled image descriptor (requires the
+    /// [`sampler2_d_view_of3_d`](crate::device::Features::sampler2_d_view_of3_d) feature).
+    ///
+    /// If set to `true`, the
+    /// [`ext_image_2d_view_of_3d`](crate::device::DeviceExtensions::ext_image_2d_view_of_3d)
+    /// extension must be enabled on the device.
+    ///
+    /// The default value is `false`.
+    pub view_2d_compatible: bool,
+
     /// For images with a compressed format, whether an image view with an uncompressed
     /// format can be created from the image, where each texel in the view will correspond to a
     /// compressed texel block in the image.
@@ -1464,6 +2195,79 @@ pub struct UnsafeImageCreateInfo {
     /// The default value is `false`.
     pub block_texel_view_compatible: bool,
 
+    /// The formats that image views created from the image are allowed to have.
+    ///
+    /// If `mutable_format` is set, this restricts the formats of the image views to those in the
+    /// list, and allows the implementation to optimize the image for them. For example, an image
+    /// with an `_UNORM` format can list the corresponding `_SRGB` format to allow both linear and
+    /// sRGB views. If `mutable_format` is not set, the list must either be empty or contain only
+    /// `format`.
+    ///
+    /// All formats must be compatible with `format`, or, if `block_texel_view_compatible` is set,
+    /// have the same block size as `format`.
+    ///
+    /// If not empty, the device API version must be at least 1.2, or the
+    /// [`khr_image_format_list`](crate::device::DeviceExtensions::khr_image_format_list)
+    /// extension must be enabled on the device.
+    ///
+    /// The default value is empty.
+    pub view_formats: SmallVec<[Format; 4]>,
+
+    /// The video profiles that the image is going to be used with.
+    ///
+    /// If `usage` contains `video_decode_dst`, `video_decode_src` or `video_decode_dpb`, this must
+    /// not be empty, and the image can then only be used by video sessions that were created with
+    /// one of these profiles.
+    ///
+    /// The default value is empty.
+    pub video_profiles: SmallVec<[VideoProfile; 1]>,
+
+    /// The DRM format modifiers that the implementation can choose from when creating the image.
+    ///
+    /// If `tiling` is [`ImageTiling::DrmFormatModifier`], this must not be empty, and every
+    /// modifier must be supported for `format`, as returned by
+    /// [`PhysicalDevice::drm_format_modifier_properties`](crate::device::physical::PhysicalDevice::drm_format_modifier_properties).
+    /// The modifier that was chosen can be retrieved with
+    /// [`UnsafeImage::drm_format_modifier`] after creation. If `tiling` is something else, this
+    /// must be empty.
+    ///
+    /// The default value is empty.
+    pub drm_format_modifiers: SmallVec<[u64; 1]>,
+
+    /// The explicit layouts of the memory planes of the image, for when the image must match
+    /// memory that was allocated elsewhere, for example a buffer imported from GBM.
+    ///
+    /// If not empty, `drm_format_modifiers` must contain exactly one modifier, and the number of
+    /// layouts must equal the `drm_format_modifier_plane_count` of that modifier. The `size` of
+    /// each layout must be `0`, and `array_pitch` and `depth_pitch` must be `0` if the image has
+    /// only one array layer or is not three-dimensional, respectively.
+    ///
+    /// The default value is empty.
+    pub drm_format_modifier_plane_layouts: SmallVec<[LinearLayout; 4]>,
+
+    /// Create an image with sparsely bound memory.
+    ///
+    /// If `Some`, the [`sparse_binding`](crate::device::Features::sparse_binding) feature must be
+    /// enabled on the device. Memory is then not bound with `bind_memory`, but with a sparse
+    /// binding operation on a queue, see [`Queue::bind_sparse`](crate::device::Queue::bind_sparse).
+    ///
+    /// If `sparse_residency` is set, `tiling` must be [`ImageTiling::Optimal`], the image must be
+    /// 2D or 3D, and the appropriate sparse residency features must be enabled for its type and
+    /// sample count. Parts of the image can then be left unbound.
+    ///
+    /// The default value is `None`.
+    pub sparse: Option<SparseLevel>,
+
+    /// A name to give to the image, to identify it in debugging tools.
+    ///
+    /// If `Some`, and the [`ext_debug_utils`](crate::instance::InstanceExtensions::ext_debug_utils)
+    /// extension is enabled on the instance, the name is set with
+    /// [`Device::set_debug_utils_object_name`](crate::device::Device::set_debug_utils_object_name)
+    /// when the image is created. Otherwise it is ignored.
+    ///
+    /// The default value is `None`.
+    pub debug_name: Option<Cow<'static, str>>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1486,7 +2290,14 @@ impl Default for UnsafeImageCreateInfo {
             mutable_format: false,
             cube_compatible: false,
             array_2d_compatible: false,
+            view_2d_compatible: false,
             block_texel_view_compatible: false,
+            view_formats: SmallVec::new(),
+            video_profiles: SmallVec::new(),
+            drm_format_modifiers: SmallVec::new(),
+            drm_format_modifier_plane_layouts: SmallVec::new(),
+            sparse: None,
+            debug_name: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1516,6 +2327,9 @@ pub enum ImageCreationError {
     /// The cube_compatible flag was enabled, but the image type was not 2D.
     CubeCompatibleNot2d,
 
+    /// The view_2d_compatible flag was enabled, but the image type was not 3D.
+    View2dCompatibleNot3d,
+
     /// The cube_compatible flag was enabled, but the number of array layers was less than 6.
     CubeCompatibleNotEnoughArrayLayers,
 
@@ -1529,6 +2343,37 @@ pub enum ImageCreationError {
     /// `Undefined`.
     ExternalMemoryInvalidInitialLayout,
 
+    /// The provided external memory handle types can not be used together for this image
+    /// configuration, as queried through the `image_format_properties` function.
+    ExternalMemoryHandleTypesNotCompatible,
+
+    /// `tiling` was `DrmFormatModifier` and the `mutable_format` flag was enabled, but
+    /// `view_formats` was empty.
+    DrmFormatModifierMutableFormatNoViewFormats,
+
+    /// A DRM format modifier in `drm_format_modifiers` is not supported for the given format.
+    DrmFormatModifierNotSupported {
+        modifier: u64,
+    },
+
+    /// The number of plane layouts in `drm_format_modifier_plane_layouts` does not match the
+    /// plane count of the DRM format modifier.
+    DrmFormatModifierPlaneCountMismatch {
+        provided: u32,
+        required: u32,
+    },
+
+    /// `drm_format_modifier_plane_layouts` was not empty, but `drm_format_modifiers` did not
+    /// contain exactly one modifier.
+    DrmFormatModifierPlaneLayoutsMultipleModifiers,
+
+    /// `tiling` was `DrmFormatModifier`, but `drm_format_modifiers` was empty.
+    DrmFormatModifiersEmpty,
+
+    /// `drm_format_modifiers` or `drm_format_modifier_plane_layouts` was not empty, but `tiling`
+    /// was not `DrmFormatModifier`.
+    DrmFormatModifiersNotDrmTiling,
+
     /// The given format was not supported by the device.
     FormatNotSupported,
 
@@ -1592,6 +2437,20 @@ pub enum ImageCreationError {
         id: u32,
     },
 
+    /// `sparse` was `Some` with `sparse_residency` set, but the image type was 1D.
+    SparseResidency1d,
+
+    /// `sparse` was `Some` with `sparse_residency` set, but the image configuration does not
+    /// support sparse residency, as queried through the `sparse_image_format_properties`
+    /// function.
+    SparseResidencyFormatNotSupported,
+
+    /// `sparse` was `Some` with `sparse_residency` set, but `tiling` was not `Optimal`.
+    SparseResidencyNotOptimalTiling,
+
+    /// `sparse` was `Some`, but the usage contained `transient_attachment`.
+    SparseTransientAttachment,
+
     /// A YCbCr format was given, but the specified width and/or height was not a multiple of 2
     /// as required by the format's chroma subsampling.
     YcbcrFormatInvalidDimensions,
@@ -1605,6 +2464,18 @@ pub enum ImageCreationError {
     /// A YCbCr format was given, but the image type was not 2D.
     YcbcrFormatNot2d,
 
+    /// A format in `view_formats` was not compatible with the format of the image.
+    ViewFormatNotCompatible {
+        view_format: Format,
+    },
+
+    /// The `mutable_format` flag was not enabled, but `view_formats` contained a format other
+    /// than the format of the image.
+    ViewFormatsNotMutable,
+
+    /// The usage contained a video usage, but no video profiles were provided.
+    VideoProfilesEmpty,
+
     DirectImageViewCreationFailed(ImageViewCreationError),
 }
 
@@ -1646,6 +2517,12 @@ impl fmt::Display for ImageCreationError {
                     "the cube_compatible flag was enabled, but the image type was not 2D"
                 )
             }
+            Self::View2dCompatibleNot3d => {
+                write!(
+                    fmt,
+                    "the view_2d_compatible flag was enabled, but the image type was not 3D"
+                )
+            }
             Self::CubeCompatibleNotEnoughArrayLayers => {
                 write!(fmt, "the cube_compatible flag was enabled, but the number of array layers was less than 6")
             }
@@ -1661,6 +2538,35 @@ impl fmt::Display for ImageCreationError {
             Self::ExternalMemoryInvalidInitialLayout => {
                 write!(fmt, "one or more external memory handle types were provided, but the initial layout was not `Undefined`")
             }
+            Self::ExternalMemoryHandleTypesNotCompatible => {
+                write!(fmt, "the provided external memory handle types can not be used together for this image configuration, as queried through the `image_format_properties` function")
+            }
+            Self::DrmFormatModifierMutableFormatNoViewFormats => write!(
+                fmt,
+                "`tiling` was `DrmFormatModifier` and the `mutable_format` flag was enabled, but `view_formats` was empty",
+            ),
+            Self::DrmFormatModifierNotSupported { modifier } => write!(
+                fmt,
+                "the DRM format modifier {:#x} is not supported for the given format",
+                modifier,
+            ),
+            Self::DrmFormatModifierPlaneCountMismatch { provided, required } => write!(
+                fmt,
+                "{} plane layouts were provided, but the DRM format modifier has {} planes",
+                provided, required,
+            ),
+            Self::DrmFormatModifierPlaneLayoutsMultipleModifiers => write!(
+                fmt,
+                "`drm_format_modifier_plane_layouts` was not empty, but `drm_format_modifiers` did not contain exactly one modifier",
+            ),
+            Self::DrmFormatModifiersEmpty => write!(
+                fmt,
+                "`tiling` was `DrmFormatModifier`, but `drm_format_modifiers` was empty",
+            ),
+            Self::DrmFormatModifiersNotDrmTiling => write!(
+                fmt,
+                "`drm_format_modifiers` or `drm_format_modifier_plane_layouts` was not empty, but `tiling` was not `DrmFormatModifier`",
+            ),
             Self::FormatNotSupported => {
                 write!(fmt, "the given format was not supported by the device")
             }
@@ -1718,6 +2624,18 @@ impl fmt::Display for ImageCreationError {
             Self::SharingInvalidQueueFamilyId { id } => {
                 write!(fmt, "the sharing mode was set to `Concurrent`, but one of the specified queue family ids was not valid")
             }
+            Self::SparseResidency1d => {
+                write!(fmt, "sparse residency was requested, but the image type was 1D")
+            }
+            Self::SparseResidencyFormatNotSupported => {
+                write!(fmt, "sparse residency was requested, but the image configuration does not support sparse residency, as queried through the `sparse_image_format_properties` function")
+            }
+            Self::SparseResidencyNotOptimalTiling => {
+                write!(fmt, "sparse residency was requested, but the tiling was not `Optimal`")
+            }
+            Self::SparseTransientAttachment => {
+                write!(fmt, "sparse binding was requested, but the usage contained `transient_attachment`")
+            }
             Self::YcbcrFormatInvalidDimensions => {
                 write!(fmt, "a YCbCr format was given, but the specified width and/or height was not a multiple of 2 as required by the format's chroma subsampling")
             }
@@ -1739,6 +2657,19 @@ impl fmt::Display for ImageCreationError {
                     "a YCbCr format was given, but the image type was not 2D"
                 )
             }
+            Self::ViewFormatNotCompatible { view_format } => write!(
+                fmt,
+                "the format {:?} in `view_formats` was not compatible with the format of the image",
+                view_format,
+            ),
+            Self::ViewFormatsNotMutable => write!(
+                fmt,
+                "the `mutable_format` flag was not enabled, but `view_formats` contained a format other than the format of the image",
+            ),
+            Self::VideoProfilesEmpty => write!(
+                fmt,
+                "the usage contained a video usage, but no video profiles were provided",
+            ),
             Self::DirectImageViewCreationFailed(e) => {
                 write!(fmt, "Image view creation failed {}", e.to_string())
             }
@@ -1795,6 +2726,56 @@ pub struct LinearLayout {
     pub depth_pitch: DeviceSize,
 }
 
+/// The memory that is bound to a sparse image, as far as the sparse binding operations submitted
+/// so far are concerned.
+#[derive(Debug, Default)]
+pub(crate) struct SparseImageMemory {
+    // Memory bound with opaque binds, by offset within the opaque memory range of the image.
+    pub(crate) opaque: RangeMap<DeviceSize, Arc<DeviceMemory>>,
+    // Memory bound to individual sparse blocks with image binds.
+    pub(crate) blocks: HashMap<SparseImageBlock, Arc<DeviceMemory>>,
+}
+
+impl SparseImageMemory {
+    /// Returns the sparse blocks that cover a region of a subresource, given the image
+    /// granularity of its aspect.
+    pub(crate) fn region_blocks(
+        aspect: ImageAspect,
+        mip_level: u32,
+        array_layer: u32,
+        offset: [u32; 3],
+        extent: [u32; 3],
+        granularity: [u32; 3],
+    ) -> impl Iterator<Item = SparseImageBlock> {
+        let range = |i: usize| {
+            offset[i] / granularity[i]
+                ..(offset[i] + extent[i] + granularity[i] - 1) / granularity[i]
+        };
+        let (x, y) = (range(0), range(1));
+
+        range(2).flat_map(move |z| {
+            let x = x.clone();
+            y.clone().flat_map(move |y| {
+                x.clone().map(move |x| SparseImageBlock {
+                    aspect,
+                    mip_level,
+                    array_layer,
+                    block: [x, y, z],
+                })
+            })
+        })
+    }
+}
+
+/// A sparse block of a subresource of an image, in units of the image granularity of its aspect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct SparseImageBlock {
+    pub(crate) aspect: ImageAspect,
+    pub(crate) mip_level: u32,
+    pub(crate) array_layer: u32,
+    pub(crate) block: [u32; 3],
+}
+
 /// The current state of an image.
 #[derive(Debug)]
 pub(crate) struct ImageState {
@@ -2172,15 +3153,18 @@ mod tests {
     use super::ImageUsage;
     use super::UnsafeImage;
     use super::UnsafeImageCreateInfo;
+    use crate::buffer::SparseLevel;
     use crate::format::Format;
     use crate::image::sys::SubresourceRangeIterator;
     use crate::image::ImageAspect;
     use crate::image::ImageAspects;
     use crate::image::ImageDimensions;
     use crate::image::ImageSubresourceRange;
+    use crate::image::ImageTiling;
     use crate::image::SampleCount;
     use crate::DeviceSize;
-    use smallvec::SmallVec;
+    use crate::Version;
+    use smallvec::{smallvec, SmallVec};
 
     #[test]
     fn create_sampled() {
@@ -2369,6 +3353,178 @@ mod tests {
         })
     }
 
+    #[test]
+    fn view_formats() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        if device.api_version() < Version::V1_2 {
+            return;
+        }
+
+        let create_info = UnsafeImageCreateInfo {
+            dimensions: ImageDimensions::Dim2d {
+                width: 32,
+                height: 32,
+                array_layers: 1,
+            },
+            format: Some(Format::R8G8B8A8_UNORM),
+            usage: ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+            view_formats: smallvec![Format::R8G8B8A8_UNORM, Format::R8G8B8A8_SRGB],
+            ..Default::default()
+        };
+
+        match UnsafeImage::new(device.clone(), create_info.clone()) {
+            Err(ImageCreationError::ViewFormatsNotMutable) => (),
+            _ => panic!(),
+        };
+
+        match UnsafeImage::new(
+            device.clone(),
+            UnsafeImageCreateInfo {
+                mutable_format: true,
+                view_formats: smallvec![Format::R8G8B8A8_UNORM, Format::R16G16_SFLOAT],
+                ..create_info.clone()
+            },
+        ) {
+            Err(ImageCreationError::ViewFormatNotCompatible {
+                view_format: Format::R16G16_SFLOAT,
+            }) => (),
+            _ => panic!(),
+        };
+
+        let image = UnsafeImage::new(
+            device,
+            UnsafeImageCreateInfo {
+                mutable_format: true,
+                ..create_info
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            image.view_formats(),
+            &[Format::R8G8B8A8_UNORM, Format::R8G8B8A8_SRGB]
+        );
+    }
+
+    #[test]
+    fn view_2d_compatible_extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match UnsafeImage::new(
+            device,
+            UnsafeImageCreateInfo {
+                dimensions: ImageDimensions::Dim3d {
+                    width: 32,
+                    height: 32,
+                    depth: 32,
+                },
+                format: Some(Format::R8G8B8A8_UNORM),
+                usage: ImageUsage {
+                    storage: true,
+                    ..ImageUsage::none()
+                },
+                view_2d_compatible: true,
+                ..Default::default()
+            },
+        ) {
+            Err(ImageCreationError::ExtensionNotEnabled {
+                extension: "ext_image_2d_view_of_3d",
+                ..
+            }) => (),
+            Err(ImageCreationError::FormatUsageNotSupported { .. }) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn drm_format_modifier_extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match UnsafeImage::new(
+            device,
+            UnsafeImageCreateInfo {
+                dimensions: ImageDimensions::Dim2d {
+                    width: 32,
+                    height: 32,
+                    array_layers: 1,
+                },
+                format: Some(Format::R8G8B8A8_UNORM),
+                tiling: ImageTiling::DrmFormatModifier,
+                usage: ImageUsage {
+                    sampled: true,
+                    ..ImageUsage::none()
+                },
+                drm_format_modifiers: smallvec![0],
+                ..Default::default()
+            },
+        ) {
+            Err(ImageCreationError::ExtensionNotEnabled {
+                extension: "ext_image_drm_format_modifier",
+                ..
+            }) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn drm_format_modifiers_not_drm_tiling() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match UnsafeImage::new(
+            device,
+            UnsafeImageCreateInfo {
+                dimensions: ImageDimensions::Dim2d {
+                    width: 32,
+                    height: 32,
+                    array_layers: 1,
+                },
+                format: Some(Format::R8G8B8A8_UNORM),
+                usage: ImageUsage {
+                    sampled: true,
+                    ..ImageUsage::none()
+                },
+                drm_format_modifiers: smallvec![0],
+                ..Default::default()
+            },
+        ) {
+            Err(ImageCreationError::DrmFormatModifiersNotDrmTiling) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn sparse_residency_not_optimal_tiling() {
+        let (device, _) = gfx_dev_and_queue!(sparse_binding);
+
+        match UnsafeImage::new(
+            device,
+            UnsafeImageCreateInfo {
+                dimensions: ImageDimensions::Dim2d {
+                    width: 32,
+                    height: 32,
+                    array_layers: 1,
+                },
+                format: Some(Format::R8G8B8A8_UNORM),
+                tiling: ImageTiling::Linear,
+                usage: ImageUsage {
+                    sampled: true,
+                    ..ImageUsage::none()
+                },
+                sparse: Some(SparseLevel {
+                    sparse_residency: true,
+                    ..SparseLevel::none()
+                }),
+                ..Default::default()
+            },
+        ) {
+            Err(ImageCreationError::SparseResidencyNotOptimalTiling) => (),
+            _ => panic!(),
+        };
+    }
+
     #[test]
     fn cubecompatible_dims_mismatch() {
         let (device, _) = gfx_dev_and_queue!();
@@ -14,6 +14,7 @@ use super::{
 use crate::{
     device::{Device, DeviceOwned},
     format::{Format, FormatFeatures},
+    memory::transient::MemoryAlias,
     SafeDeref,
 };
 use std::{
@@ -202,6 +203,17 @@ pub unsafe trait ImageAccess: DeviceOwned + Send + Sync {
     ///
     /// This must return `Some` if the image is to be used to create an image view.
     fn descriptor_layouts(&self) -> Option<ImageDescriptorLayouts>;
+
+    /// Returns the part of a block of transient memory that the image is bound to, if its memory
+    /// may be shared with other resources.
+    ///
+    /// Command buffers insert a barrier before an image whose memory is shared is used after
+    /// another resource that uses the same memory, and they discard the contents of the image
+    /// when it is first used.
+    #[inline]
+    fn memory_alias(&self) -> Option<MemoryAlias> {
+        None
+    }
 }
 
 /// Inner information about an image.
@@ -291,6 +303,11 @@ where
     fn descriptor_layouts(&self) -> Option<ImageDescriptorLayouts> {
         self.image.descriptor_layouts()
     }
+
+    #[inline]
+    fn memory_alias(&self) -> Option<MemoryAlias> {
+        self.image.memory_alias()
+    }
 }
 
 impl<I> PartialEq for ImageAccessFromUndefinedLayout<I>
@@ -354,4 +371,9 @@ where
     fn is_layout_initialized(&self) -> bool {
         (**self).is_layout_initialized()
     }
+
+    #[inline]
+    fn memory_alias(&self) -> Option<MemoryAlias> {
+        (**self).memory_alias()
+    }
 }
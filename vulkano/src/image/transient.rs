@@ -0,0 +1,132 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use super::{sys::UnsafeImage, ImageAccess, ImageDescriptorLayouts, ImageInner, ImageLayout};
+use crate::{
+    device::{Device, DeviceOwned},
+    memory::transient::{MemoryAlias, TransientMemory},
+    DeviceSize, VulkanObject,
+};
+use std::{
+    hash::{Hash, Hasher},
+    ops::Range,
+    sync::Arc,
+};
+
+/// Image whose memory may be shared with other transient resources.
+///
+/// Created with a [`TransientAllocator`](crate::memory::transient::TransientAllocator). The
+/// contents of the image are discarded when it is first used in a command buffer, so they must
+/// be written before they are read in each command buffer that uses the image.
+#[derive(Debug)]
+pub struct TransientImage {
+    image: Arc<UnsafeImage>,
+    memory: Arc<TransientMemory>,
+    range: Range<DeviceSize>,
+}
+
+impl TransientImage {
+    // Wraps an image that is bound to `range` of `memory`.
+    pub(crate) fn new(
+        image: Arc<UnsafeImage>,
+        memory: Arc<TransientMemory>,
+        range: Range<DeviceSize>,
+    ) -> Arc<TransientImage> {
+        Arc::new(TransientImage {
+            image,
+            memory,
+            range,
+        })
+    }
+
+    /// Returns the memory that the image is bound to.
+    #[inline]
+    pub fn memory(&self) -> &Arc<TransientMemory> {
+        &self.memory
+    }
+
+    /// Returns the offset in the memory that the image is bound to.
+    #[inline]
+    pub fn memory_offset(&self) -> DeviceSize {
+        self.range.start
+    }
+}
+
+unsafe impl DeviceOwned for TransientImage {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.device()
+    }
+}
+
+unsafe impl VulkanObject for TransientImage {
+    type Object = ash::vk::Image;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Image {
+        self.image.internal_object()
+    }
+}
+
+unsafe impl ImageAccess for TransientImage {
+    #[inline]
+    fn inner(&self) -> ImageInner {
+        ImageInner {
+            image: &self.image,
+            first_layer: 0,
+            num_layers: self.image.dimensions().array_layers(),
+            first_mipmap_level: 0,
+            num_mipmap_levels: self.image.mip_levels(),
+        }
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_layouts(&self) -> Option<ImageDescriptorLayouts> {
+        Some(ImageDescriptorLayouts {
+            storage_image: ImageLayout::General,
+            combined_image_sampler: ImageLayout::General,
+            sampled_image: ImageLayout::General,
+            input_attachment: ImageLayout::General,
+        })
+    }
+
+    #[inline]
+    fn memory_alias(&self) -> Option<MemoryAlias> {
+        Some(MemoryAlias {
+            memory: self.memory.clone(),
+            range: self.range.clone(),
+        })
+    }
+}
+
+impl PartialEq for TransientImage {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner() == other.inner()
+    }
+}
+
+impl Eq for TransientImage {}
+
+impl Hash for TransientImage {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner().hash(state);
+    }
+}
@@ -46,6 +46,21 @@ pub struct ImageUsage {
     /// Can be used as an input attachment. In other words, you can draw to it in a subpass then
     /// read from it in a following pass.
     pub input_attachment: bool,
+
+    /// Can be used as a fragment density map attachment in a render pass, to control the size
+    /// of the fragments that are rendered in each region of the framebuffer.
+    pub fragment_density_map: bool,
+
+    /// Can be used as the output picture of a video decode operation.
+    pub video_decode_dst: bool,
+
+    /// Can be used as the input picture of a video decode operation. This is reserved for future
+    /// use by the Vulkan specification.
+    pub video_decode_src: bool,
+
+    /// Can be used as a decoded picture buffer (DPB) picture of a video decode operation, which
+    /// holds the reference pictures of the video session.
+    pub video_decode_dpb: bool,
 }
 
 impl ImageUsage {
@@ -62,6 +77,10 @@ impl ImageUsage {
             depth_stencil_attachment: true,
             transient_attachment: true,
             input_attachment: true,
+            fragment_density_map: true,
+            video_decode_dst: true,
+            video_decode_src: true,
+            video_decode_dpb: true,
         }
     }
 
@@ -89,6 +108,10 @@ impl ImageUsage {
             depth_stencil_attachment: false,
             transient_attachment: false,
             input_attachment: false,
+            fragment_density_map: false,
+            video_decode_dst: false,
+            video_decode_src: false,
+            video_decode_dpb: false,
         }
     }
 
@@ -104,6 +127,10 @@ impl ImageUsage {
             depth_stencil_attachment: false,
             transient_attachment: false,
             input_attachment: false,
+            fragment_density_map: false,
+            video_decode_dst: false,
+            video_decode_src: false,
+            video_decode_dpb: false,
         }
     }
 
@@ -119,6 +146,10 @@ impl ImageUsage {
             depth_stencil_attachment: true,
             transient_attachment: false,
             input_attachment: false,
+            fragment_density_map: false,
+            video_decode_dst: false,
+            video_decode_src: false,
+            video_decode_dpb: false,
         }
     }
 
@@ -134,6 +165,10 @@ impl ImageUsage {
             depth_stencil_attachment: false,
             transient_attachment: true,
             input_attachment: false,
+            fragment_density_map: false,
+            video_decode_dst: false,
+            video_decode_src: false,
+            video_decode_dpb: false,
         }
     }
 
@@ -149,6 +184,10 @@ impl ImageUsage {
             depth_stencil_attachment: true,
             transient_attachment: true,
             input_attachment: false,
+            fragment_density_map: false,
+            video_decode_dst: false,
+            video_decode_src: false,
+            video_decode_dpb: false,
         }
     }
 
@@ -164,6 +203,10 @@ impl ImageUsage {
             depth_stencil_attachment: false,
             transient_attachment: true,
             input_attachment: true,
+            fragment_density_map: false,
+            video_decode_dst: false,
+            video_decode_src: false,
+            video_decode_dpb: false,
         }
     }
 }
@@ -196,6 +239,18 @@ impl From<ImageUsage> for ash::vk::ImageUsageFlags {
         if val.input_attachment {
             result |= ash::vk::ImageUsageFlags::INPUT_ATTACHMENT;
         }
+        if val.fragment_density_map {
+            result |= ash::vk::ImageUsageFlags::FRAGMENT_DENSITY_MAP_EXT;
+        }
+        if val.video_decode_dst {
+            result |= ash::vk::ImageUsageFlags::VIDEO_DECODE_DST_KHR;
+        }
+        if val.video_decode_src {
+            result |= ash::vk::ImageUsageFlags::VIDEO_DECODE_SRC_KHR;
+        }
+        if val.video_decode_dpb {
+            result |= ash::vk::ImageUsageFlags::VIDEO_DECODE_DPB_KHR;
+        }
         result
     }
 }
@@ -214,6 +269,11 @@ impl From<ash::vk::ImageUsageFlags> for ImageUsage {
             transient_attachment: !(val & ash::vk::ImageUsageFlags::TRANSIENT_ATTACHMENT)
                 .is_empty(),
             input_attachment: !(val & ash::vk::ImageUsageFlags::INPUT_ATTACHMENT).is_empty(),
+            fragment_density_map: !(val & ash::vk::ImageUsageFlags::FRAGMENT_DENSITY_MAP_EXT)
+                .is_empty(),
+            video_decode_dst: !(val & ash::vk::ImageUsageFlags::VIDEO_DECODE_DST_KHR).is_empty(),
+            video_decode_src: !(val & ash::vk::ImageUsageFlags::VIDEO_DECODE_SRC_KHR).is_empty(),
+            video_decode_dpb: !(val & ash::vk::ImageUsageFlags::VIDEO_DECODE_DPB_KHR).is_empty(),
         }
     }
 }
@@ -232,6 +292,10 @@ impl BitOr for ImageUsage {
             depth_stencil_attachment: self.depth_stencil_attachment || rhs.depth_stencil_attachment,
             transient_attachment: self.transient_attachment || rhs.transient_attachment,
             input_attachment: self.input_attachment || rhs.input_attachment,
+            fragment_density_map: self.fragment_density_map || rhs.fragment_density_map,
+            video_decode_dst: self.video_decode_dst || rhs.video_decode_dst,
+            video_decode_src: self.video_decode_src || rhs.video_decode_src,
+            video_decode_dpb: self.video_decode_dpb || rhs.video_decode_dpb,
         }
     }
 }
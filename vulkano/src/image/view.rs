@@ -23,6 +23,7 @@ use crate::{
     Error, OomError, VulkanObject,
 };
 use std::{
+    borrow::Cow,
     error, fmt,
     hash::{Hash, Hasher},
     mem::MaybeUninit,
@@ -46,6 +47,8 @@ where
     subresource_range: ImageSubresourceRange,
     usage: ImageUsage,
     view_type: ImageViewType,
+    fragment_density_map_dynamic: bool,
+    min_lod: f32,
 
     filter_cubic: bool,
     filter_cubic_minmax: bool,
@@ -78,6 +81,9 @@ where
             component_mapping,
             subresource_range,
             sampler_ycbcr_conversion,
+            fragment_density_map_dynamic,
+            min_lod,
+            debug_name,
             _ne: _,
         } = create_info;
 
@@ -96,6 +102,7 @@ where
                 mutable_format: image_inner.mutable_format(),
                 cube_compatible: image_inner.cube_compatible(),
                 array_2d_compatible: image_inner.array_2d_compatible(),
+                view_2d_compatible: image_inner.view_2d_compatible(),
                 block_texel_view_compatible: image_inner.block_texel_view_compatible(),
                 ..Default::default()
             })? {
@@ -104,7 +111,7 @@ where
             (false, false)
         };
 
-        Ok(Arc::new(ImageView {
+        let image_view = ImageView {
             handle,
             image,
 
@@ -115,10 +122,18 @@ where
             subresource_range,
             usage,
             sampler_ycbcr_conversion,
+            fragment_density_map_dynamic,
+            min_lod,
 
             filter_cubic,
             filter_cubic_minmax,
-        }))
+        };
+
+        image_view
+            .device()
+            .set_debug_name(&image_view, debug_name.as_deref())?;
+
+        Ok(Arc::new(image_view))
     }
 
     fn validate_create(
@@ -131,6 +146,9 @@ where
             component_mapping,
             ref subresource_range,
             ref sampler_ycbcr_conversion,
+            fragment_density_map_dynamic,
+            min_lod,
+            debug_name: _,
             _ne: _,
         } = create_info;
 
@@ -171,14 +189,21 @@ where
         // Get format features
         let format_features = {
             let format_features = if Some(format) != image_inner.format() {
-                let format_properties = image_inner
-                    .device()
-                    .physical_device()
-                    .format_properties(format);
+                let physical_device = image_inner.device().physical_device();
+                let format_properties = physical_device.format_properties(format);
 
                 match image_inner.tiling() {
                     ImageTiling::Optimal => format_properties.optimal_tiling_features,
                     ImageTiling::Linear => format_properties.linear_tiling_features,
+                    ImageTiling::DrmFormatModifier => physical_device
+                        .drm_format_modifier_properties(format)
+                        .into_iter()
+                        .find(|properties| {
+                            Some(properties.drm_format_modifier)
+                                == image_inner.drm_format_modifier()
+                        })
+                        .map(|properties| properties.drm_format_modifier_tiling_features)
+                        .unwrap_or_default(),
                 }
             } else {
                 *image_inner.format_features()
@@ -272,7 +297,10 @@ where
             && (view_type == ImageViewType::Dim2d || view_type == ImageViewType::Dim2dArray)
         {
             // VUID-VkImageViewCreateInfo-image-01005
-            if !image_inner.array_2d_compatible() {
+            // VUID-VkImageViewCreateInfo-image-06723
+            if !(image_inner.array_2d_compatible()
+                || view_type == ImageViewType::Dim2d && image_inner.view_2d_compatible())
+            {
                 return Err(ImageViewCreationError::ImageNotArray2dCompatible);
             }
 
@@ -315,6 +343,43 @@ where
             return Err(ImageViewCreationError::MultisamplingNot2d);
         }
 
+        if image_inner.usage().fragment_density_map {
+            // VUID-VkImageViewCreateInfo-image-02571
+            if level_count != 1 {
+                return Err(ImageViewCreationError::FragmentDensityMapMultipleMipLevels);
+            }
+        }
+
+        // VUID-VkImageViewCreateInfo-flags-02572
+        if fragment_density_map_dynamic
+            && !image_inner
+                .device()
+                .enabled_features()
+                .fragment_density_map_dynamic
+        {
+            return Err(ImageViewCreationError::FeatureNotEnabled {
+                feature: "fragment_density_map_dynamic",
+                reason: "`fragment_density_map_dynamic` was set",
+            });
+        }
+
+        if min_lod != 0.0 {
+            // VUID?
+            if !image_inner.device().enabled_features().min_lod {
+                return Err(ImageViewCreationError::FeatureNotEnabled {
+                    feature: "min_lod",
+                    reason: "`min_lod` was not 0.0",
+                });
+            }
+
+            // VUID?
+            if !(0.0..=(subresource_range.mip_levels.end - 1) as f32).contains(&min_lod) {
+                return Err(ImageViewCreationError::MinLodOutOfRange {
+                    max: subresource_range.mip_levels.end - 1,
+                });
+            }
+        }
+
         /* Check usage requirements */
 
         // VUID-VkImageViewCreateInfo-image-04441
@@ -323,7 +388,11 @@ where
             || image_inner.usage().color_attachment
             || image_inner.usage().depth_stencil_attachment
             || image_inner.usage().input_attachment
-            || image_inner.usage().transient_attachment)
+            || image_inner.usage().transient_attachment
+            || image_inner.usage().fragment_density_map
+            || image_inner.usage().video_decode_dst
+            || image_inner.usage().video_decode_src
+            || image_inner.usage().video_decode_dpb)
         {
             return Err(ImageViewCreationError::ImageMissingUsage);
         }
@@ -415,10 +484,21 @@ where
             }
         }
         // VUID-VkImageViewCreateInfo-image-01762
-        else if Some(format) != image_inner.format() {
+        else if (!image_inner.mutable_format()
+            || !image_inner.format().unwrap().planes().is_empty())
+            && Some(format) != image_inner.format()
+        {
             return Err(ImageViewCreationError::FormatNotCompatible);
         }
 
+        // VUID-VkImageViewCreateInfo-pNext-01585
+        if !image_inner.view_formats().is_empty()
+            && image_inner.format().unwrap().planes().is_empty()
+            && !image_inner.view_formats().contains(&format)
+        {
+            return Err(ImageViewCreationError::FormatNotInViewFormats { format });
+        }
+
         // VUID-VkImageViewCreateInfo-imageViewType-04973
         if (view_type == ImageViewType::Dim1d
             || view_type == ImageViewType::Dim2d
@@ -471,9 +551,21 @@ where
                     },
                 );
             }
+
+            // VUID-VkImageViewCreateInfo-pNext-06658
+            if let Some(conversion_format) = conversion.format() {
+                if conversion_format != format {
+                    return Err(
+                        ImageViewCreationError::SamplerYcbcrConversionFormatMismatch {
+                            format,
+                            conversion_format,
+                        },
+                    );
+                }
+            }
         } else {
             // VUID-VkImageViewCreateInfo-format-06415
-            if format.ycbcr_chroma_sampling().is_some() {
+            if format.ycbcr_chroma_sampling().is_some() && usage.sampled {
                 return Err(
                     ImageViewCreationError::FormatRequiresSamplerYcbcrConversion { format },
                 );
@@ -493,13 +585,22 @@ where
             component_mapping,
             ref subresource_range,
             ref sampler_ycbcr_conversion,
+            fragment_density_map_dynamic,
+            min_lod,
+            debug_name: _,
             _ne: _,
         } = create_info;
 
         let image_inner = image.inner().image;
 
+        let mut flags = ash::vk::ImageViewCreateFlags::empty();
+
+        if fragment_density_map_dynamic {
+            flags |= ash::vk::ImageViewCreateFlags::FRAGMENT_DENSITY_MAP_DYNAMIC_EXT;
+        }
+
         let mut create_info = ash::vk::ImageViewCreateInfo {
-            flags: ash::vk::ImageViewCreateFlags::empty(),
+            flags,
             image: image_inner.internal_object(),
             view_type: view_type.into(),
             format: format.unwrap().into(),
@@ -522,6 +623,20 @@ where
             create_info.p_next = sampler_ycbcr_conversion_info as *const _ as *const _;
         }
 
+        let mut min_lod_info = if min_lod != 0.0 {
+            Some(ash::vk::ImageViewMinLodCreateInfoEXT {
+                min_lod,
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+
+        if let Some(min_lod_info) = min_lod_info.as_mut() {
+            min_lod_info.p_next = create_info.p_next;
+            create_info.p_next = min_lod_info as *const _ as *const _;
+        }
+
         let handle = {
             let fns = image_inner.device().fns();
             let mut output = MaybeUninit::uninit();
@@ -550,6 +665,19 @@ where
     pub fn image(&self) -> &Arc<I> {
         &self.image
     }
+
+    /// Returns whether the image view was created with `fragment_density_map_dynamic` set, so
+    /// that the fragment density map is read by the device during command buffer execution.
+    #[inline]
+    pub fn fragment_density_map_dynamic(&self) -> bool {
+        self.fragment_density_map_dynamic
+    }
+
+    /// Returns the minimum LOD that the image view was created with.
+    #[inline]
+    pub fn min_lod(&self) -> f32 {
+        self.min_lod
+    }
 }
 
 impl<I> Drop for ImageView<I>
@@ -652,6 +780,39 @@ pub struct ImageViewCreateInfo {
     /// The default value is `None`.
     pub sampler_ycbcr_conversion: Option<Arc<SamplerYcbcrConversion>>,
 
+    /// Whether the fragment density map is read by the device during command buffer execution,
+    /// rather than during recording.
+    ///
+    /// If set to `true`, the
+    /// [`fragment_density_map_dynamic`](crate::device::Features::fragment_density_map_dynamic)
+    /// feature must be enabled on the device.
+    ///
+    /// The default value is `false`.
+    pub fragment_density_map_dynamic: bool,
+
+    /// The minimum LOD that can be accessed through the image view, as a mip level of the image.
+    ///
+    /// Accesses to the image view are clamped so that no mip level more detailed than `min_lod`
+    /// is accessed. For a fractional value, the two nearest levels are blended. This makes it
+    /// possible to stream the most detailed levels of an image in and out, while sampling it.
+    ///
+    /// If not `0.0`, the [`min_lod`](crate::device::Features::min_lod) feature must be enabled
+    /// on the device, and the value must not be greater than the index of the last mip level in
+    /// `subresource_range`.
+    ///
+    /// The default value is `0.0`.
+    pub min_lod: f32,
+
+    /// A name to give to the image view, to identify it in debugging tools.
+    ///
+    /// If `Some`, and the [`ext_debug_utils`](crate::instance::InstanceExtensions::ext_debug_utils)
+    /// extension is enabled on the instance, the name is set with
+    /// [`Device::set_debug_utils_object_name`](crate::device::Device::set_debug_utils_object_name)
+    /// when the image view is created. Otherwise it is ignored.
+    ///
+    /// The default value is `None`.
+    pub debug_name: Option<Cow<'static, str>>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -668,6 +829,9 @@ impl Default for ImageViewCreateInfo {
                 mip_levels: 0..0,
             },
             sampler_ycbcr_conversion: None,
+            fragment_density_map_dynamic: false,
+            min_lod: 0.0,
+            debug_name: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -698,6 +862,39 @@ impl ImageViewCreateInfo {
             ..Default::default()
         }
     }
+
+    /// Returns an `ImageViewCreateInfo` for a single plane of an image with a multi-planar
+    /// format, like [`from_image`](Self::from_image), but with the format of the plane and a
+    /// `subresource_range` that only selects the plane's aspect.
+    ///
+    /// The view accesses the plane as a standalone image, without any YCbCr conversion. The image
+    /// must have been created with the `mutable_format` flag.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the format of `image` does not have a plane with index `plane`.
+    pub fn from_image_plane<I>(image: &I, plane: usize) -> Self
+    where
+        I: ImageAccess + ?Sized,
+    {
+        let planes = image.format().planes();
+        assert!(
+            plane < planes.len(),
+            "the format of the image does not have a plane with index {}",
+            plane,
+        );
+
+        let mut create_info = Self::from_image(image);
+        create_info.format = Some(planes[plane]);
+        create_info.subresource_range.aspects = ImageAspects {
+            plane0: plane == 0,
+            plane1: plane == 1,
+            plane2: plane == 2,
+            ..ImageAspects::none()
+        };
+
+        create_info
+    }
 }
 
 /// Error that can happen when creating an image view.
@@ -737,6 +934,9 @@ pub enum ImageViewCreationError {
     /// The requested format was not compatible with the image.
     FormatNotCompatible,
 
+    /// The image was created with a list of view formats, and the requested format was not in it.
+    FormatNotInViewFormats { format: Format },
+
     /// The given format was not supported by the device.
     FormatNotSupported,
 
@@ -746,6 +946,10 @@ pub enum ImageViewCreationError {
     /// A requested usage flag was not supported by the given format.
     FormatUsageNotSupported { usage: &'static str },
 
+    /// The image was created with the `fragment_density_map` usage, but the range of mip levels
+    /// contained more than one level.
+    FragmentDensityMapMultipleMipLevels,
+
     /// An aspect was selected that was not present in the image.
     ImageAspectsNotCompatible {
         aspects: ImageAspects,
@@ -758,7 +962,7 @@ pub enum ImageViewCreationError {
     ImageMissingUsage,
 
     /// A 2D image view was requested from a 3D image, but the image was not created with the
-    /// `array_2d_compatible` flag.
+    /// `array_2d_compatible` flag, or, for a `Dim2d` view, the `view_2d_compatible` flag.
     ImageNotArray2dCompatible,
 
     /// A cube image view type was requested, but the image was not created with the
@@ -772,6 +976,10 @@ pub enum ImageViewCreationError {
     /// ranges of array layers and mipmap levels.
     IncompatibleType,
 
+    /// `min_lod` was negative, or greater than the index of the last mip level in
+    /// `subresource_range`.
+    MinLodOutOfRange { max: u32 },
+
     /// The specified range of mip levels was not a subset of those in the image.
     MipLevelsOutOfRange { range_end: u32, max: u32 },
 
@@ -782,6 +990,13 @@ pub enum ImageViewCreationError {
     /// Sampler YCbCr conversion was enabled, but `component_mapping` was not the identity mapping.
     SamplerYcbcrConversionComponentMappingNotIdentity { component_mapping: ComponentMapping },
 
+    /// Sampler YCbCr conversion was enabled, but the format of the image view was not the format
+    /// that the conversion was created with.
+    SamplerYcbcrConversionFormatMismatch {
+        format: Format,
+        conversion_format: Format,
+    },
+
     /// The `CubeArray` image view type was specified, but the range of array layers did not have a
     /// size that is a multiple 6.
     TypeCubeArrayNotMultipleOf6ArrayLayers,
@@ -844,6 +1059,11 @@ impl fmt::Display for ImageViewCreationError {
                 fmt,
                 "the requested format was not compatible with the image",
             ),
+            Self::FormatNotInViewFormats { format } => write!(
+                fmt,
+                "the image was created with a list of view formats, and the requested format {:?} was not in it",
+                format,
+            ),
             Self::FormatNotSupported => write!(
                 fmt,
                 "the given format was not supported by the device"
@@ -856,6 +1076,10 @@ impl fmt::Display for ImageViewCreationError {
                 fmt,
                 "a requested usage flag was not supported by the given format"
             ),
+            Self::FragmentDensityMapMultipleMipLevels => write!(
+                fmt,
+                "the image was created with the `fragment_density_map` usage, but the range of mip levels contained more than one level",
+            ),
             Self::ImageAspectsNotCompatible { .. } => write!(
                 fmt,
                 "an aspect was selected that was not present in the image",
@@ -866,7 +1090,7 @@ impl fmt::Display for ImageViewCreationError {
             ),
             Self::ImageNotArray2dCompatible => write!(
                 fmt,
-                "a 2D image view was requested from a 3D image, but the image was not created with the `array_2d_compatible` flag",
+                "a 2D image view was requested from a 3D image, but the image was not created with the `array_2d_compatible` flag, or, for a `Dim2d` view, the `view_2d_compatible` flag",
             ),
             Self::ImageNotCubeCompatible => write!(
                 fmt,
@@ -880,6 +1104,10 @@ impl fmt::Display for ImageViewCreationError {
                 fmt,
                 "image view type is not compatible with image, array layers or mipmap levels",
             ),
+            Self::MinLodOutOfRange { .. } => write!(
+                fmt,
+                "`min_lod` was negative, or greater than the index of the last mip level in `subresource_range`",
+            ),
             Self::MipLevelsOutOfRange { .. } => write!(
                 fmt,
                 "the specified range of mip levels was not a subset of those in the image",
@@ -892,6 +1120,11 @@ impl fmt::Display for ImageViewCreationError {
                 fmt,
                 "sampler YCbCr conversion was enabled, but `component_mapping` was not the identity mapping",
             ),
+            Self::SamplerYcbcrConversionFormatMismatch { format, conversion_format } => write!(
+                fmt,
+                "sampler YCbCr conversion was enabled, but the format of the image view ({:?}) was not the format that the conversion was created with ({:?})",
+                format, conversion_format,
+            ),
             Self::TypeCubeArrayNotMultipleOf6ArrayLayers => write!(
                 fmt,
                 "the `CubeArray` image view type was specified, but the range of array layers did not have a size that is a multiple 6"
@@ -1164,3 +1397,158 @@ impl Hash for dyn ImageViewAbstract {
         self.device().hash(state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ImageView, ImageViewAbstract, ImageViewCreateInfo, ImageViewCreationError};
+    use crate::format::Format;
+    use crate::image::{ImageAspects, ImageCreateFlags, ImageDimensions, ImageUsage, StorageImage};
+    use crate::sampler::ycbcr::{SamplerYcbcrConversion, SamplerYcbcrConversionCreateInfo};
+
+    #[test]
+    fn plane_view() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let format = Format::G8_B8R8_2PLANE_420_UNORM;
+        if !device
+            .physical_device()
+            .format_properties(format)
+            .optimal_tiling_features
+            .sampled_image
+        {
+            return;
+        }
+
+        let image = StorageImage::with_usage(
+            device,
+            ImageDimensions::Dim2d {
+                width: 64,
+                height: 64,
+                array_layers: 1,
+            },
+            format,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+            ImageCreateFlags {
+                mutable_format: true,
+                ..ImageCreateFlags::none()
+            },
+            Some(queue.family()),
+        )
+        .unwrap();
+
+        let view = ImageView::new(
+            image.clone(),
+            ImageViewCreateInfo::from_image_plane(&image, 1),
+        )
+        .unwrap();
+
+        assert_eq!(view.format(), Some(Format::R8G8_UNORM));
+        assert_eq!(
+            view.subresource_range().aspects,
+            ImageAspects {
+                plane1: true,
+                ..ImageAspects::none()
+            }
+        );
+    }
+
+    #[test]
+    fn plane_view_out_of_range() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let format = Format::G8_B8R8_2PLANE_420_UNORM;
+        if !device
+            .physical_device()
+            .format_properties(format)
+            .optimal_tiling_features
+            .sampled_image
+        {
+            return;
+        }
+
+        let image = StorageImage::with_usage(
+            device,
+            ImageDimensions::Dim2d {
+                width: 64,
+                height: 64,
+                array_layers: 1,
+            },
+            format,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+            ImageCreateFlags {
+                mutable_format: true,
+                ..ImageCreateFlags::none()
+            },
+            Some(queue.family()),
+        )
+        .unwrap();
+
+        assert_should_panic!({
+            ImageViewCreateInfo::from_image_plane(&image, 2);
+        });
+    }
+
+    #[test]
+    fn ycbcr_conversion_format_mismatch() {
+        let (device, queue) = gfx_dev_and_queue!(sampler_ycbcr_conversion);
+
+        let format = Format::G8_B8R8_2PLANE_420_UNORM;
+        let conversion_format = Format::G8_B8_R8_3PLANE_420_UNORM;
+        if !device
+            .physical_device()
+            .format_properties(format)
+            .optimal_tiling_features
+            .sampled_image
+        {
+            return;
+        }
+
+        let conversion = match SamplerYcbcrConversion::new(
+            device.clone(),
+            SamplerYcbcrConversionCreateInfo {
+                format: Some(conversion_format),
+                ..Default::default()
+            },
+        ) {
+            Ok(conversion) => conversion,
+            Err(_) => return,
+        };
+
+        let image = StorageImage::with_usage(
+            device,
+            ImageDimensions::Dim2d {
+                width: 64,
+                height: 64,
+                array_layers: 1,
+            },
+            format,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+            ImageCreateFlags::none(),
+            Some(queue.family()),
+        )
+        .unwrap();
+
+        match ImageView::new(
+            image.clone(),
+            ImageViewCreateInfo {
+                sampler_ycbcr_conversion: Some(conversion),
+                ..ImageViewCreateInfo::from_image(&image)
+            },
+        ) {
+            Err(ImageViewCreationError::SamplerYcbcrConversionFormatMismatch {
+                format: Format::G8_B8R8_2PLANE_420_UNORM,
+                conversion_format: Format::G8_B8_R8_3PLANE_420_UNORM,
+            }) => (),
+            _ => panic!(),
+        }
+    }
+}
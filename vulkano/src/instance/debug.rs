@@ -178,6 +178,15 @@ impl Drop for DebugUtilsMessenger {
     }
 }
 
+unsafe impl VulkanObject for DebugUtilsMessenger {
+    type Object = ash::vk::DebugUtilsMessengerEXT;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::DebugUtilsMessengerEXT {
+        self.handle
+    }
+}
+
 impl fmt::Debug for DebugUtilsMessenger {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         let Self {
@@ -300,6 +309,64 @@ impl DebugUtilsMessengerCreateInfo {
             _ne: crate::NonExhaustive(()),
         }
     }
+
+    /// Returns a `DebugUtilsMessengerCreateInfo` that calls `callback` with the output of the
+    /// `debugPrintfEXT` calls in shaders, and ignores all other messages.
+    ///
+    /// The output is only produced if the validation layer is enabled, and
+    /// [`ValidationFeatureEnable::DebugPrintf`] is in the
+    /// [`enabled_validation_features`](super::InstanceCreateInfo::enabled_validation_features)
+    /// of the instance.
+    pub fn debug_printf(callback: impl Fn(&str) + RefUnwindSafe + Send + Sync + 'static) -> Self {
+        Self {
+            message_severity: DebugUtilsMessageSeverity {
+                information: true,
+                warning: true,
+                ..DebugUtilsMessageSeverity::none()
+            },
+            message_type: DebugUtilsMessageType {
+                general: true,
+                validation: true,
+                ..DebugUtilsMessageType::none()
+            },
+            user_callback: Arc::new(move |message| {
+                if let Some(output) = debug_printf_output(message) {
+                    callback(output);
+                }
+            }),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    /// Returns a `DebugUtilsMessengerCreateInfo` that writes the output of the `debugPrintfEXT`
+    /// calls in shaders to the `log` crate, at the `info` level and with the
+    /// `vulkano::debug_printf` target.
+    ///
+    /// See [`debug_printf`](Self::debug_printf) for the requirements.
+    #[cfg(feature = "log")]
+    pub fn debug_printf_to_log() -> Self {
+        Self::debug_printf(|output| log::info!(target: "vulkano::debug_printf", "{}", output))
+    }
+}
+
+// Returns the shader output of a debug printf message, or `None` if `message` is not one.
+//
+// The validation layer identifies these messages by their message ID name, and puts the output
+// after the last `|` of the description, following information about the shader invocation.
+fn debug_printf_output<'a>(message: &Message<'a>) -> Option<&'a str> {
+    if !message
+        .layer_prefix
+        .map_or(false, |id_name| id_name.contains("DEBUG-PRINTF"))
+    {
+        return None;
+    }
+
+    let output = message
+        .description
+        .rsplit_once(" | ")
+        .map_or(message.description, |(_, output)| output);
+
+    Some(output.trim_end())
 }
 
 impl fmt::Debug for DebugUtilsMessengerCreateInfo {
@@ -560,13 +627,94 @@ impl From<ash::vk::DebugUtilsMessageTypeFlagsEXT> for DebugUtilsMessageType {
     }
 }
 
+/// A validation feature that can be enabled on an instance, in addition to the default ones.
+///
+/// Validation features are only used by the validation layer, and require the
+/// [`ext_validation_features`](crate::instance::InstanceExtensions::ext_validation_features)
+/// extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(i32)]
+pub enum ValidationFeatureEnable {
+    /// Instruments shaders to report errors that can only be detected while they run, such as
+    /// out-of-bounds descriptor indexing.
+    GpuAssisted = ash::vk::ValidationFeatureEnableEXT::GPU_ASSISTED.as_raw(),
+
+    /// Makes the validation layer reserve a descriptor set binding slot for its own use when
+    /// `GpuAssisted` is enabled, reducing `max_bound_descriptor_sets` by one.
+    GpuAssistedReserveBindingSlot =
+        ash::vk::ValidationFeatureEnableEXT::GPU_ASSISTED_RESERVE_BINDING_SLOT.as_raw(),
+
+    /// Reports API usage that is valid, but potentially inefficient.
+    BestPractices = ash::vk::ValidationFeatureEnableEXT::BEST_PRACTICES.as_raw(),
+
+    /// Reports the output of `debugPrintfEXT` calls in shaders as debug messages.
+    ///
+    /// The messages can be received with a messenger created with
+    /// [`DebugUtilsMessengerCreateInfo::debug_printf`]. This can't be enabled together with
+    /// `GpuAssisted`.
+    DebugPrintf = ash::vk::ValidationFeatureEnableEXT::DEBUG_PRINTF.as_raw(),
+
+    /// Reports missing or incorrect synchronization between commands.
+    SynchronizationValidation =
+        ash::vk::ValidationFeatureEnableEXT::SYNCHRONIZATION_VALIDATION.as_raw(),
+}
+
+impl From<ValidationFeatureEnable> for ash::vk::ValidationFeatureEnableEXT {
+    #[inline]
+    fn from(val: ValidationFeatureEnable) -> Self {
+        Self::from_raw(val as i32)
+    }
+}
+
+/// A validation feature that can be disabled on an instance.
+///
+/// Validation features are only used by the validation layer, and require the
+/// [`ext_validation_features`](crate::instance::InstanceExtensions::ext_validation_features)
+/// extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(i32)]
+pub enum ValidationFeatureDisable {
+    /// Disables all validation.
+    All = ash::vk::ValidationFeatureDisableEXT::ALL.as_raw(),
+
+    /// Disables the validation of shaders.
+    Shaders = ash::vk::ValidationFeatureDisableEXT::SHADERS.as_raw(),
+
+    /// Disables the checks for thread safety.
+    ThreadSafety = ash::vk::ValidationFeatureDisableEXT::THREAD_SAFETY.as_raw(),
+
+    /// Disables the validation of function parameters.
+    ApiParameters = ash::vk::ValidationFeatureDisableEXT::API_PARAMETERS.as_raw(),
+
+    /// Disables the tracking of object lifetimes.
+    ObjectLifetimes = ash::vk::ValidationFeatureDisableEXT::OBJECT_LIFETIMES.as_raw(),
+
+    /// Disables the core validation checks.
+    CoreChecks = ash::vk::ValidationFeatureDisableEXT::CORE_CHECKS.as_raw(),
+
+    /// Disables the protection against duplicate non-dispatchable handles.
+    UniqueHandles = ash::vk::ValidationFeatureDisableEXT::UNIQUE_HANDLES.as_raw(),
+
+    /// Disables the cache of shader validation results.
+    ShaderValidationCache = ash::vk::ValidationFeatureDisableEXT::SHADER_VALIDATION_CACHE.as_raw(),
+}
+
+impl From<ValidationFeatureDisable> for ash::vk::ValidationFeatureDisableEXT {
+    #[inline]
+    fn from(val: ValidationFeatureDisable) -> Self {
+        Self::from_raw(val as i32)
+    }
+}
+
 /// A label to associate with a span of work in a queue.
 ///
 /// When debugging, labels can be useful to identify which queue, or where in a specific queue,
 /// something happened.
 #[derive(Clone, Debug)]
 pub struct DebugUtilsLabel {
-    /// The name of the label.
+    /// The name of the label. Must not contain nul characters.
     ///
     /// The default value is empty.
     pub label_name: String,
@@ -597,6 +745,32 @@ mod tests {
     use super::*;
     use std::thread;
 
+    #[test]
+    fn debug_printf_output() {
+        let message = |layer_prefix, description| Message {
+            severity: DebugUtilsMessageSeverity {
+                information: true,
+                ..DebugUtilsMessageSeverity::none()
+            },
+            ty: DebugUtilsMessageType::validation(),
+            layer_prefix,
+            description,
+        };
+
+        assert_eq!(
+            super::debug_printf_output(&message(
+                Some("UNASSIGNED-DEBUG-PRINTF"),
+                "Validation Information: [ UNASSIGNED-DEBUG-PRINTF ] | MessageID = 0x92394c89 | value = 42\n",
+            )),
+            Some("value = 42"),
+        );
+        assert_eq!(
+            super::debug_printf_output(&message(Some("VUID-vkCmdDraw-None-02699"), "error")),
+            None,
+        );
+        assert_eq!(super::debug_printf_output(&message(None, "error")), None);
+    }
+
     #[test]
     fn ensure_sendable() {
         // It's useful to be able to initialize a DebugUtilsMessenger on one thread
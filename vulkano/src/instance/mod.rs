@@ -52,7 +52,10 @@
 //! `device` module for more info.
 
 use self::{
-    debug::{DebugUtilsMessengerCreateInfo, UserCallback},
+    debug::{
+        DebugUtilsMessengerCreateInfo, UserCallback, ValidationFeatureDisable,
+        ValidationFeatureEnable,
+    },
     loader::{FunctionPointers, Loader},
 };
 pub use self::{
@@ -265,6 +268,11 @@ impl Instance {
     ///
     /// - Panics if the `message_severity` or `message_type` members of any element of
     ///   `debug_utils_messengers` are empty.
+    /// - Panics if `create_info.enabled_validation_features` contains
+    ///   [`ValidationFeatureEnable::GpuAssistedReserveBindingSlot`] without also containing
+    ///   [`ValidationFeatureEnable::GpuAssisted`].
+    /// - Panics if `create_info.enabled_validation_features` contains both
+    ///   [`ValidationFeatureEnable::GpuAssisted`] and [`ValidationFeatureEnable::DebugPrintf`].
     ///
     /// # Safety
     ///
@@ -284,6 +292,8 @@ impl Instance {
             function_pointers,
             max_api_version,
             enumerate_portability,
+            enabled_validation_features,
+            disabled_validation_features,
             _ne: _,
         } = create_info;
 
@@ -365,6 +375,50 @@ impl Instance {
             ..Default::default()
         };
 
+        // Handle validation features
+        let enabled_validation_features_vk: SmallVec<[_; 5]> = enabled_validation_features
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect();
+        let disabled_validation_features_vk: SmallVec<[_; 8]> = disabled_validation_features
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect();
+        let mut validation_features_vk = None;
+
+        if !enabled_validation_features.is_empty() || !disabled_validation_features.is_empty() {
+            if !enabled_extensions.ext_validation_features {
+                return Err(InstanceCreationError::ExtensionNotEnabled {
+                    extension: "ext_validation_features",
+                    reason:
+                        "enabled_validation_features or disabled_validation_features were not empty",
+                });
+            }
+
+            // VUID-VkValidationFeaturesEXT-pEnabledValidationFeatures-02967
+            assert!(
+                !enabled_validation_features
+                    .contains(&ValidationFeatureEnable::GpuAssistedReserveBindingSlot)
+                    || enabled_validation_features.contains(&ValidationFeatureEnable::GpuAssisted)
+            );
+
+            // VUID-VkValidationFeaturesEXT-pEnabledValidationFeatures-02968
+            assert!(
+                !(enabled_validation_features.contains(&ValidationFeatureEnable::DebugPrintf)
+                    && enabled_validation_features.contains(&ValidationFeatureEnable::GpuAssisted))
+            );
+
+            validation_features_vk = Some(ash::vk::ValidationFeaturesEXT {
+                enabled_validation_feature_count: enabled_validation_features_vk.len() as u32,
+                p_enabled_validation_features: enabled_validation_features_vk.as_ptr(),
+                disabled_validation_feature_count: disabled_validation_features_vk.len() as u32,
+                p_disabled_validation_features: disabled_validation_features_vk.as_ptr(),
+                ..Default::default()
+            });
+        }
+
         // Handle debug messengers
         let debug_utils_messengers = debug_utils_messengers.into_iter();
         let mut debug_utils_messenger_create_infos =
@@ -419,6 +473,11 @@ impl Instance {
             create_info.p_next = info as *const _ as *const _;
         }
 
+        if let Some(validation_features_vk) = validation_features_vk.as_mut() {
+            validation_features_vk.p_next = create_info.p_next;
+            create_info.p_next = validation_features_vk as *const _ as *const _;
+        }
+
         // Creating the Vulkan instance.
         let handle = {
             let mut output = MaybeUninit::uninit();
@@ -431,6 +490,89 @@ impl Instance {
             output.assume_init()
         };
 
+        Self::from_handle_inner(
+            handle,
+            function_pointers,
+            api_version,
+            max_api_version,
+            enabled_extensions,
+            enabled_layers,
+            user_callbacks,
+        )
+    }
+
+    /// Creates a new `Instance` from a raw handle that was created outside of vulkano, for
+    /// example by an OpenXR runtime with `xrCreateVulkanInstanceKHR`.
+    ///
+    /// `create_info` must describe how the instance was created: vulkano relies on
+    /// `enabled_extensions`, `enabled_layers`, `max_api_version` and `function_pointers`, and
+    /// ignores the other members. The instance is destroyed when the returned `Instance` is
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.max_api_version` is not at least `V1_0`.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid instance handle.
+    /// - `create_info` must match the parameters that the instance was created with. Extensions
+    ///   that were enabled but are not in `enabled_extensions` are allowed, but vulkano won't
+    ///   use them.
+    /// - If `function_pointers` is provided, it must be the loader that the instance was created
+    ///   with.
+    /// - The caller must not destroy the instance, and must not use it after the returned
+    ///   `Instance` is dropped.
+    pub unsafe fn from_handle(
+        handle: ash::vk::Instance,
+        create_info: InstanceCreateInfo,
+    ) -> Result<Arc<Instance>, InstanceCreationError> {
+        let InstanceCreateInfo {
+            enabled_extensions,
+            enabled_layers,
+            function_pointers,
+            max_api_version,
+            ..
+        } = create_info;
+
+        let function_pointers = if let Some(function_pointers) = function_pointers {
+            OwnedOrRef::Owned(function_pointers)
+        } else {
+            OwnedOrRef::Ref(loader::auto_loader()?)
+        };
+
+        let api_version = function_pointers.api_version()?;
+        let max_api_version = if let Some(max_api_version) = max_api_version {
+            max_api_version
+        } else if api_version < Version::V1_1 {
+            api_version
+        } else {
+            Version::HEADER_VERSION
+        };
+
+        // VUID-VkApplicationInfo-apiVersion-04010
+        assert!(max_api_version >= Version::V1_0);
+
+        Self::from_handle_inner(
+            handle,
+            function_pointers,
+            std::cmp::min(max_api_version, api_version),
+            max_api_version,
+            enabled_extensions,
+            enabled_layers,
+            Vec::new(),
+        )
+    }
+
+    unsafe fn from_handle_inner(
+        handle: ash::vk::Instance,
+        function_pointers: OwnedOrRef<FunctionPointers<Box<dyn Loader>>>,
+        api_version: Version,
+        max_api_version: Version,
+        enabled_extensions: InstanceExtensions,
+        enabled_layers: Vec<String>,
+        user_callbacks: Vec<Box<UserCallback>>,
+    ) -> Result<Arc<Instance>, InstanceCreationError> {
         // Loading the function pointers of the newly-created instance.
         let fns = {
             InstanceFunctions::load(|name| {
@@ -616,6 +758,26 @@ pub struct InstanceCreateInfo {
     ///   extension will automatically be enabled.
     pub enumerate_portability: bool,
 
+    /// Features of the validation layer to enable.
+    ///
+    /// If not empty, the
+    /// [`ext_validation_features`](crate::instance::InstanceExtensions::ext_validation_features)
+    /// extension must be enabled on the instance. For example, enabling
+    /// [`ValidationFeatureEnable::DebugPrintf`] makes the output of `debugPrintfEXT` calls in
+    /// shaders available to debug messengers.
+    ///
+    /// The default value is empty.
+    pub enabled_validation_features: Vec<ValidationFeatureEnable>,
+
+    /// Features of the validation layer to disable.
+    ///
+    /// If not empty, the
+    /// [`ext_validation_features`](crate::instance::InstanceExtensions::ext_validation_features)
+    /// extension must be enabled on the instance.
+    ///
+    /// The default value is empty.
+    pub disabled_validation_features: Vec<ValidationFeatureDisable>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -632,6 +794,8 @@ impl Default for InstanceCreateInfo {
             function_pointers: None,
             max_api_version: None,
             enumerate_portability: false,
+            enabled_validation_features: Vec::new(),
+            disabled_validation_features: Vec::new(),
             _ne: crate::NonExhaustive(()),
         }
     }
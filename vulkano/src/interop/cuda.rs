@@ -0,0 +1,415 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Sharing buffers, images and semaphores with CUDA.
+//!
+//! CUDA can import Vulkan memory with `cuImportExternalMemory` and Vulkan semaphores with
+//! `cuImportExternalSemaphore`. [`CudaExternalMemory`] and [`CudaExternalSemaphore`] hold an
+//! exported handle together with the values that these functions expect, so that they can be
+//! passed to crates such as `cudarc` or `cust` without going through raw Vulkan calls. This
+//! module doesn't depend on CUDA itself: the handle types and flags are returned as the raw values
+//! of the CUDA enums.
+//!
+//! # Requirements
+//!
+//! CUDA must use the same physical device as Vulkan. Compare the
+//! [`device_uuid`](crate::device::Properties::device_uuid) of the physical device with the UUID
+//! returned by `cuDeviceGetUuid` to find it.
+//!
+//! On Unix, the device must have the
+//! [`khr_external_memory_fd`](crate::device::DeviceExtensions::khr_external_memory_fd) and
+//! [`khr_external_semaphore_fd`](crate::device::DeviceExtensions::khr_external_semaphore_fd)
+//! extensions enabled, and the handle types are `opaque_fd`. On Windows, it must have the
+//! [`khr_external_memory_win32`](crate::device::DeviceExtensions::khr_external_memory_win32) and
+//! [`khr_external_semaphore_win32`](crate::device::DeviceExtensions::khr_external_semaphore_win32)
+//! extensions enabled, and the handle types are `opaque_win32`.
+//!
+//! # Ping-pong between Vulkan and CUDA
+//!
+//! A buffer that is written by CUDA and then read by Vulkan, over and over, is synchronized with
+//! two binary semaphores: one that Vulkan signals when it's done with the buffer and that CUDA
+//! waits on, and one that CUDA signals when it's done and that Vulkan waits on.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use vulkano::buffer::{BufferUsage, DeviceLocalBuffer};
+//! use vulkano::command_buffer::{submit::SubmitCommandBufferBuilder, PrimaryCommandBuffer};
+//! use vulkano::interop::cuda::{CudaExternalMemory, CudaExternalSemaphore};
+//! use vulkano::memory::{ExternalMemoryHandleType, ExternalMemoryHandleTypes};
+//! use vulkano::sync::{
+//!     ExternalSemaphoreHandleType, ExternalSemaphoreHandleTypes, Fence, PipelineStages,
+//!     Semaphore, SemaphoreCreateInfo,
+//! };
+//!
+//! # let device: Arc<vulkano::device::Device> = return;
+//! # let queue: Arc<vulkano::device::Queue> = return;
+//! # let command_buffer: vulkano::command_buffer::PrimaryAutoCommandBuffer = return;
+//! let buffer = unsafe {
+//!     DeviceLocalBuffer::<[f32]>::raw_with_external_memory(
+//!         device.clone(),
+//!         1024 * 4,
+//!         BufferUsage::storage_buffer(),
+//!         ExternalMemoryHandleTypes::posix(),
+//!         [queue.family()],
+//!     )
+//! }
+//! .unwrap();
+//!
+//! let create_semaphore = || {
+//!     Semaphore::new(
+//!         device.clone(),
+//!         SemaphoreCreateInfo {
+//!             export_handle_types: ExternalSemaphoreHandleTypes::posix(),
+//!             ..Default::default()
+//!         },
+//!     )
+//!     .unwrap()
+//! };
+//! let vulkan_done = create_semaphore();
+//! let cuda_done = create_semaphore();
+//!
+//! let memory = CudaExternalMemory::from_buffer(&buffer, ExternalMemoryHandleType::OpaqueFd)
+//!     .unwrap();
+//! let export_semaphore = |semaphore| unsafe {
+//!     CudaExternalSemaphore::from_semaphore(semaphore, ExternalSemaphoreHandleType::OpaqueFd)
+//!         .unwrap()
+//! };
+//! let vulkan_done_cuda = export_semaphore(&vulkan_done);
+//! let cuda_done_cuda = export_semaphore(&cuda_done);
+//! // Import `memory` with `cuImportExternalMemory`, using `memory.cuda_handle_type()`,
+//! // `memory.allocation_size` and `memory.cuda_flags()`, then map it with
+//! // `cuExternalMemoryGetMappedBuffer`, using `memory.offset` and `memory.size`.
+//! // Import both semaphores with `cuImportExternalSemaphore`.
+//!
+//! // Vulkan hands the buffer over to CUDA first.
+//! unsafe {
+//!     let mut builder = SubmitCommandBufferBuilder::new();
+//!     builder.add_signal_semaphore(&vulkan_done);
+//!     builder.submit(&queue).unwrap();
+//! }
+//!
+//! let mut fence = Fence::from_pool(device.clone()).unwrap();
+//!
+//! loop {
+//!     // CUDA: `cuWaitExternalSemaphoresAsync` on `vulkan_done`, launch the kernel that writes
+//!     // the buffer, then `cuSignalExternalSemaphoresAsync` on `cuda_done`, all on one stream.
+//!
+//!     // Vulkan reads the buffer once CUDA is done, then hands it back. The command buffer is
+//!     // submitted together with the semaphores, because a wait only applies to the commands of
+//!     // its own submission.
+//!     unsafe {
+//!         let mut builder = SubmitCommandBufferBuilder::new();
+//!         builder.add_wait_semaphore(
+//!             &cuda_done,
+//!             PipelineStages {
+//!                 all_commands: true,
+//!                 ..PipelineStages::none()
+//!             },
+//!         );
+//!         builder.add_command_buffer(command_buffer.inner());
+//!         builder.add_signal_semaphore(&vulkan_done);
+//!         builder.set_fence_signal(&fence);
+//!         builder.submit(&queue).unwrap();
+//!     }
+//!
+//!     // The command buffer must not be submitted again before its previous execution is over.
+//!     fence.wait(None).unwrap();
+//!     fence.reset().unwrap();
+//! }
+//! ```
+//!
+//! With a timeline semaphore, a single semaphore is enough: CUDA waits for and signals odd values,
+//! and Vulkan even values. Vulkan can then only wait and signal from the host, with
+//! [`Semaphore::wait`] and [`Semaphore::signal`].
+
+use crate::{
+    buffer::{BufferAccess, BufferContents, DeviceLocalBuffer},
+    image::StorageImage,
+    memory::{DeviceMemoryExportError, ExternalMemoryHandleType},
+    sync::{ExternalSemaphoreHandleType, Semaphore, SemaphoreExportError, SemaphoreType},
+    DeviceSize,
+};
+use std::{ffi::c_void, fs::File};
+
+/// `CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD`
+pub const CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD: u32 = 1;
+/// `CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32`
+pub const CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32: u32 = 2;
+/// `CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_KMT`
+pub const CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_KMT: u32 = 3;
+
+/// `CUDA_EXTERNAL_MEMORY_DEDICATED`
+pub const CUDA_EXTERNAL_MEMORY_DEDICATED: u32 = 0x1;
+
+/// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD`
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD: u32 = 1;
+/// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32`
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32: u32 = 2;
+/// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT`
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT: u32 = 3;
+/// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD`
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD: u32 = 9;
+/// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_WIN32`
+pub const CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_WIN32: u32 = 10;
+
+/// An exported handle, in the form that CUDA imports it.
+#[derive(Debug)]
+pub enum CudaExternalHandle {
+    /// A Unix file descriptor. This goes into the `fd` member of the handle description. CUDA
+    /// takes ownership of the file descriptor if the import succeeds, so it must then be
+    /// converted with `into_raw_fd` instead of being dropped.
+    Fd(File),
+
+    /// A Win32 handle. This goes into the `win32.handle` member of the handle description. For the
+    /// `opaque_win32` handle type, the caller owns the handle and must close it with `CloseHandle`
+    /// once it has been imported.
+    Win32(*mut c_void),
+}
+
+/// Exported memory of a buffer or image, along with the values needed to import it into CUDA.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CudaExternalMemory {
+    /// The exported handle of the memory.
+    pub handle: CudaExternalHandle,
+
+    /// The Vulkan handle type of `handle`.
+    pub handle_type: ExternalMemoryHandleType,
+
+    /// The size of the whole memory allocation. This goes into the `size` member of
+    /// `CUDA_EXTERNAL_MEMORY_HANDLE_DESC`.
+    pub allocation_size: DeviceSize,
+
+    /// The offset of the resource in the memory allocation. This goes into the `offset` member of
+    /// `CUDA_EXTERNAL_MEMORY_BUFFER_DESC` or `CUDA_EXTERNAL_MEMORY_MIPMAPPED_ARRAY_DESC`.
+    pub offset: DeviceSize,
+
+    /// The size of the resource. This goes into the `size` member of
+    /// `CUDA_EXTERNAL_MEMORY_BUFFER_DESC`.
+    pub size: DeviceSize,
+
+    /// Whether the memory is a dedicated allocation of the resource.
+    pub dedicated: bool,
+}
+
+impl CudaExternalMemory {
+    /// Exports the memory of `buffer`.
+    ///
+    /// The buffer must have been created with
+    /// [`DeviceLocalBuffer::raw_with_external_memory`], and `handle_type` must have been included
+    /// in `export_handle_types`.
+    pub fn from_buffer<T>(
+        buffer: &DeviceLocalBuffer<T>,
+        handle_type: ExternalMemoryHandleType,
+    ) -> Result<CudaExternalMemory, DeviceMemoryExportError>
+    where
+        T: BufferContents + ?Sized,
+    {
+        let handle = match handle_type {
+            ExternalMemoryHandleType::OpaqueFd => {
+                CudaExternalHandle::Fd(buffer.export_fd(handle_type)?)
+            }
+            ExternalMemoryHandleType::OpaqueWin32 | ExternalMemoryHandleType::OpaqueWin32Kmt => {
+                CudaExternalHandle::Win32(buffer.export_win32_handle(handle_type)?)
+            }
+            _ => return Err(DeviceMemoryExportError::HandleTypeNotSupported { handle_type }),
+        };
+
+        Ok(CudaExternalMemory {
+            handle,
+            handle_type,
+            allocation_size: buffer.mem_size(),
+            offset: 0,
+            size: buffer.size(),
+            dedicated: true,
+        })
+    }
+
+    /// Exports the memory of `image`.
+    ///
+    /// The image must have been created with [`StorageImage::new_with_external_memory`], and
+    /// `handle_type` must have been included in `export_handle_types`. The image is imported
+    /// with `cuExternalMemoryGetMappedMipmappedArray`, for which the format and extent must be
+    /// provided separately.
+    pub fn from_image(
+        image: &StorageImage,
+        handle_type: ExternalMemoryHandleType,
+    ) -> Result<CudaExternalMemory, DeviceMemoryExportError> {
+        let handle = match handle_type {
+            ExternalMemoryHandleType::OpaqueFd => {
+                CudaExternalHandle::Fd(image.export_fd(handle_type)?)
+            }
+            ExternalMemoryHandleType::OpaqueWin32 | ExternalMemoryHandleType::OpaqueWin32Kmt => {
+                CudaExternalHandle::Win32(image.export_win32_handle(handle_type)?)
+            }
+            _ => return Err(DeviceMemoryExportError::HandleTypeNotSupported { handle_type }),
+        };
+
+        Ok(CudaExternalMemory {
+            handle,
+            handle_type,
+            allocation_size: image.mem_size(),
+            offset: 0,
+            size: image.mem_size(),
+            dedicated: true,
+        })
+    }
+
+    /// Returns the value of `CUexternalMemoryHandleType` that corresponds to `handle_type`.
+    #[inline]
+    pub fn cuda_handle_type(&self) -> u32 {
+        match self.handle_type {
+            ExternalMemoryHandleType::OpaqueFd => CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD,
+            ExternalMemoryHandleType::OpaqueWin32 => CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32,
+            ExternalMemoryHandleType::OpaqueWin32Kmt => {
+                CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_KMT
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the value of the `flags` member of `CUDA_EXTERNAL_MEMORY_HANDLE_DESC`.
+    #[inline]
+    pub fn cuda_flags(&self) -> u32 {
+        if self.dedicated {
+            CUDA_EXTERNAL_MEMORY_DEDICATED
+        } else {
+            0
+        }
+    }
+}
+
+/// An exported semaphore, along with the values needed to import it into CUDA.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct CudaExternalSemaphore {
+    /// The exported handle of the semaphore.
+    pub handle: CudaExternalHandle,
+
+    /// The Vulkan handle type of `handle`.
+    pub handle_type: ExternalSemaphoreHandleType,
+
+    /// The type of the semaphore.
+    pub semaphore_type: SemaphoreType,
+}
+
+impl CudaExternalSemaphore {
+    /// Exports `semaphore`.
+    ///
+    /// `handle_type` must be [`ExternalSemaphoreHandleType::OpaqueFd`],
+    /// [`ExternalSemaphoreHandleType::OpaqueWin32`] or, for binary semaphores,
+    /// [`ExternalSemaphoreHandleType::OpaqueWin32Kmt`], and must have been provided in
+    /// `export_handle_types` when creating the semaphore.
+    ///
+    /// # Safety
+    ///
+    /// - The semaphore must not be used, or have been used, to acquire a swapchain image.
+    pub unsafe fn from_semaphore(
+        semaphore: &Semaphore,
+        handle_type: ExternalSemaphoreHandleType,
+    ) -> Result<CudaExternalSemaphore, SemaphoreExportError> {
+        let semaphore_type = semaphore.semaphore_type();
+
+        let handle = match (handle_type, semaphore_type) {
+            (ExternalSemaphoreHandleType::OpaqueFd, _) => {
+                CudaExternalHandle::Fd(semaphore.export_fd(handle_type)?)
+            }
+            (ExternalSemaphoreHandleType::OpaqueWin32, _)
+            | (ExternalSemaphoreHandleType::OpaqueWin32Kmt, SemaphoreType::Binary) => {
+                CudaExternalHandle::Win32(semaphore.export_win32_handle(handle_type)?)
+            }
+            _ => return Err(SemaphoreExportError::HandleTypeNotSupported { handle_type }),
+        };
+
+        Ok(CudaExternalSemaphore {
+            handle,
+            handle_type,
+            semaphore_type,
+        })
+    }
+
+    /// Returns the value of `CUexternalSemaphoreHandleType` that corresponds to `handle_type` and
+    /// `semaphore_type`.
+    #[inline]
+    pub fn cuda_handle_type(&self) -> u32 {
+        match (self.handle_type, self.semaphore_type) {
+            (ExternalSemaphoreHandleType::OpaqueFd, SemaphoreType::Binary) => {
+                CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD
+            }
+            (ExternalSemaphoreHandleType::OpaqueWin32, SemaphoreType::Binary) => {
+                CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32
+            }
+            (ExternalSemaphoreHandleType::OpaqueWin32Kmt, SemaphoreType::Binary) => {
+                CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT
+            }
+            (ExternalSemaphoreHandleType::OpaqueFd, SemaphoreType::Timeline) => {
+                CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD
+            }
+            (ExternalSemaphoreHandleType::OpaqueWin32, SemaphoreType::Timeline) => {
+                CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_WIN32
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CudaExternalMemory, CudaExternalSemaphore};
+    use crate::{
+        buffer::{BufferUsage, DeviceLocalBuffer},
+        memory::{DeviceMemoryExportError, ExternalMemoryHandleType},
+        sync::{ExternalSemaphoreHandleType, Semaphore, SemaphoreCreateInfo, SemaphoreExportError},
+    };
+
+    #[test]
+    fn memory_handle_type_not_supported() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let buffer = unsafe {
+            DeviceLocalBuffer::<[u8]>::raw(
+                device,
+                1024,
+                BufferUsage::storage_buffer(),
+                [queue.family()],
+            )
+            .unwrap()
+        };
+
+        assert!(matches!(
+            CudaExternalMemory::from_buffer(&buffer, ExternalMemoryHandleType::DmaBuf),
+            Err(DeviceMemoryExportError::HandleTypeNotSupported {
+                handle_type: ExternalMemoryHandleType::DmaBuf,
+            })
+        ));
+    }
+
+    #[test]
+    fn semaphore_handle_type_not_supported() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let semaphore = Semaphore::new(device, SemaphoreCreateInfo::default()).unwrap();
+
+        assert!(matches!(
+            unsafe {
+                CudaExternalSemaphore::from_semaphore(
+                    &semaphore,
+                    ExternalSemaphoreHandleType::SyncFd,
+                )
+            },
+            Err(SemaphoreExportError::HandleTypeNotSupported {
+                handle_type: ExternalSemaphoreHandleType::SyncFd,
+            })
+        ));
+    }
+}
@@ -0,0 +1,557 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Sharing rendered images between processes.
+//!
+//! Multi-process applications, such as an editor whose viewports are rendered by separate
+//! processes, or a compositor and its clients, can render in one process and use the result in
+//! another. The *client* process renders into a [`SharedFrame`], and exports it as a
+//! [`SharedFrameDescriptor`] and [`SharedFrameHandles`]. It sends both to the *host* process,
+//! which imports them with [`SharedFrame::import`].
+//!
+//! A shared frame consists of an image and a timeline semaphore. After the client has finished
+//! rendering a frame into the image, it signals the semaphore with a value that is greater than
+//! the previous one. The host waits for that value before it reads the image. How the values are
+//! agreed on is up to the application, for example by sending the value along with a message
+//! that a new frame is ready.
+//!
+//! # Exchanging the handles
+//!
+//! The descriptor is plain data. If the `serde` feature of vulkano is enabled, it implements
+//! `Serialize` and `Deserialize`, so that it can be sent over any channel. The file descriptors in
+//! the handles must be sent separately, for example with `SCM_RIGHTS` over a Unix socket.
+//!
+//! The descriptor has a version, which is checked on import, so that processes built against
+//! incompatible versions of vulkano fail with [`SharedFrameError::VersionMismatch`] instead of
+//! misinterpreting each other's data.
+//!
+//! # Requirements
+//!
+//! Both processes must use the same physical device and driver. This is checked on import by
+//! comparing their UUIDs. Both devices must have the
+//! [`khr_external_memory_fd`](crate::device::DeviceExtensions::khr_external_memory_fd) and
+//! [`khr_external_semaphore_fd`](crate::device::DeviceExtensions::khr_external_semaphore_fd)
+//! extensions and the [`timeline_semaphore`](crate::device::Features::timeline_semaphore)
+//! feature enabled. Only Unix platforms are supported.
+//!
+//! ```no_run
+//! use vulkano::interop::{SharedFrame, SharedFrameCreateInfo};
+//! use vulkano::format::Format;
+//!
+//! # let device: std::sync::Arc<vulkano::device::Device> = return;
+//! # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+//! // In the client process.
+//! let frame = SharedFrame::new(
+//!     device.clone(),
+//!     queue.family(),
+//!     SharedFrameCreateInfo {
+//!         extent: [1280, 720],
+//!         format: Some(Format::R8G8B8A8_UNORM),
+//!         ..Default::default()
+//!     },
+//! )
+//! .unwrap();
+//! let (descriptor, handles) = frame.export().unwrap();
+//! // Send `descriptor` and `handles` to the host process.
+//!
+//! // In the host process.
+//! let frame = unsafe { SharedFrame::import(device, queue.family(), &descriptor, handles) }.unwrap();
+//! ```
+//!
+//! # Other APIs
+//!
+//! The [`cuda`] module packages exported memory and semaphores in the form that CUDA expects
+//! when importing them. The [`openxr`] module wraps the images of OpenXR swapchains.
+
+pub mod cuda;
+pub mod openxr;
+
+use crate::{
+    device::{physical::QueueFamily, Device, DeviceOwned},
+    format::Format,
+    image::{
+        ImageAccess, ImageCreateFlags, ImageCreationError, ImageDimensions, ImageUsage,
+        StorageImage,
+    },
+    memory::DeviceMemoryExportError,
+    sync::{
+        ExternalSemaphoreHandleType, ExternalSemaphoreHandleTypes, Semaphore, SemaphoreCreateInfo,
+        SemaphoreCreationError, SemaphoreExportError, SemaphoreImportError, SemaphoreType,
+    },
+    DeviceSize,
+};
+use std::{error, fmt, fs::File, sync::Arc};
+
+/// The version of [`SharedFrameDescriptor`] that is created and accepted by this version of
+/// vulkano.
+pub const SHARED_FRAME_VERSION: u32 = 1;
+
+/// An image and a timeline semaphore that can be shared with another process.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+pub struct SharedFrame {
+    image: Arc<StorageImage>,
+    semaphore: Arc<Semaphore>,
+}
+
+impl SharedFrame {
+    /// Creates a new `SharedFrame` that can be exported.
+    ///
+    /// The semaphore starts with a counter value of 0.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.format` is `None`.
+    pub fn new(
+        device: Arc<Device>,
+        queue_family: QueueFamily,
+        create_info: SharedFrameCreateInfo,
+    ) -> Result<SharedFrame, SharedFrameError> {
+        let SharedFrameCreateInfo {
+            extent,
+            format,
+            usage,
+            _ne: _,
+        } = create_info;
+
+        let format = format.unwrap();
+        check_extensions(&device)?;
+
+        let image = StorageImage::new_with_exportable_fd(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: extent[0],
+                height: extent[1],
+                array_layers: 1,
+            },
+            format,
+            usage,
+            ImageCreateFlags::none(),
+            [queue_family],
+        )?;
+
+        let semaphore = Arc::new(Semaphore::new(
+            device,
+            SemaphoreCreateInfo {
+                semaphore_type: SemaphoreType::Timeline,
+                export_handle_types: ExternalSemaphoreHandleTypes {
+                    opaque_fd: true,
+                    ..ExternalSemaphoreHandleTypes::none()
+                },
+                ..Default::default()
+            },
+        )?);
+
+        Ok(SharedFrame { image, semaphore })
+    }
+
+    /// Imports a `SharedFrame` that was exported by another process.
+    ///
+    /// # Safety
+    ///
+    /// - `descriptor` and `handles` must have been returned by the same call to
+    ///   [`export`](Self::export).
+    /// - Vulkan takes ownership of the file descriptors in `handles` if the import succeeds.
+    pub unsafe fn import(
+        device: Arc<Device>,
+        queue_family: QueueFamily,
+        descriptor: &SharedFrameDescriptor,
+        handles: SharedFrameHandles,
+    ) -> Result<SharedFrame, SharedFrameError> {
+        if descriptor.version != SHARED_FRAME_VERSION {
+            return Err(SharedFrameError::VersionMismatch {
+                version: descriptor.version,
+            });
+        }
+
+        check_extensions(&device)?;
+
+        if device_uuids(&device)? != (descriptor.device_uuid, descriptor.driver_uuid) {
+            return Err(SharedFrameError::DeviceMismatch);
+        }
+
+        let format = descriptor
+            .format()
+            .ok_or(SharedFrameError::FormatNotRecognized {
+                format: descriptor.format,
+            })?;
+
+        let memory_type_count = device.physical_device().memory_types().len() as u32;
+
+        if descriptor.memory_type_index >= memory_type_count {
+            return Err(SharedFrameError::MemoryTypeIndexOutOfRange {
+                memory_type_index: descriptor.memory_type_index,
+                memory_type_count,
+            });
+        }
+
+        let SharedFrameHandles {
+            memory,
+            semaphore: semaphore_file,
+        } = handles;
+
+        let image = StorageImage::new_from_imported_fd(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: descriptor.extent[0],
+                height: descriptor.extent[1],
+                array_layers: descriptor.array_layers,
+            },
+            format,
+            descriptor.usage(),
+            ImageCreateFlags::none(),
+            [queue_family],
+            memory,
+            descriptor.allocation_size,
+            descriptor.memory_type_index,
+        )?;
+
+        let semaphore = Semaphore::new(
+            device,
+            SemaphoreCreateInfo {
+                semaphore_type: SemaphoreType::Timeline,
+                ..Default::default()
+            },
+        )?;
+        semaphore.import_fd(ExternalSemaphoreHandleType::OpaqueFd, semaphore_file, false)?;
+
+        Ok(SharedFrame {
+            image,
+            semaphore: Arc::new(semaphore),
+        })
+    }
+
+    /// Exports the frame, so that it can be imported by another process.
+    ///
+    /// Each call returns new file descriptors.
+    pub fn export(&self) -> Result<(SharedFrameDescriptor, SharedFrameHandles), SharedFrameError> {
+        let (device_uuid, driver_uuid) = device_uuids(self.image.device())?;
+        let dimensions = self.image.dimensions();
+
+        let descriptor = SharedFrameDescriptor {
+            version: SHARED_FRAME_VERSION,
+            device_uuid,
+            driver_uuid,
+            format: ash::vk::Format::from(self.image.format()).as_raw(),
+            extent: [dimensions.width(), dimensions.height()],
+            array_layers: dimensions.array_layers(),
+            usage: ash::vk::ImageUsageFlags::from(*self.image.inner().image.usage()).as_raw(),
+            allocation_size: self.image.mem_size(),
+            memory_type_index: self.image.memory_type_index(),
+        };
+
+        let handles = SharedFrameHandles {
+            memory: self.image.export_posix_fd()?,
+            // The semaphore is never used to acquire a swapchain image.
+            semaphore: unsafe { self.semaphore.export_opaque_fd()? },
+        };
+
+        Ok((descriptor, handles))
+    }
+
+    /// Returns the image of the frame.
+    #[inline]
+    pub fn image(&self) -> &Arc<StorageImage> {
+        &self.image
+    }
+
+    /// Returns the timeline semaphore that is signaled when a new frame is ready.
+    #[inline]
+    pub fn semaphore(&self) -> &Arc<Semaphore> {
+        &self.semaphore
+    }
+}
+
+fn check_extensions(device: &Device) -> Result<(), SharedFrameError> {
+    if !device.enabled_extensions().khr_external_memory_fd {
+        return Err(SharedFrameError::ExtensionNotEnabled {
+            extension: "khr_external_memory_fd",
+            reason: "created or imported a shared frame",
+        });
+    }
+
+    if !device.enabled_extensions().khr_external_semaphore_fd {
+        return Err(SharedFrameError::ExtensionNotEnabled {
+            extension: "khr_external_semaphore_fd",
+            reason: "created or imported a shared frame",
+        });
+    }
+
+    Ok(())
+}
+
+fn device_uuids(device: &Device) -> Result<([u8; 16], [u8; 16]), SharedFrameError> {
+    let physical_device = device.physical_device();
+    let properties = physical_device.properties();
+
+    // The UUIDs are available with the same instance extension that is needed to use external
+    // memory, or with Vulkan 1.1.
+    match (properties.device_uuid, properties.driver_uuid) {
+        (Some(device_uuid), Some(driver_uuid)) => Ok((device_uuid, driver_uuid)),
+        _ => Err(SharedFrameError::ExtensionNotEnabled {
+            extension: "khr_external_memory_capabilities",
+            reason: "the device and driver UUIDs are needed to share a frame",
+        }),
+    }
+}
+
+/// Parameters to create a new `SharedFrame`.
+#[derive(Clone, Debug)]
+pub struct SharedFrameCreateInfo {
+    /// The width and height of the image.
+    ///
+    /// The default value is `[0, 0]`, which must be overridden.
+    pub extent: [u32; 2],
+
+    /// The format of the image.
+    ///
+    /// The default value is `None`, which must be overridden.
+    pub format: Option<Format>,
+
+    /// How the image is going to be used, by either process.
+    ///
+    /// The default value is `color_attachment`, `sampled`, `transfer_src` and `transfer_dst`.
+    pub usage: ImageUsage,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for SharedFrameCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            extent: [0, 0],
+            format: None,
+            usage: ImageUsage {
+                color_attachment: true,
+                sampled: true,
+                transfer_src: true,
+                transfer_dst: true,
+                ..ImageUsage::none()
+            },
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Describes an exported [`SharedFrame`], so that it can be imported by another process.
+///
+/// The values are stored in their raw Vulkan representation, so that they can be serialized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct SharedFrameDescriptor {
+    /// The version of the descriptor. This is [`SHARED_FRAME_VERSION`] for descriptors created
+    /// by this version of vulkano.
+    pub version: u32,
+
+    /// The UUID of the physical device that exported the frame.
+    pub device_uuid: [u8; 16],
+
+    /// The UUID of the driver that exported the frame.
+    pub driver_uuid: [u8; 16],
+
+    /// The format of the image, as a raw `VkFormat`.
+    pub format: i32,
+
+    /// The width and height of the image.
+    pub extent: [u32; 2],
+
+    /// The number of array layers of the image.
+    pub array_layers: u32,
+
+    /// The usage of the image, as raw `VkImageUsageFlags`.
+    pub usage: u32,
+
+    /// The size of the memory allocation of the image.
+    pub allocation_size: DeviceSize,
+
+    /// The index of the memory type of the memory allocation of the image.
+    pub memory_type_index: u32,
+}
+
+impl SharedFrameDescriptor {
+    /// Returns the format of the image, or `None` if it's not known to this version of vulkano.
+    #[inline]
+    pub fn format(&self) -> Option<Format> {
+        Format::try_from(ash::vk::Format::from_raw(self.format)).ok()
+    }
+
+    /// Returns the usage of the image.
+    #[inline]
+    pub fn usage(&self) -> ImageUsage {
+        ImageUsage::from(ash::vk::ImageUsageFlags::from_raw(self.usage))
+    }
+}
+
+/// The file descriptors of an exported [`SharedFrame`].
+#[derive(Debug)]
+pub struct SharedFrameHandles {
+    /// The memory of the image.
+    pub memory: File,
+
+    /// The payload of the timeline semaphore.
+    pub semaphore: File,
+}
+
+/// Error that can happen when creating, exporting or importing a `SharedFrame`.
+#[derive(Clone, Debug)]
+pub enum SharedFrameError {
+    /// Creating the image failed.
+    ImageCreationError(ImageCreationError),
+
+    /// Creating the semaphore failed.
+    SemaphoreCreationError(SemaphoreCreationError),
+
+    /// Exporting the memory of the image failed.
+    MemoryExportError(DeviceMemoryExportError),
+
+    /// Exporting the semaphore failed.
+    SemaphoreExportError(SemaphoreExportError),
+
+    /// Importing the semaphore failed.
+    SemaphoreImportError(SemaphoreImportError),
+
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+
+    /// The device and driver UUIDs of the descriptor are not those of the device.
+    DeviceMismatch,
+
+    /// The format of the descriptor is not known to this version of vulkano.
+    FormatNotRecognized { format: i32 },
+
+    /// The memory type index of the descriptor is not a memory type of the device.
+    MemoryTypeIndexOutOfRange {
+        memory_type_index: u32,
+        memory_type_count: u32,
+    },
+
+    /// The version of the descriptor is not [`SHARED_FRAME_VERSION`].
+    VersionMismatch { version: u32 },
+}
+
+impl error::Error for SharedFrameError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::ImageCreationError(ref err) => Some(err),
+            Self::SemaphoreCreationError(ref err) => Some(err),
+            Self::MemoryExportError(ref err) => Some(err),
+            Self::SemaphoreExportError(ref err) => Some(err),
+            Self::SemaphoreImportError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SharedFrameError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::ImageCreationError(_) => write!(fmt, "creating the image failed"),
+            Self::SemaphoreCreationError(_) => write!(fmt, "creating the semaphore failed"),
+            Self::MemoryExportError(_) => write!(fmt, "exporting the memory of the image failed"),
+            Self::SemaphoreExportError(_) => write!(fmt, "exporting the semaphore failed"),
+            Self::SemaphoreImportError(_) => write!(fmt, "importing the semaphore failed"),
+            Self::ExtensionNotEnabled { extension, reason } => write!(
+                fmt,
+                "the extension {} must be enabled: {}",
+                extension, reason
+            ),
+            Self::DeviceMismatch => write!(
+                fmt,
+                "the device and driver UUIDs of the descriptor are not those of the device",
+            ),
+            Self::FormatNotRecognized { format } => write!(
+                fmt,
+                "the format of the descriptor ({}) is not known to this version of vulkano",
+                format,
+            ),
+            Self::MemoryTypeIndexOutOfRange {
+                memory_type_index,
+                memory_type_count,
+            } => write!(
+                fmt,
+                "the memory type index of the descriptor ({}) is not less than the number of memory types of the device ({})",
+                memory_type_index, memory_type_count,
+            ),
+            Self::VersionMismatch { version } => write!(
+                fmt,
+                "the version of the descriptor ({}) is not {}",
+                version, SHARED_FRAME_VERSION,
+            ),
+        }
+    }
+}
+
+impl From<ImageCreationError> for SharedFrameError {
+    #[inline]
+    fn from(err: ImageCreationError) -> Self {
+        Self::ImageCreationError(err)
+    }
+}
+
+impl From<SemaphoreCreationError> for SharedFrameError {
+    #[inline]
+    fn from(err: SemaphoreCreationError) -> Self {
+        Self::SemaphoreCreationError(err)
+    }
+}
+
+impl From<DeviceMemoryExportError> for SharedFrameError {
+    #[inline]
+    fn from(err: DeviceMemoryExportError) -> Self {
+        Self::MemoryExportError(err)
+    }
+}
+
+impl From<SemaphoreExportError> for SharedFrameError {
+    #[inline]
+    fn from(err: SemaphoreExportError) -> Self {
+        Self::SemaphoreExportError(err)
+    }
+}
+
+impl From<SemaphoreImportError> for SharedFrameError {
+    #[inline]
+    fn from(err: SemaphoreImportError) -> Self {
+        Self::SemaphoreImportError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SharedFrame, SharedFrameCreateInfo, SharedFrameError};
+    use crate::format::Format;
+
+    #[test]
+    fn extension_not_enabled() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        assert!(matches!(
+            SharedFrame::new(
+                device,
+                queue.family(),
+                SharedFrameCreateInfo {
+                    extent: [64, 64],
+                    format: Some(Format::R8G8B8A8_UNORM),
+                    ..Default::default()
+                },
+            ),
+            Err(SharedFrameError::ExtensionNotEnabled {
+                extension: "khr_external_memory_fd",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,304 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Rendering to OpenXR swapchains.
+//!
+//! With the `XR_KHR_vulkan_enable2` extension, the OpenXR runtime creates the Vulkan instance and
+//! device itself, with `xrCreateVulkanInstanceKHR` and `xrCreateVulkanDeviceKHR`, and chooses the
+//! physical device with `xrGetVulkanGraphicsDevice2KHR`. The resulting handles are turned into
+//! vulkano objects with:
+//!
+//! - [`Instance::from_handle`](crate::instance::Instance::from_handle), with the
+//!   `InstanceCreateInfo` whose extensions, layers and API version were passed to the runtime.
+//! - [`PhysicalDevice::from_handle`](crate::device::physical::PhysicalDevice::from_handle).
+//! - [`Device::from_handle`](crate::device::Device::from_handle), with the `DeviceCreateInfo`
+//!   whose extensions, features and queues were passed to the runtime. It also returns the
+//!   queues.
+//!
+//! The `XrGraphicsBindingVulkan2KHR` structure of the session is then filled with the
+//! [`internal_object`](crate::VulkanObject::internal_object) of the instance, physical device
+//! and device, and with the queue family index and queue index of the queue that the frames are
+//! submitted on.
+//!
+//! The images of an OpenXR swapchain, returned by `xrEnumerateSwapchainImages`, are wrapped with
+//! [`OpenXrSwapchainImage::from_handles`]. Like [`SwapchainImage`](crate::image::SwapchainImage),
+//! an image must only be used between `xrWaitSwapchainImage` and `xrReleaseSwapchainImage`.
+//!
+//! ```no_run
+//! use vulkano::device::{physical::PhysicalDevice, Device, DeviceCreateInfo, QueueCreateInfo};
+//! use vulkano::format::Format;
+//! use vulkano::image::ImageUsage;
+//! use vulkano::instance::{Instance, InstanceCreateInfo};
+//! use vulkano::interop::openxr::{OpenXrSwapchainImage, OpenXrSwapchainImageCreateInfo};
+//! use vulkano::Handle;
+//!
+//! # let raw_instance: ash::vk::Instance = return;
+//! # let raw_physical_device: ash::vk::PhysicalDevice = return;
+//! # let raw_device: ash::vk::Device = return;
+//! # let raw_images: Vec<u64> = return;
+//! # let queue_family_index: u32 = return;
+//! // `raw_instance` was created by `xrCreateVulkanInstanceKHR`.
+//! let instance = unsafe { Instance::from_handle(raw_instance, InstanceCreateInfo::default()) }
+//!     .unwrap();
+//!
+//! // `raw_physical_device` was returned by `xrGetVulkanGraphicsDevice2KHR`.
+//! let physical_device = PhysicalDevice::from_handle(&instance, raw_physical_device).unwrap();
+//! let queue_family = physical_device.queue_family_by_id(queue_family_index).unwrap();
+//!
+//! // `raw_device` was created by `xrCreateVulkanDeviceKHR`.
+//! let (device, mut queues) = unsafe {
+//!     Device::from_handle(
+//!         physical_device,
+//!         raw_device,
+//!         DeviceCreateInfo {
+//!             queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
+//!             ..Default::default()
+//!         },
+//!     )
+//! };
+//! let queue = queues.next().unwrap();
+//!
+//! // `raw_images` were returned by `xrEnumerateSwapchainImages`, for a swapchain that was created
+//! // with the same parameters.
+//! let images = unsafe {
+//!     OpenXrSwapchainImage::from_handles(
+//!         device.clone(),
+//!         raw_images.into_iter().map(ash::vk::Image::from_raw),
+//!         OpenXrSwapchainImageCreateInfo {
+//!             format: Some(Format::R8G8B8A8_SRGB),
+//!             extent: [1440, 1600],
+//!             array_layers: 2,
+//!             usage: ImageUsage::color_attachment(),
+//!             ..Default::default()
+//!         },
+//!     )
+//! };
+//! ```
+
+use crate::{
+    device::{Device, DeviceOwned},
+    format::Format,
+    image::{
+        sys::UnsafeImage, traits::ImageContent, ImageAccess, ImageCreateFlags,
+        ImageDescriptorLayouts, ImageDimensions, ImageInner, ImageLayout, ImageUsage, SampleCount,
+    },
+    VulkanObject,
+};
+use smallvec::SmallVec;
+use std::{
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// An image of an OpenXR swapchain.
+///
+/// The runtime guarantees that an image is in the `ColorAttachmentOptimal` layout, or
+/// `DepthStencilAttachmentOptimal` for depth and stencil formats, once it has been acquired and
+/// waited on. Command buffers that use the image transition it back to that layout at the end, as
+/// the runtime requires before the image is released.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+pub struct OpenXrSwapchainImage {
+    image: Arc<UnsafeImage>,
+    layout: ImageLayout,
+}
+
+impl OpenXrSwapchainImage {
+    /// Wraps the images of an OpenXR swapchain, in the order of `handles`. The images are not
+    /// destroyed when the returned values are dropped; they belong to the swapchain.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.format` is `None`.
+    ///
+    /// # Safety
+    ///
+    /// - `handles` must be the images of an OpenXR swapchain whose session uses `device`.
+    /// - `create_info` must match the parameters that the swapchain was created with.
+    /// - The images must not be used after the swapchain is destroyed.
+    pub unsafe fn from_handles(
+        device: Arc<Device>,
+        handles: impl IntoIterator<Item = ash::vk::Image>,
+        create_info: OpenXrSwapchainImageCreateInfo,
+    ) -> Vec<Arc<OpenXrSwapchainImage>> {
+        let OpenXrSwapchainImageCreateInfo {
+            format,
+            extent,
+            array_layers,
+            mip_levels,
+            samples,
+            usage,
+            _ne: _,
+        } = create_info;
+
+        let format = format.unwrap();
+        let aspects = format.aspects();
+        let layout = if aspects.depth || aspects.stencil {
+            ImageLayout::DepthStencilAttachmentOptimal
+        } else {
+            ImageLayout::ColorAttachmentOptimal
+        };
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                let image = UnsafeImage::from_raw(
+                    device.clone(),
+                    handle,
+                    usage,
+                    format,
+                    ImageCreateFlags::none(),
+                    SmallVec::new(),
+                    ImageDimensions::Dim2d {
+                        width: extent[0],
+                        height: extent[1],
+                        array_layers,
+                    },
+                    samples,
+                    mip_levels,
+                );
+
+                Arc::new(OpenXrSwapchainImage { image, layout })
+            })
+            .collect()
+    }
+}
+
+/// Parameters of the OpenXR swapchain that [`OpenXrSwapchainImage`]s belong to.
+///
+/// These correspond to the members of `XrSwapchainCreateInfo`.
+#[derive(Clone, Debug)]
+pub struct OpenXrSwapchainImageCreateInfo {
+    /// The format of the images.
+    ///
+    /// The default value is `None`, which must be overridden.
+    pub format: Option<Format>,
+
+    /// The width and height of the images.
+    ///
+    /// The default value is `[0, 0]`, which must be overridden.
+    pub extent: [u32; 2],
+
+    /// The number of array layers of the images. This is 2 for a swapchain that holds the views
+    /// of both eyes, to render them with multiview.
+    ///
+    /// The default value is `1`.
+    pub array_layers: u32,
+
+    /// The number of mipmap levels of the images.
+    ///
+    /// The default value is `1`.
+    pub mip_levels: u32,
+
+    /// The number of samples of the images.
+    ///
+    /// The default value is [`SampleCount::Sample1`].
+    pub samples: SampleCount,
+
+    /// The usage that the swapchain was created with, converted from `XrSwapchainUsageFlags`.
+    ///
+    /// The default value is [`ImageUsage::none()`], which must be overridden.
+    pub usage: ImageUsage,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for OpenXrSwapchainImageCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            format: None,
+            extent: [0, 0],
+            array_layers: 1,
+            mip_levels: 1,
+            samples: SampleCount::Sample1,
+            usage: ImageUsage::none(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+unsafe impl DeviceOwned for OpenXrSwapchainImage {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.device()
+    }
+}
+
+unsafe impl VulkanObject for OpenXrSwapchainImage {
+    type Object = ash::vk::Image;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Image {
+        self.image.internal_object()
+    }
+}
+
+unsafe impl ImageAccess for OpenXrSwapchainImage {
+    #[inline]
+    fn inner(&self) -> ImageInner {
+        ImageInner {
+            image: &self.image,
+            first_layer: 0,
+            num_layers: self.image.dimensions().array_layers(),
+            first_mipmap_level: 0,
+            num_mipmap_levels: self.image.mip_levels(),
+        }
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        self.layout
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        self.layout
+    }
+
+    #[inline]
+    fn descriptor_layouts(&self) -> Option<ImageDescriptorLayouts> {
+        Some(ImageDescriptorLayouts {
+            storage_image: ImageLayout::General,
+            combined_image_sampler: ImageLayout::ShaderReadOnlyOptimal,
+            sampled_image: ImageLayout::ShaderReadOnlyOptimal,
+            input_attachment: ImageLayout::ShaderReadOnlyOptimal,
+        })
+    }
+
+    // The runtime transitions the image before handing it out, so it's never `Undefined`.
+    #[inline]
+    fn is_layout_initialized(&self) -> bool {
+        true
+    }
+}
+
+unsafe impl<P> ImageContent<P> for OpenXrSwapchainImage {
+    #[inline]
+    fn matches_format(&self) -> bool {
+        true // FIXME:
+    }
+}
+
+impl PartialEq for OpenXrSwapchainImage {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner() == other.inner()
+    }
+}
+
+impl Eq for OpenXrSwapchainImage {}
+
+impl Hash for OpenXrSwapchainImage {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner().hash(state);
+    }
+}
@@ -71,12 +71,19 @@ use std::{
 };
 pub use version::Version;
 
+// Allows the procedural macros, which refer to `::vulkano`, to be used inside this crate.
+#[cfg(feature = "macros")]
+extern crate self as vulkano;
+
 #[macro_use]
 mod tests;
 #[macro_use]
 mod extensions;
+#[cfg(feature = "benchmark")]
+pub mod benchmark;
 pub mod buffer;
 pub mod command_buffer;
+pub mod debug;
 pub mod descriptor_set;
 pub mod device;
 pub mod format;
@@ -86,7 +93,9 @@ pub mod render_pass;
 mod fns;
 pub mod image;
 pub mod instance;
+pub mod interop;
 pub mod memory;
+pub mod optical_flow;
 pub mod pipeline;
 pub mod query;
 mod range_map;
@@ -95,6 +104,7 @@ pub mod sampler;
 pub mod shader;
 pub mod swapchain;
 pub mod sync;
+pub mod video;
 
 /// Represents memory size and offset values on a Vulkan device.
 /// Analogous to the Rust `usize` type on the host.
@@ -107,6 +117,10 @@ unsafe impl<T: ?Sized> SafeDeref for Arc<T> {}
 unsafe impl<T: ?Sized> SafeDeref for Box<T> {}
 
 /// Gives access to the internal identifier of an object.
+///
+/// This is implemented by every type that wraps a Vulkan object, including the high-level buffer,
+/// image, descriptor set and command buffer types, so that the raw handle can be passed to other
+/// libraries and debugging tools.
 pub unsafe trait VulkanObject {
     /// The type of the object.
     type Object: ash::vk::Handle;
@@ -172,6 +186,7 @@ enum Success {
     EventReset = ash::vk::Result::EVENT_RESET.as_raw(),
     Incomplete = ash::vk::Result::INCOMPLETE.as_raw(),
     Suboptimal = ash::vk::Result::SUBOPTIMAL_KHR.as_raw(),
+    PipelineCompileRequired = ash::vk::Result::PIPELINE_COMPILE_REQUIRED.as_raw(),
 }
 
 /// All possible errors returned by any Vulkan function.
@@ -226,6 +241,7 @@ fn check_errors(result: ash::vk::Result) -> Result<Success, Error> {
         ash::vk::Result::ERROR_SURFACE_LOST_KHR => Err(Error::SurfaceLost),
         ash::vk::Result::ERROR_NATIVE_WINDOW_IN_USE_KHR => Err(Error::NativeWindowInUse),
         ash::vk::Result::SUBOPTIMAL_KHR => Ok(Success::Suboptimal),
+        ash::vk::Result::PIPELINE_COMPILE_REQUIRED => Ok(Success::PipelineCompileRequired),
         ash::vk::Result::ERROR_OUT_OF_DATE_KHR => Err(Error::OutOfDate),
         ash::vk::Result::ERROR_INCOMPATIBLE_DISPLAY_KHR => Err(Error::IncompatibleDisplay),
         ash::vk::Result::ERROR_VALIDATION_FAILED_EXT => Err(Error::ValidationFailed),
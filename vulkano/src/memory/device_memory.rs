@@ -22,7 +22,7 @@ use std::{
     mem::MaybeUninit,
     ops::{BitOr, Range},
     ptr, slice,
-    sync::{Arc, Mutex},
+    sync::{atomic::Ordering, Arc, Mutex},
 };
 
 /// Represents memory that has been allocated from the device.
@@ -57,6 +57,7 @@ pub struct DeviceMemory {
     export_handle_types: ExternalMemoryHandleTypes,
 
     mapped: Mutex<bool>,
+    tracking_id: Option<u64>,
 }
 
 impl DeviceMemory {
@@ -67,9 +68,12 @@ impl DeviceMemory {
     ///
     /// # Panics
     ///
-    /// - Panics if `allocate_info.allocation_size` is 0.
+    /// - Panics if `allocate_info.allocation_size` is 0, unless the memory is exported as an
+    ///   Android hardware buffer from a dedicated image allocation.
     /// - Panics if `allocate_info.dedicated_allocation` is `Some` and the contained buffer or
     ///   image does not belong to `device`.
+    /// - Panics if `allocate_info.priority` is `Some` and the contained value is not between 0.0
+    ///   and 1.0 inclusive.
     pub fn allocate(
         device: Arc<Device>,
         mut allocate_info: MemoryAllocateInfo,
@@ -82,9 +86,12 @@ impl DeviceMemory {
             memory_type_index,
             dedicated_allocation,
             export_handle_types,
+            priority: _,
             _ne: _,
         } = allocate_info;
 
+        let tracking_id = Self::track(&device, allocation_size, memory_type_index);
+
         Ok(DeviceMemory {
             handle,
             device,
@@ -94,6 +101,7 @@ impl DeviceMemory {
             export_handle_types,
 
             mapped: Mutex::new(false),
+            tracking_id,
         })
     }
 
@@ -108,6 +116,8 @@ impl DeviceMemory {
     /// - Panics if `allocate_info.allocation_size` is 0.
     /// - Panics if `allocate_info.dedicated_allocation` is `Some` and the contained buffer or
     ///   image does not belong to `device`.
+    /// - Panics if `allocate_info.priority` is `Some` and the contained value is not between 0.0
+    ///   and 1.0 inclusive.
     pub unsafe fn import(
         device: Arc<Device>,
         mut allocate_info: MemoryAllocateInfo,
@@ -121,9 +131,12 @@ impl DeviceMemory {
             memory_type_index,
             dedicated_allocation,
             export_handle_types,
+            priority: _,
             _ne: _,
         } = allocate_info;
 
+        let tracking_id = Self::track(&device, allocation_size, memory_type_index);
+
         Ok(DeviceMemory {
             handle,
             device,
@@ -133,6 +146,7 @@ impl DeviceMemory {
             export_handle_types,
 
             mapped: Mutex::new(false),
+            tracking_id,
         })
     }
 
@@ -146,6 +160,7 @@ impl DeviceMemory {
             memory_type_index,
             ref mut dedicated_allocation,
             export_handle_types,
+            priority,
             _ne: _,
         } = allocate_info;
 
@@ -173,8 +188,31 @@ impl DeviceMemory {
             });
         }
 
-        // VUID-VkMemoryAllocateInfo-pNext-01874
-        assert!(allocation_size != 0);
+        let is_android_hardware_buffer_import = matches!(
+            import_info.as_deref(),
+            Some(MemoryImportInfo::AndroidHardwareBuffer { .. })
+        );
+
+        // An image that is exported to an Android hardware buffer gets its memory size from the
+        // implementation, and its memory requirements can't be queried before it is bound.
+        let is_android_hardware_buffer_image_export = !is_android_hardware_buffer_import
+            && export_handle_types.android_hardware_buffer
+            && matches!(dedicated_allocation, Some(DedicatedAllocation::Image(_)));
+
+        if is_android_hardware_buffer_image_export {
+            // VUID-VkMemoryAllocateInfo-pNext-01874
+            if allocation_size != 0 {
+                return Err(
+                    DeviceMemoryAllocationError::DedicatedAllocationSizeMismatch {
+                        allocation_size,
+                        required_size: 0,
+                    },
+                );
+            }
+        } else {
+            // VUID-VkMemoryAllocateInfo-allocationSize-07899
+            assert!(allocation_size != 0);
+        }
 
         // VUID-vkAllocateMemory-pAllocateInfo-01713
         let heap_size = memory_type.heap().size();
@@ -185,6 +223,18 @@ impl DeviceMemory {
             });
         }
 
+        if let Some(priority) = priority {
+            if !device.enabled_features().memory_priority {
+                return Err(DeviceMemoryAllocationError::FeatureNotEnabled {
+                    feature: "memory_priority",
+                    reason: "`priority` was `Some`",
+                });
+            }
+
+            // VUID-VkMemoryPriorityAllocateInfoEXT-priority-02602
+            assert!((0.0..=1.0).contains(&priority));
+        }
+
         if let Some(dedicated_allocation) = dedicated_allocation {
             match dedicated_allocation {
                 DedicatedAllocation::Buffer(buffer) => {
@@ -194,7 +244,7 @@ impl DeviceMemory {
                     let required_size = buffer.memory_requirements().size;
 
                     // VUID-VkMemoryDedicatedAllocateInfo-buffer-02965
-                    if allocation_size != required_size {
+                    if !is_android_hardware_buffer_import && allocation_size != required_size {
                         return Err(
                             DeviceMemoryAllocationError::DedicatedAllocationSizeMismatch {
                                 allocation_size,
@@ -207,16 +257,20 @@ impl DeviceMemory {
                     // VUID-VkMemoryDedicatedAllocateInfo-commonparent
                     assert_eq!(device, image.device().as_ref());
 
-                    let required_size = image.memory_requirements().size;
-
                     // VUID-VkMemoryDedicatedAllocateInfo-image-02964
-                    if allocation_size != required_size {
-                        return Err(
-                            DeviceMemoryAllocationError::DedicatedAllocationSizeMismatch {
-                                allocation_size,
-                                required_size,
-                            },
-                        );
+                    if !(is_android_hardware_buffer_import
+                        || is_android_hardware_buffer_image_export)
+                    {
+                        let required_size = image.memory_requirements().size;
+
+                        if allocation_size != required_size {
+                            return Err(
+                                DeviceMemoryAllocationError::DedicatedAllocationSizeMismatch {
+                                    allocation_size,
+                                    required_size,
+                                },
+                            );
+                        }
                     }
                 }
             }
@@ -242,6 +296,26 @@ impl DeviceMemory {
             });
         }
 
+        if (export_handle_types.opaque_win32 || export_handle_types.opaque_win32_kmt)
+            && !device.enabled_extensions().khr_external_memory_win32
+        {
+            return Err(DeviceMemoryAllocationError::ExtensionNotEnabled {
+                extension: "khr_external_memory_win32",
+                reason: "`export_handle_types.opaque_win32` or `export_handle_types.opaque_win32_kmt` was set",
+            });
+        }
+
+        if export_handle_types.android_hardware_buffer
+            && !device
+                .enabled_extensions()
+                .android_external_memory_android_hardware_buffer
+        {
+            return Err(DeviceMemoryAllocationError::ExtensionNotEnabled {
+                extension: "android_external_memory_android_hardware_buffer",
+                reason: "`export_handle_types.android_hardware_buffer` was set",
+            });
+        }
+
         if let Some(import_info) = import_info {
             match import_info {
                 &mut MemoryImportInfo::Fd {
@@ -295,6 +369,54 @@ impl DeviceMemory {
                         // Can't validate, must be ensured by user
                     }
                 }
+                &mut MemoryImportInfo::Win32 {
+                    handle_type,
+                    handle: _,
+                } => {
+                    if !device.enabled_extensions().khr_external_memory_win32 {
+                        return Err(DeviceMemoryAllocationError::ExtensionNotEnabled {
+                            extension: "khr_external_memory_win32",
+                            reason: "`import_info` was `MemoryImportInfo::Win32`",
+                        });
+                    }
+
+                    // VUID-VkImportMemoryWin32HandleInfoKHR-handleType-00658
+                    if !matches!(
+                        handle_type,
+                        ExternalMemoryHandleType::OpaqueWin32
+                            | ExternalMemoryHandleType::OpaqueWin32Kmt
+                    ) {
+                        return Err(
+                            DeviceMemoryAllocationError::ImportWin32HandleTypeNotSupported {
+                                handle_type,
+                            },
+                        );
+                    }
+
+                    // VUID-VkMemoryAllocateInfo-allocationSize-01743
+                    // Can't validate, must be ensured by user
+
+                    // VUID-VkImportMemoryWin32HandleInfoKHR-handle-00660
+                    // Can't validate, must be ensured by user
+                }
+                &mut MemoryImportInfo::AndroidHardwareBuffer { buffer: _ } => {
+                    if !device
+                        .enabled_extensions()
+                        .android_external_memory_android_hardware_buffer
+                    {
+                        return Err(DeviceMemoryAllocationError::ExtensionNotEnabled {
+                            extension: "android_external_memory_android_hardware_buffer",
+                            reason: "`import_info` was `MemoryImportInfo::AndroidHardwareBuffer`",
+                        });
+                    }
+
+                    // VUID-VkMemoryAllocateInfo-allocationSize-02383
+                    // VUID-VkMemoryAllocateInfo-memoryTypeIndex-02385
+                    // Can't validate, must be ensured by user
+
+                    // VUID-VkMemoryAllocateInfo-pNext-02384
+                    // Can't validate, must be ensured by user
+                }
             }
         }
 
@@ -311,6 +433,7 @@ impl DeviceMemory {
             memory_type_index,
             dedicated_allocation,
             export_handle_types,
+            priority,
             _ne: _,
         } = allocate_info;
 
@@ -351,6 +474,47 @@ impl DeviceMemory {
             allocate_info = allocate_info.push_next(info);
         }
 
+        let mut priority_allocate_info =
+            priority.map(|priority| ash::vk::MemoryPriorityAllocateInfoEXT {
+                priority,
+                ..Default::default()
+            });
+
+        if let Some(info) = priority_allocate_info.as_mut() {
+            allocate_info = allocate_info.push_next(info);
+        }
+
+        let mut import_win32_handle_info = match import_info {
+            Some(MemoryImportInfo::Win32 {
+                handle_type,
+                handle,
+            }) => Some(ash::vk::ImportMemoryWin32HandleInfoKHR {
+                handle_type: handle_type.into(),
+                handle,
+                ..Default::default()
+            }),
+            _ => None,
+        };
+
+        if let Some(info) = import_win32_handle_info.as_mut() {
+            allocate_info = allocate_info.push_next(info);
+        }
+
+        let mut import_android_hardware_buffer_info = match import_info {
+            Some(MemoryImportInfo::AndroidHardwareBuffer { buffer }) => {
+                Some(ash::vk::ImportAndroidHardwareBufferInfoANDROID {
+                    buffer: buffer as *mut _,
+                    ..Default::default()
+                })
+            }
+            _ => None,
+        };
+
+        if let Some(info) = import_android_hardware_buffer_info.as_mut() {
+            allocate_info = allocate_info.push_next(info);
+        }
+
+        // Must come last, because it moves `file` out of `import_info`.
         #[cfg(unix)]
         let mut import_fd_info = match import_info {
             Some(MemoryImportInfo::Fd { handle_type, file }) => {
@@ -383,6 +547,23 @@ impl DeviceMemory {
             return Err(DeviceMemoryAllocationError::TooManyObjects);
         }
 
+        let heap_index = device
+            .physical_device()
+            .memory_type_by_id(memory_type_index)
+            .unwrap()
+            .heap()
+            .id() as usize;
+        #[cfg(feature = "test-support")]
+        let mut simulated_heap_limits = device.simulated_heap_limits().lock().unwrap();
+
+        // The simulated limit behaves like a real device that is out of memory.
+        #[cfg(feature = "test-support")]
+        if let Some(limit) = simulated_heap_limits.limits[heap_index] {
+            if simulated_heap_limits.usage[heap_index] + allocation_size > limit {
+                return Err(OomError::OutOfDeviceMemory.into());
+            }
+        }
+
         let handle = {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -397,9 +578,30 @@ impl DeviceMemory {
 
         *allocation_count += 1;
 
+        device.allocated_memory_counters()[heap_index]
+            .fetch_add(allocation_size, Ordering::Relaxed);
+
+        #[cfg(feature = "test-support")]
+        {
+            simulated_heap_limits.usage[heap_index] += allocation_size;
+        }
+
         Ok(handle)
     }
 
+    // Records the allocation in the allocation tracker of the device, if it has one.
+    fn track(device: &Device, allocation_size: DeviceSize, memory_type_index: u32) -> Option<u64> {
+        device.allocation_tracker().map(|tracker| {
+            let heap_index = device
+                .physical_device()
+                .memory_type_by_id(memory_type_index)
+                .unwrap()
+                .heap()
+                .id();
+            tracker.record(allocation_size, heap_index, memory_type_index)
+        })
+    }
+
     /// Returns the memory type that this memory was allocated from.
     #[inline]
     pub fn memory_type(&self) -> MemoryType {
@@ -410,11 +612,44 @@ impl DeviceMemory {
     }
 
     /// Returns the size in bytes of the memory allocation.
+    ///
+    /// For memory that was allocated to be exported as an Android hardware buffer from a
+    /// dedicated image allocation, this is 0, because the size is chosen by the implementation.
     #[inline]
     pub fn allocation_size(&self) -> DeviceSize {
         self.allocation_size
     }
 
+    /// Changes the priority of the memory relative to other allocations, after it was allocated.
+    ///
+    /// This can be used to lower the priority of resources that are not needed for a while, so
+    /// that the implementation moves them out of device-local memory first when it runs out of
+    /// it, without having to free and reallocate them.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the
+    ///   [`pageable_device_local_memory`](crate::device::Features::pageable_device_local_memory)
+    ///   feature is not enabled on the device.
+    /// - Panics if `priority` is not between 0.0 and 1.0 inclusive.
+    #[inline]
+    pub fn set_priority(&self, priority: f32) {
+        assert!(self.device.enabled_features().pageable_device_local_memory);
+
+        // VUID-vkSetDeviceMemoryPriorityEXT-priority-06258
+        assert!((0.0..=1.0).contains(&priority));
+
+        unsafe {
+            let fns = self.device.fns();
+            (fns.ext_pageable_device_local_memory
+                .set_device_memory_priority_ext)(
+                self.device.internal_object(),
+                self.handle,
+                priority,
+            );
+        }
+    }
+
     /// Exports the device memory into a Unix file descriptor. The caller owns the returned `File`.
     ///
     /// # Panic
@@ -470,6 +705,93 @@ impl DeviceMemory {
             Ok(file)
         }
     }
+
+    /// Exports the device memory into a Win32 handle.
+    ///
+    /// For [`ExternalMemoryHandleType::OpaqueWin32`], the caller owns the returned handle, and
+    /// must close it with `CloseHandle` when it's no longer needed.
+    #[inline]
+    pub fn export_win32_handle(
+        &self,
+        handle_type: ExternalMemoryHandleType,
+    ) -> Result<*mut c_void, DeviceMemoryExportError> {
+        // VUID-VkMemoryGetWin32HandleInfoKHR-handleType-00662
+        if !matches!(
+            handle_type,
+            ExternalMemoryHandleType::OpaqueWin32 | ExternalMemoryHandleType::OpaqueWin32Kmt
+        ) {
+            return Err(DeviceMemoryExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        // VUID-VkMemoryGetWin32HandleInfoKHR-handleType-00662
+        if !ash::vk::ExternalMemoryHandleTypeFlags::from(self.export_handle_types)
+            .intersects(ash::vk::ExternalMemoryHandleTypeFlags::from(handle_type))
+        {
+            return Err(DeviceMemoryExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        debug_assert!(self.device().enabled_extensions().khr_external_memory_win32);
+
+        let handle = unsafe {
+            let fns = self.device.fns();
+            let info = ash::vk::MemoryGetWin32HandleInfoKHR {
+                memory: self.handle,
+                handle_type: handle_type.into(),
+                ..Default::default()
+            };
+
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns.khr_external_memory_win32.get_memory_win32_handle_khr)(
+                self.device.internal_object(),
+                &info,
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
+        };
+
+        Ok(handle)
+    }
+
+    /// Exports the device memory into an Android hardware buffer, and returns a pointer to the
+    /// `AHardwareBuffer`. The caller owns a reference to the returned buffer, and must release it
+    /// with `AHardwareBuffer_release` when it's no longer needed.
+    ///
+    /// If the memory was allocated for a dedicated image, the image must already be bound to it.
+    #[inline]
+    pub fn export_android_hardware_buffer(&self) -> Result<*mut c_void, DeviceMemoryExportError> {
+        let handle_type = ExternalMemoryHandleType::AndroidHardwareBuffer;
+
+        // VUID-VkMemoryGetAndroidHardwareBufferInfoANDROID-handleTypes-01882
+        if !self.export_handle_types.android_hardware_buffer {
+            return Err(DeviceMemoryExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        debug_assert!(
+            self.device()
+                .enabled_extensions()
+                .android_external_memory_android_hardware_buffer
+        );
+
+        let buffer = unsafe {
+            let fns = self.device.fns();
+            let info = ash::vk::MemoryGetAndroidHardwareBufferInfoANDROID {
+                memory: self.handle,
+                ..Default::default()
+            };
+
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns
+                .android_external_memory_android_hardware_buffer
+                .get_memory_android_hardware_buffer_android)(
+                self.device.internal_object(),
+                &info,
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
+        };
+
+        Ok(buffer as *mut c_void)
+    }
 }
 
 impl Drop for DeviceMemory {
@@ -485,6 +807,22 @@ impl Drop for DeviceMemory {
                 .expect("Poisoned mutex");
             *allocation_count -= 1;
         }
+
+        let heap_index = self.memory_type().heap().id() as usize;
+        self.device.allocated_memory_counters()[heap_index]
+            .fetch_sub(self.allocation_size, Ordering::Relaxed);
+
+        if let (Some(tracker), Some(tracking_id)) =
+            (self.device.allocation_tracker(), self.tracking_id)
+        {
+            tracker.release(tracking_id);
+        }
+
+        #[cfg(feature = "test-support")]
+        {
+            let mut simulated_heap_limits = self.device.simulated_heap_limits().lock().unwrap();
+            simulated_heap_limits.usage[heap_index] -= self.allocation_size;
+        }
     }
 }
 
@@ -554,6 +892,22 @@ pub enum DeviceMemoryAllocationError {
         handle_type: ExternalMemoryHandleType,
     },
 
+    /// The provided `MemoryImportInfo::Win32::handle_type` is not supported for Win32 handles.
+    ImportWin32HandleTypeNotSupported {
+        handle_type: ExternalMemoryHandleType,
+    },
+
+    /// A memory pool could not allocate more memory, because it would exceed the budget that was
+    /// set for the memory heap.
+    HeapBudgetExceeded { heap_index: u32, budget: DeviceSize },
+
+    /// A buffer pool could not grow, because it would exceed the maximum capacity that was set
+    /// for it.
+    PoolCapacityExceeded {
+        required_capacity: DeviceSize,
+        max_capacity: DeviceSize,
+    },
+
     /// The provided `allocation_size` was greater than the memory type's heap size.
     MemoryTypeHeapSizeExceeded {
         allocation_size: DeviceSize,
@@ -614,6 +968,21 @@ impl fmt::Display for DeviceMemoryAllocationError {
                 "the provided `MemoryImportInfo::Fd::handle_type` ({:?}) is not supported for file descriptors",
                 handle_type,
             ),
+            Self::ImportWin32HandleTypeNotSupported { handle_type } => write!(
+                fmt,
+                "the provided `MemoryImportInfo::Win32::handle_type` ({:?}) is not supported for Win32 handles",
+                handle_type,
+            ),
+            Self::HeapBudgetExceeded { heap_index, budget } => write!(
+                fmt,
+                "allocating more memory from heap {} would exceed its budget of {} bytes",
+                heap_index, budget,
+            ),
+            Self::PoolCapacityExceeded { required_capacity, max_capacity } => write!(
+                fmt,
+                "the pool would need a capacity of {} elements, which exceeds its maximum capacity of {}",
+                required_capacity, max_capacity,
+            ),
             Self::MemoryTypeHeapSizeExceeded { allocation_size, heap_size } => write!(
                 fmt,
                 "the provided `allocation_size` ({}) was greater than the memory type's heap size ({})",
@@ -665,6 +1034,9 @@ impl From<MemoryMapError> for DeviceMemoryAllocationError {
 pub struct MemoryAllocateInfo<'d> {
     /// The number of bytes to allocate.
     ///
+    /// When the memory is exported as an Android hardware buffer from a dedicated image
+    /// allocation, this must be 0, and the size is chosen by the implementation instead.
+    ///
     /// The default value is `0`, which must be overridden.
     pub allocation_size: DeviceSize,
 
@@ -684,6 +1056,17 @@ pub struct MemoryAllocateInfo<'d> {
     /// The handle types that can be exported from the allocated memory.
     pub export_handle_types: ExternalMemoryHandleTypes,
 
+    /// The priority of the memory relative to other allocations, between 0.0 and 1.0. When the
+    /// device runs out of memory, the implementation moves memory with a lower priority to
+    /// system memory first.
+    ///
+    /// If set to `Some`, the
+    /// [`memory_priority`](crate::device::Features::memory_priority) feature must be enabled on
+    /// the device. If `None`, the implementation uses a priority of 0.5.
+    ///
+    /// The default value is `None`.
+    pub priority: Option<f32>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -695,6 +1078,7 @@ impl Default for MemoryAllocateInfo<'static> {
             memory_type_index: u32::MAX,
             dedicated_allocation: None,
             export_handle_types: ExternalMemoryHandleTypes::none(),
+            priority: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -708,6 +1092,7 @@ impl<'d> MemoryAllocateInfo<'d> {
             memory_type_index: u32::MAX,
             dedicated_allocation: Some(dedicated_allocation),
             export_handle_types: ExternalMemoryHandleTypes::none(),
+            priority: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -741,6 +1126,46 @@ pub enum MemoryImportInfo {
         handle_type: ExternalMemoryHandleType,
         file: File,
     },
+
+    /// Import memory from a Windows handle.
+    ///
+    /// `handle_type` must be either [`ExternalMemoryHandleType::OpaqueWin32`] or
+    /// [`ExternalMemoryHandleType::OpaqueWin32Kmt`].
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid Windows handle, exported from a memory allocation by the Vulkan
+    ///   API, with the same `handle_type`.
+    /// - Unlike file descriptors, Vulkan does not take ownership of `handle`. A
+    ///   [`ExternalMemoryHandleType::OpaqueWin32`] handle must still be closed with
+    ///   `CloseHandle` once it's no longer needed.
+    /// - [`MemoryAllocateInfo::allocation_size`] and [`MemoryAllocateInfo::memory_type_index`]
+    ///   must match those of the original memory allocation.
+    /// - If the original memory allocation used [`MemoryAllocateInfo::dedicated_allocation`],
+    ///   the imported one must also use it, and the associated buffer or image must be defined
+    ///   identically to the original.
+    Win32 {
+        handle_type: ExternalMemoryHandleType,
+        handle: *mut c_void,
+    },
+
+    /// Import memory from an Android hardware buffer, as
+    /// [`ExternalMemoryHandleType::AndroidHardwareBuffer`].
+    ///
+    /// # Safety
+    ///
+    /// - `buffer` must be a valid pointer to an `AHardwareBuffer`, which was allocated with at
+    ///   least one of the `AHARDWAREBUFFER_USAGE_GPU_*` usage flags. Vulkan acquires its own
+    ///   reference to the buffer, so the caller keeps its reference.
+    /// - [`MemoryAllocateInfo::allocation_size`] must be the `allocation_size`, and
+    ///   [`MemoryAllocateInfo::memory_type_index`] must be one of the memory types in the
+    ///   `memory_type_bits`, returned by [`Device::android_hardware_buffer_properties`].
+    /// - If the buffer does not have the `AHARDWAREBUFFER_FORMAT_BLOB` format,
+    ///   [`MemoryAllocateInfo::dedicated_allocation`] must be an image whose parameters are
+    ///   compatible with the buffer.
+    ///
+    /// [`Device::android_hardware_buffer_properties`]: crate::device::Device::android_hardware_buffer_properties
+    AndroidHardwareBuffer { buffer: *mut c_void },
 }
 
 /// Describes a handle type used for Vulkan external memory apis.  This is **not** just a
@@ -969,6 +1394,13 @@ impl From<ash::vk::ExternalMemoryHandleTypeFlags> for ExternalMemoryHandleTypes
     }
 }
 
+impl From<ExternalMemoryHandleType> for ExternalMemoryHandleTypes {
+    #[inline]
+    fn from(val: ExternalMemoryHandleType) -> Self {
+        ash::vk::ExternalMemoryHandleTypeFlags::from(val).into()
+    }
+}
+
 impl BitOr for ExternalMemoryHandleTypes {
     type Output = Self;
 
@@ -1189,6 +1621,44 @@ impl MappedDeviceMemory {
         self.memory
     }
 
+    /// Returns whether the mapped memory is host-coherent.
+    ///
+    /// If it is not, writes by the host must be made visible to the device with `flush_range`,
+    /// and writes by the device must be made visible to the host with `invalidate_range`.
+    #[inline]
+    pub fn is_coherent(&self) -> bool {
+        self.coherent
+    }
+
+    /// Returns the range of the memory that is mapped, in bytes relative to the start of the
+    /// memory allocation.
+    #[inline]
+    pub fn range(&self) -> Range<DeviceSize> {
+        self.range.clone()
+    }
+
+    /// Returns the smallest range containing `range` that can be given to `invalidate_range` and
+    /// `flush_range`.
+    ///
+    /// If the mapped memory is not host-coherent, the start and end of `range` are rounded to a
+    /// multiple of the [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size)
+    /// device property, and then clamped to the mapped range. Otherwise, `range` is returned
+    /// unchanged.
+    ///
+    /// Keep in mind that flushing or invalidating the returned range also affects the bytes
+    /// around `range`, which must not be in use by anything else at that time.
+    #[inline]
+    pub fn align_range(&self, range: Range<DeviceSize>) -> Range<DeviceSize> {
+        if self.coherent {
+            return range;
+        }
+
+        let start = range.start / self.atom_size * self.atom_size;
+        let end = (range.end + self.atom_size - 1) / self.atom_size * self.atom_size;
+
+        start.max(self.range.start)..end.min(self.range.end)
+    }
+
     /// Invalidates the host (CPU) cache for a range of mapped memory.
     ///
     /// If the mapped memory is not host-coherent, you must call this function before the memory is
@@ -1199,7 +1669,8 @@ impl MappedDeviceMemory {
     /// within the range of the memory mapping given to `new`. If the memory was not allocated
     /// from host-coherent memory, then the start and end of `range` must be a multiple of the
     /// [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size) device
-    /// property, but `range.end` can also equal the memory's `allocation_size`.
+    /// property, but `range.end` can also equal the memory's `allocation_size`. Use
+    /// [`align_range`](Self::align_range) to get such a range.
     ///
     /// # Safety
     ///
@@ -1246,7 +1717,8 @@ impl MappedDeviceMemory {
     /// within the range of the memory mapping given to `map`. If the memory was not allocated
     /// from host-coherent memory, then the start and end of `range` must be a multiple of the
     /// [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size) device
-    /// property, but `range.end` can also equal the memory's `allocation_size`.
+    /// property, but `range.end` can also equal the memory's `allocation_size`. Use
+    /// [`align_range`](Self::align_range) to get such a range.
     ///
     /// # Safety
     ///
@@ -1475,7 +1947,7 @@ impl From<OomError> for MemoryMapError {
 
 #[cfg(test)]
 mod tests {
-    use super::MemoryAllocateInfo;
+    use super::{ExternalMemoryHandleTypes, MemoryAllocateInfo, MemoryImportInfo};
     use crate::memory::DeviceMemory;
     use crate::memory::DeviceMemoryAllocationError;
     use crate::OomError;
@@ -1512,6 +1984,51 @@ mod tests {
         });
     }
 
+    #[test]
+    fn android_hardware_buffer_extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+
+        match DeviceMemory::allocate(
+            device.clone(),
+            MemoryAllocateInfo {
+                allocation_size: 256,
+                memory_type_index: memory_type.id(),
+                export_handle_types: ExternalMemoryHandleTypes {
+                    android_hardware_buffer: true,
+                    ..ExternalMemoryHandleTypes::none()
+                },
+                ..Default::default()
+            },
+        ) {
+            Err(DeviceMemoryAllocationError::ExtensionNotEnabled {
+                extension: "android_external_memory_android_hardware_buffer",
+                ..
+            }) => (),
+            _ => panic!(),
+        }
+
+        match unsafe {
+            DeviceMemory::import(
+                device.clone(),
+                MemoryAllocateInfo {
+                    allocation_size: 256,
+                    memory_type_index: memory_type.id(),
+                    ..Default::default()
+                },
+                MemoryImportInfo::AndroidHardwareBuffer {
+                    buffer: std::ptr::null_mut(),
+                },
+            )
+        } {
+            Err(DeviceMemoryAllocationError::ExtensionNotEnabled {
+                extension: "android_external_memory_android_hardware_buffer",
+                ..
+            }) => (),
+            _ => panic!(),
+        }
+    }
+
     #[test]
     #[cfg(target_pointer_width = "64")]
     fn oom_single() {
@@ -1568,6 +2085,41 @@ mod tests {
         panic!()
     }
 
+    #[test]
+    #[cfg(feature = "test-support")]
+    fn oom_simulated() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+        let heap_index = memory_type.heap().id();
+        device.set_simulated_heap_limit(heap_index, Some(1024));
+
+        let allocate = |allocation_size| {
+            DeviceMemory::allocate(
+                device.clone(),
+                MemoryAllocateInfo {
+                    allocation_size,
+                    memory_type_index: memory_type.id(),
+                    ..Default::default()
+                },
+            )
+        };
+
+        let mem1 = allocate(768).unwrap();
+        assert_eq!(device.simulated_heap_usage(heap_index), 768);
+        match allocate(512) {
+            Err(DeviceMemoryAllocationError::OomError(OomError::OutOfDeviceMemory)) => (),
+            _ => panic!(),
+        }
+
+        let _mem2 = allocate(256).unwrap();
+        drop(mem1);
+        assert_eq!(device.simulated_heap_usage(heap_index), 256);
+        let _mem3 = allocate(512).unwrap();
+
+        device.set_simulated_heap_limit(heap_index, None);
+        let _mem4 = allocate(4096).unwrap();
+    }
+
     #[test]
     fn allocation_count() {
         let (device, _) = gfx_dev_and_queue!();
@@ -1597,4 +2149,48 @@ mod tests {
         }
         assert_eq!(*device.allocation_count().lock().unwrap(), 1);
     }
+
+    #[test]
+    fn priority_feature_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+
+        match DeviceMemory::allocate(
+            device.clone(),
+            MemoryAllocateInfo {
+                allocation_size: 256,
+                memory_type_index: memory_type.id(),
+                priority: Some(1.0),
+                ..Default::default()
+            },
+        ) {
+            Err(DeviceMemoryAllocationError::FeatureNotEnabled {
+                feature: "memory_priority",
+                ..
+            }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn allocated_memory() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+        let heap_index = memory_type.heap().id();
+        assert_eq!(device.allocated_memory(heap_index), 0);
+
+        let mem = DeviceMemory::allocate(
+            device.clone(),
+            MemoryAllocateInfo {
+                allocation_size: 256,
+                memory_type_index: memory_type.id(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(device.allocated_memory(heap_index), 256);
+
+        drop(mem);
+        assert_eq!(device.allocated_memory(heap_index), 0);
+    }
 }
@@ -104,6 +104,8 @@ use crate::{buffer::sys::UnsafeBuffer, image::sys::UnsafeImage, DeviceSize};
 
 mod device_memory;
 pub mod pool;
+pub mod tracker;
+pub mod transient;
 
 /// Represents requirements expressed by the Vulkan implementation when it comes to binding memory
 /// to a resource.
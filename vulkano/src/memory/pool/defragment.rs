@@ -0,0 +1,475 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Incremental defragmentation of the blocks of a `StdMemoryPool`.
+//!
+//! Vulkan resources can't be moved to other memory once they are bound, so the pool can't move
+//! allocations by itself. Instead, a defragmentation pass picks the allocations that should move
+//! out of the blocks that are the least used, and reserves a new place for each of them in the
+//! blocks that are used the most. The caller then creates new resources at the new places, copies
+//! the contents over, and replaces the old resources with the new ones once the copies have
+//! completed. Dropping the old resources frees their allocations, and the blocks that are left
+//! empty can then be released with `StdMemoryPool::trim`.
+
+use super::tlsf::{Tlsf, TlsfAlloc};
+use super::MemoryPoolAlloc;
+use super::StdMemoryPoolAlloc;
+use crate::buffer::sys::{UnsafeBuffer, UnsafeBufferCreateInfo};
+use crate::buffer::{BufferAccess, BufferCreationError, BufferInner, BufferUsage};
+use crate::command_buffer::{BufferCopy, CopyBufferInfo};
+use crate::device::{Device, DeviceOwned};
+use crate::memory::{DeviceMemory, MappedDeviceMemory};
+use crate::{DeviceSize, OomError, VulkanObject};
+use smallvec::smallvec;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+/// Parameters of a defragmentation pass of a `StdMemoryPool`.
+///
+/// The limits make it possible to spread the defragmentation over many frames, so that the
+/// copies don't take too much GPU time at once.
+#[derive(Clone, Debug)]
+pub struct DefragmentationInfo {
+    /// The maximum number of bytes that the pass may move.
+    ///
+    /// The default value is 64 MiB.
+    pub max_bytes: DeviceSize,
+
+    /// The maximum number of allocations that the pass may move.
+    ///
+    /// The default value is 256.
+    pub max_moves: u32,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for DefragmentationInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_bytes: 64 * 1024 * 1024,
+            max_moves: 256,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// An allocation that a defragmentation pass wants to move to another place.
+///
+/// The move holds the allocation at the new place. To perform the move, the caller:
+///
+/// 1. Finds the resource whose memory is being moved, with [`is_source`](Self::is_source).
+/// 2. Creates a new resource and binds it to the memory of [`dst`](Self::dst).
+/// 3. Records a copy of the contents, for example with [`copy_buffer_info`](Self::copy_buffer_info)
+///    for buffers, and submits it.
+/// 4. Once the copy has completed, uses the new resource instead of the old one and drops the
+///    old resource, which frees the old allocation.
+///
+/// If the resource doesn't exist anymore or shouldn't be moved, dropping the move gives the
+/// reserved memory back to the pool.
+#[derive(Debug)]
+pub struct DefragmentationMove {
+    pub(super) src_memory: BlockMemory,
+    pub(super) src_offset: DeviceSize,
+    pub(super) dst_memory: BlockMemory,
+    pub(super) dst: StdMemoryPoolAlloc,
+}
+
+impl DefragmentationMove {
+    /// Returns the memory that the allocation is moved out of.
+    #[inline]
+    pub fn src_memory(&self) -> &DeviceMemory {
+        self.src_memory.as_ref()
+    }
+
+    /// Returns the offset in `src_memory` of the allocation that is moved.
+    #[inline]
+    pub fn src_offset(&self) -> DeviceSize {
+        self.src_offset
+    }
+
+    /// Returns the size in bytes of the allocation that is moved.
+    #[inline]
+    pub fn size(&self) -> DeviceSize {
+        self.dst.size()
+    }
+
+    /// Returns true if `alloc` is the allocation that is moved.
+    #[inline]
+    pub fn is_source(&self, alloc: &impl MemoryPoolAlloc) -> bool {
+        alloc.memory().internal_object() == self.src_memory().internal_object()
+            && alloc.offset() == self.src_offset
+    }
+
+    /// Returns the allocation at the new place.
+    #[inline]
+    pub fn dst(&self) -> &StdMemoryPoolAlloc {
+        &self.dst
+    }
+
+    /// Consumes the move and returns the allocation at the new place, so that it can be held by
+    /// the new resource.
+    #[inline]
+    pub fn into_dst(self) -> StdMemoryPoolAlloc {
+        self.dst
+    }
+
+    /// Returns the parameters of a buffer copy command that copies the contents of the
+    /// allocation to the new place.
+    ///
+    /// The copy is done between two buffers that are created over the whole blocks of device
+    /// memory, so it can be recorded into any command buffer that supports transfer operations.
+    /// The contents are copied byte by byte, so this can only be used for allocations of buffers
+    /// and of images with linear tiling.
+    ///
+    /// # Safety
+    ///
+    /// - The old resource must not be written to by the device while the copy is executing, and
+    ///   any prior writes must be made visible to the copy.
+    /// - The new resource must not be accessed until the copy has completed.
+    pub unsafe fn copy_buffer_info(&self) -> Result<CopyBufferInfo, DefragmentationCopyError> {
+        let src_buffer = BlockBuffer::new(self.src_memory.clone(), BufferUsage::transfer_src())?;
+        let dst_buffer = BlockBuffer::new(self.dst_memory.clone(), BufferUsage::transfer_dst())?;
+
+        Ok(CopyBufferInfo {
+            regions: smallvec![BufferCopy {
+                src_offset: self.src_offset,
+                dst_offset: self.dst.offset(),
+                size: self.size(),
+                ..Default::default()
+            }],
+            ..CopyBufferInfo::buffers(src_buffer, dst_buffer)
+        })
+    }
+}
+
+/// Error that can happen when preparing the copy of a `DefragmentationMove`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DefragmentationCopyError {
+    /// Creating a buffer over the block of device memory failed.
+    BufferCreationError(BufferCreationError),
+
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The memory type of the block can't be used by transfer buffers, or the block is smaller
+    /// than a buffer of the same size requires.
+    MemoryNotSupported,
+}
+
+impl error::Error for DefragmentationCopyError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::BufferCreationError(ref err) => Some(err),
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DefragmentationCopyError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::BufferCreationError(_) => write!(fmt, "creating a buffer over the block failed"),
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::MemoryNotSupported => write!(
+                fmt,
+                "the block of device memory can't be used by a transfer buffer",
+            ),
+        }
+    }
+}
+
+impl From<BufferCreationError> for DefragmentationCopyError {
+    #[inline]
+    fn from(err: BufferCreationError) -> Self {
+        Self::BufferCreationError(err)
+    }
+}
+
+impl From<OomError> for DefragmentationCopyError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+// A block of device memory of one of the memory type pools.
+#[derive(Clone, Debug)]
+pub(super) enum BlockMemory {
+    NonHostVisible(Arc<DeviceMemory>),
+    HostVisible(Arc<MappedDeviceMemory>),
+}
+
+impl AsRef<DeviceMemory> for BlockMemory {
+    #[inline]
+    fn as_ref(&self) -> &DeviceMemory {
+        match self {
+            Self::NonHostVisible(memory) => memory,
+            Self::HostVisible(memory) => memory.as_ref(),
+        }
+    }
+}
+
+// A buffer that covers a whole block of device memory, used to copy between blocks.
+#[derive(Debug)]
+struct BlockBuffer {
+    inner: Arc<UnsafeBuffer>,
+    _memory: BlockMemory,
+}
+
+impl BlockBuffer {
+    unsafe fn new(
+        memory: BlockMemory,
+        usage: BufferUsage,
+    ) -> Result<Arc<BlockBuffer>, DefragmentationCopyError> {
+        let device_memory = memory.as_ref();
+        let inner = UnsafeBuffer::new(
+            device_memory.device().clone(),
+            UnsafeBufferCreateInfo {
+                size: device_memory.allocation_size(),
+                usage,
+                ..Default::default()
+            },
+        )?;
+
+        let requirements = inner.memory_requirements();
+
+        if requirements.memory_type_bits & (1 << device_memory.memory_type().id()) == 0
+            || requirements.size > device_memory.allocation_size()
+        {
+            return Err(DefragmentationCopyError::MemoryNotSupported);
+        }
+
+        inner.bind_memory(device_memory, 0)?;
+
+        Ok(Arc::new(BlockBuffer {
+            inner,
+            _memory: memory,
+        }))
+    }
+}
+
+unsafe impl DeviceOwned for BlockBuffer {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.inner.device()
+    }
+}
+
+unsafe impl BufferAccess for BlockBuffer {
+    #[inline]
+    fn inner(&self) -> BufferInner {
+        BufferInner {
+            buffer: &self.inner,
+            offset: 0,
+        }
+    }
+
+    #[inline]
+    fn size(&self) -> DeviceSize {
+        self.inner.size()
+    }
+}
+
+// A move that was planned by a memory type pool, with the allocation at the new place.
+#[derive(Debug)]
+pub(super) struct PoolMove<A> {
+    pub(super) src_memory: BlockMemory,
+    pub(super) src_offset: DeviceSize,
+    pub(super) dst_memory: BlockMemory,
+    pub(super) dst: A,
+}
+
+impl<A> PoolMove<A> {
+    pub(super) fn map_dst<B>(self, f: impl FnOnce(A) -> B) -> PoolMove<B> {
+        PoolMove {
+            src_memory: self.src_memory,
+            src_offset: self.src_offset,
+            dst_memory: self.dst_memory,
+            dst: f(self.dst),
+        }
+    }
+}
+
+// The limits that are left for the rest of a defragmentation pass.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct DefragmentationBudget {
+    pub(super) bytes: DeviceSize,
+    pub(super) moves: u32,
+}
+
+// A move that was planned inside the blocks of a memory type pool. The destination region is
+// already allocated in the TLSF of its block.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PlannedMove {
+    pub(super) src_block: usize,
+    pub(super) src: TlsfAlloc,
+    pub(super) size: DeviceSize,
+    pub(super) dst_block: usize,
+    pub(super) dst: TlsfAlloc,
+}
+
+// Plans the moves of a defragmentation pass over the blocks of a memory type pool.
+//
+// The blocks that hold the least data are emptied into the blocks that hold the most, one block
+// at a time. A block is only picked if all of its allocations fit into the fuller blocks, so that
+// it can be released once the moves are done. If the budget runs out in the middle of a block,
+// the rest of it is left for the next pass.
+pub(super) fn plan_moves(
+    blocks: &mut [&mut Tlsf],
+    budget: &mut DefragmentationBudget,
+) -> Vec<PlannedMove> {
+    let mut order: Vec<usize> = (0..blocks.len())
+        .filter(|&index| !blocks[index].is_empty())
+        .collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(blocks[index].allocated_size()));
+
+    let mut moves = Vec::new();
+    let mut is_dst = vec![false; blocks.len()];
+
+    for src_pos in (1..order.len()).rev() {
+        let src_block = order[src_pos];
+
+        // Don't move the allocations that were just placed in this block.
+        if is_dst[src_block] || budget.moves == 0 || budget.bytes == 0 {
+            break;
+        }
+
+        let allocations: Vec<_> = blocks[src_block].allocations().collect();
+        let first_move = moves.len();
+        let mut fits = true;
+
+        for (src, size, alignment) in allocations {
+            let dst = order[..src_pos].iter().find_map(|&dst_block| {
+                blocks[dst_block]
+                    .allocate(size, alignment)
+                    .map(|dst| (dst_block, dst))
+            });
+
+            match dst {
+                Some((dst_block, dst)) => moves.push(PlannedMove {
+                    src_block,
+                    src,
+                    size,
+                    dst_block,
+                    dst,
+                }),
+                None => {
+                    fits = false;
+                    break;
+                }
+            }
+        }
+
+        // Only keep the moves that are within the budget, and all of them only if the block can
+        // be emptied.
+        let planned_count = moves.len();
+        let mut keep = first_move;
+
+        if fits {
+            for planned in &moves[first_move..] {
+                if budget.moves == 0 || planned.size > budget.bytes {
+                    break;
+                }
+
+                budget.moves -= 1;
+                budget.bytes -= planned.size;
+                keep += 1;
+            }
+        }
+
+        for planned in moves.drain(keep..) {
+            blocks[planned.dst_block].free(planned.dst);
+        }
+
+        for planned in &moves[first_move..] {
+            is_dst[planned.dst_block] = true;
+        }
+
+        if !fits || keep < planned_count {
+            break;
+        }
+    }
+
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan_moves, DefragmentationBudget};
+    use crate::memory::pool::tlsf::Tlsf;
+
+    #[test]
+    fn empties_sparse_block() {
+        let mut full = Tlsf::new(1024);
+        let mut sparse = Tlsf::new(1024);
+
+        let _a = full.allocate(512, 16).unwrap();
+        let _b = sparse.allocate(64, 16).unwrap();
+        let _c = sparse.allocate(128, 64).unwrap();
+
+        let mut budget = DefragmentationBudget {
+            bytes: 1024,
+            moves: 16,
+        };
+        let moves = plan_moves(&mut [&mut full, &mut sparse], &mut budget);
+
+        assert_eq!(moves.len(), 2);
+        assert!(moves.iter().all(|m| m.src_block == 1 && m.dst_block == 0));
+        assert!(moves.iter().all(|m| m.dst.offset % 16 == 0));
+        assert_eq!(full.allocated_size(), 512 + 64 + 128);
+        assert_eq!(budget.moves, 14);
+        assert_eq!(budget.bytes, 1024 - 192);
+    }
+
+    #[test]
+    fn respects_budget() {
+        let mut full = Tlsf::new(1024);
+        let mut sparse = Tlsf::new(1024);
+
+        let _a = full.allocate(512, 16).unwrap();
+        let _b = sparse.allocate(64, 16).unwrap();
+        let _c = sparse.allocate(64, 16).unwrap();
+
+        let mut budget = DefragmentationBudget {
+            bytes: 1024,
+            moves: 1,
+        };
+        let moves = plan_moves(&mut [&mut full, &mut sparse], &mut budget);
+
+        // The reservation of the move that didn't fit in the budget is given back.
+        assert_eq!(moves.len(), 1);
+        assert_eq!(full.allocated_size(), 512 + 64);
+        assert_eq!(budget.moves, 0);
+    }
+
+    #[test]
+    fn block_that_does_not_fit() {
+        let mut full = Tlsf::new(1024);
+        let mut sparse = Tlsf::new(1024);
+
+        let _a = full.allocate(768, 16).unwrap();
+        let _b = sparse.allocate(128, 16).unwrap();
+        let _c = sparse.allocate(256, 16).unwrap();
+
+        let mut budget = DefragmentationBudget {
+            bytes: 1024,
+            moves: 16,
+        };
+        let moves = plan_moves(&mut [&mut full, &mut sparse], &mut budget);
+
+        assert!(moves.is_empty());
+        assert_eq!(full.allocated_size(), 768);
+        assert_eq!(budget.moves, 16);
+    }
+}
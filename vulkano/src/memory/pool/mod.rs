@@ -7,12 +7,17 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+pub use self::defragment::DefragmentationCopyError;
+pub use self::defragment::DefragmentationInfo;
+pub use self::defragment::DefragmentationMove;
 pub use self::host_visible::StdHostVisibleMemoryTypePool;
 pub use self::host_visible::StdHostVisibleMemoryTypePoolAlloc;
 pub use self::non_host_visible::StdNonHostVisibleMemoryTypePool;
 pub use self::non_host_visible::StdNonHostVisibleMemoryTypePoolAlloc;
 pub use self::pool::StdMemoryPool;
 pub use self::pool::StdMemoryPoolAlloc;
+pub use self::pool::StdMemoryPoolCreateInfo;
+use self::tlsf::Tlsf;
 use crate::device::physical::MemoryType;
 use crate::device::{Device, DeviceOwned};
 use crate::memory::device_memory::MemoryAllocateInfo;
@@ -23,16 +28,29 @@ use crate::memory::ExternalMemoryHandleTypes;
 use crate::memory::MappedDeviceMemory;
 use crate::memory::MemoryRequirements;
 use crate::DeviceSize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+mod defragment;
 mod host_visible;
 mod non_host_visible;
 mod pool;
+mod tlsf;
 
 // If the allocation size goes beyond this, then we perform a dedicated allocation which bypasses
 // the pool. This prevents the pool from overallocating a significant amount of memory.
 const MAX_POOL_ALLOC: DeviceSize = 256 * 1024 * 1024;
 
+// The largest block size that is used by default. Blocks are smaller than this for heaps of less
+// than 512 MiB, so that they don't use up a large portion of the heap.
+const MAX_DEFAULT_BLOCK_SIZE: DeviceSize = 64 * 1024 * 1024;
+
+// Returns the size of the blocks that are allocated by a memory type pool by default.
+fn default_block_size(memory_type: MemoryType) -> DeviceSize {
+    let heap_size = memory_type.heap().size();
+    (heap_size / 8).clamp(1, MAX_DEFAULT_BLOCK_SIZE)
+}
+
 fn choose_allocation_memory_type<'s, F>(
     device: &'s Arc<Device>,
     requirements: &MemoryRequirements,
@@ -84,16 +102,38 @@ where
     assert!(device.enabled_extensions().khr_external_memory_fd);
     assert!(device.enabled_extensions().khr_external_memory);
 
+    alloc_dedicated_with_export_handle_types(
+        device,
+        requirements,
+        layout,
+        map,
+        dedicated_allocation,
+        ExternalMemoryHandleTypes::posix(),
+        filter,
+    )
+}
+
+/// Allocate dedicated memory that can be exported with `export_handle_types`.
+/// Memory pool memory always exports the same handle, thus dedicated is preferred.
+pub(crate) fn alloc_dedicated_with_export_handle_types<F>(
+    device: Arc<Device>,
+    requirements: &MemoryRequirements,
+    _layout: AllocLayout,
+    map: MappingRequirement,
+    dedicated_allocation: DedicatedAllocation,
+    export_handle_types: ExternalMemoryHandleTypes,
+    filter: F,
+) -> Result<PotentialDedicatedAllocation<StdMemoryPoolAlloc>, DeviceMemoryAllocationError>
+where
+    F: FnMut(MemoryType) -> AllocFromRequirementsFilter,
+{
     let memory_type = choose_allocation_memory_type(&device, requirements, filter, map);
     let memory = DeviceMemory::allocate(
         device.clone(),
         MemoryAllocateInfo {
             allocation_size: requirements.size,
             memory_type_index: memory_type.id(),
-            export_handle_types: ExternalMemoryHandleTypes {
-                opaque_fd: true,
-                ..ExternalMemoryHandleTypes::none()
-            },
+            export_handle_types,
             ..MemoryAllocateInfo::dedicated_allocation(dedicated_allocation)
         },
     )?;
@@ -226,6 +266,170 @@ pub unsafe trait MemoryPool: DeviceOwned {
             MappingRequirement::DoNotMap => Ok(PotentialDedicatedAllocation::Dedicated(memory)),
         }
     }
+
+    /// Same as `alloc_from_requirements`, but the memory is given a priority relative to other
+    /// allocations, between 0.0 and 1.0. When the device runs out of memory, the implementation
+    /// moves memory with a lower priority to system memory first.
+    ///
+    /// The priority is only a hint. The default implementation ignores it, and calls
+    /// `alloc_from_requirements`.
+    ///
+    /// # Safety
+    ///
+    /// See `alloc_from_requirements`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `priority` is not between 0.0 and 1.0 inclusive.
+    /// - See `alloc_from_requirements`.
+    ///
+    fn alloc_from_requirements_with_priority<F>(
+        &self,
+        requirements: &MemoryRequirements,
+        layout: AllocLayout,
+        map: MappingRequirement,
+        dedicated_allocation: Option<DedicatedAllocation>,
+        priority: f32,
+        filter: F,
+    ) -> Result<PotentialDedicatedAllocation<Self::Alloc>, DeviceMemoryAllocationError>
+    where
+        F: FnMut(MemoryType) -> AllocFromRequirementsFilter,
+    {
+        assert!((0.0..=1.0).contains(&priority));
+        self.alloc_from_requirements(requirements, layout, map, dedicated_allocation, filter)
+    }
+}
+
+/// Statistics about the memory that is used by a memory pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryPoolStatistics {
+    /// The number of blocks of device memory that the pool has allocated. This includes dedicated
+    /// allocations.
+    pub block_count: u32,
+
+    /// The total size in bytes of all blocks of device memory.
+    pub block_bytes: DeviceSize,
+
+    /// The number of allocations that the pool has handed out.
+    pub allocation_count: u32,
+
+    /// The total size in bytes of all allocations, not counting the padding inserted before an
+    /// allocation to align its offset. For memory types that are not host-coherent, the size of
+    /// each allocation is first rounded up to a multiple of the
+    /// [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size) device
+    /// property, so this can be larger than the sum of the requested sizes.
+    pub allocation_bytes: DeviceSize,
+
+    /// The number of blocks that currently hold no allocations. These can be released to the
+    /// driver with [`StdMemoryPool::trim`].
+    pub empty_block_count: u32,
+
+    /// The total size in bytes of the free space inside the blocks that allocations are
+    /// suballocated from.
+    pub free_bytes: DeviceSize,
+
+    /// The size in bytes of the largest contiguous free region inside a single block. An
+    /// allocation larger than this requires a new block to be allocated from the device.
+    pub largest_free_bytes: DeviceSize,
+}
+
+impl MemoryPoolStatistics {
+    // Adds a block that allocations are suballocated from.
+    fn add_block(&mut self, tlsf: &Tlsf) {
+        self.block_count += 1;
+        self.block_bytes += tlsf.size();
+        self.allocation_count += tlsf.allocation_count();
+        self.allocation_bytes += tlsf.allocated_size();
+        self.empty_block_count += tlsf.is_empty() as u32;
+        self.free_bytes += tlsf.size() - tlsf.allocated_size();
+        self.largest_free_bytes = self.largest_free_bytes.max(tlsf.largest_free_size());
+    }
+
+    // Adds a block that holds a single allocation.
+    fn add_dedicated(&mut self, size: DeviceSize) {
+        self.block_count += 1;
+        self.block_bytes += size;
+        self.allocation_count += 1;
+        self.allocation_bytes += size;
+    }
+
+    // Removes a block that holds a single allocation.
+    fn remove_dedicated(&mut self, size: DeviceSize) {
+        self.block_count -= 1;
+        self.block_bytes -= size;
+        self.allocation_count -= 1;
+        self.allocation_bytes -= size;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.block_count += other.block_count;
+        self.block_bytes += other.block_bytes;
+        self.allocation_count += other.allocation_count;
+        self.allocation_bytes += other.allocation_bytes;
+        self.empty_block_count += other.empty_block_count;
+        self.free_bytes += other.free_bytes;
+        self.largest_free_bytes = self.largest_free_bytes.max(other.largest_free_bytes);
+    }
+}
+
+// Keeps track of how much device memory a pool has allocated from each memory heap, and makes
+// sure that the budgets of the heaps are not exceeded.
+#[derive(Debug)]
+struct HeapUsage {
+    budgets: Vec<Option<DeviceSize>>,
+    used: Vec<AtomicU64>,
+}
+
+impl HeapUsage {
+    fn new(heap_count: usize, budgets: Vec<Option<DeviceSize>>) -> Self {
+        HeapUsage {
+            budgets,
+            used: (0..heap_count).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    // Records that `size` bytes are about to be allocated from the heap, or returns an error if
+    // that would exceed the budget.
+    fn reserve(
+        &self,
+        heap_index: u32,
+        size: DeviceSize,
+    ) -> Result<(), DeviceMemoryAllocationError> {
+        let used = &self.used[heap_index as usize];
+        let budget = match self.budgets.get(heap_index as usize).copied().flatten() {
+            Some(budget) => budget,
+            None => {
+                used.fetch_add(size, Ordering::Relaxed);
+                return Ok(());
+            }
+        };
+
+        used.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+            used.checked_add(size)
+                .filter(|&new_used| new_used <= budget)
+        })
+        .map(|_| ())
+        .map_err(|_| DeviceMemoryAllocationError::HeapBudgetExceeded { heap_index, budget })
+    }
+
+    // Records that `size` bytes were returned to the heap.
+    fn release(&self, heap_index: u32, size: DeviceSize) {
+        self.used[heap_index as usize].fetch_sub(size, Ordering::Relaxed);
+    }
+
+    fn used(&self, heap_index: u32) -> DeviceSize {
+        self.used[heap_index as usize].load(Ordering::Relaxed)
+    }
+
+    // Returns the number of bytes that can still be allocated from the heap without exceeding
+    // its budget, if it has one.
+    fn remaining(&self, heap_index: u32) -> Option<DeviceSize> {
+        self.budgets
+            .get(heap_index as usize)
+            .copied()
+            .flatten()
+            .map(|budget| budget.saturating_sub(self.used(heap_index)))
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -258,6 +462,72 @@ pub enum MappingRequirement {
     DoNotMap,
 }
 
+/// Which kind of host-visible memory an allocation should preferably come from.
+///
+/// This is only a preference: if the device has no memory type of the preferred kind, another
+/// host-visible memory type is used instead.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HostMemoryPreference {
+    /// Prefer memory that is cached on the host. Reads from the CPU are fast, which makes it the
+    /// best choice for reading back data written by the GPU.
+    ///
+    /// This kind of memory is often not host-coherent, which means that it must be invalidated
+    /// and flushed with a granularity of
+    /// [`non_coherent_atom_size`](crate::device::Properties::non_coherent_atom_size).
+    HostCached,
+
+    /// Prefer memory that is not cached on the host, which is usually write-combined. Sequential
+    /// writes from the CPU are fast but reads are slow, which makes it the best choice for
+    /// uploading data to the GPU.
+    WriteCombined,
+
+    /// Prefer memory that is local to the device, if the device has
+    /// [resizable BAR](crate::device::physical::PhysicalDevice::has_resizable_bar). Data that the
+    /// CPU writes often and the GPU reads, like dynamic uniform and vertex data, is then read by
+    /// the GPU directly from its own memory, without being copied through a staging buffer.
+    /// Reads from the CPU are very slow.
+    ///
+    /// If the device doesn't have resizable BAR, this is the same as `WriteCombined`.
+    DeviceLocal,
+}
+
+impl HostMemoryPreference {
+    /// Returns how a memory type should be treated by `alloc_from_requirements` to honor this
+    /// preference.
+    #[inline]
+    pub fn memory_type_filter(self, memory_type: MemoryType) -> AllocFromRequirementsFilter {
+        let preferred = match self {
+            HostMemoryPreference::HostCached => memory_type.is_host_cached(),
+            HostMemoryPreference::WriteCombined => !memory_type.is_host_cached(),
+            HostMemoryPreference::DeviceLocal => {
+                if memory_type.physical_device().has_resizable_bar() {
+                    memory_type.is_device_local()
+                } else {
+                    !memory_type.is_host_cached()
+                }
+            }
+        };
+
+        if preferred {
+            AllocFromRequirementsFilter::Preferred
+        } else {
+            AllocFromRequirementsFilter::Allowed
+        }
+    }
+}
+
+/// `true` converts to `HostCached` and `false` to `WriteCombined`.
+impl From<bool> for HostMemoryPreference {
+    #[inline]
+    fn from(host_cached: bool) -> Self {
+        if host_cached {
+            HostMemoryPreference::HostCached
+        } else {
+            HostMemoryPreference::WriteCombined
+        }
+    }
+}
+
 /// Layout of the object being allocated.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum AllocLayout {
@@ -7,6 +7,9 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use super::defragment::{plan_moves, BlockMemory, DefragmentationBudget, PoolMove};
+use super::tlsf::{Tlsf, TlsfAlloc};
+use super::{default_block_size, HeapUsage, MemoryPoolStatistics};
 use crate::device::physical::MemoryType;
 use crate::device::Device;
 use crate::instance::Instance;
@@ -14,22 +17,28 @@ use crate::memory::device_memory::MemoryAllocateInfo;
 use crate::memory::DeviceMemory;
 use crate::memory::DeviceMemoryAllocationError;
 use crate::DeviceSize;
+use crate::OomError;
 use std::cmp;
-use std::ops::Range;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 /// Memory pool that operates on a given memory type.
+///
+/// Memory is allocated from the device in large blocks, and each allocation is placed inside one
+/// of the blocks with a TLSF allocator.
 #[derive(Debug)]
 pub struct StdNonHostVisibleMemoryTypePool {
     device: Arc<Device>,
     memory_type: u32,
-    // TODO: obviously very inefficient
-    occupied: Mutex<Vec<(Arc<DeviceMemory>, Vec<Range<DeviceSize>>)>>,
+    block_size: DeviceSize,
+    heap_usage: Option<Arc<HeapUsage>>,
+    priority: Option<f32>,
+    blocks: Mutex<Vec<(Arc<DeviceMemory>, Tlsf)>>,
 }
 
 impl StdNonHostVisibleMemoryTypePool {
-    /// Creates a new pool that will operate on the given memory type.
+    /// Creates a new pool that will operate on the given memory type, with a default block size
+    /// that depends on the size of the memory heap.
     ///
     /// # Panic
     ///
@@ -39,6 +48,37 @@ impl StdNonHostVisibleMemoryTypePool {
     pub fn new(
         device: Arc<Device>,
         memory_type: MemoryType,
+    ) -> Arc<StdNonHostVisibleMemoryTypePool> {
+        let block_size = default_block_size(memory_type);
+        Self::with_block_size(device, memory_type, block_size)
+    }
+
+    /// Creates a new pool that will operate on the given memory type, and that allocates memory
+    /// from the device in blocks of `block_size` bytes. Allocations that are larger than
+    /// `block_size` get a block of their own.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the `device` and `memory_type` don't belong to the same physical device.
+    /// - Panics if `block_size` is 0.
+    ///
+    #[inline]
+    pub fn with_block_size(
+        device: Arc<Device>,
+        memory_type: MemoryType,
+        block_size: DeviceSize,
+    ) -> Arc<StdNonHostVisibleMemoryTypePool> {
+        Self::new_inner(device, memory_type, block_size, None, None)
+    }
+
+    // Creates a pool that records the memory it allocates from the device in `heap_usage`, and
+    // that allocates its blocks with the given priority.
+    pub(super) fn new_inner(
+        device: Arc<Device>,
+        memory_type: MemoryType,
+        block_size: DeviceSize,
+        heap_usage: Option<Arc<HeapUsage>>,
+        priority: Option<f32>,
     ) -> Arc<StdNonHostVisibleMemoryTypePool> {
         assert_eq!(
             &**device.physical_device().instance() as *const Instance,
@@ -48,11 +88,15 @@ impl StdNonHostVisibleMemoryTypePool {
             device.physical_device().index(),
             memory_type.physical_device().index()
         );
+        assert!(block_size != 0);
 
         Arc::new(StdNonHostVisibleMemoryTypePool {
             device: device.clone(),
             memory_type: memory_type.id(),
-            occupied: Mutex::new(Vec::new()),
+            block_size,
+            heap_usage,
+            priority,
+            blocks: Mutex::new(Vec::new()),
         })
     }
 
@@ -71,69 +115,81 @@ impl StdNonHostVisibleMemoryTypePool {
         assert!(size != 0);
         assert!(alignment != 0);
 
-        #[inline]
-        fn align(val: DeviceSize, al: DeviceSize) -> DeviceSize {
-            al * (1 + (val - 1) / al)
-        }
-
-        // Find a location.
-        let mut occupied = me.occupied.lock().unwrap();
-
-        // Try finding an entry in already-allocated chunks.
-        for &mut (ref dev_mem, ref mut entries) in occupied.iter_mut() {
-            // Try find some free space in-between two entries.
-            for i in 0..entries.len().saturating_sub(1) {
-                let entry1 = entries[i].clone();
-                let entry1_end = align(entry1.end, alignment);
-                let entry2 = entries[i + 1].clone();
-                if entry1_end + size <= entry2.start {
-                    entries.insert(i + 1, entry1_end..entry1_end + size);
-                    return Ok(StdNonHostVisibleMemoryTypePoolAlloc {
-                        pool: me.clone(),
-                        memory: dev_mem.clone(),
-                        offset: entry1_end,
-                        size: size,
-                    });
-                }
-            }
+        let mut blocks = me.blocks.lock().unwrap();
 
-            // Try append at the end.
-            let last_end = entries.last().map(|e| align(e.end, alignment)).unwrap_or(0);
-            if last_end + size <= dev_mem.allocation_size() {
-                entries.push(last_end..last_end + size);
+        // Try finding some free space in the existing blocks.
+        for (memory, tlsf) in blocks.iter_mut() {
+            if let Some(tlsf_alloc) = tlsf.allocate(size, alignment) {
                 return Ok(StdNonHostVisibleMemoryTypePoolAlloc {
                     pool: me.clone(),
-                    memory: dev_mem.clone(),
-                    offset: last_end,
-                    size: size,
+                    memory: memory.clone(),
+                    tlsf_alloc,
+                    size,
                 });
             }
         }
 
-        // We need to allocate a new block.
-        let new_block = {
-            const MIN_BLOCK_SIZE: DeviceSize = 8 * 1024 * 1024; // 8 MB
-            let allocation_size = cmp::max(MIN_BLOCK_SIZE, size.next_power_of_two());
-            let new_block = DeviceMemory::allocate(
-                me.device.clone(),
-                MemoryAllocateInfo {
-                    allocation_size,
-                    memory_type_index: me.memory_type().id(),
-                    ..Default::default()
-                },
-            )?;
-            Arc::new(new_block)
+        // We need to allocate a new block. If the device runs out of memory or the heap budget
+        // is exceeded, retry with smaller blocks until the block is no larger than the allocation
+        // itself.
+        let mut allocation_size = cmp::max(me.block_size, size);
+
+        let memory = loop {
+            match me.allocate_block(allocation_size) {
+                Ok(memory) => break memory,
+                Err(
+                    DeviceMemoryAllocationError::OomError(OomError::OutOfDeviceMemory)
+                    | DeviceMemoryAllocationError::HeapBudgetExceeded { .. },
+                ) if allocation_size / 2 >= size => {
+                    allocation_size /= 2;
+                }
+                Err(err) => return Err(err),
+            }
         };
 
-        occupied.push((new_block.clone(), vec![0..size]));
+        let mut tlsf = Tlsf::new(allocation_size);
+        let tlsf_alloc = tlsf.allocate(size, alignment).unwrap();
+        blocks.push((memory.clone(), tlsf));
+
         Ok(StdNonHostVisibleMemoryTypePoolAlloc {
             pool: me.clone(),
-            memory: new_block,
-            offset: 0,
-            size: size,
+            memory,
+            tlsf_alloc,
+            size,
         })
     }
 
+    // Allocates a new block from the device.
+    fn allocate_block(
+        &self,
+        allocation_size: DeviceSize,
+    ) -> Result<Arc<DeviceMemory>, DeviceMemoryAllocationError> {
+        let heap_index = self.memory_type().heap().id();
+
+        if let Some(heap_usage) = &self.heap_usage {
+            heap_usage.reserve(heap_index, allocation_size)?;
+        }
+
+        let result = DeviceMemory::allocate(
+            self.device.clone(),
+            MemoryAllocateInfo {
+                allocation_size,
+                memory_type_index: self.memory_type,
+                priority: self.priority,
+                ..Default::default()
+            },
+        )
+        .map(Arc::new);
+
+        if result.is_err() {
+            if let Some(heap_usage) = &self.heap_usage {
+                heap_usage.release(heap_index, allocation_size);
+            }
+        }
+
+        result
+    }
+
     /// Returns the device this pool operates on.
     #[inline]
     pub fn device(&self) -> &Arc<Device> {
@@ -148,13 +204,90 @@ impl StdNonHostVisibleMemoryTypePool {
             .memory_type_by_id(self.memory_type)
             .unwrap()
     }
+
+    /// Returns the size of the blocks that this pool allocates from the device.
+    #[inline]
+    pub fn block_size(&self) -> DeviceSize {
+        self.block_size
+    }
+
+    /// Returns statistics about the memory that is used by this pool.
+    pub fn statistics(&self) -> MemoryPoolStatistics {
+        let blocks = self.blocks.lock().unwrap();
+        let mut statistics = MemoryPoolStatistics::default();
+
+        for (_, tlsf) in blocks.iter() {
+            statistics.add_block(tlsf);
+        }
+
+        statistics
+    }
+
+    // Plans the moves of a defragmentation pass over the blocks of this pool.
+    pub(super) fn defragment(
+        me: &Arc<Self>,
+        budget: &mut DefragmentationBudget,
+    ) -> Vec<PoolMove<StdNonHostVisibleMemoryTypePoolAlloc>> {
+        let mut blocks = me.blocks.lock().unwrap();
+        let planned = plan_moves(
+            &mut blocks.iter_mut().map(|(_, tlsf)| tlsf).collect::<Vec<_>>(),
+            budget,
+        );
+
+        planned
+            .into_iter()
+            .map(|planned| {
+                let src_memory = &blocks[planned.src_block].0;
+                let dst_memory = &blocks[planned.dst_block].0;
+
+                PoolMove {
+                    src_memory: BlockMemory::NonHostVisible(src_memory.clone()),
+                    src_offset: planned.src.offset,
+                    dst_memory: BlockMemory::NonHostVisible(dst_memory.clone()),
+                    dst: StdNonHostVisibleMemoryTypePoolAlloc {
+                        pool: me.clone(),
+                        memory: dst_memory.clone(),
+                        tlsf_alloc: planned.dst,
+                        size: planned.size,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Releases all blocks that hold no allocations back to the device, and returns the number
+    /// of bytes that were released.
+    ///
+    /// Normally, the pool keeps one empty block around so that it doesn't repeatedly allocate and
+    /// free device memory. This also releases that block.
+    pub fn trim(&self) -> DeviceSize {
+        let mut blocks = self.blocks.lock().unwrap();
+        let mut released = 0;
+
+        blocks.retain(|(_, tlsf)| {
+            if tlsf.is_empty() {
+                released += tlsf.size();
+                false
+            } else {
+                true
+            }
+        });
+
+        if released != 0 {
+            if let Some(heap_usage) = &self.heap_usage {
+                heap_usage.release(self.memory_type().heap().id(), released);
+            }
+        }
+
+        released
+    }
 }
 
 #[derive(Debug)]
 pub struct StdNonHostVisibleMemoryTypePoolAlloc {
     pool: Arc<StdNonHostVisibleMemoryTypePool>,
     memory: Arc<DeviceMemory>,
-    offset: DeviceSize,
+    tlsf_alloc: TlsfAlloc,
     size: DeviceSize,
 }
 
@@ -166,7 +299,7 @@ impl StdNonHostVisibleMemoryTypePoolAlloc {
 
     #[inline]
     pub fn offset(&self) -> DeviceSize {
-        self.offset
+        self.tlsf_alloc.offset
     }
 
     #[inline]
@@ -177,13 +310,23 @@ impl StdNonHostVisibleMemoryTypePoolAlloc {
 
 impl Drop for StdNonHostVisibleMemoryTypePoolAlloc {
     fn drop(&mut self) {
-        let mut occupied = self.pool.occupied.lock().unwrap();
+        let mut blocks = self.pool.blocks.lock().unwrap();
 
-        let entries = occupied
-            .iter_mut()
-            .find(|e| &*e.0 as *const DeviceMemory == &*self.memory)
+        let index = blocks
+            .iter()
+            .position(|(memory, _)| Arc::ptr_eq(memory, &self.memory))
             .unwrap();
+        let tlsf = &mut blocks[index].1;
+        tlsf.free(self.tlsf_alloc);
 
-        entries.1.retain(|e| e.start != self.offset);
+        // Keep one empty block around to avoid allocating and freeing device memory repeatedly,
+        // but release any others.
+        if tlsf.is_empty() && blocks.iter().filter(|(_, tlsf)| tlsf.is_empty()).count() > 1 {
+            let (_, tlsf) = blocks.swap_remove(index);
+
+            if let Some(heap_usage) = &self.pool.heap_usage {
+                heap_usage.release(self.pool.memory_type().heap().id(), tlsf.size());
+            }
+        }
     }
 }
@@ -7,9 +7,16 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use super::defragment::DefragmentationBudget;
+use super::{
+    choose_allocation_memory_type, default_block_size, AllocFromRequirementsFilter,
+    DefragmentationInfo, DefragmentationMove, HeapUsage, MemoryPoolStatistics,
+    PotentialDedicatedAllocation,
+};
 use crate::device::physical::MemoryType;
 use crate::device::Device;
 use crate::device::DeviceOwned;
+use crate::memory::device_memory::MemoryAllocateInfo;
 use crate::memory::pool::AllocLayout;
 use crate::memory::pool::MappingRequirement;
 use crate::memory::pool::MemoryPool;
@@ -18,36 +25,360 @@ use crate::memory::pool::StdHostVisibleMemoryTypePool;
 use crate::memory::pool::StdHostVisibleMemoryTypePoolAlloc;
 use crate::memory::pool::StdNonHostVisibleMemoryTypePool;
 use crate::memory::pool::StdNonHostVisibleMemoryTypePoolAlloc;
+use crate::memory::DedicatedAllocation;
 use crate::memory::DeviceMemory;
 use crate::memory::DeviceMemoryAllocationError;
 use crate::memory::MappedDeviceMemory;
+use crate::memory::MemoryRequirements;
 use crate::DeviceSize;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+/// General-purpose memory pool.
+///
+/// Memory is allocated from the device in large blocks, separately for each memory type, and
+/// allocations are placed inside the blocks with a TLSF (two-level segregated fit) allocator.
+/// This keeps the number of device memory objects low, and makes allocating and freeing fast and
+/// resistant to fragmentation.
+///
+/// Large allocations, and allocations for resources that the implementation prefers to have
+/// their own memory, are given a dedicated block of device memory instead.
+///
+/// The amount of memory that the pool allocates from each memory heap can be limited with
+/// [`StdMemoryPoolCreateInfo::heap_budgets`], and [`statistics`](StdMemoryPool::statistics)
+/// reports how much memory is in use. Blocks that no longer hold any allocations can be released
+/// to the driver with [`trim`](StdMemoryPool::trim). Applications that run for a long time can
+/// also compact the blocks with [`defragment`](StdMemoryPool::defragment).
 #[derive(Debug)]
 pub struct StdMemoryPool {
     device: Arc<Device>,
+    block_size: Option<DeviceSize>,
+    dedicated_allocation_threshold: Option<DeviceSize>,
+    heap_usage: Arc<HeapUsage>,
 
-    // For each memory type index, stores the associated pool.
-    pools: Mutex<HashMap<(u32, AllocLayout, MappingRequirement), Pool>>,
+    // For each memory type index, stores the associated pool. Allocations with a priority are
+    // kept in separate pools for each priority, stored as the bits of the `f32`.
+    pools: Mutex<HashMap<(u32, AllocLayout, MappingRequirement, Option<u32>), Pool>>,
+
+    // For each memory heap index, the statistics of the dedicated allocations.
+    dedicated_statistics: Mutex<Vec<MemoryPoolStatistics>>,
 }
 
 impl StdMemoryPool {
-    /// Creates a new pool.
+    /// Creates a new pool with default parameters.
     #[inline]
     pub fn new(device: Arc<Device>) -> Arc<StdMemoryPool> {
+        Self::with_create_info(device, Default::default())
+    }
+
+    /// Creates a new pool with the given parameters.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.block_size` is `Some(0)`.
+    pub fn with_create_info(
+        device: Arc<Device>,
+        create_info: StdMemoryPoolCreateInfo,
+    ) -> Arc<StdMemoryPool> {
+        let StdMemoryPoolCreateInfo {
+            block_size,
+            dedicated_allocation_threshold,
+            heap_budgets,
+            _ne: _,
+        } = create_info;
+
+        assert!(block_size != Some(0));
+
         let cap = device.physical_device().memory_types().len();
+        let heap_count = device.physical_device().memory_heaps().len();
 
         Arc::new(StdMemoryPool {
             device: device.clone(),
+            block_size,
+            dedicated_allocation_threshold,
+            heap_usage: Arc::new(HeapUsage::new(heap_count, heap_budgets)),
             pools: Mutex::new(HashMap::with_capacity(cap)),
+            dedicated_statistics: Mutex::new(vec![Default::default(); heap_count]),
+        })
+    }
+
+    /// Returns statistics about the memory that is used by the pool, across all memory heaps.
+    pub fn statistics(&self) -> MemoryPoolStatistics {
+        let heap_count = self.device.physical_device().memory_heaps().len() as u32;
+        let mut statistics = MemoryPoolStatistics::default();
+
+        for heap_index in 0..heap_count {
+            statistics.merge(&self.heap_statistics(heap_index));
+        }
+
+        statistics
+    }
+
+    /// Returns statistics about the memory that is used by the pool from the given memory heap.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `heap_index` is not less than the number of memory heaps of the device.
+    pub fn heap_statistics(&self, heap_index: u32) -> MemoryPoolStatistics {
+        let mut statistics = self.dedicated_statistics.lock().unwrap()[heap_index as usize];
+        let pools = self.pools.lock().unwrap();
+
+        for (&(memory_type_index, _, _, _), pool) in pools.iter() {
+            let memory_type = self
+                .device
+                .physical_device()
+                .memory_type_by_id(memory_type_index)
+                .unwrap();
+
+            if memory_type.heap().id() != heap_index {
+                continue;
+            }
+
+            statistics.merge(&match pool {
+                Pool::HostVisible(pool) => pool.statistics(),
+                Pool::NonHostVisible(pool) => pool.statistics(),
+            });
+        }
+
+        statistics
+    }
+
+    /// Returns the number of bytes of device memory that the pool has allocated from the given
+    /// memory heap. This is what is compared against the heap's budget.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `heap_index` is not less than the number of memory heaps of the device.
+    #[inline]
+    pub fn heap_usage(&self, heap_index: u32) -> DeviceSize {
+        self.heap_usage.used(heap_index)
+    }
+
+    /// Returns the number of bytes that can still be allocated from the given memory heap, or
+    /// `None` if there is no known limit.
+    ///
+    /// This takes into account both the budget that was given to the pool in
+    /// [`StdMemoryPoolCreateInfo::heap_budgets`], and the budget of the heap that is reported by
+    /// the driver if the physical device supports
+    /// [`memory_budget`](crate::device::physical::PhysicalDevice::memory_budget). Applications
+    /// that stream resources in can compare the size of the next resources against this value,
+    /// and hold off or evict other resources instead of oversubscribing the heap.
+    ///
+    /// Note that this doesn't include the free space inside the blocks that the pool has already
+    /// allocated, which is given by [`heap_statistics`](Self::heap_statistics).
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `heap_index` is not less than the number of memory heaps of the device.
+    pub fn available_memory(&self, heap_index: u32) -> Option<DeviceSize> {
+        assert!(heap_index < self.device.physical_device().memory_heaps().len() as u32);
+
+        let pool_remaining = self.heap_usage.remaining(heap_index);
+        let driver_remaining = self
+            .device
+            .physical_device()
+            .memory_budget()
+            .map(|budgets| budgets[heap_index as usize].available());
+
+        match (pool_remaining, driver_remaining) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Releases all blocks of device memory that hold no allocations, across all memory types,
+    /// and returns the number of bytes that were released.
+    ///
+    /// The pool keeps an empty block around for each memory type after all of its allocations
+    /// are freed, so that it doesn't repeatedly allocate and free device memory. Applications
+    /// that run for a long time can call this after freeing many resources, for example after
+    /// unloading a level, to give that memory back to the driver.
+    pub fn trim(&self) -> DeviceSize {
+        let pools = self.pools.lock().unwrap();
+
+        pools
+            .values()
+            .map(|pool| match pool {
+                Pool::HostVisible(pool) => pool.trim(),
+                Pool::NonHostVisible(pool) => pool.trim(),
+            })
+            .sum()
+    }
+
+    /// Performs a defragmentation pass, and returns the allocations that should be moved.
+    ///
+    /// The pass picks the blocks that hold the least data, and reserves a place for each of their
+    /// allocations in the blocks that hold the most. Each [`DefragmentationMove`] holds the
+    /// allocation at the new place; the caller creates a new resource there, copies the contents
+    /// over, and replaces the old resource once the copy has completed. After the old resources
+    /// are dropped, the blocks that were emptied can be released with [`trim`](Self::trim). Moves
+    /// that are dropped instead give their reserved memory back to the pool.
+    ///
+    /// A pass moves at most `info.max_bytes` bytes and `info.max_moves` allocations, so the
+    /// defragmentation can be spread over many frames by performing a pass every so often. Only
+    /// allocations with a linear layout are moved, because the contents of images with optimal
+    /// tiling can't be copied byte by byte. Dedicated allocations are never moved.
+    pub fn defragment(self: &Arc<Self>, info: DefragmentationInfo) -> Vec<DefragmentationMove> {
+        let DefragmentationInfo {
+            max_bytes,
+            max_moves,
+            _ne: _,
+        } = info;
+
+        let pools = self.pools.lock().unwrap();
+        let mut budget = DefragmentationBudget {
+            bytes: max_bytes,
+            moves: max_moves,
+        };
+        let mut moves = Vec::new();
+
+        for (&(_, layout, _, _), pool) in pools.iter() {
+            if layout != AllocLayout::Linear {
+                continue;
+            }
+
+            let pool_moves: Vec<_> = match pool {
+                Pool::HostVisible(pool) => {
+                    StdHostVisibleMemoryTypePool::defragment(pool, &mut budget)
+                        .into_iter()
+                        .map(|pool_move| pool_move.map_dst(StdMemoryPoolAllocInner::HostVisible))
+                        .collect()
+                }
+                Pool::NonHostVisible(pool) => {
+                    StdNonHostVisibleMemoryTypePool::defragment(pool, &mut budget)
+                        .into_iter()
+                        .map(|pool_move| pool_move.map_dst(StdMemoryPoolAllocInner::NonHostVisible))
+                        .collect()
+                }
+            };
+
+            moves.extend(pool_moves.into_iter().map(|pool_move| DefragmentationMove {
+                src_memory: pool_move.src_memory,
+                src_offset: pool_move.src_offset,
+                dst_memory: pool_move.dst_memory,
+                dst: StdMemoryPoolAlloc {
+                    inner: pool_move.dst,
+                    pool: self.clone(),
+                },
+            }));
+        }
+
+        moves
+    }
+
+    /// Returns the size of the blocks that the pool allocates from the device for the given
+    /// memory type.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `memory_type` does not belong to the physical device of the pool.
+    #[inline]
+    pub fn block_size(&self, memory_type: MemoryType) -> DeviceSize {
+        assert_eq!(
+            memory_type.physical_device().index(),
+            self.device.physical_device().index()
+        );
+
+        self.block_size
+            .unwrap_or_else(|| default_block_size(memory_type))
+    }
+
+    // Performs an allocation that gets its own block of device memory.
+    fn dedicated_allocation(
+        self: &Arc<Self>,
+        memory_type: MemoryType,
+        size: DeviceSize,
+        map: MappingRequirement,
+        dedicated_allocation: Option<DedicatedAllocation>,
+        priority: Option<f32>,
+    ) -> Result<StdMemoryPoolAlloc, DeviceMemoryAllocationError> {
+        let heap_index = memory_type.heap().id();
+        self.heap_usage.reserve(heap_index, size)?;
+
+        let result = DeviceMemory::allocate(
+            self.device.clone(),
+            MemoryAllocateInfo {
+                allocation_size: size,
+                memory_type_index: memory_type.id(),
+                dedicated_allocation,
+                priority,
+                ..Default::default()
+            },
+        )
+        .and_then(|memory| {
+            Ok(match map {
+                MappingRequirement::Map => StdMemoryPoolAllocInner::DedicatedMapped(
+                    MappedDeviceMemory::new(memory, 0..size)?,
+                ),
+                MappingRequirement::DoNotMap => StdMemoryPoolAllocInner::Dedicated(memory),
+            })
+        });
+
+        let inner = match result {
+            Ok(inner) => inner,
+            Err(err) => {
+                self.heap_usage.release(heap_index, size);
+                return Err(err);
+            }
+        };
+
+        self.dedicated_statistics.lock().unwrap()[heap_index as usize].add_dedicated(size);
+
+        Ok(StdMemoryPoolAlloc {
+            inner,
+            pool: self.clone(),
         })
     }
 }
 
+/// Parameters to create a new `StdMemoryPool`.
+#[derive(Clone, Debug)]
+pub struct StdMemoryPoolCreateInfo {
+    /// The size of the blocks that are allocated from the device, from which the allocations are
+    /// then suballocated.
+    ///
+    /// If `None`, the block size is chosen based on the size of the memory heap: it is 64 MiB, or
+    /// an eighth of the heap size for heaps smaller than 512 MiB.
+    ///
+    /// The default value is `None`.
+    pub block_size: Option<DeviceSize>,
+
+    /// Allocations larger than this size are given their own block of device memory. If the
+    /// resource is known, the memory is also dedicated to it.
+    ///
+    /// If `None`, half the block size is used.
+    ///
+    /// The default value is `None`.
+    pub dedicated_allocation_threshold: Option<DeviceSize>,
+
+    /// The maximum number of bytes of device memory that the pool may allocate from each memory
+    /// heap, indexed by the heap index. Heaps without an entry, or with an entry of `None`, are
+    /// not limited.
+    ///
+    /// When an allocation would exceed the budget, the pool first tries to allocate a smaller
+    /// block, and returns [`DeviceMemoryAllocationError::HeapBudgetExceeded`] if that is not
+    /// possible either.
+    ///
+    /// The default value is empty.
+    pub heap_budgets: Vec<Option<DeviceSize>>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for StdMemoryPoolCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            block_size: None,
+            dedicated_allocation_threshold: None,
+            heap_budgets: Vec::new(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
 fn generic_allocation(
     mem_pool: Arc<StdMemoryPool>,
     memory_type: MemoryType,
@@ -55,55 +386,52 @@ fn generic_allocation(
     alignment: DeviceSize,
     layout: AllocLayout,
     map: MappingRequirement,
+    priority: Option<f32>,
 ) -> Result<StdMemoryPoolAlloc, DeviceMemoryAllocationError> {
     let mut pools = mem_pool.pools.lock().unwrap();
 
     let memory_type_host_visible = memory_type.is_host_visible();
     assert!(memory_type_host_visible || map == MappingRequirement::DoNotMap);
 
-    match pools.entry((memory_type.id(), layout, map)) {
-        Entry::Occupied(entry) => match entry.get() {
-            &Pool::HostVisible(ref pool) => {
-                let alloc = StdHostVisibleMemoryTypePool::alloc(&pool, size, alignment)?;
-                let inner = StdMemoryPoolAllocInner::HostVisible(alloc);
-                Ok(StdMemoryPoolAlloc {
-                    inner,
-                    pool: mem_pool.clone(),
-                })
-            }
-            &Pool::NonHostVisible(ref pool) => {
-                let alloc = StdNonHostVisibleMemoryTypePool::alloc(&pool, size, alignment)?;
-                let inner = StdMemoryPoolAllocInner::NonHostVisible(alloc);
-                Ok(StdMemoryPoolAlloc {
-                    inner,
-                    pool: mem_pool.clone(),
-                })
-            }
-        },
-
+    let pool = match pools.entry((memory_type.id(), layout, map, priority.map(f32::to_bits))) {
+        Entry::Occupied(entry) => entry.into_mut(),
         Entry::Vacant(entry) => {
-            if memory_type_host_visible {
-                let pool = StdHostVisibleMemoryTypePool::new(mem_pool.device.clone(), memory_type);
-                entry.insert(Pool::HostVisible(pool.clone()));
-                let alloc = StdHostVisibleMemoryTypePool::alloc(&pool, size, alignment)?;
-                let inner = StdMemoryPoolAllocInner::HostVisible(alloc);
-                Ok(StdMemoryPoolAlloc {
-                    inner,
-                    pool: mem_pool.clone(),
-                })
+            let block_size = mem_pool.block_size(memory_type);
+            let heap_usage = Some(mem_pool.heap_usage.clone());
+
+            entry.insert(if memory_type_host_visible {
+                Pool::HostVisible(StdHostVisibleMemoryTypePool::new_inner(
+                    mem_pool.device.clone(),
+                    memory_type,
+                    block_size,
+                    heap_usage,
+                    priority,
+                ))
             } else {
-                let pool =
-                    StdNonHostVisibleMemoryTypePool::new(mem_pool.device.clone(), memory_type);
-                entry.insert(Pool::NonHostVisible(pool.clone()));
-                let alloc = StdNonHostVisibleMemoryTypePool::alloc(&pool, size, alignment)?;
-                let inner = StdMemoryPoolAllocInner::NonHostVisible(alloc);
-                Ok(StdMemoryPoolAlloc {
-                    inner,
-                    pool: mem_pool.clone(),
-                })
-            }
+                Pool::NonHostVisible(StdNonHostVisibleMemoryTypePool::new_inner(
+                    mem_pool.device.clone(),
+                    memory_type,
+                    block_size,
+                    heap_usage,
+                    priority,
+                ))
+            })
         }
-    }
+    };
+
+    let inner = match pool {
+        Pool::HostVisible(pool) => StdMemoryPoolAllocInner::HostVisible(
+            StdHostVisibleMemoryTypePool::alloc(pool, size, alignment)?,
+        ),
+        Pool::NonHostVisible(pool) => StdMemoryPoolAllocInner::NonHostVisible(
+            StdNonHostVisibleMemoryTypePool::alloc(pool, size, alignment)?,
+        ),
+    };
+
+    Ok(StdMemoryPoolAlloc {
+        inner,
+        pool: mem_pool.clone(),
+    })
 }
 
 unsafe impl MemoryPool for Arc<StdMemoryPool> {
@@ -117,8 +445,117 @@ unsafe impl MemoryPool for Arc<StdMemoryPool> {
         layout: AllocLayout,
         map: MappingRequirement,
     ) -> Result<StdMemoryPoolAlloc, DeviceMemoryAllocationError> {
-        generic_allocation(self.clone(), memory_type, size, alignment, layout, map)
+        generic_allocation(
+            self.clone(),
+            memory_type,
+            size,
+            alignment,
+            layout,
+            map,
+            None,
+        )
     }
+
+    #[inline]
+    fn alloc_from_requirements<F>(
+        &self,
+        requirements: &MemoryRequirements,
+        layout: AllocLayout,
+        map: MappingRequirement,
+        dedicated_allocation: Option<DedicatedAllocation>,
+        filter: F,
+    ) -> Result<PotentialDedicatedAllocation<StdMemoryPoolAlloc>, DeviceMemoryAllocationError>
+    where
+        F: FnMut(MemoryType) -> AllocFromRequirementsFilter,
+    {
+        alloc_from_requirements(
+            self,
+            requirements,
+            layout,
+            map,
+            dedicated_allocation,
+            None,
+            filter,
+        )
+    }
+
+    /// Same as `alloc_from_requirements`, but the memory is given a priority relative to other
+    /// allocations.
+    ///
+    /// Allocations with a priority are placed in separate blocks of device memory for each
+    /// priority value, so only a few distinct values should be used. The priority is ignored if
+    /// the [`memory_priority`](crate::device::Features::memory_priority) feature is not enabled
+    /// on the device.
+    fn alloc_from_requirements_with_priority<F>(
+        &self,
+        requirements: &MemoryRequirements,
+        layout: AllocLayout,
+        map: MappingRequirement,
+        dedicated_allocation: Option<DedicatedAllocation>,
+        priority: f32,
+        filter: F,
+    ) -> Result<PotentialDedicatedAllocation<StdMemoryPoolAlloc>, DeviceMemoryAllocationError>
+    where
+        F: FnMut(MemoryType) -> AllocFromRequirementsFilter,
+    {
+        assert!((0.0..=1.0).contains(&priority));
+        let priority = Some(priority).filter(|_| self.device.enabled_features().memory_priority);
+
+        alloc_from_requirements(
+            self,
+            requirements,
+            layout,
+            map,
+            dedicated_allocation,
+            priority,
+            filter,
+        )
+    }
+}
+
+fn alloc_from_requirements<F>(
+    mem_pool: &Arc<StdMemoryPool>,
+    requirements: &MemoryRequirements,
+    layout: AllocLayout,
+    map: MappingRequirement,
+    dedicated_allocation: Option<DedicatedAllocation>,
+    priority: Option<f32>,
+    filter: F,
+) -> Result<PotentialDedicatedAllocation<StdMemoryPoolAlloc>, DeviceMemoryAllocationError>
+where
+    F: FnMut(MemoryType) -> AllocFromRequirementsFilter,
+{
+    let memory_type = choose_allocation_memory_type(&mem_pool.device, requirements, filter, map);
+    let dedicated_allocation_threshold = mem_pool
+        .dedicated_allocation_threshold
+        .unwrap_or_else(|| mem_pool.block_size(memory_type) / 2);
+
+    // Give the allocation its own block of memory if the implementation prefers that for the
+    // resource, or if it would take up a large part of a block. The memory can only be
+    // dedicated to the resource if the resource was provided.
+    let alloc = if requirements.prefer_dedicated && dedicated_allocation.is_some()
+        || requirements.size > dedicated_allocation_threshold
+    {
+        mem_pool.dedicated_allocation(
+            memory_type,
+            requirements.size,
+            map,
+            dedicated_allocation,
+            priority,
+        )?
+    } else {
+        generic_allocation(
+            mem_pool.clone(),
+            memory_type,
+            requirements.size,
+            requirements.alignment,
+            layout,
+            map,
+            priority,
+        )?
+    };
+
+    Ok(alloc.into())
 }
 
 unsafe impl DeviceOwned for StdMemoryPool {
@@ -146,8 +583,19 @@ impl StdMemoryPoolAlloc {
         match self.inner {
             StdMemoryPoolAllocInner::NonHostVisible(ref mem) => mem.size(),
             StdMemoryPoolAllocInner::HostVisible(ref mem) => mem.size(),
+            StdMemoryPoolAllocInner::Dedicated(ref mem) => mem.allocation_size(),
+            StdMemoryPoolAllocInner::DedicatedMapped(ref mem) => mem.as_ref().allocation_size(),
         }
     }
+
+    /// Returns true if the allocation has its own block of device memory.
+    #[inline]
+    pub fn is_dedicated(&self) -> bool {
+        matches!(
+            self.inner,
+            StdMemoryPoolAllocInner::Dedicated(_) | StdMemoryPoolAllocInner::DedicatedMapped(_)
+        )
+    }
 }
 
 unsafe impl MemoryPoolAlloc for StdMemoryPoolAlloc {
@@ -156,6 +604,8 @@ unsafe impl MemoryPoolAlloc for StdMemoryPoolAlloc {
         match self.inner {
             StdMemoryPoolAllocInner::NonHostVisible(ref mem) => mem.memory(),
             StdMemoryPoolAllocInner::HostVisible(ref mem) => mem.memory().as_ref(),
+            StdMemoryPoolAllocInner::Dedicated(ref mem) => mem,
+            StdMemoryPoolAllocInner::DedicatedMapped(ref mem) => mem.as_ref(),
         }
     }
 
@@ -164,6 +614,8 @@ unsafe impl MemoryPoolAlloc for StdMemoryPoolAlloc {
         match self.inner {
             StdMemoryPoolAllocInner::NonHostVisible(_) => None,
             StdMemoryPoolAllocInner::HostVisible(ref mem) => Some(mem.memory()),
+            StdMemoryPoolAllocInner::Dedicated(_) => None,
+            StdMemoryPoolAllocInner::DedicatedMapped(ref mem) => Some(mem),
         }
     }
 
@@ -172,6 +624,21 @@ unsafe impl MemoryPoolAlloc for StdMemoryPoolAlloc {
         match self.inner {
             StdMemoryPoolAllocInner::NonHostVisible(ref mem) => mem.offset(),
             StdMemoryPoolAllocInner::HostVisible(ref mem) => mem.offset(),
+            StdMemoryPoolAllocInner::Dedicated(_) => 0,
+            StdMemoryPoolAllocInner::DedicatedMapped(_) => 0,
+        }
+    }
+}
+
+impl Drop for StdMemoryPoolAlloc {
+    fn drop(&mut self) {
+        if self.is_dedicated() {
+            let size = self.size();
+            let heap_index = self.memory().memory_type().heap().id();
+
+            self.pool.dedicated_statistics.lock().unwrap()[heap_index as usize]
+                .remove_dedicated(size);
+            self.pool.heap_usage.release(heap_index, size);
         }
     }
 }
@@ -180,4 +647,263 @@ unsafe impl MemoryPoolAlloc for StdMemoryPoolAlloc {
 enum StdMemoryPoolAllocInner {
     NonHostVisible(StdNonHostVisibleMemoryTypePoolAlloc),
     HostVisible(StdHostVisibleMemoryTypePoolAlloc),
+    Dedicated(DeviceMemory),
+    DedicatedMapped(MappedDeviceMemory),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StdMemoryPool, StdMemoryPoolCreateInfo};
+    use crate::memory::pool::{
+        AllocLayout, DefragmentationInfo, MappingRequirement, MemoryPool, MemoryPoolAlloc,
+    };
+    use crate::memory::DeviceMemoryAllocationError;
+
+    #[test]
+    fn statistics() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+        let heap_index = memory_type.heap().id();
+        let pool = StdMemoryPool::with_create_info(
+            device.clone(),
+            StdMemoryPoolCreateInfo {
+                block_size: Some(1024 * 1024),
+                ..Default::default()
+            },
+        );
+
+        let alloc1 = pool
+            .alloc_generic(
+                memory_type,
+                256,
+                16,
+                AllocLayout::Linear,
+                MappingRequirement::DoNotMap,
+            )
+            .unwrap();
+        let alloc2 = pool
+            .alloc_generic(
+                memory_type,
+                512,
+                16,
+                AllocLayout::Linear,
+                MappingRequirement::DoNotMap,
+            )
+            .unwrap();
+
+        let statistics = pool.heap_statistics(heap_index);
+        assert_eq!(statistics.block_count, 1);
+        assert_eq!(statistics.block_bytes, 1024 * 1024);
+        assert_eq!(statistics.allocation_count, 2);
+        assert_eq!(statistics.allocation_bytes, 768);
+        assert_eq!(statistics.empty_block_count, 0);
+        assert_eq!(statistics.free_bytes, 1024 * 1024 - 768);
+        assert_eq!(pool.heap_usage(heap_index), 1024 * 1024);
+
+        drop(alloc1);
+        drop(alloc2);
+
+        let statistics = pool.statistics();
+        assert_eq!(statistics.block_count, 1);
+        assert_eq!(statistics.allocation_count, 0);
+        assert_eq!(statistics.empty_block_count, 1);
+        assert_eq!(statistics.largest_free_bytes, 1024 * 1024);
+    }
+
+    #[test]
+    fn non_coherent_atom_alignment() {
+        let (device, _) = gfx_dev_and_queue!();
+        let atom_size = device.physical_device().properties().non_coherent_atom_size;
+        let memory_type = match device
+            .physical_device()
+            .memory_types()
+            .find(|t| t.is_host_visible() && !t.is_host_coherent())
+        {
+            Some(memory_type) => memory_type,
+            None => return,
+        };
+        let pool = StdMemoryPool::with_create_info(device.clone(), Default::default());
+
+        let alloc1 = pool
+            .alloc_generic(
+                memory_type,
+                3,
+                1,
+                AllocLayout::Linear,
+                MappingRequirement::Map,
+            )
+            .unwrap();
+        let alloc2 = pool
+            .alloc_generic(
+                memory_type,
+                3,
+                1,
+                AllocLayout::Linear,
+                MappingRequirement::Map,
+            )
+            .unwrap();
+
+        // Each allocation gets whole atoms of its own.
+        assert_eq!(alloc1.offset() % atom_size, 0);
+        assert_eq!(alloc2.offset() % atom_size, 0);
+        assert_ne!(alloc1.offset(), alloc2.offset());
+    }
+
+    #[test]
+    fn trim() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+        let heap_index = memory_type.heap().id();
+        let pool = StdMemoryPool::with_create_info(
+            device.clone(),
+            StdMemoryPoolCreateInfo {
+                block_size: Some(1024 * 1024),
+                ..Default::default()
+            },
+        );
+
+        let alloc = pool
+            .alloc_generic(
+                memory_type,
+                256,
+                16,
+                AllocLayout::Linear,
+                MappingRequirement::DoNotMap,
+            )
+            .unwrap();
+
+        // Blocks that are in use are kept.
+        assert_eq!(pool.trim(), 0);
+        assert_eq!(pool.heap_statistics(heap_index).block_count, 1);
+
+        // The pool keeps the empty block around until it is trimmed.
+        drop(alloc);
+        assert_eq!(pool.heap_usage(heap_index), 1024 * 1024);
+        assert_eq!(pool.trim(), 1024 * 1024);
+        assert_eq!(pool.heap_statistics(heap_index).block_count, 0);
+        assert_eq!(pool.heap_usage(heap_index), 0);
+    }
+
+    #[test]
+    fn heap_budget() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+        let heap_index = memory_type.heap().id();
+        let mut heap_budgets = vec![None; heap_index as usize + 1];
+        heap_budgets[heap_index as usize] = Some(64 * 1024);
+
+        let pool = StdMemoryPool::with_create_info(
+            device.clone(),
+            StdMemoryPoolCreateInfo {
+                block_size: Some(1024 * 1024),
+                heap_budgets,
+                ..Default::default()
+            },
+        );
+
+        // The block size is reduced to fit in the budget.
+        let _alloc = pool
+            .alloc_generic(
+                memory_type,
+                1024,
+                16,
+                AllocLayout::Linear,
+                MappingRequirement::DoNotMap,
+            )
+            .unwrap();
+        assert!(pool.heap_usage(heap_index) <= 64 * 1024);
+
+        match pool.alloc_generic(
+            memory_type,
+            128 * 1024,
+            16,
+            AllocLayout::Linear,
+            MappingRequirement::DoNotMap,
+        ) {
+            Err(DeviceMemoryAllocationError::HeapBudgetExceeded { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn available_memory() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+        let heap_index = memory_type.heap().id();
+        let mut heap_budgets = vec![None; heap_index as usize + 1];
+        heap_budgets[heap_index as usize] = Some(4 * 1024 * 1024);
+
+        let pool = StdMemoryPool::with_create_info(
+            device.clone(),
+            StdMemoryPoolCreateInfo {
+                block_size: Some(1024 * 1024),
+                heap_budgets,
+                ..Default::default()
+            },
+        );
+        assert!(pool.available_memory(heap_index).unwrap() <= 4 * 1024 * 1024);
+
+        let _alloc = pool
+            .alloc_generic(
+                memory_type,
+                256,
+                16,
+                AllocLayout::Linear,
+                MappingRequirement::DoNotMap,
+            )
+            .unwrap();
+        assert!(pool.available_memory(heap_index).unwrap() <= 3 * 1024 * 1024);
+    }
+
+    #[test]
+    fn defragment() {
+        let (device, _) = gfx_dev_and_queue!();
+        let memory_type = device.physical_device().memory_types().next().unwrap();
+        let heap_index = memory_type.heap().id();
+        let pool = StdMemoryPool::with_create_info(
+            device.clone(),
+            StdMemoryPoolCreateInfo {
+                block_size: Some(64 * 1024),
+                ..Default::default()
+            },
+        );
+
+        // Fill two blocks, then free most of the second one.
+        let mut allocs: Vec<_> = (0..8)
+            .map(|_| {
+                pool.alloc_generic(
+                    memory_type,
+                    16 * 1024,
+                    256,
+                    AllocLayout::Linear,
+                    MappingRequirement::DoNotMap,
+                )
+                .unwrap()
+            })
+            .collect();
+        assert_eq!(pool.heap_statistics(heap_index).block_count, 2);
+        allocs.truncate(5);
+        drop(allocs.remove(0));
+
+        let moves = pool.defragment(Default::default());
+        assert_eq!(moves.len(), 1);
+        let source = allocs
+            .iter()
+            .position(|alloc| moves[0].is_source(alloc))
+            .unwrap();
+        assert_eq!(moves[0].size(), 16 * 1024);
+        assert_eq!(moves[0].dst().offset() % 256, 0);
+
+        // Replace the old allocation with the new one, which empties the second block.
+        allocs[source] = moves.into_iter().next().unwrap().into_dst();
+        assert_eq!(pool.trim(), 64 * 1024);
+        assert_eq!(pool.heap_statistics(heap_index).block_count, 1);
+
+        // A pass without budget doesn't move anything.
+        let moves = pool.defragment(DefragmentationInfo {
+            max_moves: 0,
+            ..Default::default()
+        });
+        assert!(moves.is_empty());
+    }
 }
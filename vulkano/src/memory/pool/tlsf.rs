@@ -0,0 +1,575 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Two-level segregated fit (TLSF) suballocator.
+//!
+//! This only manages offsets within a range of `0..size`, and doesn't know anything about the
+//! memory itself. It is used by the memory type pools to place allocations inside their blocks of
+//! device memory.
+//!
+//! Free regions are sorted into a two-dimensional array of free lists. The first level splits the
+//! sizes in powers of two, and the second level splits each power of two linearly into
+//! `SL_COUNT` subranges. Finding a free region that is large enough and freeing an allocation
+//! are both done in constant time, and adjacent free regions are always merged so that the
+//! fragmentation stays low.
+
+use crate::DeviceSize;
+
+const SL_COUNT_LOG2: u32 = 5;
+const SL_COUNT: usize = 1 << SL_COUNT_LOG2;
+const SMALL_SIZE: DeviceSize = 1 << SL_COUNT_LOG2;
+const FL_COUNT: usize = (DeviceSize::BITS - SL_COUNT_LOG2 + 1) as usize;
+
+const NONE: u32 = u32::MAX;
+
+#[derive(Debug)]
+pub(crate) struct Tlsf {
+    size: DeviceSize,
+    allocated_size: DeviceSize,
+    allocation_count: u32,
+
+    // All regions of the managed range, both free and allocated. Unused entries are listed in
+    // `unused_nodes` and reused.
+    nodes: Vec<Node>,
+    unused_nodes: Vec<u32>,
+
+    // Bit `fl` is set if any of the free lists of the first level index `fl` is non-empty.
+    fl_bitmap: u64,
+    // Bit `sl` of element `fl` is set if the free list `(fl, sl)` is non-empty.
+    sl_bitmaps: [u32; FL_COUNT],
+    // The first node of each free list, indexed by `fl * SL_COUNT + sl`.
+    free_heads: Vec<u32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    offset: DeviceSize,
+    size: DeviceSize,
+    free: bool,
+
+    // The alignment that the region was allocated with, if it's not free.
+    alignment: DeviceSize,
+
+    // Neighbours in the managed range.
+    prev_phys: u32,
+    next_phys: u32,
+
+    // Neighbours in the free list, if the node is free.
+    prev_free: u32,
+    next_free: u32,
+}
+
+/// A region that was allocated from a `Tlsf`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TlsfAlloc {
+    pub(crate) offset: DeviceSize,
+    node: u32,
+}
+
+impl Tlsf {
+    /// Creates a new allocator that manages the range `0..size`.
+    pub(crate) fn new(size: DeviceSize) -> Self {
+        assert!(size != 0);
+
+        let mut tlsf = Tlsf {
+            size,
+            allocated_size: 0,
+            allocation_count: 0,
+            nodes: Vec::new(),
+            unused_nodes: Vec::new(),
+            fl_bitmap: 0,
+            sl_bitmaps: [0; FL_COUNT],
+            free_heads: vec![NONE; FL_COUNT * SL_COUNT],
+        };
+
+        let node = tlsf.new_node(Node {
+            offset: 0,
+            size,
+            free: true,
+            alignment: 0,
+            prev_phys: NONE,
+            next_phys: NONE,
+            prev_free: NONE,
+            next_free: NONE,
+        });
+        tlsf.insert_free(node);
+
+        tlsf
+    }
+
+    /// Returns the size of the managed range.
+    #[inline]
+    pub(crate) fn size(&self) -> DeviceSize {
+        self.size
+    }
+
+    /// Returns the sum of the sizes passed to `allocate` for all current allocations. The padding
+    /// before an allocation to align its offset is not counted.
+    #[inline]
+    pub(crate) fn allocated_size(&self) -> DeviceSize {
+        self.allocated_size
+    }
+
+    /// Returns the number of current allocations.
+    #[inline]
+    pub(crate) fn allocation_count(&self) -> u32 {
+        self.allocation_count
+    }
+
+    /// Returns the size of the largest free region, not accounting for alignment.
+    pub(crate) fn largest_free_size(&self) -> DeviceSize {
+        if self.fl_bitmap == 0 {
+            return 0;
+        }
+
+        // All nodes in the highest non-empty list are at least as large as the nodes in any other
+        // list, so only that list needs to be searched.
+        let fl = (u64::BITS - 1 - self.fl_bitmap.leading_zeros()) as usize;
+        let sl = (u32::BITS - 1 - self.sl_bitmaps[fl].leading_zeros()) as usize;
+        let mut node = self.free_heads[fl * SL_COUNT + sl];
+        let mut largest = 0;
+
+        while node != NONE {
+            let n = &self.nodes[node as usize];
+            largest = largest.max(n.size);
+            node = n.next_free;
+        }
+
+        largest
+    }
+
+    /// Returns true if there are no allocations.
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.allocation_count == 0
+    }
+
+    /// Returns all current allocations, together with their size and the alignment that they were
+    /// allocated with.
+    pub(crate) fn allocations(
+        &self,
+    ) -> impl Iterator<Item = (TlsfAlloc, DeviceSize, DeviceSize)> + '_ {
+        // Nodes that are unused are always marked as free, so they are skipped as well.
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !n.free)
+            .map(|(node, n)| {
+                let alloc = TlsfAlloc {
+                    offset: n.offset,
+                    node: node as u32,
+                };
+                (alloc, n.size, n.alignment)
+            })
+    }
+
+    /// Allocates a region of `size` bytes whose offset is a multiple of `alignment`. Returns
+    /// `None` if there is no free region that is large enough.
+    pub(crate) fn allocate(
+        &mut self,
+        size: DeviceSize,
+        alignment: DeviceSize,
+    ) -> Option<TlsfAlloc> {
+        assert!(size != 0);
+        assert!(alignment != 0);
+
+        if size > self.size {
+            return None;
+        }
+
+        let node = self.find_free(size, alignment)?;
+        self.remove_free(node);
+
+        let Node {
+            offset: node_offset,
+            size: node_size,
+            ..
+        } = self.nodes[node as usize];
+        let offset = align_up(node_offset, alignment);
+
+        // Give the alignment padding at the start back to the free lists.
+        if offset > node_offset {
+            let prev_phys = self.nodes[node as usize].prev_phys;
+            let padding = self.new_node(Node {
+                offset: node_offset,
+                size: offset - node_offset,
+                free: true,
+                alignment: 0,
+                prev_phys,
+                next_phys: node,
+                prev_free: NONE,
+                next_free: NONE,
+            });
+
+            if prev_phys != NONE {
+                self.nodes[prev_phys as usize].next_phys = padding;
+            }
+
+            let node = &mut self.nodes[node as usize];
+            node.prev_phys = padding;
+            node.offset = offset;
+            node.size -= offset - node_offset;
+
+            self.insert_free(padding);
+        }
+
+        // Give the remainder at the end back to the free lists.
+        let end = offset + size;
+        let node_end = node_offset + node_size;
+
+        if end < node_end {
+            let next_phys = self.nodes[node as usize].next_phys;
+            let remainder = self.new_node(Node {
+                offset: end,
+                size: node_end - end,
+                free: true,
+                alignment: 0,
+                prev_phys: node,
+                next_phys,
+                prev_free: NONE,
+                next_free: NONE,
+            });
+
+            if next_phys != NONE {
+                self.nodes[next_phys as usize].prev_phys = remainder;
+            }
+
+            let node = &mut self.nodes[node as usize];
+            node.next_phys = remainder;
+            node.size = size;
+
+            self.insert_free(remainder);
+        }
+
+        self.nodes[node as usize].free = false;
+        self.nodes[node as usize].alignment = alignment;
+        self.allocated_size += size;
+        self.allocation_count += 1;
+
+        Some(TlsfAlloc { offset, node })
+    }
+
+    /// Frees a region that was previously allocated from this allocator.
+    pub(crate) fn free(&mut self, alloc: TlsfAlloc) {
+        let mut node = alloc.node;
+
+        {
+            let n = &mut self.nodes[node as usize];
+            debug_assert!(!n.free && n.offset == alloc.offset);
+            n.free = true;
+            self.allocated_size -= n.size;
+            self.allocation_count -= 1;
+        }
+
+        // Merge with the previous region if it's free.
+        let prev_phys = self.nodes[node as usize].prev_phys;
+
+        if prev_phys != NONE && self.nodes[prev_phys as usize].free {
+            self.remove_free(prev_phys);
+            self.absorb_next(prev_phys);
+            node = prev_phys;
+        }
+
+        // Merge with the next region if it's free.
+        let next_phys = self.nodes[node as usize].next_phys;
+
+        if next_phys != NONE && self.nodes[next_phys as usize].free {
+            self.remove_free(next_phys);
+            self.absorb_next(node);
+        }
+
+        self.insert_free(node);
+    }
+
+    // Merges the node that follows `node` in the managed range into `node`.
+    fn absorb_next(&mut self, node: u32) {
+        let next = self.nodes[node as usize].next_phys;
+        let Node {
+            size: next_size,
+            next_phys: next_next,
+            ..
+        } = self.nodes[next as usize];
+
+        self.nodes[node as usize].size += next_size;
+        self.nodes[node as usize].next_phys = next_next;
+
+        if next_next != NONE {
+            self.nodes[next_next as usize].prev_phys = node;
+        }
+
+        self.unused_nodes.push(next);
+    }
+
+    fn new_node(&mut self, node: Node) -> u32 {
+        match self.unused_nodes.pop() {
+            Some(index) => {
+                self.nodes[index as usize] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                (self.nodes.len() - 1) as u32
+            }
+        }
+    }
+
+    // Finds a free node that can hold `size` bytes at the given alignment.
+    fn find_free(&self, size: DeviceSize, alignment: DeviceSize) -> Option<u32> {
+        // Round up the size to the next list, so that any node of the lists that are searched is
+        // large enough. Only the alignment can then make a node unsuitable.
+        let (mut fl, mut sl) = mapping(round_up_to_list(size));
+
+        while fl < FL_COUNT {
+            let sl_map = self.sl_bitmaps[fl] & (!0u32).checked_shl(sl as u32).unwrap_or(0);
+
+            let (next_fl, next_sl) = if sl_map != 0 {
+                (fl, sl_map.trailing_zeros() as usize)
+            } else {
+                let fl_map = self.fl_bitmap & (!0u64).checked_shl(fl as u32 + 1).unwrap_or(0);
+
+                if fl_map == 0 {
+                    return None;
+                }
+
+                let next_fl = fl_map.trailing_zeros() as usize;
+                (next_fl, self.sl_bitmaps[next_fl].trailing_zeros() as usize)
+            };
+
+            let mut node = self.free_heads[next_fl * SL_COUNT + next_sl];
+
+            while node != NONE {
+                let n = &self.nodes[node as usize];
+
+                if align_up(n.offset, alignment) + size <= n.offset + n.size {
+                    return Some(node);
+                }
+
+                node = n.next_free;
+            }
+
+            fl = next_fl;
+            sl = next_sl + 1;
+
+            if sl == SL_COUNT {
+                fl += 1;
+                sl = 0;
+            }
+        }
+
+        None
+    }
+
+    fn insert_free(&mut self, node: u32) {
+        let (fl, sl) = mapping(self.nodes[node as usize].size);
+        let list = fl * SL_COUNT + sl;
+        let head = self.free_heads[list];
+
+        {
+            let n = &mut self.nodes[node as usize];
+            n.free = true;
+            n.prev_free = NONE;
+            n.next_free = head;
+        }
+
+        if head != NONE {
+            self.nodes[head as usize].prev_free = node;
+        }
+
+        self.free_heads[list] = node;
+        self.fl_bitmap |= 1 << fl;
+        self.sl_bitmaps[fl] |= 1 << sl;
+    }
+
+    fn remove_free(&mut self, node: u32) {
+        let Node {
+            size,
+            prev_free,
+            next_free,
+            ..
+        } = self.nodes[node as usize];
+
+        if prev_free != NONE {
+            self.nodes[prev_free as usize].next_free = next_free;
+        }
+
+        if next_free != NONE {
+            self.nodes[next_free as usize].prev_free = prev_free;
+        }
+
+        let (fl, sl) = mapping(size);
+        let list = fl * SL_COUNT + sl;
+
+        if self.free_heads[list] == node {
+            self.free_heads[list] = next_free;
+
+            if next_free == NONE {
+                self.sl_bitmaps[fl] &= !(1 << sl);
+
+                if self.sl_bitmaps[fl] == 0 {
+                    self.fl_bitmap &= !(1 << fl);
+                }
+            }
+        }
+    }
+}
+
+// Returns the indices of the free list that a region of `size` bytes belongs to.
+#[inline]
+fn mapping(size: DeviceSize) -> (usize, usize) {
+    if size < SMALL_SIZE {
+        (0, size as usize)
+    } else {
+        let log2 = DeviceSize::BITS - 1 - size.leading_zeros();
+        let sl = (size >> (log2 - SL_COUNT_LOG2)) ^ (1 << SL_COUNT_LOG2);
+        ((log2 - SL_COUNT_LOG2 + 1) as usize, sl as usize)
+    }
+}
+
+// Rounds `size` up to the smallest size of the next free list, unless it's already the smallest
+// size of its list.
+#[inline]
+fn round_up_to_list(size: DeviceSize) -> DeviceSize {
+    if size < SMALL_SIZE {
+        size
+    } else {
+        let log2 = DeviceSize::BITS - 1 - size.leading_zeros();
+        let round = (1 << (log2 - SL_COUNT_LOG2)) - 1;
+        size.saturating_add(round)
+    }
+}
+
+#[inline]
+pub(super) fn align_up(value: DeviceSize, alignment: DeviceSize) -> DeviceSize {
+    (value + alignment - 1) / alignment * alignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic() {
+        let mut tlsf = Tlsf::new(1024);
+
+        let a = tlsf.allocate(100, 1).unwrap();
+        let b = tlsf.allocate(200, 1).unwrap();
+        assert_eq!(a.offset, 0);
+        assert_eq!(b.offset, 100);
+        assert_eq!(tlsf.allocated_size(), 300);
+        assert_eq!(tlsf.allocation_count(), 2);
+
+        tlsf.free(a);
+        tlsf.free(b);
+        assert!(tlsf.is_empty());
+
+        // Everything was merged back together.
+        assert_eq!(tlsf.largest_free_size(), 1024);
+        let c = tlsf.allocate(1024, 1).unwrap();
+        assert_eq!(c.offset, 0);
+        assert!(tlsf.allocate(1, 1).is_none());
+    }
+
+    #[test]
+    fn alignment() {
+        let mut tlsf = Tlsf::new(4096);
+
+        let a = tlsf.allocate(3, 1).unwrap();
+        let b = tlsf.allocate(64, 256).unwrap();
+        assert_eq!(b.offset, 256);
+
+        // The padding between the two allocations can still be used.
+        let c = tlsf.allocate(16, 16).unwrap();
+        assert_eq!(c.offset, 16);
+
+        tlsf.free(b);
+        tlsf.free(a);
+        tlsf.free(c);
+        assert_eq!(tlsf.allocate(4096, 4096).unwrap().offset, 0);
+    }
+
+    #[test]
+    fn reuse_freed_region() {
+        let mut tlsf = Tlsf::new(1 << 20);
+
+        let allocs: Vec<_> = (0..64)
+            .map(|_| tlsf.allocate(1 << 14, 256).unwrap())
+            .collect();
+        assert!(tlsf.allocate(1, 1).is_none());
+
+        tlsf.free(allocs[10]);
+        assert_eq!(tlsf.largest_free_size(), 1 << 14);
+        assert_eq!(
+            tlsf.allocate(1 << 14, 256).unwrap().offset,
+            allocs[10].offset
+        );
+        assert!(tlsf.allocate(1, 1).is_none());
+    }
+
+    #[test]
+    fn allocations() {
+        let mut tlsf = Tlsf::new(4096);
+
+        let a = tlsf.allocate(100, 4).unwrap();
+        let b = tlsf.allocate(200, 256).unwrap();
+        let c = tlsf.allocate(300, 16).unwrap();
+        tlsf.free(a);
+
+        let mut allocations: Vec<_> = tlsf.allocations().collect();
+        allocations.sort_by_key(|(alloc, _, _)| alloc.offset);
+        assert_eq!(allocations, [(b, 200, 256), (c, 300, 16)]);
+
+        tlsf.free(b);
+        tlsf.free(c);
+        assert_eq!(tlsf.allocations().count(), 0);
+    }
+
+    #[test]
+    fn stress() {
+        let mut tlsf = Tlsf::new(1 << 24);
+        let mut allocs: Vec<(TlsfAlloc, DeviceSize)> = Vec::new();
+        let mut state = 0x1234_5678_u64;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..10_000 {
+            if allocs.is_empty() || random() % 3 != 0 {
+                let size = random() % 65536 + 1;
+                let alignment = 1 << (random() % 9);
+
+                if let Some(alloc) = tlsf.allocate(size, alignment) {
+                    assert_eq!(alloc.offset % alignment, 0);
+                    assert!(alloc.offset + size <= tlsf.size());
+
+                    for &(other, other_size) in &allocs {
+                        assert!(
+                            alloc.offset + size <= other.offset
+                                || other.offset + other_size <= alloc.offset
+                        );
+                    }
+
+                    allocs.push((alloc, size));
+                }
+            } else {
+                let index = random() as usize % allocs.len();
+                tlsf.free(allocs.swap_remove(index).0);
+            }
+        }
+
+        for (alloc, _) in allocs {
+            tlsf.free(alloc);
+        }
+
+        assert!(tlsf.is_empty());
+        assert_eq!(tlsf.allocate(1 << 24, 1).unwrap().offset, 0);
+    }
+}
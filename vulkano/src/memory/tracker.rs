@@ -0,0 +1,400 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Tracking of device memory allocations, to debug memory usage.
+//!
+//! An [`AllocationTracker`] records every block of device memory that is allocated on a device,
+//! together with its size, its heap, an optional label and optionally the backtrace of where it
+//! was allocated. It is enabled by passing it in
+//! [`DeviceCreateInfo::allocation_tracker`](crate::device::DeviceCreateInfo::allocation_tracker).
+//!
+//! Allocations are labelled by wrapping the code that creates resources in [`with_label`]. The
+//! label applies to all the memory that is allocated on the current thread while the closure
+//! runs, including memory that is allocated by memory pools. Note that a memory pool allocates
+//! memory in large blocks that are shared by many resources, so the label of a block is the label
+//! that was active when the block was allocated.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use vulkano::device::{Device, DeviceCreateInfo, QueueCreateInfo};
+//! use vulkano::memory::tracker::{self, AllocationTracker, AllocationTrackerCreateInfo};
+//! # let physical_device: vulkano::device::physical::PhysicalDevice = return;
+//! # let queue_family: vulkano::device::physical::QueueFamily = return;
+//!
+//! let tracker = Arc::new(AllocationTracker::new(AllocationTrackerCreateInfo {
+//!     capture_backtraces: true,
+//!     ..Default::default()
+//! }));
+//!
+//! let (device, _queues) = Device::new(
+//!     physical_device,
+//!     DeviceCreateInfo {
+//!         queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
+//!         allocation_tracker: Some(tracker.clone()),
+//!         ..Default::default()
+//!     },
+//! )
+//! .unwrap();
+//!
+//! tracker::with_label("terrain", || {
+//!     // Create the buffers and images of the terrain here.
+//! });
+//!
+//! // Prints the totals per label and the ten largest allocations.
+//! println!("{}", tracker.report(10));
+//! ```
+
+use crate::DeviceSize;
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+thread_local! {
+    // The labels that are active on the current thread. The last one is used.
+    static LABELS: RefCell<Vec<Arc<str>>> = RefCell::new(Vec::new());
+}
+
+/// Runs `f`, and gives the label `label` to all the device memory that is allocated on the
+/// current thread while it runs.
+///
+/// Calls to `with_label` can be nested, in which case the innermost label is used.
+pub fn with_label<R>(label: impl Into<Arc<str>>, f: impl FnOnce() -> R) -> R {
+    // Removes the label again when `f` returns or panics.
+    struct LabelGuard;
+
+    impl Drop for LabelGuard {
+        fn drop(&mut self) {
+            LABELS.with(|labels| labels.borrow_mut().pop());
+        }
+    }
+
+    LABELS.with(|labels| labels.borrow_mut().push(label.into()));
+    let _guard = LabelGuard;
+
+    f()
+}
+
+// Returns the label that is currently active on this thread.
+fn current_label() -> Option<Arc<str>> {
+    LABELS.with(|labels| labels.borrow().last().cloned())
+}
+
+/// Records the device memory allocations of a device.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+pub struct AllocationTracker {
+    capture_backtraces: bool,
+    state: Mutex<TrackerState>,
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    next_id: u64,
+    allocations: HashMap<u64, TrackedAllocation>,
+}
+
+impl AllocationTracker {
+    /// Creates a new `AllocationTracker`.
+    #[inline]
+    pub fn new(create_info: AllocationTrackerCreateInfo) -> Self {
+        let AllocationTrackerCreateInfo {
+            capture_backtraces,
+            _ne: _,
+        } = create_info;
+
+        AllocationTracker {
+            capture_backtraces,
+            state: Mutex::new(Default::default()),
+        }
+    }
+
+    /// Returns whether the tracker captures the backtrace of each allocation.
+    #[inline]
+    pub fn capture_backtraces(&self) -> bool {
+        self.capture_backtraces
+    }
+
+    // Records a new allocation, and returns the id that must be passed to `release` when it is
+    // freed.
+    pub(crate) fn record(&self, size: DeviceSize, heap_index: u32, memory_type_index: u32) -> u64 {
+        let backtrace = if self.capture_backtraces {
+            Some(Arc::new(Backtrace::force_capture()))
+        } else {
+            None
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.allocations.insert(
+            id,
+            TrackedAllocation {
+                id,
+                label: current_label(),
+                size,
+                heap_index,
+                memory_type_index,
+                backtrace,
+            },
+        );
+
+        id
+    }
+
+    // Removes an allocation that was freed.
+    pub(crate) fn release(&self, id: u64) {
+        self.state.lock().unwrap().allocations.remove(&id);
+    }
+
+    /// Returns all allocations that are currently alive, in the order in which they were made.
+    pub fn allocations(&self) -> Vec<TrackedAllocation> {
+        let state = self.state.lock().unwrap();
+        let mut allocations: Vec<_> = state.allocations.values().cloned().collect();
+        allocations.sort_by_key(|allocation| allocation.id);
+
+        allocations
+    }
+
+    /// Returns a summary of the allocations that are currently alive, with the
+    /// `largest_count` largest allocations.
+    pub fn report(&self, largest_count: usize) -> AllocationReport {
+        let state = self.state.lock().unwrap();
+        let mut report = AllocationReport {
+            allocation_count: state.allocations.len(),
+            total_bytes: 0,
+            heap_bytes: BTreeMap::new(),
+            labels: Vec::new(),
+            largest: Vec::new(),
+        };
+        let mut labels: HashMap<Option<Arc<str>>, LabelUsage> = HashMap::new();
+
+        for allocation in state.allocations.values() {
+            report.total_bytes += allocation.size;
+            *report.heap_bytes.entry(allocation.heap_index).or_default() += allocation.size;
+
+            let usage = labels
+                .entry(allocation.label.clone())
+                .or_insert_with(|| LabelUsage {
+                    label: allocation.label.clone(),
+                    allocation_count: 0,
+                    bytes: 0,
+                });
+            usage.allocation_count += 1;
+            usage.bytes += allocation.size;
+        }
+
+        report.labels = labels.into_values().collect();
+        report
+            .labels
+            .sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.label.cmp(&b.label)));
+
+        let mut largest: Vec<_> = state.allocations.values().collect();
+        largest.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.id.cmp(&b.id)));
+        report.largest = largest.into_iter().take(largest_count).cloned().collect();
+
+        report
+    }
+
+    /// Returns the allocations that are still alive, as suspected leaks.
+    ///
+    /// This is meant to be called when the application shuts down, after it has dropped the
+    /// device and everything that was created from it. Every allocation keeps its device alive,
+    /// so a device is only destroyed once all of its memory has been freed. Any allocation that
+    /// is returned here therefore kept a device from being destroyed, usually because of a
+    /// reference cycle or an object that was leaked with `mem::forget`. The backtraces of the
+    /// allocations, if they were captured, show where they were made.
+    #[inline]
+    pub fn suspected_leaks(&self) -> Vec<TrackedAllocation> {
+        self.allocations()
+    }
+}
+
+impl Default for AllocationTracker {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+/// Parameters to create a new `AllocationTracker`.
+#[derive(Clone, Debug)]
+pub struct AllocationTrackerCreateInfo {
+    /// Whether to capture the backtrace of every allocation.
+    ///
+    /// Capturing a backtrace is slow, and resolving its symbols when it is printed is even
+    /// slower, so this should only be enabled while debugging.
+    ///
+    /// The default value is `false`.
+    pub capture_backtraces: bool,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for AllocationTrackerCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            capture_backtraces: false,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// A block of device memory that was recorded by an [`AllocationTracker`].
+#[derive(Clone, Debug)]
+pub struct TrackedAllocation {
+    id: u64,
+
+    /// The label that was active when the memory was allocated.
+    pub label: Option<Arc<str>>,
+
+    /// The size of the allocation in bytes.
+    pub size: DeviceSize,
+
+    /// The index of the memory heap that the memory was allocated from.
+    pub heap_index: u32,
+
+    /// The index of the memory type that the memory was allocated from.
+    pub memory_type_index: u32,
+
+    /// Where the memory was allocated, if the tracker captures backtraces.
+    pub backtrace: Option<Arc<Backtrace>>,
+}
+
+impl fmt::Display for TrackedAllocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes from heap {} (memory type {}), label: {}",
+            self.size,
+            self.heap_index,
+            self.memory_type_index,
+            self.label.as_deref().unwrap_or("<none>"),
+        )
+    }
+}
+
+/// The allocations of an [`AllocationTracker`] that have the same label.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabelUsage {
+    /// The label, or `None` for allocations without a label.
+    pub label: Option<Arc<str>>,
+
+    /// The number of allocations with this label.
+    pub allocation_count: usize,
+
+    /// The total size in bytes of the allocations with this label.
+    pub bytes: DeviceSize,
+}
+
+/// A summary of the allocations of an [`AllocationTracker`].
+///
+/// Returned by [`AllocationTracker::report`]. The `Display` implementation prints it in a
+/// human-readable form.
+#[derive(Clone, Debug)]
+pub struct AllocationReport {
+    /// The number of allocations that are alive.
+    pub allocation_count: usize,
+
+    /// The total size in bytes of the allocations that are alive.
+    pub total_bytes: DeviceSize,
+
+    /// The total size in bytes of the allocations that are alive, for each memory heap index.
+    pub heap_bytes: BTreeMap<u32, DeviceSize>,
+
+    /// The totals for each label, from the largest to the smallest.
+    pub labels: Vec<LabelUsage>,
+
+    /// The largest allocations, from the largest to the smallest.
+    pub largest: Vec<TrackedAllocation>,
+}
+
+impl fmt::Display for AllocationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} allocations, {} bytes in total",
+            self.allocation_count, self.total_bytes,
+        )?;
+
+        for (heap_index, bytes) in &self.heap_bytes {
+            writeln!(f, "  heap {}: {} bytes", heap_index, bytes)?;
+        }
+
+        writeln!(f, "per label:")?;
+
+        for usage in &self.labels {
+            writeln!(
+                f,
+                "  {}: {} bytes in {} allocations",
+                usage.label.as_deref().unwrap_or("<none>"),
+                usage.bytes,
+                usage.allocation_count,
+            )?;
+        }
+
+        writeln!(f, "largest allocations:")?;
+
+        for allocation in &self.largest {
+            writeln!(f, "  {}", allocation)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{with_label, AllocationTracker};
+
+    #[test]
+    fn report() {
+        let tracker = AllocationTracker::default();
+
+        let a = with_label("textures", || tracker.record(1024, 0, 0));
+        let _b = with_label("textures", || tracker.record(4096, 0, 0));
+        let _c = tracker.record(256, 1, 2);
+
+        let report = tracker.report(2);
+        assert_eq!(report.allocation_count, 3);
+        assert_eq!(report.total_bytes, 1024 + 4096 + 256);
+        assert_eq!(report.heap_bytes[&0], 1024 + 4096);
+        assert_eq!(report.heap_bytes[&1], 256);
+        assert_eq!(report.labels[0].label.as_deref(), Some("textures"));
+        assert_eq!(report.labels[0].allocation_count, 2);
+        assert_eq!(report.labels[1].label, None);
+        assert_eq!(report.largest.len(), 2);
+        assert_eq!(report.largest[0].size, 4096);
+        assert_eq!(report.largest[1].size, 1024);
+
+        tracker.release(a);
+        assert_eq!(tracker.report(0).total_bytes, 4096 + 256);
+    }
+
+    #[test]
+    fn nested_labels() {
+        let tracker = AllocationTracker::default();
+
+        with_label("outer", || {
+            with_label("inner", || tracker.record(1, 0, 0));
+            tracker.record(2, 0, 0);
+        });
+        tracker.record(3, 0, 0);
+
+        let allocations = tracker.allocations();
+        assert_eq!(allocations[0].label.as_deref(), Some("inner"));
+        assert_eq!(allocations[1].label.as_deref(), Some("outer"));
+        assert_eq!(allocations[2].label, None);
+    }
+}
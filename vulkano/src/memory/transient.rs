@@ -0,0 +1,582 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Transient resources that share memory.
+//!
+//! A frame of a renderer typically uses many intermediate buffers and images, that are each only
+//! needed by a few of its passes. Resources that are never in use at the same time can be placed
+//! in the same memory, which can greatly reduce the amount of memory that is needed.
+//!
+//! A [`TransientAllocator`] is given the resources to create, together with their lifetime: the
+//! range of steps, for example passes of a render graph, during which each resource is in use.
+//! It then places the resources in a single block of device memory, so that resources whose
+//! lifetimes overlap never overlap in memory, while the others may alias each other.
+//!
+//! ```
+//! use vulkano::buffer::{sys::UnsafeBufferCreateInfo, BufferUsage};
+//! use vulkano::format::Format;
+//! use vulkano::image::{sys::UnsafeImageCreateInfo, ImageDimensions, ImageUsage};
+//! use vulkano::memory::transient::TransientAllocator;
+//! # let device: std::sync::Arc<vulkano::device::Device> = return;
+//!
+//! let mut allocator = TransientAllocator::new(device.clone());
+//!
+//! // Used by passes 0 and 1.
+//! let depth = allocator
+//!     .add_image(
+//!         UnsafeImageCreateInfo {
+//!             dimensions: ImageDimensions::Dim2d {
+//!                 width: 1920,
+//!                 height: 1080,
+//!                 array_layers: 1,
+//!             },
+//!             format: Some(Format::D32_SFLOAT),
+//!             usage: ImageUsage {
+//!                 depth_stencil_attachment: true,
+//!                 sampled: true,
+//!                 ..ImageUsage::none()
+//!             },
+//!             ..Default::default()
+//!         },
+//!         0..2,
+//!     )
+//!     .unwrap();
+//!
+//! // Used by passes 2 and 3, so it can share memory with the depth image.
+//! let histogram = allocator
+//!     .add_buffer(
+//!         UnsafeBufferCreateInfo {
+//!             size: 4096,
+//!             usage: BufferUsage::storage_buffer(),
+//!             ..Default::default()
+//!         },
+//!         2..4,
+//!     )
+//!     .unwrap();
+//!
+//! let resources = allocator.allocate().unwrap();
+//! let depth = resources.image(depth);
+//! let histogram = resources.buffer(histogram);
+//! ```
+//!
+//! When a command buffer uses a transient resource whose memory was used by another transient
+//! resource earlier in the same command buffer, a barrier is inserted automatically so that the
+//! accesses of the two resources don't overlap. The contents of a transient resource are not
+//! preserved when another resource that shares its memory is used, and the contents of transient
+//! images are also discarded at the start of each command buffer that uses them. A transient
+//! image must not be used again in a command buffer after another resource that shares its memory
+//! was used in it.
+//!
+//! Barriers are only inserted between the uses in a single command buffer. If resources that
+//! share memory are used in different command buffers, the command buffers must be synchronized
+//! with each other, for example by waiting on a semaphore, or by executing them with a barrier in
+//! between.
+
+use crate::{
+    buffer::{
+        sys::{UnsafeBuffer, UnsafeBufferCreateInfo},
+        BufferCreationError, TransientBuffer,
+    },
+    device::{Device, DeviceOwned},
+    image::{
+        sys::{UnsafeImage, UnsafeImageCreateInfo},
+        ImageCreationError, TransientImage,
+    },
+    memory::{DeviceMemory, DeviceMemoryAllocationError, MemoryAllocateInfo, MemoryRequirements},
+    DeviceSize, OomError,
+};
+use std::{error, fmt, ops::Range, sync::Arc};
+
+/// Places transient resources in a single block of device memory, according to their lifetimes.
+#[derive(Debug)]
+pub struct TransientAllocator {
+    device: Arc<Device>,
+    buffers: Vec<(Arc<UnsafeBuffer>, Range<u32>)>,
+    images: Vec<(Arc<UnsafeImage>, Range<u32>)>,
+}
+
+impl TransientAllocator {
+    /// Creates a new allocator without any resources.
+    #[inline]
+    pub fn new(device: Arc<Device>) -> Self {
+        TransientAllocator {
+            device,
+            buffers: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Adds a buffer that is in use during the steps in `lifetime`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `lifetime` is empty.
+    /// - Panics if `create_info.sparse` is `Some`.
+    pub fn add_buffer(
+        &mut self,
+        create_info: UnsafeBufferCreateInfo,
+        lifetime: Range<u32>,
+    ) -> Result<TransientBufferId, BufferCreationError> {
+        assert!(lifetime.start < lifetime.end);
+        assert!(create_info.sparse.is_none());
+
+        let buffer = UnsafeBuffer::new(self.device.clone(), create_info)?;
+        self.buffers.push((buffer, lifetime));
+
+        Ok(TransientBufferId(self.buffers.len() - 1))
+    }
+
+    /// Adds an image that is in use during the steps in `lifetime`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `lifetime` is empty.
+    /// - Panics if `create_info.sparse` is `Some`.
+    pub fn add_image(
+        &mut self,
+        create_info: UnsafeImageCreateInfo,
+        lifetime: Range<u32>,
+    ) -> Result<TransientImageId, ImageCreationError> {
+        assert!(lifetime.start < lifetime.end);
+        assert!(create_info.sparse.is_none());
+
+        let image = UnsafeImage::new(self.device.clone(), create_info)?;
+        self.images.push((image, lifetime));
+
+        Ok(TransientImageId(self.images.len() - 1))
+    }
+
+    /// Places the resources, allocates the memory and binds the resources to it.
+    ///
+    /// The memory is allocated from a memory type that all resources support, preferring
+    /// device-local memory.
+    pub fn allocate(self) -> Result<TransientResources, TransientAllocationError> {
+        let TransientAllocator {
+            device,
+            buffers,
+            images,
+        } = self;
+
+        let requirements: Vec<(MemoryRequirements, Range<u32>)> = buffers
+            .iter()
+            .map(|(buffer, lifetime)| (buffer.memory_requirements(), lifetime.clone()))
+            .chain(
+                images
+                    .iter()
+                    .map(|(image, lifetime)| (image.memory_requirements(), lifetime.clone())),
+            )
+            .collect();
+
+        if requirements.is_empty() {
+            return Err(TransientAllocationError::NoResources);
+        }
+
+        let memory_type_bits = requirements.iter().fold(!0, |bits, (requirements, _)| {
+            bits & requirements.memory_type_bits
+        });
+        let memory_types: Vec<_> = device
+            .physical_device()
+            .memory_types()
+            .filter(|memory_type| memory_type_bits & (1 << memory_type.id()) != 0)
+            .collect();
+        let memory_type = memory_types
+            .iter()
+            .find(|memory_type| memory_type.is_device_local())
+            .or_else(|| memory_types.first())
+            .copied()
+            .ok_or(TransientAllocationError::NoCompatibleMemoryType)?;
+
+        // Linear and non-linear resources that are in use at the same time must be at least
+        // `buffer_image_granularity` apart. Buffers are linear and images are assumed to be
+        // non-linear, so if there are both, every resource is aligned to the granularity.
+        let granularity = if !buffers.is_empty() && !images.is_empty() {
+            device
+                .physical_device()
+                .properties()
+                .buffer_image_granularity
+        } else {
+            1
+        };
+
+        let (offsets, size) = place(
+            &requirements
+                .iter()
+                .map(|(requirements, lifetime)| {
+                    (
+                        align_up(requirements.size, granularity),
+                        requirements.alignment.max(granularity),
+                        lifetime.clone(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let memory = DeviceMemory::allocate(
+            device,
+            MemoryAllocateInfo {
+                allocation_size: size,
+                memory_type_index: memory_type.id(),
+                ..Default::default()
+            },
+        )?;
+        let placements = requirements
+            .iter()
+            .zip(&offsets)
+            .map(|((requirements, lifetime), &offset)| TransientPlacement {
+                offset,
+                size: requirements.size,
+                lifetime: lifetime.clone(),
+            })
+            .collect();
+        let memory = Arc::new(TransientMemory { memory, placements });
+
+        let mut ranges = memory
+            .placements
+            .iter()
+            .map(|placement| placement.offset..placement.offset + placement.size)
+            .collect::<Vec<_>>()
+            .into_iter();
+        let buffers = buffers
+            .into_iter()
+            .zip(&mut ranges)
+            .map(|((buffer, _), range)| unsafe {
+                buffer.bind_memory(&memory.memory, range.start)?;
+                Ok(TransientBuffer::new(buffer, memory.clone(), range))
+            })
+            .collect::<Result<_, OomError>>()?;
+        let images = images
+            .into_iter()
+            .zip(&mut ranges)
+            .map(|((image, _), range)| unsafe {
+                image.bind_memory(&memory.memory, range.start)?;
+                Ok(TransientImage::new(image, memory.clone(), range))
+            })
+            .collect::<Result<_, OomError>>()?;
+
+        Ok(TransientResources {
+            memory,
+            buffers,
+            images,
+        })
+    }
+}
+
+/// Identifies a buffer that was added to a `TransientAllocator`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TransientBufferId(usize);
+
+/// Identifies an image that was added to a `TransientAllocator`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TransientImageId(usize);
+
+/// The resources that were created by a `TransientAllocator`.
+#[derive(Debug)]
+pub struct TransientResources {
+    memory: Arc<TransientMemory>,
+    buffers: Vec<Arc<TransientBuffer>>,
+    images: Vec<Arc<TransientImage>>,
+}
+
+impl TransientResources {
+    /// Returns the memory that the resources are bound to.
+    #[inline]
+    pub fn memory(&self) -> &Arc<TransientMemory> {
+        &self.memory
+    }
+
+    /// Returns the buffer that was added with the given id.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `id` was returned by a different allocator.
+    #[inline]
+    pub fn buffer(&self, id: TransientBufferId) -> Arc<TransientBuffer> {
+        self.buffers[id.0].clone()
+    }
+
+    /// Returns the image that was added with the given id.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `id` was returned by a different allocator.
+    #[inline]
+    pub fn image(&self, id: TransientImageId) -> Arc<TransientImage> {
+        self.images[id.0].clone()
+    }
+}
+
+/// A block of device memory that is shared by transient resources.
+#[derive(Debug)]
+pub struct TransientMemory {
+    memory: DeviceMemory,
+    // The placement of the buffers, followed by the placement of the images.
+    placements: Vec<TransientPlacement>,
+}
+
+#[derive(Clone, Debug)]
+struct TransientPlacement {
+    offset: DeviceSize,
+    size: DeviceSize,
+    lifetime: Range<u32>,
+}
+
+impl TransientMemory {
+    /// Returns the device memory.
+    #[inline]
+    pub fn memory(&self) -> &DeviceMemory {
+        &self.memory
+    }
+
+    /// Returns the size in bytes of the device memory.
+    #[inline]
+    pub fn size(&self) -> DeviceSize {
+        self.memory.allocation_size()
+    }
+
+    /// Returns the sum of the sizes of all resources. The difference with
+    /// [`size`](Self::size) is the amount of memory that was saved by aliasing.
+    #[inline]
+    pub fn resources_size(&self) -> DeviceSize {
+        self.placements.iter().map(|placement| placement.size).sum()
+    }
+
+    /// Returns the number of pairs of resources that share some of their memory.
+    pub fn alias_count(&self) -> usize {
+        let mut count = 0;
+
+        for (index, a) in self.placements.iter().enumerate() {
+            for b in &self.placements[index + 1..] {
+                if a.offset < b.offset + b.size && b.offset < a.offset + a.size {
+                    debug_assert!(
+                        a.lifetime.end <= b.lifetime.start || b.lifetime.end <= a.lifetime.start
+                    );
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+}
+
+unsafe impl DeviceOwned for TransientMemory {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.memory.device()
+    }
+}
+
+/// The part of a block of transient memory that a resource is bound to.
+///
+/// This is returned by [`BufferAccess::memory_alias`](crate::buffer::BufferAccess::memory_alias)
+/// and [`ImageAccess::memory_alias`](crate::image::ImageAccess::memory_alias), and is used by
+/// command buffers to insert barriers between resources that share memory.
+#[derive(Clone, Debug)]
+pub struct MemoryAlias {
+    pub(crate) memory: Arc<TransientMemory>,
+    pub(crate) range: Range<DeviceSize>,
+}
+
+impl MemoryAlias {
+    /// Returns the memory that the resource is bound to.
+    #[inline]
+    pub fn memory(&self) -> &Arc<TransientMemory> {
+        &self.memory
+    }
+
+    /// Returns the range of the memory that the resource is bound to.
+    #[inline]
+    pub fn range(&self) -> &Range<DeviceSize> {
+        &self.range
+    }
+}
+
+/// Error that can happen when allocating transient resources.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransientAllocationError {
+    /// Allocating the device memory failed.
+    AllocError(DeviceMemoryAllocationError),
+
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// No resources were added to the allocator.
+    NoResources,
+
+    /// There is no memory type that all of the resources support.
+    NoCompatibleMemoryType,
+}
+
+impl error::Error for TransientAllocationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::AllocError(ref err) => Some(err),
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TransientAllocationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::AllocError(_) => write!(fmt, "allocating the device memory failed"),
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::NoResources => write!(fmt, "no resources were added to the allocator"),
+            Self::NoCompatibleMemoryType => write!(
+                fmt,
+                "there is no memory type that all of the resources support",
+            ),
+        }
+    }
+}
+
+impl From<DeviceMemoryAllocationError> for TransientAllocationError {
+    #[inline]
+    fn from(err: DeviceMemoryAllocationError) -> Self {
+        Self::AllocError(err)
+    }
+}
+
+impl From<OomError> for TransientAllocationError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+// Places resources with the given size, alignment and lifetime, so that resources whose lifetimes
+// overlap don't overlap in memory. Returns the offset of each resource and the total size.
+//
+// The resources are placed from the largest to the smallest, each at the lowest offset where it
+// doesn't overlap with the resources that were already placed and that are alive at the same
+// time.
+fn place(resources: &[(DeviceSize, DeviceSize, Range<u32>)]) -> (Vec<DeviceSize>, DeviceSize) {
+    let mut order: Vec<usize> = (0..resources.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(resources[index].0));
+
+    let mut offsets = vec![0; resources.len()];
+    let mut placed: Vec<usize> = Vec::with_capacity(resources.len());
+    let mut total = 0;
+
+    for index in order {
+        let (size, alignment, ref lifetime) = resources[index];
+
+        let mut occupied: Vec<Range<DeviceSize>> = placed
+            .iter()
+            .filter(|&&other| {
+                let other_lifetime = &resources[other].2;
+                lifetime.start < other_lifetime.end && other_lifetime.start < lifetime.end
+            })
+            .map(|&other| offsets[other]..offsets[other] + resources[other].0)
+            .collect();
+        occupied.sort_by_key(|range| range.start);
+
+        let mut offset = 0;
+
+        for range in occupied {
+            if offset + size <= range.start {
+                break;
+            }
+
+            offset = offset.max(align_up(range.end, alignment));
+        }
+
+        offsets[index] = offset;
+        placed.push(index);
+        total = total.max(offset + size);
+    }
+
+    (offsets, total)
+}
+
+#[inline]
+fn align_up(value: DeviceSize, alignment: DeviceSize) -> DeviceSize {
+    (value + alignment - 1) / alignment * alignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{place, TransientAllocationError, TransientAllocator};
+    use crate::buffer::{sys::UnsafeBufferCreateInfo, BufferAccess, BufferUsage};
+
+    #[test]
+    fn place_disjoint_lifetimes() {
+        let (offsets, size) = place(&[(1024, 256, 0..2), (512, 256, 2..4), (256, 256, 4..5)]);
+
+        // Nothing is alive at the same time, so everything shares the same memory.
+        assert_eq!(offsets, [0, 0, 0]);
+        assert_eq!(size, 1024);
+    }
+
+    #[test]
+    fn place_overlapping_lifetimes() {
+        let (offsets, size) = place(&[(1024, 256, 0..2), (512, 256, 1..3), (300, 256, 2..4)]);
+
+        // The first two are alive at the same time, the third one can reuse the memory of the
+        // first one but not of the second one.
+        assert_eq!(offsets[0], 0);
+        assert_eq!(offsets[1], 1024);
+        assert_eq!(offsets[2], 0);
+        assert_eq!(size, 1536);
+    }
+
+    #[test]
+    fn place_alignment() {
+        let (offsets, size) = place(&[(1000, 1, 0..2), (100, 256, 1..2)]);
+
+        assert_eq!(offsets, [0, 1024]);
+        assert_eq!(size, 1124);
+    }
+
+    #[test]
+    fn no_resources() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match TransientAllocator::new(device).allocate() {
+            Err(TransientAllocationError::NoResources) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn buffers_alias() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let mut allocator = TransientAllocator::new(device);
+        let a = allocator
+            .add_buffer(
+                UnsafeBufferCreateInfo {
+                    size: 4096,
+                    usage: BufferUsage::storage_buffer(),
+                    ..Default::default()
+                },
+                0..1,
+            )
+            .unwrap();
+        let b = allocator
+            .add_buffer(
+                UnsafeBufferCreateInfo {
+                    size: 4096,
+                    usage: BufferUsage::storage_buffer(),
+                    ..Default::default()
+                },
+                1..2,
+            )
+            .unwrap();
+        let resources = allocator.allocate().unwrap();
+
+        assert_eq!(resources.memory().alias_count(), 1);
+        assert!(resources.memory().size() < resources.memory().resources_size());
+        assert_eq!(resources.buffer(a).size(), 4096);
+        assert_eq!(resources.buffer(b).size(), 4096);
+    }
+}
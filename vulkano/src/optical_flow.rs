@@ -0,0 +1,792 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Hardware-accelerated estimation of the motion between two images.
+//!
+//! Optical flow compares an *input* image with a *reference* image, and writes the estimated
+//! motion of each region of the input image into a *flow vector* image. This can be used for
+//! frame interpolation, motion estimation for video encoding, temporal upscaling and similar
+//! workloads.
+//!
+//! The work is done by an [`OpticalFlowSession`], which fixes the dimensions and formats of the
+//! images that are compared. The images are bound to the session with
+//! [`bind_image`](OpticalFlowSession::bind_image), and the motion is then estimated by recording
+//! the [`optical_flow_execute`] command into a command buffer. The command buffer must be
+//! submitted to a queue whose family supports optical flow operations, which can be checked with
+//! [`QueueFamily::supports_optical_flow`].
+//!
+//! Optical flow requires the [`nv_optical_flow`](crate::device::DeviceExtensions::nv_optical_flow)
+//! extension and the [`optical_flow`](crate::device::Features::optical_flow) feature to be enabled
+//! on the device.
+//!
+//! [`optical_flow_execute`]: crate::command_buffer::AutoCommandBufferBuilder::optical_flow_execute
+//! [`QueueFamily::supports_optical_flow`]: crate::device::physical::QueueFamily::supports_optical_flow
+
+use crate::{
+    check_errors,
+    device::{physical::OpticalFlowGridSizes, Device, DeviceOwned},
+    format::Format,
+    image::{view::ImageViewAbstract, ImageLayout},
+    pipeline::graphics::viewport::Scissor,
+    Error, OomError, VulkanObject,
+};
+use parking_lot::Mutex;
+use smallvec::SmallVec;
+use std::{
+    collections::HashMap,
+    error, fmt,
+    hash::{Hash, Hasher},
+    mem::MaybeUninit,
+    ptr,
+    sync::Arc,
+};
+
+/// An optical flow session, which estimates the motion between images of a fixed size and
+/// format.
+#[derive(Debug)]
+pub struct OpticalFlowSession {
+    handle: ash::vk::OpticalFlowSessionNV,
+    device: Arc<Device>,
+
+    width: u32,
+    height: u32,
+    image_format: Format,
+    flow_vector_format: Format,
+    cost_format: Option<Format>,
+    output_grid_size: OpticalFlowGridSize,
+    hint_grid_size: Option<OpticalFlowGridSize>,
+    performance_level: OpticalFlowPerformanceLevel,
+    enable_global_flow: bool,
+    allow_regions: bool,
+    both_directions: bool,
+
+    bound_images: Mutex<HashMap<OpticalFlowSessionBindingPoint, Arc<dyn ImageViewAbstract>>>,
+}
+
+impl OpticalFlowSession {
+    /// Creates a new `OpticalFlowSession`.
+    pub fn new(
+        device: Arc<Device>,
+        create_info: OpticalFlowSessionCreateInfo,
+    ) -> Result<Arc<OpticalFlowSession>, OpticalFlowSessionCreationError> {
+        Self::validate(&device, &create_info)?;
+
+        let OpticalFlowSessionCreateInfo {
+            width,
+            height,
+            image_format,
+            flow_vector_format,
+            cost_format,
+            output_grid_size,
+            hint_grid_size,
+            performance_level,
+            enable_global_flow,
+            allow_regions,
+            both_directions,
+            _ne: _,
+        } = create_info;
+
+        let mut flags = ash::vk::OpticalFlowSessionCreateFlagsNV::empty();
+
+        if hint_grid_size.is_some() {
+            flags |= ash::vk::OpticalFlowSessionCreateFlagsNV::ENABLE_HINT;
+        }
+
+        if cost_format.is_some() {
+            flags |= ash::vk::OpticalFlowSessionCreateFlagsNV::ENABLE_COST;
+        }
+
+        if enable_global_flow {
+            flags |= ash::vk::OpticalFlowSessionCreateFlagsNV::ENABLE_GLOBAL_FLOW;
+        }
+
+        if allow_regions {
+            flags |= ash::vk::OpticalFlowSessionCreateFlagsNV::ALLOW_REGIONS;
+        }
+
+        if both_directions {
+            flags |= ash::vk::OpticalFlowSessionCreateFlagsNV::BOTH_DIRECTIONS;
+        }
+
+        let create_info = ash::vk::OpticalFlowSessionCreateInfoNV {
+            width,
+            height,
+            image_format: image_format.into(),
+            flow_vector_format: flow_vector_format.into(),
+            cost_format: cost_format.map_or(ash::vk::Format::UNDEFINED, Into::into),
+            output_grid_size: output_grid_size.into(),
+            hint_grid_size: hint_grid_size
+                .map_or(ash::vk::OpticalFlowGridSizeFlagsNV::UNKNOWN, Into::into),
+            performance_level: performance_level.into(),
+            flags,
+            ..Default::default()
+        };
+
+        let handle = unsafe {
+            let fns = device.fns();
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns.nv_optical_flow.create_optical_flow_session_nv)(
+                device.internal_object(),
+                &create_info,
+                ptr::null(),
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
+        };
+
+        Ok(Arc::new(OpticalFlowSession {
+            handle,
+            device,
+
+            width,
+            height,
+            image_format,
+            flow_vector_format,
+            cost_format,
+            output_grid_size,
+            hint_grid_size,
+            performance_level,
+            enable_global_flow,
+            allow_regions,
+            both_directions,
+
+            bound_images: Mutex::new(HashMap::default()),
+        }))
+    }
+
+    fn validate(
+        device: &Device,
+        create_info: &OpticalFlowSessionCreateInfo,
+    ) -> Result<(), OpticalFlowSessionCreationError> {
+        if !device.enabled_extensions().nv_optical_flow {
+            return Err(OpticalFlowSessionCreationError::ExtensionNotEnabled {
+                extension: "nv_optical_flow",
+                reason: "tried to create an optical flow session",
+            });
+        }
+
+        if !device.enabled_features().optical_flow {
+            return Err(OpticalFlowSessionCreationError::FeatureNotEnabled {
+                feature: "optical_flow",
+                reason: "tried to create an optical flow session",
+            });
+        }
+
+        let &OpticalFlowSessionCreateInfo {
+            width,
+            height,
+            image_format: _,
+            flow_vector_format: _,
+            cost_format,
+            output_grid_size,
+            hint_grid_size,
+            performance_level: _,
+            enable_global_flow,
+            allow_regions: _,
+            both_directions,
+            _ne: _,
+        } = create_info;
+
+        let properties = device.physical_device().properties();
+
+        let min = [
+            properties.min_width.unwrap_or(0),
+            properties.min_height.unwrap_or(0),
+        ];
+        let max = [
+            properties.max_width.unwrap_or(0),
+            properties.max_height.unwrap_or(0),
+        ];
+
+        if width < min[0] || width > max[0] || height < min[1] || height > max[1] {
+            return Err(OpticalFlowSessionCreationError::DimensionsOutOfRange {
+                dimensions: [width, height],
+                min,
+                max,
+            });
+        }
+
+        if !properties
+            .supported_output_grid_sizes
+            .map_or(false, |sizes| output_grid_size.is_contained_in(&sizes))
+        {
+            return Err(
+                OpticalFlowSessionCreationError::OutputGridSizeNotSupported {
+                    grid_size: output_grid_size,
+                },
+            );
+        }
+
+        if let Some(hint_grid_size) = hint_grid_size {
+            if !properties.hint_supported.unwrap_or(false) {
+                return Err(OpticalFlowSessionCreationError::HintNotSupported);
+            }
+
+            if !properties
+                .supported_hint_grid_sizes
+                .map_or(false, |sizes| hint_grid_size.is_contained_in(&sizes))
+            {
+                return Err(OpticalFlowSessionCreationError::HintGridSizeNotSupported {
+                    grid_size: hint_grid_size,
+                });
+            }
+        }
+
+        if cost_format.is_some() && !properties.cost_supported.unwrap_or(false) {
+            return Err(OpticalFlowSessionCreationError::CostNotSupported);
+        }
+
+        if enable_global_flow && !properties.global_flow_supported.unwrap_or(false) {
+            return Err(OpticalFlowSessionCreationError::GlobalFlowNotSupported);
+        }
+
+        if both_directions && !properties.bidirectional_flow_supported.unwrap_or(false) {
+            return Err(OpticalFlowSessionCreationError::BidirectionalFlowNotSupported);
+        }
+
+        // TODO: The formats must be among those returned by
+        // `vkGetPhysicalDeviceOpticalFlowImageFormatsNV` for the corresponding usage.
+
+        Ok(())
+    }
+
+    /// Binds an image view to a binding point of the session.
+    ///
+    /// The image view is kept alive by the session until another image view is bound to the same
+    /// binding point, or until the session is destroyed. The image must be in the
+    /// [`General`](ImageLayout::General) layout when the session is executed, which is taken care
+    /// of by the command buffer that executes the session.
+    ///
+    /// # Safety
+    ///
+    /// - The session must not be in use by a command buffer that is pending execution.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `image_view` doesn't belong to the same device as the session.
+    pub unsafe fn bind_image(
+        &self,
+        binding_point: OpticalFlowSessionBindingPoint,
+        image_view: Arc<dyn ImageViewAbstract>,
+    ) -> Result<(), OomError> {
+        assert_eq!(self.device, *image_view.device());
+
+        let mut bound_images = self.bound_images.lock();
+
+        let fns = self.device.fns();
+        check_errors((fns.nv_optical_flow.bind_optical_flow_session_image_nv)(
+            self.device.internal_object(),
+            self.handle,
+            binding_point.into(),
+            image_view.internal_object(),
+            ImageLayout::General.into(),
+        ))?;
+
+        bound_images.insert(binding_point, image_view);
+
+        Ok(())
+    }
+
+    /// Returns the image view that is bound to `binding_point`, if any.
+    #[inline]
+    pub fn bound_image(
+        &self,
+        binding_point: OpticalFlowSessionBindingPoint,
+    ) -> Option<Arc<dyn ImageViewAbstract>> {
+        self.bound_images.lock().get(&binding_point).cloned()
+    }
+
+    /// Returns all the image views that are currently bound to the session.
+    #[inline]
+    pub(crate) fn bound_images(
+        &self,
+    ) -> SmallVec<[(OpticalFlowSessionBindingPoint, Arc<dyn ImageViewAbstract>); 4]> {
+        self.bound_images
+            .lock()
+            .iter()
+            .map(|(&binding_point, image_view)| (binding_point, image_view.clone()))
+            .collect()
+    }
+
+    /// Returns the width of the images that the session compares.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the images that the session compares.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the format of the input and reference images.
+    #[inline]
+    pub fn image_format(&self) -> Format {
+        self.image_format
+    }
+
+    /// Returns the format of the flow vector images.
+    #[inline]
+    pub fn flow_vector_format(&self) -> Format {
+        self.flow_vector_format
+    }
+
+    /// Returns the format of the cost images, if the session outputs costs.
+    #[inline]
+    pub fn cost_format(&self) -> Option<Format> {
+        self.cost_format
+    }
+
+    /// Returns the size of the grid that the flow vectors are estimated for.
+    #[inline]
+    pub fn output_grid_size(&self) -> OpticalFlowGridSize {
+        self.output_grid_size
+    }
+
+    /// Returns the grid size of the hint images, if the session accepts hints.
+    #[inline]
+    pub fn hint_grid_size(&self) -> Option<OpticalFlowGridSize> {
+        self.hint_grid_size
+    }
+
+    /// Returns the performance level that the session was created with.
+    #[inline]
+    pub fn performance_level(&self) -> OpticalFlowPerformanceLevel {
+        self.performance_level
+    }
+
+    /// Returns whether the session outputs a global flow vector.
+    #[inline]
+    pub fn enable_global_flow(&self) -> bool {
+        self.enable_global_flow
+    }
+
+    /// Returns whether the session can be executed on regions of interest.
+    #[inline]
+    pub fn allow_regions(&self) -> bool {
+        self.allow_regions
+    }
+
+    /// Returns whether the session also estimates the flow from the reference image to the input
+    /// image.
+    #[inline]
+    pub fn both_directions(&self) -> bool {
+        self.both_directions
+    }
+}
+
+impl Drop for OpticalFlowSession {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let fns = self.device.fns();
+            (fns.nv_optical_flow.destroy_optical_flow_session_nv)(
+                self.device.internal_object(),
+                self.handle,
+                ptr::null(),
+            );
+        }
+    }
+}
+
+unsafe impl VulkanObject for OpticalFlowSession {
+    type Object = ash::vk::OpticalFlowSessionNV;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::OpticalFlowSessionNV {
+        self.handle
+    }
+}
+
+unsafe impl DeviceOwned for OpticalFlowSession {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl PartialEq for OpticalFlowSession {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle && self.device() == other.device()
+    }
+}
+
+impl Eq for OpticalFlowSession {}
+
+impl Hash for OpticalFlowSession {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state);
+        self.device().hash(state);
+    }
+}
+
+/// Parameters to create a new `OpticalFlowSession`.
+#[derive(Clone, Debug)]
+pub struct OpticalFlowSessionCreateInfo {
+    /// The width of the input and reference images.
+    ///
+    /// The default value is `0`, which must be overridden.
+    pub width: u32,
+
+    /// The height of the input and reference images.
+    ///
+    /// The default value is `0`, which must be overridden.
+    pub height: u32,
+
+    /// The format of the input and reference images.
+    ///
+    /// There is no default value.
+    pub image_format: Format,
+
+    /// The format of the flow vector images.
+    ///
+    /// The default value is [`Format::R16G16_S10_5_NV`].
+    pub flow_vector_format: Format,
+
+    /// If `Some`, the session also outputs the cost of each flow vector, in images of the given
+    /// format.
+    ///
+    /// The default value is `None`.
+    pub cost_format: Option<Format>,
+
+    /// The size of the grid that the flow vectors are estimated for. Each flow vector covers a
+    /// block of pixels of this size.
+    ///
+    /// The default value is [`OpticalFlowGridSize::Size4x4`].
+    pub output_grid_size: OpticalFlowGridSize,
+
+    /// If `Some`, the session accepts external hints for the flow vectors, in images with the
+    /// given grid size.
+    ///
+    /// The default value is `None`.
+    pub hint_grid_size: Option<OpticalFlowGridSize>,
+
+    /// The tradeoff between speed and quality that the implementation should make.
+    ///
+    /// The default value is [`OpticalFlowPerformanceLevel::Medium`].
+    pub performance_level: OpticalFlowPerformanceLevel,
+
+    /// Whether the session outputs a global flow vector, which estimates the motion of the whole
+    /// image.
+    ///
+    /// The default value is `false`.
+    pub enable_global_flow: bool,
+
+    /// Whether the session can be executed on regions of interest instead of on the whole image.
+    ///
+    /// The default value is `false`.
+    pub allow_regions: bool,
+
+    /// Whether the session also estimates the flow from the reference image to the input image.
+    ///
+    /// The default value is `false`.
+    pub both_directions: bool,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl OpticalFlowSessionCreateInfo {
+    /// Returns an `OpticalFlowSessionCreateInfo` with the specified `image_format`.
+    #[inline]
+    pub fn image_format(image_format: Format) -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            image_format,
+            flow_vector_format: Format::R16G16_S10_5_NV,
+            cost_format: None,
+            output_grid_size: OpticalFlowGridSize::Size4x4,
+            hint_grid_size: None,
+            performance_level: OpticalFlowPerformanceLevel::Medium,
+            enable_global_flow: false,
+            allow_regions: false,
+            both_directions: false,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The size of the blocks of pixels that optical flow vectors are estimated for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum OpticalFlowGridSize {
+    Size1x1 = ash::vk::OpticalFlowGridSizeFlagsNV::TYPE_1X1.as_raw(),
+    Size2x2 = ash::vk::OpticalFlowGridSizeFlagsNV::TYPE_2X2.as_raw(),
+    Size4x4 = ash::vk::OpticalFlowGridSizeFlagsNV::TYPE_4X4.as_raw(),
+    Size8x8 = ash::vk::OpticalFlowGridSizeFlagsNV::TYPE_8X8.as_raw(),
+}
+
+impl OpticalFlowGridSize {
+    /// Returns whether `self` is one of the grid sizes in `sizes`.
+    #[inline]
+    pub fn is_contained_in(&self, sizes: &OpticalFlowGridSizes) -> bool {
+        match self {
+            Self::Size1x1 => sizes.size_1x1,
+            Self::Size2x2 => sizes.size_2x2,
+            Self::Size4x4 => sizes.size_4x4,
+            Self::Size8x8 => sizes.size_8x8,
+        }
+    }
+}
+
+impl From<OpticalFlowGridSize> for ash::vk::OpticalFlowGridSizeFlagsNV {
+    #[inline]
+    fn from(val: OpticalFlowGridSize) -> Self {
+        Self::from_raw(val as u32)
+    }
+}
+
+/// The tradeoff between speed and quality of an optical flow session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum OpticalFlowPerformanceLevel {
+    Slow = ash::vk::OpticalFlowPerformanceLevelNV::SLOW.as_raw(),
+    Medium = ash::vk::OpticalFlowPerformanceLevelNV::MEDIUM.as_raw(),
+    Fast = ash::vk::OpticalFlowPerformanceLevelNV::FAST.as_raw(),
+}
+
+impl From<OpticalFlowPerformanceLevel> for ash::vk::OpticalFlowPerformanceLevelNV {
+    #[inline]
+    fn from(val: OpticalFlowPerformanceLevel) -> Self {
+        Self::from_raw(val as i32)
+    }
+}
+
+/// A binding point of an optical flow session, that an image view can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum OpticalFlowSessionBindingPoint {
+    /// The image whose motion is estimated. Read by the session.
+    Input = ash::vk::OpticalFlowSessionBindingPointNV::INPUT.as_raw(),
+
+    /// The image that the input image is compared with. Read by the session.
+    Reference = ash::vk::OpticalFlowSessionBindingPointNV::REFERENCE.as_raw(),
+
+    /// The external hints for the flow vectors. Read by the session.
+    Hint = ash::vk::OpticalFlowSessionBindingPointNV::HINT.as_raw(),
+
+    /// The estimated flow vectors. Written by the session.
+    FlowVector = ash::vk::OpticalFlowSessionBindingPointNV::FLOW_VECTOR.as_raw(),
+
+    /// The estimated flow vectors from the reference image to the input image. Written by the
+    /// session.
+    BackwardFlowVector = ash::vk::OpticalFlowSessionBindingPointNV::BACKWARD_FLOW_VECTOR.as_raw(),
+
+    /// The cost of each flow vector. Written by the session.
+    Cost = ash::vk::OpticalFlowSessionBindingPointNV::COST.as_raw(),
+
+    /// The cost of each backward flow vector. Written by the session.
+    BackwardCost = ash::vk::OpticalFlowSessionBindingPointNV::BACKWARD_COST.as_raw(),
+
+    /// The global flow vector. Written by the session.
+    GlobalFlow = ash::vk::OpticalFlowSessionBindingPointNV::GLOBAL_FLOW.as_raw(),
+}
+
+impl OpticalFlowSessionBindingPoint {
+    /// Returns whether the image bound to this binding point is written by the session.
+    #[inline]
+    pub fn is_output(&self) -> bool {
+        !matches!(self, Self::Input | Self::Reference | Self::Hint)
+    }
+}
+
+impl From<OpticalFlowSessionBindingPoint> for ash::vk::OpticalFlowSessionBindingPointNV {
+    #[inline]
+    fn from(val: OpticalFlowSessionBindingPoint) -> Self {
+        Self::from_raw(val as i32)
+    }
+}
+
+/// Parameters to execute an optical flow session.
+#[derive(Clone, Debug)]
+pub struct OpticalFlowExecuteInfo {
+    /// Whether the implementation should not use the flow vectors of previous executions of the
+    /// session as hints. This should be set when the input images are not consecutive frames of
+    /// the same sequence.
+    ///
+    /// The default value is `false`.
+    pub disable_temporal_hints: bool,
+
+    /// The regions of interest to estimate the flow for. If empty, the flow is estimated for the
+    /// whole image.
+    ///
+    /// If not empty, the session must have been created with
+    /// [`allow_regions`](OpticalFlowSessionCreateInfo::allow_regions) enabled.
+    ///
+    /// The default value is empty.
+    pub regions: SmallVec<[Scissor; 2]>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for OpticalFlowExecuteInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            disable_temporal_hints: false,
+            regions: SmallVec::new(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Error that can happen when creating an optical flow session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OpticalFlowSessionCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// Bidirectional flow was requested, but it is not supported by the physical device.
+    BidirectionalFlowNotSupported,
+
+    /// A cost format was provided, but cost output is not supported by the physical device.
+    CostNotSupported,
+
+    /// The width or height is outside the range supported by the physical device.
+    DimensionsOutOfRange {
+        dimensions: [u32; 2],
+        min: [u32; 2],
+        max: [u32; 2],
+    },
+
+    /// A global flow vector was requested, but it is not supported by the physical device.
+    GlobalFlowNotSupported,
+
+    /// The hint grid size is not supported by the physical device.
+    HintGridSizeNotSupported { grid_size: OpticalFlowGridSize },
+
+    /// A hint grid size was provided, but hints are not supported by the physical device.
+    HintNotSupported,
+
+    /// The output grid size is not supported by the physical device.
+    OutputGridSizeNotSupported { grid_size: OpticalFlowGridSize },
+}
+
+impl error::Error for OpticalFlowSessionCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::OomError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OpticalFlowSessionCreationError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::OomError(_) => write!(f, "not enough memory available"),
+            Self::ExtensionNotEnabled { extension, reason } => {
+                write!(f, "the extension {} must be enabled: {}", extension, reason)
+            }
+            Self::FeatureNotEnabled { feature, reason } => {
+                write!(f, "the feature {} must be enabled: {}", feature, reason)
+            }
+            Self::BidirectionalFlowNotSupported => write!(
+                f,
+                "bidirectional flow was requested, but it is not supported by the physical device",
+            ),
+            Self::CostNotSupported => write!(
+                f,
+                "a cost format was provided, but cost output is not supported by the physical \
+                device",
+            ),
+            Self::DimensionsOutOfRange {
+                dimensions,
+                min,
+                max,
+            } => write!(
+                f,
+                "the dimensions {:?} are outside the range supported by the physical device \
+                ({:?} to {:?})",
+                dimensions, min, max,
+            ),
+            Self::GlobalFlowNotSupported => write!(
+                f,
+                "a global flow vector was requested, but it is not supported by the physical \
+                device",
+            ),
+            Self::HintGridSizeNotSupported { grid_size } => write!(
+                f,
+                "the hint grid size {:?} is not supported by the physical device",
+                grid_size,
+            ),
+            Self::HintNotSupported => write!(
+                f,
+                "a hint grid size was provided, but hints are not supported by the physical \
+                device",
+            ),
+            Self::OutputGridSizeNotSupported { grid_size } => write!(
+                f,
+                "the output grid size {:?} is not supported by the physical device",
+                grid_size,
+            ),
+        }
+    }
+}
+
+impl From<OomError> for OpticalFlowSessionCreationError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+impl From<Error> for OpticalFlowSessionCreationError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            err @ Error::OutOfHostMemory => Self::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => Self::OomError(OomError::from(err)),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        OpticalFlowSession, OpticalFlowSessionCreateInfo, OpticalFlowSessionCreationError,
+    };
+    use crate::format::Format;
+
+    #[test]
+    fn extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match OpticalFlowSession::new(
+            device,
+            OpticalFlowSessionCreateInfo {
+                width: 64,
+                height: 64,
+                ..OpticalFlowSessionCreateInfo::image_format(Format::R8_UNORM)
+            },
+        ) {
+            Err(OpticalFlowSessionCreationError::ExtensionNotEnabled { .. }) => (),
+            _ => panic!(),
+        }
+    }
+}
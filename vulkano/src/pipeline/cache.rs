@@ -20,14 +20,27 @@
 //! Once that is done, you can extract the data from the cache and store it. See the documentation
 //! of [`get_data`](crate::pipeline::cache::PipelineCache::get_data) for example of how to store the data
 //! on the disk, and [`with_data`](crate::pipeline::cache::PipelineCache::with_data) for how to reload it.
+//!
+//! The data returned by `get_data` is trusted by the Vulkan implementation when it is reloaded, so
+//! `with_data` is unsafe. For a safe round-trip, use [`save`](PipelineCache::save) and
+//! [`load`](PipelineCache::load) instead. `save` appends a checksum to the data, and `load`
+//! verifies the checksum and the [header](PipelineCacheHeader) of the data before handing it to
+//! the implementation, so that corrupted data or data from another device or driver version is
+//! rejected.
 
 use crate::check_errors;
 use crate::device::Device;
+use crate::device::DeviceOwned;
+use crate::Error;
 use crate::OomError;
 use crate::Success;
 use crate::VulkanObject;
+use parking_lot::Mutex;
+use std::error;
+use std::fmt;
 use std::mem::MaybeUninit;
 use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 /// Opaque cache that contains pipeline objects.
@@ -37,6 +50,12 @@ use std::sync::Arc;
 pub struct PipelineCache {
     device: Arc<Device>,
     cache: ash::vk::PipelineCache,
+
+    // `vkMergePipelineCaches` requires the destination cache to be externally synchronized.
+    merge_lock: Mutex<()>,
+
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 impl PipelineCache {
@@ -51,7 +70,10 @@ impl PipelineCache {
     ///
     /// This example loads a cache from a file, if it exists.
     /// See [`get_data`](#method.get_data) for how to store the data in a file.
-    /// TODO: there's a header in the cached data that must be checked ; talk about this
+    ///
+    /// The header of the data can be checked against the device with
+    /// [`validate_header`](PipelineCache::validate_header) first, but the rest of the data is
+    /// still trusted. Use [`load`](PipelineCache::load) to also detect corrupted data.
     ///
     /// ```
     /// # use std::sync::Arc;
@@ -132,9 +154,106 @@ impl PipelineCache {
         Ok(Arc::new(PipelineCache {
             device: device.clone(),
             cache: cache,
+
+            merge_lock: Mutex::new(()),
+
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
         }))
     }
 
+    /// Builds a new pipeline cache from data that was previously obtained with
+    /// [`save`](PipelineCache::save).
+    ///
+    /// The checksum that `save` appended to the data is verified, and the header of the data is
+    /// checked against `device` with [`validate_header`](PipelineCache::validate_header). If
+    /// either check fails, an error is returned and no cache is created. Callers will usually want
+    /// to fall back to an [empty](PipelineCache::empty) cache in that case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use vulkano::device::Device;
+    /// use std::fs;
+    /// use vulkano::pipeline::cache::PipelineCache;
+    /// # let device: Arc<Device> = return;
+    ///
+    /// let cache = fs::read("pipeline_cache.bin")
+    ///     .ok()
+    ///     .and_then(|data| PipelineCache::load(device.clone(), &data).ok())
+    ///     .unwrap_or_else(|| PipelineCache::empty(device.clone()).unwrap());
+    /// ```
+    pub fn load(
+        device: Arc<Device>,
+        data: &[u8],
+    ) -> Result<Arc<PipelineCache>, PipelineCacheDataError> {
+        if data.len() < CHECKSUM_SIZE {
+            return Err(PipelineCacheDataError::DataTooSmall);
+        }
+
+        let (data, checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+
+        if checksum != fnv1a(data).to_le_bytes() {
+            return Err(PipelineCacheDataError::ChecksumMismatch);
+        }
+
+        PipelineCache::validate_header(&device, data)?;
+
+        // Safety: the header matches the device, and the checksum guarantees that the data is the
+        // data that the implementation returned from `save`.
+        Ok(unsafe { PipelineCache::new_impl(device, Some(data))? })
+    }
+
+    /// Parses the header of data that was obtained with [`get_data`](PipelineCache::get_data),
+    /// and checks that it is compatible with `device`.
+    ///
+    /// Data is only compatible with a device if it was produced by a device with the same vendor
+    /// ID, device ID and pipeline cache UUID. The UUID changes whenever the driver changes the
+    /// layout of its cache data, for example after a driver update.
+    pub fn validate_header(
+        device: &Device,
+        data: &[u8],
+    ) -> Result<PipelineCacheHeader, PipelineCacheDataError> {
+        let header = PipelineCacheHeader::parse(data)?;
+        let properties = device.physical_device().properties();
+
+        if header.vendor_id != properties.vendor_id {
+            return Err(PipelineCacheDataError::VendorIdMismatch {
+                provided: header.vendor_id,
+                expected: properties.vendor_id,
+            });
+        }
+
+        if header.device_id != properties.device_id {
+            return Err(PipelineCacheDataError::DeviceIdMismatch {
+                provided: header.device_id,
+                expected: properties.device_id,
+            });
+        }
+
+        if header.pipeline_cache_uuid != properties.pipeline_cache_uuid {
+            return Err(PipelineCacheDataError::PipelineCacheUuidMismatch);
+        }
+
+        Ok(header)
+    }
+
+    /// Builds a new pipeline cache that contains the contents of all of `caches`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if one of `caches` doesn't belong to `device`.
+    pub fn from_merged<'a>(
+        device: Arc<Device>,
+        caches: impl IntoIterator<Item = &'a Arc<PipelineCache>>,
+    ) -> Result<Arc<PipelineCache>, OomError> {
+        let cache = PipelineCache::empty(device)?;
+        let caches: Vec<_> = caches.into_iter().collect();
+        cache.merge(&caches)?;
+        Ok(cache)
+    }
+
     /// Merges other pipeline caches into this one.
     ///
     /// It is `self` that is modified here. The pipeline caches passed as parameter are untouched.
@@ -142,9 +261,23 @@ impl PipelineCache {
     /// # Panic
     ///
     /// - Panics if `self` is included in the list of other pipelines.
+    /// - Panics if one of the other pipeline caches doesn't belong to the same device as `self`.
+    ///
+    /// # Example
+    ///
+    /// This example merges the caches that were filled by several threads into a single cache,
+    /// which can then be stored.
     ///
-    // FIXME: vkMergePipelineCaches is not thread safe for the destination cache
-    // TODO: write example
+    /// ```
+    /// # use std::sync::Arc;
+    /// use vulkano::pipeline::cache::PipelineCache;
+    /// # let main_cache: Arc<PipelineCache> = return;
+    /// # let thread_caches: Vec<Arc<PipelineCache>> = return;
+    ///
+    /// let thread_caches: Vec<&Arc<PipelineCache>> = thread_caches.iter().collect();
+    /// main_cache.merge(&thread_caches).unwrap();
+    /// let data = main_cache.save().unwrap();
+    /// ```
     pub fn merge<'a, I>(&self, pipelines: I) -> Result<(), OomError>
     where
         I: IntoIterator<Item = &'a &'a Arc<PipelineCache>>,
@@ -156,10 +289,12 @@ impl PipelineCache {
                 .into_iter()
                 .map(|pipeline| {
                     assert!(&***pipeline as *const _ != &*self as *const _);
+                    assert_eq!(self.device, pipeline.device);
                     pipeline.cache
                 })
                 .collect::<Vec<_>>();
 
+            let _lock = self.merge_lock.lock();
             check_errors((fns.v1_0.merge_pipeline_caches)(
                 self.device.internal_object(),
                 self.cache,
@@ -229,6 +364,52 @@ impl PipelineCache {
 
         Ok(data)
     }
+
+    /// Obtains the data from the cache, followed by a checksum of the data.
+    ///
+    /// This data can be stored and then reloaded and passed to
+    /// [`PipelineCache::load`](PipelineCache::load).
+    pub fn save(&self) -> Result<Vec<u8>, OomError> {
+        let mut data = self.get_data()?;
+        let checksum = fnv1a(&data);
+        data.extend_from_slice(&checksum.to_le_bytes());
+        Ok(data)
+    }
+
+    /// Returns how many pipelines were found or not found in the cache when they were created.
+    ///
+    /// Only pipelines that were created with cache statistics enabled are counted. See
+    /// [`GraphicsPipelineBuilder::record_cache_statistics`].
+    ///
+    /// [`GraphicsPipelineBuilder::record_cache_statistics`]: crate::pipeline::graphics::GraphicsPipelineBuilder::record_cache_statistics
+    #[inline]
+    pub fn statistics(&self) -> PipelineCacheStatistics {
+        PipelineCacheStatistics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records whether the creation of a pipeline found it in the cache.
+    #[inline]
+    pub(crate) fn record_lookup(&self, hit: bool) {
+        self.device
+            .statistics_counters()
+            .record_pipeline_cache_lookup(hit);
+
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+unsafe impl DeviceOwned for PipelineCache {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
 }
 
 unsafe impl VulkanObject for PipelineCache {
@@ -254,9 +435,181 @@ impl Drop for PipelineCache {
     }
 }
 
+/// The header at the start of the data of a pipeline cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PipelineCacheHeader {
+    /// The version of the header. Only version 1 is currently defined.
+    pub header_version: u32,
+
+    /// The vendor ID of the physical device that produced the data.
+    pub vendor_id: u32,
+
+    /// The device ID of the physical device that produced the data.
+    pub device_id: u32,
+
+    /// The pipeline cache UUID of the physical device that produced the data.
+    pub pipeline_cache_uuid: [u8; 16],
+}
+
+impl PipelineCacheHeader {
+    /// Parses the header at the start of the data of a pipeline cache.
+    pub fn parse(data: &[u8]) -> Result<PipelineCacheHeader, PipelineCacheDataError> {
+        // The fields of the header are little-endian, whatever the byte order of the host.
+        let read_u32 = |offset: usize| {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&data[offset..offset + 4]);
+            u32::from_le_bytes(bytes)
+        };
+
+        if data.len() < HEADER_VERSION_ONE_SIZE {
+            return Err(PipelineCacheDataError::DataTooSmall);
+        }
+
+        let header_size = read_u32(0);
+        let header_version = read_u32(4);
+
+        if header_version != ash::vk::PipelineCacheHeaderVersion::ONE.as_raw() as u32 {
+            return Err(PipelineCacheDataError::HeaderVersionNotSupported { header_version });
+        }
+
+        if (header_size as usize) < HEADER_VERSION_ONE_SIZE || header_size as usize > data.len() {
+            return Err(PipelineCacheDataError::HeaderSizeInvalid { header_size });
+        }
+
+        let mut pipeline_cache_uuid = [0; 16];
+        pipeline_cache_uuid.copy_from_slice(&data[16..32]);
+
+        Ok(PipelineCacheHeader {
+            header_version,
+            vendor_id: read_u32(8),
+            device_id: read_u32(12),
+            pipeline_cache_uuid,
+        })
+    }
+}
+
+/// The number of pipelines that were found or not found in a pipeline cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PipelineCacheStatistics {
+    /// The number of pipelines that were found in the cache.
+    pub hits: u64,
+
+    /// The number of pipelines that were not found in the cache.
+    pub misses: u64,
+}
+
+// The size of `VkPipelineCacheHeaderVersionOne`.
+const HEADER_VERSION_ONE_SIZE: usize = 32;
+
+// The size of the checksum appended by `PipelineCache::save`.
+const CHECKSUM_SIZE: usize = 8;
+
+// 64-bit FNV-1a hash, used as the checksum of saved cache data.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Error that can happen when loading the data of a pipeline cache.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PipelineCacheDataError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The checksum at the end of the data doesn't match the data.
+    ChecksumMismatch,
+
+    /// The data is too small to contain a header.
+    DataTooSmall,
+
+    /// The data was produced by a device with a different device ID.
+    DeviceIdMismatch { provided: u32, expected: u32 },
+
+    /// The header size stored in the data is smaller than the header or larger than the data.
+    HeaderSizeInvalid { header_size: u32 },
+
+    /// The header version stored in the data is not supported.
+    HeaderVersionNotSupported { header_version: u32 },
+
+    /// The data was produced by a device or driver with a different pipeline cache UUID.
+    PipelineCacheUuidMismatch,
+
+    /// The data was produced by a device with a different vendor ID.
+    VendorIdMismatch { provided: u32, expected: u32 },
+}
+
+impl error::Error for PipelineCacheDataError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::OomError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PipelineCacheDataError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::OomError(_) => write!(f, "not enough memory available"),
+            Self::ChecksumMismatch => {
+                write!(
+                    f,
+                    "the checksum at the end of the data doesn't match the data"
+                )
+            }
+            Self::DataTooSmall => write!(f, "the data is too small to contain a header"),
+            Self::DeviceIdMismatch { provided, expected } => write!(
+                f,
+                "the data was produced by a device with device ID {:#x}, but the device has ID \
+                {:#x}",
+                provided, expected,
+            ),
+            Self::HeaderSizeInvalid { header_size } => write!(
+                f,
+                "the header size {} is smaller than the header or larger than the data",
+                header_size,
+            ),
+            Self::HeaderVersionNotSupported { header_version } => {
+                write!(f, "the header version {} is not supported", header_version,)
+            }
+            Self::PipelineCacheUuidMismatch => write!(
+                f,
+                "the data was produced by a device or driver with a different pipeline cache UUID",
+            ),
+            Self::VendorIdMismatch { provided, expected } => write!(
+                f,
+                "the data was produced by a device with vendor ID {:#x}, but the device has ID \
+                {:#x}",
+                provided, expected,
+            ),
+        }
+    }
+}
+
+impl From<OomError> for PipelineCacheDataError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+impl From<Error> for PipelineCacheDataError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            err @ Error::OutOfHostMemory => Self::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => Self::OomError(OomError::from(err)),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::pipeline::cache::PipelineCache;
+    use crate::pipeline::cache::{PipelineCache, PipelineCacheDataError};
     use crate::pipeline::ComputePipeline;
     use crate::shader::ShaderModule;
     use std::sync::Arc;
@@ -270,6 +623,56 @@ mod tests {
         });
     }
 
+    #[test]
+    fn save_load_round_trip() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cache = PipelineCache::empty(device.clone()).unwrap();
+        let data = cache.save().unwrap();
+
+        match PipelineCache::load(device.clone(), &data) {
+            Ok(loaded) => assert_eq!(loaded.get_data().unwrap(), cache.get_data().unwrap()),
+            // Some implementations return no data at all for an empty cache.
+            Err(PipelineCacheDataError::DataTooSmall) => assert_eq!(data.len(), 8),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn load_corrupted() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let cache = PipelineCache::empty(device.clone()).unwrap();
+        let mut data = cache.save().unwrap();
+        data[0] ^= 1;
+
+        assert_eq!(
+            PipelineCache::load(device.clone(), &data).unwrap_err(),
+            PipelineCacheDataError::ChecksumMismatch,
+        );
+    }
+
+    #[test]
+    fn validate_header_mismatch() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let properties = device.physical_device().properties();
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&32u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&properties.vendor_id.to_le_bytes());
+        data.extend_from_slice(&properties.device_id.to_le_bytes());
+        data.extend_from_slice(&properties.pipeline_cache_uuid);
+
+        assert!(PipelineCache::validate_header(&device, &data).is_ok());
+
+        data[31] ^= 1;
+        assert_eq!(
+            PipelineCache::validate_header(&device, &data),
+            Err(PipelineCacheDataError::PipelineCacheUuidMismatch),
+        );
+    }
+
     #[test]
     fn cache_returns_same_data() {
         let (device, queue) = gfx_dev_and_queue!();
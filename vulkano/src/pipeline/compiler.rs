@@ -0,0 +1,538 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Compiling pipelines in the background.
+//!
+//! Creating a pipeline can take a long time, especially if the implementation doesn't find it in
+//! its caches. Creating pipelines while rendering therefore causes stutter. A
+//! [`PipelineCompiler`] owns a pool of threads that pipelines can be created on instead, all
+//! sharing the same [`PipelineCache`].
+//!
+//! Because pipeline builders borrow the shader entry points that they use, a pipeline is
+//! described to the compiler by a closure that builds it. The closure is given a
+//! [`PipelineCompileContext`], which contains the cache to build the pipeline with.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use vulkano::device::Device;
+//! # use vulkano::pipeline::ComputePipeline;
+//! # use vulkano::pipeline::compiler::{PipelineCompiler, PipelineCompilerCreateInfo};
+//! # use vulkano::shader::ShaderModule;
+//! # fn example(device: Arc<Device>, module: Arc<ShaderModule>) {
+//! let compiler = PipelineCompiler::new(device.clone(), Default::default()).unwrap();
+//!
+//! let handle = compiler.compile(move |context| {
+//!     ComputePipeline::new(
+//!         device.clone(),
+//!         module.entry_point("main").unwrap(),
+//!         &(),
+//!         Some(context.cache().clone()),
+//!         |_| {},
+//!     )
+//! });
+//!
+//! // Do other work here...
+//!
+//! let pipeline = handle.wait().unwrap();
+//! # }
+//! ```
+//!
+//! # Fallback pipelines
+//!
+//! With [`compile_or_defer`](PipelineCompiler::compile_or_defer), the pipeline is first created
+//! on the current thread with `fail_on_compile_required` set, which is fast if the pipeline is
+//! found in the caches. Only if it isn't, the pipeline is compiled in the background, and a
+//! simpler fallback pipeline can be used until it is ready. This requires the
+//! [`pipeline_creation_cache_control`](crate::device::Features::pipeline_creation_cache_control)
+//! feature to be enabled on the device.
+
+use super::{
+    cache::PipelineCache, compute::ComputePipelineCreationError,
+    graphics::GraphicsPipelineCreationError,
+};
+use crate::{
+    device::{Device, DeviceOwned},
+    OomError,
+};
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex},
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle},
+};
+
+/// Compiles pipelines on a pool of threads.
+///
+/// When the compiler is dropped, the pipelines that are still queued are compiled before the
+/// threads exit.
+pub struct PipelineCompiler {
+    device: Arc<Device>,
+    cache: Arc<PipelineCache>,
+    queue: Arc<JobQueue>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl PipelineCompiler {
+    /// Creates a new `PipelineCompiler` and starts its threads.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.thread_count` is 0.
+    /// - Panics if `create_info.cache` was not created from `device`.
+    /// - Panics if a thread could not be spawned.
+    pub fn new(
+        device: Arc<Device>,
+        create_info: PipelineCompilerCreateInfo,
+    ) -> Result<Arc<PipelineCompiler>, OomError> {
+        let PipelineCompilerCreateInfo {
+            thread_count,
+            cache,
+            _ne: _,
+        } = create_info;
+
+        assert!(thread_count != 0);
+
+        let cache = match cache {
+            Some(cache) => {
+                assert_eq!(cache.device(), &device);
+                cache
+            }
+            None => PipelineCache::empty(device.clone())?,
+        };
+
+        let queue = Arc::new(JobQueue {
+            state: Mutex::new(JobQueueState {
+                jobs: VecDeque::new(),
+                shutdown: false,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        let threads = (0..thread_count)
+            .map(|index| {
+                let queue = queue.clone();
+
+                thread::Builder::new()
+                    .name(format!("vulkano-pipeline-compiler-{}", index))
+                    .spawn(move || queue.run())
+                    .expect("failed to spawn a pipeline compiler thread")
+            })
+            .collect();
+
+        Ok(Arc::new(PipelineCompiler {
+            device,
+            cache,
+            queue,
+            threads,
+        }))
+    }
+
+    /// Returns the pipeline cache that all pipelines are compiled with.
+    #[inline]
+    pub fn cache(&self) -> &Arc<PipelineCache> {
+        &self.cache
+    }
+
+    /// Returns the number of threads that pipelines are compiled on.
+    #[inline]
+    pub fn thread_count(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Returns the number of pipelines that are queued, and that no thread has started compiling
+    /// yet.
+    #[inline]
+    pub fn queued_count(&self) -> usize {
+        self.queue.state.lock().unwrap().jobs.len()
+    }
+
+    /// Queues a pipeline to be compiled on one of the threads of the compiler.
+    ///
+    /// `build` is called with a context whose
+    /// [`fail_on_compile_required`](PipelineCompileContext::fail_on_compile_required) is `false`.
+    pub fn compile<T, E, F>(&self, build: F) -> PipelineCompileHandle<T, E>
+    where
+        T: Send + 'static,
+        E: Send + 'static,
+        F: FnOnce(&PipelineCompileContext) -> Result<T, E> + Send + 'static,
+    {
+        let slot = Arc::new(ResultSlot {
+            state: Mutex::new(ResultSlotState {
+                result: None,
+                panicked: false,
+                waker: None,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        let context = PipelineCompileContext {
+            cache: self.cache.clone(),
+            fail_on_compile_required: false,
+        };
+
+        let job_slot = slot.clone();
+        self.queue.push(Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| build(&context)));
+            job_slot.complete(result.ok());
+        }));
+
+        PipelineCompileHandle { slot }
+    }
+
+    /// Creates a pipeline on the current thread if the implementation finds it in its caches,
+    /// and otherwise queues it to be compiled on one of the threads of the compiler.
+    ///
+    /// `build` is first called on the current thread with a context whose
+    /// [`fail_on_compile_required`](PipelineCompileContext::fail_on_compile_required) is `true`.
+    /// If that returns an error for which
+    /// [`is_compile_required`](PipelineCompileError::is_compile_required) is true, `build` is
+    /// called again on a compiler thread with `fail_on_compile_required` set to `false`.
+    ///
+    /// The [`pipeline_creation_cache_control`](crate::device::Features::pipeline_creation_cache_control)
+    /// feature must be enabled on the device.
+    pub fn compile_or_defer<T, E, F>(&self, build: F) -> PipelineCompileStatus<T, E>
+    where
+        T: Send + 'static,
+        E: PipelineCompileError + Send + 'static,
+        F: Fn(&PipelineCompileContext) -> Result<T, E> + Send + 'static,
+    {
+        let context = PipelineCompileContext {
+            cache: self.cache.clone(),
+            fail_on_compile_required: true,
+        };
+
+        match build(&context) {
+            Err(err) if err.is_compile_required() => {
+                PipelineCompileStatus::Pending(self.compile(build))
+            }
+            result => PipelineCompileStatus::Ready(result),
+        }
+    }
+}
+
+impl Drop for PipelineCompiler {
+    fn drop(&mut self) {
+        {
+            let mut state = self.queue.state.lock().unwrap();
+            state.shutdown = true;
+        }
+
+        self.queue.condvar.notify_all();
+
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+unsafe impl DeviceOwned for PipelineCompiler {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl fmt::Debug for PipelineCompiler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PipelineCompiler")
+            .field("device", &self.device)
+            .field("cache", &self.cache)
+            .field("thread_count", &self.threads.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parameters to create a new `PipelineCompiler`.
+#[derive(Clone, Debug)]
+pub struct PipelineCompilerCreateInfo {
+    /// The number of threads to compile pipelines on.
+    ///
+    /// The default value is one less than the available parallelism of the system, and at
+    /// least 1.
+    pub thread_count: usize,
+
+    /// The pipeline cache to compile all pipelines with.
+    ///
+    /// If `None`, a new empty cache is created.
+    ///
+    /// The default value is `None`.
+    pub cache: Option<Arc<PipelineCache>>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for PipelineCompilerCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            thread_count: thread::available_parallelism()
+                .map(|count| count.get().saturating_sub(1).max(1))
+                .unwrap_or(1),
+            cache: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The context that a pipeline is built in by a [`PipelineCompiler`].
+#[derive(Clone, Debug)]
+pub struct PipelineCompileContext {
+    cache: Arc<PipelineCache>,
+    fail_on_compile_required: bool,
+}
+
+impl PipelineCompileContext {
+    /// Returns the pipeline cache that the pipeline should be built with.
+    #[inline]
+    pub fn cache(&self) -> &Arc<PipelineCache> {
+        &self.cache
+    }
+
+    /// Returns the value that `fail_on_compile_required` should be set to when building the
+    /// pipeline.
+    #[inline]
+    pub fn fail_on_compile_required(&self) -> bool {
+        self.fail_on_compile_required
+    }
+}
+
+/// Errors that can tell whether a pipeline failed to build because it needed to be compiled.
+pub trait PipelineCompileError {
+    /// Returns whether the error indicates that the pipeline needed to be compiled, and
+    /// `fail_on_compile_required` was set.
+    fn is_compile_required(&self) -> bool;
+}
+
+impl PipelineCompileError for GraphicsPipelineCreationError {
+    #[inline]
+    fn is_compile_required(&self) -> bool {
+        matches!(self, Self::PipelineCompileRequired)
+    }
+}
+
+impl PipelineCompileError for ComputePipelineCreationError {
+    #[inline]
+    fn is_compile_required(&self) -> bool {
+        matches!(self, Self::PipelineCompileRequired)
+    }
+}
+
+/// The result of [`PipelineCompiler::compile_or_defer`].
+#[derive(Debug)]
+pub enum PipelineCompileStatus<T, E> {
+    /// The pipeline was built on the current thread, or building it failed.
+    Ready(Result<T, E>),
+
+    /// The pipeline needs to be compiled, and is queued on the compiler.
+    Pending(PipelineCompileHandle<T, E>),
+}
+
+/// A handle to a pipeline that is being compiled by a [`PipelineCompiler`].
+///
+/// The result can be retrieved by polling it with [`try_take`](Self::try_take), by blocking
+/// with [`wait`](Self::wait), or by awaiting the handle.
+///
+/// # Panics
+///
+/// - Retrieving the result panics if building the pipeline panicked.
+pub struct PipelineCompileHandle<T, E> {
+    slot: Arc<ResultSlot<T, E>>,
+}
+
+impl<T, E> PipelineCompileHandle<T, E> {
+    /// Returns whether the pipeline has finished building.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        let state = self.slot.state.lock().unwrap();
+        state.result.is_some() || state.panicked
+    }
+
+    /// Returns the result of building the pipeline if it has finished building, or `None`
+    /// otherwise.
+    ///
+    /// After the result has been returned once, this returns `None`.
+    #[inline]
+    pub fn try_take(&mut self) -> Option<Result<T, E>> {
+        let mut state = self.slot.state.lock().unwrap();
+        state.take()
+    }
+
+    /// Blocks the current thread until the pipeline has finished building, and returns the
+    /// result.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the result was already taken with [`try_take`](Self::try_take).
+    pub fn wait(self) -> Result<T, E> {
+        let mut state = self.slot.state.lock().unwrap();
+
+        loop {
+            if let Some(result) = state.take() {
+                return result;
+            }
+
+            state = self.slot.condvar.wait(state).unwrap();
+        }
+    }
+}
+
+impl<T, E> Future for PipelineCompileHandle<T, E> {
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.slot.state.lock().unwrap();
+
+        match state.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T, E> fmt::Debug for PipelineCompileHandle<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PipelineCompileHandle")
+            .field("is_ready", &self.is_ready())
+            .finish()
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct JobQueue {
+    state: Mutex<JobQueueState>,
+    condvar: Condvar,
+}
+
+struct JobQueueState {
+    jobs: VecDeque<Job>,
+    shutdown: bool,
+}
+
+impl JobQueue {
+    fn push(&self, job: Job) {
+        self.state.lock().unwrap().jobs.push_back(job);
+        self.condvar.notify_one();
+    }
+
+    // The loop that each thread of the compiler runs. Queued jobs are drained before the thread
+    // exits on shutdown.
+    fn run(&self) {
+        loop {
+            let job = {
+                let mut state = self.state.lock().unwrap();
+
+                loop {
+                    if let Some(job) = state.jobs.pop_front() {
+                        break job;
+                    }
+
+                    if state.shutdown {
+                        return;
+                    }
+
+                    state = self.condvar.wait(state).unwrap();
+                }
+            };
+
+            job();
+        }
+    }
+}
+
+struct ResultSlot<T, E> {
+    state: Mutex<ResultSlotState<T, E>>,
+    condvar: Condvar,
+}
+
+struct ResultSlotState<T, E> {
+    result: Option<Result<T, E>>,
+    panicked: bool,
+    waker: Option<Waker>,
+}
+
+impl<T, E> ResultSlotState<T, E> {
+    fn take(&mut self) -> Option<Result<T, E>> {
+        if self.panicked {
+            panic!("building a pipeline on a PipelineCompiler thread panicked");
+        }
+
+        self.result.take()
+    }
+}
+
+impl<T, E> ResultSlot<T, E> {
+    // Stores the result of a job, or `None` if the job panicked, and wakes up whoever is waiting
+    // on it.
+    fn complete(&self, result: Option<Result<T, E>>) {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+
+            match result {
+                Some(result) => state.result = Some(result),
+                None => state.panicked = true,
+            }
+
+            state.waker.take()
+        };
+
+        self.condvar.notify_all();
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PipelineCompileStatus, PipelineCompiler, PipelineCompilerCreateInfo};
+
+    #[test]
+    fn compile_on_threads() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let compiler = PipelineCompiler::new(
+            device,
+            PipelineCompilerCreateInfo {
+                thread_count: 2,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(compiler.thread_count(), 2);
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| compiler.compile(move |_| Ok::<_, ()>(i)))
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.wait().unwrap()).collect();
+        assert_eq!(results, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn compile_or_defer_ready() {
+        let (device, _) = gfx_dev_and_queue!();
+        let compiler = PipelineCompiler::new(device, Default::default()).unwrap();
+
+        match compiler.compile_or_defer(|context| {
+            assert!(context.fail_on_compile_required());
+            Err::<(), _>(crate::pipeline::compute::ComputePipelineCreationError::IncompatibleSpecializationConstants)
+        }) {
+            PipelineCompileStatus::Ready(Err(_)) => (),
+            _ => panic!(),
+        }
+    }
+}
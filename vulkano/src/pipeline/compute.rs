@@ -29,15 +29,22 @@ use crate::descriptor_set::layout::{
 };
 use crate::device::{Device, DeviceOwned};
 use crate::pipeline::cache::PipelineCache;
+use crate::pipeline::executable::{
+    self, PipelineExecutableCapture, PipelineExecutableError,
+    PipelineExecutableInternalRepresentation, PipelineExecutableProperties,
+    PipelineExecutableStatistic,
+};
 use crate::pipeline::layout::{
     PipelineLayout, PipelineLayoutCreationError, PipelineLayoutSupersetError,
 };
-use crate::pipeline::{Pipeline, PipelineBindPoint};
+use crate::pipeline::{Pipeline, PipelineBindPoint, PipelineRobustness};
 use crate::shader::{DescriptorRequirements, EntryPoint, SpecializationConstants};
 use crate::DeviceSize;
 use crate::Error;
 use crate::OomError;
+use crate::Success;
 use crate::VulkanObject;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::error;
 use std::fmt;
@@ -60,6 +67,7 @@ pub struct ComputePipeline {
     layout: Arc<PipelineLayout>,
     descriptor_requirements: HashMap<(u32, u32), DescriptorRequirements>,
     num_used_descriptor_sets: u32,
+    executable_capture: PipelineExecutableCapture,
 }
 
 impl ComputePipeline {
@@ -125,6 +133,91 @@ impl ComputePipeline {
     where
         Css: SpecializationConstants,
     {
+        ComputePipeline::with_options(
+            device,
+            shader,
+            specialization_constants,
+            layout,
+            cache,
+            ComputePipelineOptions::default(),
+        )
+    }
+
+    /// Same as `with_pipeline_layout`, but also captures information about the executables of
+    /// the pipeline, so that it can be queried later with
+    /// [`executable_statistics`](Self::executable_statistics) and
+    /// [`executable_internal_representations`](Self::executable_internal_representations).
+    ///
+    /// If any information is captured, the
+    /// [`pipeline_executable_info`](crate::device::Features::pipeline_executable_info) feature
+    /// must be enabled on the device.
+    pub fn with_executable_capture<Css>(
+        device: Arc<Device>,
+        shader: EntryPoint,
+        specialization_constants: &Css,
+        layout: Arc<PipelineLayout>,
+        cache: Option<Arc<PipelineCache>>,
+        executable_capture: PipelineExecutableCapture,
+    ) -> Result<Arc<ComputePipeline>, ComputePipelineCreationError>
+    where
+        Css: SpecializationConstants,
+    {
+        ComputePipeline::with_options(
+            device,
+            shader,
+            specialization_constants,
+            layout,
+            cache,
+            ComputePipelineOptions {
+                executable_capture,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Same as `with_pipeline_layout`, but with additional options for the pipeline.
+    pub fn with_options<Css>(
+        device: Arc<Device>,
+        shader: EntryPoint,
+        specialization_constants: &Css,
+        layout: Arc<PipelineLayout>,
+        cache: Option<Arc<PipelineCache>>,
+        options: ComputePipelineOptions,
+    ) -> Result<Arc<ComputePipeline>, ComputePipelineCreationError>
+    where
+        Css: SpecializationConstants,
+    {
+        let ComputePipelineOptions {
+            executable_capture,
+            robustness,
+            fail_on_compile_required,
+            debug_name: _,
+            _ne: _,
+        } = options;
+
+        if (executable_capture.statistics || executable_capture.internal_representations)
+            && !device.enabled_features().pipeline_executable_info
+        {
+            return Err(ComputePipelineCreationError::FeatureNotEnabled {
+                feature: "pipeline_executable_info",
+                reason: "executable information was requested to be captured",
+            });
+        }
+
+        if let Some(feature) = robustness.missing_feature(&device) {
+            return Err(ComputePipelineCreationError::FeatureNotEnabled {
+                feature,
+                reason: "ComputePipelineOptions::robustness was set to a non-default value",
+            });
+        }
+
+        if fail_on_compile_required && !device.enabled_features().pipeline_creation_cache_control {
+            return Err(ComputePipelineCreationError::FeatureNotEnabled {
+                feature: "pipeline_creation_cache_control",
+                reason: "ComputePipelineOptions::fail_on_compile_required was set",
+            });
+        }
+
         let spec_descriptors = Css::descriptors();
 
         for (constant_id, reqs) in shader.specialization_constant_requirements() {
@@ -144,12 +237,13 @@ impl ComputePipeline {
         )?;
 
         unsafe {
-            ComputePipeline::with_unchecked_pipeline_layout(
+            ComputePipeline::new_impl(
                 device,
                 shader,
                 specialization_constants,
                 layout,
                 cache,
+                options,
             )
         }
     }
@@ -166,6 +260,35 @@ impl ComputePipeline {
     where
         Css: SpecializationConstants,
     {
+        ComputePipeline::new_impl(
+            device,
+            shader,
+            specialization_constants,
+            layout,
+            cache,
+            ComputePipelineOptions::default(),
+        )
+    }
+
+    unsafe fn new_impl<Css>(
+        device: Arc<Device>,
+        shader: EntryPoint,
+        specialization_constants: &Css,
+        layout: Arc<PipelineLayout>,
+        cache: Option<Arc<PipelineCache>>,
+        options: ComputePipelineOptions,
+    ) -> Result<Arc<ComputePipeline>, ComputePipelineCreationError>
+    where
+        Css: SpecializationConstants,
+    {
+        let ComputePipelineOptions {
+            executable_capture,
+            robustness,
+            fail_on_compile_required,
+            debug_name,
+            _ne: _,
+        } = options;
+
         let fns = device.fns();
 
         let handle = {
@@ -177,7 +300,9 @@ impl ComputePipeline {
                 p_data: specialization_constants as *const Css as *const _,
             };
 
-            let stage = ash::vk::PipelineShaderStageCreateInfo {
+            let module_identifier_info = shader.module().module_identifier_create_info_vk();
+
+            let mut stage = ash::vk::PipelineShaderStageCreateInfo {
                 flags: ash::vk::PipelineShaderStageCreateFlags::empty(),
                 stage: ash::vk::ShaderStageFlags::COMPUTE,
                 module: shader.module().internal_object(),
@@ -190,8 +315,19 @@ impl ComputePipeline {
                 ..Default::default()
             };
 
-            let infos = ash::vk::ComputePipelineCreateInfo {
-                flags: ash::vk::PipelineCreateFlags::empty(),
+            let mut flags = executable_capture.into();
+
+            if fail_on_compile_required {
+                flags |= ash::vk::PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED;
+            }
+
+            if let Some(info) = module_identifier_info.as_ref() {
+                stage.p_next = info as *const _ as *const _;
+                flags |= ash::vk::PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED;
+            }
+
+            let mut infos = ash::vk::ComputePipelineCreateInfo {
+                flags,
                 stage,
                 layout: layout.internal_object(),
                 base_pipeline_handle: ash::vk::Pipeline::null(),
@@ -199,13 +335,20 @@ impl ComputePipeline {
                 ..Default::default()
             };
 
+            let robustness_vk = (!robustness.is_device_default())
+                .then(|| ash::vk::PipelineRobustnessCreateInfoEXT::from(robustness));
+
+            if let Some(info) = robustness_vk.as_ref() {
+                infos.p_next = info as *const _ as *const _;
+            }
+
             let cache_handle = match cache {
                 Some(ref cache) => cache.internal_object(),
                 None => ash::vk::PipelineCache::null(),
             };
 
             let mut output = MaybeUninit::uninit();
-            check_errors((fns.v1_0.create_compute_pipelines)(
+            let result = check_errors((fns.v1_0.create_compute_pipelines)(
                 device.internal_object(),
                 cache_handle,
                 1,
@@ -213,6 +356,11 @@ impl ComputePipeline {
                 ptr::null(),
                 output.as_mut_ptr(),
             ))?;
+
+            if let Success::PipelineCompileRequired = result {
+                return Err(ComputePipelineCreationError::PipelineCompileRequired);
+            }
+
             output.assume_init()
         };
 
@@ -227,13 +375,18 @@ impl ComputePipeline {
             .map(|x| x + 1)
             .unwrap_or(0);
 
-        Ok(Arc::new(ComputePipeline {
+        let pipeline = ComputePipeline {
             handle,
             device: device.clone(),
             layout,
             descriptor_requirements,
             num_used_descriptor_sets,
-        }))
+            executable_capture,
+        };
+
+        device.set_debug_name(&pipeline, debug_name.as_deref())?;
+
+        Ok(Arc::new(pipeline))
     }
 
     /// Returns the `Device` this compute pipeline was created with.
@@ -251,6 +404,53 @@ impl ComputePipeline {
             .iter()
             .map(|(loc, reqs)| (*loc, reqs))
     }
+
+    /// Returns the properties of the executables that the pipeline was compiled into.
+    ///
+    /// The [`khr_pipeline_executable_properties`] extension and the
+    /// [`pipeline_executable_info`](crate::device::Features::pipeline_executable_info) feature
+    /// must be enabled on the device.
+    ///
+    /// [`khr_pipeline_executable_properties`]: crate::device::DeviceExtensions::khr_pipeline_executable_properties
+    #[inline]
+    pub fn executable_properties(
+        &self,
+    ) -> Result<Vec<PipelineExecutableProperties>, PipelineExecutableError> {
+        executable::executable_properties(&self.device, self.handle)
+    }
+
+    /// Returns statistics about the executable with index `executable_index`.
+    ///
+    /// The pipeline must have been built with [`PipelineExecutableCapture::statistics`] enabled.
+    #[inline]
+    pub fn executable_statistics(
+        &self,
+        executable_index: u32,
+    ) -> Result<Vec<PipelineExecutableStatistic>, PipelineExecutableError> {
+        executable::executable_statistics(
+            &self.device,
+            self.handle,
+            self.executable_capture,
+            executable_index,
+        )
+    }
+
+    /// Returns the internal representations of the executable with index `executable_index`.
+    ///
+    /// The pipeline must have been built with
+    /// [`PipelineExecutableCapture::internal_representations`] enabled.
+    #[inline]
+    pub fn executable_internal_representations(
+        &self,
+        executable_index: u32,
+    ) -> Result<Vec<PipelineExecutableInternalRepresentation>, PipelineExecutableError> {
+        executable::executable_internal_representations(
+            &self.device,
+            self.handle,
+            self.executable_capture,
+            executable_index,
+        )
+    }
 }
 
 impl Pipeline for ComputePipeline {
@@ -312,6 +512,57 @@ impl Drop for ComputePipeline {
     }
 }
 
+/// Additional options for creating a compute pipeline with
+/// [`ComputePipeline::with_options`].
+#[derive(Clone, Debug)]
+pub struct ComputePipelineOptions {
+    /// Which information about the executables of the pipeline to capture.
+    ///
+    /// The default value is [`PipelineExecutableCapture::none()`].
+    pub executable_capture: PipelineExecutableCapture,
+
+    /// The robustness behavior of the pipeline.
+    ///
+    /// The default value is [`PipelineRobustness::device_default()`].
+    pub robustness: PipelineRobustness,
+
+    /// Whether creating the pipeline should fail with
+    /// [`ComputePipelineCreationError::PipelineCompileRequired`] instead of compiling it, if the
+    /// implementation cannot find the compiled pipeline in its caches.
+    ///
+    /// If set to `true`, the
+    /// [`pipeline_creation_cache_control`](crate::device::Features::pipeline_creation_cache_control)
+    /// feature must be enabled on the device.
+    ///
+    /// The default value is `false`.
+    pub fail_on_compile_required: bool,
+
+    /// A name to give to the pipeline, to identify it in debugging tools.
+    ///
+    /// If `Some`, and the [`ext_debug_utils`](crate::instance::InstanceExtensions::ext_debug_utils)
+    /// extension is enabled on the instance, the name is set with
+    /// [`Device::set_debug_utils_object_name`](crate::device::Device::set_debug_utils_object_name)
+    /// when the pipeline is created. Otherwise it is ignored.
+    ///
+    /// The default value is `None`.
+    pub debug_name: Option<Cow<'static, str>>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for ComputePipelineOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            executable_capture: PipelineExecutableCapture::none(),
+            robustness: PipelineRobustness::device_default(),
+            fail_on_compile_required: false,
+            debug_name: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
 /// Error that can happen when creating a compute pipeline.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ComputePipelineCreationError {
@@ -325,6 +576,16 @@ pub enum ComputePipelineCreationError {
     IncompatiblePipelineLayout(PipelineLayoutSupersetError),
     /// The provided specialization constants are not compatible with what the shader expects.
     IncompatibleSpecializationConstants,
+
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// The pipeline needed to be compiled, because the implementation could not find it in its
+    /// caches, but `fail_on_compile_required` was set or the shader module was created from an
+    /// identifier.
+    PipelineCompileRequired,
 }
 
 impl error::Error for ComputePipelineCreationError {
@@ -336,6 +597,8 @@ impl error::Error for ComputePipelineCreationError {
             Self::PipelineLayoutCreationError(ref err) => Some(err),
             Self::IncompatiblePipelineLayout(ref err) => Some(err),
             Self::IncompatibleSpecializationConstants => None,
+            Self::FeatureNotEnabled { .. } => None,
+            Self::PipelineCompileRequired => None,
         }
     }
 }
@@ -343,6 +606,10 @@ impl error::Error for ComputePipelineCreationError {
 impl fmt::Display for ComputePipelineCreationError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        if let Self::FeatureNotEnabled { feature, reason } = self {
+            return write!(fmt, "the feature {} must be enabled: {}", feature, reason);
+        }
+
         write!(
             fmt,
             "{}",
@@ -360,6 +627,10 @@ impl fmt::Display for ComputePipelineCreationError {
                 ComputePipelineCreationError::IncompatibleSpecializationConstants => {
                     "the provided specialization constants are not compatible with what the shader expects"
                 }
+                ComputePipelineCreationError::PipelineCompileRequired => {
+                    "the pipeline needs to be compiled, but was requested to fail instead"
+                }
+                ComputePipelineCreationError::FeatureNotEnabled { .. } => unreachable!(),
             }
         )
     }
@@ -0,0 +1,549 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Information about the executables that a pipeline was compiled into.
+//!
+//! When a pipeline is built, the implementation compiles its shaders into one or more
+//! *executables*. With the `khr_pipeline_executable_properties` extension and the
+//! `pipeline_executable_info` feature enabled, the executables of a pipeline can be listed with
+//! `executable_properties`, which is available on both [`GraphicsPipeline`] and
+//! [`ComputePipeline`].
+//!
+//! If the pipeline was built with [`PipelineExecutableCapture::statistics`] enabled, the
+//! implementation also provides statistics about each executable, such as the number of
+//! registers that it uses. If it was built with
+//! [`PipelineExecutableCapture::internal_representations`] enabled, the implementation provides
+//! internal representations of each executable, such as its disassembly. The names and meanings
+//! of statistics and internal representations are implementation-specific.
+//!
+//! [`GraphicsPipeline`]: crate::pipeline::GraphicsPipeline
+//! [`ComputePipeline`]: crate::pipeline::ComputePipeline
+
+use crate::{
+    check_errors, device::Device, shader::ShaderStages, Error, OomError, Success, VulkanObject,
+};
+use std::{error, ffi::CStr, fmt, os::raw::c_char, ptr};
+
+/// Which information about its executables a pipeline should capture when it is built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PipelineExecutableCapture {
+    /// Capture statistics about the executables, which can be retrieved with
+    /// `executable_statistics`.
+    pub statistics: bool,
+
+    /// Capture internal representations of the executables, which can be retrieved with
+    /// `executable_internal_representations`.
+    pub internal_representations: bool,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl PipelineExecutableCapture {
+    /// Returns a `PipelineExecutableCapture` with none of the information captured.
+    #[inline]
+    pub const fn none() -> Self {
+        Self {
+            statistics: false,
+            internal_representations: false,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    /// Returns a `PipelineExecutableCapture` with all of the information captured.
+    #[inline]
+    pub const fn all() -> Self {
+        Self {
+            statistics: true,
+            internal_representations: true,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+impl Default for PipelineExecutableCapture {
+    #[inline]
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl From<PipelineExecutableCapture> for ash::vk::PipelineCreateFlags {
+    #[inline]
+    fn from(val: PipelineExecutableCapture) -> Self {
+        let mut result = Self::empty();
+
+        if val.statistics {
+            result |= Self::CAPTURE_STATISTICS_KHR;
+        }
+
+        if val.internal_representations {
+            result |= Self::CAPTURE_INTERNAL_REPRESENTATIONS_KHR;
+        }
+
+        result
+    }
+}
+
+/// The properties of an executable of a pipeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineExecutableProperties {
+    /// The shader stages that were compiled into the executable.
+    pub stages: ShaderStages,
+
+    /// The name of the executable.
+    pub name: String,
+
+    /// A description of the executable.
+    pub description: String,
+
+    /// The subgroup size that the executable is dispatched with, or 0 if the executable has no
+    /// subgroups.
+    pub subgroup_size: u32,
+}
+
+/// A statistic about an executable of a pipeline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineExecutableStatistic {
+    /// The name of the statistic.
+    pub name: String,
+
+    /// A description of the statistic.
+    pub description: String,
+
+    /// The value of the statistic.
+    pub value: PipelineExecutableStatisticValue,
+}
+
+/// The value of a [`PipelineExecutableStatistic`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PipelineExecutableStatisticValue {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+}
+
+/// An internal representation of an executable of a pipeline, such as its disassembly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineExecutableInternalRepresentation {
+    /// The name of the internal representation.
+    pub name: String,
+
+    /// A description of the internal representation.
+    pub description: String,
+
+    /// The data of the internal representation.
+    pub data: PipelineExecutableInternalRepresentationData,
+}
+
+/// The data of a [`PipelineExecutableInternalRepresentation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PipelineExecutableInternalRepresentationData {
+    /// Human-readable text.
+    Text(String),
+
+    /// Opaque binary data, in an implementation-specific format.
+    Binary(Vec<u8>),
+}
+
+// Queries the executables of `pipeline`.
+pub(crate) fn executable_properties(
+    device: &Device,
+    pipeline: ash::vk::Pipeline,
+) -> Result<Vec<PipelineExecutableProperties>, PipelineExecutableError> {
+    validate(device)?;
+
+    let pipeline_info = ash::vk::PipelineInfoKHR {
+        pipeline,
+        ..Default::default()
+    };
+
+    let fns = device.fns();
+
+    let properties_vk = unsafe {
+        loop {
+            let mut count = 0;
+            check_errors((fns
+                .khr_pipeline_executable_properties
+                .get_pipeline_executable_properties_khr)(
+                device.internal_object(),
+                &pipeline_info,
+                &mut count,
+                ptr::null_mut(),
+            ))?;
+
+            let mut properties = vec![Default::default(); count as usize];
+            let result = check_errors((fns
+                .khr_pipeline_executable_properties
+                .get_pipeline_executable_properties_khr)(
+                device.internal_object(),
+                &pipeline_info,
+                &mut count,
+                properties.as_mut_ptr(),
+            ))?;
+
+            if !matches!(result, Success::Incomplete) {
+                properties.truncate(count as usize);
+                break properties;
+            }
+        }
+    };
+
+    Ok(properties_vk
+        .iter()
+        .map(
+            |properties: &ash::vk::PipelineExecutablePropertiesKHR| PipelineExecutableProperties {
+                stages: properties.stages.into(),
+                name: string(&properties.name),
+                description: string(&properties.description),
+                subgroup_size: properties.subgroup_size,
+            },
+        )
+        .collect())
+}
+
+// Queries the statistics of an executable of `pipeline`.
+pub(crate) fn executable_statistics(
+    device: &Device,
+    pipeline: ash::vk::Pipeline,
+    capture: PipelineExecutableCapture,
+    executable_index: u32,
+) -> Result<Vec<PipelineExecutableStatistic>, PipelineExecutableError> {
+    validate_executable(device, pipeline, executable_index)?;
+
+    if !capture.statistics {
+        return Err(PipelineExecutableError::NotCaptured {
+            capture: "statistics",
+        });
+    }
+
+    let executable_info = ash::vk::PipelineExecutableInfoKHR {
+        pipeline,
+        executable_index,
+        ..Default::default()
+    };
+
+    let fns = device.fns();
+
+    let statistics_vk = unsafe {
+        loop {
+            let mut count = 0;
+            check_errors((fns
+                .khr_pipeline_executable_properties
+                .get_pipeline_executable_statistics_khr)(
+                device.internal_object(),
+                &executable_info,
+                &mut count,
+                ptr::null_mut(),
+            ))?;
+
+            let mut statistics = vec![Default::default(); count as usize];
+            let result = check_errors((fns
+                .khr_pipeline_executable_properties
+                .get_pipeline_executable_statistics_khr)(
+                device.internal_object(),
+                &executable_info,
+                &mut count,
+                statistics.as_mut_ptr(),
+            ))?;
+
+            if !matches!(result, Success::Incomplete) {
+                statistics.truncate(count as usize);
+                break statistics;
+            }
+        }
+    };
+
+    Ok(statistics_vk
+        .iter()
+        .map(|statistic: &ash::vk::PipelineExecutableStatisticKHR| {
+            let value = unsafe {
+                match statistic.format {
+                    ash::vk::PipelineExecutableStatisticFormatKHR::BOOL32 => {
+                        PipelineExecutableStatisticValue::Bool(
+                            statistic.value.b32 != ash::vk::FALSE,
+                        )
+                    }
+                    ash::vk::PipelineExecutableStatisticFormatKHR::INT64 => {
+                        PipelineExecutableStatisticValue::I64(statistic.value.i64)
+                    }
+                    ash::vk::PipelineExecutableStatisticFormatKHR::FLOAT64 => {
+                        PipelineExecutableStatisticValue::F64(statistic.value.f64)
+                    }
+                    _ => PipelineExecutableStatisticValue::U64(statistic.value.u64),
+                }
+            };
+
+            PipelineExecutableStatistic {
+                name: string(&statistic.name),
+                description: string(&statistic.description),
+                value,
+            }
+        })
+        .collect())
+}
+
+// Queries the internal representations of an executable of `pipeline`.
+pub(crate) fn executable_internal_representations(
+    device: &Device,
+    pipeline: ash::vk::Pipeline,
+    capture: PipelineExecutableCapture,
+    executable_index: u32,
+) -> Result<Vec<PipelineExecutableInternalRepresentation>, PipelineExecutableError> {
+    validate_executable(device, pipeline, executable_index)?;
+
+    if !capture.internal_representations {
+        return Err(PipelineExecutableError::NotCaptured {
+            capture: "internal_representations",
+        });
+    }
+
+    let executable_info = ash::vk::PipelineExecutableInfoKHR {
+        pipeline,
+        executable_index,
+        ..Default::default()
+    };
+
+    let fns = device.fns();
+
+    let (representations_vk, data) = unsafe {
+        loop {
+            // First query the number of representations, then the size of their data, and
+            // finally the data itself.
+            let mut count = 0;
+            check_errors((fns
+                .khr_pipeline_executable_properties
+                .get_pipeline_executable_internal_representations_khr)(
+                device.internal_object(),
+                &executable_info,
+                &mut count,
+                ptr::null_mut(),
+            ))?;
+
+            let mut representations: Vec<ash::vk::PipelineExecutableInternalRepresentationKHR> =
+                vec![Default::default(); count as usize];
+            let result = check_errors((fns
+                .khr_pipeline_executable_properties
+                .get_pipeline_executable_internal_representations_khr)(
+                device.internal_object(),
+                &executable_info,
+                &mut count,
+                representations.as_mut_ptr(),
+            ))?;
+
+            if matches!(result, Success::Incomplete) {
+                continue;
+            }
+
+            representations.truncate(count as usize);
+
+            let mut data: Vec<Vec<u8>> = representations
+                .iter_mut()
+                .map(|representation| {
+                    let mut data = vec![0u8; representation.data_size];
+                    representation.p_data = data.as_mut_ptr() as *mut _;
+                    data
+                })
+                .collect();
+
+            let result = check_errors((fns
+                .khr_pipeline_executable_properties
+                .get_pipeline_executable_internal_representations_khr)(
+                device.internal_object(),
+                &executable_info,
+                &mut count,
+                representations.as_mut_ptr(),
+            ))?;
+
+            if matches!(result, Success::Incomplete) {
+                continue;
+            }
+
+            for (representation, data) in representations.iter().zip(data.iter_mut()) {
+                data.truncate(representation.data_size);
+            }
+
+            break (representations, data);
+        }
+    };
+
+    Ok(representations_vk
+        .iter()
+        .zip(data)
+        .map(|(representation, data)| {
+            let data = if representation.is_text != ash::vk::FALSE {
+                // The text is null-terminated.
+                let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                PipelineExecutableInternalRepresentationData::Text(
+                    String::from_utf8_lossy(&data[..len]).into_owned(),
+                )
+            } else {
+                PipelineExecutableInternalRepresentationData::Binary(data)
+            };
+
+            PipelineExecutableInternalRepresentation {
+                name: string(&representation.name),
+                description: string(&representation.description),
+                data,
+            }
+        })
+        .collect())
+}
+
+fn validate(device: &Device) -> Result<(), PipelineExecutableError> {
+    if !device
+        .enabled_extensions()
+        .khr_pipeline_executable_properties
+    {
+        return Err(PipelineExecutableError::ExtensionNotEnabled {
+            extension: "khr_pipeline_executable_properties",
+            reason: "tried to query the executables of a pipeline",
+        });
+    }
+
+    if !device.enabled_features().pipeline_executable_info {
+        return Err(PipelineExecutableError::FeatureNotEnabled {
+            feature: "pipeline_executable_info",
+            reason: "tried to query the executables of a pipeline",
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_executable(
+    device: &Device,
+    pipeline: ash::vk::Pipeline,
+    executable_index: u32,
+) -> Result<(), PipelineExecutableError> {
+    let executable_count = executable_properties(device, pipeline)?.len() as u32;
+
+    if executable_index >= executable_count {
+        return Err(PipelineExecutableError::ExecutableIndexOutOfRange {
+            executable_index,
+            executable_count,
+        });
+    }
+
+    Ok(())
+}
+
+fn string(chars: &[c_char]) -> String {
+    unsafe { CStr::from_ptr(chars.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Error that can happen when querying information about the executables of a pipeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PipelineExecutableError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// The executable index is not less than the number of executables of the pipeline.
+    ExecutableIndexOutOfRange {
+        executable_index: u32,
+        executable_count: u32,
+    },
+
+    /// The requested information was not captured when the pipeline was built.
+    NotCaptured { capture: &'static str },
+}
+
+impl error::Error for PipelineExecutableError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::OomError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PipelineExecutableError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::OomError(_) => write!(f, "not enough memory available"),
+            Self::ExtensionNotEnabled { extension, reason } => {
+                write!(f, "the extension {} must be enabled: {}", extension, reason)
+            }
+            Self::FeatureNotEnabled { feature, reason } => {
+                write!(f, "the feature {} must be enabled: {}", feature, reason)
+            }
+            Self::ExecutableIndexOutOfRange {
+                executable_index,
+                executable_count,
+            } => write!(
+                f,
+                "the executable index {} is not less than the number of executables of the \
+                pipeline ({})",
+                executable_index, executable_count,
+            ),
+            Self::NotCaptured { capture } => write!(
+                f,
+                "the pipeline was not built with PipelineExecutableCapture::{} enabled",
+                capture,
+            ),
+        }
+    }
+}
+
+impl From<OomError> for PipelineExecutableError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+impl From<Error> for PipelineExecutableError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            err @ Error::OutOfHostMemory => Self::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => Self::OomError(OomError::from(err)),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{executable_properties, PipelineExecutableCapture, PipelineExecutableError};
+
+    #[test]
+    fn capture_flags() {
+        assert!(ash::vk::PipelineCreateFlags::from(PipelineExecutableCapture::none()).is_empty());
+        assert_eq!(
+            ash::vk::PipelineCreateFlags::from(PipelineExecutableCapture::all()),
+            ash::vk::PipelineCreateFlags::CAPTURE_STATISTICS_KHR
+                | ash::vk::PipelineCreateFlags::CAPTURE_INTERNAL_REPRESENTATIONS_KHR,
+        );
+    }
+
+    #[test]
+    fn extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match executable_properties(&device, ash::vk::Pipeline::null()) {
+            Err(PipelineExecutableError::ExtensionNotEnabled { .. }) => (),
+            _ => panic!(),
+        }
+    }
+}
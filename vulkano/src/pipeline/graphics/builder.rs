@@ -35,22 +35,24 @@ use crate::{
     format::NumericType,
     pipeline::{
         cache::PipelineCache,
+        executable::PipelineExecutableCapture,
         graphics::{
             color_blend::BlendFactor,
             depth_stencil::{DepthBoundsState, DepthState, StencilOpState, StencilState},
             vertex_input::VertexInputRate,
         },
         layout::{PipelineLayoutCreateInfo, PushConstantRange},
-        DynamicState, PartialStateMode, PipelineLayout, StateMode,
+        DynamicState, PartialStateMode, PipelineLayout, PipelineRobustness, StateMode,
     },
     shader::{
-        DescriptorRequirements, EntryPoint, ShaderExecution, ShaderStage, SpecializationConstants,
-        SpecializationMapEntry,
+        spirv::Capability, DescriptorRequirements, EntryPoint, ShaderExecution, ShaderStage,
+        SpecializationConstants, SpecializationMapEntry,
     },
-    DeviceSize, Version, VulkanObject,
+    DeviceSize, Success, Version, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{
+    borrow::Cow,
     collections::{hash_map::Entry, HashMap},
     mem::{size_of_val, MaybeUninit},
     ptr, slice,
@@ -62,6 +64,11 @@ use std::{
 pub struct GraphicsPipelineBuilder<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss> {
     render_pass: Option<PipelineRenderPassType>,
     cache: Option<Arc<PipelineCache>>,
+    cache_statistics: bool,
+    executable_capture: PipelineExecutableCapture,
+    robustness: PipelineRobustness,
+    fail_on_compile_required: bool,
+    debug_name: Option<Cow<'static, str>>,
 
     vertex_shader: Option<(EntryPoint<'vs>, Vss)>,
     tessellation_shaders: Option<TessellationShaders<'tcs, 'tes, Tcss, Tess>>,
@@ -106,6 +113,11 @@ impl
         GraphicsPipelineBuilder {
             render_pass: None,
             cache: None,
+            cache_statistics: false,
+            executable_capture: PipelineExecutableCapture::none(),
+            robustness: PipelineRobustness::device_default(),
+            fail_on_compile_required: false,
+            debug_name: None,
 
             vertex_shader: None,
             tessellation_shaders: None,
@@ -294,6 +306,11 @@ where
             let &Self {
                 ref render_pass,
                 ref cache,
+                cache_statistics: _,
+                executable_capture: _,
+                robustness: _,
+                fail_on_compile_required: _,
+                debug_name: _,
 
                 ref vertex_shader,
                 ref tessellation_shaders,
@@ -365,6 +382,11 @@ where
         let Self {
             mut render_pass,
             cache,
+            cache_statistics: _,
+            executable_capture,
+            robustness: _,
+            fail_on_compile_required: _,
+            debug_name,
             vertex_shader,
             tessellation_shaders,
             geometry_shader,
@@ -387,10 +409,11 @@ where
             .map(|x| x + 1)
             .unwrap_or(0);
 
-        Ok(Arc::new(GraphicsPipeline {
+        let pipeline = GraphicsPipeline {
             handle,
             device,
             layout: pipeline_layout,
+            executable_capture,
             render_pass: render_pass.take().expect("Missing render pass"),
             shaders,
             descriptor_requirements,
@@ -408,7 +431,13 @@ where
             depth_stencil_state: has.depth_stencil_state.then(|| depth_stencil_state),
             color_blend_state: has.color_blend_state.then(|| color_blend_state),
             dynamic_state,
-        }))
+        };
+
+        pipeline
+            .device
+            .set_debug_name(&pipeline, debug_name.as_deref())?;
+
+        Ok(Arc::new(pipeline))
     }
 
     fn validate_create(
@@ -424,6 +453,11 @@ where
         let &Self {
             ref render_pass,
             ref cache,
+            cache_statistics,
+            executable_capture,
+            robustness,
+            fail_on_compile_required,
+            debug_name: _,
 
             ref vertex_shader,
             ref tessellation_shaders,
@@ -445,6 +479,40 @@ where
 
         let mut shader_stages: SmallVec<[_; 5]> = SmallVec::new();
 
+        if cache_statistics
+            && cache.is_some()
+            && !(device.api_version() >= Version::V1_3
+                || device.enabled_extensions().ext_pipeline_creation_feedback)
+        {
+            return Err(GraphicsPipelineCreationError::ExtensionNotEnabled {
+                extension: "ext_pipeline_creation_feedback",
+                reason: "cache statistics were requested",
+            });
+        }
+
+        if (executable_capture.statistics || executable_capture.internal_representations)
+            && !device.enabled_features().pipeline_executable_info
+        {
+            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                feature: "pipeline_executable_info",
+                reason: "executable information was requested to be captured",
+            });
+        }
+
+        if let Some(feature) = robustness.missing_feature(device) {
+            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                feature,
+                reason: "the robustness of the pipeline was set to a non-default value",
+            });
+        }
+
+        if fail_on_compile_required && !device.enabled_features().pipeline_creation_cache_control {
+            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                feature: "pipeline_creation_cache_control",
+                reason: "the pipeline was requested to fail if it needs to be compiled",
+            });
+        }
+
         // VUID-VkGraphicsPipelineCreateInfo-layout-01688
         // Checked at pipeline layout creation time.
 
@@ -1057,12 +1125,28 @@ where
                     line_stipple,
                 } = rasterization_state;
 
-                // VUID-VkPipelineRasterizationStateCreateInfo-depthClampEnable-00782
-                if depth_clamp_enable && !device.enabled_features().depth_clamp {
-                    return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
-                        feature: "depth_clamp",
-                        reason: "RasterizationState::depth_clamp_enable was true",
-                    });
+                match depth_clamp_enable {
+                    StateMode::Fixed(depth_clamp_enable) => {
+                        // VUID-VkPipelineRasterizationStateCreateInfo-depthClampEnable-00782
+                        if depth_clamp_enable && !device.enabled_features().depth_clamp {
+                            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                                feature: "depth_clamp",
+                                reason: "RasterizationState::depth_clamp_enable was Fixed(true)",
+                            });
+                        }
+                    }
+                    StateMode::Dynamic => {
+                        // VUID?
+                        if !device
+                            .enabled_features()
+                            .extended_dynamic_state3_depth_clamp_enable
+                        {
+                            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                                feature: "extended_dynamic_state3_depth_clamp_enable",
+                                reason: "RasterizationState::depth_clamp_enable was set to Dynamic",
+                            });
+                        }
+                    }
                 }
 
                 // VUID?
@@ -1076,14 +1160,30 @@ where
                     });
                 }
 
-                // VUID-VkPipelineRasterizationStateCreateInfo-polygonMode-01507
-                if polygon_mode != PolygonMode::Fill
-                    && !device.enabled_features().fill_mode_non_solid
-                {
-                    return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
-                        feature: "fill_mode_non_solid",
-                        reason: "RasterizationState::polygon_mode was not Fill",
-                    });
+                match polygon_mode {
+                    StateMode::Fixed(polygon_mode) => {
+                        // VUID-VkPipelineRasterizationStateCreateInfo-polygonMode-01507
+                        if polygon_mode != PolygonMode::Fill
+                            && !device.enabled_features().fill_mode_non_solid
+                        {
+                            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                                feature: "fill_mode_non_solid",
+                                reason: "RasterizationState::polygon_mode was not Fixed(Fill)",
+                            });
+                        }
+                    }
+                    StateMode::Dynamic => {
+                        // VUID?
+                        if !device
+                            .enabled_features()
+                            .extended_dynamic_state3_polygon_mode
+                        {
+                            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                                feature: "extended_dynamic_state3_polygon_mode",
+                                reason: "RasterizationState::polygon_mode was set to Dynamic",
+                            });
+                        }
+                    }
                 }
 
                 // VUID?
@@ -1554,6 +1654,25 @@ where
                                 GraphicsPipelineCreationError::FragmentShaderRenderPassIncompatible,
                             );
                         }
+
+                        // VUID-VkGraphicsPipelineCreateInfo-renderPass-08710
+                        if entry_point
+                            .module()
+                            .spirv_capabilities()
+                            .iter()
+                            .any(|capability| {
+                                matches!(
+                                    capability,
+                                    Capability::TileImageColorReadAccessEXT
+                                        | Capability::TileImageDepthReadAccessEXT
+                                        | Capability::TileImageStencilReadAccessEXT
+                                )
+                            })
+                        {
+                            return Err(
+                                GraphicsPipelineCreationError::FragmentShaderTileImageRenderPass,
+                            );
+                        }
                     }
                     PipelineRenderPassType::BeginRendering(_) => {
                         // TODO:
@@ -1820,14 +1939,42 @@ where
                     assert!(min_sample_shading >= 0.0 && min_sample_shading <= 1.0);
                 }
 
-                // VUID-VkPipelineMultisampleStateCreateInfo-alphaToOneEnable-00785
-                if alpha_to_one_enable && !device.enabled_features().alpha_to_one {
+                // VUID?
+                if matches!(alpha_to_coverage_enable, StateMode::Dynamic)
+                    && !device
+                        .enabled_features()
+                        .extended_dynamic_state3_alpha_to_coverage_enable
+                {
                     return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
-                        feature: "alpha_to_one",
-                        reason: "MultisampleState::alpha_to_one was true",
+                        feature: "extended_dynamic_state3_alpha_to_coverage_enable",
+                        reason: "MultisampleState::alpha_to_coverage_enable was set to Dynamic",
                     });
                 }
 
+                match alpha_to_one_enable {
+                    StateMode::Fixed(alpha_to_one_enable) => {
+                        // VUID-VkPipelineMultisampleStateCreateInfo-alphaToOneEnable-00785
+                        if alpha_to_one_enable && !device.enabled_features().alpha_to_one {
+                            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                                feature: "alpha_to_one",
+                                reason: "MultisampleState::alpha_to_one was Fixed(true)",
+                            });
+                        }
+                    }
+                    StateMode::Dynamic => {
+                        // VUID?
+                        if !device
+                            .enabled_features()
+                            .extended_dynamic_state3_alpha_to_one_enable
+                        {
+                            return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                                feature: "extended_dynamic_state3_alpha_to_one_enable",
+                                reason: "MultisampleState::alpha_to_one_enable was set to Dynamic",
+                            });
+                        }
+                    }
+                }
+
                 // TODO:
                 // VUID-VkGraphicsPipelineCreateInfo-lineRasterizationMode-02766
             }
@@ -1897,6 +2044,58 @@ where
                 }
             }
 
+            if attachments
+                .iter()
+                .any(|state| matches!(state.blend, Some(StateMode::Dynamic)))
+            {
+                // VUID?
+                if !device
+                    .enabled_features()
+                    .extended_dynamic_state3_color_blend_equation
+                {
+                    return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                        feature: "extended_dynamic_state3_color_blend_equation",
+                        reason: "ColorBlendAttachmentState::blend was set to Some(Dynamic)",
+                    });
+                }
+
+                // The dynamic blend equations apply to all attachments at once.
+                if attachments
+                    .iter()
+                    .any(|state| matches!(state.blend, Some(StateMode::Fixed(_))))
+                {
+                    return Err(
+                        GraphicsPipelineCreationError::ColorBlendAttachmentDynamicStateMismatch,
+                    );
+                }
+            }
+
+            if attachments
+                .iter()
+                .any(|state| matches!(state.color_write_mask, StateMode::Dynamic))
+            {
+                // VUID?
+                if !device
+                    .enabled_features()
+                    .extended_dynamic_state3_color_write_mask
+                {
+                    return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
+                        feature: "extended_dynamic_state3_color_write_mask",
+                        reason: "ColorBlendAttachmentState::color_write_mask was set to Dynamic",
+                    });
+                }
+
+                // The dynamic color write masks apply to all attachments at once.
+                if attachments
+                    .iter()
+                    .any(|state| matches!(state.color_write_mask, StateMode::Fixed(_)))
+                {
+                    return Err(
+                        GraphicsPipelineCreationError::ColorBlendAttachmentDynamicStateMismatch,
+                    );
+                }
+            }
+
             for (attachment_index, state) in attachments.iter().enumerate() {
                 let &ColorBlendAttachmentState {
                     blend,
@@ -1907,7 +2106,7 @@ where
                 if let Some(blend) = blend {
                     // VUID?
                     if !device.enabled_features().dual_src_blend
-                        && [
+                        && matches!(blend, StateMode::Fixed(blend) if [
                             blend.color_source,
                             blend.color_destination,
                             blend.alpha_source,
@@ -1922,7 +2121,7 @@ where
                                     | BlendFactor::Src1Alpha
                                     | BlendFactor::OneMinusSrc1Alpha
                             )
-                        })
+                        }))
                     {
                         return Err(GraphicsPipelineCreationError::FeatureNotEnabled {
                             feature: "dual_src_blend",
@@ -2009,6 +2208,35 @@ where
                     return Err(GraphicsPipelineCreationError::IncompatibleSpecializationConstants);
                 }
             }
+
+            let clip_distances = stage_info.entry_point.clip_distance_array_size();
+            let cull_distances = stage_info.entry_point.cull_distance_array_size();
+
+            // VUID-VkPipelineShaderStageCreateInfo-maxClipDistances-00708
+            if clip_distances > properties.max_clip_distances {
+                return Err(GraphicsPipelineCreationError::MaxClipDistancesExceeded {
+                    clip_distances,
+                    max: properties.max_clip_distances,
+                });
+            }
+
+            // VUID-VkPipelineShaderStageCreateInfo-maxCullDistances-00709
+            if cull_distances > properties.max_cull_distances {
+                return Err(GraphicsPipelineCreationError::MaxCullDistancesExceeded {
+                    cull_distances,
+                    max: properties.max_cull_distances,
+                });
+            }
+
+            // VUID-VkPipelineShaderStageCreateInfo-maxCombinedClipAndCullDistances-00710
+            if clip_distances + cull_distances > properties.max_combined_clip_and_cull_distances {
+                return Err(
+                    GraphicsPipelineCreationError::MaxCombinedClipAndCullDistancesExceeded {
+                        clip_and_cull_distances: clip_distances + cull_distances,
+                        max: properties.max_combined_clip_and_cull_distances,
+                    },
+                );
+            }
         }
 
         // VUID-VkGraphicsPipelineCreateInfo-pStages-00742
@@ -2026,9 +2254,6 @@ where
         }
 
         // TODO:
-        // VUID-VkPipelineShaderStageCreateInfo-maxClipDistances-00708
-        // VUID-VkPipelineShaderStageCreateInfo-maxCullDistances-00709
-        // VUID-VkPipelineShaderStageCreateInfo-maxCombinedClipAndCullDistances-00710
         // VUID-VkPipelineShaderStageCreateInfo-maxSampleMaskWords-00711
 
         // Dynamic states not handled yet:
@@ -2060,6 +2285,11 @@ where
         let Self {
             render_pass,
             cache,
+            cache_statistics,
+            executable_capture,
+            robustness,
+            fail_on_compile_required,
+            debug_name: _,
 
             vertex_shader,
             tessellation_shaders,
@@ -2084,6 +2314,7 @@ where
         let mut dynamic_state: HashMap<DynamicState, bool> = HashMap::default();
         let mut stages = HashMap::default();
         let mut stages_vk: SmallVec<[_; 5]> = SmallVec::new();
+        let mut module_identifiers_vk: SmallVec<[_; 5]> = SmallVec::new();
 
         /*
             Render pass
@@ -2305,6 +2536,7 @@ where
                     p_specialization_info: specialization_info_vk as *const _,
                     ..Default::default()
                 });
+                module_identifiers_vk.push(entry_point.module().module_identifier_create_info_vk());
             }
 
             // Tessellation shaders
@@ -2346,6 +2578,8 @@ where
                         p_specialization_info: specialization_info_vk as *const _,
                         ..Default::default()
                     });
+                    module_identifiers_vk
+                        .push(entry_point.module().module_identifier_create_info_vk());
                 }
 
                 {
@@ -2385,6 +2619,8 @@ where
                         p_specialization_info: specialization_info_vk as *const _,
                         ..Default::default()
                     });
+                    module_identifiers_vk
+                        .push(entry_point.module().module_identifier_create_info_vk());
                 }
             }
 
@@ -2425,6 +2661,7 @@ where
                     p_specialization_info: specialization_info_vk as *const _,
                     ..Default::default()
                 });
+                module_identifiers_vk.push(entry_point.module().module_identifier_create_info_vk());
             }
 
             // Rasterization state
@@ -2441,6 +2678,17 @@ where
                     line_stipple,
                 } = rasterization_state;
 
+                let depth_clamp_enable = match depth_clamp_enable {
+                    StateMode::Fixed(depth_clamp_enable) => {
+                        dynamic_state.insert(DynamicState::DepthClampEnable, false);
+                        depth_clamp_enable as ash::vk::Bool32
+                    }
+                    StateMode::Dynamic => {
+                        dynamic_state.insert(DynamicState::DepthClampEnable, true);
+                        ash::vk::FALSE
+                    }
+                };
+
                 let rasterizer_discard_enable = match rasterizer_discard_enable {
                     StateMode::Fixed(rasterizer_discard_enable) => {
                         dynamic_state.insert(DynamicState::RasterizerDiscardEnable, false);
@@ -2452,6 +2700,17 @@ where
                     }
                 };
 
+                let polygon_mode = match polygon_mode {
+                    StateMode::Fixed(polygon_mode) => {
+                        dynamic_state.insert(DynamicState::PolygonMode, false);
+                        polygon_mode.into()
+                    }
+                    StateMode::Dynamic => {
+                        dynamic_state.insert(DynamicState::PolygonMode, true);
+                        PolygonMode::default().into()
+                    }
+                };
+
                 let cull_mode = match cull_mode {
                     StateMode::Fixed(cull_mode) => {
                         dynamic_state.insert(DynamicState::CullMode, false);
@@ -2516,9 +2775,9 @@ where
                 let rasterization_state =
                     rasterization_state_vk.insert(ash::vk::PipelineRasterizationStateCreateInfo {
                         flags: ash::vk::PipelineRasterizationStateCreateFlags::empty(),
-                        depth_clamp_enable: depth_clamp_enable as ash::vk::Bool32,
+                        depth_clamp_enable,
                         rasterizer_discard_enable,
-                        polygon_mode: polygon_mode.into(),
+                        polygon_mode,
                         cull_mode,
                         front_face,
                         depth_bias_enable,
@@ -2763,6 +3022,7 @@ where
                     p_specialization_info: specialization_info_vk as *const _,
                     ..Default::default()
                 });
+                module_identifiers_vk.push(entry_point.module().module_identifier_create_info_vk());
             }
         }
 
@@ -2981,14 +3241,36 @@ where
                         (ash::vk::FALSE, 0.0)
                     };
 
+                let alpha_to_coverage_enable = match alpha_to_coverage_enable {
+                    StateMode::Fixed(alpha_to_coverage_enable) => {
+                        dynamic_state.insert(DynamicState::AlphaToCoverageEnable, false);
+                        alpha_to_coverage_enable as ash::vk::Bool32
+                    }
+                    StateMode::Dynamic => {
+                        dynamic_state.insert(DynamicState::AlphaToCoverageEnable, true);
+                        ash::vk::FALSE
+                    }
+                };
+
+                let alpha_to_one_enable = match alpha_to_one_enable {
+                    StateMode::Fixed(alpha_to_one_enable) => {
+                        dynamic_state.insert(DynamicState::AlphaToOneEnable, false);
+                        alpha_to_one_enable as ash::vk::Bool32
+                    }
+                    StateMode::Dynamic => {
+                        dynamic_state.insert(DynamicState::AlphaToOneEnable, true);
+                        ash::vk::FALSE
+                    }
+                };
+
                 let _ = multisample_state_vk.insert(ash::vk::PipelineMultisampleStateCreateInfo {
                     flags: ash::vk::PipelineMultisampleStateCreateFlags::empty(),
                     rasterization_samples: rasterization_samples.into(),
                     sample_shading_enable,
                     min_sample_shading,
                     p_sample_mask: sample_mask as _,
-                    alpha_to_coverage_enable: alpha_to_coverage_enable as ash::vk::Bool32,
-                    alpha_to_one_enable: alpha_to_one_enable as ash::vk::Bool32,
+                    alpha_to_coverage_enable,
+                    alpha_to_one_enable,
                     ..Default::default()
                 });
             }
@@ -3010,14 +3292,34 @@ where
                         color_write_enable,
                     } = color_blend_attachment_state;
 
-                    let blend = if let Some(blend) = blend {
-                        blend.clone().into()
-                    } else {
-                        Default::default()
+                    let blend = match blend {
+                        Some(StateMode::Fixed(blend)) => {
+                            dynamic_state.insert(DynamicState::ColorBlendEquation, false);
+                            blend.into()
+                        }
+                        Some(StateMode::Dynamic) => {
+                            dynamic_state.insert(DynamicState::ColorBlendEquation, true);
+                            ash::vk::PipelineColorBlendAttachmentState {
+                                blend_enable: ash::vk::TRUE,
+                                ..Default::default()
+                            }
+                        }
+                        None => Default::default(),
+                    };
+
+                    let color_write_mask = match color_write_mask {
+                        StateMode::Fixed(color_write_mask) => {
+                            dynamic_state.insert(DynamicState::ColorWriteMask, false);
+                            color_write_mask.into()
+                        }
+                        StateMode::Dynamic => {
+                            dynamic_state.insert(DynamicState::ColorWriteMask, true);
+                            Default::default()
+                        }
                     };
 
                     ash::vk::PipelineColorBlendAttachmentState {
-                        color_write_mask: color_write_mask.into(),
+                        color_write_mask,
                         ..blend
                     }
                 },
@@ -3129,8 +3431,22 @@ where
             Create
         */
 
+        // TODO: other flags are available but none are critical
+        let mut flags: ash::vk::PipelineCreateFlags = (*executable_capture).into();
+
+        if *fail_on_compile_required {
+            flags |= ash::vk::PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED;
+        }
+
+        for (stage_vk, info) in stages_vk.iter_mut().zip(&module_identifiers_vk) {
+            if let Some(info) = info {
+                stage_vk.p_next = info as *const _ as *const _;
+                flags |= ash::vk::PipelineCreateFlags::FAIL_ON_PIPELINE_COMPILE_REQUIRED;
+            }
+        }
+
         let mut create_info = ash::vk::GraphicsPipelineCreateInfo {
-            flags: ash::vk::PipelineCreateFlags::empty(), // TODO: some flags are available but none are critical
+            flags,
             stage_count: stages_vk.len() as u32,
             p_stages: stages_vk.as_ptr(),
             p_vertex_input_state: vertex_input_state_vk
@@ -3187,6 +3503,28 @@ where
             create_info.p_next = info as *const _ as *const _;
         }
 
+        let mut robustness_vk = (!robustness.is_device_default())
+            .then(|| ash::vk::PipelineRobustnessCreateInfoEXT::from(*robustness));
+
+        if let Some(info) = robustness_vk.as_mut() {
+            info.p_next = create_info.p_next;
+            create_info.p_next = info as *const _ as *const _;
+        }
+
+        let mut creation_feedback_vk = ash::vk::PipelineCreationFeedback::default();
+        let mut creation_feedback_create_info_vk =
+            (*cache_statistics && cache.is_some()).then(|| {
+                ash::vk::PipelineCreationFeedbackCreateInfo {
+                    p_pipeline_creation_feedback: &mut creation_feedback_vk,
+                    ..Default::default()
+                }
+            });
+
+        if let Some(info) = creation_feedback_create_info_vk.as_mut() {
+            info.p_next = create_info.p_next;
+            create_info.p_next = info as *const _ as *const _;
+        }
+
         let cache_handle = match cache.as_ref() {
             Some(cache) => cache.internal_object(),
             None => ash::vk::PipelineCache::null(),
@@ -3195,7 +3533,7 @@ where
         let handle = {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
-            check_errors((fns.v1_0.create_graphics_pipelines)(
+            let result = check_errors((fns.v1_0.create_graphics_pipelines)(
                 device.internal_object(),
                 cache_handle,
                 1,
@@ -3203,6 +3541,11 @@ where
                 ptr::null(),
                 output.as_mut_ptr(),
             ))?;
+
+            if let Success::PipelineCompileRequired = result {
+                return Err(GraphicsPipelineCreationError::PipelineCompileRequired);
+            }
+
             output.assume_init()
         };
 
@@ -3213,6 +3556,17 @@ where
             panic!("vkCreateGraphicsPipelines provided a NULL handle");
         }
 
+        if let (Some(cache), Some(_)) = (cache.as_ref(), creation_feedback_create_info_vk) {
+            if creation_feedback_vk
+                .flags
+                .intersects(ash::vk::PipelineCreationFeedbackFlags::VALID)
+            {
+                cache.record_lookup(creation_feedback_vk.flags.intersects(
+                    ash::vk::PipelineCreationFeedbackFlags::APPLICATION_PIPELINE_CACHE_HIT,
+                ));
+            }
+        }
+
         Ok((handle, descriptor_requirements, dynamic_state, stages))
     }
 
@@ -3244,6 +3598,11 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         GraphicsPipelineBuilder {
             render_pass: self.render_pass,
             cache: self.cache,
+            cache_statistics: self.cache_statistics,
+            executable_capture: self.executable_capture,
+            robustness: self.robustness,
+            fail_on_compile_required: self.fail_on_compile_required,
+            debug_name: self.debug_name,
 
             vertex_shader: Some((shader, specialization_constants)),
             tessellation_shaders: self.tessellation_shaders,
@@ -3279,6 +3638,11 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         GraphicsPipelineBuilder {
             render_pass: self.render_pass,
             cache: self.cache,
+            cache_statistics: self.cache_statistics,
+            executable_capture: self.executable_capture,
+            robustness: self.robustness,
+            fail_on_compile_required: self.fail_on_compile_required,
+            debug_name: self.debug_name,
 
             vertex_shader: self.vertex_shader,
             tessellation_shaders: Some(TessellationShaders {
@@ -3314,6 +3678,11 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         GraphicsPipelineBuilder {
             render_pass: self.render_pass,
             cache: self.cache,
+            cache_statistics: self.cache_statistics,
+            executable_capture: self.executable_capture,
+            robustness: self.robustness,
+            fail_on_compile_required: self.fail_on_compile_required,
+            debug_name: self.debug_name,
 
             vertex_shader: self.vertex_shader,
             tessellation_shaders: self.tessellation_shaders,
@@ -3348,6 +3717,11 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         GraphicsPipelineBuilder {
             render_pass: self.render_pass,
             cache: self.cache,
+            cache_statistics: self.cache_statistics,
+            executable_capture: self.executable_capture,
+            robustness: self.robustness,
+            fail_on_compile_required: self.fail_on_compile_required,
+            debug_name: self.debug_name,
 
             vertex_shader: self.vertex_shader,
             tessellation_shaders: self.tessellation_shaders,
@@ -3380,6 +3754,11 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         GraphicsPipelineBuilder {
             render_pass: self.render_pass,
             cache: self.cache,
+            cache_statistics: self.cache_statistics,
+            executable_capture: self.executable_capture,
+            robustness: self.robustness,
+            fail_on_compile_required: self.fail_on_compile_required,
+            debug_name: self.debug_name,
 
             vertex_shader: self.vertex_shader,
             tessellation_shaders: self.tessellation_shaders,
@@ -3726,7 +4105,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     #[deprecated(since = "0.27", note = "Use `rasterization_state` instead")]
     #[inline]
     pub fn depth_clamp(mut self, clamp: bool) -> Self {
-        self.rasterization_state.depth_clamp_enable = clamp;
+        self.rasterization_state.depth_clamp_enable = StateMode::Fixed(clamp);
         self
     }
 
@@ -3793,7 +4172,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     #[deprecated(since = "0.27", note = "Use `rasterization_state` instead")]
     #[inline]
     pub fn polygon_mode_fill(mut self) -> Self {
-        self.rasterization_state.polygon_mode = PolygonMode::Fill;
+        self.rasterization_state.polygon_mode = StateMode::Fixed(PolygonMode::Fill);
         self
     }
 
@@ -3801,7 +4180,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     #[deprecated(since = "0.27", note = "Use `rasterization_state` instead")]
     #[inline]
     pub fn polygon_mode_line(mut self) -> Self {
-        self.rasterization_state.polygon_mode = PolygonMode::Line;
+        self.rasterization_state.polygon_mode = StateMode::Fixed(PolygonMode::Line);
         self
     }
 
@@ -3809,7 +4188,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     #[deprecated(since = "0.27", note = "Use `rasterization_state` instead")]
     #[inline]
     pub fn polygon_mode_point(mut self) -> Self {
-        self.rasterization_state.polygon_mode = PolygonMode::Point;
+        self.rasterization_state.polygon_mode = StateMode::Fixed(PolygonMode::Point);
         self
     }
 
@@ -3869,14 +4248,14 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     // TODO: doc
     #[deprecated(since = "0.27", note = "Use `multisample_state` instead")]
     pub fn alpha_to_coverage_disabled(mut self) -> Self {
-        self.multisample_state.alpha_to_coverage_enable = false;
+        self.multisample_state.alpha_to_coverage_enable = StateMode::Fixed(false);
         self
     }
 
     // TODO: doc
     #[deprecated(since = "0.27", note = "Use `multisample_state` instead")]
     pub fn alpha_to_coverage_enabled(mut self) -> Self {
-        self.multisample_state.alpha_to_coverage_enable = true;
+        self.multisample_state.alpha_to_coverage_enable = StateMode::Fixed(true);
         self
     }
 
@@ -3886,7 +4265,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     #[deprecated(since = "0.27", note = "Use `multisample_state` instead")]
     #[inline]
     pub fn alpha_to_one_disabled(mut self) -> Self {
-        self.multisample_state.alpha_to_one_enable = false;
+        self.multisample_state.alpha_to_one_enable = StateMode::Fixed(false);
         self
     }
 
@@ -3899,7 +4278,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     #[deprecated(since = "0.27", note = "Use `multisample_state` instead")]
     #[inline]
     pub fn alpha_to_one_enabled(mut self) -> Self {
-        self.multisample_state.alpha_to_one_enable = true;
+        self.multisample_state.alpha_to_one_enable = StateMode::Fixed(true);
         self
     }
 
@@ -3950,8 +4329,8 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     #[inline]
     pub fn blend_collective(mut self, blend: AttachmentBlend) -> Self {
         self.color_blend_state.attachments = vec![ColorBlendAttachmentState {
-            blend: Some(blend),
-            color_write_mask: ColorComponents::all(),
+            blend: Some(StateMode::Fixed(blend)),
+            color_write_mask: StateMode::Fixed(ColorComponents::all()),
             color_write_enable: StateMode::Fixed(true),
         }];
         self
@@ -3966,8 +4345,8 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         self.color_blend_state.attachments = blend
             .into_iter()
             .map(|x| ColorBlendAttachmentState {
-                blend: Some(x),
-                color_write_mask: ColorComponents::all(),
+                blend: Some(StateMode::Fixed(x)),
+                color_write_mask: StateMode::Fixed(ColorComponents::all()),
                 color_write_enable: StateMode::Fixed(true),
             })
             .collect();
@@ -3983,7 +4362,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     pub fn blend_pass_through(mut self) -> Self {
         self.color_blend_state.attachments = vec![ColorBlendAttachmentState {
             blend: None,
-            color_write_mask: ColorComponents::all(),
+            color_write_mask: StateMode::Fixed(ColorComponents::all()),
             color_write_enable: StateMode::Fixed(true),
         }];
         self
@@ -3995,8 +4374,8 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     #[inline]
     pub fn blend_alpha_blending(mut self) -> Self {
         self.color_blend_state.attachments = vec![ColorBlendAttachmentState {
-            blend: Some(AttachmentBlend::alpha()),
-            color_write_mask: ColorComponents::all(),
+            blend: Some(StateMode::Fixed(AttachmentBlend::alpha())),
+            color_write_mask: StateMode::Fixed(ColorComponents::all()),
             color_write_enable: StateMode::Fixed(true),
         }];
         self
@@ -4043,6 +4422,11 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         GraphicsPipelineBuilder {
             render_pass: Some(render_pass.into()),
             cache: self.cache,
+            cache_statistics: self.cache_statistics,
+            executable_capture: self.executable_capture,
+            robustness: self.robustness,
+            fail_on_compile_required: self.fail_on_compile_required,
+            debug_name: self.debug_name,
 
             vertex_shader: self.vertex_shader,
             tessellation_shaders: self.tessellation_shaders,
@@ -4072,6 +4456,74 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         self.cache = Some(pipeline_cache);
         self
     }
+
+    /// Records whether this pipeline was found in the cache passed to
+    /// [`build_with_cache`](Self::build_with_cache) into the
+    /// [statistics](PipelineCache::statistics) of the cache.
+    ///
+    /// This requires Vulkan 1.3 or the
+    /// [`ext_pipeline_creation_feedback`](crate::device::DeviceExtensions::ext_pipeline_creation_feedback)
+    /// extension to be enabled on the device. It does nothing if no cache is used. Pipelines for
+    /// which the implementation doesn't report whether the cache was hit are not counted.
+    #[inline]
+    pub fn record_cache_statistics(mut self) -> Self {
+        self.cache_statistics = true;
+        self
+    }
+
+    /// Sets which information about the executables of the pipeline is captured when it is
+    /// built, so that it can be queried later with
+    /// [`GraphicsPipeline::executable_statistics`] and
+    /// [`GraphicsPipeline::executable_internal_representations`].
+    ///
+    /// If any information is captured, the
+    /// [`pipeline_executable_info`](crate::device::Features::pipeline_executable_info) feature
+    /// must be enabled on the device.
+    #[inline]
+    pub fn capture_executable_info(mut self, capture: PipelineExecutableCapture) -> Self {
+        self.executable_capture = capture;
+        self
+    }
+
+    /// Sets the robustness behavior of the pipeline, overriding that of the device for the
+    /// resources that the pipeline accesses.
+    ///
+    /// If any value other than `DeviceDefault` is set, the
+    /// [`pipeline_robustness`](crate::device::Features::pipeline_robustness) feature must be
+    /// enabled on the device.
+    #[inline]
+    pub fn robustness(mut self, robustness: PipelineRobustness) -> Self {
+        self.robustness = robustness;
+        self
+    }
+
+    /// Sets whether building the pipeline should fail with
+    /// [`GraphicsPipelineCreationError::PipelineCompileRequired`] instead of compiling it, if
+    /// the implementation cannot find the compiled pipeline in its caches.
+    ///
+    /// This can be used to check whether a pipeline can be created quickly, and to use a
+    /// fallback pipeline in the meantime otherwise.
+    ///
+    /// If set to `true`, the
+    /// [`pipeline_creation_cache_control`](crate::device::Features::pipeline_creation_cache_control)
+    /// feature must be enabled on the device.
+    #[inline]
+    pub fn fail_on_compile_required(mut self, fail_on_compile_required: bool) -> Self {
+        self.fail_on_compile_required = fail_on_compile_required;
+        self
+    }
+
+    /// Sets a name to give to the pipeline, to identify it in debugging tools.
+    ///
+    /// If the [`ext_debug_utils`](crate::instance::InstanceExtensions::ext_debug_utils) extension
+    /// is enabled on the instance, the name is set with
+    /// [`Device::set_debug_utils_object_name`](crate::device::Device::set_debug_utils_object_name)
+    /// when the pipeline is built. Otherwise it is ignored.
+    #[inline]
+    pub fn debug_name(mut self, debug_name: impl Into<Cow<'static, str>>) -> Self {
+        self.debug_name = Some(debug_name.into());
+        self
+    }
 }
 
 impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss> Clone
@@ -4088,6 +4540,11 @@ where
         GraphicsPipelineBuilder {
             render_pass: self.render_pass.clone(),
             cache: self.cache.clone(),
+            cache_statistics: self.cache_statistics,
+            executable_capture: self.executable_capture,
+            robustness: self.robustness,
+            fail_on_compile_required: self.fail_on_compile_required,
+            debug_name: self.debug_name.clone(),
 
             vertex_shader: self.vertex_shader.clone(),
             tessellation_shaders: self.tessellation_shaders.clone(),
@@ -42,6 +42,10 @@ pub struct ColorBlendState {
     /// If there are multiple elements, and the `blend` and `color_write_mask` members of each
     /// element differ, then the [`independent_blend`](crate::device::Features::independent_blend)
     /// feature must be enabled on the device.
+    ///
+    /// If the `blend` member of any element is `Some(Dynamic)`, then the `blend` member of every
+    /// element must be either `None` or `Some(Dynamic)`. Likewise, if the `color_write_mask`
+    /// member of any element is `Dynamic`, then it must be `Dynamic` for every element.
     pub attachments: Vec<ColorBlendAttachmentState>,
 
     /// The constant color to use for some of the `BlendFactor` variants.
@@ -59,7 +63,7 @@ impl ColorBlendState {
             attachments: (0..num)
                 .map(|_| ColorBlendAttachmentState {
                     blend: None,
-                    color_write_mask: ColorComponents::all(),
+                    color_write_mask: StateMode::Fixed(ColorComponents::all()),
                     color_write_enable: StateMode::Fixed(true),
                 })
                 .collect(),
@@ -86,16 +90,25 @@ impl ColorBlendState {
     pub fn blend(mut self, blend: AttachmentBlend) -> Self {
         self.attachments
             .iter_mut()
-            .for_each(|attachment_state| attachment_state.blend = Some(blend));
+            .for_each(|attachment_state| attachment_state.blend = Some(StateMode::Fixed(blend)));
         self
     }
 
-    /// Enables blending for all attachments, with alpha blending.
+    /// Enables blending for all attachments, with dynamic blend equations.
     #[inline]
-    pub fn blend_alpha(mut self) -> Self {
+    pub fn blend_dynamic(mut self) -> Self {
         self.attachments
             .iter_mut()
-            .for_each(|attachment_state| attachment_state.blend = Some(AttachmentBlend::alpha()));
+            .for_each(|attachment_state| attachment_state.blend = Some(StateMode::Dynamic));
+        self
+    }
+
+    /// Enables blending for all attachments, with alpha blending.
+    #[inline]
+    pub fn blend_alpha(mut self) -> Self {
+        self.attachments.iter_mut().for_each(|attachment_state| {
+            attachment_state.blend = Some(StateMode::Fixed(AttachmentBlend::alpha()))
+        });
         self
     }
 
@@ -103,7 +116,16 @@ impl ColorBlendState {
     #[inline]
     pub fn blend_additive(mut self) -> Self {
         self.attachments.iter_mut().for_each(|attachment_state| {
-            attachment_state.blend = Some(AttachmentBlend::additive())
+            attachment_state.blend = Some(StateMode::Fixed(AttachmentBlend::additive()))
+        });
+        self
+    }
+
+    /// Enables blending for all attachments, with premultiplied alpha blending.
+    #[inline]
+    pub fn blend_premultiplied_alpha(mut self) -> Self {
+        self.attachments.iter_mut().for_each(|attachment_state| {
+            attachment_state.blend = Some(StateMode::Fixed(AttachmentBlend::premultiplied_alpha()))
         });
         self
     }
@@ -111,9 +133,18 @@ impl ColorBlendState {
     /// Sets the color write mask for all attachments.
     #[inline]
     pub fn color_write_mask(mut self, color_write_mask: ColorComponents) -> Self {
+        self.attachments.iter_mut().for_each(|attachment_state| {
+            attachment_state.color_write_mask = StateMode::Fixed(color_write_mask)
+        });
+        self
+    }
+
+    /// Sets the color write mask for all attachments to dynamic.
+    #[inline]
+    pub fn color_write_mask_dynamic(mut self) -> Self {
         self.attachments
             .iter_mut()
-            .for_each(|attachment_state| attachment_state.color_write_mask = color_write_mask);
+            .for_each(|attachment_state| attachment_state.color_write_mask = StateMode::Dynamic);
         self
     }
 
@@ -205,10 +236,18 @@ pub struct ColorBlendAttachmentState {
     /// The blend parameters for the attachment.
     ///
     /// If set to `None`, blending is disabled, and all incoming pixels will be used directly.
-    pub blend: Option<AttachmentBlend>,
+    ///
+    /// If set to `Some(Dynamic)`, the
+    /// [`extended_dynamic_state3_color_blend_equation`](crate::device::Features::extended_dynamic_state3_color_blend_equation)
+    /// feature must be enabled on the device.
+    pub blend: Option<StateMode<AttachmentBlend>>,
 
     /// Sets which components of the final pixel value are written to the attachment.
-    pub color_write_mask: ColorComponents,
+    ///
+    /// If set to `Dynamic`, the
+    /// [`extended_dynamic_state3_color_write_mask`](crate::device::Features::extended_dynamic_state3_color_write_mask)
+    /// feature must be enabled on the device.
+    pub color_write_mask: StateMode<ColorComponents>,
 
     /// Sets whether anything at all is written to the attachment. If enabled, the pixel data
     /// that is written is determined by the `color_write_mask`. If disabled, the mask is ignored
@@ -273,6 +312,27 @@ impl AttachmentBlend {
         }
     }
 
+    /// Builds an `AttachmentBlend` where the output will be merged with the existing value
+    /// based on the alpha of the source, assuming the source color has already been multiplied
+    /// by its alpha.
+    ///
+    /// This is the correct mode for textures with premultiplied alpha, which is what most image
+    /// compositors and font rasterizers produce, and for anything rendered into an intermediate
+    /// target that is later composited. Using [`alpha`](Self::alpha) on premultiplied data
+    /// darkens semi-transparent edges, and using it to render into a target with an alpha
+    /// channel produces an alpha value that is not suitable for further compositing.
+    #[inline]
+    pub fn premultiplied_alpha() -> Self {
+        Self {
+            color_op: BlendOp::Add,
+            color_source: BlendFactor::One,
+            color_destination: BlendFactor::OneMinusSrcAlpha,
+            alpha_op: BlendOp::Add,
+            alpha_source: BlendFactor::One,
+            alpha_destination: BlendFactor::OneMinusSrcAlpha,
+        }
+    }
+
     /// Builds an `AttachmentBlend` where the colors are added, and alpha is set to the maximum of
     /// the two.
     #[inline]
@@ -304,6 +364,20 @@ impl From<AttachmentBlend> for ash::vk::PipelineColorBlendAttachmentState {
     }
 }
 
+impl From<AttachmentBlend> for ash::vk::ColorBlendEquationEXT {
+    #[inline]
+    fn from(val: AttachmentBlend) -> Self {
+        ash::vk::ColorBlendEquationEXT {
+            src_color_blend_factor: val.color_source.into(),
+            dst_color_blend_factor: val.color_destination.into(),
+            color_blend_op: val.color_op.into(),
+            src_alpha_blend_factor: val.alpha_source.into(),
+            dst_alpha_blend_factor: val.alpha_destination.into(),
+            alpha_blend_op: val.alpha_op.into(),
+        }
+    }
+}
+
 /// The operation that takes `source` (output from the fragment shader), `destination` (value
 /// currently in the framebuffer attachment) and `blend_constant` input values,
 /// and produces new inputs to be fed to `BlendOp`.
@@ -32,6 +32,10 @@ pub enum GraphicsPipelineCreationError {
         reason: &'static str,
     },
 
+    /// Some color blend attachments had a dynamic blend equation or color write mask, while
+    /// others had a fixed one.
+    ColorBlendAttachmentDynamicStateMismatch,
+
     /// A color attachment has a format that does not support blending.
     ColorAttachmentFormatBlendNotSupported { attachment_index: u32 },
 
@@ -48,6 +52,10 @@ pub enum GraphicsPipelineCreationError {
     /// expects.
     FragmentShaderRenderPassIncompatible,
 
+    /// The fragment shader reads from tile images, but the pipeline is created for a render pass
+    /// subpass. Tile images can only be read when using dynamic rendering.
+    FragmentShaderTileImageRenderPass,
+
     /// The pipeline layout is not compatible with what the shaders expect.
     IncompatiblePipelineLayout(PipelineLayoutSupersetError),
 
@@ -64,6 +72,18 @@ pub enum GraphicsPipelineCreationError {
     /// `patch_control_points` was not greater than 0 and less than or equal to the `max_tessellation_patch_size` limit.
     InvalidNumPatchControlPoints,
 
+    /// The `max_clip_distances` limit has been exceeded by a shader stage.
+    MaxClipDistancesExceeded { clip_distances: u32, max: u32 },
+
+    /// The `max_combined_clip_and_cull_distances` limit has been exceeded by a shader stage.
+    MaxCombinedClipAndCullDistancesExceeded {
+        clip_and_cull_distances: u32,
+        max: u32,
+    },
+
+    /// The `max_cull_distances` limit has been exceeded by a shader stage.
+    MaxCullDistancesExceeded { cull_distances: u32, max: u32 },
+
     /// The maximum number of discard rectangles has been exceeded.
     MaxDiscardRectanglesExceeded {
         /// Maximum allowed value.
@@ -157,6 +177,11 @@ pub enum GraphicsPipelineCreationError {
     /// Error while creating the pipeline layout object.
     PipelineLayoutCreationError(PipelineLayoutCreationError),
 
+    /// The pipeline needed to be compiled, because the implementation could not find it in its
+    /// caches, but `fail_on_compile_required` was set or a shader module was created from an
+    /// identifier.
+    PipelineCompileRequired,
+
     /// The output interface of one shader and the input interface of the next shader do not match.
     ShaderStagesMismatch(ShaderInterfaceMismatchError),
 
@@ -228,6 +253,10 @@ impl fmt::Display for GraphicsPipelineCreationError {
                 "the feature {} must be enabled: {}",
                 feature, reason
             ),
+            Self::ColorBlendAttachmentDynamicStateMismatch => write!(
+                f,
+                "some color blend attachments had a dynamic blend equation or color write mask, while others had a fixed one",
+            ),
             Self::ColorAttachmentFormatBlendNotSupported { attachment_index } => write!(
                 f,
                 "color attachment {} has a format that does not support blending",
@@ -250,6 +279,10 @@ impl fmt::Display for GraphicsPipelineCreationError {
                 f,
                 "the output of the fragment shader is not compatible with what the render pass subpass expects",
             ),
+            Self::FragmentShaderTileImageRenderPass => write!(
+                f,
+                "the fragment shader reads from tile images, but the pipeline is created for a render pass subpass",
+            ),
             Self::IncompatiblePipelineLayout(_) => write!(
                 f,
                 "the pipeline layout is not compatible with what the shaders expect",
@@ -270,6 +303,31 @@ impl fmt::Display for GraphicsPipelineCreationError {
                 f,
                 "patch_control_points was not greater than 0 and less than or equal to the max_tessellation_patch_size limit",
             ),
+            Self::MaxClipDistancesExceeded {
+                clip_distances,
+                max,
+            } => write!(
+                f,
+                "a shader stage uses {} clip distances, but the `max_clip_distances` limit is {}",
+                clip_distances, max,
+            ),
+            Self::MaxCombinedClipAndCullDistancesExceeded {
+                clip_and_cull_distances,
+                max,
+            } => write!(
+                f,
+                "a shader stage uses {} clip and cull distances combined, but the \
+                `max_combined_clip_and_cull_distances` limit is {}",
+                clip_and_cull_distances, max,
+            ),
+            Self::MaxCullDistancesExceeded {
+                cull_distances,
+                max,
+            } => write!(
+                f,
+                "a shader stage uses {} cull distances, but the `max_cull_distances` limit is {}",
+                cull_distances, max,
+            ),
             Self::MaxDiscardRectanglesExceeded { .. } => write!(
                 f,
                 "the maximum number of discard rectangles has been exceeded",
@@ -333,6 +391,10 @@ impl fmt::Display for GraphicsPipelineCreationError {
                 f,
                 "error while creating the pipeline layout object",
             ),
+            Self::PipelineCompileRequired => write!(
+                f,
+                "the pipeline needs to be compiled, but was requested to fail instead",
+            ),
             Self::ShaderStagesMismatch(_) => write!(
                 f,
                 "the output interface of one shader and the input interface of the next shader do not match",
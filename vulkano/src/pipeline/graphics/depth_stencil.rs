@@ -70,6 +70,65 @@ impl DepthStencilState {
             stencil: Default::default(),
         }
     }
+
+    /// Creates a `DepthStencilState` with a `Less` depth test, `depth_write` set to false, and
+    /// other tests disabled.
+    ///
+    /// This is meant for geometry that must be occluded by the scene but must not occlude
+    /// anything itself, such as transparent objects drawn after the opaque ones.
+    #[inline]
+    pub fn simple_depth_test_read_only() -> Self {
+        Self {
+            depth: Some(DepthState {
+                enable_dynamic: false,
+                compare_op: StateMode::Fixed(CompareOp::Less),
+                write_enable: StateMode::Fixed(false),
+            }),
+            depth_bounds: Default::default(),
+            stencil: Default::default(),
+        }
+    }
+
+    /// Creates a `DepthStencilState` with an `Equal` depth test, `depth_write` set to false, and
+    /// other tests disabled.
+    ///
+    /// This is meant for the shading pass that follows a depth pre-pass: the depth buffer already
+    /// holds the closest depth of every pixel, so only the fragments that produced it are shaded.
+    /// The vertex shader of both passes must compute positions identically, using the `invariant`
+    /// qualifier if needed, or fragments will fail the test.
+    #[inline]
+    pub fn depth_equal() -> Self {
+        Self {
+            depth: Some(DepthState {
+                enable_dynamic: false,
+                compare_op: StateMode::Fixed(CompareOp::Equal),
+                write_enable: StateMode::Fixed(false),
+            }),
+            depth_bounds: Default::default(),
+            stencil: Default::default(),
+        }
+    }
+
+    /// Creates a `DepthStencilState` with a `Greater` depth test, `depth_write` set to true, and
+    /// other tests disabled.
+    ///
+    /// This is meant for a reversed depth range, where the near plane maps to a depth of 1.0 and
+    /// the far plane to 0.0, which greatly improves the precision of floating-point depth
+    /// buffers. The projection matrix must be built accordingly, and the depth buffer must be
+    /// cleared to 0.0 rather than 1.0. Using [`simple_depth_test`](Self::simple_depth_test) with
+    /// a reversed projection silently discards all geometry.
+    #[inline]
+    pub fn reverse_z() -> Self {
+        Self {
+            depth: Some(DepthState {
+                enable_dynamic: false,
+                compare_op: StateMode::Fixed(CompareOp::Greater),
+                write_enable: StateMode::Fixed(true),
+            }),
+            depth_bounds: Default::default(),
+            stencil: Default::default(),
+        }
+    }
 }
 
 impl Default for DepthStencilState {
@@ -291,7 +350,7 @@ pub struct DynamicStencilValue {
 /// Specifies how two values should be compared to decide whether a test passes or fails.
 ///
 /// Used for both depth testing and stencil testing.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(i32)]
 pub enum CompareOp {
     /// The test never passes.
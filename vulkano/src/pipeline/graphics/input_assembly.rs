@@ -10,9 +10,11 @@
 //! Configures how input vertices are assembled into primitives.
 
 use crate::{
+    buffer::{BufferAccess, TypedBufferAccess},
     pipeline::{PartialStateMode, StateMode},
     DeviceSize,
 };
+use std::sync::Arc;
 
 /// The state in a graphics pipeline describing how the input assembly stage should behave.
 #[derive(Clone, Copy, Debug)]
@@ -235,6 +237,17 @@ impl IndexType {
             IndexType::U32 => 4,
         }
     }
+
+    /// Returns the special index value that restarts the primitive when primitive restart is
+    /// enabled. This is the maximum value that can be represented by the index type.
+    #[inline]
+    pub fn primitive_restart_index(&self) -> u32 {
+        match self {
+            IndexType::U8 => u8::MAX as u32,
+            IndexType::U16 => u16::MAX as u32,
+            IndexType::U32 => u32::MAX,
+        }
+    }
 }
 
 impl From<IndexType> for ash::vk::IndexType {
@@ -243,3 +256,89 @@ impl From<IndexType> for ash::vk::IndexType {
         Self::from_raw(val as i32)
     }
 }
+
+/// A buffer of indices, together with the type of the indices it contains.
+///
+/// This can only be created from a buffer whose content is a slice of an [`Index`] type, so the
+/// index type can never mismatch the data.
+#[derive(Clone, Debug)]
+pub struct IndexBuffer {
+    buffer: Arc<dyn BufferAccess>,
+    index_type: IndexType,
+}
+
+impl IndexBuffer {
+    /// Creates a new `IndexBuffer` from a typed buffer.
+    #[inline]
+    pub fn new<B, I>(buffer: Arc<B>) -> Self
+    where
+        B: TypedBufferAccess<Content = [I]> + 'static,
+        I: Index + 'static,
+    {
+        Self {
+            buffer,
+            index_type: I::ty(),
+        }
+    }
+
+    /// Returns the buffer containing the indices.
+    #[inline]
+    pub fn buffer(&self) -> &Arc<dyn BufferAccess> {
+        &self.buffer
+    }
+
+    /// Returns the type of the indices in the buffer.
+    #[inline]
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+
+    /// Returns the number of indices in the buffer.
+    #[inline]
+    pub fn len(&self) -> DeviceSize {
+        self.buffer.size() / self.index_type.size()
+    }
+
+    /// Returns whether the buffer contains no indices.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<B, I> From<Arc<B>> for IndexBuffer
+where
+    B: TypedBufferAccess<Content = [I]> + 'static,
+    I: Index + 'static,
+{
+    #[inline]
+    fn from(buffer: Arc<B>) -> Self {
+        Self::new(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexBuffer, IndexType};
+    use crate::buffer::{BufferUsage, CpuAccessibleBuffer};
+
+    #[test]
+    fn index_buffer_type() {
+        let (device, _) = gfx_dev_and_queue!();
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device,
+            BufferUsage::index_buffer(),
+            false,
+            [0u16, 1, 2, u16::MAX, 3, 4, 5],
+        )
+        .unwrap();
+
+        let index_buffer = IndexBuffer::from(buffer);
+        assert_eq!(index_buffer.index_type(), IndexType::U16);
+        assert_eq!(index_buffer.len(), 7);
+        assert_eq!(
+            index_buffer.index_type().primitive_restart_index(),
+            u16::MAX as u32
+        );
+    }
+}
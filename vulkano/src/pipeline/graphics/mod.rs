@@ -64,7 +64,14 @@ use self::{
     render_pass::PipelineRenderPassType, tessellation::TessellationState,
     vertex_input::VertexInputState, viewport::ViewportState,
 };
-use super::{DynamicState, Pipeline, PipelineBindPoint, PipelineLayout};
+use super::{
+    executable::{
+        self, PipelineExecutableCapture, PipelineExecutableError,
+        PipelineExecutableInternalRepresentation, PipelineExecutableProperties,
+        PipelineExecutableStatistic,
+    },
+    DynamicState, Pipeline, PipelineBindPoint, PipelineLayout,
+};
 use crate::{
     device::{Device, DeviceOwned},
     shader::{DescriptorRequirements, ShaderStage},
@@ -102,6 +109,7 @@ pub struct GraphicsPipeline {
     device: Arc<Device>,
     layout: Arc<PipelineLayout>,
     render_pass: PipelineRenderPassType,
+    executable_capture: PipelineExecutableCapture,
 
     // TODO: replace () with an object that describes the shaders in some way.
     shaders: HashMap<ShaderStage, ()>,
@@ -238,6 +246,53 @@ impl GraphicsPipeline {
     pub fn dynamic_states(&self) -> impl ExactSizeIterator<Item = (DynamicState, bool)> + '_ {
         self.dynamic_state.iter().map(|(k, v)| (*k, *v))
     }
+
+    /// Returns the properties of the executables that the pipeline was compiled into.
+    ///
+    /// The [`khr_pipeline_executable_properties`] extension and the
+    /// [`pipeline_executable_info`](crate::device::Features::pipeline_executable_info) feature
+    /// must be enabled on the device.
+    ///
+    /// [`khr_pipeline_executable_properties`]: crate::device::DeviceExtensions::khr_pipeline_executable_properties
+    #[inline]
+    pub fn executable_properties(
+        &self,
+    ) -> Result<Vec<PipelineExecutableProperties>, PipelineExecutableError> {
+        executable::executable_properties(&self.device, self.handle)
+    }
+
+    /// Returns statistics about the executable with index `executable_index`.
+    ///
+    /// The pipeline must have been built with [`PipelineExecutableCapture::statistics`] enabled.
+    #[inline]
+    pub fn executable_statistics(
+        &self,
+        executable_index: u32,
+    ) -> Result<Vec<PipelineExecutableStatistic>, PipelineExecutableError> {
+        executable::executable_statistics(
+            &self.device,
+            self.handle,
+            self.executable_capture,
+            executable_index,
+        )
+    }
+
+    /// Returns the internal representations of the executable with index `executable_index`.
+    ///
+    /// The pipeline must have been built with
+    /// [`PipelineExecutableCapture::internal_representations`] enabled.
+    #[inline]
+    pub fn executable_internal_representations(
+        &self,
+        executable_index: u32,
+    ) -> Result<Vec<PipelineExecutableInternalRepresentation>, PipelineExecutableError> {
+        executable::executable_internal_representations(
+            &self.device,
+            self.handle,
+            self.executable_capture,
+            executable_index,
+        )
+    }
 }
 
 impl Pipeline for GraphicsPipeline {
@@ -10,7 +10,7 @@
 //! Generates multiple fragments per framebuffer pixel when rasterizing. This can be used for
 //! anti-aliasing.
 
-use crate::image::SampleCount;
+use crate::{image::SampleCount, pipeline::StateMode};
 
 // TODO: handle some weird behaviors with non-floating-point targets
 
@@ -47,14 +47,20 @@ pub struct MultisampleState {
     /// then about half of the samples will be discarded. If you render to a multisample image, this
     /// means that the color will end up being mixed with whatever color was underneath, which gives
     /// the same effect as alpha blending.
-    pub alpha_to_coverage_enable: bool,
+    ///
+    /// If set to `Dynamic`, the
+    /// [`extended_dynamic_state3_alpha_to_coverage_enable`](crate::device::Features::extended_dynamic_state3_alpha_to_coverage_enable)
+    /// feature must be enabled on the device.
+    pub alpha_to_coverage_enable: StateMode<bool>,
 
     /// Controls whether the alpha value of all the samples will be forced to 1.0 (or the
     /// maximum possible value) after the effects of `alpha_to_coverage` have been applied.
     ///
-    /// If set to `true`, the [`alpha_to_one`](crate::device::Features::alpha_to_one)
+    /// If set to `Fixed(true)`, the [`alpha_to_one`](crate::device::Features::alpha_to_one)
+    /// feature must be enabled on the device. If set to `Dynamic`, the
+    /// [`extended_dynamic_state3_alpha_to_one_enable`](crate::device::Features::extended_dynamic_state3_alpha_to_one_enable)
     /// feature must be enabled on the device.
-    pub alpha_to_one_enable: bool,
+    pub alpha_to_one_enable: StateMode<bool>,
 }
 
 impl MultisampleState {
@@ -65,8 +71,8 @@ impl MultisampleState {
             rasterization_samples: SampleCount::Sample1,
             sample_shading: None,
             sample_mask: [0xFFFFFFFF; 2],
-            alpha_to_coverage_enable: false,
-            alpha_to_one_enable: false,
+            alpha_to_coverage_enable: StateMode::Fixed(false),
+            alpha_to_one_enable: StateMode::Fixed(false),
         }
     }
 }
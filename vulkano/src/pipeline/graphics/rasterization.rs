@@ -18,8 +18,10 @@ pub struct RasterizationState {
     /// false, fragments whose depth is outside of this range will be discarded.
     ///
     /// If enabled, the [`depth_clamp`](crate::device::Features::depth_clamp) feature must be
-    /// enabled on the device.
-    pub depth_clamp_enable: bool,
+    /// enabled on the device. If set to `Dynamic`, the
+    /// [`extended_dynamic_state3_depth_clamp_enable`](crate::device::Features::extended_dynamic_state3_depth_clamp_enable)
+    /// feature must be enabled on the device.
+    pub depth_clamp_enable: StateMode<bool>,
 
     /// If true, all the fragments will be discarded, and the fragment shader will not be run. This
     /// is usually used when your vertex shader has some side effects and you don't need to run the
@@ -35,8 +37,10 @@ pub struct RasterizationState {
     ///
     /// If set to a value other than `Fill`, the
     /// [`fill_mode_non_solid`](crate::device::Features::fill_mode_non_solid) feature must be
-    /// enabled on the device.
-    pub polygon_mode: PolygonMode,
+    /// enabled on the device. If set to `Dynamic`, the
+    /// [`extended_dynamic_state3_polygon_mode`](crate::device::Features::extended_dynamic_state3_polygon_mode)
+    /// feature must be enabled on the device.
+    pub polygon_mode: StateMode<PolygonMode>,
 
     /// Specifies whether front faces or back faces should be discarded, or none, or both.
     ///
@@ -87,9 +91,9 @@ impl RasterizationState {
     #[inline]
     pub fn new() -> Self {
         Self {
-            depth_clamp_enable: false,
+            depth_clamp_enable: StateMode::Fixed(false),
             rasterizer_discard_enable: StateMode::Fixed(false),
-            polygon_mode: Default::default(),
+            polygon_mode: StateMode::Fixed(Default::default()),
             cull_mode: StateMode::Fixed(Default::default()),
             front_face: StateMode::Fixed(Default::default()),
             depth_bias: None,
@@ -102,7 +106,28 @@ impl RasterizationState {
     /// Sets the polygon mode.
     #[inline]
     pub fn polygon_mode(mut self, polygon_mode: PolygonMode) -> Self {
-        self.polygon_mode = polygon_mode;
+        self.polygon_mode = StateMode::Fixed(polygon_mode);
+        self
+    }
+
+    /// Sets the polygon mode to dynamic.
+    #[inline]
+    pub fn polygon_mode_dynamic(mut self) -> Self {
+        self.polygon_mode = StateMode::Dynamic;
+        self
+    }
+
+    /// Sets whether depth clamping is enabled.
+    #[inline]
+    pub fn depth_clamp_enable(mut self, enable: bool) -> Self {
+        self.depth_clamp_enable = StateMode::Fixed(enable);
+        self
+    }
+
+    /// Sets whether depth clamping is enabled to dynamic.
+    #[inline]
+    pub fn depth_clamp_enable_dynamic(mut self) -> Self {
+        self.depth_clamp_enable = StateMode::Dynamic;
         self
     }
 
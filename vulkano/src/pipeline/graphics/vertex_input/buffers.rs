@@ -7,7 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
-use super::VertexMemberInfo;
+use super::{VertexMember, VertexMemberInfo, VertexMemberTy};
 use crate::pipeline::graphics::vertex_input::IncompatibleVertexDefinitionError;
 use crate::pipeline::graphics::vertex_input::Vertex;
 use crate::pipeline::graphics::vertex_input::VertexDefinition;
@@ -15,33 +15,78 @@ use crate::pipeline::graphics::vertex_input::VertexInputAttributeDescription;
 use crate::pipeline::graphics::vertex_input::VertexInputBindingDescription;
 use crate::pipeline::graphics::vertex_input::VertexInputRate;
 use crate::pipeline::graphics::vertex_input::VertexInputState;
-use crate::shader::ShaderInterface;
+use crate::shader::{ShaderInterface, ShaderScalarType};
 use crate::DeviceSize;
-use std::mem;
+use std::{borrow::Cow, mem};
 
 /// A vertex definition for any number of vertex and instance buffers.
+///
+/// Each buffer that is added becomes a binding, numbered in the order that the buffers were
+/// added. A buffer can either contain interleaved elements of a [`Vertex`] type, which provide
+/// several shader inputs at once, or a single deinterleaved stream of values that provides one
+/// shader input. Both kinds can be mixed freely, so that for example positions and normals can be
+/// read from separate buffers while per-instance data is interleaved in a third.
 #[derive(Clone, Debug, Default)]
 pub struct BuffersDefinition(Vec<VertexBuffer>);
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Debug)]
 struct VertexBuffer {
-    info_fn: fn(&str) -> Option<VertexMemberInfo>,
+    members: VertexBufferMembers,
     stride: u32,
     input_rate: VertexInputRate,
 }
 
-impl std::fmt::Debug for VertexBuffer {
+#[derive(Clone)]
+enum VertexBufferMembers {
+    // The members of a `Vertex` type, interleaved in each element.
+    Interleaved(fn(&str) -> Option<VertexMemberInfo>),
+    // A single shader input, which is the only thing in each element.
+    Stream {
+        name: Cow<'static, str>,
+        ty: VertexMemberTy,
+        array_size: usize,
+    },
+}
+
+impl VertexBufferMembers {
+    fn member(&self, name: &str) -> Option<VertexMemberInfo> {
+        match self {
+            Self::Interleaved(info_fn) => info_fn(name),
+            Self::Stream {
+                name: stream_name,
+                ty,
+                array_size,
+            } => (stream_name == name).then(|| VertexMemberInfo {
+                offset: 0,
+                ty: *ty,
+                array_size: *array_size,
+                format: None,
+            }),
+        }
+    }
+}
+
+impl std::fmt::Debug for VertexBufferMembers {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("VertexBuffer")
-            .field("stride", &self.stride)
-            .field("input_rate", &self.input_rate)
-            .finish()
+        match self {
+            Self::Interleaved(_) => f.write_str("Interleaved"),
+            Self::Stream {
+                name,
+                ty,
+                array_size,
+            } => f
+                .debug_struct("Stream")
+                .field("name", name)
+                .field("ty", ty)
+                .field("array_size", array_size)
+                .finish(),
+        }
     }
 }
 
-impl From<VertexBuffer> for VertexInputBindingDescription {
+impl From<&VertexBuffer> for VertexInputBindingDescription {
     #[inline]
-    fn from(val: VertexBuffer) -> Self {
+    fn from(val: &VertexBuffer) -> Self {
         Self {
             stride: val.stride,
             input_rate: val.input_rate,
@@ -55,10 +100,21 @@ impl BuffersDefinition {
         BuffersDefinition(Vec::new())
     }
 
+    /// Adds a new buffer containing elements of type `V` to the definition, with the input rate
+    /// returned by [`V::input_rate`](Vertex::input_rate).
+    pub fn buffer<V: Vertex>(mut self) -> Self {
+        self.0.push(VertexBuffer {
+            members: VertexBufferMembers::Interleaved(V::member),
+            stride: mem::size_of::<V>() as u32,
+            input_rate: V::input_rate(),
+        });
+        self
+    }
+
     /// Adds a new vertex buffer containing elements of type `V` to the definition.
     pub fn vertex<V: Vertex>(mut self) -> Self {
         self.0.push(VertexBuffer {
-            info_fn: V::member,
+            members: VertexBufferMembers::Interleaved(V::member),
             stride: mem::size_of::<V>() as u32,
             input_rate: VertexInputRate::Vertex,
         });
@@ -68,7 +124,7 @@ impl BuffersDefinition {
     /// Adds a new instance buffer containing elements of type `V` to the definition.
     pub fn instance<V: Vertex>(mut self) -> Self {
         self.0.push(VertexBuffer {
-            info_fn: V::member,
+            members: VertexBufferMembers::Interleaved(V::member),
             stride: mem::size_of::<V>() as u32,
             input_rate: VertexInputRate::Instance { divisor: 1 },
         });
@@ -88,12 +144,53 @@ impl BuffersDefinition {
     /// data.
     pub fn instance_with_divisor<V: Vertex>(mut self, divisor: u32) -> Self {
         self.0.push(VertexBuffer {
-            info_fn: V::member,
+            members: VertexBufferMembers::Interleaved(V::member),
             stride: mem::size_of::<V>() as u32,
             input_rate: VertexInputRate::Instance { divisor },
         });
         self
     }
+
+    /// Adds a new buffer containing elements of type `V` to the definition, with an explicit
+    /// stride and input rate.
+    ///
+    /// `stride` is the number of bytes from the start of one element to the start of the next.
+    /// It can be larger than the size of `V`, for example to read only the first part of each
+    /// element of a buffer that contains more data, but it must be large enough to hold every
+    /// member of `V` that is read by the vertex shader.
+    ///
+    /// See [`instance_with_divisor`](Self::instance_with_divisor) for the features that are
+    /// required for divisors other than 1.
+    pub fn binding<V: Vertex>(mut self, stride: u32, input_rate: VertexInputRate) -> Self {
+        self.0.push(VertexBuffer {
+            members: VertexBufferMembers::Interleaved(V::member),
+            stride,
+            input_rate,
+        });
+        self
+    }
+
+    /// Adds a new buffer containing a single deinterleaved stream of values of type `T`, which
+    /// are read by the vertex shader input named `name`.
+    ///
+    /// The stride of the buffer is the size of `T`.
+    pub fn stream<T: VertexMember>(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        input_rate: VertexInputRate,
+    ) -> Self {
+        let (ty, array_size) = T::format();
+        self.0.push(VertexBuffer {
+            members: VertexBufferMembers::Stream {
+                name: name.into(),
+                ty,
+                array_size,
+            },
+            stride: mem::size_of::<T>() as u32,
+            input_rate,
+        });
+        self
+    }
 }
 
 unsafe impl VertexDefinition for BuffersDefinition {
@@ -105,18 +202,22 @@ unsafe impl VertexDefinition for BuffersDefinition {
             .0
             .iter()
             .enumerate()
-            .map(|(binding, &buffer)| (binding as u32, buffer.into()));
+            .map(|(binding, buffer)| (binding as u32, buffer.into()));
         let mut attributes: Vec<(u32, VertexInputAttributeDescription)> = Vec::new();
 
         for element in interface.elements() {
             let name = element.name.as_ref().unwrap();
 
+            // If several buffers provide the attribute, it is read from the first one.
             let (infos, binding) = self
                 .0
                 .iter()
                 .enumerate()
                 .find_map(|(binding, buffer)| {
-                    (buffer.info_fn)(name).map(|infos| (infos, binding as u32))
+                    buffer
+                        .members
+                        .member(name)
+                        .map(|infos| (infos, binding as u32))
                 })
                 .ok_or_else(||
                     // TODO: move this check to GraphicsPipelineBuilder
@@ -124,22 +225,39 @@ unsafe impl VertexDefinition for BuffersDefinition {
                         attribute: name.clone().into_owned(),
                     })?;
 
-            if !infos.ty.matches(
-                infos.array_size,
-                element.ty.to_format(),
-                element.ty.num_locations(),
-            ) {
+            // If the member has an explicit format, it must be compatible with the type of the
+            // shader input, and the member's data must fit it.
+            let format = infos.format.unwrap_or_else(|| element.ty.to_format());
+            let compatible = format.type_color().map_or(false, |numeric_type| {
+                ShaderScalarType::from(numeric_type) == element.ty.base_type
+            });
+
+            if !compatible
+                || !infos
+                    .ty
+                    .matches(infos.array_size, format, element.ty.num_locations())
+            {
                 // TODO: move this check to GraphicsPipelineBuilder
                 return Err(IncompatibleVertexDefinitionError::FormatMismatch {
                     attribute: name.clone().into_owned(),
-                    shader: (
-                        element.ty.to_format(),
-                        (element.ty.num_locations()) as usize,
-                    ),
+                    shader: (element.ty.to_format(), element.ty.num_locations() as usize),
                     definition: (infos.ty, infos.array_size),
                 });
             }
 
+            // TODO: move this check to GraphicsPipelineBuilder
+            let stride = self.0[binding as usize].stride;
+            let end = infos.offset as DeviceSize
+                + format.block_size().unwrap() * element.ty.num_locations() as DeviceSize;
+
+            if end > stride as DeviceSize {
+                return Err(IncompatibleVertexDefinitionError::StrideTooSmall {
+                    attribute: name.clone().into_owned(),
+                    binding,
+                    stride,
+                });
+            }
+
             let mut offset = infos.offset as DeviceSize;
             let location_range = element.location..element.location + element.ty.num_locations();
 
@@ -148,11 +266,11 @@ unsafe impl VertexDefinition for BuffersDefinition {
                     location,
                     VertexInputAttributeDescription {
                         binding,
-                        format: element.ty.to_format(),
+                        format,
                         offset: offset as u32,
                     },
                 ));
-                offset += element.ty.to_format().block_size().unwrap();
+                offset += format.block_size().unwrap();
             }
         }
 
@@ -161,3 +279,115 @@ unsafe impl VertexDefinition for BuffersDefinition {
             .attributes(attributes))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::BuffersDefinition;
+    use crate::{
+        format::Format,
+        pipeline::graphics::vertex_input::{
+            IncompatibleVertexDefinitionError, VertexDefinition, VertexInputRate,
+        },
+        shader::{
+            ShaderInterface, ShaderInterfaceEntry, ShaderInterfaceEntryType, ShaderScalarType,
+        },
+    };
+
+    fn interface(entries: &[(&'static str, u32, u32)]) -> ShaderInterface {
+        let elements = entries
+            .iter()
+            .map(|&(name, location, num_components)| ShaderInterfaceEntry {
+                location,
+                component: 0,
+                name: Some(name.into()),
+                ty: ShaderInterfaceEntryType {
+                    base_type: ShaderScalarType::Float,
+                    num_components,
+                    num_elements: 1,
+                    is_64bit: false,
+                },
+            })
+            .collect();
+
+        unsafe { ShaderInterface::new_unchecked(elements) }
+    }
+
+    #[test]
+    fn multiple_streams() {
+        let interface = interface(&[("position", 0, 3), ("normal", 1, 3), ("offset", 2, 2)]);
+        let state = BuffersDefinition::new()
+            .stream::<[f32; 3]>("position", VertexInputRate::Vertex)
+            .stream::<[f32; 3]>("normal", VertexInputRate::Vertex)
+            .stream::<[f32; 2]>("offset", VertexInputRate::Instance { divisor: 2 })
+            .definition(&interface)
+            .unwrap();
+
+        assert_eq!(state.bindings.len(), 3);
+        assert_eq!(state.bindings[&0].stride, 12);
+        assert_eq!(state.bindings[&2].stride, 8);
+        assert!(matches!(
+            state.bindings[&2].input_rate,
+            VertexInputRate::Instance { divisor: 2 }
+        ));
+
+        for (location, binding, format) in [
+            (0, 0, Format::R32G32B32_SFLOAT),
+            (1, 1, Format::R32G32B32_SFLOAT),
+            (2, 2, Format::R32G32_SFLOAT),
+        ] {
+            let attribute = &state.attributes[&location];
+            assert_eq!(attribute.binding, binding);
+            assert_eq!(attribute.format, format);
+            assert_eq!(attribute.offset, 0);
+        }
+    }
+
+    #[test]
+    fn attribute_in_several_buffers() {
+        let interface = interface(&[("position", 0, 3)]);
+        let state = BuffersDefinition::new()
+            .stream::<[f32; 3]>("position", VertexInputRate::Vertex)
+            .stream::<[f32; 3]>("position", VertexInputRate::Vertex)
+            .definition(&interface)
+            .unwrap();
+
+        // The attribute is read from the first buffer that provides it.
+        assert_eq!(state.attributes[&0].binding, 0);
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn interleaved_binding() {
+        use crate::pipeline::graphics::vertex_input::Vertex;
+        use bytemuck::{Pod, Zeroable};
+
+        #[repr(C)]
+        #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+        struct Instance {
+            offset: [f32; 2],
+            scale: f32,
+        }
+
+        let interface = interface(&[("position", 0, 3), ("offset", 1, 2), ("scale", 2, 1)]);
+
+        let state = BuffersDefinition::new()
+            .stream::<[f32; 3]>("position", VertexInputRate::Vertex)
+            .binding::<Instance>(16, VertexInputRate::Instance { divisor: 1 })
+            .definition(&interface)
+            .unwrap();
+
+        assert_eq!(state.bindings[&1].stride, 16);
+        assert_eq!(state.attributes[&1].binding, 1);
+        assert_eq!(state.attributes[&2].offset, 8);
+
+        let result = BuffersDefinition::new()
+            .stream::<[f32; 3]>("position", VertexInputRate::Vertex)
+            .binding::<Instance>(8, VertexInputRate::Instance { divisor: 1 })
+            .definition(&interface);
+
+        assert!(matches!(
+            result,
+            Err(IncompatibleVertexDefinitionError::StrideTooSmall { binding: 1, .. })
+        ));
+    }
+}
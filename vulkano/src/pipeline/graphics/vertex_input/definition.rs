@@ -14,8 +14,7 @@
 //! The implementations of the `VertexDefinition` trait that are provided by vulkano require you to
 //! use a buffer whose content is `[V]` where `V` implements the `Vertex` trait.
 //!
-//! The `Vertex` trait is unsafe, but can be implemented on a struct with the `impl_vertex!`
-//! macro.
+//! The `Vertex` trait is unsafe, but can be implemented on a struct with `#[derive(Vertex)]`.
 //!
 //! # Example
 //!
@@ -28,22 +27,22 @@
 //! use vulkano::buffer::BufferAccess;
 //! use vulkano::buffer::BufferUsage;
 //! use vulkano::memory::HostVisible;
-//! use vulkano::pipeline::vertex::;
+//! use vulkano::pipeline::graphics::vertex_input::Vertex;
 //! # let device: Arc<Device> = return;
 //! # let queue: Arc<Queue> = return;
 //!
-//! struct Vertex {
+//! #[repr(C)]
+//! #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+//! struct MyVertex {
 //!     position: [f32; 2]
 //! }
 //!
-//! impl_vertex!(Vertex, position);
-//!
 //! let usage = BufferUsage {
 //!     vertex_buffer: true,
 //!     .. BufferUsage::none()
 //! };
 //!
-//! let vertex_buffer = BufferAccess::<[Vertex], _>::array(&device, 128, &usage, HostVisible, &queue)
+//! let vertex_buffer = BufferAccess::<[MyVertex], _>::array(&device, 128, &usage, HostVisible, &queue)
 //!                                                     .expect("failed to create buffer");
 //!
 //! // TODO: finish example
@@ -95,6 +94,16 @@ pub enum IncompatibleVertexDefinitionError {
         /// The format in the vertex definition.
         definition: (VertexMemberTy, usize),
     },
+
+    /// The stride of a buffer is too small to hold an attribute that is read from it.
+    StrideTooSmall {
+        /// Name of the attribute.
+        attribute: String,
+        /// The binding that the attribute is read from.
+        binding: u32,
+        /// The stride of the binding.
+        stride: u32,
+    },
 }
 
 impl error::Error for IncompatibleVertexDefinitionError {}
@@ -109,6 +118,15 @@ impl fmt::Display for IncompatibleVertexDefinitionError {
             IncompatibleVertexDefinitionError::FormatMismatch { .. } => {
                 write!(fmt, "the format of an attribute does not match")
             }
+            IncompatibleVertexDefinitionError::StrideTooSmall {
+                ref attribute,
+                binding,
+                stride,
+            } => write!(
+                fmt,
+                "the stride {} of binding {} is too small to hold the attribute `{}`",
+                stride, binding, attribute,
+            ),
         }
     }
 }
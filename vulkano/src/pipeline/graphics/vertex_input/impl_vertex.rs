@@ -10,6 +10,10 @@
 use crate::pipeline::graphics::vertex_input::VertexMemberTy;
 
 /// Implements the `Vertex` trait on a struct.
+///
+/// This macro is deprecated in favour of `#[derive(Vertex)]`, which does not require the struct
+/// to implement `Default`, and supports explicit formats and per-instance structs.
+///
 ///# Example
 ///
 ///```
@@ -22,9 +26,11 @@ use crate::pipeline::graphics::vertex_input::VertexMemberTy;
 ///  color: [f32; 4]
 ///}
 ///
+///# #[allow(deprecated)]
 ///vulkano::impl_vertex!(Vertex, position, color);
 ///
 ///```
+#[deprecated(since = "0.31.0", note = "use `#[derive(Vertex)]` instead")]
 #[macro_export]
 macro_rules! impl_vertex {
     ($out:ty $(, $member:ident)*) => (
@@ -52,6 +58,7 @@ macro_rules! impl_vertex {
                             offset: member_ptr as usize - dummy_ptr as usize,
                             ty: ty,
                             array_size: array_size,
+                            format: None,
                         });
                     }
                 )*
@@ -62,7 +69,7 @@ macro_rules! impl_vertex {
     )
 }
 
-/// Trait for data types that can be used as vertex members. Used by the `impl_vertex!` macro.
+/// Trait for data types that can be used as vertex members. Used by `#[derive(Vertex)]`.
 pub unsafe trait VertexMember {
     /// Returns the format and array size of the member.
     fn format() -> (VertexMemberTy, usize);
@@ -107,6 +107,8 @@ pub use self::{
 };
 use crate::format::Format;
 use std::collections::HashMap;
+#[cfg(feature = "macros")]
+pub use vulkano_macros::Vertex;
 
 mod buffers;
 mod collection;
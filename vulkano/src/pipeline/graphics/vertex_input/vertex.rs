@@ -7,6 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use super::VertexInputRate;
 use crate::format::Format;
 use bytemuck::Pod;
 
@@ -15,9 +16,22 @@ use bytemuck::Pod;
 ///
 /// At this stage, the vertex is in a "raw" format. For example a `[f32; 4]` can match both a
 /// `vec4` or a `float[4]`. The way the things are bound depends on the shader.
+///
+/// This trait should be implemented with `#[derive(Vertex)]`, which is available when the
+/// `macros` feature of vulkano is enabled.
 pub unsafe trait Vertex: Pod + Send + Sync + 'static {
     /// Returns the characteristics of a vertex member by its name.
     fn member(name: &str) -> Option<VertexMemberInfo>;
+
+    /// Returns the input rate that this type is meant to be used with.
+    ///
+    /// This is used by [`BuffersDefinition::buffer`](super::BuffersDefinition::buffer). The
+    /// default implementation returns [`VertexInputRate::Vertex`]; the derive macro returns a
+    /// per-instance rate if the struct has the `#[per_instance]` attribute.
+    #[inline]
+    fn input_rate() -> VertexInputRate {
+        VertexInputRate::Vertex
+    }
 }
 
 unsafe impl Vertex for () {
@@ -35,6 +49,10 @@ pub struct VertexMemberInfo {
     pub ty: VertexMemberTy,
     /// Number of consecutive elements of that type.
     pub array_size: usize,
+    /// The format that the member should be read with, if it was explicitly specified.
+    ///
+    /// If `None`, the format is derived from the type of the vertex shader input.
+    pub format: Option<Format>,
 }
 
 /// Type of a member of a vertex struct.
@@ -75,3 +93,53 @@ impl VertexMemberTy {
         array_size * my_size == format_size * num_locs as usize
     }
 }
+
+#[cfg(all(test, feature = "macros"))]
+mod tests {
+    use crate::format::Format;
+    use crate::pipeline::graphics::vertex_input::{Vertex, VertexInputRate, VertexMemberTy};
+    use bytemuck::{Pod, Zeroable};
+
+    #[test]
+    fn derive_vertex() {
+        #[repr(C)]
+        #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+        struct TestVertex {
+            position: [f32; 3],
+            #[format(R8G8B8A8_UNORM)]
+            color: [u8; 4],
+            weight: f32,
+        }
+
+        let position = TestVertex::member("position").unwrap();
+        assert_eq!(position.offset, 0);
+        assert_eq!(position.ty, VertexMemberTy::F32);
+        assert_eq!(position.array_size, 3);
+        assert_eq!(position.format, None);
+
+        let color = TestVertex::member("color").unwrap();
+        assert_eq!(color.offset, 12);
+        assert_eq!(color.ty, VertexMemberTy::U8);
+        assert_eq!(color.array_size, 4);
+        assert_eq!(color.format, Some(Format::R8G8B8A8_UNORM));
+
+        assert_eq!(TestVertex::member("weight").unwrap().offset, 16);
+        assert!(TestVertex::member("normal").is_none());
+        assert!(matches!(TestVertex::input_rate(), VertexInputRate::Vertex));
+    }
+
+    #[test]
+    fn derive_vertex_per_instance() {
+        #[repr(C)]
+        #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+        #[per_instance]
+        struct TestInstance {
+            offset: [f32; 2],
+        }
+
+        assert!(matches!(
+            TestInstance::input_rate(),
+            VertexInputRate::Instance { divisor: 1 }
+        ));
+    }
+}
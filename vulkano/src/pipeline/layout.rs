@@ -65,7 +65,10 @@
 
 use crate::{
     check_errors,
-    descriptor_set::layout::{DescriptorRequirementsNotMet, DescriptorSetLayout, DescriptorType},
+    descriptor_set::layout::{
+        DescriptorRequirementsNotMet, DescriptorSetLayout, DescriptorSetLayoutDifference,
+        DescriptorType,
+    },
     device::{Device, DeviceOwned},
     shader::{DescriptorRequirements, ShaderStages},
     Error, OomError, VulkanObject,
@@ -506,6 +509,10 @@ impl PipelineLayout {
             output.assume_init()
         };
 
+        device
+            .statistics_counters()
+            .record_pipeline_layout_created();
+
         Ok(handle)
     }
 
@@ -567,6 +574,51 @@ impl PipelineLayout {
             })
     }
 
+    /// Returns the differences that make `self` incompatible with `other` for the given number of
+    /// sets, treating `self` as the expected layout and `other` as the one that was found.
+    ///
+    /// The returned list is empty if and only if [`is_compatible_with`](Self::is_compatible_with)
+    /// returns `true`.
+    pub fn compatibility_differences(
+        &self,
+        other: &PipelineLayout,
+        num_sets: u32,
+    ) -> Vec<PipelineLayoutDifference> {
+        let mut differences = Vec::new();
+
+        if self == other {
+            return differences;
+        }
+
+        if self.push_constant_ranges != other.push_constant_ranges {
+            differences.push(PipelineLayoutDifference::PushConstantRanges {
+                expected: self.push_constant_ranges.clone(),
+                found: other.push_constant_ranges.clone(),
+            });
+        }
+
+        for (set_num, self_set_layout) in
+            self.set_layouts.iter().enumerate().take(num_sets as usize)
+        {
+            let set_num = set_num as u32;
+
+            match other.set_layouts.get(set_num as usize) {
+                Some(other_set_layout) => differences.extend(
+                    self_set_layout
+                        .compatibility_differences(other_set_layout)
+                        .into_iter()
+                        .map(|difference| PipelineLayoutDifference::SetLayout {
+                            set_num,
+                            difference,
+                        }),
+                ),
+                None => differences.push(PipelineLayoutDifference::SetLayoutMissing { set_num }),
+            }
+        }
+
+        differences
+    }
+
     /// Makes sure that `self` is a superset of the provided descriptor set layouts and push
     /// constant ranges. Returns an `Err` if this is not the case.
     pub fn ensure_compatible_with_shader<'a>(
@@ -979,6 +1031,51 @@ impl fmt::Display for PipelineLayoutSupersetError {
     }
 }
 
+/// A difference between two pipeline layouts that makes them incompatible.
+///
+/// Returned by [`PipelineLayout::compatibility_differences`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PipelineLayoutDifference {
+    /// The push constant ranges of the layouts are not identical.
+    PushConstantRanges {
+        expected: Vec<PushConstantRange>,
+        found: Vec<PushConstantRange>,
+    },
+
+    /// The expected layout has a descriptor set layout at `set_num`, the found layout does not.
+    SetLayoutMissing { set_num: u32 },
+
+    /// The descriptor set layouts at `set_num` are not compatible.
+    SetLayout {
+        set_num: u32,
+        difference: DescriptorSetLayoutDifference,
+    },
+}
+
+impl fmt::Display for PipelineLayoutDifference {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::PushConstantRanges { expected, found } => write!(
+                fmt,
+                "push constant ranges: expected {:?}, found {:?}",
+                expected, found,
+            ),
+            Self::SetLayoutMissing { set_num } => {
+                write!(
+                    fmt,
+                    "set {}: expected a descriptor set layout, found nothing",
+                    set_num
+                )
+            }
+            Self::SetLayout {
+                set_num,
+                difference,
+            } => write!(fmt, "set {}, {}", set_num, difference),
+        }
+    }
+}
+
 /// Parameters to create a new `PipelineLayout`.
 #[derive(Clone, Debug)]
 pub struct PipelineLayoutCreateInfo {
@@ -20,11 +20,13 @@
 pub use self::compute::ComputePipeline;
 pub use self::graphics::GraphicsPipeline;
 pub use self::layout::PipelineLayout;
-use crate::device::DeviceOwned;
+use crate::device::{Device, DeviceOwned};
 use std::sync::Arc;
 
 pub mod cache;
+pub mod compiler;
 pub mod compute;
+pub mod executable;
 pub mod graphics;
 pub mod layout;
 
@@ -104,6 +106,16 @@ pub enum DynamicState {
     LogicOp = ash::vk::DynamicState::LOGIC_OP_EXT.as_raw(),
     PrimitiveRestartEnable = ash::vk::DynamicState::PRIMITIVE_RESTART_ENABLE_EXT.as_raw(),
     ColorWriteEnable = ash::vk::DynamicState::COLOR_WRITE_ENABLE_EXT.as_raw(),
+    DepthClampEnable = ash::vk::DynamicState::DEPTH_CLAMP_ENABLE_EXT.as_raw(),
+    PolygonMode = ash::vk::DynamicState::POLYGON_MODE_EXT.as_raw(),
+    RasterizationSamples = ash::vk::DynamicState::RASTERIZATION_SAMPLES_EXT.as_raw(),
+    SampleMask = ash::vk::DynamicState::SAMPLE_MASK_EXT.as_raw(),
+    AlphaToCoverageEnable = ash::vk::DynamicState::ALPHA_TO_COVERAGE_ENABLE_EXT.as_raw(),
+    AlphaToOneEnable = ash::vk::DynamicState::ALPHA_TO_ONE_ENABLE_EXT.as_raw(),
+    LogicOpEnable = ash::vk::DynamicState::LOGIC_OP_ENABLE_EXT.as_raw(),
+    ColorBlendEnable = ash::vk::DynamicState::COLOR_BLEND_ENABLE_EXT.as_raw(),
+    ColorBlendEquation = ash::vk::DynamicState::COLOR_BLEND_EQUATION_EXT.as_raw(),
+    ColorWriteMask = ash::vk::DynamicState::COLOR_WRITE_MASK_EXT.as_raw(),
 }
 
 impl From<DynamicState> for ash::vk::DynamicState {
@@ -151,3 +163,221 @@ pub enum PartialStateMode<F, D> {
     Fixed(F),
     Dynamic(D),
 }
+
+/// Specifies how out-of-bounds accesses to buffers are handled by a pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum PipelineRobustnessBufferBehavior {
+    /// The robustness behavior of the device is used, as determined by the features that were
+    /// enabled on it.
+    DeviceDefault = ash::vk::PipelineRobustnessBufferBehaviorEXT::DEVICE_DEFAULT.as_raw(),
+    /// Out-of-bounds accesses are undefined behavior.
+    Disabled = ash::vk::PipelineRobustnessBufferBehaviorEXT::DISABLED.as_raw(),
+    /// Out-of-bounds accesses behave as with the `robust_buffer_access` feature.
+    RobustBufferAccess =
+        ash::vk::PipelineRobustnessBufferBehaviorEXT::ROBUST_BUFFER_ACCESS.as_raw(),
+    /// Out-of-bounds accesses behave as with the `robust_buffer_access2` feature.
+    RobustBufferAccess2 =
+        ash::vk::PipelineRobustnessBufferBehaviorEXT::ROBUST_BUFFER_ACCESS_2.as_raw(),
+}
+
+impl From<PipelineRobustnessBufferBehavior> for ash::vk::PipelineRobustnessBufferBehaviorEXT {
+    #[inline]
+    fn from(val: PipelineRobustnessBufferBehavior) -> Self {
+        Self::from_raw(val as i32)
+    }
+}
+
+impl TryFrom<ash::vk::PipelineRobustnessBufferBehaviorEXT> for PipelineRobustnessBufferBehavior {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::PipelineRobustnessBufferBehaviorEXT) -> Result<Self, Self::Error> {
+        match val {
+            ash::vk::PipelineRobustnessBufferBehaviorEXT::DEVICE_DEFAULT => Ok(Self::DeviceDefault),
+            ash::vk::PipelineRobustnessBufferBehaviorEXT::DISABLED => Ok(Self::Disabled),
+            ash::vk::PipelineRobustnessBufferBehaviorEXT::ROBUST_BUFFER_ACCESS => {
+                Ok(Self::RobustBufferAccess)
+            }
+            ash::vk::PipelineRobustnessBufferBehaviorEXT::ROBUST_BUFFER_ACCESS_2 => {
+                Ok(Self::RobustBufferAccess2)
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+/// Specifies how out-of-bounds accesses to images are handled by a pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum PipelineRobustnessImageBehavior {
+    /// The robustness behavior of the device is used, as determined by the features that were
+    /// enabled on it.
+    DeviceDefault = ash::vk::PipelineRobustnessImageBehaviorEXT::DEVICE_DEFAULT.as_raw(),
+    /// Out-of-bounds accesses are undefined behavior.
+    Disabled = ash::vk::PipelineRobustnessImageBehaviorEXT::DISABLED.as_raw(),
+    /// Out-of-bounds accesses behave as with the `robust_image_access` feature.
+    RobustImageAccess = ash::vk::PipelineRobustnessImageBehaviorEXT::ROBUST_IMAGE_ACCESS.as_raw(),
+    /// Out-of-bounds accesses behave as with the `robust_image_access2` feature.
+    RobustImageAccess2 =
+        ash::vk::PipelineRobustnessImageBehaviorEXT::ROBUST_IMAGE_ACCESS_2.as_raw(),
+}
+
+impl From<PipelineRobustnessImageBehavior> for ash::vk::PipelineRobustnessImageBehaviorEXT {
+    #[inline]
+    fn from(val: PipelineRobustnessImageBehavior) -> Self {
+        Self::from_raw(val as i32)
+    }
+}
+
+impl TryFrom<ash::vk::PipelineRobustnessImageBehaviorEXT> for PipelineRobustnessImageBehavior {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::PipelineRobustnessImageBehaviorEXT) -> Result<Self, Self::Error> {
+        match val {
+            ash::vk::PipelineRobustnessImageBehaviorEXT::DEVICE_DEFAULT => Ok(Self::DeviceDefault),
+            ash::vk::PipelineRobustnessImageBehaviorEXT::DISABLED => Ok(Self::Disabled),
+            ash::vk::PipelineRobustnessImageBehaviorEXT::ROBUST_IMAGE_ACCESS => {
+                Ok(Self::RobustImageAccess)
+            }
+            ash::vk::PipelineRobustnessImageBehaviorEXT::ROBUST_IMAGE_ACCESS_2 => {
+                Ok(Self::RobustImageAccess2)
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+/// Per-pipeline robustness settings, overriding the robustness behavior of the device for the
+/// resources that the pipeline accesses.
+///
+/// Setting any value other than `DeviceDefault` requires the
+/// [`pipeline_robustness`](crate::device::Features::pipeline_robustness) feature to be enabled
+/// on the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineRobustness {
+    /// How out-of-bounds accesses to storage buffers are handled.
+    ///
+    /// The default value is [`PipelineRobustnessBufferBehavior::DeviceDefault`].
+    pub storage_buffers: PipelineRobustnessBufferBehavior,
+
+    /// How out-of-bounds accesses to uniform buffers are handled.
+    ///
+    /// The default value is [`PipelineRobustnessBufferBehavior::DeviceDefault`].
+    pub uniform_buffers: PipelineRobustnessBufferBehavior,
+
+    /// How out-of-bounds accesses to vertex input attributes are handled.
+    ///
+    /// The default value is [`PipelineRobustnessBufferBehavior::DeviceDefault`].
+    pub vertex_inputs: PipelineRobustnessBufferBehavior,
+
+    /// How out-of-bounds accesses to images are handled.
+    ///
+    /// The default value is [`PipelineRobustnessImageBehavior::DeviceDefault`].
+    pub images: PipelineRobustnessImageBehavior,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl PipelineRobustness {
+    /// Returns a `PipelineRobustness` that uses the robustness behavior of the device for
+    /// everything.
+    #[inline]
+    pub const fn device_default() -> Self {
+        Self {
+            storage_buffers: PipelineRobustnessBufferBehavior::DeviceDefault,
+            uniform_buffers: PipelineRobustnessBufferBehavior::DeviceDefault,
+            vertex_inputs: PipelineRobustnessBufferBehavior::DeviceDefault,
+            images: PipelineRobustnessImageBehavior::DeviceDefault,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    /// Returns whether all values are `DeviceDefault`.
+    #[inline]
+    pub fn is_device_default(&self) -> bool {
+        *self == Self::device_default()
+    }
+
+    // Returns the name of a feature that must be enabled on `device` for these settings to be
+    // used, if there is one that is not enabled.
+    pub(crate) fn missing_feature(&self, device: &Device) -> Option<&'static str> {
+        let features = device.enabled_features();
+
+        if self.is_device_default() {
+            return None;
+        }
+
+        if !features.pipeline_robustness {
+            return Some("pipeline_robustness");
+        }
+
+        if [
+            self.storage_buffers,
+            self.uniform_buffers,
+            self.vertex_inputs,
+        ]
+        .contains(&PipelineRobustnessBufferBehavior::RobustBufferAccess2)
+            && !features.robust_buffer_access2
+        {
+            return Some("robust_buffer_access2");
+        }
+
+        match self.images {
+            PipelineRobustnessImageBehavior::RobustImageAccess if !features.robust_image_access => {
+                Some("robust_image_access")
+            }
+            PipelineRobustnessImageBehavior::RobustImageAccess2
+                if !features.robust_image_access2 =>
+            {
+                Some("robust_image_access2")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for PipelineRobustness {
+    #[inline]
+    fn default() -> Self {
+        Self::device_default()
+    }
+}
+
+impl From<PipelineRobustness> for ash::vk::PipelineRobustnessCreateInfoEXT {
+    #[inline]
+    fn from(val: PipelineRobustness) -> Self {
+        Self {
+            storage_buffers: val.storage_buffers.into(),
+            uniform_buffers: val.uniform_buffers.into(),
+            vertex_inputs: val.vertex_inputs.into(),
+            images: val.images.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PipelineRobustness, PipelineRobustnessBufferBehavior};
+
+    #[test]
+    fn robustness_feature_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        assert_eq!(
+            PipelineRobustness::device_default().missing_feature(&device),
+            None,
+        );
+
+        let robustness = PipelineRobustness {
+            storage_buffers: PipelineRobustnessBufferBehavior::RobustBufferAccess,
+            ..Default::default()
+        };
+        assert_eq!(
+            robustness.missing_feature(&device),
+            Some("pipeline_robustness"),
+        );
+    }
+}
@@ -13,22 +13,28 @@
 //! represent a collection of queries. Whenever you use a query, you have to specify both the query
 //! pool and the slot id within that query pool.
 
+use self::performance::{PerformanceCounter, PerformanceCounterResult};
 use crate::{
     check_errors,
     device::{Device, DeviceOwned},
     DeviceSize, Error, OomError, Success, VulkanObject,
 };
+use parking_lot::{Mutex, MutexGuard};
 use std::{
     error,
     ffi::c_void,
     fmt,
     hash::{Hash, Hasher},
-    mem::{size_of_val, MaybeUninit},
+    mem::{size_of, size_of_val, MaybeUninit},
     ops::Range,
     ptr,
     sync::Arc,
 };
 
+pub mod performance;
+pub mod rotating;
+pub mod timeline;
+
 /// A collection of one or more queries of a particular type.
 #[derive(Debug)]
 pub struct QueryPool {
@@ -37,6 +43,13 @@ pub struct QueryPool {
 
     query_type: QueryType,
     query_count: u32,
+    pipeline_statistics: QueryPipelineStatisticFlags,
+    performance_queue_family_index: u32,
+    performance_counters: Vec<PerformanceCounter>,
+
+    // For each query, whether it has been reset and not used since. Updated when command buffers
+    // are submitted.
+    reset_state: Mutex<Vec<bool>>,
 }
 
 impl QueryPool {
@@ -52,35 +65,110 @@ impl QueryPool {
         let QueryPoolCreateInfo {
             query_type,
             query_count,
+            mut pipeline_statistics,
+            mut performance_queue_family_index,
+            performance_counter_indices,
             _ne: _,
         } = create_info;
 
         // VUID-VkQueryPoolCreateInfo-queryCount-02763
         assert!(query_count != 0);
 
-        let pipeline_statistics = match query_type {
-            QueryType::PipelineStatistics(flags) => {
+        let mut performance_counters = Vec::new();
+
+        match query_type {
+            QueryType::PipelineStatistics => {
                 // VUID-VkQueryPoolCreateInfo-queryType-00791
                 if !device.enabled_features().pipeline_statistics_query {
                     return Err(QueryPoolCreationError::PipelineStatisticsQueryFeatureNotEnabled);
                 }
 
                 // VUID-VkQueryPoolCreateInfo-queryType-00792
-                flags.into()
+                // Guaranteed by the type.
+            }
+            QueryType::PerformanceQuery => {
+                if !device.enabled_extensions().khr_performance_query {
+                    return Err(QueryPoolCreationError::ExtensionNotEnabled {
+                        extension: "khr_performance_query",
+                        reason: "query_type was QueryType::PerformanceQuery",
+                    });
+                }
+
+                // VUID-VkQueryPoolPerformanceCreateInfoKHR-performanceCounterQueryPools-03237
+                if !device.enabled_features().performance_counter_query_pools {
+                    return Err(QueryPoolCreationError::FeatureNotEnabled {
+                        feature: "performance_counter_query_pools",
+                        reason: "query_type was QueryType::PerformanceQuery",
+                    });
+                }
+
+                // VUID-VkQueryPoolPerformanceCreateInfoKHR-queueFamilyIndex-03236
+                let queue_family = device
+                    .physical_device()
+                    .queue_family_by_id(performance_queue_family_index)
+                    .ok_or(
+                        QueryPoolCreationError::PerformanceQueueFamilyIndexOutOfRange {
+                            queue_family_index: performance_queue_family_index,
+                        },
+                    )?;
+
+                // VUID-VkQueryPoolPerformanceCreateInfoKHR-counterIndexCount-arraylength
+                assert!(!performance_counter_indices.is_empty());
+
+                let available_counters = queue_family.performance_counters()?;
+
+                performance_counters = performance_counter_indices
+                    .iter()
+                    .map(|&counter_index| {
+                        // VUID-VkQueryPoolPerformanceCreateInfoKHR-pCounterIndices-03321
+                        available_counters
+                            .get(counter_index as usize)
+                            .cloned()
+                            .ok_or(QueryPoolCreationError::PerformanceCounterIndexOutOfRange {
+                                counter_index,
+                                counter_count: available_counters.len() as u32,
+                            })
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                // `pipeline_statistics` is ignored for other query types.
+                pipeline_statistics = QueryPipelineStatisticFlags::none();
             }
             QueryType::Occlusion | QueryType::Timestamp => {
-                ash::vk::QueryPipelineStatisticFlags::empty()
+                // `pipeline_statistics` is ignored for other query types.
+                pipeline_statistics = QueryPipelineStatisticFlags::none();
             }
-        };
+        }
 
-        let create_info = ash::vk::QueryPoolCreateInfo {
+        if query_type != QueryType::PerformanceQuery {
+            // `performance_queue_family_index` is ignored for other query types.
+            performance_queue_family_index = 0;
+        }
+
+        let mut create_info = ash::vk::QueryPoolCreateInfo {
             flags: ash::vk::QueryPoolCreateFlags::empty(),
             query_type: query_type.into(),
             query_count,
-            pipeline_statistics,
+            pipeline_statistics: pipeline_statistics.into(),
             ..Default::default()
         };
 
+        let mut performance_create_info = if query_type == QueryType::PerformanceQuery {
+            Some(ash::vk::QueryPoolPerformanceCreateInfoKHR {
+                queue_family_index: performance_queue_family_index,
+                counter_index_count: performance_counter_indices.len() as u32,
+                p_counter_indices: performance_counter_indices.as_ptr(),
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+
+        if let Some(performance_create_info) = performance_create_info.as_mut() {
+            performance_create_info.p_next = create_info.p_next;
+            create_info.p_next = performance_create_info as *const _ as *const _;
+        }
+
         let handle = unsafe {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -99,6 +187,10 @@ impl QueryPool {
 
             query_type,
             query_count,
+            pipeline_statistics,
+            performance_queue_family_index,
+            performance_counters,
+            reset_state: Mutex::new(vec![false; query_count as usize]),
         }))
     }
 
@@ -114,6 +206,52 @@ impl QueryPool {
         self.query_count
     }
 
+    /// Returns the statistics that are gathered by queries of this pool, if it is a pipeline
+    /// statistics query pool.
+    ///
+    /// For other query types, this returns [`QueryPipelineStatisticFlags::none()`].
+    #[inline]
+    pub fn pipeline_statistics(&self) -> QueryPipelineStatisticFlags {
+        self.pipeline_statistics
+    }
+
+    /// Returns the index of the queue family that performance queries of this pool can be used
+    /// on, if it is a performance query pool.
+    ///
+    /// For other query types, this returns `0`.
+    #[inline]
+    pub fn performance_queue_family_index(&self) -> u32 {
+        self.performance_queue_family_index
+    }
+
+    /// Returns the performance counters that are gathered by queries of this pool, in the order
+    /// that they were specified in [`QueryPoolCreateInfo::performance_counter_indices`].
+    ///
+    /// For other query types, this returns an empty slice.
+    #[inline]
+    pub fn performance_counters(&self) -> &[PerformanceCounter] {
+        &self.performance_counters
+    }
+
+    /// Returns the number of [`QueryResultElement`]s that are needed to hold the result of a
+    /// single query of this pool.
+    ///
+    /// - For `Occlusion` and `Timestamp` queries, this returns 1.
+    /// - For `PipelineStatistics` queries, this returns the number of statistics flags enabled.
+    /// - For `PerformanceQuery` queries, this returns the number of counters. These results can
+    ///   only be retrieved with [`QueriesRange::get_performance_results`].
+    ///
+    /// If the results are retrieved with [`QueryResultFlags::with_availability`] enabled, then
+    /// an additional element is required per query.
+    #[inline]
+    pub fn result_len(&self) -> DeviceSize {
+        match self.query_type {
+            QueryType::Occlusion | QueryType::Timestamp => 1,
+            QueryType::PipelineStatistics => self.pipeline_statistics.count(),
+            QueryType::PerformanceQuery => self.performance_counters.len() as DeviceSize,
+        }
+    }
+
     /// Returns a reference to a single query slot, or `None` if the index is out of range.
     #[inline]
     pub fn query(&self, index: u32) -> Option<Query> {
@@ -139,6 +277,15 @@ impl QueryPool {
             None
         }
     }
+
+    /// Locks the reset state of the queries in the pool.
+    ///
+    /// An element is `true` if the query has been reset by a submitted command buffer, and has not
+    /// been used by a submitted command buffer since.
+    #[inline]
+    pub(crate) fn reset_state(&self) -> MutexGuard<Vec<bool>> {
+        self.reset_state.lock()
+    }
 }
 
 impl Drop for QueryPool {
@@ -197,6 +344,31 @@ pub struct QueryPoolCreateInfo {
     /// The default value is `0`, which must be overridden.
     pub query_count: u32,
 
+    /// If `query_type` is [`QueryType::PipelineStatistics`], the statistics to query.
+    ///
+    /// For any other value of `query_type`, this is ignored.
+    ///
+    /// The default value is [`QueryPipelineStatisticFlags::none()`].
+    pub pipeline_statistics: QueryPipelineStatisticFlags,
+
+    /// If `query_type` is [`QueryType::PerformanceQuery`], the index of the queue family that
+    /// the queries will be used on.
+    ///
+    /// For any other value of `query_type`, this is ignored.
+    ///
+    /// The default value is `0`.
+    pub performance_queue_family_index: u32,
+
+    /// If `query_type` is [`QueryType::PerformanceQuery`], the indices of the counters to query,
+    /// as returned by [`QueueFamily::performance_counters`].
+    ///
+    /// For any other value of `query_type`, this is ignored.
+    ///
+    /// The default value is empty, which must be overridden for performance queries.
+    ///
+    /// [`QueueFamily::performance_counters`]: crate::device::physical::QueueFamily::performance_counters
+    pub performance_counter_indices: Vec<u32>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -207,6 +379,9 @@ impl QueryPoolCreateInfo {
         Self {
             query_type,
             query_count: 0,
+            pipeline_statistics: QueryPipelineStatisticFlags::none(),
+            performance_queue_family_index: 0,
+            performance_counter_indices: Vec::new(),
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -219,6 +394,27 @@ pub enum QueryPoolCreationError {
     OomError(OomError),
     /// A pipeline statistics pool was requested but the corresponding feature wasn't enabled.
     PipelineStatisticsQueryFeatureNotEnabled,
+
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// A performance counter index was not less than the number of counters available on the
+    /// queue family.
+    PerformanceCounterIndexOutOfRange {
+        counter_index: u32,
+        counter_count: u32,
+    },
+
+    /// The performance queue family index does not refer to a queue family of the physical
+    /// device.
+    PerformanceQueueFamilyIndexOutOfRange { queue_family_index: u32 },
 }
 
 impl error::Error for QueryPoolCreationError {
@@ -234,17 +430,37 @@ impl error::Error for QueryPoolCreationError {
 impl fmt::Display for QueryPoolCreationError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(
-            fmt,
-            "{}",
-            match *self {
-                QueryPoolCreationError::OomError(_) => "not enough memory available",
-                QueryPoolCreationError::PipelineStatisticsQueryFeatureNotEnabled => {
-                    "a pipeline statistics pool was requested but the corresponding feature \
-                 wasn't enabled"
-                }
+        match *self {
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::PipelineStatisticsQueryFeatureNotEnabled => write!(
+                fmt,
+                "a pipeline statistics pool was requested but the corresponding feature wasn't \
+                enabled",
+            ),
+            Self::ExtensionNotEnabled { extension, reason } => write!(
+                fmt,
+                "the extension {} must be enabled: {}",
+                extension, reason,
+            ),
+            Self::FeatureNotEnabled { feature, reason } => {
+                write!(fmt, "the feature {} must be enabled: {}", feature, reason)
             }
-        )
+            Self::PerformanceCounterIndexOutOfRange {
+                counter_index,
+                counter_count,
+            } => write!(
+                fmt,
+                "performance counter index {} is not less than the number of counters available \
+                on the queue family ({})",
+                counter_index, counter_count,
+            ),
+            Self::PerformanceQueueFamilyIndexOutOfRange { queue_family_index } => write!(
+                fmt,
+                "the performance queue family index {} does not refer to a queue family of the \
+                physical device",
+                queue_family_index,
+            ),
+        }
     }
 }
 
@@ -313,7 +529,7 @@ impl<'a> QueriesRange<'a> {
 
     /// Copies the results of this range of queries to a buffer on the CPU.
     ///
-    /// [`self.pool().ty().result_len()`](QueryType::result_len) elements
+    /// [`self.pool().result_len()`](QueryPool::result_len) elements
     /// will be written for each query in the range, plus 1 extra element per query if
     /// [`QueryResultFlags::with_availability`] is enabled.
     /// The provided buffer must be large enough to hold the data.
@@ -357,6 +573,111 @@ impl<'a> QueriesRange<'a> {
         })
     }
 
+    /// Retrieves the results of this range of queries, and decodes them into
+    /// [`PipelineStatisticsResult`]s.
+    ///
+    /// The query pool must be a pipeline statistics query pool. The results are always retrieved
+    /// with [`QueryResultFlags::with_availability`] enabled, regardless of the value in `flags`.
+    /// An element of the returned `Vec` is `None` if the result for that query was not yet
+    /// available.
+    pub fn get_pipeline_statistics(
+        &self,
+        flags: QueryResultFlags,
+    ) -> Result<Vec<Option<PipelineStatisticsResult>>, GetResultsError> {
+        if self.pool.query_type != QueryType::PipelineStatistics {
+            return Err(GetResultsError::InvalidQueryType);
+        }
+
+        let flags = QueryResultFlags {
+            with_availability: true,
+            ..flags
+        };
+        let per_query_len = self.pool.result_len() as usize + 1;
+        let count = (self.range.end - self.range.start) as usize;
+        let mut results = vec![0u64; per_query_len * count];
+        self.get_results(&mut results, flags)?;
+
+        Ok(results
+            .chunks_exact(per_query_len)
+            .map(|query_results| {
+                let (&availability, values) = query_results.split_last().unwrap();
+
+                if availability != 0 {
+                    Some(PipelineStatisticsResult::from_results(
+                        self.pool.pipeline_statistics,
+                        values,
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Retrieves the results of this range of queries, and decodes them into
+    /// [`PerformanceCounterResult`]s.
+    ///
+    /// The query pool must be a performance query pool. `None` is returned if the results were
+    /// not yet available. Otherwise, [`self.pool().result_len()`](QueryPool::result_len) results
+    /// are returned for each query in the range, in the order of
+    /// [`self.pool().performance_counters()`](QueryPool::performance_counters).
+    ///
+    /// [`QueryResultFlags::with_availability`] and [`QueryResultFlags::partial`] are not allowed
+    /// for performance queries.
+    pub fn get_performance_results(
+        &self,
+        flags: QueryResultFlags,
+    ) -> Result<Option<Vec<PerformanceCounterResult>>, GetResultsError> {
+        if self.pool.query_type != QueryType::PerformanceQuery {
+            return Err(GetResultsError::InvalidQueryType);
+        }
+
+        // VUID-vkGetQueryPoolResults-queryType-03229
+        // VUID-vkGetQueryPoolResults-queryType-04519
+        if flags.with_availability || flags.partial {
+            return Err(GetResultsError::InvalidFlags);
+        }
+
+        let per_query_len = self.pool.performance_counters.len();
+        let count = (self.range.end - self.range.start) as usize;
+        let mut results =
+            vec![ash::vk::PerformanceCounterResultKHR::default(); per_query_len * count];
+
+        let result = unsafe {
+            let fns = self.pool.device.fns();
+            check_errors((fns.v1_0.get_query_pool_results)(
+                self.pool.device.internal_object(),
+                self.pool.internal_object(),
+                self.range.start,
+                count as u32,
+                size_of_val(results.as_slice()),
+                results.as_mut_ptr() as *mut c_void,
+                (per_query_len * size_of::<ash::vk::PerformanceCounterResultKHR>()) as DeviceSize,
+                ash::vk::QueryResultFlags::from(flags),
+            ))?
+        };
+
+        match result {
+            Success::Success => (),
+            Success::NotReady => return Ok(None),
+            s => panic!("unexpected success value: {:?}", s),
+        }
+
+        Ok(Some(
+            results
+                .chunks_exact(per_query_len)
+                .flat_map(|query_results| {
+                    query_results
+                        .iter()
+                        .zip(self.pool.performance_counters.iter())
+                        .map(|(&value, counter)| {
+                            PerformanceCounterResult::from_vulkan(counter.storage, value)
+                        })
+                })
+                .collect(),
+        ))
+    }
+
     pub(crate) fn check_query_pool_results<T>(
         &self,
         buffer_start: DeviceSize,
@@ -373,8 +694,7 @@ impl<'a> QueriesRange<'a> {
         debug_assert!(buffer_start % std::mem::size_of::<T>() as DeviceSize == 0);
 
         let count = self.range.end - self.range.start;
-        let per_query_len =
-            self.pool.query_type.result_len() + flags.with_availability as DeviceSize;
+        let per_query_len = self.pool.result_len() + flags.with_availability as DeviceSize;
         let required_len = per_query_len * count as DeviceSize;
 
         // VUID-vkGetQueryPoolResults-dataSize-00817
@@ -386,8 +706,10 @@ impl<'a> QueriesRange<'a> {
         }
 
         match self.pool.query_type {
+            // Performance query results can't be retrieved as plain integers.
+            QueryType::PerformanceQuery => return Err(GetResultsError::InvalidQueryType),
             QueryType::Occlusion => (),
-            QueryType::PipelineStatistics(_) => (),
+            QueryType::PipelineStatistics => (),
             QueryType::Timestamp => {
                 // VUID-vkGetQueryPoolResults-queryType-00818
                 if flags.partial {
@@ -414,6 +736,8 @@ pub enum GetResultsError {
     DeviceLost,
     /// The provided flags are not allowed for this type of query.
     InvalidFlags,
+    /// The query pool is not of the type required by the operation.
+    InvalidQueryType,
     /// Not enough memory.
     OomError(OomError),
 }
@@ -452,6 +776,9 @@ impl fmt::Display for GetResultsError {
                 Self::InvalidFlags => {
                     "the provided flags are not allowed for this type of query"
                 }
+                Self::InvalidQueryType => {
+                    "the query pool is not of the type required by the operation"
+                }
                 Self::OomError(_) => "not enough memory available",
             }
         )
@@ -486,32 +813,24 @@ unsafe impl QueryResultElement for u64 {
 }
 
 /// The type of query that a query pool should perform.
-#[derive(Debug, Copy, Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum QueryType {
     /// Tracks the number of samples that pass per-fragment tests (e.g. the depth test).
     Occlusion,
     /// Tracks statistics on pipeline invocations and their input data.
-    PipelineStatistics(QueryPipelineStatisticFlags),
+    ///
+    /// The statistics to gather are specified with
+    /// [`QueryPoolCreateInfo::pipeline_statistics`].
+    PipelineStatistics,
     /// Writes timestamps at chosen points in a command buffer.
     Timestamp,
-}
-
-impl QueryType {
-    /// Returns the number of [`QueryResultElement`]s that are needed to hold the result of a
-    /// single query of this type.
-    ///
-    /// - For `Occlusion` and `Timestamp` queries, this returns 1.
-    /// - For `PipelineStatistics` queries, this returns the number of statistics flags enabled.
+    /// Gathers hardware performance counters.
     ///
-    /// If the results are retrieved with [`QueryResultFlags::with_availability`] enabled, then
-    /// an additional element is required per query.
-    #[inline]
-    pub const fn result_len(&self) -> DeviceSize {
-        match self {
-            Self::Occlusion | Self::Timestamp => 1,
-            Self::PipelineStatistics(flags) => flags.count(),
-        }
-    }
+    /// The counters to gather are specified with
+    /// [`QueryPoolCreateInfo::performance_counter_indices`]. Requires the `khr_performance_query`
+    /// extension, and the [profiling lock](performance::ProfilingLock) must be held while
+    /// recording and executing command buffers that use this query type.
+    PerformanceQuery,
 }
 
 impl From<QueryType> for ash::vk::QueryType {
@@ -519,8 +838,9 @@ impl From<QueryType> for ash::vk::QueryType {
     fn from(value: QueryType) -> Self {
         match value {
             QueryType::Occlusion => ash::vk::QueryType::OCCLUSION,
-            QueryType::PipelineStatistics(_) => ash::vk::QueryType::PIPELINE_STATISTICS,
+            QueryType::PipelineStatistics => ash::vk::QueryType::PIPELINE_STATISTICS,
             QueryType::Timestamp => ash::vk::QueryType::TIMESTAMP,
+            QueryType::PerformanceQuery => ash::vk::QueryType::PERFORMANCE_QUERY_KHR,
         }
     }
 }
@@ -699,6 +1019,91 @@ impl From<QueryPipelineStatisticFlags> for ash::vk::QueryPipelineStatisticFlags
     }
 }
 
+/// The decoded result of a pipeline statistics query.
+///
+/// Each field is `Some` if the corresponding statistic was enabled in the query pool's
+/// [`pipeline_statistics`](QueryPoolCreateInfo::pipeline_statistics), and `None` otherwise.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PipelineStatisticsResult {
+    /// The number of vertices processed by the input assembly.
+    pub input_assembly_vertices: Option<u64>,
+    /// The number of primitives processed by the input assembly.
+    pub input_assembly_primitives: Option<u64>,
+    /// The number of times a vertex shader was invoked.
+    pub vertex_shader_invocations: Option<u64>,
+    /// The number of times a geometry shader was invoked.
+    pub geometry_shader_invocations: Option<u64>,
+    /// The number of primitives generated by geometry shaders.
+    pub geometry_shader_primitives: Option<u64>,
+    /// The number of times the clipping stage was invoked on a primitive.
+    pub clipping_invocations: Option<u64>,
+    /// The number of primitives that were output by the clipping stage.
+    pub clipping_primitives: Option<u64>,
+    /// The number of times a fragment shader was invoked.
+    pub fragment_shader_invocations: Option<u64>,
+    /// The number of patches processed by a tessellation control shader.
+    pub tessellation_control_shader_patches: Option<u64>,
+    /// The number of times a tessellation evaluation shader was invoked.
+    pub tessellation_evaluation_shader_invocations: Option<u64>,
+    /// The number of times a compute shader was invoked.
+    pub compute_shader_invocations: Option<u64>,
+}
+
+impl PipelineStatisticsResult {
+    /// Decodes the packed results of a single pipeline statistics query, as written by
+    /// [`QueriesRange::get_results`] or
+    /// [`copy_query_pool_results`](crate::command_buffer::AutoCommandBufferBuilder::copy_query_pool_results).
+    ///
+    /// `flags` must be the statistics that the query pool was created with. Vulkan writes the
+    /// enabled statistics in the order of the fields of [`QueryPipelineStatisticFlags`], so
+    /// `results` must start with the first statistic of the query. Any elements after the
+    /// enabled statistics, such as the availability value, are ignored.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `results` contains fewer elements than
+    ///   [`flags.count()`](QueryPipelineStatisticFlags::count).
+    pub fn from_results<T>(flags: QueryPipelineStatisticFlags, results: &[T]) -> Self
+    where
+        T: QueryResultElement + Copy + Into<u64>,
+    {
+        assert!(results.len() as DeviceSize >= flags.count());
+
+        let QueryPipelineStatisticFlags {
+            input_assembly_vertices,
+            input_assembly_primitives,
+            vertex_shader_invocations,
+            geometry_shader_invocations,
+            geometry_shader_primitives,
+            clipping_invocations,
+            clipping_primitives,
+            fragment_shader_invocations,
+            tessellation_control_shader_patches,
+            tessellation_evaluation_shader_invocations,
+            compute_shader_invocations,
+        } = flags;
+
+        let mut results = results.iter().map(|&value| value.into());
+        let mut next = |enabled: bool| enabled.then(|| results.next().unwrap());
+
+        PipelineStatisticsResult {
+            input_assembly_vertices: next(input_assembly_vertices),
+            input_assembly_primitives: next(input_assembly_primitives),
+            vertex_shader_invocations: next(vertex_shader_invocations),
+            geometry_shader_invocations: next(geometry_shader_invocations),
+            geometry_shader_primitives: next(geometry_shader_primitives),
+            clipping_invocations: next(clipping_invocations),
+            clipping_primitives: next(clipping_primitives),
+            fragment_shader_invocations: next(fragment_shader_invocations),
+            tessellation_control_shader_patches: next(tessellation_control_shader_patches),
+            tessellation_evaluation_shader_invocations: next(
+                tessellation_evaluation_shader_invocations,
+            ),
+            compute_shader_invocations: next(compute_shader_invocations),
+        }
+    }
+}
+
 /// Flags to control how the results of a query should be retrieved.
 ///
 /// `VK_QUERY_RESULT_64_BIT` is not included, as it is determined automatically via the
@@ -737,6 +1142,7 @@ impl From<QueryResultFlags> for ash::vk::QueryResultFlags {
 #[cfg(test)]
 mod tests {
     use super::QueryPoolCreateInfo;
+    use crate::query::PipelineStatisticsResult;
     use crate::query::QueryPipelineStatisticFlags;
     use crate::query::QueryPool;
     use crate::query::QueryPoolCreationError;
@@ -745,16 +1151,53 @@ mod tests {
     #[test]
     fn pipeline_statistics_feature() {
         let (device, _) = gfx_dev_and_queue!();
-        let query_type = QueryType::PipelineStatistics(QueryPipelineStatisticFlags::none());
         match QueryPool::new(
             device,
             QueryPoolCreateInfo {
                 query_count: 256,
-                ..QueryPoolCreateInfo::query_type(query_type)
+                ..QueryPoolCreateInfo::query_type(QueryType::PipelineStatistics)
             },
         ) {
             Err(QueryPoolCreationError::PipelineStatisticsQueryFeatureNotEnabled) => (),
             _ => panic!(),
         };
     }
+
+    #[test]
+    fn performance_query_extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+        match QueryPool::new(
+            device,
+            QueryPoolCreateInfo {
+                query_count: 1,
+                performance_counter_indices: vec![0],
+                ..QueryPoolCreateInfo::query_type(QueryType::PerformanceQuery)
+            },
+        ) {
+            Err(QueryPoolCreationError::ExtensionNotEnabled { .. }) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn pipeline_statistics_result_decode() {
+        let flags = QueryPipelineStatisticFlags {
+            input_assembly_vertices: true,
+            fragment_shader_invocations: true,
+            compute_shader_invocations: true,
+            ..QueryPipelineStatisticFlags::none()
+        };
+
+        // The trailing element is an availability value, and should be ignored.
+        let result = PipelineStatisticsResult::from_results(flags, &[3u32, 7, 11, 1]);
+        assert_eq!(
+            result,
+            PipelineStatisticsResult {
+                input_assembly_vertices: Some(3),
+                fragment_shader_invocations: Some(7),
+                compute_shader_invocations: Some(11),
+                ..Default::default()
+            }
+        );
+    }
 }
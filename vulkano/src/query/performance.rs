@@ -0,0 +1,431 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Hardware performance counters, provided by the `khr_performance_query` extension.
+//!
+//! The counters that are available can be enumerated per queue family with
+//! [`QueueFamily::performance_counters`]. A query pool of type
+//! [`QueryType::PerformanceQuery`](super::QueryType::PerformanceQuery) is created for a selection
+//! of these counters, and its results are read back with
+//! [`QueriesRange::get_performance_results`](super::QueriesRange::get_performance_results).
+//!
+//! Command buffers that use performance queries must be recorded and executed while the device's
+//! [`ProfilingLock`] is held.
+//!
+//! [`QueueFamily::performance_counters`]: crate::device::physical::QueueFamily::performance_counters
+
+use crate::{
+    check_errors,
+    device::{Device, DeviceOwned},
+    Error, OomError, Success, VulkanObject,
+};
+use std::{
+    error,
+    ffi::CStr,
+    fmt,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
+
+/// A hardware performance counter that is available on a queue family.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PerformanceCounter {
+    /// The unit that the counter's values are expressed in.
+    pub unit: PerformanceCounterUnit,
+
+    /// The scope over which the counter is measured.
+    pub scope: PerformanceCounterScope,
+
+    /// The type that the counter's values are stored as.
+    pub storage: PerformanceCounterStorage,
+
+    /// A universally unique identifier for the counter.
+    pub uuid: [u8; 16],
+
+    /// Whether recording the counter may have a noticeable performance impact.
+    pub performance_impacting: bool,
+
+    /// Whether recording the counter causes other counters in the same query pool to be impacted.
+    pub concurrently_impacted: bool,
+
+    /// The name of the counter.
+    pub name: String,
+
+    /// The category of the counter.
+    pub category: String,
+
+    /// A description of the counter.
+    pub description: String,
+}
+
+impl PerformanceCounter {
+    pub(crate) fn from_vulkan(
+        counter: &ash::vk::PerformanceCounterKHR,
+        description: &ash::vk::PerformanceCounterDescriptionKHR,
+    ) -> Self {
+        let string = |chars: &[std::os::raw::c_char]| unsafe {
+            CStr::from_ptr(chars.as_ptr())
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        PerformanceCounter {
+            unit: counter
+                .unit
+                .try_into()
+                .unwrap_or(PerformanceCounterUnit::Generic),
+            scope: counter
+                .scope
+                .try_into()
+                .unwrap_or(PerformanceCounterScope::CommandBuffer),
+            storage: counter
+                .storage
+                .try_into()
+                .unwrap_or(PerformanceCounterStorage::Uint64),
+            uuid: counter.uuid,
+            performance_impacting: description
+                .flags
+                .intersects(ash::vk::PerformanceCounterDescriptionFlagsKHR::PERFORMANCE_IMPACTING),
+            concurrently_impacted: description
+                .flags
+                .intersects(ash::vk::PerformanceCounterDescriptionFlagsKHR::CONCURRENTLY_IMPACTED),
+            name: string(&description.name),
+            category: string(&description.category),
+            description: string(&description.description),
+        }
+    }
+}
+
+/// The unit that the values of a performance counter are expressed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum PerformanceCounterUnit {
+    /// A dimensionless value.
+    Generic = ash::vk::PerformanceCounterUnitKHR::GENERIC.as_raw(),
+    /// A percentage, in the range 0.0 to 100.0.
+    Percentage = ash::vk::PerformanceCounterUnitKHR::PERCENTAGE.as_raw(),
+    /// Nanoseconds.
+    Nanoseconds = ash::vk::PerformanceCounterUnitKHR::NANOSECONDS.as_raw(),
+    /// Bytes.
+    Bytes = ash::vk::PerformanceCounterUnitKHR::BYTES.as_raw(),
+    /// Bytes per second.
+    BytesPerSecond = ash::vk::PerformanceCounterUnitKHR::BYTES_PER_SECOND.as_raw(),
+    /// Kelvin.
+    Kelvin = ash::vk::PerformanceCounterUnitKHR::KELVIN.as_raw(),
+    /// Watts.
+    Watts = ash::vk::PerformanceCounterUnitKHR::WATTS.as_raw(),
+    /// Volts.
+    Volts = ash::vk::PerformanceCounterUnitKHR::VOLTS.as_raw(),
+    /// Amperes.
+    Amps = ash::vk::PerformanceCounterUnitKHR::AMPS.as_raw(),
+    /// Hertz.
+    Hertz = ash::vk::PerformanceCounterUnitKHR::HERTZ.as_raw(),
+    /// Clock cycles.
+    Cycles = ash::vk::PerformanceCounterUnitKHR::CYCLES.as_raw(),
+}
+
+impl TryFrom<ash::vk::PerformanceCounterUnitKHR> for PerformanceCounterUnit {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::PerformanceCounterUnitKHR) -> Result<Self, Self::Error> {
+        match val {
+            ash::vk::PerformanceCounterUnitKHR::GENERIC => Ok(Self::Generic),
+            ash::vk::PerformanceCounterUnitKHR::PERCENTAGE => Ok(Self::Percentage),
+            ash::vk::PerformanceCounterUnitKHR::NANOSECONDS => Ok(Self::Nanoseconds),
+            ash::vk::PerformanceCounterUnitKHR::BYTES => Ok(Self::Bytes),
+            ash::vk::PerformanceCounterUnitKHR::BYTES_PER_SECOND => Ok(Self::BytesPerSecond),
+            ash::vk::PerformanceCounterUnitKHR::KELVIN => Ok(Self::Kelvin),
+            ash::vk::PerformanceCounterUnitKHR::WATTS => Ok(Self::Watts),
+            ash::vk::PerformanceCounterUnitKHR::VOLTS => Ok(Self::Volts),
+            ash::vk::PerformanceCounterUnitKHR::AMPS => Ok(Self::Amps),
+            ash::vk::PerformanceCounterUnitKHR::HERTZ => Ok(Self::Hertz),
+            ash::vk::PerformanceCounterUnitKHR::CYCLES => Ok(Self::Cycles),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The scope over which a performance counter is measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum PerformanceCounterScope {
+    /// The counter is measured over a whole command buffer. A query using the counter must be
+    /// begun as the first command of the command buffer, and ended as the last.
+    CommandBuffer = ash::vk::PerformanceCounterScopeKHR::COMMAND_BUFFER.as_raw(),
+    /// The counter is measured over a whole render pass. A query using the counter must be begun
+    /// and ended outside of a render pass.
+    RenderPass = ash::vk::PerformanceCounterScopeKHR::RENDER_PASS.as_raw(),
+    /// The counter is measured over any sequence of commands.
+    Command = ash::vk::PerformanceCounterScopeKHR::COMMAND.as_raw(),
+}
+
+impl TryFrom<ash::vk::PerformanceCounterScopeKHR> for PerformanceCounterScope {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::PerformanceCounterScopeKHR) -> Result<Self, Self::Error> {
+        match val {
+            ash::vk::PerformanceCounterScopeKHR::COMMAND_BUFFER => Ok(Self::CommandBuffer),
+            ash::vk::PerformanceCounterScopeKHR::RENDER_PASS => Ok(Self::RenderPass),
+            ash::vk::PerformanceCounterScopeKHR::COMMAND => Ok(Self::Command),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The type that the values of a performance counter are stored as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum PerformanceCounterStorage {
+    Int32 = ash::vk::PerformanceCounterStorageKHR::INT32.as_raw(),
+    Int64 = ash::vk::PerformanceCounterStorageKHR::INT64.as_raw(),
+    Uint32 = ash::vk::PerformanceCounterStorageKHR::UINT32.as_raw(),
+    Uint64 = ash::vk::PerformanceCounterStorageKHR::UINT64.as_raw(),
+    Float32 = ash::vk::PerformanceCounterStorageKHR::FLOAT32.as_raw(),
+    Float64 = ash::vk::PerformanceCounterStorageKHR::FLOAT64.as_raw(),
+}
+
+impl TryFrom<ash::vk::PerformanceCounterStorageKHR> for PerformanceCounterStorage {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::PerformanceCounterStorageKHR) -> Result<Self, Self::Error> {
+        match val {
+            ash::vk::PerformanceCounterStorageKHR::INT32 => Ok(Self::Int32),
+            ash::vk::PerformanceCounterStorageKHR::INT64 => Ok(Self::Int64),
+            ash::vk::PerformanceCounterStorageKHR::UINT32 => Ok(Self::Uint32),
+            ash::vk::PerformanceCounterStorageKHR::UINT64 => Ok(Self::Uint64),
+            ash::vk::PerformanceCounterStorageKHR::FLOAT32 => Ok(Self::Float32),
+            ash::vk::PerformanceCounterStorageKHR::FLOAT64 => Ok(Self::Float64),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The decoded value of a single performance counter in a query result.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PerformanceCounterResult {
+    Int32(i32),
+    Int64(i64),
+    Uint32(u32),
+    Uint64(u64),
+    Float32(f32),
+    Float64(f64),
+}
+
+impl PerformanceCounterResult {
+    /// Decodes a raw counter result, given the storage type of the counter.
+    #[inline]
+    pub(crate) fn from_vulkan(
+        storage: PerformanceCounterStorage,
+        value: ash::vk::PerformanceCounterResultKHR,
+    ) -> Self {
+        unsafe {
+            match storage {
+                PerformanceCounterStorage::Int32 => Self::Int32(value.int32),
+                PerformanceCounterStorage::Int64 => Self::Int64(value.int64),
+                PerformanceCounterStorage::Uint32 => Self::Uint32(value.uint32),
+                PerformanceCounterStorage::Uint64 => Self::Uint64(value.uint64),
+                PerformanceCounterStorage::Float32 => Self::Float32(value.float32),
+                PerformanceCounterStorage::Float64 => Self::Float64(value.float64),
+            }
+        }
+    }
+
+    /// Returns the value converted to `f64`.
+    #[inline]
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Self::Int32(value) => value as f64,
+            Self::Int64(value) => value as f64,
+            Self::Uint32(value) => value as f64,
+            Self::Uint64(value) => value as f64,
+            Self::Float32(value) => value as f64,
+            Self::Float64(value) => value,
+        }
+    }
+}
+
+/// The profiling lock of a device, which must be held while recording and executing command
+/// buffers that use performance queries.
+///
+/// The lock is released when this object is dropped.
+#[derive(Debug)]
+pub struct ProfilingLock {
+    device: Arc<Device>,
+}
+
+impl ProfilingLock {
+    /// Acquires the profiling lock of `device`.
+    ///
+    /// If `timeout` is `None`, waits indefinitely for the lock to become available.
+    pub fn acquire(
+        device: Arc<Device>,
+        timeout: Option<Duration>,
+    ) -> Result<ProfilingLock, ProfilingLockError> {
+        if !device.enabled_extensions().khr_performance_query {
+            return Err(ProfilingLockError::ExtensionNotEnabled {
+                extension: "khr_performance_query",
+                reason: "acquired the profiling lock",
+            });
+        }
+
+        // Not required by spec, but a second acquisition would make the release ambiguous.
+        if device.profiling_lock_held().swap(true, Ordering::AcqRel) {
+            return Err(ProfilingLockError::AlreadyHeld);
+        }
+
+        let info = ash::vk::AcquireProfilingLockInfoKHR {
+            flags: ash::vk::AcquireProfilingLockFlagsKHR::empty(),
+            timeout: timeout.map_or(u64::MAX, |timeout| {
+                timeout.as_nanos().try_into().unwrap_or(u64::MAX)
+            }),
+            ..Default::default()
+        };
+
+        let result = unsafe {
+            let fns = device.fns();
+            check_errors((fns.khr_performance_query.acquire_profiling_lock_khr)(
+                device.internal_object(),
+                &info,
+            ))
+        };
+
+        match result {
+            Ok(Success::Success) => Ok(ProfilingLock { device }),
+            Ok(Success::Timeout) => {
+                device.profiling_lock_held().store(false, Ordering::Release);
+                Err(ProfilingLockError::Timeout)
+            }
+            Ok(s) => panic!("unexpected success value: {:?}", s),
+            Err(err) => {
+                device.profiling_lock_held().store(false, Ordering::Release);
+                Err(err.into())
+            }
+        }
+    }
+}
+
+impl Drop for ProfilingLock {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let fns = self.device.fns();
+            (fns.khr_performance_query.release_profiling_lock_khr)(self.device.internal_object());
+        }
+
+        self.device
+            .profiling_lock_held()
+            .store(false, Ordering::Release);
+    }
+}
+
+unsafe impl DeviceOwned for ProfilingLock {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+/// Error that can happen when acquiring the profiling lock.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProfilingLockError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+
+    /// The profiling lock is already held.
+    AlreadyHeld,
+
+    /// The timeout expired before the profiling lock could be acquired.
+    Timeout,
+}
+
+impl error::Error for ProfilingLockError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProfilingLockError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::OomError(_) => write!(f, "not enough memory available"),
+            Self::ExtensionNotEnabled { extension, reason } => {
+                write!(f, "the extension {} must be enabled: {}", extension, reason)
+            }
+            Self::AlreadyHeld => write!(f, "the profiling lock is already held"),
+            Self::Timeout => write!(
+                f,
+                "the timeout expired before the profiling lock could be acquired",
+            ),
+        }
+    }
+}
+
+impl From<OomError> for ProfilingLockError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+impl From<Error> for ProfilingLockError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            err @ Error::OutOfHostMemory => Self::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => Self::OomError(OomError::from(err)),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        PerformanceCounterResult, PerformanceCounterStorage, ProfilingLock, ProfilingLockError,
+    };
+
+    #[test]
+    fn decode_result() {
+        let value = ash::vk::PerformanceCounterResultKHR { float64: 2.5 };
+        assert_eq!(
+            PerformanceCounterResult::from_vulkan(PerformanceCounterStorage::Float64, value),
+            PerformanceCounterResult::Float64(2.5),
+        );
+
+        let value = ash::vk::PerformanceCounterResultKHR { uint64: 42 };
+        assert_eq!(
+            PerformanceCounterResult::from_vulkan(PerformanceCounterStorage::Uint64, value)
+                .as_f64(),
+            42.0,
+        );
+    }
+
+    #[test]
+    fn profiling_lock_extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match ProfilingLock::acquire(device, None) {
+            Err(ProfilingLockError::ExtensionNotEnabled { .. }) => (),
+            _ => panic!(),
+        }
+    }
+}
@@ -0,0 +1,380 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! A query pool that is split into per-frame slots, for reading results without stalling.
+//!
+//! Reading the results of a query with [`QueryResultFlags::wait`] in the same frame that recorded
+//! it makes the CPU wait for the GPU to catch up, which removes any overlap between the two.
+//! A [`RotatingQueryPool`] avoids this by keeping the queries of the last few frames around: each
+//! frame writes to its own slot of the pool, and the results are read from the oldest frame that
+//! is still stored, which has usually finished executing by then.
+//!
+//! ```no_run
+//! # use vulkano::query::{QueryControlFlags, QueryType, rotating::RotatingQueryPool};
+//! # let device: std::sync::Arc<vulkano::device::Device> = return;
+//! # let mut builder: vulkano::command_buffer::AutoCommandBufferBuilder<vulkano::command_buffer::PrimaryAutoCommandBuffer> = return;
+//! let mut queries = RotatingQueryPool::new(device.clone(), QueryType::Occlusion, 16, 3).unwrap();
+//!
+//! // Once per frame, after waiting for the frame that last used the same slot.
+//! unsafe { queries.begin_frame(&mut builder).unwrap() };
+//!
+//! // Inside a render pass.
+//! let query = queries
+//!     .begin_query(&mut builder, QueryControlFlags { precise: false })
+//!     .unwrap();
+//! // Draw the object...
+//! queries.end_query(&mut builder, query).unwrap();
+//!
+//! // Build and submit the command buffer.
+//!
+//! if let Some(results) = queries.oldest_results().unwrap() {
+//!     println!("frame {}: {:?}", results.frame, results.results);
+//! }
+//! ```
+
+use super::{
+    GetResultsError, QueryControlFlags, QueryPool, QueryPoolCreateInfo, QueryPoolCreationError,
+    QueryResultFlags, QueryType,
+};
+use crate::{
+    command_buffer::{AutoCommandBufferBuilder, QueryError},
+    device::Device,
+    sync::PipelineStage,
+};
+use std::{error, fmt, sync::Arc};
+
+/// A query pool that keeps the queries of several frames, so that results can be read from an
+/// older frame while newer frames are being recorded.
+#[derive(Debug)]
+pub struct RotatingQueryPool {
+    query_pool: Arc<QueryPool>,
+    queries_per_frame: u32,
+
+    // The frame that is currently being recorded, or `None` before the first frame.
+    frame: Option<u64>,
+    // The next unused query in the slot of the current frame, relative to the start of the slot.
+    next_query: u32,
+    // For each slot, the frame that was recorded in it and the number of queries that it used.
+    slots: Vec<Option<(u64, u32)>>,
+}
+
+impl RotatingQueryPool {
+    /// Creates a new `RotatingQueryPool` of `query_type`, which can record `queries_per_frame`
+    /// queries in each frame, and keeps the queries of `frames_in_flight` frames.
+    ///
+    /// `frames_in_flight` should be at least the number of frames that can be executing on the
+    /// device at the same time, plus one.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `queries_per_frame` or `frames_in_flight` is `0`.
+    pub fn new(
+        device: Arc<Device>,
+        query_type: QueryType,
+        queries_per_frame: u32,
+        frames_in_flight: u32,
+    ) -> Result<Self, QueryPoolCreationError> {
+        assert!(queries_per_frame != 0);
+        assert!(frames_in_flight != 0);
+
+        let query_pool = QueryPool::new(
+            device,
+            QueryPoolCreateInfo {
+                query_count: queries_per_frame * frames_in_flight,
+                ..QueryPoolCreateInfo::query_type(query_type)
+            },
+        )?;
+
+        Ok(RotatingQueryPool {
+            query_pool,
+            queries_per_frame,
+
+            frame: None,
+            next_query: 0,
+            slots: vec![None; frames_in_flight as usize],
+        })
+    }
+
+    /// Returns the query pool that holds the queries of all the frames.
+    #[inline]
+    pub fn query_pool(&self) -> &Arc<QueryPool> {
+        &self.query_pool
+    }
+
+    /// Returns the number of queries that can be recorded in each frame.
+    #[inline]
+    pub fn queries_per_frame(&self) -> u32 {
+        self.queries_per_frame
+    }
+
+    /// Returns the number of frames whose queries are kept.
+    #[inline]
+    pub fn frames_in_flight(&self) -> u32 {
+        self.slots.len() as u32
+    }
+
+    /// Returns the number of the frame that is currently being recorded, or `None` if
+    /// [`begin_frame`](Self::begin_frame) has not been called yet.
+    #[inline]
+    pub fn frame(&self) -> Option<u64> {
+        self.frame
+    }
+
+    /// Starts recording a new frame, and records a command to reset the slot that it will use.
+    ///
+    /// The slot previously held the queries of the frame that was recorded `frames_in_flight`
+    /// frames ago, whose results can no longer be read afterwards. Returns the number of the new
+    /// frame.
+    ///
+    /// # Safety
+    ///
+    /// - The command buffers that recorded the queries of the frame `frames_in_flight` frames ago
+    ///   must have finished executing.
+    pub unsafe fn begin_frame<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+    ) -> Result<u64, QueryError> {
+        let frame = self.frame.map_or(0, |frame| frame + 1);
+        let slot = self.slot(frame);
+        let first_query = slot * self.queries_per_frame;
+
+        builder.reset_query_pool(
+            self.query_pool.clone(),
+            first_query..first_query + self.queries_per_frame,
+        )?;
+
+        self.frame = Some(frame);
+        self.next_query = 0;
+        self.slots[slot as usize] = Some((frame, 0));
+
+        Ok(frame)
+    }
+
+    /// Returns the index in [`query_pool`](Self::query_pool) of a query that has not been used
+    /// yet in the current frame.
+    ///
+    /// This can be used to record query commands that the other methods don't cover.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if [`begin_frame`](Self::begin_frame) has not been called yet.
+    pub fn next_query(&mut self) -> Result<u32, RotatingQueryPoolError> {
+        let frame = self.frame.expect("begin_frame has not been called");
+
+        if self.next_query >= self.queries_per_frame {
+            return Err(RotatingQueryPoolError::OutOfQueries);
+        }
+
+        let slot = self.slot(frame);
+        let query = slot * self.queries_per_frame + self.next_query;
+        self.next_query += 1;
+        self.slots[slot as usize] = Some((frame, self.next_query));
+
+        Ok(query)
+    }
+
+    /// Begins a query of the current frame, and returns its index in the query pool.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if [`begin_frame`](Self::begin_frame) has not been called yet.
+    pub fn begin_query<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        flags: QueryControlFlags,
+    ) -> Result<u32, RotatingQueryPoolError> {
+        let query = self.next_query()?;
+        builder.begin_query(self.query_pool.clone(), query, flags)?;
+
+        Ok(query)
+    }
+
+    /// Ends a query that was begun with [`begin_query`](Self::begin_query).
+    pub fn end_query<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        query: u32,
+    ) -> Result<(), RotatingQueryPoolError> {
+        builder.end_query(self.query_pool.clone(), query)?;
+
+        Ok(())
+    }
+
+    /// Writes a timestamp to a query of the current frame, and returns its index in the query
+    /// pool.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if [`begin_frame`](Self::begin_frame) has not been called yet.
+    pub fn write_timestamp<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        stage: PipelineStage,
+    ) -> Result<u32, RotatingQueryPoolError> {
+        let query = self.next_query()?;
+        builder.write_timestamp(self.query_pool.clone(), query, stage)?;
+
+        Ok(query)
+    }
+
+    /// Reads the results of the oldest frame that is still stored, other than the current frame.
+    ///
+    /// This never waits for the device. Returns `None` if there is no such frame, or if its
+    /// results are not available yet, in which case you can try again later. The same frame is
+    /// returned until [`begin_frame`](Self::begin_frame) reuses its slot.
+    pub fn oldest_results(&self) -> Result<Option<FrameQueryResults>, GetResultsError> {
+        let current = self.frame;
+        let oldest = self
+            .slots
+            .iter()
+            .flatten()
+            .filter(|&&(frame, _)| Some(frame) != current)
+            .min_by_key(|&&(frame, _)| frame);
+
+        let &(frame, query_count) = match oldest {
+            Some(oldest) => oldest,
+            None => return Ok(None),
+        };
+
+        let result_len = self.query_pool.result_len() as usize;
+        let mut results = vec![0u64; query_count as usize * result_len];
+
+        if query_count != 0 {
+            let first_query = self.slot(frame) * self.queries_per_frame;
+
+            if !self
+                .query_pool
+                .queries_range(first_query..first_query + query_count)
+                .unwrap()
+                .get_results(&mut results, QueryResultFlags::default())?
+            {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(FrameQueryResults { frame, results }))
+    }
+
+    #[inline]
+    fn slot(&self, frame: u64) -> u32 {
+        (frame % self.slots.len() as u64) as u32
+    }
+}
+
+/// The results of the queries of one frame of a [`RotatingQueryPool`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameQueryResults {
+    /// The number of the frame, as returned by
+    /// [`RotatingQueryPool::begin_frame`].
+    pub frame: u64,
+
+    /// The results of the queries, in the order that they were used in the frame.
+    ///
+    /// Each query has [`QueryPool::result_len`] elements.
+    pub results: Vec<u64>,
+}
+
+/// Error that can happen when recording a query of a `RotatingQueryPool`.
+#[derive(Clone, Debug)]
+pub enum RotatingQueryPoolError {
+    /// All the queries of the current frame have been used.
+    OutOfQueries,
+
+    /// Recording the query command failed.
+    QueryError(QueryError),
+}
+
+impl error::Error for RotatingQueryPoolError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::QueryError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RotatingQueryPoolError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OutOfQueries => write!(f, "all the queries of the current frame have been used",),
+            Self::QueryError(_) => write!(f, "recording a query command failed"),
+        }
+    }
+}
+
+impl From<QueryError> for RotatingQueryPoolError {
+    #[inline]
+    fn from(err: QueryError) -> Self {
+        Self::QueryError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RotatingQueryPool, RotatingQueryPoolError};
+    use crate::{
+        command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryCommandBuffer},
+        query::QueryType,
+        sync::{GpuFuture, PipelineStage},
+    };
+
+    #[test]
+    fn rotating_timestamps() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        if queue.family().timestamp_valid_bits().is_none() {
+            return;
+        }
+
+        let mut queries =
+            RotatingQueryPool::new(device.clone(), QueryType::Timestamp, 1, 2).unwrap();
+        assert!(queries.oldest_results().unwrap().is_none());
+
+        for expected_frame in 0..3 {
+            let mut builder = AutoCommandBufferBuilder::primary(
+                device.clone(),
+                queue.family(),
+                CommandBufferUsage::OneTimeSubmit,
+            )
+            .unwrap();
+
+            let frame = unsafe { queries.begin_frame(&mut builder).unwrap() };
+            assert_eq!(frame, expected_frame);
+            queries
+                .write_timestamp(&mut builder, PipelineStage::BottomOfPipe)
+                .unwrap();
+            assert!(matches!(
+                queries.write_timestamp(&mut builder, PipelineStage::BottomOfPipe),
+                Err(RotatingQueryPoolError::OutOfQueries)
+            ));
+
+            builder
+                .build()
+                .unwrap()
+                .execute(queue.clone())
+                .unwrap()
+                .then_signal_fence_and_flush()
+                .unwrap()
+                .wait(None)
+                .unwrap();
+
+            // With two slots, the oldest frame other than the current one is the previous frame.
+            let results = queries.oldest_results().unwrap();
+            if frame == 0 {
+                assert!(results.is_none());
+            } else {
+                let results = results.unwrap();
+                assert_eq!(results.frame, frame - 1);
+                assert_eq!(results.results.len(), 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,552 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Records a timeline of GPU work using timestamp queries, and exports it for visualization.
+//!
+//! A [`GpuTimeline`] owns a timestamp query pool. While recording command buffers, you open and
+//! close *scopes* around the work that you want to measure, such as whole command buffers,
+//! render passes or labeled sections. Each scope records a timestamp at its start and at its end.
+//! Once the command buffers have finished executing, [`GpuTimeline::resolve`] reads back the
+//! timestamps and turns every scope into a [`TimelineEvent`].
+//!
+//! Events are grouped per queue, so that work that runs in parallel on different queues shows up
+//! on separate tracks. The collected events can be written in the Chrome trace event format with
+//! [`GpuTimeline::write_chrome_trace`], which can be opened in `chrome://tracing` or in the
+//! Perfetto UI.
+//!
+//! ```no_run
+//! # use vulkano::query::timeline::{GpuTimeline, TimelineScopeKind};
+//! # let device: std::sync::Arc<vulkano::device::Device> = return;
+//! # let queue: std::sync::Arc<vulkano::device::Queue> = return;
+//! # let mut builder: vulkano::command_buffer::AutoCommandBufferBuilder<vulkano::command_buffer::PrimaryAutoCommandBuffer> = return;
+//! let mut timeline = GpuTimeline::new(device.clone(), 256).unwrap();
+//!
+//! unsafe {
+//!     timeline.begin_frame(&mut builder).unwrap();
+//!     timeline
+//!         .begin_scope(&mut builder, &queue, "frame", TimelineScopeKind::CommandBuffer)
+//!         .unwrap();
+//!     // Record commands...
+//!     timeline.end_scope(&mut builder).unwrap();
+//! }
+//!
+//! // Build and submit the command buffer, and wait for it to finish.
+//!
+//! timeline.resolve().unwrap();
+//! timeline
+//!     .write_chrome_trace(std::fs::File::create("trace.json").unwrap())
+//!     .unwrap();
+//! ```
+
+use super::{
+    GetResultsError, QueryPool, QueryPoolCreateInfo, QueryPoolCreationError, QueryResultFlags,
+    QueryType,
+};
+use crate::{
+    command_buffer::{AutoCommandBufferBuilder, QueryError},
+    device::{Device, DeviceOwned, Queue},
+    sync::PipelineStage,
+};
+use std::{
+    borrow::Cow,
+    error, fmt,
+    io::{self, Write},
+    sync::Arc,
+};
+
+/// Records scopes of GPU work with timestamp queries, and collects them into a timeline.
+#[derive(Debug)]
+pub struct GpuTimeline {
+    query_pool: Arc<QueryPool>,
+    timestamp_period: f32,
+
+    // The frame that is currently being recorded.
+    frame: u64,
+    // The next unused query in the pool.
+    next_query: u32,
+    // Indices into `pending` of the scopes that have been opened but not closed yet.
+    open_scopes: Vec<usize>,
+    // Scopes that were recorded in the current frame, but whose results haven't been read yet.
+    pending: Vec<PendingScope>,
+    // Scopes whose results have been read.
+    events: Vec<TimelineEvent>,
+}
+
+#[derive(Debug)]
+struct PendingScope {
+    name: Cow<'static, str>,
+    kind: TimelineScopeKind,
+    track: TimelineTrack,
+    depth: u32,
+    start_query: u32,
+    end_query: Option<u32>,
+}
+
+impl GpuTimeline {
+    /// Creates a new `GpuTimeline` that can record up to `max_scopes` scopes per frame.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `max_scopes` is `0`.
+    pub fn new(device: Arc<Device>, max_scopes: u32) -> Result<Self, QueryPoolCreationError> {
+        assert!(max_scopes != 0);
+
+        let timestamp_period = device.physical_device().properties().timestamp_period;
+        let query_pool = QueryPool::new(
+            device,
+            QueryPoolCreateInfo {
+                query_count: max_scopes * 2,
+                ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+            },
+        )?;
+
+        Ok(GpuTimeline {
+            query_pool,
+            timestamp_period,
+
+            frame: 0,
+            next_query: 0,
+            open_scopes: Vec::new(),
+            pending: Vec::new(),
+            events: Vec::new(),
+        })
+    }
+
+    /// Returns the query pool that holds the timestamps.
+    #[inline]
+    pub fn query_pool(&self) -> &Arc<QueryPool> {
+        &self.query_pool
+    }
+
+    /// Returns the number of the frame that is currently being recorded.
+    #[inline]
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Starts recording a new frame, and records a command to reset the queries of the timeline.
+    ///
+    /// Any scopes of the previous frame that have not been resolved yet are discarded, so you
+    /// should call [`resolve`](Self::resolve) before this.
+    ///
+    /// # Safety
+    ///
+    /// - The command buffers that recorded the previous frame must have finished executing.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if there are scopes that have not been closed.
+    pub unsafe fn begin_frame<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+    ) -> Result<(), QueryError> {
+        assert!(
+            self.open_scopes.is_empty(),
+            "a scope was still open when beginning a new frame"
+        );
+
+        builder.reset_query_pool(self.query_pool.clone(), 0..self.query_pool.query_count())?;
+
+        if self.next_query != 0 {
+            self.frame += 1;
+        }
+
+        self.next_query = 0;
+        self.pending.clear();
+
+        Ok(())
+    }
+
+    /// Opens a new scope, recording a timestamp at the start of it.
+    ///
+    /// `queue` is the queue that the command buffer will be submitted to. It determines the track
+    /// that the scope is shown on. Scopes can be nested; each scope must be closed with
+    /// [`end_scope`](Self::end_scope) in the same command buffer.
+    ///
+    /// [`begin_frame`](Self::begin_frame) must have been recorded in a command buffer that
+    /// executes before this one, otherwise submitting the command buffer will fail.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `queue` does not belong to the same device as the timeline.
+    pub fn begin_scope<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+        queue: &Queue,
+        name: impl Into<Cow<'static, str>>,
+        kind: TimelineScopeKind,
+    ) -> Result<(), TimelineError> {
+        assert_eq!(self.query_pool.device(), queue.device());
+
+        // Each open scope still needs a query for its end.
+        if self.next_query + self.open_scopes.len() as u32 + 2 > self.query_pool.query_count() {
+            return Err(TimelineError::OutOfQueries);
+        }
+
+        let start_query = self.next_query;
+        builder.write_timestamp(
+            self.query_pool.clone(),
+            start_query,
+            PipelineStage::TopOfPipe,
+        )?;
+        self.next_query += 1;
+
+        self.open_scopes.push(self.pending.len());
+        self.pending.push(PendingScope {
+            name: name.into(),
+            kind,
+            track: TimelineTrack {
+                queue_family_index: queue.family().id(),
+                queue_index: queue.id_within_family(),
+            },
+            depth: self.open_scopes.len() as u32 - 1,
+            start_query,
+            end_query: None,
+        });
+
+        Ok(())
+    }
+
+    /// Closes the most recently opened scope, recording a timestamp at the end of it.
+    ///
+    /// # Safety
+    ///
+    /// - The scope must have been opened in the same command buffer.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if there is no open scope.
+    pub unsafe fn end_scope<L, P>(
+        &mut self,
+        builder: &mut AutoCommandBufferBuilder<L, P>,
+    ) -> Result<(), TimelineError> {
+        let index = *self.open_scopes.last().expect("no scope is open");
+
+        // `begin_scope` made sure that a query is left for the end of the scope.
+        let end_query = self.next_query;
+        builder.write_timestamp(
+            self.query_pool.clone(),
+            end_query,
+            PipelineStage::BottomOfPipe,
+        )?;
+        self.next_query += 1;
+
+        self.open_scopes.pop();
+        self.pending[index].end_query = Some(end_query);
+
+        Ok(())
+    }
+
+    /// Reads back the timestamps of the scopes recorded in the current frame, and adds them to the
+    /// list of events.
+    ///
+    /// Returns `false` if the results are not available yet, in which case nothing is added and
+    /// you can try again later. Scopes that have not been closed are ignored.
+    pub fn resolve(&mut self) -> Result<bool, GetResultsError> {
+        if self.next_query == 0 {
+            return Ok(true);
+        }
+
+        let mut timestamps = vec![0u64; self.next_query as usize];
+
+        if !self
+            .query_pool
+            .queries_range(0..self.next_query)
+            .unwrap()
+            .get_results(&mut timestamps, QueryResultFlags::default())?
+        {
+            return Ok(false);
+        }
+
+        let ns = |ticks: u64| (ticks as f64 * self.timestamp_period as f64) as u64;
+        let frame = self.frame;
+
+        self.events
+            .extend(self.pending.drain(..).filter_map(|scope| {
+                let end_query = scope.end_query?;
+                let start = timestamps[scope.start_query as usize];
+                let end = timestamps[end_query as usize];
+
+                Some(TimelineEvent {
+                    name: scope.name,
+                    kind: scope.kind,
+                    track: scope.track,
+                    frame,
+                    depth: scope.depth,
+                    start_ns: ns(start),
+                    duration_ns: ns(end.saturating_sub(start)),
+                })
+            }));
+
+        Ok(true)
+    }
+
+    /// Returns the events that have been resolved so far.
+    #[inline]
+    pub fn events(&self) -> &[TimelineEvent] {
+        &self.events
+    }
+
+    /// Removes all resolved events.
+    #[inline]
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    /// Writes the resolved events in the Chrome trace event format.
+    ///
+    /// Each queue family is written as a separate process, and each queue within it as a thread.
+    #[inline]
+    pub fn write_chrome_trace(&self, writer: impl Write) -> io::Result<()> {
+        write_chrome_trace(&self.events, writer)
+    }
+}
+
+/// Writes a list of events in the Chrome trace event format.
+///
+/// The output can be loaded in `chrome://tracing` or in the Perfetto UI. Timestamps are made
+/// relative to the earliest event.
+pub fn write_chrome_trace(events: &[TimelineEvent], mut writer: impl Write) -> io::Result<()> {
+    let origin = events.iter().map(|event| event.start_ns).min().unwrap_or(0);
+    let mut tracks: Vec<TimelineTrack> = events.iter().map(|event| event.track).collect();
+    tracks.sort_unstable();
+    tracks.dedup();
+
+    write!(writer, "{{\"traceEvents\":[")?;
+    let mut first = true;
+
+    for track in &tracks {
+        if !first {
+            write!(writer, ",")?;
+        }
+        first = false;
+
+        write!(
+            writer,
+            "{{\"name\":\"process_name\",\"ph\":\"M\",\"pid\":{},\"args\":{{\"name\":\"Queue family {}\"}}}},",
+            track.queue_family_index, track.queue_family_index,
+        )?;
+        write!(
+            writer,
+            "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{},\"tid\":{},\"args\":{{\"name\":\"Queue {}\"}}}}",
+            track.queue_family_index, track.queue_index, track.queue_index,
+        )?;
+    }
+
+    for event in events {
+        if !first {
+            write!(writer, ",")?;
+        }
+        first = false;
+
+        write!(writer, "{{\"name\":")?;
+        write_json_string(&mut writer, &event.name)?;
+        write!(
+            writer,
+            ",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":{},\"tid\":{},\"args\":{{\"frame\":{}}}}}",
+            event.kind.category(),
+            (event.start_ns - origin) as f64 / 1000.0,
+            event.duration_ns as f64 / 1000.0,
+            event.track.queue_family_index,
+            event.track.queue_index,
+            event.frame,
+        )?;
+    }
+
+    write!(writer, "],\"displayTimeUnit\":\"ns\"}}")
+}
+
+fn write_json_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+
+    for c in value.chars() {
+        match c {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            '\n' => write!(writer, "\\n")?,
+            '\r' => write!(writer, "\\r")?,
+            '\t' => write!(writer, "\\t")?,
+            c if (c as u32) < 0x20 => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+
+    write!(writer, "\"")
+}
+
+/// A scope of GPU work whose timestamps have been resolved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimelineEvent {
+    /// The name that was given to the scope.
+    pub name: Cow<'static, str>,
+
+    /// The kind of work that the scope represents.
+    pub kind: TimelineScopeKind,
+
+    /// The queue that the work was executed on.
+    pub track: TimelineTrack,
+
+    /// The frame that the scope was recorded in.
+    pub frame: u64,
+
+    /// The number of scopes that enclosed this scope when it was opened.
+    pub depth: u32,
+
+    /// The time at which the scope started, in nanoseconds. The origin of the time is
+    /// implementation-defined, so only differences between events are meaningful.
+    pub start_ns: u64,
+
+    /// The time between the start and the end of the scope, in nanoseconds.
+    pub duration_ns: u64,
+}
+
+/// Identifies the queue that a scope was executed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimelineTrack {
+    /// The index of the queue family of the queue.
+    pub queue_family_index: u32,
+
+    /// The index of the queue within its queue family.
+    pub queue_index: u32,
+}
+
+/// The kind of work that a scope represents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimelineScopeKind {
+    /// The scope covers a whole command buffer.
+    CommandBuffer,
+
+    /// The scope covers a render pass or a dynamic rendering instance.
+    RenderPass,
+
+    /// The scope covers a user-defined section of commands.
+    Label,
+}
+
+impl TimelineScopeKind {
+    fn category(self) -> &'static str {
+        match self {
+            Self::CommandBuffer => "command_buffer",
+            Self::RenderPass => "render_pass",
+            Self::Label => "label",
+        }
+    }
+}
+
+/// Error that can happen when recording a scope of a `GpuTimeline`.
+#[derive(Clone, Debug)]
+pub enum TimelineError {
+    /// All the queries of the timeline have been used in the current frame.
+    OutOfQueries,
+
+    /// Recording the timestamp query failed.
+    QueryError(QueryError),
+}
+
+impl error::Error for TimelineError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::QueryError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TimelineError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OutOfQueries => write!(
+                f,
+                "all the queries of the timeline have been used in the current frame",
+            ),
+            Self::QueryError(_) => write!(f, "recording a timestamp query failed"),
+        }
+    }
+}
+
+impl From<QueryError> for TimelineError {
+    #[inline]
+    fn from(err: QueryError) -> Self {
+        Self::QueryError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_chrome_trace, TimelineEvent, TimelineScopeKind, TimelineTrack};
+
+    #[test]
+    fn chrome_trace() {
+        let events = [
+            TimelineEvent {
+                name: "frame".into(),
+                kind: TimelineScopeKind::CommandBuffer,
+                track: TimelineTrack {
+                    queue_family_index: 0,
+                    queue_index: 0,
+                },
+                frame: 3,
+                depth: 0,
+                start_ns: 1_000,
+                duration_ns: 5_000,
+            },
+            TimelineEvent {
+                name: "\"shadow\" pass".into(),
+                kind: TimelineScopeKind::RenderPass,
+                track: TimelineTrack {
+                    queue_family_index: 0,
+                    queue_index: 0,
+                },
+                frame: 3,
+                depth: 1,
+                start_ns: 2_000,
+                duration_ns: 1_500,
+            },
+        ];
+
+        let mut output = Vec::new();
+        write_chrome_trace(&events, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("{\"traceEvents\":["));
+        assert!(output.contains(
+            "{\"name\":\"frame\",\"cat\":\"command_buffer\",\"ph\":\"X\",\"ts\":0.000,\"dur\":5.000,\"pid\":0,\"tid\":0,\"args\":{\"frame\":3}}"
+        ));
+        assert!(output.contains("\"name\":\"\\\"shadow\\\" pass\",\"cat\":\"render_pass\""));
+        assert!(output.contains("\"ts\":1.000,\"dur\":1.500"));
+        assert_eq!(output.matches("\"ph\":\"M\"").count(), 2);
+    }
+
+    #[test]
+    fn timeline_out_of_queries() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let mut timeline = super::GpuTimeline::new(device.clone(), 1).unwrap();
+
+        let mut builder = crate::command_buffer::AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            crate::command_buffer::CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        unsafe {
+            timeline.begin_frame(&mut builder).unwrap();
+            timeline
+                .begin_scope(&mut builder, &queue, "a", TimelineScopeKind::Label)
+                .unwrap();
+            assert!(matches!(
+                timeline.begin_scope(&mut builder, &queue, "b", TimelineScopeKind::Label),
+                Err(super::TimelineError::OutOfQueries)
+            ));
+            timeline.end_scope(&mut builder).unwrap();
+        }
+    }
+}
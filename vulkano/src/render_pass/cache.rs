@@ -0,0 +1,273 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use super::{
+    Framebuffer, FramebufferCreateInfo, FramebufferCreationError, RenderPass, RenderPassCreateInfo,
+    RenderPassCreationError,
+};
+use crate::{
+    device::{Device, DeviceOwned},
+    VulkanObject,
+};
+use smallvec::SmallVec;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Deduplicates render passes and framebuffers, so that they can be requested every frame
+/// without creating new objects each time.
+///
+/// Requesting a render pass with the same `RenderPassCreateInfo` as an earlier request returns
+/// the same `RenderPass`. Requesting a framebuffer for the same render pass, with the same
+/// attachment image views, extent and layers as an earlier request returns the same
+/// `Framebuffer`.
+///
+/// The standard cache of a device is returned by
+/// [`Device::render_pass_cache`](crate::device::Device::render_pass_cache).
+///
+/// # Framebuffer lifetime
+///
+/// Cached framebuffers keep their attachments alive. When a new framebuffer is created, the
+/// cache first destroys the framebuffers that have an attachment that is no longer used outside
+/// of the cache, for example the image views of a swapchain that was recreated. You can also do
+/// this manually with [`trim`](Self::trim).
+#[derive(Debug)]
+pub struct RenderPassCache {
+    device: Arc<Device>,
+    render_passes: Mutex<HashMap<RenderPassCreateInfo, Arc<RenderPass>>>,
+    framebuffers: Mutex<HashMap<FramebufferKey, Arc<Framebuffer>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct FramebufferKey {
+    render_pass: ash::vk::RenderPass,
+    attachments: SmallVec<[ash::vk::ImageView; 4]>,
+    extent: [u32; 2],
+    layers: u32,
+}
+
+impl RenderPassCache {
+    /// Creates a new empty `RenderPassCache`.
+    #[inline]
+    pub fn new(device: Arc<Device>) -> Self {
+        RenderPassCache {
+            device,
+            render_passes: Mutex::new(HashMap::default()),
+            framebuffers: Mutex::new(HashMap::default()),
+        }
+    }
+
+    /// Returns a render pass created from `create_info`, creating it if it isn't in the cache
+    /// yet.
+    pub fn render_pass(
+        &self,
+        create_info: RenderPassCreateInfo,
+    ) -> Result<Arc<RenderPass>, RenderPassCreationError> {
+        let mut render_passes = self.render_passes.lock().unwrap();
+
+        if let Some(render_pass) = render_passes.get(&create_info) {
+            return Ok(render_pass.clone());
+        }
+
+        let render_pass = RenderPass::new(self.device.clone(), create_info.clone())?;
+        render_passes.insert(create_info, render_pass.clone());
+
+        Ok(render_pass)
+    }
+
+    /// Returns a framebuffer for `render_pass` created from `create_info`, creating it if it
+    /// isn't in the cache yet.
+    ///
+    /// Attachments are compared by image view, not by image. An `extent` or `layers` of zero is
+    /// only equal to another zero, even if an explicit value would result in the same
+    /// framebuffer.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `render_pass` was not created from the same device as the cache.
+    pub fn framebuffer(
+        &self,
+        render_pass: Arc<RenderPass>,
+        create_info: FramebufferCreateInfo,
+    ) -> Result<Arc<Framebuffer>, FramebufferCreationError> {
+        assert_eq!(
+            render_pass.device().internal_object(),
+            self.device.internal_object()
+        );
+
+        let key = FramebufferKey {
+            render_pass: render_pass.internal_object(),
+            attachments: create_info
+                .attachments
+                .iter()
+                .map(|view| view.internal_object())
+                .collect(),
+            extent: create_info.extent,
+            layers: create_info.layers,
+        };
+
+        let mut framebuffers = self.framebuffers.lock().unwrap();
+
+        if let Some(framebuffer) = framebuffers.get(&key) {
+            return Ok(framebuffer.clone());
+        }
+
+        trim_framebuffers(&mut framebuffers);
+
+        let framebuffer = Framebuffer::new(render_pass, create_info)?;
+        framebuffers.insert(key, framebuffer.clone());
+
+        Ok(framebuffer)
+    }
+
+    /// Destroys the cached framebuffers that are not used outside of the cache, and that have an
+    /// attachment that is not used outside of the cache either. Returns the number of
+    /// framebuffers that were destroyed.
+    ///
+    /// Such framebuffers can never be requested again, as their attachments are unreachable.
+    #[inline]
+    pub fn trim(&self) -> usize {
+        trim_framebuffers(&mut self.framebuffers.lock().unwrap())
+    }
+
+    /// Removes all render passes and framebuffers from the cache.
+    ///
+    /// Objects that are still used outside of the cache are kept alive by their users.
+    #[inline]
+    pub fn clear(&self) {
+        self.framebuffers.lock().unwrap().clear();
+        self.render_passes.lock().unwrap().clear();
+    }
+
+    /// Returns the number of render passes in the cache.
+    #[inline]
+    pub fn render_pass_count(&self) -> usize {
+        self.render_passes.lock().unwrap().len()
+    }
+
+    /// Returns the number of framebuffers in the cache.
+    #[inline]
+    pub fn framebuffer_count(&self) -> usize {
+        self.framebuffers.lock().unwrap().len()
+    }
+}
+
+unsafe impl DeviceOwned for RenderPassCache {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+fn trim_framebuffers(framebuffers: &mut HashMap<FramebufferKey, Arc<Framebuffer>>) -> usize {
+    // The number of references to each attachment that are held by the cached framebuffers. An
+    // attachment is unused outside of the cache if it has no other references.
+    let mut cached_references: HashMap<ash::vk::ImageView, usize> = HashMap::default();
+
+    for framebuffer in framebuffers.values() {
+        if Arc::strong_count(framebuffer) == 1 {
+            for view in framebuffer.attachments() {
+                *cached_references.entry(view.internal_object()).or_default() += 1;
+            }
+        }
+    }
+
+    let len = framebuffers.len();
+    framebuffers.retain(|_, framebuffer| {
+        Arc::strong_count(framebuffer) != 1
+            || framebuffer
+                .attachments()
+                .iter()
+                .all(|view| Arc::strong_count(view) > cached_references[&view.internal_object()])
+    });
+
+    len - framebuffers.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenderPassCache;
+    use crate::{
+        format::Format,
+        image::{view::ImageView, AttachmentImage, ImageLayout},
+        render_pass::{
+            AttachmentDescription, AttachmentReference, FramebufferCreateInfo, LoadOp,
+            RenderPassCreateInfo, StoreOp, SubpassDescription,
+        },
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn reuse_and_trim() {
+        let (device, _) = gfx_dev_and_queue!();
+        let cache = RenderPassCache::new(device.clone());
+
+        let create_info = || RenderPassCreateInfo {
+            attachments: vec![AttachmentDescription {
+                format: Some(Format::R8G8B8A8_UNORM),
+                load_op: LoadOp::Clear,
+                store_op: StoreOp::Store,
+                initial_layout: ImageLayout::ColorAttachmentOptimal,
+                final_layout: ImageLayout::ColorAttachmentOptimal,
+                ..Default::default()
+            }],
+            subpasses: vec![SubpassDescription {
+                color_attachments: vec![Some(AttachmentReference {
+                    attachment: 0,
+                    layout: ImageLayout::ColorAttachmentOptimal,
+                    ..Default::default()
+                })],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let render_pass = cache.render_pass(create_info()).unwrap();
+        assert!(Arc::ptr_eq(
+            &render_pass,
+            &cache.render_pass(create_info()).unwrap()
+        ));
+        assert_eq!(cache.render_pass_count(), 1);
+
+        let new_view = || {
+            ImageView::new_default(
+                AttachmentImage::new(device.clone(), [64, 64], Format::R8G8B8A8_UNORM).unwrap(),
+            )
+            .unwrap()
+        };
+        let framebuffer_info = |view: &Arc<ImageView<AttachmentImage>>| FramebufferCreateInfo {
+            attachments: vec![view.clone()],
+            ..Default::default()
+        };
+
+        let view = new_view();
+        let framebuffer = cache
+            .framebuffer(render_pass.clone(), framebuffer_info(&view))
+            .unwrap();
+        assert!(Arc::ptr_eq(
+            &framebuffer,
+            &cache
+                .framebuffer(render_pass.clone(), framebuffer_info(&view))
+                .unwrap()
+        ));
+        assert_eq!(cache.framebuffer_count(), 1);
+
+        // Still in use.
+        drop(view);
+        assert_eq!(cache.trim(), 0);
+
+        // The attachment is now unreachable, so the framebuffer is destroyed.
+        drop(framebuffer);
+        cache
+            .framebuffer(render_pass, framebuffer_info(&new_view()))
+            .unwrap();
+        assert_eq!(cache.framebuffer_count(), 1);
+    }
+}
@@ -8,7 +8,7 @@
 // according to those terms.
 
 use super::{
-    AttachmentDescription, AttachmentReference, LoadOp, RenderPass, RenderPassCreateInfo,
+    AttachmentDescription, AttachmentReference, LoadOp, RenderPass, RenderPassCreateInfo, StoreOp,
     SubpassDependency, SubpassDescription,
 };
 use crate::{
@@ -33,6 +33,7 @@ impl RenderPass {
             subpasses,
             dependencies,
             correlated_view_masks,
+            fragment_density_map_attachment,
             _ne: _,
         } = create_info;
 
@@ -54,10 +55,21 @@ impl RenderPass {
                 stencil_store_op,
                 initial_layout,
                 final_layout,
+                name,
                 _ne: _,
             } = attachment;
             let atch_num = atch_num as u32;
 
+            if name.is_some()
+                && attachments[..atch_num as usize]
+                    .iter()
+                    .any(|other| other.name == name)
+            {
+                return Err(RenderPassCreationError::AttachmentNameNotUnique {
+                    attachment: atch_num,
+                });
+            }
+
             // VUID-VkAttachmentDescription2-finalLayout-03061
             if matches!(
                 final_layout,
@@ -593,6 +605,17 @@ impl RenderPass {
                     });
                 }
 
+                // VUID-VkSubpassDependency2-srcStageMask-04093
+                // VUID-VkSubpassDependency2-dstStageMask-04093
+                if stages.fragment_density_process
+                    && !device.enabled_features().fragment_density_map
+                {
+                    return Err(RenderPassCreationError::FeatureNotEnabled {
+                        feature: "fragment_density_map",
+                        reason: "a dependency specified the `fragment_density_process` stage",
+                    });
+                }
+
                 // VUID-VkSubpassDependency2-srcStageMask-03937
                 // VUID-VkSubpassDependency2-dstStageMask-03937
                 if *stages == PipelineStages::none() && !device.enabled_features().synchronization2
@@ -659,6 +682,7 @@ impl RenderPass {
                         late_fragment_tests: false,
                         color_attachment_output: false,
                         all_graphics: false,
+                        fragment_density_process: false,
                         ..*stages
                     };
 
@@ -848,6 +872,87 @@ impl RenderPass {
             }
         }
 
+        /*
+            Fragment density map attachment
+        */
+
+        if let Some(atch_ref) = fragment_density_map_attachment {
+            if !device.enabled_extensions().ext_fragment_density_map {
+                return Err(RenderPassCreationError::ExtensionNotEnabled {
+                    extension: "ext_fragment_density_map",
+                    reason: "`fragment_density_map_attachment` was `Some`",
+                });
+            }
+
+            // VUID-VkRenderPassCreateInfo-fragmentDensityMapAttachment-06471
+            let atch = attachments.get(atch_ref.attachment as usize).ok_or(
+                RenderPassCreationError::FragmentDensityMapAttachmentOutOfRange {
+                    attachment: atch_ref.attachment,
+                },
+            )?;
+
+            // VUID-VkRenderPassFragmentDensityMapCreateInfoEXT-fragmentDensityMapAttachment-02547
+            if let Some(subpass_num) = subpasses.iter().position(|subpass| {
+                (subpass.input_attachments.iter())
+                    .chain(subpass.color_attachments.iter())
+                    .chain(subpass.resolve_attachments.iter())
+                    .flatten()
+                    .chain(subpass.depth_stencil_attachment.iter())
+                    .any(|other_atch_ref| other_atch_ref.attachment == atch_ref.attachment)
+                    || subpass.preserve_attachments.contains(&atch_ref.attachment)
+            }) {
+                return Err(
+                    RenderPassCreationError::FragmentDensityMapAttachmentUsedInSubpass {
+                        attachment: atch_ref.attachment,
+                        subpass: subpass_num as u32,
+                    },
+                );
+            }
+
+            // VUID-VkRenderPassFragmentDensityMapCreateInfoEXT-fragmentDensityMapAttachment-02548
+            if !matches!(
+                atch_ref.layout,
+                ImageLayout::FragmentDensityMapOptimal | ImageLayout::General
+            ) {
+                return Err(
+                    RenderPassCreationError::FragmentDensityMapAttachmentLayoutInvalid {
+                        attachment: atch_ref.attachment,
+                    },
+                );
+            }
+
+            // VUID-VkRenderPassFragmentDensityMapCreateInfoEXT-fragmentDensityMapAttachment-02549
+            // VUID-VkRenderPassFragmentDensityMapCreateInfoEXT-fragmentDensityMapAttachment-02550
+            if atch.load_op == LoadOp::Clear || atch.store_op != StoreOp::DontCare {
+                return Err(
+                    RenderPassCreationError::FragmentDensityMapAttachmentLoadStoreOpInvalid {
+                        attachment: atch_ref.attachment,
+                    },
+                );
+            }
+
+            // Not required by spec, but enforced by Vulkano for sanity.
+            if atch_ref.aspects != ImageAspects::none() {
+                return Err(
+                    RenderPassCreationError::FragmentDensityMapAttachmentAspectsNotEmpty {
+                        attachment: atch_ref.attachment,
+                    },
+                );
+            }
+
+            // An image with a format that doesn't support the usage can't be bound to the
+            // attachment, so catch this early.
+            if !attachment_potential_format_features[atch_ref.attachment as usize]
+                .fragment_density_map
+            {
+                return Err(
+                    RenderPassCreationError::FragmentDensityMapAttachmentFormatNotSupported {
+                        attachment: atch_ref.attachment,
+                    },
+                );
+            }
+        }
+
         /*
             Correlated view masks
         */
@@ -880,6 +985,7 @@ impl RenderPass {
             subpasses,
             dependencies,
             correlated_view_masks,
+            fragment_density_map_attachment,
             _ne: _,
         } = create_info;
 
@@ -1022,7 +1128,18 @@ impl RenderPass {
             })
             .collect::<SmallVec<[_; 4]>>();
 
-        let create_info = ash::vk::RenderPassCreateInfo2 {
+        let mut fragment_density_map_create_info =
+            fragment_density_map_attachment.as_ref().map(|atch_ref| {
+                ash::vk::RenderPassFragmentDensityMapCreateInfoEXT {
+                    fragment_density_map_attachment: ash::vk::AttachmentReference {
+                        attachment: atch_ref.attachment,
+                        layout: atch_ref.layout.into(),
+                    },
+                    ..Default::default()
+                }
+            });
+
+        let mut create_info = ash::vk::RenderPassCreateInfo2 {
             flags: ash::vk::RenderPassCreateFlags::empty(),
             attachment_count: attachments_vk.len() as u32,
             p_attachments: if attachments_vk.is_empty() {
@@ -1047,6 +1164,11 @@ impl RenderPass {
             ..Default::default()
         };
 
+        if let Some(fragment_density_map_create_info) = fragment_density_map_create_info.as_mut() {
+            fragment_density_map_create_info.p_next = create_info.p_next;
+            create_info.p_next = fragment_density_map_create_info as *const _ as *const _;
+        }
+
         Ok({
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -1080,6 +1202,7 @@ impl RenderPass {
             subpasses,
             dependencies,
             correlated_view_masks,
+            fragment_density_map_attachment,
             _ne: _,
         } = create_info;
 
@@ -1278,6 +1401,19 @@ impl RenderPass {
             None
         };
 
+        /* Fragment density map */
+
+        let mut fragment_density_map_create_info =
+            fragment_density_map_attachment.as_ref().map(|atch_ref| {
+                ash::vk::RenderPassFragmentDensityMapCreateInfoEXT {
+                    fragment_density_map_attachment: ash::vk::AttachmentReference {
+                        attachment: atch_ref.attachment,
+                        layout: atch_ref.layout.into(),
+                    },
+                    ..Default::default()
+                }
+            });
+
         /* Create */
 
         let mut create_info = ash::vk::RenderPassCreateInfo {
@@ -1315,6 +1451,11 @@ impl RenderPass {
             create_info.p_next = multiview_create_info as *const _ as *const _;
         }
 
+        if let Some(fragment_density_map_create_info) = fragment_density_map_create_info.as_mut() {
+            fragment_density_map_create_info.p_next = create_info.p_next;
+            create_info.p_next = fragment_density_map_create_info as *const _ as *const _;
+        }
+
         Ok({
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -1355,6 +1496,9 @@ pub enum RenderPassCreationError {
     /// provided `format`.
     AttachmentLayoutInvalid { attachment: u32 },
 
+    /// An attachment has the same `name` as an earlier attachment.
+    AttachmentNameNotUnique { attachment: u32 },
+
     /// Correlated view masks were included, but multiview is not enabled on the render pass.
     CorrelatedViewMasksMultiviewNotEnabled,
 
@@ -1404,6 +1548,29 @@ pub enum RenderPassCreationError {
     /// on the render pass.
     DependencyViewLocalMultiviewNotEnabled { dependency: u32 },
 
+    /// The fragment density map attachment has one or more aspects selected.
+    FragmentDensityMapAttachmentAspectsNotEmpty { attachment: u32 },
+
+    /// The fragment density map attachment has a format that does not support being used as a
+    /// fragment density map.
+    FragmentDensityMapAttachmentFormatNotSupported { attachment: u32 },
+
+    /// The fragment density map attachment reference has a layout other than
+    /// [`ImageLayout::FragmentDensityMapOptimal`] or [`ImageLayout::General`].
+    FragmentDensityMapAttachmentLayoutInvalid { attachment: u32 },
+
+    /// The fragment density map attachment has a `load_op` of [`LoadOp::Clear`], or a `store_op`
+    /// other than [`StoreOp::DontCare`].
+    FragmentDensityMapAttachmentLoadStoreOpInvalid { attachment: u32 },
+
+    /// The fragment density map attachment index is not less than the number of attachments in
+    /// the render pass.
+    FragmentDensityMapAttachmentOutOfRange { attachment: u32 },
+
+    /// The fragment density map attachment is also used as an attachment or preserve attachment
+    /// in a subpass.
+    FragmentDensityMapAttachmentUsedInSubpass { attachment: u32, subpass: u32 },
+
     /// A reference to an attachment used other than as an input attachment in a subpass has
     /// one or more aspects selected.
     SubpassAttachmentAspectsNotEmpty { subpass: u32, attachment: u32 },
@@ -1532,6 +1699,11 @@ impl fmt::Display for RenderPassCreationError {
                 "attachment {} has an `initial_layout` or `final_layout` value that is invalid for the provided `format`",
                 attachment,
             ),
+            Self::AttachmentNameNotUnique { attachment } => write!(
+                fmt,
+                "attachment {} has the same `name` as an earlier attachment",
+                attachment,
+            ),
             Self::CorrelatedViewMasksMultiviewNotEnabled => write!(
                 fmt,
                 "correlated view masks were included, but multiview is not enabled on the render pass",
@@ -1598,6 +1770,36 @@ impl fmt::Display for RenderPassCreationError {
                 "subpass dependency {} has the `view_local` dependency enabled, but multiview is not enabled on the render pass",
                 dependency,
             ),
+            Self::FragmentDensityMapAttachmentAspectsNotEmpty { attachment } => write!(
+                fmt,
+                "the fragment density map attachment {} has one or more aspects selected",
+                attachment,
+            ),
+            Self::FragmentDensityMapAttachmentFormatNotSupported { attachment } => write!(
+                fmt,
+                "the fragment density map attachment {} has a format that does not support being used as a fragment density map",
+                attachment,
+            ),
+            Self::FragmentDensityMapAttachmentLayoutInvalid { attachment } => write!(
+                fmt,
+                "the fragment density map attachment {} has a layout other than `ImageLayout::FragmentDensityMapOptimal` or `ImageLayout::General`",
+                attachment,
+            ),
+            Self::FragmentDensityMapAttachmentLoadStoreOpInvalid { attachment } => write!(
+                fmt,
+                "the fragment density map attachment {} has a `load_op` of `LoadOp::Clear`, or a `store_op` other than `StoreOp::DontCare`",
+                attachment,
+            ),
+            Self::FragmentDensityMapAttachmentOutOfRange { attachment } => write!(
+                fmt,
+                "the fragment density map attachment index {} is not less than the number of attachments in the render pass",
+                attachment,
+            ),
+            Self::FragmentDensityMapAttachmentUsedInSubpass { attachment, subpass } => write!(
+                fmt,
+                "the fragment density map attachment {} is also used in subpass {}",
+                attachment, subpass,
+            ),
             Self::SubpassAttachmentAspectsNotEmpty { subpass, attachment } => write!(
                 fmt,
                 "a reference to attachment {} used other than as an input attachment in subpass {} has one or more aspects selected",
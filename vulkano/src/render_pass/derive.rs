@@ -0,0 +1,429 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use super::{AttachmentReference, RenderPassCreateInfo, SubpassDependency};
+use crate::{
+    image::ImageLayout,
+    sync::{AccessFlags, PipelineStages},
+};
+use std::fmt;
+
+impl RenderPassCreateInfo {
+    /// Computes the dependencies between subpasses that are needed because of the way the
+    /// subpasses use the attachments, and appends them to `dependencies`.
+    ///
+    /// A dependency is generated from a subpass to a later subpass if the later subpass reads an
+    /// attachment that the earlier one wrote, writes an attachment that the earlier one read or
+    /// wrote, or uses an attachment in a different layout. Its stages and accesses only cover
+    /// the ways in which the two subpasses use these attachments. All generated dependencies are
+    /// `by_region`, as attachments are only accessed in framebuffer space.
+    ///
+    /// Dependencies on commands outside the render pass are not generated, so the implicit
+    /// external dependencies of Vulkan apply unless you add your own.
+    ///
+    /// Returns a description of every generated dependency and of the reasons for it, in the
+    /// order in which they were appended.
+    pub fn derive_dependencies(&mut self) -> Vec<DerivedDependency> {
+        let mut derived: Vec<DerivedDependency> = Vec::new();
+        let mut states = vec![AttachmentState::default(); self.attachments.len()];
+
+        for (subpass, description) in (0..).zip(&self.subpasses) {
+            let uses = subpass_uses(
+                &description.input_attachments,
+                &description.color_attachments,
+                &description.resolve_attachments,
+                description.depth_stencil_attachment.as_ref(),
+            );
+
+            for (attachment, attachment_use, layout) in uses {
+                let state = match states.get_mut(attachment as usize) {
+                    Some(state) => state,
+                    None => continue,
+                };
+
+                let layout_transition = matches!(
+                    state.previous_layout,
+                    Some(previous_layout) if previous_layout != layout
+                );
+                let writes = attachment_use.writes() || layout_transition;
+
+                let mut sources: Vec<_> = state.last_write.into_iter().collect();
+
+                if writes {
+                    sources.extend(state.reads.iter().copied());
+                }
+
+                for (source_subpass, source_use, source_layout, source_writes) in sources {
+                    if source_subpass == subpass {
+                        continue;
+                    }
+
+                    let reason = DependencyReason {
+                        attachment,
+                        source_use,
+                        destination_use: attachment_use,
+                        layout_transition: source_layout != layout,
+                    };
+                    let source_access = if source_writes {
+                        source_use.write_access()
+                    } else {
+                        AccessFlags::none()
+                    };
+
+                    match derived.iter_mut().find(|d| {
+                        d.dependency.source_subpass == Some(source_subpass)
+                            && d.dependency.destination_subpass == Some(subpass)
+                    }) {
+                        Some(d) => {
+                            d.dependency.source_stages |= source_use.stages();
+                            d.dependency.destination_stages |= attachment_use.stages();
+                            d.dependency.source_access |= source_access;
+                            d.dependency.destination_access |= attachment_use.access();
+
+                            if !d.reasons.contains(&reason) {
+                                d.reasons.push(reason);
+                            }
+                        }
+                        None => derived.push(DerivedDependency {
+                            dependency: SubpassDependency {
+                                source_subpass: Some(source_subpass),
+                                destination_subpass: Some(subpass),
+                                source_stages: source_use.stages(),
+                                destination_stages: attachment_use.stages(),
+                                source_access,
+                                destination_access: attachment_use.access(),
+                                by_region: true,
+                                ..Default::default()
+                            },
+                            reasons: vec![reason],
+                        }),
+                    }
+                }
+
+                let access = (subpass, attachment_use, layout, attachment_use.writes());
+
+                if writes {
+                    state.last_write = Some(access);
+                    state.reads.clear();
+                } else {
+                    state.reads.push(access);
+                }
+
+                state.previous_layout = Some(layout);
+            }
+        }
+
+        self.dependencies
+            .extend(derived.iter().map(|d| d.dependency.clone()));
+
+        derived
+    }
+}
+
+// Returns the attachments used by a subpass, in the order in which they are accessed.
+fn subpass_uses(
+    input_attachments: &[Option<AttachmentReference>],
+    color_attachments: &[Option<AttachmentReference>],
+    resolve_attachments: &[Option<AttachmentReference>],
+    depth_stencil_attachment: Option<&AttachmentReference>,
+) -> Vec<(u32, AttachmentUse, ImageLayout)> {
+    let mut uses = Vec::new();
+    let mut push = |reference: &AttachmentReference, attachment_use| {
+        uses.push((reference.attachment, attachment_use, reference.layout));
+    };
+
+    if let Some(reference) = depth_stencil_attachment {
+        let attachment_use = if reference.layout == ImageLayout::DepthStencilReadOnlyOptimal {
+            AttachmentUse::DepthStencilReadOnly
+        } else {
+            AttachmentUse::DepthStencil
+        };
+        push(reference, attachment_use);
+    }
+
+    for reference in input_attachments.iter().flatten() {
+        push(reference, AttachmentUse::Input);
+    }
+
+    for reference in color_attachments.iter().flatten() {
+        push(reference, AttachmentUse::Color);
+    }
+
+    for reference in resolve_attachments.iter().flatten() {
+        push(reference, AttachmentUse::Resolve);
+    }
+
+    uses
+}
+
+// Subpass index, use, layout, and whether the use writes to the attachment.
+type Access = (u32, AttachmentUse, ImageLayout, bool);
+
+#[derive(Clone, Default)]
+struct AttachmentState {
+    // The last subpass that wrote to the attachment or transitioned its layout.
+    last_write: Option<Access>,
+    // The subpasses that read the attachment since `last_write`.
+    reads: Vec<Access>,
+    // The layout of the attachment in the last subpass that used it.
+    previous_layout: Option<ImageLayout>,
+}
+
+/// A subpass dependency generated by
+/// [`RenderPassCreateInfo::derive_dependencies`], together with the reasons for generating it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivedDependency {
+    /// The dependency that was appended to `dependencies`.
+    pub dependency: SubpassDependency,
+
+    /// The attachment accesses that required the dependency.
+    pub reasons: Vec<DependencyReason>,
+}
+
+impl fmt::Display for DerivedDependency {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "subpass {} depends on subpass {}:",
+            self.dependency.destination_subpass.unwrap(),
+            self.dependency.source_subpass.unwrap(),
+        )?;
+
+        for reason in &self.reasons {
+            write!(fmt, " {};", reason)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a dependency was generated by [`RenderPassCreateInfo::derive_dependencies`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DependencyReason {
+    /// The attachment that is accessed by both subpasses.
+    pub attachment: u32,
+
+    /// How the source subpass uses the attachment.
+    pub source_use: AttachmentUse,
+
+    /// How the destination subpass uses the attachment.
+    pub destination_use: AttachmentUse,
+
+    /// Whether the attachment has a different layout in the two subpasses.
+    pub layout_transition: bool,
+}
+
+impl fmt::Display for DependencyReason {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "attachment {} is used as {} after being used as {}",
+            self.attachment, self.destination_use, self.source_use,
+        )?;
+
+        if self.layout_transition {
+            write!(fmt, ", with a layout transition")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The ways in which a subpass can use an attachment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AttachmentUse {
+    /// The attachment is an input attachment.
+    Input,
+
+    /// The attachment is a color attachment.
+    Color,
+
+    /// The attachment is a resolve attachment.
+    Resolve,
+
+    /// The attachment is a depth/stencil attachment.
+    DepthStencil,
+
+    /// The attachment is a depth/stencil attachment in the
+    /// [`ImageLayout::DepthStencilReadOnlyOptimal`] layout.
+    DepthStencilReadOnly,
+}
+
+impl AttachmentUse {
+    fn stages(self) -> PipelineStages {
+        match self {
+            AttachmentUse::Input => PipelineStages {
+                fragment_shader: true,
+                ..PipelineStages::none()
+            },
+            AttachmentUse::Color | AttachmentUse::Resolve => PipelineStages {
+                color_attachment_output: true,
+                ..PipelineStages::none()
+            },
+            AttachmentUse::DepthStencil | AttachmentUse::DepthStencilReadOnly => PipelineStages {
+                early_fragment_tests: true,
+                late_fragment_tests: true,
+                ..PipelineStages::none()
+            },
+        }
+    }
+
+    fn access(self) -> AccessFlags {
+        match self {
+            AttachmentUse::Input => AccessFlags {
+                input_attachment_read: true,
+                ..AccessFlags::none()
+            },
+            AttachmentUse::Color => AccessFlags {
+                color_attachment_read: true,
+                color_attachment_write: true,
+                ..AccessFlags::none()
+            },
+            AttachmentUse::Resolve => AccessFlags {
+                color_attachment_write: true,
+                ..AccessFlags::none()
+            },
+            AttachmentUse::DepthStencil => AccessFlags {
+                depth_stencil_attachment_read: true,
+                depth_stencil_attachment_write: true,
+                ..AccessFlags::none()
+            },
+            AttachmentUse::DepthStencilReadOnly => AccessFlags {
+                depth_stencil_attachment_read: true,
+                ..AccessFlags::none()
+            },
+        }
+    }
+
+    fn write_access(self) -> AccessFlags {
+        match self {
+            AttachmentUse::Color | AttachmentUse::Resolve => AccessFlags {
+                color_attachment_write: true,
+                ..AccessFlags::none()
+            },
+            AttachmentUse::DepthStencil => AccessFlags {
+                depth_stencil_attachment_write: true,
+                ..AccessFlags::none()
+            },
+            AttachmentUse::Input | AttachmentUse::DepthStencilReadOnly => AccessFlags::none(),
+        }
+    }
+
+    fn writes(self) -> bool {
+        self.write_access() != AccessFlags::none()
+    }
+}
+
+impl fmt::Display for AttachmentUse {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "{}",
+            match self {
+                AttachmentUse::Input => "an input attachment",
+                AttachmentUse::Color => "a color attachment",
+                AttachmentUse::Resolve => "a resolve attachment",
+                AttachmentUse::DepthStencil => "a depth/stencil attachment",
+                AttachmentUse::DepthStencilReadOnly => "a read-only depth/stencil attachment",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AttachmentUse, DependencyReason};
+    use crate::{
+        format::Format,
+        image::{ImageLayout, SampleCount},
+        render_pass::{
+            AttachmentDescription, AttachmentReference, RenderPassCreateInfo, SubpassDescription,
+        },
+    };
+
+    #[test]
+    fn deferred_lighting() {
+        let attachment = |format| AttachmentDescription {
+            format: Some(format),
+            samples: SampleCount::Sample1,
+            ..Default::default()
+        };
+        let reference = |attachment, layout| {
+            Some(AttachmentReference {
+                attachment,
+                layout,
+                ..Default::default()
+            })
+        };
+
+        // Subpass 0 writes the albedo and depth, subpass 1 reads them and writes the output.
+        let mut create_info = RenderPassCreateInfo {
+            attachments: vec![
+                attachment(Format::R8G8B8A8_UNORM),
+                attachment(Format::D32_SFLOAT),
+                attachment(Format::R8G8B8A8_UNORM),
+            ],
+            subpasses: vec![
+                SubpassDescription {
+                    color_attachments: vec![reference(0, ImageLayout::ColorAttachmentOptimal)],
+                    depth_stencil_attachment: reference(
+                        1,
+                        ImageLayout::DepthStencilAttachmentOptimal,
+                    ),
+                    ..Default::default()
+                },
+                SubpassDescription {
+                    input_attachments: vec![
+                        reference(0, ImageLayout::ShaderReadOnlyOptimal),
+                        reference(1, ImageLayout::DepthStencilReadOnlyOptimal),
+                    ],
+                    color_attachments: vec![reference(2, ImageLayout::ColorAttachmentOptimal)],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let derived = create_info.derive_dependencies();
+        assert_eq!(derived.len(), 1);
+        assert_eq!(
+            create_info.dependencies,
+            vec![derived[0].dependency.clone()]
+        );
+
+        let dependency = &derived[0].dependency;
+        assert_eq!(dependency.source_subpass, Some(0));
+        assert_eq!(dependency.destination_subpass, Some(1));
+        assert!(dependency.by_region);
+        assert!(dependency.source_stages.color_attachment_output);
+        assert!(dependency.source_stages.late_fragment_tests);
+        assert!(!dependency.source_stages.fragment_shader);
+        assert!(dependency.source_access.color_attachment_write);
+        assert!(dependency.source_access.depth_stencil_attachment_write);
+        assert!(!dependency.source_access.color_attachment_read);
+        assert!(dependency.destination_stages.fragment_shader);
+        assert!(dependency.destination_access.input_attachment_read);
+        assert!(!dependency.destination_access.color_attachment_write);
+
+        assert_eq!(
+            derived[0].reasons[0],
+            DependencyReason {
+                attachment: 0,
+                source_use: AttachmentUse::Color,
+                destination_use: AttachmentUse::Input,
+                layout_transition: true,
+            }
+        );
+        assert_eq!(derived[0].reasons.len(), 2);
+    }
+}
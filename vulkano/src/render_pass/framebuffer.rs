@@ -114,6 +114,11 @@ impl Framebuffer {
             }
         }
 
+        let fragment_density_map_attachment = render_pass
+            .fragment_density_map_attachment()
+            .map(|atch_ref| atch_ref.attachment);
+        let mut fragment_density_map_extent = None;
+
         let attachments_vk = attachments
             .iter()
             .zip(render_pass.attachments())
@@ -122,6 +127,17 @@ impl Framebuffer {
                 let attachment_num = attachment_num as u32;
                 assert_eq!(device, image_view.device());
 
+                let is_fragment_density_map =
+                    fragment_density_map_attachment == Some(attachment_num);
+
+                // VUID-VkFramebufferCreateInfo-pAttachments-02552
+                if is_fragment_density_map && !image_view.usage().fragment_density_map {
+                    return Err(FramebufferCreationError::AttachmentMissingUsage {
+                        attachment: attachment_num,
+                        usage: "fragment_density_map",
+                    });
+                }
+
                 for subpass in render_pass.subpasses() {
                     // VUID-VkFramebufferCreateInfo-pAttachments-00877
                     if subpass
@@ -199,28 +215,34 @@ impl Framebuffer {
                     );
                 }
 
-                // VUID-VkFramebufferCreateInfo-flags-04533
-                // VUID-VkFramebufferCreateInfo-flags-04534
-                if auto_extent {
-                    extent[0] = extent[0].min(image_view_extent[0]);
-                    extent[1] = extent[1].min(image_view_extent[1]);
-                } else if image_view_extent[0] < extent[0] || image_view_extent[1] < extent[1] {
-                    return Err(FramebufferCreationError::AttachmentExtentTooSmall {
-                        attachment: attachment_num,
-                        provided: image_view_extent,
-                        min: extent,
-                    });
-                }
+                if is_fragment_density_map {
+                    // The fragment density map attachment doesn't need to match the framebuffer
+                    // extent, it is checked separately once the extent is known.
+                    fragment_density_map_extent = Some((attachment_num, image_view_extent));
+                } else {
+                    // VUID-VkFramebufferCreateInfo-flags-04533
+                    // VUID-VkFramebufferCreateInfo-flags-04534
+                    if auto_extent {
+                        extent[0] = extent[0].min(image_view_extent[0]);
+                        extent[1] = extent[1].min(image_view_extent[1]);
+                    } else if image_view_extent[0] < extent[0] || image_view_extent[1] < extent[1] {
+                        return Err(FramebufferCreationError::AttachmentExtentTooSmall {
+                            attachment: attachment_num,
+                            provided: image_view_extent,
+                            min: extent,
+                        });
+                    }
 
-                // VUID-VkFramebufferCreateInfo-flags-04535
-                if auto_layers {
-                    layers = layers.min(image_view_array_layers);
-                } else if image_view_array_layers < layers {
-                    return Err(FramebufferCreationError::AttachmentNotEnoughLayers {
-                        attachment: attachment_num,
-                        provided: image_view_array_layers,
-                        min: layers,
-                    });
+                    // VUID-VkFramebufferCreateInfo-flags-04535
+                    if auto_layers {
+                        layers = layers.min(image_view_array_layers);
+                    } else if image_view_array_layers < layers {
+                        return Err(FramebufferCreationError::AttachmentNotEnoughLayers {
+                            attachment: attachment_num,
+                            provided: image_view_array_layers,
+                            min: layers,
+                        });
+                    }
                 }
 
                 // VUID-VkFramebufferCreateInfo-pAttachments-00883
@@ -293,6 +315,24 @@ impl Framebuffer {
                     max: properties.max_framebuffer_layers,
                 });
             }
+
+            // VUID-VkFramebufferCreateInfo-pAttachments-02555
+            // VUID-VkFramebufferCreateInfo-pAttachments-02556
+            if let Some((attachment, provided)) = fragment_density_map_extent {
+                let texel_size = properties.max_fragment_density_texel_size.unwrap_or([1, 1]);
+                let min = [
+                    (extent[0] / texel_size[0]) + (extent[0] % texel_size[0] != 0) as u32,
+                    (extent[1] / texel_size[1]) + (extent[1] % texel_size[1] != 0) as u32,
+                ];
+
+                if provided[0] < min[0] || provided[1] < min[1] {
+                    return Err(FramebufferCreationError::AttachmentExtentTooSmall {
+                        attachment,
+                        provided,
+                        min,
+                    });
+                }
+            }
         }
 
         let create_info = ash::vk::FramebufferCreateInfo {
@@ -191,6 +191,7 @@ macro_rules! ordered_passes_renderpass {
                         store_op: $crate::render_pass::StoreOp::$store,
                         stencil_load_op: $crate::render_pass::LoadOp::$load,
                         stencil_store_op: $crate::render_pass::StoreOp::$store,
+                        name: Some(stringify!($atch_name)),
                         initial_layout: layout.0.expect(
                             format!(
                                 "Attachment {} is missing initial_layout, this is normally \
@@ -256,4 +257,34 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn attachment_names() {
+        let (device, _) = gfx_dev_and_queue!();
+        let render_pass = single_pass_renderpass!(device.clone(),
+            attachments: {
+                color: {
+                    load: Clear,
+                    store: Store,
+                    format: Format::R8G8B8A8_UNORM,
+                    samples: 1,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: Format::D16_UNORM,
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [color],
+                depth_stencil: {depth}
+            }
+        )
+        .unwrap();
+
+        assert_eq!(render_pass.attachment_index("color"), Some(0));
+        assert_eq!(render_pass.attachment_index("depth"), Some(1));
+        assert_eq!(render_pass.attachment_index("normals"), None);
+    }
 }
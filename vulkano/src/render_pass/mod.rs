@@ -25,7 +25,9 @@
 //! Consequently you can create graphics pipelines from a render pass object alone.
 //! A `Framebuffer` object is only needed when you actually add draw commands to a command buffer.
 
+pub use self::cache::RenderPassCache;
 pub use self::create::RenderPassCreationError;
+pub use self::derive::{AttachmentUse, DependencyReason, DerivedDependency};
 pub use self::framebuffer::Framebuffer;
 pub use self::framebuffer::FramebufferCreateInfo;
 pub use self::framebuffer::FramebufferCreationError;
@@ -47,7 +49,9 @@ use std::{
 
 #[macro_use]
 mod macros;
+mod cache;
 mod create;
+mod derive;
 mod framebuffer;
 
 /// An object representing the discrete steps in which rendering is done.
@@ -112,6 +116,7 @@ pub struct RenderPass {
     subpasses: Vec<SubpassDescription>,
     dependencies: Vec<SubpassDependency>,
     correlated_view_masks: Vec<u32>,
+    fragment_density_map_attachment: Option<AttachmentReference>,
 
     granularity: [u32; 2],
     views_used: u32,
@@ -145,6 +150,7 @@ impl RenderPass {
             subpasses,
             dependencies,
             correlated_view_masks,
+            fragment_density_map_attachment,
             _ne: _,
         } = create_info;
 
@@ -171,6 +177,7 @@ impl RenderPass {
             subpasses,
             dependencies,
             correlated_view_masks,
+            fragment_density_map_attachment,
 
             granularity,
             views_used,
@@ -199,6 +206,15 @@ impl RenderPass {
         &self.attachments
     }
 
+    /// Returns the index of the attachment whose `name` is `name`, if any.
+    #[inline]
+    pub fn attachment_index(&self, name: &str) -> Option<u32> {
+        self.attachments
+            .iter()
+            .position(|attachment| attachment.name == Some(name))
+            .map(|index| index as u32)
+    }
+
     /// Returns the subpasses of the render pass.
     #[inline]
     pub fn subpasses(&self) -> &[SubpassDescription] {
@@ -217,6 +233,12 @@ impl RenderPass {
         &self.correlated_view_masks
     }
 
+    /// Returns the fragment density map attachment of the render pass, if any.
+    #[inline]
+    pub fn fragment_density_map_attachment(&self) -> Option<&AttachmentReference> {
+        self.fragment_density_map_attachment.as_ref()
+    }
+
     /// If the render pass has multiview enabled, returns the number of views used by the render
     /// pass. Returns 0 if multiview is not enabled.
     #[inline]
@@ -257,6 +279,7 @@ impl RenderPass {
             subpasses: subpasses1,
             dependencies: dependencies1,
             correlated_view_masks: correlated_view_masks1,
+            fragment_density_map_attachment: fragment_density_map_attachment1,
             granularity: _,
             views_used: _,
         } = self;
@@ -267,6 +290,7 @@ impl RenderPass {
             subpasses: subpasses2,
             dependencies: dependencies2,
             correlated_view_masks: correlated_view_masks2,
+            fragment_density_map_attachment: fragment_density_map_attachment2,
             granularity: _,
             views_used: _,
         } = other;
@@ -288,6 +312,7 @@ impl RenderPass {
                     stencil_store_op: _,
                     initial_layout: _,
                     final_layout: _,
+                    name: _,
                     _ne: _,
                 } = attachment_desc1;
                 let AttachmentDescription {
@@ -299,6 +324,7 @@ impl RenderPass {
                     stencil_store_op: _,
                     initial_layout: _,
                     final_layout: _,
+                    name: _,
                     _ne: _,
                 } = attachment_desc2;
 
@@ -326,6 +352,7 @@ impl RenderPass {
                     stencil_store_op: _,
                     initial_layout: _,
                     final_layout: _,
+                    name: _,
                     _ne: _,
                 } = &attachments1[attachment1 as usize];
 
@@ -344,6 +371,7 @@ impl RenderPass {
                     stencil_store_op: _,
                     initial_layout: _,
                     final_layout: _,
+                    name: _,
                     _ne: _,
                 } = &attachments2[attachment2 as usize];
 
@@ -432,6 +460,13 @@ impl RenderPass {
             return false;
         }
 
+        if !are_atch_refs_compatible(
+            fragment_density_map_attachment1.as_ref(),
+            fragment_density_map_attachment2.as_ref(),
+        ) {
+            return false;
+        }
+
         true
     }
 
@@ -693,7 +728,7 @@ impl From<Subpass> for (Arc<RenderPass>, u32) {
 }
 
 /// Parameters to create a new `RenderPass`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RenderPassCreateInfo {
     /// The attachments available for the render pass.
     ///
@@ -726,6 +761,21 @@ pub struct RenderPassCreateInfo {
     /// The default value is empty.
     pub correlated_view_masks: Vec<u32>,
 
+    /// An attachment that is read during the render pass to determine the size of the fragments
+    /// that are rendered in each region of the framebuffer.
+    ///
+    /// The attachment must not be used by any subpass. Its `layout` must be
+    /// [`ImageLayout::FragmentDensityMapOptimal`] or [`ImageLayout::General`], and the attachment
+    /// must have a `load_op` other than [`LoadOp::Clear`] and a `store_op` of
+    /// [`StoreOp::DontCare`].
+    ///
+    /// If set to `Some`, the
+    /// [`ext_fragment_density_map`](crate::device::DeviceExtensions::ext_fragment_density_map)
+    /// extension must be enabled on the device.
+    ///
+    /// The default value is `None`.
+    pub fragment_density_map_attachment: Option<AttachmentReference>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -737,13 +787,14 @@ impl Default for RenderPassCreateInfo {
             subpasses: Vec::new(),
             dependencies: Vec::new(),
             correlated_view_masks: Vec::new(),
+            fragment_density_map_attachment: None,
             _ne: crate::NonExhaustive(()),
         }
     }
 }
 
 /// Describes an attachment that will be used in a render pass.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct AttachmentDescription {
     /// The format of the image that is going to be bound.
     ///
@@ -792,6 +843,15 @@ pub struct AttachmentDescription {
     /// The default value is [`ImageLayout::Undefined`], which must be overridden.
     pub final_layout: ImageLayout,
 
+    /// A name to identify the attachment by, for example when providing clear values with
+    /// [`RenderPassBeginInfo::set_clear_value`](crate::command_buffer::RenderPassBeginInfo::set_clear_value).
+    /// If set, it must be unique among the attachments of the render pass.
+    ///
+    /// The render pass macros set this to the name that the attachment is declared with.
+    ///
+    /// The default value is `None`.
+    pub name: Option<&'static str>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -807,6 +867,7 @@ impl Default for AttachmentDescription {
             stencil_store_op: StoreOp::DontCare,
             initial_layout: ImageLayout::Undefined,
             final_layout: ImageLayout::Undefined,
+            name: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -824,7 +885,7 @@ impl Default for AttachmentDescription {
 /// If an attachment is used more than once, i.e. a given `AttachmentReference::attachment` occurs
 /// more than once in the `SubpassDescription`, then their `AttachmentReference::layout` must be
 /// the same as well.
-#[derive(Debug, Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SubpassDescription {
     /// If not `0`, enables multiview rendering, and specifies the view indices that are rendered to
     /// in this subpass. The value is a bitmask, so that that for example `0b11` will draw to the
@@ -901,7 +962,7 @@ impl Default for SubpassDescription {
 }
 
 /// A reference in a subpass description to a particular attachment of the render pass.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct AttachmentReference {
     /// The number of the attachment being referred to.
     ///
@@ -968,7 +1029,7 @@ impl Default for AttachmentReference {
 /// the render pass instance began (for `source_subpass`), or on commands that will be submitted
 /// after the render pass instance ends (for `destination_subpass`). The values must not both be
 /// `None`.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SubpassDependency {
     /// The index of the subpass that writes the data that `destination_subpass` is going to use.
     ///
@@ -1191,8 +1252,15 @@ impl From<ash::vk::ResolveModeFlags> for ResolveModes {
 #[cfg(test)]
 mod tests {
     use crate::format::Format;
+    use crate::image::ImageLayout;
+    use crate::render_pass::AttachmentDescription;
+    use crate::render_pass::AttachmentReference;
+    use crate::render_pass::LoadOp;
     use crate::render_pass::RenderPass;
+    use crate::render_pass::RenderPassCreateInfo;
     use crate::render_pass::RenderPassCreationError;
+    use crate::render_pass::StoreOp;
+    use crate::render_pass::SubpassDescription;
 
     #[test]
     fn empty() {
@@ -1254,4 +1322,38 @@ mod tests {
         assert_ne!(granularity[0], 0);
         assert_ne!(granularity[1], 0);
     }
+
+    #[test]
+    fn fragment_density_map_extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let rp = RenderPass::new(
+            device,
+            RenderPassCreateInfo {
+                attachments: vec![AttachmentDescription {
+                    format: Some(Format::R8G8_UNORM),
+                    load_op: LoadOp::DontCare,
+                    store_op: StoreOp::DontCare,
+                    initial_layout: ImageLayout::FragmentDensityMapOptimal,
+                    final_layout: ImageLayout::FragmentDensityMapOptimal,
+                    ..Default::default()
+                }],
+                subpasses: vec![SubpassDescription::default()],
+                fragment_density_map_attachment: Some(AttachmentReference {
+                    attachment: 0,
+                    layout: ImageLayout::FragmentDensityMapOptimal,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        );
+
+        match rp {
+            Err(RenderPassCreationError::ExtensionNotEnabled {
+                extension: "ext_fragment_density_map",
+                ..
+            }) => (),
+            _ => panic!(),
+        }
+    }
 }
@@ -115,6 +115,9 @@ pub struct Sampler {
 impl Sampler {
     /// Creates a new `Sampler`.
     ///
+    /// Samplers are deduplicated: if a sampler that was created with an identical `create_info`
+    /// on the same device is still alive, it is returned instead of creating a new one.
+    ///
     /// # Panics
     ///
     /// - Panics if `create_info.anisotropy` is `Some` and contains a value less than 1.0.
@@ -122,6 +125,17 @@ impl Sampler {
     pub fn new(
         device: Arc<Device>,
         create_info: SamplerCreateInfo,
+    ) -> Result<Arc<Sampler>, SamplerCreationError> {
+        device
+            .sampler_cache()
+            .get_or_try_insert(SamplerCacheKey::new(&create_info), || {
+                Self::new_uncached(device.clone(), create_info)
+            })
+    }
+
+    fn new_uncached(
+        device: Arc<Device>,
+        create_info: SamplerCreateInfo,
     ) -> Result<Arc<Sampler>, SamplerCreationError> {
         let SamplerCreateInfo {
             mag_filter,
@@ -299,14 +313,19 @@ impl Sampler {
         {
             assert_eq!(&device, sampler_ycbcr_conversion.device());
 
-            let potential_format_features = device
-                .physical_device()
-                .format_properties(sampler_ycbcr_conversion.format().unwrap())
-                .potential_format_features();
+            // For an external format, the format features are only known from the properties of
+            // the external memory that the image was imported from.
+            let separate_reconstruction_filter =
+                sampler_ycbcr_conversion.format().map_or(true, |format| {
+                    device
+                        .physical_device()
+                        .format_properties(format)
+                        .potential_format_features()
+                        .sampled_image_ycbcr_conversion_separate_reconstruction_filter
+                });
 
             // VUID-VkSamplerCreateInfo-minFilter-01645
-            if !potential_format_features
-                .sampled_image_ycbcr_conversion_separate_reconstruction_filter
+            if !separate_reconstruction_filter
                 && !(mag_filter == sampler_ycbcr_conversion.chroma_filter()
                     && min_filter == sampler_ycbcr_conversion.chroma_filter())
             {
@@ -402,6 +421,8 @@ impl Sampler {
             output.assume_init()
         };
 
+        device.statistics_counters().record_sampler_created();
+
         Ok(Arc::new(Sampler {
             handle,
             device,
@@ -1011,6 +1032,63 @@ pub struct SamplerCreateInfo {
     pub _ne: crate::NonExhaustive,
 }
 
+// The parameters of a sampler that determine whether it can be shared, with floats compared by
+// their bits.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct SamplerCacheKey {
+    mag_filter: Filter,
+    min_filter: Filter,
+    mipmap_mode: SamplerMipmapMode,
+    address_mode: [SamplerAddressMode; 3],
+    mip_lod_bias: u32,
+    anisotropy: Option<u32>,
+    compare: Option<CompareOp>,
+    lod: [u32; 2],
+    border_color: BorderColor,
+    unnormalized_coordinates: bool,
+    reduction_mode: SamplerReductionMode,
+    sampler_ycbcr_conversion: Option<ash::vk::SamplerYcbcrConversion>,
+}
+
+impl SamplerCacheKey {
+    fn new(create_info: &SamplerCreateInfo) -> Self {
+        let &SamplerCreateInfo {
+            mag_filter,
+            min_filter,
+            mipmap_mode,
+            address_mode,
+            mip_lod_bias,
+            anisotropy,
+            compare,
+            ref lod,
+            border_color,
+            unnormalized_coordinates,
+            reduction_mode,
+            ref sampler_ycbcr_conversion,
+            _ne: _,
+        } = create_info;
+
+        SamplerCacheKey {
+            mag_filter,
+            min_filter,
+            mipmap_mode,
+            address_mode,
+            mip_lod_bias: mip_lod_bias.to_bits(),
+            anisotropy: anisotropy.map(f32::to_bits),
+            compare,
+            lod: [lod.start().to_bits(), lod.end().to_bits()],
+            border_color,
+            unnormalized_coordinates,
+            reduction_mode,
+            // The conversion is kept alive by the cached sampler, so its handle can't be reused
+            // while the sampler is alive.
+            sampler_ycbcr_conversion: sampler_ycbcr_conversion
+                .as_ref()
+                .map(|conversion| conversion.internal_object()),
+        }
+    }
+}
+
 impl Default for SamplerCreateInfo {
     fn default() -> Self {
         Self {
@@ -1058,6 +1136,65 @@ impl SamplerCreateInfo {
             ..Default::default()
         }
     }
+
+    /// Shortcut for creating a sampler with linear sampling, linear mipmaps, and with the
+    /// clamp-to-edge mode for borders.
+    ///
+    /// This is the usual choice for textures that must not wrap around, such as UI elements,
+    /// sprites from an atlas or the result of a previous render pass. Using `Repeat` for these
+    /// causes the opposite edge to bleed into the border texels when filtering.
+    #[inline]
+    pub fn clamp_linear_mip() -> Self {
+        Self {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap_mode: SamplerMipmapMode::Linear,
+            address_mode: [SamplerAddressMode::ClampToEdge; 3],
+            lod: 0.0..=LOD_CLAMP_NONE,
+            ..Default::default()
+        }
+    }
+
+    /// Shortcut for creating a sampler with nearest sampling, that only uses the main level of
+    /// images, and with the clamp-to-edge mode for borders.
+    ///
+    /// This is suited for reading back intermediate render targets texel-for-texel, and for
+    /// pixel art that must not be smoothed when magnified.
+    #[inline]
+    pub fn clamp_nearest() -> Self {
+        Self {
+            mag_filter: Filter::Nearest,
+            min_filter: Filter::Nearest,
+            address_mode: [SamplerAddressMode::ClampToEdge; 3],
+            lod: 0.0..=0.0,
+            ..Default::default()
+        }
+    }
+
+    /// Shortcut for creating a depth comparison sampler for sampling shadow maps.
+    ///
+    /// The sampler performs a `LessOrEqual` comparison against the reference value and uses
+    /// linear filtering, which gives hardware percentage-closer filtering on most
+    /// implementations. Coordinates outside the shadow map resolve to a depth of 1.0, so that
+    /// fragments outside the light's frustum are considered lit.
+    ///
+    /// If the shadow map is rendered with a reversed depth range (see
+    /// [`DepthStencilState::reverse_z`]), set `compare` to
+    /// [`GreaterOrEqual`](CompareOp::GreaterOrEqual) and `border_color` to
+    /// [`FloatOpaqueBlack`](BorderColor::FloatOpaqueBlack) instead.
+    ///
+    /// [`DepthStencilState::reverse_z`]: crate::pipeline::graphics::depth_stencil::DepthStencilState::reverse_z
+    #[inline]
+    pub fn shadow_compare() -> Self {
+        Self {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            address_mode: [SamplerAddressMode::ClampToBorder; 3],
+            compare: Some(CompareOp::LessOrEqual),
+            border_color: BorderColor::FloatOpaqueWhite,
+            ..Default::default()
+        }
+    }
 }
 
 /// A special value to indicate that the maximum LOD should not be clamped.
@@ -1175,6 +1312,20 @@ impl From<ComponentMapping> for ash::vk::ComponentMapping {
     }
 }
 
+impl TryFrom<ash::vk::ComponentMapping> for ComponentMapping {
+    type Error = ();
+
+    #[inline]
+    fn try_from(value: ash::vk::ComponentMapping) -> Result<Self, Self::Error> {
+        Ok(Self {
+            r: value.r.try_into()?,
+            g: value.g.try_into()?,
+            b: value.b.try_into()?,
+            a: value.a.try_into()?,
+        })
+    }
+}
+
 /// Describes the value that an individual component must return when being accessed.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(i32)]
@@ -1204,6 +1355,24 @@ impl From<ComponentSwizzle> for ash::vk::ComponentSwizzle {
     }
 }
 
+impl TryFrom<ash::vk::ComponentSwizzle> for ComponentSwizzle {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::ComponentSwizzle) -> Result<Self, Self::Error> {
+        Ok(match val {
+            ash::vk::ComponentSwizzle::IDENTITY => Self::Identity,
+            ash::vk::ComponentSwizzle::ZERO => Self::Zero,
+            ash::vk::ComponentSwizzle::ONE => Self::One,
+            ash::vk::ComponentSwizzle::R => Self::Red,
+            ash::vk::ComponentSwizzle::G => Self::Green,
+            ash::vk::ComponentSwizzle::B => Self::Blue,
+            ash::vk::ComponentSwizzle::A => Self::Alpha,
+            _ => return Err(()),
+        })
+    }
+}
+
 impl Default for ComponentSwizzle {
     #[inline]
     fn default() -> ComponentSwizzle {
@@ -1439,6 +1608,7 @@ mod tests {
             SamplerReductionMode,
         },
     };
+    use std::sync::Arc;
 
     #[test]
     fn create_regular() {
@@ -1511,6 +1681,22 @@ mod tests {
         let _ = Sampler::new(device, SamplerCreateInfo::simple_repeat_linear_no_mipmap());
     }
 
+    #[test]
+    fn presets() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        for create_info in [
+            SamplerCreateInfo::clamp_linear_mip(),
+            SamplerCreateInfo::clamp_nearest(),
+            SamplerCreateInfo::shadow_compare(),
+        ] {
+            Sampler::new(device.clone(), create_info).unwrap();
+        }
+
+        let sampler = Sampler::new(device, SamplerCreateInfo::shadow_compare()).unwrap();
+        assert_eq!(sampler.compare(), Some(CompareOp::LessOrEqual));
+    }
+
     #[test]
     fn min_lod_inferior() {
         let (device, queue) = gfx_dev_and_queue!();
@@ -1680,4 +1866,39 @@ mod tests {
             _ => panic!(),
         }
     }
+
+    #[test]
+    fn deduplicated() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let create_info = || SamplerCreateInfo {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            lod: 0.0..=4.0,
+            ..Default::default()
+        };
+
+        let a = Sampler::new(device.clone(), create_info()).unwrap();
+        let b = Sampler::new(device.clone(), create_info()).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let c = Sampler::new(
+            device.clone(),
+            SamplerCreateInfo {
+                mip_lod_bias: 1.0,
+                ..create_info()
+            },
+        )
+        .unwrap();
+        assert!(!Arc::ptr_eq(&a, &c));
+
+        // Once dropped, the sampler is created again.
+        drop((a, b));
+        let stats = device.take_statistics();
+        Sampler::new(device.clone(), create_info()).unwrap();
+        assert_eq!(
+            device.take_statistics().samplers_created - stats.samplers_created,
+            1
+        );
+    }
 }
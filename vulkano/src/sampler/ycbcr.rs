@@ -106,6 +106,7 @@ pub struct SamplerYcbcrConversion {
     device: Arc<Device>,
 
     format: Option<Format>,
+    external_format: Option<u64>,
     ycbcr_model: SamplerYcbcrModelConversion,
     ycbcr_range: SamplerYcbcrRange,
     component_mapping: ComponentMapping,
@@ -125,6 +126,7 @@ impl SamplerYcbcrConversion {
     ) -> Result<Arc<SamplerYcbcrConversion>, SamplerYcbcrConversionCreationError> {
         let SamplerYcbcrConversionCreateInfo {
             format,
+            external_format,
             ycbcr_model,
             ycbcr_range,
             component_mapping,
@@ -141,139 +143,158 @@ impl SamplerYcbcrConversion {
             });
         }
 
-        let format = match format {
-            Some(f) => f,
-            None => {
+        let format = match (format, external_format) {
+            (Some(format), None) => Some(format),
+            (None, Some(_)) => {
+                if !device
+                    .enabled_extensions()
+                    .android_external_memory_android_hardware_buffer
+                {
+                    return Err(SamplerYcbcrConversionCreationError::ExtensionNotEnabled {
+                        extension: "android_external_memory_android_hardware_buffer",
+                        reason: "`external_format` was `Some`",
+                    });
+                }
+
+                // The format features of an external format are not known to Vulkano, so
+                // the remaining checks are left to the user.
+                None
+            }
+            // VUID-VkSamplerYcbcrConversionCreateInfo-format-01904
+            (Some(_), Some(_)) => {
+                return Err(SamplerYcbcrConversionCreationError::FormatWithExternalFormat);
+            }
+            (None, None) => {
                 return Err(SamplerYcbcrConversionCreationError::FormatMissing);
             }
         };
 
-        // VUID-VkSamplerYcbcrConversionCreateInfo-format-04061
-        if !format
-            .type_color()
-            .map_or(false, |ty| ty == NumericType::UNORM)
-        {
-            return Err(SamplerYcbcrConversionCreationError::FormatNotUnorm);
-        }
-
-        let potential_format_features = device
-            .physical_device()
-            .format_properties(format)
-            .potential_format_features();
+        if let Some(format) = format {
+            // VUID-VkSamplerYcbcrConversionCreateInfo-format-04061
+            if !format
+                .type_color()
+                .map_or(false, |ty| ty == NumericType::UNORM)
+            {
+                return Err(SamplerYcbcrConversionCreationError::FormatNotUnorm);
+            }
 
-        // VUID-VkSamplerYcbcrConversionCreateInfo-format-01650
-        if !(potential_format_features.midpoint_chroma_samples
-            || potential_format_features.cosited_chroma_samples)
-        {
-            return Err(SamplerYcbcrConversionCreationError::FormatNotSupported);
-        }
+            let potential_format_features = device
+                .physical_device()
+                .format_properties(format)
+                .potential_format_features();
 
-        if let Some(chroma_sampling @ (ChromaSampling::Mode422 | ChromaSampling::Mode420)) =
-            format.ycbcr_chroma_sampling()
-        {
-            let chroma_offsets_to_check = match chroma_sampling {
-                ChromaSampling::Mode420 => &chroma_offset[0..2],
-                ChromaSampling::Mode422 => &chroma_offset[0..1],
-                _ => unreachable!(),
-            };
+            // VUID-VkSamplerYcbcrConversionCreateInfo-format-01650
+            if !(potential_format_features.midpoint_chroma_samples
+                || potential_format_features.cosited_chroma_samples)
+            {
+                return Err(SamplerYcbcrConversionCreationError::FormatNotSupported);
+            }
 
-            for offset in chroma_offsets_to_check {
-                match offset {
-                    ChromaLocation::CositedEven => {
-                        // VUID-VkSamplerYcbcrConversionCreateInfo-xChromaOffset-01651
-                        if !potential_format_features.cosited_chroma_samples {
-                            return Err(
-                                SamplerYcbcrConversionCreationError::FormatChromaOffsetNotSupported,
-                            );
+            if let Some(chroma_sampling @ (ChromaSampling::Mode422 | ChromaSampling::Mode420)) =
+                format.ycbcr_chroma_sampling()
+            {
+                let chroma_offsets_to_check = match chroma_sampling {
+                    ChromaSampling::Mode420 => &chroma_offset[0..2],
+                    ChromaSampling::Mode422 => &chroma_offset[0..1],
+                    _ => unreachable!(),
+                };
+
+                for offset in chroma_offsets_to_check {
+                    match offset {
+                        ChromaLocation::CositedEven => {
+                            // VUID-VkSamplerYcbcrConversionCreateInfo-xChromaOffset-01651
+                            if !potential_format_features.cosited_chroma_samples {
+                                return Err(
+                                    SamplerYcbcrConversionCreationError::FormatChromaOffsetNotSupported,
+                                );
+                            }
                         }
-                    }
-                    ChromaLocation::Midpoint => {
-                        // VUID-VkSamplerYcbcrConversionCreateInfo-xChromaOffset-01652
-                        if !potential_format_features.midpoint_chroma_samples {
-                            return Err(
-                                SamplerYcbcrConversionCreationError::FormatChromaOffsetNotSupported,
-                            );
+                        ChromaLocation::Midpoint => {
+                            // VUID-VkSamplerYcbcrConversionCreateInfo-xChromaOffset-01652
+                            if !potential_format_features.midpoint_chroma_samples {
+                                return Err(
+                                    SamplerYcbcrConversionCreationError::FormatChromaOffsetNotSupported,
+                                );
+                            }
                         }
                     }
                 }
-            }
 
-            // VUID-VkSamplerYcbcrConversionCreateInfo-components-02581
-            let g_ok = component_mapping.g_is_identity();
+                // VUID-VkSamplerYcbcrConversionCreateInfo-components-02581
+                let g_ok = component_mapping.g_is_identity();
 
-            // VUID-VkSamplerYcbcrConversionCreateInfo-components-02582
-            let a_ok = component_mapping.a_is_identity()
-                || matches!(
-                    component_mapping.a,
-                    ComponentSwizzle::One | ComponentSwizzle::Zero
-                );
+                // VUID-VkSamplerYcbcrConversionCreateInfo-components-02582
+                let a_ok = component_mapping.a_is_identity()
+                    || matches!(
+                        component_mapping.a,
+                        ComponentSwizzle::One | ComponentSwizzle::Zero
+                    );
 
-            // VUID-VkSamplerYcbcrConversionCreateInfo-components-02583
-            // VUID-VkSamplerYcbcrConversionCreateInfo-components-02584
-            // VUID-VkSamplerYcbcrConversionCreateInfo-components-02585
-            let rb_ok1 = component_mapping.r_is_identity() && component_mapping.b_is_identity();
-            let rb_ok2 = matches!(component_mapping.r, ComponentSwizzle::Blue)
-                && matches!(component_mapping.b, ComponentSwizzle::Red);
+                // VUID-VkSamplerYcbcrConversionCreateInfo-components-02583
+                // VUID-VkSamplerYcbcrConversionCreateInfo-components-02584
+                // VUID-VkSamplerYcbcrConversionCreateInfo-components-02585
+                let rb_ok1 = component_mapping.r_is_identity() && component_mapping.b_is_identity();
+                let rb_ok2 = matches!(component_mapping.r, ComponentSwizzle::Blue)
+                    && matches!(component_mapping.b, ComponentSwizzle::Red);
 
-            if !(g_ok && a_ok && (rb_ok1 || rb_ok2)) {
-                return Err(SamplerYcbcrConversionCreationError::FormatInvalidComponentMapping);
+                if !(g_ok && a_ok && (rb_ok1 || rb_ok2)) {
+                    return Err(SamplerYcbcrConversionCreationError::FormatInvalidComponentMapping);
+                }
             }
-        }
 
-        let components_bits = {
-            let bits = format.components();
-            component_mapping
-                .component_map()
-                .map(move |i| i.map(|i| bits[i]))
-        };
+            let components_bits = {
+                let bits = format.components();
+                component_mapping
+                    .component_map()
+                    .map(move |i| i.map(|i| bits[i]))
+            };
 
-        // VUID-VkSamplerYcbcrConversionCreateInfo-ycbcrModel-01655
-        if ycbcr_model != SamplerYcbcrModelConversion::RgbIdentity
-            && !components_bits[0..3]
-                .iter()
-                .all(|b| b.map_or(false, |b| b != 0))
-        {
-            return Err(SamplerYcbcrConversionCreationError::YcbcrModelInvalidComponentMapping);
-        }
+            // VUID-VkSamplerYcbcrConversionCreateInfo-ycbcrModel-01655
+            if ycbcr_model != SamplerYcbcrModelConversion::RgbIdentity
+                && !components_bits[0..3]
+                    .iter()
+                    .all(|b| b.map_or(false, |b| b != 0))
+            {
+                return Err(SamplerYcbcrConversionCreationError::YcbcrModelInvalidComponentMapping);
+            }
 
-        // VUID-VkSamplerYcbcrConversionCreateInfo-ycbcrRange-02748
-        if ycbcr_range == SamplerYcbcrRange::ItuNarrow {
-            // TODO: Spec doesn't say how many bits `Zero` and `One` are considered to have, so
-            // just skip them for now.
-            for &bits in components_bits[0..3].iter().flatten() {
-                if bits < 8 {
-                    return Err(SamplerYcbcrConversionCreationError::YcbcrRangeFormatNotEnoughBits);
+            // VUID-VkSamplerYcbcrConversionCreateInfo-ycbcrRange-02748
+            if ycbcr_range == SamplerYcbcrRange::ItuNarrow {
+                // TODO: Spec doesn't say how many bits `Zero` and `One` are considered to have, so
+                // just skip them for now.
+                for &bits in components_bits[0..3].iter().flatten() {
+                    if bits < 8 {
+                        return Err(
+                            SamplerYcbcrConversionCreationError::YcbcrRangeFormatNotEnoughBits,
+                        );
+                    }
                 }
             }
-        }
 
-        // VUID-VkSamplerYcbcrConversionCreateInfo-forceExplicitReconstruction-01656
-        if force_explicit_reconstruction
-            && !potential_format_features
-                .sampled_image_ycbcr_conversion_chroma_reconstruction_explicit_forceable
-        {
-            return Err(
-                SamplerYcbcrConversionCreationError::FormatForceExplicitReconstructionNotSupported,
-            );
-        }
-
-        match chroma_filter {
-            Filter::Nearest => (),
-            Filter::Linear => {
-                // VUID-VkSamplerYcbcrConversionCreateInfo-chromaFilter-01657
-                if !potential_format_features.sampled_image_ycbcr_conversion_linear_filter {
-                    return Err(
-                        SamplerYcbcrConversionCreationError::FormatLinearFilterNotSupported,
-                    );
-                }
+            // VUID-VkSamplerYcbcrConversionCreateInfo-forceExplicitReconstruction-01656
+            if force_explicit_reconstruction
+                && !potential_format_features
+                    .sampled_image_ycbcr_conversion_chroma_reconstruction_explicit_forceable
+            {
+                return Err(
+                    SamplerYcbcrConversionCreationError::FormatForceExplicitReconstructionNotSupported,
+                );
             }
-            Filter::Cubic => {
-                return Err(SamplerYcbcrConversionCreationError::CubicFilterNotSupported);
+
+            // VUID-VkSamplerYcbcrConversionCreateInfo-chromaFilter-01657
+            if chroma_filter == Filter::Linear
+                && !potential_format_features.sampled_image_ycbcr_conversion_linear_filter
+            {
+                return Err(SamplerYcbcrConversionCreationError::FormatLinearFilterNotSupported);
             }
         }
 
-        let create_info = ash::vk::SamplerYcbcrConversionCreateInfo {
-            format: format.into(),
+        if chroma_filter == Filter::Cubic {
+            return Err(SamplerYcbcrConversionCreationError::CubicFilterNotSupported);
+        }
+
+        let mut create_info = ash::vk::SamplerYcbcrConversionCreateInfo {
+            format: format.map_or(ash::vk::Format::UNDEFINED, Into::into),
             ycbcr_model: ycbcr_model.into(),
             ycbcr_range: ycbcr_range.into(),
             components: component_mapping.into(),
@@ -284,6 +305,17 @@ impl SamplerYcbcrConversion {
             ..Default::default()
         };
 
+        let mut external_format_info =
+            external_format.map(|external_format| ash::vk::ExternalFormatANDROID {
+                external_format,
+                ..Default::default()
+            });
+
+        if let Some(external_format_info) = external_format_info.as_mut() {
+            external_format_info.p_next = create_info.p_next as *mut _;
+            create_info.p_next = external_format_info as *const _ as *const _;
+        }
+
         let handle = unsafe {
             let fns = device.fns();
             let create_sampler_ycbcr_conversion = if device.api_version() >= Version::V1_1 {
@@ -306,7 +338,8 @@ impl SamplerYcbcrConversion {
         Ok(Arc::new(SamplerYcbcrConversion {
             handle,
             device,
-            format: Some(format),
+            format,
+            external_format,
             ycbcr_model,
             ycbcr_range,
             component_mapping,
@@ -346,6 +379,12 @@ impl SamplerYcbcrConversion {
         self.format
     }
 
+    /// Returns the external format that the conversion was created for.
+    #[inline]
+    pub fn external_format(&self) -> Option<u64> {
+        self.external_format
+    }
+
     /// Returns the YCbCr model of the conversion.
     #[inline]
     pub fn ycbcr_model(&self) -> SamplerYcbcrModelConversion {
@@ -366,6 +405,7 @@ impl SamplerYcbcrConversion {
                 handle: _,
                 device: _,
                 format,
+                external_format,
                 ycbcr_model,
                 ycbcr_range,
                 component_mapping,
@@ -375,6 +415,7 @@ impl SamplerYcbcrConversion {
             } = self;
 
             format == other.format
+                && external_format == other.external_format
                 && ycbcr_model == other.ycbcr_model
                 && ycbcr_range == other.ycbcr_range
                 && component_mapping == other.component_mapping
@@ -445,6 +486,10 @@ pub enum SamplerYcbcrConversionCreationError {
     /// Not enough memory.
     OomError(OomError),
 
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
     FeatureNotEnabled {
         feature: &'static str,
         reason: &'static str,
@@ -456,6 +501,9 @@ pub enum SamplerYcbcrConversionCreationError {
     /// No format was specified when one was required.
     FormatMissing,
 
+    /// Both a format and an external format were specified.
+    FormatWithExternalFormat,
+
     /// The format has a color type other than `UNORM`.
     FormatNotUnorm,
 
@@ -497,6 +545,11 @@ impl fmt::Display for SamplerYcbcrConversionCreationError {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match *self {
             Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::ExtensionNotEnabled { extension, reason } => write!(
+                fmt,
+                "the extension {} must be enabled: {}",
+                extension, reason
+            ),
             Self::FeatureNotEnabled { feature, reason } => {
                 write!(fmt, "the feature {} must be enabled: {}", feature, reason)
             }
@@ -506,6 +559,9 @@ impl fmt::Display for SamplerYcbcrConversionCreationError {
             Self::FormatMissing => {
                 write!(fmt, "no format was specified when one was required")
             }
+            Self::FormatWithExternalFormat => {
+                write!(fmt, "both a format and an external format were specified")
+            }
             Self::FormatNotUnorm => {
                 write!(fmt, "the format has a color type other than `UNORM`")
             }
@@ -580,12 +636,25 @@ pub struct SamplerYcbcrConversionCreateInfo {
     /// - `a` must be identity swizzled or `Zero` or `One`.
     /// - `r` and `b` must be identity swizzled or mapped to each other.
     ///
-    /// Compatibility notice: currently, this value must be `Some`, but future additions may allow
-    /// `None` as a valid value as well.
+    /// This must be `None` if `external_format` is `Some`, and `Some` otherwise.
     ///
     /// The default value is `None`.
     pub format: Option<Format>,
 
+    /// An implementation-defined external format to read data from, instead of `format`. This is
+    /// used to sample images imported from Android hardware buffers that have no Vulkan format,
+    /// and its value is the `external_format` returned by
+    /// [`Device::android_hardware_buffer_properties`](crate::device::Device::android_hardware_buffer_properties).
+    ///
+    /// If this is `Some`, the
+    /// [`android_external_memory_android_hardware_buffer`](crate::device::DeviceExtensions::android_external_memory_android_hardware_buffer)
+    /// extension must be enabled on the device. The format features of an external format are
+    /// not known to Vulkano, so the user must ensure that the other parameters of the conversion
+    /// are supported by it.
+    ///
+    /// The default value is `None`.
+    pub external_format: Option<u64>,
+
     /// The conversion between the input color model and the output RGB color model.
     ///
     /// If this is not set to `RgbIdentity`, then the `r`, `g` and `b` components of
@@ -643,6 +712,7 @@ impl Default for SamplerYcbcrConversionCreateInfo {
     fn default() -> Self {
         Self {
             format: None,
+            external_format: None,
             ycbcr_model: SamplerYcbcrModelConversion::RgbIdentity,
             ycbcr_range: SamplerYcbcrRange::ItuFull,
             component_mapping: ComponentMapping::identity(),
@@ -684,6 +754,22 @@ impl From<SamplerYcbcrModelConversion> for ash::vk::SamplerYcbcrModelConversion
     }
 }
 
+impl TryFrom<ash::vk::SamplerYcbcrModelConversion> for SamplerYcbcrModelConversion {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::SamplerYcbcrModelConversion) -> Result<Self, Self::Error> {
+        Ok(match val {
+            ash::vk::SamplerYcbcrModelConversion::RGB_IDENTITY => Self::RgbIdentity,
+            ash::vk::SamplerYcbcrModelConversion::YCBCR_IDENTITY => Self::YcbcrIdentity,
+            ash::vk::SamplerYcbcrModelConversion::YCBCR_709 => Self::Ycbcr709,
+            ash::vk::SamplerYcbcrModelConversion::YCBCR_601 => Self::Ycbcr601,
+            ash::vk::SamplerYcbcrModelConversion::YCBCR_2020 => Self::Ycbcr2020,
+            _ => return Err(()),
+        })
+    }
+}
+
 /// How the numeric range of the input data is converted.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(i32)]
@@ -704,6 +790,19 @@ impl From<SamplerYcbcrRange> for ash::vk::SamplerYcbcrRange {
     }
 }
 
+impl TryFrom<ash::vk::SamplerYcbcrRange> for SamplerYcbcrRange {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::SamplerYcbcrRange) -> Result<Self, Self::Error> {
+        Ok(match val {
+            ash::vk::SamplerYcbcrRange::ITU_FULL => Self::ItuFull,
+            ash::vk::SamplerYcbcrRange::ITU_NARROW => Self::ItuNarrow,
+            _ => return Err(()),
+        })
+    }
+}
+
 /// For formats with chroma subsampling, the location where the chroma components are sampled,
 /// relative to the luma component.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -724,6 +823,19 @@ impl From<ChromaLocation> for ash::vk::ChromaLocation {
     }
 }
 
+impl TryFrom<ash::vk::ChromaLocation> for ChromaLocation {
+    type Error = ();
+
+    #[inline]
+    fn try_from(val: ash::vk::ChromaLocation) -> Result<Self, Self::Error> {
+        Ok(match val {
+            ash::vk::ChromaLocation::COSITED_EVEN => Self::CositedEven,
+            ash::vk::ChromaLocation::MIDPOINT => Self::Midpoint,
+            _ => return Err(()),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{SamplerYcbcrConversion, SamplerYcbcrConversionCreationError};
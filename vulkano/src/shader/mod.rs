@@ -30,6 +30,7 @@ use crate::DeviceSize;
 use crate::OomError;
 use crate::Version;
 use crate::VulkanObject;
+use smallvec::SmallVec;
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::error;
@@ -44,8 +45,12 @@ use std::ops::BitOr;
 use std::ptr;
 use std::sync::Arc;
 
+pub use self::object::ShaderObject;
+
+pub mod object;
 pub mod reflect;
 pub mod spirv;
+pub mod translate;
 
 use spirv::ExecutionModel;
 
@@ -58,6 +63,9 @@ pub struct ShaderModule {
     handle: ash::vk::ShaderModule,
     device: Arc<Device>,
     entry_points: HashMap<String, HashMap<ExecutionModel, EntryPointInfo>>,
+    spirv_capabilities: Vec<Capability>,
+    words: Vec<u32>,
+    identifier: Option<ShaderModuleIdentifier>,
 }
 
 impl ShaderModule {
@@ -125,7 +133,9 @@ impl ShaderModule {
             });
         }
 
-        for capability in spirv_capabilities {
+        let spirv_capabilities: Vec<Capability> = spirv_capabilities.into_iter().cloned().collect();
+
+        for capability in &spirv_capabilities {
             if let Err(reason) = check_spirv_capability(&device, capability.clone()) {
                 return Err(ShaderCreationError::SpirvCapabilityNotSupported {
                     capability: capability.clone(),
@@ -162,33 +172,78 @@ impl ShaderModule {
             output.assume_init()
         };
 
-        let entries = entry_points.into_iter().collect::<Vec<_>>();
-        let entry_points = entries
-            .iter()
-            .filter_map(|(name, _, _)| Some(name))
-            .collect::<HashSet<_>>()
-            .iter()
-            .map(|name| {
-                (
-                    (*name).clone(),
-                    entries
-                        .iter()
-                        .filter_map(|(entry_name, entry_model, info)| {
-                            if &entry_name == name {
-                                Some((*entry_model, info.clone()))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<HashMap<_, _>>(),
-                )
-            })
-            .collect();
+        let identifier = if device.enabled_features().shader_module_identifier {
+            let fns = device.fns();
+            let mut identifier_vk = ash::vk::ShaderModuleIdentifierEXT::default();
+            (fns.ext_shader_module_identifier
+                .get_shader_module_identifier_ext)(
+                device.internal_object(),
+                handle,
+                &mut identifier_vk,
+            );
+
+            ShaderModuleIdentifier::new(
+                &identifier_vk.identifier[..identifier_vk.identifier_size as usize],
+            )
+        } else {
+            None
+        };
 
         Ok(Arc::new(ShaderModule {
             handle,
             device,
-            entry_points,
+            entry_points: group_entry_points(entry_points),
+            spirv_capabilities,
+            words: words.to_owned(),
+            identifier,
+        }))
+    }
+
+    /// Builds a new shader module that has no SPIR-V code, but only an identifier that was
+    /// previously retrieved with [`identifier`](ShaderModule::identifier).
+    ///
+    /// Pipelines created from such a module are only successfully created if the implementation
+    /// can find the compiled pipeline in its caches. Otherwise, pipeline creation returns a
+    /// `PipelineCompileRequired` error, and the pipeline must be created again from a shader
+    /// module that contains SPIR-V code.
+    ///
+    /// The [`shader_module_identifier`](crate::device::Features::shader_module_identifier) and
+    /// [`pipeline_creation_cache_control`](crate::device::Features::pipeline_creation_cache_control)
+    /// features must be enabled on the device.
+    ///
+    /// # Safety
+    ///
+    /// - The identifier must have been retrieved from a device whose
+    ///   [`shader_module_identifier_algorithm_uuid`](crate::device::Properties::shader_module_identifier_algorithm_uuid)
+    ///   property is equal to that of `device`.
+    /// - The provided entry points must match what the SPIR-V code that the identifier was
+    ///   retrieved from contains.
+    pub unsafe fn from_identifier_with_data(
+        device: Arc<Device>,
+        identifier: ShaderModuleIdentifier,
+        entry_points: impl IntoIterator<Item = (String, ExecutionModel, EntryPointInfo)>,
+    ) -> Result<Arc<ShaderModule>, ShaderCreationError> {
+        if !device.enabled_features().shader_module_identifier {
+            return Err(ShaderCreationError::FeatureNotEnabled {
+                feature: "shader_module_identifier",
+                reason: "a shader module was created from an identifier",
+            });
+        }
+
+        if !device.enabled_features().pipeline_creation_cache_control {
+            return Err(ShaderCreationError::FeatureNotEnabled {
+                feature: "pipeline_creation_cache_control",
+                reason: "a shader module was created from an identifier",
+            });
+        }
+
+        Ok(Arc::new(ShaderModule {
+            handle: ash::vk::ShaderModule::null(),
+            device,
+            entry_points: group_entry_points(entry_points),
+            spirv_capabilities: Vec::new(),
+            words: Vec::new(),
+            identifier: Some(identifier),
         }))
     }
 
@@ -219,6 +274,79 @@ impl ShaderModule {
         )
     }
 
+    /// Returns the SPIR-V code that the shader module was created from.
+    ///
+    /// This is empty if the shader module was created from an identifier.
+    #[inline]
+    pub fn spirv_words(&self) -> &[u32] {
+        &self.words
+    }
+
+    /// Returns the identifier of the shader module.
+    ///
+    /// This returns `None` if the
+    /// [`shader_module_identifier`](crate::device::Features::shader_module_identifier) feature
+    /// was not enabled on the device when the module was created.
+    ///
+    /// The identifier can be stored and used in a later run of the application to create the
+    /// module again with [`from_identifier_with_data`](ShaderModule::from_identifier_with_data),
+    /// without having to provide the SPIR-V code.
+    #[inline]
+    pub fn identifier(&self) -> Option<&ShaderModuleIdentifier> {
+        self.identifier.as_ref()
+    }
+
+    /// Returns whether the shader module was created from an identifier, and has no SPIR-V code.
+    #[inline]
+    pub fn is_identifier_only(&self) -> bool {
+        self.handle == ash::vk::ShaderModule::null()
+    }
+
+    // Returns the structure to chain to a shader stage when the module has no handle.
+    pub(crate) fn module_identifier_create_info_vk(
+        &self,
+    ) -> Option<ash::vk::PipelineShaderStageModuleIdentifierCreateInfoEXT> {
+        self.is_identifier_only().then(|| {
+            let identifier = self.identifier.as_ref().unwrap();
+
+            ash::vk::PipelineShaderStageModuleIdentifierCreateInfoEXT {
+                identifier_size: identifier.data.len() as u32,
+                p_identifier: identifier.data.as_ptr(),
+                ..Default::default()
+            }
+        })
+    }
+
+    /// Returns the SPIR-V capabilities that are declared by the shader module.
+    #[inline]
+    pub fn spirv_capabilities(&self) -> &[Capability] {
+        &self.spirv_capabilities
+    }
+
+    /// Disassembles the SPIR-V code of the shader module into its textual representation.
+    ///
+    /// This requires the `rspirv` feature to be enabled.
+    #[cfg(feature = "rspirv")]
+    #[inline]
+    pub fn disassemble(&self) -> Result<String, translate::TranslationError> {
+        translate::disassemble(&self.words)
+    }
+
+    /// Translates the entry point with the provided name and execution model into source code of
+    /// another shading language.
+    ///
+    /// This requires the `naga` feature to be enabled.
+    #[cfg(feature = "naga")]
+    #[inline]
+    pub fn cross_compile(
+        &self,
+        entry_point: &str,
+        execution_model: ExecutionModel,
+        target: translate::TargetLanguage,
+    ) -> Result<String, translate::TranslationError> {
+        translate::cross_compile(&self.words, entry_point, execution_model, target)
+    }
+
     /// Returns information about the entry point with the provided name. Returns `None` if no entry
     /// point with that name exists in the shader module or if multiple entry points with the same
     /// name exist.
@@ -265,6 +393,10 @@ unsafe impl VulkanObject for ShaderModule {
 impl Drop for ShaderModule {
     #[inline]
     fn drop(&mut self) {
+        if self.is_identifier_only() {
+            return;
+        }
+
         unsafe {
             let fns = self.device.fns();
             (fns.v1_0.destroy_shader_module)(
@@ -276,10 +408,77 @@ impl Drop for ShaderModule {
     }
 }
 
+fn group_entry_points(
+    entry_points: impl IntoIterator<Item = (String, ExecutionModel, EntryPointInfo)>,
+) -> HashMap<String, HashMap<ExecutionModel, EntryPointInfo>> {
+    let entries = entry_points.into_iter().collect::<Vec<_>>();
+    entries
+        .iter()
+        .filter_map(|(name, _, _)| Some(name))
+        .collect::<HashSet<_>>()
+        .iter()
+        .map(|name| {
+            (
+                (*name).clone(),
+                entries
+                    .iter()
+                    .filter_map(|(entry_name, entry_model, info)| {
+                        if &entry_name == name {
+                            Some((*entry_model, info.clone()))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<HashMap<_, _>>(),
+            )
+        })
+        .collect()
+}
+
+/// An opaque identifier of a shader module, that can be used to create pipelines without
+/// providing the SPIR-V code of the module.
+///
+/// Identifiers are only valid on devices that have the same
+/// [`shader_module_identifier_algorithm_uuid`](crate::device::Properties::shader_module_identifier_algorithm_uuid)
+/// property as the device that the identifier was retrieved from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ShaderModuleIdentifier {
+    data: SmallVec<[u8; ash::vk::MAX_SHADER_MODULE_IDENTIFIER_SIZE_EXT]>,
+}
+
+impl ShaderModuleIdentifier {
+    /// The maximum size of an identifier, in bytes.
+    pub const MAX_SIZE: usize = ash::vk::MAX_SHADER_MODULE_IDENTIFIER_SIZE_EXT;
+
+    /// Creates an identifier from previously stored data.
+    ///
+    /// Returns `None` if `data` is empty or is longer than [`MAX_SIZE`](Self::MAX_SIZE).
+    #[inline]
+    pub fn new(data: &[u8]) -> Option<Self> {
+        if data.is_empty() || data.len() > Self::MAX_SIZE {
+            return None;
+        }
+
+        Some(Self {
+            data: SmallVec::from_slice(data),
+        })
+    }
+
+    /// Returns the data of the identifier.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
 /// Error that can happen when creating a new shader module.
 #[derive(Clone, Debug)]
 pub enum ShaderCreationError {
     OomError(OomError),
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
     SpirvCapabilityNotSupported {
         capability: Capability,
         reason: ShaderSupportError,
@@ -300,6 +499,7 @@ impl Error for ShaderCreationError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Self::OomError(err) => Some(err),
+            Self::FeatureNotEnabled { .. } => None,
             Self::SpirvCapabilityNotSupported { reason, .. } => Some(reason),
             Self::SpirvError(err) => Some(err),
             Self::SpirvExtensionNotSupported { reason, .. } => Some(reason),
@@ -312,6 +512,9 @@ impl Display for ShaderCreationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::OomError(_) => write!(f, "not enough memory available"),
+            Self::FeatureNotEnabled { feature, reason } => {
+                write!(f, "the feature {} must be enabled: {}", feature, reason,)
+            }
             Self::SpirvCapabilityNotSupported { capability, .. } => write!(
                 f,
                 "the SPIR-V capability {:?} enabled by the shader is not supported by the device",
@@ -375,6 +578,12 @@ pub struct EntryPointInfo {
     pub specialization_constant_requirements: HashMap<u32, SpecializationConstantRequirements>,
     pub input_interface: ShaderInterface,
     pub output_interface: ShaderInterface,
+    /// The largest array size of the `ClipDistance` builtin variables in the interface of the
+    /// entry point, or 0 if it doesn't use the builtin.
+    pub clip_distance_array_size: u32,
+    /// The largest array size of the `CullDistance` builtin variables in the interface of the
+    /// entry point, or 0 if it doesn't use the builtin.
+    pub cull_distance_array_size: u32,
 }
 
 /// Represents a shader entry point in a shader module.
@@ -445,6 +654,20 @@ impl<'a> EntryPoint<'a> {
     pub fn output_interface(&self) -> &ShaderInterface {
         &self.info.output_interface
     }
+
+    /// Returns the array size of the `ClipDistance` builtin used by the shader stage, or 0 if it
+    /// doesn't use it.
+    #[inline]
+    pub fn clip_distance_array_size(&self) -> u32 {
+        self.info.clip_distance_array_size
+    }
+
+    /// Returns the array size of the `CullDistance` builtin used by the shader stage, or 0 if it
+    /// doesn't use it.
+    #[inline]
+    pub fn cull_distance_array_size(&self) -> u32 {
+        self.info.cull_distance_array_size
+    }
 }
 
 /// The mode in which a shader executes. This includes both information about the shader type/stage,
@@ -561,6 +784,12 @@ pub struct DescriptorRequirements {
     /// This is `None` for non-image descriptors.
     pub image_view_type: Option<ImageViewType>,
 
+    /// The name of the variable that the descriptor is declared as in the shader. For blocks
+    /// without an instance name, this is the name of the block instead.
+    ///
+    /// This is `None` if the shader was compiled without debug information.
+    pub name: Option<String>,
+
     /// For sampler bindings, the descriptor indices that require a depth comparison sampler.
     pub sampler_compare: HashSet<u32>,
 
@@ -654,6 +883,7 @@ impl DescriptorRequirements {
             image_multisampled: self.image_multisampled,
             image_scalar_type: self.image_scalar_type.or(other.image_scalar_type),
             image_view_type: self.image_view_type.or(other.image_view_type),
+            name: self.name.clone().or_else(|| other.name.clone()),
             sampler_compare: &self.sampler_compare | &other.sampler_compare,
             sampler_no_unnormalized_coordinates: &self.sampler_no_unnormalized_coordinates
                 | &other.sampler_no_unnormalized_coordinates,
@@ -1143,7 +1373,7 @@ impl From<ShaderStage> for ash::vk::ShaderStageFlags {
 
 /// A set of shader stages.
 // TODO: add example with BitOr
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct ShaderStages {
     pub vertex: bool,
     pub tessellation_control: bool,
@@ -0,0 +1,556 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Shaders that can be bound directly to a command buffer, without a pipeline.
+//!
+//! With the [`ext_shader_object`](crate::device::DeviceExtensions::ext_shader_object) extension,
+//! each shader stage is compiled into its own [`ShaderObject`], which can be bound with
+//! [`bind_shaders`](crate::command_buffer::AutoCommandBufferBuilder::bind_shaders) in place of a
+//! graphics or compute pipeline. All the state that would otherwise be part of a pipeline is then
+//! taken from the dynamic state of the command buffer, and must be set before drawing.
+//!
+//! Graphics shader objects can only be used inside a render pass instance that was begun with
+//! [`begin_rendering`](crate::command_buffer::AutoCommandBufferBuilder::begin_rendering).
+
+use crate::{
+    check_errors,
+    device::{Device, DeviceOwned},
+    pipeline::layout::{PipelineLayout, PipelineLayoutSupersetError},
+    shader::{
+        DescriptorRequirements, EntryPoint, ShaderInterface, ShaderStage, ShaderStages,
+        SpecializationConstants,
+    },
+    DeviceSize, Error, OomError, VulkanObject,
+};
+use std::{
+    collections::HashMap,
+    error, fmt,
+    hash::{Hash, Hasher},
+    mem,
+    mem::MaybeUninit,
+    ptr,
+    sync::Arc,
+};
+
+/// A single shader stage that can be bound to a command buffer without a pipeline.
+#[derive(Debug)]
+pub struct ShaderObject {
+    handle: ash::vk::ShaderEXT,
+    device: Arc<Device>,
+
+    stage: ShaderStage,
+    next_stages: ShaderStages,
+    layout: Arc<PipelineLayout>,
+    descriptor_requirements: HashMap<(u32, u32), DescriptorRequirements>,
+    num_used_descriptor_sets: u32,
+    input_interface: ShaderInterface,
+}
+
+impl ShaderObject {
+    /// Creates a new `ShaderObject` from an entry point of a shader module.
+    ///
+    /// The [`shader_object`](crate::device::Features::shader_object) feature must be enabled on
+    /// the device.
+    pub fn new<Css>(
+        device: Arc<Device>,
+        entry_point: EntryPoint,
+        specialization_constants: &Css,
+        create_info: ShaderObjectCreateInfo,
+    ) -> Result<Arc<ShaderObject>, ShaderObjectCreationError>
+    where
+        Css: SpecializationConstants,
+    {
+        let ShaderObjectCreateInfo {
+            ref layout,
+            next_stages,
+            _ne: _,
+        } = create_info;
+
+        // VUID-vkCreateShadersEXT-None-08400
+        if !device.enabled_features().shader_object {
+            return Err(ShaderObjectCreationError::FeatureNotEnabled {
+                feature: "shader_object",
+                reason: "ShaderObject::new was called",
+            });
+        }
+
+        // VUID?
+        if entry_point.module().is_identifier_only() {
+            return Err(ShaderObjectCreationError::ModuleHasNoCode);
+        }
+
+        let stage = ShaderStage::from(*entry_point.execution());
+
+        let allowed_next_stages = match stage {
+            ShaderStage::Vertex => ShaderStages {
+                tessellation_control: true,
+                geometry: true,
+                fragment: true,
+                ..ShaderStages::none()
+            },
+            ShaderStage::TessellationControl => ShaderStages {
+                tessellation_evaluation: true,
+                ..ShaderStages::none()
+            },
+            ShaderStage::TessellationEvaluation => ShaderStages {
+                geometry: true,
+                fragment: true,
+                ..ShaderStages::none()
+            },
+            ShaderStage::Geometry => ShaderStages {
+                fragment: true,
+                ..ShaderStages::none()
+            },
+            ShaderStage::Fragment | ShaderStage::Compute => ShaderStages::none(),
+            _ => return Err(ShaderObjectCreationError::StageNotSupported { stage }),
+        };
+
+        match stage {
+            ShaderStage::TessellationControl | ShaderStage::TessellationEvaluation => {
+                // VUID?
+                if !device.enabled_features().tessellation_shader {
+                    return Err(ShaderObjectCreationError::FeatureNotEnabled {
+                        feature: "tessellation_shader",
+                        reason: "the entry point is a tessellation shader",
+                    });
+                }
+            }
+            ShaderStage::Geometry => {
+                // VUID?
+                if !device.enabled_features().geometry_shader {
+                    return Err(ShaderObjectCreationError::FeatureNotEnabled {
+                        feature: "geometry_shader",
+                        reason: "the entry point is a geometry shader",
+                    });
+                }
+            }
+            _ => (),
+        }
+
+        // VUID?
+        if !allowed_next_stages.is_superset_of(&next_stages) {
+            return Err(ShaderObjectCreationError::NextStagesNotAllowed { stage, next_stages });
+        }
+
+        let spec_descriptors = Css::descriptors();
+
+        for (constant_id, reqs) in entry_point.specialization_constant_requirements() {
+            let map_entry = spec_descriptors
+                .iter()
+                .find(|desc| desc.constant_id == constant_id)
+                .ok_or(ShaderObjectCreationError::IncompatibleSpecializationConstants)?;
+
+            if map_entry.size as DeviceSize != reqs.size {
+                return Err(ShaderObjectCreationError::IncompatibleSpecializationConstants);
+            }
+        }
+
+        layout.ensure_compatible_with_shader(
+            entry_point.descriptor_requirements(),
+            entry_point.push_constant_requirements(),
+        )?;
+
+        unsafe { Self::new_unchecked(device, entry_point, specialization_constants, create_info) }
+    }
+
+    /// Same as `new`, but doesn't check whether the create info is valid, or whether the layout
+    /// is a superset of what the shader expects.
+    pub unsafe fn new_unchecked<Css>(
+        device: Arc<Device>,
+        entry_point: EntryPoint,
+        specialization_constants: &Css,
+        create_info: ShaderObjectCreateInfo,
+    ) -> Result<Arc<ShaderObject>, ShaderObjectCreationError>
+    where
+        Css: SpecializationConstants,
+    {
+        let ShaderObjectCreateInfo {
+            layout,
+            next_stages,
+            _ne: _,
+        } = create_info;
+
+        let stage = ShaderStage::from(*entry_point.execution());
+        let words = entry_point.module().spirv_words();
+
+        let handle = {
+            let spec_descriptors = Css::descriptors();
+            let specialization = ash::vk::SpecializationInfo {
+                map_entry_count: spec_descriptors.len() as u32,
+                p_map_entries: spec_descriptors.as_ptr() as *const _,
+                data_size: mem::size_of_val(specialization_constants),
+                p_data: specialization_constants as *const Css as *const _,
+            };
+
+            let set_layouts_vk: Vec<_> = layout
+                .set_layouts()
+                .iter()
+                .map(|set_layout| set_layout.internal_object())
+                .collect();
+            let push_constant_ranges_vk: Vec<_> = layout
+                .push_constant_ranges()
+                .iter()
+                .map(|range| ash::vk::PushConstantRange {
+                    stage_flags: range.stages.into(),
+                    offset: range.offset,
+                    size: range.size,
+                })
+                .collect();
+
+            let create_info_vk = ash::vk::ShaderCreateInfoEXT {
+                flags: ash::vk::ShaderCreateFlagsEXT::empty(),
+                stage: stage.into(),
+                next_stage: next_stages.into(),
+                code_type: ash::vk::ShaderCodeTypeEXT::SPIRV,
+                code_size: mem::size_of_val(words),
+                p_code: words.as_ptr() as *const _,
+                p_name: entry_point.name().as_ptr(),
+                set_layout_count: set_layouts_vk.len() as u32,
+                p_set_layouts: set_layouts_vk.as_ptr(),
+                push_constant_range_count: push_constant_ranges_vk.len() as u32,
+                p_push_constant_ranges: push_constant_ranges_vk.as_ptr(),
+                p_specialization_info: if specialization.data_size == 0 {
+                    ptr::null()
+                } else {
+                    &specialization
+                },
+                ..Default::default()
+            };
+
+            let fns = device.fns();
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns.ext_shader_object.create_shaders_ext)(
+                device.internal_object(),
+                1,
+                &create_info_vk,
+                ptr::null(),
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
+        };
+
+        let descriptor_requirements: HashMap<_, _> = entry_point
+            .descriptor_requirements()
+            .map(|(loc, reqs)| (loc, reqs.clone()))
+            .collect();
+        let num_used_descriptor_sets = descriptor_requirements
+            .keys()
+            .map(|loc| loc.0)
+            .max()
+            .map(|x| x + 1)
+            .unwrap_or(0);
+
+        Ok(Arc::new(ShaderObject {
+            handle,
+            device,
+
+            stage,
+            next_stages,
+            layout,
+            descriptor_requirements,
+            num_used_descriptor_sets,
+            input_interface: entry_point.input_interface().clone(),
+        }))
+    }
+
+    /// Returns the stage of the shader.
+    #[inline]
+    pub fn stage(&self) -> ShaderStage {
+        self.stage
+    }
+
+    /// Returns the stages that may follow this shader, as specified when it was created.
+    #[inline]
+    pub fn next_stages(&self) -> ShaderStages {
+        self.next_stages
+    }
+
+    /// Returns the pipeline layout that the shader was created with.
+    ///
+    /// Descriptor sets and push constants that the shader uses must be bound and set with a
+    /// layout that is compatible with this one.
+    #[inline]
+    pub fn layout(&self) -> &Arc<PipelineLayout> {
+        &self.layout
+    }
+
+    /// Returns an iterator over the descriptor requirements of the shader.
+    #[inline]
+    pub fn descriptor_requirements(
+        &self,
+    ) -> impl ExactSizeIterator<Item = ((u32, u32), &DescriptorRequirements)> {
+        self.descriptor_requirements
+            .iter()
+            .map(|(loc, reqs)| (*loc, reqs))
+    }
+
+    /// Returns the number of descriptor sets that are actually used by the shader.
+    #[inline]
+    pub fn num_used_descriptor_sets(&self) -> u32 {
+        self.num_used_descriptor_sets
+    }
+
+    /// Returns the input attributes used by the shader.
+    #[inline]
+    pub fn input_interface(&self) -> &ShaderInterface {
+        &self.input_interface
+    }
+}
+
+impl Drop for ShaderObject {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let fns = self.device.fns();
+            (fns.ext_shader_object.destroy_shader_ext)(
+                self.device.internal_object(),
+                self.handle,
+                ptr::null(),
+            );
+        }
+    }
+}
+
+unsafe impl VulkanObject for ShaderObject {
+    type Object = ash::vk::ShaderEXT;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::ShaderEXT {
+        self.handle
+    }
+}
+
+unsafe impl DeviceOwned for ShaderObject {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl PartialEq for ShaderObject {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle && self.device() == other.device()
+    }
+}
+
+impl Eq for ShaderObject {}
+
+impl Hash for ShaderObject {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state);
+        self.device().hash(state);
+    }
+}
+
+/// Parameters to create a new `ShaderObject`.
+#[derive(Clone, Debug)]
+pub struct ShaderObjectCreateInfo {
+    /// The layout of the descriptor sets and push constants used by the shader.
+    ///
+    /// The layout must be a superset of what the shader expects. When binding descriptor sets or
+    /// setting push constants for the shader, a compatible layout must be used.
+    ///
+    /// There is no default value.
+    pub layout: Arc<PipelineLayout>,
+
+    /// The stages that may be bound after this shader when drawing.
+    ///
+    /// Only stages that can logically follow the stage of the shader are allowed. Compute and
+    /// fragment shaders have no next stages.
+    ///
+    /// The default value is [`ShaderStages::none()`].
+    pub next_stages: ShaderStages,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl ShaderObjectCreateInfo {
+    /// Returns a `ShaderObjectCreateInfo` with the specified `layout`.
+    #[inline]
+    pub fn layout(layout: Arc<PipelineLayout>) -> Self {
+        Self {
+            layout,
+            next_stages: ShaderStages::none(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Error that can happen when creating a shader object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShaderObjectCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// The layout is not compatible with what the shader expects.
+    IncompatiblePipelineLayout(PipelineLayoutSupersetError),
+
+    /// The provided specialization constants are not compatible with what the shader expects.
+    IncompatibleSpecializationConstants,
+
+    /// The shader module of the entry point was created from an identifier, and has no SPIR-V
+    /// code.
+    ModuleHasNoCode,
+
+    /// `next_stages` contains stages that can't follow the stage of the shader.
+    NextStagesNotAllowed {
+        stage: ShaderStage,
+        next_stages: ShaderStages,
+    },
+
+    /// The stage of the entry point can't be created as a shader object.
+    StageNotSupported { stage: ShaderStage },
+}
+
+impl error::Error for ShaderObjectCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::OomError(err) => Some(err),
+            Self::IncompatiblePipelineLayout(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ShaderObjectCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::FeatureNotEnabled { feature, reason } => {
+                write!(fmt, "the feature {} must be enabled: {}", feature, reason)
+            }
+            Self::IncompatiblePipelineLayout(_) => write!(
+                fmt,
+                "the layout is not compatible with what the shader expects",
+            ),
+            Self::IncompatibleSpecializationConstants => write!(
+                fmt,
+                "the provided specialization constants are not compatible with what the shader expects",
+            ),
+            Self::ModuleHasNoCode => write!(
+                fmt,
+                "the shader module was created from an identifier and has no SPIR-V code",
+            ),
+            Self::NextStagesNotAllowed { stage, next_stages } => write!(
+                fmt,
+                "the next stages {:?} can't follow a {:?} shader",
+                next_stages, stage,
+            ),
+            Self::StageNotSupported { stage } => write!(
+                fmt,
+                "a {:?} shader can't be created as a shader object",
+                stage,
+            ),
+        }
+    }
+}
+
+impl From<OomError> for ShaderObjectCreationError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+impl From<PipelineLayoutSupersetError> for ShaderObjectCreationError {
+    #[inline]
+    fn from(err: PipelineLayoutSupersetError) -> Self {
+        Self::IncompatiblePipelineLayout(err)
+    }
+}
+
+impl From<Error> for ShaderObjectCreationError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            err @ Error::OutOfHostMemory => Self::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => Self::OomError(OomError::from(err)),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShaderObject, ShaderObjectCreateInfo, ShaderObjectCreationError};
+    use crate::{
+        pipeline::layout::{PipelineLayout, PipelineLayoutCreateInfo},
+        shader::ShaderModule,
+    };
+
+    #[test]
+    fn feature_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let module = unsafe {
+            /*
+            #version 450
+
+            layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+            layout(constant_id = 83) const int VALUE = 0xdeadbeef;
+
+            layout(set = 0, binding = 0) buffer Output {
+                int write;
+            } write;
+
+            void main() {
+                write.write = VALUE;
+            }
+            */
+            const MODULE: [u8; 480] = [
+                3, 2, 35, 7, 0, 0, 1, 0, 1, 0, 8, 0, 14, 0, 0, 0, 0, 0, 0, 0, 17, 0, 2, 0, 1, 0, 0,
+                0, 11, 0, 6, 0, 1, 0, 0, 0, 71, 76, 83, 76, 46, 115, 116, 100, 46, 52, 53, 48, 0,
+                0, 0, 0, 14, 0, 3, 0, 0, 0, 0, 0, 1, 0, 0, 0, 15, 0, 5, 0, 5, 0, 0, 0, 4, 0, 0, 0,
+                109, 97, 105, 110, 0, 0, 0, 0, 16, 0, 6, 0, 4, 0, 0, 0, 17, 0, 0, 0, 1, 0, 0, 0, 1,
+                0, 0, 0, 1, 0, 0, 0, 3, 0, 3, 0, 2, 0, 0, 0, 194, 1, 0, 0, 5, 0, 4, 0, 4, 0, 0, 0,
+                109, 97, 105, 110, 0, 0, 0, 0, 5, 0, 4, 0, 7, 0, 0, 0, 79, 117, 116, 112, 117, 116,
+                0, 0, 6, 0, 5, 0, 7, 0, 0, 0, 0, 0, 0, 0, 119, 114, 105, 116, 101, 0, 0, 0, 5, 0,
+                4, 0, 9, 0, 0, 0, 119, 114, 105, 116, 101, 0, 0, 0, 5, 0, 4, 0, 11, 0, 0, 0, 86,
+                65, 76, 85, 69, 0, 0, 0, 72, 0, 5, 0, 7, 0, 0, 0, 0, 0, 0, 0, 35, 0, 0, 0, 0, 0, 0,
+                0, 71, 0, 3, 0, 7, 0, 0, 0, 3, 0, 0, 0, 71, 0, 4, 0, 9, 0, 0, 0, 34, 0, 0, 0, 0, 0,
+                0, 0, 71, 0, 4, 0, 9, 0, 0, 0, 33, 0, 0, 0, 0, 0, 0, 0, 71, 0, 4, 0, 11, 0, 0, 0,
+                1, 0, 0, 0, 83, 0, 0, 0, 19, 0, 2, 0, 2, 0, 0, 0, 33, 0, 3, 0, 3, 0, 0, 0, 2, 0, 0,
+                0, 21, 0, 4, 0, 6, 0, 0, 0, 32, 0, 0, 0, 1, 0, 0, 0, 30, 0, 3, 0, 7, 0, 0, 0, 6, 0,
+                0, 0, 32, 0, 4, 0, 8, 0, 0, 0, 2, 0, 0, 0, 7, 0, 0, 0, 59, 0, 4, 0, 8, 0, 0, 0, 9,
+                0, 0, 0, 2, 0, 0, 0, 43, 0, 4, 0, 6, 0, 0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 50, 0, 4, 0,
+                6, 0, 0, 0, 11, 0, 0, 0, 239, 190, 173, 222, 32, 0, 4, 0, 12, 0, 0, 0, 2, 0, 0, 0,
+                6, 0, 0, 0, 54, 0, 5, 0, 2, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 248, 0, 2,
+                0, 5, 0, 0, 0, 65, 0, 5, 0, 12, 0, 0, 0, 13, 0, 0, 0, 9, 0, 0, 0, 10, 0, 0, 0, 62,
+                0, 3, 0, 13, 0, 0, 0, 11, 0, 0, 0, 253, 0, 1, 0, 56, 0, 1, 0,
+            ];
+            ShaderModule::from_bytes(device.clone(), &MODULE).unwrap()
+        };
+
+        let layout =
+            PipelineLayout::new(device.clone(), PipelineLayoutCreateInfo::default()).unwrap();
+
+        match ShaderObject::new(
+            device,
+            module.entry_point("main").unwrap(),
+            &(),
+            ShaderObjectCreateInfo::layout(layout),
+        ) {
+            Err(ShaderObjectCreationError::FeatureNotEnabled {
+                feature: "shader_object",
+                ..
+            }) => (),
+            _ => panic!(),
+        }
+    }
+}
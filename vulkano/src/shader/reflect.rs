@@ -17,8 +17,8 @@ use crate::{
     pipeline::layout::PushConstantRange,
     shader::{
         spirv::{
-            Capability, Decoration, Dim, ExecutionMode, ExecutionModel, Id, Instruction, Spirv,
-            StorageClass,
+            BuiltIn, Capability, Decoration, Dim, ExecutionMode, ExecutionModel, Id, IdInfo,
+            Instruction, Spirv, StorageClass,
         },
         DescriptorIdentifier, DescriptorRequirements, EntryPointInfo, GeometryShaderExecution,
         GeometryShaderInput, ShaderExecution, ShaderInterface, ShaderInterfaceEntry,
@@ -53,6 +53,7 @@ pub fn entry_points<'a>(
     spirv: &'a Spirv,
 ) -> impl Iterator<Item = (String, ExecutionModel, EntryPointInfo)> + 'a {
     let interface_variables = interface_variables(spirv);
+    let is_single_entry_point = spirv.iter_entry_point().len() == 1;
 
     spirv.iter_entry_point().filter_map(move |instruction| {
         let (execution_model, function_id, entry_point_name, interface) = match instruction {
@@ -76,7 +77,10 @@ pub fn entry_points<'a>(
             reqs.stages = stage.into();
         }
 
-        let push_constant_requirements = push_constant_requirements(&spirv, stage);
+        // If there is only one entry point, all push constants in the module belong to it.
+        // Otherwise, only include them if the entry point actually accesses them.
+        let push_constant_requirements = push_constant_requirements(&spirv, stage)
+            .filter(|_| is_single_entry_point || accesses_push_constants(&spirv, function_id));
         let specialization_constant_requirements = specialization_constant_requirements(&spirv);
         let input_interface = shader_interface(
             &spirv,
@@ -95,6 +99,18 @@ pub fn entry_points<'a>(
             StorageClass::Output,
             matches!(execution_model, ExecutionModel::TessellationControl),
         );
+        let clip_distance_array_size = builtin_array_size(
+            &spirv,
+            interface,
+            Capability::ClipDistance,
+            BuiltIn::ClipDistance,
+        );
+        let cull_distance_array_size = builtin_array_size(
+            &spirv,
+            interface,
+            Capability::CullDistance,
+            BuiltIn::CullDistance,
+        );
 
         Some((
             entry_point_name.clone(),
@@ -106,6 +122,8 @@ pub fn entry_points<'a>(
                 specialization_constant_requirements,
                 input_interface,
                 output_interface,
+                clip_distance_array_size,
+                cull_distance_array_size,
             },
         ))
     })
@@ -692,6 +710,7 @@ fn descriptor_requirements_of(spirv: &Spirv, variable_id: Id) -> DescriptorVaria
 
     let mut reqs = DescriptorRequirements {
         descriptor_count: 1,
+        name: name_of(&variable_id_info).map(ToOwned::to_owned),
         ..Default::default()
     };
 
@@ -742,6 +761,10 @@ fn descriptor_requirements_of(spirv: &Spirv, variable_id: Id) -> DescriptorVaria
                     "Structs in shader interface are expected to be decorated with one of Block or BufferBlock"
                 );
 
+                if reqs.name.is_none() {
+                    reqs.name = name_of(&id_info).map(ToOwned::to_owned);
+                }
+
                 if decoration_buffer_block || decoration_block && is_storage_buffer {
                     reqs.descriptor_types = vec![
                         DescriptorType::StorageBuffer,
@@ -908,6 +931,17 @@ fn descriptor_requirements_of(spirv: &Spirv, variable_id: Id) -> DescriptorVaria
     }
 }
 
+/// Returns the debug name of an id, if it has a non-empty one.
+fn name_of<'a>(id_info: &IdInfo<'a>) -> Option<&'a str> {
+    id_info
+        .iter_name()
+        .find_map(|instruction| match instruction {
+            Instruction::Name { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .filter(|name| !name.is_empty())
+}
+
 /// Extracts the `PushConstantRange` from `spirv`.
 fn push_constant_requirements(spirv: &Spirv, stage: ShaderStage) -> Option<PushConstantRange> {
     spirv
@@ -936,6 +970,95 @@ fn push_constant_requirements(spirv: &Spirv, stage: ShaderStage) -> Option<PushC
         })
 }
 
+/// Returns whether the function `function_id`, or any function that it calls, accesses a variable
+/// in the `PushConstant` storage class.
+fn accesses_push_constants(spirv: &Spirv, function_id: Id) -> bool {
+    fn accesses_push_constants_r(
+        spirv: &Spirv,
+        variables: &HashSet<Id>,
+        inspected_functions: &mut HashSet<Id>,
+        function: Id,
+    ) -> bool {
+        inspected_functions.insert(function);
+        let mut in_function = false;
+
+        for instruction in spirv.instructions() {
+            if !in_function {
+                match instruction {
+                    Instruction::Function { result_id, .. } if result_id == &function => {
+                        in_function = true;
+                    }
+                    _ => {}
+                }
+            } else {
+                match instruction {
+                    &Instruction::AccessChain { base, .. }
+                    | &Instruction::InBoundsAccessChain { base, .. }
+                    | &Instruction::PtrAccessChain { base, .. }
+                    | &Instruction::InBoundsPtrAccessChain { base, .. } => {
+                        if variables.contains(&base) {
+                            return true;
+                        }
+                    }
+                    &Instruction::Load { pointer, .. } => {
+                        if variables.contains(&pointer) {
+                            return true;
+                        }
+                    }
+                    &Instruction::CopyMemory { source, .. }
+                    | &Instruction::CopyMemorySized { source, .. } => {
+                        if variables.contains(&source) {
+                            return true;
+                        }
+                    }
+                    &Instruction::FunctionCall {
+                        function,
+                        ref arguments,
+                        ..
+                    } => {
+                        if arguments
+                            .iter()
+                            .any(|argument| variables.contains(argument))
+                        {
+                            return true;
+                        }
+
+                        if !inspected_functions.contains(&function)
+                            && accesses_push_constants_r(
+                                spirv,
+                                variables,
+                                inspected_functions,
+                                function,
+                            )
+                        {
+                            return true;
+                        }
+                    }
+                    &Instruction::FunctionEnd => return false,
+                    _ => (),
+                }
+            }
+        }
+
+        false
+    }
+
+    let variables: HashSet<Id> = spirv
+        .iter_global()
+        .filter_map(|instruction| match instruction {
+            &Instruction::Variable {
+                result_id,
+                storage_class: StorageClass::PushConstant,
+                ..
+            } => Some(result_id),
+            _ => None,
+        })
+        .collect();
+
+    !variables.is_empty()
+        && accesses_push_constants_r(spirv, &variables, &mut HashSet::default(), function_id)
+}
+
 /// Extracts the `SpecializationConstantRequirements` from `spirv`.
 fn specialization_constant_requirements(
     spirv: &Spirv,
@@ -1298,6 +1421,102 @@ fn shader_interface_type_of(
     }
 }
 
+/// Returns the largest array size of the variables in `interface` that are decorated with
+/// `built_in`, or 0 if `spirv` doesn't declare `capability`.
+///
+/// Compilers may declare unused builtins in `gl_PerVertex` blocks, but only declare the
+/// capability if the builtin is actually used, so the capability is checked first.
+fn builtin_array_size(
+    spirv: &Spirv,
+    interface: &[Id],
+    capability: Capability,
+    built_in: BuiltIn,
+) -> u32 {
+    if !spirv_capabilities(spirv).any(|c| *c == capability) {
+        return 0;
+    }
+
+    interface
+        .iter()
+        .filter_map(|&id| {
+            let (result_type_id, result_id) = match spirv.id(id).instruction() {
+                &Instruction::Variable {
+                    result_type_id,
+                    result_id,
+                    storage_class: StorageClass::Input | StorageClass::Output,
+                    ..
+                } => (result_type_id, result_id),
+                _ => return None,
+            };
+
+            if has_builtin_decoration(spirv.id(result_id).iter_decoration(), &built_in) {
+                Some(float_array_length(spirv, result_type_id))
+            } else {
+                member_builtin_array_size(spirv, result_type_id, &built_in)
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn has_builtin_decoration<'a>(
+    mut decorations: impl Iterator<Item = &'a Instruction>,
+    built_in: &BuiltIn,
+) -> bool {
+    decorations.any(|instruction| match instruction {
+        Instruction::Decorate {
+            decoration: Decoration::BuiltIn { built_in: b },
+            ..
+        }
+        | Instruction::MemberDecorate {
+            decoration: Decoration::BuiltIn { built_in: b },
+            ..
+        } => b == built_in,
+        _ => false,
+    })
+}
+
+// Looks for a struct member decorated with `built_in` in the type `id`, and returns its array
+// size.
+fn member_builtin_array_size(spirv: &Spirv, id: Id, built_in: &BuiltIn) -> Option<u32> {
+    let id_info = spirv.id(id);
+
+    match id_info.instruction() {
+        &Instruction::TypePointer { ty, .. } => member_builtin_array_size(spirv, ty, built_in),
+        &Instruction::TypeArray { element_type, .. } => {
+            member_builtin_array_size(spirv, element_type, built_in)
+        }
+        Instruction::TypeStruct { member_types, .. } => id_info
+            .iter_members()
+            .zip(member_types)
+            .find(|(member_info, _)| {
+                has_builtin_decoration(member_info.iter_decoration(), built_in)
+            })
+            .map(|(_, &member_type)| float_array_length(spirv, member_type)),
+        _ => None,
+    }
+}
+
+// Returns the length of the innermost array in the type `id`. Outer arrays are the per-vertex
+// arrays of tessellation and geometry shaders.
+fn float_array_length(spirv: &Spirv, id: Id) -> u32 {
+    match spirv.id(id).instruction() {
+        &Instruction::TypePointer { ty, .. } => float_array_length(spirv, ty),
+        &Instruction::TypeArray {
+            element_type,
+            length,
+            ..
+        } => match spirv.id(element_type).instruction() {
+            Instruction::TypeArray { .. } => float_array_length(spirv, element_type),
+            _ => match spirv.id(length).instruction() {
+                Instruction::Constant { value, .. } => value[0],
+                _ => panic!("failed to find array length"),
+            },
+        },
+        _ => 1,
+    }
+}
+
 /// Returns true if a `BuiltIn` decorator is applied on an id.
 fn is_builtin(spirv: &Spirv, id: Id) -> bool {
     let id_info = spirv.id(id);
@@ -1353,3 +1572,125 @@ fn is_builtin(spirv: &Spirv, id: Id) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+    #version 450
+
+    layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+    layout(constant_id = 83) const int VALUE = 0xdeadbeef;
+
+    layout(set = 0, binding = 0) buffer Output {
+        int write;
+    } write;
+
+    void main() {
+        write.write = VALUE;
+    }
+    */
+    const MODULE: [u32; 120] = [
+        0x07230203, 0x00010000, 0x00080001, 0x0000000e, 0x00000000, 0x00020011, 0x00000001,
+        0x0006000b, 0x00000001, 0x4c534c47, 0x6474732e, 0x3035342e, 0x00000000, 0x0003000e,
+        0x00000000, 0x00000001, 0x0005000f, 0x00000005, 0x00000004, 0x6e69616d, 0x00000000,
+        0x00060010, 0x00000004, 0x00000011, 0x00000001, 0x00000001, 0x00000001, 0x00030003,
+        0x00000002, 0x000001c2, 0x00040005, 0x00000004, 0x6e69616d, 0x00000000, 0x00040005,
+        0x00000007, 0x7074754f, 0x00007475, 0x00050006, 0x00000007, 0x00000000, 0x74697277,
+        0x00000065, 0x00040005, 0x00000009, 0x74697277, 0x00000065, 0x00040005, 0x0000000b,
+        0x554c4156, 0x00000045, 0x00050048, 0x00000007, 0x00000000, 0x00000023, 0x00000000,
+        0x00030047, 0x00000007, 0x00000003, 0x00040047, 0x00000009, 0x00000022, 0x00000000,
+        0x00040047, 0x00000009, 0x00000021, 0x00000000, 0x00040047, 0x0000000b, 0x00000001,
+        0x00000053, 0x00020013, 0x00000002, 0x00030021, 0x00000003, 0x00000002, 0x00040015,
+        0x00000006, 0x00000020, 0x00000001, 0x0003001e, 0x00000007, 0x00000006, 0x00040020,
+        0x00000008, 0x00000002, 0x00000007, 0x0004003b, 0x00000008, 0x00000009, 0x00000002,
+        0x0004002b, 0x00000006, 0x0000000a, 0x00000000, 0x00040032, 0x00000006, 0x0000000b,
+        0xdeadbeef, 0x00040020, 0x0000000c, 0x00000002, 0x00000006, 0x00050036, 0x00000002,
+        0x00000004, 0x00000000, 0x00000003, 0x000200f8, 0x00000005, 0x00050041, 0x0000000c,
+        0x0000000d, 0x00000009, 0x0000000a, 0x0003003e, 0x0000000d, 0x0000000b, 0x000100fd,
+        0x00010038,
+    ];
+
+    #[test]
+    fn descriptor_names() {
+        let spirv = Spirv::new(&MODULE).unwrap();
+        let (_, _, info) = entry_points(&spirv).next().unwrap();
+
+        assert_eq!(
+            info.descriptor_requirements[&(0, 0)].name.as_deref(),
+            Some("write")
+        );
+    }
+
+    /*
+    OpCapability Shader
+    OpCapability ClipDistance
+    OpMemoryModel Logical GLSL450
+    OpEntryPoint Vertex %main "main" %per_vertex
+    OpMemberDecorate %PerVertex 0 BuiltIn Position
+    OpMemberDecorate %PerVertex 1 BuiltIn ClipDistance
+    OpMemberDecorate %PerVertex 2 BuiltIn CullDistance
+    OpDecorate %PerVertex Block
+    ...
+    %PerVertex = OpTypeStruct %v4float %_arr_float_uint_3 %_arr_float_uint_1
+    %per_vertex = OpVariable %_ptr_Output_PerVertex Output
+    */
+    const CLIP_DISTANCE_MODULE: [u32; 90] = [
+        0x07230203, 0x00010000, 0x00000000, 0x0000000f, 0x00000000, 0x00020011, 0x00000001,
+        0x00020011, 0x00000020, 0x0003000e, 0x00000000, 0x00000001, 0x0006000f, 0x00000000,
+        0x0000000d, 0x6e69616d, 0x00000000, 0x0000000c, 0x00050048, 0x0000000a, 0x00000000,
+        0x0000000b, 0x00000000, 0x00050048, 0x0000000a, 0x00000001, 0x0000000b, 0x00000003,
+        0x00050048, 0x0000000a, 0x00000002, 0x0000000b, 0x00000004, 0x00030047, 0x0000000a,
+        0x00000002, 0x00020013, 0x00000001, 0x00030021, 0x00000002, 0x00000001, 0x00030016,
+        0x00000003, 0x00000020, 0x00040017, 0x00000004, 0x00000003, 0x00000004, 0x00040015,
+        0x00000005, 0x00000020, 0x00000000, 0x0004002b, 0x00000005, 0x00000006, 0x00000003,
+        0x0004002b, 0x00000005, 0x00000007, 0x00000001, 0x0004001c, 0x00000008, 0x00000003,
+        0x00000006, 0x0004001c, 0x00000009, 0x00000003, 0x00000007, 0x0005001e, 0x0000000a,
+        0x00000004, 0x00000008, 0x00000009, 0x00040020, 0x0000000b, 0x00000003, 0x0000000a,
+        0x0004003b, 0x0000000b, 0x0000000c, 0x00000003, 0x00050036, 0x00000001, 0x0000000d,
+        0x00000000, 0x00000002, 0x000200f8, 0x0000000e, 0x000100fd, 0x00010038,
+    ];
+
+    #[test]
+    fn clip_and_cull_distances() {
+        let spirv = Spirv::new(&CLIP_DISTANCE_MODULE).unwrap();
+        let (_, _, info) = entry_points(&spirv).next().unwrap();
+
+        assert_eq!(info.clip_distance_array_size, 3);
+        // The `CullDistance` capability is not declared, so the builtin is unused.
+        assert_eq!(info.cull_distance_array_size, 0);
+    }
+
+    // Two compute entry points, `a` and `b`, where only `a` loads from the push constant block.
+    const PUSH_CONSTANT_MODULE: [u32; 97] = [
+        0x07230203, 0x00010000, 0x00000000, 0x00000010, 0x00000000, 0x00020011, 0x00000001,
+        0x0003000e, 0x00000000, 0x00000001, 0x0004000f, 0x00000005, 0x0000000a, 0x00000061,
+        0x0004000f, 0x00000005, 0x0000000e, 0x00000062, 0x00060010, 0x0000000a, 0x00000011,
+        0x00000001, 0x00000001, 0x00000001, 0x00060010, 0x0000000e, 0x00000011, 0x00000001,
+        0x00000001, 0x00000001, 0x00030047, 0x00000004, 0x00000002, 0x00050048, 0x00000004,
+        0x00000000, 0x00000023, 0x00000000, 0x00020013, 0x00000001, 0x00030021, 0x00000002,
+        0x00000001, 0x00040015, 0x00000003, 0x00000020, 0x00000000, 0x0003001e, 0x00000004,
+        0x00000003, 0x00040020, 0x00000005, 0x00000009, 0x00000004, 0x0004003b, 0x00000005,
+        0x00000006, 0x00000009, 0x00040015, 0x00000007, 0x00000020, 0x00000001, 0x0004002b,
+        0x00000007, 0x00000008, 0x00000000, 0x00040020, 0x00000009, 0x00000009, 0x00000003,
+        0x00050036, 0x00000001, 0x0000000a, 0x00000000, 0x00000002, 0x000200f8, 0x0000000b,
+        0x00050041, 0x00000009, 0x0000000c, 0x00000006, 0x00000008, 0x0004003d, 0x00000003,
+        0x0000000d, 0x0000000c, 0x000100fd, 0x00010038, 0x00050036, 0x00000001, 0x0000000e,
+        0x00000000, 0x00000002, 0x000200f8, 0x0000000f, 0x000100fd, 0x00010038,
+    ];
+
+    #[test]
+    fn push_constants_per_entry_point() {
+        let spirv = Spirv::new(&PUSH_CONSTANT_MODULE).unwrap();
+        let entry_points: HashMap<_, _> = entry_points(&spirv)
+            .map(|(name, _, info)| (name, info))
+            .collect();
+
+        let push_constant_range = entry_points["a"].push_constant_requirements.unwrap();
+        assert_eq!(push_constant_range.offset, 0);
+        assert_eq!(push_constant_range.size, 4);
+        assert!(entry_points["b"].push_constant_requirements.is_none());
+    }
+}
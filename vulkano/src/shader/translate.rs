@@ -0,0 +1,316 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Utilities for inspecting and translating SPIR-V code.
+//!
+//! These are mostly useful for debugging: looking at the disassembly of a shader can help to
+//! understand why reflection produced an unexpected result, and translating a shader into another
+//! language can help when porting pipelines to a different backend.
+//!
+//! Disassembly requires the `rspirv` feature to be enabled, and cross-compilation requires the
+//! `naga` feature.
+
+use crate::shader::spirv::ExecutionModel;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+/// Disassembles SPIR-V code into its textual representation.
+#[cfg(feature = "rspirv")]
+pub fn disassemble(words: &[u32]) -> Result<String, TranslationError> {
+    use rspirv::binary::Disassemble;
+
+    let module = rspirv::dr::load_words(words)
+        .map_err(|err| TranslationError::ParseError(err.to_string()))?;
+
+    Ok(module.disassemble())
+}
+
+/// Translates an entry point of SPIR-V code into source code of another shading language.
+///
+/// Only the entry point with the given name and execution model is kept in the output.
+#[cfg(feature = "naga")]
+pub fn cross_compile(
+    words: &[u32],
+    entry_point: &str,
+    execution_model: ExecutionModel,
+    target: TargetLanguage,
+) -> Result<String, TranslationError> {
+    let stage = match execution_model {
+        ExecutionModel::Vertex => naga::ShaderStage::Vertex,
+        ExecutionModel::Fragment => naga::ShaderStage::Fragment,
+        ExecutionModel::GLCompute => naga::ShaderStage::Compute,
+        _ => {
+            return Err(TranslationError::ExecutionModelNotSupported(
+                execution_model,
+            ))
+        }
+    };
+
+    let mut module = naga::front::spv::Parser::new(
+        words.iter().copied(),
+        &naga::front::spv::Options {
+            adjust_coordinate_space: false,
+            ..Default::default()
+        },
+    )
+    .parse()
+    .map_err(|err| TranslationError::ParseError(err.to_string()))?;
+
+    module
+        .entry_points
+        .retain(|ep| ep.name == entry_point && ep.stage == stage);
+
+    if module.entry_points.is_empty() {
+        return Err(TranslationError::EntryPointNotFound);
+    }
+
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|err| TranslationError::ValidationError(err.into_inner().to_string()))?;
+
+    match target {
+        TargetLanguage::Glsl { version, es } => {
+            let version = if es {
+                naga::back::glsl::Version::new_gles(version)
+            } else {
+                naga::back::glsl::Version::Desktop(version)
+            };
+            let options = naga::back::glsl::Options {
+                version,
+                writer_flags: naga::back::glsl::WriterFlags::empty(),
+                ..Default::default()
+            };
+            let pipeline_options = naga::back::glsl::PipelineOptions {
+                shader_stage: stage,
+                entry_point: entry_point.to_owned(),
+                multiview: None,
+            };
+
+            let mut output = String::new();
+            naga::back::glsl::Writer::new(
+                &mut output,
+                &module,
+                &info,
+                &options,
+                &pipeline_options,
+                Default::default(),
+            )
+            .and_then(|mut writer| writer.write())
+            .map_err(|err| TranslationError::WriteError(err.to_string()))?;
+
+            Ok(output)
+        }
+        TargetLanguage::Msl { version } => {
+            let options = naga::back::msl::Options {
+                lang_version: version,
+                ..Default::default()
+            };
+            let pipeline_options = naga::back::msl::PipelineOptions {
+                allow_point_size: true,
+            };
+
+            naga::back::msl::write_string(&module, &info, &options, &pipeline_options)
+                .map(|(output, _)| output)
+                .map_err(|err| TranslationError::WriteError(err.to_string()))
+        }
+        TargetLanguage::Hlsl { shader_model } => {
+            let shader_model = match shader_model {
+                (5, 0) => naga::back::hlsl::ShaderModel::V5_0,
+                (5, 1) => naga::back::hlsl::ShaderModel::V5_1,
+                (6, 0) => naga::back::hlsl::ShaderModel::V6_0,
+                _ => return Err(TranslationError::TargetVersionNotSupported),
+            };
+            let options = naga::back::hlsl::Options {
+                shader_model,
+                ..Default::default()
+            };
+
+            let mut output = String::new();
+            naga::back::hlsl::Writer::new(&mut output, &options)
+                .write(&module, &info)
+                .map_err(|err| TranslationError::WriteError(err.to_string()))?;
+
+            Ok(output)
+        }
+    }
+}
+
+/// A shading language that SPIR-V code can be translated into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TargetLanguage {
+    /// GLSL, for OpenGL or OpenGL ES if `es` is true.
+    ///
+    /// `version` is the GLSL version number as it appears in the `#version` directive, such as
+    /// `450` or `310`.
+    Glsl { version: u16, es: bool },
+
+    /// Metal Shading Language, with `version` given as `(major, minor)`.
+    Msl { version: (u8, u8) },
+
+    /// HLSL, for the given shader model given as `(major, minor)`. Supported shader models are
+    /// 5.0, 5.1 and 6.0.
+    Hlsl { shader_model: (u8, u8) },
+}
+
+/// Error that can happen when disassembling or translating SPIR-V code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranslationError {
+    /// The SPIR-V code could not be parsed.
+    ParseError(String),
+
+    /// The SPIR-V code was parsed, but was rejected by the translator's validation.
+    ValidationError(String),
+
+    /// No entry point with the requested name and execution model exists in the code.
+    EntryPointNotFound,
+
+    /// The execution model of the requested entry point can't be translated.
+    ExecutionModelNotSupported(ExecutionModel),
+
+    /// The requested version of the target language is not supported.
+    TargetVersionNotSupported,
+
+    /// Writing the translated code failed.
+    WriteError(String),
+}
+
+impl Error for TranslationError {}
+
+impl Display for TranslationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ParseError(msg) => write!(f, "the SPIR-V code could not be parsed: {}", msg),
+            Self::ValidationError(msg) => write!(f, "the SPIR-V code is not valid: {}", msg),
+            Self::EntryPointNotFound => write!(
+                f,
+                "no entry point with the requested name and execution model exists",
+            ),
+            Self::ExecutionModelNotSupported(execution_model) => write!(
+                f,
+                "the execution model {:?} is not supported by the translator",
+                execution_model,
+            ),
+            Self::TargetVersionNotSupported => write!(
+                f,
+                "the requested version of the target language is not supported",
+            ),
+            Self::WriteError(msg) => write!(f, "the translated code could not be written: {}", msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+    #version 450
+
+    layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+    layout(constant_id = 83) const int VALUE = 0xdeadbeef;
+
+    layout(set = 0, binding = 0) buffer Output {
+        int write;
+    } write;
+
+    void main() {
+        write.write = VALUE;
+    }
+    */
+    const MODULE: [u32; 120] = [
+        0x07230203, 0x00010000, 0x00080001, 0x0000000e, 0x00000000, 0x00020011, 0x00000001,
+        0x0006000b, 0x00000001, 0x4c534c47, 0x6474732e, 0x3035342e, 0x00000000, 0x0003000e,
+        0x00000000, 0x00000001, 0x0005000f, 0x00000005, 0x00000004, 0x6e69616d, 0x00000000,
+        0x00060010, 0x00000004, 0x00000011, 0x00000001, 0x00000001, 0x00000001, 0x00030003,
+        0x00000002, 0x000001c2, 0x00040005, 0x00000004, 0x6e69616d, 0x00000000, 0x00040005,
+        0x00000007, 0x7074754f, 0x00007475, 0x00050006, 0x00000007, 0x00000000, 0x74697277,
+        0x00000065, 0x00040005, 0x00000009, 0x74697277, 0x00000065, 0x00040005, 0x0000000b,
+        0x554c4156, 0x00000045, 0x00050048, 0x00000007, 0x00000000, 0x00000023, 0x00000000,
+        0x00030047, 0x00000007, 0x00000003, 0x00040047, 0x00000009, 0x00000022, 0x00000000,
+        0x00040047, 0x00000009, 0x00000021, 0x00000000, 0x00040047, 0x0000000b, 0x00000001,
+        0x00000053, 0x00020013, 0x00000002, 0x00030021, 0x00000003, 0x00000002, 0x00040015,
+        0x00000006, 0x00000020, 0x00000001, 0x0003001e, 0x00000007, 0x00000006, 0x00040020,
+        0x00000008, 0x00000002, 0x00000007, 0x0004003b, 0x00000008, 0x00000009, 0x00000002,
+        0x0004002b, 0x00000006, 0x0000000a, 0x00000000, 0x00040032, 0x00000006, 0x0000000b,
+        0xdeadbeef, 0x00040020, 0x0000000c, 0x00000002, 0x00000006, 0x00050036, 0x00000002,
+        0x00000004, 0x00000000, 0x00000003, 0x000200f8, 0x00000005, 0x00050041, 0x0000000c,
+        0x0000000d, 0x00000009, 0x0000000a, 0x0003003e, 0x0000000d, 0x0000000b, 0x000100fd,
+        0x00010038,
+    ];
+
+    #[test]
+    #[cfg(feature = "rspirv")]
+    fn disassemble_compute() {
+        let text = disassemble(&MODULE).unwrap();
+        assert!(text.contains("OpEntryPoint GLCompute"));
+        assert!(text.contains("SpecId 83"));
+    }
+
+    #[test]
+    #[cfg(feature = "rspirv")]
+    fn disassemble_invalid() {
+        assert!(matches!(
+            disassemble(&[0xdeadbeef]),
+            Err(TranslationError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "naga")]
+    fn cross_compile_compute() {
+        for target in [
+            TargetLanguage::Glsl {
+                version: 450,
+                es: false,
+            },
+            TargetLanguage::Glsl {
+                version: 310,
+                es: true,
+            },
+            TargetLanguage::Msl { version: (2, 0) },
+            TargetLanguage::Hlsl {
+                shader_model: (5, 1),
+            },
+        ] {
+            let source = cross_compile(&MODULE, "main", ExecutionModel::GLCompute, target).unwrap();
+            assert!(!source.is_empty());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "naga")]
+    fn cross_compile_wrong_entry_point() {
+        assert_eq!(
+            cross_compile(
+                &MODULE,
+                "main",
+                ExecutionModel::Fragment,
+                TargetLanguage::Msl { version: (2, 0) },
+            ),
+            Err(TranslationError::EntryPointNotFound)
+        );
+        assert_eq!(
+            cross_compile(
+                &MODULE,
+                "main",
+                ExecutionModel::Geometry,
+                TargetLanguage::Msl { version: (2, 0) },
+            ),
+            Err(TranslationError::ExecutionModelNotSupported(
+                ExecutionModel::Geometry
+            ))
+        );
+    }
+}
@@ -9,42 +9,64 @@
 
 //! Allows you to create surfaces that fill a whole display, outside of the windowing system.
 //!
-//! **As far as the author knows, no existing device supports these features. Therefore the code
-//! here is mostly a draft and needs rework in both the API and the implementation.**
+//! This is useful for embedded and kiosk systems that have no windowing system, and requires the
+//! [`khr_display`](crate::instance::InstanceExtensions::khr_display) extension to be enabled on
+//! the instance.
 //!
 //! The purpose of the objects in this module is to let you create a `Surface` object that
 //! represents a location on the screen. This is done in four steps:
 //!
 //! - Choose a `Display` where the surface will be located. A `Display` represents a display
-//!   display, usually a monitor. The available displays can be enumerated with
+//!   device, usually a monitor. The available displays can be enumerated with
 //!   `Display::enumerate`.
 //! - Choose a `DisplayMode`, which is the combination of a display, a resolution and a refresh
 //!   rate. You can enumerate the modes available on a display with `Display::display_modes`, or
-//!   attempt to create your own mode with `TODO`.
-//! - Choose a `DisplayPlane`. A display can show multiple planes in a stacking fashion.
-//! - Create a `Surface` object with `Surface::from_display_plane` and pass the chosen `DisplayMode`
-//!   and `DisplayPlane`.
-
-#![allow(dead_code)] // TODO: this module isn't finished
-#![allow(unused_variables)] // TODO: this module isn't finished
+//!   attempt to create your own mode with `DisplayMode::new`.
+//! - Choose a `DisplayPlane`. A display can show multiple planes in a stacking fashion. The
+//!   planes that can be used with a display are those for which `DisplayPlane::supports` returns
+//!   `true`.
+//! - Create a `Surface` object with `Surface::from_display_mode` and pass the chosen
+//!   `DisplayMode` and `DisplayPlane`.
+//!
+//! ```no_run
+//! use vulkano::swapchain::display::{Display, DisplayPlane};
+//! use vulkano::swapchain::{DisplaySurfaceCreateInfo, Surface};
+//!
+//! # let physical_device: vulkano::device::physical::PhysicalDevice = return;
+//! let display = Display::enumerate(physical_device).next().unwrap();
+//! let display_mode = display.display_modes().next().unwrap();
+//! let plane = DisplayPlane::enumerate(physical_device)
+//!     .find(|plane| plane.supports(&display))
+//!     .unwrap();
+//!
+//! let surface = Surface::from_display_mode(
+//!     &display_mode,
+//!     &plane,
+//!     DisplaySurfaceCreateInfo::default(),
+//! )
+//! .unwrap();
+//! ```
 
 use crate::check_errors;
 use crate::device::physical::PhysicalDevice;
 use crate::instance::Instance;
 use crate::swapchain::SupportedSurfaceTransforms;
+use crate::Error;
 use crate::OomError;
 use crate::Success;
 use crate::VulkanObject;
+use std::error;
 use std::ffi::CStr;
 use std::fmt::Formatter;
+use std::mem::MaybeUninit;
 use std::sync::Arc;
 use std::vec::IntoIter;
 use std::{fmt, ptr};
 
-// TODO: extract this to a `display` module and solve the visibility problems
-
-/// ?
-// TODO: plane capabilities
+/// A plane of a physical device, which can show a display mode of a display.
+///
+/// The images of the surfaces that are created with a plane are shown on the display by the
+/// plane. When a display shows multiple planes, they are stacked according to their stack index.
 // TODO: store properties in the instance?
 pub struct DisplayPlane {
     instance: Arc<Instance>,
@@ -56,10 +78,15 @@ pub struct DisplayPlane {
 
 impl DisplayPlane {
     /// See the docs of enumerate().
-    pub fn enumerate_raw(device: PhysicalDevice) -> Result<IntoIter<DisplayPlane>, OomError> {
-        let fns = device.instance().fns();
+    pub fn enumerate_raw(device: PhysicalDevice) -> Result<IntoIter<DisplayPlane>, DisplayError> {
+        if !device.instance().enabled_extensions().khr_display {
+            return Err(DisplayError::ExtensionNotEnabled {
+                extension: "khr_display",
+                reason: "enumerated the display planes of a physical device",
+            });
+        }
 
-        assert!(device.instance().enabled_extensions().khr_display); // TODO: return error instead
+        let fns = device.instance().fns();
 
         let display_plane_properties = unsafe {
             loop {
@@ -100,8 +127,7 @@ impl DisplayPlane {
                             index as u32,
                             &mut count,
                             ptr::null_mut(),
-                        ))
-                        .unwrap(); // TODO: shouldn't unwrap
+                        ))?;
 
                         let mut displays = Vec::with_capacity(count as usize);
                         let result = check_errors((fns
@@ -111,8 +137,7 @@ impl DisplayPlane {
                             index as u32,
                             &mut count,
                             displays.as_mut_ptr(),
-                        ))
-                        .unwrap(); // TODO: shouldn't unwrap
+                        ))?;
 
                         if !matches!(result, Success::Incomplete) {
                             displays.set_len(count as usize);
@@ -121,15 +146,15 @@ impl DisplayPlane {
                     }
                 };
 
-                DisplayPlane {
+                Ok(DisplayPlane {
                     instance: device.instance().clone(),
                     physical_device: device.index(),
                     index: index as u32,
                     properties: prop,
                     supported_displays,
-                }
+                })
             })
-            .collect::<Vec<_>>()
+            .collect::<Result<Vec<_>, DisplayError>>()?
             .into_iter())
     }
 
@@ -137,6 +162,7 @@ impl DisplayPlane {
     ///
     /// # Panic
     ///
+    /// - Panics if the `khr_display` extension is not enabled on the instance.
     /// - Panics if the device or host ran out of memory.
     ///
     // TODO: move iterator creation here from raw constructor?
@@ -157,6 +183,13 @@ impl DisplayPlane {
         self.index
     }
 
+    /// Returns the current stack index of the plane, which determines the order in which the
+    /// planes of a display are stacked.
+    #[inline]
+    pub fn current_stack_index(&self) -> u32 {
+        self.properties.current_stack_index
+    }
+
     /// Returns true if this plane supports the given display.
     #[inline]
     pub fn supports(&self, display: &Display) -> bool {
@@ -167,8 +200,160 @@ impl DisplayPlane {
 
         self.supported_displays
             .iter()
-            .find(|&&d| d == display.internal_object())
-            .is_some()
+            .any(|&d| d == display.internal_object())
+    }
+
+    /// Returns the capabilities of this plane when it's used with the given display mode.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `display_mode` and `self` don't belong to the same physical device.
+    pub fn capabilities(
+        &self,
+        display_mode: &DisplayMode,
+    ) -> Result<DisplayPlaneCapabilities, DisplayError> {
+        assert_eq!(
+            self.physical_device().internal_object(),
+            display_mode.display().physical_device().internal_object()
+        );
+
+        let capabilities = unsafe {
+            let fns = self.instance.fns();
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns.khr_display.get_display_plane_capabilities_khr)(
+                self.physical_device().internal_object(),
+                display_mode.internal_object(),
+                self.index,
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
+        };
+
+        let offset = |o: ash::vk::Offset2D| [o.x, o.y];
+        let extent = |e: ash::vk::Extent2D| [e.width, e.height];
+
+        Ok(DisplayPlaneCapabilities {
+            supported_alpha: capabilities.supported_alpha.into(),
+            min_src_position: offset(capabilities.min_src_position),
+            max_src_position: offset(capabilities.max_src_position),
+            min_src_extent: extent(capabilities.min_src_extent),
+            max_src_extent: extent(capabilities.max_src_extent),
+            min_dst_position: offset(capabilities.min_dst_position),
+            max_dst_position: offset(capabilities.max_dst_position),
+            min_dst_extent: extent(capabilities.min_dst_extent),
+            max_dst_extent: extent(capabilities.max_dst_extent),
+        })
+    }
+}
+
+/// The capabilities of a display plane when it's used with a display mode.
+///
+/// The source values describe the region of the presented images that can be shown by the plane,
+/// and the destination values describe the region of the display that the plane can cover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DisplayPlaneCapabilities {
+    /// The alpha modes that are supported by the plane.
+    pub supported_alpha: SupportedDisplayPlaneAlpha,
+    pub min_src_position: [i32; 2],
+    pub max_src_position: [i32; 2],
+    pub min_src_extent: [u32; 2],
+    pub max_src_extent: [u32; 2],
+    pub min_dst_position: [i32; 2],
+    pub max_dst_position: [i32; 2],
+    pub min_dst_extent: [u32; 2],
+    pub max_dst_extent: [u32; 2],
+}
+
+/// How the alpha values of the pixels of a display plane are used to blend it with the planes
+/// that are below it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum DisplayPlaneAlpha {
+    /// The plane is opaque, and the alpha channel of the image is ignored.
+    Opaque = ash::vk::DisplayPlaneAlphaFlagsKHR::OPAQUE.as_raw(),
+
+    /// The global alpha value given when creating the surface is used for all the pixels, and the
+    /// alpha channel of the image is ignored.
+    Global = ash::vk::DisplayPlaneAlphaFlagsKHR::GLOBAL.as_raw(),
+
+    /// The alpha channel of the image is respected. The color channels will be multiplied by the
+    /// alpha value before being blended.
+    PerPixel = ash::vk::DisplayPlaneAlphaFlagsKHR::PER_PIXEL.as_raw(),
+
+    /// The alpha channel of the image is respected. The color channels are expected to have
+    /// already been multiplied by the alpha value.
+    PerPixelPremultiplied = ash::vk::DisplayPlaneAlphaFlagsKHR::PER_PIXEL_PREMULTIPLIED.as_raw(),
+}
+
+impl From<DisplayPlaneAlpha> for ash::vk::DisplayPlaneAlphaFlagsKHR {
+    #[inline]
+    fn from(val: DisplayPlaneAlpha) -> Self {
+        Self::from_raw(val as u32)
+    }
+}
+
+/// List of supported display plane alpha modes.
+///
+/// See the docs of `DisplayPlaneAlpha`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SupportedDisplayPlaneAlpha {
+    pub opaque: bool,
+    pub global: bool,
+    pub per_pixel: bool,
+    pub per_pixel_premultiplied: bool,
+}
+
+impl From<ash::vk::DisplayPlaneAlphaFlagsKHR> for SupportedDisplayPlaneAlpha {
+    #[inline]
+    fn from(val: ash::vk::DisplayPlaneAlphaFlagsKHR) -> SupportedDisplayPlaneAlpha {
+        SupportedDisplayPlaneAlpha {
+            opaque: val.intersects(ash::vk::DisplayPlaneAlphaFlagsKHR::OPAQUE),
+            global: val.intersects(ash::vk::DisplayPlaneAlphaFlagsKHR::GLOBAL),
+            per_pixel: val.intersects(ash::vk::DisplayPlaneAlphaFlagsKHR::PER_PIXEL),
+            per_pixel_premultiplied: val
+                .intersects(ash::vk::DisplayPlaneAlphaFlagsKHR::PER_PIXEL_PREMULTIPLIED),
+        }
+    }
+}
+
+impl SupportedDisplayPlaneAlpha {
+    /// Builds a `SupportedDisplayPlaneAlpha` with all fields set to false.
+    #[inline]
+    pub fn none() -> SupportedDisplayPlaneAlpha {
+        SupportedDisplayPlaneAlpha {
+            opaque: false,
+            global: false,
+            per_pixel: false,
+            per_pixel_premultiplied: false,
+        }
+    }
+
+    /// Returns true if the given `DisplayPlaneAlpha` is in this list.
+    #[inline]
+    pub fn supports(&self, value: DisplayPlaneAlpha) -> bool {
+        match value {
+            DisplayPlaneAlpha::Opaque => self.opaque,
+            DisplayPlaneAlpha::Global => self.global,
+            DisplayPlaneAlpha::PerPixel => self.per_pixel,
+            DisplayPlaneAlpha::PerPixelPremultiplied => self.per_pixel_premultiplied,
+        }
+    }
+
+    /// Returns an iterator to the list of supported display plane alpha modes.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = DisplayPlaneAlpha> {
+        let moved = *self;
+        [
+            DisplayPlaneAlpha::Opaque,
+            DisplayPlaneAlpha::Global,
+            DisplayPlaneAlpha::PerPixel,
+            DisplayPlaneAlpha::PerPixelPremultiplied,
+        ]
+        .into_iter()
+        .filter(move |&mode| moved.supports(mode))
     }
 }
 
@@ -183,9 +368,15 @@ pub struct Display {
 
 impl Display {
     /// See the docs of enumerate().
-    pub fn enumerate_raw(device: PhysicalDevice) -> Result<IntoIter<Display>, OomError> {
+    pub fn enumerate_raw(device: PhysicalDevice) -> Result<IntoIter<Display>, DisplayError> {
+        if !device.instance().enabled_extensions().khr_display {
+            return Err(DisplayError::ExtensionNotEnabled {
+                extension: "khr_display",
+                reason: "enumerated the displays of a physical device",
+            });
+        }
+
         let fns = device.instance().fns();
-        assert!(device.instance().enabled_extensions().khr_display); // TODO: return error instead
 
         let display_properties = unsafe {
             loop {
@@ -229,6 +420,7 @@ impl Display {
     ///
     /// # Panic
     ///
+    /// - Panics if the `khr_display` extension is not enabled on the instance.
     /// - Panics if the device or host ran out of memory.
     ///
     // TODO: move iterator creation here from raw constructor?
@@ -256,7 +448,7 @@ impl Display {
     /// Returns the physical dimensions of the display in millimeters.
     #[inline]
     pub fn physical_dimensions(&self) -> [u32; 2] {
-        let ref r = self.properties.physical_dimensions;
+        let r = &self.properties.physical_dimensions;
         [r.width, r.height]
     }
 
@@ -266,7 +458,7 @@ impl Display {
     /// > only the "best" resolution.
     #[inline]
     pub fn physical_resolution(&self) -> [u32; 2] {
-        let ref r = self.properties.physical_resolution;
+        let r = &self.properties.physical_resolution;
         [r.width, r.height]
     }
 
@@ -276,20 +468,21 @@ impl Display {
         self.properties.supported_transforms.into()
     }
 
-    /// Returns true if TODO.
+    /// Returns true if the planes of this display can have their stack order changed.
     #[inline]
     pub fn plane_reorder_possible(&self) -> bool {
         self.properties.plane_reorder_possible != 0
     }
 
-    /// Returns true if TODO.
+    /// Returns true if the display can keep showing its content without the images being
+    /// presented again, for example with a self-refresh panel.
     #[inline]
     pub fn persistent_content(&self) -> bool {
         self.properties.persistent_content != 0
     }
 
     /// See the docs of display_modes().
-    pub fn display_modes_raw(&self) -> Result<IntoIter<DisplayMode>, OomError> {
+    pub fn display_modes_raw(&self) -> Result<IntoIter<DisplayMode>, DisplayError> {
         let fns = self.instance.fns();
 
         let mode_properties = unsafe {
@@ -360,35 +553,59 @@ pub struct DisplayMode {
 }
 
 impl DisplayMode {
-    /*pub fn new(display: &Display) -> Result<Arc<DisplayMode>, OomError> {
-        let fns = instance.fns();
-        assert!(device.instance().enabled_extensions().khr_display);     // TODO: return error instead
+    /// Creates a new display mode on `display`, with the given visible region and refresh rate.
+    ///
+    /// The refresh rate is in millihertz, like [`refresh_rate`](Self::refresh_rate). The display
+    /// mode is owned by the display, and remains valid as long as the instance exists.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if an element of `visible_region` is 0, or if `refresh_rate` is 0.
+    pub fn new(
+        display: &Display,
+        visible_region: [u32; 2],
+        refresh_rate: u32,
+    ) -> Result<DisplayMode, DisplayError> {
+        // VUID-VkDisplayModeParametersKHR-width-01990
+        // VUID-VkDisplayModeParametersKHR-height-01991
+        assert!(visible_region[0] != 0 && visible_region[1] != 0);
+
+        // VUID-VkDisplayModeParametersKHR-refreshRate-01992
+        assert!(refresh_rate != 0);
 
         let parameters = ash::vk::DisplayModeParametersKHR {
-            visibleRegion: ash::vk::Extent2D { width: , height:  },
-            refreshRate: ,
+            visible_region: ash::vk::Extent2D {
+                width: visible_region[0],
+                height: visible_region[1],
+            },
+            refresh_rate,
         };
 
-        let display_mode = {
-            let infos = ash::vk::DisplayModeCreateInfoKHR {
-                flags: ash::vk::DisplayModeCreateFlags::empty(),
-                parameters: parameters,
+        let display_mode = unsafe {
+            let create_info = ash::vk::DisplayModeCreateInfoKHR {
+                flags: ash::vk::DisplayModeCreateFlagsKHR::empty(),
+                parameters,
                 ..Default::default()
             };
 
-            let mut output = mem::uninitialized();
-            check_errors((fns.v1_0.CreateDisplayModeKHR)(display.device.internal_object(),
-                                                      display.display, &infos, ptr::null(),
-                                                      &mut output))?;
-            output
+            let fns = display.instance.fns();
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns.khr_display.create_display_mode_khr)(
+                display.physical_device().internal_object(),
+                display.internal_object(),
+                &create_info,
+                ptr::null(),
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
         };
 
-        Ok(Arc::new(DisplayMode {
-            instance: display.device.instance().clone(),
-            display_mode: display_mode,
-            parameters: ,
-        }))
-    }*/
+        Ok(DisplayMode {
+            display: display.clone(),
+            display_mode,
+            parameters,
+        })
+    }
 
     /// Returns the display corresponding to this mode.
     #[inline]
@@ -399,7 +616,7 @@ impl DisplayMode {
     /// Returns the dimensions of the region that is visible on the monitor.
     #[inline]
     pub fn visible_region(&self) -> [u32; 2] {
-        let ref d = self.parameters.visible_region;
+        let d = &self.parameters.visible_region;
         [d.width, d.height]
     }
 
@@ -437,3 +654,65 @@ unsafe impl VulkanObject for DisplayMode {
         self.display_mode
     }
 }
+
+/// Error that can happen when querying or creating display objects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisplayError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The display mode could not be created with the given parameters.
+    InitializationFailed,
+
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+}
+
+impl error::Error for DisplayError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DisplayError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::InitializationFailed => write!(
+                fmt,
+                "the display mode could not be created with the given parameters",
+            ),
+            Self::ExtensionNotEnabled { extension, reason } => write!(
+                fmt,
+                "the extension {} must be enabled: {}",
+                extension, reason
+            ),
+        }
+    }
+}
+
+impl From<OomError> for DisplayError {
+    #[inline]
+    fn from(err: OomError) -> DisplayError {
+        Self::OomError(err)
+    }
+}
+
+impl From<Error> for DisplayError {
+    #[inline]
+    fn from(err: Error) -> DisplayError {
+        match err {
+            err @ Error::OutOfHostMemory => Self::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => Self::OomError(OomError::from(err)),
+            Error::InitializationFailed => Self::InitializationFailed,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
@@ -0,0 +1,387 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use super::{
+    acquire_next_image, AcquireError, PresentFuture, Swapchain, SwapchainAcquireFuture,
+    SwapchainCreateInfo, SwapchainCreationError,
+};
+use crate::{
+    device::Queue,
+    image::{
+        view::{ImageView, ImageViewCreationError},
+        SwapchainImage,
+    },
+    sync::{FenceSignalFuture, FlushError, GpuFuture},
+    OomError,
+};
+use std::{error, fmt, sync::Arc, time::Duration};
+
+/// The future of a presentation that was submitted through a `SwapchainManager`.
+pub type ManagedPresentFuture<W> = Arc<FenceSignalFuture<PresentFuture<Box<dyn GpuFuture>, W>>>;
+
+/// Keeps a swapchain and views of its images up to date with the surface.
+///
+/// Presenting to a window requires recreating the swapchain whenever acquiring or presenting
+/// reports that it is out of date or suboptimal, or when the window is resized. The manager takes
+/// care of this: `acquire` recreates the swapchain when needed, passing the old swapchain as
+/// `old_swapchain`, rebuilds the image views, and reports the new extent so that size-dependent
+/// resources can be recreated as well.
+///
+/// A swapchain that was replaced may still have presentations in flight. The manager keeps it and
+/// its image views alive until the presentations that were submitted through
+/// `SwapchainManager::present` have completed, and only then drops them.
+///
+/// # Example
+///
+/// ```
+/// use vulkano::swapchain::SwapchainManager;
+/// use vulkano::sync::GpuFuture;
+///
+/// # let swapchain: ::std::sync::Arc<::vulkano::swapchain::Swapchain<()>> = return;
+/// # let images: Vec<::std::sync::Arc<::vulkano::image::SwapchainImage<()>>> = return;
+/// # let queue: ::std::sync::Arc<::vulkano::device::Queue> = return;
+/// # let window_size = || [1024, 768];
+/// let mut manager = SwapchainManager::new(swapchain, images).unwrap();
+///
+/// loop {
+///     let frame = match manager.acquire(window_size(), None) {
+///         Ok(frame) => frame,
+///         Err(_) => continue,
+///     };
+///
+///     if let Some(extent) = frame.recreated {
+///         // Recreate the framebuffers and the resources that depend on `extent`.
+///     }
+///
+///     let future = frame.future;
+///     // .then_execute(...) rendering to `frame.image_view`
+///
+///     manager
+///         .present(future.boxed(), queue.clone(), frame.image_index)
+///         .ok();
+/// }
+/// ```
+pub struct SwapchainManager<W> {
+    swapchain: Arc<Swapchain<W>>,
+    image_views: Vec<Arc<ImageView<SwapchainImage<W>>>>,
+    needs_recreate: bool,
+
+    // The presentations of the current swapchain that may not have completed yet.
+    presents: Vec<ManagedPresentFuture<W>>,
+    // The swapchains that were replaced, and their presentations that may not have completed yet.
+    retired: Vec<RetiredSwapchain<W>>,
+}
+
+struct RetiredSwapchain<W> {
+    _swapchain: Arc<Swapchain<W>>,
+    _image_views: Vec<Arc<ImageView<SwapchainImage<W>>>>,
+    presents: Vec<ManagedPresentFuture<W>>,
+}
+
+impl<W> SwapchainManager<W>
+where
+    W: Send + Sync + 'static,
+{
+    /// Creates a manager for a swapchain that was just created, and creates default views of its
+    /// images.
+    pub fn new(
+        swapchain: Arc<Swapchain<W>>,
+        images: Vec<Arc<SwapchainImage<W>>>,
+    ) -> Result<Self, ImageViewCreationError> {
+        Ok(SwapchainManager {
+            swapchain,
+            image_views: create_image_views(images)?,
+            needs_recreate: false,
+            presents: Vec::new(),
+            retired: Vec::new(),
+        })
+    }
+
+    /// Returns the current swapchain.
+    #[inline]
+    pub fn swapchain(&self) -> &Arc<Swapchain<W>> {
+        &self.swapchain
+    }
+
+    /// Returns the views of the images of the current swapchain, in the order of their indices.
+    #[inline]
+    pub fn image_views(&self) -> &[Arc<ImageView<SwapchainImage<W>>>] {
+        &self.image_views
+    }
+
+    /// Returns the extent of the images of the current swapchain.
+    #[inline]
+    pub fn image_extent(&self) -> [u32; 2] {
+        self.swapchain.image_extent()
+    }
+
+    /// Returns whether the swapchain will be recreated on the next call to `acquire`.
+    #[inline]
+    pub fn needs_recreate(&self) -> bool {
+        self.needs_recreate
+    }
+
+    /// Forces the swapchain to be recreated on the next call to `acquire`, for example because
+    /// the present mode was changed.
+    #[inline]
+    pub fn set_needs_recreate(&mut self) {
+        self.needs_recreate = true;
+    }
+
+    /// Recreates the swapchain with the parameters returned by `f`, which is given the current
+    /// parameters.
+    ///
+    /// The current swapchain is passed as `old_swapchain`, and it's kept alive until its
+    /// presentations have completed.
+    pub fn recreate(
+        &mut self,
+        f: impl FnOnce(SwapchainCreateInfo) -> SwapchainCreateInfo,
+    ) -> Result<[u32; 2], SwapchainManagerError> {
+        let (swapchain, images) = self.swapchain.recreate(f(self.swapchain.create_info()))?;
+        let image_views = create_image_views(images)?;
+
+        let old_swapchain = std::mem::replace(&mut self.swapchain, swapchain);
+        let old_image_views = std::mem::replace(&mut self.image_views, image_views);
+        self.retired.push(RetiredSwapchain {
+            _swapchain: old_swapchain,
+            _image_views: old_image_views,
+            presents: std::mem::take(&mut self.presents),
+        });
+        self.needs_recreate = false;
+
+        Ok(self.swapchain.image_extent())
+    }
+
+    /// Acquires the next image of the swapchain.
+    ///
+    /// `image_extent` is the current size of the surface, usually the inner size of the window.
+    /// The swapchain is recreated first if it doesn't have that size, if it was reported out of
+    /// date or suboptimal, or if `set_needs_recreate` was called. If acquiring reports that the
+    /// swapchain is out of date, it is recreated and acquiring is tried once more.
+    ///
+    /// Returns `SwapchainCreationError::ImageExtentNotSupported` if the surface can't be presented
+    /// to with this extent, for example when the window is minimized. Skip the frame in that case.
+    pub fn acquire(
+        &mut self,
+        image_extent: [u32; 2],
+        timeout: Option<Duration>,
+    ) -> Result<ManagedFrame<W>, SwapchainManagerError> {
+        self.cleanup_finished()?;
+
+        let mut recreated = None;
+
+        for attempt in 0..2 {
+            if self.needs_recreate || self.swapchain.image_extent() != image_extent {
+                recreated = Some(self.recreate(|create_info| SwapchainCreateInfo {
+                    image_extent,
+                    ..create_info
+                })?);
+            }
+
+            match acquire_next_image(self.swapchain.clone(), timeout) {
+                Ok((image_index, suboptimal, future)) => {
+                    if suboptimal {
+                        self.needs_recreate = true;
+                    }
+
+                    return Ok(ManagedFrame {
+                        image_index,
+                        image_view: self.image_views[image_index].clone(),
+                        future,
+                        recreated,
+                    });
+                }
+                Err(AcquireError::OutOfDate) if attempt == 0 => {
+                    self.needs_recreate = true;
+                }
+                Err(err) => {
+                    if err == AcquireError::OutOfDate {
+                        self.needs_recreate = true;
+                    }
+
+                    return Err(err.into());
+                }
+            }
+        }
+
+        unreachable!()
+    }
+
+    /// Presents an image of the current swapchain after `future`, and flushes.
+    ///
+    /// If presenting reports that the swapchain is out of date or suboptimal, it will be recreated
+    /// on the next call to `acquire`.
+    pub fn present(
+        &mut self,
+        future: Box<dyn GpuFuture>,
+        queue: Arc<Queue>,
+        image_index: usize,
+    ) -> Result<ManagedPresentFuture<W>, FlushError> {
+        let result = future
+            .then_swapchain_present(queue, self.swapchain.clone(), image_index)
+            .then_signal_fence_and_flush();
+
+        match result {
+            Ok(future) => {
+                let future = Arc::new(future);
+                self.presents.push(future.clone());
+                Ok(future)
+            }
+            Err(err) => {
+                if err == FlushError::OutOfDate {
+                    self.needs_recreate = true;
+                }
+
+                Err(err)
+            }
+        }
+    }
+
+    /// Drops the futures of the presentations that have completed, and the swapchains that were
+    /// replaced and no longer have presentations in flight.
+    ///
+    /// This is called automatically by `acquire`.
+    pub fn cleanup_finished(&mut self) -> Result<(), OomError> {
+        fn retain_pending<W>(presents: &mut Vec<ManagedPresentFuture<W>>) -> Result<(), OomError> {
+            let mut result = Ok(());
+            presents.retain(|future| match future.is_signaled() {
+                Ok(signaled) => !signaled,
+                Err(err) => {
+                    result = Err(err);
+                    true
+                }
+            });
+            result
+        }
+
+        retain_pending(&mut self.presents)?;
+
+        for retired in &mut self.retired {
+            retain_pending(&mut retired.presents)?;
+        }
+
+        self.retired.retain(|retired| !retired.presents.is_empty());
+
+        Ok(())
+    }
+
+    /// Returns the number of swapchains that were replaced, but are kept alive because their
+    /// presentations may not have completed yet.
+    #[inline]
+    pub fn retired_count(&self) -> usize {
+        self.retired.len()
+    }
+}
+
+fn create_image_views<W>(
+    images: Vec<Arc<SwapchainImage<W>>>,
+) -> Result<Vec<Arc<ImageView<SwapchainImage<W>>>>, ImageViewCreationError>
+where
+    W: Send + Sync + 'static,
+{
+    images.into_iter().map(ImageView::new_default).collect()
+}
+
+impl<W> fmt::Debug for SwapchainManager<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SwapchainManager")
+            .field("image_extent", &self.swapchain.image_extent())
+            .field("image_count", &self.image_views.len())
+            .field("needs_recreate", &self.needs_recreate)
+            .field("presents", &self.presents.len())
+            .field("retired", &self.retired.len())
+            .finish()
+    }
+}
+
+/// An image that was acquired by `SwapchainManager::acquire`.
+pub struct ManagedFrame<W> {
+    /// The index of the acquired image, to pass to `SwapchainManager::present`.
+    pub image_index: usize,
+
+    /// The view of the acquired image.
+    pub image_view: Arc<ImageView<SwapchainImage<W>>>,
+
+    /// The future that must be waited on before using the image.
+    pub future: SwapchainAcquireFuture<W>,
+
+    /// If the swapchain was recreated before acquiring, the extent of its new images. Resources
+    /// that depend on the swapchain, such as framebuffers, must be recreated.
+    pub recreated: Option<[u32; 2]>,
+}
+
+/// Error that can happen when acquiring an image with a `SwapchainManager`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SwapchainManagerError {
+    /// Acquiring the next image failed.
+    AcquireError(AcquireError),
+
+    /// Recreating the swapchain failed.
+    SwapchainCreationError(SwapchainCreationError),
+
+    /// Creating the views of the images of the recreated swapchain failed.
+    ImageViewCreationError(ImageViewCreationError),
+
+    /// Checking whether a presentation has completed failed.
+    OomError(OomError),
+}
+
+impl error::Error for SwapchainManagerError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::AcquireError(err) => Some(err),
+            Self::SwapchainCreationError(err) => Some(err),
+            Self::ImageViewCreationError(err) => Some(err),
+            Self::OomError(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for SwapchainManagerError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AcquireError(_) => write!(f, "acquiring the next image failed"),
+            Self::SwapchainCreationError(_) => write!(f, "recreating the swapchain failed"),
+            Self::ImageViewCreationError(_) => {
+                write!(f, "creating the views of the swapchain images failed")
+            }
+            Self::OomError(_) => write!(f, "not enough memory available"),
+        }
+    }
+}
+
+impl From<AcquireError> for SwapchainManagerError {
+    #[inline]
+    fn from(err: AcquireError) -> Self {
+        Self::AcquireError(err)
+    }
+}
+
+impl From<SwapchainCreationError> for SwapchainManagerError {
+    #[inline]
+    fn from(err: SwapchainCreationError) -> Self {
+        Self::SwapchainCreationError(err)
+    }
+}
+
+impl From<ImageViewCreationError> for SwapchainManagerError {
+    #[inline]
+    fn from(err: ImageViewCreationError) -> Self {
+        Self::ImageViewCreationError(err)
+    }
+}
+
+impl From<OomError> for SwapchainManagerError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
@@ -91,8 +91,8 @@
 //!
 //! ## Creating a surface from a monitor
 //!
-//! Currently no system provides the `VK_KHR_display` extension that contains this feature.
-//! This feature is still a work-in-progress in vulkano and will reside in the `display` module.
+//! On systems without a windowing system, a surface can be created that fills a whole display,
+//! with the `VK_KHR_display` extension. See the `display` module for more information.
 //!
 //! # Swapchains
 //!
@@ -221,6 +221,50 @@
 //! Creating a swapchain not only returns the swapchain object, but also all the images that belong
 //! to it.
 //!
+//! ## Viewing the images with another format
+//!
+//! By default, the images of a swapchain can only be viewed with the format of the swapchain. If
+//! the [`khr_swapchain_mutable_format`](crate::device::DeviceExtensions::khr_swapchain_mutable_format)
+//! extension is enabled, `SwapchainCreateInfo::image_view_formats` can list other compatible
+//! formats, for example to render both sRGB scene content and linear UI to the same image.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use smallvec::smallvec;
+//! # use vulkano::format::Format;
+//! # use vulkano::image::view::{ImageView, ImageViewCreateInfo};
+//! # use vulkano::swapchain::{Swapchain, SwapchainCreateInfo};
+//! # fn create_swapchain(
+//! #     device: Arc<vulkano::device::Device>, surface: Arc<vulkano::swapchain::Surface<()>>,
+//! #     create_info: SwapchainCreateInfo,
+//! # ) -> Result<(), Box<dyn std::error::Error>> {
+//! let (swapchain, images) = Swapchain::new(
+//!     device,
+//!     surface,
+//!     SwapchainCreateInfo {
+//!         image_format: Some(Format::B8G8R8A8_UNORM),
+//!         image_view_formats: smallvec![Format::B8G8R8A8_UNORM, Format::B8G8R8A8_SRGB],
+//!         ..create_info
+//!     },
+//! )?;
+//!
+//! for image in images {
+//!     // Writes linear values, for UI that was blended in sRGB space.
+//!     let linear_view = ImageView::new_default(image.clone())?;
+//!
+//!     // Converts linear values to sRGB when writing, for the scene.
+//!     let srgb_view = ImageView::new(
+//!         image.clone(),
+//!         ImageViewCreateInfo {
+//!             format: Some(Format::B8G8R8A8_SRGB),
+//!             ..ImageViewCreateInfo::from_image(&image)
+//!         },
+//!     )?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## Acquiring and presenting images
 //!
 //! Once you created a swapchain and retrieved all the images that belong to it (see previous
@@ -310,13 +354,63 @@
 //! }
 //! ```
 //!
+//! `SwapchainManager` does all of this automatically, and also keeps the old swapchain alive until
+//! the presentations that use it have completed.
+//!
+//! ## Frame pacing
+//!
+//! If the `present_id` and `present_wait` features are enabled on the device, each presentation
+//! can be given an increasing present ID, and `Swapchain::wait_for_present` can be used to wait
+//! until a presentation has actually been displayed. Waiting for the presentation of an earlier
+//! frame before starting to render the next one keeps the application from getting too far ahead
+//! of the display, which reduces latency.
+//!
+//! ```
+//! use std::time::Duration;
+//! use vulkano::swapchain;
+//! use vulkano::sync::GpuFuture;
+//!
+//! # let swapchain: ::std::sync::Arc<::vulkano::swapchain::Swapchain<()>> = return;
+//! # let queue: ::std::sync::Arc<::vulkano::device::Queue> = return;
+//! let mut present_id = 0;
+//!
+//! loop {
+//!     // Allow at most one frame to be queued for presentation.
+//!     if present_id > 1 {
+//!         swapchain
+//!             .wait_for_present(present_id - 1, Some(Duration::from_millis(100)))
+//!             .ok();
+//!     }
+//!
+//!     let (index, _, acq_future) = swapchain::acquire_next_image(swapchain.clone(), None).unwrap();
+//!
+//!     // ...
+//!
+//!     present_id += 1;
+//!     acq_future
+//!         // .then_execute(...)
+//!         .then_swapchain_present_with_id(queue.clone(), swapchain.clone(), index, present_id)
+//!         .then_signal_fence_and_flush()
+//!         .unwrap();
+//! }
+//! ```
+//!
 
+pub use self::manager::ManagedFrame;
+pub use self::manager::ManagedPresentFuture;
+pub use self::manager::SwapchainManager;
+pub use self::manager::SwapchainManagerError;
 pub use self::present_region::PresentRegion;
 pub use self::present_region::RectangleLayer;
 pub use self::surface::ColorSpace;
 pub use self::surface::CompositeAlpha;
+pub use self::surface::DisplaySurfaceCreateInfo;
+pub use self::surface::PresentGravity;
 pub use self::surface::PresentMode;
+pub use self::surface::PresentScaling;
 pub use self::surface::SupportedCompositeAlpha;
+pub use self::surface::SupportedPresentGravity;
+pub use self::surface::SupportedPresentScaling;
 pub use self::surface::SupportedSurfaceTransforms;
 pub use self::surface::Surface;
 pub use self::surface::SurfaceApi;
@@ -328,19 +422,26 @@ pub use self::swapchain::acquire_next_image;
 pub use self::swapchain::acquire_next_image_raw;
 pub use self::swapchain::present;
 pub use self::swapchain::present_incremental;
+pub use self::swapchain::present_with_id;
 pub use self::swapchain::AcquireError;
 pub use self::swapchain::AcquiredImage;
 pub use self::swapchain::FullScreenExclusive;
 pub use self::swapchain::FullScreenExclusiveError;
+pub use self::swapchain::HdrMetadata;
+pub use self::swapchain::HdrMetadataError;
 pub use self::swapchain::PresentFuture;
+pub use self::swapchain::PresentWaitError;
 pub use self::swapchain::Swapchain;
 pub use self::swapchain::SwapchainAcquireFuture;
 pub use self::swapchain::SwapchainCreateInfo;
 pub use self::swapchain::SwapchainCreationError;
+pub use self::swapchain::SwapchainPresentResult;
+pub use self::swapchain::SwapchainStatistics;
 pub use self::swapchain::Win32Monitor;
 use std::sync::atomic::AtomicBool;
 
 pub mod display;
+mod manager;
 mod present_region;
 mod surface;
 mod swapchain;
@@ -12,12 +12,15 @@ use super::Win32Monitor;
 use crate::check_errors;
 use crate::image::ImageUsage;
 use crate::instance::Instance;
+use crate::swapchain::display::DisplayError;
 use crate::swapchain::display::DisplayMode;
 use crate::swapchain::display::DisplayPlane;
+use crate::swapchain::display::DisplayPlaneAlpha;
 use crate::swapchain::SurfaceSwapchainLock;
 use crate::Error;
 use crate::OomError;
 use crate::VulkanObject;
+use smallvec::SmallVec;
 use std::error;
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -66,74 +69,6 @@ impl<W> Surface<W> {
         }
     }
 
-    /// Creates a `Surface` from a `DisplayPlane`.
-    ///
-    /// # Panic
-    ///
-    /// - Panics if `display_mode` and `plane` don't belong to the same physical device.
-    /// - Panics if `plane` doesn't support the display of `display_mode`.
-    pub fn from_display_plane(
-        display_mode: &DisplayMode,
-        plane: &DisplayPlane,
-    ) -> Result<Arc<Surface<()>>, SurfaceCreationError> {
-        if !display_mode
-            .display()
-            .physical_device()
-            .instance()
-            .enabled_extensions()
-            .khr_display
-        {
-            return Err(SurfaceCreationError::MissingExtension {
-                name: "VK_KHR_display",
-            });
-        }
-
-        assert_eq!(
-            display_mode.display().physical_device().internal_object(),
-            plane.physical_device().internal_object()
-        );
-        assert!(plane.supports(display_mode.display()));
-
-        let instance = display_mode.display().physical_device().instance();
-
-        let create_info = ash::vk::DisplaySurfaceCreateInfoKHR {
-            flags: ash::vk::DisplaySurfaceCreateFlagsKHR::empty(),
-            display_mode: display_mode.internal_object(),
-            plane_index: plane.index(),
-            plane_stack_index: 0, // FIXME: plane.properties.currentStackIndex,
-            transform: ash::vk::SurfaceTransformFlagsKHR::IDENTITY, // TODO: let user choose
-            global_alpha: 0.0,    // TODO: let user choose
-            alpha_mode: ash::vk::DisplayPlaneAlphaFlagsKHR::OPAQUE, // TODO: let user choose
-            image_extent: ash::vk::Extent2D {
-                // TODO: let user choose
-                width: display_mode.visible_region()[0],
-                height: display_mode.visible_region()[1],
-            },
-            ..Default::default()
-        };
-
-        let handle = unsafe {
-            let fns = instance.fns();
-            let mut output = MaybeUninit::uninit();
-            check_errors((fns.khr_display.create_display_plane_surface_khr)(
-                instance.internal_object(),
-                &create_info,
-                ptr::null(),
-                output.as_mut_ptr(),
-            ))?;
-            output.assume_init()
-        };
-
-        Ok(Arc::new(Surface {
-            handle,
-            instance: instance.clone(),
-            api: SurfaceApi::DisplayPlane,
-            window: (),
-
-            has_swapchain: AtomicBool::new(false),
-        }))
-    }
-
     /// Creates a `Surface` from an Android window.
     ///
     /// # Safety
@@ -647,6 +582,138 @@ impl<W> Hash for Surface<W> {
     }
 }
 
+impl Surface<()> {
+    /// Creates a `Surface` from a `DisplayPlane`.
+    ///
+    /// This is the same as [`Surface::from_display_mode`] with the default
+    /// [`DisplaySurfaceCreateInfo`].
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `display_mode` and `plane` don't belong to the same physical device.
+    /// - Panics if `plane` doesn't support the display of `display_mode`.
+    #[inline]
+    pub fn from_display_plane(
+        display_mode: &DisplayMode,
+        plane: &DisplayPlane,
+    ) -> Result<Arc<Surface<()>>, SurfaceCreationError> {
+        Surface::from_display_mode(display_mode, plane, DisplaySurfaceCreateInfo::default())
+    }
+
+    /// Creates a `Surface` that shows the presented images on `plane`, with the display mode
+    /// `display_mode`.
+    ///
+    /// The [`khr_display`](crate::instance::InstanceExtensions::khr_display) extension must be
+    /// enabled on the instance.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `display_mode` and `plane` don't belong to the same physical device.
+    /// - Panics if `plane` doesn't support the display of `display_mode`.
+    pub fn from_display_mode(
+        display_mode: &DisplayMode,
+        plane: &DisplayPlane,
+        create_info: DisplaySurfaceCreateInfo,
+    ) -> Result<Arc<Surface<()>>, SurfaceCreationError> {
+        let display = display_mode.display();
+        let physical_device = display.physical_device();
+        let instance = physical_device.instance();
+
+        if !instance.enabled_extensions().khr_display {
+            return Err(SurfaceCreationError::MissingExtension {
+                name: "VK_KHR_display",
+            });
+        }
+
+        assert_eq!(
+            physical_device.internal_object(),
+            plane.physical_device().internal_object()
+        );
+        assert!(plane.supports(display));
+
+        let DisplaySurfaceCreateInfo {
+            plane_stack_index,
+            transform,
+            global_alpha,
+            alpha_mode,
+            image_extent,
+            _ne: _,
+        } = create_info;
+
+        let plane_stack_index = plane_stack_index.unwrap_or_else(|| plane.current_stack_index());
+        let image_extent = image_extent.unwrap_or_else(|| display_mode.visible_region());
+
+        // VUID-VkDisplaySurfaceCreateInfoKHR-transform-06740
+        if !display.supported_transforms().supports(transform) {
+            return Err(SurfaceCreationError::DisplayTransformNotSupported {
+                provided: transform,
+            });
+        }
+
+        let capabilities = plane.capabilities(display_mode).map_err(|err| match err {
+            DisplayError::OomError(err) => SurfaceCreationError::OomError(err),
+            _ => unreachable!(),
+        })?;
+
+        // VUID-VkDisplaySurfaceCreateInfoKHR-alphaMode-01255
+        if !capabilities.supported_alpha.supports(alpha_mode) {
+            return Err(SurfaceCreationError::DisplayPlaneAlphaNotSupported {
+                provided: alpha_mode,
+            });
+        }
+
+        // VUID-VkDisplaySurfaceCreateInfoKHR-alphaMode-01254
+        if alpha_mode == DisplayPlaneAlpha::Global && !(0.0..=1.0).contains(&global_alpha) {
+            return Err(SurfaceCreationError::GlobalAlphaOutOfRange);
+        }
+
+        // VUID-VkDisplaySurfaceCreateInfoKHR-width-01256
+        let max_image_dimension = physical_device.properties().max_image_dimension2_d;
+        if image_extent[0] > max_image_dimension || image_extent[1] > max_image_dimension {
+            return Err(SurfaceCreationError::DisplayImageExtentNotSupported {
+                provided: image_extent,
+                max_supported: max_image_dimension,
+            });
+        }
+
+        let create_info = ash::vk::DisplaySurfaceCreateInfoKHR {
+            flags: ash::vk::DisplaySurfaceCreateFlagsKHR::empty(),
+            display_mode: display_mode.internal_object(),
+            plane_index: plane.index(),
+            plane_stack_index,
+            transform: transform.into(),
+            global_alpha,
+            alpha_mode: alpha_mode.into(),
+            image_extent: ash::vk::Extent2D {
+                width: image_extent[0],
+                height: image_extent[1],
+            },
+            ..Default::default()
+        };
+
+        let handle = unsafe {
+            let fns = instance.fns();
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns.khr_display.create_display_plane_surface_khr)(
+                instance.internal_object(),
+                &create_info,
+                ptr::null(),
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
+        };
+
+        Ok(Arc::new(Surface {
+            handle,
+            instance: instance.clone(),
+            api: SurfaceApi::DisplayPlane,
+            window: (),
+
+            has_swapchain: AtomicBool::new(false),
+        }))
+    }
+}
+
 unsafe impl<W> SurfaceSwapchainLock for Surface<W> {
     #[inline]
     fn flag(&self) -> &AtomicBool {
@@ -665,6 +732,22 @@ pub enum SurfaceCreationError {
         /// Name of the missing extension.
         name: &'static str,
     },
+
+    /// The provided `image_extent` is greater than what is supported by the device.
+    DisplayImageExtentNotSupported {
+        provided: [u32; 2],
+        max_supported: u32,
+    },
+
+    /// The provided `alpha_mode` is not supported by the display plane with the display mode.
+    DisplayPlaneAlphaNotSupported { provided: DisplayPlaneAlpha },
+
+    /// The provided `transform` is not supported by the display.
+    DisplayTransformNotSupported { provided: SurfaceTransform },
+
+    /// The provided `alpha_mode` is `DisplayPlaneAlpha::Global`, but `global_alpha` is not
+    /// between 0.0 and 1.0 inclusive.
+    GlobalAlphaOutOfRange,
 }
 
 impl error::Error for SurfaceCreationError {
@@ -688,6 +771,18 @@ impl fmt::Display for SurfaceCreationError {
                 SurfaceCreationError::MissingExtension { .. } => {
                     "the extension required for this function was not enabled"
                 }
+                SurfaceCreationError::DisplayImageExtentNotSupported { .. } => {
+                    "the provided `image_extent` is greater than what is supported by the device"
+                }
+                SurfaceCreationError::DisplayPlaneAlphaNotSupported { .. } => {
+                    "the provided `alpha_mode` is not supported by the display plane"
+                }
+                SurfaceCreationError::DisplayTransformNotSupported { .. } => {
+                    "the provided `transform` is not supported by the display"
+                }
+                SurfaceCreationError::GlobalAlphaOutOfRange => {
+                    "the provided `global_alpha` is not between 0.0 and 1.0 inclusive"
+                }
             }
         )
     }
@@ -711,6 +806,61 @@ impl From<Error> for SurfaceCreationError {
     }
 }
 
+/// Parameters to create a surface from a display mode with `Surface::from_display_mode`.
+#[derive(Clone, Debug)]
+pub struct DisplaySurfaceCreateInfo {
+    /// The stack index of the plane.
+    ///
+    /// If set to `None`, the current stack index of the plane is used.
+    ///
+    /// The default value is `None`.
+    pub plane_stack_index: Option<u32>,
+
+    /// The transformation to apply to the images before showing them on the display.
+    ///
+    /// The transform must be supported by the display.
+    ///
+    /// The default value is [`SurfaceTransform::Identity`].
+    pub transform: SurfaceTransform,
+
+    /// The alpha value of all the pixels of the plane, if `alpha_mode` is
+    /// [`DisplayPlaneAlpha::Global`]. It must be between 0.0 and 1.0 inclusive.
+    ///
+    /// The default value is `1.0`.
+    pub global_alpha: f32,
+
+    /// How the alpha values of the pixels are used to blend the plane with the planes below it.
+    ///
+    /// The alpha mode must be supported by the plane with the display mode, as reported by
+    /// [`DisplayPlane::capabilities`].
+    ///
+    /// The default value is [`DisplayPlaneAlpha::Opaque`].
+    pub alpha_mode: DisplayPlaneAlpha,
+
+    /// The size of the presented images.
+    ///
+    /// If set to `None`, the visible region of the display mode is used.
+    ///
+    /// The default value is `None`.
+    pub image_extent: Option<[u32; 2]>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for DisplaySurfaceCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            plane_stack_index: None,
+            transform: SurfaceTransform::Identity,
+            global_alpha: 1.0,
+            alpha_mode: DisplayPlaneAlpha::Opaque,
+            image_extent: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
 /// The windowing API that was used to construct a surface.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[non_exhaustive]
@@ -1061,6 +1211,167 @@ impl SupportedCompositeAlpha {
     }
 }
 
+/// How an image is scaled when it's presented to a surface whose size is different from the
+/// size of the image.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum PresentScaling {
+    /// The image is not scaled. Each pixel of the image is mapped to one pixel of the surface.
+    OneToOne = ash::vk::PresentScalingFlagsEXT::ONE_TO_ONE.as_raw(),
+
+    /// The image is scaled to fit the surface as much as possible, while keeping its aspect ratio.
+    AspectRatioStretch = ash::vk::PresentScalingFlagsEXT::ASPECT_RATIO_STRETCH.as_raw(),
+
+    /// The image is scaled in both directions to the size of the surface.
+    Stretch = ash::vk::PresentScalingFlagsEXT::STRETCH.as_raw(),
+}
+
+impl From<PresentScaling> for ash::vk::PresentScalingFlagsEXT {
+    #[inline]
+    fn from(val: PresentScaling) -> Self {
+        Self::from_raw(val as u32)
+    }
+}
+
+/// List of supported present scaling behaviors.
+///
+/// See the docs of `PresentScaling`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SupportedPresentScaling {
+    pub one_to_one: bool,
+    pub aspect_ratio_stretch: bool,
+    pub stretch: bool,
+}
+
+impl From<ash::vk::PresentScalingFlagsEXT> for SupportedPresentScaling {
+    #[inline]
+    fn from(val: ash::vk::PresentScalingFlagsEXT) -> SupportedPresentScaling {
+        SupportedPresentScaling {
+            one_to_one: val.intersects(ash::vk::PresentScalingFlagsEXT::ONE_TO_ONE),
+            aspect_ratio_stretch: val
+                .intersects(ash::vk::PresentScalingFlagsEXT::ASPECT_RATIO_STRETCH),
+            stretch: val.intersects(ash::vk::PresentScalingFlagsEXT::STRETCH),
+        }
+    }
+}
+
+impl SupportedPresentScaling {
+    /// Builds a `SupportedPresentScaling` with all fields set to false.
+    #[inline]
+    pub fn none() -> SupportedPresentScaling {
+        SupportedPresentScaling {
+            one_to_one: false,
+            aspect_ratio_stretch: false,
+            stretch: false,
+        }
+    }
+
+    /// Returns true if the given `PresentScaling` is in this list.
+    #[inline]
+    pub fn supports(&self, value: PresentScaling) -> bool {
+        match value {
+            PresentScaling::OneToOne => self.one_to_one,
+            PresentScaling::AspectRatioStretch => self.aspect_ratio_stretch,
+            PresentScaling::Stretch => self.stretch,
+        }
+    }
+
+    /// Returns an iterator to the list of supported present scaling behaviors.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = PresentScaling> {
+        let moved = *self;
+        [
+            PresentScaling::OneToOne,
+            PresentScaling::AspectRatioStretch,
+            PresentScaling::Stretch,
+        ]
+        .into_iter()
+        .filter(move |&mode| moved.supports(mode))
+    }
+}
+
+/// Where an image is placed along one axis of a surface, when it's presented with a
+/// [`PresentScaling`] that doesn't make it cover the whole surface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum PresentGravity {
+    /// The image is aligned with the top or left edge of the surface.
+    Min = ash::vk::PresentGravityFlagsEXT::MIN.as_raw(),
+
+    /// The image is aligned with the bottom or right edge of the surface.
+    Max = ash::vk::PresentGravityFlagsEXT::MAX.as_raw(),
+
+    /// The image is centered in the surface.
+    Centered = ash::vk::PresentGravityFlagsEXT::CENTERED.as_raw(),
+}
+
+impl From<PresentGravity> for ash::vk::PresentGravityFlagsEXT {
+    #[inline]
+    fn from(val: PresentGravity) -> Self {
+        Self::from_raw(val as u32)
+    }
+}
+
+/// List of supported present gravities.
+///
+/// See the docs of `PresentGravity`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SupportedPresentGravity {
+    pub min: bool,
+    pub max: bool,
+    pub centered: bool,
+}
+
+impl From<ash::vk::PresentGravityFlagsEXT> for SupportedPresentGravity {
+    #[inline]
+    fn from(val: ash::vk::PresentGravityFlagsEXT) -> SupportedPresentGravity {
+        SupportedPresentGravity {
+            min: val.intersects(ash::vk::PresentGravityFlagsEXT::MIN),
+            max: val.intersects(ash::vk::PresentGravityFlagsEXT::MAX),
+            centered: val.intersects(ash::vk::PresentGravityFlagsEXT::CENTERED),
+        }
+    }
+}
+
+impl SupportedPresentGravity {
+    /// Builds a `SupportedPresentGravity` with all fields set to false.
+    #[inline]
+    pub fn none() -> SupportedPresentGravity {
+        SupportedPresentGravity {
+            min: false,
+            max: false,
+            centered: false,
+        }
+    }
+
+    /// Returns true if the given `PresentGravity` is in this list.
+    #[inline]
+    pub fn supports(&self, value: PresentGravity) -> bool {
+        match value {
+            PresentGravity::Min => self.min,
+            PresentGravity::Max => self.max,
+            PresentGravity::Centered => self.centered,
+        }
+    }
+
+    /// Returns an iterator to the list of supported present gravities.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = PresentGravity> {
+        let moved = *self;
+        [
+            PresentGravity::Min,
+            PresentGravity::Max,
+            PresentGravity::Centered,
+        ]
+        .into_iter()
+        .filter(move |&mode| moved.supports(mode))
+    }
+}
+
 /// How the presentation engine should interpret the data.
 ///
 /// # A quick lesson about color spaces
@@ -1212,6 +1523,17 @@ impl From<ash::vk::ColorSpaceKHR> for ColorSpace {
 pub struct SurfaceInfo {
     pub full_screen_exclusive: FullScreenExclusive,
     pub win32_monitor: Option<Win32Monitor>,
+
+    /// The present mode to query the present scaling capabilities and the compatible present
+    /// modes for.
+    ///
+    /// If this is `Some`, the
+    /// [`ext_surface_maintenance1`](crate::instance::InstanceExtensions::ext_surface_maintenance1)
+    /// extension must be enabled on the instance.
+    ///
+    /// The default value is `None`.
+    pub present_mode: Option<PresentMode>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1221,6 +1543,7 @@ impl Default for SurfaceInfo {
         Self {
             full_screen_exclusive: FullScreenExclusive::Default,
             win32_monitor: None,
+            present_mode: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1268,14 +1591,60 @@ pub struct SurfaceCapabilities {
 
     /// Whether full-screen exclusivity is supported.
     pub full_screen_exclusive_supported: bool,
+
+    /// The scaling behaviors that are supported for the queried present mode. Empty if
+    /// [`SurfaceInfo::present_mode`] was `None`.
+    pub supported_present_scaling: SupportedPresentScaling,
+
+    /// The gravities that are supported for the queried present mode, in the x and y direction.
+    /// Empty if [`SurfaceInfo::present_mode`] was `None`.
+    pub supported_present_gravity: [SupportedPresentGravity; 2],
+
+    /// The minimum width and height of a swapchain that is scaled with one of the
+    /// `supported_present_scaling` behaviors, or `None` if scaling is not supported or
+    /// [`SurfaceInfo::present_mode`] was `None`.
+    pub min_scaled_image_extent: Option<[u32; 2]>,
+
+    /// The maximum width and height of a swapchain that is scaled with one of the
+    /// `supported_present_scaling` behaviors, or `None` if scaling is not supported or
+    /// [`SurfaceInfo::present_mode`] was `None`.
+    pub max_scaled_image_extent: Option<[u32; 2]>,
+
+    /// The present modes that a swapchain created with the queried present mode can switch to
+    /// without being recreated, including the queried mode itself. Empty if
+    /// [`SurfaceInfo::present_mode`] was `None`.
+    pub compatible_present_modes: SmallVec<[PresentMode; 4]>,
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::device::physical::PhysicalDevice;
+    use crate::swapchain::display::{Display, DisplayError, DisplayPlane};
     use crate::swapchain::Surface;
     use crate::swapchain::SurfaceCreationError;
+    use crate::swapchain::{
+        PresentGravity, PresentScaling, SupportedPresentGravity, SupportedPresentScaling,
+    };
     use std::ptr;
 
+    #[test]
+    fn khr_display_ext_missing() {
+        let instance = instance!();
+        let physical_device = match PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return,
+        };
+
+        assert!(matches!(
+            Display::enumerate_raw(physical_device),
+            Err(DisplayError::ExtensionNotEnabled { .. })
+        ));
+        assert!(matches!(
+            DisplayPlane::enumerate_raw(physical_device),
+            Err(DisplayError::ExtensionNotEnabled { .. })
+        ));
+    }
+
     #[test]
     fn khr_win32_surface_ext_missing() {
         let instance = instance!();
@@ -1320,4 +1689,19 @@ mod tests {
             _ => panic!(),
         }
     }
+
+    #[test]
+    fn supported_present_scaling() {
+        let scaling = SupportedPresentScaling::from(
+            ash::vk::PresentScalingFlagsEXT::ONE_TO_ONE | ash::vk::PresentScalingFlagsEXT::STRETCH,
+        );
+        assert_eq!(
+            scaling.iter().collect::<Vec<_>>(),
+            [PresentScaling::OneToOne, PresentScaling::Stretch]
+        );
+
+        let gravity = SupportedPresentGravity::from(ash::vk::PresentGravityFlagsEXT::CENTERED);
+        assert!(gravity.supports(PresentGravity::Centered));
+        assert!(!gravity.supports(PresentGravity::Min));
+    }
 }
@@ -8,14 +8,21 @@
 // according to those terms.
 
 use super::{
-    ColorSpace, CompositeAlpha, PresentMode, PresentRegion, SupportedCompositeAlpha,
+    ColorSpace, CompositeAlpha, PresentGravity, PresentMode, PresentRegion, PresentScaling,
+    SupportedCompositeAlpha, SupportedPresentGravity, SupportedPresentScaling,
     SupportedSurfaceTransforms, Surface, SurfaceTransform,
 };
 use crate::{
     buffer::sys::UnsafeBuffer,
     check_errors,
-    command_buffer::submit::{
-        SubmitAnyBuilder, SubmitPresentBuilder, SubmitPresentError, SubmitSemaphoresWaitBuilder,
+    command_buffer::{
+        pool::{standard::StandardCommandPoolAlloc, CommandPool, CommandPoolBuilderAlloc},
+        submit::{
+            SubmitAnyBuilder, SubmitCommandBufferBuilder, SubmitPresentBuilder, SubmitPresentError,
+            SubmitSemaphoresWaitBuilder,
+        },
+        sys::{CommandBufferBeginInfo, UnsafeCommandBuffer, UnsafeCommandBufferBuilder},
+        CommandBufferLevel, CommandBufferUsage,
     },
     device::{physical::SurfacePropertiesError, Device, DeviceOwned, Queue},
     format::Format,
@@ -25,13 +32,14 @@ use crate::{
     },
     swapchain::{SurfaceApi, SurfaceInfo, SurfaceSwapchainLock},
     sync::{
-        AccessCheckError, AccessError, AccessFlags, Fence, FlushError, GpuFuture, PipelineStages,
-        Semaphore, SemaphoreCreationError, Sharing,
+        AccessCheckError, AccessError, AccessFlags, DependencyInfo, Fence, FenceWaitError,
+        FlushError, GpuFuture, ImageMemoryBarrier, PipelineStages, QueueFamilyTransfer, Semaphore,
+        SemaphoreCreationError, Sharing,
     },
     DeviceSize, Error, OomError, Success, VulkanObject,
 };
 use parking_lot::Mutex;
-use smallvec::SmallVec;
+use smallvec::{smallvec, SmallVec};
 use std::{
     error, fmt,
     hash::{Hash, Hasher},
@@ -39,10 +47,10 @@ use std::{
     ops::Range,
     ptr,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 /// Contains the swapping system and the images that can be shown on a surface.
@@ -59,16 +67,33 @@ pub struct Swapchain<W> {
     image_array_layers: u32,
     image_usage: ImageUsage,
     image_sharing: Sharing<SmallVec<[u32; 4]>>,
+    image_view_formats: SmallVec<[Format; 4]>,
     pre_transform: SurfaceTransform,
     composite_alpha: CompositeAlpha,
     present_mode: PresentMode,
     clipped: bool,
     full_screen_exclusive: FullScreenExclusive,
     win32_monitor: Option<Win32Monitor>,
+    deferred_memory_allocation: bool,
+    scaling_behavior: Option<PresentScaling>,
+    present_gravity: Option<[PresentGravity; 2]>,
 
     // Whether full-screen exclusive is currently held.
     full_screen_exclusive_held: AtomicBool,
 
+    // The highest present ID that has been used with this swapchain so far.
+    prev_present_id: AtomicU64,
+
+    // The fences of the presentations that may not have finished yet. Only used if the
+    // `swapchain_maintenance1` feature is enabled.
+    present_fences: Mutex<Vec<Arc<Fence>>>,
+
+    // The result of the last presentation to this swapchain.
+    last_present_result: Mutex<Option<SwapchainPresentResult>>,
+
+    // Statistics about the frames that were acquired and presented.
+    statistics: Mutex<FrameStatistics>,
+
     // The images of this swapchain.
     images: Vec<ImageEntry>,
 
@@ -139,12 +164,16 @@ impl<W> Swapchain<W> {
             image_array_layers,
             image_usage,
             image_sharing,
+            image_view_formats,
             pre_transform,
             composite_alpha,
             present_mode,
             clipped,
             full_screen_exclusive,
             win32_monitor,
+            deferred_memory_allocation,
+            scaling_behavior,
+            present_gravity,
             _ne: _,
         } = create_info;
 
@@ -160,14 +189,22 @@ impl<W> Swapchain<W> {
             image_array_layers,
             image_usage,
             image_sharing,
+            image_view_formats,
             pre_transform,
             composite_alpha,
             present_mode,
             clipped,
             full_screen_exclusive,
             win32_monitor,
+            deferred_memory_allocation,
+            scaling_behavior,
+            present_gravity,
 
             full_screen_exclusive_held: AtomicBool::new(false),
+            prev_present_id: AtomicU64::new(0),
+            present_fences: Mutex::new(Vec::new()),
+            last_present_result: Mutex::new(None),
+            statistics: Mutex::new(FrameStatistics::new(images.len())),
             images,
             retired: Mutex::new(false),
         });
@@ -233,12 +270,16 @@ impl<W> Swapchain<W> {
             image_array_layers,
             image_usage,
             image_sharing,
+            image_view_formats,
             pre_transform,
             composite_alpha,
             present_mode,
             clipped,
             full_screen_exclusive,
             win32_monitor,
+            deferred_memory_allocation,
+            scaling_behavior,
+            present_gravity,
             _ne: _,
         } = create_info;
 
@@ -254,14 +295,22 @@ impl<W> Swapchain<W> {
             image_array_layers,
             image_usage,
             image_sharing,
+            image_view_formats,
             pre_transform,
             composite_alpha,
             present_mode,
             clipped,
             full_screen_exclusive,
             win32_monitor,
+            deferred_memory_allocation,
+            scaling_behavior,
+            present_gravity,
 
             full_screen_exclusive_held: AtomicBool::new(full_screen_exclusive_held),
+            prev_present_id: AtomicU64::new(0),
+            present_fences: Mutex::new(Vec::new()),
+            last_present_result: Mutex::new(None),
+            statistics: Mutex::new(FrameStatistics::new(images.len())),
             images,
             retired: Mutex::new(false),
         });
@@ -286,18 +335,66 @@ impl<W> Swapchain<W> {
             image_array_layers,
             image_usage,
             ref mut image_sharing,
+            ref image_view_formats,
             pre_transform,
             composite_alpha,
             present_mode,
             clipped,
             full_screen_exclusive,
             win32_monitor,
+            deferred_memory_allocation,
+            scaling_behavior,
+            present_gravity,
             _ne: _,
         } = create_info;
 
+        if deferred_memory_allocation && !device.enabled_features().swapchain_maintenance1 {
+            return Err(SwapchainCreationError::FeatureNotEnabled {
+                feature: "swapchain_maintenance1",
+                reason: "`deferred_memory_allocation` was set",
+            });
+        }
+
+        if scaling_behavior.is_some() && !device.enabled_features().swapchain_maintenance1 {
+            return Err(SwapchainCreationError::FeatureNotEnabled {
+                feature: "swapchain_maintenance1",
+                reason: "`scaling_behavior` was `Some`",
+            });
+        }
+
+        // VUID-VkSwapchainPresentScalingCreateInfoEXT-presentGravityX-07766
+        if present_gravity.is_some() && scaling_behavior.is_none() {
+            return Err(SwapchainCreationError::PresentGravityWithoutScaling);
+        }
+
         // VUID-VkSwapchainCreateInfoKHR-imageUsage-requiredbitmask
         assert!(image_usage != ImageUsage::none());
 
+        // VUID-VkSwapchainCreateInfoKHR-imageColorSpace-parameter
+        match image_color_space {
+            ColorSpace::SrgbNonLinear => (),
+            ColorSpace::DisplayNative => {
+                if !device.enabled_extensions().amd_display_native_hdr {
+                    return Err(SwapchainCreationError::ExtensionNotEnabled {
+                        extension: "amd_display_native_hdr",
+                        reason: "`image_color_space` was `ColorSpace::DisplayNative`",
+                    });
+                }
+            }
+            _ => {
+                if !device
+                    .instance()
+                    .enabled_extensions()
+                    .ext_swapchain_colorspace
+                {
+                    return Err(SwapchainCreationError::ExtensionNotEnabled {
+                        extension: "ext_swapchain_colorspace",
+                        reason: "`image_color_space` was not `ColorSpace::SrgbNonLinear`",
+                    });
+                }
+            }
+        }
+
         if full_screen_exclusive != FullScreenExclusive::Default
             && !device.enabled_extensions().ext_full_screen_exclusive
         {
@@ -340,13 +437,20 @@ impl<W> Swapchain<W> {
                 }
                 *format
             } else {
+                let default_formats = default_formats(image_color_space);
+
+                // Pick the supported format that comes first in `default_formats`.
                 surface_formats
                     .into_iter()
-                    .find_map(|(f, c)| {
-                        (c == image_color_space
-                            && [Format::R8G8B8A8_UNORM, Format::B8G8R8A8_UNORM].contains(&f))
-                        .then(|| f)
+                    .filter(|&(_, c)| c == image_color_space)
+                    .filter_map(|(f, _)| {
+                        default_formats
+                            .iter()
+                            .position(|&default| default == f)
+                            .map(|index| (index, f))
                     })
+                    .min_by_key(|&(index, _)| index)
+                    .map(|(_, f)| f)
                     .ok_or_else(|| SwapchainCreationError::FormatColorSpaceNotSupported)?
             }
         });
@@ -356,6 +460,7 @@ impl<W> Swapchain<W> {
             SurfaceInfo {
                 full_screen_exclusive,
                 win32_monitor,
+                present_mode: scaling_behavior.map(|_| present_mode),
                 ..Default::default()
             },
         )?;
@@ -378,16 +483,56 @@ impl<W> Swapchain<W> {
             *image_extent = surface_capabilities.current_extent.unwrap();
         }
 
-        // VUID-VkSwapchainCreateInfoKHR-imageExtent-01274
-        if image_extent[0] < surface_capabilities.min_image_extent[0]
-            || image_extent[1] < surface_capabilities.min_image_extent[1]
-            || image_extent[0] > surface_capabilities.max_image_extent[0]
-            || image_extent[1] > surface_capabilities.max_image_extent[1]
+        if let Some(scaling_behavior) = scaling_behavior {
+            // VUID-VkSwapchainPresentScalingCreateInfoEXT-scalingBehavior-07770
+            if !surface_capabilities
+                .supported_present_scaling
+                .supports(scaling_behavior)
+            {
+                return Err(SwapchainCreationError::PresentScalingNotSupported {
+                    provided: scaling_behavior,
+                    supported: surface_capabilities.supported_present_scaling,
+                });
+            }
+
+            // VUID-VkSwapchainPresentScalingCreateInfoEXT-presentGravityX-07772
+            // VUID-VkSwapchainPresentScalingCreateInfoEXT-presentGravityY-07774
+            if let Some(present_gravity) = present_gravity {
+                if !(surface_capabilities.supported_present_gravity[0].supports(present_gravity[0])
+                    && surface_capabilities.supported_present_gravity[1]
+                        .supports(present_gravity[1]))
+                {
+                    return Err(SwapchainCreationError::PresentGravityNotSupported {
+                        provided: present_gravity,
+                        supported: surface_capabilities.supported_present_gravity,
+                    });
+                }
+            }
+        }
+
+        // VUID-VkSwapchainCreateInfoKHR-pNext-07781
+        // VUID-VkSwapchainCreateInfoKHR-pNext-07782
+        let (min_image_extent, max_image_extent) = if scaling_behavior.is_some() {
+            (
+                surface_capabilities.min_scaled_image_extent.unwrap(),
+                surface_capabilities.max_scaled_image_extent.unwrap(),
+            )
+        } else {
+            (
+                surface_capabilities.min_image_extent,
+                surface_capabilities.max_image_extent,
+            )
+        };
+
+        if image_extent[0] < min_image_extent[0]
+            || image_extent[1] < min_image_extent[1]
+            || image_extent[0] > max_image_extent[0]
+            || image_extent[1] > max_image_extent[1]
         {
             return Err(SwapchainCreationError::ImageExtentNotSupported {
                 provided: *image_extent,
-                min_supported: surface_capabilities.min_image_extent,
-                max_supported: surface_capabilities.max_image_extent,
+                min_supported: min_image_extent,
+                max_supported: max_image_extent,
             });
         }
 
@@ -469,6 +614,29 @@ impl<W> Swapchain<W> {
             return Err(SwapchainCreationError::PresentModeNotSupported);
         }
 
+        if !image_view_formats.is_empty() {
+            if !device.enabled_extensions().khr_swapchain_mutable_format {
+                return Err(SwapchainCreationError::ExtensionNotEnabled {
+                    extension: "khr_swapchain_mutable_format",
+                    reason: "`image_view_formats` was not empty",
+                });
+            }
+
+            // VUID-VkSwapchainCreateInfoKHR-flags-03168
+            if !image_view_formats.contains(&image_format.unwrap()) {
+                return Err(SwapchainCreationError::ImageViewFormatsMissingImageFormat);
+            }
+
+            for &view_format in image_view_formats.iter() {
+                // VUID-VkImageFormatListCreateInfo-pViewFormats-01253
+                if view_format.compatibility() != image_format.unwrap().compatibility() {
+                    return Err(SwapchainCreationError::ImageViewFormatNotCompatible {
+                        view_format,
+                    });
+                }
+            }
+        }
+
         // VUID-VkSwapchainCreateInfoKHR-imageFormat-01778
         if device
             .physical_device()
@@ -477,6 +645,7 @@ impl<W> Swapchain<W> {
                 image_type: ImageType::Dim2d,
                 tiling: ImageTiling::Optimal,
                 usage: image_usage,
+                mutable_format: !image_view_formats.is_empty(),
                 ..Default::default()
             })?
             .is_none()
@@ -501,12 +670,16 @@ impl<W> Swapchain<W> {
             image_array_layers,
             image_usage,
             ref image_sharing,
+            ref image_view_formats,
             pre_transform,
             composite_alpha,
             present_mode,
             clipped,
             full_screen_exclusive,
             win32_monitor,
+            deferred_memory_allocation,
+            scaling_behavior,
+            present_gravity,
             _ne: _,
         } = create_info;
 
@@ -540,8 +713,44 @@ impl<W> Swapchain<W> {
                 None
             };
 
+        let image_view_formats_vk: SmallVec<[_; 4]> =
+            image_view_formats.iter().copied().map(Into::into).collect();
+        let mut image_format_list_info = if !image_view_formats.is_empty() {
+            Some(ash::vk::ImageFormatListCreateInfo {
+                view_format_count: image_view_formats_vk.len() as u32,
+                p_view_formats: image_view_formats_vk.as_ptr(),
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+
+        let mut present_scaling_info = scaling_behavior.map(|scaling_behavior| {
+            let [present_gravity_x, present_gravity_y] = present_gravity
+                .map_or([ash::vk::PresentGravityFlagsEXT::empty(); 2], |gravity| {
+                    [gravity[0].into(), gravity[1].into()]
+                });
+
+            ash::vk::SwapchainPresentScalingCreateInfoEXT {
+                scaling_behavior: scaling_behavior.into(),
+                present_gravity_x,
+                present_gravity_y,
+                ..Default::default()
+            }
+        });
+
+        let mut flags = ash::vk::SwapchainCreateFlagsKHR::empty();
+
+        if !image_view_formats.is_empty() {
+            flags |= ash::vk::SwapchainCreateFlagsKHR::MUTABLE_FORMAT;
+        }
+
+        if deferred_memory_allocation {
+            flags |= ash::vk::SwapchainCreateFlagsKHR::DEFERRED_MEMORY_ALLOCATION_EXT;
+        }
+
         let mut create_info = ash::vk::SwapchainCreateInfoKHR {
-            flags: ash::vk::SwapchainCreateFlagsKHR::empty(),
+            flags,
             surface: surface.internal_object(),
             min_image_count,
             image_format: image_format.unwrap().into(),
@@ -577,6 +786,16 @@ impl<W> Swapchain<W> {
             create_info.p_next = surface_full_screen_exclusive_win32_info as *const _ as *const _;
         }
 
+        if let Some(image_format_list_info) = image_format_list_info.as_mut() {
+            image_format_list_info.p_next = create_info.p_next as *mut _;
+            create_info.p_next = image_format_list_info as *const _ as *const _;
+        }
+
+        if let Some(present_scaling_info) = present_scaling_info.as_mut() {
+            present_scaling_info.p_next = create_info.p_next as *mut _;
+            create_info.p_next = present_scaling_info as *const _ as *const _;
+        }
+
         let fns = device.fns();
 
         let handle = {
@@ -627,6 +846,7 @@ impl<W> Swapchain<W> {
             image_array_layers,
             image_usage,
             ref image_sharing, // TODO: put this in the image too
+            ref image_view_formats,
             ..
         } = create_info;
 
@@ -645,7 +865,11 @@ impl<W> Swapchain<W> {
                         handle,
                         image_usage,
                         image_format.unwrap(),
-                        ImageCreateFlags::none(),
+                        ImageCreateFlags {
+                            mutable_format: !image_view_formats.is_empty(),
+                            ..ImageCreateFlags::none()
+                        },
+                        image_view_formats.clone(),
                         dims,
                         SampleCount::Sample1,
                         1,
@@ -671,12 +895,16 @@ impl<W> Swapchain<W> {
             image_array_layers: self.image_array_layers,
             image_usage: self.image_usage,
             image_sharing: self.image_sharing.clone(),
+            image_view_formats: self.image_view_formats.clone(),
             pre_transform: self.pre_transform,
             composite_alpha: self.composite_alpha,
             present_mode: self.present_mode,
             clipped: self.clipped,
             full_screen_exclusive: self.full_screen_exclusive,
             win32_monitor: self.win32_monitor,
+            deferred_memory_allocation: self.deferred_memory_allocation,
+            scaling_behavior: self.scaling_behavior,
+            present_gravity: self.present_gravity,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -729,6 +957,12 @@ impl<W> Swapchain<W> {
         self.image_array_layers
     }
 
+    /// Returns the formats that image views of the swapchain images are allowed to have.
+    #[inline]
+    pub fn image_view_formats(&self) -> &[Format] {
+        &self.image_view_formats
+    }
+
     /// Returns the pre-transform that was passed when creating the swapchain.
     #[inline]
     pub fn pre_transform(&self) -> SurfaceTransform {
@@ -759,6 +993,75 @@ impl<W> Swapchain<W> {
         self.full_screen_exclusive
     }
 
+    /// Returns whether the memory of the swapchain images is allocated when they are first
+    /// acquired.
+    #[inline]
+    pub fn deferred_memory_allocation(&self) -> bool {
+        self.deferred_memory_allocation
+    }
+
+    /// Returns the value of `scaling_behavior` that was passed when creating the swapchain.
+    #[inline]
+    pub fn scaling_behavior(&self) -> Option<PresentScaling> {
+        self.scaling_behavior
+    }
+
+    /// Returns the value of `present_gravity` that was passed when creating the swapchain.
+    #[inline]
+    pub fn present_gravity(&self) -> Option<[PresentGravity; 2]> {
+        self.present_gravity
+    }
+
+    /// Waits until all the presentations of this swapchain that have been submitted so far have
+    /// finished using the swapchain, or until `timeout` has elapsed.
+    ///
+    /// Once this returns `Ok`, the presentations no longer use the resources of the swapchain, so
+    /// it is safe to drop a retired swapchain without blocking. A swapchain waits for its
+    /// presentations itself before it's destroyed, so calling this is not needed for
+    /// correctness.
+    ///
+    /// Presentations are only tracked if the
+    /// [`swapchain_maintenance1`](crate::device::Features::swapchain_maintenance1) feature is
+    /// enabled on the device. Otherwise, this always returns `Ok` immediately.
+    pub fn wait_for_presents(&self, timeout: Option<Duration>) -> Result<(), FenceWaitError> {
+        let present_fences = self.present_fences.lock().clone();
+
+        if !present_fences.is_empty() {
+            Fence::multi_wait(present_fences.iter().map(|fence| &**fence), timeout)?;
+        }
+
+        let mut present_fences_lock = self.present_fences.lock();
+        present_fences_lock.retain(|fence| {
+            !present_fences
+                .iter()
+                .any(|waited| Arc::ptr_eq(fence, waited))
+        });
+
+        Ok(())
+    }
+
+    // The fences of the presentations that may not have finished yet. A fence must only be
+    // added once the presentation that signals it has been submitted.
+    pub(crate) fn present_fences(&self) -> &Mutex<Vec<Arc<Fence>>> {
+        &self.present_fences
+    }
+
+    /// Returns the result of the last presentation to this swapchain, or `None` if no image has
+    /// been presented yet.
+    ///
+    /// When images are presented to several swapchains at once, for example by joining their
+    /// present futures, the error returned by flushing is the error of only one of them. This
+    /// returns the result for this swapchain specifically, so that for example only the windows
+    /// whose swapchain is out of date or suboptimal are recreated.
+    #[inline]
+    pub fn last_present_result(&self) -> Option<SwapchainPresentResult> {
+        *self.last_present_result.lock()
+    }
+
+    pub(crate) fn last_present_result_mut(&self) -> &Mutex<Option<SwapchainPresentResult>> {
+        &self.last_present_result
+    }
+
     /// Acquires full-screen exclusivity.
     ///
     /// The swapchain must have been created with [`FullScreenExclusive::ApplicationControlled`],
@@ -816,6 +1119,134 @@ impl<W> Swapchain<W> {
         Ok(())
     }
 
+    /// Sets the HDR metadata of the swapchain, which describes the display that the content was
+    /// mastered on and the light levels of the content.
+    ///
+    /// The metadata is used by the display for tone mapping, and is typically set once after
+    /// creating a swapchain with an HDR color space such as [`ColorSpace::Hdr10St2084`]. It
+    /// applies to the images that are presented after this call.
+    ///
+    /// The [`ext_hdr_metadata`](crate::device::DeviceExtensions::ext_hdr_metadata) extension must
+    /// be enabled on the device.
+    pub fn set_hdr_metadata(&self, metadata: HdrMetadata) -> Result<(), HdrMetadataError> {
+        if !self.device.enabled_extensions().ext_hdr_metadata {
+            return Err(HdrMetadataError::ExtensionNotEnabled {
+                extension: "ext_hdr_metadata",
+                reason: "tried to set the HDR metadata of a swapchain",
+            });
+        }
+
+        let HdrMetadata {
+            display_primary_red,
+            display_primary_green,
+            display_primary_blue,
+            white_point,
+            max_luminance,
+            min_luminance,
+            max_content_light_level,
+            max_frame_average_light_level,
+            _ne: _,
+        } = metadata;
+
+        let xy = |[x, y]: [f32; 2]| ash::vk::XYColorEXT { x, y };
+        let metadata_vk = ash::vk::HdrMetadataEXT {
+            display_primary_red: xy(display_primary_red),
+            display_primary_green: xy(display_primary_green),
+            display_primary_blue: xy(display_primary_blue),
+            white_point: xy(white_point),
+            max_luminance,
+            min_luminance,
+            max_content_light_level,
+            max_frame_average_light_level,
+            ..Default::default()
+        };
+
+        unsafe {
+            let fns = self.device.fns();
+            (fns.ext_hdr_metadata.set_hdr_metadata_ext)(
+                self.device.internal_object(),
+                1,
+                &self.handle,
+                &metadata_vk,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Waits until the presentation with the given present ID has been displayed, or until
+    /// `timeout` has elapsed.
+    ///
+    /// Present IDs are assigned with [`present_with_id`]. This can be used to limit how far ahead
+    /// of the display the application renders, which reduces latency. If the presentation has
+    /// already been displayed, or a presentation with a higher present ID has been displayed, this
+    /// returns immediately.
+    ///
+    /// Returns `true` if the swapchain is suboptimal for the surface.
+    ///
+    /// The [`present_wait`](crate::device::Features::present_wait) feature must be enabled on
+    /// the device.
+    pub fn wait_for_present(
+        &self,
+        present_id: u64,
+        timeout: Option<Duration>,
+    ) -> Result<bool, PresentWaitError> {
+        if !self.device.enabled_features().present_wait {
+            return Err(PresentWaitError::FeatureNotEnabled {
+                feature: "present_wait",
+                reason: "tried to wait for a presentation",
+            });
+        }
+
+        // VUID?
+        if present_id == 0 {
+            return Err(PresentWaitError::PresentIdZero);
+        }
+
+        let timeout_ns = if let Some(timeout) = timeout {
+            timeout
+                .as_secs()
+                .saturating_mul(1_000_000_000)
+                .saturating_add(timeout.subsec_nanos() as u64)
+        } else {
+            u64::MAX
+        };
+
+        let result = unsafe {
+            let fns = self.device.fns();
+            check_errors((fns.khr_present_wait.wait_for_present_khr)(
+                self.device.internal_object(),
+                self.handle,
+                present_id,
+                timeout_ns,
+            ))?
+        };
+
+        match result {
+            Success::Success => Ok(false),
+            Success::Suboptimal => Ok(true),
+            Success::Timeout => Err(PresentWaitError::Timeout),
+            s => panic!("unexpected success value: {:?}", s),
+        }
+    }
+
+    /// Returns statistics about the frames that were acquired and presented with this swapchain.
+    ///
+    /// They can be used to detect pacing problems: for example, a long wait in
+    /// `acquire_next_image` means that the application is ahead of the presentation engine, and
+    /// a growing number of out-of-date events means that the swapchain isn't recreated quickly
+    /// enough. A swapchain that is created with `recreate` starts with empty statistics.
+    #[inline]
+    pub fn statistics(&self) -> SwapchainStatistics {
+        self.statistics.lock().statistics
+    }
+
+    /// Resets the statistics returned by [`statistics`](Self::statistics).
+    #[inline]
+    pub fn reset_statistics(&self) {
+        self.statistics.lock().statistics = SwapchainStatistics::default();
+    }
+
     /// `FullScreenExclusive::AppControlled` is not the active full-screen exclusivity mode,
     /// then this function will always return false. If true is returned the swapchain
     /// is in `FullScreenExclusive::AppControlled` full-screen exclusivity mode and exclusivity
@@ -853,6 +1284,13 @@ impl<W> Drop for Swapchain<W> {
     #[inline]
     fn drop(&mut self) {
         unsafe {
+            // The swapchain must not be destroyed while a presentation still uses it.
+            let present_fences = self.present_fences.get_mut();
+
+            if !present_fences.is_empty() {
+                let _ = Fence::multi_wait(present_fences.iter().map(|fence| &**fence), None);
+            }
+
             let fns = self.device.fns();
             (fns.khr_swapchain.destroy_swapchain_khr)(
                 self.device.internal_object(),
@@ -912,14 +1350,29 @@ pub struct SwapchainCreateInfo {
 
     /// The format of the created images.
     ///
-    /// If set to `None`, [`Format::R8G8B8A8_UNORM`] or [`Format::B8G8R8A8_UNORM`] will be selected,
-    /// based on which is supported by the surface.
+    /// If set to `None`, a format suited to `image_color_space` will be selected, based on which
+    /// is supported by the surface:
+    /// - For [`ColorSpace::Hdr10St2084`], [`ColorSpace::Hdr10Hlg`] and
+    ///   [`ColorSpace::DolbyVision`], a 10-bit format such as [`Format::A2B10G10R10_UNORM_PACK32`],
+    ///   or [`Format::R16G16B16A16_SFLOAT`].
+    /// - For [`ColorSpace::ExtendedSrgbLinear`], [`ColorSpace::ExtendedSrgbNonLinear`] and the
+    ///   other linear color spaces, [`Format::R16G16B16A16_SFLOAT`].
+    /// - Otherwise, [`Format::R8G8B8A8_UNORM`] or [`Format::B8G8R8A8_UNORM`].
     ///
     /// The default value is `None`.
     pub image_format: Option<Format>,
 
     /// The color space of the created images.
     ///
+    /// Color spaces other than [`ColorSpace::SrgbNonLinear`] require the
+    /// [`ext_swapchain_colorspace`](crate::instance::InstanceExtensions::ext_swapchain_colorspace)
+    /// extension to be enabled on the instance, except for [`ColorSpace::DisplayNative`], which
+    /// requires the
+    /// [`amd_display_native_hdr`](crate::device::DeviceExtensions::amd_display_native_hdr)
+    /// extension to be enabled on the device. The color spaces supported for each format are
+    /// returned by
+    /// [`PhysicalDevice::surface_formats`](crate::device::physical::PhysicalDevice::surface_formats).
+    ///
     /// The default value is [`ColorSpace::SrgbNonLinear`].
     pub image_color_space: ColorSpace,
 
@@ -947,6 +1400,18 @@ pub struct SwapchainCreateInfo {
     /// The default value is [`Sharing::Exclusive`].
     pub image_sharing: Sharing<SmallVec<[u32; 4]>>,
 
+    /// The formats that image views created from the swapchain images are allowed to have.
+    ///
+    /// If not empty, the swapchain images are created with the `mutable_format` flag, so that
+    /// for example a swapchain with a `B8G8R8A8_UNORM` format can be rendered to through a
+    /// `B8G8R8A8_SRGB` image view. The list must contain `image_format`, and all formats must be
+    /// compatible with it. The
+    /// [`khr_swapchain_mutable_format`](crate::device::DeviceExtensions::khr_swapchain_mutable_format)
+    /// extension must be enabled on the device.
+    ///
+    /// The default value is empty.
+    pub image_view_formats: SmallVec<[Format; 4]>,
+
     /// The transform that should be applied to an image before it is presented.
     ///
     /// The default value is [`SurfaceTransform::Identity`].
@@ -988,6 +1453,45 @@ pub struct SwapchainCreateInfo {
     /// The default value is `None`.
     pub win32_monitor: Option<Win32Monitor>,
 
+    /// Whether the memory of the swapchain images is allocated when they are first acquired,
+    /// rather than when the swapchain is created.
+    ///
+    /// This reduces the time it takes to create the swapchain, which helps when it's recreated
+    /// often, such as during window resizes. Image views of a swapchain image must not be created
+    /// until the image has been acquired at least once.
+    ///
+    /// If set to `true`, the
+    /// [`swapchain_maintenance1`](crate::device::Features::swapchain_maintenance1) feature must
+    /// be enabled on the device.
+    ///
+    /// The default value is `false`.
+    pub deferred_memory_allocation: bool,
+
+    /// How the swapchain images are scaled when the size of the surface is different from
+    /// `image_extent`, or `None` to use the platform's default behavior.
+    ///
+    /// This allows the swapchain to keep being used while a window is being resized, instead of
+    /// having to recreate it every time. If set to `Some`, the
+    /// [`swapchain_maintenance1`](crate::device::Features::swapchain_maintenance1) feature must
+    /// be enabled on the device, the value must be in the
+    /// [`supported_present_scaling`](crate::swapchain::SurfaceCapabilities::supported_present_scaling)
+    /// of the surface for `present_mode`, and `image_extent` is checked against the minimum and
+    /// maximum scaled image extent instead of the minimum and maximum image extent.
+    ///
+    /// The default value is `None`.
+    pub scaling_behavior: Option<PresentScaling>,
+
+    /// Where the swapchain images are placed in the surface, in the x and y direction, when
+    /// `scaling_behavior` doesn't make them cover the whole surface, or `None` to use the
+    /// platform's default behavior.
+    ///
+    /// If set to `Some`, `scaling_behavior` must also be `Some`, and the values must be in the
+    /// [`supported_present_gravity`](crate::swapchain::SurfaceCapabilities::supported_present_gravity)
+    /// of the surface for `present_mode`.
+    ///
+    /// The default value is `None`.
+    pub present_gravity: Option<[PresentGravity; 2]>,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -1002,12 +1506,16 @@ impl Default for SwapchainCreateInfo {
             image_array_layers: 1,
             image_usage: ImageUsage::none(),
             image_sharing: Sharing::Exclusive,
+            image_view_formats: SmallVec::new(),
             pre_transform: SurfaceTransform::Identity,
             composite_alpha: CompositeAlpha::Opaque,
             present_mode: PresentMode::Fifo,
             clipped: true,
             full_screen_exclusive: FullScreenExclusive::Default,
             win32_monitor: None,
+            deferred_memory_allocation: false,
+            scaling_behavior: None,
+            present_gravity: None,
             _ne: crate::NonExhaustive(()),
         }
     }
@@ -1036,6 +1544,11 @@ pub enum SwapchainCreationError {
         reason: &'static str,
     },
 
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
     /// The provided `composite_alpha` is not supported by the surface for this device.
     CompositeAlphaNotSupported {
         provided: CompositeAlpha,
@@ -1072,6 +1585,12 @@ pub enum SwapchainCreationError {
     /// ids was not valid.
     ImageSharingInvalidQueueFamilyId { id: u32 },
 
+    /// A format in the provided `image_view_formats` was not compatible with `image_format`.
+    ImageViewFormatNotCompatible { view_format: Format },
+
+    /// The provided `image_view_formats` was not empty, but did not contain `image_format`.
+    ImageViewFormatsMissingImageFormat,
+
     /// The provided `image_usage` has fields set that are not supported by the surface for this
     /// device.
     ImageUsageNotSupported {
@@ -1087,9 +1606,26 @@ pub enum SwapchainCreationError {
         max_supported: Option<u32>,
     },
 
+    /// The provided `present_gravity` was `Some`, but `scaling_behavior` was `None`.
+    PresentGravityWithoutScaling,
+
+    /// The provided `present_gravity` is not supported by the surface for this device and the
+    /// provided `present_mode`.
+    PresentGravityNotSupported {
+        provided: [PresentGravity; 2],
+        supported: [SupportedPresentGravity; 2],
+    },
+
     /// The provided `present_mode` is not supported by the surface for this device.
     PresentModeNotSupported,
 
+    /// The provided `scaling_behavior` is not supported by the surface for this device and the
+    /// provided `present_mode`.
+    PresentScalingNotSupported {
+        provided: PresentScaling,
+        supported: SupportedPresentScaling,
+    },
+
     /// The provided `pre_transform` is not supported by the surface for this device.
     PreTransformNotSupported {
         provided: SurfaceTransform,
@@ -1132,6 +1668,11 @@ impl fmt::Display for SwapchainCreationError {
                 "the extension {} must be enabled: {}",
                 extension, reason
             ),
+            Self::FeatureNotEnabled { feature, reason } => write!(
+                fmt,
+                "the feature {} must be enabled: {}",
+                feature, reason
+            ),
 
             Self::CompositeAlphaNotSupported { .. } => write!(
                 fmt,
@@ -1159,6 +1700,15 @@ impl fmt::Display for SwapchainCreationError {
                 fmt,
                 "the provided image parameters are not supported as queried from `image_format_properties`",
             ),
+            Self::ImageViewFormatNotCompatible { view_format } => write!(
+                fmt,
+                "the format {:?} in the provided `image_view_formats` was not compatible with `image_format`",
+                view_format,
+            ),
+            Self::ImageViewFormatsMissingImageFormat => write!(
+                fmt,
+                "the provided `image_view_formats` was not empty, but did not contain `image_format`",
+            ),
             Self::ImageSharingInvalidQueueFamilyId { id } => write!(
                 fmt,
                 "the provided `image_sharing` was set to `Concurrent`, but one of the specified queue family ids ({}) was not valid",
@@ -1173,10 +1723,24 @@ impl fmt::Display for SwapchainCreationError {
                 "the provided `min_image_count` ({}) is not within the range (min: {}, max: {:?}) supported by the surface for this device",
                 provided, min_supported, max_supported,
             ),
+            Self::PresentGravityWithoutScaling => write!(
+                fmt,
+                "the provided `present_gravity` was `Some`, but `scaling_behavior` was `None`",
+            ),
+            Self::PresentGravityNotSupported { provided, supported } => write!(
+                fmt,
+                "the provided `present_gravity` ({:?}) is not supported by the surface for this device and the provided `present_mode` (supported: {:?})",
+                provided, supported,
+            ),
             Self::PresentModeNotSupported => write!(
                 fmt,
                 "the provided `present_mode` is not supported by the surface for this device",
             ),
+            Self::PresentScalingNotSupported { provided, supported } => write!(
+                fmt,
+                "the provided `scaling_behavior` ({:?}) is not supported by the surface for this device and the provided `present_mode` (supported: {:?})",
+                provided, supported,
+            ),
             Self::PreTransformNotSupported { .. } => write!(
                 fmt,
                 "the provided `pre_transform` is not supported by the surface for this device",
@@ -1358,6 +1922,313 @@ impl From<OomError> for FullScreenExclusiveError {
     }
 }
 
+/// Describes the display that HDR content was mastered on, and the light levels of the content.
+///
+/// Chromaticity coordinates are given as `[x, y]` in the CIE 1931 color space, and luminances are
+/// given in nits (candela per square meter). A value of zero for the luminances or light levels
+/// means that the value is unknown.
+///
+/// This is the metadata of the SMPTE ST 2086 and CTA-861.3 standards, used by HDR10.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HdrMetadata {
+    /// The chromaticity of the red primary of the mastering display.
+    ///
+    /// The default value is the red primary of BT.2020, `[0.708, 0.292]`.
+    pub display_primary_red: [f32; 2],
+
+    /// The chromaticity of the green primary of the mastering display.
+    ///
+    /// The default value is the green primary of BT.2020, `[0.170, 0.797]`.
+    pub display_primary_green: [f32; 2],
+
+    /// The chromaticity of the blue primary of the mastering display.
+    ///
+    /// The default value is the blue primary of BT.2020, `[0.131, 0.046]`.
+    pub display_primary_blue: [f32; 2],
+
+    /// The chromaticity of the white point of the mastering display.
+    ///
+    /// The default value is D65, `[0.3127, 0.3290]`.
+    pub white_point: [f32; 2],
+
+    /// The maximum luminance of the mastering display, in nits.
+    ///
+    /// The default value is `0.0`.
+    pub max_luminance: f32,
+
+    /// The minimum luminance of the mastering display, in nits.
+    ///
+    /// The default value is `0.0`.
+    pub min_luminance: f32,
+
+    /// The maximum content light level (MaxCLL): the luminance of the brightest pixel of the
+    /// content, in nits.
+    ///
+    /// The default value is `0.0`.
+    pub max_content_light_level: f32,
+
+    /// The maximum frame-average light level (MaxFALL): the highest average luminance of all the
+    /// pixels in a frame of the content, in nits.
+    ///
+    /// The default value is `0.0`.
+    pub max_frame_average_light_level: f32,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for HdrMetadata {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            display_primary_red: [0.708, 0.292],
+            display_primary_green: [0.170, 0.797],
+            display_primary_blue: [0.131, 0.046],
+            white_point: [0.3127, 0.3290],
+            max_luminance: 0.0,
+            min_luminance: 0.0,
+            max_content_light_level: 0.0,
+            max_frame_average_light_level: 0.0,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Error that can happen when calling `Swapchain::set_hdr_metadata`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HdrMetadataError {
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+}
+
+impl error::Error for HdrMetadataError {}
+
+impl fmt::Display for HdrMetadataError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::ExtensionNotEnabled { extension, reason } => write!(
+                fmt,
+                "the extension {} must be enabled: {}",
+                extension, reason
+            ),
+        }
+    }
+}
+
+/// The result of presenting an image to one swapchain, returned by
+/// [`Swapchain::last_present_result`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapchainPresentResult {
+    /// The image was presented.
+    Success,
+
+    /// The image was presented, but the swapchain no longer matches the properties of the surface
+    /// exactly. It can still be used, but it should be recreated for best results.
+    Suboptimal,
+
+    /// The image could not be presented to this swapchain.
+    Failed(SubmitPresentError),
+}
+
+/// Error that can happen when calling `Swapchain::wait_for_present`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PresentWaitError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The connection to the device has been lost.
+    DeviceLost,
+
+    /// The surface is no longer accessible and must be recreated.
+    SurfaceLost,
+
+    /// The swapchain has lost or doesn't have full-screen exclusivity possibly for
+    /// implementation-specific reasons outside of the application’s control.
+    FullScreenExclusiveLost,
+
+    /// The surface has changed in a way that makes the swapchain unusable. You must query the
+    /// surface's new properties and recreate a new swapchain if you want to continue drawing.
+    OutOfDate,
+
+    /// The timeout has elapsed before the presentation was displayed.
+    Timeout,
+
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// The present ID 0 was given, which is never assigned to a presentation.
+    PresentIdZero,
+}
+
+impl error::Error for PresentWaitError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PresentWaitError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::OomError(_) => write!(fmt, "not enough memory"),
+            Self::DeviceLost => write!(fmt, "the connection to the device has been lost"),
+            Self::SurfaceLost => write!(fmt, "the surface of this swapchain is no longer valid"),
+            Self::FullScreenExclusiveLost => {
+                write!(fmt, "the swapchain no longer has full-screen exclusivity")
+            }
+            Self::OutOfDate => write!(fmt, "the swapchain needs to be recreated"),
+            Self::Timeout => write!(
+                fmt,
+                "the timeout has elapsed before the presentation was displayed"
+            ),
+            Self::FeatureNotEnabled { feature, reason } => {
+                write!(fmt, "the feature {} must be enabled: {}", feature, reason)
+            }
+            Self::PresentIdZero => write!(fmt, "the present ID 0 was given"),
+        }
+    }
+}
+
+impl From<Error> for PresentWaitError {
+    #[inline]
+    fn from(err: Error) -> PresentWaitError {
+        match err {
+            err @ Error::OutOfHostMemory => Self::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => Self::OomError(OomError::from(err)),
+            Error::DeviceLost => Self::DeviceLost,
+            Error::SurfaceLost => Self::SurfaceLost,
+            Error::OutOfDate => Self::OutOfDate,
+            Error::FullScreenExclusiveLost => Self::FullScreenExclusiveLost,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+/// Statistics about the frames of a swapchain, returned by [`Swapchain::statistics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapchainStatistics {
+    /// The number of images that were successfully acquired.
+    pub acquire_count: u64,
+
+    /// The number of images whose presentation was submitted, including presentations that
+    /// failed because the swapchain was out of date.
+    pub present_count: u64,
+
+    /// The total time that `acquire_next_image` spent blocked waiting for an image.
+    pub acquire_wait_total: Duration,
+
+    /// The longest time that a single call to `acquire_next_image` spent blocked.
+    pub acquire_wait_max: Duration,
+
+    /// The time that the most recent call to `acquire_next_image` spent blocked.
+    pub last_acquire_wait: Duration,
+
+    /// The total time between acquiring images and submitting their presentation.
+    pub acquire_to_present_total: Duration,
+
+    /// The time between acquiring the most recently presented image and submitting its
+    /// presentation.
+    pub last_acquire_to_present: Duration,
+
+    /// The number of times that acquiring an image reported that the swapchain is suboptimal.
+    pub suboptimal_count: u64,
+
+    /// The number of times that acquiring an image or presenting failed because the swapchain is
+    /// out of date.
+    pub out_of_date_count: u64,
+}
+
+impl SwapchainStatistics {
+    /// Returns the average time that `acquire_next_image` spent blocked, or zero if no image was
+    /// acquired.
+    #[inline]
+    pub fn average_acquire_wait(&self) -> Duration {
+        average(self.acquire_wait_total, self.acquire_count)
+    }
+
+    /// Returns the average time between acquiring an image and submitting its presentation, or
+    /// zero if no image was presented.
+    #[inline]
+    pub fn average_acquire_to_present(&self) -> Duration {
+        average(self.acquire_to_present_total, self.present_count)
+    }
+}
+
+fn average(total: Duration, count: u64) -> Duration {
+    if count == 0 {
+        Duration::ZERO
+    } else {
+        Duration::from_nanos((total.as_nanos() / count as u128) as u64)
+    }
+}
+
+// Collects the statistics of a swapchain.
+#[derive(Debug)]
+struct FrameStatistics {
+    statistics: SwapchainStatistics,
+    // For each image, the moment it was acquired if it hasn't been presented yet.
+    acquired_at: Vec<Option<Instant>>,
+}
+
+impl FrameStatistics {
+    fn new(image_count: usize) -> Self {
+        FrameStatistics {
+            statistics: SwapchainStatistics::default(),
+            acquired_at: vec![None; image_count],
+        }
+    }
+
+    fn add_acquire(&mut self, image_id: usize, wait: Duration, suboptimal: bool) {
+        let statistics = &mut self.statistics;
+        statistics.acquire_count += 1;
+        statistics.acquire_wait_total += wait;
+        statistics.acquire_wait_max = statistics.acquire_wait_max.max(wait);
+        statistics.last_acquire_wait = wait;
+        statistics.suboptimal_count += suboptimal as u64;
+        self.acquired_at[image_id] = Some(Instant::now());
+    }
+
+    fn add_present(&mut self, image_id: usize) {
+        self.statistics.present_count += 1;
+
+        if let Some(acquired_at) = self.acquired_at[image_id].take() {
+            let elapsed = acquired_at.elapsed();
+            self.statistics.acquire_to_present_total += elapsed;
+            self.statistics.last_acquire_to_present = elapsed;
+        }
+    }
+
+    fn add_out_of_date(&mut self) {
+        self.statistics.out_of_date_count += 1;
+    }
+}
+
+/// Returns the formats that are selected by default for a color space, in order of preference.
+fn default_formats(color_space: ColorSpace) -> &'static [Format] {
+    match color_space {
+        ColorSpace::Hdr10St2084 | ColorSpace::Hdr10Hlg | ColorSpace::DolbyVision => &[
+            Format::A2B10G10R10_UNORM_PACK32,
+            Format::A2R10G10B10_UNORM_PACK32,
+            Format::R16G16B16A16_SFLOAT,
+        ],
+        ColorSpace::ExtendedSrgbLinear
+        | ColorSpace::ExtendedSrgbNonLinear
+        | ColorSpace::DisplayP3Linear
+        | ColorSpace::Bt709Linear
+        | ColorSpace::Bt2020Linear
+        | ColorSpace::AdobeRgbLinear => &[Format::R16G16B16A16_SFLOAT],
+        _ => &[Format::R8G8B8A8_UNORM, Format::B8G8R8A8_UNORM],
+    }
+}
+
 /// Tries to take ownership of an image in order to draw on it.
 ///
 /// The function returns the index of the image in the array of images that was returned
@@ -1386,8 +2257,21 @@ pub fn acquire_next_image<W>(
             return Err(AcquireError::OutOfDate);
         }
 
+        let acquire_start = Instant::now();
         let acquire_result =
             unsafe { acquire_next_image_raw(&swapchain, timeout, Some(&semaphore), Some(&fence)) };
+        let acquire_wait = acquire_start.elapsed();
+
+        match &acquire_result {
+            Ok(AcquiredImage { id, suboptimal }) => {
+                swapchain
+                    .statistics
+                    .lock()
+                    .add_acquire(*id, acquire_wait, *suboptimal);
+            }
+            Err(AcquireError::OutOfDate) => swapchain.statistics.lock().add_out_of_date(),
+            Err(_) => (),
+        }
 
         if let &Err(AcquireError::FullScreenExclusiveLost) = &acquire_result {
             swapchain
@@ -1418,6 +2302,18 @@ pub fn acquire_next_image<W>(
 ///
 /// The actual behavior depends on the present mode that you passed when creating the
 /// swapchain.
+///
+/// `queue` doesn't have to be the queue that `before` executes on. If it's another queue, flushing
+/// the returned future first submits the work of `before` to its own queue, together with a
+/// semaphore that the presentation waits on.
+///
+/// If `queue` is in another queue family, and the images of the swapchain are owned exclusively,
+/// the ownership of the image is transferred to the queue family of `queue` before presenting it.
+/// This requires the queue family of `queue` to support graphics, compute or transfer operations,
+/// otherwise flushing returns [`FlushError::OwnershipTransferNotSupported`]. The ownership isn't
+/// transferred back when the image is acquired again, so its contents are undefined for the next
+/// frame. If the images are shared concurrently instead, both queue families must be among the
+/// families they are shared with.
 pub fn present<F, W>(
     swapchain: Arc<Swapchain<W>>,
     before: F,
@@ -1428,6 +2324,13 @@ where
     F: GpuFuture,
 {
     assert!(index < swapchain.images.len());
+    let queue_change = QueueChange::new(
+        before.queue(),
+        queue.clone(),
+        swapchain.images[index].image.clone(),
+        &swapchain.image_sharing,
+        ImageLayout::PresentSrc,
+    );
 
     // TODO: restore this check with a dummy ImageAccess implementation
     /*let swapchain_image = me.images.lock().unwrap().get(index).unwrap().0.upgrade().unwrap();       // TODO: return error instead
@@ -1442,6 +2345,8 @@ where
         swapchain,
         image_id: index,
         present_region: None,
+        present_id: None,
+        queue_change,
         flushed: AtomicBool::new(false),
         finished: AtomicBool::new(false),
     }
@@ -1464,6 +2369,13 @@ where
     F: GpuFuture,
 {
     assert!(index < swapchain.images.len());
+    let queue_change = QueueChange::new(
+        before.queue(),
+        queue.clone(),
+        swapchain.images[index].image.clone(),
+        &swapchain.image_sharing,
+        ImageLayout::PresentSrc,
+    );
 
     // TODO: restore this check with a dummy ImageAccess implementation
     /*let swapchain_image = me.images.lock().unwrap().get(index).unwrap().0.upgrade().unwrap();       // TODO: return error instead
@@ -1478,11 +2390,261 @@ where
         swapchain,
         image_id: index,
         present_region: Some(present_region),
+        present_id: None,
+        queue_change,
         flushed: AtomicBool::new(false),
         finished: AtomicBool::new(false),
     }
 }
 
+/// Same as `swapchain::present`, except it assigns a present ID to the presentation.
+///
+/// The present ID can be passed to [`Swapchain::wait_for_present`] to wait until the
+/// presentation has been displayed, which allows applications to pace their frames.
+///
+/// The [`present_id`](crate::device::Features::present_id) feature must be enabled on the
+/// device.
+///
+/// # Panics
+///
+/// - Panics if the `present_id` feature is not enabled on the device.
+/// - Panics if `present_id` is 0, or is not greater than all the present IDs previously used
+///   with `swapchain`.
+pub fn present_with_id<F, W>(
+    swapchain: Arc<Swapchain<W>>,
+    before: F,
+    queue: Arc<Queue>,
+    index: usize,
+    present_id: u64,
+) -> PresentFuture<F, W>
+where
+    F: GpuFuture,
+{
+    assert!(index < swapchain.images.len());
+    assert!(swapchain.device.enabled_features().present_id);
+    let queue_change = QueueChange::new(
+        before.queue(),
+        queue.clone(),
+        swapchain.images[index].image.clone(),
+        &swapchain.image_sharing,
+        ImageLayout::PresentSrc,
+    );
+
+    // VUID-VkPresentIdKHR-presentIds-04999
+    let prev_present_id = swapchain
+        .prev_present_id
+        .fetch_max(present_id, Ordering::SeqCst);
+    assert!(present_id > prev_present_id);
+
+    PresentFuture {
+        previous: before,
+        queue,
+        swapchain,
+        image_id: index,
+        present_region: None,
+        present_id: Some(present_id),
+        queue_change,
+        flushed: AtomicBool::new(false),
+        finished: AtomicBool::new(false),
+    }
+}
+
+// Hands an image over from the queue that the work before its presentation executes on, to the
+// queue that presents it.
+struct QueueChange {
+    // The queue that the image is handed over from.
+    source_queue: Arc<Queue>,
+    // The queue that the image is handed over to.
+    destination_queue: Arc<Queue>,
+    // Signaled by `source_queue` once the work before the presentation is done.
+    source_semaphore: Semaphore,
+    // If the image is owned exclusively by the queue family of `source_queue`, the barrier that
+    // transfers its ownership to the queue family of `destination_queue`, and the semaphore that
+    // `destination_queue` signals once it has acquired the ownership.
+    ownership_transfer: Option<(ImageMemoryBarrier, Semaphore)>,
+    state: Mutex<QueueChangeState>,
+}
+
+#[derive(Default)]
+struct QueueChangeState {
+    // Whether the submission to `source_queue` has been made.
+    source_submitted: bool,
+    // Whether the submission that acquires the ownership has been made to `destination_queue`.
+    destination_submitted: bool,
+    // The command buffers that release and acquire the ownership of the image, if they have been
+    // recorded. They must be kept alive until they have finished executing.
+    command_buffers: Vec<(UnsafeCommandBuffer, StandardCommandPoolAlloc)>,
+}
+
+impl QueueChange {
+    // Returns `None` if `source_queue` is `None` or the same queue as `destination_queue`, in
+    // which case the image doesn't need to be handed over.
+    //
+    // `layout` is the layout that the image is in after the work on `source_queue`.
+    fn new(
+        source_queue: Option<Arc<Queue>>,
+        destination_queue: Arc<Queue>,
+        image: Arc<UnsafeImage>,
+        image_sharing: &Sharing<SmallVec<[u32; 4]>>,
+        layout: ImageLayout,
+    ) -> Option<Self> {
+        let source_queue = source_queue.filter(|queue| *queue != destination_queue)?;
+        let device = destination_queue.device();
+        let source_index = source_queue.family().id();
+        let destination_index = destination_queue.family().id();
+
+        // Concurrently shared images can be used by all the queue families they are shared with,
+        // but the ownership of exclusively owned images must be released by one family and
+        // acquired by the other, with two barriers that must match.
+        let ownership_transfer = (source_index != destination_index
+            && matches!(image_sharing, Sharing::Exclusive))
+        .then(|| {
+            let subresource_range = image.subresource_range();
+            let barrier = ImageMemoryBarrier {
+                source_stages: PipelineStages {
+                    all_commands: true,
+                    ..PipelineStages::none()
+                },
+                source_access: AccessFlags {
+                    memory_write: true,
+                    ..AccessFlags::none()
+                },
+                destination_stages: PipelineStages {
+                    all_commands: true,
+                    ..PipelineStages::none()
+                },
+                old_layout: layout,
+                new_layout: layout,
+                queue_family_transfer: Some(QueueFamilyTransfer {
+                    source_index,
+                    destination_index,
+                }),
+                subresource_range,
+                ..ImageMemoryBarrier::image(image)
+            };
+
+            (barrier, Semaphore::from_pool(device.clone()).unwrap())
+        });
+
+        Some(QueueChange {
+            source_semaphore: Semaphore::from_pool(device.clone()).unwrap(),
+            source_queue,
+            destination_queue,
+            ownership_transfer,
+            state: Mutex::new(QueueChangeState::default()),
+        })
+    }
+
+    // Submits the work of `previous` to the source queue, followed by the release of the
+    // ownership of the image if needed, and the acquire of the ownership to the destination
+    // queue. Returns the semaphore that the destination queue must wait on before using the
+    // image.
+    //
+    // The submissions are only made once, so this can be called again after an error, or when
+    // the submission is built again.
+    unsafe fn submit<F>(&self, previous: &F) -> Result<&Semaphore, FlushError>
+    where
+        F: GpuFuture,
+    {
+        let mut state = self.state.lock();
+
+        if let Some((barrier, _)) = &self.ownership_transfer {
+            if state.command_buffers.is_empty() {
+                state.command_buffers = vec![
+                    record_barrier(&self.source_queue, barrier)?,
+                    record_barrier(&self.destination_queue, barrier)?,
+                ];
+            }
+        }
+
+        if !state.source_submitted {
+            let mut builder = match previous.build_submission()? {
+                SubmitAnyBuilder::Empty => SubmitCommandBufferBuilder::new(),
+                SubmitAnyBuilder::SemaphoresWait(sem) => sem.into(),
+                SubmitAnyBuilder::CommandBuffer(builder) => {
+                    debug_assert_eq!(builder.num_signal_semaphores(), 0);
+                    builder
+                }
+                SubmitAnyBuilder::BindSparse(_) | SubmitAnyBuilder::QueuePresent(_) => {
+                    // These can't signal the semaphore, so they are submitted by flushing
+                    // `previous`, and the semaphore is signaled by a separate submission.
+                    previous.flush()?;
+                    SubmitCommandBufferBuilder::new()
+                }
+            };
+
+            if let Some((release, _)) = state.command_buffers.first() {
+                builder.add_command_buffer(release);
+            }
+
+            builder.add_signal_semaphore(&self.source_semaphore);
+            builder.submit(&self.source_queue)?;
+            state.source_submitted = true;
+        }
+
+        match &self.ownership_transfer {
+            Some((_, acquire_semaphore)) => {
+                if !state.destination_submitted {
+                    let mut builder = SubmitCommandBufferBuilder::new();
+                    builder.add_wait_semaphore(
+                        &self.source_semaphore,
+                        PipelineStages {
+                            all_commands: true,
+                            ..PipelineStages::none()
+                        },
+                    );
+                    builder.add_command_buffer(&state.command_buffers[1].0);
+                    builder.add_signal_semaphore(acquire_semaphore);
+                    builder.submit(&self.destination_queue)?;
+                    state.destination_submitted = true;
+                }
+
+                Ok(acquire_semaphore)
+            }
+            None => Ok(&self.source_semaphore),
+        }
+    }
+}
+
+// Records a command buffer for the queue family of `queue` that only contains `barrier`.
+unsafe fn record_barrier(
+    queue: &Queue,
+    barrier: &ImageMemoryBarrier,
+) -> Result<(UnsafeCommandBuffer, StandardCommandPoolAlloc), FlushError> {
+    let queue_family = queue.family();
+
+    // VUID-vkCmdPipelineBarrier-commandBuffer-cmdpool
+    if !(queue_family.supports_graphics()
+        || queue_family.supports_compute()
+        || queue_family.explicitly_supports_transfers())
+    {
+        return Err(FlushError::OwnershipTransferNotSupported {
+            queue_family_index: queue_family.id(),
+        });
+    }
+
+    let pool_builder_alloc = Device::standard_command_pool(queue.device(), queue_family)
+        .allocate(CommandBufferLevel::Primary, 1)
+        .map_err(FlushError::OomError)?
+        .next()
+        .expect("Requested one command buffer from the command pool, but got zero.");
+    let mut builder = UnsafeCommandBufferBuilder::new(
+        pool_builder_alloc.inner(),
+        CommandBufferBeginInfo {
+            usage: CommandBufferUsage::OneTimeSubmit,
+            ..Default::default()
+        },
+    )
+    .map_err(FlushError::OomError)?;
+    builder.pipeline_barrier(&DependencyInfo {
+        image_memory_barriers: smallvec![barrier.clone()],
+        ..Default::default()
+    });
+    let command_buffer = builder.build().map_err(FlushError::OomError)?;
+
+    Ok((command_buffer, pool_builder_alloc.into_alloc()))
+}
+
 /// Represents the moment when the GPU will have access to a swapchain image.
 #[must_use]
 pub struct SwapchainAcquireFuture<W> {
@@ -1711,6 +2873,9 @@ where
     swapchain: Arc<Swapchain<W>>,
     image_id: usize,
     present_region: Option<PresentRegion>,
+    present_id: Option<u64>,
+    // If `previous` executes on another queue, hands the image over from that queue.
+    queue_change: Option<QueueChange>,
     // True if `flush()` has been called on the future, which means that the present command has
     // been submitted.
     flushed: AtomicBool,
@@ -1723,6 +2888,12 @@ impl<P, W> PresentFuture<P, W>
 where
     P: GpuFuture,
 {
+    /// Returns the present ID that was assigned to the presentation, if any.
+    #[inline]
+    pub fn present_id(&self) -> Option<u64> {
+        self.present_id
+    }
+
     /// Returns the index of the image in the list of images returned when creating the swapchain.
     #[inline]
     pub fn image_id(&self) -> usize {
@@ -1756,6 +2927,23 @@ where
         // TODO: if the swapchain image layout is not PRESENT, should add a transition command
         // buffer
 
+        if let Some(queue_change) = &self.queue_change {
+            // `previous` executes on another queue, so its work is submitted to that queue here,
+            // together with a semaphore that the present command waits on.
+            let semaphore = queue_change.submit(&self.previous)?;
+
+            let mut sem = SubmitSemaphoresWaitBuilder::new();
+            sem.add_wait_semaphore(semaphore);
+            let mut builder: SubmitPresentBuilder = sem.into();
+            builder.add_swapchain(
+                &self.swapchain,
+                self.image_id as u32,
+                self.present_region.as_ref(),
+                self.present_id,
+            );
+            return Ok(SubmitAnyBuilder::QueuePresent(builder));
+        }
+
         Ok(match self.previous.build_submission()? {
             SubmitAnyBuilder::Empty => {
                 let mut builder = SubmitPresentBuilder::new();
@@ -1763,6 +2951,7 @@ where
                     &self.swapchain,
                     self.image_id as u32,
                     self.present_region.as_ref(),
+                    self.present_id,
                 );
                 SubmitAnyBuilder::QueuePresent(builder)
             }
@@ -1772,6 +2961,7 @@ where
                     &self.swapchain,
                     self.image_id as u32,
                     self.present_region.as_ref(),
+                    self.present_id,
                 );
                 SubmitAnyBuilder::QueuePresent(builder)
             }
@@ -1785,6 +2975,7 @@ where
                     &self.swapchain,
                     self.image_id as u32,
                     self.present_region.as_ref(),
+                    self.present_id,
                 );
                 SubmitAnyBuilder::QueuePresent(builder)
             }
@@ -1798,6 +2989,7 @@ where
                     &self.swapchain,
                     self.image_id as u32,
                     self.present_region.as_ref(),
+                    self.present_id,
                 );
                 SubmitAnyBuilder::QueuePresent(builder)
             }
@@ -1836,6 +3028,15 @@ where
                             .store(false, Ordering::SeqCst);
                     }
 
+                    {
+                        let mut statistics = self.swapchain.statistics.lock();
+                        statistics.add_present(self.image_id);
+
+                        if let &Err(SubmitPresentError::OutOfDate) = &present_result {
+                            statistics.add_out_of_date();
+                        }
+                    }
+
                     present_result?;
                 }
                 _ => unreachable!(),
@@ -1861,7 +3062,7 @@ where
     fn queue(&self) -> Option<Arc<Queue>> {
         debug_assert!(match self.previous.queue() {
             None => true,
-            Some(q) => q == self.queue,
+            Some(q) => q == self.queue || self.queue_change.is_some(),
         });
 
         Some(self.queue.clone())
@@ -1987,3 +3188,202 @@ pub unsafe fn acquire_next_image_raw<W>(
 
     Ok(AcquiredImage { id, suboptimal })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameStatistics, QueueChange};
+    use crate::{
+        command_buffer::{
+            submit::SubmitCommandBufferBuilder, AutoCommandBufferBuilder, ClearColorImageInfo,
+            CommandBufferUsage,
+        },
+        device::{physical::PhysicalDevice, Device, DeviceCreateInfo, Queue, QueueCreateInfo},
+        format::Format,
+        image::{ImageAccess, ImageDimensions, ImageLayout, StorageImage},
+        instance::Instance,
+        sync::{self, Fence, GpuFuture, PipelineStages, Sharing},
+    };
+    use std::{ptr, sync::Arc, time::Duration};
+
+    #[test]
+    fn frame_statistics() {
+        let mut statistics = FrameStatistics::new(2);
+
+        statistics.add_acquire(0, Duration::from_millis(4), false);
+        statistics.add_acquire(1, Duration::from_millis(2), true);
+        statistics.add_present(0);
+        statistics.add_out_of_date();
+
+        let statistics = statistics.statistics;
+        assert_eq!(statistics.acquire_count, 2);
+        assert_eq!(statistics.present_count, 1);
+        assert_eq!(statistics.acquire_wait_max, Duration::from_millis(4));
+        assert_eq!(statistics.last_acquire_wait, Duration::from_millis(2));
+        assert_eq!(statistics.average_acquire_wait(), Duration::from_millis(3));
+        assert_eq!(statistics.suboptimal_count, 1);
+        assert_eq!(statistics.out_of_date_count, 1);
+    }
+
+    // Creates a device with a queue to render on and a queue to present on, in different queue
+    // families if `other_family` is true.
+    fn dev_and_queues(other_family: bool) -> Option<(Arc<Device>, Arc<Queue>, Arc<Queue>)> {
+        let instance = Instance::new(Default::default()).ok()?;
+
+        let (physical_device, source_family, destination_family) =
+            PhysicalDevice::enumerate(&instance).find_map(|p| {
+                let source_family = p.queue_families().find(|q| q.supports_graphics())?;
+                let destination_family = if other_family {
+                    p.queue_families().find(|q| {
+                        q.id() != source_family.id()
+                            && (q.supports_graphics()
+                                || q.supports_compute()
+                                || q.explicitly_supports_transfers())
+                    })?
+                } else {
+                    Some(source_family).filter(|q| q.queues_count() >= 2)?
+                };
+
+                Some((p, source_family, destination_family))
+            })?;
+
+        let queue_create_infos = if other_family {
+            vec![
+                QueueCreateInfo::family(source_family),
+                QueueCreateInfo::family(destination_family),
+            ]
+        } else {
+            vec![QueueCreateInfo {
+                queues: vec![0.5; 2],
+                ..QueueCreateInfo::family(source_family)
+            }]
+        };
+
+        let (device, mut queues) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                queue_create_infos,
+                ..Default::default()
+            },
+        )
+        .ok()?;
+
+        let source_queue = queues.next().unwrap();
+        let destination_queue = queues.next().unwrap();
+        Some((device, source_queue, destination_queue))
+    }
+
+    // Clears an image on the source queue, and hands it over to the destination queue.
+    fn hand_over_image(
+        device: Arc<Device>,
+        source_queue: Arc<Queue>,
+        destination_queue: Arc<Queue>,
+    ) -> bool {
+        let image = StorageImage::new(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: 4,
+                height: 4,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            [source_queue.family()],
+        )
+        .unwrap();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            source_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .clear_color_image(ClearColorImageInfo::image(image.clone()))
+            .unwrap();
+        let future = sync::now(device.clone())
+            .then_execute(source_queue.clone(), builder.build().unwrap())
+            .unwrap();
+
+        let queue_change = QueueChange::new(
+            future.queue(),
+            destination_queue.clone(),
+            image.inner().image.clone(),
+            &Sharing::Exclusive,
+            ImageLayout::General,
+        )
+        .unwrap();
+        let ownership_transferred = queue_change.ownership_transfer.is_some();
+
+        unsafe {
+            let semaphore = queue_change.submit(&future).unwrap();
+            // The work of `future` is only submitted once.
+            assert!(ptr::eq(queue_change.submit(&future).unwrap(), semaphore));
+
+            let fence = Fence::from_pool(device).unwrap();
+            let mut builder = SubmitCommandBufferBuilder::new();
+            builder.add_wait_semaphore(
+                semaphore,
+                PipelineStages {
+                    all_commands: true,
+                    ..PipelineStages::none()
+                },
+            );
+            builder.set_fence_signal(&fence);
+            builder.submit(&destination_queue).unwrap();
+
+            fence.wait(None).unwrap();
+            source_queue.wait().unwrap();
+            future.signal_finished();
+        }
+
+        ownership_transferred
+    }
+
+    #[test]
+    fn queue_change_same_queue() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let image = StorageImage::new(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: 4,
+                height: 4,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            [queue.family()],
+        )
+        .unwrap();
+
+        // Nothing needs to be handed over when presenting on the queue that rendered the image.
+        assert!(QueueChange::new(
+            Some(queue.clone()),
+            queue,
+            image.inner().image.clone(),
+            &Sharing::Exclusive,
+            ImageLayout::PresentSrc,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn queue_change_same_family() {
+        let (device, source_queue, destination_queue) = match dev_and_queues(false) {
+            Some(x) => x,
+            None => return,
+        };
+
+        // The queues are in the same family, so only a semaphore is needed.
+        assert!(!hand_over_image(device, source_queue, destination_queue));
+    }
+
+    #[test]
+    fn queue_change_other_family() {
+        let (device, source_queue, destination_queue) = match dev_and_queues(true) {
+            Some(x) => x,
+            None => return,
+        };
+
+        // The image is owned exclusively, so its ownership is transferred after the semaphore.
+        assert!(hand_over_image(device, source_queue, destination_queue));
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Destruction of resources once the GPU has finished using them.
+//!
+//! Keeping an `Arc` to every resource used by a submission until that submission's future is
+//! dropped is the usual way to keep resources alive in vulkano. This can hold on to memory long
+//! after the GPU is done with it, for example when futures are kept around for several frames,
+//! and doesn't help at all for objects destroyed through raw handles.
+//!
+//! The [`DeferredDeleter`] of a device, which is returned by
+//! [`Device::deferred_deleter`](crate::device::Device::deferred_deleter), instead queues
+//! resources together with the fence of the last submission that uses them, and destroys them
+//! as soon as that fence is signaled.
+
+use super::{Fence, FenceSignalFuture, GpuFuture};
+use crate::OomError;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// Queue of resources that are destroyed once the GPU has finished using them.
+///
+/// Call [`cleanup`](Self::cleanup) regularly, for example once per frame, to destroy the
+/// resources whose fences have been signaled.
+///
+/// Resources that hold an `Arc<Device>` keep the device alive while they are queued. Resources
+/// that are still queued when the device is dropped are destroyed after waiting for the device
+/// to become idle.
+pub struct DeferredDeleter {
+    pending: Mutex<Vec<PendingDeletions>>,
+}
+
+struct PendingDeletions {
+    fence: Arc<dyn FenceStatus>,
+    deletions: Vec<Box<dyn FnOnce() + Send>>,
+}
+
+impl DeferredDeleter {
+    pub(crate) fn new() -> Self {
+        DeferredDeleter {
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queues `resource` to be dropped once `fence` is signaled.
+    #[inline]
+    pub fn defer<R>(&self, fence: Arc<dyn FenceStatus>, resource: R)
+    where
+        R: Send + 'static,
+    {
+        self.defer_fn(fence, move || drop(resource))
+    }
+
+    /// Queues `destroy` to be called once `fence` is signaled.
+    ///
+    /// This is meant for objects that are destroyed through raw handles, which must not be
+    /// destroyed while the GPU is still using them.
+    pub fn defer_fn<D>(&self, fence: Arc<dyn FenceStatus>, destroy: D)
+    where
+        D: FnOnce() + Send + 'static,
+    {
+        let mut pending = self.pending.lock().unwrap();
+
+        match pending
+            .iter_mut()
+            .find(|entry| same_fence(&entry.fence, &fence))
+        {
+            Some(entry) => entry.deletions.push(Box::new(destroy)),
+            None => pending.push(PendingDeletions {
+                fence,
+                deletions: vec![Box::new(destroy)],
+            }),
+        }
+    }
+
+    /// Destroys the queued resources whose fence has been signaled, and returns how many were
+    /// destroyed.
+    ///
+    /// This never blocks.
+    pub fn cleanup(&self) -> Result<usize, OomError> {
+        let ready = {
+            let mut pending = self.pending.lock().unwrap();
+            let mut ready = Vec::new();
+            let mut index = 0;
+
+            while index < pending.len() {
+                if pending[index].fence.is_signaled()? {
+                    ready.push(pending.swap_remove(index));
+                } else {
+                    index += 1;
+                }
+            }
+
+            ready
+        };
+
+        // The lock is released at this point, so that destroying a resource can queue others.
+        Ok(destroy(ready))
+    }
+
+    /// Returns the number of resources that are waiting to be destroyed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pending
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|entry| entry.deletions.len())
+            .sum()
+    }
+
+    /// Returns true if no resources are waiting to be destroyed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pending.lock().unwrap().is_empty()
+    }
+
+    /// Destroys all queued resources, regardless of their fences.
+    ///
+    /// # Safety
+    ///
+    /// - The device must be idle.
+    pub(crate) unsafe fn destroy_all(&self) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        destroy(pending);
+    }
+}
+
+impl fmt::Debug for DeferredDeleter {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt.debug_struct("DeferredDeleter")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+fn destroy(entries: Vec<PendingDeletions>) -> usize {
+    let mut count = 0;
+
+    for entry in entries {
+        for deletion in entry.deletions {
+            deletion();
+            count += 1;
+        }
+    }
+
+    count
+}
+
+// Only compares the data pointers, as the same object can have several vtables.
+fn same_fence(a: &Arc<dyn FenceStatus>, b: &Arc<dyn FenceStatus>) -> bool {
+    Arc::as_ptr(a) as *const () == Arc::as_ptr(b) as *const ()
+}
+
+/// An object that can tell whether the GPU work it is associated with has finished.
+pub trait FenceStatus: Send + Sync {
+    /// Returns true if the GPU work has finished.
+    fn is_signaled(&self) -> Result<bool, OomError>;
+}
+
+impl FenceStatus for Fence {
+    #[inline]
+    fn is_signaled(&self) -> Result<bool, OomError> {
+        Fence::is_signaled(self)
+    }
+}
+
+impl<F> FenceStatus for FenceSignalFuture<F>
+where
+    F: GpuFuture,
+    FenceSignalFuture<F>: Send + Sync,
+{
+    #[inline]
+    fn is_signaled(&self) -> Result<bool, OomError> {
+        FenceSignalFuture::is_signaled(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sync::{Fence, FenceCreateInfo};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn destroy_when_signaled() {
+        let (device, _) = gfx_dev_and_queue!();
+        let deleter = device.deferred_deleter();
+
+        let unsignaled = Arc::new(Fence::new(device.clone(), Default::default()).unwrap());
+        let signaled = Arc::new(
+            Fence::new(
+                device.clone(),
+                FenceCreateInfo {
+                    signaled: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        let destroyed = Arc::new(AtomicUsize::new(0));
+
+        for fence in [&unsignaled, &signaled, &signaled] {
+            let destroyed = destroyed.clone();
+            deleter.defer_fn(fence.clone(), move || {
+                destroyed.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        assert_eq!(deleter.len(), 3);
+        assert_eq!(deleter.cleanup().unwrap(), 2);
+        assert_eq!(destroyed.load(Ordering::SeqCst), 2);
+        assert_eq!(deleter.len(), 1);
+
+        unsafe { deleter.destroy_all() };
+        assert_eq!(destroyed.load(Ordering::SeqCst), 3);
+        assert!(deleter.is_empty());
+    }
+}
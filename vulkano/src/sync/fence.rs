@@ -10,13 +10,17 @@
 use crate::{
     check_errors,
     device::{Device, DeviceOwned},
-    Error, OomError, Success, VulkanObject,
+    Error, OomError, Success, Version, VulkanObject,
 };
 use smallvec::SmallVec;
 use std::{
-    error, fmt,
+    error,
+    ffi::c_void,
+    fmt,
+    fs::File,
     hash::{Hash, Hasher},
     mem::MaybeUninit,
+    ops::BitOr,
     ptr,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -43,30 +47,93 @@ pub struct Fence {
     // Indicates whether this fence was taken from the fence pool.
     // If true, will be put back into fence pool on drop.
     must_put_in_pool: bool,
+
+    export_handle_types: ExternalFenceHandleTypes,
 }
 
 impl Fence {
     /// Creates a new `Fence`.
-    pub fn new(device: Arc<Device>, create_info: FenceCreateInfo) -> Result<Fence, OomError> {
-        let FenceCreateInfo { signaled, _ne: _ } = create_info;
+    pub fn new(
+        device: Arc<Device>,
+        create_info: FenceCreateInfo,
+    ) -> Result<Fence, FenceCreationError> {
+        let FenceCreateInfo {
+            signaled,
+            export_handle_types,
+            _ne: _,
+        } = create_info;
+        let instance = device.instance();
+
+        if export_handle_types != ExternalFenceHandleTypes::none() {
+            if !(device.api_version() >= Version::V1_1
+                || device.enabled_extensions().khr_external_fence)
+            {
+                return Err(FenceCreationError::MissingExtension("khr_external_fence"));
+            }
+
+            if !(instance.api_version() >= Version::V1_1
+                || instance
+                    .enabled_extensions()
+                    .khr_external_fence_capabilities)
+            {
+                return Err(FenceCreationError::MissingExtension(
+                    "khr_external_fence_capabilities",
+                ));
+            }
+
+            // VUID-VkExportFenceCreateInfo-handleTypes-01446
+            for handle_type in ExternalFenceHandleType::ALL {
+                if !export_handle_types.contains(handle_type) {
+                    continue;
+                }
+
+                let exportable = device
+                    .physical_device()
+                    .external_fence_properties(ExternalFenceInfo::handle_type(handle_type))
+                    .map_or(true, |properties| properties.exportable);
 
+                if !exportable {
+                    return Err(FenceCreationError::HandleTypeNotExportable { handle_type });
+                }
+            }
+        }
+
+        Ok(Fence::new_raw(device, signaled, export_handle_types)?)
+    }
+
+    fn new_raw(
+        device: Arc<Device>,
+        signaled: bool,
+        export_handle_types: ExternalFenceHandleTypes,
+    ) -> Result<Fence, OomError> {
         let mut flags = ash::vk::FenceCreateFlags::empty();
 
         if signaled {
             flags |= ash::vk::FenceCreateFlags::SIGNALED;
         }
 
-        let create_info = ash::vk::FenceCreateInfo {
-            flags,
-            ..Default::default()
-        };
+        let mut create_info = ash::vk::FenceCreateInfo::builder().flags(flags);
+
+        let mut export_fence_create_info =
+            if export_handle_types != ExternalFenceHandleTypes::none() {
+                Some(ash::vk::ExportFenceCreateInfo {
+                    handle_types: export_handle_types.into(),
+                    ..Default::default()
+                })
+            } else {
+                None
+            };
+
+        if let Some(info) = export_fence_create_info.as_mut() {
+            create_info = create_info.push_next(info);
+        }
 
         let handle = unsafe {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
             check_errors((fns.v1_0.create_fence)(
                 device.internal_object(),
-                &create_info,
+                &create_info.build(),
                 ptr::null(),
                 output.as_mut_ptr(),
             ))?;
@@ -78,6 +145,8 @@ impl Fence {
             device,
             is_signaled: AtomicBool::new(signaled),
             must_put_in_pool: false,
+
+            export_handle_types,
         })
     }
 
@@ -106,11 +175,13 @@ impl Fence {
                     device,
                     is_signaled: AtomicBool::new(false),
                     must_put_in_pool: true,
+
+                    export_handle_types: ExternalFenceHandleTypes::none(),
                 }
             }
             None => {
                 // Pool is empty, alloc new fence
-                let mut fence = Fence::new(device, FenceCreateInfo::default())?;
+                let mut fence = Fence::new_raw(device, false, ExternalFenceHandleTypes::none())?;
                 fence.must_put_in_pool = true;
                 fence
             }
@@ -119,6 +190,12 @@ impl Fence {
         Ok(fence)
     }
 
+    /// Returns the handle types that can be exported from the fence.
+    #[inline]
+    pub fn export_handle_types(&self) -> ExternalFenceHandleTypes {
+        self.export_handle_types
+    }
+
     /// Returns true if the fence is signaled.
     #[inline]
     pub fn is_signaled(&self) -> Result<bool, OomError> {
@@ -305,6 +382,254 @@ impl Fence {
         }
         Ok(())
     }
+
+    /// Exports the fence into a Unix file descriptor. The caller owns the returned `File`.
+    ///
+    /// `handle_type` must be [`ExternalFenceHandleType::OpaqueFd`] or
+    /// [`ExternalFenceHandleType::SyncFd`], and must have been provided in `export_handle_types`
+    /// when creating the fence. Exporting a `SyncFd` has the same effect as resetting the fence:
+    /// the returned file descriptor becomes signaled instead of the fence.
+    ///
+    /// # Safety
+    ///
+    /// - If `handle_type` is [`ExternalFenceHandleType::SyncFd`], the fence must be signaled, or
+    ///   have a signal operation pending.
+    pub unsafe fn export_fd(
+        &self,
+        handle_type: ExternalFenceHandleType,
+    ) -> Result<File, FenceExportError> {
+        if !self.device.enabled_extensions().khr_external_fence_fd {
+            return Err(FenceExportError::MissingExtension("khr_external_fence_fd"));
+        }
+
+        // VUID-VkFenceGetFdInfoKHR-handleType-01456
+        if !matches!(
+            handle_type,
+            ExternalFenceHandleType::OpaqueFd | ExternalFenceHandleType::SyncFd
+        ) {
+            return Err(FenceExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        // VUID-VkFenceGetFdInfoKHR-handleType-01453
+        if !self.export_handle_types.contains(handle_type) {
+            return Err(FenceExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        // VUID-VkFenceGetFdInfoKHR-handleType-01454
+        // Can't validate, therefore unsafe
+
+        #[cfg(not(unix))]
+        unreachable!("`khr_external_fence_fd` was somehow enabled on a non-Unix system");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::FromRawFd;
+
+            let fd = {
+                let info = ash::vk::FenceGetFdInfoKHR {
+                    fence: self.handle,
+                    handle_type: handle_type.into(),
+                    ..Default::default()
+                };
+
+                let fns = self.device.fns();
+                let mut output = MaybeUninit::uninit();
+                check_errors((fns.khr_external_fence_fd.get_fence_fd_khr)(
+                    self.device.internal_object(),
+                    &info,
+                    output.as_mut_ptr(),
+                ))?;
+                output.assume_init()
+            };
+
+            if handle_type == ExternalFenceHandleType::SyncFd {
+                self.is_signaled.store(false, Ordering::Relaxed);
+            }
+
+            let file = File::from_raw_fd(fd);
+            Ok(file)
+        }
+    }
+
+    /// Exports the fence into a Win32 handle.
+    ///
+    /// `handle_type` must be [`ExternalFenceHandleType::OpaqueWin32`] or
+    /// [`ExternalFenceHandleType::OpaqueWin32Kmt`], and must have been provided in
+    /// `export_handle_types` when creating the fence. For `OpaqueWin32`, the caller owns the
+    /// returned handle, and must close it with `CloseHandle` when it's no longer needed.
+    pub unsafe fn export_win32_handle(
+        &self,
+        handle_type: ExternalFenceHandleType,
+    ) -> Result<*mut c_void, FenceExportError> {
+        if !self.device.enabled_extensions().khr_external_fence_win32 {
+            return Err(FenceExportError::MissingExtension(
+                "khr_external_fence_win32",
+            ));
+        }
+
+        // VUID-VkFenceGetWin32HandleInfoKHR-handleType-01452
+        if !matches!(
+            handle_type,
+            ExternalFenceHandleType::OpaqueWin32 | ExternalFenceHandleType::OpaqueWin32Kmt
+        ) {
+            return Err(FenceExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        // VUID-VkFenceGetWin32HandleInfoKHR-handleType-01448
+        if !self.export_handle_types.contains(handle_type) {
+            return Err(FenceExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        let info = ash::vk::FenceGetWin32HandleInfoKHR {
+            fence: self.handle,
+            handle_type: handle_type.into(),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        let mut output = MaybeUninit::uninit();
+        check_errors((fns.khr_external_fence_win32.get_fence_win32_handle_khr)(
+            self.device.internal_object(),
+            &info,
+            output.as_mut_ptr(),
+        ))?;
+
+        Ok(output.assume_init())
+    }
+
+    /// Imports a Unix file descriptor into the fence, replacing its payload.
+    ///
+    /// If `temporary` is `true`, the imported payload is only used until the fence is next reset,
+    /// after which the previous payload is restored.
+    ///
+    /// `handle_type` must be [`ExternalFenceHandleType::OpaqueFd`] or
+    /// [`ExternalFenceHandleType::SyncFd`]. A `SyncFd` can only be imported temporarily.
+    ///
+    /// # Safety
+    ///
+    /// - `file` must be a valid file descriptor of type `handle_type`.
+    /// - If `handle_type` is [`ExternalFenceHandleType::OpaqueFd`], `file` must have been exported
+    ///   from a fence on a device with the same device and driver UUIDs.
+    /// - The fence must not be in use by a pending queue operation.
+    /// - Vulkan takes ownership of `file` if the import succeeds. You must not perform any
+    ///   operations on it or its duplicates afterwards.
+    pub unsafe fn import_fd(
+        &self,
+        handle_type: ExternalFenceHandleType,
+        file: File,
+        temporary: bool,
+    ) -> Result<(), FenceImportError> {
+        if !self.device.enabled_extensions().khr_external_fence_fd {
+            return Err(FenceImportError::MissingExtension("khr_external_fence_fd"));
+        }
+
+        match handle_type {
+            ExternalFenceHandleType::OpaqueFd => (),
+            // VUID-VkImportFenceFdInfoKHR-handleType-07306
+            ExternalFenceHandleType::SyncFd if temporary => (),
+            // VUID-VkImportFenceFdInfoKHR-handleType-01464
+            _ => return Err(FenceImportError::HandleTypeNotSupported { handle_type }),
+        }
+
+        #[cfg(not(unix))]
+        unreachable!("`khr_external_fence_fd` was somehow enabled on a non-Unix system");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+            let fd = file.into_raw_fd();
+            let import_info = ash::vk::ImportFenceFdInfoKHR {
+                fence: self.handle,
+                flags: if temporary {
+                    ash::vk::FenceImportFlags::TEMPORARY
+                } else {
+                    ash::vk::FenceImportFlags::empty()
+                },
+                handle_type: handle_type.into(),
+                fd,
+                ..Default::default()
+            };
+
+            let fns = self.device.fns();
+            let result = check_errors((fns.khr_external_fence_fd.import_fence_fd_khr)(
+                self.device.internal_object(),
+                &import_info,
+            ));
+
+            if let Err(err) = result {
+                // Ownership is only transferred on success.
+                drop(File::from_raw_fd(fd));
+                return Err(err.into());
+            }
+
+            // The state of the fence now depends on the imported payload.
+            self.is_signaled.store(false, Ordering::Relaxed);
+
+            Ok(())
+        }
+    }
+
+    /// Imports a Win32 handle into the fence, replacing its payload.
+    ///
+    /// If `temporary` is `true`, the imported payload is only used until the fence is next reset,
+    /// after which the previous payload is restored.
+    ///
+    /// `handle_type` must be [`ExternalFenceHandleType::OpaqueWin32`] or
+    /// [`ExternalFenceHandleType::OpaqueWin32Kmt`]. Unlike file descriptors, Win32 handles are not
+    /// owned by Vulkan after they are imported, and must still be closed by the caller.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid handle of type `handle_type`, that was exported from a fence on
+    ///   a device with the same device and driver UUIDs.
+    /// - The fence must not be in use by a pending queue operation.
+    pub unsafe fn import_win32_handle(
+        &self,
+        handle_type: ExternalFenceHandleType,
+        handle: *mut c_void,
+        temporary: bool,
+    ) -> Result<(), FenceImportError> {
+        if !self.device.enabled_extensions().khr_external_fence_win32 {
+            return Err(FenceImportError::MissingExtension(
+                "khr_external_fence_win32",
+            ));
+        }
+
+        // VUID-VkImportFenceWin32HandleInfoKHR-handleType-01457
+        if !matches!(
+            handle_type,
+            ExternalFenceHandleType::OpaqueWin32 | ExternalFenceHandleType::OpaqueWin32Kmt
+        ) {
+            return Err(FenceImportError::HandleTypeNotSupported { handle_type });
+        }
+
+        let import_info = ash::vk::ImportFenceWin32HandleInfoKHR {
+            fence: self.handle,
+            flags: if temporary {
+                ash::vk::FenceImportFlags::TEMPORARY
+            } else {
+                ash::vk::FenceImportFlags::empty()
+            },
+            handle_type: handle_type.into(),
+            handle,
+            name: ptr::null(),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        check_errors(
+            (fns.khr_external_fence_win32.import_fence_win32_handle_khr)(
+                self.device.internal_object(),
+                &import_info,
+            ),
+        )?;
+
+        // The state of the fence now depends on the imported payload.
+        self.is_signaled.store(false, Ordering::Relaxed);
+
+        Ok(())
+    }
 }
 
 impl Drop for Fence {
@@ -363,6 +688,11 @@ pub struct FenceCreateInfo {
     /// The default value is `false`.
     pub signaled: bool,
 
+    /// The handle types that can be exported from the fence.
+    ///
+    /// The default value is [`ExternalFenceHandleTypes::none()`].
+    pub export_handle_types: ExternalFenceHandleTypes,
+
     pub _ne: crate::NonExhaustive,
 }
 
@@ -371,11 +701,245 @@ impl Default for FenceCreateInfo {
     fn default() -> Self {
         Self {
             signaled: false,
+            export_handle_types: ExternalFenceHandleTypes::none(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Error that can happen when creating a fence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FenceCreationError {
+    /// Not enough memory available.
+    OomError(OomError),
+
+    /// An extension is missing.
+    MissingExtension(&'static str),
+
+    /// A handle type in `export_handle_types` can't be exported from a fence on this device.
+    HandleTypeNotExportable {
+        handle_type: ExternalFenceHandleType,
+    },
+}
+
+impl fmt::Display for FenceCreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::MissingExtension(s) => {
+                write!(fmt, "Missing the following extension: {}", s)
+            }
+            Self::HandleTypeNotExportable { handle_type } => write!(
+                fmt,
+                "the handle type {:?} in `export_handle_types` can't be exported from a fence on this device",
+                handle_type,
+            ),
+        }
+    }
+}
+
+impl From<Error> for FenceCreationError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            e @ Error::OutOfHostMemory | e @ Error::OutOfDeviceMemory => Self::OomError(e.into()),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+impl error::Error for FenceCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<OomError> for FenceCreationError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+/// Describes the handle type used for Vulkan external fence APIs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ExternalFenceHandleType {
+    OpaqueFd = ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_FD.as_raw(),
+    OpaqueWin32 = ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32.as_raw(),
+    OpaqueWin32Kmt = ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32_KMT.as_raw(),
+    SyncFd = ash::vk::ExternalFenceHandleTypeFlags::SYNC_FD.as_raw(),
+}
+
+impl ExternalFenceHandleType {
+    // All the handle types, in the order of their bits.
+    pub(crate) const ALL: [ExternalFenceHandleType; 4] = [
+        ExternalFenceHandleType::OpaqueFd,
+        ExternalFenceHandleType::OpaqueWin32,
+        ExternalFenceHandleType::OpaqueWin32Kmt,
+        ExternalFenceHandleType::SyncFd,
+    ];
+}
+
+impl From<ExternalFenceHandleType> for ash::vk::ExternalFenceHandleTypeFlags {
+    fn from(val: ExternalFenceHandleType) -> Self {
+        Self::from_raw(val as u32)
+    }
+}
+
+/// A mask of multiple handle types.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ExternalFenceHandleTypes {
+    pub opaque_fd: bool,
+    pub opaque_win32: bool,
+    pub opaque_win32_kmt: bool,
+    pub sync_fd: bool,
+}
+
+impl ExternalFenceHandleTypes {
+    /// Builds a `ExternalFenceHandleTypes` with all values set to false. Useful as a default value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use vulkano::sync::ExternalFenceHandleTypes;
+    ///
+    /// let _handle_type = ExternalFenceHandleTypes {
+    ///     opaque_fd: true,
+    ///     .. ExternalFenceHandleTypes::none()
+    /// };
+    /// ```
+    #[inline]
+    pub fn none() -> ExternalFenceHandleTypes {
+        ExternalFenceHandleTypes {
+            opaque_fd: false,
+            opaque_win32: false,
+            opaque_win32_kmt: false,
+            sync_fd: false,
+        }
+    }
+
+    /// Builds an `ExternalFenceHandleTypes` for a posix file descriptor.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use vulkano::sync::ExternalFenceHandleTypes;
+    ///
+    /// let _handle_type = ExternalFenceHandleTypes::posix();
+    /// ```
+    #[inline]
+    pub fn posix() -> ExternalFenceHandleTypes {
+        ExternalFenceHandleTypes {
+            opaque_fd: true,
+            ..ExternalFenceHandleTypes::none()
+        }
+    }
+
+    /// Returns whether `handle_type` is included.
+    #[inline]
+    pub fn contains(&self, handle_type: ExternalFenceHandleType) -> bool {
+        match handle_type {
+            ExternalFenceHandleType::OpaqueFd => self.opaque_fd,
+            ExternalFenceHandleType::OpaqueWin32 => self.opaque_win32,
+            ExternalFenceHandleType::OpaqueWin32Kmt => self.opaque_win32_kmt,
+            ExternalFenceHandleType::SyncFd => self.sync_fd,
+        }
+    }
+}
+
+impl From<ExternalFenceHandleTypes> for ash::vk::ExternalFenceHandleTypeFlags {
+    #[inline]
+    fn from(val: ExternalFenceHandleTypes) -> Self {
+        let mut result = ash::vk::ExternalFenceHandleTypeFlags::empty();
+        if val.opaque_fd {
+            result |= ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_FD;
+        }
+        if val.opaque_win32 {
+            result |= ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32;
+        }
+        if val.opaque_win32_kmt {
+            result |= ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32_KMT;
+        }
+        if val.sync_fd {
+            result |= ash::vk::ExternalFenceHandleTypeFlags::SYNC_FD;
+        }
+        result
+    }
+}
+
+impl From<ash::vk::ExternalFenceHandleTypeFlags> for ExternalFenceHandleTypes {
+    fn from(val: ash::vk::ExternalFenceHandleTypeFlags) -> Self {
+        Self {
+            opaque_fd: !(val & ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_FD).is_empty(),
+            opaque_win32: !(val & ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32).is_empty(),
+            opaque_win32_kmt: !(val & ash::vk::ExternalFenceHandleTypeFlags::OPAQUE_WIN32_KMT)
+                .is_empty(),
+            sync_fd: !(val & ash::vk::ExternalFenceHandleTypeFlags::SYNC_FD).is_empty(),
+        }
+    }
+}
+
+impl BitOr for ExternalFenceHandleTypes {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        ExternalFenceHandleTypes {
+            opaque_fd: self.opaque_fd || rhs.opaque_fd,
+            opaque_win32: self.opaque_win32 || rhs.opaque_win32,
+            opaque_win32_kmt: self.opaque_win32_kmt || rhs.opaque_win32_kmt,
+            sync_fd: self.sync_fd || rhs.sync_fd,
+        }
+    }
+}
+
+/// The fence configuration to query in
+/// [`PhysicalDevice::external_fence_properties`](crate::device::physical::PhysicalDevice::external_fence_properties).
+#[derive(Clone, Debug)]
+pub struct ExternalFenceInfo {
+    /// The external handle type that will be used with the fence.
+    pub handle_type: ExternalFenceHandleType,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl ExternalFenceInfo {
+    /// Returns an `ExternalFenceInfo` with the specified `handle_type`.
+    #[inline]
+    pub fn handle_type(handle_type: ExternalFenceHandleType) -> Self {
+        Self {
+            handle_type,
             _ne: crate::NonExhaustive(()),
         }
     }
 }
 
+/// The properties for exporting or importing external handles, when a fence is created with a
+/// specific configuration.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ExternalFenceProperties {
+    /// Whether a handle can be exported to an external source with the queried
+    /// external handle type.
+    pub exportable: bool,
+
+    /// Whether a handle can be imported from an external source with the queried
+    /// external handle type.
+    pub importable: bool,
+
+    /// Which external handle types can be re-exported after the queried external handle type has
+    /// been imported.
+    pub export_from_imported_handle_types: ExternalFenceHandleTypes,
+
+    /// Which external handle types can be enabled along with the queried external handle type
+    /// when creating the fence.
+    pub compatible_handle_types: ExternalFenceHandleTypes,
+}
+
 /// Error that can be returned when waiting on a fence.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum FenceWaitError {
@@ -426,10 +990,138 @@ impl From<Error> for FenceWaitError {
     }
 }
 
+/// Error that can happen when exporting a handle from a fence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FenceExportError {
+    /// Not enough memory available.
+    OomError(OomError),
+
+    /// An extension is missing.
+    MissingExtension(&'static str),
+
+    /// The requested export handle type was not provided in `export_handle_types` when creating the
+    /// fence, or can't be exported with the called method.
+    HandleTypeNotSupported {
+        handle_type: ExternalFenceHandleType,
+    },
+}
+
+impl fmt::Display for FenceExportError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::MissingExtension(s) => {
+                write!(fmt, "Missing the following extension: {}", s)
+            }
+            Self::HandleTypeNotSupported { handle_type } => write!(
+                fmt,
+                "the requested export handle type ({:?}) was not provided in `export_handle_types` when creating the fence",
+                handle_type,
+            ),
+        }
+    }
+}
+
+impl From<Error> for FenceExportError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            e @ Error::OutOfHostMemory | e @ Error::OutOfDeviceMemory => Self::OomError(e.into()),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+impl error::Error for FenceExportError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<OomError> for FenceExportError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+/// Error that can happen when importing a handle into a fence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FenceImportError {
+    /// Not enough memory available.
+    OomError(OomError),
+
+    /// An extension is missing.
+    MissingExtension(&'static str),
+
+    /// The handle type can't be imported into a fence with the requested permanence.
+    HandleTypeNotSupported {
+        handle_type: ExternalFenceHandleType,
+    },
+
+    /// The handle was not valid, or was not compatible with the fence.
+    InvalidExternalHandle,
+}
+
+impl fmt::Display for FenceImportError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::MissingExtension(s) => {
+                write!(fmt, "Missing the following extension: {}", s)
+            }
+            Self::HandleTypeNotSupported { handle_type } => write!(
+                fmt,
+                "the handle type ({:?}) can't be imported into a fence with the requested permanence",
+                handle_type,
+            ),
+            Self::InvalidExternalHandle => write!(
+                fmt,
+                "the handle was not valid, or was not compatible with the fence",
+            ),
+        }
+    }
+}
+
+impl From<Error> for FenceImportError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            e @ Error::OutOfHostMemory | e @ Error::OutOfDeviceMemory => Self::OomError(e.into()),
+            Error::InvalidExternalHandle => Self::InvalidExternalHandle,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+impl error::Error for FenceImportError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<OomError> for FenceImportError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::device::physical::PhysicalDevice;
+    use crate::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo};
+    use crate::instance::{Instance, InstanceCreateInfo, InstanceExtensions};
     use crate::sync::fence::FenceCreateInfo;
-    use crate::sync::Fence;
+    use crate::sync::{ExternalFenceHandleType, ExternalFenceHandleTypes, Fence, FenceExportError};
     use crate::VulkanObject;
     use std::time::Duration;
 
@@ -568,4 +1260,54 @@ mod tests {
         assert_eq!(device.fence_pool().lock().unwrap().len(), 0);
         assert_eq!(fence2.internal_object(), fence1_internal_obj);
     }
+
+    #[test]
+    fn fence_export() {
+        let instance = match Instance::new(InstanceCreateInfo {
+            enabled_extensions: InstanceExtensions {
+                khr_get_physical_device_properties2: true,
+                khr_external_fence_capabilities: true,
+                ..InstanceExtensions::none()
+            },
+            ..Default::default()
+        }) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+
+        let physical_device = PhysicalDevice::enumerate(&instance).next().unwrap();
+        let queue_family = physical_device.queue_families().next().unwrap();
+
+        let (device, _) = match Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                enabled_extensions: DeviceExtensions {
+                    khr_external_fence: true,
+                    khr_external_fence_fd: true,
+                    ..DeviceExtensions::none()
+                },
+                queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
+                ..Default::default()
+            },
+        ) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+
+        let fence = Fence::new(
+            device.clone(),
+            FenceCreateInfo {
+                export_handle_types: ExternalFenceHandleTypes::posix(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let _fd = unsafe { fence.export_fd(ExternalFenceHandleType::OpaqueFd).unwrap() };
+
+        // `sync_fd` was not in `export_handle_types`.
+        assert!(matches!(
+            unsafe { fence.export_fd(ExternalFenceHandleType::SyncFd) },
+            Err(FenceExportError::HandleTypeNotSupported { .. })
+        ));
+    }
 }
@@ -0,0 +1,1377 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use super::{AccessCheckError, FlushError, GpuFuture};
+use crate::{
+    buffer::{sys::UnsafeBuffer, BufferAccess},
+    command_buffer::submit::{
+        SubmitAnyBuilder, SubmitBindSparseBatchBuilder, SubmitBindSparseBufferBindBuilder,
+        SubmitBindSparseBuilder, SubmitBindSparseImageBindBuilder,
+        SubmitBindSparseImageOpaqueBindBuilder, SubmitSemaphoresWaitBuilder,
+    },
+    device::{Device, DeviceOwned, Queue},
+    image::{
+        sys::{SparseImageMemory, UnsafeImage},
+        ImageAccess, ImageAspect, ImageLayout,
+    },
+    memory::{DeviceMemory, MemoryRequirements},
+    sync::{AccessFlags, PipelineStages, Semaphore},
+    DeviceSize, VulkanObject,
+};
+use parking_lot::Mutex;
+use std::{
+    error, fmt,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Builds a new sparse binding future.
+pub fn then_bind_sparse<F>(
+    future: F,
+    queue: Arc<Queue>,
+    bind_info: BindSparseInfo,
+) -> Result<BindSparseFuture<F>, BindSparseError>
+where
+    F: GpuFuture,
+{
+    assert_eq!(
+        queue.device().internal_object(),
+        future.device().internal_object()
+    );
+
+    if !future.queue_change_allowed() {
+        assert!(future.queue().unwrap() == queue);
+    }
+
+    validate(&queue, &bind_info)?;
+
+    // Update the memory that is tracked by the resources. The memory that is replaced or unbound
+    // is kept alive by the future, until the GPU has finished the binding operation.
+    let mut released_memory = Vec::new();
+
+    for buffer_bind_info in bind_info.buffer_binds.iter() {
+        let inner = buffer_bind_info.buffer.inner();
+        let mut sparse_memory = inner.buffer.sparse_memory();
+
+        for bind in buffer_bind_info.binds.iter() {
+            let range = inner.offset + bind.offset..inner.offset + bind.offset + bind.size;
+            released_memory.extend(
+                sparse_memory
+                    .range(&range)
+                    .map(|(_, memory)| memory.clone()),
+            );
+
+            match &bind.memory {
+                Some((memory, _)) => sparse_memory.insert(range, memory.clone()),
+                None => sparse_memory.remove(range),
+            }
+        }
+    }
+
+    for image_opaque_bind_info in bind_info.image_opaque_binds.iter() {
+        let inner = image_opaque_bind_info.image.inner();
+        let mut sparse_memory = inner.image.sparse_memory();
+
+        for bind in image_opaque_bind_info.binds.iter() {
+            let range = bind.offset..bind.offset + bind.size;
+            released_memory.extend(
+                sparse_memory
+                    .opaque
+                    .range(&range)
+                    .map(|(_, memory)| memory.clone()),
+            );
+
+            match &bind.memory {
+                Some((memory, _)) => sparse_memory.opaque.insert(range, memory.clone()),
+                None => sparse_memory.opaque.remove(range),
+            }
+        }
+    }
+
+    for image_bind_info in bind_info.image_binds.iter() {
+        let inner = image_bind_info.image.inner();
+        let sparse_memory_requirements = inner.image.sparse_memory_requirements();
+        let mut sparse_memory = inner.image.sparse_memory();
+
+        for bind in image_bind_info.binds.iter() {
+            let granularity = sparse_memory_requirements
+                .iter()
+                .find(|requirements| {
+                    requirements
+                        .format_properties
+                        .aspects
+                        .contains(&bind.aspect.into())
+                })
+                .unwrap()
+                .format_properties
+                .image_granularity;
+
+            for block in SparseImageMemory::region_blocks(
+                bind.aspect,
+                inner.first_mipmap_level + bind.mip_level,
+                inner.first_layer + bind.array_layer,
+                bind.offset,
+                bind.extent,
+                granularity,
+            ) {
+                let replaced = match &bind.memory {
+                    Some((memory, _)) => sparse_memory.blocks.insert(block, memory.clone()),
+                    None => sparse_memory.blocks.remove(&block),
+                };
+                released_memory.extend(replaced);
+            }
+        }
+    }
+
+    let device = queue.device().clone();
+
+    Ok(BindSparseFuture {
+        previous: future,
+        queue,
+        bind_info,
+        _released_memory: released_memory,
+        wait_semaphore: Semaphore::from_pool(device.clone()).unwrap(),
+        signal_semaphore: Semaphore::from_pool(device).unwrap(),
+        submitted: Mutex::new(false),
+        finished: AtomicBool::new(false),
+    })
+}
+
+fn validate(queue: &Queue, bind_info: &BindSparseInfo) -> Result<(), BindSparseError> {
+    let BindSparseInfo {
+        buffer_binds,
+        image_opaque_binds,
+        image_binds,
+        _ne: _,
+    } = bind_info;
+
+    if !queue.family().supports_sparse_binding() {
+        return Err(BindSparseError::QueueFamilyNotSupported);
+    }
+
+    for (buffer_index, buffer_bind_info) in buffer_binds.iter().enumerate() {
+        let SparseBufferBindInfo { buffer, binds } = buffer_bind_info;
+        let resource = BindSparseErrorResource::Buffer(buffer_index);
+
+        // VUID-VkSparseBufferMemoryBindInfo-commonparent
+        assert_eq!(
+            buffer.device().internal_object(),
+            queue.device().internal_object()
+        );
+
+        let inner = buffer.inner();
+
+        if inner.buffer.sparse().is_none() {
+            return Err(BindSparseError::BufferNotSparse { buffer_index });
+        }
+
+        let memory_requirements = inner.buffer.memory_requirements();
+        let block_size = memory_requirements.alignment;
+
+        for (bind_index, bind) in binds.iter().enumerate() {
+            let &SparseBufferMemoryBind {
+                offset,
+                size,
+                ref memory,
+                _ne: _,
+            } = bind;
+
+            // VUID-VkSparseMemoryBind-size-01098
+            assert!(size != 0);
+
+            // VUID-VkSparseMemoryBind-resourceOffset-01099
+            // VUID-VkSparseMemoryBind-size-01100
+            if offset + size > buffer.size() {
+                return Err(BindSparseError::RegionOutOfResourceBounds {
+                    resource,
+                    bind_index,
+                });
+            }
+
+            let resource_offset = inner.offset + offset;
+
+            if resource_offset % block_size != 0
+                || (size % block_size != 0 && resource_offset + size != inner.buffer.size())
+            {
+                return Err(BindSparseError::RegionNotAligned {
+                    resource,
+                    bind_index,
+                    required_alignment: block_size,
+                });
+            }
+
+            if let Some((memory, memory_offset)) = memory {
+                validate_memory(
+                    queue,
+                    resource,
+                    bind_index,
+                    memory,
+                    *memory_offset,
+                    size,
+                    &memory_requirements,
+                )?;
+            }
+        }
+    }
+
+    for (image_index, image_opaque_bind_info) in image_opaque_binds.iter().enumerate() {
+        let SparseImageOpaqueBindInfo { image, binds } = image_opaque_bind_info;
+        let resource = BindSparseErrorResource::ImageOpaque(image_index);
+
+        // VUID-VkSparseImageOpaqueMemoryBindInfo-commonparent
+        assert_eq!(
+            image.device().internal_object(),
+            queue.device().internal_object()
+        );
+
+        let inner = image.inner();
+
+        if inner.image.sparse().is_none() {
+            return Err(BindSparseError::ImageNotSparse { image_index });
+        }
+
+        let memory_requirements = inner.image.memory_requirements();
+        let block_size = memory_requirements.alignment;
+
+        for (bind_index, bind) in binds.iter().enumerate() {
+            let &SparseImageOpaqueMemoryBind {
+                offset,
+                size,
+                ref memory,
+                metadata: _,
+                _ne: _,
+            } = bind;
+
+            // VUID-VkSparseMemoryBind-size-01098
+            assert!(size != 0);
+
+            // VUID-VkSparseMemoryBind-resourceOffset-01099
+            // VUID-VkSparseMemoryBind-size-01100
+            if offset + size > memory_requirements.size {
+                return Err(BindSparseError::RegionOutOfResourceBounds {
+                    resource,
+                    bind_index,
+                });
+            }
+
+            if offset % block_size != 0 || size % block_size != 0 {
+                return Err(BindSparseError::RegionNotAligned {
+                    resource,
+                    bind_index,
+                    required_alignment: block_size,
+                });
+            }
+
+            if let Some((memory, memory_offset)) = memory {
+                validate_memory(
+                    queue,
+                    resource,
+                    bind_index,
+                    memory,
+                    *memory_offset,
+                    size,
+                    &memory_requirements,
+                )?;
+            }
+        }
+    }
+
+    for (image_index, image_bind_info) in image_binds.iter().enumerate() {
+        let SparseImageBindInfo { image, binds } = image_bind_info;
+        let resource = BindSparseErrorResource::Image(image_index);
+
+        // VUID-VkSparseImageMemoryBindInfo-commonparent
+        assert_eq!(
+            image.device().internal_object(),
+            queue.device().internal_object()
+        );
+
+        let inner = image.inner();
+
+        // VUID-VkSparseImageMemoryBindInfo-image-02901
+        if !inner
+            .image
+            .sparse()
+            .map_or(false, |sparse_level| sparse_level.sparse_residency)
+        {
+            return Err(BindSparseError::ImageNotSparseResidency { image_index });
+        }
+
+        let memory_requirements = inner.image.memory_requirements();
+        let sparse_memory_requirements = inner.image.sparse_memory_requirements();
+        let block_size = memory_requirements.alignment;
+
+        for (bind_index, bind) in binds.iter().enumerate() {
+            let &SparseImageMemoryBind {
+                aspect,
+                mip_level,
+                array_layer,
+                offset,
+                extent,
+                ref memory,
+                _ne: _,
+            } = bind;
+
+            assert!(extent[0] != 0 && extent[1] != 0 && extent[2] != 0);
+
+            // VUID-VkSparseImageMemoryBindInfo-subresource-01722
+            // VUID-VkSparseImageMemoryBindInfo-subresource-01723
+            // VUID-VkSparseImageMemoryBindInfo-subresource-01106
+            let requirements = sparse_memory_requirements
+                .iter()
+                .find(|requirements| {
+                    requirements
+                        .format_properties
+                        .aspects
+                        .contains(&aspect.into())
+                })
+                .filter(|_| {
+                    aspect != ImageAspect::Metadata
+                        && mip_level < inner.num_mipmap_levels
+                        && array_layer < inner.num_layers
+                })
+                .ok_or(BindSparseError::ImageSubresourceOutOfRange {
+                    image_index,
+                    bind_index,
+                })?;
+
+            let mip_level = inner.first_mipmap_level + mip_level;
+
+            if mip_level >= requirements.image_mip_tail_first_lod {
+                return Err(BindSparseError::ImageSubresourceInMipTail {
+                    image_index,
+                    bind_index,
+                });
+            }
+
+            let subresource_extent = subresource_extent(inner.image, aspect, mip_level);
+
+            if (0..3).any(|i| offset[i] + extent[i] > subresource_extent[i]) {
+                return Err(BindSparseError::RegionOutOfResourceBounds {
+                    resource,
+                    bind_index,
+                });
+            }
+
+            let granularity = requirements.format_properties.image_granularity;
+
+            // VUID-VkSparseImageMemoryBind-offset-01107
+            // VUID-VkSparseImageMemoryBind-offset-01109
+            // VUID-VkSparseImageMemoryBind-offset-01111
+            // VUID-VkSparseImageMemoryBind-extent-01108
+            // VUID-VkSparseImageMemoryBind-extent-01110
+            // VUID-VkSparseImageMemoryBind-extent-01112
+            if (0..3).any(|i| {
+                offset[i] % granularity[i] != 0
+                    || (extent[i] % granularity[i] != 0
+                        && offset[i] + extent[i] != subresource_extent[i])
+            }) {
+                return Err(BindSparseError::ImageRegionNotAligned {
+                    image_index,
+                    bind_index,
+                    required_granularity: granularity,
+                });
+            }
+
+            if let Some((memory, memory_offset)) = memory {
+                let block_count = (0..3)
+                    .map(|i| ((extent[i] + granularity[i] - 1) / granularity[i]) as DeviceSize)
+                    .product::<DeviceSize>();
+
+                validate_memory(
+                    queue,
+                    resource,
+                    bind_index,
+                    memory,
+                    *memory_offset,
+                    block_count * block_size,
+                    &memory_requirements,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_memory(
+    queue: &Queue,
+    resource: BindSparseErrorResource,
+    bind_index: usize,
+    memory: &DeviceMemory,
+    memory_offset: DeviceSize,
+    size: DeviceSize,
+    memory_requirements: &MemoryRequirements,
+) -> Result<(), BindSparseError> {
+    // VUID-VkBindSparseInfo-commonparent
+    assert_eq!(
+        memory.device().internal_object(),
+        queue.device().internal_object()
+    );
+
+    // VUID-VkSparseMemoryBind-memory-01096
+    if memory_requirements.memory_type_bits & (1 << memory.memory_type().id()) == 0 {
+        return Err(BindSparseError::MemoryTypeNotSupported {
+            resource,
+            bind_index,
+        });
+    }
+
+    if memory_offset % memory_requirements.alignment != 0 {
+        return Err(BindSparseError::MemoryOffsetNotAligned {
+            resource,
+            bind_index,
+            required_alignment: memory_requirements.alignment,
+        });
+    }
+
+    // VUID-VkSparseMemoryBind-size-01102
+    if memory_offset + size > memory.allocation_size() {
+        return Err(BindSparseError::RegionOutOfMemoryBounds {
+            resource,
+            bind_index,
+        });
+    }
+
+    Ok(())
+}
+
+// Returns the extent of a subresource of an image, taking the chroma subsampling of the planes of
+// YCbCr formats into account.
+fn subresource_extent(image: &UnsafeImage, aspect: ImageAspect, mip_level: u32) -> [u32; 3] {
+    let extent = image
+        .dimensions()
+        .mip_level_dimensions(mip_level)
+        .unwrap()
+        .width_height_depth();
+
+    match (aspect, image.format().unwrap().ycbcr_chroma_sampling()) {
+        (ImageAspect::Plane1 | ImageAspect::Plane2, Some(chroma_sampling)) => {
+            chroma_sampling.subsampled_extent(extent)
+        }
+        _ => extent,
+    }
+}
+
+/// Parameters of a sparse binding operation.
+#[derive(Clone, Debug)]
+pub struct BindSparseInfo {
+    /// The binding operations to perform on sparse buffers.
+    ///
+    /// The default value is empty.
+    pub buffer_binds: Vec<SparseBufferBindInfo>,
+
+    /// The opaque binding operations to perform on sparse images.
+    ///
+    /// The default value is empty.
+    pub image_opaque_binds: Vec<SparseImageOpaqueBindInfo>,
+
+    /// The binding operations to perform on blocks of sparse residency images.
+    ///
+    /// The default value is empty.
+    pub image_binds: Vec<SparseImageBindInfo>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for BindSparseInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            buffer_binds: Vec::new(),
+            image_opaque_binds: Vec::new(),
+            image_binds: Vec::new(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The binding operations to perform on a single sparse buffer.
+#[derive(Clone, Debug)]
+pub struct SparseBufferBindInfo {
+    /// The buffer to bind memory to. It must have been created with sparse binding enabled, for
+    /// example a [`SparseBuffer`](crate::buffer::SparseBuffer).
+    pub buffer: Arc<dyn BufferAccess>,
+
+    /// The ranges of the buffer to bind or unbind.
+    pub binds: Vec<SparseBufferMemoryBind>,
+}
+
+/// A range of a sparse buffer to bind memory to, or to unbind.
+#[derive(Clone, Debug)]
+pub struct SparseBufferMemoryBind {
+    /// The offset in bytes from the start of the buffer.
+    ///
+    /// This must be a multiple of the `alignment` of the buffer's memory requirements.
+    ///
+    /// The default value is `0`.
+    pub offset: DeviceSize,
+
+    /// The size in bytes of the range.
+    ///
+    /// This must be a multiple of the `alignment` of the buffer's memory requirements, unless the
+    /// range extends to the end of the buffer.
+    ///
+    /// The default value is `0`, which must be overridden.
+    pub size: DeviceSize,
+
+    /// The memory to bind to the range, and the offset in bytes within the memory. If `None`, the
+    /// range is unbound.
+    ///
+    /// The memory type must be allowed by the `memory_type_bits` of the buffer's memory
+    /// requirements, and the offset must be a multiple of their `alignment`.
+    ///
+    /// The default value is `None`.
+    pub memory: Option<(Arc<DeviceMemory>, DeviceSize)>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for SparseBufferMemoryBind {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            size: 0,
+            memory: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The opaque binding operations to perform on a single sparse image.
+#[derive(Clone, Debug)]
+pub struct SparseImageOpaqueBindInfo {
+    /// The image to bind memory to. It must have been created with sparse binding enabled, for
+    /// example a [`SparseImage`](crate::image::SparseImage).
+    pub image: Arc<dyn ImageAccess>,
+
+    /// The ranges of the opaque memory of the image to bind or unbind.
+    pub binds: Vec<SparseImageOpaqueMemoryBind>,
+}
+
+/// A range of the opaque memory of a sparse image to bind memory to, or to unbind.
+///
+/// The opaque memory of an image has an implementation-defined layout, except for the mip tail
+/// and the metadata of sparse residency images, whose offsets are given by the image's sparse
+/// memory requirements.
+#[derive(Clone, Debug)]
+pub struct SparseImageOpaqueMemoryBind {
+    /// The offset in bytes from the start of the opaque memory of the image.
+    ///
+    /// This must be a multiple of the `alignment` of the image's memory requirements.
+    ///
+    /// The default value is `0`.
+    pub offset: DeviceSize,
+
+    /// The size in bytes of the range.
+    ///
+    /// This must be a multiple of the `alignment` of the image's memory requirements.
+    ///
+    /// The default value is `0`, which must be overridden.
+    pub size: DeviceSize,
+
+    /// The memory to bind to the range, and the offset in bytes within the memory. If `None`, the
+    /// range is unbound.
+    ///
+    /// The memory type must be allowed by the `memory_type_bits` of the image's memory
+    /// requirements, and the offset must be a multiple of their `alignment`.
+    ///
+    /// The default value is `None`.
+    pub memory: Option<(Arc<DeviceMemory>, DeviceSize)>,
+
+    /// Whether the range is the mip tail of the metadata aspect of the image.
+    ///
+    /// The default value is `false`.
+    pub metadata: bool,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for SparseImageOpaqueMemoryBind {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            size: 0,
+            memory: None,
+            metadata: false,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The binding operations to perform on blocks of a single sparse residency image.
+#[derive(Clone, Debug)]
+pub struct SparseImageBindInfo {
+    /// The image to bind memory to. It must have been created with sparse residency enabled, for
+    /// example a [`SparseImage`](crate::image::SparseImage).
+    pub image: Arc<dyn ImageAccess>,
+
+    /// The regions of the image to bind or unbind.
+    pub binds: Vec<SparseImageMemoryBind>,
+}
+
+/// A region of a subresource of a sparse residency image to bind memory to, or to unbind.
+///
+/// The region is made of sparse blocks, whose size in texels is the `image_granularity` of the
+/// image's sparse memory requirements for the aspect. Each block is bound to memory of the size
+/// of the `alignment` of the image's memory requirements, and the blocks are laid out
+/// consecutively in memory, in x, then y, then z order.
+#[derive(Clone, Debug)]
+pub struct SparseImageMemoryBind {
+    /// The aspect of the subresource.
+    ///
+    /// The default value is [`ImageAspect::Color`].
+    pub aspect: ImageAspect,
+
+    /// The mip level of the subresource, relative to the first mip level of the image.
+    ///
+    /// The mip level must not be part of the mip tail of the aspect, which must be bound with an
+    /// opaque bind instead.
+    ///
+    /// The default value is `0`.
+    pub mip_level: u32,
+
+    /// The array layer of the subresource, relative to the first array layer of the image.
+    ///
+    /// The default value is `0`.
+    pub array_layer: u32,
+
+    /// The offset in texels of the region within the subresource.
+    ///
+    /// This must be a multiple of the image granularity.
+    ///
+    /// The default value is `[0; 3]`.
+    pub offset: [u32; 3],
+
+    /// The extent in texels of the region.
+    ///
+    /// This must be a multiple of the image granularity, unless the region extends to the edge
+    /// of the subresource.
+    ///
+    /// The default value is `[0; 3]`, which must be overridden.
+    pub extent: [u32; 3],
+
+    /// The memory to bind to the region, and the offset in bytes within the memory. If `None`,
+    /// the region is unbound.
+    ///
+    /// The memory type must be allowed by the `memory_type_bits` of the image's memory
+    /// requirements, and the offset must be a multiple of their `alignment`.
+    ///
+    /// The default value is `None`.
+    pub memory: Option<(Arc<DeviceMemory>, DeviceSize)>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for SparseImageMemoryBind {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            aspect: ImageAspect::Color,
+            mip_level: 0,
+            array_layer: 0,
+            offset: [0; 3],
+            extent: [0; 3],
+            memory: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Represents memory being bound to sparse resources after a previous event.
+///
+/// Once the future is flushed, the binding operation signals a semaphore that subsequent
+/// submissions wait on, so that they see the new bindings.
+#[must_use = "Dropping this object will immediately block the thread until the GPU has finished \
+              processing the submission"]
+pub struct BindSparseFuture<F>
+where
+    F: GpuFuture,
+{
+    previous: F,
+    queue: Arc<Queue>,
+    bind_info: BindSparseInfo,
+    // Memory that was replaced or unbound by the operation. It is only kept alive.
+    _released_memory: Vec<Arc<DeviceMemory>>,
+    // Waited upon by the binding operation if the previous future is a command buffer submission,
+    // as sparse binding operations are not ordered with other submissions to the queue.
+    wait_semaphore: Semaphore,
+    // Signaled by the binding operation, and waited upon by the next submission.
+    signal_semaphore: Semaphore,
+    // True if the binding operation has already been submitted.
+    // If flush is called multiple times, we want to block so that only one flushing is executed.
+    // Therefore we use a `Mutex<bool>` and not an `AtomicBool`.
+    submitted: Mutex<bool>,
+    finished: AtomicBool,
+}
+
+unsafe impl<F> GpuFuture for BindSparseFuture<F>
+where
+    F: GpuFuture,
+{
+    #[inline]
+    fn cleanup_finished(&mut self) {
+        self.previous.cleanup_finished();
+    }
+
+    #[inline]
+    unsafe fn build_submission(&self) -> Result<SubmitAnyBuilder, FlushError> {
+        // Flushing the binding operation, since it must always be submitted before the waiting
+        // part.
+        self.flush()?;
+
+        let mut sem = SubmitSemaphoresWaitBuilder::new();
+        sem.add_wait_semaphore(&self.signal_semaphore);
+        Ok(SubmitAnyBuilder::SemaphoresWait(sem))
+    }
+
+    fn flush(&self) -> Result<(), FlushError> {
+        unsafe {
+            let mut submitted = self.submitted.lock();
+
+            if *submitted {
+                return Ok(());
+            }
+
+            let (mut builder, mut batch) = match self.previous.build_submission()? {
+                SubmitAnyBuilder::Empty => (
+                    SubmitBindSparseBuilder::new(),
+                    SubmitBindSparseBatchBuilder::new(),
+                ),
+                SubmitAnyBuilder::SemaphoresWait(sem) => {
+                    (SubmitBindSparseBuilder::new(), sem.into())
+                }
+                SubmitAnyBuilder::CommandBuffer(mut cb_builder) => {
+                    cb_builder.add_signal_semaphore(&self.wait_semaphore);
+                    cb_builder.submit(&self.queue)?;
+
+                    let mut batch = SubmitBindSparseBatchBuilder::new();
+                    batch.add_wait_semaphore(&self.wait_semaphore);
+                    (SubmitBindSparseBuilder::new(), batch)
+                }
+                SubmitAnyBuilder::QueuePresent(present) => {
+                    present.submit(&self.queue)?;
+                    (
+                        SubmitBindSparseBuilder::new(),
+                        SubmitBindSparseBatchBuilder::new(),
+                    )
+                }
+                SubmitAnyBuilder::BindSparse(builder) => {
+                    (builder, SubmitBindSparseBatchBuilder::new())
+                }
+            };
+
+            for buffer_bind_info in self.bind_info.buffer_binds.iter() {
+                let inner = buffer_bind_info.buffer.inner();
+                let mut buffer_bind = SubmitBindSparseBufferBindBuilder::new(inner.buffer);
+
+                for bind in buffer_bind_info.binds.iter() {
+                    match &bind.memory {
+                        Some((memory, memory_offset)) => buffer_bind.add_bind(
+                            inner.offset + bind.offset,
+                            bind.size,
+                            memory,
+                            *memory_offset,
+                        ),
+                        None => buffer_bind.add_unbind(inner.offset + bind.offset, bind.size),
+                    }
+                }
+
+                batch.add_buffer(buffer_bind);
+            }
+
+            for image_opaque_bind_info in self.bind_info.image_opaque_binds.iter() {
+                let inner = image_opaque_bind_info.image.inner();
+                let mut image_opaque_bind =
+                    SubmitBindSparseImageOpaqueBindBuilder::new(inner.image);
+
+                for bind in image_opaque_bind_info.binds.iter() {
+                    match &bind.memory {
+                        Some((memory, memory_offset)) => image_opaque_bind.add_bind(
+                            bind.offset,
+                            bind.size,
+                            memory,
+                            *memory_offset,
+                            bind.metadata,
+                        ),
+                        None => image_opaque_bind.add_unbind(bind.offset, bind.size, bind.metadata),
+                    }
+                }
+
+                batch.add_image_opaque(image_opaque_bind);
+            }
+
+            for image_bind_info in self.bind_info.image_binds.iter() {
+                let inner = image_bind_info.image.inner();
+                let mut image_bind = SubmitBindSparseImageBindBuilder::new(inner.image);
+
+                for bind in image_bind_info.binds.iter() {
+                    let mip_level = inner.first_mipmap_level + bind.mip_level;
+                    let array_layer = inner.first_layer + bind.array_layer;
+
+                    match &bind.memory {
+                        Some((memory, memory_offset)) => image_bind.add_bind(
+                            bind.aspect,
+                            mip_level,
+                            array_layer,
+                            bind.offset,
+                            bind.extent,
+                            memory,
+                            *memory_offset,
+                        ),
+                        None => image_bind.add_unbind(
+                            bind.aspect,
+                            mip_level,
+                            array_layer,
+                            bind.offset,
+                            bind.extent,
+                        ),
+                    }
+                }
+
+                batch.add_image(image_bind);
+            }
+
+            batch.add_signal_semaphore(&self.signal_semaphore);
+            builder.add(batch);
+            builder.submit(&self.queue)?;
+
+            // Only write `true` here in order to try again next time if an error occurs.
+            *submitted = true;
+            Ok(())
+        }
+    }
+
+    #[inline]
+    unsafe fn signal_finished(&self) {
+        debug_assert!(*self.submitted.lock());
+        self.finished.store(true, Ordering::SeqCst);
+        self.previous.signal_finished();
+    }
+
+    #[inline]
+    fn queue_change_allowed(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn queue(&self) -> Option<Arc<Queue>> {
+        Some(self.queue.clone())
+    }
+
+    #[inline]
+    fn check_buffer_access(
+        &self,
+        buffer: &UnsafeBuffer,
+        range: Range<DeviceSize>,
+        exclusive: bool,
+        queue: &Queue,
+    ) -> Result<Option<(PipelineStages, AccessFlags)>, AccessCheckError> {
+        self.previous
+            .check_buffer_access(buffer, range, exclusive, queue)
+            .map(|_| None)
+    }
+
+    #[inline]
+    fn check_image_access(
+        &self,
+        image: &UnsafeImage,
+        range: Range<DeviceSize>,
+        exclusive: bool,
+        expected_layout: ImageLayout,
+        queue: &Queue,
+    ) -> Result<Option<(PipelineStages, AccessFlags)>, AccessCheckError> {
+        self.previous
+            .check_image_access(image, range, exclusive, expected_layout, queue)
+            .map(|_| None)
+    }
+}
+
+unsafe impl<F> DeviceOwned for BindSparseFuture<F>
+where
+    F: GpuFuture,
+{
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.queue.device()
+    }
+}
+
+impl<F> Drop for BindSparseFuture<F>
+where
+    F: GpuFuture,
+{
+    fn drop(&mut self) {
+        unsafe {
+            if !*self.finished.get_mut() {
+                // TODO: handle errors?
+                self.flush().unwrap();
+                // Block until the queue finished.
+                self.queue.wait().unwrap();
+                self.previous.signal_finished();
+            }
+        }
+    }
+}
+
+/// Error that can happen when binding memory to sparse resources.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BindSparseError {
+    /// A buffer in `buffer_binds` was not created with sparse binding enabled.
+    BufferNotSparse { buffer_index: usize },
+
+    /// An image in `image_opaque_binds` was not created with sparse binding enabled.
+    ImageNotSparse { image_index: usize },
+
+    /// An image in `image_binds` was not created with sparse residency enabled.
+    ImageNotSparseResidency { image_index: usize },
+
+    /// The offset or extent of a bind in `image_binds` is not a multiple of the image
+    /// granularity of its aspect.
+    ImageRegionNotAligned {
+        image_index: usize,
+        bind_index: usize,
+        required_granularity: [u32; 3],
+    },
+
+    /// The mip level of a bind in `image_binds` is part of the mip tail of its aspect, which can
+    /// only be bound with an opaque bind.
+    ImageSubresourceInMipTail {
+        image_index: usize,
+        bind_index: usize,
+    },
+
+    /// The aspect, mip level or array layer of a bind in `image_binds` does not exist in the
+    /// image, or the aspect can't be bound with an image bind.
+    ImageSubresourceOutOfRange {
+        image_index: usize,
+        bind_index: usize,
+    },
+
+    /// The memory type of the memory of a bind is not allowed by the resource's memory
+    /// requirements.
+    MemoryTypeNotSupported {
+        resource: BindSparseErrorResource,
+        bind_index: usize,
+    },
+
+    /// The memory offset of a bind is not a multiple of the sparse block size of the resource.
+    MemoryOffsetNotAligned {
+        resource: BindSparseErrorResource,
+        bind_index: usize,
+        required_alignment: DeviceSize,
+    },
+
+    /// The queue family of the queue does not support sparse binding operations.
+    QueueFamilyNotSupported,
+
+    /// The offset or size of a bind is not a multiple of the sparse block size of the resource.
+    RegionNotAligned {
+        resource: BindSparseErrorResource,
+        bind_index: usize,
+        required_alignment: DeviceSize,
+    },
+
+    /// The region of a bind exceeds the size of the resource, or of its subresource.
+    RegionOutOfResourceBounds {
+        resource: BindSparseErrorResource,
+        bind_index: usize,
+    },
+
+    /// The region of a bind, starting at its memory offset, exceeds the size of the memory.
+    RegionOutOfMemoryBounds {
+        resource: BindSparseErrorResource,
+        bind_index: usize,
+    },
+}
+
+impl error::Error for BindSparseError {}
+
+impl fmt::Display for BindSparseError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::BufferNotSparse { buffer_index } => write!(
+                fmt,
+                "buffer {} was not created with sparse binding enabled",
+                buffer_index,
+            ),
+            Self::ImageNotSparse { image_index } => write!(
+                fmt,
+                "image {} of the opaque binds was not created with sparse binding enabled",
+                image_index,
+            ),
+            Self::ImageNotSparseResidency { image_index } => write!(
+                fmt,
+                "image {} was not created with sparse residency enabled",
+                image_index,
+            ),
+            Self::ImageRegionNotAligned {
+                image_index,
+                bind_index,
+                required_granularity,
+            } => write!(
+                fmt,
+                "the offset or extent of bind {} of image {} is not a multiple of the image granularity {:?}",
+                bind_index, image_index, required_granularity,
+            ),
+            Self::ImageSubresourceInMipTail {
+                image_index,
+                bind_index,
+            } => write!(
+                fmt,
+                "the mip level of bind {} of image {} is part of the mip tail",
+                bind_index, image_index,
+            ),
+            Self::ImageSubresourceOutOfRange {
+                image_index,
+                bind_index,
+            } => write!(
+                fmt,
+                "the subresource of bind {} of image {} does not exist in the image or can't be bound",
+                bind_index, image_index,
+            ),
+            Self::MemoryTypeNotSupported {
+                resource,
+                bind_index,
+            } => write!(
+                fmt,
+                "the memory type of bind {} of {} is not allowed by the resource's memory requirements",
+                bind_index, resource,
+            ),
+            Self::MemoryOffsetNotAligned {
+                resource,
+                bind_index,
+                required_alignment,
+            } => write!(
+                fmt,
+                "the memory offset of bind {} of {} is not a multiple of the sparse block size {}",
+                bind_index, resource, required_alignment,
+            ),
+            Self::QueueFamilyNotSupported => write!(
+                fmt,
+                "the queue family of the queue does not support sparse binding operations",
+            ),
+            Self::RegionNotAligned {
+                resource,
+                bind_index,
+                required_alignment,
+            } => write!(
+                fmt,
+                "the offset or size of bind {} of {} is not a multiple of the sparse block size {}",
+                bind_index, resource, required_alignment,
+            ),
+            Self::RegionOutOfResourceBounds {
+                resource,
+                bind_index,
+            } => write!(
+                fmt,
+                "the region of bind {} of {} exceeds the size of the resource",
+                bind_index, resource,
+            ),
+            Self::RegionOutOfMemoryBounds {
+                resource,
+                bind_index,
+            } => write!(
+                fmt,
+                "the region of bind {} of {} exceeds the size of the memory",
+                bind_index, resource,
+            ),
+        }
+    }
+}
+
+/// Indicates which resource of a sparse binding operation a [`BindSparseError`] relates to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindSparseErrorResource {
+    /// The buffer at the given index in `buffer_binds`.
+    Buffer(usize),
+    /// The image at the given index in `image_opaque_binds`.
+    ImageOpaque(usize),
+    /// The image at the given index in `image_binds`.
+    Image(usize),
+}
+
+impl fmt::Display for BindSparseErrorResource {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::Buffer(index) => write!(fmt, "buffer {}", index),
+            Self::ImageOpaque(index) => write!(fmt, "image {} of the opaque binds", index),
+            Self::Image(index) => write!(fmt, "image {}", index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BindSparseError, BindSparseErrorResource, BindSparseInfo, SparseBufferBindInfo,
+        SparseBufferMemoryBind, SparseImageBindInfo, SparseImageMemoryBind,
+    };
+    use crate::{
+        buffer::{BufferUsage, CpuAccessibleBuffer, SparseBuffer, SparseLevel},
+        format::Format,
+        image::{
+            ImageAspect, ImageCreationError, ImageDimensions, ImageUsage, MipmapsCount,
+            SparseImage, StorageImage,
+        },
+        memory::{DeviceMemory, MemoryAllocateInfo},
+        sync::GpuFuture,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn buffer_not_sparse() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        if !queue.family().supports_sparse_binding() {
+            return;
+        }
+
+        let buffer =
+            CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), false, 0u32)
+                .unwrap();
+
+        match queue.bind_sparse(BindSparseInfo {
+            buffer_binds: vec![SparseBufferBindInfo {
+                buffer,
+                binds: vec![SparseBufferMemoryBind {
+                    size: 4,
+                    ..Default::default()
+                }],
+            }],
+            ..Default::default()
+        }) {
+            Err(BindSparseError::BufferNotSparse { buffer_index: 0 }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn bind_and_unbind() {
+        let (device, queue) = gfx_dev_and_queue!(sparse_binding);
+
+        if !queue.family().supports_sparse_binding() {
+            return;
+        }
+
+        let buffer = SparseBuffer::<[u8]>::array(
+            device.clone(),
+            1 << 20,
+            BufferUsage::storage_buffer(),
+            SparseLevel::none(),
+            [queue.family()],
+        )
+        .unwrap();
+        let requirements = buffer.memory_requirements();
+        let memory = Arc::new(
+            DeviceMemory::allocate(
+                device.clone(),
+                MemoryAllocateInfo {
+                    allocation_size: requirements.size,
+                    memory_type_index: requirements.memory_type_bits.trailing_zeros(),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        queue
+            .bind_sparse(BindSparseInfo {
+                buffer_binds: vec![SparseBufferBindInfo {
+                    buffer: buffer.clone(),
+                    binds: vec![SparseBufferMemoryBind {
+                        size: requirements.size,
+                        memory: Some((memory.clone(), 0)),
+                        ..Default::default()
+                    }],
+                }],
+                ..Default::default()
+            })
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+        assert!(buffer.is_bound(0, 1 << 20));
+
+        queue
+            .bind_sparse(BindSparseInfo {
+                buffer_binds: vec![SparseBufferBindInfo {
+                    buffer: buffer.clone(),
+                    binds: vec![SparseBufferMemoryBind {
+                        size: requirements.size,
+                        ..Default::default()
+                    }],
+                }],
+                ..Default::default()
+            })
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+        assert!(!buffer.is_bound(0, 1 << 20));
+    }
+
+    #[test]
+    fn image_not_sparse_residency() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        if !queue.family().supports_sparse_binding() {
+            return;
+        }
+
+        let image = StorageImage::new(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: 32,
+                height: 32,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            [queue.family()],
+        )
+        .unwrap();
+
+        match queue.bind_sparse(BindSparseInfo {
+            image_binds: vec![SparseImageBindInfo {
+                image,
+                binds: vec![SparseImageMemoryBind {
+                    extent: [32, 32, 1],
+                    ..Default::default()
+                }],
+            }],
+            ..Default::default()
+        }) {
+            Err(BindSparseError::ImageNotSparseResidency { image_index: 0 }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn bind_and_unbind_image_block() {
+        let (device, queue) = gfx_dev_and_queue!(sparse_binding, sparse_residency_image2_d);
+
+        if !queue.family().supports_sparse_binding() {
+            return;
+        }
+
+        let image = match SparseImage::new(
+            device.clone(),
+            ImageDimensions::Dim2d {
+                width: 1024,
+                height: 1024,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            MipmapsCount::Log2,
+            ImageUsage::sampled(),
+            SparseLevel {
+                sparse_residency: true,
+                ..SparseLevel::none()
+            },
+            [queue.family()],
+        ) {
+            Ok(x) => x,
+            Err(ImageCreationError::SparseResidencyFormatNotSupported) => return,
+            Err(err) => panic!("{:?}", err),
+        };
+        let requirements = image.memory_requirements();
+        let granularity = image.sparse_memory_requirements()[0]
+            .format_properties
+            .image_granularity;
+        let memory = Arc::new(
+            DeviceMemory::allocate(
+                device.clone(),
+                MemoryAllocateInfo {
+                    allocation_size: requirements.alignment,
+                    memory_type_index: requirements.memory_type_bits.trailing_zeros(),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+
+        // The memory is only large enough for a single block.
+        match queue.bind_sparse(BindSparseInfo {
+            image_binds: vec![SparseImageBindInfo {
+                image: image.clone(),
+                binds: vec![SparseImageMemoryBind {
+                    extent: [granularity[0] * 2, granularity[1], 1],
+                    memory: Some((memory.clone(), 0)),
+                    ..Default::default()
+                }],
+            }],
+            ..Default::default()
+        }) {
+            Err(BindSparseError::RegionOutOfMemoryBounds {
+                resource: BindSparseErrorResource::Image(0),
+                bind_index: 0,
+            }) => (),
+            _ => panic!(),
+        }
+
+        queue
+            .bind_sparse(BindSparseInfo {
+                image_binds: vec![SparseImageBindInfo {
+                    image: image.clone(),
+                    binds: vec![SparseImageMemoryBind {
+                        extent: [granularity[0], granularity[1], 1],
+                        memory: Some((memory.clone(), 0)),
+                        ..Default::default()
+                    }],
+                }],
+                ..Default::default()
+            })
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+        assert!(image.is_resident(
+            ImageAspect::Color,
+            0,
+            0,
+            [0; 3],
+            [granularity[0], granularity[1], 1],
+        ));
+        assert!(!image.is_resident(ImageAspect::Color, 0, 0, [0; 3], [1024, 1024, 1]));
+
+        queue
+            .bind_sparse(BindSparseInfo {
+                image_binds: vec![SparseImageBindInfo {
+                    image: image.clone(),
+                    binds: vec![SparseImageMemoryBind {
+                        extent: [granularity[0], granularity[1], 1],
+                        ..Default::default()
+                    }],
+                }],
+                ..Default::default()
+            })
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+        assert!(!image.is_resident(
+            ImageAspect::Color,
+            0,
+            0,
+            [0; 3],
+            [granularity[0], granularity[1], 1],
+        ));
+    }
+}
@@ -129,8 +129,11 @@ where
                 SubmitAnyBuilder::CommandBuffer(new)
             }
             (SubmitAnyBuilder::QueuePresent(a), SubmitAnyBuilder::QueuePresent(b)) => {
-                self.first.flush()?;
-                self.second.flush()?;
+                // Both are flushed even if the first one fails, so that a swapchain that can't be
+                // presented to doesn't prevent presenting to the other one.
+                let first_result = self.first.flush();
+                let second_result = self.second.flush();
+                first_result.and(second_result)?;
                 SubmitAnyBuilder::Empty
             }
             (SubmitAnyBuilder::CommandBuffer(a), SubmitAnyBuilder::QueuePresent(b)) => {
@@ -8,6 +8,11 @@
 // according to those terms.
 
 pub use self::{
+    bind_sparse::{
+        BindSparseError, BindSparseErrorResource, BindSparseFuture, BindSparseInfo,
+        SparseBufferBindInfo, SparseBufferMemoryBind, SparseImageBindInfo, SparseImageMemoryBind,
+        SparseImageOpaqueBindInfo, SparseImageOpaqueMemoryBind,
+    },
     fence_signal::{FenceSignalFuture, FenceSignalFutureBehavior},
     join::JoinFuture,
     now::{now, NowFuture},
@@ -29,6 +34,7 @@ use crate::{
 };
 use std::{error, fmt, ops::Range, sync::Arc};
 
+mod bind_sparse;
 mod fence_signal;
 mod join;
 mod now;
@@ -182,6 +188,34 @@ pub unsafe trait GpuFuture: DeviceOwned {
         command_buffer.execute_after(self, queue)
     }
 
+    /// Binds memory to sparse resources after this future. Returns another future that
+    /// represents the binding operation.
+    ///
+    /// The binding operation is submitted to `queue`, whose family must support sparse binding.
+    /// Operations that are submitted after the returned future wait on a semaphore that is
+    /// signaled by the binding operation.
+    ///
+    /// > **Note**: This is just a shortcut for the `Queue::bind_sparse()` function when `self`
+    /// > is a `NowFuture`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the device of `queue` or of one of the resources or memory in `bind_info` is
+    ///   not the same as the device of the future.
+    /// - Panics if the `size` of a buffer or opaque image bind, or the `extent` of an image bind,
+    ///   is zero.
+    #[inline]
+    fn then_bind_sparse(
+        self,
+        queue: Arc<Queue>,
+        bind_info: BindSparseInfo,
+    ) -> Result<BindSparseFuture<Self>, BindSparseError>
+    where
+        Self: Sized,
+    {
+        bind_sparse::then_bind_sparse(self, queue, bind_info)
+    }
+
     /// Signals a semaphore after this future. Returns another future that represents the signal.
     ///
     /// Call this function when you want to execute some operations on a queue and want to see the
@@ -278,6 +312,23 @@ pub unsafe trait GpuFuture: DeviceOwned {
         swapchain::present_incremental(swapchain, self, queue, image_index, present_region)
     }
 
+    /// Same as `then_swapchain_present`, except it assigns a present ID to the presentation.
+    ///
+    /// > **Note**: This is just a shortcut for the `swapchain::present_with_id()` function.
+    #[inline]
+    fn then_swapchain_present_with_id<W>(
+        self,
+        queue: Arc<Queue>,
+        swapchain: Arc<Swapchain<W>>,
+        image_index: usize,
+        present_id: u64,
+    ) -> PresentFuture<Self, W>
+    where
+        Self: Sized,
+    {
+        swapchain::present_with_id(swapchain, self, queue, image_index, present_id)
+    }
+
     /// Turn the current future into a `Box<dyn GpuFuture>`.
     ///
     /// This is a helper function that calls `Box::new(yourFuture) as Box<dyn GpuFuture>`.
@@ -401,6 +452,10 @@ pub enum AccessError {
     /// Trying to use a buffer that still contains garbage data.
     BufferNotInitialized,
 
+    /// Trying to use a sparse resource that was created without sparse residency, while not all
+    /// of it is bound to memory.
+    SparseNotBound,
+
     /// Trying to use a swapchain image without depending on a corresponding acquire image future.
     SwapchainImageAcquireOnly,
 }
@@ -428,6 +483,10 @@ impl fmt::Display for AccessError {
                 AccessError::BufferNotInitialized => {
                     "trying to use a buffer that still contains garbage data"
                 }
+                AccessError::SparseNotBound => {
+                    "trying to use a sparse resource that was created without sparse residency, \
+                 while not all of it is bound to memory"
+                }
                 AccessError::SwapchainImageAcquireOnly => {
                     "trying to use a swapchain image without depending on a corresponding acquire \
                  image future"
@@ -494,6 +553,11 @@ pub enum FlushError {
 
     /// The flush operation needed to block, but the timeout has elapsed.
     Timeout,
+
+    /// The ownership of an image had to be transferred to a queue family that doesn't support
+    /// graphics, compute or transfer operations, and therefore can't record the barrier that
+    /// acquires the ownership.
+    OwnershipTransferNotSupported { queue_family_index: u32 },
 }
 
 impl error::Error for FlushError {
@@ -526,6 +590,10 @@ impl fmt::Display for FlushError {
                     "the flush operation needed to block, but the timeout has \
                                     elapsed"
                 }
+                FlushError::OwnershipTransferNotSupported { .. } => {
+                    "the ownership of an image had to be transferred to a queue family that \
+                     doesn't support graphics, compute or transfer operations"
+                }
             }
         )
     }
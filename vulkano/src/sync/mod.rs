@@ -104,14 +104,23 @@
 //! TODO: talk about using fences to clean up
 
 use crate::device::Queue;
+use smallvec::SmallVec;
 use std::sync::Arc;
 
 pub use self::{
+    deferred::{DeferredDeleter, FenceStatus},
     event::{Event, EventCreateInfo},
-    fence::{Fence, FenceCreateInfo, FenceWaitError},
+    fence::{
+        ExternalFenceHandleType, ExternalFenceHandleTypes, ExternalFenceInfo,
+        ExternalFenceProperties, Fence, FenceCreateInfo, FenceCreationError, FenceExportError,
+        FenceImportError, FenceWaitError,
+    },
     future::{
-        now, AccessCheckError, AccessError, FenceSignalFuture, FlushError, GpuFuture, JoinFuture,
-        NowFuture, SemaphoreSignalFuture,
+        now, AccessCheckError, AccessError, BindSparseError, BindSparseErrorResource,
+        BindSparseFuture, BindSparseInfo, FenceSignalFuture, FlushError, GpuFuture, JoinFuture,
+        NowFuture, SemaphoreSignalFuture, SparseBufferBindInfo, SparseBufferMemoryBind,
+        SparseImageBindInfo, SparseImageMemoryBind, SparseImageOpaqueBindInfo,
+        SparseImageOpaqueMemoryBind,
     },
     pipeline::{
         AccessFlags, BufferMemoryBarrier, DependencyInfo, ImageMemoryBarrier, MemoryBarrier,
@@ -120,9 +129,11 @@ pub use self::{
     semaphore::{
         ExternalSemaphoreHandleType, ExternalSemaphoreHandleTypes, ExternalSemaphoreInfo,
         ExternalSemaphoreProperties, Semaphore, SemaphoreCreateInfo, SemaphoreCreationError,
+        SemaphoreExportError, SemaphoreImportError, SemaphoreType, TimelineSemaphoreError,
     },
 };
 
+pub mod deferred;
 mod event;
 mod fence;
 mod future;
@@ -169,6 +180,29 @@ where
     Concurrent(I),
 }
 
+impl<'a> From<&'a [&'a Arc<Queue>]> for Sharing<SmallVec<[u32; 4]>> {
+    /// Returns `Exclusive` if all the queues are of the same queue family, and otherwise
+    /// `Concurrent` with the queue families of the queues.
+    #[inline]
+    fn from(queues: &'a [&'a Arc<Queue>]) -> Self {
+        let mut queue_family_indices: SmallVec<[u32; 4]> = SmallVec::new();
+
+        for queue in queues {
+            let queue_family_index = queue.family().id();
+
+            if !queue_family_indices.contains(&queue_family_index) {
+                queue_family_indices.push(queue_family_index);
+            }
+        }
+
+        if queue_family_indices.len() > 1 {
+            Sharing::Concurrent(queue_family_indices)
+        } else {
+            Sharing::Exclusive
+        }
+    }
+}
+
 /// How the memory of a resource is currently being accessed.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum CurrentAccess {
@@ -20,7 +20,7 @@ use std::{
 
 macro_rules! pipeline_stages {
     ($($elem:ident, $var:ident => $val:ident, $queue:expr;)+) => (
-        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
         pub struct PipelineStages {
             $(
                 pub $elem: bool,
@@ -49,6 +49,17 @@ macro_rules! pipeline_stages {
             }
         }
 
+        impl From<ash::vk::PipelineStageFlags> for PipelineStages {
+            #[inline]
+            fn from(val: ash::vk::PipelineStageFlags) -> Self {
+                PipelineStages {
+                    $(
+                        $elem: val.intersects(ash::vk::PipelineStageFlags::$val),
+                    )+
+                }
+            }
+        }
+
         impl From<PipelineStages> for ash::vk::PipelineStageFlags2 {
             #[inline]
             fn from(val: PipelineStages) -> Self {
@@ -134,6 +145,7 @@ impl PipelineStages {
             all_graphics,
             all_commands,
             ray_tracing_shader,
+            mut fragment_density_process,
         } = *self;
 
         if all_graphics {
@@ -152,7 +164,7 @@ impl PipelineStages {
             //conditional_rendering = true;
             //transform_feedback = true;
             //fragment_shading_rate_attachment = true;
-            //fragment_density_process = true;
+            fragment_density_process = true;
         }
 
         AccessFlags {
@@ -205,6 +217,7 @@ impl PipelineStages {
             host_write: host,
             memory_read: true,
             memory_write: true,
+            fragment_density_map_read: fragment_density_process,
 
             /*
             color_attachment_read_noncoherent: color_attachment_output,
@@ -218,7 +231,6 @@ impl PipelineStages {
             transform_feedback_counter_read: transform_feedback || draw_indirect,
             acceleration_structure_read: task_shader || mesh_shader || vertex_shader || tessellation_control_shader || tessellation_evaluation_shader || geometry_shader || fragment_shader || compute_shader || ray_tracing_shader || acceleration_structure_build,
             acceleration_structure_write: acceleration_structure_build,
-            fragment_density_map_read: fragment_density_process,
             */
         }
     }
@@ -257,11 +269,12 @@ pipeline_stages! {
     all_graphics, AllGraphics => ALL_GRAPHICS, ash::vk::QueueFlags::GRAPHICS;
     all_commands, AllCommands => ALL_COMMANDS, ash::vk::QueueFlags::empty();
     ray_tracing_shader, RayTracingShader => RAY_TRACING_SHADER_KHR, ash::vk::QueueFlags::GRAPHICS | ash::vk::QueueFlags::COMPUTE | ash::vk::QueueFlags::TRANSFER;
+    fragment_density_process, FragmentDensityProcess => FRAGMENT_DENSITY_PROCESS_EXT, ash::vk::QueueFlags::GRAPHICS;
 }
 
 macro_rules! access_flags {
     ($($elem:ident => $val:ident,)+) => (
-        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
         #[allow(missing_docs)]
         pub struct AccessFlags {
             $(
@@ -360,6 +373,7 @@ access_flags! {
     host_write => HOST_WRITE,
     memory_read => MEMORY_READ,
     memory_write => MEMORY_WRITE,
+    fragment_density_map_read => FRAGMENT_DENSITY_MAP_READ_EXT,
 }
 
 /// The full specification of memory access by the pipeline for a particular resource.
@@ -10,9 +10,10 @@
 use crate::{
     check_errors,
     device::{Device, DeviceOwned},
-    Error, OomError, Version, VulkanObject,
+    Error, OomError, Success, Version, VulkanObject,
 };
 use std::{
+    ffi::c_void,
     fmt,
     fs::File,
     hash::{Hash, Hasher},
@@ -20,18 +21,24 @@ use std::{
     ops::BitOr,
     ptr,
     sync::Arc,
+    time::Duration,
 };
 
 /// Used to provide synchronization between command buffers during their execution.
 ///
 /// It is similar to a fence, except that it is purely on the GPU side. The CPU can't query a
-/// semaphore's status or wait for it to be signaled.
+/// binary semaphore's status or wait for it to be signaled.
+///
+/// A timeline semaphore instead has a 64-bit counter value that only increases. It can be
+/// queried, signaled and waited for from the host, with [`counter_value`](Self::counter_value),
+/// [`signal`](Self::signal) and [`wait`](Self::wait).
 #[derive(Debug)]
 pub struct Semaphore {
     handle: ash::vk::Semaphore,
     device: Arc<Device>,
     must_put_in_pool: bool,
 
+    semaphore_type: SemaphoreType,
     export_handle_types: ExternalSemaphoreHandleTypes,
 }
 
@@ -42,11 +49,31 @@ impl Semaphore {
         create_info: SemaphoreCreateInfo,
     ) -> Result<Semaphore, SemaphoreCreationError> {
         let SemaphoreCreateInfo {
+            semaphore_type,
+            initial_value,
             export_handle_types,
             _ne: _,
         } = create_info;
         let instance = device.instance();
 
+        match semaphore_type {
+            SemaphoreType::Binary => {
+                // VUID-VkSemaphoreTypeCreateInfo-semaphoreType-03279
+                if initial_value != 0 {
+                    return Err(SemaphoreCreationError::BinaryInitialValueNotZero);
+                }
+            }
+            SemaphoreType::Timeline => {
+                // VUID-VkSemaphoreTypeCreateInfo-timelineSemaphore-03252
+                if !device.enabled_features().timeline_semaphore {
+                    return Err(SemaphoreCreationError::FeatureNotEnabled {
+                        feature: "timeline_semaphore",
+                        reason: "`semaphore_type` was `SemaphoreType::Timeline`",
+                    });
+                }
+            }
+        }
+
         if export_handle_types != ExternalSemaphoreHandleTypes::none() {
             if !(device.api_version() >= Version::V1_1
                 || device.enabled_extensions().khr_external_semaphore)
@@ -72,8 +99,20 @@ impl Semaphore {
             }
 
             // VUID-VkExportSemaphoreCreateInfo-handleTypes-01124
-            // TODO: `vkGetPhysicalDeviceExternalSemaphoreProperties` can only be called with one
-            // handle type, so which one do we give it?
+            for handle_type in ExternalSemaphoreHandleType::ALL {
+                if !export_handle_types.contains(handle_type) {
+                    continue;
+                }
+
+                let exportable = device
+                    .physical_device()
+                    .external_semaphore_properties(ExternalSemaphoreInfo::handle_type(handle_type))
+                    .map_or(true, |properties| properties.exportable);
+
+                if !exportable {
+                    return Err(SemaphoreCreationError::HandleTypeNotExportable { handle_type });
+                }
+            }
         }
 
         let mut create_info = ash::vk::SemaphoreCreateInfo::builder();
@@ -92,6 +131,20 @@ impl Semaphore {
             create_info = create_info.push_next(info);
         }
 
+        let mut semaphore_type_create_info = if semaphore_type != SemaphoreType::Binary {
+            Some(ash::vk::SemaphoreTypeCreateInfo {
+                semaphore_type: semaphore_type.into(),
+                initial_value,
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+
+        if let Some(info) = semaphore_type_create_info.as_mut() {
+            create_info = create_info.push_next(info);
+        }
+
         let handle = unsafe {
             let fns = device.fns();
             let mut output = MaybeUninit::uninit();
@@ -109,6 +162,7 @@ impl Semaphore {
             handle,
             must_put_in_pool: false,
 
+            semaphore_type,
             export_handle_types,
         })
     }
@@ -127,6 +181,7 @@ impl Semaphore {
                 handle,
                 must_put_in_pool: true,
 
+                semaphore_type: SemaphoreType::Binary,
                 export_handle_types: ExternalSemaphoreHandleTypes::none(),
             },
             None => {
@@ -140,21 +195,200 @@ impl Semaphore {
         Ok(semaphore)
     }
 
+    /// Returns the type of the semaphore.
+    #[inline]
+    pub fn semaphore_type(&self) -> SemaphoreType {
+        self.semaphore_type
+    }
+
+    /// Returns the handle types that can be exported from the semaphore.
+    #[inline]
+    pub fn export_handle_types(&self) -> ExternalSemaphoreHandleTypes {
+        self.export_handle_types
+    }
+
+    /// Returns the current counter value of a timeline semaphore.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the semaphore is not a timeline semaphore.
+    pub fn counter_value(&self) -> Result<u64, TimelineSemaphoreError> {
+        // VUID?
+        assert_eq!(self.semaphore_type, SemaphoreType::Timeline);
+
+        unsafe {
+            let fns = self.device.fns();
+            let mut output = MaybeUninit::uninit();
+
+            if self.device.api_version() >= Version::V1_2 {
+                check_errors((fns.v1_2.get_semaphore_counter_value)(
+                    self.device.internal_object(),
+                    self.handle,
+                    output.as_mut_ptr(),
+                ))?;
+            } else {
+                check_errors(
+                    (fns.khr_timeline_semaphore.get_semaphore_counter_value_khr)(
+                        self.device.internal_object(),
+                        self.handle,
+                        output.as_mut_ptr(),
+                    ),
+                )?;
+            }
+
+            Ok(output.assume_init())
+        }
+    }
+
+    /// Sets the counter value of a timeline semaphore from the host.
+    ///
+    /// # Safety
+    ///
+    /// - `value` must be greater than the current counter value of the semaphore.
+    /// - `value` must be less than the value of any signal operation on the semaphore that is
+    ///   pending on a queue.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the semaphore is not a timeline semaphore.
+    pub unsafe fn signal(&self, value: u64) -> Result<(), TimelineSemaphoreError> {
+        // VUID?
+        assert_eq!(self.semaphore_type, SemaphoreType::Timeline);
+
+        let signal_info = ash::vk::SemaphoreSignalInfo {
+            semaphore: self.handle,
+            value,
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+
+        if self.device.api_version() >= Version::V1_2 {
+            check_errors((fns.v1_2.signal_semaphore)(
+                self.device.internal_object(),
+                &signal_info,
+            ))?;
+        } else {
+            check_errors((fns.khr_timeline_semaphore.signal_semaphore_khr)(
+                self.device.internal_object(),
+                &signal_info,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits until the counter value of a timeline semaphore is at least `value`.
+    ///
+    /// If `timeout` is `None`, waits without a time limit.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the semaphore is not a timeline semaphore.
+    pub fn wait(
+        &self,
+        value: u64,
+        timeout: Option<Duration>,
+    ) -> Result<(), TimelineSemaphoreError> {
+        // VUID?
+        assert_eq!(self.semaphore_type, SemaphoreType::Timeline);
+
+        let timeout_ns = if let Some(timeout) = timeout {
+            timeout
+                .as_secs()
+                .saturating_mul(1_000_000_000)
+                .saturating_add(timeout.subsec_nanos() as u64)
+        } else {
+            u64::MAX
+        };
+
+        let wait_info = ash::vk::SemaphoreWaitInfo {
+            semaphore_count: 1,
+            p_semaphores: &self.handle,
+            p_values: &value,
+            ..Default::default()
+        };
+
+        let result = unsafe {
+            let fns = self.device.fns();
+
+            if self.device.api_version() >= Version::V1_2 {
+                check_errors((fns.v1_2.wait_semaphores)(
+                    self.device.internal_object(),
+                    &wait_info,
+                    timeout_ns,
+                ))?
+            } else {
+                check_errors((fns.khr_timeline_semaphore.wait_semaphores_khr)(
+                    self.device.internal_object(),
+                    &wait_info,
+                    timeout_ns,
+                ))?
+            }
+        };
+
+        match result {
+            Success::Success => Ok(()),
+            Success::Timeout => Err(TimelineSemaphoreError::Timeout),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Exports the semaphore into a Unix file descriptor of type
+    /// [`ExternalSemaphoreHandleType::OpaqueFd`]. The caller owns the returned `File`.
+    ///
+    /// This is equivalent to `export_fd(ExternalSemaphoreHandleType::OpaqueFd)`.
+    ///
     /// # Safety
     ///
     /// - The semaphore must not be used, or have been used, to acquire a swapchain image.
+    #[inline]
     pub unsafe fn export_opaque_fd(&self) -> Result<File, SemaphoreExportError> {
-        let fns = self.device.fns();
+        self.export_fd(ExternalSemaphoreHandleType::OpaqueFd)
+    }
+
+    /// Exports the semaphore into a Unix file descriptor. The caller owns the returned `File`.
+    ///
+    /// `handle_type` must be [`ExternalSemaphoreHandleType::OpaqueFd`] or
+    /// [`ExternalSemaphoreHandleType::SyncFd`], and must have been provided in
+    /// `export_handle_types` when creating the semaphore. A `SyncFd` can only be exported from a
+    /// binary semaphore, and exporting it has the same effect as waiting on the semaphore: it
+    /// becomes unsignaled, and the returned file descriptor is signaled instead.
+    ///
+    /// # Safety
+    ///
+    /// - The semaphore must not be used, or have been used, to acquire a swapchain image.
+    /// - If `handle_type` is [`ExternalSemaphoreHandleType::SyncFd`], the semaphore must be
+    ///   signaled, or have a signal operation pending.
+    pub unsafe fn export_fd(
+        &self,
+        handle_type: ExternalSemaphoreHandleType,
+    ) -> Result<File, SemaphoreExportError> {
+        if !self.device.enabled_extensions().khr_external_semaphore_fd {
+            return Err(SemaphoreExportError::MissingExtension(
+                "khr_external_semaphore_fd",
+            ));
+        }
+
+        // VUID-VkSemaphoreGetFdInfoKHR-handleType-01136
+        if !matches!(
+            handle_type,
+            ExternalSemaphoreHandleType::OpaqueFd | ExternalSemaphoreHandleType::SyncFd
+        ) {
+            return Err(SemaphoreExportError::HandleTypeNotSupported { handle_type });
+        }
 
         // VUID-VkSemaphoreGetFdInfoKHR-handleType-01132
-        if !self.export_handle_types.opaque_fd {
-            return Err(SemaphoreExportError::HandleTypeNotSupported {
-                handle_type: ExternalSemaphoreHandleType::OpaqueFd,
-            });
+        if !self.export_handle_types.contains(handle_type) {
+            return Err(SemaphoreExportError::HandleTypeNotSupported { handle_type });
         }
 
-        assert!(self.device.enabled_extensions().khr_external_semaphore);
-        assert!(self.device.enabled_extensions().khr_external_semaphore_fd);
+        // VUID-VkSemaphoreGetFdInfoKHR-handleType-03253
+        if handle_type == ExternalSemaphoreHandleType::SyncFd
+            && self.semaphore_type != SemaphoreType::Binary
+        {
+            return Err(SemaphoreExportError::BinarySemaphoreRequired { handle_type });
+        }
 
         // VUID-VkSemaphoreGetFdInfoKHR-semaphore-01133
         // Can't validate for swapchain.
@@ -169,10 +403,11 @@ impl Semaphore {
             let fd = {
                 let info = ash::vk::SemaphoreGetFdInfoKHR {
                     semaphore: self.handle,
-                    handle_type: ash::vk::ExternalSemaphoreHandleTypeFlagsKHR::OPAQUE_FD,
+                    handle_type: handle_type.into(),
                     ..Default::default()
                 };
 
+                let fns = self.device.fns();
                 let mut output = MaybeUninit::uninit();
                 check_errors((fns.khr_external_semaphore_fd.get_semaphore_fd_khr)(
                     self.device.internal_object(),
@@ -185,6 +420,206 @@ impl Semaphore {
             Ok(file)
         }
     }
+
+    /// Exports the semaphore into a Win32 handle.
+    ///
+    /// `handle_type` must be [`ExternalSemaphoreHandleType::OpaqueWin32`],
+    /// [`ExternalSemaphoreHandleType::OpaqueWin32Kmt`] or
+    /// [`ExternalSemaphoreHandleType::D3D12Fence`], and must have been provided in
+    /// `export_handle_types` when creating the semaphore. For `OpaqueWin32` and `D3D12Fence`, the
+    /// caller owns the returned handle, and must close it with `CloseHandle` when it's no longer
+    /// needed.
+    ///
+    /// # Safety
+    ///
+    /// - The semaphore must not be used, or have been used, to acquire a swapchain image.
+    pub unsafe fn export_win32_handle(
+        &self,
+        handle_type: ExternalSemaphoreHandleType,
+    ) -> Result<*mut c_void, SemaphoreExportError> {
+        if !self
+            .device
+            .enabled_extensions()
+            .khr_external_semaphore_win32
+        {
+            return Err(SemaphoreExportError::MissingExtension(
+                "khr_external_semaphore_win32",
+            ));
+        }
+
+        // VUID-VkSemaphoreGetWin32HandleInfoKHR-handleType-01131
+        if !matches!(
+            handle_type,
+            ExternalSemaphoreHandleType::OpaqueWin32
+                | ExternalSemaphoreHandleType::OpaqueWin32Kmt
+                | ExternalSemaphoreHandleType::D3D12Fence
+        ) {
+            return Err(SemaphoreExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        // VUID-VkSemaphoreGetWin32HandleInfoKHR-handleType-01126
+        if !self.export_handle_types.contains(handle_type) {
+            return Err(SemaphoreExportError::HandleTypeNotSupported { handle_type });
+        }
+
+        let info = ash::vk::SemaphoreGetWin32HandleInfoKHR {
+            semaphore: self.handle,
+            handle_type: handle_type.into(),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        let mut output = MaybeUninit::uninit();
+        check_errors((fns
+            .khr_external_semaphore_win32
+            .get_semaphore_win32_handle_khr)(
+            self.device.internal_object(),
+            &info,
+            output.as_mut_ptr(),
+        ))?;
+
+        Ok(output.assume_init())
+    }
+
+    /// Imports a Unix file descriptor into the semaphore, replacing its payload.
+    ///
+    /// If `temporary` is `true`, the imported payload is only used until the semaphore is next
+    /// waited on, after which the previous payload is restored.
+    ///
+    /// `handle_type` must be [`ExternalSemaphoreHandleType::OpaqueFd`] or
+    /// [`ExternalSemaphoreHandleType::SyncFd`]. A `SyncFd` can only be imported temporarily, and
+    /// only into a binary semaphore.
+    ///
+    /// # Safety
+    ///
+    /// - `file` must be a valid file descriptor of type `handle_type`.
+    /// - If `handle_type` is [`ExternalSemaphoreHandleType::OpaqueFd`], `file` must have been
+    ///   exported from a semaphore of the same type, on a device with the same device and driver
+    ///   UUIDs.
+    /// - The semaphore must not be in use by a pending queue operation.
+    /// - Vulkan takes ownership of `file` if the import succeeds. You must not perform any
+    ///   operations on it or its duplicates afterwards.
+    pub unsafe fn import_fd(
+        &self,
+        handle_type: ExternalSemaphoreHandleType,
+        file: File,
+        temporary: bool,
+    ) -> Result<(), SemaphoreImportError> {
+        if !self.device.enabled_extensions().khr_external_semaphore_fd {
+            return Err(SemaphoreImportError::MissingExtension(
+                "khr_external_semaphore_fd",
+            ));
+        }
+
+        match handle_type {
+            ExternalSemaphoreHandleType::OpaqueFd => (),
+            // VUID?
+            ExternalSemaphoreHandleType::SyncFd
+                if temporary && self.semaphore_type == SemaphoreType::Binary => {}
+            // VUID-VkImportSemaphoreFdInfoKHR-handleType-01143
+            _ => return Err(SemaphoreImportError::HandleTypeNotSupported { handle_type }),
+        }
+
+        #[cfg(not(unix))]
+        unreachable!("`khr_external_semaphore_fd` was somehow enabled on a non-Unix system");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+            let fd = file.into_raw_fd();
+            let import_info = ash::vk::ImportSemaphoreFdInfoKHR {
+                semaphore: self.handle,
+                flags: if temporary {
+                    ash::vk::SemaphoreImportFlags::TEMPORARY
+                } else {
+                    ash::vk::SemaphoreImportFlags::empty()
+                },
+                handle_type: handle_type.into(),
+                fd,
+                ..Default::default()
+            };
+
+            let fns = self.device.fns();
+            let result = check_errors((fns.khr_external_semaphore_fd.import_semaphore_fd_khr)(
+                self.device.internal_object(),
+                &import_info,
+            ));
+
+            if let Err(err) = result {
+                // Ownership is only transferred on success.
+                drop(File::from_raw_fd(fd));
+                return Err(err.into());
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Imports a Win32 handle into the semaphore, replacing its payload.
+    ///
+    /// If `temporary` is `true`, the imported payload is only used until the semaphore is next
+    /// waited on, after which the previous payload is restored.
+    ///
+    /// `handle_type` must be [`ExternalSemaphoreHandleType::OpaqueWin32`],
+    /// [`ExternalSemaphoreHandleType::OpaqueWin32Kmt`] or
+    /// [`ExternalSemaphoreHandleType::D3D12Fence`]. Unlike file descriptors, Win32 handles are
+    /// not owned by Vulkan after they are imported, and must still be closed by the caller.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid handle of type `handle_type`, that was exported from a
+    ///   semaphore or fence on a device with the same device and driver UUIDs.
+    /// - The semaphore must not be in use by a pending queue operation.
+    pub unsafe fn import_win32_handle(
+        &self,
+        handle_type: ExternalSemaphoreHandleType,
+        handle: *mut c_void,
+        temporary: bool,
+    ) -> Result<(), SemaphoreImportError> {
+        if !self
+            .device
+            .enabled_extensions()
+            .khr_external_semaphore_win32
+        {
+            return Err(SemaphoreImportError::MissingExtension(
+                "khr_external_semaphore_win32",
+            ));
+        }
+
+        // VUID-VkImportSemaphoreWin32HandleInfoKHR-handleType-01140
+        if !matches!(
+            handle_type,
+            ExternalSemaphoreHandleType::OpaqueWin32
+                | ExternalSemaphoreHandleType::OpaqueWin32Kmt
+                | ExternalSemaphoreHandleType::D3D12Fence
+        ) {
+            return Err(SemaphoreImportError::HandleTypeNotSupported { handle_type });
+        }
+
+        let import_info = ash::vk::ImportSemaphoreWin32HandleInfoKHR {
+            semaphore: self.handle,
+            flags: if temporary {
+                ash::vk::SemaphoreImportFlags::TEMPORARY
+            } else {
+                ash::vk::SemaphoreImportFlags::empty()
+            },
+            handle_type: handle_type.into(),
+            handle,
+            name: ptr::null(),
+            ..Default::default()
+        };
+
+        let fns = self.device.fns();
+        check_errors((fns
+            .khr_external_semaphore_win32
+            .import_semaphore_win32_handle_khr)(
+            self.device.internal_object(),
+            &import_info,
+        ))?;
+
+        Ok(())
+    }
 }
 
 impl Drop for Semaphore {
@@ -246,6 +681,19 @@ pub enum SemaphoreCreationError {
 
     /// An extension is missing.
     MissingExtension(&'static str),
+
+    FeatureNotEnabled {
+        feature: &'static str,
+        reason: &'static str,
+    },
+
+    /// `semaphore_type` was `SemaphoreType::Binary`, but `initial_value` was not 0.
+    BinaryInitialValueNotZero,
+
+    /// A handle type in `export_handle_types` can't be exported from a semaphore on this device.
+    HandleTypeNotExportable {
+        handle_type: ExternalSemaphoreHandleType,
+    },
 }
 
 impl fmt::Display for SemaphoreCreationError {
@@ -255,6 +703,18 @@ impl fmt::Display for SemaphoreCreationError {
             Self::MissingExtension(s) => {
                 write!(fmt, "Missing the following extension: {}", s)
             }
+            Self::FeatureNotEnabled { feature, reason } => {
+                write!(fmt, "the feature {} must be enabled: {}", feature, reason)
+            }
+            Self::BinaryInitialValueNotZero => write!(
+                fmt,
+                "`semaphore_type` was `SemaphoreType::Binary`, but `initial_value` was not 0",
+            ),
+            Self::HandleTypeNotExportable { handle_type } => write!(
+                fmt,
+                "the handle type {:?} in `export_handle_types` can't be exported from a semaphore on this device",
+                handle_type,
+            ),
         }
     }
 }
@@ -289,6 +749,22 @@ impl From<OomError> for SemaphoreCreationError {
 /// Parameters to create a new `Semaphore`.
 #[derive(Clone, Debug)]
 pub struct SemaphoreCreateInfo {
+    /// The type of semaphore to create.
+    ///
+    /// If set to [`SemaphoreType::Timeline`], the
+    /// [`timeline_semaphore`](crate::device::Features::timeline_semaphore) feature must be
+    /// enabled on the device.
+    ///
+    /// The default value is [`SemaphoreType::Binary`].
+    pub semaphore_type: SemaphoreType,
+
+    /// The initial counter value of a timeline semaphore.
+    ///
+    /// Must be 0 for a binary semaphore.
+    ///
+    /// The default value is `0`.
+    pub initial_value: u64,
+
     /// The handle types that can be exported from the semaphore.
     ///
     /// The default value is [`ExternalSemaphoreHandleTypes::none()`].
@@ -301,12 +777,34 @@ impl Default for SemaphoreCreateInfo {
     #[inline]
     fn default() -> Self {
         Self {
+            semaphore_type: SemaphoreType::Binary,
+            initial_value: 0,
             export_handle_types: ExternalSemaphoreHandleTypes::none(),
             _ne: crate::NonExhaustive(()),
         }
     }
 }
 
+/// The type of a semaphore.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum SemaphoreType {
+    /// A semaphore that is either signaled or unsignaled. It can only be signaled and waited
+    /// for by queue operations.
+    Binary = ash::vk::SemaphoreType::BINARY.as_raw(),
+
+    /// A semaphore with a 64-bit counter value that only increases. It can also be signaled and
+    /// waited for by the host.
+    Timeline = ash::vk::SemaphoreType::TIMELINE.as_raw(),
+}
+
+impl From<SemaphoreType> for ash::vk::SemaphoreType {
+    #[inline]
+    fn from(val: SemaphoreType) -> Self {
+        Self::from_raw(val as i32)
+    }
+}
+
 /// Describes the handle type used for Vulkan external semaphore APIs.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u32)]
@@ -318,6 +816,17 @@ pub enum ExternalSemaphoreHandleType {
     SyncFd = ash::vk::ExternalSemaphoreHandleTypeFlags::SYNC_FD.as_raw(),
 }
 
+impl ExternalSemaphoreHandleType {
+    // All the handle types, in the order of their bits.
+    pub(crate) const ALL: [ExternalSemaphoreHandleType; 5] = [
+        ExternalSemaphoreHandleType::OpaqueFd,
+        ExternalSemaphoreHandleType::OpaqueWin32,
+        ExternalSemaphoreHandleType::OpaqueWin32Kmt,
+        ExternalSemaphoreHandleType::D3D12Fence,
+        ExternalSemaphoreHandleType::SyncFd,
+    ];
+}
+
 impl From<ExternalSemaphoreHandleType> for ash::vk::ExternalSemaphoreHandleTypeFlags {
     fn from(val: ExternalSemaphoreHandleType) -> Self {
         Self::from_raw(val as u32)
@@ -374,6 +883,18 @@ impl ExternalSemaphoreHandleTypes {
             ..ExternalSemaphoreHandleTypes::none()
         }
     }
+
+    /// Returns whether `handle_type` is included.
+    #[inline]
+    pub fn contains(&self, handle_type: ExternalSemaphoreHandleType) -> bool {
+        match handle_type {
+            ExternalSemaphoreHandleType::OpaqueFd => self.opaque_fd,
+            ExternalSemaphoreHandleType::OpaqueWin32 => self.opaque_win32,
+            ExternalSemaphoreHandleType::OpaqueWin32Kmt => self.opaque_win32_kmt,
+            ExternalSemaphoreHandleType::D3D12Fence => self.d3d12_fence,
+            ExternalSemaphoreHandleType::SyncFd => self.sync_fd,
+        }
+    }
 }
 
 impl From<ExternalSemaphoreHandleTypes> for ash::vk::ExternalSemaphoreHandleTypeFlags {
@@ -470,27 +991,44 @@ pub struct ExternalSemaphoreProperties {
     pub compatible_handle_types: ExternalSemaphoreHandleTypes,
 }
 
+/// Error that can happen when exporting a handle from a semaphore.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SemaphoreExportError {
     /// Not enough memory available.
     OomError(OomError),
 
+    /// An extension is missing.
+    MissingExtension(&'static str),
+
     /// The requested export handle type was not provided in `export_handle_types` when creating the
-    /// semaphore.
+    /// semaphore, or can't be exported with the called method.
     HandleTypeNotSupported {
         handle_type: ExternalSemaphoreHandleType,
     },
+
+    /// The requested export handle type can only be exported from a binary semaphore.
+    BinarySemaphoreRequired {
+        handle_type: ExternalSemaphoreHandleType,
+    },
 }
 
 impl fmt::Display for SemaphoreExportError {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
             Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::MissingExtension(s) => {
+                write!(fmt, "Missing the following extension: {}", s)
+            }
             Self::HandleTypeNotSupported { handle_type } => write!(
                 fmt,
                 "the requested export handle type ({:?}) was not provided in `export_handle_types` when creating the semaphore",
                 handle_type,
             ),
+            Self::BinarySemaphoreRequired { handle_type } => write!(
+                fmt,
+                "the requested export handle type ({:?}) can only be exported from a binary semaphore",
+                handle_type,
+            ),
         }
     }
 }
@@ -522,12 +1060,132 @@ impl From<OomError> for SemaphoreExportError {
     }
 }
 
+/// Error that can happen when importing a handle into a semaphore.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SemaphoreImportError {
+    /// Not enough memory available.
+    OomError(OomError),
+
+    /// An extension is missing.
+    MissingExtension(&'static str),
+
+    /// The handle type can't be imported into this semaphore with the requested permanence.
+    HandleTypeNotSupported {
+        handle_type: ExternalSemaphoreHandleType,
+    },
+
+    /// The handle was not valid, or was not compatible with the semaphore.
+    InvalidExternalHandle,
+}
+
+impl fmt::Display for SemaphoreImportError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::MissingExtension(s) => {
+                write!(fmt, "Missing the following extension: {}", s)
+            }
+            Self::HandleTypeNotSupported { handle_type } => write!(
+                fmt,
+                "the handle type ({:?}) can't be imported into this semaphore with the requested permanence",
+                handle_type,
+            ),
+            Self::InvalidExternalHandle => write!(
+                fmt,
+                "the handle was not valid, or was not compatible with the semaphore",
+            ),
+        }
+    }
+}
+
+impl From<Error> for SemaphoreImportError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            e @ Error::OutOfHostMemory | e @ Error::OutOfDeviceMemory => Self::OomError(e.into()),
+            Error::InvalidExternalHandle => Self::InvalidExternalHandle,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+impl std::error::Error for SemaphoreImportError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<OomError> for SemaphoreImportError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+/// Error that can happen when querying, signaling or waiting for a timeline semaphore.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimelineSemaphoreError {
+    /// Not enough memory available.
+    OomError(OomError),
+
+    /// The specified timeout wasn't long enough.
+    Timeout,
+
+    /// The device has been lost.
+    DeviceLost,
+}
+
+impl fmt::Display for TimelineSemaphoreError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::OomError(_) => write!(fmt, "not enough memory available"),
+            Self::Timeout => write!(fmt, "the timeout has been reached"),
+            Self::DeviceLost => write!(fmt, "the device was lost"),
+        }
+    }
+}
+
+impl From<Error> for TimelineSemaphoreError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            e @ Error::OutOfHostMemory | e @ Error::OutOfDeviceMemory => Self::OomError(e.into()),
+            Error::DeviceLost => Self::DeviceLost,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+impl std::error::Error for TimelineSemaphoreError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Self::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<OomError> for TimelineSemaphoreError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::device::physical::PhysicalDevice;
     use crate::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo};
     use crate::instance::{Instance, InstanceCreateInfo, InstanceExtensions};
-    use crate::sync::{ExternalSemaphoreHandleTypes, Semaphore, SemaphoreCreateInfo};
+    use crate::sync::{
+        ExternalSemaphoreHandleType, ExternalSemaphoreHandleTypes, Semaphore, SemaphoreCreateInfo,
+        SemaphoreCreationError, SemaphoreExportError, SemaphoreType,
+    };
     use crate::VulkanObject;
 
     #[test]
@@ -536,6 +1194,25 @@ mod tests {
         let _ = Semaphore::new(device.clone(), Default::default());
     }
 
+    #[test]
+    fn timeline_feature_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        assert!(matches!(
+            Semaphore::new(
+                device,
+                SemaphoreCreateInfo {
+                    semaphore_type: SemaphoreType::Timeline,
+                    ..Default::default()
+                },
+            ),
+            Err(SemaphoreCreationError::FeatureNotEnabled {
+                feature: "timeline_semaphore",
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn semaphore_pool() {
         let (device, _) = gfx_dev_and_queue!();
@@ -595,5 +1272,11 @@ mod tests {
         )
         .unwrap();
         let fd = unsafe { sem.export_opaque_fd().unwrap() };
+
+        // `sync_fd` was not in `export_handle_types`.
+        assert!(matches!(
+            unsafe { sem.export_fd(ExternalSemaphoreHandleType::SyncFd) },
+            Err(SemaphoreExportError::HandleTypeNotSupported { .. })
+        ));
     }
 }
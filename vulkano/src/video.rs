@@ -0,0 +1,2079 @@
+// Copyright (c) 2022 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Hardware-accelerated video decoding.
+//!
+//! A [`VideoSession`] decodes a video stream that was encoded with the codec, profile, chroma
+//! subsampling and bit depth of a [`VideoProfile`]. The profiles that a physical device supports,
+//! and the limits that apply to them, can be queried with
+//! [`PhysicalDevice::video_capabilities`]. The codec-specific parameter sets of the stream, such
+//! as the sequence and picture parameter sets of H.264, are stored in a
+//! [`VideoSessionParameters`] object that belongs to the session.
+//!
+//! Decoding reads the compressed bitstream from a buffer, and writes the decoded picture to an
+//! image. Pictures that later pictures are predicted from are kept in the *decoded picture
+//! buffer* (DPB), a set of numbered slots that each hold a *reference picture*. The buffers and
+//! images that are used for video decoding must be created for the profile of the session, which
+//! is taken care of by [`VideoImage`] and by
+//! [`CpuAccessibleBuffer::video_bitstream`](crate::buffer::CpuAccessibleBuffer::video_bitstream).
+//! The DPB is usually a single [`VideoImage`] with one array layer per slot, and [`DpbSlots`]
+//! keeps track of which slots currently hold a reference picture.
+//!
+//! Video decode commands are recorded between
+//! [`begin_video_coding`](crate::command_buffer::AutoCommandBufferBuilder::begin_video_coding)
+//! and [`end_video_coding`](crate::command_buffer::AutoCommandBufferBuilder::end_video_coding).
+//! The session must be reset with
+//! [`reset_video_coding`](crate::command_buffer::AutoCommandBufferBuilder::reset_video_coding)
+//! before the first picture is decoded. Each picture is then decoded with
+//! [`decode_video`](crate::command_buffer::AutoCommandBufferBuilder::decode_video). The command
+//! buffer must be submitted to a queue whose family supports video decode operations, which can
+//! be checked with [`QueueFamily::supports_video_decode`].
+//!
+//! Video decoding requires the
+//! [`khr_video_queue`](crate::device::DeviceExtensions::khr_video_queue) and
+//! [`khr_video_decode_queue`](crate::device::DeviceExtensions::khr_video_decode_queue)
+//! extensions to be enabled on the device, as well as the extension of the codec:
+//! [`khr_video_decode_h264`](crate::device::DeviceExtensions::khr_video_decode_h264) or
+//! [`khr_video_decode_h265`](crate::device::DeviceExtensions::khr_video_decode_h265).
+//!
+//! The codec-specific structures, such as the parameter sets and the picture information, are the
+//! `StdVideo*` types of the Vulkan video codec headers, which are provided by
+//! [`ash::vk::native`]. They are passed to the implementation as they are, and are not validated
+//! by vulkano; parsing them from the bitstream is the responsibility of the application.
+//!
+//! [`PhysicalDevice::video_capabilities`]: crate::device::physical::PhysicalDevice::video_capabilities
+//! [`QueueFamily::supports_video_decode`]: crate::device::physical::QueueFamily::supports_video_decode
+
+use crate::{
+    buffer::BufferAccess,
+    check_errors,
+    device::{Device, DeviceOwned},
+    format::Format,
+    image::{
+        sys::{UnsafeImage, UnsafeImageCreateInfo},
+        traits::ImageContent,
+        view::ImageViewAbstract,
+        ImageAccess, ImageCreationError, ImageDescriptorLayouts, ImageDimensions, ImageInner,
+        ImageLayout, ImageUsage,
+    },
+    memory::{
+        pool::{
+            AllocFromRequirementsFilter, AllocLayout, MappingRequirement, MemoryPoolAlloc,
+            PotentialDedicatedAllocation, StdMemoryPoolAlloc,
+        },
+        DedicatedAllocation, DeviceMemory, DeviceMemoryAllocationError, MemoryAllocateInfo,
+        MemoryPool,
+    },
+    sync::Sharing,
+    DeviceSize, Error, OomError, Success, VulkanObject,
+};
+use ash::vk::native::{
+    StdVideoDecodeH264PictureInfo, StdVideoDecodeH264ReferenceInfo, StdVideoDecodeH265PictureInfo,
+    StdVideoDecodeH265ReferenceInfo, StdVideoH264PictureParameterSet,
+    StdVideoH264SequenceParameterSet, StdVideoH265PictureParameterSet,
+    StdVideoH265SequenceParameterSet, StdVideoH265VideoParameterSet,
+};
+use smallvec::SmallVec;
+use std::{
+    error, fmt,
+    hash::{Hash, Hasher},
+    mem::MaybeUninit,
+    ops::Range,
+    ptr, slice,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// The codec, profile and picture format of a video stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VideoProfile {
+    /// The codec of the video stream, and the codec-specific profile.
+    ///
+    /// There is no default value.
+    pub codec: VideoDecodeCodec,
+
+    /// The chroma subsampling of the decoded pictures.
+    ///
+    /// The default value is [`VideoChromaSubsampling::Mode420`].
+    pub chroma_subsampling: VideoChromaSubsampling,
+
+    /// The bit depth of the luma component of the decoded pictures.
+    ///
+    /// The default value is [`VideoComponentBitDepth::Bits8`].
+    pub luma_bit_depth: VideoComponentBitDepth,
+
+    /// The bit depth of the chroma components of the decoded pictures. This must be `None` if
+    /// `chroma_subsampling` is [`VideoChromaSubsampling::Monochrome`], and `Some` otherwise.
+    ///
+    /// The default value is `Some(VideoComponentBitDepth::Bits8)`.
+    pub chroma_bit_depth: Option<VideoComponentBitDepth>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl VideoProfile {
+    /// Returns a `VideoProfile` for decoding H.264 with the specified `profile_idc`, with
+    /// progressive pictures.
+    #[inline]
+    pub fn decode_h264(profile_idc: H264ProfileIdc) -> Self {
+        Self::codec(VideoDecodeCodec::H264 {
+            profile_idc,
+            picture_layout: H264PictureLayout::Progressive,
+        })
+    }
+
+    /// Returns a `VideoProfile` for decoding H.265 with the specified `profile_idc`.
+    #[inline]
+    pub fn decode_h265(profile_idc: H265ProfileIdc) -> Self {
+        Self::codec(VideoDecodeCodec::H265 { profile_idc })
+    }
+
+    /// Returns a `VideoProfile` with the specified `codec`.
+    #[inline]
+    pub fn codec(codec: VideoDecodeCodec) -> Self {
+        Self {
+            codec,
+            chroma_subsampling: VideoChromaSubsampling::Mode420,
+            luma_bit_depth: VideoComponentBitDepth::Bits8,
+            chroma_bit_depth: Some(VideoComponentBitDepth::Bits8),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// A video codec that can be decoded, with its codec-specific profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum VideoDecodeCodec {
+    /// H.264 / AVC.
+    ///
+    /// Requires the [`khr_video_decode_h264`](crate::device::DeviceExtensions::khr_video_decode_h264)
+    /// extension.
+    H264 {
+        profile_idc: H264ProfileIdc,
+        picture_layout: H264PictureLayout,
+    },
+
+    /// H.265 / HEVC.
+    ///
+    /// Requires the [`khr_video_decode_h265`](crate::device::DeviceExtensions::khr_video_decode_h265)
+    /// extension.
+    H265 { profile_idc: H265ProfileIdc },
+}
+
+impl VideoDecodeCodec {
+    /// Returns the name of the device extension that is needed to decode this codec.
+    #[inline]
+    pub fn required_extension(&self) -> &'static str {
+        match self {
+            Self::H264 { .. } => "khr_video_decode_h264",
+            Self::H265 { .. } => "khr_video_decode_h265",
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_supported_by(&self, extensions: &crate::device::DeviceExtensions) -> bool {
+        match self {
+            Self::H264 { .. } => extensions.khr_video_decode_h264,
+            Self::H265 { .. } => extensions.khr_video_decode_h265,
+        }
+    }
+
+    // Returns whether `self` and `other` are the same codec, regardless of the profile.
+    #[inline]
+    pub(crate) fn is_same_codec(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::H264 { .. }, Self::H264 { .. }) | (Self::H265 { .. }, Self::H265 { .. })
+        )
+    }
+}
+
+impl From<VideoDecodeCodec> for ash::vk::VideoCodecOperationFlagsKHR {
+    #[inline]
+    fn from(val: VideoDecodeCodec) -> Self {
+        match val {
+            VideoDecodeCodec::H264 { .. } => Self::DECODE_H264,
+            VideoDecodeCodec::H265 { .. } => Self::DECODE_H265,
+        }
+    }
+}
+
+/// An H.264 profile, corresponding to the `profile_idc` of the sequence parameter set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum H264ProfileIdc {
+    Baseline = ash::vk::native::StdVideoH264ProfileIdc_STD_VIDEO_H264_PROFILE_IDC_BASELINE,
+    Main = ash::vk::native::StdVideoH264ProfileIdc_STD_VIDEO_H264_PROFILE_IDC_MAIN,
+    High = ash::vk::native::StdVideoH264ProfileIdc_STD_VIDEO_H264_PROFILE_IDC_HIGH,
+    High444Predictive =
+        ash::vk::native::StdVideoH264ProfileIdc_STD_VIDEO_H264_PROFILE_IDC_HIGH_444_PREDICTIVE,
+}
+
+/// An H.265 profile, corresponding to the `general_profile_idc` of the profile tier level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum H265ProfileIdc {
+    Main = ash::vk::native::StdVideoH265ProfileIdc_STD_VIDEO_H265_PROFILE_IDC_MAIN,
+    Main10 = ash::vk::native::StdVideoH265ProfileIdc_STD_VIDEO_H265_PROFILE_IDC_MAIN_10,
+    MainStillPicture =
+        ash::vk::native::StdVideoH265ProfileIdc_STD_VIDEO_H265_PROFILE_IDC_MAIN_STILL_PICTURE,
+    FormatRangeExtensions =
+        ash::vk::native::StdVideoH265ProfileIdc_STD_VIDEO_H265_PROFILE_IDC_FORMAT_RANGE_EXTENSIONS,
+    SccExtensions =
+        ash::vk::native::StdVideoH265ProfileIdc_STD_VIDEO_H265_PROFILE_IDC_SCC_EXTENSIONS,
+}
+
+/// How the fields of interlaced H.264 pictures are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum H264PictureLayout {
+    /// The stream only contains progressive pictures.
+    Progressive = ash::vk::VideoDecodeH264PictureLayoutFlagsKHR::PROGRESSIVE.as_raw(),
+
+    /// The two fields of a picture are stored in the even and odd lines of the same image.
+    InterlacedInterleavedLines =
+        ash::vk::VideoDecodeH264PictureLayoutFlagsKHR::INTERLACED_INTERLEAVED_LINES.as_raw(),
+
+    /// The two fields of a picture are stored in separate images.
+    InterlacedSeparatePlanes =
+        ash::vk::VideoDecodeH264PictureLayoutFlagsKHR::INTERLACED_SEPARATE_PLANES.as_raw(),
+}
+
+impl From<H264PictureLayout> for ash::vk::VideoDecodeH264PictureLayoutFlagsKHR {
+    #[inline]
+    fn from(val: H264PictureLayout) -> Self {
+        Self::from_raw(val as u32)
+    }
+}
+
+/// The chroma subsampling of video pictures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum VideoChromaSubsampling {
+    /// The pictures only have a luma component.
+    Monochrome = ash::vk::VideoChromaSubsamplingFlagsKHR::MONOCHROME.as_raw(),
+
+    /// The chroma components have half the width and half the height of the luma component.
+    Mode420 = ash::vk::VideoChromaSubsamplingFlagsKHR::TYPE_420.as_raw(),
+
+    /// The chroma components have half the width of the luma component.
+    Mode422 = ash::vk::VideoChromaSubsamplingFlagsKHR::TYPE_422.as_raw(),
+
+    /// The chroma components have the same size as the luma component.
+    Mode444 = ash::vk::VideoChromaSubsamplingFlagsKHR::TYPE_444.as_raw(),
+}
+
+impl From<VideoChromaSubsampling> for ash::vk::VideoChromaSubsamplingFlagsKHR {
+    #[inline]
+    fn from(val: VideoChromaSubsampling) -> Self {
+        Self::from_raw(val as u32)
+    }
+}
+
+/// The bit depth of a component of video pictures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum VideoComponentBitDepth {
+    Bits8 = ash::vk::VideoComponentBitDepthFlagsKHR::TYPE_8.as_raw(),
+    Bits10 = ash::vk::VideoComponentBitDepthFlagsKHR::TYPE_10.as_raw(),
+    Bits12 = ash::vk::VideoComponentBitDepthFlagsKHR::TYPE_12.as_raw(),
+}
+
+impl From<VideoComponentBitDepth> for ash::vk::VideoComponentBitDepthFlagsKHR {
+    #[inline]
+    fn from(val: VideoComponentBitDepth) -> Self {
+        Self::from_raw(val as u32)
+    }
+}
+
+// The Vulkan structures that describe a list of video profiles. The codec-specific structures are
+// stored separately, so that the profile structures can point to them.
+pub(crate) struct VideoProfilesVk {
+    _codec_infos: Vec<VideoCodecProfileVk>,
+    profile_infos: Vec<ash::vk::VideoProfileInfoKHR>,
+}
+
+enum VideoCodecProfileVk {
+    H264(ash::vk::VideoDecodeH264ProfileInfoKHR),
+    H265(ash::vk::VideoDecodeH265ProfileInfoKHR),
+}
+
+impl VideoProfilesVk {
+    pub(crate) fn new(profiles: &[VideoProfile]) -> Self {
+        let codec_infos: Vec<_> = profiles
+            .iter()
+            .map(|profile| match profile.codec {
+                VideoDecodeCodec::H264 {
+                    profile_idc,
+                    picture_layout,
+                } => VideoCodecProfileVk::H264(ash::vk::VideoDecodeH264ProfileInfoKHR {
+                    std_profile_idc: profile_idc as _,
+                    picture_layout: picture_layout.into(),
+                    ..Default::default()
+                }),
+                VideoDecodeCodec::H265 { profile_idc } => {
+                    VideoCodecProfileVk::H265(ash::vk::VideoDecodeH265ProfileInfoKHR {
+                        std_profile_idc: profile_idc as _,
+                        ..Default::default()
+                    })
+                }
+            })
+            .collect();
+
+        // `codec_infos` is not modified after this point, so the pointers stay valid even when
+        // `Self` is moved.
+        let profile_infos = profiles
+            .iter()
+            .zip(&codec_infos)
+            .map(|(profile, codec_info)| ash::vk::VideoProfileInfoKHR {
+                p_next: match codec_info {
+                    VideoCodecProfileVk::H264(info) => info as *const _ as *const _,
+                    VideoCodecProfileVk::H265(info) => info as *const _ as *const _,
+                },
+                video_codec_operation: profile.codec.into(),
+                chroma_subsampling: profile.chroma_subsampling.into(),
+                luma_bit_depth: profile.luma_bit_depth.into(),
+                chroma_bit_depth: profile
+                    .chroma_bit_depth
+                    .map_or(ash::vk::VideoComponentBitDepthFlagsKHR::INVALID, Into::into),
+                ..Default::default()
+            })
+            .collect();
+
+        Self {
+            _codec_infos: codec_infos,
+            profile_infos,
+        }
+    }
+
+    // The returned structure points into `self`.
+    pub(crate) fn profile_list_info(&self) -> ash::vk::VideoProfileListInfoKHR {
+        ash::vk::VideoProfileListInfoKHR {
+            profile_count: self.profile_infos.len() as u32,
+            p_profiles: self.profile_infos.as_ptr(),
+            ..Default::default()
+        }
+    }
+
+    // Returns the first profile. There must be at least one.
+    pub(crate) fn profile_info(&self) -> &ash::vk::VideoProfileInfoKHR {
+        &self.profile_infos[0]
+    }
+}
+
+// Returns whether `result` is one of the errors that are returned by queries when a video profile
+// is not supported. `check_errors` doesn't know about these, so they must be handled first.
+pub(crate) fn is_video_profile_unsupported(result: ash::vk::Result) -> bool {
+    matches!(
+        result,
+        ash::vk::Result::ERROR_IMAGE_USAGE_NOT_SUPPORTED_KHR
+            | ash::vk::Result::ERROR_VIDEO_PICTURE_LAYOUT_NOT_SUPPORTED_KHR
+            | ash::vk::Result::ERROR_VIDEO_PROFILE_OPERATION_NOT_SUPPORTED_KHR
+            | ash::vk::Result::ERROR_VIDEO_PROFILE_FORMAT_NOT_SUPPORTED_KHR
+            | ash::vk::Result::ERROR_VIDEO_PROFILE_CODEC_NOT_SUPPORTED_KHR
+            | ash::vk::Result::ERROR_VIDEO_STD_VERSION_NOT_SUPPORTED_KHR
+    )
+}
+
+/// The capabilities of a physical device for decoding video with a particular profile.
+///
+/// Returned by
+/// [`PhysicalDevice::video_capabilities`](crate::device::physical::PhysicalDevice::video_capabilities).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct VideoCapabilities {
+    /// Whether protected video content can be decoded.
+    pub protected_content: bool,
+
+    /// Whether the reference pictures of the DPB can be stored in separate images. If `false`,
+    /// all reference pictures must be array layers of the same image.
+    pub separate_reference_images: bool,
+
+    /// The alignment of the offset of the bitstream data in the source buffer.
+    pub min_bitstream_buffer_offset_alignment: DeviceSize,
+
+    /// The alignment of the size of the bitstream data in the source buffer.
+    pub min_bitstream_buffer_size_alignment: DeviceSize,
+
+    /// The granularity of the coded offsets and extents of decoded pictures.
+    pub picture_access_granularity: [u32; 2],
+
+    /// The minimum width and height of the decoded pictures.
+    pub min_coded_extent: [u32; 2],
+
+    /// The maximum width and height of the decoded pictures.
+    pub max_coded_extent: [u32; 2],
+
+    /// The maximum number of slots of the decoded picture buffer.
+    pub max_dpb_slots: u32,
+
+    /// The maximum number of reference pictures that a single picture can be predicted from.
+    pub max_active_reference_pictures: u32,
+
+    /// Whether a picture can be decoded directly into a DPB slot, with the same image being used
+    /// as the output picture and as the reconstructed reference picture.
+    pub dpb_and_output_coincide: bool,
+
+    /// Whether a picture can be decoded into an output image that is separate from the DPB
+    /// slot that the reconstructed reference picture is written to.
+    pub dpb_and_output_distinct: bool,
+
+    /// The highest codec level that is supported, as a `StdVideoH264LevelIdc` or
+    /// `StdVideoH265LevelIdc` value.
+    pub max_level_idc: u32,
+
+    pub(crate) std_header_version: ash::vk::ExtensionProperties,
+}
+
+impl VideoCapabilities {
+    pub(crate) fn from_vulkan(
+        capabilities: &ash::vk::VideoCapabilitiesKHR,
+        decode_capabilities: &ash::vk::VideoDecodeCapabilitiesKHR,
+        max_level_idc: u32,
+    ) -> Self {
+        Self {
+            protected_content: capabilities
+                .flags
+                .intersects(ash::vk::VideoCapabilityFlagsKHR::PROTECTED_CONTENT),
+            separate_reference_images: capabilities
+                .flags
+                .intersects(ash::vk::VideoCapabilityFlagsKHR::SEPARATE_REFERENCE_IMAGES),
+            min_bitstream_buffer_offset_alignment: capabilities
+                .min_bitstream_buffer_offset_alignment,
+            min_bitstream_buffer_size_alignment: capabilities.min_bitstream_buffer_size_alignment,
+            picture_access_granularity: [
+                capabilities.picture_access_granularity.width,
+                capabilities.picture_access_granularity.height,
+            ],
+            min_coded_extent: [
+                capabilities.min_coded_extent.width,
+                capabilities.min_coded_extent.height,
+            ],
+            max_coded_extent: [
+                capabilities.max_coded_extent.width,
+                capabilities.max_coded_extent.height,
+            ],
+            max_dpb_slots: capabilities.max_dpb_slots,
+            max_active_reference_pictures: capabilities.max_active_reference_pictures,
+            dpb_and_output_coincide: decode_capabilities
+                .flags
+                .intersects(ash::vk::VideoDecodeCapabilityFlagsKHR::DPB_AND_OUTPUT_COINCIDE),
+            dpb_and_output_distinct: decode_capabilities
+                .flags
+                .intersects(ash::vk::VideoDecodeCapabilityFlagsKHR::DPB_AND_OUTPUT_DISTINCT),
+            max_level_idc,
+            std_header_version: capabilities.std_header_version,
+        }
+    }
+}
+
+/// A video session, which decodes a video stream with a fixed profile and maximum picture size.
+#[derive(Debug)]
+pub struct VideoSession {
+    handle: ash::vk::VideoSessionKHR,
+    device: Arc<Device>,
+
+    queue_family_index: u32,
+    profile: VideoProfile,
+    picture_format: Format,
+    max_coded_extent: [u32; 2],
+    reference_picture_format: Option<Format>,
+    max_dpb_slots: u32,
+    max_active_reference_pictures: u32,
+    capabilities: VideoCapabilities,
+
+    // The memory that is bound to the session. Must be dropped after the session is destroyed.
+    _memory: Vec<DeviceMemory>,
+}
+
+impl VideoSession {
+    /// Creates a new `VideoSession`, and allocates and binds the memory that it needs.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.picture_format` is `None`.
+    /// - Panics if `create_info.max_dpb_slots` is not `0`, and
+    ///   `create_info.reference_picture_format` is `None`.
+    pub fn new(
+        device: Arc<Device>,
+        create_info: VideoSessionCreateInfo,
+    ) -> Result<Arc<VideoSession>, VideoSessionCreationError> {
+        let capabilities = Self::validate(&device, &create_info)?;
+
+        let VideoSessionCreateInfo {
+            queue_family_index,
+            profile,
+            picture_format,
+            max_coded_extent,
+            reference_picture_format,
+            max_dpb_slots,
+            max_active_reference_pictures,
+            _ne: _,
+        } = create_info;
+
+        let picture_format = picture_format.unwrap();
+        let profiles_vk = VideoProfilesVk::new(slice::from_ref(&profile));
+
+        let create_info = ash::vk::VideoSessionCreateInfoKHR {
+            queue_family_index,
+            p_video_profile: profiles_vk.profile_info(),
+            picture_format: picture_format.into(),
+            max_coded_extent: ash::vk::Extent2D {
+                width: max_coded_extent[0],
+                height: max_coded_extent[1],
+            },
+            reference_picture_format: reference_picture_format
+                .map_or(ash::vk::Format::UNDEFINED, Into::into),
+            max_dpb_slots,
+            max_active_reference_pictures,
+            p_std_header_version: &capabilities.std_header_version,
+            ..Default::default()
+        };
+
+        let handle = unsafe {
+            let fns = device.fns();
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns.khr_video_queue.create_video_session_khr)(
+                device.internal_object(),
+                &create_info,
+                ptr::null(),
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
+        };
+
+        // Create the session first, so that it is destroyed if binding the memory fails.
+        let mut session = VideoSession {
+            handle,
+            device,
+
+            queue_family_index,
+            profile,
+            picture_format,
+            max_coded_extent,
+            reference_picture_format,
+            max_dpb_slots,
+            max_active_reference_pictures,
+            capabilities,
+
+            _memory: Vec::new(),
+        };
+
+        session._memory = unsafe { session.bind_memory()? };
+
+        Ok(Arc::new(session))
+    }
+
+    fn validate(
+        device: &Device,
+        create_info: &VideoSessionCreateInfo,
+    ) -> Result<VideoCapabilities, VideoSessionCreationError> {
+        if !device.enabled_extensions().khr_video_queue {
+            return Err(VideoSessionCreationError::ExtensionNotEnabled {
+                extension: "khr_video_queue",
+                reason: "tried to create a video session",
+            });
+        }
+
+        if !device.enabled_extensions().khr_video_decode_queue {
+            return Err(VideoSessionCreationError::ExtensionNotEnabled {
+                extension: "khr_video_decode_queue",
+                reason: "tried to create a video decode session",
+            });
+        }
+
+        let &VideoSessionCreateInfo {
+            queue_family_index,
+            ref profile,
+            picture_format,
+            max_coded_extent,
+            reference_picture_format,
+            max_dpb_slots,
+            max_active_reference_pictures,
+            _ne: _,
+        } = create_info;
+
+        if !profile.codec.is_supported_by(device.enabled_extensions()) {
+            return Err(VideoSessionCreationError::ExtensionNotEnabled {
+                extension: profile.codec.required_extension(),
+                reason: "tried to create a video session for this codec",
+            });
+        }
+
+        let physical_device = device.physical_device();
+
+        // VUID-VkVideoSessionCreateInfoKHR-queueFamilyIndex-04265
+        // TODO: also check the codec operations of `VkQueueFamilyVideoPropertiesKHR`.
+        if !physical_device
+            .queue_family_by_id(queue_family_index)
+            .map_or(false, |queue_family| queue_family.supports_video_decode())
+        {
+            return Err(VideoSessionCreationError::NotSupportedByQueueFamily);
+        }
+
+        // VUID-VkVideoSessionCreateInfoKHR-pVideoProfile-04845
+        let capabilities = physical_device
+            .video_capabilities(profile)?
+            .ok_or(VideoSessionCreationError::ProfileNotSupported)?;
+
+        // VUID-VkVideoSessionCreateInfoKHR-maxCodedExtent-04851
+        if max_coded_extent[0] < capabilities.min_coded_extent[0]
+            || max_coded_extent[1] < capabilities.min_coded_extent[1]
+            || max_coded_extent[0] > capabilities.max_coded_extent[0]
+            || max_coded_extent[1] > capabilities.max_coded_extent[1]
+        {
+            return Err(VideoSessionCreationError::MaxCodedExtentOutOfRange {
+                extent: max_coded_extent,
+                min: capabilities.min_coded_extent,
+                max: capabilities.max_coded_extent,
+            });
+        }
+
+        // VUID-VkVideoSessionCreateInfoKHR-maxDpbSlots-04847
+        if max_dpb_slots > capabilities.max_dpb_slots {
+            return Err(VideoSessionCreationError::MaxDpbSlotsExceeded {
+                provided: max_dpb_slots,
+                max: capabilities.max_dpb_slots,
+            });
+        }
+
+        // VUID-VkVideoSessionCreateInfoKHR-maxActiveReferencePictures-04849
+        if max_active_reference_pictures > capabilities.max_active_reference_pictures {
+            return Err(
+                VideoSessionCreationError::MaxActiveReferencePicturesExceeded {
+                    provided: max_active_reference_pictures,
+                    max: capabilities.max_active_reference_pictures,
+                },
+            );
+        }
+
+        // VUID-VkVideoSessionCreateInfoKHR-referencePictureFormat-04852
+        let picture_format = picture_format.unwrap();
+
+        if !physical_device
+            .video_formats(
+                profile,
+                ImageUsage {
+                    video_decode_dst: true,
+                    ..ImageUsage::none()
+                },
+            )?
+            .contains(&picture_format)
+        {
+            return Err(VideoSessionCreationError::PictureFormatNotSupported {
+                format: picture_format,
+            });
+        }
+
+        if max_dpb_slots != 0 {
+            // VUID-VkVideoSessionCreateInfoKHR-referencePictureFormat-04853
+            let reference_picture_format = reference_picture_format.unwrap();
+
+            if !physical_device
+                .video_formats(
+                    profile,
+                    ImageUsage {
+                        video_decode_dpb: true,
+                        ..ImageUsage::none()
+                    },
+                )?
+                .contains(&reference_picture_format)
+            {
+                return Err(
+                    VideoSessionCreationError::ReferencePictureFormatNotSupported {
+                        format: reference_picture_format,
+                    },
+                );
+            }
+        }
+
+        Ok(capabilities)
+    }
+
+    // Allocates and binds the memory that the session needs. Each memory binding index gets its
+    // own allocation, preferably in device-local memory.
+    unsafe fn bind_memory(&self) -> Result<Vec<DeviceMemory>, VideoSessionCreationError> {
+        let fns = self.device.fns();
+
+        let memory_requirements = loop {
+            let mut count = 0;
+            check_errors((fns
+                .khr_video_queue
+                .get_video_session_memory_requirements_khr)(
+                self.device.internal_object(),
+                self.handle,
+                &mut count,
+                ptr::null_mut(),
+            ))?;
+
+            let mut memory_requirements =
+                vec![ash::vk::VideoSessionMemoryRequirementsKHR::default(); count as usize];
+            let result = check_errors((fns
+                .khr_video_queue
+                .get_video_session_memory_requirements_khr)(
+                self.device.internal_object(),
+                self.handle,
+                &mut count,
+                memory_requirements.as_mut_ptr(),
+            ))?;
+
+            if !matches!(result, Success::Incomplete) {
+                memory_requirements.truncate(count as usize);
+                break memory_requirements;
+            }
+        };
+
+        let physical_device = self.device.physical_device();
+        let mut memory = Vec::with_capacity(memory_requirements.len());
+        let mut bind_infos: SmallVec<[_; 8]> = SmallVec::new();
+
+        for requirements in memory_requirements {
+            let ash::vk::MemoryRequirements {
+                size,
+                memory_type_bits,
+                ..
+            } = requirements.memory_requirements;
+
+            // `memory_type_bits` always contains at least one memory type.
+            let memory_type = physical_device
+                .memory_types()
+                .filter(|memory_type| memory_type_bits & (1 << memory_type.id()) != 0)
+                .min_by_key(|memory_type| !memory_type.is_device_local())
+                .unwrap();
+
+            let allocation = DeviceMemory::allocate(
+                self.device.clone(),
+                MemoryAllocateInfo {
+                    allocation_size: size,
+                    memory_type_index: memory_type.id(),
+                    ..Default::default()
+                },
+            )?;
+
+            bind_infos.push(ash::vk::BindVideoSessionMemoryInfoKHR {
+                memory_bind_index: requirements.memory_bind_index,
+                memory: allocation.internal_object(),
+                memory_offset: 0,
+                memory_size: size,
+                ..Default::default()
+            });
+            memory.push(allocation);
+        }
+
+        if !bind_infos.is_empty() {
+            check_errors((fns.khr_video_queue.bind_video_session_memory_khr)(
+                self.device.internal_object(),
+                self.handle,
+                bind_infos.len() as u32,
+                bind_infos.as_ptr(),
+            ))?;
+        }
+
+        Ok(memory)
+    }
+
+    /// Returns the index of the queue family that the session can be used on.
+    #[inline]
+    pub fn queue_family_index(&self) -> u32 {
+        self.queue_family_index
+    }
+
+    /// Returns the profile of the video stream that the session decodes.
+    #[inline]
+    pub fn profile(&self) -> &VideoProfile {
+        &self.profile
+    }
+
+    /// Returns the format of the decoded pictures.
+    #[inline]
+    pub fn picture_format(&self) -> Format {
+        self.picture_format
+    }
+
+    /// Returns the maximum width and height of the decoded pictures.
+    #[inline]
+    pub fn max_coded_extent(&self) -> [u32; 2] {
+        self.max_coded_extent
+    }
+
+    /// Returns the format of the reference pictures, if the session has DPB slots.
+    #[inline]
+    pub fn reference_picture_format(&self) -> Option<Format> {
+        self.reference_picture_format
+    }
+
+    /// Returns the number of slots of the decoded picture buffer.
+    #[inline]
+    pub fn max_dpb_slots(&self) -> u32 {
+        self.max_dpb_slots
+    }
+
+    /// Returns the maximum number of reference pictures that a single picture can be predicted
+    /// from.
+    #[inline]
+    pub fn max_active_reference_pictures(&self) -> u32 {
+        self.max_active_reference_pictures
+    }
+
+    /// Returns the capabilities of the physical device for the profile of the session.
+    #[inline]
+    pub fn capabilities(&self) -> &VideoCapabilities {
+        &self.capabilities
+    }
+}
+
+impl Drop for VideoSession {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let fns = self.device.fns();
+            (fns.khr_video_queue.destroy_video_session_khr)(
+                self.device.internal_object(),
+                self.handle,
+                ptr::null(),
+            );
+        }
+    }
+}
+
+unsafe impl VulkanObject for VideoSession {
+    type Object = ash::vk::VideoSessionKHR;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::VideoSessionKHR {
+        self.handle
+    }
+}
+
+unsafe impl DeviceOwned for VideoSession {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl PartialEq for VideoSession {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle && self.device() == other.device()
+    }
+}
+
+impl Eq for VideoSession {}
+
+impl Hash for VideoSession {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state);
+        self.device().hash(state);
+    }
+}
+
+/// Parameters to create a new `VideoSession`.
+#[derive(Clone, Debug)]
+pub struct VideoSessionCreateInfo {
+    /// The index of the queue family that the session will be used on. The queue family must
+    /// support video decode operations.
+    ///
+    /// The default value is `0`.
+    pub queue_family_index: u32,
+
+    /// The profile of the video stream that the session decodes.
+    ///
+    /// There is no default value.
+    pub profile: VideoProfile,
+
+    /// The format of the decoded pictures. It must be one of the formats returned by
+    /// [`PhysicalDevice::video_formats`](crate::device::physical::PhysicalDevice::video_formats)
+    /// for the `video_decode_dst` usage.
+    ///
+    /// The default value is `None`, which must be overridden.
+    pub picture_format: Option<Format>,
+
+    /// The maximum width and height of the decoded pictures.
+    ///
+    /// The default value is `[0, 0]`, which must be overridden.
+    pub max_coded_extent: [u32; 2],
+
+    /// The format of the reference pictures in the DPB. It must be one of the formats returned
+    /// by [`PhysicalDevice::video_formats`](crate::device::physical::PhysicalDevice::video_formats)
+    /// for the `video_decode_dpb` usage.
+    ///
+    /// This must be `Some` if `max_dpb_slots` is not `0`.
+    ///
+    /// The default value is `None`.
+    pub reference_picture_format: Option<Format>,
+
+    /// The number of slots of the decoded picture buffer.
+    ///
+    /// The default value is `0`.
+    pub max_dpb_slots: u32,
+
+    /// The maximum number of reference pictures that a single picture can be predicted from.
+    ///
+    /// The default value is `0`.
+    pub max_active_reference_pictures: u32,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl VideoSessionCreateInfo {
+    /// Returns a `VideoSessionCreateInfo` with the specified `profile`.
+    #[inline]
+    pub fn profile(profile: VideoProfile) -> Self {
+        Self {
+            queue_family_index: 0,
+            profile,
+            picture_format: None,
+            max_coded_extent: [0, 0],
+            reference_picture_format: None,
+            max_dpb_slots: 0,
+            max_active_reference_pictures: 0,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Error that can happen when creating a video session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VideoSessionCreationError {
+    /// Allocating memory failed.
+    AllocError(DeviceMemoryAllocationError),
+
+    ExtensionNotEnabled {
+        extension: &'static str,
+        reason: &'static str,
+    },
+
+    /// The maximum coded extent is outside the range supported by the physical device.
+    MaxCodedExtentOutOfRange {
+        extent: [u32; 2],
+        min: [u32; 2],
+        max: [u32; 2],
+    },
+
+    /// The maximum number of active reference pictures exceeds the maximum supported by the
+    /// physical device.
+    MaxActiveReferencePicturesExceeded { provided: u32, max: u32 },
+
+    /// The number of DPB slots exceeds the maximum supported by the physical device.
+    MaxDpbSlotsExceeded { provided: u32, max: u32 },
+
+    /// The queue family doesn't support video decode operations.
+    NotSupportedByQueueFamily,
+
+    /// The picture format is not supported for the profile.
+    PictureFormatNotSupported { format: Format },
+
+    /// The profile is not supported by the physical device.
+    ProfileNotSupported,
+
+    /// The reference picture format is not supported for the profile.
+    ReferencePictureFormatNotSupported { format: Format },
+}
+
+impl error::Error for VideoSessionCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::AllocError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for VideoSessionCreationError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::AllocError(_) => write!(f, "allocating memory failed"),
+            Self::ExtensionNotEnabled { extension, reason } => {
+                write!(f, "the extension {} must be enabled: {}", extension, reason)
+            }
+            Self::MaxCodedExtentOutOfRange { extent, min, max } => write!(
+                f,
+                "the maximum coded extent {:?} is outside the range supported by the physical \
+                device ({:?} to {:?})",
+                extent, min, max,
+            ),
+            Self::MaxActiveReferencePicturesExceeded { provided, max } => write!(
+                f,
+                "the maximum number of active reference pictures {} exceeds the maximum supported \
+                by the physical device ({})",
+                provided, max,
+            ),
+            Self::MaxDpbSlotsExceeded { provided, max } => write!(
+                f,
+                "the number of DPB slots {} exceeds the maximum supported by the physical device \
+                ({})",
+                provided, max,
+            ),
+            Self::NotSupportedByQueueFamily => write!(
+                f,
+                "the queue family doesn't support video decode operations",
+            ),
+            Self::PictureFormatNotSupported { format } => write!(
+                f,
+                "the picture format {:?} is not supported for the profile",
+                format,
+            ),
+            Self::ProfileNotSupported => {
+                write!(f, "the profile is not supported by the physical device")
+            }
+            Self::ReferencePictureFormatNotSupported { format } => write!(
+                f,
+                "the reference picture format {:?} is not supported for the profile",
+                format,
+            ),
+        }
+    }
+}
+
+impl From<OomError> for VideoSessionCreationError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::AllocError(err.into())
+    }
+}
+
+impl From<DeviceMemoryAllocationError> for VideoSessionCreationError {
+    #[inline]
+    fn from(err: DeviceMemoryAllocationError) -> Self {
+        Self::AllocError(err)
+    }
+}
+
+impl From<Error> for VideoSessionCreationError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            err @ Error::OutOfHostMemory => Self::AllocError(err.into()),
+            err @ Error::OutOfDeviceMemory => Self::AllocError(err.into()),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+/// The codec-specific parameter sets of a video stream, which are stored in a
+/// [`VideoSessionParameters`] object.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum VideoParameterSets {
+    /// The sequence and picture parameter sets of an H.264 stream.
+    H264 {
+        sps: Vec<StdVideoH264SequenceParameterSet>,
+        pps: Vec<StdVideoH264PictureParameterSet>,
+    },
+
+    /// The video, sequence and picture parameter sets of an H.265 stream.
+    H265 {
+        vps: Vec<StdVideoH265VideoParameterSet>,
+        sps: Vec<StdVideoH265SequenceParameterSet>,
+        pps: Vec<StdVideoH265PictureParameterSet>,
+    },
+}
+
+impl VideoParameterSets {
+    #[inline]
+    fn is_for_codec(&self, codec: &VideoDecodeCodec) -> bool {
+        matches!(
+            (self, codec),
+            (Self::H264 { .. }, VideoDecodeCodec::H264 { .. })
+                | (Self::H265 { .. }, VideoDecodeCodec::H265 { .. })
+        )
+    }
+}
+
+/// The codec-specific parameter sets of a video stream, for use by a video session.
+///
+/// The parameter sets are copied when the object is created. A stream that sends new parameter
+/// sets needs a new `VideoSessionParameters` object.
+#[derive(Debug)]
+pub struct VideoSessionParameters {
+    handle: ash::vk::VideoSessionParametersKHR,
+    session: Arc<VideoSession>,
+}
+
+impl VideoSessionParameters {
+    /// Creates a new `VideoSessionParameters` for `session`.
+    ///
+    /// # Safety
+    ///
+    /// - The parameter sets are not validated. They must be valid for the codec, and the pointers
+    ///   that they contain must either be null or point to valid data.
+    pub unsafe fn new(
+        session: Arc<VideoSession>,
+        create_info: VideoSessionParametersCreateInfo,
+    ) -> Result<Arc<VideoSessionParameters>, VideoSessionParametersCreationError> {
+        let VideoSessionParametersCreateInfo {
+            parameter_sets,
+            _ne: _,
+        } = create_info;
+
+        if !parameter_sets.is_for_codec(&session.profile().codec) {
+            return Err(VideoSessionParametersCreationError::CodecMismatch);
+        }
+
+        let h264_add_info;
+        let h264_create_info;
+        let h265_add_info;
+        let h265_create_info;
+
+        let p_next: *const _ = match &parameter_sets {
+            VideoParameterSets::H264 { sps, pps } => {
+                h264_add_info = ash::vk::VideoDecodeH264SessionParametersAddInfoKHR {
+                    std_sps_count: sps.len() as u32,
+                    p_std_sp_ss: sps.as_ptr(),
+                    std_pps_count: pps.len() as u32,
+                    p_std_pp_ss: pps.as_ptr(),
+                    ..Default::default()
+                };
+                h264_create_info = ash::vk::VideoDecodeH264SessionParametersCreateInfoKHR {
+                    max_std_sps_count: sps.len() as u32,
+                    max_std_pps_count: pps.len() as u32,
+                    p_parameters_add_info: &h264_add_info,
+                    ..Default::default()
+                };
+                &h264_create_info as *const _ as *const _
+            }
+            VideoParameterSets::H265 { vps, sps, pps } => {
+                h265_add_info = ash::vk::VideoDecodeH265SessionParametersAddInfoKHR {
+                    std_vps_count: vps.len() as u32,
+                    p_std_vp_ss: vps.as_ptr(),
+                    std_sps_count: sps.len() as u32,
+                    p_std_sp_ss: sps.as_ptr(),
+                    std_pps_count: pps.len() as u32,
+                    p_std_pp_ss: pps.as_ptr(),
+                    ..Default::default()
+                };
+                h265_create_info = ash::vk::VideoDecodeH265SessionParametersCreateInfoKHR {
+                    max_std_vps_count: vps.len() as u32,
+                    max_std_sps_count: sps.len() as u32,
+                    max_std_pps_count: pps.len() as u32,
+                    p_parameters_add_info: &h265_add_info,
+                    ..Default::default()
+                };
+                &h265_create_info as *const _ as *const _
+            }
+        };
+
+        let create_info = ash::vk::VideoSessionParametersCreateInfoKHR {
+            p_next,
+            video_session: session.internal_object(),
+            ..Default::default()
+        };
+
+        let handle = {
+            let fns = session.device().fns();
+            let mut output = MaybeUninit::uninit();
+            check_errors((fns.khr_video_queue.create_video_session_parameters_khr)(
+                session.device().internal_object(),
+                &create_info,
+                ptr::null(),
+                output.as_mut_ptr(),
+            ))?;
+            output.assume_init()
+        };
+
+        Ok(Arc::new(VideoSessionParameters { handle, session }))
+    }
+
+    /// Returns the video session that the parameters belong to.
+    #[inline]
+    pub fn session(&self) -> &Arc<VideoSession> {
+        &self.session
+    }
+}
+
+impl Drop for VideoSessionParameters {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let fns = self.session.device().fns();
+            (fns.khr_video_queue.destroy_video_session_parameters_khr)(
+                self.session.device().internal_object(),
+                self.handle,
+                ptr::null(),
+            );
+        }
+    }
+}
+
+unsafe impl VulkanObject for VideoSessionParameters {
+    type Object = ash::vk::VideoSessionParametersKHR;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::VideoSessionParametersKHR {
+        self.handle
+    }
+}
+
+unsafe impl DeviceOwned for VideoSessionParameters {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.session.device()
+    }
+}
+
+impl PartialEq for VideoSessionParameters {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle && self.device() == other.device()
+    }
+}
+
+impl Eq for VideoSessionParameters {}
+
+impl Hash for VideoSessionParameters {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.handle.hash(state);
+        self.device().hash(state);
+    }
+}
+
+/// Parameters to create a new `VideoSessionParameters`.
+#[derive(Clone, Debug)]
+pub struct VideoSessionParametersCreateInfo {
+    /// The parameter sets to store. They must be for the codec of the video session.
+    ///
+    /// There is no default value.
+    pub parameter_sets: VideoParameterSets,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl VideoSessionParametersCreateInfo {
+    /// Returns a `VideoSessionParametersCreateInfo` with the specified `parameter_sets`.
+    #[inline]
+    pub fn parameter_sets(parameter_sets: VideoParameterSets) -> Self {
+        Self {
+            parameter_sets,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Error that can happen when creating video session parameters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VideoSessionParametersCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The parameter sets are not for the codec of the video session.
+    CodecMismatch,
+}
+
+impl error::Error for VideoSessionParametersCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::OomError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for VideoSessionParametersCreationError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::OomError(_) => write!(f, "not enough memory available"),
+            Self::CodecMismatch => write!(
+                f,
+                "the parameter sets are not for the codec of the video session",
+            ),
+        }
+    }
+}
+
+impl From<OomError> for VideoSessionParametersCreationError {
+    #[inline]
+    fn from(err: OomError) -> Self {
+        Self::OomError(err)
+    }
+}
+
+impl From<Error> for VideoSessionParametersCreationError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        match err {
+            err @ Error::OutOfHostMemory => Self::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => Self::OomError(OomError::from(err)),
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+/// An image that holds decoded pictures or reference pictures of a video session.
+///
+/// The image has the size of the [`max_coded_extent`](VideoSession::max_coded_extent) of the
+/// session. If its usage contains `video_decode_dpb`, it has the
+/// [`reference_picture_format`](VideoSession::reference_picture_format) of the session, and
+/// usually one array layer per DPB slot. Otherwise it has the
+/// [`picture_format`](VideoSession::picture_format) of the session.
+#[derive(Debug)]
+pub struct VideoImage {
+    image: Arc<UnsafeImage>,
+    memory: PotentialDedicatedAllocation<StdMemoryPoolAlloc>,
+
+    // The layout that the image is in between video decode commands. Either `VideoDecodeDpb` or
+    // `VideoDecodeDst`.
+    layout: ImageLayout,
+
+    // If true, then the image is in `layout`. If false, then it is still `Undefined`.
+    initialized: AtomicBool,
+}
+
+impl VideoImage {
+    /// Creates a new `VideoImage` for the profile of `session`.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `create_info.usage` contains `video_decode_dpb`, but `session` has no DPB
+    ///   slots.
+    pub fn new(
+        session: &VideoSession,
+        create_info: VideoImageCreateInfo,
+    ) -> Result<Arc<VideoImage>, ImageCreationError> {
+        let VideoImageCreateInfo {
+            usage,
+            array_layers,
+            sharing,
+            _ne: _,
+        } = create_info;
+
+        let device = session.device();
+        let (format, layout) = if usage.video_decode_dpb {
+            (
+                session.reference_picture_format().unwrap(),
+                ImageLayout::VideoDecodeDpb,
+            )
+        } else {
+            (session.picture_format(), ImageLayout::VideoDecodeDst)
+        };
+
+        let image = UnsafeImage::new(
+            device.clone(),
+            UnsafeImageCreateInfo {
+                dimensions: ImageDimensions::Dim2d {
+                    width: session.max_coded_extent()[0],
+                    height: session.max_coded_extent()[1],
+                    array_layers,
+                },
+                format: Some(format),
+                usage,
+                sharing,
+                video_profiles: [*session.profile()].into_iter().collect(),
+                ..Default::default()
+            },
+        )?;
+
+        let mem_reqs = image.memory_requirements();
+        let memory = MemoryPool::alloc_from_requirements(
+            &Device::standard_pool(device),
+            &mem_reqs,
+            AllocLayout::Optimal,
+            MappingRequirement::DoNotMap,
+            Some(DedicatedAllocation::Image(&image)),
+            |t| {
+                if t.is_device_local() {
+                    AllocFromRequirementsFilter::Preferred
+                } else {
+                    AllocFromRequirementsFilter::Allowed
+                }
+            },
+        )?;
+        debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
+        unsafe {
+            image.bind_memory(memory.memory(), memory.offset())?;
+        }
+
+        Ok(Arc::new(VideoImage {
+            image,
+            memory,
+            layout,
+            initialized: AtomicBool::new(false),
+        }))
+    }
+}
+
+/// Parameters to create a new `VideoImage`.
+#[derive(Clone, Debug)]
+pub struct VideoImageCreateInfo {
+    /// How the image is going to be used. This must contain `video_decode_dst`,
+    /// `video_decode_dpb` or both. If the image is both a DPB image and the output of decode
+    /// operations, then the [`dpb_and_output_coincide`](VideoCapabilities::dpb_and_output_coincide)
+    /// capability must be supported.
+    ///
+    /// The default value is [`ImageUsage::none()`], which must be overridden.
+    pub usage: ImageUsage,
+
+    /// The number of array layers of the image.
+    ///
+    /// The default value is `1`.
+    pub array_layers: u32,
+
+    /// Whether the image can be shared across multiple queues, or is limited to a single queue.
+    /// Decoded pictures are usually read on another queue than the video decode queue.
+    ///
+    /// The default value is [`Sharing::Exclusive`].
+    pub sharing: Sharing<SmallVec<[u32; 4]>>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl Default for VideoImageCreateInfo {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            usage: ImageUsage::none(),
+            array_layers: 1,
+            sharing: Sharing::Exclusive,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+unsafe impl DeviceOwned for VideoImage {
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.image.device()
+    }
+}
+
+unsafe impl VulkanObject for VideoImage {
+    type Object = ash::vk::Image;
+
+    #[inline]
+    fn internal_object(&self) -> ash::vk::Image {
+        self.image.internal_object()
+    }
+}
+
+unsafe impl ImageAccess for VideoImage {
+    #[inline]
+    fn inner(&self) -> ImageInner {
+        ImageInner {
+            image: &self.image,
+            first_layer: 0,
+            num_layers: self.image.dimensions().array_layers(),
+            first_mipmap_level: 0,
+            num_mipmap_levels: 1,
+        }
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        self.layout
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        self.layout
+    }
+
+    #[inline]
+    fn descriptor_layouts(&self) -> Option<ImageDescriptorLayouts> {
+        Some(ImageDescriptorLayouts {
+            storage_image: ImageLayout::General,
+            combined_image_sampler: ImageLayout::ShaderReadOnlyOptimal,
+            sampled_image: ImageLayout::ShaderReadOnlyOptimal,
+            input_attachment: ImageLayout::ShaderReadOnlyOptimal,
+        })
+    }
+
+    #[inline]
+    unsafe fn layout_initialized(&self) {
+        self.initialized.store(true, Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn is_layout_initialized(&self) -> bool {
+        self.initialized.load(Ordering::SeqCst)
+    }
+}
+
+unsafe impl<P> ImageContent<P> for VideoImage {
+    #[inline]
+    fn matches_format(&self) -> bool {
+        true // FIXME:
+    }
+}
+
+impl PartialEq for VideoImage {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner() == other.inner()
+    }
+}
+
+impl Eq for VideoImage {}
+
+impl Hash for VideoImage {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner().hash(state);
+    }
+}
+
+/// Keeps track of which slots of the decoded picture buffer of a video session hold a reference
+/// picture.
+///
+/// The slots are only bookkeeping for the application; the implementation keeps its own state,
+/// which is only updated when the command buffer executes. A slot is activated by decoding a
+/// picture with a setup reference slot that uses it, and all slots are deactivated by
+/// [`reset_video_coding`](crate::command_buffer::AutoCommandBufferBuilder::reset_video_coding).
+///
+/// ```
+/// use vulkano::video::DpbSlots;
+///
+/// let mut dpb_slots = DpbSlots::new(2);
+/// let first = dpb_slots.allocate().unwrap();
+/// let second = dpb_slots.allocate().unwrap();
+/// assert!(dpb_slots.allocate().is_none());
+///
+/// // The first picture is no longer used as a reference.
+/// dpb_slots.free(first);
+/// assert_eq!(dpb_slots.allocate(), Some(first));
+/// # let _ = second;
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DpbSlots {
+    active: Vec<bool>,
+}
+
+impl DpbSlots {
+    /// Creates a new `DpbSlots` with `slot_count` inactive slots. This is usually the
+    /// [`max_dpb_slots`](VideoSession::max_dpb_slots) of the session.
+    #[inline]
+    pub fn new(slot_count: u32) -> Self {
+        Self {
+            active: vec![false; slot_count as usize],
+        }
+    }
+
+    /// Returns the number of slots.
+    #[inline]
+    pub fn slot_count(&self) -> u32 {
+        self.active.len() as u32
+    }
+
+    /// Marks the first inactive slot as active and returns its index, or returns `None` if all
+    /// slots are active.
+    #[inline]
+    pub fn allocate(&mut self) -> Option<u32> {
+        let index = self.active.iter().position(|&active| !active)?;
+        self.active[index] = true;
+        Some(index as u32)
+    }
+
+    /// Marks a slot as inactive, so that it can be reused.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `slot_index` is out of range.
+    #[inline]
+    pub fn free(&mut self, slot_index: u32) {
+        self.active[slot_index as usize] = false;
+    }
+
+    /// Returns whether a slot is active.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `slot_index` is out of range.
+    #[inline]
+    pub fn is_active(&self, slot_index: u32) -> bool {
+        self.active[slot_index as usize]
+    }
+
+    /// Returns the indices of the active slots.
+    #[inline]
+    pub fn active_slots(&self) -> impl Iterator<Item = u32> + '_ {
+        self.active
+            .iter()
+            .enumerate()
+            .filter(|(_, &active)| active)
+            .map(|(index, _)| index as u32)
+    }
+
+    /// Marks all slots as inactive. This corresponds to
+    /// [`reset_video_coding`](crate::command_buffer::AutoCommandBufferBuilder::reset_video_coding).
+    #[inline]
+    pub fn reset(&mut self) {
+        self.active.iter_mut().for_each(|active| *active = false);
+    }
+}
+
+/// A region of an image view that holds a video picture.
+#[derive(Clone, Debug)]
+pub struct VideoPictureResource {
+    /// The image view that holds the picture. It must be a 2D or 2D array view of a
+    /// [`VideoImage`] or another image that was created for the profile of the session.
+    ///
+    /// There is no default value.
+    pub image_view: Arc<dyn ImageViewAbstract>,
+
+    /// The array layer of `image_view` that holds the picture, relative to the first array layer
+    /// of the view.
+    ///
+    /// The default value is `0`.
+    pub base_array_layer: u32,
+
+    /// The offset of the picture within the array layer.
+    ///
+    /// The default value is `[0, 0]`.
+    pub coded_offset: [u32; 2],
+
+    /// The width and height of the picture.
+    ///
+    /// The default value is the width and height of the image.
+    pub coded_extent: [u32; 2],
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl VideoPictureResource {
+    /// Returns a `VideoPictureResource` with the specified `image_view`, that covers the whole
+    /// first array layer of the view.
+    #[inline]
+    pub fn image_view(image_view: Arc<dyn ImageViewAbstract>) -> Self {
+        let dimensions = image_view.image().dimensions();
+
+        Self {
+            image_view,
+            base_array_layer: 0,
+            coded_offset: [0, 0],
+            coded_extent: [dimensions.width(), dimensions.height()],
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+
+    /// Returns the array layer of the image that holds the picture.
+    #[inline]
+    pub(crate) fn image_array_layer(&self) -> u32 {
+        self.image_view.subresource_range().array_layers.start + self.base_array_layer
+    }
+
+    /// Returns whether `self` and `other` refer to the same array layer of the same image view.
+    #[inline]
+    pub(crate) fn is_same_picture(&self, other: &Self) -> bool {
+        self.image_view.internal_object() == other.image_view.internal_object()
+            && self.base_array_layer == other.base_array_layer
+    }
+
+    pub(crate) fn to_vulkan(&self) -> ash::vk::VideoPictureResourceInfoKHR {
+        ash::vk::VideoPictureResourceInfoKHR {
+            coded_offset: ash::vk::Offset2D {
+                x: self.coded_offset[0] as i32,
+                y: self.coded_offset[1] as i32,
+            },
+            coded_extent: ash::vk::Extent2D {
+                width: self.coded_extent[0],
+                height: self.coded_extent[1],
+            },
+            base_array_layer: self.base_array_layer,
+            image_view_binding: self.image_view.internal_object(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A DPB slot and the reference picture that it holds.
+#[derive(Clone, Debug)]
+pub struct VideoReferenceSlot {
+    /// The index of the DPB slot.
+    ///
+    /// When beginning video coding, this can be `None` for a picture resource that is going to be
+    /// used by the setup reference slot of a decode command, but is not yet associated with a
+    /// slot. It must be `Some` in all other cases.
+    ///
+    /// There is no default value.
+    pub slot_index: Option<u32>,
+
+    /// The picture resource that holds the reference picture.
+    ///
+    /// There is no default value.
+    pub picture_resource: VideoPictureResource,
+
+    /// The codec-specific information about the reference picture. This must be `Some` for the
+    /// setup reference slot and the reference slots of a decode command, and is ignored when
+    /// beginning video coding.
+    ///
+    /// The default value is `None`.
+    pub reference_info: Option<VideoDecodeReferenceInfo>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl VideoReferenceSlot {
+    /// Returns a `VideoReferenceSlot` with the specified `slot_index` and `picture_resource`.
+    #[inline]
+    pub fn new(slot_index: Option<u32>, picture_resource: VideoPictureResource) -> Self {
+        Self {
+            slot_index,
+            picture_resource,
+            reference_info: None,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// The codec-specific information about a reference picture.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum VideoDecodeReferenceInfo {
+    H264(StdVideoDecodeH264ReferenceInfo),
+    H265(StdVideoDecodeH265ReferenceInfo),
+}
+
+/// The codec-specific information about a picture to decode.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum VideoDecodePictureInfo {
+    H264 {
+        /// The information about the picture, taken from its slice headers.
+        std_picture_info: StdVideoDecodeH264PictureInfo,
+
+        /// The offsets of the slices of the picture, relative to the start of the bitstream
+        /// range of the decode command.
+        slice_offsets: Vec<u32>,
+    },
+    H265 {
+        /// The information about the picture, taken from its slice segment headers.
+        std_picture_info: StdVideoDecodeH265PictureInfo,
+
+        /// The offsets of the slice segments of the picture, relative to the start of the
+        /// bitstream range of the decode command.
+        slice_segment_offsets: Vec<u32>,
+    },
+}
+
+impl VideoDecodePictureInfo {
+    #[inline]
+    pub(crate) fn is_for_codec(&self, codec: &VideoDecodeCodec) -> bool {
+        matches!(
+            (self, codec),
+            (Self::H264 { .. }, VideoDecodeCodec::H264 { .. })
+                | (Self::H265 { .. }, VideoDecodeCodec::H265 { .. })
+        )
+    }
+}
+
+impl VideoDecodeReferenceInfo {
+    #[inline]
+    pub(crate) fn is_for_codec(&self, codec: &VideoDecodeCodec) -> bool {
+        matches!(
+            (self, codec),
+            (Self::H264(_), VideoDecodeCodec::H264 { .. })
+                | (Self::H265(_), VideoDecodeCodec::H265 { .. })
+        )
+    }
+}
+
+/// Parameters to begin a video coding scope.
+#[derive(Clone, Debug)]
+pub struct VideoBeginCodingInfo {
+    /// The video session to use.
+    ///
+    /// There is no default value.
+    pub session: Arc<VideoSession>,
+
+    /// The parameters to use. This must be `Some` to decode pictures, and must belong to
+    /// `session`.
+    ///
+    /// The default value is `None`.
+    pub parameters: Option<Arc<VideoSessionParameters>>,
+
+    /// The picture resources that can be used as reference pictures or as the setup reference
+    /// picture by the decode commands of the scope, and the DPB slots that they are associated
+    /// with.
+    ///
+    /// The default value is empty.
+    pub reference_slots: Vec<VideoReferenceSlot>,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl VideoBeginCodingInfo {
+    /// Returns a `VideoBeginCodingInfo` with the specified `session`.
+    #[inline]
+    pub fn session(session: Arc<VideoSession>) -> Self {
+        Self {
+            session,
+            parameters: None,
+            reference_slots: Vec::new(),
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+/// Parameters to decode a video picture.
+#[derive(Clone, Debug)]
+pub struct VideoDecodeInfo {
+    /// The buffer that holds the bitstream data of the picture. It must have been created with
+    /// the `video_decode_src` usage and the profile of the session.
+    ///
+    /// There is no default value.
+    pub src_buffer: Arc<dyn BufferAccess>,
+
+    /// The range of `src_buffer` that holds the bitstream data of the picture. The start and the
+    /// length of the range must be multiples of the
+    /// [`min_bitstream_buffer_offset_alignment`](VideoCapabilities::min_bitstream_buffer_offset_alignment)
+    /// and [`min_bitstream_buffer_size_alignment`](VideoCapabilities::min_bitstream_buffer_size_alignment)
+    /// capabilities.
+    ///
+    /// There is no default value.
+    pub src_buffer_range: Range<DeviceSize>,
+
+    /// The picture resource that the decoded picture is written to. If the DPB and the output
+    /// coincide, this must be the picture resource of `setup_reference_slot`.
+    ///
+    /// There is no default value.
+    pub dst_picture_resource: VideoPictureResource,
+
+    /// The DPB slot that the reconstructed picture is written to, so that later pictures can be
+    /// predicted from it. If `None`, the picture is not used as a reference picture.
+    ///
+    /// The default value is `None`.
+    pub setup_reference_slot: Option<VideoReferenceSlot>,
+
+    /// The reference pictures that the picture is predicted from.
+    ///
+    /// The default value is empty.
+    pub reference_slots: Vec<VideoReferenceSlot>,
+
+    /// The codec-specific information about the picture.
+    ///
+    /// There is no default value.
+    pub picture_info: VideoDecodePictureInfo,
+
+    pub _ne: crate::NonExhaustive,
+}
+
+impl VideoDecodeInfo {
+    /// Returns a `VideoDecodeInfo` with the specified `src_buffer`, `src_buffer_range`,
+    /// `dst_picture_resource` and `picture_info`.
+    #[inline]
+    pub fn new(
+        src_buffer: Arc<dyn BufferAccess>,
+        src_buffer_range: Range<DeviceSize>,
+        dst_picture_resource: VideoPictureResource,
+        picture_info: VideoDecodePictureInfo,
+    ) -> Self {
+        Self {
+            src_buffer,
+            src_buffer_range,
+            dst_picture_resource,
+            setup_reference_slot: None,
+            reference_slots: Vec::new(),
+            picture_info,
+            _ne: crate::NonExhaustive(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DpbSlots, H264PictureLayout, H264ProfileIdc, H265ProfileIdc, VideoDecodeCodec,
+        VideoDecodePictureInfo, VideoDecodeReferenceInfo, VideoPictureResource, VideoProfile,
+        VideoSession, VideoSessionCreateInfo, VideoSessionCreationError,
+    };
+    use crate::{
+        device::{
+            physical::PhysicalDevice, Device, DeviceCreateInfo, DeviceExtensions, Queue,
+            QueueCreateInfo,
+        },
+        format::Format,
+        image::{ImageUsage, StorageImage},
+        instance::Instance,
+    };
+    use std::{mem, sync::Arc};
+
+    // Creates a device with `enabled_extensions` and a graphics queue.
+    fn video_dev_and_queue(
+        enabled_extensions: DeviceExtensions,
+    ) -> Option<(Arc<Device>, Arc<Queue>)> {
+        let instance = Instance::new(Default::default()).ok()?;
+
+        let (physical_device, queue_family) =
+            PhysicalDevice::enumerate(&instance).find_map(|p| {
+                if !p.supported_extensions().is_superset_of(&enabled_extensions) {
+                    return None;
+                }
+
+                let queue_family = p.queue_families().find(|q| q.supports_graphics())?;
+                Some((p, queue_family))
+            })?;
+
+        let (device, mut queues) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
+                enabled_extensions,
+                ..Default::default()
+            },
+        )
+        .ok()?;
+
+        Some((device, queues.next().unwrap()))
+    }
+
+    fn h264_session_create_info(queue_family_index: u32) -> VideoSessionCreateInfo {
+        VideoSessionCreateInfo {
+            queue_family_index,
+            picture_format: Some(Format::G8_B8R8_2PLANE_420_UNORM),
+            max_coded_extent: [64, 64],
+            ..VideoSessionCreateInfo::profile(VideoProfile::decode_h264(H264ProfileIdc::Main))
+        }
+    }
+
+    #[test]
+    fn extension_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        match VideoSession::new(
+            device,
+            VideoSessionCreateInfo {
+                picture_format: Some(Format::G8_B8R8_2PLANE_420_UNORM),
+                max_coded_extent: [64, 64],
+                ..VideoSessionCreateInfo::profile(VideoProfile::decode_h264(H264ProfileIdc::Main))
+            },
+        ) {
+            Err(VideoSessionCreationError::ExtensionNotEnabled { .. }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn codec_extension_not_enabled() {
+        let (device, queue) = match video_dev_and_queue(DeviceExtensions {
+            khr_video_queue: true,
+            khr_video_decode_queue: true,
+            khr_synchronization2: true,
+            ..DeviceExtensions::none()
+        }) {
+            Some(x) => x,
+            None => return,
+        };
+
+        match VideoSession::new(device, h264_session_create_info(queue.family().id())) {
+            Err(VideoSessionCreationError::ExtensionNotEnabled {
+                extension: "khr_video_decode_h264",
+                ..
+            }) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn not_supported_by_queue_family() {
+        let (device, queue) = match video_dev_and_queue(DeviceExtensions {
+            khr_video_queue: true,
+            khr_video_decode_queue: true,
+            khr_video_decode_h264: true,
+            khr_synchronization2: true,
+            ..DeviceExtensions::none()
+        }) {
+            Some(x) => x,
+            None => return,
+        };
+
+        if queue.family().supports_video_decode() {
+            return;
+        }
+
+        match VideoSession::new(device, h264_session_create_info(queue.family().id())) {
+            Err(VideoSessionCreationError::NotSupportedByQueueFamily) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn same_picture() {
+        let (_, queue) = gfx_dev_and_queue!();
+
+        let usage = ImageUsage {
+            sampled: true,
+            ..ImageUsage::none()
+        };
+        let first = StorageImage::general_purpose_image_view(
+            queue.clone(),
+            [64, 64],
+            Format::R8G8B8A8_UNORM,
+            usage,
+        )
+        .unwrap();
+        let second = StorageImage::general_purpose_image_view(
+            queue,
+            [64, 64],
+            Format::R8G8B8A8_UNORM,
+            usage,
+        )
+        .unwrap();
+
+        let resource = VideoPictureResource::image_view(first.clone());
+        assert!(resource.is_same_picture(&VideoPictureResource::image_view(first.clone())));
+
+        // Another layer of the same view, or the same layer of another view, is another picture.
+        assert!(!resource.is_same_picture(&VideoPictureResource {
+            base_array_layer: 1,
+            ..VideoPictureResource::image_view(first)
+        }));
+        assert!(!resource.is_same_picture(&VideoPictureResource::image_view(second)));
+    }
+
+    #[test]
+    fn info_for_codec() {
+        let h264 = VideoDecodeCodec::H264 {
+            profile_idc: H264ProfileIdc::Main,
+            picture_layout: H264PictureLayout::Progressive,
+        };
+        let h265 = VideoDecodeCodec::H265 {
+            profile_idc: H265ProfileIdc::Main,
+        };
+
+        // The codec-specific structures are plain C data, which are valid when zeroed.
+        let picture_info = VideoDecodePictureInfo::H264 {
+            std_picture_info: unsafe { mem::zeroed() },
+            slice_offsets: vec![0],
+        };
+        assert!(picture_info.is_for_codec(&h264));
+        assert!(!picture_info.is_for_codec(&h265));
+
+        let reference_info = VideoDecodeReferenceInfo::H265(unsafe { mem::zeroed() });
+        assert!(reference_info.is_for_codec(&h265));
+        assert!(!reference_info.is_for_codec(&h264));
+    }
+
+    #[test]
+    fn dpb_slots() {
+        let mut dpb_slots = DpbSlots::new(3);
+        assert_eq!(dpb_slots.allocate(), Some(0));
+        assert_eq!(dpb_slots.allocate(), Some(1));
+        dpb_slots.free(0);
+        assert!(!dpb_slots.is_active(0));
+        assert_eq!(dpb_slots.active_slots().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(dpb_slots.allocate(), Some(0));
+        assert_eq!(dpb_slots.allocate(), Some(2));
+        assert_eq!(dpb_slots.allocate(), None);
+        dpb_slots.reset();
+        assert_eq!(dpb_slots.active_slots().count(), 0);
+    }
+}